@@ -380,6 +380,45 @@ impl ChartCommand for ChangeChartType {
     }
 }
 
+/// Command to set the alt text and decorative flag of a chart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetChartAltText {
+    /// New alt text
+    pub alt_text: Option<String>,
+    /// Whether the chart is decorative
+    pub decorative: bool,
+}
+
+impl SetChartAltText {
+    /// Set the chart's alt text
+    pub fn new(alt_text: impl Into<String>) -> Self {
+        Self {
+            alt_text: Some(alt_text.into()),
+            decorative: false,
+        }
+    }
+
+    /// Mark the chart as decorative, clearing its alt text
+    pub fn decorative() -> Self {
+        Self {
+            alt_text: None,
+            decorative: true,
+        }
+    }
+}
+
+impl ChartCommand for SetChartAltText {
+    fn execute(&self, chart: &mut Chart) -> ChartResult<()> {
+        chart.alt_text = self.alt_text.clone();
+        chart.decorative = self.decorative;
+        Ok(())
+    }
+
+    fn description(&self) -> String {
+        "Edit chart alt text".to_string()
+    }
+}
+
 /// Command to update chart styling
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateChartStyle {
@@ -634,6 +673,18 @@ mod tests {
         assert_eq!(chart.data.series.len(), 1);
     }
 
+    #[test]
+    fn test_set_chart_alt_text() {
+        let mut chart = Chart::new("test", ChartType::default());
+
+        SetChartAltText::new("Sales by quarter").execute(&mut chart).unwrap();
+        assert_eq!(chart.alt_text.as_deref(), Some("Sales by quarter"));
+
+        SetChartAltText::decorative().execute(&mut chart).unwrap();
+        assert!(chart.decorative);
+        assert!(chart.alt_text.is_none());
+    }
+
     #[test]
     fn test_update_chart_data_add_series() {
         let mut chart = Chart::new("test", ChartType::default());
@@ -85,6 +85,42 @@ impl DrawingMLParser {
         Ok(chart)
     }
 
+    /// Read the alt text and decorative flag from a chart's graphic frame
+    ///
+    /// `xml` is the `<wp:inline>` or `<wp:anchor>` that places the chart in
+    /// document.xml.
+    pub fn parse_frame(&self, xml: &str, chart: &mut Chart) -> ChartResult<()> {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        let mut buf = Vec::new();
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => match e.local_name().as_ref() {
+                    b"docPr" => {
+                        for attr in e.attributes() {
+                            let attr = attr?;
+                            if attr.key.as_ref() == b"descr" {
+                                chart.alt_text = Some(attr.unescape_value()?.into_owned());
+                            }
+                        }
+                    }
+                    b"decorative" => {
+                        let val = get_attribute(e, "val")?;
+                        chart.decorative = matches!(val.as_deref(), Some("1") | Some("true") | Some("on"));
+                    }
+                    _ => {}
+                },
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(ChartError::XmlParse(e.to_string())),
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        Ok(())
+    }
+
     fn handle_start_element(
         &self,
         reader: &mut Reader<&[u8]>,
@@ -9,6 +9,15 @@ use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
 use quick_xml::Writer;
 use std::io::Cursor;
 
+/// Graphic data URI and namespace of charts
+const CHART_GRAPHIC_URI: &str = "http://schemas.openxmlformats.org/drawingml/2006/chart";
+
+/// Extension URI of Word's decorative flag
+const DECORATIVE_EXT_URI: &str = "{C183D7F6-B498-43B3-948B-1728B52AA6E4}";
+
+/// Namespace of Word's decorative flag
+const DECORATIVE_NS: &str = "http://schemas.microsoft.com/office/drawing/2017/decorative";
+
 /// Writer for DrawingML chart XML
 pub struct DrawingMLWriter {
     /// XML namespace prefix for chart elements
@@ -65,6 +74,77 @@ impl DrawingMLWriter {
         String::from_utf8(result).map_err(|e| ChartError::Serialization(e.to_string()))
     }
 
+    /// Write the inline graphic frame that places a chart in document.xml
+    ///
+    /// The frame's `<wp:docPr>` carries the chart's alt text, or Word's
+    /// decorative extension when the chart is decorative.
+    pub fn write_inline_frame(
+        &self,
+        chart: &Chart,
+        rel_id: &str,
+        doc_pr_id: u32,
+        width_emu: i64,
+        height_emu: i64,
+    ) -> ChartResult<String> {
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        let err = |e: quick_xml::Error| ChartError::Serialization(e.to_string());
+
+        writer
+            .write_event(Event::Start(BytesStart::new("wp:inline")))
+            .map_err(err)?;
+
+        let mut extent = BytesStart::new("wp:extent");
+        extent.push_attribute(("cx", width_emu.to_string().as_str()));
+        extent.push_attribute(("cy", height_emu.to_string().as_str()));
+        writer.write_event(Event::Empty(extent)).map_err(err)?;
+
+        let mut doc_pr = BytesStart::new("wp:docPr");
+        doc_pr.push_attribute(("id", doc_pr_id.to_string().as_str()));
+        doc_pr.push_attribute(("name", format!("Chart {}", doc_pr_id).as_str()));
+        if chart.decorative {
+            writer.write_event(Event::Start(doc_pr)).map_err(err)?;
+            let mut ext_lst = BytesStart::new("a:extLst");
+            ext_lst.push_attribute(("xmlns:a", "http://schemas.openxmlformats.org/drawingml/2006/main"));
+            writer.write_event(Event::Start(ext_lst)).map_err(err)?;
+            let mut ext = BytesStart::new("a:ext");
+            ext.push_attribute(("uri", DECORATIVE_EXT_URI));
+            writer.write_event(Event::Start(ext)).map_err(err)?;
+            let mut decorative = BytesStart::new("adec:decorative");
+            decorative.push_attribute(("xmlns:adec", DECORATIVE_NS));
+            decorative.push_attribute(("val", "1"));
+            writer.write_event(Event::Empty(decorative)).map_err(err)?;
+            writer.write_event(Event::End(BytesEnd::new("a:ext"))).map_err(err)?;
+            writer.write_event(Event::End(BytesEnd::new("a:extLst"))).map_err(err)?;
+            writer.write_event(Event::End(BytesEnd::new("wp:docPr"))).map_err(err)?;
+        } else {
+            if let Some(ref alt_text) = chart.alt_text {
+                doc_pr.push_attribute(("descr", alt_text.as_str()));
+            }
+            writer.write_event(Event::Empty(doc_pr)).map_err(err)?;
+        }
+
+        let mut graphic = BytesStart::new("a:graphic");
+        graphic.push_attribute(("xmlns:a", "http://schemas.openxmlformats.org/drawingml/2006/main"));
+        writer.write_event(Event::Start(graphic)).map_err(err)?;
+        let mut graphic_data = BytesStart::new("a:graphicData");
+        graphic_data.push_attribute(("uri", CHART_GRAPHIC_URI));
+        writer.write_event(Event::Start(graphic_data)).map_err(err)?;
+        let mut chart_ref = BytesStart::new("c:chart");
+        chart_ref.push_attribute(("xmlns:c", CHART_GRAPHIC_URI));
+        chart_ref.push_attribute((
+            "xmlns:r",
+            "http://schemas.openxmlformats.org/officeDocument/2006/relationships",
+        ));
+        chart_ref.push_attribute(("r:id", rel_id));
+        writer.write_event(Event::Empty(chart_ref)).map_err(err)?;
+        writer.write_event(Event::End(BytesEnd::new("a:graphicData"))).map_err(err)?;
+        writer.write_event(Event::End(BytesEnd::new("a:graphic"))).map_err(err)?;
+        writer.write_event(Event::End(BytesEnd::new("wp:inline"))).map_err(err)?;
+
+        let result = writer.into_inner().into_inner();
+        String::from_utf8(result).map_err(|e| ChartError::Serialization(e.to_string()))
+    }
+
     fn write_chart_space<W: std::io::Write>(
         &self,
         writer: &mut Writer<W>,
@@ -1437,6 +1517,29 @@ mod tests {
         assert_eq!(xml, original_xml);
     }
 
+    #[test]
+    fn test_inline_frame_alt_text_round_trip() {
+        let writer = DrawingMLWriter::new();
+        let parser = crate::DrawingMLParser::new();
+
+        let chart = Chart::new("test", ChartType::default()).with_alt_text("Sales \"by\" region");
+        let xml = writer.write_inline_frame(&chart, "rId5", 3, 5486400, 3200400).unwrap();
+        assert!(xml.contains("r:id=\"rId5\""));
+        let mut parsed = Chart::new("parsed", ChartType::default());
+        parser.parse_frame(&xml, &mut parsed).unwrap();
+        assert_eq!(parsed.alt_text.as_deref(), Some("Sales \"by\" region"));
+        assert!(!parsed.decorative);
+
+        let mut chart = Chart::new("test", ChartType::default()).with_alt_text("Ignored");
+        chart.decorative = true;
+        let xml = writer.write_inline_frame(&chart, "rId5", 3, 5486400, 3200400).unwrap();
+        assert!(!xml.contains("descr"));
+        let mut parsed = Chart::new("parsed", ChartType::default());
+        parser.parse_frame(&xml, &mut parsed).unwrap();
+        assert!(parsed.decorative);
+        assert!(parsed.alt_text.is_none());
+    }
+
     #[test]
     fn test_write_chart_with_categories() {
         let mut chart = Chart::new(
@@ -24,6 +24,12 @@ pub struct Chart {
     pub axes: ChartAxes,
     /// Preserve original XML for round-trip fidelity
     pub original_xml: Option<String>,
+    /// Alternative text for accessibility
+    #[serde(default)]
+    pub alt_text: Option<String>,
+    /// Whether the chart is decorative and skipped by screen readers
+    #[serde(default)]
+    pub decorative: bool,
 }
 
 impl Chart {
@@ -38,9 +44,17 @@ impl Chart {
             legend: None,
             axes: ChartAxes::default(),
             original_xml: None,
+            alt_text: None,
+            decorative: false,
         }
     }
 
+    /// Set the alt text
+    pub fn with_alt_text(mut self, alt_text: impl Into<String>) -> Self {
+        self.alt_text = Some(alt_text.into());
+        self
+    }
+
     /// Set the chart title
     pub fn with_title(mut self, text: impl Into<String>) -> Self {
         self.title = Some(ChartTitle {
@@ -13,11 +13,18 @@ use crate::clock::{HybridClock, VectorClock};
 use crate::crdt_tree::{BlockData, CrdtTree};
 use crate::lww_register::LwwMap;
 use crate::op_id::{ClientId, OpId};
-use crate::operation::{CrdtOp, OpLog};
+use crate::operation::{Annotation, CrdtOp, OpLog};
+use crate::presence::Position;
 use crate::rga::Rga;
-use doc_model::{DocumentTree, Node, NodeId, Paragraph, Run};
+use chrono::{DateTime, Utc};
+use doc_model::{
+    Comment, CommentAnchor, CommentId, CommentReply, DocumentTree, Node, NodeId, Paragraph, ReplyId, Run,
+};
+use revisions::{Revision, RevisionId, RevisionStatus};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use uuid::Uuid;
 
 /// The collaborative document state, combining all CRDT structures
 #[derive(Clone, Debug)]
@@ -34,6 +41,8 @@ pub struct CollaborativeDocument {
     text_content: HashMap<NodeId, Rga<char>>,
     /// Formatting per text node
     formatting: HashMap<NodeId, LwwMap<String, serde_json::Value>>,
+    /// Comment, reply, and tracked change fields
+    annotations: LwwMap<(Annotation, String), serde_json::Value>,
     /// Operation log for persistence and sync
     op_log: OpLog,
     /// Mapping from document positions to CRDT positions
@@ -59,6 +68,7 @@ impl CollaborativeDocument {
             tree,
             text_content: HashMap::new(),
             formatting: HashMap::new(),
+            annotations: LwwMap::new(client_id),
             op_log: OpLog::new(),
             position_map: PositionMap::new(),
             pending_ops: Vec::new(),
@@ -208,7 +218,7 @@ impl CollaborativeDocument {
 
             // Apply delete to RGA
             if let Some(rga) = self.text_content.get_mut(&node_id) {
-                rga.apply_delete(target_id);
+                rga.apply_delete_by(target_id, op_id);
             }
 
             let op = CrdtOp::TextDelete { id: op_id, target_id };
@@ -348,7 +358,7 @@ impl CollaborativeDocument {
         };
 
         // Delete from tree
-        self.tree.delete_block(target_op_id);
+        self.tree.apply_delete_block_by(target_op_id, op_id);
 
         // Remove associated data
         self.text_content.remove(&node_id);
@@ -471,6 +481,14 @@ impl CollaborativeDocument {
             return false;
         }
 
+        // Local writes made after seeing this one must win over it
+        if let CrdtOp::FormatSet { timestamp, .. }
+        | CrdtOp::BlockUpdate { timestamp, .. }
+        | CrdtOp::AnnotationSet { timestamp, .. } = &op
+        {
+            self.clock.update(*timestamp);
+        }
+
         match &op {
             CrdtOp::TextInsert {
                 id,
@@ -499,7 +517,7 @@ impl CollaborativeDocument {
                 for node_id in node_ids {
                     if let Some(rga) = self.text_content.get_mut(&node_id) {
                         if rga.get_node(*target_id).is_some() {
-                            rga.apply_delete(*target_id);
+                            rga.apply_delete_by(*target_id, op_id);
                             // Update position map after mutation
                             let rga_ref = self.text_content.get(&node_id).unwrap();
                             self.position_map.update(node_id, rga_ref);
@@ -559,7 +577,7 @@ impl CollaborativeDocument {
             }
 
             CrdtOp::BlockDelete { target_id, .. } => {
-                self.tree.apply_delete_block(*target_id);
+                self.tree.apply_delete_block_by(*target_id, op_id);
             }
 
             CrdtOp::BlockMove {
@@ -578,6 +596,21 @@ impl CollaborativeDocument {
             } => {
                 self.tree.update_block_data(*target_id, data.clone());
             }
+
+            CrdtOp::AnnotationSet {
+                target,
+                field,
+                value,
+                timestamp,
+                ..
+            } => {
+                self.annotations.apply(
+                    (target.clone(), field.clone()),
+                    Some(value.clone()),
+                    *timestamp,
+                    op_id.client_id,
+                );
+            }
         }
 
         self.op_log.add(op);
@@ -676,6 +709,213 @@ impl CollaborativeDocument {
         result
     }
 
+    // ========== Comments and Revisions ==========
+
+    /// Share a new comment, including any replies and its resolution
+    pub fn add_comment(&mut self, comment: &Comment) -> Vec<CrdtOp> {
+        let target = comment_target(comment.id());
+        let anchor = AnchorValue {
+            start: self.anchor_point(&comment.anchor().start),
+            end: self.anchor_point(&comment.anchor().end),
+        };
+        let mut ops = vec![
+            self.set_annotation(target.clone(), "anchor", json(anchor)),
+            self.set_annotation(target.clone(), "author", json(comment.author())),
+            self.set_annotation(target.clone(), "date", json(comment.date())),
+            self.set_annotation(target, "content", json(comment.content())),
+        ];
+        if let (Some(by), Some(date)) = (comment.resolved_by(), comment.resolved_date()) {
+            ops.push(self.set_resolution(comment.id(), Some(ResolutionValue { by: by.to_string(), date })));
+        }
+        for reply in comment.replies() {
+            ops.extend(self.add_reply(comment.id(), reply));
+        }
+        ops
+    }
+
+    /// Change a comment's text
+    pub fn set_comment_content(&mut self, comment_id: CommentId, content: &str) -> CrdtOp {
+        self.set_annotation(comment_target(comment_id), "content", json(content))
+    }
+
+    /// Resolve a comment, or reopen it when `resolved_by` is None
+    pub fn resolve_comment(&mut self, comment_id: CommentId, resolved_by: Option<&str>) -> CrdtOp {
+        let resolution = resolved_by.map(|by| ResolutionValue {
+            by: by.to_string(),
+            date: Utc::now(),
+        });
+        self.set_resolution(comment_id, resolution)
+    }
+
+    /// Delete a comment and its replies
+    pub fn delete_comment(&mut self, comment_id: CommentId) -> CrdtOp {
+        self.set_annotation(comment_target(comment_id), "deleted", json(true))
+    }
+
+    /// Share a reply to a comment
+    pub fn add_reply(&mut self, comment_id: CommentId, reply: &CommentReply) -> Vec<CrdtOp> {
+        let target = reply_target(comment_id, reply.id());
+        vec![
+            self.set_annotation(target.clone(), "author", json(reply.author())),
+            self.set_annotation(target.clone(), "date", json(reply.date())),
+            self.set_annotation(target, "content", json(reply.content())),
+        ]
+    }
+
+    /// Delete a reply
+    pub fn delete_reply(&mut self, comment_id: CommentId, reply_id: ReplyId) -> CrdtOp {
+        self.set_annotation(reply_target(comment_id, reply_id), "deleted", json(true))
+    }
+
+    /// Share a tracked change
+    pub fn track_revision(&mut self, revision: &Revision) -> Vec<CrdtOp> {
+        let target = revision_target(revision.id);
+        vec![
+            self.set_annotation(target.clone(), "revision", json(revision)),
+            self.set_annotation(target, "status", json(revision.status)),
+        ]
+    }
+
+    /// Accept or reject a tracked change
+    pub fn set_revision_status(&mut self, revision_id: RevisionId, status: RevisionStatus) -> CrdtOp {
+        self.set_annotation(revision_target(revision_id), "status", json(status))
+    }
+
+    /// Current comments with their replies, oldest first
+    ///
+    /// Comments whose fields haven't all arrived yet are left out.
+    pub fn comments(&self) -> Vec<Comment> {
+        let annotations = self.annotation_fields();
+        let mut comments: Vec<Comment> = annotations
+            .iter()
+            .filter_map(|(target, fields)| match target {
+                Annotation::Comment { comment_id } => self.comment_from(comment_id, fields),
+                _ => None,
+            })
+            .collect();
+
+        for (target, fields) in &annotations {
+            let Annotation::Reply { comment_id, reply_id } = target else {
+                continue;
+            };
+            let comment = comments.iter_mut().find(|comment| comment.id().to_string() == *comment_id);
+            if let (Some(comment), Some(reply)) = (comment, reply_from(reply_id, fields)) {
+                comment.add_reply(reply);
+            }
+        }
+
+        for comment in &mut comments {
+            comment.replies_mut().sort_by_key(|reply| reply.date());
+        }
+        comments.sort_by_key(|comment| comment.date());
+        comments
+    }
+
+    /// Current tracked changes, oldest first
+    pub fn revisions(&self) -> Vec<Revision> {
+        let mut revisions: Vec<Revision> = self
+            .annotation_fields()
+            .into_iter()
+            .filter(|(target, _)| matches!(target, Annotation::Revision { .. }))
+            .filter_map(|(_, fields)| {
+                let mut revision: Revision = fields.get("revision")?;
+                if let Some(status) = fields.get("status") {
+                    revision.status = status;
+                }
+                Some(revision)
+            })
+            .collect();
+        revisions.sort_by_key(|revision| revision.timestamp);
+        revisions
+    }
+
+    fn comment_from(&self, comment_id: &str, fields: &AnnotationFields) -> Option<Comment> {
+        if fields.is_deleted() {
+            return None;
+        }
+        let anchor: AnchorValue = fields.get("anchor")?;
+        let mut comment = Comment::with_id(
+            CommentId::from_uuid(Uuid::parse_str(comment_id).ok()?),
+            CommentAnchor::new(self.anchor_position(&anchor.start), self.anchor_position(&anchor.end)),
+            fields.get::<String>("author")?,
+            fields.get("date")?,
+            fields.get::<String>("content")?,
+        );
+        if let Some(Some(resolution)) = fields.get::<Option<ResolutionValue>>("resolution") {
+            comment.resolve_at(resolution.by, resolution.date);
+        }
+        Some(comment)
+    }
+
+    /// Set one field of a comment, reply, or tracked change
+    fn set_annotation(&mut self, target: Annotation, field: &str, value: serde_json::Value) -> CrdtOp {
+        let timestamp = self.clock.now();
+        let op_id = self.next_op_id();
+
+        self.annotations
+            .set((target.clone(), field.to_string()), value.clone(), timestamp);
+
+        let op = CrdtOp::AnnotationSet {
+            id: op_id,
+            target,
+            field: field.to_string(),
+            value,
+            timestamp,
+        };
+
+        self.op_log.add(op.clone());
+        self.pending_ops.push(op.clone());
+        op
+    }
+
+    fn set_resolution(&mut self, comment_id: CommentId, resolution: Option<ResolutionValue>) -> CrdtOp {
+        self.set_annotation(comment_target(comment_id), "resolution", json(resolution))
+    }
+
+    /// Annotation fields grouped by target
+    fn annotation_fields(&self) -> BTreeMap<&Annotation, AnnotationFields<'_>> {
+        let mut grouped: BTreeMap<&Annotation, AnnotationFields<'_>> = BTreeMap::new();
+        for ((target, field), value) in self.annotations.iter() {
+            grouped.entry(target).or_default().0.insert(field, value);
+        }
+        grouped
+    }
+
+    /// Anchor a document position to the character before it, so it moves
+    /// with the text as other collaborators edit
+    fn anchor_point(&self, position: &doc_model::Position) -> AnchorPoint {
+        let after = match position.offset.checked_sub(1) {
+            Some(index) => self.position_map.to_op_id(position.node_id, index).unwrap_or(OpId::root()),
+            None => OpId::root(),
+        };
+        AnchorPoint {
+            node_id: position.node_id,
+            after,
+        }
+    }
+
+    /// Document position of an anchor point
+    ///
+    /// If the anchoring character has been deleted, the position falls back
+    /// to where it used to be.
+    fn anchor_position(&self, point: &AnchorPoint) -> doc_model::Position {
+        let mut offset = 0;
+        if let Some(rga) = self.text_content.get(&point.node_id) {
+            for node in rga.nodes_in_order() {
+                if point.after.is_root() {
+                    break;
+                }
+                if node.value.is_some() {
+                    offset += 1;
+                }
+                if node.id == point.after {
+                    break;
+                }
+            }
+        }
+        doc_model::Position::new(point.node_id, offset)
+    }
+
     // ========== Sync Support ==========
 
     /// Get all operations since a vector clock
@@ -703,6 +943,47 @@ impl CollaborativeDocument {
         self.pending_ops.retain(|op| op.id().seq > up_to_seq);
     }
 
+    // ========== History Pruning ==========
+
+    /// Prune history every collaborator has seen
+    ///
+    /// `stable` must only cover operations every replica has received, such
+    /// as the floor of what the server reports each client has acknowledged.
+    /// Tombstones deleted within it, blocks deleted within it with their
+    /// text, and the operations it covers are dropped, keeping what the last
+    /// `undo_depth` undo steps, comment anchors, and pending operations need.
+    pub fn prune_history(&mut self, stable: &VectorClock, config: &PruneConfig) -> PruneStats {
+        self.undo_stack.truncate(config.undo_depth);
+
+        let mut keep: HashSet<OpId> = HashSet::new();
+        let undo_ops = self.undo_stack.undo_stack.iter().chain(&self.undo_stack.redo_stack);
+        for op in undo_ops.flat_map(|(_, ops)| ops).chain(&self.pending_ops) {
+            keep.insert(op.id());
+            keep.extend(op.target_id());
+        }
+        for ((_, field), value) in self.annotations.iter() {
+            if field != "anchor" {
+                continue;
+            }
+            if let Ok(anchor) = serde_json::from_value::<AnchorValue>(value.clone()) {
+                keep.extend([anchor.start.after, anchor.end.after]);
+            }
+        }
+
+        let mut stats = PruneStats::default();
+        for rga in self.text_content.values_mut() {
+            stats.tombstones += rga.prune_tombstones(stable, |id| keep.contains(&id));
+        }
+        for node_id in self.tree.prune_tombstones(stable, |id| keep.contains(&id)) {
+            self.text_content.remove(&node_id);
+            self.formatting.remove(&node_id);
+            self.position_map.remove_node(node_id);
+            stats.blocks += 1;
+        }
+        stats.ops = self.op_log.prune(stable, |op| keep.contains(&op.id()));
+        stats
+    }
+
     // ========== Undo/Redo ==========
 
     /// Generate undo operations for the last local operations
@@ -824,6 +1105,73 @@ impl CollaborativeDocument {
         }
     }
 
+    /// Undo an operation's effect with a new local operation
+    ///
+    /// Returns the new operation, which has been applied and is pending.
+    pub fn retract(&mut self, op: &CrdtOp) -> Option<CrdtOp> {
+        let inverse = self.generate_inverse(op)?;
+        self.apply_new_local(inverse)
+    }
+
+    /// Re-issue a last-writer-wins operation (formatting or a block update)
+    /// as a new local operation, timestamped after the original so it wins
+    pub fn reassert(&mut self, op: &CrdtOp) -> Option<CrdtOp> {
+        let op = match op {
+            CrdtOp::FormatSet {
+                node_id,
+                start_op_id,
+                end_op_id,
+                attribute,
+                value,
+                timestamp,
+                ..
+            } => CrdtOp::FormatSet {
+                id: self.next_op_id(),
+                node_id: *node_id,
+                start_op_id: *start_op_id,
+                end_op_id: *end_op_id,
+                attribute: attribute.clone(),
+                value: value.clone(),
+                timestamp: self.clock.update(*timestamp),
+            },
+            CrdtOp::BlockUpdate {
+                target_id,
+                data,
+                timestamp,
+                ..
+            } => CrdtOp::BlockUpdate {
+                id: self.next_op_id(),
+                target_id: *target_id,
+                data: data.clone(),
+                timestamp: self.clock.update(*timestamp),
+            },
+            CrdtOp::AnnotationSet {
+                target,
+                field,
+                value,
+                timestamp,
+                ..
+            } => CrdtOp::AnnotationSet {
+                id: self.next_op_id(),
+                target: target.clone(),
+                field: field.clone(),
+                value: value.clone(),
+                timestamp: self.clock.update(*timestamp),
+            },
+            _ => return None,
+        };
+        self.apply_new_local(op)
+    }
+
+    /// Apply an operation created locally and queue it to send
+    fn apply_new_local(&mut self, op: CrdtOp) -> Option<CrdtOp> {
+        if !self.apply_remote(op.clone()) {
+            return None;
+        }
+        self.pending_ops.push(op.clone());
+        Some(op)
+    }
+
     /// Check if undo is available
     pub fn can_undo(&self) -> bool {
         !self.undo_stack.undo_stack.is_empty()
@@ -838,6 +1186,81 @@ impl CollaborativeDocument {
     pub fn client_id(&self) -> ClientId {
         self.client_id
     }
+
+    /// Get the map between document positions and CRDT operations
+    pub fn position_map(&self) -> &PositionMap {
+        &self.position_map
+    }
+}
+
+/// A comment anchor endpoint: the character it follows, or the node start
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AnchorPoint {
+    node_id: NodeId,
+    after: OpId,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+struct AnchorValue {
+    start: AnchorPoint,
+    end: AnchorPoint,
+}
+
+/// Who resolved a comment and when, set as one field so it can't be torn
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ResolutionValue {
+    by: String,
+    date: DateTime<Utc>,
+}
+
+/// The current fields of one comment, reply, or tracked change
+#[derive(Default)]
+struct AnnotationFields<'a>(HashMap<&'a str, &'a serde_json::Value>);
+
+impl AnnotationFields<'_> {
+    fn get<T: DeserializeOwned>(&self, field: &str) -> Option<T> {
+        serde_json::from_value((*self.0.get(field)?).clone()).ok()
+    }
+
+    fn is_deleted(&self) -> bool {
+        self.get::<bool>("deleted").unwrap_or(false)
+    }
+}
+
+fn reply_from(reply_id: &str, fields: &AnnotationFields) -> Option<CommentReply> {
+    if fields.is_deleted() {
+        return None;
+    }
+    Some(CommentReply::with_id_and_date(
+        ReplyId::from_uuid(Uuid::parse_str(reply_id).ok()?),
+        fields.get::<String>("author")?,
+        fields.get("date")?,
+        fields.get::<String>("content")?,
+    ))
+}
+
+fn comment_target(comment_id: CommentId) -> Annotation {
+    Annotation::Comment {
+        comment_id: comment_id.to_string(),
+    }
+}
+
+fn reply_target(comment_id: CommentId, reply_id: ReplyId) -> Annotation {
+    Annotation::Reply {
+        comment_id: comment_id.to_string(),
+        reply_id: reply_id.to_string(),
+    }
+}
+
+fn revision_target(revision_id: RevisionId) -> Annotation {
+    Annotation::Revision {
+        revision_id: revision_id.0.to_string(),
+    }
+}
+
+fn json(value: impl Serialize) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or(serde_json::Value::Null)
 }
 
 /// Maps document positions to CRDT OpIds and back
@@ -902,6 +1325,41 @@ impl PositionMap {
     pub fn to_position(&self, op_id: OpId) -> Option<(NodeId, usize)> {
         self.op_to_offset.get(&op_id).copied()
     }
+
+    /// Resolve a presence position against the current document
+    ///
+    /// Returns None if the node isn't in the document. An offset past the
+    /// end of the node's text (the sender hadn't seen a deletion yet) is
+    /// moved to the end.
+    pub fn resolve(&self, position: &Position) -> Option<doc_model::Position> {
+        let node_id = NodeId::from_string(&position.node_id)?;
+        let len = self.offset_to_op.get(&node_id)?.len();
+        Some(doc_model::Position::new(node_id, position.offset.min(len)))
+    }
+}
+
+/// How much history pruning keeps
+#[derive(Clone, Copy, Debug)]
+pub struct PruneConfig {
+    /// Undo steps kept; what they need to be undone survives pruning
+    pub undo_depth: usize,
+}
+
+impl Default for PruneConfig {
+    fn default() -> Self {
+        Self { undo_depth: 100 }
+    }
+}
+
+/// What pruning history removed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PruneStats {
+    /// Deleted characters
+    pub tombstones: usize,
+    /// Deleted blocks, with their text
+    pub blocks: usize,
+    /// Logged operations
+    pub ops: usize,
 }
 
 /// Undo stack for collaborative editing
@@ -970,6 +1428,14 @@ impl CollaborativeUndoStack {
         self.redo_stack
             .retain(|(client, _)| *client != self.client_id);
     }
+
+    /// Keep only the most recent entries of each stack
+    pub fn truncate(&mut self, depth: usize) {
+        for stack in [&mut self.undo_stack, &mut self.redo_stack] {
+            let excess = stack.len().saturating_sub(depth);
+            stack.drain(..excess);
+        }
+    }
 }
 
 impl Default for CollaborativeUndoStack {
@@ -1178,6 +1644,127 @@ mod tests {
         // based on exact undo semantics
     }
 
+    #[test]
+    fn test_prune_history() {
+        let mut doc = CollaborativeDocument::new(make_client_id(1));
+        let (para_id, _) = doc.insert_paragraph(NodeId::new());
+        doc.insert_text(para_id, 0, "Hello world");
+        doc.delete_text(para_id, 5, 11);
+        let (other_id, _) = doc.insert_paragraph(para_id);
+        doc.insert_text(other_id, 0, "Gone");
+        doc.delete_paragraph(other_id);
+        doc.clear_pending_ops();
+
+        let mut stable = VectorClock::new();
+        stable.set(make_client_id(1), doc.seq);
+
+        // Undo history still needs everything
+        assert_eq!(doc.prune_history(&stable, &PruneConfig::default()), PruneStats::default());
+
+        let stats = doc.prune_history(&stable, &PruneConfig { undo_depth: 0 });
+        assert_eq!((stats.tombstones, stats.blocks), (6, 1));
+        assert_eq!(stats.ops, doc.seq as usize);
+        assert!(doc.op_log.is_empty());
+        assert_eq!(doc.get_text(para_id), Some("Hello".to_string()));
+        assert_eq!(doc.get_text(other_id), None);
+
+        // Pruned operations are still recognised as seen
+        assert!(doc.op_log.contains(OpId::new(make_client_id(1), 1)));
+
+        doc.insert_text(para_id, 5, "!");
+        assert_eq!(doc.get_text(para_id), Some("Hello!".to_string()));
+    }
+
+    #[test]
+    fn test_retract_and_reassert() {
+        let mut doc1 = CollaborativeDocument::new(make_client_id(1));
+        let mut doc2 = CollaborativeDocument::new(make_client_id(2));
+        let (node_id, ops) = doc1.insert_paragraph(NodeId::new());
+        let mut shared = ops;
+        shared.extend(doc1.insert_text(node_id, 0, "ab"));
+        doc2.apply_remote_batch(shared);
+
+        // doc2's later formatting wins until doc1 re-issues its own
+        let ours = doc1.format_text(node_id, 0, 2, "bold", serde_json::json!(true)).remove(0);
+        let theirs = doc2.format_text(node_id, 0, 2, "bold", serde_json::json!(false)).remove(0);
+        doc1.apply_remote(theirs.clone());
+        doc2.apply_remote(ours.clone());
+        let reasserted = doc1.reassert(&ours).unwrap();
+        assert!(reasserted.id() > ours.id());
+        doc2.apply_remote(reasserted);
+        for doc in [&doc1, &doc2] {
+            assert_eq!(doc.get_formatting(node_id, 0)["bold"], serde_json::json!(true));
+        }
+
+        // Retracting an insert deletes the character
+        let insert = doc1.insert_text(node_id, 2, "c").remove(0);
+        let delete = doc1.retract(&insert).unwrap();
+        assert!(matches!(delete, CrdtOp::TextDelete { target_id, .. } if target_id == insert.id()));
+        assert_eq!(doc1.get_text(node_id).as_deref(), Some("ab"));
+        assert!(doc1.pending_ops().iter().any(|op| op.id() == delete.id()));
+    }
+
+    #[test]
+    fn test_comment_and_revision_sync() {
+        let mut doc1 = CollaborativeDocument::new(make_client_id(1));
+        let mut doc2 = CollaborativeDocument::new(make_client_id(2));
+        let (node_id, mut shared) = doc1.insert_paragraph(NodeId::new());
+        shared.extend(doc1.insert_text(node_id, 0, "hello world"));
+
+        let at = |offset| doc_model::Position::new(node_id, offset);
+        let anchor = CommentAnchor::new(at(6), at(11));
+        let mut comment = Comment::with_id(CommentId::new(), anchor, "Alice", Utc::now(), "Which world?");
+        let reply = CommentReply::new("Bob", "This one");
+        comment.add_reply(reply.clone());
+        let revision = Revision::insert("Alice", revisions::RevisionRange::new(node_id, 0, 5));
+        shared.extend(doc1.add_comment(&comment));
+        shared.extend(doc1.track_revision(&revision));
+        doc2.apply_remote_batch(shared);
+
+        // The anchor follows text inserted before it
+        let mut ops = doc2.insert_text(node_id, 0, "Oh, ");
+        ops.push(doc2.resolve_comment(comment.id(), Some("Bob")));
+        ops.push(doc2.set_revision_status(revision.id, RevisionStatus::Accepted));
+        doc1.apply_remote_batch(ops);
+        doc2.apply_remote(doc1.delete_reply(comment.id(), reply.id()));
+
+        for doc in [&doc1, &doc2] {
+            let comments = doc.comments();
+            assert_eq!(comments.len(), 1);
+            assert_eq!((comments[0].anchor().start.offset, comments[0].anchor().end.offset), (10, 15));
+            assert_eq!(comments[0].resolved_by(), Some("Bob"));
+            assert!(comments[0].replies().is_empty());
+            assert_eq!(doc.revisions()[0].status, RevisionStatus::Accepted);
+        }
+
+        doc1.apply_remote(doc2.delete_comment(comment.id()));
+        assert!(doc1.comments().is_empty());
+    }
+
+    #[test]
+    fn test_local_write_after_remote_annotation_wins() {
+        let mut doc1 = CollaborativeDocument::new(make_client_id(1));
+        let mut doc2 = CollaborativeDocument::new(make_client_id(2));
+        let (node_id, mut shared) = doc1.insert_paragraph(NodeId::new());
+        shared.extend(doc1.insert_text(node_id, 0, "hello"));
+        let revision = Revision::insert("Alice", revisions::RevisionRange::new(node_id, 0, 5));
+        shared.extend(doc1.track_revision(&revision));
+        doc2.apply_remote_batch(shared);
+
+        // A write from a replica whose clock runs ahead of doc2's
+        let mut remote = doc1.set_revision_status(revision.id, RevisionStatus::Accepted);
+        if let CrdtOp::AnnotationSet { timestamp, .. } = &mut remote {
+            timestamp.physical += 60 * 60 * 1000;
+        }
+        assert!(doc2.apply_remote(remote));
+
+        // doc2 saw the remote write before making its own, so its own wins
+        doc1.apply_remote(doc2.set_revision_status(revision.id, RevisionStatus::Rejected));
+        for doc in [&doc1, &doc2] {
+            assert_eq!(doc.revisions()[0].status, RevisionStatus::Rejected);
+        }
+    }
+
     #[test]
     fn test_position_map() {
         let mut map = PositionMap::new();
@@ -1200,6 +1787,11 @@ mod tests {
         assert_eq!(map.to_position(id1), Some((node_id, 0)));
         assert_eq!(map.to_position(id2), Some((node_id, 1)));
         assert_eq!(map.to_position(id3), Some((node_id, 2)));
+
+        // Presence positions are clamped to the text
+        let resolved = map.resolve(&Position::new(node_id.to_string(), 7)).unwrap();
+        assert_eq!((resolved.node_id, resolved.offset), (node_id, 3));
+        assert!(map.resolve(&Position::new(NodeId::new().to_string(), 0)).is_none());
     }
 
     #[test]
@@ -446,6 +446,48 @@ impl ConflictResolver {
         }
     }
 
+    /// Resolve concurrent writes to the same comment or revision field using LWW.
+    pub fn resolve_annotation(&mut self, op1: &CrdtOp, op2: &CrdtOp) -> ConflictResult {
+        match (op1, op2) {
+            (
+                CrdtOp::AnnotationSet {
+                    id: id1,
+                    target: target1,
+                    field: field1,
+                    timestamp: ts1,
+                    ..
+                },
+                CrdtOp::AnnotationSet {
+                    id: id2,
+                    target: target2,
+                    field: field2,
+                    timestamp: ts2,
+                    ..
+                },
+            ) => {
+                if target1 != target2 || field1 != field2 {
+                    return ConflictResult::Compatible;
+                }
+
+                let (result, winner) = match (ts1, id1).cmp(&(ts2, id2)) {
+                    std::cmp::Ordering::Greater => (ConflictResult::Wins, *id1),
+                    std::cmp::Ordering::Less => (ConflictResult::Loses, *id2),
+                    std::cmp::Ordering::Equal => return ConflictResult::NoConflict,
+                };
+                self.log_conflict(
+                    ConflictType::FormattingConflict {
+                        attribute: field1.clone(),
+                        op1: *id1,
+                        op2: *id2,
+                    },
+                    winner,
+                );
+                result
+            }
+            _ => ConflictResult::NoConflict,
+        }
+    }
+
     // ========== Special Cases ==========
 
     /// Resolve table cell conflicts.
@@ -594,6 +636,11 @@ impl ConflictResolver {
                 CrdtOp::FormatSet { timestamp: ts2, .. },
             ) => self.resolve_formatting(op1, op2, *ts1, *ts2),
 
+            // Comment and revision field conflicts
+            (CrdtOp::AnnotationSet { .. }, CrdtOp::AnnotationSet { .. }) => {
+                self.resolve_annotation(op1, op2)
+            }
+
             // Block insert conflicts
             (CrdtOp::BlockInsert { .. }, CrdtOp::BlockInsert { .. }) => {
                 self.resolve_block_insert(op1, op2)
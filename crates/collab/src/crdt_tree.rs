@@ -7,10 +7,12 @@
 //! # Key Concepts
 //!
 //! - **Children Ordering**: Children are ordered by (after_sibling, OpId) for deterministic ordering
-//! - **Tombstones**: Deleted nodes stay in tree but are filtered from traversal
+//! - **Tombstones**: Deleted nodes stay in tree but are filtered from traversal,
+//!   until their deletion is stable and they are pruned
 //! - **Move = Delete + Insert**: Moving is equivalent to deleting from old parent and inserting at new
 //! - **NodeId Mapping**: Maintains bidirectional mapping between doc NodeId and CRDT OpId
 
+use crate::clock::VectorClock;
 use crate::op_id::{ClientId, OpId};
 use doc_model::NodeId;
 use serde::{Deserialize, Serialize};
@@ -197,6 +199,9 @@ pub struct CrdtTreeNode {
     pub data: BlockData,
     /// Whether this node is deleted (tombstone)
     pub tombstone: bool,
+    /// Operation that deleted this node, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_by: Option<OpId>,
 }
 
 impl CrdtTreeNode {
@@ -215,6 +220,7 @@ impl CrdtTreeNode {
             position_in_parent,
             data,
             tombstone: false,
+            deleted_by: None,
         }
     }
 
@@ -445,6 +451,50 @@ impl CrdtTree {
         }
     }
 
+    /// Apply a delete, recording the operation that made it
+    ///
+    /// Only tombstones whose deleting operation is known can be pruned.
+    pub fn apply_delete_block_by(&mut self, op_id: OpId, deleted_by: OpId) -> bool {
+        let deleted = self.apply_delete_block(op_id);
+        if deleted {
+            if let Some(node) = self.nodes.get_mut(&op_id) {
+                node.deleted_by = Some(deleted_by);
+            }
+        }
+        deleted
+    }
+
+    /// Remove deleted blocks, with everything under them, whose insert and
+    /// delete are both covered by the stable clock, except those `keep`
+    /// asks for
+    ///
+    /// Returns the document NodeIds that no longer have a block.
+    pub fn prune_tombstones(&mut self, stable: &VectorClock, keep: impl Fn(OpId) -> bool) -> Vec<NodeId> {
+        let is_stable = |id: OpId| stable.get(id.client_id) >= id.seq;
+        let mut pending: Vec<OpId> = self
+            .nodes
+            .values()
+            .filter(|node| node.tombstone && node.deleted_by.is_some_and(is_stable) && is_stable(node.id))
+            .map(|node| node.id)
+            .filter(|&id| !keep(id))
+            .collect();
+
+        let mut removed = Vec::new();
+        while let Some(op_id) = pending.pop() {
+            let Some(node) = self.nodes.remove(&op_id) else {
+                continue;
+            };
+            if let Some(children) = self.children.remove(&op_id) {
+                pending.extend(children.iter_ids());
+            }
+            if self.node_id_to_op_id.get(&node.node_id) == Some(&op_id) {
+                self.node_id_to_op_id.remove(&node.node_id);
+                removed.push(node.node_id);
+            }
+        }
+        removed
+    }
+
     /// Move a block to a new parent/position
     ///
     /// Returns the OpId of the move operation if successful, None otherwise.
@@ -855,6 +905,18 @@ mod tests {
         // Can't delete again
         assert!(!tree.delete_block(para_op_id));
 
+        // Once the delete is stable the block is pruned
+        let other = tree.insert_block(root, None, NodeId::new(), BlockData::Paragraph { style: None });
+        let node_id = tree.get_node(other).unwrap().node_id;
+        tree.apply_delete_block_by(other, OpId::new(make_client_id(2), 1));
+        let mut stable = VectorClock::new();
+        stable.set(make_client_id(1), tree.current_seq());
+        assert_eq!(tree.prune_tombstones(&stable, |_| false), Vec::<NodeId>::new());
+        stable.set(make_client_id(2), 1);
+        assert_eq!(tree.prune_tombstones(&stable, |_| false), vec![node_id]);
+        assert!(tree.get_node(other).is_none());
+        assert_eq!(tree.total_nodes(), 2);
+
         // Can't delete root
         assert!(!tree.delete_block(root));
     }
@@ -13,6 +13,7 @@
 //! - `lww_register`: Last-Writer-Wins register for attribute values
 //! - `operation`: Operation types for collaborative editing
 //! - `permissions`: Permission and access control for collaboration
+//! - `reconnect`: Client-side reconnection, resume, and heartbeats
 //! - `bridge`: Bridge between CRDT operations and the document model
 //! - `error`: Error types for the collaboration crate
 //!
@@ -47,6 +48,7 @@ pub mod op_id;
 pub mod operation;
 pub mod permissions;
 pub mod presence;
+pub mod reconnect;
 pub mod rga;
 pub mod sync;
 pub mod version;
@@ -82,11 +84,18 @@ pub use permissions::{
     ShareLink, UserId,
 };
 pub use presence::{
-    Position, PresenceManager, PresenceState, RemoteCursor, RemoteSelection, SelectionRange,
+    FollowTarget, Position, PresenceManager, PresenceState, RemoteCursor, RemoteSelection, SelectionRange,
+    Viewport,
+};
+pub use reconnect::{
+    Backoff, BackoffConfig, ConnectionAction, ConnectionEvent, HeartbeatConfig, ReconnectConfig, ReconnectManager,
 };
 pub use rga::{Rga, RgaNode, RgaOperation};
 pub use sync::{OpState, SyncEngine, SyncManager, SyncState, SyncStatus};
-pub use bridge::{CollaborativeDocument, CollaborativeUndoStack, PositionMap};
-pub use offline::{ConnectionStatus, MergeResult, OfflineError, OfflineManager, OfflineState, OfflineStatusInfo};
+pub use bridge::{CollaborativeDocument, CollaborativeUndoStack, PositionMap, PruneConfig, PruneStats};
+pub use offline::{
+    ConflictChoice, ConflictPlan, ConnectionStatus, MergeResult, OfflineError, OfflineManager, OfflineState,
+    OfflineStatusInfo, PendingConflict,
+};
 pub use version::{CheckpointConfig, Version, VersionDiff, VersionHistory, VersionId, VersionInfo};
 pub use conflict::{are_concurrent, merge_with_resolution, ConflictRecord, ConflictResolver, ConflictResult, ConflictType};
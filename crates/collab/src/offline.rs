@@ -9,12 +9,15 @@
 //! - Connection status tracking (Online, Offline, Reconnecting, Syncing)
 //! - Offline operation queue with persistent storage
 //! - Reconnection sync with conflict detection
+//! - Interactive review of conflicting offline edits
 //! - Time tracking for last successful sync
 //! - UI status information
 
 use crate::clock::VectorClock;
+use crate::conflict::{are_concurrent, ConflictRecord, ConflictResolver, ConflictResult};
 use crate::op_id::ClientId;
 use crate::operation::CrdtOp;
+use crate::reconnect::ConnectionEvent;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -60,6 +63,8 @@ pub struct OfflineManager {
     auto_save: bool,
     /// Client ID
     client_id: ClientId,
+    /// Conflicts awaiting the user's choice
+    conflicts: Vec<PendingConflict>,
 }
 
 impl OfflineManager {
@@ -73,6 +78,7 @@ impl OfflineManager {
             storage_path: None,
             auto_save: false,
             client_id,
+            conflicts: Vec::new(),
         }
     }
 
@@ -86,6 +92,7 @@ impl OfflineManager {
             storage_path: Some(path.as_ref().to_path_buf()),
             auto_save: true,
             client_id,
+            conflicts: Vec::new(),
         }
     }
 
@@ -104,6 +111,13 @@ impl OfflineManager {
         }
     }
 
+    /// Track the status reported by the connection manager
+    pub fn handle_connection_event(&mut self, event: &ConnectionEvent) {
+        if let ConnectionEvent::StatusChanged(status) = event {
+            self.set_status(*status);
+        }
+    }
+
     /// Check if currently offline
     pub fn is_offline(&self) -> bool {
         matches!(self.status, ConnectionStatus::Offline)
@@ -242,13 +256,7 @@ impl OfflineManager {
         }
 
         // Update the server clock with merged operations
-        for op in &remote_ops {
-            let op_id = op.id();
-            let current = self.last_server_clock.get(op_id.client_id);
-            if op_id.seq > current {
-                self.last_server_clock.set(op_id.client_id, op_id.seq);
-            }
-        }
+        self.record_server_ops(&remote_ops);
 
         // Generate summary
         let changes_summary = if had_conflicts {
@@ -271,6 +279,93 @@ impl OfflineManager {
         }
     }
 
+    fn record_server_ops(&mut self, remote_ops: &[CrdtOp]) {
+        for op in remote_ops {
+            let op_id = op.id();
+            let current = self.last_server_clock.get(op_id.client_id);
+            if op_id.seq > current {
+                self.last_server_clock.set(op_id.client_id, op_id.seq);
+            }
+        }
+    }
+
+    // ========== Conflict Review ==========
+
+    /// Find offline edits that conflict with concurrent remote ones
+    ///
+    /// Unlike [`handle_sync_response`](Self::handle_sync_response), which
+    /// lets the CRDT settle every conflict, this holds each one for the user
+    /// to choose a side. Replaces any review in progress.
+    pub fn review_sync_response(&mut self, remote_ops: &[CrdtOp]) -> &[PendingConflict] {
+        let mut resolver = ConflictResolver::new().with_logging();
+        self.conflicts.clear();
+
+        for local in &self.offline_queue {
+            for remote in remote_ops {
+                if !are_concurrent(local, remote) {
+                    continue;
+                }
+                resolver.clear_history();
+                if !matches!(resolver.resolve(local, remote), ConflictResult::Wins | ConflictResult::Loses) {
+                    continue;
+                }
+                if let Some(record) = resolver.history().last() {
+                    self.conflicts.push(PendingConflict {
+                        id: self.conflicts.len(),
+                        record: record.clone(),
+                        local: local.clone(),
+                        remote: remote.clone(),
+                        choice: None,
+                    });
+                }
+            }
+        }
+
+        self.record_server_ops(remote_ops);
+        &self.conflicts
+    }
+
+    /// Conflicts in the current review
+    pub fn pending_conflicts(&self) -> &[PendingConflict] {
+        &self.conflicts
+    }
+
+    /// Choose how to settle a conflict
+    pub fn choose_resolution(&mut self, conflict_id: usize, choice: ConflictChoice) -> Result<(), OfflineError> {
+        let conflict = self
+            .conflicts
+            .iter_mut()
+            .find(|conflict| conflict.id == conflict_id)
+            .ok_or(OfflineError::UnknownConflict(conflict_id))?;
+        conflict.choice = Some(choice);
+        Ok(())
+    }
+
+    /// Finish the review once every conflict has a choice
+    ///
+    /// Returns the follow-up operations the document must issue to carry
+    /// out the choices; the review is cleared.
+    pub fn finalize_review(&mut self) -> Result<ConflictPlan, OfflineError> {
+        let unresolved = self.conflicts.iter().filter(|conflict| conflict.choice.is_none()).count();
+        if unresolved > 0 {
+            return Err(OfflineError::UnresolvedConflicts(unresolved));
+        }
+
+        let mut plan = ConflictPlan::default();
+        for conflict in std::mem::take(&mut self.conflicts) {
+            let local_wins = conflict.record.winner == conflict.local.id();
+            match (conflict.choice, is_last_writer_wins(&conflict.local)) {
+                (Some(ConflictChoice::Ours), true) if !local_wins => plan.reassert(&conflict.local, &conflict.remote),
+                (Some(ConflictChoice::Theirs), true) if local_wins => plan.reassert(&conflict.remote, &conflict.local),
+                (Some(ConflictChoice::Theirs), false) => plan.retract(conflict.local),
+                // Either the choice already matches the CRDT's outcome, or
+                // (for a remote delete) it can't be overridden
+                _ => {}
+            }
+        }
+        Ok(plan)
+    }
+
     /// Mark sync as complete
     pub fn sync_complete(&mut self) {
         self.update_sync_time();
@@ -416,6 +511,82 @@ impl MergeResult {
     }
 }
 
+/// How to settle a conflict between an offline edit and a remote one
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictChoice {
+    /// Keep the offline edit
+    Ours,
+    /// Keep the remote edit
+    Theirs,
+    /// Keep both, as the CRDT merged them
+    Both,
+}
+
+/// A conflict awaiting the user's choice
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PendingConflict {
+    /// Identifier within the review
+    pub id: usize,
+    /// The conflict as the resolver settled it
+    pub record: ConflictRecord,
+    /// The offline edit
+    pub local: CrdtOp,
+    /// The concurrent remote edit
+    pub remote: CrdtOp,
+    /// The user's choice, once made
+    pub choice: Option<ConflictChoice>,
+}
+
+/// Operations a finished conflict review asks the document to issue
+#[derive(Clone, Debug, Default)]
+pub struct ConflictPlan {
+    /// Offline edits to undo (see `CollaborativeDocument::retract`)
+    pub retract: Vec<CrdtOp>,
+    /// Last-writer-wins edits to re-issue so they win (see
+    /// `CollaborativeDocument::reassert`); each is timestamped no earlier
+    /// than the edit it must beat
+    pub reassert: Vec<CrdtOp>,
+}
+
+impl ConflictPlan {
+    /// Check if no follow-up operations are needed
+    pub fn is_empty(&self) -> bool {
+        self.retract.is_empty() && self.reassert.is_empty()
+    }
+
+    fn retract(&mut self, op: CrdtOp) {
+        if !self.retract.iter().any(|other| other.id() == op.id()) {
+            self.retract.push(op);
+        }
+    }
+
+    fn reassert(&mut self, winner: &CrdtOp, loser: &CrdtOp) {
+        let mut op = winner.clone();
+        if let (
+            CrdtOp::FormatSet { timestamp, .. }
+            | CrdtOp::BlockUpdate { timestamp, .. }
+            | CrdtOp::AnnotationSet { timestamp, .. },
+            CrdtOp::FormatSet { timestamp: other, .. }
+            | CrdtOp::BlockUpdate { timestamp: other, .. }
+            | CrdtOp::AnnotationSet { timestamp: other, .. },
+        ) = (&mut op, loser)
+        {
+            *timestamp = (*timestamp).max(*other);
+        }
+        let id = op.id();
+        self.reassert.retain(|other| other.id() != id);
+        self.reassert.push(op);
+    }
+}
+
+fn is_last_writer_wins(op: &CrdtOp) -> bool {
+    matches!(
+        op,
+        CrdtOp::FormatSet { .. } | CrdtOp::BlockUpdate { .. } | CrdtOp::AnnotationSet { .. }
+    )
+}
+
 /// Offline errors
 #[derive(Clone, Debug, thiserror::Error)]
 pub enum OfflineError {
@@ -427,6 +598,10 @@ pub enum OfflineError {
     NoStoragePath,
     #[error("Merge error: {0}")]
     MergeError(String),
+    #[error("Unknown conflict: {0}")]
+    UnknownConflict(usize),
+    #[error("{0} conflicts have no resolution chosen")]
+    UnresolvedConflicts(usize),
 }
 
 /// UI display information for offline status
@@ -770,6 +945,56 @@ mod tests {
         assert!(result.changes_summary.unwrap().contains("conflicts"));
     }
 
+    #[test]
+    fn test_conflict_review() {
+        use crate::clock::Timestamp;
+
+        let mut manager = OfflineManager::new(make_client_id(1));
+        let node_id = NodeId::new();
+        let format = |client, seq, physical| CrdtOp::FormatSet {
+            id: make_op_id(client, seq),
+            node_id,
+            start_op_id: OpId::root(),
+            end_op_id: make_op_id(1, 1),
+            attribute: "bold".to_string(),
+            value: serde_json::json!(client == 1),
+            timestamp: Timestamp::new(physical, 0, make_client_id(client)),
+        };
+        let insert = |client, seq| CrdtOp::TextInsert {
+            id: make_op_id(client, seq),
+            node_id,
+            parent_op_id: OpId::root(),
+            char: 'a',
+        };
+
+        manager.queue_operation(insert(1, 1));
+        manager.queue_operation(format(1, 2, 100));
+        let remote_ops = vec![insert(2, 1), format(2, 2, 200), make_block_insert(2, 3)];
+
+        let conflicts = manager.review_sync_response(&remote_ops);
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[1].record.winner, make_op_id(2, 2));
+        assert_eq!(manager.get_sync_clock().get(make_client_id(2)), 3);
+
+        manager.choose_resolution(0, ConflictChoice::Theirs).unwrap();
+        assert!(matches!(manager.finalize_review(), Err(OfflineError::UnresolvedConflicts(1))));
+        assert!(manager.choose_resolution(5, ConflictChoice::Both).is_err());
+        manager.choose_resolution(1, ConflictChoice::Ours).unwrap();
+
+        // The remote insert stays and the offline formatting is re-issued
+        // after the remote formatting
+        let plan = manager.finalize_review().unwrap();
+        assert_eq!(plan.retract.iter().map(CrdtOp::id).collect::<Vec<_>>(), [make_op_id(1, 1)]);
+        match plan.reassert.as_slice() {
+            [CrdtOp::FormatSet { id, timestamp, .. }] => {
+                assert_eq!(*id, make_op_id(1, 2));
+                assert_eq!(timestamp.physical, 200);
+            }
+            other => panic!("unexpected plan {:?}", other),
+        }
+        assert!(manager.pending_conflicts().is_empty());
+    }
+
     #[test]
     fn test_handle_sync_response_updates_clock() {
         let client_id = make_client_id(1);
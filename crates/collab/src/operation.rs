@@ -65,6 +65,29 @@ pub enum CrdtOp {
         data: BlockData,
         timestamp: Timestamp,
     },
+
+    /// Set a field of a comment, reply, or tracked change
+    ///
+    /// Each field is a last-writer-wins register keyed by target and name.
+    AnnotationSet {
+        id: OpId,
+        target: Annotation,
+        field: String,
+        value: serde_json::Value,
+        timestamp: Timestamp,
+    },
+}
+
+/// Document metadata synced alongside text
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum Annotation {
+    /// A comment thread
+    Comment { comment_id: String },
+    /// A reply within a comment thread
+    Reply { comment_id: String, reply_id: String },
+    /// A tracked change
+    Revision { revision_id: String },
 }
 
 impl CrdtOp {
@@ -78,6 +101,7 @@ impl CrdtOp {
             CrdtOp::BlockDelete { id, .. } => *id,
             CrdtOp::BlockMove { id, .. } => *id,
             CrdtOp::BlockUpdate { id, .. } => *id,
+            CrdtOp::AnnotationSet { id, .. } => *id,
         }
     }
 
@@ -155,6 +179,20 @@ impl CrdtOp {
                 CrdtOp::BlockUpdate { target_id: t2, .. },
             ) => t1 == t2,
 
+            // Setting the same annotation field
+            (
+                CrdtOp::AnnotationSet {
+                    target: t1,
+                    field: f1,
+                    ..
+                },
+                CrdtOp::AnnotationSet {
+                    target: t2,
+                    field: f2,
+                    ..
+                },
+            ) => t1 == t2 && f1 == f2,
+
             // Different operation types don't conflict directly
             // (though there may be semantic conflicts)
             _ => false,
@@ -171,6 +209,7 @@ impl CrdtOp {
             CrdtOp::BlockDelete { target_id, .. } => Some(*target_id),
             CrdtOp::BlockMove { target_id, .. } => Some(*target_id),
             CrdtOp::BlockUpdate { target_id, .. } => Some(*target_id),
+            CrdtOp::AnnotationSet { .. } => None,
         }
     }
 
@@ -179,6 +218,17 @@ impl CrdtOp {
         matches!(self, CrdtOp::TextDelete { .. } | CrdtOp::BlockDelete { .. })
     }
 
+    /// Check if this operation only touches comments or their replies
+    pub fn is_comment(&self) -> bool {
+        matches!(
+            self,
+            CrdtOp::AnnotationSet {
+                target: Annotation::Comment { .. } | Annotation::Reply { .. },
+                ..
+            }
+        )
+    }
+
     /// Check if this is an insert operation
     pub fn is_insert(&self) -> bool {
         matches!(self, CrdtOp::TextInsert { .. } | CrdtOp::BlockInsert { .. })
@@ -246,6 +296,10 @@ pub struct OpLog {
     index: HashMap<OpId, usize>,
     /// Current vector clock
     clock: VectorClock,
+    /// Operations up to this clock may have been pruned; they still count
+    /// as received
+    #[serde(default)]
+    pruned: VectorClock,
 }
 
 impl OpLog {
@@ -255,6 +309,7 @@ impl OpLog {
             ops: Vec::new(),
             index: HashMap::new(),
             clock: VectorClock::new(),
+            pruned: VectorClock::new(),
         }
     }
 
@@ -263,7 +318,7 @@ impl OpLog {
         let op_id = op.id();
 
         // Check for duplicate
-        if self.index.contains_key(&op_id) {
+        if self.contains(op_id) {
             return false;
         }
 
@@ -286,9 +341,25 @@ impl OpLog {
         self.index.get(&id).map(|&idx| &self.ops[idx])
     }
 
-    /// Check if operation exists
+    /// Check if operation exists, or existed before being pruned
     pub fn contains(&self, id: OpId) -> bool {
-        self.index.contains_key(&id)
+        self.index.contains_key(&id) || self.pruned.get(id.client_id) >= id.seq
+    }
+
+    /// Drop operations covered by the stable clock, except those `keep`
+    /// asks for
+    ///
+    /// The stable clock must only cover operations every replica has seen;
+    /// they can no longer be sent to anyone. Returns the number dropped.
+    pub fn prune(&mut self, stable: &VectorClock, keep: impl Fn(&CrdtOp) -> bool) -> usize {
+        let before = self.ops.len();
+        self.ops.retain(|op| {
+            let id = op.id();
+            stable.get(id.client_id) < id.seq || keep(op)
+        });
+        self.pruned.merge(stable);
+        self.rebuild_index();
+        before - self.ops.len()
     }
 
     /// Get all operations after a given vector clock
@@ -677,6 +748,12 @@ mod tests {
         full_clock.set(ClientId::new(2), 2);
         let no_ops = log.ops_since(&full_clock);
         assert_eq!(no_ops.len(), 0);
+
+        // Pruned operations are gone but still count as received
+        assert_eq!(log.prune(&partial_clock, |_| false), 1);
+        assert_eq!(log.len(), 3);
+        assert!(log.contains(OpId::new(ClientId::new(1), 1)));
+        assert!(!log.add(make_text_insert(1, 1, 0, 'a')));
     }
 
     #[test]
@@ -636,6 +636,36 @@ impl PermissionManager {
         Ok(link)
     }
 
+    /// Get the level a user has when opening a document, optionally
+    /// through a share link (token and password)
+    ///
+    /// The link raises the user's own level, never lowers it. Fails if the
+    /// link is invalid or for another document, or if the user ends up
+    /// unable to view the document.
+    pub fn access_level(
+        &self,
+        user_id: &UserId,
+        doc_id: &DocId,
+        share_link: Option<(&str, Option<&str>)>,
+    ) -> Result<PermissionLevel, PermissionError> {
+        let mut level = self.get_level(user_id, doc_id);
+        if let Some((token, password)) = share_link {
+            let link = self.validate_share_link(token, password)?;
+            if &link.doc_id != doc_id {
+                return Err(PermissionError::InvalidShareLink);
+            }
+            level = level.max(link.level);
+        }
+
+        if !level.can_view() {
+            return Err(PermissionError::Denied(format!(
+                "No access to document {}",
+                doc_id
+            )));
+        }
+        Ok(level)
+    }
+
     /// Revoke a share link
     pub fn revoke_share_link(
         &mut self,
@@ -1269,6 +1299,51 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_access_level() {
+        let doc_id = DocId::from("doc1");
+        let owner = UserId::from("owner");
+        let guest = UserId::from("guest");
+
+        let mut manager = setup_manager_with_owner(&doc_id, &owner);
+        let link = manager
+            .create_share_link(
+                doc_id.clone(),
+                PermissionLevel::Editor,
+                owner.clone(),
+                None,
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(
+            manager.access_level(&owner, &doc_id, None).unwrap(),
+            PermissionLevel::Owner
+        );
+        assert!(matches!(
+            manager.access_level(&guest, &doc_id, None),
+            Err(PermissionError::Denied(_))
+        ));
+        assert_eq!(
+            manager
+                .access_level(&guest, &doc_id, Some((&link.token, None)))
+                .unwrap(),
+            PermissionLevel::Editor
+        );
+        // A link never lowers a user's own level
+        assert_eq!(
+            manager
+                .access_level(&owner, &doc_id, Some((&link.token, None)))
+                .unwrap(),
+            PermissionLevel::Owner
+        );
+        // A link only opens its own document
+        assert!(matches!(
+            manager.access_level(&guest, &DocId::from("doc2"), Some((&link.token, None))),
+            Err(PermissionError::InvalidShareLink)
+        ));
+    }
+
     #[test]
     fn test_revoke_share_link() {
         let doc_id = DocId::from("doc1");
@@ -3,6 +3,9 @@
 //! This module provides types and utilities for tracking user presence
 //! in a collaborative document editing session, including cursor positions,
 //! selections, and typing indicators.
+//!
+//! Users can also share where they're looking: others can follow a user's
+//! viewport, and a presenter pins everyone else to theirs until they stop.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -48,6 +51,23 @@ impl SelectionRange {
     }
 }
 
+/// The part of the document a user is looking at
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Viewport {
+    /// First visible position, so followers land on the same text even
+    /// when their layout differs
+    pub top: Position,
+    /// Vertical scroll offset in points
+    pub scroll_position: f64,
+}
+
+impl Viewport {
+    /// Create a new viewport
+    pub fn new(top: Position, scroll_position: f64) -> Self {
+        Self { top, scroll_position }
+    }
+}
+
 /// User's presence state in a document
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PresenceState {
@@ -67,6 +87,12 @@ pub struct PresenceState {
     pub last_active: u64,
     /// User's view scroll position (for "follow" feature)
     pub scroll_position: Option<f64>,
+    /// Shared viewport, for others to follow
+    #[serde(default)]
+    pub viewport: Option<Viewport>,
+    /// When the user started presenting (ms since epoch)
+    #[serde(default)]
+    pub presenting_since: Option<u64>,
 }
 
 impl PresenceState {
@@ -81,6 +107,8 @@ impl PresenceState {
             is_typing: false,
             last_active: current_timestamp_ms(),
             scroll_position: None,
+            viewport: None,
+            presenting_since: None,
         }
     }
 
@@ -108,6 +136,27 @@ impl PresenceState {
         self.touch();
     }
 
+    /// Share (or stop sharing) the viewport
+    pub fn set_viewport(&mut self, viewport: Option<Viewport>) {
+        self.viewport = viewport;
+        self.touch();
+    }
+
+    /// Start or stop presenting
+    pub fn set_presenting(&mut self, presenting: bool) {
+        self.presenting_since = match (presenting, self.presenting_since) {
+            (true, Some(since)) => Some(since),
+            (true, None) => Some(current_timestamp_ms()),
+            (false, _) => None,
+        };
+        self.touch();
+    }
+
+    /// Check if the user is presenting
+    pub fn is_presenting(&self) -> bool {
+        self.presenting_since.is_some()
+    }
+
     /// Touch last active timestamp
     pub fn touch(&mut self) {
         self.last_active = current_timestamp_ms();
@@ -146,6 +195,25 @@ pub struct RemoteSelection {
     pub selection: SelectionRange,
 }
 
+/// Where a following user should look
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FollowTarget {
+    /// User being followed
+    pub user_id: String,
+    /// Display name
+    pub display_name: String,
+    /// Color (hex)
+    pub color: String,
+    /// Their viewport, if they share it
+    pub viewport: Option<Viewport>,
+    /// Their cursor
+    pub cursor: Option<Position>,
+    /// Their selection
+    pub selection: Option<SelectionRange>,
+    /// Whether they're presenting, so the follower can't leave
+    pub pinned: bool,
+}
+
 /// Presence manager for a document
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PresenceManager {
@@ -159,6 +227,9 @@ pub struct PresenceManager {
     idle_threshold_ms: u64,
     /// Next color index for round-robin assignment
     next_color_index: usize,
+    /// Followed user of each following user
+    #[serde(default)]
+    follows: HashMap<String, String>,
 }
 
 impl Default for PresenceManager {
@@ -176,6 +247,7 @@ impl PresenceManager {
             available_colors: default_colors(),
             idle_threshold_ms: 60_000, // 1 minute
             next_color_index: 0,
+            follows: HashMap::new(),
         }
     }
 
@@ -187,6 +259,7 @@ impl PresenceManager {
             available_colors: default_colors(),
             idle_threshold_ms,
             next_color_index: 0,
+            follows: HashMap::new(),
         }
     }
 
@@ -204,6 +277,7 @@ impl PresenceManager {
     /// Remove a user
     pub fn remove_user(&mut self, user_id: &str) {
         self.users.remove(user_id);
+        self.forget_follows(user_id);
         // Keep color assignment for consistency if user rejoins
     }
 
@@ -289,6 +363,7 @@ impl PresenceManager {
 
         for id in &idle_users {
             self.users.remove(id);
+            self.forget_follows(id);
         }
 
         idle_users
@@ -322,6 +397,92 @@ impl PresenceManager {
             .collect()
     }
 
+    // ========== Following ==========
+
+    /// Follow another user's viewport
+    ///
+    /// Returns false if the leader isn't present or is the follower.
+    pub fn follow(&mut self, follower_id: &str, leader_id: &str) -> bool {
+        if follower_id == leader_id || !self.users.contains_key(leader_id) {
+            return false;
+        }
+        self.follows.insert(follower_id.to_string(), leader_id.to_string());
+        true
+    }
+
+    /// Stop following
+    ///
+    /// A presenter still pins the user until they stop presenting.
+    pub fn unfollow(&mut self, follower_id: &str) {
+        self.follows.remove(follower_id);
+    }
+
+    /// The user someone follows: the presenter if there is one, otherwise
+    /// whoever they chose to follow
+    pub fn following(&self, user_id: &str) -> Option<&str> {
+        match self.presenter() {
+            Some(presenter) if presenter.user_id != user_id => Some(&presenter.user_id),
+            _ => self.follows.get(user_id).map(String::as_str),
+        }
+    }
+
+    /// Users following someone, sorted by ID
+    pub fn followers(&self, leader_id: &str) -> Vec<&str> {
+        let mut followers: Vec<&str> = self
+            .users
+            .keys()
+            .map(String::as_str)
+            .filter(|user_id| self.following(user_id) == Some(leader_id))
+            .collect();
+        followers.sort_unstable();
+        followers
+    }
+
+    /// Where a user following someone should look
+    pub fn follow_target(&self, user_id: &str) -> Option<FollowTarget> {
+        let leader = self.users.get(self.following(user_id)?)?;
+        Some(FollowTarget {
+            user_id: leader.user_id.clone(),
+            display_name: leader.display_name.clone(),
+            color: leader.color.clone(),
+            viewport: leader.viewport.clone(),
+            cursor: leader.cursor.clone(),
+            selection: leader.selection.clone(),
+            pinned: leader.is_presenting(),
+        })
+    }
+
+    /// Share a user's viewport
+    pub fn update_viewport(&mut self, user_id: &str, viewport: Option<Viewport>) {
+        if let Some(state) = self.users.get_mut(user_id) {
+            state.set_viewport(viewport);
+        }
+    }
+
+    /// Start or stop presenting: bring everyone to this user
+    pub fn set_presenting(&mut self, user_id: &str, presenting: bool) {
+        if let Some(state) = self.users.get_mut(user_id) {
+            state.set_presenting(presenting);
+        }
+    }
+
+    /// The presenter everyone follows
+    ///
+    /// If several users present at once, the one who started first wins.
+    pub fn presenter(&self) -> Option<&PresenceState> {
+        self.users
+            .values()
+            .filter_map(|state| Some((state.presenting_since?, state)))
+            .min_by(|(a, a_state), (b, b_state)| a.cmp(b).then_with(|| a_state.user_id.cmp(&b_state.user_id)))
+            .map(|(_, state)| state)
+    }
+
+    /// Drop follows to and from a user who left
+    fn forget_follows(&mut self, user_id: &str) {
+        self.follows
+            .retain(|follower, leader| follower != user_id && leader != user_id);
+    }
+
     /// Set the idle threshold
     pub fn set_idle_threshold(&mut self, threshold_ms: u64) {
         self.idle_threshold_ms = threshold_ms;
@@ -779,4 +940,35 @@ mod tests {
         state.set_scroll_position(None);
         assert!(state.scroll_position.is_none());
     }
+
+    #[test]
+    fn test_follow_and_present() {
+        let mut manager = PresenceManager::new();
+        for (id, name) in [("alice", "Alice"), ("bob", "Bob"), ("carol", "Carol")] {
+            manager.update_user(PresenceState::new(id.into(), name.into(), "#E91E63".into()));
+        }
+        let viewport = Viewport::new(Position::new("node-1", 4), 320.0);
+        manager.update_viewport("alice", Some(viewport.clone()));
+
+        assert!(!manager.follow("bob", "bob"));
+        assert!(!manager.follow("bob", "dave"));
+        assert!(manager.follow("bob", "alice"));
+        let target = manager.follow_target("bob").unwrap();
+        assert_eq!((target.user_id.as_str(), target.viewport, target.pinned), ("alice", Some(viewport), false));
+        assert!(manager.follow_target("carol").is_none());
+
+        // A presenter pins everyone else, overriding their own follows
+        manager.set_presenting("carol", true);
+        assert_eq!(manager.followers("carol"), vec!["alice", "bob"]);
+        manager.unfollow("bob");
+        assert!(manager.follow_target("bob").unwrap().pinned);
+        assert!(manager.following("carol").is_none());
+
+        // Releasing returns followers to their own choice
+        manager.set_presenting("carol", false);
+        assert!(manager.following("bob").is_none());
+        manager.follow("bob", "alice");
+        manager.remove_user("alice");
+        assert!(manager.following("bob").is_none());
+    }
 }
@@ -0,0 +1,421 @@
+//! Client-side connection management for the collaboration server.
+//!
+//! [`ReconnectManager`] decides when to connect, ping, and give up on a
+//! connection; the caller owns the socket and reports what happens to it.
+//! It does no I/O itself, so it works with any WebSocket client and can be
+//! driven with fake time in tests.
+//!
+//! # Features
+//!
+//! - Reconnection with jittered exponential backoff
+//! - Resume tokens, so the server only sends what was missed while away
+//! - Heartbeats that detect connections that died without closing
+//! - Connection status events for the offline manager
+
+use crate::offline::ConnectionStatus;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Backoff between reconnection attempts
+#[derive(Clone, Debug)]
+pub struct BackoffConfig {
+    /// Delay before the first retry
+    pub initial_delay: Duration,
+    /// Longest delay between retries
+    pub max_delay: Duration,
+    /// Factor the delay grows by after each failed attempt
+    pub multiplier: f64,
+    /// Fraction of each delay that's randomized (0.0 to 1.0), so clients
+    /// dropped together don't reconnect together
+    pub jitter: f64,
+    /// Attempts before giving up; None retries forever
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.5,
+            max_attempts: None,
+        }
+    }
+}
+
+/// Jittered exponential backoff
+#[derive(Clone, Debug)]
+pub struct Backoff {
+    config: BackoffConfig,
+    /// Retries since the last reset
+    attempt: u32,
+    /// Random number generator state (xorshift)
+    rng: u64,
+}
+
+impl Backoff {
+    /// Create a backoff seeded from the clock
+    pub fn new(config: BackoffConfig) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+        Self {
+            config,
+            attempt: 0,
+            rng: 0,
+        }
+        .with_seed(seed)
+    }
+
+    /// Use a fixed seed for the jitter
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        // Xorshift gets stuck at zero
+        self.rng = seed | 1;
+        self
+    }
+
+    /// Delay before the next attempt, or None once the attempts are used up
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        if self.config.max_attempts.is_some_and(|max| self.attempt >= max) {
+            return None;
+        }
+        let growth = self.config.multiplier.max(1.0).powi(self.attempt.min(64) as i32);
+        let base = (self.config.initial_delay.as_secs_f64() * growth).min(self.config.max_delay.as_secs_f64());
+        let jitter = base * self.config.jitter.clamp(0.0, 1.0) * self.next_random();
+        self.attempt += 1;
+        Some(Duration::from_secs_f64(base - jitter))
+    }
+
+    /// Start over after a successful connection
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    /// Retries since the last reset
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// A random number in [0, 1)
+    fn next_random(&mut self) -> f64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Heartbeat timing
+#[derive(Clone, Debug)]
+pub struct HeartbeatConfig {
+    /// Time without a ping before sending one
+    pub interval: Duration,
+    /// Time without hearing from the server before the connection is
+    /// considered dead
+    pub timeout: Duration,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(15),
+            timeout: Duration::from_secs(45),
+        }
+    }
+}
+
+/// Reconnection configuration
+#[derive(Clone, Debug, Default)]
+pub struct ReconnectConfig {
+    pub backoff: BackoffConfig,
+    pub heartbeat: HeartbeatConfig,
+}
+
+/// Something the caller should do with the connection
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConnectionAction {
+    /// Open a connection and join the document, passing the resume token
+    /// if there is one
+    Connect { resume_token: Option<String> },
+    /// Send a ping
+    Ping,
+    /// Close the connection; it stopped responding
+    Close,
+}
+
+/// Something that happened to the connection
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// The connection status changed
+    StatusChanged(ConnectionStatus),
+    /// The connection dropped and another attempt is scheduled
+    RetryScheduled { attempt: u32, delay: Duration },
+    /// The server stopped responding to heartbeats
+    HeartbeatTimeout,
+    /// The attempts are used up; `connect` starts over
+    GaveUp,
+}
+
+/// Where the connection is in its lifecycle
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LinkState {
+    /// Not connected and not trying to
+    Idle,
+    /// Waiting for the socket to open and the join to complete
+    Connecting,
+    /// Joined; waiting for the server to bring us up to date
+    Syncing,
+    /// Up to date
+    Online,
+    /// Waiting to retry
+    Waiting { until: Instant },
+}
+
+/// Client-side connection manager
+pub struct ReconnectManager {
+    config: ReconnectConfig,
+    backoff: Backoff,
+    state: LinkState,
+    status: ConnectionStatus,
+    /// Token from the server's last join, used to resume the session
+    resume_token: Option<String>,
+    /// When anything was last heard from the server
+    last_received: Option<Instant>,
+    /// When the last ping was sent
+    last_ping: Option<Instant>,
+    events: Vec<ConnectionEvent>,
+}
+
+impl ReconnectManager {
+    /// Create a manager that hasn't connected yet
+    pub fn new(config: ReconnectConfig) -> Self {
+        Self {
+            backoff: Backoff::new(config.backoff.clone()),
+            config,
+            state: LinkState::Idle,
+            status: ConnectionStatus::Offline,
+            resume_token: None,
+            last_received: None,
+            last_ping: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// Use a fixed seed for the backoff jitter
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.backoff = self.backoff.with_seed(seed);
+        self
+    }
+
+    /// Start connecting now, cancelling any scheduled retry
+    pub fn connect(&mut self) -> ConnectionAction {
+        self.state = LinkState::Connecting;
+        self.set_status(ConnectionStatus::Reconnecting);
+        ConnectionAction::Connect {
+            resume_token: self.resume_token.clone(),
+        }
+    }
+
+    /// The server accepted the join, issuing a token for the next one
+    ///
+    /// If the server resumed the session it sends only what was missed;
+    /// otherwise the caller should request a sync from its own clock.
+    pub fn joined(&mut self, now: Instant, resume_token: Option<String>) {
+        self.resume_token = resume_token;
+        self.state = LinkState::Syncing;
+        self.last_received = Some(now);
+        self.last_ping = Some(now);
+        self.set_status(ConnectionStatus::Syncing);
+    }
+
+    /// The sync after joining finished
+    pub fn synced(&mut self, now: Instant) {
+        self.state = LinkState::Online;
+        self.last_received = Some(now);
+        self.backoff.reset();
+        self.set_status(ConnectionStatus::Online);
+    }
+
+    /// A message arrived from the server
+    pub fn message_received(&mut self, now: Instant) {
+        self.last_received = Some(now);
+    }
+
+    /// The connection closed or failed to open; schedules a retry
+    pub fn disconnected(&mut self, now: Instant) {
+        if self.state == LinkState::Idle {
+            return;
+        }
+        self.last_received = None;
+        self.last_ping = None;
+        match self.backoff.next_delay() {
+            Some(delay) => {
+                self.state = LinkState::Waiting { until: now + delay };
+                self.events.push(ConnectionEvent::RetryScheduled {
+                    attempt: self.backoff.attempt(),
+                    delay,
+                });
+                self.set_status(ConnectionStatus::Reconnecting);
+            }
+            None => {
+                self.state = LinkState::Idle;
+                self.backoff.reset();
+                self.events.push(ConnectionEvent::GaveUp);
+                self.set_status(ConnectionStatus::Offline);
+            }
+        }
+    }
+
+    /// Stop connecting, e.g. when the user closes the document
+    pub fn stop(&mut self) {
+        self.state = LinkState::Idle;
+        self.resume_token = None;
+        self.backoff.reset();
+        self.set_status(ConnectionStatus::Offline);
+    }
+
+    /// What to do now: retry, ping, or close a dead connection
+    pub fn poll(&mut self, now: Instant) -> Option<ConnectionAction> {
+        match self.state {
+            LinkState::Waiting { until } if now >= until => Some(self.connect()),
+            LinkState::Syncing | LinkState::Online => {
+                let heartbeat = &self.config.heartbeat;
+                let last_received = self.last_received.unwrap_or(now);
+                if now.duration_since(last_received) >= heartbeat.timeout {
+                    self.events.push(ConnectionEvent::HeartbeatTimeout);
+                    self.disconnected(now);
+                    return Some(ConnectionAction::Close);
+                }
+                let last_ping = self.last_ping.unwrap_or(now);
+                if now.duration_since(last_ping) >= heartbeat.interval {
+                    self.last_ping = Some(now);
+                    return Some(ConnectionAction::Ping);
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// When `poll` next has something to do, for the caller to sleep until
+    pub fn next_deadline(&self) -> Option<Instant> {
+        match self.state {
+            LinkState::Waiting { until } => Some(until),
+            LinkState::Syncing | LinkState::Online => {
+                let heartbeat = &self.config.heartbeat;
+                let timeout = self.last_received.map(|at| at + heartbeat.timeout);
+                let ping = self.last_ping.map(|at| at + heartbeat.interval);
+                timeout.into_iter().chain(ping).min()
+            }
+            LinkState::Idle | LinkState::Connecting => None,
+        }
+    }
+
+    /// Current connection status
+    pub fn status(&self) -> ConnectionStatus {
+        self.status
+    }
+
+    /// Token to resume the session with on the next connection
+    pub fn resume_token(&self) -> Option<&str> {
+        self.resume_token.as_deref()
+    }
+
+    /// Take the events since the last call
+    pub fn take_events(&mut self) -> Vec<ConnectionEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn set_status(&mut self, status: ConnectionStatus) {
+        if self.status != status {
+            self.status = status;
+            self.events.push(ConnectionEvent::StatusChanged(status));
+        }
+    }
+}
+
+impl Default for ReconnectManager {
+    fn default() -> Self {
+        Self::new(ReconnectConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offline::OfflineManager;
+    use crate::op_id::ClientId;
+
+    #[test]
+    fn test_backoff() {
+        let config = BackoffConfig {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            multiplier: 2.0,
+            jitter: 0.0,
+            max_attempts: Some(6),
+        };
+        let mut backoff = Backoff::new(config.clone());
+        let delays: Vec<u128> = std::iter::from_fn(|| backoff.next_delay()).map(|d| d.as_millis()).collect();
+        assert_eq!(delays, [100, 200, 400, 800, 1000, 1000]);
+
+        // Jitter shortens delays by up to the given fraction
+        let mut backoff = Backoff::new(BackoffConfig { jitter: 0.5, max_attempts: None, ..config }).with_seed(7);
+        for _ in 0..20 {
+            backoff.reset();
+            let delay = backoff.next_delay().unwrap();
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn test_reconnect_cycle() {
+        let config = ReconnectConfig {
+            backoff: BackoffConfig { jitter: 0.0, ..Default::default() },
+            heartbeat: HeartbeatConfig {
+                interval: Duration::from_secs(10),
+                timeout: Duration::from_secs(30),
+            },
+        };
+        let mut manager = ReconnectManager::new(config);
+        let mut offline = OfflineManager::new(ClientId::new(1));
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert_eq!(manager.connect(), ConnectionAction::Connect { resume_token: None });
+        manager.joined(at(0), Some("token-1".into()));
+        manager.synced(at(0));
+        for event in manager.take_events() {
+            offline.handle_connection_event(&event);
+        }
+        assert!(offline.is_online());
+
+        // Pings when idle, and gives up on a silent server
+        assert_eq!(manager.poll(at(10)), Some(ConnectionAction::Ping));
+        assert_eq!(manager.poll(at(15)), None);
+        assert_eq!(manager.poll(at(30)), Some(ConnectionAction::Close));
+        assert_eq!(
+            manager.take_events(),
+            [
+                ConnectionEvent::HeartbeatTimeout,
+                ConnectionEvent::RetryScheduled { attempt: 1, delay: Duration::from_millis(500) },
+                ConnectionEvent::StatusChanged(ConnectionStatus::Reconnecting),
+            ]
+        );
+
+        // The retry resumes the session
+        assert_eq!(manager.poll(at(30)), None);
+        let retry = at(30) + Duration::from_millis(500);
+        assert_eq!(manager.next_deadline(), Some(retry));
+        assert_eq!(
+            manager.poll(retry),
+            Some(ConnectionAction::Connect { resume_token: Some("token-1".into()) })
+        );
+        manager.disconnected(retry);
+        assert!(matches!(
+            manager.take_events().as_slice(),
+            [ConnectionEvent::RetryScheduled { attempt: 2, delay }] if *delay == Duration::from_secs(1)
+        ));
+    }
+}
@@ -17,7 +17,16 @@
 //! they are ordered by their OpId in descending order (higher IDs come first in the
 //! children list, then we traverse in reverse for left-to-right reading order).
 //! This ensures all replicas converge to the same sequence.
+//!
+//! # Pruning
+//!
+//! Tombstones are kept so that concurrent inserts can still find their
+//! parent. Once a deletion is stable (every replica has seen it), nothing new
+//! can be inserted after the tombstone and it can be pruned: its children
+//! take its place in its parent's children, ordered by the tombstone's ID so
+//! pruned and unpruned replicas place later inserts the same way.
 
+use crate::clock::VectorClock;
 use crate::op_id::{ClientId, OpId};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -33,6 +42,13 @@ pub struct RgaNode<T> {
     pub parent_id: Option<OpId>,
     /// IDs of nodes inserted immediately after this one (sorted descending by OpId)
     children: Vec<OpId>,
+    /// Operation that deleted this node, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deleted_by: Option<OpId>,
+    /// ID this node is ordered by among its siblings, when it took the place
+    /// of a pruned tombstone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    order_key: Option<OpId>,
 }
 
 impl<T> RgaNode<T> {
@@ -43,6 +59,8 @@ impl<T> RgaNode<T> {
             value,
             parent_id,
             children: Vec::new(),
+            deleted_by: None,
+            order_key: None,
         }
     }
 
@@ -55,6 +73,16 @@ impl<T> RgaNode<T> {
     pub fn children(&self) -> &[OpId] {
         &self.children
     }
+
+    /// Get the operation that deleted this node, if known
+    pub fn deleted_by(&self) -> Option<OpId> {
+        self.deleted_by
+    }
+
+    /// ID that orders this node among its siblings
+    fn sort_key(&self) -> OpId {
+        self.order_key.unwrap_or(self.id)
+    }
 }
 
 /// Replicated Growable Array - a CRDT for ordered sequences
@@ -152,18 +180,21 @@ impl<T: Clone> Rga<T> {
         // Resolve parent_id - use root if None
         let actual_parent_id = parent_id.unwrap_or(self.root);
 
+        // Find insertion position to maintain descending order
+        let pos = self.nodes.get(&actual_parent_id).map(|parent| {
+            parent
+                .children
+                .iter()
+                .position(|child_id| self.nodes.get(child_id).map_or(*child_id, RgaNode::sort_key) < id)
+                .unwrap_or(parent.children.len())
+        });
+
         // Create the new node
         let node = RgaNode::new(id, Some(value), Some(actual_parent_id));
         self.nodes.insert(id, node);
 
         // Add to parent's children in sorted order (descending by OpId)
-        if let Some(parent) = self.nodes.get_mut(&actual_parent_id) {
-            // Find insertion position to maintain descending order
-            let pos = parent
-                .children
-                .iter()
-                .position(|&child_id| child_id < id)
-                .unwrap_or(parent.children.len());
+        if let (Some(parent), Some(pos)) = (self.nodes.get_mut(&actual_parent_id), pos) {
             parent.children.insert(pos, id);
         }
 
@@ -193,6 +224,66 @@ impl<T: Clone> Rga<T> {
         }
     }
 
+    /// Apply a delete, recording the operation that made it
+    ///
+    /// Only tombstones whose deleting operation is known can be pruned.
+    pub fn apply_delete_by(&mut self, id: OpId, deleted_by: OpId) -> bool {
+        match self.nodes.get_mut(&id) {
+            Some(node) if !id.is_root() => {
+                if node.value.take().is_some() || node.deleted_by.is_none() {
+                    node.deleted_by = Some(deleted_by);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove tombstones whose insert and delete are both covered by the
+    /// stable clock, except those `keep` asks for
+    ///
+    /// The stable clock must only cover operations every replica has seen,
+    /// so that nothing can still be inserted after a pruned tombstone.
+    /// Returns the number of tombstones removed.
+    pub fn prune_tombstones(&mut self, stable: &VectorClock, keep: impl Fn(OpId) -> bool) -> usize {
+        let is_stable = |id: OpId| stable.get(id.client_id) >= id.seq;
+        let prunable: Vec<OpId> = self
+            .nodes
+            .values()
+            .filter(|node| node.value.is_none() && !node.id.is_root())
+            .filter(|node| node.deleted_by.is_some_and(is_stable) && is_stable(node.id))
+            .map(|node| node.id)
+            .filter(|&id| !keep(id))
+            .collect();
+
+        for &id in &prunable {
+            let Some(node) = self.nodes.remove(&id) else {
+                continue;
+            };
+            let parent_id = node.parent_id.unwrap_or(self.root);
+            let sort_key = node.sort_key();
+
+            // The children take the tombstone's place, in the same order
+            for child_id in &node.children {
+                if let Some(child) = self.nodes.get_mut(child_id) {
+                    child.parent_id = Some(parent_id);
+                    child.order_key = Some(sort_key);
+                }
+            }
+            if let Some(parent) = self.nodes.get_mut(&parent_id) {
+                if let Some(pos) = parent.children.iter().position(|&child_id| child_id == id) {
+                    parent.children.splice(pos..=pos, node.children);
+                }
+            }
+        }
+        prunable.len()
+    }
+
+    /// Get the number of nodes, including tombstones
+    pub fn node_count(&self) -> usize {
+        self.nodes.len() - 1
+    }
+
     /// Apply any RGA operation
     pub fn apply_operation(&mut self, op: &RgaOperation<T>) {
         match op {
@@ -775,4 +866,32 @@ mod tests {
         assert!(delete_op.is_delete());
         assert_eq!(delete_op.op_id(), OpId::new(1u64, 2));
     }
+
+    #[test]
+    fn test_prune_tombstones_keeps_order() {
+        let mut pruned = Rga::<char>::new(1u64);
+        let a = pruned.insert(None, 'a');
+        let b = pruned.insert(Some(a), 'b');
+        let c = pruned.insert(Some(b), 'c');
+        pruned.insert(Some(c), 'd');
+        pruned.apply_delete_by(b, OpId::new(1u64, 5));
+        pruned.apply_delete_by(c, OpId::new(1u64, 6));
+        let mut unpruned = pruned.clone();
+
+        // Nothing is pruned until the deletes are stable
+        let mut stable = VectorClock::new();
+        stable.set(ClientId::new(1), 4);
+        assert_eq!(pruned.prune_tombstones(&stable, |_| false), 0);
+        stable.set(ClientId::new(1), 6);
+        assert_eq!(pruned.prune_tombstones(&stable, |id| id == b), 1);
+        assert_eq!(pruned.prune_tombstones(&stable, |_| false), 1);
+        assert_eq!(pruned.node_count(), 2);
+
+        // A later insert after 'a' lands in the same place on both replicas
+        for rga in [&mut pruned, &mut unpruned] {
+            rga.apply_insert(OpId::new(2u64, 3), Some(a), 'x');
+            rga.apply_insert(OpId::new(2u64, 1), Some(a), 'y');
+        }
+        assert_eq!(pruned.to_vec(), unpruned.to_vec());
+    }
 }
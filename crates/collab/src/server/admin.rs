@@ -0,0 +1,375 @@
+//! Admin API for the collaboration server.
+//!
+//! An [`AdminHandle`] lists document sessions, disconnects clients, and
+//! closes documents while the server runs. When
+//! [`ServerConfig::admin_port`](super::ServerConfig::admin_port) is set, the
+//! server also answers HTTP requests on that port:
+//!
+//! - `GET /metrics`: metrics in the Prometheus text format
+//! - `GET /admin/sessions`: document sessions as JSON
+//! - `POST /admin/connections/{id}/kick`: disconnect a client
+//! - `POST /admin/documents/{id}/close`: close a document
+//!
+//! The `/admin` routes need an `Authorization: Bearer` header with the
+//! configured admin token, and are off without one.
+
+use super::connection::{ConnectionId, ConnectionManager, DocumentRateLimiter};
+use super::metrics::ServerMetrics;
+use super::DocumentSession;
+use crate::permissions::PermissionLevel;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, RwLock};
+
+/// Largest HTTP request head the admin endpoint reads.
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// How long a client has to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A client connected to a document.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionInfo {
+    /// Connection ID, as used to kick it.
+    pub connection_id: u64,
+    /// Authenticated user.
+    pub user_id: Option<String>,
+    /// User's display name.
+    pub display_name: Option<String>,
+    /// Access level in the document.
+    pub permission: PermissionLevel,
+}
+
+/// A document session and who is in it.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    /// Document ID.
+    pub doc_id: String,
+    /// Number of operations stored.
+    pub version: u64,
+    /// Operations since the latest snapshot.
+    pub pending_ops: usize,
+    /// Connected clients.
+    pub connections: Vec<ConnectionInfo>,
+}
+
+/// Handle for inspecting and managing a running server.
+#[derive(Clone)]
+pub struct AdminHandle {
+    pub(super) connections: Arc<RwLock<ConnectionManager>>,
+    pub(super) documents: Arc<RwLock<HashMap<String, DocumentSession>>>,
+    pub(super) document_limits: Arc<Mutex<DocumentRateLimiter>>,
+    pub(super) metrics: Arc<ServerMetrics>,
+}
+
+impl AdminHandle {
+    /// List document sessions, sorted by document ID.
+    pub async fn sessions(&self) -> Vec<SessionInfo> {
+        let docs = self.documents.read().await;
+        let conns = self.connections.read().await;
+
+        let mut sessions = Vec::with_capacity(docs.len());
+        for (doc_id, session) in docs.iter() {
+            let mut connections = Vec::new();
+            for conn in conns.document_connections(doc_id) {
+                let conn = conn.read().await;
+                connections.push(ConnectionInfo {
+                    connection_id: conn.id.0,
+                    user_id: conn.user.as_ref().map(|user| user.user_id.clone()),
+                    display_name: conn.user.as_ref().map(|user| user.display_name.clone()),
+                    permission: conn.permission,
+                });
+            }
+            sessions.push(SessionInfo {
+                doc_id: doc_id.clone(),
+                version: session.version.value(),
+                pending_ops: session.ops.len(),
+                connections,
+            });
+        }
+        sessions.sort_by(|a, b| a.doc_id.cmp(&b.doc_id));
+        sessions
+    }
+
+    /// Disconnect a client, telling it why.
+    ///
+    /// Returns false if there is no such connection.
+    pub async fn kick(&self, conn_id: ConnectionId, reason: &str) -> bool {
+        let Some(conn) = self.connections.read().await.get(conn_id) else {
+            return false;
+        };
+        conn.write().await.kick(reason);
+        self.metrics.connection_kicked();
+        tracing::info!("Connection {} kicked: {}", conn_id, reason);
+        true
+    }
+
+    /// Close a document: its clients are told why and removed from it, and
+    /// its session is dropped.
+    ///
+    /// Operations are already stored, so nothing is lost; a client that
+    /// joins again loads the document from storage. Returns the number of
+    /// clients that were in the document.
+    pub async fn close_document(&self, doc_id: &str, reason: &str) -> usize {
+        let conns = self.connections.read().await.document_connections(doc_id);
+        let mut conn_ids = Vec::with_capacity(conns.len());
+        for conn in &conns {
+            let mut conn = conn.write().await;
+            let _ = conn.send_error("document_closed", reason);
+            conn.leave_document();
+            conn_ids.push(conn.id);
+        }
+
+        let mut manager = self.connections.write().await;
+        for conn_id in conn_ids {
+            manager.leave_document(doc_id, conn_id);
+        }
+        drop(manager);
+
+        let closed = self.documents.write().await.remove(doc_id).is_some();
+        self.document_limits.lock().unwrap().remove(doc_id);
+        if closed || !conns.is_empty() {
+            self.metrics.document_closed();
+            tracing::info!("Document {} closed: {}", doc_id, reason);
+        }
+        conns.len()
+    }
+
+    /// Metrics in the Prometheus text format.
+    pub async fn metrics(&self) -> String {
+        let connections = self.connections.read().await.connection_count();
+        let documents = self.documents.read().await.len();
+        self.metrics.render(connections, documents)
+    }
+
+    /// Answer an HTTP request.
+    async fn respond(&self, request: &HttpRequest, admin_token: Option<&str>) -> HttpResponse {
+        if request.method == "GET" && request.path == "/metrics" {
+            return HttpResponse::new(200, "text/plain; version=0.0.4", self.metrics().await);
+        }
+        if !request.path.starts_with("/admin/") {
+            return HttpResponse::not_found();
+        }
+        let Some(admin_token) = admin_token else {
+            return HttpResponse::not_found();
+        };
+        if request.bearer_token.as_deref() != Some(admin_token) {
+            return HttpResponse::json(401, serde_json::json!({ "error": "unauthorized" }));
+        }
+
+        let path = request.path.as_str();
+        match request.method.as_str() {
+            "GET" if path == "/admin/sessions" => HttpResponse::json(200, self.sessions().await),
+            "POST" => {
+                if let Some(id) = path.strip_prefix("/admin/connections/").and_then(|p| p.strip_suffix("/kick")) {
+                    let Ok(id) = id.parse() else {
+                        return HttpResponse::not_found();
+                    };
+                    if !self.kick(ConnectionId(id), "Disconnected by an administrator").await {
+                        return HttpResponse::not_found();
+                    }
+                    HttpResponse::json(200, serde_json::json!({ "kicked": id }))
+                } else if let Some(doc_id) =
+                    path.strip_prefix("/admin/documents/").and_then(|p| p.strip_suffix("/close"))
+                {
+                    let connections = self.close_document(doc_id, "Closed by an administrator").await;
+                    HttpResponse::json(200, serde_json::json!({ "closed": doc_id, "connections": connections }))
+                } else {
+                    HttpResponse::not_found()
+                }
+            }
+            _ => HttpResponse::not_found(),
+        }
+    }
+}
+
+/// Answer metrics and admin requests until shutdown.
+pub(super) async fn serve(
+    listener: TcpListener,
+    admin: AdminHandle,
+    admin_token: Option<String>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) {
+    loop {
+        tokio::select! {
+            result = listener.accept() => {
+                match result {
+                    Ok((stream, _)) => {
+                        let (admin, admin_token) = (admin.clone(), admin_token.clone());
+                        tokio::spawn(async move {
+                            if let Err(e) = handle_request(stream, &admin, admin_token.as_deref()).await {
+                                tracing::debug!("Admin request failed: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to accept admin connection: {}", e);
+                    }
+                }
+            }
+            _ = shutdown_rx.recv() => {
+                break;
+            }
+        }
+    }
+}
+
+/// Read one request from a connection and answer it.
+async fn handle_request(mut stream: TcpStream, admin: &AdminHandle, admin_token: Option<&str>) -> std::io::Result<()> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    let read = tokio::time::timeout(REQUEST_TIMEOUT, async {
+        while !head.windows(4).any(|window| window == b"\r\n\r\n") && head.len() < MAX_REQUEST_BYTES {
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            head.extend_from_slice(&buf[..n]);
+        }
+        Ok::<_, std::io::Error>(())
+    })
+    .await;
+    if !matches!(read, Ok(Ok(()))) {
+        return Ok(());
+    }
+
+    let response = match HttpRequest::parse(&String::from_utf8_lossy(&head)) {
+        Some(request) => admin.respond(&request, admin_token).await,
+        None => HttpResponse::new(400, "text/plain", "Bad request\n".to_string()),
+    };
+    stream.write_all(&response.to_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// The parts of an HTTP request the admin endpoint uses.
+#[derive(Debug)]
+struct HttpRequest {
+    method: String,
+    path: String,
+    bearer_token: Option<String>,
+}
+
+impl HttpRequest {
+    /// Parse a request head; the query string is ignored.
+    fn parse(head: &str) -> Option<Self> {
+        let mut lines = head.split("\r\n");
+        let mut request_line = lines.next()?.split(' ');
+        let method = request_line.next()?.to_string();
+        let target = request_line.next()?;
+        if !request_line.next()?.starts_with("HTTP/1.") {
+            return None;
+        }
+        let path = target.split('?').next().unwrap_or_default().to_string();
+
+        let bearer_token = lines.take_while(|line| !line.is_empty()).find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            if !name.trim().eq_ignore_ascii_case("authorization") {
+                return None;
+            }
+            value.trim().strip_prefix("Bearer ").map(|token| token.trim().to_string())
+        });
+        Some(Self { method, path, bearer_token })
+    }
+}
+
+/// A response from the admin endpoint.
+struct HttpResponse {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl HttpResponse {
+    fn new(status: u16, content_type: &'static str, body: String) -> Self {
+        Self { status, content_type, body }
+    }
+
+    fn json(status: u16, body: impl Serialize) -> Self {
+        let body = serde_json::to_string(&body).unwrap_or_default();
+        Self::new(status, "application/json", body)
+    }
+
+    fn not_found() -> Self {
+        Self::json(404, serde_json::json!({ "error": "not found" }))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            _ => "Not Found",
+        };
+        format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            reason,
+            self.content_type,
+            self.body.len(),
+            self.body
+        )
+        .into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::connection::{AuthenticatedUser, ClientConnection};
+    use crate::server::message::ServerMessage;
+    use crate::server::{CollaborationServer, ServerConfig};
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_admin_sessions_kick_and_close() {
+        let server = CollaborationServer::new(ServerConfig {
+            admin_token: Some("secret".to_string()),
+            ..ServerConfig::default()
+        });
+        let admin = server.admin_handle();
+
+        let (msg_tx, mut msg_rx) = mpsc::channel(8);
+        let mut conn = ClientConnection::new(msg_tx);
+        conn.set_authenticated(AuthenticatedUser {
+            user_id: "alice".to_string(),
+            display_name: "Alice".to_string(),
+            color: "#E91E63".to_string(),
+        });
+        conn.join_document("doc".to_string(), PermissionLevel::Editor);
+        let conn_id = conn.id;
+        let kicked = Arc::clone(&conn.kicked);
+        let conn = admin.connections.write().await.add(conn);
+        admin.connections.write().await.join_document("doc", conn_id);
+        admin.documents.write().await.insert("doc".to_string(), DocumentSession::new(10));
+
+        let request = |text: &str| HttpRequest::parse(text).unwrap();
+        let sessions = admin.respond(&request("GET /admin/sessions HTTP/1.1\r\n\r\n"), Some("secret")).await;
+        assert_eq!(sessions.status, 401);
+        let authorized = "GET /admin/sessions HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n";
+        let sessions = admin.respond(&request(authorized), Some("secret")).await;
+        assert_eq!(sessions.status, 200);
+        assert!(sessions.body.contains(r#""docId":"doc""#) && sessions.body.contains(r#""userId":"alice""#));
+
+        // Closing the document tells its clients and drops the session
+        assert_eq!(admin.close_document("doc", "maintenance").await, 1);
+        assert!(matches!(msg_rx.recv().await, Some(ServerMessage::Error { code, .. }) if code == "document_closed"));
+        assert!(!conn.read().await.is_in_document());
+        assert!(admin.sessions().await.is_empty());
+
+        // Kicking wakes the connection's task
+        assert!(admin.kick(conn_id, "spam").await);
+        assert!(matches!(msg_rx.recv().await, Some(ServerMessage::Error { code, .. }) if code == "kicked"));
+        kicked.notified().await;
+        assert!(!admin.kick(ConnectionId(u64::MAX), "spam").await);
+
+        let metrics = admin.respond(&request("GET /metrics HTTP/1.1\r\n\r\n"), None).await;
+        assert!(metrics.body.contains("collab_kicked_total 1\n"));
+        assert!(metrics.body.contains("collab_documents_closed_total 1\n"));
+    }
+}
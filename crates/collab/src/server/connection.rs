@@ -4,9 +4,11 @@
 //! authentication, message handling, and connection lifecycle.
 
 use super::message::{ServerMessage, UserInfo, WireOpId, WirePresenceState};
+use crate::permissions::PermissionLevel;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Notify};
 
 /// Unique connection identifier.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -79,23 +81,35 @@ pub struct ClientConnection {
     /// Current presence state.
     pub presence: Option<WirePresenceState>,
     /// Channel to send messages to this client.
-    pub tx: mpsc::UnboundedSender<ServerMessage>,
+    pub tx: mpsc::Sender<ServerMessage>,
+    /// Signalled when the client falls too far behind and must be dropped.
+    pub overflow: Arc<Notify>,
+    /// Signalled when an admin closes the connection.
+    pub kicked: Arc<Notify>,
     /// Document ID this client is currently in (if any).
     pub current_doc: Option<String>,
+    /// Access level in the current document.
+    pub permission: PermissionLevel,
     /// Last acknowledged operation IDs.
     pub last_ack: Vec<WireOpId>,
 }
 
 impl ClientConnection {
     /// Create a new client connection.
-    pub fn new(tx: mpsc::UnboundedSender<ServerMessage>) -> Self {
+    ///
+    /// The channel is bounded: a client that doesn't read its messages
+    /// fills it, and is then disconnected rather than queueing without end.
+    pub fn new(tx: mpsc::Sender<ServerMessage>) -> Self {
         Self {
             id: ConnectionId::new(),
             state: ConnectionState::Connected,
             user: None,
             presence: None,
             tx,
+            overflow: Arc::new(Notify::new()),
+            kicked: Arc::new(Notify::new()),
             current_doc: None,
+            permission: PermissionLevel::None,
             last_ack: Vec::new(),
         }
     }
@@ -121,10 +135,17 @@ impl ClientConnection {
     }
 
     /// Send a message to this client.
+    ///
+    /// If the client's queue is full, its connection is closed; it resyncs
+    /// when it reconnects.
     pub fn send(&self, msg: ServerMessage) -> Result<(), SendError> {
-        self.tx
-            .send(msg)
-            .map_err(|_| SendError::ChannelClosed(self.id))
+        self.tx.try_send(msg).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => {
+                self.overflow.notify_one();
+                SendError::QueueFull(self.id)
+            }
+            mpsc::error::TrySendError::Closed(_) => SendError::ChannelClosed(self.id),
+        })
     }
 
     /// Send an error message to this client.
@@ -138,15 +159,17 @@ impl ClientConnection {
         self.state = ConnectionState::Authenticated;
     }
 
-    /// Join a document session.
-    pub fn join_document(&mut self, doc_id: String) {
+    /// Join a document session with an access level.
+    pub fn join_document(&mut self, doc_id: String, permission: PermissionLevel) {
         self.current_doc = Some(doc_id.clone());
+        self.permission = permission;
         self.state = ConnectionState::InDocument(doc_id);
     }
 
     /// Leave the current document session.
     pub fn leave_document(&mut self) {
         self.current_doc = None;
+        self.permission = PermissionLevel::None;
         if self.is_authenticated() {
             self.state = ConnectionState::Authenticated;
         } else {
@@ -155,6 +178,16 @@ impl ClientConnection {
         self.presence = None;
     }
 
+    /// Check if the connection may change the current document.
+    pub fn can_edit(&self) -> bool {
+        self.is_in_document() && self.permission.can_edit()
+    }
+
+    /// Check if the connection may comment on the current document.
+    pub fn can_comment(&self) -> bool {
+        self.is_in_document() && self.permission.can_comment()
+    }
+
     /// Update presence state.
     pub fn update_presence(&mut self, presence: WirePresenceState) {
         self.presence = Some(presence);
@@ -171,6 +204,13 @@ impl ClientConnection {
     pub fn close(&mut self) {
         self.state = ConnectionState::Closing;
     }
+
+    /// Tell the client why, then close its connection.
+    pub fn kick(&mut self, reason: &str) {
+        let _ = self.send_error("kicked", reason);
+        self.close();
+        self.kicked.notify_one();
+    }
 }
 
 /// Error when sending a message fails.
@@ -178,18 +218,123 @@ impl ClientConnection {
 pub enum SendError {
     /// The channel to the client is closed.
     ChannelClosed(ConnectionId),
+    /// The client isn't reading its messages fast enough.
+    QueueFull(ConnectionId),
 }
 
 impl std::fmt::Display for SendError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             SendError::ChannelClosed(id) => write!(f, "Channel closed for connection {}", id),
+            SendError::QueueFull(id) => write!(f, "Send queue full for connection {}", id),
         }
     }
 }
 
 impl std::error::Error for SendError {}
 
+/// Limits how many messages a connection may send.
+///
+/// A token bucket: it holds up to `burst` messages and refills at
+/// `messages_per_sec`, so short bursts of typing pass while floods don't.
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimit {
+    /// Sustained message rate.
+    pub messages_per_sec: f64,
+    /// Messages that may arrive at once.
+    pub burst: u32,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            messages_per_sec: 100.0,
+            burst: 200,
+        }
+    }
+}
+
+/// A connection's rate limiter state.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// Create a limiter with a full bucket.
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Take a token for a message, returning false if the limit is exceeded.
+    pub fn check(&mut self) -> bool {
+        self.check_at(Instant::now())
+    }
+
+    fn check_at(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.last_refill = now;
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.limit.messages_per_sec).min(self.limit.burst as f64);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Time until the next message is allowed.
+    pub fn retry_after(&self) -> Duration {
+        if self.tokens >= 1.0 || self.limit.messages_per_sec <= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64((1.0 - self.tokens) / self.limit.messages_per_sec)
+    }
+}
+
+/// Limits how many operation messages each document accepts, across all
+/// of its connections.
+#[derive(Debug)]
+pub struct DocumentRateLimiter {
+    limit: RateLimit,
+    limiters: std::collections::HashMap<String, RateLimiter>,
+}
+
+impl DocumentRateLimiter {
+    /// Create a limiter applying the same limit to every document.
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            limiters: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Take a token for a message to a document, returning the time until
+    /// the next one is allowed if the limit is exceeded.
+    pub fn check(&mut self, doc_id: &str) -> Result<(), Duration> {
+        let limiter = self
+            .limiters
+            .entry(doc_id.to_string())
+            .or_insert_with(|| RateLimiter::new(self.limit));
+        if limiter.check() {
+            Ok(())
+        } else {
+            Err(limiter.retry_after())
+        }
+    }
+
+    /// Forget a document's limiter.
+    pub fn remove(&mut self, doc_id: &str) {
+        self.limiters.remove(doc_id);
+    }
+}
+
 /// Authentication provider trait.
 ///
 /// Implement this trait to provide custom authentication logic.
@@ -397,7 +542,7 @@ mod tests {
 
     #[test]
     fn test_connection_state_transitions() {
-        let (tx, _rx) = mpsc::unbounded_channel();
+        let (tx, _rx) = mpsc::channel(8);
         let mut conn = ClientConnection::new(tx);
 
         assert_eq!(conn.state, ConnectionState::Connected);
@@ -412,15 +557,47 @@ mod tests {
         assert_eq!(conn.state, ConnectionState::Authenticated);
         assert!(conn.is_authenticated());
 
-        conn.join_document("doc-1".to_string());
+        conn.join_document("doc-1".to_string(), PermissionLevel::Editor);
         assert!(conn.is_in_document());
         assert_eq!(conn.doc_id(), Some("doc-1"));
+        assert!(conn.can_edit());
 
         conn.leave_document();
         assert!(!conn.is_in_document());
         assert_eq!(conn.state, ConnectionState::Authenticated);
     }
 
+    #[test]
+    fn test_send_queue_full() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let conn = ClientConnection::new(tx);
+
+        conn.send(ServerMessage::Pong).unwrap();
+        assert!(matches!(conn.send(ServerMessage::Pong), Err(SendError::QueueFull(_))));
+        assert!(rx.try_recv().is_ok());
+        conn.send(ServerMessage::Pong).unwrap();
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let mut limiter = RateLimiter::new(RateLimit { messages_per_sec: 10.0, burst: 3 });
+        let start = limiter.last_refill;
+
+        assert!((0..3).all(|_| limiter.check_at(start)));
+        assert!(!limiter.check_at(start));
+        assert!(limiter.retry_after() > Duration::ZERO);
+
+        // A tenth of a second refills one message
+        assert!(limiter.check_at(start + Duration::from_millis(100)));
+        assert!(!limiter.check_at(start + Duration::from_millis(100)));
+
+        // Each document has its own bucket
+        let mut documents = DocumentRateLimiter::new(RateLimit { messages_per_sec: 0.001, burst: 1 });
+        assert!(documents.check("a").is_ok());
+        assert!(documents.check("a").is_err());
+        assert!(documents.check("b").is_ok());
+    }
+
     #[test]
     fn test_color_assignment_deterministic() {
         let color1 = assign_color("user-123");
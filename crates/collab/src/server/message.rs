@@ -4,10 +4,13 @@
 //! the collaboration server and clients. Messages are designed to be
 //! compatible with the frontend TypeScript client.
 
-use crate::clock::VectorClock;
-use crate::op_id::OpId;
-use crate::operation::CrdtOp;
-use crate::presence::{Position, SelectionRange};
+use crate::clock::{Timestamp, VectorClock};
+use crate::crdt_tree::BlockData;
+use crate::op_id::{ClientId, OpId};
+use crate::operation::{Annotation, CrdtOp};
+use crate::permissions::PermissionLevel;
+use doc_model::NodeId;
+use crate::presence::{Position, SelectionRange, Viewport};
 use serde::{Deserialize, Serialize};
 
 /// Operation ID as used in the wire protocol.
@@ -55,6 +58,28 @@ impl From<VectorClock> for WireVectorClock {
 }
 
 impl WireVectorClock {
+    /// Get the latest sequence number seen from a client.
+    pub fn get(&self, client_id: &str) -> u64 {
+        self.clocks.get(client_id).copied().unwrap_or(0)
+    }
+
+    /// Check if an operation is covered by this clock.
+    pub fn contains(&self, op_id: &WireOpId) -> bool {
+        op_id.seq <= self.get(&op_id.client_id)
+    }
+
+    /// Advance the clock to include an operation.
+    pub fn observe(&mut self, op_id: &WireOpId) {
+        if !self.contains(op_id) {
+            self.clocks.insert(op_id.client_id.clone(), op_id.seq);
+        }
+    }
+
+    /// Check if this clock has seen everything `other` has.
+    pub fn dominates(&self, other: &WireVectorClock) -> bool {
+        other.clocks.iter().all(|(client_id, &seq)| self.get(client_id) >= seq)
+    }
+
     /// Convert to internal VectorClock representation.
     pub fn to_vector_clock(&self) -> VectorClock {
         let mut vc = VectorClock::new();
@@ -67,6 +92,21 @@ impl WireVectorClock {
     }
 }
 
+/// Compacted document state sent to clients that are behind it.
+///
+/// Holds every operation up to `clock`, with duplicates removed; clients
+/// apply it like any other batch of operations.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WireSnapshot {
+    /// Number of operations the server had stored when it was taken.
+    pub version: u64,
+    /// Operations the snapshot covers.
+    pub clock: WireVectorClock,
+    /// The operations, in the order the server received them.
+    pub ops: Vec<WireCrdtOp>,
+}
+
 /// Wire format for CRDT operations.
 /// Uses JSON value for flexibility.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -182,10 +222,113 @@ impl From<&CrdtOp> for WireCrdtOp {
                     }
                 }),
             },
+            CrdtOp::AnnotationSet {
+                target,
+                field,
+                value,
+                timestamp,
+                ..
+            } => Self {
+                id,
+                op_type: "annotation_set".to_string(),
+                payload: serde_json::json!({
+                    "target": target,
+                    "field": field,
+                    "value": value,
+                    "timestamp": {
+                        "physical": timestamp.physical,
+                        "logical": timestamp.logical,
+                        "clientId": timestamp.client_id.0.to_string()
+                    }
+                }),
+            },
         }
     }
 }
 
+impl WireCrdtOp {
+    /// Convert to internal CrdtOp representation.
+    ///
+    /// Returns None if the type is unknown or the payload is malformed.
+    pub fn to_crdt_op(&self) -> Option<CrdtOp> {
+        let id = self.id.to_op_id()?;
+        let payload = &self.payload;
+        let op_id = |key: &str| {
+            serde_json::from_value::<WireOpId>(payload.get(key)?.clone())
+                .ok()?
+                .to_op_id()
+        };
+        let optional_op_id = |key: &str| match payload.get(key) {
+            None | Some(serde_json::Value::Null) => Some(None),
+            Some(_) => op_id(key).map(Some),
+        };
+        let node_id = || NodeId::from_string(payload.get("nodeId")?.as_str()?);
+        let data = || serde_json::from_value::<BlockData>(payload.get("data")?.clone()).ok();
+        let timestamp = || {
+            let timestamp = payload.get("timestamp")?;
+            Some(Timestamp::new(
+                timestamp.get("physical")?.as_u64()?,
+                timestamp.get("logical")?.as_u64()?,
+                ClientId::new(timestamp.get("clientId")?.as_str()?.parse().ok()?),
+            ))
+        };
+
+        let op = match self.op_type.as_str() {
+            "text_insert" => CrdtOp::TextInsert {
+                id,
+                node_id: node_id()?,
+                parent_op_id: op_id("parentOpId")?,
+                char: payload.get("char")?.as_str()?.chars().next()?,
+            },
+            "text_delete" => CrdtOp::TextDelete {
+                id,
+                target_id: op_id("targetId")?,
+            },
+            "format_set" => CrdtOp::FormatSet {
+                id,
+                node_id: node_id()?,
+                start_op_id: op_id("startOpId")?,
+                end_op_id: op_id("endOpId")?,
+                attribute: payload.get("attribute")?.as_str()?.to_string(),
+                value: payload.get("value").cloned().unwrap_or(serde_json::Value::Null),
+                timestamp: timestamp()?,
+            },
+            "block_insert" => CrdtOp::BlockInsert {
+                id,
+                parent_op_id: op_id("parentOpId")?,
+                after_sibling: optional_op_id("afterSibling")?,
+                node_id: node_id()?,
+                data: data()?,
+            },
+            "block_delete" => CrdtOp::BlockDelete {
+                id,
+                target_id: op_id("targetId")?,
+            },
+            "block_move" => CrdtOp::BlockMove {
+                id,
+                target_id: op_id("targetId")?,
+                new_parent: op_id("newParent")?,
+                after_sibling: optional_op_id("afterSibling")?,
+            },
+            "block_update" => CrdtOp::BlockUpdate {
+                id,
+                target_id: op_id("targetId")?,
+                data: data()?,
+                timestamp: timestamp()?,
+            },
+            "annotation_set" => CrdtOp::AnnotationSet {
+                id,
+                target: serde_json::from_value::<Annotation>(payload.get("target")?.clone()).ok()?,
+                field: payload.get("field")?.as_str()?.to_string(),
+                value: payload.get("value").cloned().unwrap_or(serde_json::Value::Null),
+                timestamp: timestamp()?,
+            },
+            _ => return None,
+        };
+        Some(op)
+    }
+}
+
 /// Presence state as received from clients.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -196,6 +339,35 @@ pub struct WirePresenceState {
     pub selection: Option<WireRange>,
     pub is_typing: bool,
     pub last_active: u64,
+    /// Shared viewport, for followers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub viewport: Option<WireViewport>,
+    /// When the user started presenting, if they are
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presenting_since: Option<u64>,
+}
+
+/// Viewport shared for following.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WireViewport {
+    pub top: WirePosition,
+    pub scroll_position: f64,
+}
+
+impl From<&Viewport> for WireViewport {
+    fn from(viewport: &Viewport) -> Self {
+        Self {
+            top: WirePosition::from(&viewport.top),
+            scroll_position: viewport.scroll_position,
+        }
+    }
+}
+
+impl From<WireViewport> for Viewport {
+    fn from(wire: WireViewport) -> Self {
+        Viewport::new(wire.top.into(), wire.scroll_position)
+    }
 }
 
 /// Position in document.
@@ -261,10 +433,19 @@ pub enum ClientMessage {
     /// Authentication request.
     Auth { token: String },
 
-    /// Join a document session.
+    /// Join a document session, optionally through a share link.
     Join {
         #[serde(rename = "docId")]
         doc_id: String,
+        /// Share link token that grants access to the document.
+        #[serde(rename = "shareToken", default)]
+        share_token: Option<String>,
+        /// Password of the share link, if it has one.
+        #[serde(default)]
+        password: Option<String>,
+        /// Token from the previous join, to resume where the client left off.
+        #[serde(rename = "resumeToken", default)]
+        resume_token: Option<String>,
     },
 
     /// Leave a document session.
@@ -312,6 +493,14 @@ pub enum ServerMessage {
         #[serde(rename = "docId")]
         doc_id: String,
         users: Vec<UserInfo>,
+        /// The joining user's access level.
+        permission: PermissionLevel,
+        /// Token to resume this session after a reconnect.
+        #[serde(rename = "resumeToken")]
+        resume_token: String,
+        /// Whether the previous session was resumed; if so, a sync response
+        /// with what the client missed follows.
+        resumed: bool,
     },
 
     /// A user joined the document.
@@ -340,7 +529,12 @@ pub enum ServerMessage {
     },
 
     /// Response to sync request.
+    ///
+    /// Clients whose clock is behind the latest snapshot get the snapshot,
+    /// then the operations after it.
     SyncResponse {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        snapshot: Option<WireSnapshot>,
         ops: Vec<WireCrdtOp>,
         clock: WireVectorClock,
     },
@@ -365,6 +559,46 @@ impl ServerMessage {
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string(self)
     }
+
+    /// Serialize to MessagePack, with field names so the layout matches JSON.
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+        rmp_serde::to_vec_named(self)
+    }
+}
+
+impl ClientMessage {
+    /// Parse a JSON message.
+    pub fn from_json(text: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(text)
+    }
+
+    /// Parse a MessagePack message.
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Self, rmp_serde::decode::Error> {
+        rmp_serde::from_slice(bytes)
+    }
+}
+
+/// Encoding of messages on a connection.
+///
+/// Clients that send binary frames get MessagePack replies; text frames
+/// are JSON.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WireEncoding {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+/// Merge consecutive `Ops` messages into one, keeping everything else in order.
+pub fn coalesce_ops(messages: impl IntoIterator<Item = ServerMessage>) -> Vec<ServerMessage> {
+    let mut coalesced: Vec<ServerMessage> = Vec::new();
+    for message in messages {
+        match (coalesced.last_mut(), message) {
+            (Some(ServerMessage::Ops { ops }), ServerMessage::Ops { ops: more }) => ops.extend(more),
+            (_, message) => coalesced.push(message),
+        }
+    }
+    coalesced
 }
 
 #[cfg(test)]
@@ -384,6 +618,85 @@ mod tests {
         assert_eq!(back.seq, 5);
     }
 
+    #[test]
+    fn test_msgpack_encoding() {
+        let join = serde_json::json!({ "type": "join", "docId": "doc-1", "shareToken": "abc" });
+        let bytes = rmp_serde::to_vec_named(&join).unwrap();
+        match ClientMessage::from_msgpack(&bytes).unwrap() {
+            ClientMessage::Join { doc_id, share_token, password, .. } => {
+                assert_eq!(doc_id, "doc-1");
+                assert_eq!(share_token.as_deref(), Some("abc"));
+                assert!(password.is_none());
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+
+        let pong: serde_json::Value = rmp_serde::from_slice(&ServerMessage::Pong.to_msgpack().unwrap()).unwrap();
+        assert_eq!(pong, serde_json::json!({ "type": "pong" }));
+    }
+
+    #[test]
+    fn test_coalesce_ops() {
+        let op = |seq| WireCrdtOp {
+            id: WireOpId { client_id: "1".to_string(), seq },
+            op_type: "text_insert".to_string(),
+            payload: serde_json::Value::Null,
+        };
+        let messages = vec![
+            ServerMessage::Ops { ops: vec![op(1)] },
+            ServerMessage::Ops { ops: vec![op(2), op(3)] },
+            ServerMessage::Pong,
+            ServerMessage::Ops { ops: vec![op(4)] },
+        ];
+        let coalesced = coalesce_ops(messages);
+        assert_eq!(coalesced.len(), 3);
+        match &coalesced[0] {
+            ServerMessage::Ops { ops } => assert_eq!(ops.iter().map(|op| op.id.seq).collect::<Vec<_>>(), [1, 2, 3]),
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_wire_crdt_op_round_trip() {
+        let op = CrdtOp::FormatSet {
+            id: OpId::new(ClientId::new(3), 7),
+            node_id: NodeId::new(),
+            start_op_id: OpId::new(ClientId::new(3), 1),
+            end_op_id: OpId::new(ClientId::new(3), 5),
+            attribute: "bold".to_string(),
+            value: serde_json::json!(true),
+            timestamp: Timestamp::new(1000, 2, ClientId::new(3)),
+        };
+        let wire = WireCrdtOp::from(&op);
+        let converted = wire.to_crdt_op().unwrap();
+        assert_eq!(
+            serde_json::to_value(&converted).unwrap(),
+            serde_json::to_value(&op).unwrap()
+        );
+
+        let annotation = CrdtOp::AnnotationSet {
+            id: OpId::new(ClientId::new(3), 8),
+            target: Annotation::Reply {
+                comment_id: "c1".to_string(),
+                reply_id: "r1".to_string(),
+            },
+            field: "content".to_string(),
+            value: serde_json::json!("Agreed"),
+            timestamp: Timestamp::new(1001, 0, ClientId::new(3)),
+        };
+        let converted = WireCrdtOp::from(&annotation).to_crdt_op().unwrap();
+        assert_eq!(
+            serde_json::to_value(&converted).unwrap(),
+            serde_json::to_value(&annotation).unwrap()
+        );
+
+        let malformed = WireCrdtOp {
+            payload: serde_json::json!({}),
+            ..wire
+        };
+        assert!(malformed.to_crdt_op().is_none());
+    }
+
     #[test]
     fn test_client_message_deserialization() {
         let json = r#"{"type":"auth","token":"secret123"}"#;
@@ -414,7 +727,19 @@ mod tests {
         let msg: ClientMessage = serde_json::from_str(json).unwrap();
 
         match msg {
-            ClientMessage::Join { doc_id } => assert_eq!(doc_id, "doc-123"),
+            ClientMessage::Join { doc_id, share_token, .. } => {
+                assert_eq!(doc_id, "doc-123");
+                assert!(share_token.is_none());
+            }
+            _ => panic!("Wrong message type"),
+        }
+
+        let json = r#"{"type":"join","docId":"doc-123","shareToken":"abc","password":"pw"}"#;
+        match serde_json::from_str(json).unwrap() {
+            ClientMessage::Join { share_token, password, .. } => {
+                assert_eq!(share_token.as_deref(), Some("abc"));
+                assert_eq!(password.as_deref(), Some("pw"));
+            }
             _ => panic!("Wrong message type"),
         }
     }
@@ -429,6 +754,14 @@ mod tests {
             selection: None,
             is_typing: true,
             last_active: 1234567890,
+            viewport: Some(WireViewport {
+                top: WirePosition {
+                    node_id: "node-1".to_string(),
+                    offset: 0,
+                },
+                scroll_position: 480.0,
+            }),
+            presenting_since: None,
         };
 
         let msg = ServerMessage::Presence {
@@ -440,5 +773,7 @@ mod tests {
         assert!(json.contains("presence"));
         assert!(json.contains("isTyping"));
         assert!(json.contains("node-1"));
+        assert!(json.contains("scrollPosition"));
+        assert!(!json.contains("presentingSince"));
     }
 }
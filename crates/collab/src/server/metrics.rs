@@ -0,0 +1,239 @@
+//! Server metrics in the Prometheus text format.
+//!
+//! Counters are updated as the server runs; gauges such as the number of
+//! open connections are read when the metrics are rendered.
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Upper bounds of the broadcast latency buckets, in seconds.
+pub const BROADCAST_LATENCY_BUCKETS: [f64; 10] = [
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 1.0,
+];
+
+/// How far back the operation rate looks.
+const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Counters and latencies collected by the server.
+#[derive(Debug, Default)]
+pub struct ServerMetrics {
+    connections_total: AtomicU64,
+    messages_total: AtomicU64,
+    ops_total: AtomicU64,
+    connection_rate_limited_total: AtomicU64,
+    document_rate_limited_total: AtomicU64,
+    kicked_total: AtomicU64,
+    documents_closed_total: AtomicU64,
+    broadcast_latency: Mutex<Histogram>,
+    ops_rate: Mutex<RateMeter>,
+}
+
+impl ServerMetrics {
+    /// Create empty metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an accepted connection.
+    pub fn connection_opened(&self) {
+        self.connections_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a message from a client.
+    pub fn message_received(&self) {
+        self.messages_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a message dropped by a connection's rate limit.
+    pub fn connection_rate_limited(&self) {
+        self.connection_rate_limited_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record operations rejected by a document's rate limit.
+    pub fn document_rate_limited(&self) {
+        self.document_rate_limited_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a connection closed by an admin.
+    pub fn connection_kicked(&self) {
+        self.kicked_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a document closed by an admin.
+    pub fn document_closed(&self) {
+        self.documents_closed_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record new operations and how long they took to reach the other
+    /// clients after arriving.
+    pub fn ops_broadcast(&self, count: usize, latency: Duration) {
+        self.ops_total.fetch_add(count as u64, Ordering::Relaxed);
+        self.ops_rate.lock().unwrap().record(count as u64, Instant::now());
+        self.broadcast_latency.lock().unwrap().observe(latency.as_secs_f64());
+    }
+
+    /// Operations per second over the last few seconds.
+    pub fn ops_per_sec(&self) -> f64 {
+        self.ops_rate.lock().unwrap().rate(Instant::now())
+    }
+
+    /// Render the metrics, with the current connection and document counts.
+    pub fn render(&self, connections: usize, documents: usize) -> String {
+        let mut out = String::new();
+        gauge(&mut out, "collab_connections", "Open connections.", connections as f64);
+        gauge(&mut out, "collab_documents", "Documents with a session.", documents as f64);
+        counter(&mut out, "collab_connections_total", "Connections accepted.", &self.connections_total);
+        counter(&mut out, "collab_messages_total", "Messages received from clients.", &self.messages_total);
+        counter(&mut out, "collab_ops_total", "Operations broadcast.", &self.ops_total);
+        gauge(&mut out, "collab_ops_per_second", "Operations broadcast per second.", self.ops_per_sec());
+
+        let _ = writeln!(out, "# HELP collab_rate_limited_total Messages rejected by a rate limit.");
+        let _ = writeln!(out, "# TYPE collab_rate_limited_total counter");
+        for (scope, value) in [
+            ("connection", &self.connection_rate_limited_total),
+            ("document", &self.document_rate_limited_total),
+        ] {
+            let _ = writeln!(
+                out,
+                "collab_rate_limited_total{{scope=\"{}\"}} {}",
+                scope,
+                value.load(Ordering::Relaxed)
+            );
+        }
+
+        counter(&mut out, "collab_kicked_total", "Connections closed by an admin.", &self.kicked_total);
+        counter(
+            &mut out,
+            "collab_documents_closed_total",
+            "Documents closed by an admin.",
+            &self.documents_closed_total,
+        );
+
+        self.broadcast_latency.lock().unwrap().render(
+            &mut out,
+            "collab_broadcast_latency_seconds",
+            "Time from receiving operations to sending them to other clients.",
+        );
+        out
+    }
+}
+
+fn metric(out: &mut String, name: &str, kind: &str, help: &str, value: impl std::fmt::Display) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+fn counter(out: &mut String, name: &str, help: &str, value: &AtomicU64) {
+    metric(out, name, "counter", help, value.load(Ordering::Relaxed));
+}
+
+fn gauge(out: &mut String, name: &str, help: &str, value: f64) {
+    metric(out, name, "gauge", help, value);
+}
+
+/// A histogram with fixed buckets.
+#[derive(Debug)]
+struct Histogram {
+    /// Observations in each bucket; the last is above every bound.
+    counts: [u64; BROADCAST_LATENCY_BUCKETS.len() + 1],
+    sum: f64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            counts: [0; BROADCAST_LATENCY_BUCKETS.len() + 1],
+            sum: 0.0,
+        }
+    }
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        let bucket = BROADCAST_LATENCY_BUCKETS
+            .iter()
+            .position(|&bound| value <= bound)
+            .unwrap_or(BROADCAST_LATENCY_BUCKETS.len());
+        self.counts[bucket] += 1;
+        self.sum += value;
+    }
+
+    /// Write the cumulative buckets, sum, and count.
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        let mut cumulative = 0;
+        for (bound, count) in BROADCAST_LATENCY_BUCKETS.iter().zip(&self.counts) {
+            cumulative += count;
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, cumulative);
+        }
+        cumulative += self.counts[BROADCAST_LATENCY_BUCKETS.len()];
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, cumulative);
+        let _ = writeln!(out, "{}_sum {}", name, self.sum);
+        let _ = writeln!(out, "{}_count {}", name, cumulative);
+    }
+}
+
+/// Events per second over a sliding window.
+#[derive(Debug, Default)]
+struct RateMeter {
+    events: VecDeque<(Instant, u64)>,
+}
+
+impl RateMeter {
+    fn record(&mut self, count: u64, now: Instant) {
+        self.prune(now);
+        self.events.push_back((now, count));
+    }
+
+    fn rate(&mut self, now: Instant) -> f64 {
+        self.prune(now);
+        let total: u64 = self.events.iter().map(|(_, count)| count).sum();
+        total as f64 / RATE_WINDOW.as_secs_f64()
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while let Some(&(at, _)) = self.events.front() {
+            if now.saturating_duration_since(at) < RATE_WINDOW {
+                break;
+            }
+            self.events.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_metrics() {
+        let metrics = ServerMetrics::new();
+        metrics.connection_opened();
+        metrics.document_rate_limited();
+        metrics.ops_broadcast(20, Duration::from_micros(800));
+        metrics.ops_broadcast(5, Duration::from_secs(2));
+
+        let text = metrics.render(1, 2);
+        assert!(text.contains("collab_connections 1\n"));
+        assert!(text.contains("collab_documents 2\n"));
+        assert!(text.contains("collab_ops_total 25\n"));
+        assert!(text.contains("collab_ops_per_second 2.5\n"));
+        assert!(text.contains("collab_rate_limited_total{scope=\"document\"} 1\n"));
+        assert!(text.contains("collab_broadcast_latency_seconds_bucket{le=\"0.0005\"} 0\n"));
+        assert!(text.contains("collab_broadcast_latency_seconds_bucket{le=\"0.001\"} 1\n"));
+        assert!(text.contains("collab_broadcast_latency_seconds_bucket{le=\"1\"} 1\n"));
+        assert!(text.contains("collab_broadcast_latency_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("collab_broadcast_latency_seconds_count 2\n"));
+
+        // Old operations fall out of the rate
+        let mut meter = RateMeter::default();
+        let start = Instant::now();
+        meter.record(50, start);
+        assert_eq!(meter.rate(start + RATE_WINDOW), 0.0);
+    }
+}
@@ -28,34 +28,46 @@
 //! }
 //! ```
 
+pub mod admin;
 pub mod client;
 pub mod connection;
 pub mod file_store;
 pub mod memory_store;
 pub mod message;
+pub mod metrics;
 pub mod router;
 pub mod session;
+pub mod sqlite_store;
 pub mod storage;
 
 use connection::{
-    AcceptAllAuthProvider, AuthProvider, ClientConnection, ConnectionId, ConnectionManager,
+    AcceptAllAuthProvider, AuthProvider, ClientConnection, ConnectionId, ConnectionManager, DocumentRateLimiter,
+    RateLimiter,
 };
-use message::{ClientMessage, ServerMessage, WireCrdtOp, WireOpId, WireVectorClock};
+use message::{coalesce_ops, ClientMessage, ServerMessage, WireCrdtOp, WireOpId, WireVectorClock};
 
+use crate::permissions::{DocId, PermissionLevel, PermissionManager, UserId};
 use futures_util::{SinkExt, StreamExt};
+use std::collections::HashMap;
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio::sync::{broadcast, mpsc, watch, RwLock};
 use tokio_tungstenite::{accept_async, tungstenite::Message};
 
 // Re-export key types
-pub use connection::{AuthenticatedUser, ConnectionState, SendError, SimpleAuthProvider};
+pub use admin::{AdminHandle, ConnectionInfo, SessionInfo};
+pub use connection::{AuthenticatedUser, ConnectionState, RateLimit, SendError, SimpleAuthProvider};
 pub use file_store::FileOperationStore;
 pub use memory_store::MemoryOperationStore;
-pub use message::{WirePresenceState, WirePosition, WireRange};
+pub use metrics::ServerMetrics;
+pub use sqlite_store::SqliteOperationStore;
+pub use message::{WireEncoding, WirePresenceState, WirePosition, WireRange, WireSnapshot, WireViewport};
 pub use storage::{
-    OperationStore, Snapshot, StorageError, StorageResult, StorageStats, StoredOperation, Version,
+    OperationStore, RetentionPolicy, Snapshot, StorageError, StorageResult, StorageStats, StoredOperation,
+    Version,
 };
 
 // Re-export new session management types
@@ -86,6 +98,29 @@ pub struct ServerConfig {
     pub ping_interval_secs: u64,
     /// Connection timeout in seconds.
     pub connection_timeout_secs: u64,
+    /// Number of operations a document keeps after its snapshot before
+    /// the acknowledged ones are folded into a new snapshot.
+    pub snapshot_interval: usize,
+    /// Where documents are stored.
+    pub storage: StorageBackend,
+    /// How long operations for a client are held so that bursts of
+    /// keystrokes go out as one message; zero sends each immediately.
+    pub batch_window_ms: u64,
+    /// Messages queued for a client before it's disconnected as too slow.
+    pub max_queued_messages: usize,
+    /// Limit on the messages each client may send.
+    pub rate_limit: RateLimit,
+    /// Limit on the operation messages each document accepts from all of
+    /// its clients together.
+    pub document_rate_limit: RateLimit,
+    /// How long a disconnected client can resume its session, getting only
+    /// the operations it missed.
+    pub resume_ttl_secs: u64,
+    /// Port serving metrics and the admin API over HTTP; off when unset.
+    pub admin_port: Option<u16>,
+    /// Bearer token the admin API requires; without one only metrics are
+    /// served.
+    pub admin_token: Option<String>,
 }
 
 impl Default for ServerConfig {
@@ -97,6 +132,18 @@ impl Default for ServerConfig {
             max_total_connections: 1000,
             ping_interval_secs: 30,
             connection_timeout_secs: 60,
+            snapshot_interval: 1000,
+            storage: StorageBackend::default(),
+            batch_window_ms: 10,
+            max_queued_messages: 1024,
+            rate_limit: RateLimit::default(),
+            document_rate_limit: RateLimit {
+                messages_per_sec: 1000.0,
+                burst: 2000,
+            },
+            resume_ttl_secs: 300,
+            admin_port: None,
+            admin_token: None,
         }
     }
 }
@@ -116,6 +163,32 @@ impl ServerConfig {
     }
 }
 
+/// Where the server stores document operations and snapshots.
+///
+/// Documents in a file or SQLite store survive a server restart; they are
+/// loaded when the first client joins them.
+#[derive(Clone, Debug, Default)]
+pub enum StorageBackend {
+    /// In memory; documents are lost when the server stops.
+    #[default]
+    Memory,
+    /// A directory of files (see [`FileOperationStore`]).
+    File(PathBuf),
+    /// A SQLite database file (see [`SqliteOperationStore`]).
+    Sqlite(PathBuf),
+}
+
+impl StorageBackend {
+    /// Open the store.
+    pub fn open(&self) -> StorageResult<Arc<dyn OperationStore>> {
+        Ok(match self {
+            StorageBackend::Memory => Arc::new(MemoryOperationStore::new()),
+            StorageBackend::File(path) => Arc::new(FileOperationStore::new(path)?),
+            StorageBackend::Sqlite(path) => Arc::new(SqliteOperationStore::open(path)?),
+        })
+    }
+}
+
 /// Commands sent to the server from connection handlers.
 #[derive(Debug)]
 enum ServerCommand {
@@ -128,6 +201,8 @@ enum ServerCommand {
     JoinDocument {
         conn_id: ConnectionId,
         doc_id: String,
+        user_id: String,
+        resume_token: Option<String>,
     },
     /// Client left a document.
     LeaveDocument {
@@ -139,6 +214,7 @@ enum ServerCommand {
         conn_id: ConnectionId,
         doc_id: String,
         ops: Vec<WireCrdtOp>,
+        received_at: Instant,
     },
     /// Broadcast presence update.
     BroadcastPresence {
@@ -147,6 +223,12 @@ enum ServerCommand {
         user_id: String,
         presence: WirePresenceState,
     },
+    /// Client acknowledged operations.
+    Acknowledge {
+        conn_id: ConnectionId,
+        doc_id: String,
+        op_ids: Vec<WireOpId>,
+    },
     /// Client disconnected.
     Disconnected { conn_id: ConnectionId },
     /// Sync request from client.
@@ -157,43 +239,280 @@ enum ServerCommand {
     },
 }
 
+/// A disconnected client's place in a document, kept so it can resume.
+struct Resumable {
+    user_id: String,
+    /// Operations the client had acknowledged.
+    clock: WireVectorClock,
+    expires_at: Instant,
+}
+
 /// Document session state.
-#[derive(Default)]
+///
+/// Operations are kept as a tail after the latest snapshot. Once the tail
+/// reaches the snapshot interval, the operations every connection in the
+/// document has acknowledged are folded into a new snapshot, so clients
+/// that join late download one snapshot and a short tail instead of the
+/// whole history.
 struct DocumentSession {
-    /// Operations stored for sync.
+    /// Operations received since the latest snapshot.
     ops: Vec<WireCrdtOp>,
     /// Current vector clock.
     clock: WireVectorClock,
+    /// Latest snapshot; its data is the JSON-encoded operations it covers.
+    snapshot: Option<Snapshot>,
+    /// Operations the latest snapshot covers.
+    snapshot_clock: WireVectorClock,
+    /// Operations each connection in the document has acknowledged.
+    acknowledged: HashMap<ConnectionId, WireVectorClock>,
+    /// Number of operations stored.
+    version: Version,
+    /// Tail length at which operations are compacted.
+    snapshot_interval: usize,
+    /// Whether the latest snapshot hasn't been stored yet.
+    snapshot_unsaved: bool,
+    /// Resume token and user of each connection.
+    resume_tokens: HashMap<ConnectionId, (String, String)>,
+    /// Disconnected clients that can resume, by resume token.
+    resumable: HashMap<String, Resumable>,
+    /// How long a disconnected client can resume.
+    resume_ttl: Duration,
 }
 
 impl DocumentSession {
-    fn new() -> Self {
-        Self::default()
+    fn new(snapshot_interval: usize) -> Self {
+        Self {
+            ops: Vec::new(),
+            clock: WireVectorClock::default(),
+            snapshot: None,
+            snapshot_clock: WireVectorClock::default(),
+            acknowledged: HashMap::new(),
+            version: Version::initial(),
+            snapshot_interval,
+            snapshot_unsaved: false,
+            resume_tokens: HashMap::new(),
+            resumable: HashMap::new(),
+            resume_ttl: Duration::from_secs(ServerConfig::default().resume_ttl_secs),
+        }
     }
 
-    /// Add operations to the session.
-    fn add_ops(&mut self, ops: Vec<WireCrdtOp>) {
-        // Update clock from operations
-        for op in &ops {
-            let current = self.clock.clocks.get(&op.id.client_id).copied().unwrap_or(0);
-            if op.id.seq > current {
-                self.clock.clocks.insert(op.id.client_id.clone(), op.id.seq);
+    /// Set how long a disconnected client can resume.
+    fn with_resume_ttl(mut self, resume_ttl: Duration) -> Self {
+        self.resume_ttl = resume_ttl;
+        self
+    }
+
+    /// Load a session from its stored snapshot and operations.
+    fn restore(store: &dyn OperationStore, doc_id: &str, snapshot_interval: usize) -> StorageResult<Self> {
+        let doc_id = DocId::from(doc_id);
+        let mut session = Self::new(snapshot_interval);
+        if let Some(snapshot) = store.get_latest_snapshot(&doc_id)? {
+            let ops: Vec<WireCrdtOp> = serde_json::from_slice(&snapshot.data)?;
+            for op in &ops {
+                session.snapshot_clock.observe(&op.id);
             }
+            session.clock = session.snapshot_clock.clone();
+            session.version = snapshot.version.clone();
+            session.snapshot = Some(snapshot);
         }
-        self.ops.extend(ops);
+
+        // Operations already in the snapshot are dropped
+        let ops = store
+            .get_all_operations(&doc_id)?
+            .iter()
+            .map(|stored| WireCrdtOp::from(&stored.operation))
+            .collect();
+        session.insert(ops);
+        Ok(session)
+    }
+
+    /// Add operations from a connection to the session.
+    ///
+    /// Operations the session has already seen (resent after a reconnect)
+    /// are dropped. Returns the new operations.
+    fn add_ops(&mut self, conn_id: ConnectionId, ops: Vec<WireCrdtOp>) -> Vec<WireCrdtOp> {
+        let added = self.insert(ops);
+
+        // The sender has its own operations
+        if let Some(acknowledged) = self.acknowledged.get_mut(&conn_id) {
+            for op in &added {
+                acknowledged.observe(&op.id);
+            }
+        }
+        self.compact();
+        added
+    }
+
+    /// Append the operations the session hasn't seen; returns them.
+    fn insert(&mut self, ops: Vec<WireCrdtOp>) -> Vec<WireCrdtOp> {
+        let mut added = Vec::with_capacity(ops.len());
+        for op in ops {
+            if self.clock.contains(&op.id) {
+                continue;
+            }
+            self.clock.observe(&op.id);
+            self.version.increment();
+            added.push(op);
+        }
+        self.ops.extend(added.iter().cloned());
+        added
     }
 
     /// Get operations since a vector clock.
     fn ops_since(&self, since: &WireVectorClock) -> Vec<WireCrdtOp> {
         self.ops
             .iter()
-            .filter(|op| {
-                let since_seq = since.clocks.get(&op.id.client_id).copied().unwrap_or(0);
-                op.id.seq > since_seq
-            })
+            .filter(|op| !since.contains(&op.id))
             .cloned()
             .collect()
     }
+
+    /// Get what a client at `since` is missing: the latest snapshot if
+    /// the client hasn't seen all of it, then the operations after it.
+    fn sync(&self, since: &WireVectorClock) -> (Option<WireSnapshot>, Vec<WireCrdtOp>) {
+        let snapshot = self
+            .snapshot
+            .as_ref()
+            .filter(|_| !since.dominates(&self.snapshot_clock))
+            .map(|snapshot| WireSnapshot {
+                version: snapshot.version.value(),
+                clock: self.snapshot_clock.clone(),
+                ops: self.snapshot_ops(),
+            });
+        (snapshot, self.ops_since(since))
+    }
+
+    /// Track a connection that joined the document.
+    fn join(&mut self, conn_id: ConnectionId) {
+        self.acknowledged.entry(conn_id).or_default();
+    }
+
+    /// Stop tracking a connection; it no longer holds back compaction.
+    ///
+    /// What it had acknowledged is kept under its resume token.
+    fn leave(&mut self, conn_id: ConnectionId) {
+        let Some(clock) = self.acknowledged.remove(&conn_id) else {
+            return;
+        };
+        if let Some((token, user_id)) = self.resume_tokens.remove(&conn_id) {
+            let expires_at = Instant::now() + self.resume_ttl;
+            self.resumable.insert(token, Resumable { user_id, clock, expires_at });
+        }
+        self.compact();
+    }
+
+    /// Issue a new resume token to a connection.
+    fn issue_resume_token(&mut self, conn_id: ConnectionId, user_id: &str) -> String {
+        let token = uuid::Uuid::new_v4().to_string();
+        self.resume_tokens.insert(conn_id, (token.clone(), user_id.to_string()));
+        token
+    }
+
+    /// Pick up a disconnected client's session on a new connection.
+    ///
+    /// Returns what the client had acknowledged, or None if the token is
+    /// unknown, expired, or another user's. Tokens are single-use.
+    fn resume(&mut self, conn_id: ConnectionId, token: &str, user_id: &str, now: Instant) -> Option<WireVectorClock> {
+        self.resumable.retain(|_, resumable| resumable.expires_at > now);
+        let resumable = self.resumable.remove(token).filter(|resumable| resumable.user_id == user_id)?;
+        self.acknowledged.insert(conn_id, resumable.clock.clone());
+        Some(resumable.clock)
+    }
+
+    /// Record operations a connection has acknowledged.
+    fn acknowledge<'a>(&mut self, conn_id: ConnectionId, op_ids: impl IntoIterator<Item = &'a WireOpId>) {
+        let Some(acknowledged) = self.acknowledged.get_mut(&conn_id) else {
+            return;
+        };
+        for op_id in op_ids {
+            acknowledged.observe(op_id);
+        }
+        self.compact();
+    }
+
+    /// Record that a connection has everything the session has.
+    fn acknowledge_all(&mut self, conn_id: ConnectionId) {
+        if let Some(acknowledged) = self.acknowledged.get_mut(&conn_id) {
+            *acknowledged = self.clock.clone();
+        }
+    }
+
+    /// The operations every connection has acknowledged; everything when
+    /// no one is connected.
+    fn acknowledged_floor(&self) -> WireVectorClock {
+        let clocks = self
+            .clock
+            .clocks
+            .iter()
+            .map(|(client_id, &seq)| {
+                let floor = self.acknowledged.values().map(|ack| ack.get(client_id)).min();
+                (client_id.clone(), floor.unwrap_or(seq))
+            })
+            .collect();
+        WireVectorClock { clocks }
+    }
+
+    /// Operations in the latest snapshot.
+    fn snapshot_ops(&self) -> Vec<WireCrdtOp> {
+        self.snapshot
+            .as_ref()
+            .and_then(|snapshot| serde_json::from_slice(&snapshot.data).ok())
+            .unwrap_or_default()
+    }
+
+    /// Fold acknowledged operations into a new snapshot once the tail is
+    /// long enough.
+    fn compact(&mut self) {
+        if self.ops.len() < self.snapshot_interval.max(1) {
+            return;
+        }
+        let floor = self.acknowledged_floor();
+        if !self.ops.iter().any(|op| floor.contains(&op.id)) {
+            return;
+        }
+
+        let (folded, tail): (Vec<_>, Vec<_>) = self.ops.iter().cloned().partition(|op| floor.contains(&op.id));
+        let mut snapshot_clock = self.snapshot_clock.clone();
+        for op in &folded {
+            snapshot_clock.observe(&op.id);
+        }
+        let mut ops = self.snapshot_ops();
+        ops.extend(folded);
+        let data = match serde_json::to_vec(&ops) {
+            Ok(data) => data,
+            Err(e) => {
+                tracing::error!("Failed to serialize snapshot: {}", e);
+                return;
+            }
+        };
+
+        self.ops = tail;
+        self.snapshot = Some(Snapshot::with_description(
+            self.version.clone(),
+            snapshot_clock.to_vector_clock(),
+            data,
+            format!("{} operations", ops.len()),
+        ));
+        self.snapshot_clock = snapshot_clock;
+        self.snapshot_unsaved = true;
+    }
+
+    /// Take the latest snapshot if it hasn't been stored yet.
+    fn take_unsaved_snapshot(&mut self) -> Option<Snapshot> {
+        if !std::mem::take(&mut self.snapshot_unsaved) {
+            return None;
+        }
+        self.snapshot.clone()
+    }
+}
+
+/// Store a session's latest snapshot if it is new.
+fn save_snapshot(store: &dyn OperationStore, doc_id: &str, session: &mut DocumentSession) {
+    if let Some(snapshot) = session.take_unsaved_snapshot() {
+        if let Err(e) = store.save_snapshot(&DocId::from(doc_id), snapshot) {
+            tracing::error!("Failed to store snapshot of {}: {}", doc_id, e);
+        }
+    }
 }
 
 /// The main collaboration server.
@@ -205,7 +524,14 @@ pub struct CollaborationServer<A: AuthProvider = AcceptAllAuthProvider> {
     /// Connection manager.
     connections: Arc<RwLock<ConnectionManager>>,
     /// Document sessions.
-    documents: Arc<RwLock<std::collections::HashMap<String, DocumentSession>>>,
+    documents: Arc<RwLock<HashMap<String, DocumentSession>>>,
+    /// Document permissions; without them every authenticated user may
+    /// edit every document.
+    permissions: Option<Arc<RwLock<PermissionManager>>>,
+    /// Rate limits of each document.
+    document_limits: Arc<Mutex<DocumentRateLimiter>>,
+    /// Counters and latencies.
+    metrics: Arc<ServerMetrics>,
     /// Shutdown signal sender.
     shutdown_tx: broadcast::Sender<()>,
 }
@@ -221,15 +547,32 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
     /// Create a new server with custom authentication.
     pub fn with_auth(config: ServerConfig, auth_provider: A) -> Self {
         let (shutdown_tx, _) = broadcast::channel(1);
+        let document_limits = DocumentRateLimiter::new(config.document_rate_limit);
         Self {
             config,
             auth_provider: Arc::new(auth_provider),
             connections: Arc::new(RwLock::new(ConnectionManager::new())),
-            documents: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            documents: Arc::new(RwLock::new(HashMap::new())),
+            permissions: None,
+            document_limits: Arc::new(Mutex::new(document_limits)),
+            metrics: Arc::new(ServerMetrics::new()),
             shutdown_tx,
         }
     }
 
+    /// Check document permissions when clients join, and reject edits
+    /// from clients that may only view or comment.
+    pub fn with_permissions(mut self, permissions: PermissionManager) -> Self {
+        self.permissions = Some(Arc::new(RwLock::new(permissions)));
+        self
+    }
+
+    /// Get the permission manager, to grant or revoke access while the
+    /// server runs.
+    pub fn permissions(&self) -> Option<Arc<RwLock<PermissionManager>>> {
+        self.permissions.clone()
+    }
+
     /// Get a shutdown handle.
     pub fn shutdown_handle(&self) -> ShutdownHandle {
         ShutdownHandle {
@@ -237,6 +580,17 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
         }
     }
 
+    /// Get a handle for listing sessions, kicking clients, closing
+    /// documents, and reading metrics.
+    pub fn admin_handle(&self) -> AdminHandle {
+        AdminHandle {
+            connections: Arc::clone(&self.connections),
+            documents: Arc::clone(&self.documents),
+            document_limits: Arc::clone(&self.document_limits),
+            metrics: Arc::clone(&self.metrics),
+        }
+    }
+
     /// Run the server.
     ///
     /// This will bind to the configured address and start accepting
@@ -249,12 +603,32 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
 
         tracing::info!("Collaboration server listening on {}", addr);
 
+        let store = self.config.storage.open().map_err(ServerError::Storage)?;
+
+        // Metrics and the admin API are served on their own port
+        if let Some(port) = self.config.admin_port {
+            let admin_addr = format!("{}:{}", self.config.bind_address, port);
+            let admin_listener = TcpListener::bind(&admin_addr)
+                .await
+                .map_err(|e| ServerError::BindFailed(admin_addr.clone(), e))?;
+            tracing::info!("Admin endpoint listening on {}", admin_addr);
+            tokio::spawn(admin::serve(
+                admin_listener,
+                self.admin_handle(),
+                self.config.admin_token.clone(),
+                self.shutdown_tx.subscribe(),
+            ));
+        }
+
         // Create command channel
         let (cmd_tx, mut cmd_rx) = mpsc::unbounded_channel::<ServerCommand>();
 
         // Clone things for the command handler task
         let connections = Arc::clone(&self.connections);
         let documents = Arc::clone(&self.documents);
+        let snapshot_interval = self.config.snapshot_interval;
+        let resume_ttl = Duration::from_secs(self.config.resume_ttl_secs);
+        let metrics = Arc::clone(&self.metrics);
         let mut shutdown_rx = self.shutdown_tx.subscribe();
 
         // Spawn command handler task
@@ -262,7 +636,16 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
             loop {
                 tokio::select! {
                     Some(cmd) = cmd_rx.recv() => {
-                        Self::handle_command(&connections, &documents, cmd).await;
+                        Self::handle_command(
+                            &connections,
+                            &documents,
+                            store.as_ref(),
+                            snapshot_interval,
+                            resume_ttl,
+                            &metrics,
+                            cmd,
+                        )
+                        .await;
                     }
                     _ = shutdown_rx.recv() => {
                         tracing::info!("Command handler received shutdown signal");
@@ -328,58 +711,92 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
         let (mut ws_tx, mut ws_rx) = ws_stream.split();
 
         // Create message channel for this connection
-        let (msg_tx, mut msg_rx) = mpsc::unbounded_channel::<ServerMessage>();
+        let (msg_tx, mut msg_rx) = mpsc::channel::<ServerMessage>(self.config.max_queued_messages.max(1));
 
         // Create connection
         let conn = ClientConnection::new(msg_tx);
         let conn_id = conn.id;
+        let overflow = Arc::clone(&conn.overflow);
+        let kicked = Arc::clone(&conn.kicked);
 
         // Add to manager
         let conn = self.connections.write().await.add(conn);
+        self.metrics.connection_opened();
 
         let auth_provider = Arc::clone(&self.auth_provider);
+        let permissions = self.permissions.clone();
         let connections = Arc::clone(&self.connections);
-        let _config = self.config.clone(); // Reserved for future use (timeouts, etc.)
+        let batch_window = Duration::from_millis(self.config.batch_window_ms);
+        let mut rate_limiter = RateLimiter::new(self.config.rate_limit);
+        let document_limits = Arc::clone(&self.document_limits);
+        let metrics = Arc::clone(&self.metrics);
         let mut shutdown_rx = self.shutdown_tx.subscribe();
 
+        // Replies use the encoding of the client's latest message
+        let (encoding_tx, encoding_rx) = watch::channel(WireEncoding::Json);
+
         // Spawn connection handler task
         tokio::spawn(async move {
             // Outgoing message forwarder
             let outgoing = tokio::spawn(async move {
                 while let Some(msg) = msg_rx.recv().await {
-                    match msg.to_json() {
-                        Ok(json) => {
-                            if ws_tx.send(Message::Text(json.into())).await.is_err() {
+                    let mut pending = vec![msg];
+
+                    // Hold operations briefly so a burst goes out as one message
+                    if !batch_window.is_zero() && matches!(pending[0], ServerMessage::Ops { .. }) {
+                        let deadline = tokio::time::Instant::now() + batch_window;
+                        while let Ok(Some(next)) = tokio::time::timeout_at(deadline, msg_rx.recv()).await {
+                            let is_ops = matches!(next, ServerMessage::Ops { .. });
+                            pending.push(next);
+                            if !is_ops {
                                 break;
                             }
                         }
-                        Err(e) => {
-                            tracing::error!("Failed to serialize message: {}", e);
+                    }
+
+                    for msg in coalesce_ops(pending) {
+                        let encoding = *encoding_rx.borrow();
+                        let frame = match encoding {
+                            WireEncoding::Json => msg.to_json().map(Message::Text).map_err(|e| e.to_string()),
+                            WireEncoding::MessagePack => {
+                                msg.to_msgpack().map(Message::Binary).map_err(|e| e.to_string())
+                            }
+                        };
+                        match frame {
+                            Ok(frame) => {
+                                if ws_tx.send(frame).await.is_err() {
+                                    return;
+                                }
+                            }
+                            Err(e) => {
+                                tracing::error!("Failed to serialize message: {}", e);
+                            }
                         }
                     }
                 }
             });
 
             // Incoming message handler
+            let mut rate_limited = false;
             loop {
                 tokio::select! {
                     msg = ws_rx.next() => {
-                        match msg {
+                        let parsed = match msg {
                             Some(Ok(Message::Text(text))) => {
-                                if let Err(e) = Self::handle_message(
-                                    &conn,
-                                    &text,
-                                    &auth_provider,
-                                    &cmd_tx,
-                                ).await {
-                                    tracing::error!("Message handling error: {}", e);
-                                    // Send error to client
-                                    let conn_guard = conn.read().await;
-                                    let _ = conn_guard.send_error("message_error", e.to_string());
-                                }
+                                encoding_tx.send_if_modified(|encoding| {
+                                    std::mem::replace(encoding, WireEncoding::Json) != WireEncoding::Json
+                                });
+                                ClientMessage::from_json(&text).map_err(|e| e.to_string())
+                            }
+                            Some(Ok(Message::Binary(bytes))) => {
+                                encoding_tx.send_if_modified(|encoding| {
+                                    std::mem::replace(encoding, WireEncoding::MessagePack) != WireEncoding::MessagePack
+                                });
+                                ClientMessage::from_msgpack(&bytes).map_err(|e| e.to_string())
                             }
                             Some(Ok(Message::Ping(_data))) => {
                                 // Respond with pong (handled by tungstenite automatically in most cases)
+                                continue;
                             }
                             Some(Ok(Message::Close(_))) | None => {
                                 tracing::debug!("Connection {} closed", conn_id);
@@ -389,9 +806,53 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
                                 tracing::error!("WebSocket error for {}: {}", conn_id, e);
                                 break;
                             }
-                            _ => {}
+                            _ => continue,
+                        };
+
+                        metrics.message_received();
+
+                        // Drop messages over the limit, telling the client once per flood
+                        if !rate_limiter.check() {
+                            metrics.connection_rate_limited();
+                            if !rate_limited {
+                                rate_limited = true;
+                                tracing::warn!("Connection {} exceeded its rate limit", conn_id);
+                                let retry_ms = rate_limiter.retry_after().as_millis();
+                                let _ = conn.read().await.send_error(
+                                    "rate_limited",
+                                    format!("Too many messages; retry in {} ms", retry_ms),
+                                );
+                            }
+                            continue;
+                        }
+                        rate_limited = false;
+
+                        let result = match parsed {
+                            Ok(msg) => Self::handle_message(
+                                &conn,
+                                msg,
+                                &auth_provider,
+                                permissions.as_deref(),
+                                &document_limits,
+                                &metrics,
+                                &cmd_tx,
+                            ).await,
+                            Err(e) => Err(MessageError::ParseError(e)),
+                        };
+                        if let Err(e) = result {
+                            tracing::error!("Message handling error: {}", e);
+                            // Send error to client
+                            let conn_guard = conn.read().await;
+                            let _ = conn_guard.send_error("message_error", e.to_string());
                         }
                     }
+                    _ = overflow.notified() => {
+                        tracing::warn!("Connection {} fell too far behind; disconnecting", conn_id);
+                        break;
+                    }
+                    _ = kicked.notified() => {
+                        break;
+                    }
                     _ = shutdown_rx.recv() => {
                         tracing::debug!("Connection {} received shutdown signal", conn_id);
                         break;
@@ -399,27 +860,32 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
                 }
             }
 
-            // Cleanup
-            outgoing.abort();
-
             // Notify server of disconnect
             let _ = cmd_tx.send(ServerCommand::Disconnected { conn_id });
 
             // Remove from manager
             connections.write().await.remove(conn_id).await;
+
+            // With the connection gone its queue closes; messages already in
+            // it, such as why the client was kicked, are sent first
+            drop(conn);
+            let mut outgoing = outgoing;
+            if tokio::time::timeout(Duration::from_secs(1), &mut outgoing).await.is_err() {
+                outgoing.abort();
+            }
         });
     }
 
     /// Handle an incoming message from a client.
     async fn handle_message(
         conn: &Arc<RwLock<ClientConnection>>,
-        text: &str,
+        msg: ClientMessage,
         auth_provider: &Arc<A>,
+        permissions: Option<&RwLock<PermissionManager>>,
+        document_limits: &Mutex<DocumentRateLimiter>,
+        metrics: &ServerMetrics,
         cmd_tx: &mpsc::UnboundedSender<ServerCommand>,
     ) -> Result<(), MessageError> {
-        let msg: ClientMessage =
-            serde_json::from_str(text).map_err(|e| MessageError::ParseError(e.to_string()))?;
-
         let mut conn_guard = conn.write().await;
         let conn_id = conn_guard.id;
 
@@ -449,12 +915,37 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
                 }
             }
 
-            ClientMessage::Join { doc_id } => {
+            ClientMessage::Join {
+                doc_id,
+                share_token,
+                password,
+                resume_token,
+            } => {
                 if !conn_guard.is_authenticated() {
                     conn_guard.send_error("not_authenticated", "Must authenticate first")?;
                     return Ok(());
                 }
 
+                let permission = match permissions {
+                    Some(permissions) => {
+                        let user_id = UserId::from(conn_guard.user_id().unwrap_or_default());
+                        let share_link = share_token.as_deref().map(|token| (token, password.as_deref()));
+                        let level = permissions.read().await.access_level(
+                            &user_id,
+                            &DocId::from(doc_id.as_str()),
+                            share_link,
+                        );
+                        match level {
+                            Ok(level) => level,
+                            Err(e) => {
+                                conn_guard.send_error("permission_denied", e.to_string())?;
+                                return Ok(());
+                            }
+                        }
+                    }
+                    None => PermissionLevel::Editor,
+                };
+
                 // Leave current document if any
                 if let Some(old_doc) = conn_guard.doc_id() {
                     let _ = cmd_tx.send(ServerCommand::LeaveDocument {
@@ -463,11 +954,13 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
                     });
                 }
 
-                conn_guard.join_document(doc_id.clone());
+                conn_guard.join_document(doc_id.clone(), permission);
 
                 let _ = cmd_tx.send(ServerCommand::JoinDocument {
                     conn_id,
                     doc_id,
+                    user_id: conn_guard.user_id().unwrap_or_default().to_string(),
+                    resume_token,
                 });
             }
 
@@ -479,9 +972,28 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
             }
 
             ClientMessage::Ops { ops } => {
-                if let Some(doc_id) = conn_guard.doc_id() {
+                let comments_only = ops
+                    .iter()
+                    .all(|op| op.to_crdt_op().is_some_and(|op| op.is_comment()));
+                if conn_guard.is_in_document()
+                    && !conn_guard.can_edit()
+                    && !(comments_only && conn_guard.can_comment())
+                {
+                    conn_guard.send_error("permission_denied", "Read-only access to this document")?;
+                } else if let Some(doc_id) = conn_guard.doc_id() {
                     let doc_id = doc_id.to_string();
 
+                    // Unacknowledged operations are resent by the client later
+                    let limited = document_limits.lock().unwrap().check(&doc_id);
+                    if let Err(retry_after) = limited {
+                        metrics.document_rate_limited();
+                        conn_guard.send_error(
+                            "rate_limited",
+                            format!("Too many changes to this document; retry in {} ms", retry_after.as_millis()),
+                        )?;
+                        return Ok(());
+                    }
+
                     // Acknowledge operations
                     let op_ids: Vec<WireOpId> = ops.iter().map(|op| op.id.clone()).collect();
                     conn_guard.send(ServerMessage::Ack { op_ids })?;
@@ -491,6 +1003,7 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
                         conn_id,
                         doc_id,
                         ops,
+                        received_at: Instant::now(),
                     });
                 } else {
                     conn_guard.send_error("not_in_document", "Must join a document first")?;
@@ -498,6 +1011,13 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
             }
 
             ClientMessage::Ack { op_ids } => {
+                if let Some(doc_id) = conn_guard.doc_id() {
+                    let _ = cmd_tx.send(ServerCommand::Acknowledge {
+                        conn_id,
+                        doc_id: doc_id.to_string(),
+                        op_ids: op_ids.clone(),
+                    });
+                }
                 conn_guard.last_ack = op_ids;
             }
 
@@ -543,7 +1063,11 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
     /// Handle a server command.
     async fn handle_command(
         connections: &Arc<RwLock<ConnectionManager>>,
-        documents: &Arc<RwLock<std::collections::HashMap<String, DocumentSession>>>,
+        documents: &Arc<RwLock<HashMap<String, DocumentSession>>>,
+        store: &dyn OperationStore,
+        snapshot_interval: usize,
+        resume_ttl: Duration,
+        metrics: &ServerMetrics,
         cmd: ServerCommand,
     ) {
         match cmd {
@@ -551,12 +1075,38 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
                 connections.write().await.register_user(user_id, conn_id);
             }
 
-            ServerCommand::JoinDocument { conn_id, doc_id } => {
-                // Ensure document session exists
-                {
-                    let mut docs = documents.write().await;
-                    docs.entry(doc_id.clone()).or_insert_with(DocumentSession::new);
-                }
+            ServerCommand::JoinDocument {
+                conn_id,
+                doc_id,
+                user_id,
+                resume_token,
+            } => {
+                // Ensure document session exists, loading it from the store
+                // the first time
+                let mut docs = documents.write().await;
+                let session = docs.entry(doc_id.clone()).or_insert_with(|| {
+                    DocumentSession::restore(store, &doc_id, snapshot_interval)
+                        .unwrap_or_else(|e| {
+                            tracing::error!("Failed to load {} from storage: {}", doc_id, e);
+                            DocumentSession::new(snapshot_interval)
+                        })
+                        .with_resume_ttl(resume_ttl)
+                });
+                session.join(conn_id);
+
+                // A resuming client is sent what it missed without asking
+                let resumed = resume_token.and_then(|token| session.resume(conn_id, &token, &user_id, Instant::now()));
+                let sync = resumed.map(|since| {
+                    let (snapshot, ops) = session.sync(&since);
+                    session.acknowledge_all(conn_id);
+                    ServerMessage::SyncResponse {
+                        snapshot,
+                        ops,
+                        clock: session.clock.clone(),
+                    }
+                });
+                let resume_token = session.issue_resume_token(conn_id, &user_id);
+                drop(docs);
 
                 // Get current users in document
                 let conns = connections.read().await;
@@ -591,7 +1141,13 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
                     let _ = conn_guard.send(ServerMessage::Joined {
                         doc_id: doc_id.clone(),
                         users,
+                        permission: conn_guard.permission,
+                        resume_token,
+                        resumed: sync.is_some(),
                     });
+                    if let Some(sync) = sync {
+                        let _ = conn_guard.send(sync);
+                    }
                 }
 
                 // Notify other users
@@ -631,15 +1187,38 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
 
                 // Remove from document
                 connections.write().await.leave_document(&doc_id, conn_id);
+                if let Some(session) = documents.write().await.get_mut(&doc_id) {
+                    session.leave(conn_id);
+                    save_snapshot(store, &doc_id, session);
+                }
             }
 
-            ServerCommand::BroadcastOps { conn_id, doc_id, ops } => {
-                // Store operations in document session
-                {
-                    let mut docs = documents.write().await;
-                    if let Some(session) = docs.get_mut(&doc_id) {
-                        session.add_ops(ops.clone());
+            ServerCommand::BroadcastOps {
+                conn_id,
+                doc_id,
+                ops,
+                received_at,
+            } => {
+                // Store operations in document session; only new ones are
+                // passed on
+                let ops = match documents.write().await.get_mut(&doc_id) {
+                    Some(session) => {
+                        let ops = session.add_ops(conn_id, ops);
+                        save_snapshot(store, &doc_id, session);
+                        ops
                     }
+                    None => ops,
+                };
+                if ops.is_empty() {
+                    return;
+                }
+
+                let crdt_ops: Vec<_> = ops.iter().filter_map(WireCrdtOp::to_crdt_op).collect();
+                if crdt_ops.len() < ops.len() {
+                    tracing::warn!("{} malformed operations for {} not stored", ops.len() - crdt_ops.len(), doc_id);
+                }
+                if let Err(e) = store.save_operations(&DocId::from(doc_id.as_str()), crdt_ops) {
+                    tracing::error!("Failed to store operations for {}: {}", doc_id, e);
                 }
 
                 // Broadcast to other clients
@@ -650,6 +1229,7 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
                         let _ = other.send(ServerMessage::Ops { ops: ops.clone() });
                     }
                 }
+                metrics.ops_broadcast(ops.len(), received_at.elapsed());
             }
 
             ServerCommand::BroadcastPresence {
@@ -675,18 +1255,24 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
                 doc_id,
                 since,
             } => {
-                let docs = documents.read().await;
-                let response = if let Some(session) = docs.get(&doc_id) {
+                // The response brings the client up to date
+                let mut docs = documents.write().await;
+                let response = if let Some(session) = docs.get_mut(&doc_id) {
+                    let (snapshot, ops) = session.sync(&since);
+                    session.acknowledge_all(conn_id);
                     ServerMessage::SyncResponse {
-                        ops: session.ops_since(&since),
+                        snapshot,
+                        ops,
                         clock: session.clock.clone(),
                     }
                 } else {
                     ServerMessage::SyncResponse {
+                        snapshot: None,
                         ops: Vec::new(),
                         clock: WireVectorClock::default(),
                     }
                 };
+                drop(docs);
 
                 let conns = connections.read().await;
                 if let Some(conn) = conns.get(conn_id) {
@@ -695,9 +1281,21 @@ impl<A: AuthProvider + 'static> CollaborationServer<A> {
                 }
             }
 
+            ServerCommand::Acknowledge { conn_id, doc_id, op_ids } => {
+                if let Some(session) = documents.write().await.get_mut(&doc_id) {
+                    session.acknowledge(conn_id, &op_ids);
+                    save_snapshot(store, &doc_id, session);
+                }
+            }
+
             ServerCommand::Disconnected { conn_id } => {
-                // This is handled in the connection cleanup
+                // The connection is removed from the manager in its cleanup;
+                // it no longer holds back compaction
                 tracing::debug!("Connection {} disconnected", conn_id);
+                for (doc_id, session) in documents.write().await.iter_mut() {
+                    session.leave(conn_id);
+                    save_snapshot(store, doc_id, session);
+                }
             }
         }
     }
@@ -743,6 +1341,8 @@ pub enum ServerError {
     BindFailed(String, std::io::Error),
     /// WebSocket error.
     WebSocket(String),
+    /// The document store couldn't be opened.
+    Storage(StorageError),
 }
 
 impl std::fmt::Display for ServerError {
@@ -752,6 +1352,7 @@ impl std::fmt::Display for ServerError {
                 write!(f, "Failed to bind to {}: {}", addr, e)
             }
             ServerError::WebSocket(e) => write!(f, "WebSocket error: {}", e),
+            ServerError::Storage(e) => write!(f, "Storage error: {}", e),
         }
     }
 }
@@ -803,9 +1404,20 @@ mod tests {
         assert_eq!(config.socket_addr(), "0.0.0.0:9000");
     }
 
+    fn text_insert(client_id: &str, seq: u64) -> WireCrdtOp {
+        WireCrdtOp {
+            id: WireOpId {
+                client_id: client_id.to_string(),
+                seq,
+            },
+            op_type: "text_insert".to_string(),
+            payload: serde_json::json!({}),
+        }
+    }
+
     #[test]
     fn test_document_session() {
-        let mut session = DocumentSession::new();
+        let mut session = DocumentSession::new(ServerConfig::default().snapshot_interval);
 
         let ops = vec![
             WireCrdtOp {
@@ -826,7 +1438,7 @@ mod tests {
             },
         ];
 
-        session.add_ops(ops);
+        session.add_ops(ConnectionId(1), ops);
 
         assert_eq!(session.ops.len(), 2);
         assert_eq!(session.clock.clocks.get("1"), Some(&2));
@@ -840,6 +1452,111 @@ mod tests {
         assert_eq!(new_ops[0].id.seq, 2);
     }
 
+    #[test]
+    fn test_document_session_snapshot() {
+        let (alice, bob) = (ConnectionId(1), ConnectionId(2));
+        let mut session = DocumentSession::new(3);
+        session.join(alice);
+        session.join(bob);
+
+        let ops: Vec<_> = (1..=4).map(|seq| text_insert("1", seq)).collect();
+        assert_eq!(session.add_ops(alice, ops.clone()).len(), 4);
+        // Resent operations are dropped
+        assert!(session.add_ops(alice, ops).is_empty());
+        // Bob hasn't acknowledged anything, so nothing is compacted
+        assert!(session.snapshot.is_none());
+
+        session.acknowledge(bob, &[WireOpId { client_id: "1".to_string(), seq: 3 }]);
+        let snapshot = session.snapshot.as_ref().unwrap();
+        assert_eq!(snapshot.version, Version::new(4));
+        assert_eq!(session.ops.len(), 1);
+        assert_eq!(session.snapshot_clock.get("1"), 3);
+
+        // A late joiner gets the snapshot and the tail
+        let (snapshot, tail) = session.sync(&WireVectorClock::default());
+        let snapshot = snapshot.unwrap();
+        assert_eq!(snapshot.ops.len(), 3);
+        assert_eq!(tail.len(), 1);
+        assert_eq!(tail[0].id.seq, 4);
+
+        // A client past the snapshot only gets the tail
+        let since = WireVectorClock {
+            clocks: [("1".to_string(), 3)].into_iter().collect(),
+        };
+        let (snapshot, tail) = session.sync(&since);
+        assert!(snapshot.is_none());
+        assert_eq!(tail.len(), 1);
+    }
+
+    #[test]
+    fn test_document_session_resume() {
+        let (old_conn, new_conn) = (ConnectionId(1), ConnectionId(2));
+        let mut session = DocumentSession::new(100);
+        let disconnect = |session: &mut DocumentSession| {
+            session.join(old_conn);
+            let token = session.issue_resume_token(old_conn, "alice");
+            session.leave(old_conn);
+            token
+        };
+
+        let token = disconnect(&mut session);
+        session.add_ops(ConnectionId(3), vec![text_insert("2", 1)]);
+
+        // The client gets only what it missed while away
+        session.join(new_conn);
+        let since = session.resume(new_conn, &token, "alice", Instant::now()).unwrap();
+        let (snapshot, ops) = session.sync(&since);
+        assert!(snapshot.is_none());
+        assert_eq!(ops.len(), 1);
+
+        // Tokens are single-use, tied to their user, and expire
+        assert!(session.resume(new_conn, &token, "alice", Instant::now()).is_none());
+        let token = disconnect(&mut session);
+        assert!(session.resume(new_conn, &token, "mallory", Instant::now()).is_none());
+        let token = disconnect(&mut session);
+        let later = Instant::now() + session.resume_ttl;
+        assert!(session.resume(new_conn, &token, "alice", later).is_none());
+    }
+
+    #[test]
+    fn test_document_session_restore() {
+        use crate::op_id::{ClientId, OpId};
+        use crate::operation::CrdtOp;
+        use doc_model::NodeId;
+
+        let store = SqliteOperationStore::open_in_memory().unwrap();
+        let conn = ConnectionId(1);
+        let node_id = NodeId::new();
+        let ops: Vec<_> = (1..=3)
+            .map(|seq| {
+                let op = CrdtOp::TextInsert {
+                    id: OpId::new(ClientId::new(1), seq),
+                    node_id,
+                    parent_op_id: OpId::new(ClientId::new(1), seq - 1),
+                    char: 'a',
+                };
+                WireCrdtOp::from(&op)
+            })
+            .collect();
+
+        // The first two operations are folded into a snapshot
+        let mut session = DocumentSession::new(2);
+        session.join(conn);
+        for op in ops {
+            let added = session.add_ops(conn, vec![op]);
+            let crdt_ops = added.iter().filter_map(WireCrdtOp::to_crdt_op).collect();
+            store.save_operations(&DocId::from("doc"), crdt_ops).unwrap();
+            save_snapshot(&store, "doc", &mut session);
+        }
+        assert_eq!(session.snapshot_clock.get("1"), 2);
+
+        let restored = DocumentSession::restore(&store, "doc", 2).unwrap();
+        assert_eq!(restored.clock.get("1"), 3);
+        assert_eq!(restored.snapshot_clock.get("1"), 2);
+        assert_eq!(restored.ops.len(), 1);
+        assert_eq!(restored.version, Version::new(3));
+    }
+
     #[tokio::test]
     async fn test_server_creation() {
         let config = ServerConfig::with_port(0);
@@ -850,6 +1567,53 @@ mod tests {
         assert_eq!(stats.total_documents, 0);
     }
 
+    #[tokio::test]
+    async fn test_join_permissions() {
+        type Server = CollaborationServer<AcceptAllAuthProvider>;
+
+        let doc_id = DocId::from("doc");
+        let owner = UserId::from("owner");
+        let mut manager = PermissionManager::new();
+        manager.grant_owner(doc_id.clone(), owner.clone());
+        let link = manager
+            .create_share_link(doc_id, PermissionLevel::Viewer, owner, None, None)
+            .unwrap();
+        let permissions = RwLock::new(manager);
+
+        let auth = Arc::new(AcceptAllAuthProvider);
+        let limits = Mutex::new(DocumentRateLimiter::new(RateLimit::default()));
+        let metrics = ServerMetrics::new();
+        let (cmd_tx, _cmd_rx) = mpsc::unbounded_channel();
+        let (msg_tx, mut msg_rx) = mpsc::channel(8);
+        let conn = Arc::new(RwLock::new(ClientConnection::new(msg_tx)));
+        let send = |text: String| {
+            let (conn, auth, permissions, cmd_tx) = (&conn, &auth, &permissions, &cmd_tx);
+            let msg = ClientMessage::from_json(&text).unwrap();
+            let (limits, metrics) = (&limits, &metrics);
+            async move {
+                Server::handle_message(conn, msg, auth, Some(permissions), limits, metrics, cmd_tx).await.unwrap()
+            }
+        };
+        let error_code = |msg: Option<ServerMessage>| match msg {
+            Some(ServerMessage::Error { code, .. }) => code,
+            other => panic!("Expected an error, got {:?}", other),
+        };
+
+        send(r#"{"type":"auth","token":"guest"}"#.to_string()).await;
+        assert!(matches!(msg_rx.recv().await, Some(ServerMessage::AuthSuccess { .. })));
+
+        // No access without the link
+        send(r#"{"type":"join","docId":"doc"}"#.to_string()).await;
+        assert_eq!(error_code(msg_rx.recv().await), "permission_denied");
+        assert!(!conn.read().await.is_in_document());
+
+        // The link makes the guest a viewer, who can't send operations
+        send(format!(r#"{{"type":"join","docId":"doc","shareToken":"{}"}}"#, link.token)).await;
+        assert_eq!(conn.read().await.permission, PermissionLevel::Viewer);
+        send(r#"{"type":"ops","ops":[]}"#.to_string()).await;
+        assert_eq!(error_code(msg_rx.recv().await), "permission_denied");
+    }
+
     #[test]
     fn test_shutdown_handle() {
         let config = ServerConfig::default();
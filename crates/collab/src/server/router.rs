@@ -520,7 +520,8 @@ impl OperationRouter {
         let client = session.get_client(client_id)
             .ok_or_else(|| RouterError::SessionError(SessionError::ClientNotFound(client_id.clone())))?;
 
-        if !client.can_edit() {
+        let allowed = client.can_edit() || (client.can_comment() && ops.iter().all(CrdtOp::is_comment));
+        if !allowed {
             return Err(RouterError::PermissionDenied(
                 "Client does not have edit permission".to_string()
             ));
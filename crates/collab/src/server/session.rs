@@ -529,7 +529,8 @@ impl DocumentSession {
         let client = self.clients.get(client_id)
             .ok_or_else(|| SessionError::ClientNotFound(client_id.clone()))?;
 
-        if !client.can_edit() {
+        let allowed = client.can_edit() || (client.can_comment() && ops.iter().all(CrdtOp::is_comment));
+        if !allowed {
             return Err(SessionError::PermissionDenied(
                 "Client does not have edit permission".to_string(),
             ));
@@ -120,6 +120,36 @@ impl Snapshot {
     }
 }
 
+/// How much history a store keeps for a document
+///
+/// The default keeps everything.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    /// Number of most recent snapshots to keep (`None` keeps all)
+    #[serde(default)]
+    pub max_snapshots: Option<usize>,
+    /// Whether operations covered by the latest snapshot are deleted
+    #[serde(default)]
+    pub prune_snapshotted_operations: bool,
+}
+
+impl RetentionPolicy {
+    /// Keep only the latest snapshot and the operations after it
+    pub fn latest_snapshot_only() -> Self {
+        Self {
+            max_snapshots: Some(1),
+            prune_snapshotted_operations: true,
+        }
+    }
+
+    /// Keep at most `count` snapshots
+    pub fn with_max_snapshots(mut self, count: usize) -> Self {
+        self.max_snapshots = Some(count);
+        self
+    }
+}
+
 /// Errors that can occur during storage operations
 #[derive(Error, Debug)]
 pub enum StorageError {
@@ -123,6 +123,7 @@ impl Version {
         let mut block_deletes = 0;
         let mut block_moves = 0;
         let mut block_updates = 0;
+        let mut annotation_changes = 0;
 
         for op in ops {
             match op {
@@ -133,6 +134,7 @@ impl Version {
                 CrdtOp::BlockDelete { .. } => block_deletes += 1,
                 CrdtOp::BlockMove { .. } => block_moves += 1,
                 CrdtOp::BlockUpdate { .. } => block_updates += 1,
+                CrdtOp::AnnotationSet { .. } => annotation_changes += 1,
             }
         }
 
@@ -187,6 +189,13 @@ impl Version {
                 if block_updates == 1 { "" } else { "s" }
             ));
         }
+        if annotation_changes > 0 {
+            parts.push(format!(
+                "{} comment or revision change{}",
+                annotation_changes,
+                if annotation_changes == 1 { "" } else { "s" }
+            ));
+        }
 
         if parts.is_empty() {
             "No changes".to_string()
@@ -591,6 +600,10 @@ impl VersionHistory {
                 // Would need previous block data
                 None
             }
+            CrdtOp::AnnotationSet { .. } => {
+                // LWW like formatting - would need the previous value
+                None
+            }
         }
     }
 
@@ -0,0 +1,199 @@
+//! Building blocks (Quick Parts)
+//!
+//! A building block is a named piece of reusable content, such as a cover
+//! page, a header, a text box or an AutoText phrase. Its content is kept as
+//! a standalone fragment tree and copied into a document when inserted.
+//! Blocks are grouped into galleries, and into categories within a gallery.
+
+use crate::DocumentTree;
+use serde::{Deserialize, Serialize};
+
+/// Category used when none is given
+pub const DEFAULT_BUILDING_BLOCK_CATEGORY: &str = "General";
+
+/// The gallery a building block appears in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BuildingBlockGallery {
+    /// Cover pages, inserted at the start of the document
+    CoverPages,
+    /// Page headers
+    Headers,
+    /// Page footers
+    Footers,
+    /// Text boxes
+    TextBoxes,
+    /// Equations
+    Equations,
+    /// Tables
+    Tables,
+    /// General Quick Parts
+    #[default]
+    QuickParts,
+    /// AutoText entries, inserted by typing their name
+    AutoText,
+}
+
+impl BuildingBlockGallery {
+    /// All galleries in display order
+    pub const ALL: [BuildingBlockGallery; 8] = [
+        BuildingBlockGallery::CoverPages,
+        BuildingBlockGallery::Headers,
+        BuildingBlockGallery::Footers,
+        BuildingBlockGallery::TextBoxes,
+        BuildingBlockGallery::Equations,
+        BuildingBlockGallery::Tables,
+        BuildingBlockGallery::QuickParts,
+        BuildingBlockGallery::AutoText,
+    ];
+
+    /// Display name
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            BuildingBlockGallery::CoverPages => "Cover Pages",
+            BuildingBlockGallery::Headers => "Headers",
+            BuildingBlockGallery::Footers => "Footers",
+            BuildingBlockGallery::TextBoxes => "Text Boxes",
+            BuildingBlockGallery::Equations => "Equations",
+            BuildingBlockGallery::Tables => "Tables",
+            BuildingBlockGallery::QuickParts => "Quick Parts",
+            BuildingBlockGallery::AutoText => "AutoText",
+        }
+    }
+}
+
+/// A named piece of reusable content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildingBlock {
+    /// Unique ID
+    pub id: String,
+    /// Name shown in the gallery
+    pub name: String,
+    /// Gallery the block appears in
+    pub gallery: BuildingBlockGallery,
+    /// Category within the gallery
+    pub category: String,
+    /// Description shown as a tooltip
+    #[serde(default)]
+    pub description: String,
+    /// The content, as a standalone fragment
+    pub content: DocumentTree,
+}
+
+impl BuildingBlock {
+    /// Create a building block in the default category
+    pub fn new(name: impl Into<String>, gallery: BuildingBlockGallery, content: DocumentTree) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.into(),
+            gallery,
+            category: DEFAULT_BUILDING_BLOCK_CATEGORY.to_string(),
+            description: String::new(),
+            content,
+        }
+    }
+
+    /// Set the ID
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    /// Set the category
+    pub fn with_category(mut self, category: impl Into<String>) -> Self {
+        self.category = category.into();
+        self
+    }
+
+    /// Set the description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+}
+
+/// A set of building blocks, such as a document's or a user's
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildingBlockCollection {
+    #[serde(default)]
+    blocks: Vec<BuildingBlock>,
+}
+
+impl BuildingBlockCollection {
+    /// Create an empty collection
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a block, replacing any block with the same name in the same gallery
+    ///
+    /// Returns the replaced block.
+    pub fn add(&mut self, block: BuildingBlock) -> Option<BuildingBlock> {
+        match self
+            .blocks
+            .iter_mut()
+            .find(|b| b.gallery == block.gallery && b.name.eq_ignore_ascii_case(&block.name))
+        {
+            Some(existing) => Some(std::mem::replace(existing, block)),
+            None => {
+                self.blocks.push(block);
+                None
+            }
+        }
+    }
+
+    /// Remove a block by ID
+    pub fn remove(&mut self, id: &str) -> Option<BuildingBlock> {
+        let index = self.blocks.iter().position(|b| b.id == id)?;
+        Some(self.blocks.remove(index))
+    }
+
+    /// Get a block by ID
+    pub fn get(&self, id: &str) -> Option<&BuildingBlock> {
+        self.blocks.iter().find(|b| b.id == id)
+    }
+
+    /// Find a block by gallery and name, ignoring case
+    pub fn find(&self, gallery: BuildingBlockGallery, name: &str) -> Option<&BuildingBlock> {
+        self.blocks
+            .iter()
+            .find(|b| b.gallery == gallery && b.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Blocks in a gallery, sorted by category and name
+    pub fn in_gallery(&self, gallery: BuildingBlockGallery) -> Vec<&BuildingBlock> {
+        let mut blocks: Vec<_> = self.blocks.iter().filter(|b| b.gallery == gallery).collect();
+        blocks.sort_by(|a, b| a.category.cmp(&b.category).then_with(|| a.name.cmp(&b.name)));
+        blocks
+    }
+
+    /// Categories used in a gallery, sorted
+    pub fn categories(&self, gallery: BuildingBlockGallery) -> Vec<&str> {
+        let mut categories: Vec<_> = self
+            .blocks
+            .iter()
+            .filter(|b| b.gallery == gallery)
+            .map(|b| b.category.as_str())
+            .collect();
+        categories.sort_unstable();
+        categories.dedup();
+        categories
+    }
+
+    /// All blocks
+    pub fn iter(&self) -> impl Iterator<Item = &BuildingBlock> {
+        self.blocks.iter()
+    }
+
+    /// Number of blocks
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Check if there are no blocks
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+}
@@ -0,0 +1,204 @@
+//! Drawing canvas - a frame that groups shapes in its own coordinate space
+//!
+//! Shapes on a canvas are children of the canvas rather than of a paragraph.
+//! Their anchor offsets are measured from the canvas's top-left corner, so
+//! moving the canvas moves every shape on it.
+
+use crate::{
+    AnchorPosition, ImagePosition, Node, NodeId, NodeType, Point, Rect, ShapeFill, ShapeNode,
+    ShapeStroke, WrapType,
+};
+use serde::{Deserialize, Serialize};
+
+/// A drawing canvas holding shapes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawingCanvas {
+    /// Unique node ID
+    id: NodeId,
+    /// Parent paragraph
+    parent: Option<NodeId>,
+    /// Shapes on the canvas, back to front
+    shapes: Vec<NodeId>,
+    /// Width of the canvas in points
+    pub width: f32,
+    /// Height of the canvas in points
+    pub height: f32,
+    /// Background fill
+    pub fill: Option<ShapeFill>,
+    /// Border
+    pub stroke: Option<ShapeStroke>,
+    /// How text wraps around the canvas
+    pub wrap_type: WrapType,
+    /// Position of the canvas relative to its paragraph
+    pub position: ImagePosition,
+    /// Optional name for the canvas
+    pub name: Option<String>,
+    /// Alternative text for accessibility
+    pub alt_text: Option<String>,
+}
+
+impl DrawingCanvas {
+    /// Create an empty inline canvas
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            id: NodeId::new(),
+            parent: None,
+            shapes: Vec::new(),
+            width,
+            height,
+            fill: None,
+            stroke: None,
+            wrap_type: WrapType::Inline,
+            position: ImagePosition::Inline,
+            name: None,
+            alt_text: None,
+        }
+    }
+
+    /// Create an empty canvas floating at an offset from its paragraph
+    pub fn floating(frame: Rect) -> Self {
+        let mut canvas = Self::new(frame.width, frame.height);
+        canvas.wrap_type = WrapType::InFront;
+        canvas.position = ImagePosition::Anchor(AnchorPosition {
+            offset_x: frame.x,
+            offset_y: frame.y,
+            ..Default::default()
+        });
+        canvas
+    }
+
+    /// Shapes on the canvas, back to front
+    pub fn shapes(&self) -> &[NodeId] {
+        &self.shapes
+    }
+
+    /// Check if a shape is on the canvas
+    pub fn contains(&self, shape_id: NodeId) -> bool {
+        self.shapes.contains(&shape_id)
+    }
+
+    /// Check if the canvas has no shapes
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    /// Bounds of the canvas in its own coordinates
+    pub fn bounds(&self) -> Rect {
+        Rect::new(0.0, 0.0, self.width, self.height)
+    }
+
+    /// Offset of the canvas from its anchor (zero when inline)
+    pub fn offset(&self) -> Point {
+        match self.position {
+            ImagePosition::Anchor(anchor) => Point::new(anchor.offset_x, anchor.offset_y),
+            ImagePosition::Inline => Point::new(0.0, 0.0),
+        }
+    }
+
+    /// Grow the canvas so it covers every given rect (in canvas coordinates)
+    pub fn fit_to(&mut self, rects: &[Rect]) {
+        for rect in rects {
+            self.width = self.width.max(rect.x + rect.width);
+            self.height = self.height.max(rect.y + rect.height);
+        }
+    }
+
+    pub(crate) fn add_shape(&mut self, shape_id: NodeId) {
+        if !self.shapes.contains(&shape_id) {
+            self.shapes.push(shape_id);
+        }
+    }
+
+    pub(crate) fn remove_shape(&mut self, shape_id: NodeId) -> bool {
+        let before = self.shapes.len();
+        self.shapes.retain(|&id| id != shape_id);
+        self.shapes.len() != before
+    }
+}
+
+impl Node for DrawingCanvas {
+    fn id(&self) -> NodeId {
+        self.id
+    }
+
+    fn node_type(&self) -> NodeType {
+        NodeType::Canvas
+    }
+
+    fn children(&self) -> &[NodeId] {
+        &self.shapes
+    }
+
+    fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    fn set_parent(&mut self, parent: Option<NodeId>) {
+        self.parent = parent;
+    }
+
+    fn can_have_children(&self) -> bool {
+        true
+    }
+}
+
+/// Bounds of a shape relative to its anchor: the anchor offset and its size
+///
+/// For a shape on a canvas this is in canvas coordinates.
+pub fn shape_frame(shape: &ShapeNode) -> Rect {
+    let (x, y) = match shape.properties.position {
+        ImagePosition::Anchor(anchor) => (anchor.offset_x, anchor.offset_y),
+        ImagePosition::Inline => (0.0, 0.0),
+    };
+    Rect::new(x, y, shape.effective_width(0.0), shape.effective_height(0.0))
+}
+
+/// Float a shape at an offset from its anchor
+pub fn set_shape_offset(shape: &mut ShapeNode, x: f32, y: f32) {
+    let anchor = match shape.properties.position {
+        ImagePosition::Anchor(anchor) => anchor,
+        ImagePosition::Inline => AnchorPosition::default(),
+    };
+    shape.properties.position = ImagePosition::Anchor(AnchorPosition {
+        offset_x: x,
+        offset_y: y,
+        ..anchor
+    });
+    if shape.is_inline() {
+        shape.properties.wrap_type = WrapType::InFront;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DocumentTree, Paragraph};
+
+    #[test]
+    fn test_shapes_move_onto_and_off_a_canvas() {
+        let mut tree = DocumentTree::new();
+        let para = Paragraph::new();
+        let para_id = para.id();
+        tree.insert_paragraph(para, tree.root_id(), None).unwrap();
+
+        let mut shape = ShapeNode::rectangle(40.0, 20.0);
+        set_shape_offset(&mut shape, 10.0, 5.0);
+        let shape_id = tree.insert_shape(shape, para_id, None).unwrap();
+        let canvas_id = tree.insert_canvas(DrawingCanvas::new(200.0, 100.0), para_id, None).unwrap();
+
+        tree.move_shape_to_canvas(shape_id, canvas_id).unwrap();
+        assert_eq!(tree.get_paragraph(para_id).unwrap().children(), &[canvas_id]);
+        assert_eq!(tree.get_shape(shape_id).unwrap().parent(), Some(canvas_id));
+        assert_eq!(shape_frame(tree.canvas_shapes(canvas_id)[0]), Rect::new(10.0, 5.0, 40.0, 20.0));
+        assert_eq!(tree.path_to_node(shape_id).unwrap().len(), 4);
+
+        tree.move_shape_to_paragraph(shape_id, para_id).unwrap();
+        assert!(tree.get_canvas(canvas_id).unwrap().is_empty());
+        tree.move_shape_to_canvas(shape_id, canvas_id).unwrap();
+
+        let (_, shapes) = tree.remove_canvas(canvas_id).unwrap();
+        assert_eq!(shapes.len(), 1);
+        assert!(tree.get_shape(shape_id).is_none());
+        assert!(tree.get_paragraph(para_id).unwrap().children().is_empty());
+    }
+}
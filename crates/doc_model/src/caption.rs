@@ -116,6 +116,9 @@ pub struct CaptionFormat {
     pub default_position: CaptionPosition,
     /// Paragraph style to apply to caption paragraphs
     pub paragraph_style: StyleId,
+    /// Brackets enclosing the full number (e.g., "(" and ")" for "(3.2)")
+    #[serde(default)]
+    pub number_brackets: Option<(String, String)>,
 }
 
 impl CaptionFormat {
@@ -135,9 +138,18 @@ impl CaptionFormat {
             chapter_style: None,
             default_position,
             paragraph_style: StyleId::new("Caption"),
+            number_brackets: None,
         }
     }
 
+    /// Create the conventional equation numbering format: "(3.2)", numbered
+    /// per chapter and restarting at each chapter heading
+    pub fn equation_numbering(chapter_style: StyleId) -> Self {
+        Self::new(CaptionLabel::Equation)
+            .with_chapter_numbering(chapter_style, ".")
+            .with_number_brackets("(", ")")
+    }
+
     /// Set the separator
     pub fn with_separator(mut self, separator: impl Into<String>) -> Self {
         self.separator = separator.into();
@@ -170,6 +182,12 @@ impl CaptionFormat {
         self
     }
 
+    /// Enclose the full number in brackets (e.g., "(" and ")")
+    pub fn with_number_brackets(mut self, open: &str, close: &str) -> Self {
+        self.number_brackets = Some((open.to_string(), close.to_string()));
+        self
+    }
+
     /// Format the caption number (without chapter)
     pub fn format_number(&self, number: u32) -> String {
         self.number_format.format(number)
@@ -189,6 +207,15 @@ impl CaptionFormat {
         }
         self.format_number(number)
     }
+
+    /// Format the caption number with chapter prefix and enclosing brackets
+    pub fn format_display_number(&self, chapter: Option<u32>, number: u32) -> String {
+        let full = self.format_full_number(chapter, number);
+        match &self.number_brackets {
+            Some((open, close)) => format!("{}{}{}", open, full, close),
+            None => full,
+        }
+    }
 }
 
 impl Default for CaptionFormat {
@@ -222,6 +249,9 @@ pub struct Caption {
     pub bookmark_name: String,
     /// Whether this caption should be included in a list of figures/tables
     pub include_in_list: bool,
+    /// Chapter number of the heading preceding this caption, if known
+    #[serde(default)]
+    pub chapter: Option<u32>,
 }
 
 impl Caption {
@@ -247,6 +277,7 @@ impl Caption {
             paragraph_id,
             bookmark_name,
             include_in_list: true,
+            chapter: None,
         }
     }
 
@@ -279,6 +310,11 @@ impl Caption {
     pub fn set_include_in_list(&mut self, include: bool) {
         self.include_in_list = include;
     }
+
+    /// Set the chapter this caption belongs to
+    pub fn set_chapter(&mut self, chapter: Option<u32>) {
+        self.chapter = chapter;
+    }
 }
 
 // =============================================================================
@@ -342,6 +378,28 @@ impl CaptionRegistry {
         id
     }
 
+    /// Insert a caption at a specific position in its label's sequence
+    ///
+    /// Use this when a caption is added between existing ones so that later
+    /// captions are renumbered.
+    pub fn insert_at(&mut self, caption: Caption, index: usize) -> NodeId {
+        let id = caption.id;
+        let label_key = caption.label.seq_identifier();
+
+        if let Some(target_id) = caption.target_id {
+            self.target_index.insert(target_id, id);
+        }
+        self.paragraph_index.insert(caption.paragraph_id, id);
+
+        let order = self.ordering.entry(label_key).or_default();
+        let index = index.min(order.len());
+        order.insert(index, id);
+
+        self.captions.insert(id, caption);
+
+        id
+    }
+
     /// Remove a caption from the registry
     pub fn remove(&mut self, id: NodeId) -> Option<Caption> {
         if let Some(caption) = self.captions.remove(&id) {
@@ -424,15 +482,47 @@ impl CaptionRegistry {
     }
 
     /// Get the number (position) of a caption in its sequence
+    ///
+    /// When the label's format includes chapter numbers, numbering restarts
+    /// at 1 for each chapter.
     pub fn get_caption_number(&self, id: NodeId) -> Option<u32> {
         let caption = self.captions.get(&id)?;
         let label_key = caption.label.seq_identifier();
         let ordering = self.ordering.get(&label_key)?;
+        let per_chapter = self
+            .formats
+            .get(&label_key)
+            .map(|f| f.include_chapter)
+            .unwrap_or(false);
 
-        ordering
+        let pos = ordering.iter().position(|&cid| cid == id)?;
+        let count = ordering[..=pos]
             .iter()
-            .position(|&cid| cid == id)
-            .map(|pos| (pos + 1) as u32)
+            .filter_map(|cid| self.captions.get(cid))
+            .filter(|c| !per_chapter || c.chapter == caption.chapter)
+            .count();
+        Some(count as u32)
+    }
+
+    /// Get the formatted number of a caption, including chapter prefix and
+    /// brackets (e.g., "3.2" or "(3.2)")
+    pub fn get_display_number(&self, id: NodeId) -> Option<String> {
+        let caption = self.captions.get(&id)?;
+        let number = self.get_caption_number(id)?;
+        let display = match self.get_format(&caption.label) {
+            Some(format) => format.format_display_number(caption.chapter, number),
+            None => CaptionFormat::new(caption.label.clone())
+                .format_display_number(caption.chapter, number),
+        };
+        Some(display)
+    }
+
+    /// Assign chapter numbers to captions from a map of caption paragraph ID
+    /// to chapter number. Captions whose paragraph is absent lose their chapter.
+    pub fn update_chapters(&mut self, chapters: &HashMap<NodeId, u32>) {
+        for caption in self.captions.values_mut() {
+            caption.chapter = chapters.get(&caption.paragraph_id).copied();
+        }
     }
 
     /// Get the format for a label type
@@ -533,6 +623,7 @@ impl CaptionBuilder {
             options: SeqOptions {
                 identifier: self.label.seq_identifier(),
                 format: format.number_format,
+                reset_at_heading_level: format.include_chapter.then_some(1),
                 ..Default::default()
             },
         });
@@ -935,4 +1026,82 @@ mod tests {
         assert_eq!(registry.get_caption_number(ids[0]), Some(2));
         assert_eq!(registry.get_caption_number(ids[2]), Some(1));
     }
+
+    #[test]
+    fn test_equation_numbering_per_chapter() {
+        let mut registry = CaptionRegistry::new();
+        registry.set_format(CaptionFormat::equation_numbering(StyleId::new("Heading1")));
+
+        let mut ids = Vec::new();
+        for chapter in [1, 1, 2] {
+            let mut caption = Caption::new(
+                CaptionLabel::Equation,
+                "",
+                CaptionPosition::Below,
+                None,
+                NodeId::new(),
+                NodeId::new(),
+            );
+            caption.set_chapter(Some(chapter));
+            ids.push(registry.insert(caption));
+        }
+
+        assert_eq!(registry.get_display_number(ids[0]).as_deref(), Some("(1.1)"));
+        assert_eq!(registry.get_display_number(ids[1]).as_deref(), Some("(1.2)"));
+        assert_eq!(registry.get_display_number(ids[2]).as_deref(), Some("(2.1)"));
+    }
+
+    #[test]
+    fn test_insert_at_renumbers_following_captions() {
+        let mut registry = CaptionRegistry::new();
+        let make = || {
+            Caption::new(
+                CaptionLabel::Equation,
+                "",
+                CaptionPosition::Below,
+                None,
+                NodeId::new(),
+                NodeId::new(),
+            )
+        };
+
+        let first = registry.insert(make());
+        let last = registry.insert(make());
+        let middle = registry.insert_at(make(), 1);
+
+        assert_eq!(registry.get_caption_number(first), Some(1));
+        assert_eq!(registry.get_caption_number(middle), Some(2));
+        assert_eq!(registry.get_caption_number(last), Some(3));
+    }
+
+    #[test]
+    fn test_update_chapters_from_paragraphs() {
+        let mut registry = CaptionRegistry::new();
+        let para_id = NodeId::new();
+        let id = registry.insert(Caption::new(
+            CaptionLabel::Equation,
+            "",
+            CaptionPosition::Below,
+            None,
+            para_id,
+            NodeId::new(),
+        ));
+
+        let chapters = HashMap::from([(para_id, 4)]);
+        registry.update_chapters(&chapters);
+        assert_eq!(registry.get(id).unwrap().chapter, Some(4));
+    }
+
+    #[test]
+    fn test_equation_builder_resets_per_chapter() {
+        let components = CaptionBuilder::new(CaptionLabel::Equation)
+            .with_format(CaptionFormat::equation_numbering(StyleId::new("Heading1")))
+            .build();
+
+        if let FieldInstruction::Seq { options } = &components.seq_field.instruction {
+            assert_eq!(options.reset_at_heading_level, Some(1));
+        } else {
+            panic!("Expected SEQ field instruction");
+        }
+    }
 }
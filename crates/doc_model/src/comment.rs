@@ -396,6 +396,17 @@ impl Comment {
         self.content = content.into();
     }
 
+    /// Replace every name on the comment, its replies, and its resolution
+    pub fn anonymize(&mut self, name: &str) {
+        self.author = name.to_string();
+        for reply in &mut self.replies {
+            reply.author = name.to_string();
+        }
+        if self.resolved_by.is_some() {
+            self.resolved_by = Some(name.to_string());
+        }
+    }
+
     /// Get the replies
     pub fn replies(&self) -> &[CommentReply] {
         &self.replies
@@ -447,9 +458,14 @@ impl Comment {
 
     /// Resolve the comment
     pub fn resolve(&mut self, resolved_by: impl Into<String>) {
+        self.resolve_at(resolved_by, Utc::now());
+    }
+
+    /// Resolve the comment with a specific date (for sync/undo)
+    pub fn resolve_at(&mut self, resolved_by: impl Into<String>, date: DateTime<Utc>) {
         self.resolved = true;
         self.resolved_by = Some(resolved_by.into());
-        self.resolved_date = Some(Utc::now());
+        self.resolved_date = Some(date);
     }
 
     /// Reopen the comment (unresolve it)
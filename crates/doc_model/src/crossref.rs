@@ -21,8 +21,8 @@
 
 use crate::{
     field::{Field, FieldInstruction, RefDisplayType, RefOptions},
-    BookmarkRegistry, CaptionLabel, CaptionRegistry, Node, NodeId, NodeType, NoteId, NoteStore,
-    Position,
+    BookmarkRegistry, Caption, CaptionLabel, CaptionRegistry, Node, NodeId, NodeType, NoteId,
+    NoteStore, Position,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -676,6 +676,28 @@ impl CrossRefRegistry {
         }
     }
 
+    /// Handle captions of a label being added, removed, or reordered
+    ///
+    /// Caption numbers shift when the sequence changes, so every reference to
+    /// a caption with this label is marked dirty for re-evaluation.
+    pub fn handle_caption_sequence_changed(&mut self, label: &CaptionLabel) {
+        let affected: Vec<NodeId> = self
+            .references
+            .values()
+            .filter(|r| match r.ref_type {
+                CrossRefType::CustomCaption => {
+                    r.custom_label.as_deref() == Some(&label.seq_identifier())
+                }
+                ref_type => ref_type.to_caption_label().as_ref() == Some(label),
+            })
+            .map(|r| r.id)
+            .collect();
+
+        for id in affected {
+            self.mark_dirty(id);
+        }
+    }
+
     /// Handle a target being deleted (mark all references as broken)
     pub fn handle_target_deleted(&mut self, target_id: &str) {
         if let Some(ref_ids) = self.target_index.get(target_id) {
@@ -1108,7 +1130,6 @@ impl CrossRefUpdater {
         match crossref.display {
             CrossRefDisplay::FullCaption => {
                 if let Some(c) = caption {
-                    let number = captions.get_caption_number(c.id()).unwrap_or(0);
                     let format = captions
                         .get_format(&label)
                         .cloned()
@@ -1116,7 +1137,7 @@ impl CrossRefUpdater {
                     format!(
                         "{} {}{}{}",
                         label.display_text(),
-                        format.format_number(number),
+                        Self::caption_number_text(captions, c),
                         format.separator,
                         c.text()
                     )
@@ -1126,24 +1147,18 @@ impl CrossRefUpdater {
             }
             CrossRefDisplay::LabelAndNumber => {
                 if let Some(c) = caption {
-                    let number = captions.get_caption_number(c.id()).unwrap_or(0);
-                    let format = captions
-                        .get_format(&label)
-                        .cloned()
-                        .unwrap_or_else(|| crate::caption::CaptionFormat::new(label.clone()));
-                    format!("{} {}", label.display_text(), format.format_number(number))
+                    format!(
+                        "{} {}",
+                        label.display_text(),
+                        Self::caption_number_text(captions, c)
+                    )
                 } else {
                     "Error! Caption not found.".to_string()
                 }
             }
             CrossRefDisplay::Number => {
                 if let Some(c) = caption {
-                    let number = captions.get_caption_number(c.id()).unwrap_or(0);
-                    let format = captions
-                        .get_format(&label)
-                        .cloned()
-                        .unwrap_or_else(|| crate::caption::CaptionFormat::new(label.clone()));
-                    format.format_number(number)
+                    Self::caption_number_text(captions, c)
                 } else {
                     "?".to_string()
                 }
@@ -1159,12 +1174,11 @@ impl CrossRefUpdater {
             _ => {
                 // Default to label and number
                 if let Some(c) = caption {
-                    let number = captions.get_caption_number(c.id()).unwrap_or(0);
-                    let format = captions
-                        .get_format(&label)
-                        .cloned()
-                        .unwrap_or_else(|| crate::caption::CaptionFormat::new(label.clone()));
-                    format!("{} {}", label.display_text(), format.format_number(number))
+                    format!(
+                        "{} {}",
+                        label.display_text(),
+                        Self::caption_number_text(captions, c)
+                    )
                 } else {
                     "Error! Caption not found.".to_string()
                 }
@@ -1172,6 +1186,13 @@ impl CrossRefUpdater {
         }
     }
 
+    /// Formatted number of a caption, including chapter prefix and brackets
+    fn caption_number_text(captions: &CaptionRegistry, caption: &Caption) -> String {
+        captions
+            .get_display_number(caption.id())
+            .unwrap_or_else(|| "0".to_string())
+    }
+
     /// Update all cross-references in a registry
     pub fn update_all(
         registry: &mut CrossRefRegistry,
@@ -1499,4 +1520,55 @@ mod tests {
         assert_eq!(broken[0].ref_id, id);
         assert_eq!(broken[0].error_message, "Heading not found");
     }
+
+    #[test]
+    fn test_equation_references_follow_renumbering() {
+        use crate::{CaptionFormat, CaptionPosition, StyleId};
+
+        let mut captions = CaptionRegistry::new();
+        captions.set_format(CaptionFormat::equation_numbering(StyleId::new("Heading1")));
+
+        let mut eq_ids = Vec::new();
+        for _ in 0..3 {
+            let mut caption = Caption::new(
+                CaptionLabel::Equation,
+                "",
+                CaptionPosition::Below,
+                None,
+                NodeId::new(),
+                NodeId::new(),
+            );
+            caption.set_chapter(Some(3));
+            eq_ids.push(caption.id());
+            captions.insert(caption);
+        }
+        let target = captions.get(eq_ids[2]).unwrap().bookmark_name().to_string();
+
+        let mut registry = CrossRefRegistry::new();
+        let crossref = CrossReference::equation(target).with_display(CrossRefDisplay::Number);
+        let ref_id = registry.insert(crossref);
+
+        let bookmarks = BookmarkRegistry::new();
+        let notes = NoteStore::new();
+        let pages = HashMap::new();
+        CrossRefUpdater::update_all(&mut registry, &bookmarks, &captions, &notes, &pages);
+        assert_eq!(registry.get(ref_id).unwrap().display_text(), "(3.3)");
+
+        captions.remove(eq_ids[0]);
+        registry.handle_caption_sequence_changed(&CaptionLabel::Equation);
+        assert_eq!(registry.dirty_refs(), &[ref_id]);
+
+        CrossRefUpdater::update_all(&mut registry, &bookmarks, &captions, &notes, &pages);
+        assert_eq!(registry.get(ref_id).unwrap().display_text(), "(3.2)");
+    }
+
+    #[test]
+    fn test_caption_sequence_change_ignores_other_labels() {
+        let mut registry = CrossRefRegistry::new();
+        registry.insert(CrossReference::figure("_RefFigure_1"));
+        registry.clear_dirty();
+
+        registry.handle_caption_sequence_changed(&CaptionLabel::Equation);
+        assert!(registry.dirty_refs().is_empty());
+    }
 }
@@ -1,9 +1,9 @@
 //! Document root node and document-level operations
 
-use crate::{Node, NodeId, NodeType, Paragraph, Run};
+use crate::{DocumentSettings, Node, NodeId, NodeType, Paragraph, Run};
 use crate::protection::DocumentProtection;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Document metadata
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -12,6 +12,12 @@ pub struct DocumentMetadata {
     pub author: Option<String>,
     pub created: Option<String>,
     pub modified: Option<String>,
+    /// Base that relative hyperlinks are resolved against
+    #[serde(default)]
+    pub hyperlink_base: Option<String>,
+    /// Document variables, by name
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
 }
 
 /// Page setup configuration
@@ -59,6 +65,9 @@ pub struct Document {
     version: u64,
     /// Document protection settings
     pub protection: DocumentProtection,
+    /// Document-wide settings
+    #[serde(default)]
+    pub settings: DocumentSettings,
 }
 
 impl Document {
@@ -71,6 +80,7 @@ impl Document {
             page_setup: PageSetup::default(),
             version: 0,
             protection: DocumentProtection::default(),
+            settings: DocumentSettings::default(),
         }
     }
 
@@ -0,0 +1,114 @@
+//! Document-wide settings
+//!
+//! Settings that apply to the whole document rather than to a section or
+//! paragraph: the default tab stop, automatic hyphenation, whether changes
+//! are tracked, even/odd headers, and the compatibility options that make
+//! Word lay the document out like the version that created it.
+
+use crate::DEFAULT_TAB_INTERVAL;
+use serde::{Deserialize, Serialize};
+
+/// Automatic hyphenation settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HyphenationSettings {
+    /// Whether words are hyphenated automatically
+    pub auto_hyphenate: bool,
+    /// Whether words in all capitals may be hyphenated
+    pub hyphenate_caps: bool,
+    /// Width of the zone at the end of a line where hyphenation is tried,
+    /// in points
+    pub zone: Option<f32>,
+    /// Most consecutive lines that may end in a hyphen
+    pub consecutive_limit: Option<u32>,
+}
+
+impl Default for HyphenationSettings {
+    fn default() -> Self {
+        Self {
+            auto_hyphenate: false,
+            hyphenate_caps: true,
+            zone: None,
+            consecutive_limit: None,
+        }
+    }
+}
+
+/// A named compatibility setting (`w:compatSetting`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompatSetting {
+    /// Setting name
+    pub name: String,
+    /// Namespace URI the name belongs to
+    pub uri: String,
+    /// Setting value
+    pub value: String,
+}
+
+/// Compatibility options for the application that created the document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompatibilitySettings {
+    /// Word version whose layout rules apply (11 = 2003, 12 = 2007,
+    /// 14 = 2010, 15 = 2013 and later); None if the source file didn't say
+    pub mode: Option<u32>,
+    /// Legacy layout options that are switched on, by OOXML name
+    #[serde(default)]
+    pub options: Vec<String>,
+    /// Other named settings, in document order
+    #[serde(default)]
+    pub settings: Vec<CompatSetting>,
+}
+
+impl Default for CompatibilitySettings {
+    fn default() -> Self {
+        Self {
+            mode: Some(Self::CURRENT_MODE),
+            options: Vec::new(),
+            settings: Vec::new(),
+        }
+    }
+}
+
+impl CompatibilitySettings {
+    /// Compatibility mode of documents created here
+    pub const CURRENT_MODE: u32 = 15;
+
+    /// Check if a legacy option is switched on
+    pub fn has_option(&self, name: &str) -> bool {
+        self.options.iter().any(|option| option == name)
+    }
+}
+
+/// Document-wide settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSettings {
+    /// Interval of the default tab stops, in points
+    pub default_tab_stop: f32,
+    /// Automatic hyphenation
+    #[serde(default)]
+    pub hyphenation: HyphenationSettings,
+    /// Whether changes are tracked when the document is opened
+    #[serde(default)]
+    pub track_revisions: bool,
+    /// Whether odd and even pages have different headers and footers
+    #[serde(default)]
+    pub even_and_odd_headers: bool,
+    /// Compatibility options
+    #[serde(default)]
+    pub compatibility: CompatibilitySettings,
+}
+
+impl Default for DocumentSettings {
+    fn default() -> Self {
+        Self {
+            default_tab_stop: DEFAULT_TAB_INTERVAL,
+            hyphenation: HyphenationSettings::default(),
+            track_revisions: false,
+            even_and_odd_headers: false,
+            compatibility: CompatibilitySettings::default(),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Embedded (OLE) objects
+//!
+//! Objects from other applications, such as Excel worksheets, that a
+//! document carries but can't edit. The object's data is kept unchanged so
+//! it survives a round trip, and its cached preview picture is shown in
+//! its place in the text flow.
+
+use crate::{Node, NodeId, NodeType};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A binary part carried through unchanged from the source file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddedPart {
+    /// File name of the part in the source package, e.g. "oleObject1.bin"
+    pub file_name: String,
+    /// MIME content type
+    pub content_type: String,
+    /// Part contents
+    #[serde(serialize_with = "serialize_bytes", deserialize_with = "deserialize_bytes")]
+    pub data: Vec<u8>,
+}
+
+impl EmbeddedPart {
+    /// Create a part
+    pub fn new(file_name: impl Into<String>, content_type: impl Into<String>, data: Vec<u8>) -> Self {
+        Self {
+            file_name: file_name.into(),
+            content_type: content_type.into(),
+            data,
+        }
+    }
+
+    /// File extension, lowercased
+    pub fn extension(&self) -> String {
+        self.file_name
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_lowercase())
+            .unwrap_or_default()
+    }
+}
+
+fn serialize_bytes<S: Serializer>(data: &[u8], serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(&STANDARD.encode(data))
+}
+
+fn deserialize_bytes<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<u8>, D::Error> {
+    let encoded = String::deserialize(deserializer)?;
+    STANDARD.decode(encoded).map_err(serde::de::Error::custom)
+}
+
+/// An object from another application, shown inline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddedObject {
+    /// Unique node ID
+    id: NodeId,
+    /// Parent paragraph ID
+    parent: Option<NodeId>,
+    /// OLE program ID of the application that owns the object, e.g. "Excel.Sheet.12"
+    pub prog_id: Option<String>,
+    /// Displayed width in points
+    pub width: f32,
+    /// Displayed height in points
+    pub height: f32,
+    /// Whether the object is shown as an icon rather than its content
+    #[serde(default)]
+    pub show_as_icon: bool,
+    /// The object's data; None for a linked object or if the part was missing
+    pub embedding: Option<EmbeddedPart>,
+    /// Path of the file a linked object comes from
+    #[serde(default)]
+    pub link_target: Option<String>,
+    /// Cached preview picture (usually EMF or WMF)
+    pub preview: Option<EmbeddedPart>,
+}
+
+impl EmbeddedObject {
+    /// Create an embedded object; `embedding` is None if its data is missing
+    pub fn new(prog_id: Option<String>, embedding: Option<EmbeddedPart>, width: f32, height: f32) -> Self {
+        Self {
+            id: NodeId::new(),
+            parent: None,
+            prog_id,
+            width,
+            height,
+            show_as_icon: false,
+            embedding,
+            link_target: None,
+            preview: None,
+        }
+    }
+
+    /// Create an object linked to an external file
+    pub fn linked(prog_id: Option<String>, link_target: impl Into<String>, width: f32, height: f32) -> Self {
+        Self {
+            id: NodeId::new(),
+            parent: None,
+            prog_id,
+            width,
+            height,
+            show_as_icon: false,
+            embedding: None,
+            link_target: Some(link_target.into()),
+            preview: None,
+        }
+    }
+
+    /// Set the cached preview picture
+    pub fn with_preview(mut self, preview: EmbeddedPart) -> Self {
+        self.preview = Some(preview);
+        self
+    }
+
+    /// Check if the object links to an external file
+    pub fn is_linked(&self) -> bool {
+        self.link_target.is_some()
+    }
+
+    /// Name to show for the object, from its program ID
+    pub fn display_name(&self) -> String {
+        let Some(prog_id) = self.prog_id.as_deref() else {
+            return "Object".to_string();
+        };
+        match prog_id.split('.').next().unwrap_or(prog_id) {
+            "Excel" => "Microsoft Excel Worksheet".to_string(),
+            "Word" => "Microsoft Word Document".to_string(),
+            "PowerPoint" => "Microsoft PowerPoint Presentation".to_string(),
+            "Visio" => "Microsoft Visio Drawing".to_string(),
+            "Equation" => "Equation".to_string(),
+            "Package" => "Package".to_string(),
+            "AcroExch" => "Adobe Acrobat Document".to_string(),
+            _ => prog_id.to_string(),
+        }
+    }
+}
+
+impl Node for EmbeddedObject {
+    fn id(&self) -> NodeId {
+        self.id
+    }
+
+    fn node_type(&self) -> NodeType {
+        NodeType::EmbeddedObject
+    }
+
+    fn children(&self) -> &[NodeId] {
+        &[]
+    }
+
+    fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    fn set_parent(&mut self, parent: Option<NodeId>) {
+        self.parent = parent;
+    }
+
+    fn can_have_children(&self) -> bool {
+        false
+    }
+}
@@ -3,7 +3,8 @@
 //! Fields are placeholders for dynamic content that gets calculated at render time.
 //! Common fields include page numbers, dates, file names, table of contents, etc.
 
-use crate::{Node, NodeId, NodeType, Run};
+use crate::numerals;
+use crate::{Node, NodeId, NodeType, Run, TabLeader, TabStop};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::ops::Range;
@@ -32,6 +33,16 @@ pub enum NumberFormat {
     CardinalText,
     /// Ordinal text (first, second, third...)
     OrdinalText,
+    /// Hebrew letters (א, ב, ג...)
+    Hebrew,
+    /// Arabic-Indic digits (١, ٢, ٣...)
+    ArabicIndic,
+    /// Chinese counting numerals (一, 二, 三... 十)
+    ChineseCounting,
+    /// Japanese counting numerals (一, 二, 三... 十)
+    JapaneseCounting,
+    /// Thai digits (๑, ๒, ๓...)
+    Thai,
 }
 
 impl NumberFormat {
@@ -46,6 +57,11 @@ impl NumberFormat {
             NumberFormat::Ordinal => Self::to_ordinal(number),
             NumberFormat::CardinalText => Self::to_cardinal_text(number),
             NumberFormat::OrdinalText => Self::to_ordinal_text(number),
+            NumberFormat::Hebrew => numerals::hebrew(number),
+            NumberFormat::ArabicIndic => numerals::arabic_indic(number),
+            NumberFormat::ChineseCounting => numerals::chinese_counting(number),
+            NumberFormat::JapaneseCounting => numerals::japanese_counting(number),
+            NumberFormat::Thai => numerals::thai(number),
         }
     }
 
@@ -203,6 +219,28 @@ pub enum TocTabLeader {
     Underline,
 }
 
+impl From<TocTabLeader> for TabLeader {
+    fn from(leader: TocTabLeader) -> Self {
+        match leader {
+            TocTabLeader::None => TabLeader::None,
+            TocTabLeader::Dots => TabLeader::Dot,
+            TocTabLeader::Dashes => TabLeader::Hyphen,
+            TocTabLeader::Underline => TabLeader::Underscore,
+        }
+    }
+}
+
+impl TocSwitches {
+    /// Tab stop for right-aligned page numbers at `right_edge` points
+    ///
+    /// TOC entry paragraphs carry this stop so the tab between the entry text
+    /// and its page number is drawn with the leader.
+    pub fn page_number_tab_stop(&self, right_edge: f32) -> Option<TabStop> {
+        (self.include_page_numbers && self.right_align_page_numbers)
+            .then(|| TabStop::right(right_edge).with_leader(self.tab_leader.into()))
+    }
+}
+
 // =============================================================================
 // SEQ (Sequence) Options
 // =============================================================================
@@ -882,8 +920,11 @@ pub struct TocEntry {
 /// Context information needed to evaluate fields
 #[derive(Debug, Clone, Default)]
 pub struct FieldContext {
-    /// Current page number (1-indexed)
+    /// Current page number, after any restarts in earlier sections
     pub current_page: u32,
+    /// Format of the current section's page numbers, used by PAGE fields
+    /// without a format switch
+    pub page_number_format: NumberFormat,
     /// Total number of pages
     pub total_pages: u32,
     /// Current section number (1-indexed)
@@ -935,6 +976,12 @@ impl FieldContext {
         self
     }
 
+    /// Set the current section's page number format
+    pub fn with_page_number_format(mut self, format: NumberFormat) -> Self {
+        self.page_number_format = format;
+        self
+    }
+
     /// Set section information
     pub fn with_section_info(mut self, current: u32, pages: u32) -> Self {
         self.current_section = current;
@@ -979,6 +1026,12 @@ impl FieldEvaluator {
     pub fn evaluate_instruction(instruction: &FieldInstruction, context: &FieldContext) -> String {
         match instruction {
             FieldInstruction::Page { format } => {
+                // An Arabic PAGE field has no format switch of its own
+                let format = if *format == NumberFormat::Arabic {
+                    &context.page_number_format
+                } else {
+                    format
+                };
                 format.format(context.current_page)
             }
             FieldInstruction::NumPages { format } => {
@@ -1117,7 +1170,10 @@ impl FieldEvaluator {
                 && entry.level < switches.heading_levels.end
             {
                 let indent = "  ".repeat((entry.level - 1) as usize);
-                let page_str = if switches.include_page_numbers {
+                let page_str = if switches.include_page_numbers && switches.right_align_page_numbers {
+                    // The entry's right tab stop draws the leader
+                    format!("\t{}", entry.page_number)
+                } else if switches.include_page_numbers {
                     let leader = match switches.tab_leader {
                         TocTabLeader::None => " ",
                         TocTabLeader::Dots => "...",
@@ -1354,6 +1410,10 @@ mod tests {
         assert!(result.contains("Chapter 1"));
         assert!(result.contains("Section 1.1"));
         assert!(result.contains("Chapter 2"));
+        assert!(result.contains("Chapter 2\t10"));
+
+        let stop = switches.page_number_tab_stop(468.0).unwrap();
+        assert_eq!(stop, TabStop::right(468.0).with_leader(TabLeader::Dot));
     }
 
     #[test]
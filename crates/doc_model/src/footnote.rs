@@ -11,6 +11,7 @@
 //! - Restart numbering per page, section, or continuous
 //! - Note continuation across pages with separator lines
 
+use crate::numerals;
 use crate::{Node, NodeId, NodeType, Position};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -108,6 +109,16 @@ pub enum NumberingScheme {
     UpperLetter,
     /// Symbols: *, dagger, double dagger, section, etc.
     Symbols,
+    /// Hebrew letters: א, ב, ג, ...
+    Hebrew,
+    /// Arabic-Indic digits: ١, ٢, ٣, ...
+    ArabicIndic,
+    /// Chinese counting numerals: 一, 二, 三, ... 十, ...
+    ChineseCounting,
+    /// Japanese counting numerals: 一, 二, 三, ... 十, ...
+    JapaneseCounting,
+    /// Thai digits: ๑, ๒, ๓, ...
+    Thai,
 }
 
 impl Default for NumberingScheme {
@@ -126,6 +137,11 @@ impl NumberingScheme {
             NumberingScheme::LowerLetter => Self::to_letter(number, false),
             NumberingScheme::UpperLetter => Self::to_letter(number, true),
             NumberingScheme::Symbols => Self::to_symbol(number),
+            NumberingScheme::Hebrew => numerals::hebrew(number),
+            NumberingScheme::ArabicIndic => numerals::arabic_indic(number),
+            NumberingScheme::ChineseCounting => numerals::chinese_counting(number),
+            NumberingScheme::JapaneseCounting => numerals::japanese_counting(number),
+            NumberingScheme::Thai => numerals::thai(number),
         }
     }
 
@@ -309,7 +325,8 @@ impl Default for FootnoteProperties {
 // =============================================================================
 
 /// Properties for endnote formatting
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct EndnoteProperties {
     /// Numbering scheme
     pub numbering: NumberingScheme,
@@ -319,6 +336,12 @@ pub struct EndnoteProperties {
     pub start_at: u32,
     /// Position of endnotes
     pub position: EndnotePosition,
+    /// Whether to draw a separator line above the endnotes
+    pub show_separator: bool,
+    /// Separator line length as fraction of the column width (0.0 to 1.0)
+    pub separator_length: f32,
+    /// Separator line weight (in points)
+    pub separator_weight: f32,
 }
 
 impl Default for EndnoteProperties {
@@ -328,6 +351,9 @@ impl Default for EndnoteProperties {
             restart: RestartNumbering::Continuous,
             start_at: 1,
             position: EndnotePosition::EndOfDocument,
+            show_separator: true,
+            separator_length: 0.33,
+            separator_weight: 0.5,
         }
     }
 }
@@ -0,0 +1,521 @@
+//! Freeform shape geometry
+//!
+//! Freeform and polyline shapes are a list of edit points joined by straight
+//! or cubic Bezier segments. Coordinates are in points, relative to the
+//! shape's top-left corner.
+
+use crate::{DocModelError, Point, Rect, Result, ShapeType};
+use serde::{Deserialize, Serialize};
+
+/// The segment joining one edit point to the next
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum PathSegment {
+    /// Straight line
+    #[default]
+    Line,
+    /// Cubic Bezier curve
+    Cubic {
+        /// Control point leaving the start point
+        control1: Point,
+        /// Control point entering the end point
+        control2: Point,
+    },
+}
+
+/// Segment type, as chosen in the edit points UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SegmentType {
+    Straight,
+    Curved,
+}
+
+/// Geometry of a freeform or polyline shape
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct FreeformPath {
+    /// Edit points in drawing order
+    pub points: Vec<Point>,
+    /// `segments[i]` joins `points[i]` to the next point (wrapping around to
+    /// the first when closed); missing entries are straight lines
+    #[serde(default)]
+    pub segments: Vec<PathSegment>,
+    /// Whether the last point joins back to the first
+    pub closed: bool,
+}
+
+impl FreeformPath {
+    /// Create a path of straight segments
+    pub fn new(points: Vec<Point>, closed: bool) -> Self {
+        Self {
+            points,
+            segments: Vec::new(),
+            closed,
+        }
+    }
+
+    /// Open path of straight segments
+    pub fn polyline(points: Vec<Point>) -> Self {
+        Self::new(points, false)
+    }
+
+    /// Closed path of straight segments
+    pub fn polygon(points: Vec<Point>) -> Self {
+        Self::new(points, true)
+    }
+
+    /// Geometry of a freeform shape type
+    pub fn from_shape_type(shape_type: &ShapeType) -> Option<Self> {
+        match shape_type {
+            ShapeType::Freeform { points, closed, segments } => Some(Self {
+                points: points.clone(),
+                segments: segments.clone(),
+                closed: *closed,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Number of segments between edit points
+    pub fn segment_count(&self) -> usize {
+        match self.points.len() {
+            0 | 1 => 0,
+            n if self.closed => n,
+            n => n - 1,
+        }
+    }
+
+    /// The segment starting at edit point `index`
+    pub fn segment(&self, index: usize) -> PathSegment {
+        self.segments.get(index).copied().unwrap_or_default()
+    }
+
+    /// Start and end points of a segment
+    pub fn segment_ends(&self, index: usize) -> (Point, Point) {
+        let next = (index + 1) % self.points.len();
+        (self.points[index], self.points[next])
+    }
+
+    /// Whether any segment is curved
+    pub fn has_curves(&self) -> bool {
+        self.segments.iter().any(|s| matches!(s, PathSegment::Cubic { .. }))
+    }
+
+    /// Add an edit point on a segment at parameter `t` (0 to 1), splitting
+    /// the segment in two. Returns the new point's index.
+    pub fn insert_point(&mut self, segment: usize, t: f32) -> Result<usize> {
+        self.check_segment(segment)?;
+        self.normalize();
+        let t = t.clamp(0.0, 1.0);
+        let (start, end) = self.segment_ends(segment);
+
+        let (point, first, second) = match self.segment(segment) {
+            PathSegment::Line => (lerp(start, end, t), PathSegment::Line, PathSegment::Line),
+            PathSegment::Cubic { control1, control2 } => {
+                // de Casteljau subdivision
+                let a = lerp(start, control1, t);
+                let b = lerp(control1, control2, t);
+                let c = lerp(control2, end, t);
+                let ab = lerp(a, b, t);
+                let bc = lerp(b, c, t);
+                let point = lerp(ab, bc, t);
+                (
+                    point,
+                    PathSegment::Cubic { control1: a, control2: ab },
+                    PathSegment::Cubic { control1: bc, control2: c },
+                )
+            }
+        };
+
+        self.segments[segment] = first;
+        self.segments.insert(segment + 1, second);
+        self.points.insert(segment + 1, point);
+        Ok(segment + 1)
+    }
+
+    /// Remove an edit point, joining its neighbors with one segment
+    pub fn delete_point(&mut self, index: usize) -> Result<()> {
+        self.check_point(index)?;
+        let minimum = if self.closed { 3 } else { 2 };
+        if self.points.len() <= minimum {
+            return Err(DocModelError::InvalidOperation(format!(
+                "A {} path needs at least {} points",
+                if self.closed { "closed" } else { "open" },
+                minimum
+            )));
+        }
+        self.normalize();
+
+        let last = self.points.len() - 1;
+        if !self.closed && index == 0 {
+            self.segments.remove(0);
+        } else if !self.closed && index == last {
+            self.segments.remove(last - 1);
+        } else {
+            let incoming = if index == 0 { last } else { index - 1 };
+            let (start, _) = self.segment_ends(incoming);
+            let (_, end) = self.segment_ends(index);
+            let merged = match (self.segment(incoming), self.segment(index)) {
+                (PathSegment::Line, PathSegment::Line) => PathSegment::Line,
+                (before, after) => PathSegment::Cubic {
+                    control1: match before {
+                        PathSegment::Cubic { control1, .. } => control1,
+                        PathSegment::Line => start,
+                    },
+                    control2: match after {
+                        PathSegment::Cubic { control2, .. } => control2,
+                        PathSegment::Line => end,
+                    },
+                },
+            };
+            self.segments[incoming] = merged;
+            self.segments.remove(index);
+        }
+        self.points.remove(index);
+        Ok(())
+    }
+
+    /// Move an edit point; the control points attached to it move with it
+    pub fn move_point(&mut self, index: usize, to: Point) -> Result<()> {
+        self.check_point(index)?;
+        self.normalize();
+        let from = self.points[index];
+        let (dx, dy) = (to.x - from.x, to.y - from.y);
+        let shift = |p: &mut Point| {
+            p.x += dx;
+            p.y += dy;
+        };
+
+        if index < self.segments.len() {
+            if let PathSegment::Cubic { control1, .. } = &mut self.segments[index] {
+                shift(control1);
+            }
+        }
+        let incoming = match index {
+            0 if self.closed => Some(self.points.len() - 1),
+            0 => None,
+            _ => Some(index - 1),
+        };
+        if let Some(PathSegment::Cubic { control2, .. }) = incoming.and_then(|i| self.segments.get_mut(i)) {
+            shift(control2);
+        }
+        self.points[index] = to;
+        Ok(())
+    }
+
+    /// Make a segment straight or curved. A straightened curve becomes a
+    /// line; a curved line starts with control points a third of the way
+    /// along it, so its shape is unchanged until edited.
+    pub fn set_segment_type(&mut self, segment: usize, segment_type: SegmentType) -> Result<()> {
+        self.check_segment(segment)?;
+        self.normalize();
+        let (start, end) = self.segment_ends(segment);
+        self.segments[segment] = match (segment_type, self.segment(segment)) {
+            (SegmentType::Straight, _) => PathSegment::Line,
+            (SegmentType::Curved, PathSegment::Line) => PathSegment::Cubic {
+                control1: lerp(start, end, 1.0 / 3.0),
+                control2: lerp(start, end, 2.0 / 3.0),
+            },
+            (SegmentType::Curved, curve) => curve,
+        };
+        Ok(())
+    }
+
+    /// Move a segment's control point (1 or 2)
+    pub fn move_control_point(&mut self, segment: usize, control: u8, to: Point) -> Result<()> {
+        self.check_segment(segment)?;
+        match self.segments.get_mut(segment) {
+            Some(PathSegment::Cubic { control1, .. }) if control == 1 => *control1 = to,
+            Some(PathSegment::Cubic { control2, .. }) if control == 2 => *control2 = to,
+            _ => {
+                return Err(DocModelError::InvalidOperation(format!(
+                    "Segment {} has no control point {}",
+                    segment, control
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Bounding box of the edit and control points
+    pub fn bounds(&self) -> Rect {
+        let controls = self.segments.iter().flat_map(|segment| match segment {
+            PathSegment::Cubic { control1, control2 } => vec![*control1, *control2],
+            PathSegment::Line => Vec::new(),
+        });
+        let mut points = self.points.iter().copied().chain(controls);
+        let Some(first) = points.next() else {
+            return Rect::default();
+        };
+        let (min, max) = points.fold((first, first), |(min, max), p| {
+            (
+                Point::new(min.x.min(p.x), min.y.min(p.y)),
+                Point::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        });
+        Rect::from_points(min, max)
+    }
+
+    /// Serialize as SVG-style path data ("M x y L x y C ... Z")
+    pub fn to_path_data(&self) -> String {
+        let Some(first) = self.points.first() else {
+            return String::new();
+        };
+        let mut data = format!("M {} {}", first.x, first.y);
+        for index in 0..self.segment_count() {
+            let (_, end) = self.segment_ends(index);
+            match self.segment(index) {
+                PathSegment::Line => data.push_str(&format!(" L {} {}", end.x, end.y)),
+                PathSegment::Cubic { control1, control2 } => data.push_str(&format!(
+                    " C {} {} {} {} {} {}",
+                    control1.x, control1.y, control2.x, control2.y, end.x, end.y
+                )),
+            }
+        }
+        if self.closed {
+            data.push_str(" Z");
+        }
+        data
+    }
+
+    /// Parse SVG-style path data (M, L, H, V, C, Q and Z, absolute or
+    /// relative). Only the first figure is used.
+    pub fn from_path_data(data: &str) -> Option<Self> {
+        let tokens = tokenize(data);
+        let mut path = Self::default();
+        let mut current = Point::ORIGIN;
+        let mut command = ' ';
+        let mut i = 0;
+        while i < tokens.len() {
+            if let Token::Command(c) = tokens[i] {
+                i += 1;
+                match c {
+                    'Z' | 'z' => {
+                        path.closed = true;
+                        break;
+                    }
+                    'M' | 'm' if !path.points.is_empty() => break,
+                    _ => command = c,
+                }
+            }
+            let arity = match command.to_ascii_uppercase() {
+                'M' | 'L' => 2,
+                'H' | 'V' => 1,
+                'Q' => 4,
+                'C' => 6,
+                _ => return None,
+            };
+            let args: Vec<f32> = tokens.get(i..i + arity)?.iter().map_while(Token::number).collect();
+            if args.len() < arity {
+                return None;
+            }
+            i += arity;
+
+            let relative = command.is_ascii_lowercase();
+            let point = |x: f32, y: f32| if relative { Point::new(current.x + x, current.y + y) } else { Point::new(x, y) };
+            let (segment, end) = match command.to_ascii_uppercase() {
+                'M' => {
+                    current = point(args[0], args[1]);
+                    path.points.push(current);
+                    // Further pairs are implicit line-tos
+                    command = if relative { 'l' } else { 'L' };
+                    continue;
+                }
+                'L' => (PathSegment::Line, point(args[0], args[1])),
+                'H' => (PathSegment::Line, Point::new(if relative { current.x + args[0] } else { args[0] }, current.y)),
+                'V' => (PathSegment::Line, Point::new(current.x, if relative { current.y + args[0] } else { args[0] })),
+                'C' => (
+                    PathSegment::Cubic { control1: point(args[0], args[1]), control2: point(args[2], args[3]) },
+                    point(args[4], args[5]),
+                ),
+                _ => {
+                    // Quadratic, raised to a cubic
+                    let control = point(args[0], args[1]);
+                    let end = point(args[2], args[3]);
+                    (
+                        PathSegment::Cubic {
+                            control1: lerp(current, control, 2.0 / 3.0),
+                            control2: lerp(end, control, 2.0 / 3.0),
+                        },
+                        end,
+                    )
+                }
+            };
+            if path.points.is_empty() {
+                return None;
+            }
+            path.segments.push(segment);
+            path.points.push(end);
+            current = end;
+        }
+
+        // An explicit segment back to the start is the closing segment
+        if path.closed && path.points.len() > 2 && path.points.first() == path.points.last() {
+            path.points.pop();
+        }
+        path.segments.truncate(path.segment_count());
+        (!path.points.is_empty()).then_some(path)
+    }
+
+    /// Pad `segments` to one entry per segment
+    fn normalize(&mut self) {
+        let count = self.segment_count();
+        self.segments.resize(count, PathSegment::Line);
+    }
+
+    fn check_point(&self, index: usize) -> Result<()> {
+        if index >= self.points.len() {
+            return Err(DocModelError::InvalidOperation(format!("Edit point {} out of range", index)));
+        }
+        Ok(())
+    }
+
+    fn check_segment(&self, index: usize) -> Result<()> {
+        if index >= self.segment_count() {
+            return Err(DocModelError::InvalidOperation(format!("Segment {} out of range", index)));
+        }
+        Ok(())
+    }
+}
+
+impl From<FreeformPath> for ShapeType {
+    fn from(path: FreeformPath) -> Self {
+        ShapeType::Freeform {
+            points: path.points,
+            closed: path.closed,
+            segments: path.segments,
+        }
+    }
+}
+
+fn lerp(a: Point, b: Point, t: f32) -> Point {
+    Point::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Token {
+    Command(char),
+    Number(f32),
+}
+
+impl Token {
+    fn number(&self) -> Option<f32> {
+        match self {
+            Token::Number(n) => Some(*n),
+            Token::Command(_) => None,
+        }
+    }
+}
+
+/// Split path data into commands and numbers
+fn tokenize(data: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut number = String::new();
+    let flush = |number: &mut String, tokens: &mut Vec<Token>| {
+        if let Ok(n) = number.parse() {
+            tokens.push(Token::Number(n));
+        }
+        number.clear();
+    };
+    for c in data.chars() {
+        match c {
+            'e' | 'E' if !number.is_empty() => number.push(c),
+            c if c.is_ascii_alphabetic() => {
+                flush(&mut number, &mut tokens);
+                tokens.push(Token::Command(c));
+            }
+            '-' | '+' if !number.is_empty() && !number.ends_with(['e', 'E']) => {
+                flush(&mut number, &mut tokens);
+                number.push(c);
+            }
+            '.' if number.contains('.') => {
+                flush(&mut number, &mut tokens);
+                number.push(c);
+            }
+            c if c.is_ascii_digit() || matches!(c, '.' | '-' | '+') => number.push(c),
+            _ => flush(&mut number, &mut tokens),
+        }
+    }
+    flush(&mut number, &mut tokens);
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> FreeformPath {
+        FreeformPath::polygon(vec![
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 0.0),
+            Point::new(10.0, 10.0),
+            Point::new(0.0, 10.0),
+        ])
+    }
+
+    #[test]
+    fn test_insert_and_delete_points() {
+        let mut path = square();
+        assert_eq!(path.insert_point(3, 0.5).unwrap(), 4);
+        assert_eq!(path.points[4], Point::new(0.0, 5.0));
+        assert_eq!(path.segment_count(), 5);
+
+        path.delete_point(4).unwrap();
+        assert_eq!(path, FreeformPath { segments: vec![PathSegment::Line; 4], ..square() });
+
+        let mut line = FreeformPath::polyline(vec![Point::new(0.0, 0.0), Point::new(5.0, 5.0)]);
+        assert!(line.delete_point(0).is_err());
+        assert!(line.insert_point(1, 0.5).is_err());
+    }
+
+    #[test]
+    fn test_split_curve_keeps_shape() {
+        let mut path = square();
+        path.set_segment_type(0, SegmentType::Curved).unwrap();
+        path.move_control_point(0, 1, Point::new(0.0, -6.0)).unwrap();
+        path.move_control_point(0, 2, Point::new(10.0, -6.0)).unwrap();
+
+        let index = path.insert_point(0, 0.5).unwrap();
+        // Midpoint of the curve
+        assert_eq!(path.points[index], Point::new(5.0, -4.5));
+
+        // Deleting the split point restores the outer control points
+        path.delete_point(index).unwrap();
+        assert_eq!(
+            path.segment(0),
+            PathSegment::Cubic { control1: Point::new(0.0, -3.0), control2: Point::new(10.0, -3.0) }
+        );
+        assert_eq!(path.bounds(), Rect::new(0.0, -3.0, 10.0, 13.0));
+    }
+
+    #[test]
+    fn test_move_point_moves_attached_controls() {
+        let mut path = square();
+        path.set_segment_type(0, SegmentType::Curved).unwrap();
+        path.set_segment_type(3, SegmentType::Curved).unwrap();
+        path.move_point(0, Point::new(-3.0, 0.0)).unwrap();
+
+        let PathSegment::Cubic { control1, .. } = path.segment(0) else { panic!() };
+        assert!((control1.x - (10.0 / 3.0 - 3.0)).abs() < 1e-5);
+        let PathSegment::Cubic { control2, .. } = path.segment(3) else { panic!() };
+        assert!((control2.x + 3.0).abs() < 1e-5);
+
+        path.set_segment_type(0, SegmentType::Straight).unwrap();
+        assert_eq!(path.segment(0), PathSegment::Line);
+    }
+
+    #[test]
+    fn test_path_data_round_trip() {
+        let mut path = square();
+        path.set_segment_type(1, SegmentType::Curved).unwrap();
+        let data = path.to_path_data();
+        assert!(data.starts_with("M 0 0 L 10 0 C "));
+        assert!(data.ends_with(" Z"));
+        assert_eq!(FreeformPath::from_path_data(&data).unwrap(), path);
+
+        let parsed = FreeformPath::from_path_data("m1,1 h9 v9 q-4.5,4.5 -9,0 L1 1z").unwrap();
+        assert!(parsed.closed);
+        assert_eq!(parsed.points.len(), 4);
+        assert_eq!(parsed.points[2], Point::new(10.0, 10.0));
+        assert!(matches!(parsed.segment(2), PathSegment::Cubic { .. }));
+        assert_eq!(parsed.segment(3), PathSegment::Line);
+    }
+}
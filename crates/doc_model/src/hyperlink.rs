@@ -109,6 +109,46 @@ impl HyperlinkTarget {
     }
 }
 
+/// Check if a URL is relative (has no scheme and isn't a UNC path)
+pub fn is_relative_url(url: &str) -> bool {
+    if url.is_empty() || url.starts_with('#') || url.starts_with("\\\\") || url.starts_with("//") {
+        return false;
+    }
+    // A scheme is letters, digits, '+', '-' or '.' before the first ':'
+    // (a single letter is a drive, as in C:\)
+    match url.find(':') {
+        Some(colon) if colon > 1 => !url[..colon]
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')),
+        Some(1) => !url.as_bytes()[0].is_ascii_alphabetic(),
+        _ => true,
+    }
+}
+
+/// Resolve a relative URL against a hyperlink base
+///
+/// Absolute URLs and links without a base are returned unchanged. A link
+/// starting with '/' replaces the path of the base.
+pub fn resolve_hyperlink(url: &str, base: Option<&str>) -> String {
+    let base = match base.map(str::trim) {
+        Some(base) if !base.is_empty() && is_relative_url(url) => base,
+        _ => return url.to_string(),
+    };
+    if let Some(path) = url.strip_prefix('/') {
+        let origin_end = base
+            .find("://")
+            .and_then(|i| base[i + 3..].find('/').map(|j| i + 3 + j))
+            .unwrap_or(base.len());
+        return format!("{}/{}", &base[..origin_end], path);
+    }
+    let separator = if base.contains('\\') && !base.contains('/') { '\\' } else { '/' };
+    if base.ends_with(separator) {
+        format!("{}{}", base, url)
+    } else {
+        format!("{}{}{}", base, separator, url)
+    }
+}
+
 /// Errors that can occur during hyperlink validation
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum HyperlinkValidationError {
@@ -293,4 +333,19 @@ mod tests {
         );
         assert_eq!(hyperlink.tooltip, Some("Click to visit example.com".to_string()));
     }
+
+    #[test]
+    fn test_resolve_against_hyperlink_base() {
+        assert!(is_relative_url("docs/a.html"));
+        assert!(!is_relative_url("https://example.com"));
+        assert!(!is_relative_url("C:\\files\\a.docx"));
+        assert!(!is_relative_url("\\\\server\\share"));
+
+        let base = Some("https://example.com/site/");
+        assert_eq!(resolve_hyperlink("docs/a.html", base), "https://example.com/site/docs/a.html");
+        assert_eq!(resolve_hyperlink("/top.html", base), "https://example.com/top.html");
+        assert_eq!(resolve_hyperlink("mailto:a@b.com", base), "mailto:a@b.com");
+        assert_eq!(resolve_hyperlink("a.docx", Some("\\\\server\\share")), "\\\\server\\share\\a.docx");
+        assert_eq!(resolve_hyperlink("a.docx", None), "a.docx");
+    }
 }
@@ -274,6 +274,9 @@ pub struct ImageProperties {
     pub crop: Option<CropRect>,
     /// Whether to lock aspect ratio during resize
     pub lock_aspect_ratio: bool,
+    /// Keep this image at full quality when pictures are compressed
+    #[serde(default)]
+    pub skip_compression: bool,
 }
 
 impl ImageProperties {
@@ -292,6 +295,7 @@ impl ImageProperties {
             rotation: 0.0,
             crop: None,
             lock_aspect_ratio: true,
+            skip_compression: false,
         }
     }
 
@@ -305,6 +309,7 @@ impl ImageProperties {
             rotation: 0.0,
             crop: None,
             lock_aspect_ratio: true,
+            skip_compression: false,
         }
     }
 }
@@ -319,6 +324,7 @@ impl Default for ImageProperties {
             rotation: 0.0,
             crop: None,
             lock_aspect_ratio: true,
+            skip_compression: false,
         }
     }
 }
@@ -334,6 +340,9 @@ pub struct ImageNode {
     pub resource_id: ResourceId,
     /// Alternative text for accessibility
     pub alt_text: Option<String>,
+    /// Whether the image is decorative and skipped by screen readers
+    #[serde(default)]
+    pub decorative: bool,
     /// Image title (shown as tooltip)
     pub title: Option<String>,
     /// Image properties
@@ -352,6 +361,7 @@ impl ImageNode {
             parent: None,
             resource_id,
             alt_text: None,
+            decorative: false,
             title: None,
             properties: ImageProperties::default(),
             original_width,
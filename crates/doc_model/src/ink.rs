@@ -0,0 +1,394 @@
+//! Ink annotations
+//!
+//! Freehand pen strokes captured from a stylus, mouse or touch screen. An ink
+//! node is anchored to a paragraph and drawn over the page; its stroke
+//! coordinates are in points, relative to the top-left of the anchor
+//! paragraph, so the ink moves with the text it annotates.
+
+use crate::{Node, NodeId, NodeType, Point, Rect, ShapeColor};
+use serde::{Deserialize, Serialize};
+
+/// Pressure recorded for devices that don't report one
+pub const DEFAULT_INK_PRESSURE: f32 = 0.5;
+
+/// Interpolated points per segment when smoothing a stroke
+const SMOOTHING_STEPS: usize = 4;
+
+/// Vertices used for each round end cap of a stroke outline
+const CAP_STEPS: usize = 6;
+
+fn default_pressure() -> f32 {
+    DEFAULT_INK_PRESSURE
+}
+
+/// A sampled pen position
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct InkPoint {
+    pub x: f32,
+    pub y: f32,
+    /// Pen pressure from 0.0 to 1.0
+    #[serde(default = "default_pressure")]
+    pub pressure: f32,
+}
+
+impl InkPoint {
+    pub const fn new(x: f32, y: f32, pressure: f32) -> Self {
+        Self { x, y, pressure }
+    }
+
+    /// A point from a device without pressure
+    pub const fn at(x: f32, y: f32) -> Self {
+        Self::new(x, y, DEFAULT_INK_PRESSURE)
+    }
+
+    fn point(&self) -> Point {
+        Point::new(self.x, self.y)
+    }
+}
+
+/// A single pen stroke
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InkStroke {
+    /// Sampled pen positions in drawing order
+    pub points: Vec<InkPoint>,
+    /// Stroke color; highlighters use a translucent color
+    pub color: ShapeColor,
+    /// Pen width in points at the default pressure
+    pub width: f32,
+}
+
+impl InkStroke {
+    /// Create a stroke
+    pub fn new(points: Vec<InkPoint>, color: ShapeColor, width: f32) -> Self {
+        Self { points, color, width }
+    }
+
+    /// Create a highlighter stroke, drawn half transparent
+    pub fn highlighter(points: Vec<InkPoint>, color: ShapeColor, width: f32) -> Self {
+        Self::new(points, ShapeColor { a: 128, ..color }, width)
+    }
+
+    /// Pen width at a pressure; light strokes are thinner
+    pub fn width_at(&self, pressure: f32) -> f32 {
+        self.width * (0.5 + pressure.clamp(0.0, 1.0))
+    }
+
+    /// Bounding box of the drawn stroke, including its width
+    pub fn bounds(&self) -> Option<Rect> {
+        let first = self.points.first()?;
+        let (mut min, mut max) = (first.point(), first.point());
+        let mut radius: f32 = 0.0;
+        for point in &self.points {
+            min = Point::new(min.x.min(point.x), min.y.min(point.y));
+            max = Point::new(max.x.max(point.x), max.y.max(point.y));
+            radius = radius.max(self.width_at(point.pressure) / 2.0);
+        }
+        Some(Rect::new(
+            min.x - radius,
+            min.y - radius,
+            max.x - min.x + radius * 2.0,
+            max.y - min.y + radius * 2.0,
+        ))
+    }
+
+    /// Distance from a point to the edge of the drawn stroke (zero inside it)
+    pub fn distance_to(&self, point: Point) -> f32 {
+        let mut distance = f32::INFINITY;
+        let mut previous: Option<&InkPoint> = None;
+        for current in &self.points {
+            let start = previous.unwrap_or(current);
+            let (to_center, t) = segment_distance(point, start.point(), current.point());
+            let pressure = start.pressure + (current.pressure - start.pressure) * t;
+            distance = distance.min(to_center - self.width_at(pressure) / 2.0);
+            previous = Some(current);
+        }
+        distance.max(0.0)
+    }
+
+    /// Whether a point is on the stroke, within `tolerance` points
+    pub fn hit_test(&self, point: Point, tolerance: f32) -> bool {
+        self.distance_to(point) <= tolerance
+    }
+
+    /// Move every point of the stroke
+    pub fn translate(&mut self, dx: f32, dy: f32) {
+        for point in &mut self.points {
+            point.x += dx;
+            point.y += dy;
+        }
+    }
+
+    /// The pen path smoothed with a Catmull-Rom spline through the samples
+    pub fn smoothed_points(&self) -> Vec<InkPoint> {
+        let points = &self.points;
+        if points.len() < 3 {
+            return points.clone();
+        }
+        let mut smoothed = Vec::with_capacity((points.len() - 1) * SMOOTHING_STEPS + 1);
+        for index in 0..points.len() - 1 {
+            let p0 = points[index.saturating_sub(1)];
+            let p1 = points[index];
+            let p2 = points[index + 1];
+            let p3 = points[(index + 2).min(points.len() - 1)];
+            for step in 0..SMOOTHING_STEPS {
+                let t = step as f32 / SMOOTHING_STEPS as f32;
+                smoothed.push(InkPoint::new(
+                    catmull_rom(p0.x, p1.x, p2.x, p3.x, t),
+                    catmull_rom(p0.y, p1.y, p2.y, p3.y, t),
+                    p1.pressure + (p2.pressure - p1.pressure) * t,
+                ));
+            }
+        }
+        smoothed.extend(points.last().copied());
+        smoothed
+    }
+
+    /// Closed outline of the smoothed stroke with round ends, its width
+    /// following the pen pressure. Fill it with the nonzero rule.
+    pub fn outline(&self) -> Vec<Point> {
+        let mut centers = self.smoothed_points();
+        centers.dedup_by(|b, a| (a.x - b.x).hypot(a.y - b.y) < 0.01);
+        let Some(&first) = centers.first() else {
+            return Vec::new();
+        };
+
+        // A tap is a dot
+        if centers.len() == 1 {
+            let radius = self.width_at(first.pressure) / 2.0;
+            let steps = CAP_STEPS * 2;
+            return (0..steps)
+                .map(|step| {
+                    let angle = std::f32::consts::TAU * step as f32 / steps as f32;
+                    Point::new(first.x + radius * angle.cos(), first.y + radius * angle.sin())
+                })
+                .collect();
+        }
+
+        // Unit direction of travel and half width at each center point
+        let last = centers.len() - 1;
+        let frame: Vec<(Point, f32)> = (0..centers.len())
+            .map(|index| {
+                let before = centers[index.saturating_sub(1)];
+                let after = centers[(index + 1).min(last)];
+                let (dx, dy) = (after.x - before.x, after.y - before.y);
+                let length = dx.hypot(dy).max(f32::EPSILON);
+                (Point::new(dx / length, dy / length), self.width_at(centers[index].pressure) / 2.0)
+            })
+            .collect();
+        let offset = |index: usize, normal: f32, along: f32| {
+            let (direction, radius) = frame[index];
+            let center = centers[index];
+            Point::new(
+                center.x + radius * (-direction.y * normal + direction.x * along),
+                center.y + radius * (direction.x * normal + direction.y * along),
+            )
+        };
+        // Sweep half a circle around an end, from one side to the other
+        let cap = |index: usize, backwards: bool| {
+            (1..CAP_STEPS).map(move |step| {
+                let angle = std::f32::consts::PI * step as f32 / CAP_STEPS as f32;
+                if backwards {
+                    offset(index, -angle.cos(), -angle.sin())
+                } else {
+                    offset(index, angle.cos(), angle.sin())
+                }
+            })
+        };
+
+        let mut outline = Vec::with_capacity(centers.len() * 2 + CAP_STEPS * 2);
+        outline.extend(cap(0, true));
+        outline.extend((0..=last).map(|index| offset(index, 1.0, 0.0)));
+        outline.extend(cap(last, false));
+        outline.extend((0..=last).rev().map(|index| offset(index, -1.0, 0.0)));
+        outline
+    }
+}
+
+fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    let (t2, t3) = (t * t, t * t * t);
+    0.5 * (2.0 * p1 + (p2 - p0) * t + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2 + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+/// Distance from a point to a segment, and how far along the segment the
+/// nearest point is
+fn segment_distance(point: Point, start: Point, end: Point) -> (f32, f32) {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared > 0.0 {
+        (((point.x - start.x) * dx + (point.y - start.y) * dy) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let nearest = Point::new(start.x + dx * t, start.y + dy * t);
+    ((point.x - nearest.x).hypot(point.y - nearest.y), t)
+}
+
+/// A group of ink strokes anchored to a paragraph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InkNode {
+    /// Unique node ID
+    id: NodeId,
+    /// Anchor paragraph
+    parent: Option<NodeId>,
+    /// Strokes in drawing order
+    pub strokes: Vec<InkStroke>,
+    /// Alternative text for accessibility
+    pub alt_text: Option<String>,
+}
+
+impl InkNode {
+    /// Create an ink node from strokes
+    pub fn new(strokes: Vec<InkStroke>) -> Self {
+        Self {
+            id: NodeId::new(),
+            parent: None,
+            strokes,
+            alt_text: None,
+        }
+    }
+
+    /// Set the alternative text
+    pub fn with_alt_text(mut self, alt_text: impl Into<String>) -> Self {
+        self.alt_text = Some(alt_text.into());
+        self
+    }
+
+    /// Whether every stroke has been erased
+    pub fn is_empty(&self) -> bool {
+        self.strokes.is_empty()
+    }
+
+    /// Bounding box of all strokes, relative to the anchor paragraph
+    pub fn bounds(&self) -> Option<Rect> {
+        self.strokes.iter().filter_map(InkStroke::bounds).reduce(|a, b| a.union(&b))
+    }
+
+    /// The topmost stroke within `tolerance` points of a point
+    pub fn stroke_at(&self, point: Point, tolerance: f32) -> Option<usize> {
+        self.strokes.iter().rposition(|stroke| stroke.hit_test(point, tolerance))
+    }
+
+    /// Strokes lying entirely inside a rectangle (marquee selection)
+    pub fn strokes_in_rect(&self, rect: Rect) -> Vec<usize> {
+        self.strokes
+            .iter()
+            .enumerate()
+            .filter(|(_, stroke)| stroke.points.iter().all(|point| rect.contains(point.point())))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Remove strokes by index, returning them in their original order
+    pub fn remove_strokes(&mut self, indices: &[usize]) -> Vec<InkStroke> {
+        let mut index = 0;
+        let mut removed = Vec::new();
+        self.strokes.retain(|stroke| {
+            let keep = !indices.contains(&index);
+            if !keep {
+                removed.push(stroke.clone());
+            }
+            index += 1;
+            keep
+        });
+        removed
+    }
+
+    /// Stroke eraser: remove every stroke touched by a circle
+    pub fn erase_at(&mut self, point: Point, radius: f32) -> Vec<InkStroke> {
+        let touched: Vec<usize> = self
+            .strokes
+            .iter()
+            .enumerate()
+            .filter(|(_, stroke)| stroke.hit_test(point, radius))
+            .map(|(index, _)| index)
+            .collect();
+        self.remove_strokes(&touched)
+    }
+
+    /// Move strokes by an offset
+    pub fn move_strokes(&mut self, indices: &[usize], dx: f32, dy: f32) {
+        for index in indices {
+            if let Some(stroke) = self.strokes.get_mut(*index) {
+                stroke.translate(dx, dy);
+            }
+        }
+    }
+}
+
+impl Node for InkNode {
+    fn id(&self) -> NodeId {
+        self.id
+    }
+
+    fn node_type(&self) -> NodeType {
+        NodeType::Ink
+    }
+
+    fn children(&self) -> &[NodeId] {
+        &[]
+    }
+
+    fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    fn set_parent(&mut self, parent: Option<NodeId>) {
+        self.parent = parent;
+    }
+
+    fn can_have_children(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(x1: f32, x2: f32, y: f32) -> InkStroke {
+        InkStroke::new(
+            vec![InkPoint::at(x1, y), InkPoint::new((x1 + x2) / 2.0, y, 1.0), InkPoint::at(x2, y)],
+            ShapeColor::BLACK,
+            2.0,
+        )
+    }
+
+    #[test]
+    fn test_outline_follows_pressure() {
+        let stroke = line(0.0, 20.0, 10.0);
+        let smoothed = stroke.smoothed_points();
+        assert_eq!(smoothed.len(), 2 * SMOOTHING_STEPS + 1);
+        // A straight stroke stays straight
+        assert!(smoothed.iter().all(|point| (point.y - 10.0).abs() < 1e-4));
+
+        let outline = stroke.outline();
+        let half_width_at = |x: f32| {
+            outline
+                .iter()
+                .filter(|point| (point.x - x).abs() < 0.01)
+                .map(|point| (point.y - 10.0).abs())
+                .fold(0.0, f32::max)
+        };
+        // Full pressure in the middle is thicker than the default at the ends
+        assert!((half_width_at(10.0) - 1.5).abs() < 0.01);
+        assert!((half_width_at(0.0) - 1.0).abs() < 0.01);
+        // Round caps extend past the ends
+        let min_x = outline.iter().map(|point| point.x).fold(f32::INFINITY, f32::min);
+        assert!((min_x + 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_hit_test_select_and_erase() {
+        let mut ink = InkNode::new(vec![line(0.0, 20.0, 10.0), line(0.0, 20.0, 30.0)]);
+        let bounds = ink.bounds().unwrap();
+        assert_eq!((bounds.y, bounds.height), (8.5, 23.0));
+
+        assert_eq!(ink.stroke_at(Point::new(5.0, 12.0), 1.5), Some(0));
+        assert_eq!(ink.stroke_at(Point::new(5.0, 20.0), 1.5), None);
+        assert_eq!(ink.strokes_in_rect(Rect::new(-1.0, 25.0, 30.0, 10.0)), vec![1]);
+
+        let erased = ink.erase_at(Point::new(10.0, 28.0), 1.0);
+        assert_eq!(erased, vec![line(0.0, 20.0, 30.0)]);
+        assert_eq!(ink.strokes.len(), 1);
+    }
+}
@@ -5,6 +5,7 @@
 
 mod node;
 mod document;
+mod document_settings;
 mod paragraph;
 mod run;
 mod selection;
@@ -16,9 +17,14 @@ pub mod style;
 mod image;
 mod bookmark;
 pub mod table;
+pub mod table_style;
 pub mod list;
 pub mod shape;
+mod freeform;
 pub mod textbox;
+mod ink;
+mod embedded_object;
+mod canvas;
 pub mod section;
 pub mod field;
 mod comment;
@@ -28,9 +34,18 @@ pub mod footnote;
 pub mod crossref;
 pub mod content_control;
 pub mod protection;
+mod theme;
+mod tab_stop;
+mod text_effects;
+mod watermark;
+mod preserved_markup;
+mod building_block;
+mod ruby;
+mod numerals;
 
 pub use node::*;
 pub use document::*;
+pub use document_settings::*;
 pub use paragraph::*;
 pub use run::*;
 pub use selection::*;
@@ -42,9 +57,14 @@ pub use style::*;
 pub use image::*;
 pub use bookmark::*;
 pub use table::*;
+pub use table_style::*;
 pub use list::*;
 pub use shape::*;
+pub use freeform::*;
 pub use textbox::*;
+pub use ink::*;
+pub use embedded_object::*;
+pub use canvas::*;
 pub use section::*;
 pub use field::*;
 pub use comment::*;
@@ -54,3 +74,10 @@ pub use footnote::*;
 pub use crossref::*;
 pub use content_control::*;
 pub use protection::*;
+pub use theme::*;
+pub use tab_stop::*;
+pub use text_effects::*;
+pub use watermark::*;
+pub use preserved_markup::*;
+pub use building_block::*;
+pub use ruby::*;
@@ -6,6 +6,7 @@
 //! - Multi-level lists (up to 9 levels, 0-8)
 //! - Various number formats (decimal, roman, letters, bullets)
 
+use crate::numerals;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -75,6 +76,16 @@ pub enum NumberFormat {
     CardinalText,
     /// Ordinal text: First, Second, Third, ...
     OrdinalText,
+    /// Hebrew letters: א, ב, ג, ...
+    Hebrew,
+    /// Arabic-Indic digits: ١, ٢, ٣, ...
+    ArabicIndic,
+    /// Chinese counting numerals: 一, 二, 三, ... 十, ...
+    ChineseCounting,
+    /// Japanese counting numerals: 一, 二, 三, ... 十, ...
+    JapaneseCounting,
+    /// Thai digits: ๑, ๒, ๓, ...
+    Thai,
 }
 
 impl NumberFormat {
@@ -97,6 +108,11 @@ impl NumberFormat {
             NumberFormat::Ordinal => format_ordinal(value),
             NumberFormat::CardinalText => format_cardinal_text(value),
             NumberFormat::OrdinalText => format_ordinal_text(value),
+            NumberFormat::Hebrew => numerals::hebrew(value),
+            NumberFormat::ArabicIndic => numerals::arabic_indic(value),
+            NumberFormat::ChineseCounting => numerals::chinese_counting(value),
+            NumberFormat::JapaneseCounting => numerals::japanese_counting(value),
+            NumberFormat::Thai => numerals::thai(value),
         }
     }
 }
@@ -20,6 +20,9 @@ pub enum NodeType {
     Field,
     Bookmark,
     ContentControl,
+    Ink,
+    EmbeddedObject,
+    Canvas,
 }
 
 /// Common interface for all document nodes
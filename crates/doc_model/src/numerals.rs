@@ -0,0 +1,163 @@
+//! Non-Latin numeral systems
+//!
+//! Formatting shared by list, field, page and note numbering. Numbers a
+//! system can't express fall back to Arabic digits, as Roman numerals do
+//! beyond 3999.
+
+/// Hebrew letter numerals (א, ב, ג, ... י, יא, ... ק, ... ת, תק)
+///
+/// 15 and 16 are written טו and טז rather than spelling a divine name.
+/// Hundreds above 400 repeat ת. Numbers from 1000 fall back to Arabic.
+pub(crate) fn hebrew(number: u32) -> String {
+    const ONES: [char; 9] = ['א', 'ב', 'ג', 'ד', 'ה', 'ו', 'ז', 'ח', 'ט'];
+    const TENS: [char; 9] = ['י', 'כ', 'ל', 'מ', 'נ', 'ס', 'ע', 'פ', 'צ'];
+    const HUNDREDS: [char; 4] = ['ק', 'ר', 'ש', 'ת'];
+
+    if number == 0 || number >= 1000 {
+        return number.to_string();
+    }
+
+    let mut result = String::new();
+    let mut hundreds = number / 100;
+    while hundreds > 4 {
+        result.push('ת');
+        hundreds -= 4;
+    }
+    if hundreds > 0 {
+        result.push(HUNDREDS[hundreds as usize - 1]);
+    }
+
+    match number % 100 {
+        15 => result.push_str("טו"),
+        16 => result.push_str("טז"),
+        rest => {
+            if rest >= 10 {
+                result.push(TENS[(rest / 10) as usize - 1]);
+            }
+            if rest % 10 > 0 {
+                result.push(ONES[(rest % 10) as usize - 1]);
+            }
+        }
+    }
+    result
+}
+
+/// Arabic-Indic digits (١, ٢, ٣, ... ١٠)
+pub(crate) fn arabic_indic(number: u32) -> String {
+    map_digits(number, '\u{0660}')
+}
+
+/// Thai digits (๑, ๒, ๓, ... ๑๐)
+pub(crate) fn thai(number: u32) -> String {
+    map_digits(number, '\u{0E50}')
+}
+
+/// Chinese counting numerals (一, 二, ... 十, 十一, ... 一百〇一)
+///
+/// A leading 一 is dropped only before 十, and skipped places are marked
+/// with 〇.
+pub(crate) fn chinese_counting(number: u32) -> String {
+    ideographic_counting(number, false)
+}
+
+/// Japanese counting numerals (一, 二, ... 十, 十一, ... 百一)
+///
+/// 一 is dropped before 十, 百 and 千, and skipped places are left out.
+pub(crate) fn japanese_counting(number: u32) -> String {
+    ideographic_counting(number, true)
+}
+
+/// Replace the decimal digits of a number, starting from the script's zero
+fn map_digits(number: u32, zero: char) -> String {
+    number
+        .to_string()
+        .chars()
+        .map(|digit| {
+            let value = digit.to_digit(10).unwrap_or(0);
+            char::from_u32(zero as u32 + value).unwrap_or(digit)
+        })
+        .collect()
+}
+
+const IDEOGRAPHIC_DIGITS: [char; 10] = ['〇', '一', '二', '三', '四', '五', '六', '七', '八', '九'];
+
+/// Counting numerals below 10^8, grouped in myriads (万)
+fn ideographic_counting(number: u32, japanese: bool) -> String {
+    if number == 0 {
+        return IDEOGRAPHIC_DIGITS[0].to_string();
+    }
+    if number >= 100_000_000 {
+        return number.to_string();
+    }
+
+    let (high, low) = (number / 10_000, number % 10_000);
+    let mut result = String::new();
+    if high > 0 {
+        result.push_str(&myriad_group(high, japanese, true));
+        result.push('万');
+        if low > 0 && low < 1000 && !japanese {
+            result.push(IDEOGRAPHIC_DIGITS[0]);
+        }
+    }
+    if low > 0 {
+        result.push_str(&myriad_group(low, japanese, high == 0));
+    }
+    result
+}
+
+/// Counting numerals for 1..=9999
+fn myriad_group(group: u32, japanese: bool, leading: bool) -> String {
+    const UNITS: [(u32, Option<char>); 4] = [(1000, Some('千')), (100, Some('百')), (10, Some('十')), (1, None)];
+
+    let mut result = String::new();
+    let mut pending_zero = false;
+    for (value, unit) in UNITS {
+        let digit = (group / value) % 10;
+        if digit == 0 {
+            pending_zero |= !result.is_empty();
+            continue;
+        }
+        if pending_zero && !japanese {
+            result.push(IDEOGRAPHIC_DIGITS[0]);
+        }
+        pending_zero = false;
+
+        // 十 alone means ten; Japanese also writes 百 and 千 alone
+        let first = leading && result.is_empty();
+        let drop_one = digit == 1 && unit.is_some() && (japanese || (value == 10 && first));
+        if !drop_one {
+            result.push(IDEOGRAPHIC_DIGITS[digit as usize]);
+        }
+        if let Some(unit) = unit {
+            result.push(unit);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_latin_numerals() {
+        assert_eq!(hebrew(1), "א");
+        assert_eq!(hebrew(15), "טו");
+        assert_eq!(hebrew(42), "מב");
+        assert_eq!(hebrew(745), "תשמה");
+        assert_eq!(arabic_indic(2024), "٢٠٢٤");
+        assert_eq!(thai(17), "๑๗");
+
+        assert_eq!(chinese_counting(10), "十");
+        assert_eq!(chinese_counting(11), "十一");
+        assert_eq!(chinese_counting(21), "二十一");
+        assert_eq!(chinese_counting(101), "一百〇一");
+        assert_eq!(chinese_counting(110), "一百一十");
+        assert_eq!(chinese_counting(10_005), "一万〇五");
+
+        assert_eq!(japanese_counting(10), "十");
+        assert_eq!(japanese_counting(101), "百一");
+        assert_eq!(japanese_counting(1234), "千二百三十四");
+        assert_eq!(japanese_counting(10_005), "一万五");
+    }
+}
@@ -0,0 +1,137 @@
+//! Preserved markup ("round-trip bag")
+//!
+//! Elements and attributes from an imported file that the document model
+//! doesn't represent yet are kept verbatim, attached to the nearest node,
+//! so that saving back to the same format writes them where they were
+//! instead of dropping them. Markup is kept in the source format (OOXML
+//! for DOCX) and is only meaningful to that format's writer.
+
+use crate::NodeId;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Where preserved markup goes relative to its node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind", content = "offset")]
+pub enum MarkupPlacement {
+    /// Before the node, as a sibling
+    Before,
+    /// After the node, as a sibling
+    After,
+    /// Inside the node's properties (e.g. `w:pPr`)
+    Properties,
+    /// Inside the node, at a character offset into its text
+    Content(usize),
+}
+
+/// A preserved element
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreservedElement {
+    /// Where the element goes
+    pub placement: MarkupPlacement,
+    /// The element's markup, verbatim
+    pub markup: String,
+}
+
+impl PreservedElement {
+    /// Create a preserved element
+    pub fn new(placement: MarkupPlacement, markup: impl Into<String>) -> Self {
+        Self {
+            placement,
+            markup: markup.into(),
+        }
+    }
+}
+
+/// Preserved markup for a whole document
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreservedMarkup {
+    /// Elements by the node they are attached to, in document order
+    #[serde(default)]
+    elements: HashMap<NodeId, Vec<PreservedElement>>,
+    /// Attributes of the node's own element, as (qualified name, value)
+    #[serde(default)]
+    attributes: HashMap<NodeId, Vec<(String, String)>>,
+    /// Namespace declarations the markup needs, by prefix
+    #[serde(default)]
+    namespaces: BTreeMap<String, String>,
+    /// Namespace prefixes a reader may ignore if it doesn't know them
+    #[serde(default)]
+    ignorable: Vec<String>,
+}
+
+impl PreservedMarkup {
+    /// Create an empty bag
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if nothing is preserved
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty() && self.attributes.is_empty()
+    }
+
+    /// Attach an element to a node
+    pub fn add_element(&mut self, node_id: NodeId, element: PreservedElement) {
+        self.elements.entry(node_id).or_default().push(element);
+    }
+
+    /// Elements attached to a node
+    pub fn elements(&self, node_id: NodeId) -> &[PreservedElement] {
+        self.elements.get(&node_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Elements attached to a node at a placement
+    pub fn elements_at(&self, node_id: NodeId, placement: MarkupPlacement) -> impl Iterator<Item = &str> {
+        self.elements(node_id)
+            .iter()
+            .filter(move |element| element.placement == placement)
+            .map(|element| element.markup.as_str())
+    }
+
+    /// Attach an attribute of a node's own element
+    pub fn add_attribute(&mut self, node_id: NodeId, name: impl Into<String>, value: impl Into<String>) {
+        self.attributes.entry(node_id).or_default().push((name.into(), value.into()));
+    }
+
+    /// Attributes of a node's own element
+    pub fn attributes(&self, node_id: NodeId) -> &[(String, String)] {
+        self.attributes.get(&node_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Declare a namespace the markup uses
+    pub fn declare_namespace(&mut self, prefix: impl Into<String>, uri: impl Into<String>) {
+        self.namespaces.insert(prefix.into(), uri.into());
+    }
+
+    /// Namespace declarations, by prefix
+    pub fn namespaces(&self) -> &BTreeMap<String, String> {
+        &self.namespaces
+    }
+
+    /// Mark a namespace prefix as ignorable
+    pub fn add_ignorable(&mut self, prefix: impl Into<String>) {
+        let prefix = prefix.into();
+        if !self.ignorable.contains(&prefix) {
+            self.ignorable.push(prefix);
+        }
+    }
+
+    /// Ignorable namespace prefixes
+    pub fn ignorable(&self) -> &[String] {
+        &self.ignorable
+    }
+
+    /// Drop everything attached to a node
+    pub fn remove_node(&mut self, node_id: NodeId) {
+        self.elements.remove(&node_id);
+        self.attributes.remove(&node_id);
+    }
+
+    /// Drop all preserved markup
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
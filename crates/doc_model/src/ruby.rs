@@ -0,0 +1,89 @@
+//! Ruby annotations
+//!
+//! Ruby is small text set over its base text as a reading aid, such as
+//! furigana over Japanese kanji or pinyin over Chinese hanzi. The base text
+//! is the run's own text; the annotation rides on the run.
+
+use serde::{Deserialize, Serialize};
+
+/// Default size of ruby text relative to its base text
+pub const RUBY_SIZE_RATIO: f32 = 0.5;
+
+/// How ruby text lines up with its base text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RubyAlignment {
+    /// Centered over the base text
+    #[default]
+    Center,
+    /// Spread so the first and last characters meet the base's edges
+    DistributeLetter,
+    /// Spread with half a gap's space at each end
+    DistributeSpace,
+    /// Aligned with the base's left edge
+    Left,
+    /// Aligned with the base's right edge
+    Right,
+    /// Right-aligned, for vertical text
+    RightVertical,
+}
+
+/// A ruby annotation over a run's text
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RubyAnnotation {
+    /// The ruby text
+    pub text: String,
+    /// Alignment over the base text
+    #[serde(default)]
+    pub alignment: RubyAlignment,
+    /// Ruby font size in points; half the base size when None
+    #[serde(default)]
+    pub font_size: Option<f32>,
+    /// Distance in points from the base text's baseline to the ruby's;
+    /// just clear of the base text when None
+    #[serde(default)]
+    pub raise: Option<f32>,
+    /// Language of the ruby text (e.g. "ja-JP")
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+impl RubyAnnotation {
+    /// Create a centered annotation
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the alignment
+    pub fn with_alignment(mut self, alignment: RubyAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Set the ruby font size in points
+    pub fn with_font_size(mut self, font_size: f32) -> Self {
+        self.font_size = Some(font_size);
+        self
+    }
+
+    /// Set the raise above the base baseline in points
+    pub fn with_raise(mut self, raise: f32) -> Self {
+        self.raise = Some(raise);
+        self
+    }
+
+    /// Set the language
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Ruby font size for base text of the given size
+    pub fn effective_font_size(&self, base_font_size: f32) -> f32 {
+        self.font_size.unwrap_or(base_font_size * RUBY_SIZE_RATIO)
+    }
+}
@@ -1,6 +1,6 @@
 //! Text run node - a contiguous span of text with consistent formatting
 
-use crate::{CharacterProperties, Node, NodeId, NodeType, StyleId};
+use crate::{CharacterProperties, Node, NodeId, NodeType, RubyAnnotation, StyleId};
 use serde::{Deserialize, Serialize};
 
 /// Style reference for a run (kept for backwards compatibility)
@@ -57,6 +57,9 @@ pub struct Run {
     /// Direct formatting overrides (new style system)
     #[serde(default)]
     pub direct_formatting: CharacterProperties,
+    /// Ruby (phonetic guide) set over the run's text
+    #[serde(default)]
+    pub ruby: Option<RubyAnnotation>,
 }
 
 impl Run {
@@ -69,6 +72,7 @@ impl Run {
             style: RunStyle::default(),
             character_style_id: None,
             direct_formatting: CharacterProperties::default(),
+            ruby: None,
         }
     }
 
@@ -81,6 +85,7 @@ impl Run {
             style,
             character_style_id: None,
             direct_formatting: CharacterProperties::default(),
+            ruby: None,
         }
     }
 
@@ -93,6 +98,7 @@ impl Run {
             style: RunStyle::default(),
             character_style_id: Some(style_id.into()),
             direct_formatting: CharacterProperties::default(),
+            ruby: None,
         }
     }
 
@@ -105,9 +111,16 @@ impl Run {
             style: RunStyle::default(),
             character_style_id: None,
             direct_formatting: formatting,
+            ruby: None,
         }
     }
 
+    /// Set a ruby annotation over the run's text
+    pub fn with_ruby(mut self, ruby: RubyAnnotation) -> Self {
+        self.ruby = Some(ruby);
+        self
+    }
+
     /// Apply direct formatting to this run
     pub fn apply_direct_formatting(&mut self, formatting: CharacterProperties) {
         self.direct_formatting = self.direct_formatting.merge(&formatting);
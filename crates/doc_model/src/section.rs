@@ -12,7 +12,8 @@
 //! - Column separator lines
 //! - RTL-aware column ordering
 
-use crate::{Node, NodeId, NodeType, LineNumbering};
+use crate::field::NumberFormat;
+use crate::{Node, NodeId, NodeType, LineNumbering, Watermark};
 use serde::{Deserialize, Serialize};
 
 // =============================================================================
@@ -549,6 +550,10 @@ pub struct SectionPageSetup {
     pub gutter: f32,
     /// Gutter position
     pub gutter_position: GutterPosition,
+    /// Swap left and right margins on even pages for double-sided binding;
+    /// the left margin and a left gutter then sit on the inside
+    #[serde(default)]
+    pub mirror_margins: bool,
     /// How this section starts (section break type)
     pub section_start: SectionBreakType,
     /// Column configuration for multi-column layout
@@ -559,6 +564,12 @@ pub struct SectionPageSetup {
     pub line_numbering: LineNumbering,
     /// Text direction for this section
     pub text_direction: SectionTextDirection,
+    /// Page number format and restart
+    #[serde(default)]
+    pub page_numbering: PageNumbering,
+    /// Line and character grid for East Asian layout
+    #[serde(default)]
+    pub doc_grid: DocumentGrid,
 }
 
 /// Text direction for the section (section-level)
@@ -612,6 +623,19 @@ impl SectionPageSetup {
         }
     }
 
+    /// Get the left and right margins of a page, gutter included
+    ///
+    /// Pages are counted from 0, so odd indexes are the even-numbered
+    /// (left-hand) pages that mirrored margins flip.
+    pub fn horizontal_margins_for_page(&self, page_index: usize) -> (f32, f32) {
+        let inside = self.effective_left_margin();
+        if self.mirror_margins && page_index % 2 == 1 {
+            (self.margins.right, inside)
+        } else {
+            (inside, self.margins.right)
+        }
+    }
+
     /// Get the top margin including gutter if applicable
     pub fn effective_top_margin(&self) -> f32 {
         if self.gutter_position == GutterPosition::Top {
@@ -687,11 +711,14 @@ impl Default for SectionPageSetup {
             margins: PageMargins::default(),
             gutter: 0.0,
             gutter_position: GutterPosition::default(),
+            mirror_margins: false,
             section_start: SectionBreakType::default(),
             column_config: ColumnConfig::default(),
             vertical_alignment: VerticalAlignment::default(),
             line_numbering: LineNumbering::default(),
             text_direction: SectionTextDirection::default(),
+            page_numbering: PageNumbering::default(),
+            doc_grid: DocumentGrid::default(),
         }
     }
 }
@@ -706,6 +733,83 @@ pub enum VerticalAlignment {
     Bottom,
 }
 
+/// Kind of document grid a section lays its text on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DocumentGridType {
+    /// No grid
+    #[default]
+    None,
+    /// Lines are a whole number of line pitches high
+    Lines,
+    /// Lines snap to the line pitch, and East Asian characters take the
+    /// character pitch
+    LinesAndChars,
+    /// Lines snap to the line pitch, and East Asian characters take a
+    /// whole number of character pitches
+    SnapToChars,
+}
+
+/// A section's document grid
+///
+/// CJK documents are often set on a fixed grid: every line is one line
+/// pitch high and every ideograph one character pitch wide, so columns of
+/// characters line up down the page.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DocumentGrid {
+    /// Kind of grid
+    pub grid_type: DocumentGridType,
+    /// Height of a grid line in points
+    pub line_pitch: f32,
+    /// Points added to the document's default font size to give the
+    /// character pitch
+    pub char_space: f32,
+}
+
+impl DocumentGrid {
+    /// A line grid
+    pub fn lines(line_pitch: f32) -> Self {
+        Self {
+            grid_type: DocumentGridType::Lines,
+            line_pitch,
+            char_space: 0.0,
+        }
+    }
+
+    /// A line and character grid
+    pub fn lines_and_chars(line_pitch: f32, char_space: f32) -> Self {
+        Self {
+            grid_type: DocumentGridType::LinesAndChars,
+            line_pitch,
+            char_space,
+        }
+    }
+
+    /// Check if lines snap to the grid
+    pub fn snaps_lines(&self) -> bool {
+        self.grid_type != DocumentGridType::None && self.line_pitch > 0.0
+    }
+
+    /// Check if characters snap to the grid
+    pub fn snaps_chars(&self) -> bool {
+        matches!(self.grid_type, DocumentGridType::LinesAndChars | DocumentGridType::SnapToChars)
+    }
+
+    /// Character pitch for a document whose default font size is given
+    pub fn char_pitch(&self, default_font_size: f32) -> f32 {
+        (default_font_size + self.char_space).max(1.0)
+    }
+}
+
+impl Default for DocumentGrid {
+    fn default() -> Self {
+        Self {
+            grid_type: DocumentGridType::None,
+            line_pitch: 18.0,
+            char_space: 0.0,
+        }
+    }
+}
+
 // =============================================================================
 // Header/Footer
 // =============================================================================
@@ -845,6 +949,20 @@ impl HeaderFooterSet {
         self.default.as_ref()
     }
 
+    /// Iterate over the defined headers/footers
+    pub fn iter(&self) -> impl Iterator<Item = &HeaderFooter> {
+        [&self.default, &self.first_page, &self.odd, &self.even]
+            .into_iter()
+            .flatten()
+    }
+
+    /// Iterate mutably over the defined headers/footers
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut HeaderFooter> {
+        [&mut self.default, &mut self.first_page, &mut self.odd, &mut self.even]
+            .into_iter()
+            .flatten()
+    }
+
     /// Check if any header/footer is defined
     pub fn has_any(&self) -> bool {
         self.default.is_some()
@@ -880,6 +998,9 @@ pub struct Section {
     pub different_odd_even: bool,
     /// Link to previous section (use previous section's headers/footers)
     pub link_to_previous: bool,
+    /// Watermark drawn behind every page (inherited when linked to previous)
+    #[serde(default)]
+    pub watermark: Option<Watermark>,
     /// Child paragraph and table IDs (content of this section)
     children: Vec<NodeId>,
 }
@@ -896,6 +1017,7 @@ impl Section {
             different_first_page: false,
             different_odd_even: false,
             link_to_previous: false,
+            watermark: None,
             children: Vec::new(),
         }
     }
@@ -911,6 +1033,7 @@ impl Section {
             different_first_page: false,
             different_odd_even: false,
             link_to_previous: false,
+            watermark: None,
             children: Vec::new(),
         }
     }
@@ -974,6 +1097,12 @@ impl Section {
     pub fn set_first_page_footer(&mut self, footer: HeaderFooter) {
         self.footers.first_page = Some(footer);
     }
+
+    /// Set the watermark
+    pub fn with_watermark(mut self, watermark: Watermark) -> Self {
+        self.watermark = Some(watermark);
+        self
+    }
 }
 
 impl Default for Section {
@@ -1026,6 +1155,84 @@ pub enum PageNumberFormat {
     LowercaseRoman,
     /// Uppercase Roman numerals (I, II, III...)
     UppercaseRoman,
+    /// Hebrew letters (א, ב, ג...)
+    Hebrew,
+    /// Arabic-Indic digits (١, ٢, ٣...)
+    ArabicIndic,
+    /// Chinese counting numerals (一, 二, 三... 十)
+    ChineseCounting,
+    /// Japanese counting numerals (一, 二, 三... 十)
+    JapaneseCounting,
+    /// Thai digits (๑, ๒, ๓...)
+    Thai,
+}
+
+impl PageNumberFormat {
+    /// The equivalent field number format
+    pub fn number_format(&self) -> NumberFormat {
+        match self {
+            PageNumberFormat::Arabic => NumberFormat::Arabic,
+            PageNumberFormat::LowercaseLetter => NumberFormat::LowercaseLetter,
+            PageNumberFormat::UppercaseLetter => NumberFormat::UppercaseLetter,
+            PageNumberFormat::LowercaseRoman => NumberFormat::LowercaseRoman,
+            PageNumberFormat::UppercaseRoman => NumberFormat::UppercaseRoman,
+            PageNumberFormat::Hebrew => NumberFormat::Hebrew,
+            PageNumberFormat::ArabicIndic => NumberFormat::ArabicIndic,
+            PageNumberFormat::ChineseCounting => NumberFormat::ChineseCounting,
+            PageNumberFormat::JapaneseCounting => NumberFormat::JapaneseCounting,
+            PageNumberFormat::Thai => NumberFormat::Thai,
+        }
+    }
+
+    /// Format a page number
+    pub fn format(&self, number: u32) -> String {
+        self.number_format().format(number)
+    }
+}
+
+/// Page numbering of a section
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageNumbering {
+    /// Format of the section's page numbers
+    #[serde(default)]
+    pub format: PageNumberFormat,
+    /// Number of the section's first page; None continues the numbering
+    /// of the previous section
+    #[serde(default)]
+    pub start_at: Option<u32>,
+}
+
+impl PageNumbering {
+    /// Numbering that restarts at `start_at` in the given format
+    pub fn restart(format: PageNumberFormat, start_at: u32) -> Self {
+        Self {
+            format,
+            start_at: Some(start_at),
+        }
+    }
+
+    /// Numbering of each page of consecutive sections
+    ///
+    /// `page_sections` holds the section index of each page, in page
+    /// order. Pages continue the previous page's number unless their
+    /// section restarts numbering on its first page.
+    pub fn number_pages(sections: &[Section], page_sections: &[usize]) -> Vec<u32> {
+        let mut numbers = Vec::with_capacity(page_sections.len());
+        let mut number = 0;
+        for (index, &section_index) in page_sections.iter().enumerate() {
+            let starts_section = index == 0 || page_sections[index - 1] != section_index;
+            let restart = sections
+                .get(section_index)
+                .and_then(|section| section.page_setup.page_numbering.start_at)
+                .filter(|_| starts_section);
+            number = match restart {
+                Some(start) => start,
+                None => number + 1,
+            };
+            numbers.push(number);
+        }
+        numbers
+    }
 }
 
 // =============================================================================
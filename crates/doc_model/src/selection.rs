@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 pub struct Position {
     /// The node containing this position
     pub node_id: NodeId,
-    /// Character offset within the node (in grapheme clusters)
+    /// Character offset within the node (in chars, on a cursor cluster boundary)
     pub offset: usize,
 }
 
@@ -113,3 +113,291 @@ impl Default for Selection {
         }
     }
 }
+
+/// A rectangular (column-block) selection region on a page, in points
+///
+/// Made with Alt+drag: the anchor is where the drag started and the focus is
+/// the current pointer position. The text it covers is one range per line,
+/// mapped from the page layout.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BlockSelection {
+    /// Page the block lies on
+    pub page_index: usize,
+    /// Where the drag started (x, y)
+    pub anchor: (f32, f32),
+    /// Current drag position (x, y)
+    pub focus: (f32, f32),
+}
+
+impl BlockSelection {
+    /// Create a block selection on a page
+    pub fn new(page_index: usize, anchor: (f32, f32), focus: (f32, f32)) -> Self {
+        Self {
+            page_index,
+            anchor,
+            focus,
+        }
+    }
+
+    /// Move the focus corner, extending the block
+    pub fn extend_to(&self, focus: (f32, f32)) -> Self {
+        Self { focus, ..*self }
+    }
+
+    /// Left edge
+    pub fn left(&self) -> f32 {
+        self.anchor.0.min(self.focus.0)
+    }
+
+    /// Right edge
+    pub fn right(&self) -> f32 {
+        self.anchor.0.max(self.focus.0)
+    }
+
+    /// Top edge
+    pub fn top(&self) -> f32 {
+        self.anchor.1.min(self.focus.1)
+    }
+
+    /// Bottom edge
+    pub fn bottom(&self) -> f32 {
+        self.anchor.1.max(self.focus.1)
+    }
+}
+
+/// A set of discontiguous selection ranges (Ctrl+select)
+///
+/// One range is the primary range: it owns the caret and is what
+/// single-range operations act on. Ranges are kept in the order they were
+/// added; overlapping ranges within the same node are merged.
+///
+/// A block selection is also a multi-selection: one range per covered line,
+/// plus the block rectangle it was made from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiSelection {
+    /// The selected ranges (never empty)
+    ranges: Vec<Selection>,
+    /// Index of the primary range
+    primary: usize,
+    /// The rectangle, when this is a block selection
+    #[serde(default)]
+    block: Option<BlockSelection>,
+}
+
+impl MultiSelection {
+    /// Create a multi-selection holding a single range
+    pub fn new(selection: Selection) -> Self {
+        Self {
+            ranges: vec![selection],
+            primary: 0,
+            block: None,
+        }
+    }
+
+    /// Create a block selection from its per-line ranges (top to bottom)
+    ///
+    /// The last range is primary. Ranges are kept as given, including
+    /// collapsed ones for lines that end before the block starts.
+    pub fn from_block(block: BlockSelection, ranges: Vec<Selection>) -> Option<Self> {
+        if ranges.is_empty() {
+            return None;
+        }
+        Some(Self {
+            primary: ranges.len() - 1,
+            ranges,
+            block: Some(block),
+        })
+    }
+
+    /// Get the block rectangle, if this is a block selection
+    pub fn block(&self) -> Option<&BlockSelection> {
+        self.block.as_ref()
+    }
+
+    /// Check if this is a block (rectangular) selection
+    pub fn is_block(&self) -> bool {
+        self.block.is_some()
+    }
+
+    /// Get the primary range
+    pub fn primary(&self) -> Selection {
+        self.ranges[self.primary]
+    }
+
+    /// Get the index of the primary range
+    pub fn primary_index(&self) -> usize {
+        self.primary
+    }
+
+    /// Get all ranges
+    pub fn ranges(&self) -> &[Selection] {
+        &self.ranges
+    }
+
+    /// Number of ranges
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Always false: a multi-selection holds at least one range
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Check if more than one range is selected
+    pub fn is_multi(&self) -> bool {
+        self.ranges.len() > 1
+    }
+
+    /// Add a range (Ctrl+select), making it the primary range
+    ///
+    /// A collapsed range replaces a lone collapsed caret rather than being
+    /// added alongside it. A range overlapping an existing range in the same
+    /// node is merged into it. Adding a range leaves block mode.
+    pub fn add_range(&mut self, selection: Selection) {
+        self.block = None;
+        if self.ranges.len() == 1 && self.ranges[0].is_collapsed() {
+            self.ranges[0] = selection;
+            self.primary = 0;
+            return;
+        }
+
+        if let Some(index) = self.ranges.iter().position(|r| ranges_overlap(r, &selection)) {
+            let merged = merge_ranges(&self.ranges[index], &selection);
+            self.ranges[index] = merged;
+            self.primary = index;
+
+            // The merged range may now overlap others
+            let mut i = 0;
+            while i < self.ranges.len() {
+                if i != self.primary && ranges_overlap(&self.ranges[i], &self.ranges[self.primary]) {
+                    let merged = merge_ranges(&self.ranges[self.primary], &self.ranges[i]);
+                    self.ranges[self.primary] = merged;
+                    self.ranges.remove(i);
+                    if i < self.primary {
+                        self.primary -= 1;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+            return;
+        }
+
+        self.ranges.push(selection);
+        self.primary = self.ranges.len() - 1;
+    }
+
+    /// Remove the range at `index`; the last remaining range cannot be removed
+    pub fn remove_range(&mut self, index: usize) -> Option<Selection> {
+        if self.ranges.len() <= 1 || index >= self.ranges.len() {
+            return None;
+        }
+
+        let removed = self.ranges.remove(index);
+        self.block = None;
+        if self.primary > index || self.primary == self.ranges.len() {
+            self.primary = self.primary.saturating_sub(1);
+        }
+        Some(removed)
+    }
+
+    /// Replace the ranges, keeping the primary index where possible
+    pub fn set_ranges(&mut self, ranges: Vec<Selection>) {
+        if ranges.is_empty() {
+            return;
+        }
+        self.primary = self.primary.min(ranges.len() - 1);
+        self.ranges = ranges;
+    }
+
+    /// Drop every range except the primary one
+    pub fn collapse_to_primary(&self) -> Self {
+        Self::new(self.primary())
+    }
+}
+
+impl From<Selection> for MultiSelection {
+    fn from(selection: Selection) -> Self {
+        Self::new(selection)
+    }
+}
+
+impl Default for MultiSelection {
+    fn default() -> Self {
+        Self::new(Selection::default())
+    }
+}
+
+/// Check if two ranges within the same node overlap or touch
+fn ranges_overlap(a: &Selection, b: &Selection) -> bool {
+    let (a_start, a_end, b_start, b_end) = (a.start(), a.end(), b.start(), b.end());
+    let same_node = a_start.node_id == a_end.node_id
+        && b_start.node_id == b_end.node_id
+        && a_start.node_id == b_start.node_id;
+
+    same_node && a_start.offset <= b_end.offset && b_start.offset <= a_end.offset
+}
+
+/// Merge two overlapping same-node ranges into one forward range
+fn merge_ranges(a: &Selection, b: &Selection) -> Selection {
+    let node_id = a.start().node_id;
+    let start = a.start().offset.min(b.start().offset);
+    let end = a.end().offset.max(b.end().offset);
+    Selection::new(Position::new(node_id, start), Position::new(node_id, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(node_id: NodeId, start: usize, end: usize) -> Selection {
+        Selection::new(Position::new(node_id, start), Position::new(node_id, end))
+    }
+
+    #[test]
+    fn test_multi_selection_add_and_merge() {
+        let para = NodeId::new();
+        let other = NodeId::new();
+
+        // A lone caret is replaced by the first Ctrl+selected range
+        let mut multi = MultiSelection::new(Selection::collapsed(Position::new(para, 3)));
+        multi.add_range(range(para, 0, 4));
+        assert!(!multi.is_multi());
+
+        multi.add_range(range(other, 2, 6));
+        multi.add_range(range(para, 10, 12));
+        assert_eq!(multi.len(), 3);
+        assert_eq!(multi.primary(), range(para, 10, 12));
+
+        // Bridging [0, 4] and [10, 12] merges all three into one range
+        multi.add_range(range(para, 12, 3));
+        assert_eq!(multi.len(), 2);
+        assert_eq!(multi.primary(), range(para, 0, 12));
+
+        assert_eq!(multi.remove_range(multi.primary_index()), Some(range(para, 0, 12)));
+        assert_eq!(multi.primary(), range(other, 2, 6));
+        assert_eq!(multi.remove_range(0), None);
+    }
+
+    #[test]
+    fn test_block_selection() {
+        let block = BlockSelection::new(0, (120.0, 300.0), (80.0, 200.0)).extend_to((60.0, 180.0));
+        assert_eq!((block.left(), block.right()), (60.0, 120.0));
+        assert_eq!((block.top(), block.bottom()), (180.0, 300.0));
+
+        let (first, second) = (NodeId::new(), NodeId::new());
+        let mut multi = MultiSelection::from_block(
+            block,
+            vec![range(first, 2, 5), Selection::collapsed(Position::new(second, 1))],
+        )
+        .unwrap();
+        assert!(multi.is_block() && multi.is_multi());
+        assert!(multi.primary().is_collapsed());
+        assert!(MultiSelection::from_block(block, Vec::new()).is_none());
+
+        multi.add_range(range(first, 8, 9));
+        assert!(!multi.is_block());
+        assert_eq!(multi.len(), 3);
+    }
+}
@@ -5,7 +5,7 @@
 //! block arrows, stars, banners, and connectors. Shapes reuse the floating/anchor
 //! system from images and support text content, grouping, and advanced styling.
 
-use crate::{Dimension, ImagePosition, Node, NodeId, NodeType, WrapType};
+use crate::{Dimension, ImagePosition, Node, NodeId, NodeType, FreeformPath, PathSegment, WrapType};
 use serde::{Deserialize, Serialize};
 
 // =============================================================================
@@ -549,12 +549,15 @@ pub enum ShapeType {
         /// SVG-like path data
         path_data: String,
     },
-    /// Freeform shape with explicit points
+    /// Freeform or polyline shape with explicit points (see `FreeformPath`)
     Freeform {
-        /// Points defining the shape (closed polygon)
+        /// Edit points defining the shape
         points: Vec<Point>,
         /// Whether the shape is closed
         closed: bool,
+        /// Segments between the points; missing entries are straight lines
+        #[serde(default)]
+        segments: Vec<PathSegment>,
     },
 }
 
@@ -2779,6 +2782,9 @@ pub struct ShapeNode {
     pub name: Option<String>,
     /// Alternative text for accessibility
     pub alt_text: Option<String>,
+    /// Whether the shape is decorative and skipped by screen readers
+    #[serde(default)]
+    pub decorative: bool,
     /// Group this shape belongs to (if any)
     pub group_id: Option<NodeId>,
     /// Z-order index (higher = more in front)
@@ -2801,6 +2807,7 @@ impl ShapeNode {
             shape_text: None,
             name: None,
             alt_text: None,
+            decorative: false,
             group_id: None,
             z_order: 0,
             locked: false,
@@ -2885,6 +2892,12 @@ impl ShapeNode {
         )
     }
 
+    /// Create a freeform or polyline shape sized to its points
+    pub fn freeform(path: FreeformPath) -> Self {
+        let bounds = path.bounds();
+        Self::with_size(path.into(), bounds.x + bounds.width, bounds.y + bounds.height)
+    }
+
     /// Set the shape properties
     pub fn set_properties(&mut self, properties: ShapeProperties) {
         self.properties = properties;
@@ -5,7 +5,11 @@
 //! - Style inheritance via `based_on` chains
 //! - Property merging with direct formatting overrides
 
-use crate::{Alignment, LineSpacing, ListProperties};
+use crate::{
+    merge_tab_stops, Alignment, CellBorders, DocModelError, LineSpacing, ListProperties, Result,
+    TabStop, TableBorder, TableBorderStyle, TableBorders, TableCellPosition, TableLook, TableRegionFormat,
+    TableStyleProperties, TableStyleRegion, TextEffects, ThemeColorRef, ThemeFont,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -93,8 +97,50 @@ pub struct CharacterProperties {
     pub small_caps: Option<bool>,
     /// Character spacing adjustment in points
     pub spacing: Option<f32>,
+    /// Theme color; when set, `color` is only the last resolved value
+    #[serde(default)]
+    pub theme_color: Option<ThemeColorRef>,
+    /// Theme font; when set, `font_family` is only the last resolved value
+    #[serde(default)]
+    pub theme_font: Option<ThemeFont>,
+    /// Baseline offset in points (positive raises, negative lowers)
+    #[serde(default)]
+    pub position: Option<f32>,
+    /// Horizontal scale in percent (100 is normal width)
+    #[serde(default)]
+    pub scale: Option<u16>,
+    /// Smallest font size in points that gets pair kerning
+    #[serde(default)]
+    pub kerning: Option<f32>,
+    /// Outline (hollow) text
+    #[serde(default)]
+    pub outline: Option<bool>,
+    /// Drop shadow
+    #[serde(default)]
+    pub shadow: Option<bool>,
+    /// Embossed (raised) text
+    #[serde(default)]
+    pub emboss: Option<bool>,
+    /// Engraved (imprinted) text
+    #[serde(default)]
+    pub imprint: Option<bool>,
+    /// WordArt-style fill, outline, glow, and reflection
+    #[serde(default)]
+    pub text_effects: Option<TextEffects>,
+    /// Proofing language as a BCP 47 tag, e.g. "en-US"
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Skip spelling and grammar checking
+    #[serde(default)]
+    pub no_proof: Option<bool>,
+    /// Hidden text, not displayed or printed
+    #[serde(default)]
+    pub hidden: Option<bool>,
 }
 
+/// Size of small-caps letters relative to the font size
+pub const SMALL_CAPS_SCALE: f32 = 0.7;
+
 impl CharacterProperties {
     /// Create new empty character properties
     pub fn new() -> Self {
@@ -104,19 +150,48 @@ impl CharacterProperties {
     /// Merge another set of properties on top of this one
     /// Properties from `other` override properties from `self` when present
     pub fn merge(&self, other: &CharacterProperties) -> CharacterProperties {
+        // A literal color or font overrides an inherited theme reference (and
+        // vice versa), so each value travels with its theme reference
+        let color_source = if other.color.is_some() || other.theme_color.is_some() {
+            other
+        } else {
+            self
+        };
+        let font_source = if other.font_family.is_some() || other.theme_font.is_some() {
+            other
+        } else {
+            self
+        };
+
         CharacterProperties {
-            font_family: other.font_family.clone().or_else(|| self.font_family.clone()),
+            font_family: font_source.font_family.clone(),
             font_size: other.font_size.or(self.font_size),
             bold: other.bold.or(self.bold),
             italic: other.italic.or(self.italic),
             underline: other.underline.or(self.underline),
             strikethrough: other.strikethrough.or(self.strikethrough),
-            color: other.color.clone().or_else(|| self.color.clone()),
+            color: color_source.color.clone(),
             highlight: other.highlight.clone().or_else(|| self.highlight.clone()),
             vertical_align: other.vertical_align.or(self.vertical_align),
             all_caps: other.all_caps.or(self.all_caps),
             small_caps: other.small_caps.or(self.small_caps),
             spacing: other.spacing.or(self.spacing),
+            theme_color: color_source.theme_color,
+            theme_font: font_source.theme_font,
+            position: other.position.or(self.position),
+            scale: other.scale.or(self.scale),
+            kerning: other.kerning.or(self.kerning),
+            outline: other.outline.or(self.outline),
+            shadow: other.shadow.or(self.shadow),
+            emboss: other.emboss.or(self.emboss),
+            imprint: other.imprint.or(self.imprint),
+            text_effects: match (&self.text_effects, &other.text_effects) {
+                (Some(base), Some(top)) => Some(base.merge(top)),
+                (base, top) => top.clone().or_else(|| base.clone()),
+            },
+            language: other.language.clone().or_else(|| self.language.clone()),
+            no_proof: other.no_proof.or(self.no_proof),
+            hidden: other.hidden.or(self.hidden),
         }
     }
 
@@ -134,6 +209,58 @@ impl CharacterProperties {
             && self.all_caps.is_none()
             && self.small_caps.is_none()
             && self.spacing.is_none()
+            && self.theme_color.is_none()
+            && self.theme_font.is_none()
+            && self.position.is_none()
+            && self.scale.is_none()
+            && self.kerning.is_none()
+            && self.outline.is_none()
+            && self.shadow.is_none()
+            && self.emboss.is_none()
+            && self.imprint.is_none()
+            && self.text_effects.is_none()
+            && self.language.is_none()
+            && self.no_proof.is_none()
+            && self.hidden.is_none()
+    }
+
+    /// Text as displayed with all caps / small caps applied
+    ///
+    /// Only characters with a single-character uppercase form change, so the
+    /// result has the same characters-to-source mapping as `text`.
+    pub fn display_text(&self, text: &str) -> String {
+        if self.all_caps != Some(true) && self.small_caps != Some(true) {
+            return text.to_string();
+        }
+        text.chars().map(uppercase_char).collect()
+    }
+
+    /// Size multiplier for a source character (small caps shrink lowercase)
+    pub fn char_scale(&self, ch: char) -> f32 {
+        if self.small_caps == Some(true) && self.all_caps != Some(true) && ch.is_lowercase() {
+            SMALL_CAPS_SCALE
+        } else {
+            1.0
+        }
+    }
+
+    /// Horizontal scale as a factor (1.0 is normal width)
+    pub fn scale_factor(&self) -> f32 {
+        self.scale.map_or(1.0, |s| s as f32 / 100.0)
+    }
+
+    /// Check if pair kerning applies at `font_size`
+    pub fn kerning_enabled(&self, font_size: f32) -> bool {
+        self.kerning.is_some_and(|min| min > 0.0 && font_size >= min)
+    }
+}
+
+/// Uppercase a character when it has a single-character uppercase form
+fn uppercase_char(ch: char) -> char {
+    let mut upper = ch.to_uppercase();
+    match (upper.next(), upper.next()) {
+        (Some(u), None) => u,
+        _ => ch,
     }
 }
 
@@ -226,6 +353,12 @@ pub struct ParagraphProperties {
     pub list_props: Option<ListProperties>,
     /// Text direction (LTR, RTL, or Auto)
     pub direction: Option<TextDirection>,
+    /// Custom tab stops, layered over inherited ones
+    #[serde(default)]
+    pub tab_stops: Vec<TabStop>,
+    /// Leave the paragraph's lines out of section line numbering
+    #[serde(default)]
+    pub suppress_line_numbers: Option<bool>,
 }
 
 impl ParagraphProperties {
@@ -253,6 +386,8 @@ impl ParagraphProperties {
             outline_level: other.outline_level.or(self.outline_level),
             list_props: other.list_props.clone().or_else(|| self.list_props.clone()),
             direction: other.direction.or(self.direction),
+            tab_stops: merge_tab_stops(&self.tab_stops, &other.tab_stops),
+            suppress_line_numbers: other.suppress_line_numbers.or(self.suppress_line_numbers),
         }
     }
 
@@ -274,6 +409,8 @@ impl ParagraphProperties {
             && self.outline_level.is_none()
             && self.list_props.is_none()
             && self.direction.is_none()
+            && self.tab_stops.is_empty()
+            && self.suppress_line_numbers.is_none()
     }
 }
 
@@ -304,6 +441,16 @@ pub struct Style {
     pub paragraph_props: ParagraphProperties,
     /// Character properties (for paragraph and character styles)
     pub character_props: CharacterProperties,
+    /// Linked style: the character style paired with a paragraph style, or the
+    /// paragraph style paired with a character style
+    #[serde(default)]
+    pub linked_style: Option<StyleId>,
+    /// Alternative names the style can be found by
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Table borders and conditional regions (for table styles)
+    #[serde(default)]
+    pub table_props: TableStyleProperties,
 }
 
 impl Style {
@@ -320,6 +467,9 @@ impl Style {
             priority: 99,
             paragraph_props: ParagraphProperties::default(),
             character_props: CharacterProperties::default(),
+            linked_style: None,
+            aliases: Vec::new(),
+            table_props: TableStyleProperties::default(),
         }
     }
 
@@ -336,6 +486,28 @@ impl Style {
             priority: 99,
             paragraph_props: ParagraphProperties::default(),
             character_props: CharacterProperties::default(),
+            linked_style: None,
+            aliases: Vec::new(),
+            table_props: TableStyleProperties::default(),
+        }
+    }
+
+    /// Create a new table style
+    pub fn table(id: impl Into<StyleId>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            style_type: StyleType::Table,
+            based_on: None,
+            next_style: None,
+            built_in: false,
+            hidden: false,
+            priority: 99,
+            paragraph_props: ParagraphProperties::default(),
+            character_props: CharacterProperties::default(),
+            linked_style: None,
+            aliases: Vec::new(),
+            table_props: TableStyleProperties::default(),
         }
     }
 
@@ -374,6 +546,32 @@ impl Style {
         self.next_style = Some(next.into());
         self
     }
+
+    /// Set the linked style
+    pub fn with_linked_style(mut self, linked: impl Into<StyleId>) -> Self {
+        self.linked_style = Some(linked.into());
+        self
+    }
+
+    /// Set table style properties
+    pub fn with_table_props(mut self, props: TableStyleProperties) -> Self {
+        self.table_props = props;
+        self
+    }
+
+    /// Add an alias
+    pub fn with_alias(mut self, alias: impl Into<String>) -> Self {
+        self.aliases.push(alias.into());
+        self
+    }
+
+    /// Check if a name refers to this style (ID, display name, or alias; case-insensitive)
+    pub fn matches_name(&self, name: &str) -> bool {
+        let name = name.trim();
+        self.id.as_str().eq_ignore_ascii_case(name)
+            || self.name.eq_ignore_ascii_case(name)
+            || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+    }
 }
 
 // =============================================================================
@@ -526,6 +724,9 @@ pub struct StyleRegistry {
     default_paragraph_style: StyleId,
     /// Default character style ID
     default_character_style: StyleId,
+    /// Explicit quick-style gallery order (styles not listed follow by priority)
+    #[serde(default)]
+    gallery_order: Vec<StyleId>,
 }
 
 impl StyleRegistry {
@@ -535,6 +736,7 @@ impl StyleRegistry {
             styles: HashMap::new(),
             default_paragraph_style: StyleId::new("Normal"),
             default_character_style: StyleId::new("DefaultParagraphFont"),
+            gallery_order: Vec::new(),
         };
         registry.register_built_in_styles();
         registry
@@ -896,6 +1098,115 @@ impl StyleRegistry {
                 ..Default::default()
             });
         self.register(tof);
+
+        // HTML Preformatted: code blocks, left out of proofing
+        let preformatted = Style::paragraph("HTMLPreformatted", "HTML Preformatted")
+            .as_built_in()
+            .with_priority(99)
+            .with_based_on("Normal")
+            .with_paragraph_props(ParagraphProperties {
+                space_after: Some(0.0),
+                line_spacing: Some(LineSpacing::Multiple(1.0)),
+                ..Default::default()
+            })
+            .with_character_props(CharacterProperties {
+                font_family: Some("Courier New".to_string()),
+                font_size: Some(10.0),
+                no_proof: Some(true),
+                ..Default::default()
+            });
+        self.register(preformatted);
+
+        // HTML Code (character style): inline code, left out of proofing
+        let code = Style::character("HTMLCode", "HTML Code")
+            .as_built_in()
+            .with_priority(99)
+            .with_character_props(CharacterProperties {
+                font_family: Some("Courier New".to_string()),
+                font_size: Some(10.0),
+                no_proof: Some(true),
+                ..Default::default()
+            });
+        self.register(code);
+
+        self.register_built_in_table_styles();
+    }
+
+    /// Register the built-in table styles
+    fn register_built_in_table_styles(&mut self) {
+        // Table Normal - the base table style
+        let mut table_normal = Style::table("TableNormal", "Normal Table")
+            .as_built_in()
+            .with_priority(99);
+        table_normal.hidden = true;
+        self.register(table_normal);
+
+        // Table Grid - single borders around every cell
+        let table_grid = Style::table("TableGrid", "Table Grid")
+            .as_built_in()
+            .with_priority(59)
+            .with_based_on("TableNormal")
+            .with_table_props(
+                TableStyleProperties::new().with_borders(TableBorders::default_borders()),
+            );
+        self.register(table_grid);
+
+        // Grid Table 4 - Accent 1: filled header row, banded rows
+        let accent_border = TableBorder::single(0.5, "#8EAADB");
+        let header_text = CharacterProperties {
+            bold: Some(true),
+            color: Some("#FFFFFF".to_string()),
+            ..Default::default()
+        };
+        let bold_text = CharacterProperties {
+            bold: Some(true),
+            ..Default::default()
+        };
+        let grid_table = Style::table("GridTable4-Accent1", "Grid Table 4 - Accent 1")
+            .as_built_in()
+            .with_priority(49)
+            .with_based_on("TableNormal")
+            .with_table_props(
+                TableStyleProperties::new()
+                    .with_borders(TableBorders::all(accent_border.clone()))
+                    .with_region(
+                        TableStyleRegion::FirstRow,
+                        TableRegionFormat::new()
+                            .with_shading("#4472C4")
+                            .with_borders(CellBorders::all(TableBorder::single(0.5, "#4472C4")))
+                            .with_character_props(header_text),
+                    )
+                    .with_region(
+                        TableStyleRegion::LastRow,
+                        TableRegionFormat::new()
+                            .with_borders(CellBorders {
+                                top: Some(TableBorder {
+                                    style: TableBorderStyle::Double,
+                                    width: 1.5,
+                                    color: "#4472C4".to_string(),
+                                }),
+                                ..Default::default()
+                            })
+                            .with_character_props(bold_text.clone()),
+                    )
+                    .with_region(
+                        TableStyleRegion::FirstColumn,
+                        TableRegionFormat::new().with_character_props(bold_text.clone()),
+                    )
+                    .with_region(
+                        TableStyleRegion::LastColumn,
+                        TableRegionFormat::new().with_character_props(bold_text),
+                    )
+                    .with_region(
+                        TableStyleRegion::Band1Vertical,
+                        TableRegionFormat::new().with_shading("#D9E2F3"),
+                    )
+                    .with_region(
+                        TableStyleRegion::Band1Horizontal,
+                        TableRegionFormat::new().with_shading("#D9E2F3"),
+                    ),
+            );
+        self.register(grid_table);
     }
 
     /// Register a style in the registry
@@ -957,17 +1268,222 @@ impl StyleRegistry {
             .filter(|s| s.style_type == StyleType::Character)
     }
 
-    /// Get styles for the gallery (sorted by priority, non-hidden)
+    /// Get all table styles
+    pub fn table_styles(&self) -> impl Iterator<Item = &Style> {
+        self.styles
+            .values()
+            .filter(|s| s.style_type == StyleType::Table)
+    }
+
+    /// Get table styles for the table style gallery (non-hidden, by priority then name)
+    pub fn table_gallery_styles(&self) -> Vec<&Style> {
+        let mut styles: Vec<_> = self.table_styles().filter(|s| !s.hidden).collect();
+        styles.sort_by(|a, b| a.priority.cmp(&b.priority).then_with(|| a.name.cmp(&b.name)));
+        styles
+    }
+
+    /// Resolve a table style's borders and regions along its inheritance chain
+    pub fn resolve_table_style(&self, id: &StyleId) -> Option<TableStyleProperties> {
+        let resolved = self.resolve(id)?;
+        Some(
+            resolved
+                .inheritance_chain
+                .iter()
+                .filter_map(|style_id| self.styles.get(style_id))
+                .fold(TableStyleProperties::default(), |acc, s| acc.merge(&s.table_props)),
+        )
+    }
+
+    /// Resolve the formatting a table style gives a cell
+    ///
+    /// The style's own paragraph and character properties apply to the whole
+    /// table; the enabled conditional regions are layered on top.
+    pub fn resolve_table_cell_format(
+        &self,
+        id: &StyleId,
+        position: &TableCellPosition,
+        look: &TableLook,
+    ) -> Option<TableRegionFormat> {
+        let resolved = self.resolve(id)?;
+        let table_props = self.resolve_table_style(id)?;
+        let base = TableRegionFormat::new()
+            .with_paragraph_props(resolved.paragraph_props)
+            .with_character_props(resolved.character_props);
+        Some(base.merge(&table_props.cell_format(position, look)))
+    }
+
+    /// Get styles for the gallery (non-hidden paragraph styles)
+    ///
+    /// Styles in the explicit gallery order come first, in that order; the
+    /// rest follow sorted by priority, then name.
     pub fn gallery_styles(&self) -> Vec<&Style> {
         let mut styles: Vec<_> = self
             .styles
             .values()
             .filter(|s| !s.hidden && s.style_type == StyleType::Paragraph)
             .collect();
-        styles.sort_by_key(|s| s.priority);
+        styles.sort_by(|a, b| {
+            let rank = |s: &Style| {
+                self.gallery_order
+                    .iter()
+                    .position(|id| *id == s.id)
+                    .unwrap_or(usize::MAX)
+            };
+            rank(a)
+                .cmp(&rank(b))
+                .then(a.priority.cmp(&b.priority))
+                .then_with(|| a.name.cmp(&b.name))
+        });
         styles
     }
 
+    /// Get the explicit quick-style gallery order
+    pub fn gallery_order(&self) -> &[StyleId] {
+        &self.gallery_order
+    }
+
+    /// Set the explicit quick-style gallery order
+    pub fn set_gallery_order(&mut self, order: Vec<StyleId>) {
+        self.gallery_order = order;
+    }
+
+    /// Move a style to a position in the gallery
+    ///
+    /// Styles not yet in the explicit order are added in their current
+    /// gallery position first, so moving one style doesn't reshuffle others.
+    pub fn move_in_gallery(&mut self, id: &StyleId, index: usize) -> Result<()> {
+        if !self.contains(id) {
+            return Err(DocModelError::InvalidOperation(format!("Style not found: {}", id)));
+        }
+        let mut order: Vec<StyleId> = self.gallery_styles().iter().map(|s| s.id.clone()).collect();
+        order.retain(|s| s != id);
+        order.insert(index.min(order.len()), id.clone());
+        self.gallery_order = order;
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------------
+    // Linked styles
+    // -------------------------------------------------------------------------
+
+    /// Link a paragraph style with a character style
+    ///
+    /// Any previous links of either style are removed.
+    pub fn link_styles(&mut self, paragraph_id: &StyleId, character_id: &StyleId) -> Result<()> {
+        let is_type = |id: &StyleId, style_type| self.get(id).is_some_and(|s| s.style_type == style_type);
+        if !is_type(paragraph_id, StyleType::Paragraph) {
+            return Err(DocModelError::InvalidOperation(format!(
+                "{} is not a paragraph style",
+                paragraph_id
+            )));
+        }
+        if !is_type(character_id, StyleType::Character) {
+            return Err(DocModelError::InvalidOperation(format!(
+                "{} is not a character style",
+                character_id
+            )));
+        }
+
+        self.unlink_style(paragraph_id);
+        self.unlink_style(character_id);
+        if let Some(style) = self.styles.get_mut(paragraph_id) {
+            style.linked_style = Some(character_id.clone());
+        }
+        if let Some(style) = self.styles.get_mut(character_id) {
+            style.linked_style = Some(paragraph_id.clone());
+        }
+        Ok(())
+    }
+
+    /// Remove the link between a style and its partner
+    pub fn unlink_style(&mut self, id: &StyleId) {
+        let partner = self.styles.get_mut(id).and_then(|s| s.linked_style.take());
+        if let Some(partner) = partner {
+            if let Some(style) = self.styles.get_mut(&partner) {
+                if style.linked_style.as_ref() == Some(id) {
+                    style.linked_style = None;
+                }
+            }
+        }
+    }
+
+    /// Get the style linked to a style
+    pub fn linked_style(&self, id: &StyleId) -> Option<&Style> {
+        self.get(id)?.linked_style.as_ref().and_then(|linked| self.get(linked))
+    }
+
+    /// Create (or return) the character style linked to a paragraph style
+    ///
+    /// The new style is named "<name> Char", carries the paragraph style's
+    /// character properties, and is hidden from the gallery.
+    pub fn create_linked_character_style(&mut self, paragraph_id: &StyleId) -> Result<StyleId> {
+        if let Some(linked) = self.linked_style(paragraph_id) {
+            return Ok(linked.id.clone());
+        }
+        let paragraph = self
+            .get(paragraph_id)
+            .filter(|s| s.style_type == StyleType::Paragraph)
+            .ok_or_else(|| {
+                DocModelError::InvalidOperation(format!("{} is not a paragraph style", paragraph_id))
+            })?;
+
+        let character_id = StyleId::new(format!("{}Char", paragraph_id));
+        let mut character = Style::character(character_id.clone(), format!("{} Char", paragraph.name))
+            .with_based_on(self.default_character_style.clone())
+            .with_priority(paragraph.priority)
+            .with_character_props(paragraph.character_props.clone());
+        character.hidden = true;
+
+        self.register(character);
+        self.link_styles(paragraph_id, &character_id)?;
+        Ok(character_id)
+    }
+
+    /// Style to use when applying a style to part of a paragraph
+    ///
+    /// A linked paragraph style applies its character style; character styles
+    /// apply themselves; unlinked paragraph styles can't be applied to a range.
+    pub fn character_style_for_range(&self, id: &StyleId) -> Option<&StyleId> {
+        let style = self.get(id)?;
+        match style.style_type {
+            StyleType::Character => Some(&style.id),
+            StyleType::Paragraph => style.linked_style.as_ref(),
+            _ => None,
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Aliases
+    // -------------------------------------------------------------------------
+
+    /// Add an alias to a style
+    pub fn add_alias(&mut self, id: &StyleId, alias: impl Into<String>) -> Result<()> {
+        let alias = alias.into();
+        if let Some(other) = self.find_by_name(&alias) {
+            if other.id != *id {
+                return Err(DocModelError::InvalidOperation(format!(
+                    "'{}' already refers to style {}",
+                    alias, other.id
+                )));
+            }
+            return Ok(());
+        }
+        let style = self
+            .styles
+            .get_mut(id)
+            .ok_or_else(|| DocModelError::InvalidOperation(format!("Style not found: {}", id)))?;
+        style.aliases.push(alias);
+        Ok(())
+    }
+
+    /// Find a style by ID, display name, or alias (case-insensitive)
+    pub fn find_by_name(&self, name: &str) -> Option<&Style> {
+        if let Some(style) = self.get(&StyleId::new(name)) {
+            return Some(style);
+        }
+        self.styles.values().find(|s| s.matches_name(name))
+    }
+
     /// Resolve a style by walking the inheritance chain and merging properties
     pub fn resolve(&self, id: &StyleId) -> Option<ResolvedStyle> {
         let _style = self.styles.get(id)?;
@@ -1159,6 +1675,32 @@ impl Default for StyleRegistry {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_character_display_transforms() {
+        let small_caps = CharacterProperties {
+            small_caps: Some(true),
+            kerning: Some(12.0),
+            scale: Some(80),
+            ..Default::default()
+        };
+        // Caps keep one character per source character ("ß" has no single
+        // uppercase form)
+        assert_eq!(small_caps.display_text("Straße"), "STRAßE");
+        assert_eq!(small_caps.char_scale('a'), SMALL_CAPS_SCALE);
+        assert_eq!(small_caps.char_scale('A'), 1.0);
+        assert_eq!(small_caps.scale_factor(), 0.8);
+        assert!(small_caps.kerning_enabled(12.0));
+        assert!(!small_caps.kerning_enabled(11.5));
+
+        // All caps wins over small caps
+        let all_caps = small_caps.merge(&CharacterProperties {
+            all_caps: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(all_caps.char_scale('a'), 1.0);
+        assert_eq!(CharacterProperties::default().display_text("abc"), "abc");
+    }
+
     #[test]
     fn test_style_registry_creation() {
         let registry = StyleRegistry::new();
@@ -1340,4 +1882,114 @@ mod tests {
         // derived indent added
         assert_eq!(merged.indent_left, Some(36.0));
     }
+
+    #[test]
+    fn test_linked_styles() {
+        let mut registry = StyleRegistry::new();
+        let heading = StyleId::new("Heading1");
+
+        let char_id = registry.create_linked_character_style(&heading).unwrap();
+        assert_eq!(char_id, StyleId::new("Heading1Char"));
+        assert_eq!(registry.linked_style(&heading).unwrap().id, char_id);
+        assert_eq!(registry.linked_style(&char_id).unwrap().id, heading);
+        assert_eq!(registry.character_style_for_range(&heading), Some(&char_id));
+        assert!(!registry.gallery_styles().iter().any(|s| s.id == char_id));
+
+        // Creating again returns the existing partner
+        assert_eq!(registry.create_linked_character_style(&heading).unwrap(), char_id);
+
+        // Relinking drops the old partnership
+        let strong = StyleId::new("Strong");
+        registry.link_styles(&heading, &strong).unwrap();
+        assert!(registry.get(&char_id).unwrap().linked_style.is_none());
+        assert_eq!(registry.linked_style(&strong).unwrap().id, heading);
+
+        assert!(registry.link_styles(&strong, &heading).is_err());
+        registry.unlink_style(&strong);
+        assert!(registry.linked_style(&heading).is_none());
+        assert!(registry.character_style_for_range(&heading).is_none());
+    }
+
+    #[test]
+    fn test_style_aliases() {
+        let mut registry = StyleRegistry::new();
+        let heading = StyleId::new("Heading1");
+
+        registry.add_alias(&heading, "H1").unwrap();
+        assert_eq!(registry.find_by_name("h1").unwrap().id, heading);
+        assert_eq!(registry.find_by_name("heading 1").unwrap().id, heading);
+        assert_eq!(registry.find_by_name("Heading1").unwrap().id, heading);
+        assert!(registry.find_by_name("missing").is_none());
+
+        // Aliases can't be shared between styles
+        assert!(registry.add_alias(&StyleId::new("Heading2"), "h1").is_err());
+        assert!(registry.add_alias(&StyleId::new("Missing"), "x").is_err());
+    }
+
+    #[test]
+    fn test_gallery_order() {
+        let mut registry = StyleRegistry::new();
+        let heading2 = StyleId::new("Heading2");
+
+        registry.move_in_gallery(&heading2, 0).unwrap();
+        let gallery = registry.gallery_styles();
+        assert_eq!(gallery[0].id, heading2);
+        assert_eq!(gallery[1].id, StyleId::new("Normal"));
+        assert_eq!(registry.gallery_order().len(), gallery.len());
+
+        registry.set_gallery_order(Vec::new());
+        assert_eq!(registry.gallery_styles()[0].id, StyleId::new("Normal"));
+        assert!(registry.move_in_gallery(&StyleId::new("Missing"), 0).is_err());
+    }
+
+    #[test]
+    fn test_table_style_gallery_and_inheritance() {
+        let mut registry = StyleRegistry::new();
+        let gallery: Vec<_> = registry.table_gallery_styles().iter().map(|s| s.id.clone()).collect();
+        assert_eq!(gallery, vec![StyleId::new("GridTable4-Accent1"), StyleId::new("TableGrid")]);
+
+        // A derived style keeps its base's regions and borders
+        registry.register(
+            Style::table("MyGrid", "My Grid").with_based_on("TableGrid").with_table_props(
+                TableStyleProperties::new()
+                    .with_region(TableStyleRegion::FirstRow, TableRegionFormat::new().with_shading("#000000")),
+            ),
+        );
+        let resolved = registry.resolve_table_style(&StyleId::new("MyGrid")).unwrap();
+        assert!(resolved.borders.is_some());
+        assert!(resolved.region(TableStyleRegion::FirstRow).is_some());
+        assert!(registry.resolve_table_style(&StyleId::new("Missing")).is_none());
+    }
+
+    #[test]
+    fn test_find_and_replace_style_instances() {
+        use crate::{DocumentTree, Paragraph, Run};
+
+        let mut tree = DocumentTree::new();
+        let heading = StyleId::new("Heading1");
+        let char_id = tree.styles.create_linked_character_style(&heading).unwrap();
+
+        let p1 = tree
+            .insert_paragraph(Paragraph::with_paragraph_style("Heading1"), tree.root_id(), None)
+            .unwrap();
+        tree.insert_run(Run::new("Title"), p1, None).unwrap();
+        let p2 = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        let r2 = tree.insert_run(Run::new("Body"), p2, None).unwrap();
+        tree.apply_character_style(r2, char_id.clone()).unwrap();
+        let p3 = tree
+            .insert_paragraph(Paragraph::with_paragraph_style("Heading1"), tree.root_id(), None)
+            .unwrap();
+
+        assert_eq!(tree.find_nodes_with_style(&heading), vec![p1, r2, p3]);
+        assert_eq!(tree.find_nodes_with_style(&StyleId::new("Normal")), vec![p2]);
+
+        let heading2 = StyleId::new("Heading2");
+        let heading2_char = tree.styles.create_linked_character_style(&heading2).unwrap();
+        assert_eq!(tree.replace_style(&heading, &heading2).unwrap(), 3);
+        assert!(tree.find_nodes_with_style(&heading).is_empty());
+        assert_eq!(tree.get_run(r2).unwrap().character_style_id, Some(heading2_char));
+
+        // Types must match
+        assert!(tree.replace_style(&heading2, &StyleId::new("Strong")).is_err());
+    }
 }
@@ -0,0 +1,205 @@
+//! Tab stops - custom tab positions with alignment and leaders
+//!
+//! Tab stops are measured in points from the paragraph's text origin (the
+//! left edge of the text column). A paragraph's effective stops are its
+//! style's stops with the paragraph's own stops layered on top; a `Clear`
+//! stop removes an inherited stop at the same position.
+
+use serde::{Deserialize, Serialize};
+
+/// Default interval between automatic tab stops in points (0.5")
+pub const DEFAULT_TAB_INTERVAL: f32 = 36.0;
+
+/// Positions closer than this (in points) are the same tab stop
+const POSITION_TOLERANCE: f32 = 0.5;
+
+/// How text is aligned at a tab stop
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TabAlignment {
+    /// Text starts at the stop
+    #[default]
+    Left,
+    /// Text is centered on the stop
+    Center,
+    /// Text ends at the stop
+    Right,
+    /// The decimal separator sits on the stop
+    Decimal,
+    /// A vertical bar is drawn at the stop; text is not positioned by it
+    Bar,
+    /// Removes an inherited stop at this position
+    Clear,
+}
+
+/// Characters filling the space before a tab stop
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TabLeader {
+    /// Blank space
+    #[default]
+    None,
+    /// Dots (.....)
+    Dot,
+    /// Hyphens (-----)
+    Hyphen,
+    /// Underscores (_____)
+    Underscore,
+    /// Middle dots (·····)
+    MiddleDot,
+    /// Heavy line
+    Heavy,
+}
+
+impl TabLeader {
+    /// Character repeated to draw the leader
+    pub fn character(&self) -> Option<char> {
+        match self {
+            TabLeader::None => None,
+            TabLeader::Dot => Some('.'),
+            TabLeader::Hyphen => Some('-'),
+            TabLeader::Underscore | TabLeader::Heavy => Some('_'),
+            TabLeader::MiddleDot => Some('\u{00B7}'),
+        }
+    }
+}
+
+/// A tab stop
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TabStop {
+    /// Position in points from the text origin
+    pub position: f32,
+    /// Alignment at the stop
+    #[serde(default)]
+    pub alignment: TabAlignment,
+    /// Leader before the stop
+    #[serde(default)]
+    pub leader: TabLeader,
+}
+
+impl TabStop {
+    /// Create a tab stop
+    pub fn new(position: f32, alignment: TabAlignment) -> Self {
+        Self {
+            position,
+            alignment,
+            leader: TabLeader::None,
+        }
+    }
+
+    /// Create a left-aligned tab stop
+    pub fn left(position: f32) -> Self {
+        Self::new(position, TabAlignment::Left)
+    }
+
+    /// Create a centered tab stop
+    pub fn center(position: f32) -> Self {
+        Self::new(position, TabAlignment::Center)
+    }
+
+    /// Create a right-aligned tab stop
+    pub fn right(position: f32) -> Self {
+        Self::new(position, TabAlignment::Right)
+    }
+
+    /// Create a decimal tab stop
+    pub fn decimal(position: f32) -> Self {
+        Self::new(position, TabAlignment::Decimal)
+    }
+
+    /// Create a stop that clears an inherited stop
+    pub fn clear(position: f32) -> Self {
+        Self::new(position, TabAlignment::Clear)
+    }
+
+    /// Set the leader
+    pub fn with_leader(mut self, leader: TabLeader) -> Self {
+        self.leader = leader;
+        self
+    }
+
+    /// Check if text can be positioned at this stop
+    pub fn is_positioning(&self) -> bool {
+        !matches!(self.alignment, TabAlignment::Bar | TabAlignment::Clear)
+    }
+
+    fn same_position(&self, other: &TabStop) -> bool {
+        (self.position - other.position).abs() < POSITION_TOLERANCE
+    }
+}
+
+/// Layer tab stops over inherited ones
+///
+/// Stops in `overrides` replace inherited stops at the same position. `Clear`
+/// stops are kept in the result so they keep clearing when layered again;
+/// they never position text. The result is sorted by position.
+pub fn merge_tab_stops(base: &[TabStop], overrides: &[TabStop]) -> Vec<TabStop> {
+    let mut result = base.to_vec();
+    for stop in overrides {
+        result.retain(|s| !s.same_position(stop));
+        result.push(*stop);
+    }
+    result.sort_by(|a, b| a.position.total_cmp(&b.position));
+    result
+}
+
+/// Find the tab stop that text at `position` advances to
+///
+/// Uses the first positioning stop after `position`; past the last custom
+/// stop, automatic left stops every `default_interval` points apply.
+pub fn next_tab_stop(stops: &[TabStop], position: f32, default_interval: f32) -> TabStop {
+    if let Some(stop) = stops
+        .iter()
+        .filter(|s| s.is_positioning())
+        .find(|s| s.position > position + f32::EPSILON)
+    {
+        return *stop;
+    }
+
+    let interval = if default_interval > 0.0 {
+        default_interval
+    } else {
+        DEFAULT_TAB_INTERVAL
+    };
+    TabStop::left(((position / interval).floor() + 1.0) * interval)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_tab_stops() {
+        let base = [TabStop::left(72.0), TabStop::right(144.0), TabStop::center(216.0)];
+        let overrides = [
+            TabStop::clear(144.0),
+            TabStop::decimal(216.0).with_leader(TabLeader::Dot),
+            TabStop::left(36.0),
+        ];
+
+        let merged = merge_tab_stops(&base, &overrides);
+        assert_eq!(
+            merged,
+            vec![
+                TabStop::left(36.0),
+                TabStop::left(72.0),
+                TabStop::clear(144.0),
+                TabStop::decimal(216.0).with_leader(TabLeader::Dot),
+            ]
+        );
+
+        // The clear still removes the stop when layered over the base again
+        let relayered = merge_tab_stops(&base, &merged);
+        assert!(!relayered.contains(&TabStop::right(144.0)));
+        assert_eq!(next_tab_stop(&relayered, 72.0, DEFAULT_TAB_INTERVAL), merged[3]);
+    }
+
+    #[test]
+    fn test_next_tab_stop() {
+        let stops = [TabStop::new(50.0, TabAlignment::Bar), TabStop::right(100.0)];
+
+        // Bar stops don't position text
+        assert_eq!(next_tab_stop(&stops, 10.0, DEFAULT_TAB_INTERVAL), TabStop::right(100.0));
+        // Past the custom stops, default stops apply
+        assert_eq!(next_tab_stop(&stops, 100.0, DEFAULT_TAB_INTERVAL), TabStop::left(108.0));
+        assert_eq!(next_tab_stop(&[], 0.0, DEFAULT_TAB_INTERVAL), TabStop::left(36.0));
+    }
+}
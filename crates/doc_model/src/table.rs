@@ -12,7 +12,7 @@
 //! - Auto-fit modes
 //! - Text direction per cell
 
-use crate::{Node, NodeId, NodeType, StyleId};
+use crate::{Node, NodeId, NodeType, StyleId, TableLook};
 use serde::{Deserialize, Serialize};
 
 // =============================================================================
@@ -130,6 +130,24 @@ pub enum CellTextDirection {
     TbLr,
     /// Top-to-bottom, right-to-left
     TbRl,
+    /// Bottom-to-top, left-to-right (text rotated counter-clockwise)
+    BtLr,
+}
+
+impl CellTextDirection {
+    /// Whether lines run vertically through the cell
+    pub fn is_vertical(&self) -> bool {
+        matches!(self, CellTextDirection::TbLr | CellTextDirection::TbRl | CellTextDirection::BtLr)
+    }
+
+    /// Clockwise rotation of the cell content in degrees
+    pub fn rotation_degrees(&self) -> f32 {
+        match self {
+            CellTextDirection::Ltr | CellTextDirection::Rtl => 0.0,
+            CellTextDirection::TbLr | CellTextDirection::TbRl => 90.0,
+            CellTextDirection::BtLr => 270.0,
+        }
+    }
 }
 
 // =============================================================================
@@ -233,13 +251,19 @@ impl TableBorder {
     }
 }
 
-/// Cell borders (all four sides)
+/// Cell borders (four sides plus the two diagonals)
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct CellBorders {
     pub top: Option<TableBorder>,
     pub bottom: Option<TableBorder>,
     pub left: Option<TableBorder>,
     pub right: Option<TableBorder>,
+    /// Diagonal from the top-left to the bottom-right corner
+    #[serde(default)]
+    pub diagonal_down: Option<TableBorder>,
+    /// Diagonal from the bottom-left to the top-right corner
+    #[serde(default)]
+    pub diagonal_up: Option<TableBorder>,
 }
 
 impl CellBorders {
@@ -250,9 +274,23 @@ impl CellBorders {
             bottom: Some(border.clone()),
             left: Some(border.clone()),
             right: Some(border),
+            diagonal_down: None,
+            diagonal_up: None,
         }
     }
 
+    /// Set the top-left to bottom-right diagonal
+    pub fn with_diagonal_down(mut self, border: TableBorder) -> Self {
+        self.diagonal_down = Some(border);
+        self
+    }
+
+    /// Set the bottom-left to top-right diagonal
+    pub fn with_diagonal_up(mut self, border: TableBorder) -> Self {
+        self.diagonal_up = Some(border);
+        self
+    }
+
     /// Create default borders (single black line)
     pub fn default_borders() -> Self {
         Self::all(TableBorder::default())
@@ -737,7 +775,7 @@ impl Node for TableCell {
 // =============================================================================
 
 /// Properties for a table row
-#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RowProperties {
     /// Row height
     pub height: Option<f32>,
@@ -753,12 +791,30 @@ pub struct RowProperties {
     pub keep_with_next: bool,
 }
 
+impl Default for RowProperties {
+    fn default() -> Self {
+        Self {
+            height: None,
+            height_rule: HeightRule::default(),
+            is_header: false,
+            can_split: true,
+            cant_split: false,
+            keep_with_next: false,
+        }
+    }
+}
+
 impl RowProperties {
     /// Create default row properties
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Whether the row may break across pages
+    pub fn allows_split(&self) -> bool {
+        self.can_split && !self.cant_split
+    }
+
     /// Set the height
     pub fn with_height(mut self, height: f32, rule: HeightRule) -> Self {
         self.height = Some(height);
@@ -916,6 +972,9 @@ pub struct TableProperties {
     pub cell_spacing: Option<f32>,
     /// Table style ID reference
     pub style_id: Option<StyleId>,
+    /// Which conditional regions of the table style apply
+    #[serde(default)]
+    pub look: TableLook,
     /// Left indent from margin
     pub indent_left: Option<f32>,
     /// Auto-fit mode
@@ -924,6 +983,12 @@ pub struct TableProperties {
     pub table_layout: TableLayoutMode,
     /// Allow table to overlap other content
     pub allow_overlap: bool,
+    /// Alternative text for accessibility
+    #[serde(default)]
+    pub alt_text: Option<String>,
+    /// Whether the table is only used for layout and skipped by screen readers
+    #[serde(default)]
+    pub decorative: bool,
 }
 
 /// Table alignment options
@@ -1476,9 +1541,12 @@ mod tests {
 
     #[test]
     fn test_row_cant_split() {
+        assert!(RowProperties::new().allows_split());
+
         let props = RowProperties::new().prevent_split();
         assert!(props.cant_split);
         assert!(!props.can_split);
+        assert!(!props.allows_split());
     }
 
     #[test]
@@ -0,0 +1,500 @@
+//! Table styles - Conditional formatting regions, banding, and table look
+//!
+//! A table style carries formatting for the whole table plus conditional
+//! regions (header row, total row, first/last column, banded rows and
+//! columns, corner cells). Which regions take effect on a given table is
+//! controlled by its `TableLook` flags, mirroring DOCX `w:tblStylePr` and
+//! `w:tblLook`.
+
+use crate::{CellBorders, CharacterProperties, ParagraphProperties, TableBorders};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// =============================================================================
+// Conditional Regions
+// =============================================================================
+
+/// A conditional formatting region of a table style
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TableStyleRegion {
+    /// Every cell of the table
+    WholeTable,
+    /// Odd column bands
+    Band1Vertical,
+    /// Even column bands
+    Band2Vertical,
+    /// Odd row bands
+    Band1Horizontal,
+    /// Even row bands
+    Band2Horizontal,
+    /// First column
+    FirstColumn,
+    /// Last column
+    LastColumn,
+    /// Header row
+    FirstRow,
+    /// Total row
+    LastRow,
+    /// Top-right corner cell
+    TopRightCell,
+    /// Top-left corner cell
+    TopLeftCell,
+    /// Bottom-right corner cell
+    BottomRightCell,
+    /// Bottom-left corner cell
+    BottomLeftCell,
+}
+
+impl TableStyleRegion {
+    /// All regions in application order (later regions win)
+    pub const PRECEDENCE: [TableStyleRegion; 13] = [
+        TableStyleRegion::WholeTable,
+        TableStyleRegion::Band1Vertical,
+        TableStyleRegion::Band2Vertical,
+        TableStyleRegion::Band1Horizontal,
+        TableStyleRegion::Band2Horizontal,
+        TableStyleRegion::FirstColumn,
+        TableStyleRegion::LastColumn,
+        TableStyleRegion::FirstRow,
+        TableStyleRegion::LastRow,
+        TableStyleRegion::TopRightCell,
+        TableStyleRegion::TopLeftCell,
+        TableStyleRegion::BottomRightCell,
+        TableStyleRegion::BottomLeftCell,
+    ];
+
+    /// The DOCX `w:tblStylePr w:type` value for this region
+    pub fn docx_name(&self) -> &'static str {
+        match self {
+            TableStyleRegion::WholeTable => "wholeTable",
+            TableStyleRegion::Band1Vertical => "band1Vert",
+            TableStyleRegion::Band2Vertical => "band2Vert",
+            TableStyleRegion::Band1Horizontal => "band1Horz",
+            TableStyleRegion::Band2Horizontal => "band2Horz",
+            TableStyleRegion::FirstColumn => "firstCol",
+            TableStyleRegion::LastColumn => "lastCol",
+            TableStyleRegion::FirstRow => "firstRow",
+            TableStyleRegion::LastRow => "lastRow",
+            TableStyleRegion::TopRightCell => "neCell",
+            TableStyleRegion::TopLeftCell => "nwCell",
+            TableStyleRegion::BottomRightCell => "seCell",
+            TableStyleRegion::BottomLeftCell => "swCell",
+        }
+    }
+
+    /// Parse a DOCX `w:tblStylePr w:type` value
+    pub fn from_docx_name(name: &str) -> Option<Self> {
+        Self::PRECEDENCE.iter().copied().find(|r| r.docx_name() == name)
+    }
+}
+
+/// Formatting applied to the cells of a table style region
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TableRegionFormat {
+    /// Cell background/shading color (CSS color string)
+    pub shading: Option<String>,
+    /// Cell borders
+    pub borders: Option<CellBorders>,
+    /// Character formatting for text in the region
+    pub character_props: CharacterProperties,
+    /// Paragraph formatting for text in the region
+    pub paragraph_props: ParagraphProperties,
+}
+
+impl TableRegionFormat {
+    /// Create an empty region format
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the shading color
+    pub fn with_shading(mut self, color: &str) -> Self {
+        self.shading = Some(color.to_string());
+        self
+    }
+
+    /// Set the cell borders
+    pub fn with_borders(mut self, borders: CellBorders) -> Self {
+        self.borders = Some(borders);
+        self
+    }
+
+    /// Set the character properties
+    pub fn with_character_props(mut self, props: CharacterProperties) -> Self {
+        self.character_props = props;
+        self
+    }
+
+    /// Set the paragraph properties
+    pub fn with_paragraph_props(mut self, props: ParagraphProperties) -> Self {
+        self.paragraph_props = props;
+        self
+    }
+
+    /// Merge another format on top of this one
+    /// Values from `other` override values from `self` when present
+    pub fn merge(&self, other: &TableRegionFormat) -> TableRegionFormat {
+        let borders = match (&self.borders, &other.borders) {
+            (Some(base), Some(top)) => Some(CellBorders {
+                top: top.top.clone().or_else(|| base.top.clone()),
+                bottom: top.bottom.clone().or_else(|| base.bottom.clone()),
+                left: top.left.clone().or_else(|| base.left.clone()),
+                right: top.right.clone().or_else(|| base.right.clone()),
+                diagonal_down: top.diagonal_down.clone().or_else(|| base.diagonal_down.clone()),
+                diagonal_up: top.diagonal_up.clone().or_else(|| base.diagonal_up.clone()),
+            }),
+            (base, top) => top.clone().or_else(|| base.clone()),
+        };
+
+        TableRegionFormat {
+            shading: other.shading.clone().or_else(|| self.shading.clone()),
+            borders,
+            character_props: self.character_props.merge(&other.character_props),
+            paragraph_props: self.paragraph_props.merge(&other.paragraph_props),
+        }
+    }
+
+    /// Check if the format sets nothing
+    pub fn is_empty(&self) -> bool {
+        self.shading.is_none()
+            && self.borders.is_none()
+            && self.character_props.is_empty()
+            && self.paragraph_props.is_empty()
+    }
+}
+
+// =============================================================================
+// Table Look
+// =============================================================================
+
+/// Which conditional regions of a table style a table uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableLook {
+    /// Apply header row formatting
+    pub first_row: bool,
+    /// Apply total row formatting
+    pub last_row: bool,
+    /// Apply first column formatting
+    pub first_column: bool,
+    /// Apply last column formatting
+    pub last_column: bool,
+    /// Apply row banding
+    pub banded_rows: bool,
+    /// Apply column banding
+    pub banded_columns: bool,
+}
+
+impl Default for TableLook {
+    fn default() -> Self {
+        // Word's default look: header row, first column, banded rows
+        Self {
+            first_row: true,
+            last_row: false,
+            first_column: true,
+            last_column: false,
+            banded_rows: true,
+            banded_columns: false,
+        }
+    }
+}
+
+impl TableLook {
+    const FIRST_ROW: u16 = 0x0020;
+    const LAST_ROW: u16 = 0x0040;
+    const FIRST_COLUMN: u16 = 0x0080;
+    const LAST_COLUMN: u16 = 0x0100;
+    const NO_H_BAND: u16 = 0x0200;
+    const NO_V_BAND: u16 = 0x0400;
+
+    /// A look with no conditional regions enabled
+    pub fn none() -> Self {
+        Self {
+            first_row: false,
+            last_row: false,
+            first_column: false,
+            last_column: false,
+            banded_rows: false,
+            banded_columns: false,
+        }
+    }
+
+    /// Encode as the legacy DOCX `w:tblLook w:val` bitmask
+    pub fn to_bits(&self) -> u16 {
+        let mut bits = 0;
+        if self.first_row {
+            bits |= Self::FIRST_ROW;
+        }
+        if self.last_row {
+            bits |= Self::LAST_ROW;
+        }
+        if self.first_column {
+            bits |= Self::FIRST_COLUMN;
+        }
+        if self.last_column {
+            bits |= Self::LAST_COLUMN;
+        }
+        if !self.banded_rows {
+            bits |= Self::NO_H_BAND;
+        }
+        if !self.banded_columns {
+            bits |= Self::NO_V_BAND;
+        }
+        bits
+    }
+
+    /// Decode the legacy DOCX `w:tblLook w:val` bitmask
+    pub fn from_bits(bits: u16) -> Self {
+        Self {
+            first_row: bits & Self::FIRST_ROW != 0,
+            last_row: bits & Self::LAST_ROW != 0,
+            first_column: bits & Self::FIRST_COLUMN != 0,
+            last_column: bits & Self::LAST_COLUMN != 0,
+            banded_rows: bits & Self::NO_H_BAND == 0,
+            banded_columns: bits & Self::NO_V_BAND == 0,
+        }
+    }
+}
+
+// =============================================================================
+// Cell Position
+// =============================================================================
+
+/// A cell's place in its table, used to pick its conditional regions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableCellPosition {
+    /// Row index
+    pub row: usize,
+    /// First grid column covered by the cell
+    pub column: usize,
+    /// Number of grid columns covered by the cell
+    pub column_span: usize,
+    /// Number of rows in the table
+    pub row_count: usize,
+    /// Number of grid columns in the table
+    pub column_count: usize,
+}
+
+impl TableCellPosition {
+    /// Create a position for a single-column cell
+    pub fn new(row: usize, column: usize, row_count: usize, column_count: usize) -> Self {
+        Self {
+            row,
+            column,
+            column_span: 1,
+            row_count,
+            column_count,
+        }
+    }
+
+    /// Set the number of grid columns the cell covers
+    pub fn with_column_span(mut self, span: usize) -> Self {
+        self.column_span = span.max(1);
+        self
+    }
+
+    fn is_first_row(&self) -> bool {
+        self.row == 0
+    }
+
+    fn is_last_row(&self) -> bool {
+        self.row + 1 == self.row_count
+    }
+
+    fn is_first_column(&self) -> bool {
+        self.column == 0
+    }
+
+    fn is_last_column(&self) -> bool {
+        self.column + self.column_span >= self.column_count
+    }
+
+    /// The regions that apply to this cell, in application order
+    ///
+    /// Banding counts only the rows and columns left once the enabled
+    /// header/total rows and first/last columns are set aside.
+    pub fn regions(&self, look: &TableLook, row_band_size: u32, column_band_size: u32) -> Vec<TableStyleRegion> {
+        let first_row = look.first_row && self.is_first_row();
+        let last_row = look.last_row && self.is_last_row();
+        let first_column = look.first_column && self.is_first_column();
+        let last_column = look.last_column && self.is_last_column();
+
+        let mut regions = vec![TableStyleRegion::WholeTable];
+
+        if look.banded_columns && !first_column && !last_column {
+            let index = self.column - usize::from(look.first_column);
+            let band = index / column_band_size.max(1) as usize;
+            regions.push(if band % 2 == 0 {
+                TableStyleRegion::Band1Vertical
+            } else {
+                TableStyleRegion::Band2Vertical
+            });
+        }
+        if look.banded_rows && !first_row && !last_row {
+            let index = self.row - usize::from(look.first_row);
+            let band = index / row_band_size.max(1) as usize;
+            regions.push(if band % 2 == 0 {
+                TableStyleRegion::Band1Horizontal
+            } else {
+                TableStyleRegion::Band2Horizontal
+            });
+        }
+        if first_column {
+            regions.push(TableStyleRegion::FirstColumn);
+        }
+        if last_column {
+            regions.push(TableStyleRegion::LastColumn);
+        }
+        if first_row {
+            regions.push(TableStyleRegion::FirstRow);
+        }
+        if last_row {
+            regions.push(TableStyleRegion::LastRow);
+        }
+        if first_row && last_column {
+            regions.push(TableStyleRegion::TopRightCell);
+        }
+        if first_row && first_column {
+            regions.push(TableStyleRegion::TopLeftCell);
+        }
+        if last_row && last_column {
+            regions.push(TableStyleRegion::BottomRightCell);
+        }
+        if last_row && first_column {
+            regions.push(TableStyleRegion::BottomLeftCell);
+        }
+
+        regions
+    }
+}
+
+// =============================================================================
+// Table Style Properties
+// =============================================================================
+
+/// Table-specific properties of a table style
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableStyleProperties {
+    /// Table borders
+    pub borders: Option<TableBorders>,
+    /// Number of rows in each row band
+    pub row_band_size: u32,
+    /// Number of columns in each column band
+    pub column_band_size: u32,
+    /// Formatting for each conditional region
+    pub regions: HashMap<TableStyleRegion, TableRegionFormat>,
+}
+
+impl Default for TableStyleProperties {
+    fn default() -> Self {
+        Self {
+            borders: None,
+            row_band_size: 1,
+            column_band_size: 1,
+            regions: HashMap::new(),
+        }
+    }
+}
+
+impl TableStyleProperties {
+    /// Create empty table style properties
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the table borders
+    pub fn with_borders(mut self, borders: TableBorders) -> Self {
+        self.borders = Some(borders);
+        self
+    }
+
+    /// Set the formatting of a region
+    pub fn with_region(mut self, region: TableStyleRegion, format: TableRegionFormat) -> Self {
+        self.regions.insert(region, format);
+        self
+    }
+
+    /// Get the formatting of a region
+    pub fn region(&self, region: TableStyleRegion) -> Option<&TableRegionFormat> {
+        self.regions.get(&region)
+    }
+
+    /// Check if nothing table-specific is set
+    pub fn is_empty(&self) -> bool {
+        self.borders.is_none()
+            && self.row_band_size == 1
+            && self.column_band_size == 1
+            && self.regions.is_empty()
+    }
+
+    /// Merge a derived style's properties on top of these
+    pub fn merge(&self, other: &TableStyleProperties) -> TableStyleProperties {
+        let mut regions = self.regions.clone();
+        for (region, format) in &other.regions {
+            let merged = match regions.get(region) {
+                Some(base) => base.merge(format),
+                None => format.clone(),
+            };
+            regions.insert(*region, merged);
+        }
+
+        TableStyleProperties {
+            borders: other.borders.clone().or_else(|| self.borders.clone()),
+            row_band_size: if other.row_band_size != 1 { other.row_band_size } else { self.row_band_size },
+            column_band_size: if other.column_band_size != 1 {
+                other.column_band_size
+            } else {
+                self.column_band_size
+            },
+            regions,
+        }
+    }
+
+    /// Resolve the formatting a cell gets from this style
+    pub fn cell_format(&self, position: &TableCellPosition, look: &TableLook) -> TableRegionFormat {
+        position
+            .regions(look, self.row_band_size, self.column_band_size)
+            .into_iter()
+            .filter_map(|region| self.regions.get(&region))
+            .fold(TableRegionFormat::default(), |acc, format| acc.merge(format))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn banded_style() -> TableStyleProperties {
+        TableStyleProperties::new()
+            .with_region(TableStyleRegion::FirstRow, TableRegionFormat::new().with_shading("#4472C4"))
+            .with_region(TableStyleRegion::Band1Horizontal, TableRegionFormat::new().with_shading("#D9E2F3"))
+            .with_region(TableStyleRegion::LastRow, TableRegionFormat::new().with_shading("#FFFFFF"))
+    }
+
+    #[test]
+    fn test_banding_skips_header_row() {
+        let style = banded_style();
+        let look = TableLook::default();
+        let shading = |row| style.cell_format(&TableCellPosition::new(row, 1, 5, 3), &look).shading;
+
+        assert_eq!(shading(0).as_deref(), Some("#4472C4"));
+        assert_eq!(shading(1).as_deref(), Some("#D9E2F3"));
+        assert_eq!(shading(2), None);
+        assert_eq!(shading(3).as_deref(), Some("#D9E2F3"));
+
+        // The total row only applies when the look enables it
+        assert_eq!(shading(4), None);
+        let look = TableLook { last_row: true, ..look };
+        assert_eq!(
+            style.cell_format(&TableCellPosition::new(4, 1, 5, 3), &look).shading.as_deref(),
+            Some("#FFFFFF")
+        );
+    }
+
+    #[test]
+    fn test_table_look_bits() {
+        let look = TableLook::default();
+        assert_eq!(look.to_bits(), 0x04A0);
+        assert_eq!(TableLook::from_bits(0x04A0), look);
+        assert_eq!(TableLook::from_bits(TableLook::none().to_bits()), TableLook::none());
+    }
+}
@@ -0,0 +1,249 @@
+//! Text effects - WordArt-style fills, outlines, glow, and reflection on runs
+//!
+//! These are the Word 2010 (`w14`) run effects. They decorate the glyphs of
+//! a run on top of its regular formatting; a run with a text fill is drawn
+//! with that fill instead of its text color.
+
+use crate::{DocumentTheme, ShapeColor, ThemeColor};
+use serde::{Deserialize, Serialize};
+
+/// A color used by a text effect
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum EffectColor {
+    /// A literal color
+    Rgb(ShapeColor),
+    /// A document theme color slot
+    Theme(ThemeColor),
+}
+
+impl EffectColor {
+    /// Resolve to a concrete color against the document theme
+    pub fn resolve(&self, theme: &DocumentTheme) -> ShapeColor {
+        match self {
+            EffectColor::Rgb(color) => *color,
+            EffectColor::Theme(slot) => {
+                ShapeColor::from_hex(&theme.color(*slot)).unwrap_or(ShapeColor::BLACK)
+            }
+        }
+    }
+}
+
+impl From<ShapeColor> for EffectColor {
+    fn from(color: ShapeColor) -> Self {
+        EffectColor::Rgb(color)
+    }
+}
+
+impl From<ThemeColor> for EffectColor {
+    fn from(slot: ThemeColor) -> Self {
+        EffectColor::Theme(slot)
+    }
+}
+
+/// A stop in a text gradient
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TextGradientStop {
+    /// Color at this stop
+    pub color: EffectColor,
+    /// Position (0.0 to 1.0)
+    pub position: f32,
+    /// Transparency at this stop (0.0 = opaque, 1.0 = transparent)
+    pub transparency: f32,
+}
+
+impl TextGradientStop {
+    pub fn new(color: impl Into<EffectColor>, position: f32) -> Self {
+        Self {
+            color: color.into(),
+            position: position.clamp(0.0, 1.0),
+            transparency: 0.0,
+        }
+    }
+}
+
+/// Fill painted inside text glyphs or along their outline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TextFill {
+    /// No fill (hollow glyphs)
+    NoFill,
+    /// Solid color fill
+    Solid {
+        color: EffectColor,
+        /// Transparency (0.0 = opaque, 1.0 = transparent)
+        transparency: f32,
+    },
+    /// Linear gradient fill
+    LinearGradient {
+        /// Angle in degrees (0 = left-to-right, 90 = top-to-bottom)
+        angle: f32,
+        stops: Vec<TextGradientStop>,
+    },
+    /// Radial gradient fill from the glyphs' center
+    RadialGradient { stops: Vec<TextGradientStop> },
+}
+
+impl TextFill {
+    /// Create an opaque solid fill
+    pub fn solid(color: impl Into<EffectColor>) -> Self {
+        TextFill::Solid {
+            color: color.into(),
+            transparency: 0.0,
+        }
+    }
+
+    /// Create a linear gradient fill between two colors
+    pub fn linear(angle: f32, start: impl Into<EffectColor>, end: impl Into<EffectColor>) -> Self {
+        TextFill::LinearGradient {
+            angle,
+            stops: vec![TextGradientStop::new(start, 0.0), TextGradientStop::new(end, 1.0)],
+        }
+    }
+}
+
+/// Outline stroked around text glyphs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextOutline {
+    /// Stroke width in points
+    pub width: f32,
+    /// Stroke fill
+    pub fill: TextFill,
+}
+
+impl TextOutline {
+    pub fn new(width: f32, fill: TextFill) -> Self {
+        Self { width, fill }
+    }
+}
+
+/// Soft colored glow around text glyphs
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TextGlow {
+    /// Glow radius in points
+    pub radius: f32,
+    /// Glow color
+    pub color: EffectColor,
+    /// Transparency (0.0 = opaque, 1.0 = transparent)
+    pub transparency: f32,
+}
+
+impl TextGlow {
+    pub fn new(radius: f32, color: impl Into<EffectColor>) -> Self {
+        Self {
+            radius,
+            color: color.into(),
+            transparency: 0.0,
+        }
+    }
+
+    pub fn with_transparency(mut self, transparency: f32) -> Self {
+        self.transparency = transparency.clamp(0.0, 1.0);
+        self
+    }
+}
+
+/// Mirrored copy of the text below its baseline, fading out
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TextReflection {
+    /// Blur radius in points
+    pub blur_radius: f32,
+    /// Opacity where the reflection starts (0.0 to 1.0)
+    pub start_opacity: f32,
+    /// Opacity where the reflection ends (0.0 to 1.0)
+    pub end_opacity: f32,
+    /// Fraction of the text height the fade covers (0.0 to 1.0)
+    pub end_position: f32,
+    /// Gap between the text and its reflection in points
+    pub distance: f32,
+}
+
+impl Default for TextReflection {
+    /// Word's "tight reflection, touching" preset
+    fn default() -> Self {
+        Self {
+            blur_radius: 0.5,
+            start_opacity: 0.5,
+            end_opacity: 0.0,
+            end_position: 0.35,
+            distance: 0.0,
+        }
+    }
+}
+
+/// Rich text effects on a run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextEffects {
+    /// Glyph fill (replaces the text color)
+    pub fill: Option<TextFill>,
+    /// Glyph outline
+    pub outline: Option<TextOutline>,
+    /// Glow
+    pub glow: Option<TextGlow>,
+    /// Reflection
+    pub reflection: Option<TextReflection>,
+}
+
+impl TextEffects {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_fill(mut self, fill: TextFill) -> Self {
+        self.fill = Some(fill);
+        self
+    }
+
+    pub fn with_outline(mut self, outline: TextOutline) -> Self {
+        self.outline = Some(outline);
+        self
+    }
+
+    pub fn with_glow(mut self, glow: TextGlow) -> Self {
+        self.glow = Some(glow);
+        self
+    }
+
+    pub fn with_reflection(mut self, reflection: TextReflection) -> Self {
+        self.reflection = Some(reflection);
+        self
+    }
+
+    /// Check if no effect is set
+    pub fn is_empty(&self) -> bool {
+        self.fill.is_none()
+            && self.outline.is_none()
+            && self.glow.is_none()
+            && self.reflection.is_none()
+    }
+
+    /// Layer `other` on top: each effect it sets replaces ours
+    pub fn merge(&self, other: &TextEffects) -> TextEffects {
+        TextEffects {
+            fill: other.fill.clone().or_else(|| self.fill.clone()),
+            outline: other.outline.clone().or_else(|| self.outline.clone()),
+            glow: other.glow.or(self.glow),
+            reflection: other.reflection.or(self.reflection),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effects_merge_and_theme_colors() {
+        let style = TextEffects::new()
+            .with_fill(TextFill::linear(90.0, ThemeColor::Accent1, ShapeColor::WHITE))
+            .with_glow(TextGlow::new(5.0, ThemeColor::Accent2).with_transparency(0.4));
+        let direct = TextEffects::new().with_glow(TextGlow::new(8.0, ShapeColor::RED));
+
+        let merged = style.merge(&direct);
+        assert_eq!(merged.fill, style.fill);
+        assert_eq!(merged.glow.unwrap().radius, 8.0);
+        assert!(!merged.is_empty());
+
+        let theme = DocumentTheme::default().with_color(ThemeColor::Accent1, "#112233");
+        let color = EffectColor::Theme(ThemeColor::Accent1).resolve(&theme);
+        assert_eq!(color, ShapeColor::rgb(0x11, 0x22, 0x33));
+    }
+}
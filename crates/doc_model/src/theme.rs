@@ -0,0 +1,399 @@
+//! Document themes - color scheme, font scheme, and effects
+//!
+//! A theme defines named color slots (Dark 1, Accent 1, ...) and a major
+//! (headings) / minor (body) font pair. Character properties can reference
+//! these slots instead of literal values, so switching the theme recolors
+//! and re-fonts every theme-aware style and run.
+
+use crate::CharacterProperties;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// =============================================================================
+// Theme Colors
+// =============================================================================
+
+/// A color slot in the theme's color scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ThemeColor {
+    /// Dark 1 (usually the text color)
+    Dark1,
+    /// Light 1 (usually the background color)
+    Light1,
+    /// Dark 2
+    Dark2,
+    /// Light 2
+    Light2,
+    Accent1,
+    Accent2,
+    Accent3,
+    Accent4,
+    Accent5,
+    Accent6,
+    /// Hyperlink
+    Hyperlink,
+    /// Followed hyperlink
+    FollowedHyperlink,
+}
+
+impl ThemeColor {
+    /// All color slots in scheme order
+    pub const ALL: [ThemeColor; 12] = [
+        ThemeColor::Dark1,
+        ThemeColor::Light1,
+        ThemeColor::Dark2,
+        ThemeColor::Light2,
+        ThemeColor::Accent1,
+        ThemeColor::Accent2,
+        ThemeColor::Accent3,
+        ThemeColor::Accent4,
+        ThemeColor::Accent5,
+        ThemeColor::Accent6,
+        ThemeColor::Hyperlink,
+        ThemeColor::FollowedHyperlink,
+    ];
+
+    /// Element name in the theme's color scheme (`dk1`, `accent1`, ...)
+    pub fn scheme_name(&self) -> &'static str {
+        match self {
+            ThemeColor::Dark1 => "dk1",
+            ThemeColor::Light1 => "lt1",
+            ThemeColor::Dark2 => "dk2",
+            ThemeColor::Light2 => "lt2",
+            ThemeColor::Accent1 => "accent1",
+            ThemeColor::Accent2 => "accent2",
+            ThemeColor::Accent3 => "accent3",
+            ThemeColor::Accent4 => "accent4",
+            ThemeColor::Accent5 => "accent5",
+            ThemeColor::Accent6 => "accent6",
+            ThemeColor::Hyperlink => "hlink",
+            ThemeColor::FollowedHyperlink => "folHlink",
+        }
+    }
+
+    /// Name used when referencing the slot from text (`text1`, `accent1`, ...)
+    pub fn reference_name(&self) -> &'static str {
+        match self {
+            ThemeColor::Dark1 => "text1",
+            ThemeColor::Light1 => "background1",
+            ThemeColor::Dark2 => "text2",
+            ThemeColor::Light2 => "background2",
+            ThemeColor::Hyperlink => "hyperlink",
+            ThemeColor::FollowedHyperlink => "followedHyperlink",
+            accent => accent.scheme_name(),
+        }
+    }
+
+    /// Parse a scheme or reference name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "dk1" | "dark1" | "text1" | "tx1" => ThemeColor::Dark1,
+            "lt1" | "light1" | "background1" | "bg1" => ThemeColor::Light1,
+            "dk2" | "dark2" | "text2" | "tx2" => ThemeColor::Dark2,
+            "lt2" | "light2" | "background2" | "bg2" => ThemeColor::Light2,
+            "accent1" => ThemeColor::Accent1,
+            "accent2" => ThemeColor::Accent2,
+            "accent3" => ThemeColor::Accent3,
+            "accent4" => ThemeColor::Accent4,
+            "accent5" => ThemeColor::Accent5,
+            "accent6" => ThemeColor::Accent6,
+            "hlink" | "hyperlink" => ThemeColor::Hyperlink,
+            "folHlink" | "followedHyperlink" => ThemeColor::FollowedHyperlink,
+            _ => return None,
+        })
+    }
+}
+
+/// A reference to a theme color, optionally lightened or darkened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThemeColorRef {
+    /// The referenced slot
+    pub color: ThemeColor,
+    /// Tint (0-255): lower values mix in more white
+    #[serde(default)]
+    pub tint: Option<u8>,
+    /// Shade (0-255): lower values mix in more black
+    #[serde(default)]
+    pub shade: Option<u8>,
+}
+
+impl ThemeColorRef {
+    /// Reference a theme color as-is
+    pub fn new(color: ThemeColor) -> Self {
+        Self {
+            color,
+            tint: None,
+            shade: None,
+        }
+    }
+
+    /// Lighten the color
+    pub fn with_tint(mut self, tint: u8) -> Self {
+        self.tint = Some(tint);
+        self
+    }
+
+    /// Darken the color
+    pub fn with_shade(mut self, shade: u8) -> Self {
+        self.shade = Some(shade);
+        self
+    }
+}
+
+// =============================================================================
+// Theme Fonts
+// =============================================================================
+
+/// A font slot in the theme's font scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ThemeFont {
+    /// Headings font
+    Major,
+    /// Body font
+    Minor,
+}
+
+/// Typefaces for one font slot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThemeFontSet {
+    /// Latin typeface
+    pub latin: String,
+    /// East Asian typeface (empty for none)
+    #[serde(default)]
+    pub east_asian: String,
+    /// Complex script typeface (empty for none)
+    #[serde(default)]
+    pub complex_script: String,
+}
+
+impl ThemeFontSet {
+    /// Create a font set with only a Latin typeface
+    pub fn new(latin: impl Into<String>) -> Self {
+        Self {
+            latin: latin.into(),
+            east_asian: String::new(),
+            complex_script: String::new(),
+        }
+    }
+}
+
+// =============================================================================
+// Theme
+// =============================================================================
+
+/// Color scheme
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColorScheme {
+    /// Scheme name
+    pub name: String,
+    /// Colors by slot, as `#RRGGBB`
+    pub colors: HashMap<ThemeColor, String>,
+}
+
+/// Font scheme
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FontScheme {
+    /// Scheme name
+    pub name: String,
+    /// Headings fonts
+    pub major: ThemeFontSet,
+    /// Body fonts
+    pub minor: ThemeFontSet,
+}
+
+/// Effect scheme (fills, lines, and effects used by shapes)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EffectScheme {
+    /// Scheme name
+    pub name: String,
+    /// Source markup of the scheme, kept so imported effects round-trip
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// A document theme
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentTheme {
+    /// Theme name
+    pub name: String,
+    /// Color scheme
+    pub colors: ColorScheme,
+    /// Font scheme
+    pub fonts: FontScheme,
+    /// Effect scheme
+    #[serde(default)]
+    pub effects: EffectScheme,
+}
+
+impl Default for DocumentTheme {
+    fn default() -> Self {
+        Self::office()
+    }
+}
+
+impl DocumentTheme {
+    /// The standard Office theme
+    pub fn office() -> Self {
+        let colors = [
+            (ThemeColor::Dark1, "#000000"),
+            (ThemeColor::Light1, "#FFFFFF"),
+            (ThemeColor::Dark2, "#44546A"),
+            (ThemeColor::Light2, "#E7E6E6"),
+            (ThemeColor::Accent1, "#4472C4"),
+            (ThemeColor::Accent2, "#ED7D31"),
+            (ThemeColor::Accent3, "#A5A5A5"),
+            (ThemeColor::Accent4, "#FFC000"),
+            (ThemeColor::Accent5, "#5B9BD5"),
+            (ThemeColor::Accent6, "#70AD47"),
+            (ThemeColor::Hyperlink, "#0563C1"),
+            (ThemeColor::FollowedHyperlink, "#954F72"),
+        ];
+
+        Self {
+            name: "Office Theme".to_string(),
+            colors: ColorScheme {
+                name: "Office".to_string(),
+                colors: colors.iter().map(|(slot, hex)| (*slot, hex.to_string())).collect(),
+            },
+            fonts: FontScheme {
+                name: "Office".to_string(),
+                major: ThemeFontSet::new("Calibri Light"),
+                minor: ThemeFontSet::new("Calibri"),
+            },
+            effects: EffectScheme {
+                name: "Office".to_string(),
+                source: None,
+            },
+        }
+    }
+
+    /// Set a color slot
+    pub fn with_color(mut self, slot: ThemeColor, color: impl Into<String>) -> Self {
+        self.set_color(slot, color);
+        self
+    }
+
+    /// Set the major and minor Latin fonts
+    pub fn with_fonts(mut self, major: impl Into<String>, minor: impl Into<String>) -> Self {
+        self.fonts.major.latin = major.into();
+        self.fonts.minor.latin = minor.into();
+        self
+    }
+
+    /// Set a color slot
+    pub fn set_color(&mut self, slot: ThemeColor, color: impl Into<String>) {
+        self.colors.colors.insert(slot, color.into());
+    }
+
+    /// Get the color of a slot as `#RRGGBB`
+    ///
+    /// Slots missing from the scheme fall back to the Office theme.
+    pub fn color(&self, slot: ThemeColor) -> String {
+        self.colors
+            .colors
+            .get(&slot)
+            .cloned()
+            .or_else(|| Self::office().colors.colors.remove(&slot))
+            .unwrap_or_else(|| "#000000".to_string())
+    }
+
+    /// Resolve a theme color reference, applying tint and shade
+    pub fn resolve_color(&self, color_ref: &ThemeColorRef) -> String {
+        let base = self.color(color_ref.color);
+        let Some(mut rgb) = parse_hex_rgb(&base) else {
+            return base;
+        };
+
+        if let Some(tint) = color_ref.tint {
+            let tint = tint as f32 / 255.0;
+            rgb = rgb.map(|c| (c as f32 * tint + 255.0 * (1.0 - tint)).round() as u8);
+        }
+        if let Some(shade) = color_ref.shade {
+            let shade = shade as f32 / 255.0;
+            rgb = rgb.map(|c| (c as f32 * shade).round() as u8);
+        }
+        format!("#{:02X}{:02X}{:02X}", rgb[0], rgb[1], rgb[2])
+    }
+
+    /// Get the Latin typeface of a font slot
+    pub fn font(&self, slot: ThemeFont) -> &str {
+        match slot {
+            ThemeFont::Major => &self.fonts.major.latin,
+            ThemeFont::Minor => &self.fonts.minor.latin,
+        }
+    }
+
+    /// Replace theme references in character properties with this theme's values
+    pub fn resolve_character_props(&self, props: &CharacterProperties) -> CharacterProperties {
+        let mut resolved = props.clone();
+        if let Some(color_ref) = &props.theme_color {
+            resolved.color = Some(self.resolve_color(color_ref));
+        }
+        if let Some(font) = props.theme_font {
+            resolved.font_family = Some(self.font(font).to_string());
+        }
+        resolved
+    }
+}
+
+/// Parse `#RRGGBB` (or `RRGGBB`) into components
+fn parse_hex_rgb(color: &str) -> Option<[u8; 3]> {
+    let hex = color.trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let component = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([component(0)?, component(2)?, component(4)?])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_color_names() {
+        for slot in ThemeColor::ALL {
+            assert_eq!(ThemeColor::from_name(slot.scheme_name()), Some(slot));
+            assert_eq!(ThemeColor::from_name(slot.reference_name()), Some(slot));
+        }
+        assert_eq!(ThemeColor::from_name("text1"), Some(ThemeColor::Dark1));
+        assert_eq!(ThemeColor::from_name("unknown"), None);
+    }
+
+    #[test]
+    fn test_resolve_color_with_tint_and_shade() {
+        let theme = DocumentTheme::office().with_color(ThemeColor::Accent1, "#808080");
+
+        assert_eq!(theme.resolve_color(&ThemeColorRef::new(ThemeColor::Accent1)), "#808080");
+        assert_eq!(
+            theme.resolve_color(&ThemeColorRef::new(ThemeColor::Accent1).with_shade(0)),
+            "#000000"
+        );
+        assert_eq!(
+            theme.resolve_color(&ThemeColorRef::new(ThemeColor::Accent1).with_tint(0)),
+            "#FFFFFF"
+        );
+    }
+
+    #[test]
+    fn test_resolve_character_props() {
+        let theme = DocumentTheme::office().with_fonts("Georgia", "Verdana");
+        let props = CharacterProperties {
+            color: Some("#123456".to_string()),
+            theme_color: Some(ThemeColorRef::new(ThemeColor::Accent2)),
+            theme_font: Some(ThemeFont::Major),
+            ..Default::default()
+        };
+
+        let resolved = theme.resolve_character_props(&props);
+        assert_eq!(resolved.color.as_deref(), Some("#ED7D31"));
+        assert_eq!(resolved.font_family.as_deref(), Some("Georgia"));
+
+        // Literal values are untouched
+        let literal = CharacterProperties {
+            color: Some("#123456".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(theme.resolve_character_props(&literal), literal);
+    }
+}
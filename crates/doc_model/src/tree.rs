@@ -1,13 +1,13 @@
 //! Document tree operations and storage
 
 use crate::{
-    Bookmark, BookmarkRange, BookmarkRegistry, BookmarkValidationError, CellPadding,
+    Bookmark, BookmarkRange, BookmarkRegistry, BookmarkValidationError, BuildingBlockCollection, CellPadding,
     CharacterProperties, Comment, CommentId, CommentReply, CommentStore, CommentValidationError,
-    ComputedCharacterProperties, ComputedParagraphProperties, Document, DocModelError,
-    EndnoteProperties, FootnoteProperties, Hyperlink, ImageNode, Node, NodeId, NodeType, Note,
-    NoteId, NoteRef, NoteStore, NoteType, NumberingRegistry, Paragraph, ParagraphProperties,
-    Position, ReplyId, Result, Run, Selection, ShapeNode, StyleId, StyleRegistry, Table, TableCell,
-    TableRow, TextBox,
+    ComputedCharacterProperties, ComputedParagraphProperties, Document, DrawingCanvas, DocModelError, DocumentTheme,
+    EmbeddedObject, EndnoteProperties, Field, FootnoteProperties, HeaderFooter, Hyperlink, ImageNode, InkNode, Node, NodeId,
+    NodeType, Note, NoteId, NoteRef, NoteStore, NoteType, NumberingRegistry, Paragraph, RestartNumbering,
+    ParagraphProperties, Position, PreservedMarkup, ReplyId, Result, Run, Section, Selection, ShapeNode, StyleId,
+    StyleRegistry, Table, TableCell, TableCellPosition, TableRegionFormat, TableRow, TextBox,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -24,6 +24,14 @@ pub struct NodeStorage {
     pub tables: HashMap<NodeId, Table>,
     pub table_rows: HashMap<NodeId, TableRow>,
     pub table_cells: HashMap<NodeId, TableCell>,
+    #[serde(default)]
+    pub fields: HashMap<NodeId, Field>,
+    #[serde(default)]
+    pub inks: HashMap<NodeId, InkNode>,
+    #[serde(default)]
+    pub embedded_objects: HashMap<NodeId, EmbeddedObject>,
+    #[serde(default)]
+    pub canvases: HashMap<NodeId, DrawingCanvas>,
 }
 
 /// The complete document tree structure
@@ -48,6 +56,18 @@ pub struct DocumentTree {
     /// Footnote and endnote store for this document
     #[serde(default)]
     pub notes: NoteStore,
+    /// Theme colors and fonts for this document
+    #[serde(default)]
+    pub theme: DocumentTheme,
+    /// Sections in document order (empty = one section with default setup)
+    #[serde(default)]
+    pub sections: Vec<Section>,
+    /// Imported markup the model doesn't represent, kept for saving back
+    #[serde(default)]
+    pub preserved: PreservedMarkup,
+    /// Building blocks stored with the document (a template's Quick Parts)
+    #[serde(default)]
+    pub building_blocks: BuildingBlockCollection,
 }
 
 impl DocumentTree {
@@ -61,6 +81,10 @@ impl DocumentTree {
             bookmarks: BookmarkRegistry::default(),
             numbering: NumberingRegistry::default(),
             notes: NoteStore::default(),
+            theme: DocumentTheme::default(),
+            sections: Vec::new(),
+            preserved: PreservedMarkup::default(),
+            building_blocks: BuildingBlockCollection::default(),
         }
     }
 
@@ -110,6 +134,94 @@ impl DocumentTree {
         Ok(())
     }
 
+    /// Find all paragraphs and runs that use a style, in document order
+    ///
+    /// A style's linked partner counts as the same style, so searching for a
+    /// linked paragraph style also finds runs carrying its character style.
+    /// Used for "Select All N Instances".
+    pub fn find_nodes_with_style(&self, style_id: &StyleId) -> Vec<NodeId> {
+        let linked = self.styles.get(style_id).and_then(|s| s.linked_style.as_ref());
+        let matches = |id: Option<&StyleId>| id.is_some_and(|id| id == style_id || Some(id) == linked);
+
+        let mut result = Vec::new();
+        let mut stack: Vec<NodeId> = self.document.children().iter().rev().copied().collect();
+        while let Some(id) = stack.pop() {
+            if let Some(para) = self.nodes.paragraphs.get(&id) {
+                if matches(para.paragraph_style_id.as_ref()) {
+                    result.push(id);
+                }
+            } else if let Some(run) = self.nodes.runs.get(&id) {
+                if matches(run.character_style_id.as_ref()) {
+                    result.push(id);
+                }
+            }
+            stack.extend(self.child_ids(id).iter().rev());
+        }
+        result
+    }
+
+    /// Replace every use of one style with another
+    ///
+    /// Both styles must exist and have the same type. When both are linked
+    /// styles, uses of the linked character styles are replaced as well.
+    /// Returns the number of nodes changed.
+    pub fn replace_style(&mut self, from: &StyleId, to: &StyleId) -> Result<usize> {
+        let (from_style, to_style) = match (self.styles.get(from), self.styles.get(to)) {
+            (Some(from_style), Some(to_style)) => (from_style, to_style),
+            _ => {
+                return Err(DocModelError::InvalidOperation(format!(
+                    "Cannot replace style {} with {}: style not found",
+                    from, to
+                )))
+            }
+        };
+        if from_style.style_type != to_style.style_type {
+            return Err(DocModelError::InvalidOperation(format!(
+                "Cannot replace {:?} style {} with {:?} style {}",
+                from_style.style_type, from, to_style.style_type, to
+            )));
+        }
+
+        let mut replacements = vec![(from.clone(), to.clone())];
+        if let (Some(from_linked), Some(to_linked)) = (&from_style.linked_style, &to_style.linked_style) {
+            replacements.push((from_linked.clone(), to_linked.clone()));
+        }
+        let replacement_for = |id: &Option<StyleId>| {
+            replacements
+                .iter()
+                .find(|(old, _)| id.as_ref() == Some(old))
+                .map(|(_, new)| new.clone())
+        };
+
+        let mut count = 0;
+        for para in self.nodes.paragraphs.values_mut() {
+            if let Some(new) = replacement_for(&para.paragraph_style_id) {
+                para.set_paragraph_style(Some(new));
+                count += 1;
+            }
+        }
+        for run in self.nodes.runs.values_mut() {
+            if let Some(new) = replacement_for(&run.character_style_id) {
+                run.set_character_style(Some(new));
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Child IDs of any container node
+    fn child_ids(&self, id: NodeId) -> &[NodeId] {
+        match self.node_type(id) {
+            Some(NodeType::Paragraph) => self.nodes.paragraphs[&id].children(),
+            Some(NodeType::Hyperlink) => self.nodes.hyperlinks[&id].children(),
+            Some(NodeType::Table) => self.nodes.tables[&id].children(),
+            Some(NodeType::TableRow) => self.nodes.table_rows[&id].children(),
+            Some(NodeType::TableCell) => self.nodes.table_cells[&id].children(),
+            Some(NodeType::TextBox) => self.nodes.textboxes[&id].children(),
+            _ => &[],
+        }
+    }
+
     /// Apply direct formatting to a paragraph
     pub fn apply_paragraph_direct_formatting(
         &mut self,
@@ -162,10 +274,35 @@ impl DocumentTree {
     /// Compute the resolved character properties for a run
     pub fn compute_character_properties(&self, run_id: NodeId) -> Option<CharacterProperties> {
         let run = self.nodes.runs.get(&run_id)?;
-        Some(self.styles.resolve_character_props(
+        let props = self.styles.resolve_character_props(
             run.character_style_id.as_ref(),
             &run.direct_formatting,
-        ))
+        );
+        Some(self.theme.resolve_character_props(&props))
+    }
+
+    /// Check if spelling and grammar checking skip a run
+    ///
+    /// The flag resolves like other character formatting, with the
+    /// paragraph style's setting under the run's character style and direct
+    /// formatting, so a code paragraph style turns proofing off for all of
+    /// its text.
+    pub fn is_no_proof(&self, run_id: NodeId) -> bool {
+        let Some(run) = self.nodes.runs.get(&run_id) else {
+            return false;
+        };
+        let paragraph_style = run
+            .parent()
+            .and_then(|id| self.nodes.paragraphs.get(&id))
+            .and_then(|para| para.paragraph_style_id.as_ref())
+            .and_then(|id| self.styles.resolve(id))
+            .and_then(|style| style.character_props.no_proof);
+
+        self.styles
+            .resolve_character_props(run.character_style_id.as_ref(), &run.direct_formatting)
+            .no_proof
+            .or(paragraph_style)
+            .unwrap_or(false)
     }
 
     /// Compute paragraph properties with source tracking for the inspector
@@ -186,10 +323,23 @@ impl DocumentTree {
         run_id: NodeId,
     ) -> Option<ComputedCharacterProperties> {
         let run = self.nodes.runs.get(&run_id)?;
-        Some(self.styles.compute_character_props_with_sources(
+        let mut computed = self.styles.compute_character_props_with_sources(
             run.character_style_id.as_ref(),
             &run.direct_formatting,
-        ))
+        );
+
+        // Theme references take the value from the document theme
+        let props = self.styles.resolve_character_props(
+            run.character_style_id.as_ref(),
+            &run.direct_formatting,
+        );
+        if let Some(color_ref) = &props.theme_color {
+            computed.color.value = self.theme.resolve_color(color_ref);
+        }
+        if let Some(font) = props.theme_font {
+            computed.font_family.value = self.theme.font(font).to_string();
+        }
+        Some(computed)
     }
 
     /// Internal helper to compute paragraph properties with sources
@@ -329,6 +479,18 @@ impl DocumentTree {
         if self.nodes.table_cells.contains_key(&id) {
             return Some(NodeType::TableCell);
         }
+        if self.nodes.fields.contains_key(&id) {
+            return Some(NodeType::Field);
+        }
+        if self.nodes.inks.contains_key(&id) {
+            return Some(NodeType::Ink);
+        }
+        if self.nodes.embedded_objects.contains_key(&id) {
+            return Some(NodeType::EmbeddedObject);
+        }
+        if self.nodes.canvases.contains_key(&id) {
+            return Some(NodeType::Canvas);
+        }
         None
     }
 
@@ -478,6 +640,56 @@ impl DocumentTree {
         self.nodes.images.values().filter(|img| img.is_floating())
     }
 
+    // =========================================================================
+    // Embedded Object Methods
+    // =========================================================================
+
+    /// Get an embedded object by ID
+    pub fn get_embedded_object(&self, id: NodeId) -> Option<&EmbeddedObject> {
+        self.nodes.embedded_objects.get(&id)
+    }
+
+    /// Get a mutable embedded object by ID
+    pub fn get_embedded_object_mut(&mut self, id: NodeId) -> Option<&mut EmbeddedObject> {
+        self.nodes.embedded_objects.get_mut(&id)
+    }
+
+    /// Insert an embedded object into a paragraph
+    pub fn insert_embedded_object(&mut self, mut object: EmbeddedObject, para_id: NodeId, index: Option<usize>) -> Result<NodeId> {
+        let object_id = object.id();
+        object.set_parent(Some(para_id));
+
+        let para = self.nodes.paragraphs.get_mut(&para_id)
+            .ok_or(DocModelError::NodeNotFound(para_id.as_uuid()))?;
+
+        match index {
+            Some(idx) => para.insert_child(idx, object_id),
+            None => para.add_child(object_id),
+        }
+
+        self.nodes.embedded_objects.insert(object_id, object);
+        Ok(object_id)
+    }
+
+    /// Remove an embedded object from the tree
+    pub fn remove_embedded_object(&mut self, object_id: NodeId) -> Result<EmbeddedObject> {
+        let object = self.nodes.embedded_objects.remove(&object_id)
+            .ok_or(DocModelError::NodeNotFound(object_id.as_uuid()))?;
+
+        if let Some(parent_id) = object.parent() {
+            if let Some(para) = self.nodes.paragraphs.get_mut(&parent_id) {
+                para.remove_child(object_id);
+            }
+        }
+
+        Ok(object)
+    }
+
+    /// Get all embedded objects in the document
+    pub fn embedded_objects(&self) -> impl Iterator<Item = &EmbeddedObject> {
+        self.nodes.embedded_objects.values()
+    }
+
     // =========================================================================
     // Shape Methods
     // =========================================================================
@@ -518,6 +730,8 @@ impl DocumentTree {
         if let Some(parent_id) = shape.parent() {
             if let Some(para) = self.nodes.paragraphs.get_mut(&parent_id) {
                 para.remove_child(shape_id);
+            } else if let Some(canvas) = self.nodes.canvases.get_mut(&parent_id) {
+                canvas.remove_shape(shape_id);
             }
         }
 
@@ -547,6 +761,165 @@ impl DocumentTree {
         self.nodes.shapes.values().filter(|shape| shape.is_floating())
     }
 
+    // =========================================================================
+    // Ink Methods
+    // =========================================================================
+
+    /// Get an ink node by ID
+    pub fn get_ink(&self, id: NodeId) -> Option<&InkNode> {
+        self.nodes.inks.get(&id)
+    }
+
+    /// Get a mutable ink node by ID
+    pub fn get_ink_mut(&mut self, id: NodeId) -> Option<&mut InkNode> {
+        self.nodes.inks.get_mut(&id)
+    }
+
+    /// Anchor ink to a paragraph
+    ///
+    /// Ink is drawn over the page rather than in the text flow, so it
+    /// doesn't become one of the paragraph's children.
+    pub fn insert_ink(&mut self, mut ink: InkNode, para_id: NodeId) -> Result<NodeId> {
+        if !self.nodes.paragraphs.contains_key(&para_id) {
+            return Err(DocModelError::NodeNotFound(para_id.as_uuid()));
+        }
+        let ink_id = ink.id();
+        ink.set_parent(Some(para_id));
+        self.nodes.inks.insert(ink_id, ink);
+        Ok(ink_id)
+    }
+
+    /// Remove an ink node from the tree
+    pub fn remove_ink(&mut self, ink_id: NodeId) -> Result<InkNode> {
+        self.nodes.inks.remove(&ink_id)
+            .ok_or(DocModelError::NodeNotFound(ink_id.as_uuid()))
+    }
+
+    /// Get all ink in the document
+    pub fn inks(&self) -> impl Iterator<Item = &InkNode> {
+        self.nodes.inks.values()
+    }
+
+    /// Find the ink anchored to a paragraph
+    pub fn inks_in_paragraph(&self, para_id: NodeId) -> Vec<&InkNode> {
+        self.nodes.inks.values().filter(|ink| ink.parent() == Some(para_id)).collect()
+    }
+
+    // =========================================================================
+    // Canvas Methods
+    // =========================================================================
+
+    /// Get a drawing canvas by ID
+    pub fn get_canvas(&self, id: NodeId) -> Option<&DrawingCanvas> {
+        self.nodes.canvases.get(&id)
+    }
+
+    /// Get a mutable drawing canvas by ID
+    pub fn get_canvas_mut(&mut self, id: NodeId) -> Option<&mut DrawingCanvas> {
+        self.nodes.canvases.get_mut(&id)
+    }
+
+    /// Insert a drawing canvas into a paragraph
+    pub fn insert_canvas(&mut self, mut canvas: DrawingCanvas, para_id: NodeId, index: Option<usize>) -> Result<NodeId> {
+        let canvas_id = canvas.id();
+        canvas.set_parent(Some(para_id));
+
+        let para = self.nodes.paragraphs.get_mut(&para_id)
+            .ok_or(DocModelError::NodeNotFound(para_id.as_uuid()))?;
+        match index {
+            Some(idx) => para.insert_child(idx, canvas_id),
+            None => para.add_child(canvas_id),
+        }
+
+        self.nodes.canvases.insert(canvas_id, canvas);
+        Ok(canvas_id)
+    }
+
+    /// Remove a drawing canvas, and the shapes on it, from the tree
+    pub fn remove_canvas(&mut self, canvas_id: NodeId) -> Result<(DrawingCanvas, Vec<ShapeNode>)> {
+        let canvas = self.nodes.canvases.remove(&canvas_id)
+            .ok_or(DocModelError::NodeNotFound(canvas_id.as_uuid()))?;
+
+        if let Some(parent_id) = canvas.parent() {
+            if let Some(para) = self.nodes.paragraphs.get_mut(&parent_id) {
+                para.remove_child(canvas_id);
+            }
+        }
+
+        let shapes = canvas
+            .shapes()
+            .iter()
+            .filter_map(|shape_id| self.nodes.shapes.remove(shape_id))
+            .collect();
+        Ok((canvas, shapes))
+    }
+
+    /// Move a shape onto a canvas, on top of the shapes already there
+    ///
+    /// The shape keeps its anchor offset, which is now measured from the
+    /// canvas's top-left corner.
+    pub fn move_shape_to_canvas(&mut self, shape_id: NodeId, canvas_id: NodeId) -> Result<()> {
+        if !self.nodes.canvases.contains_key(&canvas_id) {
+            return Err(DocModelError::NodeNotFound(canvas_id.as_uuid()));
+        }
+        let old_parent = self.nodes.shapes.get(&shape_id)
+            .ok_or(DocModelError::NodeNotFound(shape_id.as_uuid()))?
+            .parent();
+        if let Some(parent_id) = old_parent {
+            if let Some(para) = self.nodes.paragraphs.get_mut(&parent_id) {
+                para.remove_child(shape_id);
+            } else if let Some(canvas) = self.nodes.canvases.get_mut(&parent_id) {
+                canvas.remove_shape(shape_id);
+            }
+        }
+
+        if let Some(shape) = self.nodes.shapes.get_mut(&shape_id) {
+            shape.set_parent(Some(canvas_id));
+        }
+        if let Some(canvas) = self.nodes.canvases.get_mut(&canvas_id) {
+            canvas.add_shape(shape_id);
+        }
+        Ok(())
+    }
+
+    /// Move a shape off its canvas into a paragraph
+    pub fn move_shape_to_paragraph(&mut self, shape_id: NodeId, para_id: NodeId) -> Result<()> {
+        if !self.nodes.paragraphs.contains_key(&para_id) {
+            return Err(DocModelError::NodeNotFound(para_id.as_uuid()));
+        }
+        let old_parent = self.nodes.shapes.get(&shape_id)
+            .ok_or(DocModelError::NodeNotFound(shape_id.as_uuid()))?
+            .parent();
+        if let Some(parent_id) = old_parent {
+            if let Some(canvas) = self.nodes.canvases.get_mut(&parent_id) {
+                canvas.remove_shape(shape_id);
+            } else if let Some(para) = self.nodes.paragraphs.get_mut(&parent_id) {
+                para.remove_child(shape_id);
+            }
+        }
+
+        if let Some(shape) = self.nodes.shapes.get_mut(&shape_id) {
+            shape.set_parent(Some(para_id));
+        }
+        if let Some(para) = self.nodes.paragraphs.get_mut(&para_id) {
+            para.add_child(shape_id);
+        }
+        Ok(())
+    }
+
+    /// Get all drawing canvases in the document
+    pub fn canvases(&self) -> impl Iterator<Item = &DrawingCanvas> {
+        self.nodes.canvases.values()
+    }
+
+    /// The shapes on a canvas, back to front
+    pub fn canvas_shapes(&self, canvas_id: NodeId) -> Vec<&ShapeNode> {
+        self.nodes.canvases
+            .get(&canvas_id)
+            .map(|canvas| canvas.shapes().iter().filter_map(|id| self.nodes.shapes.get(id)).collect())
+            .unwrap_or_default()
+    }
+
     // =========================================================================
     // TextBox Methods
     // =========================================================================
@@ -767,9 +1140,34 @@ impl DocumentTree {
             }
         }
 
-        // Check if it's a shape
+        // Check if it's an embedded object
+        if let Some(object) = self.nodes.embedded_objects.get(&target_id) {
+            if let Some(para_id) = object.parent() {
+                return Some(vec![self.document.id(), para_id, target_id]);
+            }
+        }
+
+        // Check if it's a shape, which may be on a canvas
         if let Some(shape) = self.nodes.shapes.get(&target_id) {
-            if let Some(para_id) = shape.parent() {
+            if let Some(parent_id) = shape.parent() {
+                if let Some(canvas) = self.nodes.canvases.get(&parent_id) {
+                    let para_id = canvas.parent()?;
+                    return Some(vec![self.document.id(), para_id, parent_id, target_id]);
+                }
+                return Some(vec![self.document.id(), parent_id, target_id]);
+            }
+        }
+
+        // Check if it's a canvas
+        if let Some(canvas) = self.nodes.canvases.get(&target_id) {
+            if let Some(para_id) = canvas.parent() {
+                return Some(vec![self.document.id(), para_id, target_id]);
+            }
+        }
+
+        // Check if it's ink
+        if let Some(ink) = self.nodes.inks.get(&target_id) {
+            if let Some(para_id) = ink.parent() {
                 return Some(vec![self.document.id(), para_id, target_id]);
             }
         }
@@ -1130,6 +1528,49 @@ impl DocumentTree {
         CellPadding::default()
     }
 
+    /// Get the effective formatting for a cell
+    ///
+    /// Combines the table style's regions (as enabled by the table's look)
+    /// with the cell's direct shading and borders, which take precedence.
+    pub fn effective_cell_format(&self, cell_id: NodeId) -> Option<TableRegionFormat> {
+        let cell = self.nodes.table_cells.get(&cell_id)?;
+        let row_id = cell.parent()?;
+        let row = self.nodes.table_rows.get(&row_id)?;
+        let table = self.nodes.tables.get(&row.parent()?)?;
+
+        let row_index = table.children().iter().position(|&id| id == row_id)?;
+        let mut column = 0;
+        for id in row.children() {
+            if *id == cell_id {
+                break;
+            }
+            if let Some(c) = self.nodes.table_cells.get(id) {
+                column += c.effective_grid_span() as usize;
+            }
+        }
+        let position = TableCellPosition::new(
+            row_index,
+            column,
+            table.row_count(),
+            table.grid.column_count().max(column + 1),
+        )
+        .with_column_span(cell.effective_grid_span() as usize);
+
+        let style_format = table
+            .properties
+            .style_id
+            .as_ref()
+            .and_then(|id| self.styles.resolve_table_cell_format(id, &position, &table.properties.look))
+            .unwrap_or_default();
+
+        let direct = TableRegionFormat {
+            shading: cell.properties.shading.clone(),
+            borders: cell.properties.borders.clone(),
+            ..Default::default()
+        };
+        Some(style_format.merge(&direct))
+    }
+
     // =========================================================================
     // Bookmark Methods
     // =========================================================================
@@ -1491,7 +1932,7 @@ impl DocumentTree {
         let ref_id = self.notes.insert_reference(reference);
 
         // Renumber endnotes
-        self.notes.renumber_endnotes();
+        self.renumber_endnotes();
 
         (note_id, ref_id)
     }
@@ -1546,7 +1987,7 @@ impl DocumentTree {
         let note = self.notes.remove_endnote(note_id);
 
         // Renumber remaining endnotes
-        self.notes.renumber_endnotes();
+        self.renumber_endnotes();
 
         note
     }
@@ -1585,7 +2026,7 @@ impl DocumentTree {
 
         // Renumber both types
         self.notes.renumber_footnotes();
-        self.notes.renumber_endnotes();
+        self.renumber_endnotes();
 
         Some(new_id)
     }
@@ -1619,7 +2060,7 @@ impl DocumentTree {
     /// Set endnote properties for a section (or default)
     pub fn set_endnote_properties(&mut self, section_id: Option<NodeId>, props: EndnoteProperties) {
         self.notes.set_endnote_props(section_id, props);
-        self.notes.renumber_endnotes();
+        self.renumber_endnotes();
     }
 
     /// Get all footnotes sorted by document order for a page
@@ -1632,6 +2073,76 @@ impl DocumentTree {
         self.notes.get_endnotes_sorted(section_id)
     }
 
+    /// Endnotes in the order their references appear in the body, each
+    /// with the index of the section its reference is in
+    ///
+    /// Notes whose reference isn't in a body paragraph come last and
+    /// belong to the last section.
+    pub fn endnotes_in_order(&self) -> Vec<(&Note, usize)> {
+        let block_sections = self.block_sections();
+        let block_index: HashMap<NodeId, usize> = self
+            .document
+            .children()
+            .iter()
+            .enumerate()
+            .map(|(index, &block_id)| (block_id, index))
+            .collect();
+        let last_section = self.sections.len().saturating_sub(1);
+
+        let mut notes: Vec<((usize, usize), &Note)> = self
+            .notes
+            .endnotes()
+            .map(|note| {
+                let key = note
+                    .reference_position
+                    .and_then(|position| Some((*block_index.get(&position.node_id)?, position.offset)))
+                    .unwrap_or((usize::MAX, 0));
+                (key, note)
+            })
+            .collect();
+        notes.sort_by_key(|(key, note)| (*key, note.id().as_uuid()));
+
+        notes
+            .into_iter()
+            .map(|(_, note)| {
+                let section = note
+                    .reference_position
+                    .and_then(|position| block_sections.get(&position.node_id).copied())
+                    .unwrap_or(last_section);
+                (note, section)
+            })
+            .collect()
+    }
+
+    /// Renumber endnotes in reference order
+    ///
+    /// Each note takes its section's numbering scheme; numbering restarts
+    /// at sections whose endnotes restart per section.
+    pub fn renumber_endnotes(&mut self) {
+        let order: Vec<(NoteId, Option<NodeId>)> = self
+            .endnotes_in_order()
+            .into_iter()
+            .map(|(note, section)| (note.id(), self.sections.get(section).map(|section| section.id())))
+            .collect();
+
+        let mut counter = 0;
+        let mut last_section = None;
+        for (index, (note_id, section_id)) in order.into_iter().enumerate() {
+            let props = self.notes.get_endnote_props(section_id).clone();
+            let restart = props.restart == RestartNumbering::PerSection && section_id != last_section;
+            if index == 0 || restart {
+                counter = props.start_at;
+            }
+            last_section = section_id;
+
+            if let Some(note) = self.notes.get_endnote_mut(note_id) {
+                note.mark = props.numbering.format(counter);
+                note.section_id = section_id.or(note.section_id);
+            }
+            counter += 1;
+        }
+    }
+
     /// Find the note ID for a reference node ID
     pub fn find_note_for_reference(&self, ref_id: NodeId) -> Option<(NoteId, NoteType)> {
         let reference = self.notes.get_reference(ref_id)?;
@@ -1668,6 +2179,159 @@ impl DocumentTree {
     pub fn endnote_count(&self) -> usize {
         self.notes.endnote_count()
     }
+
+    // =========================================================================
+    // Field Methods
+    // =========================================================================
+
+    /// Get a field by ID
+    pub fn get_field(&self, id: NodeId) -> Option<&Field> {
+        self.nodes.fields.get(&id)
+    }
+
+    /// Get a mutable field by ID
+    pub fn get_field_mut(&mut self, id: NodeId) -> Option<&mut Field> {
+        self.nodes.fields.get_mut(&id)
+    }
+
+    /// Insert a field into a paragraph
+    pub fn insert_field(&mut self, mut field: Field, para_id: NodeId, index: Option<usize>) -> Result<NodeId> {
+        let field_id = field.id();
+        field.set_parent(Some(para_id));
+
+        let para = self.nodes.paragraphs.get_mut(&para_id)
+            .ok_or(DocModelError::NodeNotFound(para_id.as_uuid()))?;
+
+        match index {
+            Some(idx) => para.insert_child(idx, field_id),
+            None => para.add_child(field_id),
+        }
+
+        self.nodes.fields.insert(field_id, field);
+        Ok(field_id)
+    }
+
+    /// Remove a field from the tree
+    pub fn remove_field(&mut self, field_id: NodeId) -> Result<Field> {
+        let field = self.nodes.fields.remove(&field_id)
+            .ok_or(DocModelError::NodeNotFound(field_id.as_uuid()))?;
+
+        if let Some(parent_id) = field.parent() {
+            if let Some(para) = self.nodes.paragraphs.get_mut(&parent_id) {
+                para.remove_child(field_id);
+            }
+        }
+
+        Ok(field)
+    }
+
+    // =========================================================================
+    // Section Methods
+    // =========================================================================
+
+    /// Append a section
+    pub fn add_section(&mut self, mut section: Section) -> NodeId {
+        section.set_parent(Some(self.document.id()));
+        let section_id = section.id();
+        self.sections.push(section);
+        section_id
+    }
+
+    /// Get a section by ID
+    pub fn get_section(&self, id: NodeId) -> Option<&Section> {
+        self.sections.iter().find(|section| section.id() == id)
+    }
+
+    /// Get a mutable section by ID
+    pub fn get_section_mut(&mut self, id: NodeId) -> Option<&mut Section> {
+        self.sections.iter_mut().find(|section| section.id() == id)
+    }
+
+    /// Map each body block to the index of its section
+    ///
+    /// A block belongs to the section that lists it; blocks no section lists
+    /// belong to the section of the block before them, or the first section.
+    pub fn block_sections(&self) -> HashMap<NodeId, usize> {
+        let mut listed = HashMap::new();
+        for (index, section) in self.sections.iter().enumerate() {
+            for &child_id in section.children() {
+                listed.insert(child_id, index);
+            }
+        }
+
+        let mut current = 0;
+        self.document
+            .children()
+            .iter()
+            .map(|&block_id| {
+                if let Some(&index) = listed.get(&block_id) {
+                    current = index;
+                }
+                (block_id, current)
+            })
+            .collect()
+    }
+
+    /// Find a header or footer by ID
+    pub fn get_header_footer(&self, id: NodeId) -> Option<&HeaderFooter> {
+        self.sections
+            .iter()
+            .flat_map(|section| section.headers.iter().chain(section.footers.iter()))
+            .find(|hf| hf.id() == id)
+    }
+
+    /// Find a mutable header or footer by ID
+    pub fn get_header_footer_mut(&mut self, id: NodeId) -> Option<&mut HeaderFooter> {
+        self.sections
+            .iter_mut()
+            .flat_map(|section| section.headers.iter_mut().chain(section.footers.iter_mut()))
+            .find(|hf| hf.id() == id)
+    }
+
+    /// Insert a paragraph into a header or footer
+    pub fn insert_paragraph_into_header_footer(&mut self, mut para: Paragraph, header_footer_id: NodeId, index: Option<usize>) -> Result<NodeId> {
+        let para_id = para.id();
+        para.set_parent(Some(header_footer_id));
+
+        let header_footer = self.get_header_footer_mut(header_footer_id)
+            .ok_or(DocModelError::NodeNotFound(header_footer_id.as_uuid()))?;
+
+        match index {
+            Some(idx) => header_footer.insert_child(idx, para_id),
+            None => header_footer.add_child(para_id),
+        }
+
+        self.nodes.paragraphs.insert(para_id, para);
+        Ok(para_id)
+    }
+
+    /// Insert a paragraph into an endnote
+    pub fn insert_paragraph_into_endnote(&mut self, para: Paragraph, note_id: NoteId, index: Option<usize>) -> Result<NodeId> {
+        let para_id = para.id();
+
+        let note = self.notes.get_endnote_mut(note_id)
+            .ok_or(DocModelError::NodeNotFound(note_id.as_uuid()))?;
+
+        match index {
+            Some(idx) => note.insert_content(idx, para_id),
+            None => note.add_content(para_id),
+        }
+
+        self.nodes.paragraphs.insert(para_id, para);
+        Ok(para_id)
+    }
+
+    /// Get content paragraphs from a header or footer
+    pub fn header_footer_paragraphs(&self, header_footer_id: NodeId) -> Vec<&Paragraph> {
+        let Some(header_footer) = self.get_header_footer(header_footer_id) else {
+            return Vec::new();
+        };
+
+        header_footer.children()
+            .iter()
+            .filter_map(|id| self.nodes.paragraphs.get(id))
+            .collect()
+    }
 }
 
 impl Default for DocumentTree {
@@ -0,0 +1,140 @@
+//! Section watermarks
+//!
+//! A watermark is drawn behind the body text of every page in a section:
+//! either a line of text such as "DRAFT" or "CONFIDENTIAL", usually set
+//! diagonally in a light color, or a washed-out picture.
+
+use crate::ResourceId;
+use serde::{Deserialize, Serialize};
+
+/// How a text watermark is set on the page
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WatermarkLayout {
+    /// Rotated 45 degrees, rising from bottom left to top right
+    #[default]
+    Diagonal,
+    /// Level with the page
+    Horizontal,
+}
+
+/// A text watermark
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextWatermark {
+    /// The text to draw
+    pub text: String,
+    /// Font family
+    pub font_family: String,
+    /// Font size in points (None = fit the text to the page)
+    pub font_size: Option<f32>,
+    /// Text color (hex string, e.g., "#C0C0C0")
+    pub color: String,
+    /// Transparency from 0.0 (opaque) to 1.0 (invisible)
+    pub transparency: f32,
+    /// Diagonal or horizontal placement
+    pub layout: WatermarkLayout,
+}
+
+impl TextWatermark {
+    /// Create a semitransparent, silver, diagonal text watermark
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            font_family: "Calibri".to_string(),
+            font_size: None,
+            color: "#C0C0C0".to_string(),
+            transparency: 0.5,
+            layout: WatermarkLayout::Diagonal,
+        }
+    }
+
+    /// Set the font family
+    pub fn with_font_family(mut self, font_family: impl Into<String>) -> Self {
+        self.font_family = font_family.into();
+        self
+    }
+
+    /// Set a fixed font size
+    pub fn with_font_size(mut self, font_size: f32) -> Self {
+        self.font_size = Some(font_size);
+        self
+    }
+
+    /// Set the text color
+    pub fn with_color(mut self, color: impl Into<String>) -> Self {
+        self.color = color.into();
+        self
+    }
+
+    /// Set the transparency, clamped to 0.0..=1.0
+    pub fn with_transparency(mut self, transparency: f32) -> Self {
+        self.transparency = transparency.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set the layout
+    pub fn with_layout(mut self, layout: WatermarkLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+}
+
+/// A picture watermark
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PictureWatermark {
+    /// Stored image resource
+    pub resource_id: ResourceId,
+    /// Natural width in points
+    pub width: f32,
+    /// Natural height in points
+    pub height: f32,
+    /// Scale of the natural size (None = fit the page's content area)
+    pub scale: Option<f32>,
+    /// Draw the picture faded so text on top stays readable
+    pub washout: bool,
+}
+
+impl PictureWatermark {
+    /// Create a washed-out picture watermark fitted to the page
+    pub fn new(resource_id: ResourceId, width: f32, height: f32) -> Self {
+        Self {
+            resource_id,
+            width,
+            height,
+            scale: None,
+            washout: true,
+        }
+    }
+
+    /// Set a fixed scale of the natural size
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
+    /// Set whether the picture is washed out
+    pub fn with_washout(mut self, washout: bool) -> Self {
+        self.washout = washout;
+        self
+    }
+}
+
+/// A watermark drawn behind a section's pages
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Watermark {
+    /// A line of text
+    Text(TextWatermark),
+    /// A picture
+    Picture(PictureWatermark),
+}
+
+impl Watermark {
+    /// Create a default text watermark
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text(TextWatermark::new(text))
+    }
+
+    /// Create a default picture watermark
+    pub fn picture(resource_id: ResourceId, width: f32, height: f32) -> Self {
+        Self::Picture(PictureWatermark::new(resource_id, width, height))
+    }
+}
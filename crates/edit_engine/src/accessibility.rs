@@ -0,0 +1,536 @@
+//! Accessibility checker
+//!
+//! Scans a document for content that is hard to use with a screen reader or
+//! hard to read: images, shapes, charts and tables without alt text, text
+//! with too little contrast against its background, headings that skip a
+//! level, and tables without a header row. Objects marked decorative are
+//! skipped by screen readers, so they need no alt text.
+
+use crate::{Command, CommandResult, EditError, Result};
+use charts::Chart;
+use doc_model::{DocumentTree, Node, NodeId, Paragraph, Selection, ShapeColor};
+use serde::{Deserialize, Serialize};
+
+/// Minimum contrast ratio for body text (WCAG AA)
+pub const MIN_TEXT_CONTRAST: f32 = 4.5;
+
+/// Minimum contrast ratio for large text (WCAG AA)
+pub const MIN_LARGE_TEXT_CONTRAST: f32 = 3.0;
+
+/// Severity of an accessibility issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessibilitySeverity {
+    /// Content is very hard or impossible to use
+    Error,
+    /// Content is hard to use
+    Warning,
+}
+
+/// Category of accessibility issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AccessibilityCategory {
+    /// Image, shape, chart or table without alt text
+    MissingAltText,
+    /// Text with too little contrast against its background
+    LowContrast,
+    /// Heading more than one level below the previous heading
+    SkippedHeadingLevel,
+    /// Table without a header row
+    MissingTableHeader,
+}
+
+/// An accessibility issue found in the document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessibilityIssue {
+    /// Issue severity
+    pub severity: AccessibilitySeverity,
+    /// Issue category
+    pub category: AccessibilityCategory,
+    /// The node the issue is about
+    pub node_id: NodeId,
+    /// Human-readable description
+    pub description: String,
+    /// Suggestion for fixing the issue
+    pub suggestion: Option<String>,
+}
+
+impl AccessibilityIssue {
+    /// Create a new issue
+    pub fn new(
+        severity: AccessibilitySeverity,
+        category: AccessibilityCategory,
+        node_id: NodeId,
+        description: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity,
+            category,
+            node_id,
+            description: description.into(),
+            suggestion: None,
+        }
+    }
+
+    /// Add a suggestion
+    pub fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+}
+
+/// Result of an accessibility check
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessibilityReport {
+    /// Issues in document order
+    pub issues: Vec<AccessibilityIssue>,
+}
+
+impl AccessibilityReport {
+    /// Get only error-level issues
+    pub fn errors(&self) -> Vec<&AccessibilityIssue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == AccessibilitySeverity::Error)
+            .collect()
+    }
+
+    /// Get only warning-level issues
+    pub fn warnings(&self) -> Vec<&AccessibilityIssue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == AccessibilitySeverity::Warning)
+            .collect()
+    }
+
+    /// Check if the document has any issues
+    pub fn has_issues(&self) -> bool {
+        !self.issues.is_empty()
+    }
+
+    /// Count errors
+    pub fn error_count(&self) -> usize {
+        self.errors().len()
+    }
+
+    /// Count warnings
+    pub fn warning_count(&self) -> usize {
+        self.warnings().len()
+    }
+}
+
+/// Check a document for accessibility issues
+pub fn check_accessibility(tree: &DocumentTree) -> AccessibilityReport {
+    let mut scan = Scan {
+        tree,
+        report: AccessibilityReport::default(),
+        last_heading: None,
+    };
+    for &block_id in tree.document.children() {
+        scan.block(block_id);
+    }
+    scan.report
+}
+
+/// Check a document and the charts placed in it for accessibility issues
+///
+/// Charts are kept outside the document tree, so each one is paired with the
+/// node that holds it, and its issues are reported against that node.
+pub fn check_accessibility_with_charts<'a>(
+    tree: &DocumentTree,
+    charts: impl IntoIterator<Item = (NodeId, &'a Chart)>,
+) -> AccessibilityReport {
+    let mut report = check_accessibility(tree);
+    for (node_id, chart) in charts {
+        if !chart.decorative && is_blank(&chart.alt_text) {
+            report
+                .issues
+                .push(missing_alt_text(AccessibilitySeverity::Error, node_id, "Chart"));
+        }
+    }
+    report
+}
+
+/// Contrast ratio between two colors, from 1 (same) to 21 (black on white)
+pub fn contrast_ratio(a: ShapeColor, b: ShapeColor) -> f32 {
+    fn luminance(color: ShapeColor) -> f32 {
+        let channel = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * channel(color.r) + 0.7152 * channel(color.g) + 0.0722 * channel(color.b)
+    }
+
+    let (a, b) = (luminance(a), luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+struct Scan<'a> {
+    tree: &'a DocumentTree,
+    report: AccessibilityReport,
+    /// Level of the previous heading
+    last_heading: Option<u8>,
+}
+
+impl Scan<'_> {
+    fn add(&mut self, issue: AccessibilityIssue) {
+        self.report.issues.push(issue);
+    }
+
+    fn block(&mut self, block_id: NodeId) {
+        if let Some(para) = self.tree.get_paragraph(block_id) {
+            self.paragraph(para);
+        } else if let Some(table) = self.tree.get_table(block_id) {
+            self.table(block_id);
+            for &row_id in table.children() {
+                let Some(row) = self.tree.get_table_row(row_id) else {
+                    continue;
+                };
+                for &cell_id in row.children() {
+                    let Some(cell) = self.tree.get_table_cell(cell_id) else {
+                        continue;
+                    };
+                    for &child_id in cell.children() {
+                        self.block(child_id);
+                    }
+                }
+            }
+        }
+    }
+
+    fn paragraph(&mut self, para: &Paragraph) {
+        if let Some(level) = heading_level(self.tree, para) {
+            if let Some(previous) = self.last_heading.filter(|&previous| level > previous + 1) {
+                self.add(
+                    AccessibilityIssue::new(
+                        AccessibilitySeverity::Warning,
+                        AccessibilityCategory::SkippedHeadingLevel,
+                        para.id(),
+                        format!("Heading level {} follows heading level {}", level, previous),
+                    )
+                    .with_suggestion(format!("Use heading level {}", previous + 1)),
+                );
+            }
+            self.last_heading = Some(level);
+        }
+
+        if self.has_low_contrast(para) {
+            self.add(
+                AccessibilityIssue::new(
+                    AccessibilitySeverity::Warning,
+                    AccessibilityCategory::LowContrast,
+                    para.id(),
+                    "Text is hard to read against its background",
+                )
+                .with_suggestion("Use a darker or lighter text color"),
+            );
+        }
+
+        for &child_id in para.children() {
+            let (alt_text, decorative, kind) = if let Some(image) = self.tree.nodes.images.get(&child_id) {
+                (&image.alt_text, image.decorative, "Image")
+            } else if let Some(shape) = self.tree.nodes.shapes.get(&child_id) {
+                (&shape.alt_text, shape.decorative, "Shape")
+            } else {
+                continue;
+            };
+            if !decorative && is_blank(alt_text) {
+                self.add(missing_alt_text(AccessibilitySeverity::Error, child_id, kind));
+            }
+        }
+    }
+
+    /// Check if any run's text has too little contrast
+    fn has_low_contrast(&self, para: &Paragraph) -> bool {
+        let styles = &self.tree.styles;
+        let paragraph_props = para
+            .paragraph_style_id
+            .as_ref()
+            .and_then(|id| styles.resolve(id))
+            .map(|style| style.character_props)
+            .unwrap_or_default();
+        let paragraph_background = styles
+            .resolve_paragraph_props(para.paragraph_style_id.as_ref(), &para.direct_formatting)
+            .background_color;
+
+        para.children()
+            .iter()
+            .filter_map(|&id| self.tree.get_run(id))
+            .filter(|run| !run.text.trim().is_empty())
+            .any(|run| {
+                // Paragraph style formatting applies under the run's own
+                let mut props = styles.resolve_character_props(None, &paragraph_props);
+                if let Some(style) = run.character_style_id.as_ref().and_then(|id| styles.resolve(id)) {
+                    props = props.merge(&style.character_props);
+                }
+                let props = props.merge(&run.direct_formatting);
+
+                let foreground = props.color.as_deref().and_then(ShapeColor::from_hex);
+                let background = props
+                    .highlight
+                    .as_deref()
+                    .or(paragraph_background.as_deref())
+                    .map_or(Some(ShapeColor::WHITE), ShapeColor::from_hex);
+                let (Some(foreground), Some(background)) = (foreground, background) else {
+                    return false;
+                };
+
+                let size = props.font_size.unwrap_or(11.0);
+                let large = size >= 18.0 || (props.bold == Some(true) && size >= 14.0);
+                let minimum = if large { MIN_LARGE_TEXT_CONTRAST } else { MIN_TEXT_CONTRAST };
+                contrast_ratio(foreground, background) < minimum
+            })
+    }
+
+    fn table(&mut self, table_id: NodeId) {
+        let Some(table) = self.tree.get_table(table_id) else {
+            return;
+        };
+        // Layout tables are read as plain content
+        if table.properties.decorative {
+            return;
+        }
+
+        if is_blank(&table.properties.alt_text) {
+            self.add(missing_alt_text(AccessibilitySeverity::Warning, table_id, "Table"));
+        }
+
+        let has_header = table
+            .children()
+            .first()
+            .and_then(|&row_id| self.tree.get_table_row(row_id))
+            .is_some_and(|row| row.properties.is_header);
+        if !table.children().is_empty() && !has_header {
+            self.add(
+                AccessibilityIssue::new(
+                    AccessibilitySeverity::Error,
+                    AccessibilityCategory::MissingTableHeader,
+                    table_id,
+                    "Table has no header row",
+                )
+                .with_suggestion("Mark the first row as a header row, or mark the table as decorative if it is only used for layout"),
+            );
+        }
+    }
+}
+
+/// Outline level of a heading paragraph
+fn heading_level(tree: &DocumentTree, para: &Paragraph) -> Option<u8> {
+    para.direct_formatting
+        .outline_level
+        .or_else(|| {
+            para.paragraph_style_id
+                .as_ref()
+                .and_then(|style_id| tree.styles.resolve(style_id))
+                .and_then(|resolved| resolved.paragraph_props.outline_level)
+        })
+        .filter(|level| (1..=9).contains(level))
+}
+
+fn is_blank(text: &Option<String>) -> bool {
+    text.as_deref().is_none_or(|text| text.trim().is_empty())
+}
+
+fn missing_alt_text(severity: AccessibilitySeverity, node_id: NodeId, kind: &str) -> AccessibilityIssue {
+    AccessibilityIssue::new(
+        severity,
+        AccessibilityCategory::MissingAltText,
+        node_id,
+        format!("{} has no alt text", kind),
+    )
+    .with_suggestion("Add alt text describing it, or mark it as decorative")
+}
+
+/// Set the alt text and decorative flag of an image, shape or table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetAltText {
+    /// The image, shape or table
+    pub node_id: NodeId,
+    /// New alt text
+    pub alt_text: Option<String>,
+    /// Whether the object is decorative
+    pub decorative: bool,
+}
+
+impl SetAltText {
+    /// Set an object's alt text
+    pub fn new(node_id: NodeId, alt_text: impl Into<String>) -> Self {
+        Self {
+            node_id,
+            alt_text: Some(alt_text.into()),
+            decorative: false,
+        }
+    }
+
+    /// Mark an object as decorative, clearing its alt text
+    pub fn decorative(node_id: NodeId) -> Self {
+        Self {
+            node_id,
+            alt_text: None,
+            decorative: true,
+        }
+    }
+
+    /// Current alt text and decorative flag of the object
+    fn current(&self, tree: &DocumentTree) -> Option<(Option<String>, bool)> {
+        if let Some(image) = tree.nodes.images.get(&self.node_id) {
+            Some((image.alt_text.clone(), image.decorative))
+        } else if let Some(shape) = tree.nodes.shapes.get(&self.node_id) {
+            Some((shape.alt_text.clone(), shape.decorative))
+        } else {
+            tree.get_table(self.node_id)
+                .map(|table| (table.properties.alt_text.clone(), table.properties.decorative))
+        }
+    }
+}
+
+impl Command for SetAltText {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let inverse = self.invert(tree);
+        let mut new_tree = tree.clone();
+        let alt_text = self.alt_text.clone();
+
+        if let Some(image) = new_tree.nodes.images.get_mut(&self.node_id) {
+            image.alt_text = alt_text;
+            image.decorative = self.decorative;
+        } else if let Some(shape) = new_tree.nodes.shapes.get_mut(&self.node_id) {
+            shape.alt_text = alt_text;
+            shape.decorative = self.decorative;
+        } else if let Some(table) = new_tree.nodes.tables.get_mut(&self.node_id) {
+            table.properties.alt_text = alt_text;
+            table.properties.decorative = self.decorative;
+        } else {
+            return Err(EditError::InvalidCommand(format!(
+                "Not an image, shape or table: {:?}",
+                self.node_id
+            )));
+        }
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse,
+        })
+    }
+
+    fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
+        let (alt_text, decorative) = self.current(tree).unwrap_or_default();
+        Box::new(SetAltText {
+            node_id: self.node_id,
+            alt_text,
+            decorative,
+        })
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn changed_paragraphs(&self, _tree: &DocumentTree, _selection: &Selection) -> Option<Vec<NodeId>> {
+        // Alt text doesn't change layout
+        Some(Vec::new())
+    }
+
+    fn display_name(&self) -> &str {
+        "Edit Alt Text"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::{ImageNode, Position, ResourceId, Run, RowProperties, Table, TableCell, TableRow};
+
+    #[test]
+    fn test_check_accessibility_reports_each_category() {
+        let mut tree = DocumentTree::new();
+        let root = tree.root_id();
+        let h1 = tree.insert_paragraph(Paragraph::with_paragraph_style("Heading1"), root, None).unwrap();
+        tree.insert_run(Run::new("Title"), h1, None).unwrap();
+        let h3 = tree.insert_paragraph(Paragraph::with_paragraph_style("Heading3"), root, None).unwrap();
+        tree.insert_run(Run::new("Details"), h3, None).unwrap();
+
+        let body = tree.insert_paragraph(Paragraph::new(), root, None).unwrap();
+        let mut faint = Run::new("faint text");
+        faint.direct_formatting.color = Some("#BBBBBB".to_string());
+        tree.insert_run(faint, body, None).unwrap();
+        let image_id = tree
+            .insert_image(ImageNode::new(ResourceId::new("img"), 10, 10), body, None)
+            .unwrap();
+
+        let table_id = tree.insert_table(Table::new(), None).unwrap();
+        let row_id = tree.insert_table_row(TableRow::new(), table_id, None).unwrap();
+        tree.insert_table_cell(TableCell::new(), row_id, None).unwrap();
+
+        let report = check_accessibility(&tree);
+        let found: Vec<_> = report.issues.iter().map(|i| (i.category, i.node_id)).collect();
+        assert_eq!(
+            found,
+            [
+                (AccessibilityCategory::SkippedHeadingLevel, h3),
+                (AccessibilityCategory::LowContrast, body),
+                (AccessibilityCategory::MissingAltText, image_id),
+                (AccessibilityCategory::MissingAltText, table_id),
+                (AccessibilityCategory::MissingTableHeader, table_id),
+            ]
+        );
+        assert_eq!(report.error_count(), 2);
+
+        // Alt text, a decorative table and a header row clear the object issues
+        let selection = Selection::collapsed(Position::new(body, 0));
+        let result = SetAltText::new(image_id, "A chart of sales").apply(&tree, &selection).unwrap();
+        let mut tree = SetAltText::decorative(table_id).apply(&result.tree, &selection).unwrap().tree;
+        tree.nodes.table_rows.get_mut(&row_id).unwrap().properties = RowProperties::new().as_header();
+        assert_eq!(check_accessibility(&tree).issues.len(), 2);
+
+        let undone = result.inverse.apply(&result.tree, &selection).unwrap();
+        assert!(undone.tree.nodes.images[&image_id].alt_text.is_none());
+    }
+
+    #[test]
+    fn test_decorative_table_and_charts() {
+        let mut tree = DocumentTree::new();
+        let root = tree.root_id();
+        let body = tree.insert_paragraph(Paragraph::new(), root, None).unwrap();
+        let table_id = tree.insert_table(Table::new(), None).unwrap();
+        let row_id = tree.insert_table_row(TableRow::new(), table_id, None).unwrap();
+        tree.insert_table_cell(TableCell::new(), row_id, None).unwrap();
+
+        // A layout table needs neither alt text nor a header row
+        let selection = Selection::collapsed(Position::new(body, 0));
+        let result = SetAltText::decorative(table_id).apply(&tree, &selection).unwrap();
+        assert!(result.tree.nodes.tables[&table_id].properties.decorative);
+        assert!(!check_accessibility(&result.tree).has_issues());
+        let undone = result.inverse.apply(&result.tree, &selection).unwrap();
+        assert!(!undone.tree.nodes.tables[&table_id].properties.decorative);
+
+        let plain = Chart::new("chart1", charts::ChartType::default());
+        let described = Chart::new("chart2", charts::ChartType::default()).with_alt_text("Sales by region");
+        let mut decorative = Chart::new("chart3", charts::ChartType::default());
+        decorative.decorative = true;
+        let report = check_accessibility_with_charts(
+            &result.tree,
+            [(body, &plain), (body, &described), (body, &decorative)],
+        );
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].category, AccessibilityCategory::MissingAltText);
+        assert_eq!(report.issues[0].description, "Chart has no alt text");
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        assert!((contrast_ratio(ShapeColor::BLACK, ShapeColor::WHITE) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio(ShapeColor::WHITE, ShapeColor::WHITE) - 1.0).abs() < 0.01);
+    }
+}
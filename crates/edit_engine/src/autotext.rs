@@ -0,0 +1,259 @@
+//! AutoText - inserting building blocks by typing their name
+//!
+//! AutoText entries are building blocks in the AutoText gallery. After text
+//! is typed, the word ending at the caret is matched against the start of
+//! each entry's name; once at least `AUTOTEXT_MIN_PREFIX` characters match,
+//! a suggestion is offered. Accepting it replaces the typed word with a copy
+//! of the entry's content.
+
+use crate::format_painter_commands::{ordered_range, paragraph_text, word_around};
+use crate::{Command, CommandResult, DeleteRange, EditError, InsertBuildingBlock, Result};
+use doc_model::{BuildingBlock, BuildingBlockGallery, DocumentTree, NodeId, Position, Selection};
+use serde::{Deserialize, Serialize};
+
+/// Characters of a name that must be typed before it is suggested
+pub const AUTOTEXT_MIN_PREFIX: usize = 4;
+
+/// An AutoText entry offered for the word being typed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoTextSuggestion {
+    /// ID of the suggested building block
+    pub block_id: String,
+    /// Name of the entry
+    pub name: String,
+    /// Paragraph holding the typed word
+    pub paragraph_id: NodeId,
+    /// Character offset where the typed word starts
+    pub start: usize,
+    /// Character offset where the typed word ends (the caret)
+    pub end: usize,
+}
+
+/// The AutoText entries available while typing
+#[derive(Debug, Clone, Default)]
+pub struct AutoText {
+    entries: Vec<BuildingBlock>,
+}
+
+impl AutoText {
+    /// Create from building blocks; blocks outside the AutoText gallery are ignored
+    pub fn new(blocks: impl IntoIterator<Item = BuildingBlock>) -> Self {
+        Self {
+            entries: blocks
+                .into_iter()
+                .filter(|block| block.gallery == BuildingBlockGallery::AutoText)
+                .collect(),
+        }
+    }
+
+    /// The entries
+    pub fn entries(&self) -> &[BuildingBlock] {
+        &self.entries
+    }
+
+    /// Get an entry by building block ID
+    pub fn entry(&self, block_id: &str) -> Option<&BuildingBlock> {
+        self.entries.iter().find(|block| block.id == block_id)
+    }
+
+    /// Suggest an entry for the word ending at a collapsed selection
+    ///
+    /// Of several matching entries the shortest name wins.
+    pub fn suggest(&self, tree: &DocumentTree, selection: &Selection) -> Option<AutoTextSuggestion> {
+        if self.entries.is_empty() || !selection.is_collapsed() {
+            return None;
+        }
+        let ((paragraph_id, offset), _) = ordered_range(tree, selection).ok()?;
+        let text = paragraph_text(tree, paragraph_id);
+        let (start, end) = word_around(&text, offset);
+        if end != offset || end - start < AUTOTEXT_MIN_PREFIX {
+            return None;
+        }
+
+        let typed: String = text.chars().skip(start).take(end - start).collect::<String>().to_lowercase();
+        let block = self
+            .entries
+            .iter()
+            .filter(|block| block.name.to_lowercase().starts_with(&typed))
+            .min_by(|a, b| a.name.len().cmp(&b.name.len()).then_with(|| a.name.cmp(&b.name)))?;
+
+        Some(AutoTextSuggestion {
+            block_id: block.id.clone(),
+            name: block.name.clone(),
+            paragraph_id,
+            start,
+            end,
+        })
+    }
+
+    /// The command that accepts a suggestion
+    pub fn accept(&self, suggestion: &AutoTextSuggestion) -> Result<AcceptAutoText> {
+        let block = self
+            .entry(&suggestion.block_id)
+            .ok_or_else(|| EditError::InvalidCommand(format!("AutoText entry not found: {}", suggestion.name)))?;
+        Ok(AcceptAutoText::new(suggestion.clone(), block.clone()))
+    }
+}
+
+/// Replace a typed word with an AutoText entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcceptAutoText {
+    /// The suggestion being accepted
+    pub suggestion: AutoTextSuggestion,
+    /// The entry's building block
+    pub block: BuildingBlock,
+}
+
+impl AcceptAutoText {
+    /// Create an accept command
+    pub fn new(suggestion: AutoTextSuggestion, block: BuildingBlock) -> Self {
+        Self { suggestion, block }
+    }
+}
+
+impl Command for AcceptAutoText {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let start = Position::new(self.suggestion.paragraph_id, self.suggestion.start);
+        let end = Position::new(self.suggestion.paragraph_id, self.suggestion.end);
+        let deleted = DeleteRange::new(start, end).apply(tree, selection)?;
+        let inserted = InsertBuildingBlock::new(start, self.block.clone()).apply(&deleted.tree, &deleted.selection)?;
+
+        Ok(CommandResult {
+            tree: inserted.tree,
+            selection: inserted.selection,
+            inverse: Box::new(UndoAcceptAutoText {
+                steps: vec![inserted.inverse, deleted.inverse],
+                redo: self.clone(),
+            }),
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        // The inverse steps are only known once applied
+        Box::new(UndoAcceptAutoText {
+            steps: Vec::new(),
+            redo: self.clone(),
+        })
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "AutoText"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+/// Put back the typed word (for undo)
+#[derive(Debug)]
+struct UndoAcceptAutoText {
+    /// Inverses of the insertion and the deletion, in that order
+    steps: Vec<Box<dyn Command>>,
+    /// The accept, for redo
+    redo: AcceptAutoText,
+}
+
+impl Command for UndoAcceptAutoText {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut tree = tree.clone();
+        let mut selection = *selection;
+        for step in &self.steps {
+            let result = step.apply(&tree, &selection)?;
+            tree = result.tree;
+            selection = result.selection;
+        }
+
+        Ok(CommandResult {
+            tree,
+            selection,
+            inverse: Box::new(self.redo.clone()),
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        Box::new(self.redo.clone())
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Undo AutoText"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(UndoAcceptAutoText {
+            steps: self.steps.iter().map(|step| step.clone_box()).collect(),
+            redo: self.redo.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EditingEngine, EditorEvent, InsertText};
+    use doc_model::{Paragraph, Run};
+    use std::sync::{Arc, Mutex};
+
+    fn entry(name: &str, text: &str) -> BuildingBlock {
+        let mut content = DocumentTree::new();
+        let para_id = content.insert_paragraph(Paragraph::new(), content.root_id(), None).unwrap();
+        content.insert_run(Run::new(text), para_id, None).unwrap();
+        BuildingBlock::new(name, BuildingBlockGallery::AutoText, content)
+    }
+
+    #[test]
+    fn test_autotext_suggest_and_accept() {
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new("Yours "), para_id, None).unwrap();
+
+        let mut engine = EditingEngine::with_tree(tree);
+        engine.set_autotext(AutoText::new([
+            entry("Sincerely yours", "Sincerely yours,"),
+            entry("Since", "Since"),
+            entry("Signature", "J. Smith"),
+        ]));
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        engine.add_event_listener(Box::new(move |event| {
+            if let EditorEvent::AutoTextSuggestion { suggestion } = event {
+                sink.lock().unwrap().push(suggestion.as_ref().map(|s| s.name.clone()));
+            }
+        }));
+
+        let type_text = |engine: &mut EditingEngine, text: &str| {
+            let caret = engine.selection().focus;
+            engine.execute(Box::new(InsertText::new(caret, text))).unwrap();
+        };
+        engine.set_selection(Selection::collapsed(Position::new(para_id, 6)));
+        type_text(&mut engine, "Sin");
+        assert!(engine.autotext_suggestion().is_none());
+        type_text(&mut engine, "c");
+        assert_eq!(engine.autotext_suggestion().unwrap().name, "Since");
+        type_text(&mut engine, "er");
+        assert_eq!(engine.autotext_suggestion().unwrap().name, "Sincerely yours");
+        assert_eq!(*events.lock().unwrap(), [Some("Since".to_string()), Some("Sincerely yours".to_string())]);
+
+        engine.accept_autotext().unwrap();
+        assert!(engine.autotext_suggestion().is_none());
+        assert_eq!(text_of(&engine, para_id), "Yours Sincerely yours,");
+
+        engine.undo().unwrap();
+        assert_eq!(text_of(&engine, para_id), "Yours Sincer");
+        engine.redo().unwrap();
+        assert_eq!(text_of(&engine, para_id), "Yours Sincerely yours,");
+    }
+
+    fn text_of(engine: &EditingEngine, para_id: NodeId) -> String {
+        paragraph_text(engine.tree(), para_id)
+    }
+}
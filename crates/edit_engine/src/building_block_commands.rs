@@ -0,0 +1,423 @@
+//! Building block insertion
+//!
+//! Inserting a building block copies its fragment into the document with
+//! fresh node IDs, so the same block can be inserted any number of times.
+//! A fragment of a single paragraph goes inline at the cursor; anything
+//! larger is inserted as whole blocks after the block holding the cursor.
+//! Cover pages always go at the start of the document.
+//!
+//! Paragraphs (with their runs and text boxes) and tables are copied.
+//! Runs inside hyperlinks are copied as plain runs.
+
+use crate::command::resolve_position;
+use crate::format_painter_commands::RestoreParagraphs;
+use crate::{Command, CommandResult, EditError, Result};
+use doc_model::{
+    BuildingBlock, BuildingBlockGallery, DocumentTree, Node, NodeId, Paragraph, Position, Run, Selection, Table,
+    TableCell, TableRow, TextBox,
+};
+use serde::{Deserialize, Serialize};
+
+/// Insert a building block at a position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InsertBuildingBlock {
+    /// Where to insert
+    pub position: Position,
+    /// The block to insert
+    pub block: BuildingBlock,
+}
+
+impl InsertBuildingBlock {
+    /// Create an insert command
+    pub fn new(position: Position, block: BuildingBlock) -> Self {
+        Self { position, block }
+    }
+
+    /// The fragment's only body paragraph, if it is inserted inline
+    fn inline_paragraph(&self) -> Option<NodeId> {
+        let body = self.block.content.document.children();
+        if self.block.gallery == BuildingBlockGallery::CoverPages || body.len() != 1 {
+            return None;
+        }
+        self.block.content.get_paragraph(body[0]).map(|p| p.id())
+    }
+
+    fn insert_inline(&self, tree: &mut DocumentTree, source_para: NodeId) -> Result<(Selection, UndoInsert)> {
+        let resolved = resolve_position(tree, &self.position)
+            .ok_or_else(|| EditError::InvalidCommand(format!("Cannot resolve position: {:?}", self.position)))?;
+        let para_id = resolved.paragraph_id;
+        let restore = RestoreParagraphs::snapshot(tree, &[para_id]);
+
+        // Split the run at the cursor; the fragment goes between the halves
+        let mut index = None;
+        if let (Some(run_id), Some(run_index)) = (resolved.run_id, resolved.run_index) {
+            if let Some(run) = tree.get_run_mut(run_id) {
+                let byte_offset: usize = run.text.chars().take(resolved.offset_in_run).map(char::len_utf8).sum();
+                let tail_text = run.text.split_off(byte_offset);
+                if resolved.offset_in_run == 0 {
+                    run.text = tail_text;
+                    index = Some(run_index);
+                } else {
+                    if !tail_text.is_empty() {
+                        let mut tail = Run::new(tail_text);
+                        tail.style = run.style.clone();
+                        tail.character_style_id = run.character_style_id.clone();
+                        tail.direct_formatting = run.direct_formatting.clone();
+                        tree.insert_run(tail, para_id, Some(run_index + 1))?;
+                    }
+                    index = Some(run_index + 1);
+                }
+            }
+        }
+
+        let mut textboxes = Vec::new();
+        let inserted = copy_inline_content(&self.block.content, tree, source_para, para_id, index, &mut textboxes)?;
+        let selection = Selection::collapsed(Position::new(self.position.node_id, self.position.offset + inserted));
+        Ok((
+            selection,
+            UndoInsert {
+                restore: Some(restore),
+                textboxes,
+                blocks: Vec::new(),
+                redo: self.clone(),
+            },
+        ))
+    }
+
+    fn insert_blocks(&self, tree: &mut DocumentTree, selection: &Selection) -> Result<(Selection, UndoInsert)> {
+        let mut index = if self.block.gallery == BuildingBlockGallery::CoverPages {
+            0
+        } else {
+            let para_id = resolve_position(tree, &self.position)
+                .map(|r| r.paragraph_id)
+                .ok_or_else(|| EditError::InvalidCommand(format!("Cannot resolve position: {:?}", self.position)))?;
+            let block_id = tree.find_table_for_node(para_id).unwrap_or(para_id);
+            tree.document
+                .children()
+                .iter()
+                .position(|&id| id == block_id)
+                .map(|i| i + 1)
+                .unwrap_or(tree.document.children().len())
+        };
+
+        let fragment = &self.block.content;
+        let mut blocks = Vec::new();
+        let mut textboxes = Vec::new();
+        let mut new_selection = *selection;
+        for &block_id in fragment.document.children() {
+            if let Some(para) = fragment.get_paragraph(block_id) {
+                let para_id = tree.insert_paragraph(copy_paragraph(para), tree.root_id(), Some(index))?;
+                let len = copy_inline_content(fragment, tree, block_id, para_id, None, &mut textboxes)?;
+                new_selection = Selection::collapsed(Position::new(para_id, len));
+                blocks.push(para_id);
+            } else if fragment.get_table(block_id).is_some() {
+                blocks.push(copy_table(fragment, tree, block_id, index)?);
+            } else {
+                continue;
+            }
+            index += 1;
+        }
+
+        Ok((
+            new_selection,
+            UndoInsert {
+                restore: None,
+                textboxes,
+                blocks,
+                redo: self.clone(),
+            },
+        ))
+    }
+}
+
+impl Command for InsertBuildingBlock {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+        let (selection, inverse) = match self.inline_paragraph() {
+            Some(source_para) => self.insert_inline(&mut new_tree, source_para)?,
+            None => self.insert_blocks(&mut new_tree, selection)?,
+        };
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection,
+            inverse: Box::new(inverse),
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        // The inserted node IDs are only known once applied
+        Box::new(UndoInsert {
+            restore: None,
+            textboxes: Vec::new(),
+            blocks: Vec::new(),
+            redo: self.clone(),
+        })
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn changed_paragraphs(&self, tree: &DocumentTree, _selection: &Selection) -> Option<Vec<NodeId>> {
+        self.inline_paragraph()?;
+        resolve_position(tree, &self.position).map(|resolved| vec![resolved.paragraph_id])
+    }
+
+    fn display_name(&self) -> &str {
+        "Insert Building Block"
+    }
+
+    fn memory_size(&self) -> usize {
+        std::mem::size_of_val(self) + self.block.content.nodes.runs.values().map(|r| r.text.len()).sum::<usize>()
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+/// Remove an inserted building block (for undo)
+#[derive(Debug, Clone)]
+struct UndoInsert {
+    /// Snapshot of the paragraph inline content went into
+    restore: Option<RestoreParagraphs>,
+    /// Text boxes added to that paragraph
+    textboxes: Vec<NodeId>,
+    /// Inserted body blocks
+    blocks: Vec<NodeId>,
+    /// The insertion, for redo
+    redo: InsertBuildingBlock,
+}
+
+impl Command for UndoInsert {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+        for &textbox_id in &self.textboxes {
+            if new_tree.get_textbox(textbox_id).is_some() {
+                new_tree.remove_textbox(textbox_id)?;
+            }
+        }
+        for &block_id in &self.blocks {
+            if new_tree.get_table(block_id).is_some() {
+                new_tree.remove_table(block_id)?;
+            } else if new_tree.get_paragraph(block_id).is_some() {
+                new_tree.remove_paragraph(block_id)?;
+            }
+        }
+
+        let mut selection = *selection;
+        if let Some(restore) = &self.restore {
+            new_tree = restore.apply(&new_tree, &selection)?.tree;
+            selection = Selection::collapsed(self.redo.position);
+        }
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection,
+            inverse: Box::new(self.redo.clone()),
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        Box::new(self.redo.clone())
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Undo Insert Building Block"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+/// Copy a paragraph's formatting into a new, empty paragraph
+fn copy_paragraph(para: &Paragraph) -> Paragraph {
+    let mut copy = Paragraph::new();
+    copy.style = para.style.clone();
+    copy.paragraph_style_id = para.paragraph_style_id.clone();
+    copy.direct_formatting = para.direct_formatting.clone();
+    copy
+}
+
+fn copy_run(run: &Run) -> Run {
+    let mut copy = Run::new(run.text.clone());
+    copy.style = run.style.clone();
+    copy.character_style_id = run.character_style_id.clone();
+    copy.direct_formatting = run.direct_formatting.clone();
+    copy
+}
+
+/// Copy the runs and text boxes of a fragment paragraph into a paragraph
+///
+/// Content is inserted at child `index` (or appended). Returns the number
+/// of characters inserted.
+fn copy_inline_content(
+    fragment: &DocumentTree,
+    tree: &mut DocumentTree,
+    source_para: NodeId,
+    target_para: NodeId,
+    mut index: Option<usize>,
+    textboxes: &mut Vec<NodeId>,
+) -> Result<usize> {
+    let Some(para) = fragment.get_paragraph(source_para) else {
+        return Ok(0);
+    };
+
+    let mut runs = Vec::new();
+    for &child_id in para.children() {
+        if let Some(run) = fragment.get_run(child_id) {
+            runs.push(copy_run(run));
+        } else if let Some(link) = fragment.get_hyperlink(child_id) {
+            runs.extend(link.children().iter().filter_map(|&id| fragment.get_run(id)).map(copy_run));
+        } else if let Some(textbox) = fragment.get_textbox(child_id) {
+            let mut copy = TextBox::new();
+            copy.anchor = textbox.anchor.clone();
+            copy.size = textbox.size.clone();
+            copy.style = textbox.style.clone();
+            copy.name = textbox.name.clone();
+            copy.alt_text = textbox.alt_text.clone();
+            let textbox_id = tree.insert_textbox(copy, target_para, index)?;
+            for &content_id in &textbox.content {
+                if let Some(content) = fragment.get_paragraph(content_id) {
+                    let para_id = tree.insert_paragraph_into_textbox(copy_paragraph(content), textbox_id, None)?;
+                    copy_runs(fragment, tree, content_id, para_id)?;
+                }
+            }
+            textboxes.push(textbox_id);
+            index = index.map(|i| i + 1);
+        }
+
+        for run in runs.drain(..) {
+            tree.insert_run(run, target_para, index)?;
+            index = index.map(|i| i + 1);
+        }
+    }
+
+    Ok(para
+        .children()
+        .iter()
+        .filter_map(|&id| fragment.get_run(id))
+        .chain(
+            para.children()
+                .iter()
+                .filter_map(|&id| fragment.get_hyperlink(id))
+                .flat_map(|link| link.children().iter().filter_map(|&id| fragment.get_run(id))),
+        )
+        .map(|run| run.text.chars().count())
+        .sum())
+}
+
+/// Append copies of a fragment paragraph's runs to a paragraph
+fn copy_runs(fragment: &DocumentTree, tree: &mut DocumentTree, source_para: NodeId, target_para: NodeId) -> Result<()> {
+    let Some(para) = fragment.get_paragraph(source_para) else {
+        return Ok(());
+    };
+    for run in para.children().iter().filter_map(|&id| fragment.get_run(id)) {
+        tree.insert_run(copy_run(run), target_para, None)?;
+    }
+    Ok(())
+}
+
+/// Copy a fragment table into the document body at `index`
+fn copy_table(fragment: &DocumentTree, tree: &mut DocumentTree, table_id: NodeId, index: usize) -> Result<NodeId> {
+    let source = fragment
+        .get_table(table_id)
+        .ok_or_else(|| EditError::InvalidCommand(format!("Table not found: {}", table_id)))?;
+    let mut table = Table::new();
+    table.grid = source.grid.clone();
+    table.properties = source.properties.clone();
+    let new_table_id = tree.insert_table(table, Some(index))?;
+
+    for &row_id in source.children() {
+        let Some(row) = fragment.get_table_row(row_id) else {
+            continue;
+        };
+        let mut new_row = TableRow::new();
+        new_row.properties = row.properties.clone();
+        let new_row_id = tree.insert_table_row(new_row, new_table_id, None)?;
+
+        for &cell_id in row.children() {
+            let Some(cell) = fragment.get_table_cell(cell_id) else {
+                continue;
+            };
+            let mut new_cell = TableCell::with_properties(cell.properties.clone());
+            new_cell.grid_span = cell.grid_span;
+            new_cell.row_span = cell.row_span;
+            new_cell.h_merge = cell.h_merge;
+            new_cell.v_merge = cell.v_merge;
+            new_cell.v_merge_continue = cell.v_merge_continue;
+            let new_cell_id = tree.insert_table_cell(new_cell, new_row_id, None)?;
+
+            for &para_id in cell.children() {
+                if let Some(para) = fragment.get_paragraph(para_id) {
+                    let new_para_id = tree.insert_paragraph_into_cell(copy_paragraph(para), new_cell_id, None)?;
+                    copy_runs(fragment, tree, para_id, new_para_id)?;
+                }
+            }
+        }
+    }
+
+    Ok(new_table_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_of(tree: &DocumentTree, para_id: NodeId) -> String {
+        tree.get_paragraph(para_id)
+            .unwrap()
+            .children()
+            .iter()
+            .filter_map(|&id| tree.get_run(id))
+            .map(|run| run.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_insert_building_block_inline_and_blocks() {
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new("Hello world"), para_id, None).unwrap();
+
+        let mut phrase = DocumentTree::new();
+        let phrase_para = phrase.insert_paragraph(Paragraph::new(), phrase.root_id(), None).unwrap();
+        phrase.insert_run(Run::new("big "), phrase_para, None).unwrap();
+        let block = BuildingBlock::new("Big", BuildingBlockGallery::AutoText, phrase);
+
+        // Inline insertion splits the run; inserting twice gives distinct nodes
+        let command = InsertBuildingBlock::new(Position::new(para_id, 6), block);
+        let result = command.apply(&tree, &Selection::default()).unwrap();
+        let result = command.apply(&result.tree, &result.selection).unwrap();
+        assert_eq!(text_of(&result.tree, para_id), "Hello big big world");
+        assert_eq!(result.selection.focus.offset, 10);
+
+        let undone = result.inverse.apply(&result.tree, &result.selection).unwrap();
+        assert_eq!(text_of(&undone.tree, para_id), "Hello big world");
+
+        // A cover page goes before the existing content
+        let mut cover = DocumentTree::new();
+        for text in ["Title", "Subtitle"] {
+            let id = cover.insert_paragraph(Paragraph::new(), cover.root_id(), None).unwrap();
+            cover.insert_run(Run::new(text), id, None).unwrap();
+        }
+        let block = BuildingBlock::new("Plain", BuildingBlockGallery::CoverPages, cover);
+        let result = InsertBuildingBlock::new(Position::new(para_id, 0), block)
+            .apply(&tree, &Selection::default())
+            .unwrap();
+        let body = result.tree.document.children().to_vec();
+        assert_eq!(body.len(), 3);
+        assert_eq!(text_of(&result.tree, body[0]), "Title");
+        assert_eq!(body[2], para_id);
+
+        let undone = result.inverse.apply(&result.tree, &result.selection).unwrap();
+        assert_eq!(undone.tree.document.children(), [para_id]);
+        assert_eq!(undone.tree.nodes.runs.len(), 1);
+    }
+}
@@ -0,0 +1,242 @@
+//! Drawing canvas commands for grouping shapes onto a canvas and back
+
+use crate::{Command, CommandResult, EditError, Result};
+use doc_model::{
+    set_shape_offset, shape_frame, DocumentTree, DrawingCanvas, Node, NodeId, Rect, Selection, ShapeFill,
+    ShapeStroke,
+};
+use serde::{Deserialize, Serialize};
+
+/// Create a floating canvas in a paragraph and move shapes onto it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCanvas {
+    /// Anchor paragraph
+    pub paragraph_id: NodeId,
+    /// Canvas offset from the paragraph and size; fits the shapes when unset
+    pub frame: Option<Rect>,
+    /// Shapes to move onto the canvas, back to front
+    pub shape_ids: Vec<NodeId>,
+    /// Background fill
+    pub fill: Option<ShapeFill>,
+    /// Border
+    pub stroke: Option<ShapeStroke>,
+}
+
+impl CreateCanvas {
+    /// An empty canvas at an offset from the paragraph
+    pub fn new(paragraph_id: NodeId, frame: Rect) -> Self {
+        Self {
+            paragraph_id,
+            frame: Some(frame),
+            shape_ids: Vec::new(),
+            fill: None,
+            stroke: None,
+        }
+    }
+
+    /// A canvas just covering the given shapes
+    pub fn around_shapes(paragraph_id: NodeId, shape_ids: Vec<NodeId>) -> Self {
+        Self {
+            paragraph_id,
+            frame: None,
+            shape_ids,
+            fill: None,
+            stroke: None,
+        }
+    }
+}
+
+impl Command for CreateCanvas {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+
+        let mut frames = Vec::with_capacity(self.shape_ids.len());
+        for &shape_id in &self.shape_ids {
+            let shape = new_tree
+                .get_shape(shape_id)
+                .ok_or_else(|| EditError::InvalidCommand(format!("Shape not found: {:?}", shape_id)))?;
+            frames.push(shape_frame(shape));
+        }
+        let frame = match self.frame {
+            Some(frame) => frame,
+            None => frames
+                .iter()
+                .copied()
+                .reduce(|a, b| a.union(&b))
+                .ok_or_else(|| EditError::InvalidCommand("A canvas needs a frame or shapes".to_string()))?,
+        };
+
+        let mut canvas = DrawingCanvas::floating(frame);
+        canvas.fill = self.fill.clone();
+        canvas.stroke = self.stroke.clone();
+        let canvas_id = new_tree
+            .insert_canvas(canvas, self.paragraph_id, None)
+            .map_err(EditError::DocModel)?;
+
+        // Shape offsets become relative to the canvas
+        let mut placed = Vec::with_capacity(frames.len());
+        for (&shape_id, shape_frame) in self.shape_ids.iter().zip(frames) {
+            new_tree
+                .move_shape_to_canvas(shape_id, canvas_id)
+                .map_err(EditError::DocModel)?;
+            let x = shape_frame.x - frame.x;
+            let y = shape_frame.y - frame.y;
+            if let Some(shape) = new_tree.get_shape_mut(shape_id) {
+                set_shape_offset(shape, x, y);
+            }
+            placed.push(Rect::new(x, y, shape_frame.width, shape_frame.height));
+        }
+        if let Some(canvas) = new_tree.get_canvas_mut(canvas_id) {
+            canvas.fit_to(&placed);
+        }
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse: Box::new(DissolveCanvas::new(canvas_id)),
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        // This will be replaced by the proper inverse in apply()
+        Box::new(DissolveCanvas::new(NodeId::new()))
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        if self.shape_ids.is_empty() {
+            "Insert Drawing Canvas"
+        } else {
+            "Group on Canvas"
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+/// Remove a canvas, leaving its shapes floating where they were in its
+/// paragraph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DissolveCanvas {
+    /// The canvas node ID
+    pub canvas_id: NodeId,
+}
+
+impl DissolveCanvas {
+    pub fn new(canvas_id: NodeId) -> Self {
+        Self { canvas_id }
+    }
+}
+
+impl Command for DissolveCanvas {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+
+        let canvas = new_tree
+            .get_canvas(self.canvas_id)
+            .ok_or_else(|| EditError::InvalidCommand(format!("Canvas not found: {:?}", self.canvas_id)))?
+            .clone();
+        let paragraph_id = canvas
+            .parent()
+            .ok_or_else(|| EditError::InvalidCommand("Canvas has no paragraph".to_string()))?;
+        let offset = canvas.offset();
+
+        for &shape_id in canvas.shapes() {
+            new_tree
+                .move_shape_to_paragraph(shape_id, paragraph_id)
+                .map_err(EditError::DocModel)?;
+            if let Some(shape) = new_tree.get_shape_mut(shape_id) {
+                let frame = shape_frame(shape);
+                set_shape_offset(shape, frame.x + offset.x, frame.y + offset.y);
+            }
+        }
+        new_tree.remove_canvas(self.canvas_id).map_err(EditError::DocModel)?;
+
+        let inverse = CreateCanvas {
+            paragraph_id,
+            frame: Some(Rect::new(offset.x, offset.y, canvas.width, canvas.height)),
+            shape_ids: canvas.shapes().to_vec(),
+            fill: canvas.fill.clone(),
+            stroke: canvas.stroke.clone(),
+        };
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse: Box::new(inverse),
+        })
+    }
+
+    fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
+        match tree.get_canvas(self.canvas_id) {
+            Some(canvas) => {
+                let offset = canvas.offset();
+                Box::new(CreateCanvas {
+                    paragraph_id: canvas.parent().unwrap_or_else(NodeId::new),
+                    frame: Some(Rect::new(offset.x, offset.y, canvas.width, canvas.height)),
+                    shape_ids: canvas.shapes().to_vec(),
+                    fill: canvas.fill.clone(),
+                    stroke: canvas.stroke.clone(),
+                })
+            }
+            None => Box::new(self.clone()),
+        }
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Ungroup Canvas"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::{Paragraph, Position, ShapeNode};
+
+    #[test]
+    fn test_group_shapes_on_canvas_and_undo() {
+        let mut tree = DocumentTree::new();
+        let para = Paragraph::new();
+        let para_id = para.id();
+        tree.insert_paragraph(para, tree.root_id(), None).unwrap();
+        let selection = Selection::collapsed(Position::new(para_id, 0));
+
+        let mut shape_ids = Vec::new();
+        for (x, y) in [(50.0, 40.0), (120.0, 90.0)] {
+            let mut shape = ShapeNode::rectangle(30.0, 10.0);
+            set_shape_offset(&mut shape, x, y);
+            shape_ids.push(tree.insert_shape(shape, para_id, None).unwrap());
+        }
+
+        let result = CreateCanvas::around_shapes(para_id, shape_ids.clone())
+            .apply(&tree, &selection)
+            .unwrap();
+        let canvas = result.tree.canvases().next().unwrap();
+        assert_eq!(canvas.offset().x, 50.0);
+        assert_eq!((canvas.width, canvas.height), (100.0, 60.0));
+        let second = result.tree.get_shape(shape_ids[1]).unwrap();
+        assert_eq!(shape_frame(second), Rect::new(70.0, 50.0, 30.0, 10.0));
+
+        let undone = result.inverse.apply(&result.tree, &selection).unwrap();
+        assert_eq!(undone.tree.canvases().count(), 0);
+        let second = undone.tree.get_shape(shape_ids[1]).unwrap();
+        assert_eq!(shape_frame(second), Rect::new(120.0, 90.0, 30.0, 10.0));
+        assert_eq!(second.parent(), Some(para_id));
+
+        let redone = undone.inverse.apply(&undone.tree, &selection).unwrap();
+        assert_eq!(redone.tree.canvas_shapes(redone.tree.canvases().next().unwrap().id()).len(), 2);
+    }
+}
@@ -30,9 +30,38 @@ pub trait Command: std::fmt::Debug + Send + Sync {
         None
     }
 
+    /// Describe this command as a replayable macro step
+    ///
+    /// Commands that act on the selection return a step; commands bound to
+    /// document-specific positions return `None` and are not recorded.
+    fn macro_step(&self) -> Option<crate::MacroStep> {
+        None
+    }
+
+    /// Report the paragraphs this command changes when applied at
+    /// `selection`, so layout can redo just those
+    ///
+    /// `None` means the command can't tell; the whole document is then
+    /// treated as changed. Paragraphs the command creates are new to layout
+    /// anyway and needn't be listed.
+    fn changed_paragraphs(&self, _tree: &DocumentTree, _selection: &Selection) -> Option<Vec<NodeId>> {
+        None
+    }
+
+    /// Whether this command is typed text, which AutoText watches for
+    /// entry names
+    fn is_text_input(&self) -> bool {
+        false
+    }
+
     /// Get a display name for this command
     fn display_name(&self) -> &str;
 
+    /// Estimate the memory this command holds, for bounding undo history
+    fn memory_size(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+
     /// Clone this command into a box
     fn clone_box(&self) -> Box<dyn Command>;
 }
@@ -55,7 +84,7 @@ pub struct ResolvedPosition {
 }
 
 /// Resolve a position to find the containing paragraph and run
-fn resolve_position(tree: &DocumentTree, position: &Position) -> Option<ResolvedPosition> {
+pub(crate) fn resolve_position(tree: &DocumentTree, position: &Position) -> Option<ResolvedPosition> {
     let node_type = tree.node_type(position.node_id)?;
 
     match node_type {
@@ -166,6 +195,22 @@ fn get_paragraph_index(tree: &DocumentTree, para_id: NodeId) -> Option<usize> {
     tree.document.children().iter().position(|&id| id == para_id)
 }
 
+/// Get the paragraphs from the one containing `start` through the one
+/// containing `end`, in document order
+pub fn paragraphs_between(tree: &DocumentTree, start: &Position, end: &Position) -> Option<Vec<NodeId>> {
+    let first = resolve_position(tree, start)?.paragraph_id;
+    let last = resolve_position(tree, end)?.paragraph_id;
+    if first == last {
+        return Some(vec![first]);
+    }
+
+    match (get_paragraph_index(tree, first), get_paragraph_index(tree, last)) {
+        (Some(a), Some(b)) => Some(tree.document.children()[a.min(b)..=a.max(b)].to_vec()),
+        // Paragraphs outside the body (e.g. in table cells)
+        _ => Some(vec![first, last]),
+    }
+}
+
 /// Insert text at a position
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InsertText {
@@ -269,10 +314,22 @@ impl Command for InsertText {
         Selection::new(new_anchor, new_focus)
     }
 
+    fn changed_paragraphs(&self, tree: &DocumentTree, _selection: &Selection) -> Option<Vec<NodeId>> {
+        resolve_position(tree, &self.position).map(|resolved| vec![resolved.paragraph_id])
+    }
+
+    fn is_text_input(&self) -> bool {
+        true
+    }
+
     fn display_name(&self) -> &str {
         "Insert Text"
     }
 
+    fn memory_size(&self) -> usize {
+        std::mem::size_of_val(self) + self.text.capacity()
+    }
+
     fn clone_box(&self) -> Box<dyn Command> {
         Box::new(self.clone())
     }
@@ -434,6 +491,10 @@ impl Command for DeleteRange {
         Selection::new(new_anchor, new_focus)
     }
 
+    fn changed_paragraphs(&self, tree: &DocumentTree, _selection: &Selection) -> Option<Vec<NodeId>> {
+        paragraphs_between(tree, &self.start, &self.end)
+    }
+
     fn display_name(&self) -> &str {
         "Delete"
     }
@@ -662,6 +723,10 @@ impl Command for SplitParagraph {
         *selection
     }
 
+    fn changed_paragraphs(&self, tree: &DocumentTree, _selection: &Selection) -> Option<Vec<NodeId>> {
+        resolve_position(tree, &self.position).map(|resolved| vec![resolved.paragraph_id])
+    }
+
     fn display_name(&self) -> &str {
         "Split Paragraph"
     }
@@ -794,6 +859,12 @@ impl Command for MergeParagraph {
         *selection
     }
 
+    fn changed_paragraphs(&self, tree: &DocumentTree, _selection: &Selection) -> Option<Vec<NodeId>> {
+        // The paragraph is appended to the one before it
+        let index = get_paragraph_index(tree, self.paragraph_id)?;
+        Some(tree.document.children()[index.saturating_sub(1)..=index].to_vec())
+    }
+
     fn display_name(&self) -> &str {
         "Merge Paragraph"
     }
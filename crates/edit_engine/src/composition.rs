@@ -0,0 +1,168 @@
+//! IME composition
+//!
+//! While an input method is composing, its provisional text is shown in the
+//! document but is not part of the edit history. Each update re-applies the
+//! provisional text to the document as it was when composition began, so
+//! nothing reaches the undo stack until the text is committed as ordinary
+//! typing.
+
+use crate::{paragraphs_between, Command, DeleteRange, DocumentSpellingError, InsertText, Result};
+use doc_model::{DocumentTree, MultiSelection, NodeId, Position, Selection};
+
+/// An in-progress IME composition
+#[derive(Debug, Clone)]
+pub struct Composition {
+    /// Where the provisional text starts
+    pub start: Position,
+    /// The provisional text
+    pub text: String,
+    /// Caret offset within the provisional text, in characters
+    pub cursor: usize,
+    /// Document before composition began
+    base_tree: DocumentTree,
+    /// Selection before composition began; a range is replaced on commit
+    base_selection: MultiSelection,
+}
+
+impl Composition {
+    /// Begin composing over the given document and selection
+    pub(crate) fn begin(tree: &DocumentTree, selection: &MultiSelection) -> Self {
+        Self {
+            start: selection.primary().start(),
+            text: String::new(),
+            cursor: 0,
+            base_tree: tree.clone(),
+            base_selection: selection.clone(),
+        }
+    }
+
+    /// Number of characters in the provisional text
+    pub fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    /// Check if there is no provisional text
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// The provisional text's range, for underlining
+    pub fn range(&self) -> Selection {
+        Selection::new(
+            self.start,
+            Position::new(self.start.node_id, self.start.offset + self.len()),
+        )
+    }
+
+    /// Check if a range of a paragraph overlaps the provisional text
+    pub fn overlaps(&self, para_id: NodeId, start_offset: usize, end_offset: usize) -> bool {
+        para_id == self.start.node_id
+            && start_offset < self.start.offset + self.len()
+            && end_offset > self.start.offset
+    }
+
+    /// Drop spelling errors inside the provisional text, which the input
+    /// method has not finished composing
+    pub fn retain_spelling_errors(&self, errors: &mut Vec<DocumentSpellingError>) {
+        errors.retain(|error| !self.overlaps(error.para_id, error.start_offset, error.end_offset));
+    }
+
+    /// The document and selection composition began with
+    pub(crate) fn base(&self) -> (&DocumentTree, &MultiSelection) {
+        (&self.base_tree, &self.base_selection)
+    }
+
+    /// Paragraphs the provisional text touches, for relayout
+    pub(crate) fn changed_paragraphs(&self) -> Option<Vec<NodeId>> {
+        let selection = self.base_selection.primary();
+        paragraphs_between(&self.base_tree, &selection.start(), &selection.end())
+    }
+
+    /// Commands that replace the base selection with the given text
+    pub(crate) fn commands(&self, text: &str) -> Vec<Box<dyn Command>> {
+        let selection = self.base_selection.primary();
+        let mut commands: Vec<Box<dyn Command>> = Vec::new();
+        if !selection.is_collapsed() {
+            commands.push(Box::new(DeleteRange::new(selection.start(), selection.end())));
+        }
+        if !text.is_empty() {
+            commands.push(Box::new(InsertText::new(self.start, text)));
+        }
+        commands
+    }
+
+    /// Apply provisional text to the base document
+    pub(crate) fn apply(&self, text: &str) -> Result<DocumentTree> {
+        let mut tree = self.base_tree.clone();
+        let mut selection = self.base_selection.primary();
+        for command in self.commands(text) {
+            let result = command.apply(&tree, &selection)?;
+            tree = result.tree;
+            selection = result.selection;
+        }
+        Ok(tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format_painter_commands::paragraph_text;
+    use crate::EditingEngine;
+    use doc_model::{DocumentTree, NodeId, Paragraph, Position, Run, Selection};
+
+    fn engine_with_text(text: &str) -> (EditingEngine, NodeId) {
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new(text), para_id, None).unwrap();
+        let mut engine = EditingEngine::with_tree(tree);
+        engine.set_selection(Selection::collapsed(Position::new(para_id, text.chars().count())));
+        (engine, para_id)
+    }
+
+    fn text_of(engine: &EditingEngine, para_id: NodeId) -> String {
+        paragraph_text(engine.tree(), para_id)
+    }
+
+    #[test]
+    fn test_composition_stays_out_of_undo_until_commit() {
+        let (mut engine, para_id) = engine_with_text("日本");
+
+        engine.update_composition("ご", 1).unwrap();
+        engine.update_composition("ごはん", 3).unwrap();
+        assert_eq!(text_of(&engine, para_id), "日本ごはん");
+        assert_eq!(engine.selection(), Selection::collapsed(Position::new(para_id, 5)));
+        assert!(!engine.can_undo());
+
+        engine.commit_composition().unwrap();
+        assert!(engine.composition().is_none());
+        assert_eq!(text_of(&engine, para_id), "日本ごはん");
+
+        engine.undo().unwrap();
+        assert_eq!(text_of(&engine, para_id), "日本");
+        assert!(!engine.can_undo());
+    }
+
+    #[test]
+    fn test_cancel_restores_selection_and_suppresses_spelling() {
+        let (mut engine, para_id) = engine_with_text("hello world");
+        let selection = Selection::new(Position::new(para_id, 6), Position::new(para_id, 11));
+        engine.set_selection(selection);
+
+        engine.update_composition("wrld", 4).unwrap();
+        assert_eq!(text_of(&engine, para_id), "hello wrld");
+
+        let composition = engine.composition().unwrap();
+        let mut errors = vec![
+            crate::DocumentSpellingError::new(para_id, 6, 10, "wrld".into(), Vec::new()),
+            crate::DocumentSpellingError::new(para_id, 0, 5, "hello".into(), Vec::new()),
+        ];
+        composition.retain_spelling_errors(&mut errors);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].word, "hello");
+
+        engine.cancel_composition();
+        assert_eq!(text_of(&engine, para_id), "hello world");
+        assert_eq!(engine.selection(), selection);
+        assert!(!engine.can_undo());
+    }
+}
@@ -1,16 +1,111 @@
 //! Command execution engine
 
-use crate::{Command, Result, UndoManager};
-use doc_model::{DocumentTree, Node, Selection};
+use crate::{
+    ApplyCopiedFormatting, ApplyToRanges, AutoText, AutoTextSuggestion, Command, Composition, CopyFormatting, Direction, EditError,
+    FormatPainter, Macro, MacroCommandRegistry, MacroStep, MovementUnit, Result,
+    SelectionNavigation, UndoHistory, UndoManager,
+};
+use doc_model::{DocumentTree, MultiSelection, Node, NodeId, Position, Selection};
+
+/// Change notifications from the editing engine
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditorEvent {
+    /// The document changed through a command, undo, or redo
+    DocumentChanged {
+        /// Display name of the command that changed it
+        command: String,
+    },
+    /// The primary selection changed
+    SelectionChanged {
+        /// The new primary selection
+        selection: Selection,
+    },
+    /// The AutoText suggestion for the word being typed changed
+    AutoTextSuggestion {
+        /// The new suggestion, `None` when it was dismissed
+        suggestion: Option<AutoTextSuggestion>,
+    },
+}
+
+/// Callback notified of editor events
+pub type EditorEventListener = Box<dyn Fn(&EditorEvent) + Send + Sync>;
+
+/// Paragraphs changed since layout last caught up with the document
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangedParagraphs {
+    paragraphs: Vec<NodeId>,
+    all: bool,
+}
+
+impl ChangedParagraphs {
+    /// Nothing has changed
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// The whole document has changed
+    pub fn all() -> Self {
+        Self {
+            paragraphs: Vec::new(),
+            all: true,
+        }
+    }
+
+    /// Record the paragraphs a command reported, `None` meaning all of them
+    pub fn record(&mut self, changed: Option<Vec<NodeId>>) {
+        if self.all {
+            return;
+        }
+        match changed {
+            Some(changed) => {
+                for para_id in changed {
+                    if !self.paragraphs.contains(&para_id) {
+                        self.paragraphs.push(para_id);
+                    }
+                }
+            }
+            None => *self = Self::all(),
+        }
+    }
+
+    /// Check if the whole document needs layout
+    pub fn is_all(&self) -> bool {
+        self.all
+    }
+
+    /// Check if nothing has changed
+    pub fn is_empty(&self) -> bool {
+        !self.all && self.paragraphs.is_empty()
+    }
+
+    /// Get the changed paragraphs (empty when the whole document changed)
+    pub fn paragraphs(&self) -> &[NodeId] {
+        &self.paragraphs
+    }
+}
 
 /// The main editing engine that manages document state and command execution
 pub struct EditingEngine {
     /// Current document tree
     tree: DocumentTree,
-    /// Current selection
-    selection: Selection,
+    /// Current selection (one or more ranges)
+    selection: MultiSelection,
     /// Undo manager
     undo_manager: UndoManager,
+    /// Formatting clipboard for the format painter
+    format_painter: Option<FormatPainter>,
+    /// Macro being recorded, if any
+    macro_recording: Option<Macro>,
+    /// AutoText entries matched while typing
+    autotext: AutoText,
+    /// Current AutoText suggestion, if any
+    autotext_suggestion: Option<AutoTextSuggestion>,
+    /// IME composition in progress, if any
+    composition: Option<Composition>,
+    /// Callbacks notified of document and selection changes
+    listeners: Vec<EditorEventListener>,
+    /// Paragraphs changed since layout last took them
+    changed: ChangedParagraphs,
 }
 
 impl EditingEngine {
@@ -25,8 +120,15 @@ impl EditingEngine {
 
         Self {
             tree,
-            selection,
+            selection: MultiSelection::new(selection),
             undo_manager: UndoManager::new(),
+            format_painter: None,
+            macro_recording: None,
+            autotext: AutoText::default(),
+            autotext_suggestion: None,
+            composition: None,
+            listeners: Vec::new(),
+            changed: ChangedParagraphs::all(),
         }
     }
 
@@ -40,8 +142,15 @@ impl EditingEngine {
 
         Self {
             tree,
-            selection,
+            selection: MultiSelection::new(selection),
             undo_manager: UndoManager::new(),
+            format_painter: None,
+            macro_recording: None,
+            autotext: AutoText::default(),
+            autotext_suggestion: None,
+            composition: None,
+            listeners: Vec::new(),
+            changed: ChangedParagraphs::all(),
         }
     }
 
@@ -50,48 +159,192 @@ impl EditingEngine {
         &self.tree
     }
 
-    /// Get the current selection
+    /// Get the current (primary) selection
     pub fn selection(&self) -> Selection {
-        self.selection
+        self.selection.primary()
     }
 
-    /// Set the selection
+    /// Set the selection, replacing any additional ranges
     pub fn set_selection(&mut self, selection: Selection) {
+        self.selection = MultiSelection::new(selection);
+        self.emit_selection_changed();
+        self.set_autotext_suggestion(None);
+    }
+
+    /// Get all selected ranges
+    pub fn multi_selection(&self) -> &MultiSelection {
+        &self.selection
+    }
+
+    /// Set a multi-range selection
+    pub fn set_multi_selection(&mut self, selection: MultiSelection) {
         self.selection = selection;
+        self.emit_selection_changed();
+        self.set_autotext_suggestion(None);
+    }
+
+    /// Add a range to the selection (Ctrl+select)
+    pub fn add_selection_range(&mut self, selection: Selection) {
+        self.selection.add_range(selection);
+        self.emit_selection_changed();
+        self.set_autotext_suggestion(None);
+    }
+
+    /// Register a callback for document and selection changes
+    pub fn add_event_listener(&mut self, listener: EditorEventListener) {
+        self.listeners.push(listener);
+    }
+
+    fn emit(&self, event: EditorEvent) {
+        for listener in &self.listeners {
+            listener(&event);
+        }
+    }
+
+    fn emit_selection_changed(&self) {
+        self.emit(EditorEvent::SelectionChanged {
+            selection: self.selection.primary(),
+        });
+    }
+
+    /// Get the paragraphs changed since they were last taken
+    pub fn changed_paragraphs(&self) -> &ChangedParagraphs {
+        &self.changed
+    }
+
+    /// Take the paragraphs changed since the last call, for incremental
+    /// relayout; a new engine starts with the whole document changed
+    pub fn take_changed_paragraphs(&mut self) -> ChangedParagraphs {
+        std::mem::take(&mut self.changed)
+    }
+
+    /// Notify listeners of an edit, and of the selection if it moved
+    fn emit_document_changed(&self, command: &str, previous: Selection) {
+        self.emit(EditorEvent::DocumentChanged {
+            command: command.to_string(),
+        });
+        if self.selection.primary() != previous {
+            self.emit_selection_changed();
+        }
     }
 
     /// Execute a command
+    ///
+    /// With several ranges selected the command is applied to each of them
+    /// and recorded as a single undo step. A composition in progress is
+    /// committed first.
     pub fn execute(&mut self, command: Box<dyn Command>) -> Result<()> {
-        let result = command.apply(&self.tree, &self.selection)?;
+        self.commit_composition()?;
+        let step = self.macro_recording.as_ref().and_then(|_| command.macro_step());
+        let typed = command.is_text_input() && !self.selection.is_multi();
+        self.execute_unrecorded(command)?;
+
+        if let (Some(recording), Some(step)) = (self.macro_recording.as_mut(), step) {
+            recording.steps.push(step);
+        }
+
+        let suggestion = typed
+            .then(|| self.autotext.suggest(&self.tree, &self.selection.primary()))
+            .flatten();
+        self.set_autotext_suggestion(suggestion);
+        Ok(())
+    }
+
+    fn execute_unrecorded(&mut self, command: Box<dyn Command>) -> Result<()> {
+        let previous = self.selection.primary();
+        let name = command.display_name().to_string();
+
+        if self.selection.is_multi() {
+            let command = ApplyToRanges::new(
+                command,
+                self.selection.ranges().to_vec(),
+                self.selection.primary_index(),
+            );
+            let (tree, selections, inverse) = command.apply_all(&self.tree)?;
+
+            self.changed.record(command.changed_paragraphs(&self.tree, &previous));
+            self.tree = tree;
+            self.selection.set_ranges(selections);
+            self.undo_manager.push_step(
+                Box::new(command),
+                Box::new(inverse),
+                previous,
+                self.selection.primary(),
+            );
+            self.emit_document_changed(&name, previous);
+            return Ok(());
+        }
+
+        let result = command.apply(&self.tree, &previous)?;
+        self.changed.record(command.changed_paragraphs(&self.tree, &previous));
 
         // Record for undo
-        self.undo_manager.push(command, result.inverse);
+        self.undo_manager
+            .push_step(command, result.inverse, previous, result.selection);
 
         // Update state
         self.tree = result.tree;
-        self.selection = result.selection;
+        self.selection = MultiSelection::new(result.selection);
+        self.emit_document_changed(&name, previous);
+
+        Ok(())
+    }
+
+    /// Execute a command against an explicit range instead of the selection
+    ///
+    /// Used for programmatic edits (e.g. from plugins): the user's ranges
+    /// are carried through the edit rather than replaced, and the command is
+    /// never recorded into a macro.
+    pub fn execute_at(&mut self, command: Box<dyn Command>, selection: &Selection) -> Result<()> {
+        self.commit_composition()?;
+        let result = command.apply(&self.tree, selection)?;
+        self.changed.record(command.changed_paragraphs(&self.tree, selection));
+        let previous = self.selection.primary();
+        let name = command.display_name().to_string();
+        let ranges = self
+            .selection
+            .ranges()
+            .iter()
+            .map(|range| command.transform_selection(range))
+            .collect();
+
+        self.undo_manager
+            .push_step(command, result.inverse, *selection, result.selection);
+        self.tree = result.tree;
+        self.selection.set_ranges(ranges);
+        self.emit_document_changed(&name, previous);
 
         Ok(())
     }
 
     /// Undo the last command
     pub fn undo(&mut self) -> Result<()> {
+        self.commit_composition()?;
         let inverse = self.undo_manager.pop_undo()?;
-        let result = inverse.apply(&self.tree, &self.selection)?;
+        let previous = self.selection.primary();
+        let result = inverse.apply(&self.tree, &previous)?;
+        self.changed.record(inverse.changed_paragraphs(&self.tree, &previous));
 
         self.tree = result.tree;
-        self.selection = result.selection;
+        self.selection = MultiSelection::new(result.selection);
+        self.emit_document_changed("Undo", previous);
+        self.set_autotext_suggestion(None);
 
         Ok(())
     }
 
     /// Redo the last undone command
     pub fn redo(&mut self) -> Result<()> {
+        self.commit_composition()?;
         let command = self.undo_manager.pop_redo()?;
-        let result = command.apply(&self.tree, &self.selection)?;
+        let previous = self.selection.primary();
+        let result = command.apply(&self.tree, &previous)?;
+        self.changed.record(command.changed_paragraphs(&self.tree, &previous));
 
         self.tree = result.tree;
-        self.selection = result.selection;
+        self.selection = MultiSelection::new(result.selection);
+        self.emit_document_changed("Redo", previous);
+        self.set_autotext_suggestion(None);
 
         Ok(())
     }
@@ -101,10 +354,215 @@ impl EditingEngine {
         self.undo_manager.can_undo()
     }
 
+    /// Start a named transaction; edits until the matching
+    /// `end_transaction` undo as one step (e.g. "Replace All")
+    pub fn begin_transaction(&mut self, name: impl Into<String>) {
+        self.undo_manager.begin_group(name);
+    }
+
+    /// End the current transaction
+    pub fn end_transaction(&mut self) -> Result<()> {
+        self.undo_manager.end_group()
+    }
+
+    /// Get the undo and redo history for display
+    pub fn history(&self) -> UndoHistory {
+        self.undo_manager.history()
+    }
+
+    /// Capture the formatting at the current selection into the format painter
+    pub fn copy_formatting(&mut self, command: CopyFormatting) -> Result<()> {
+        self.format_painter = Some(command.capture(&self.tree, &self.selection.primary())?);
+        Ok(())
+    }
+
+    /// Apply the format painter's formatting to the current selection
+    ///
+    /// A non-sticky painter is cleared after one application.
+    pub fn apply_copied_formatting(&mut self) -> Result<()> {
+        let painter = self
+            .format_painter
+            .as_ref()
+            .ok_or_else(|| EditError::InvalidCommand("No formatting has been copied".to_string()))?;
+        let command = ApplyCopiedFormatting::new(painter.formatting.clone());
+        let sticky = painter.sticky;
+
+        self.execute(Box::new(command))?;
+        if !sticky {
+            self.format_painter = None;
+        }
+        Ok(())
+    }
+
+    /// Get the active format painter, if any
+    pub fn format_painter(&self) -> Option<&FormatPainter> {
+        self.format_painter.as_ref()
+    }
+
+    /// Deactivate the format painter
+    pub fn cancel_format_painter(&mut self) {
+        self.format_painter = None;
+    }
+
     /// Check if redo is available
     pub fn can_redo(&self) -> bool {
         self.undo_manager.can_redo()
     }
+
+    /// Move the caret (or extend the selection with `extend`)
+    pub fn move_selection(&mut self, direction: Direction, unit: MovementUnit, extend: bool) {
+        let selection = self.selection.primary().navigate(&self.tree, direction, unit, extend);
+        self.selection = MultiSelection::new(selection);
+        self.emit_selection_changed();
+        self.set_autotext_suggestion(None);
+
+        if let Some(recording) = self.macro_recording.as_mut() {
+            recording.steps.push(MacroStep::Move {
+                direction,
+                unit,
+                extend,
+            });
+        }
+    }
+
+    /// Set the AutoText entries offered while typing
+    pub fn set_autotext(&mut self, autotext: AutoText) {
+        self.autotext = autotext;
+        self.set_autotext_suggestion(None);
+    }
+
+    /// Get the current AutoText suggestion, if any
+    pub fn autotext_suggestion(&self) -> Option<&AutoTextSuggestion> {
+        self.autotext_suggestion.as_ref()
+    }
+
+    /// Replace the typed word with the suggested AutoText entry
+    pub fn accept_autotext(&mut self) -> Result<()> {
+        let suggestion = self
+            .autotext_suggestion
+            .clone()
+            .ok_or_else(|| EditError::InvalidCommand("No AutoText suggestion to accept".to_string()))?;
+        let command = self.autotext.accept(&suggestion)?;
+        self.execute(Box::new(command))
+    }
+
+    /// Update the AutoText suggestion, notifying listeners if it changed
+    fn set_autotext_suggestion(&mut self, suggestion: Option<AutoTextSuggestion>) {
+        if self.autotext_suggestion != suggestion {
+            self.autotext_suggestion = suggestion.clone();
+            self.emit(EditorEvent::AutoTextSuggestion { suggestion });
+        }
+    }
+
+    /// Get the IME composition in progress, if any
+    pub fn composition(&self) -> Option<&Composition> {
+        self.composition.as_ref()
+    }
+
+    /// Show an input method's provisional text, starting a composition if
+    /// none is in progress
+    ///
+    /// The text replaces the selection and any earlier provisional text, and
+    /// `cursor` is the caret's character offset within it. Nothing is
+    /// recorded for undo, and no AutoText is suggested, until the
+    /// composition is committed.
+    pub fn update_composition(&mut self, text: impl Into<String>, cursor: usize) -> Result<()> {
+        let previous = self.selection.primary();
+        let text = text.into();
+        let composition = self
+            .composition
+            .get_or_insert_with(|| Composition::begin(&self.tree, &self.selection));
+        self.undo_manager.begin_composition();
+
+        let tree = composition.apply(&text)?;
+        composition.cursor = cursor.min(text.chars().count());
+        composition.text = text;
+        let caret = Position::new(composition.start.node_id, composition.start.offset + composition.cursor);
+
+        self.changed.record(composition.changed_paragraphs());
+        self.tree = tree;
+        self.selection = MultiSelection::new(Selection::collapsed(caret));
+        self.emit_document_changed("Composition", previous);
+        self.set_autotext_suggestion(None);
+        Ok(())
+    }
+
+    /// Commit the provisional text as typing, recorded as one undo step
+    ///
+    /// Does nothing when no composition is in progress.
+    pub fn commit_composition(&mut self) -> Result<()> {
+        let Some(composition) = self.composition.take() else {
+            return Ok(());
+        };
+        let (tree, selection) = composition.base();
+        self.tree = tree.clone();
+        self.selection = MultiSelection::new(selection.primary());
+
+        let mut commands = composition.commands(&composition.text);
+        let result = if commands.len() > 1 {
+            self.begin_transaction("Typing");
+            let result = commands.into_iter().try_for_each(|command| self.execute(command));
+            self.end_transaction().and(result)
+        } else if let Some(command) = commands.pop() {
+            self.execute(command)
+        } else {
+            Ok(())
+        };
+        self.undo_manager.end_composition();
+        result
+    }
+
+    /// Discard the provisional text, restoring the document and selection
+    /// from before the composition
+    pub fn cancel_composition(&mut self) {
+        let Some(composition) = self.composition.take() else {
+            return;
+        };
+        let previous = self.selection.primary();
+        let (tree, selection) = composition.base();
+
+        self.changed.record(composition.changed_paragraphs());
+        self.tree = tree.clone();
+        self.selection = selection.clone();
+        self.undo_manager.end_composition();
+        self.emit_document_changed("Cancel Composition", previous);
+    }
+
+    /// Start recording a macro, discarding any recording in progress
+    pub fn start_macro_recording(&mut self, name: impl Into<String>) {
+        self.macro_recording = Some(Macro::new(name));
+    }
+
+    /// Stop recording and return the recorded macro
+    pub fn stop_macro_recording(&mut self) -> Option<Macro> {
+        self.macro_recording.take()
+    }
+
+    /// Check if a macro is being recorded
+    pub fn is_recording_macro(&self) -> bool {
+        self.macro_recording.is_some()
+    }
+
+    /// Replay a macro against the current document and selection
+    ///
+    /// Replay stops at the first step that fails. Steps run while a macro is
+    /// being recorded are not themselves recorded.
+    pub fn run_macro(&mut self, macro_def: &Macro, registry: &MacroCommandRegistry) -> Result<()> {
+        let recording = self.macro_recording.take();
+        let result = macro_def.steps.iter().try_for_each(|step| match step {
+            MacroStep::Command { name, params } => self.execute(registry.build(name, params)?),
+            MacroStep::Move {
+                direction,
+                unit,
+                extend,
+            } => {
+                self.move_selection(*direction, *unit, *extend);
+                Ok(())
+            }
+        });
+        self.macro_recording = recording;
+        result
+    }
 }
 
 impl Default for EditingEngine {
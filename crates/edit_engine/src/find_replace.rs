@@ -6,11 +6,16 @@
 //! - FindEngine for searching documents
 //! - ReplaceEngine for replacing matches
 //! - Regex pattern support with capture groups
-
-use crate::{Command, CommandResult, EditError, Result};
-use doc_model::{DocumentTree, Node, NodeId, Position, Selection};
+//! - Special tokens (`^p`, `^t`, ...) in patterns and replacements
+//! - Matching and replacing by character formatting
+//! - Search scopes (selection, section, main text, footnotes, endnotes)
+
+use crate::{Command, CommandResult, EditError, MergeParagraph, Result, SplitParagraph};
+use doc_model::{
+    CharacterProperties, DocumentTree, Node, NodeId, Note, Position, Run, Section, Selection,
+};
+use regex_lite::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
 /// Options for find operations
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -25,6 +30,12 @@ pub struct FindOptions {
     pub wrap_around: bool,
     /// Search backwards
     pub search_backwards: bool,
+    /// Only match text with these character properties (e.g. bold 14pt)
+    #[serde(default)]
+    pub format: Option<CharacterProperties>,
+    /// Part of the document to search
+    #[serde(default)]
+    pub scope: SearchScope,
 }
 
 impl FindOptions {
@@ -62,6 +73,18 @@ impl FindOptions {
         self.search_backwards = value;
         self
     }
+
+    /// Only match text with the given character properties
+    pub fn with_format(mut self, format: CharacterProperties) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Set the search scope
+    pub fn scope(mut self, scope: SearchScope) -> Self {
+        self.scope = scope;
+        self
+    }
 }
 
 /// Result of a find operation
@@ -113,6 +136,197 @@ impl FindResult {
     }
 }
 
+/// Character standing in for a paragraph mark in search text and replacements
+///
+/// Matches never span paragraphs; a pattern may only end with the mark.
+pub const PARAGRAPH_MARK: char = '\u{2029}';
+
+/// Which part of the document a search covers
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum SearchScope {
+    /// Body text of the document
+    #[default]
+    MainDocument,
+    /// Only the text inside a selection
+    Selection(Selection),
+    /// The blocks of a section (see [`SearchScope::section`])
+    Section(Vec<NodeId>),
+    /// Footnote text, in reference order
+    Footnotes,
+    /// Endnote text, in reference order
+    Endnotes,
+    /// Body text followed by footnotes and endnotes
+    AllStories,
+}
+
+impl SearchScope {
+    /// Scope a search to the content of a section
+    pub fn section(section: &Section) -> Self {
+        Self::Section(section.children().to_vec())
+    }
+}
+
+/// An element of a non-regex search pattern after special-token expansion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PatternElement {
+    Char(char),
+    AnyChar,
+    AnyDigit,
+    AnyLetter,
+    Whitespace,
+    ParagraphMark,
+}
+
+impl PatternElement {
+    /// Parse a pattern, expanding Word-style special tokens
+    ///
+    /// `^p` paragraph mark, `^t` tab, `^l` line break, `^?` any character,
+    /// `^#` any digit, `^$` any letter, `^w` white space, `^^` a caret.
+    fn parse(pattern: &str) -> Vec<Self> {
+        let mut elements = Vec::new();
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '^' {
+                elements.push(Self::Char(c));
+                continue;
+            }
+            let element = match chars.peek() {
+                Some('p') => Self::ParagraphMark,
+                Some('t') => Self::Char('\t'),
+                Some('l') => Self::Char('\n'),
+                Some('?') => Self::AnyChar,
+                Some('#') => Self::AnyDigit,
+                Some('$') => Self::AnyLetter,
+                Some('w') => Self::Whitespace,
+                Some('^') => Self::Char('^'),
+                _ => {
+                    elements.push(Self::Char('^'));
+                    continue;
+                }
+            };
+            chars.next();
+            elements.push(element);
+        }
+        elements
+    }
+
+    /// Check whether a character matches this element
+    fn matches(&self, c: char, case_sensitive: bool) -> bool {
+        match self {
+            Self::Char(p) if case_sensitive => *p == c,
+            Self::Char(p) => p.to_lowercase().eq(c.to_lowercase()),
+            Self::AnyChar => c != PARAGRAPH_MARK,
+            Self::AnyDigit => c.is_numeric(),
+            Self::AnyLetter => c.is_alphabetic(),
+            Self::Whitespace => c.is_whitespace() && c != PARAGRAPH_MARK,
+            Self::ParagraphMark => c == PARAGRAPH_MARK,
+        }
+    }
+}
+
+/// Expand special tokens in a replacement template
+///
+/// `^&` inserts the found text, `^p` a paragraph mark, `^t` a tab, `^l` a
+/// line break, and `^^` a caret.
+fn expand_replacement_tokens(template: &str, found: &str) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '^' {
+            result.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some('&') => result.push_str(found),
+            Some('p') => result.push(PARAGRAPH_MARK),
+            Some('t') => result.push('\t'),
+            Some('l') => result.push('\n'),
+            Some('^') => result.push('^'),
+            _ => {
+                result.push('^');
+                continue;
+            }
+        }
+        chars.next();
+    }
+    result
+}
+
+/// Check whether resolved character properties satisfy formatting criteria
+///
+/// Only properties set in `criteria` are compared; unset toggles count as off.
+fn format_matches(props: &CharacterProperties, criteria: &CharacterProperties) -> bool {
+    let toggle = |value: Option<bool>, wanted: Option<bool>| {
+        wanted.is_none_or(|w| value.unwrap_or(false) == w)
+    };
+    let text = |value: &Option<String>, wanted: &Option<String>| {
+        wanted.as_ref().is_none_or(|w| {
+            value.as_ref().is_some_and(|v| v.eq_ignore_ascii_case(w))
+        })
+    };
+    let number = |value: Option<f32>, wanted: Option<f32>| {
+        wanted.is_none_or(|w| value.is_some_and(|v| (v - w).abs() < 0.01))
+    };
+
+    text(&props.font_family, &criteria.font_family)
+        && number(props.font_size, criteria.font_size)
+        && toggle(props.bold, criteria.bold)
+        && toggle(props.italic, criteria.italic)
+        && toggle(props.underline, criteria.underline)
+        && toggle(props.strikethrough, criteria.strikethrough)
+        && text(&props.color, &criteria.color)
+        && text(&props.highlight, &criteria.highlight)
+        && criteria
+            .vertical_align
+            .is_none_or(|w| props.vertical_align == Some(w))
+        && toggle(props.all_caps, criteria.all_caps)
+        && toggle(props.small_caps, criteria.small_caps)
+        && number(props.spacing, criteria.spacing)
+}
+
+/// A compiled search pattern
+enum Matcher {
+    /// Plain text with special tokens expanded
+    Literal(Vec<PatternElement>),
+    /// Regular expression
+    Regex(Regex),
+    /// Empty pattern: match any text with the requested formatting
+    FormatOnly,
+}
+
+impl Matcher {
+    fn new(pattern: &str, options: &FindOptions) -> Option<Self> {
+        if pattern.is_empty() {
+            return options.format.is_some().then_some(Self::FormatOnly);
+        }
+        if options.use_regex {
+            FindEngine::compile_regex(pattern, options)
+                .ok()
+                .map(Self::Regex)
+        } else {
+            Some(Self::Literal(PatternElement::parse(pattern)))
+        }
+    }
+}
+
+/// A paragraph to search, with optional offset limits
+#[derive(Debug, Clone, Copy)]
+struct ScopedParagraph {
+    para_id: NodeId,
+    start: usize,
+    end: Option<usize>,
+}
+
+impl ScopedParagraph {
+    fn whole(para_id: NodeId) -> Self {
+        Self {
+            para_id,
+            start: 0,
+            end: None,
+        }
+    }
+}
+
 /// Find engine for searching documents
 pub struct FindEngine<'a> {
     tree: &'a DocumentTree,
@@ -124,19 +338,33 @@ impl<'a> FindEngine<'a> {
         Self { tree }
     }
 
+    /// Check that a pattern is valid for the given options
+    pub fn validate_pattern(pattern: &str, options: &FindOptions) -> Result<()> {
+        if options.use_regex {
+            Self::compile_regex(pattern, options)?;
+        }
+        Ok(())
+    }
+
+    /// Compile a regex pattern with the case sensitivity from the options
+    fn compile_regex(pattern: &str, options: &FindOptions) -> Result<Regex> {
+        RegexBuilder::new(pattern)
+            .case_insensitive(!options.case_sensitive)
+            .build()
+            .map_err(|e| EditError::InvalidCommand(format!("Invalid regular expression: {}", e)))
+    }
+
     /// Find all matches in the document
     pub fn find_all(&self, pattern: &str, options: &FindOptions) -> Vec<FindResult> {
-        if pattern.is_empty() {
+        let Some(matcher) = Matcher::new(pattern, options) else {
             return Vec::new();
-        }
-
-        let mut results = Vec::new();
-        let para_ids: Vec<NodeId> = self.tree.document.children().to_vec();
+        };
 
-        for para_id in para_ids {
-            let matches = self.find_in_paragraph(para_id, pattern, options);
-            results.extend(matches);
-        }
+        let mut results: Vec<FindResult> = self
+            .scope_paragraphs(&options.scope)
+            .iter()
+            .flat_map(|range| self.find_in_range(range, &matcher, options))
+            .collect();
 
         // Assign match indices
         for (i, result) in results.iter_mut().enumerate() {
@@ -153,26 +381,26 @@ impl<'a> FindEngine<'a> {
         from: &Position,
         options: &FindOptions,
     ) -> Option<FindResult> {
-        if pattern.is_empty() {
-            return None;
-        }
-
-        let para_ids: Vec<NodeId> = self.tree.document.children().to_vec();
-        let start_para_index = para_ids.iter().position(|&id| id == from.node_id).unwrap_or(0);
+        let matcher = Matcher::new(pattern, options)?;
+        let ranges = self.scope_paragraphs(&options.scope);
+        let start_index = ranges.iter().position(|r| r.para_id == from.node_id).unwrap_or(0);
 
         // Search from current paragraph to end
-        for (offset, &para_id) in para_ids[start_para_index..].iter().enumerate() {
+        for (offset, range) in ranges.iter().skip(start_index).enumerate() {
             let start_offset = if offset == 0 { from.offset } else { 0 };
-            if let Some(result) = self.find_in_paragraph_from(para_id, pattern, options, start_offset, false)
-            {
-                return Some(result);
+            let found = self
+                .find_in_range(range, &matcher, options)
+                .into_iter()
+                .find(|m| m.start_offset >= start_offset);
+            if found.is_some() {
+                return found;
             }
         }
 
         // Wrap around if enabled
-        if options.wrap_around && start_para_index > 0 {
-            for &para_id in para_ids[..start_para_index].iter() {
-                if let Some(result) = self.find_in_paragraph_from(para_id, pattern, options, 0, false) {
+        if options.wrap_around && start_index > 0 {
+            for range in &ranges[..start_index] {
+                if let Some(result) = self.find_in_range(range, &matcher, options).into_iter().next() {
                     return Some(result);
                 }
             }
@@ -188,30 +416,30 @@ impl<'a> FindEngine<'a> {
         from: &Position,
         options: &FindOptions,
     ) -> Option<FindResult> {
-        if pattern.is_empty() {
+        let matcher = Matcher::new(pattern, options)?;
+        let ranges = self.scope_paragraphs(&options.scope);
+        if ranges.is_empty() {
             return None;
         }
-
-        let para_ids: Vec<NodeId> = self.tree.document.children().to_vec();
-        let start_para_index = para_ids.iter().position(|&id| id == from.node_id).unwrap_or(0);
+        let start_index = ranges.iter().position(|r| r.para_id == from.node_id).unwrap_or(0);
 
         // Search backwards from current paragraph to beginning
-        for (offset, &para_id) in para_ids[..=start_para_index].iter().rev().enumerate() {
+        for (offset, range) in ranges[..=start_index].iter().rev().enumerate() {
             let end_offset = if offset == 0 { from.offset } else { usize::MAX };
-            if let Some(result) = self.find_in_paragraph_from(para_id, pattern, options, 0, true) {
-                // For backwards search, we need to find the last match before end_offset
-                let matches = self.find_in_paragraph(para_id, pattern, options);
-                if let Some(last_before) = matches.into_iter().filter(|m| m.end_offset <= end_offset).last() {
-                    return Some(last_before);
-                }
+            let found = self
+                .find_in_range(range, &matcher, options)
+                .into_iter()
+                .rev()
+                .find(|m| m.end_offset <= end_offset);
+            if found.is_some() {
+                return found;
             }
         }
 
         // Wrap around if enabled
-        if options.wrap_around && start_para_index < para_ids.len() - 1 {
-            for &para_id in para_ids[start_para_index + 1..].iter().rev() {
-                let matches = self.find_in_paragraph(para_id, pattern, options);
-                if let Some(last) = matches.into_iter().last() {
+        if options.wrap_around {
+            for range in ranges[start_index + 1..].iter().rev() {
+                if let Some(last) = self.find_in_range(range, &matcher, options).into_iter().last() {
                     return Some(last);
                 }
             }
@@ -220,47 +448,146 @@ impl<'a> FindEngine<'a> {
         None
     }
 
-    /// Find matches in a single paragraph
-    fn find_in_paragraph(
+    /// Expand a replacement template for a match
+    ///
+    /// Special tokens are expanded first; in regex mode `$1` / `${name}`
+    /// then insert capture groups of the match.
+    pub fn expand_replacement(
         &self,
-        para_id: NodeId,
+        result: &FindResult,
         pattern: &str,
+        replacement: &str,
         options: &FindOptions,
-    ) -> Vec<FindResult> {
-        let text = self.get_paragraph_text(para_id);
-        if text.is_empty() {
-            return Vec::new();
+    ) -> String {
+        if !options.use_regex {
+            return expand_replacement_tokens(replacement, &result.matched_text);
         }
 
-        let matches = if options.use_regex {
-            self.find_regex_matches(&text, pattern, options)
+        let template = expand_replacement_tokens(replacement, "$0");
+        let Ok(regex) = Self::compile_regex(pattern, options) else {
+            return template;
+        };
+        let text = self.get_paragraph_text(result.node_id);
+        let byte_start = text
+            .char_indices()
+            .nth(result.start_offset)
+            .map_or(text.len(), |(i, _)| i);
+
+        match regex.captures_at(&text, byte_start) {
+            Some(caps) if caps.get(0).is_some_and(|m| m.start() == byte_start) => {
+                let mut expanded = String::new();
+                caps.expand(&template, &mut expanded);
+                expanded
+            }
+            _ => template,
+        }
+    }
+
+    /// Paragraphs covered by a search scope, in search order
+    fn scope_paragraphs(&self, scope: &SearchScope) -> Vec<ScopedParagraph> {
+        let whole = |ids: Vec<NodeId>| ids.into_iter().map(ScopedParagraph::whole).collect();
+        match scope {
+            SearchScope::MainDocument => whole(self.tree.document.children().to_vec()),
+            SearchScope::Section(ids) => whole(ids.clone()),
+            SearchScope::Footnotes => whole(self.note_paragraphs(self.tree.notes.footnotes())),
+            SearchScope::Endnotes => whole(self.note_paragraphs(self.tree.notes.endnotes())),
+            SearchScope::AllStories => {
+                let mut ids = self.tree.document.children().to_vec();
+                ids.extend(self.note_paragraphs(self.tree.notes.footnotes()));
+                ids.extend(self.note_paragraphs(self.tree.notes.endnotes()));
+                whole(ids)
+            }
+            SearchScope::Selection(selection) => self.selection_paragraphs(selection),
+        }
+    }
+
+    /// Paragraphs of a selection, with the first and last clipped to it
+    fn selection_paragraphs(&self, selection: &Selection) -> Vec<ScopedParagraph> {
+        if selection.is_collapsed() {
+            return Vec::new();
+        }
+        let para_ids = self.tree.document.children();
+        let index_of = |pos: Position| {
+            para_ids
+                .iter()
+                .position(|&id| id == pos.node_id)
+                .map(|index| (index, pos.offset))
+        };
+        let (Some(anchor), Some(focus)) = (index_of(selection.anchor), index_of(selection.focus))
+        else {
+            return Vec::new();
+        };
+        let ((first, start_offset), (last, end_offset)) = if anchor <= focus {
+            (anchor, focus)
         } else {
-            self.find_literal_matches(&text, pattern, options)
+            (focus, anchor)
+        };
+
+        para_ids[first..=last]
+            .iter()
+            .enumerate()
+            .map(|(i, &para_id)| ScopedParagraph {
+                para_id,
+                start: if i == 0 { start_offset } else { 0 },
+                end: (first + i == last).then_some(end_offset),
+            })
+            .collect()
+    }
+
+    /// Content paragraphs of notes, ordered by their reference position
+    fn note_paragraphs<'n>(&self, notes: impl Iterator<Item = &'n Note>) -> Vec<NodeId> {
+        let body = self.tree.document.children();
+        let mut notes: Vec<&Note> = notes.collect();
+        notes.sort_by_key(|note| {
+            note.reference_position
+                .map(|pos| {
+                    let index = body.iter().position(|&id| id == pos.node_id);
+                    (index.unwrap_or(usize::MAX), pos.offset)
+                })
+                .unwrap_or((usize::MAX, usize::MAX))
+        });
+        notes
+            .into_iter()
+            .flat_map(|note| note.content().iter().copied())
+            .collect()
+    }
+
+    /// Find matches in a scoped paragraph
+    fn find_in_range(
+        &self,
+        range: &ScopedParagraph,
+        matcher: &Matcher,
+        options: &FindOptions,
+    ) -> Vec<FindResult> {
+        let text = self.get_paragraph_text(range.para_id);
+
+        let mut matches = match matcher {
+            Matcher::Literal(elements) => self.find_literal_matches(&text, elements, options),
+            Matcher::Regex(regex) => self.find_regex_matches(&text, regex, options),
+            Matcher::FormatOnly => Vec::new(),
         };
 
+        if let Some(criteria) = &options.format {
+            let mask = self.format_mask(range.para_id, criteria);
+            let formatted = |i: usize| mask.get(i).copied().unwrap_or(true);
+            if let Matcher::FormatOnly = matcher {
+                matches = Self::formatted_spans(&text, &mask);
+            } else {
+                matches.retain(|(start, end, _)| (*start..*end).all(formatted));
+            }
+        }
+
         matches
             .into_iter()
+            .filter(|(start, end, _)| *start >= range.start && range.end.is_none_or(|e| *end <= e))
             .map(|(start, end, matched)| {
-                let mut result = FindResult::new(para_id, start, end, matched);
+                let mut result = FindResult::new(range.para_id, start, end, matched);
                 result.context = Some(self.get_context(&text, start, end));
                 result
             })
             .collect()
     }
 
-    /// Find the first match in a paragraph starting from an offset
-    fn find_in_paragraph_from(
-        &self,
-        para_id: NodeId,
-        pattern: &str,
-        options: &FindOptions,
-        from_offset: usize,
-        _backwards: bool,
-    ) -> Option<FindResult> {
-        let matches = self.find_in_paragraph(para_id, pattern, options);
-        matches.into_iter().find(|m| m.start_offset >= from_offset)
-    }
-
     /// Get the text content of a paragraph
     fn get_paragraph_text(&self, para_id: NodeId) -> String {
         let para = match self.tree.get_paragraph(para_id) {
@@ -277,57 +604,89 @@ impl<'a> FindEngine<'a> {
         text
     }
 
+    /// Per-character flags telling whether the text has the requested formatting
+    fn format_mask(&self, para_id: NodeId, criteria: &CharacterProperties) -> Vec<bool> {
+        let Some(para) = self.tree.get_paragraph(para_id) else {
+            return Vec::new();
+        };
+
+        let mut mask = Vec::new();
+        for &run_id in para.children() {
+            let Some(run) = self.tree.get_run(run_id) else {
+                continue;
+            };
+            let props = self
+                .tree
+                .compute_character_properties(run_id)
+                .unwrap_or_default()
+                .merge(&run.style.to_character_properties())
+                .merge(&run.direct_formatting);
+            let matches = format_matches(&props, criteria);
+            mask.extend(std::iter::repeat_n(matches, run.text.chars().count()));
+        }
+        mask
+    }
+
+    /// Maximal spans of text whose formatting matches
+    fn formatted_spans(text: &str, mask: &[bool]) -> Vec<(usize, usize, String)> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut spans = Vec::new();
+        let mut start = None;
+        for i in 0..=chars.len() {
+            let formatted = i < chars.len() && mask.get(i).copied().unwrap_or(false);
+            match (formatted, start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    spans.push((s, i, chars[s..i].iter().collect()));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        spans
+    }
+
+    /// Check whether a match is bounded by non-word characters
+    fn is_whole_word(chars: &[char], start: usize, end: usize) -> bool {
+        let before_ok = start == 0 || !chars[start - 1].is_alphanumeric();
+        let after_ok = end >= chars.len() || !chars[end].is_alphanumeric();
+        before_ok && after_ok
+    }
+
     /// Find literal (non-regex) matches
     fn find_literal_matches(
         &self,
         text: &str,
-        pattern: &str,
+        elements: &[PatternElement],
         options: &FindOptions,
     ) -> Vec<(usize, usize, String)> {
         let mut results = Vec::new();
 
-        let (search_text, search_pattern) = if options.case_sensitive {
-            (text.to_string(), pattern.to_string())
-        } else {
-            (text.to_lowercase(), pattern.to_lowercase())
-        };
-
-        let text_chars: Vec<char> = text.chars().collect();
-        let search_chars: Vec<char> = search_text.chars().collect();
-        let pattern_chars: Vec<char> = search_pattern.chars().collect();
-        let pattern_len = pattern_chars.len();
+        // The paragraph mark is only searchable when the pattern asks for it
+        let mut chars: Vec<char> = text.chars().collect();
+        if elements.contains(&PatternElement::ParagraphMark) {
+            chars.push(PARAGRAPH_MARK);
+        }
+        let pattern_len = elements.len();
 
-        if pattern_len == 0 || search_chars.len() < pattern_len {
+        if pattern_len == 0 || chars.len() < pattern_len {
             return results;
         }
 
         let mut char_index = 0;
-        while char_index + pattern_len <= search_chars.len() {
-            let window: String = search_chars[char_index..char_index + pattern_len]
+        while char_index + pattern_len <= chars.len() {
+            let end = char_index + pattern_len;
+            let is_match = elements
                 .iter()
-                .collect();
-
-            if window == search_pattern {
-                // Check whole word if required
-                let is_whole_word = if options.whole_word {
-                    let before_ok = char_index == 0
-                        || !search_chars[char_index - 1].is_alphanumeric();
-                    let after_ok = char_index + pattern_len >= search_chars.len()
-                        || !search_chars[char_index + pattern_len].is_alphanumeric();
-                    before_ok && after_ok
-                } else {
-                    true
-                };
-
-                if is_whole_word {
-                    let matched: String = text_chars[char_index..char_index + pattern_len]
-                        .iter()
-                        .collect();
-                    results.push((char_index, char_index + pattern_len, matched));
-                }
-            }
+                .zip(&chars[char_index..end])
+                .all(|(element, &c)| element.matches(c, options.case_sensitive));
 
-            char_index += 1;
+            if is_match && (!options.whole_word || Self::is_whole_word(&chars, char_index, end)) {
+                results.push((char_index, end, chars[char_index..end].iter().collect()));
+                char_index = end;
+            } else {
+                char_index += 1;
+            }
         }
 
         results
@@ -337,38 +696,21 @@ impl<'a> FindEngine<'a> {
     fn find_regex_matches(
         &self,
         text: &str,
-        pattern: &str,
+        regex: &Regex,
         options: &FindOptions,
     ) -> Vec<(usize, usize, String)> {
-        // Build regex pattern with options
-        let regex_pattern = if options.case_sensitive {
-            pattern.to_string()
-        } else {
-            format!("(?i){}", pattern)
-        };
-
-        let regex_pattern = if options.whole_word {
-            format!(r"\b{}\b", regex_pattern)
-        } else {
-            regex_pattern
-        };
-
-        // Note: In a real implementation, we would use the regex crate
-        // For now, we'll use a simple implementation that handles basic patterns
-        self.simple_regex_match(text, &regex_pattern, options)
-    }
+        let chars: Vec<char> = text.chars().collect();
 
-    /// Simple regex matching (basic implementation)
-    /// In production, this would use the regex crate
-    fn simple_regex_match(
-        &self,
-        text: &str,
-        _pattern: &str,
-        _options: &FindOptions,
-    ) -> Vec<(usize, usize, String)> {
-        // This is a placeholder - a full implementation would use the regex crate
-        // For now, fall back to literal matching
-        Vec::new()
+        regex
+            .find_iter(text)
+            .filter(|m| !m.is_empty())
+            .map(|m| {
+                let start = text[..m.start()].chars().count();
+                let end = start + m.as_str().chars().count();
+                (start, end, m.as_str().to_string())
+            })
+            .filter(|(start, end, _)| !options.whole_word || Self::is_whole_word(&chars, *start, *end))
+            .collect()
     }
 
     /// Get context around a match for preview
@@ -376,7 +718,8 @@ impl<'a> FindEngine<'a> {
         let chars: Vec<char> = text.chars().collect();
         let context_chars = 20;
 
-        let context_start = start.saturating_sub(context_chars);
+        let end = end.min(chars.len());
+        let context_start = start.min(end).saturating_sub(context_chars);
         let context_end = (end + context_chars).min(chars.len());
 
         let mut context: String = chars[context_start..context_end].iter().collect();
@@ -412,87 +755,49 @@ impl ReplaceEngine {
         replacement: &str,
         selection: &Selection,
     ) -> Result<CommandResult> {
-        let mut new_tree = tree.clone();
+        Self::replace_with_format(tree, find_result, replacement, None, selection)
+    }
 
-        // Get the paragraph
+    /// Replace a single match, applying `format` to the inserted text
+    ///
+    /// [`PARAGRAPH_MARK`] characters in `replacement` become paragraph breaks.
+    /// With an empty replacement and a format, the found text is kept and
+    /// only reformatted.
+    pub fn replace_with_format(
+        tree: &DocumentTree,
+        find_result: &FindResult,
+        replacement: &str,
+        format: Option<&CharacterProperties>,
+        _selection: &Selection,
+    ) -> Result<CommandResult> {
         let para_id = find_result.node_id;
-        let para = new_tree.get_paragraph(para_id)
-            .ok_or_else(|| EditError::InvalidCommand("Paragraph not found".to_string()))?;
-
-        // Find which run(s) contain the match and modify them
-        let mut current_offset = 0;
         let start = find_result.start_offset;
-        let end = find_result.end_offset;
-        let run_ids: Vec<NodeId> = para.children().to_vec();
-
-        // Track modifications across runs
-        let mut remaining_delete_start = start;
-        let mut remaining_delete_end = end;
-        let mut inserted = false;
-
-        for run_id in run_ids {
-            let run = new_tree.get_run_mut(run_id)
-                .ok_or_else(|| EditError::InvalidCommand("Run not found".to_string()))?;
-
-            let run_len = run.text.chars().count();
-            let run_start = current_offset;
-            let run_end = current_offset + run_len;
-
-            // Check if this run overlaps with the deletion range
-            if run_end > remaining_delete_start && run_start < remaining_delete_end {
-                let chars: Vec<char> = run.text.chars().collect();
-
-                let delete_start_in_run = if remaining_delete_start > run_start {
-                    remaining_delete_start - run_start
-                } else {
-                    0
-                };
-
-                let delete_end_in_run = if remaining_delete_end < run_end {
-                    remaining_delete_end - run_start
-                } else {
-                    run_len
-                };
-
-                // Build new text
-                let mut new_text = String::new();
-
-                // Add text before deletion
-                for &c in &chars[..delete_start_in_run] {
-                    new_text.push(c);
-                }
-
-                // Add replacement (only once, in the first affected run)
-                if !inserted {
-                    new_text.push_str(replacement);
-                    inserted = true;
-                }
-
-                // Add text after deletion
-                for &c in &chars[delete_end_in_run..] {
-                    new_text.push(c);
-                }
-
-                run.text = new_text;
-
-                // Update remaining range
-                remaining_delete_start = run_end;
-            }
+        let text = Self::replacement_text(find_result, replacement, format);
 
-            current_offset = run_end;
-        }
+        let mut new_tree = tree.clone();
+        Self::apply_replacement(&mut new_tree, find_result, &text, format)?;
 
         // Calculate new selection position (after replacement)
-        let new_position = Position::new(para_id, start + replacement.chars().count());
-        let new_selection = Selection::collapsed(new_position);
-
-        // Create inverse command (would replace back with original)
-        let inverse = Box::new(ReplaceText {
-            para_id,
-            start_offset: start,
-            end_offset: start + replacement.chars().count(),
-            replacement: find_result.matched_text.clone(),
-        });
+        let plain = !text.contains(PARAGRAPH_MARK)
+            && !find_result.matched_text.contains(PARAGRAPH_MARK);
+        let caret = if plain { start + text.chars().count() } else { start };
+        let new_selection = Selection::collapsed(Position::new(para_id, caret));
+
+        // Plain text edits invert to the original text; structural or
+        // formatting edits restore the whole tree
+        let inverse: Box<dyn Command> = if plain && format.is_none() {
+            Box::new(ReplaceText {
+                para_id,
+                start_offset: start,
+                end_offset: start + text.chars().count(),
+                replacement: find_result.matched_text.clone(),
+            })
+        } else {
+            Box::new(ReplaceAllUndo {
+                original_tree: tree.clone(),
+                count: 1,
+            })
+        };
 
         Ok(CommandResult {
             tree: new_tree,
@@ -509,6 +814,23 @@ impl ReplaceEngine {
         options: &FindOptions,
         selection: &Selection,
     ) -> Result<CommandResult> {
+        Self::replace_all_with_format(tree, pattern, replacement, None, options, selection)
+    }
+
+    /// Replace all matches, applying `format` to the inserted text
+    ///
+    /// The replacement may use special tokens (`^&`, `^p`, `^t`, ...) and, in
+    /// regex mode, capture group references such as `$1`.
+    pub fn replace_all_with_format(
+        tree: &DocumentTree,
+        pattern: &str,
+        replacement: &str,
+        format: Option<&CharacterProperties>,
+        options: &FindOptions,
+        selection: &Selection,
+    ) -> Result<CommandResult> {
+        FindEngine::validate_pattern(pattern, options)?;
+
         let engine = FindEngine::new(tree);
         let matches = engine.find_all(pattern, options);
 
@@ -520,82 +842,24 @@ impl ReplaceEngine {
             });
         }
 
-        let mut new_tree = tree.clone();
-        let match_count = matches.len();
-
-        // Group matches by paragraph
-        let mut matches_by_para: HashMap<NodeId, Vec<FindResult>> = HashMap::new();
-        for m in matches {
-            matches_by_para.entry(m.node_id).or_default().push(m);
-        }
+        let replacements: Vec<String> = matches
+            .iter()
+            .map(|m| engine.expand_replacement(m, pattern, replacement, options))
+            .collect();
 
-        // Process each paragraph (in reverse order of matches to maintain positions)
-        for (para_id, mut para_matches) in matches_by_para {
-            // Sort matches in reverse order so we can replace from end to start
-            para_matches.sort_by(|a, b| b.start_offset.cmp(&a.start_offset));
-
-            for find_result in para_matches {
-                // Get current paragraph state
-                let para = new_tree.get_paragraph(para_id)
-                    .ok_or_else(|| EditError::InvalidCommand("Paragraph not found".to_string()))?;
-
-                let mut current_offset = 0;
-                let start = find_result.start_offset;
-                let end = find_result.end_offset;
-                let run_ids: Vec<NodeId> = para.children().to_vec();
-
-                let mut remaining_delete_start = start;
-                let mut remaining_delete_end = end;
-                let mut inserted = false;
-
-                for run_id in run_ids {
-                    if let Some(run) = new_tree.get_run_mut(run_id) {
-                        let run_len = run.text.chars().count();
-                        let run_start = current_offset;
-                        let run_end = current_offset + run_len;
-
-                        if run_end > remaining_delete_start && run_start < remaining_delete_end {
-                            let chars: Vec<char> = run.text.chars().collect();
-
-                            let delete_start_in_run = if remaining_delete_start > run_start {
-                                remaining_delete_start - run_start
-                            } else {
-                                0
-                            };
-
-                            let delete_end_in_run = if remaining_delete_end < run_end {
-                                remaining_delete_end - run_start
-                            } else {
-                                run_len
-                            };
-
-                            let mut new_text = String::new();
-                            for &c in &chars[..delete_start_in_run] {
-                                new_text.push(c);
-                            }
-                            if !inserted {
-                                new_text.push_str(replacement);
-                                inserted = true;
-                            }
-                            for &c in &chars[delete_end_in_run..] {
-                                new_text.push(c);
-                            }
-
-                            run.text = new_text;
-                            remaining_delete_start = run_end;
-                        }
-
-                        current_offset = run_end;
-                    }
-                }
-            }
+        // Replace from the end of the document backwards so that offsets of
+        // earlier matches and paragraph identities stay valid
+        let mut new_tree = tree.clone();
+        for (find_result, text) in matches.iter().zip(&replacements).rev() {
+            let text = Self::replacement_text(find_result, text, format);
+            Self::apply_replacement(&mut new_tree, find_result, &text, format)?;
         }
 
         // For replace all, the inverse would need to store all original matches
         // For simplicity, we'll use a compound undo marker
         let inverse = Box::new(ReplaceAllUndo {
             original_tree: tree.clone(),
-            count: match_count,
+            count: matches.len(),
         });
 
         Ok(CommandResult {
@@ -604,6 +868,212 @@ impl ReplaceEngine {
             inverse,
         })
     }
+
+    /// Text to insert for a match: the found text when only reformatting
+    fn replacement_text(
+        find_result: &FindResult,
+        replacement: &str,
+        format: Option<&CharacterProperties>,
+    ) -> String {
+        if replacement.is_empty() && format.is_some() {
+            find_result.matched_text.clone()
+        } else {
+            replacement.to_string()
+        }
+    }
+
+    /// Replace the text of a match in place
+    fn apply_replacement(
+        tree: &mut DocumentTree,
+        find_result: &FindResult,
+        text: &str,
+        format: Option<&CharacterProperties>,
+    ) -> Result<()> {
+        let para_id = find_result.node_id;
+        let start = find_result.start_offset;
+        let para_len = Self::paragraph_len(tree, para_id)?;
+        let end = find_result.end_offset.min(para_len);
+        let removes_mark = find_result.end_offset > para_len;
+
+        let pieces: Vec<&str> = text.split(PARAGRAPH_MARK).collect();
+        let inserted: String = pieces.concat();
+
+        match format {
+            Some(format) => {
+                Self::splice_text(tree, para_id, start, end, "")?;
+                Self::insert_formatted_run(tree, para_id, start, &inserted, format)?;
+            }
+            None => Self::splice_text(tree, para_id, start, end, &inserted)?,
+        }
+
+        // A matched paragraph mark joins the next paragraph onto this one
+        if removes_mark {
+            let body = tree.document.children();
+            let next = body
+                .iter()
+                .position(|&id| id == para_id)
+                .and_then(|i| body.get(i + 1).copied());
+            if let Some(next_id) = next {
+                let selection = Selection::collapsed(Position::new(para_id, start));
+                *tree = MergeParagraph::new(next_id).apply(tree, &selection)?.tree;
+            }
+        }
+
+        // Paragraph marks in the replacement split the paragraph, last first
+        let mut split_offset = start;
+        let mut split_offsets = Vec::new();
+        for piece in &pieces[..pieces.len() - 1] {
+            split_offset += piece.chars().count();
+            split_offsets.push(split_offset);
+        }
+        for offset in split_offsets.into_iter().rev() {
+            let position = Position::new(para_id, offset);
+            *tree = SplitParagraph::new(position)
+                .apply(tree, &Selection::collapsed(position))?
+                .tree;
+        }
+
+        Ok(())
+    }
+
+    /// Length of a paragraph's text in characters
+    fn paragraph_len(tree: &DocumentTree, para_id: NodeId) -> Result<usize> {
+        let para = tree
+            .get_paragraph(para_id)
+            .ok_or_else(|| EditError::InvalidCommand("Paragraph not found".to_string()))?;
+        Ok(para
+            .children()
+            .iter()
+            .filter_map(|&id| tree.get_run(id))
+            .map(|run| run.text.chars().count())
+            .sum())
+    }
+
+    /// Replace a character range of a paragraph with plain text
+    ///
+    /// The new text is inserted into the first affected run and takes on its
+    /// formatting.
+    fn splice_text(
+        tree: &mut DocumentTree,
+        para_id: NodeId,
+        start: usize,
+        end: usize,
+        replacement: &str,
+    ) -> Result<()> {
+        let para = tree
+            .get_paragraph(para_id)
+            .ok_or_else(|| EditError::InvalidCommand("Paragraph not found".to_string()))?;
+        let run_ids: Vec<NodeId> = para.children().to_vec();
+
+        let mut current_offset = 0;
+        let mut remaining_delete_start = start;
+        let mut inserted = false;
+
+        for run_id in run_ids {
+            let run = tree
+                .get_run_mut(run_id)
+                .ok_or_else(|| EditError::InvalidCommand("Run not found".to_string()))?;
+
+            let run_len = run.text.chars().count();
+            let run_start = current_offset;
+            let run_end = current_offset + run_len;
+
+            // Check if this run overlaps with the deletion range; an empty
+            // range inserts into the run that contains its start
+            let overlaps = if start == end {
+                !inserted && run_start <= start && start <= run_end
+            } else {
+                run_end > remaining_delete_start && run_start < end
+            };
+
+            if overlaps {
+                let chars: Vec<char> = run.text.chars().collect();
+                let delete_start_in_run = remaining_delete_start.saturating_sub(run_start);
+                let delete_end_in_run = end.min(run_end) - run_start;
+
+                let mut new_text: String = chars[..delete_start_in_run].iter().collect();
+
+                // Add replacement (only once, in the first affected run)
+                if !inserted {
+                    new_text.push_str(replacement);
+                    inserted = true;
+                }
+
+                new_text.extend(&chars[delete_end_in_run..]);
+                run.text = new_text;
+
+                // Update remaining range
+                remaining_delete_start = run_end;
+            }
+
+            current_offset = run_end;
+        }
+
+        Ok(())
+    }
+
+    /// Insert text as a new run at a paragraph offset, splitting the run there
+    ///
+    /// The new run keeps the surrounding run's formatting with `format`
+    /// applied on top.
+    fn insert_formatted_run(
+        tree: &mut DocumentTree,
+        para_id: NodeId,
+        offset: usize,
+        text: &str,
+        format: &CharacterProperties,
+    ) -> Result<()> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let para = tree
+            .get_paragraph(para_id)
+            .ok_or_else(|| EditError::InvalidCommand("Paragraph not found".to_string()))?;
+        let run_ids: Vec<NodeId> = para.children().to_vec();
+
+        // Find the run containing the offset and where in it to split
+        let mut current_offset = 0;
+        let mut target = None;
+        for (index, &run_id) in run_ids.iter().enumerate() {
+            let run_len = tree.get_run(run_id).map_or(0, |r| r.text.chars().count());
+            if offset <= current_offset + run_len {
+                target = Some((index, run_id, offset - current_offset));
+                break;
+            }
+            current_offset += run_len;
+        }
+
+        let (index, base) = match target {
+            Some((index, run_id, split_at)) => {
+                let base = tree
+                    .get_run_mut(run_id)
+                    .ok_or_else(|| EditError::InvalidCommand("Run not found".to_string()))?;
+                let tail: String = base.text.chars().skip(split_at).collect();
+                base.text = base.text.chars().take(split_at).collect();
+
+                let mut tail_run = Run::with_style(tail, base.style.clone());
+                tail_run.character_style_id = base.character_style_id.clone();
+                tail_run.direct_formatting = base.direct_formatting.clone();
+                let base = base.clone();
+                if !tail_run.text.is_empty() {
+                    tree.insert_run(tail_run, para_id, Some(index + 1))?;
+                }
+                (index + 1, Some(base))
+            }
+            None => (run_ids.len(), None),
+        };
+
+        let mut run = Run::new(text);
+        if let Some(base) = base {
+            run.style = base.style;
+            run.character_style_id = base.character_style_id;
+            run.direct_formatting = base.direct_formatting;
+        }
+        run.direct_formatting = run.direct_formatting.merge(format);
+        tree.insert_run(run, para_id, Some(index))?;
+
+        Ok(())
+    }
 }
 
 /// Command to replace text at a specific location
@@ -808,6 +1278,9 @@ pub struct ReplaceCommand {
     pub replacement: String,
     /// Optional: the pattern that was searched (for validation)
     pub expected_pattern: Option<String>,
+    /// Character formatting applied to the replacement text
+    #[serde(default)]
+    pub format: Option<CharacterProperties>,
 }
 
 impl ReplaceCommand {
@@ -816,6 +1289,7 @@ impl ReplaceCommand {
         Self {
             replacement: replacement.into(),
             expected_pattern: None,
+            format: None,
         }
     }
 
@@ -824,8 +1298,15 @@ impl ReplaceCommand {
         Self {
             replacement: replacement.into(),
             expected_pattern: Some(pattern.into()),
+            format: None,
         }
     }
+
+    /// Apply character formatting to the replacement text
+    pub fn with_format(mut self, format: CharacterProperties) -> Self {
+        self.format = Some(format);
+        self
+    }
 }
 
 impl Command for ReplaceCommand {
@@ -859,7 +1340,13 @@ impl Command for ReplaceCommand {
                 selected_text,
             );
 
-            ReplaceEngine::replace(tree, &find_result, &self.replacement, selection)
+            ReplaceEngine::replace_with_format(
+                tree,
+                &find_result,
+                &self.replacement,
+                self.format.as_ref(),
+                selection,
+            )
         } else {
             Err(EditError::InvalidCommand(
                 "Cross-paragraph replace not yet supported".to_string(),
@@ -893,6 +1380,9 @@ pub struct ReplaceAllCommand {
     pub replacement: String,
     /// Search options
     pub options: FindOptions,
+    /// Character formatting applied to the replacement text
+    #[serde(default)]
+    pub format: Option<CharacterProperties>,
 }
 
 impl ReplaceAllCommand {
@@ -902,6 +1392,7 @@ impl ReplaceAllCommand {
             pattern: pattern.into(),
             replacement: replacement.into(),
             options: FindOptions::default(),
+            format: None,
         }
     }
 
@@ -915,13 +1406,27 @@ impl ReplaceAllCommand {
             pattern: pattern.into(),
             replacement: replacement.into(),
             options,
+            format: None,
         }
     }
+
+    /// Apply character formatting to the replacement text
+    pub fn with_format(mut self, format: CharacterProperties) -> Self {
+        self.format = Some(format);
+        self
+    }
 }
 
 impl Command for ReplaceAllCommand {
     fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
-        ReplaceEngine::replace_all(tree, &self.pattern, &self.replacement, &self.options, selection)
+        ReplaceEngine::replace_all_with_format(
+            tree,
+            &self.pattern,
+            &self.replacement,
+            self.format.as_ref(),
+            &self.options,
+            selection,
+        )
     }
 
     fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
@@ -1225,4 +1730,183 @@ mod tests {
         let context = results[0].context.as_ref().unwrap();
         assert!(context.contains("fox"));
     }
+
+    fn create_test_tree_with_paragraphs(texts: &[&str]) -> (DocumentTree, Vec<NodeId>) {
+        let mut tree = DocumentTree::new();
+        let mut ids = Vec::new();
+        for text in texts {
+            let para = Paragraph::new();
+            let para_id = para.id();
+            tree.insert_paragraph(para, tree.root_id(), None).unwrap();
+            tree.insert_run(Run::new(*text), para_id, None).unwrap();
+            ids.push(para_id);
+        }
+        (tree, ids)
+    }
+
+    fn paragraph_texts(tree: &DocumentTree) -> Vec<String> {
+        let engine = FindEngine::new(tree);
+        tree.document
+            .children()
+            .iter()
+            .map(|&id| engine.get_paragraph_text(id))
+            .collect()
+    }
+
+    fn bold_14() -> CharacterProperties {
+        CharacterProperties {
+            bold: Some(true),
+            font_size: Some(14.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_regex_find_and_capture_replace() {
+        let (tree, para_id) = create_test_tree_with_text("Due 2024-01-15 and 2025-12-31.");
+
+        let options = FindOptions::new().regex(true);
+        let engine = FindEngine::new(&tree);
+        let results = engine.find_all(r"(\d{4})-(\d{2})-(\d{2})", &options);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].matched_text, "2024-01-15");
+
+        let selection = Selection::collapsed(Position::new(para_id, 0));
+        let result = ReplaceEngine::replace_all(
+            &tree,
+            r"(\d{4})-(\d{2})-(\d{2})",
+            "$3/$2/$1",
+            &options,
+            &selection,
+        )
+        .unwrap();
+        assert_eq!(paragraph_texts(&result.tree), vec!["Due 15/01/2024 and 31/12/2025."]);
+    }
+
+    #[test]
+    fn test_regex_options_and_invalid_pattern() {
+        let (tree, para_id) = create_test_tree_with_text("Cat cat catalog");
+        let engine = FindEngine::new(&tree);
+
+        let options = FindOptions::new().regex(true).whole_word(true);
+        assert_eq!(engine.find_all("ca.", &options).len(), 2);
+        assert_eq!(engine.find_all("ca.", &options.clone().case_sensitive(true)).len(), 1);
+
+        let invalid = FindOptions::new().regex(true);
+        assert!(engine.find_all("(unclosed", &invalid).is_empty());
+        let selection = Selection::collapsed(Position::new(para_id, 0));
+        assert!(ReplaceEngine::replace_all(&tree, "(unclosed", "x", &invalid, &selection).is_err());
+    }
+
+    #[test]
+    fn test_find_by_formatting() {
+        let mut tree = DocumentTree::new();
+        let para = Paragraph::new();
+        let para_id = para.id();
+        tree.insert_paragraph(para, tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new("Note: plain. "), para_id, None).unwrap();
+        tree.insert_run(Run::with_direct_formatting("Note", bold_14()), para_id, None)
+            .unwrap();
+        tree.insert_run(Run::new(" again Note"), para_id, None).unwrap();
+
+        let engine = FindEngine::new(&tree);
+        assert_eq!(engine.find_all("Note", &FindOptions::new()).len(), 3);
+
+        let options = FindOptions::new().with_format(bold_14());
+        let results = engine.find_all("Note", &options);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].start_offset, 13);
+
+        // An empty pattern finds all text with the formatting
+        let results = engine.find_all("", &options);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].matched_text, "Note");
+    }
+
+    #[test]
+    fn test_special_tokens() {
+        let (tree, ids) = create_test_tree_with_paragraphs(&["a\tb", "", "c"]);
+        let engine = FindEngine::new(&tree);
+
+        assert_eq!(engine.find_all("a^tb", &FindOptions::new()).len(), 1);
+        assert_eq!(engine.find_all("^#", &FindOptions::new()).len(), 0);
+        assert_eq!(engine.find_all("^p", &FindOptions::new()).len(), 3);
+        assert_eq!(engine.find_all("^$", &FindOptions::new()).len(), 3);
+
+        // Removing a paragraph mark joins the following paragraph
+        let selection = Selection::collapsed(Position::new(ids[0], 0));
+        let result =
+            ReplaceEngine::replace_all(&tree, "b^p", "b", &FindOptions::new(), &selection)
+                .unwrap();
+        assert_eq!(paragraph_texts(&result.tree), vec!["a\tb", "c"]);
+    }
+
+    #[test]
+    fn test_replace_with_paragraph_break_and_found_text() {
+        let (tree, para_id) = create_test_tree_with_text("one; two");
+        let selection = Selection::collapsed(Position::new(para_id, 0));
+
+        let result = ReplaceEngine::replace_all(&tree, "; ", "^p", &FindOptions::new(), &selection)
+            .unwrap();
+        assert_eq!(paragraph_texts(&result.tree), vec!["one", "two"]);
+
+        let result = ReplaceEngine::replace_all(&tree, "two", "[^&]", &FindOptions::new(), &selection)
+            .unwrap();
+        assert_eq!(paragraph_texts(&result.tree), vec!["one; [two]"]);
+    }
+
+    #[test]
+    fn test_replace_with_formatting() {
+        let (tree, para_id) = create_test_tree_with_text("cat dog cat");
+        let selection = Selection::collapsed(Position::new(para_id, 0));
+
+        let cmd = ReplaceAllCommand::new("dog", "fox").with_format(bold_14());
+        let result = cmd.apply(&tree, &selection).unwrap();
+        assert_eq!(paragraph_texts(&result.tree), vec!["cat fox cat"]);
+
+        let engine = FindEngine::new(&result.tree);
+        let bold = engine.find_all("", &FindOptions::new().with_format(bold_14()));
+        assert_eq!(bold.len(), 1);
+        assert_eq!(bold[0].matched_text, "fox");
+
+        // An empty replacement only reformats the found text
+        let cmd = ReplaceAllCommand::new("cat", "").with_format(bold_14());
+        let result = cmd.apply(&tree, &selection).unwrap();
+        assert_eq!(paragraph_texts(&result.tree), vec!["cat dog cat"]);
+        let engine = FindEngine::new(&result.tree);
+        let bold = engine.find_all("cat", &FindOptions::new().with_format(bold_14()));
+        assert_eq!(bold.len(), 2);
+    }
+
+    #[test]
+    fn test_search_scopes() {
+        let (mut tree, ids) = create_test_tree_with_paragraphs(&["alpha beta", "beta gamma", "beta"]);
+
+        // Selection from the middle of the first paragraph into the second
+        let selection = Selection::new(Position::new(ids[0], 3), Position::new(ids[1], 4));
+        let options = FindOptions::new().scope(SearchScope::Selection(selection));
+        let engine = FindEngine::new(&tree);
+        let results = engine.find_all("beta", &options);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].node_id, ids[1]);
+
+        let options = FindOptions::new().scope(SearchScope::Section(vec![ids[2]]));
+        assert_eq!(engine.find_all("beta", &options).len(), 1);
+
+        // Footnote text is only searched when the scope includes it
+        let (note_id, _) = tree.insert_footnote(Position::new(ids[0], 5), None);
+        let note_para = Paragraph::new();
+        let note_para_id = note_para.id();
+        tree.nodes.paragraphs.insert(note_para_id, note_para);
+        tree.insert_run(Run::new("beta source"), note_para_id, None).unwrap();
+        tree.add_footnote_content(note_id, note_para_id);
+
+        let engine = FindEngine::new(&tree);
+        assert_eq!(engine.find_all("beta", &FindOptions::new()).len(), 3);
+        let footnotes = engine.find_all("beta", &FindOptions::new().scope(SearchScope::Footnotes));
+        assert_eq!(footnotes.len(), 1);
+        assert_eq!(footnotes[0].node_id, note_para_id);
+        let all = FindOptions::new().scope(SearchScope::AllStories);
+        assert_eq!(engine.find_all("beta", &all).len(), 4);
+    }
 }
@@ -558,7 +558,7 @@ impl Command for ConvertNote {
 
         // Renumber both types
         new_tree.notes.renumber_footnotes();
-        new_tree.notes.renumber_endnotes();
+        new_tree.renumber_endnotes();
 
         // Create inverse command
         let new_type = match self.current_type {
@@ -0,0 +1,674 @@
+//! Format Painter commands
+//!
+//! The format painter copies the character and paragraph formatting (including
+//! list membership and level) at a position and applies it to another range:
+//! - `CopyFormatting` captures the formatting into a `FormatPainter`
+//! - `ApplyCopiedFormatting` applies it to the selection (undoable)
+//!
+//! The `EditingEngine` holds the active `FormatPainter`; in sticky mode it stays
+//! active for multiple applications until cancelled.
+//!
+//! `ApplyStyle` shares the same range formatting: it applies a named paragraph
+//! or character style to the selection.
+
+use crate::{Command, CommandResult, EditError, MacroStep, Result};
+use doc_model::{
+    CharacterProperties, DocumentTree, Node, NodeId, NodeType, Paragraph, ParagraphProperties,
+    Position, Run, Selection, StyleId, StyleType,
+};
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+/// Resolve a position to (paragraph ID, character offset in the paragraph)
+fn resolve_position(tree: &DocumentTree, position: &Position) -> Result<(NodeId, usize)> {
+    match tree.node_type(position.node_id) {
+        Some(NodeType::Paragraph) => Ok((position.node_id, position.offset)),
+        Some(NodeType::Run) => {
+            let para_id = tree
+                .get_run(position.node_id)
+                .and_then(|run| run.parent())
+                .filter(|id| tree.get_paragraph(*id).is_some())
+                .ok_or_else(|| EditError::InvalidCommand("Run has no paragraph".to_string()))?;
+            let before: usize = run_ids(tree, para_id)
+                .into_iter()
+                .take_while(|&id| id != position.node_id)
+                .filter_map(|id| tree.get_run(id))
+                .map(|run| run.text.chars().count())
+                .sum();
+            Ok((para_id, before + position.offset))
+        }
+        _ => Err(EditError::InvalidCommand(format!(
+            "Invalid position node: {:?}",
+            position.node_id
+        ))),
+    }
+}
+
+/// Direct run children of a paragraph
+pub(crate) fn run_ids(tree: &DocumentTree, para_id: NodeId) -> Vec<NodeId> {
+    tree.get_paragraph(para_id)
+        .map(|para| {
+            para.children()
+                .iter()
+                .copied()
+                .filter(|&id| tree.get_run(id).is_some())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn paragraph_text(tree: &DocumentTree, para_id: NodeId) -> String {
+    run_ids(tree, para_id)
+        .into_iter()
+        .filter_map(|id| tree.get_run(id))
+        .map(|run| run.text.as_str())
+        .collect()
+}
+
+/// Order the selection endpoints in document order
+pub(crate) fn ordered_range(
+    tree: &DocumentTree,
+    selection: &Selection,
+) -> Result<((NodeId, usize), (NodeId, usize))> {
+    let anchor = resolve_position(tree, &selection.anchor)?;
+    let focus = resolve_position(tree, &selection.focus)?;
+    if anchor.0 == focus.0 {
+        return Ok(if anchor.1 <= focus.1 { (anchor, focus) } else { (focus, anchor) });
+    }
+
+    let index_of = |para_id| tree.paragraphs().position(|p| p.id() == para_id);
+    match (index_of(anchor.0), index_of(focus.0)) {
+        (Some(a), Some(f)) if f < a => Ok((focus, anchor)),
+        (Some(_), Some(_)) => Ok((anchor, focus)),
+        _ => Err(EditError::InvalidCommand(
+            "Selection spans paragraphs outside the document body".to_string(),
+        )),
+    }
+}
+
+/// Character range of the word around an offset
+pub(crate) fn word_around(text: &str, offset: usize) -> (usize, usize) {
+    let chars: Vec<char> = text.chars().collect();
+    let is_word = |i: usize| chars.get(i).is_some_and(|c| c.is_alphanumeric() || *c == '_' || *c == '\'');
+    let mut start = offset.min(chars.len());
+    let mut end = start;
+    while start > 0 && is_word(start - 1) {
+        start -= 1;
+    }
+    while is_word(end) {
+        end += 1;
+    }
+    (start, end)
+}
+
+/// Format `start..end` of a paragraph with `apply`, splitting runs at the edges
+pub(crate) fn format_runs(
+    tree: &mut DocumentTree,
+    para_id: NodeId,
+    start: usize,
+    end: usize,
+    apply: impl Fn(&mut Run),
+) -> Result<()> {
+    let runs = run_ids(tree, para_id);
+    let mut offsets = Vec::with_capacity(runs.len());
+    let mut run_start = 0;
+    for &run_id in &runs {
+        offsets.push(run_start);
+        run_start += tree.get_run(run_id).map_or(0, |r| r.text.chars().count());
+    }
+
+    // Work backwards so child indices stay valid as runs are split
+    for (&run_id, &run_start) in runs.iter().zip(&offsets).rev() {
+        let Some(run) = tree.get_run(run_id) else {
+            continue;
+        };
+        let len = run.text.chars().count();
+        let from = start.max(run_start).min(run_start + len) - run_start;
+        let to = end.min(run_start + len).max(run_start) - run_start;
+        if from >= to {
+            continue;
+        }
+
+        let original = run.clone();
+        let piece = |from: usize, to: usize| -> String {
+            original.text.chars().skip(from).take(to - from).collect()
+        };
+        let index = tree
+            .get_paragraph(para_id)
+            .and_then(|p| p.children().iter().position(|&id| id == run_id))
+            .unwrap_or(0);
+
+        if let Some(run) = tree.get_run_mut(run_id) {
+            run.text = piece(from, to);
+            apply(run);
+        }
+        if to < len {
+            let mut tail = Run::with_style(piece(to, len), original.style.clone());
+            tail.character_style_id = original.character_style_id.clone();
+            tail.direct_formatting = original.direct_formatting.clone();
+            tree.insert_run(tail, para_id, Some(index + 1))?;
+        }
+        if from > 0 {
+            let mut head = Run::with_style(piece(0, from), original.style.clone());
+            head.character_style_id = original.character_style_id.clone();
+            head.direct_formatting = original.direct_formatting.clone();
+            tree.insert_run(head, para_id, Some(index))?;
+        }
+    }
+    Ok(())
+}
+
+/// Paragraphs from `start_para` to `end_para` in document order
+fn paragraphs_between(tree: &DocumentTree, start_para: NodeId, end_para: NodeId) -> Vec<NodeId> {
+    if start_para == end_para {
+        return vec![start_para];
+    }
+    tree.paragraphs()
+        .map(|p| p.id())
+        .skip_while(|&id| id != start_para)
+        .take_while(|&id| id != end_para)
+        .chain(std::iter::once(end_para))
+        .collect()
+}
+
+// =============================================================================
+// Copied Formatting
+// =============================================================================
+
+/// Formatting captured by the format painter
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CopiedFormatting {
+    /// Resolved character properties at the source position
+    pub character: CharacterProperties,
+    /// Character style at the source position
+    pub character_style_id: Option<StyleId>,
+    /// Direct paragraph properties (including list properties and level)
+    pub paragraph: ParagraphProperties,
+    /// Paragraph style of the source paragraph
+    pub paragraph_style_id: Option<StyleId>,
+}
+
+impl CopiedFormatting {
+    /// Capture the formatting at a position
+    ///
+    /// The character formatting is taken from the run containing the character
+    /// before the position (or the first run at the start of a paragraph).
+    pub fn capture(tree: &DocumentTree, position: &Position) -> Result<Self> {
+        let (para_id, offset) = resolve_position(tree, position)?;
+        let para = tree
+            .get_paragraph(para_id)
+            .ok_or_else(|| EditError::InvalidCommand("Paragraph not found".to_string()))?;
+
+        let mut source_run = None;
+        let mut run_start = 0;
+        for run_id in run_ids(tree, para_id) {
+            let len = tree.get_run(run_id).map_or(0, |r| r.text.chars().count());
+            if source_run.is_none() || run_start < offset {
+                source_run = Some(run_id);
+            }
+            run_start += len;
+            if run_start >= offset {
+                break;
+            }
+        }
+
+        let (character, character_style_id) = match source_run.and_then(|id| tree.get_run(id).map(|r| (id, r))) {
+            Some((run_id, run)) => (
+                tree.compute_character_properties(run_id)
+                    .unwrap_or_else(|| run.direct_formatting.clone()),
+                run.character_style_id.clone(),
+            ),
+            None => (CharacterProperties::default(), None),
+        };
+
+        Ok(Self {
+            character,
+            character_style_id,
+            paragraph: para.direct_formatting.clone(),
+            paragraph_style_id: para.paragraph_style_id.clone(),
+        })
+    }
+
+    fn apply_to_run(&self, run: &mut Run) {
+        run.direct_formatting = self.character.clone();
+        run.character_style_id = self.character_style_id.clone();
+        run.style = Default::default();
+    }
+
+    fn apply_to_paragraph(&self, para: &mut Paragraph) {
+        para.direct_formatting = self.paragraph.clone();
+        para.paragraph_style_id = self.paragraph_style_id.clone();
+        para.style.alignment = self.paragraph.alignment;
+    }
+}
+
+/// Active format painter state held by the editing engine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormatPainter {
+    /// The captured formatting
+    pub formatting: CopiedFormatting,
+    /// Stay active after applying (double-click on the toolbar button)
+    pub sticky: bool,
+}
+
+// =============================================================================
+// Copy Formatting
+// =============================================================================
+
+/// Capture the formatting at the selection focus
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CopyFormatting {
+    /// Keep the painter active for multiple applications
+    pub sticky: bool,
+}
+
+impl CopyFormatting {
+    /// Copy formatting for a single application
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copy formatting for repeated applications
+    pub fn sticky() -> Self {
+        Self { sticky: true }
+    }
+
+    /// Capture the formatting at the start of the selection
+    pub fn capture(&self, tree: &DocumentTree, selection: &Selection) -> Result<FormatPainter> {
+        let (start, _) = ordered_range(tree, selection)?;
+        let formatting = CopiedFormatting::capture(tree, &Position::new(start.0, start.1))?;
+        Ok(FormatPainter {
+            formatting,
+            sticky: self.sticky,
+        })
+    }
+}
+
+// =============================================================================
+// Apply Copied Formatting Command
+// =============================================================================
+
+/// Apply captured formatting to the selection
+///
+/// Character formatting is applied to the selected text (or the word at the
+/// cursor when the selection is collapsed); paragraph formatting is applied to
+/// every paragraph the selection touches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplyCopiedFormatting {
+    /// The formatting to apply
+    pub formatting: CopiedFormatting,
+}
+
+impl ApplyCopiedFormatting {
+    pub fn new(formatting: CopiedFormatting) -> Self {
+        Self { formatting }
+    }
+}
+
+impl Command for ApplyCopiedFormatting {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+        let ((start_para, mut start), (end_para, mut end)) = ordered_range(tree, selection)?;
+
+        let paragraphs = paragraphs_between(tree, start_para, end_para);
+
+        let inverse = Box::new(RestoreParagraphs::snapshot(tree, &paragraphs));
+
+        if selection.is_collapsed() {
+            (start, end) = word_around(&paragraph_text(tree, start_para), start);
+        }
+
+        for &para_id in &paragraphs {
+            let from = if para_id == start_para { start } else { 0 };
+            let to = if para_id == end_para {
+                end
+            } else {
+                paragraph_text(&new_tree, para_id).chars().count()
+            };
+            format_runs(&mut new_tree, para_id, from, to, |run| self.formatting.apply_to_run(run))?;
+            if let Some(para) = new_tree.get_paragraph_mut(para_id) {
+                self.formatting.apply_to_paragraph(para);
+            }
+        }
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse,
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        // Proper inverse created in apply()
+        Box::new(self.clone())
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn macro_step(&self) -> Option<MacroStep> {
+        MacroStep::command("applyCopiedFormatting", self)
+    }
+
+    fn display_name(&self) -> &str {
+        "Format Painter"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+/// Restore paragraphs and their runs from a snapshot (for undo)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct RestoreParagraphs {
+    paragraphs: Vec<(Paragraph, Vec<Run>)>,
+}
+
+impl RestoreParagraphs {
+    pub(crate) fn snapshot(tree: &DocumentTree, paragraphs: &[NodeId]) -> Self {
+        Self {
+            paragraphs: paragraphs
+                .iter()
+                .filter_map(|&para_id| {
+                    let para = tree.get_paragraph(para_id)?.clone();
+                    let runs = run_ids(tree, para_id)
+                        .into_iter()
+                        .filter_map(|id| tree.get_run(id).cloned())
+                        .collect();
+                    Some((para, runs))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Command for RestoreParagraphs {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let para_ids: Vec<NodeId> = self.paragraphs.iter().map(|(p, _)| p.id()).collect();
+        let inverse = Box::new(RestoreParagraphs::snapshot(tree, &para_ids));
+
+        let mut new_tree = tree.clone();
+        for (para, runs) in &self.paragraphs {
+            for run_id in run_ids(&new_tree, para.id()) {
+                new_tree.nodes.runs.remove(&run_id);
+            }
+            for run in runs {
+                new_tree.nodes.runs.insert(run.id(), run.clone());
+            }
+            new_tree.nodes.paragraphs.insert(para.id(), para.clone());
+        }
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse,
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Restore Formatting"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+// =============================================================================
+// Apply Style
+// =============================================================================
+
+/// Apply a named style to the selection
+///
+/// A paragraph style is set on every paragraph the selection touches; a
+/// character style is set on the selected text (or the word at the cursor
+/// when the selection is collapsed).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplyStyle {
+    /// The style to apply
+    pub style_id: StyleId,
+}
+
+impl ApplyStyle {
+    pub fn new(style_id: impl Into<StyleId>) -> Self {
+        Self {
+            style_id: style_id.into(),
+        }
+    }
+}
+
+impl Command for ApplyStyle {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let style = tree.styles.get(&self.style_id).ok_or_else(|| {
+            EditError::InvalidCommand(format!("Style not found: {}", self.style_id.as_str()))
+        })?;
+        let style_type = style.style_type;
+
+        let ((start_para, mut start), (end_para, mut end)) = ordered_range(tree, selection)?;
+        let paragraphs = paragraphs_between(tree, start_para, end_para);
+        let inverse = Box::new(RestoreParagraphs::snapshot(tree, &paragraphs));
+        let mut new_tree = tree.clone();
+
+        match style_type {
+            StyleType::Paragraph => {
+                for &para_id in &paragraphs {
+                    if let Some(para) = new_tree.get_paragraph_mut(para_id) {
+                        para.paragraph_style_id = Some(self.style_id.clone());
+                    }
+                }
+            }
+            StyleType::Character => {
+                if selection.is_collapsed() {
+                    (start, end) = word_around(&paragraph_text(tree, start_para), start);
+                }
+                for &para_id in &paragraphs {
+                    let from = if para_id == start_para { start } else { 0 };
+                    let to = if para_id == end_para {
+                        end
+                    } else {
+                        paragraph_text(&new_tree, para_id).chars().count()
+                    };
+                    format_runs(&mut new_tree, para_id, from, to, |run| {
+                        run.character_style_id = Some(self.style_id.clone());
+                    })?;
+                }
+            }
+            StyleType::Table | StyleType::Numbering => {
+                return Err(EditError::InvalidCommand(format!(
+                    "Style {} cannot be applied to text",
+                    self.style_id.as_str()
+                )));
+            }
+        }
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse,
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        // Proper inverse created in apply()
+        Box::new(self.clone())
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn macro_step(&self) -> Option<MacroStep> {
+        MacroStep::command("applyStyle", self)
+    }
+
+    fn display_name(&self) -> &str {
+        "Apply Style"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EditingEngine;
+    use doc_model::{Alignment, ListProperties, NumId};
+
+    fn create_test_tree() -> (DocumentTree, NodeId, NodeId) {
+        let mut tree = DocumentTree::new();
+
+        let mut source = Paragraph::new();
+        source.direct_formatting.alignment = Some(Alignment::Center);
+        source.direct_formatting.list_props = Some(ListProperties::new(NumId::new(1), 2));
+        let source_id = tree.insert_paragraph(source, tree.root_id(), None).unwrap();
+        let mut bold = CharacterProperties::new();
+        bold.bold = Some(true);
+        bold.color = Some("#FF0000".to_string());
+        tree.insert_run(Run::new("Plain "), source_id, None).unwrap();
+        tree.insert_run(Run::with_direct_formatting("Bold", bold), source_id, None).unwrap();
+
+        let target_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new("Hello brave world"), target_id, None).unwrap();
+
+        (tree, source_id, target_id)
+    }
+
+    fn runs(tree: &DocumentTree, para_id: NodeId) -> Vec<&Run> {
+        run_ids(tree, para_id).into_iter().filter_map(|id| tree.get_run(id)).collect()
+    }
+
+    #[test]
+    fn test_capture_formatting() {
+        let (tree, source_id, _) = create_test_tree();
+
+        let in_bold = CopiedFormatting::capture(&tree, &Position::new(source_id, 8)).unwrap();
+        assert_eq!(in_bold.character.bold, Some(true));
+        assert_eq!(in_bold.paragraph.alignment, Some(Alignment::Center));
+        assert_eq!(in_bold.paragraph.list_props.as_ref().unwrap().ilvl, Some(2));
+
+        let in_plain = CopiedFormatting::capture(&tree, &Position::new(source_id, 2)).unwrap();
+        assert_ne!(in_plain.character.bold, Some(true));
+    }
+
+    #[test]
+    fn test_apply_to_range_splits_runs() {
+        let (tree, source_id, target_id) = create_test_tree();
+        let formatting = CopiedFormatting::capture(&tree, &Position::new(source_id, 8)).unwrap();
+
+        let selection = Selection::new(Position::new(target_id, 6), Position::new(target_id, 11));
+        let result = ApplyCopiedFormatting::new(formatting).apply(&tree, &selection).unwrap();
+
+        let target = runs(&result.tree, target_id);
+        let texts: Vec<&str> = target.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(texts, vec!["Hello ", "brave", " world"]);
+        assert_eq!(target[1].direct_formatting.bold, Some(true));
+        assert_ne!(target[0].direct_formatting.bold, Some(true));
+
+        let para = result.tree.get_paragraph(target_id).unwrap();
+        assert_eq!(para.direct_formatting.alignment, Some(Alignment::Center));
+        assert!(para.direct_formatting.list_props.is_some());
+
+        // Undo restores the original run and paragraph formatting
+        let undone = result.inverse.apply(&result.tree, &selection).unwrap();
+        let target = runs(&undone.tree, target_id);
+        assert_eq!(target.len(), 1);
+        assert_eq!(target[0].text, "Hello brave world");
+        assert!(undone.tree.get_paragraph(target_id).unwrap().direct_formatting.alignment.is_none());
+    }
+
+    #[test]
+    fn test_collapsed_selection_formats_word() {
+        let (tree, source_id, target_id) = create_test_tree();
+        let formatting = CopiedFormatting::capture(&tree, &Position::new(source_id, 8)).unwrap();
+
+        let selection = Selection::collapsed(Position::new(target_id, 13));
+        let result = ApplyCopiedFormatting::new(formatting).apply(&tree, &selection).unwrap();
+        let target = runs(&result.tree, target_id);
+        assert_eq!(target.last().unwrap().text, "world");
+        assert_eq!(target.last().unwrap().direct_formatting.bold, Some(true));
+    }
+
+    #[test]
+    fn test_engine_sticky_format_painter() {
+        let (tree, source_id, target_id) = create_test_tree();
+        let mut engine = EditingEngine::with_tree(tree);
+
+        // Nothing copied yet
+        assert!(engine.apply_copied_formatting().is_err());
+
+        engine.set_selection(Selection::collapsed(Position::new(source_id, 8)));
+        engine.copy_formatting(CopyFormatting::sticky()).unwrap();
+
+        engine.set_selection(Selection::new(Position::new(target_id, 0), Position::new(target_id, 5)));
+        engine.apply_copied_formatting().unwrap();
+        assert!(engine.format_painter().is_some());
+
+        engine.set_selection(Selection::new(Position::new(target_id, 12), Position::new(target_id, 17)));
+        engine.apply_copied_formatting().unwrap();
+        let bold: Vec<&str> = runs(engine.tree(), target_id)
+            .into_iter()
+            .filter(|r| r.direct_formatting.bold == Some(true))
+            .map(|r| r.text.as_str())
+            .collect();
+        assert_eq!(bold, vec!["Hello", "world"]);
+
+        engine.cancel_format_painter();
+        assert!(engine.format_painter().is_none());
+
+        // A non-sticky painter is consumed by one application
+        engine.set_selection(Selection::collapsed(Position::new(source_id, 2)));
+        engine.copy_formatting(CopyFormatting::new()).unwrap();
+        engine.apply_copied_formatting().unwrap();
+        assert!(engine.format_painter().is_none());
+        assert!(engine.can_undo());
+    }
+
+    #[test]
+    fn test_apply_paragraph_and_character_styles() {
+        use doc_model::Style;
+
+        let (mut tree, source_id, target_id) = create_test_tree();
+        tree.styles.register(Style::paragraph("Quote", "Quote"));
+        tree.styles.register(Style::character("Emphasis", "Emphasis"));
+        let mut engine = EditingEngine::with_tree(tree);
+
+        engine.set_selection(Selection::new(Position::new(source_id, 2), Position::new(target_id, 3)));
+        engine.execute(Box::new(ApplyStyle::new("Quote"))).unwrap();
+        for id in [source_id, target_id] {
+            let para = engine.tree().get_paragraph(id).unwrap();
+            assert_eq!(para.paragraph_style_id, Some(StyleId::new("Quote")));
+        }
+
+        engine.set_selection(Selection::collapsed(Position::new(target_id, 8)));
+        engine.execute(Box::new(ApplyStyle::new("Emphasis"))).unwrap();
+        let styled: Vec<&str> = runs(engine.tree(), target_id)
+            .into_iter()
+            .filter(|r| r.character_style_id == Some(StyleId::new("Emphasis")))
+            .map(|r| r.text.as_str())
+            .collect();
+        assert_eq!(styled, vec!["brave"]);
+
+        engine.undo().unwrap();
+        assert_eq!(runs(engine.tree(), target_id).len(), 1);
+        assert!(engine.execute(Box::new(ApplyStyle::new("Missing"))).is_err());
+    }
+}
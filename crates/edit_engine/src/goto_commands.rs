@@ -0,0 +1,504 @@
+//! Go To commands
+//!
+//! Backend for the Go To dialog. Jumps to:
+//! - A page or section by number (requires layout information)
+//! - A bookmark by name
+//! - The next or previous table, footnote, endnote, comment, or field
+//!
+//! Next/previous targets are searched from the current focus and wrap around
+//! the document.
+
+use crate::{Command, CommandResult, EditError, Result};
+use doc_model::{DocumentTree, FieldRegistry, Node, NodeId, Position, Selection};
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// Targets
+// =============================================================================
+
+/// Direction to search for the next target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GoToDirection {
+    Next,
+    Previous,
+}
+
+/// What to go to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum GoToTarget {
+    /// Page number (1-based)
+    Page(usize),
+    /// Section number (1-based)
+    Section(usize),
+    /// Next or previous table
+    Table(GoToDirection),
+    /// Next or previous footnote reference
+    Footnote(GoToDirection),
+    /// Next or previous endnote reference
+    Endnote(GoToDirection),
+    /// Next or previous comment
+    Comment(GoToDirection),
+    /// Next or previous field
+    Field(GoToDirection),
+    /// Bookmark by name
+    Bookmark(String),
+}
+
+impl GoToTarget {
+    /// Label for the target kind (as shown in the Go To dialog)
+    pub fn label(&self) -> &'static str {
+        match self {
+            GoToTarget::Page(_) => "Page",
+            GoToTarget::Section(_) => "Section",
+            GoToTarget::Table(_) => "Table",
+            GoToTarget::Footnote(_) => "Footnote",
+            GoToTarget::Endnote(_) => "Endnote",
+            GoToTarget::Comment(_) => "Comment",
+            GoToTarget::Field(_) => "Field",
+            GoToTarget::Bookmark(_) => "Bookmark",
+        }
+    }
+}
+
+/// Layout information needed to resolve page and section targets
+pub trait PageLocator {
+    /// Position at the start of a page (0-based)
+    fn page_start(&self, page_index: usize) -> Option<Position>;
+
+    /// Position at the start of a section (0-based)
+    fn section_start(&self, section_index: usize) -> Option<Position>;
+}
+
+// =============================================================================
+// Go To Navigator
+// =============================================================================
+
+/// Document order key: (body index, paragraph index within a table, offset)
+type OrderKey = (usize, usize, usize);
+
+/// Resolves Go To targets to selections
+pub struct GoToNavigator<'a> {
+    tree: &'a DocumentTree,
+    layout: Option<&'a dyn PageLocator>,
+    fields: Option<&'a FieldRegistry>,
+}
+
+impl<'a> GoToNavigator<'a> {
+    /// Create a navigator for the given document tree
+    pub fn new(tree: &'a DocumentTree) -> Self {
+        Self {
+            tree,
+            layout: None,
+            fields: None,
+        }
+    }
+
+    /// Use layout information for page and section targets
+    pub fn with_layout(mut self, layout: &'a dyn PageLocator) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    /// Use a field registry for field targets
+    pub fn with_fields(mut self, fields: &'a FieldRegistry) -> Self {
+        self.fields = Some(fields);
+        self
+    }
+
+    /// Resolve a target relative to the current selection
+    pub fn go_to(&self, selection: &Selection, target: &GoToTarget) -> Result<Selection> {
+        let found = match target {
+            GoToTarget::Page(number) => self.numbered(*number, |layout, index| layout.page_start(index)),
+            GoToTarget::Section(number) => {
+                self.numbered(*number, |layout, index| layout.section_start(index))
+            }
+            GoToTarget::Bookmark(name) => self.tree.selection_for_bookmark(name),
+            GoToTarget::Table(direction) => self.adjacent(selection, *direction, self.table_targets()),
+            GoToTarget::Footnote(direction) => {
+                let notes = self.tree.notes.footnotes().filter_map(|n| n.reference_position);
+                self.adjacent(selection, *direction, notes.map(Selection::collapsed).collect())
+            }
+            GoToTarget::Endnote(direction) => {
+                let notes = self.tree.notes.endnotes().filter_map(|n| n.reference_position);
+                self.adjacent(selection, *direction, notes.map(Selection::collapsed).collect())
+            }
+            GoToTarget::Comment(direction) => {
+                let comments = self
+                    .tree
+                    .all_comments()
+                    .map(|c| Selection::new(c.anchor().start, c.anchor().end));
+                self.adjacent(selection, *direction, comments.collect())
+            }
+            GoToTarget::Field(direction) => self.adjacent(selection, *direction, self.field_targets()),
+        };
+
+        found.ok_or_else(|| EditError::InvalidCommand(Self::not_found(target)))
+    }
+
+    fn not_found(target: &GoToTarget) -> String {
+        match target {
+            GoToTarget::Page(n) | GoToTarget::Section(n) => {
+                format!("{} {} not found", target.label(), n)
+            }
+            GoToTarget::Bookmark(name) => format!("Bookmark '{}' not found", name),
+            _ => format!("No {} in document", target.label().to_lowercase()),
+        }
+    }
+
+    /// Resolve a 1-based page or section number
+    fn numbered(
+        &self,
+        number: usize,
+        locate: impl Fn(&dyn PageLocator, usize) -> Option<Position>,
+    ) -> Option<Selection> {
+        let index = number.checked_sub(1)?;
+        match self.layout {
+            Some(layout) => locate(layout, index).map(Selection::collapsed),
+            // Without layout the whole document is a single page and section
+            None if index == 0 => self.document_start().map(Selection::collapsed),
+            None => None,
+        }
+    }
+
+    fn document_start(&self) -> Option<Position> {
+        let first = *self.tree.document.children().first()?;
+        self.first_paragraph(first).map(|para_id| Position::new(para_id, 0))
+    }
+
+    /// First paragraph in a block (descending into tables)
+    fn first_paragraph(&self, block_id: NodeId) -> Option<NodeId> {
+        if self.tree.get_paragraph(block_id).is_some() {
+            return Some(block_id);
+        }
+        self.table_paragraphs(block_id).into_iter().next()
+    }
+
+    /// Paragraphs in a table in reading order
+    fn table_paragraphs(&self, table_id: NodeId) -> Vec<NodeId> {
+        let Some(table) = self.tree.get_table(table_id) else {
+            return Vec::new();
+        };
+        table
+            .children()
+            .iter()
+            .filter_map(|&row_id| self.tree.get_table_row(row_id))
+            .flat_map(|row| row.children())
+            .filter_map(|&cell_id| self.tree.get_table_cell(cell_id))
+            .flat_map(|cell| cell.children().iter().copied())
+            .collect()
+    }
+
+    fn table_targets(&self) -> Vec<Selection> {
+        self.tree
+            .tables()
+            .filter_map(|table| self.first_paragraph(table.id()))
+            .map(|para_id| Selection::collapsed(Position::new(para_id, 0)))
+            .collect()
+    }
+
+    fn field_targets(&self) -> Vec<Selection> {
+        let Some(fields) = self.fields else {
+            return Vec::new();
+        };
+        fields
+            .all()
+            .filter_map(|field| {
+                let para_id = field.parent()?;
+                let para = self.tree.get_paragraph(para_id)?;
+                // Fields placed inline sit after the runs that precede them
+                let offset = match para.children().iter().position(|&id| id == field.id()) {
+                    Some(index) => para.children()[..index]
+                        .iter()
+                        .filter_map(|&id| self.tree.get_run(id))
+                        .map(|run| run.text.chars().count())
+                        .sum(),
+                    None => 0,
+                };
+                Some(Selection::collapsed(Position::new(para_id, offset)))
+            })
+            .collect()
+    }
+
+    /// Document order of a position
+    fn order_key(&self, pos: &Position) -> Option<OrderKey> {
+        let body = self.tree.document.children();
+        if let Some(index) = body.iter().position(|&id| id == pos.node_id) {
+            return Some((index, 0, pos.offset));
+        }
+
+        let table_id = self.tree.find_table_for_node(pos.node_id)?;
+        let index = body.iter().position(|&id| id == table_id)?;
+        let sub = self
+            .table_paragraphs(table_id)
+            .iter()
+            .position(|&id| id == pos.node_id)
+            .unwrap_or(0);
+        Some((index, sub, pos.offset))
+    }
+
+    /// Find the nearest target after (or before) the focus, wrapping around
+    fn adjacent(
+        &self,
+        selection: &Selection,
+        direction: GoToDirection,
+        targets: Vec<Selection>,
+    ) -> Option<Selection> {
+        let mut keyed: Vec<(OrderKey, Selection)> = targets
+            .into_iter()
+            .filter_map(|target| Some((self.order_key(&target.start())?, target)))
+            .collect();
+        keyed.sort_by_key(|(key, _)| *key);
+
+        let current = self.order_key(&selection.focus).unwrap_or_default();
+        let found = match direction {
+            GoToDirection::Next => keyed
+                .iter()
+                .find(|(key, _)| *key > current)
+                .or_else(|| keyed.first()),
+            GoToDirection::Previous => keyed
+                .iter()
+                .rev()
+                .find(|(key, _)| *key < current)
+                .or_else(|| keyed.last()),
+        };
+        found.map(|(_, target)| *target)
+    }
+}
+
+// =============================================================================
+// Go To Command
+// =============================================================================
+
+/// Go to a target (updates selection)
+///
+/// Runs without layout information, so only page 1 and section 1 resolve;
+/// use `GoToNavigator::with_layout` for other page and section numbers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoTo {
+    /// Where to go
+    pub target: GoToTarget,
+}
+
+impl GoTo {
+    /// Create a new go to command
+    pub fn new(target: GoToTarget) -> Self {
+        Self { target }
+    }
+}
+
+impl Command for GoTo {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let new_selection = GoToNavigator::new(tree).go_to(selection, &self.target)?;
+
+        Ok(CommandResult {
+            tree: tree.clone(),
+            selection: new_selection,
+            inverse: Box::new(RestoreSelection {
+                selection: *selection,
+            }),
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+
+    fn transform_selection(&self, _selection: &Selection) -> Selection {
+        Selection::default()
+    }
+
+    fn display_name(&self) -> &str {
+        "Go To"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+/// Internal command to restore a selection (used for undo of GoTo)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RestoreSelection {
+    selection: Selection,
+}
+
+impl Command for RestoreSelection {
+    fn apply(&self, tree: &DocumentTree, _selection: &Selection) -> Result<CommandResult> {
+        Ok(CommandResult {
+            tree: tree.clone(),
+            selection: self.selection,
+            inverse: Box::new(self.clone()),
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+
+    fn transform_selection(&self, _selection: &Selection) -> Selection {
+        self.selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Restore Selection"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::{Field, Paragraph, Run, Table, TableCell, TableGrid, TableRow};
+
+    fn add_paragraph(tree: &mut DocumentTree, text: &str) -> NodeId {
+        let para = Paragraph::new();
+        let para_id = para.id();
+        tree.insert_paragraph(para, tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new(text), para_id, None).unwrap();
+        para_id
+    }
+
+    fn add_table(tree: &mut DocumentTree) -> NodeId {
+        let table = Table::with_grid(TableGrid::with_equal_columns(1, 200.0));
+        let table_id = tree.insert_table(table, None).unwrap();
+        let row_id = tree.insert_table_row(TableRow::new(), table_id, None).unwrap();
+        let cell_id = tree.insert_table_cell(TableCell::new(), row_id, None).unwrap();
+        tree.insert_paragraph_into_cell(Paragraph::new(), cell_id, None).unwrap()
+    }
+
+    /// Body: p0, table A, p1, table B, p2
+    fn create_test_tree() -> (DocumentTree, Vec<NodeId>, Vec<NodeId>) {
+        let mut tree = DocumentTree::new();
+        let p0 = add_paragraph(&mut tree, "First paragraph");
+        let a = add_table(&mut tree);
+        let p1 = add_paragraph(&mut tree, "Second paragraph");
+        let b = add_table(&mut tree);
+        let p2 = add_paragraph(&mut tree, "Third paragraph");
+        (tree, vec![p0, p1, p2], vec![a, b])
+    }
+
+    struct TestLayout(Vec<Position>);
+
+    impl PageLocator for TestLayout {
+        fn page_start(&self, page_index: usize) -> Option<Position> {
+            self.0.get(page_index).copied()
+        }
+
+        fn section_start(&self, section_index: usize) -> Option<Position> {
+            (section_index == 0).then(|| self.0[0])
+        }
+    }
+
+    #[test]
+    fn test_go_to_next_and_previous_table() {
+        let (tree, paras, tables) = create_test_tree();
+        let nav = GoToNavigator::new(&tree);
+        let at_p1 = Selection::collapsed(Position::new(paras[1], 3));
+
+        let next = nav.go_to(&at_p1, &GoToTarget::Table(GoToDirection::Next)).unwrap();
+        assert_eq!(next.focus.node_id, tables[1]);
+
+        let prev = nav.go_to(&at_p1, &GoToTarget::Table(GoToDirection::Previous)).unwrap();
+        assert_eq!(prev.focus.node_id, tables[0]);
+
+        // Wraps around past the last table
+        let at_p2 = Selection::collapsed(Position::new(paras[2], 0));
+        let wrapped = nav.go_to(&at_p2, &GoToTarget::Table(GoToDirection::Next)).unwrap();
+        assert_eq!(wrapped.focus.node_id, tables[0]);
+    }
+
+    #[test]
+    fn test_go_to_footnote_and_comment() {
+        let (mut tree, paras, _) = create_test_tree();
+        tree.insert_footnote(Position::new(paras[2], 5), None);
+        tree.insert_footnote(Position::new(paras[0], 5), None);
+        tree.add_comment(
+            Position::new(paras[1], 0),
+            Position::new(paras[1], 6),
+            "Ana",
+            "Check this",
+        )
+        .unwrap();
+
+        let nav = GoToNavigator::new(&tree);
+        let start = Selection::collapsed(Position::new(paras[0], 0));
+
+        let note = nav.go_to(&start, &GoToTarget::Footnote(GoToDirection::Next)).unwrap();
+        assert_eq!(note.focus, Position::new(paras[0], 5));
+        let note = nav.go_to(&note, &GoToTarget::Footnote(GoToDirection::Next)).unwrap();
+        assert_eq!(note.focus, Position::new(paras[2], 5));
+
+        let comment = nav.go_to(&start, &GoToTarget::Comment(GoToDirection::Next)).unwrap();
+        assert_eq!(comment.anchor, Position::new(paras[1], 0));
+        assert_eq!(comment.focus, Position::new(paras[1], 6));
+
+        assert!(nav.go_to(&start, &GoToTarget::Endnote(GoToDirection::Next)).is_err());
+    }
+
+    #[test]
+    fn test_go_to_field() {
+        let (tree, paras, _) = create_test_tree();
+        let mut fields = FieldRegistry::new();
+        let mut field = Field::page();
+        field.set_parent(Some(paras[1]));
+        fields.insert(field);
+
+        let start = Selection::collapsed(Position::new(paras[0], 0));
+        assert!(GoToNavigator::new(&tree)
+            .go_to(&start, &GoToTarget::Field(GoToDirection::Next))
+            .is_err());
+
+        let found = GoToNavigator::new(&tree)
+            .with_fields(&fields)
+            .go_to(&start, &GoToTarget::Field(GoToDirection::Previous))
+            .unwrap();
+        assert_eq!(found.focus, Position::new(paras[1], 0));
+    }
+
+    #[test]
+    fn test_go_to_page_and_section() {
+        let (tree, paras, _) = create_test_tree();
+        let selection = Selection::default();
+
+        // Without layout only the first page resolves
+        let nav = GoToNavigator::new(&tree);
+        let first = nav.go_to(&selection, &GoToTarget::Page(1)).unwrap();
+        assert_eq!(first.focus, Position::new(paras[0], 0));
+        assert!(nav.go_to(&selection, &GoToTarget::Page(2)).is_err());
+        assert!(nav.go_to(&selection, &GoToTarget::Page(0)).is_err());
+
+        let layout = TestLayout(vec![Position::new(paras[0], 0), Position::new(paras[2], 4)]);
+        let nav = GoToNavigator::new(&tree).with_layout(&layout);
+        let page = nav.go_to(&selection, &GoToTarget::Page(2)).unwrap();
+        assert_eq!(page.focus, Position::new(paras[2], 4));
+        assert!(nav.go_to(&selection, &GoToTarget::Section(1)).is_ok());
+        assert!(nav.go_to(&selection, &GoToTarget::Section(2)).is_err());
+    }
+
+    #[test]
+    fn test_go_to_command_bookmark() {
+        let (mut tree, paras, _) = create_test_tree();
+        tree.insert_point_bookmark("target", Position::new(paras[1], 7)).unwrap();
+        let selection = Selection::collapsed(Position::new(paras[0], 0));
+
+        let cmd = GoTo::new(GoToTarget::Bookmark("target".to_string()));
+        let result = cmd.apply(&tree, &selection).unwrap();
+        assert_eq!(result.selection.focus, Position::new(paras[1], 7));
+
+        // Undo restores the original selection
+        let undone = result.inverse.apply(&result.tree, &result.selection).unwrap();
+        assert_eq!(undone.selection.focus, selection.focus);
+
+        let missing = GoTo::new(GoToTarget::Bookmark("missing".to_string()));
+        assert!(missing.apply(&tree, &selection).is_err());
+    }
+}
@@ -0,0 +1,567 @@
+//! Hyperlink management: auto-detection while typing, link audit, and bulk
+//! domain edits
+
+use crate::{Command, CommandResult, EditError, RemoveHyperlink, Result};
+use doc_model::{
+    is_relative_url, resolve_hyperlink, DocumentTree, Hyperlink, HyperlinkTarget, Node, NodeId,
+    Run, Selection,
+};
+use serde::{Deserialize, Serialize};
+
+/// Schemes recognised when typed in full
+const URL_SCHEMES: &[&str] = &["http://", "https://", "ftp://", "file://"];
+
+/// A URL or email address found in typed text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DetectedLink {
+    /// Start character offset of the link text
+    pub start: usize,
+    /// End character offset of the link text (exclusive)
+    pub end: usize,
+    /// The link text as typed
+    pub text: String,
+    /// The target the text links to
+    pub target: HyperlinkTarget,
+}
+
+/// Find a URL or email address ending at a character offset
+///
+/// `end` is where the word just typed ends, normally the caret before the
+/// space or punctuation that triggered auto-formatting. Surrounding quotes
+/// and brackets and trailing sentence punctuation are not part of the link.
+pub fn detect_link(text: &str, end: usize) -> Option<DetectedLink> {
+    let chars: Vec<char> = text.chars().collect();
+    if end > chars.len() {
+        return None;
+    }
+    let mut start = chars[..end]
+        .iter()
+        .rposition(|c| c.is_whitespace())
+        .map_or(0, |i| i + 1);
+    let mut end = end;
+
+    while start < end && matches!(chars[start], '(' | '[' | '<' | '"' | '\'' | '{') {
+        start += 1;
+    }
+    while start < end {
+        let last = chars[end - 1];
+        let word = &chars[start..end];
+        let unbalanced_paren =
+            last == ')' && word.iter().filter(|&&c| c == ')').count() > word.iter().filter(|&&c| c == '(').count();
+        if matches!(last, '.' | ',' | ';' | ':' | '!' | '?' | ']' | '>' | '"' | '\'' | '}') || unbalanced_paren {
+            end -= 1;
+        } else {
+            break;
+        }
+    }
+
+    let word: String = chars[start..end].iter().collect();
+    let target = classify_link(&word)?;
+    Some(DetectedLink {
+        start,
+        end,
+        text: word,
+        target,
+    })
+}
+
+/// Work out what a word links to, if it looks like a link
+fn classify_link(word: &str) -> Option<HyperlinkTarget> {
+    let lower = word.to_lowercase();
+
+    if let Some(scheme) = URL_SCHEMES.iter().find(|scheme| lower.starts_with(*scheme)) {
+        return (word.len() > scheme.len()).then(|| HyperlinkTarget::external(word));
+    }
+    if let Some(rest) = lower.strip_prefix("www.") {
+        return (rest.contains('.') && !rest.starts_with('.'))
+            .then(|| HyperlinkTarget::external(format!("http://{}", word)));
+    }
+    if let Some(share) = word.strip_prefix("\\\\") {
+        return (share.contains('\\') && !share.starts_with('\\')).then(|| HyperlinkTarget::external(word));
+    }
+    if lower.starts_with("mailto:") {
+        let address = &word["mailto:".len()..];
+        return is_email_address(address).then(|| HyperlinkTarget::email(address, None));
+    }
+    is_email_address(word).then(|| HyperlinkTarget::email(word, None))
+}
+
+/// Check if a word is a plain email address
+fn is_email_address(word: &str) -> bool {
+    let Some((local, domain)) = word.split_once('@') else {
+        return false;
+    };
+    let local_ok = !local.is_empty()
+        && local
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '.' | '_' | '%' | '+' | '-'));
+    let domain_ok = domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && domain.chars().all(|c| c.is_alphanumeric() || matches!(c, '.' | '-'));
+    local_ok && domain_ok
+}
+
+/// Turn a URL or email address just typed into a hyperlink
+///
+/// Undoing keeps the text and removes the link, as when the user undoes an
+/// automatic correction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoFormatHyperlink {
+    /// Paragraph being typed in
+    pub paragraph_id: NodeId,
+    /// Character offset where the typed word ends
+    pub offset: usize,
+}
+
+impl AutoFormatHyperlink {
+    pub fn new(paragraph_id: NodeId, offset: usize) -> Self {
+        Self { paragraph_id, offset }
+    }
+
+    /// The link this command would create, if any
+    ///
+    /// Text already inside a hyperlink is never detected again.
+    pub fn detect(&self, tree: &DocumentTree) -> Option<DetectedLink> {
+        let para = tree.get_paragraph(self.paragraph_id)?;
+        let mut text = String::new();
+        let mut linked = Vec::new();
+        for &child_id in para.children() {
+            if let Some(run) = tree.get_run(child_id) {
+                text.push_str(&run.text);
+            } else if let Some(hyperlink) = tree.get_hyperlink(child_id) {
+                let start = text.chars().count();
+                for &run_id in hyperlink.children() {
+                    if let Some(run) = tree.get_run(run_id) {
+                        text.push_str(&run.text);
+                    }
+                }
+                linked.push(start..text.chars().count());
+            }
+        }
+
+        let link = detect_link(&text, self.offset)?;
+        let overlaps_link = linked.iter().any(|range| link.start < range.end && range.start < link.end);
+        (!overlaps_link).then_some(link)
+    }
+}
+
+impl Command for AutoFormatHyperlink {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let link = self
+            .detect(tree)
+            .ok_or_else(|| EditError::InvalidCommand("No link before the cursor".to_string()))?;
+
+        let mut new_tree = tree.clone();
+        let para = new_tree
+            .get_paragraph(self.paragraph_id)
+            .ok_or_else(|| EditError::InvalidCommand(format!("Paragraph not found: {:?}", self.paragraph_id)))?;
+
+        // The link must lie within a single run
+        let mut run_start = 0;
+        let mut found = None;
+        for (index, &child_id) in para.children().iter().enumerate() {
+            let len = if let Some(run) = new_tree.get_run(child_id) {
+                run.text.chars().count()
+            } else if let Some(hyperlink) = new_tree.get_hyperlink(child_id) {
+                hyperlink
+                    .children()
+                    .iter()
+                    .filter_map(|&id| new_tree.get_run(id))
+                    .map(|run| run.text.chars().count())
+                    .sum()
+            } else {
+                0
+            };
+            if new_tree.get_run(child_id).is_some() && run_start <= link.start && link.end <= run_start + len {
+                found = Some((index, child_id, run_start));
+                break;
+            }
+            run_start += len;
+        }
+        let (index, run_id, run_start) = found.ok_or_else(|| {
+            EditError::InvalidCommand("Links spanning differently formatted text are not detected".to_string())
+        })?;
+
+        let run = new_tree
+            .get_run(run_id)
+            .cloned()
+            .ok_or_else(|| EditError::InvalidCommand(format!("Run not found: {:?}", run_id)))?;
+        let chars: Vec<char> = run.text.chars().collect();
+        let prefix: String = chars[..link.start - run_start].iter().collect();
+        let suffix: String = chars[link.end - run_start..].iter().collect();
+        let split_run = |text: String| {
+            let mut part = Run::with_style(text, run.style.clone());
+            part.character_style_id = run.character_style_id.clone();
+            part.direct_formatting = run.direct_formatting.clone();
+            part
+        };
+
+        // Keep the original run for the text before the link
+        let mut insert_index = index + 1;
+        if prefix.is_empty() {
+            new_tree.remove_run(run_id).map_err(EditError::DocModel)?;
+            insert_index = index;
+        } else if let Some(run) = new_tree.get_run_mut(run_id) {
+            run.text = prefix;
+        }
+
+        let hyperlink_id = new_tree
+            .insert_hyperlink(Hyperlink::new(link.target.clone()), self.paragraph_id, Some(insert_index))
+            .map_err(EditError::DocModel)?;
+        let mut link_run = split_run(link.text.clone());
+        link_run.style.color = Some("#0000FF".to_string());
+        link_run.style.underline = Some(true);
+        new_tree
+            .insert_run_into_hyperlink(link_run, hyperlink_id, None)
+            .map_err(EditError::DocModel)?;
+        if !suffix.is_empty() {
+            new_tree
+                .insert_run(split_run(suffix), self.paragraph_id, Some(insert_index + 1))
+                .map_err(EditError::DocModel)?;
+        }
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse: Box::new(RemoveHyperlink::with_id(hyperlink_id)),
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        // This will be replaced by the proper inverse in apply()
+        Box::new(RemoveHyperlink::new())
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "AutoFormat Hyperlink"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+// ============================================================================
+// Link audit
+// ============================================================================
+
+/// Result of checking one hyperlink
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LinkStatus {
+    /// The link is well formed
+    Ok,
+    /// The link is relative; `resolved` is None when there is no hyperlink base
+    Relative { resolved: Option<String> },
+    /// The link points to a bookmark that doesn't exist
+    BrokenBookmark,
+    /// The link target is malformed or unsafe
+    Invalid { reason: String },
+}
+
+impl LinkStatus {
+    /// Check if the link can't be followed
+    pub fn is_broken(&self) -> bool {
+        matches!(
+            self,
+            LinkStatus::BrokenBookmark | LinkStatus::Invalid { .. } | LinkStatus::Relative { resolved: None }
+        )
+    }
+}
+
+/// One hyperlink in a link audit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkAuditEntry {
+    /// The hyperlink node ID
+    pub hyperlink_id: NodeId,
+    /// Paragraph containing the link
+    pub paragraph_id: Option<NodeId>,
+    /// Text shown for the link
+    pub display_text: String,
+    /// The link target as a URL
+    pub url: String,
+    /// Whether the link can be followed
+    pub status: LinkStatus,
+}
+
+/// Check the target of a hyperlink against the document
+pub fn link_status(tree: &DocumentTree, target: &HyperlinkTarget) -> LinkStatus {
+    if let Err(e) = target.validate() {
+        return LinkStatus::Invalid { reason: e.to_string() };
+    }
+    match target {
+        HyperlinkTarget::External(url) if is_relative_url(url) => {
+            let base = tree.document.metadata.hyperlink_base.as_deref().filter(|base| !base.trim().is_empty());
+            LinkStatus::Relative {
+                resolved: base.map(|base| resolve_hyperlink(url, Some(base))),
+            }
+        }
+        // "_top" is the start of the document and needs no bookmark
+        HyperlinkTarget::Internal(name) if name != "_top" && tree.get_bookmark_by_name(name).is_none() => {
+            LinkStatus::BrokenBookmark
+        }
+        _ => LinkStatus::Ok,
+    }
+}
+
+/// List every hyperlink in the document with its status
+///
+/// Links in the body come first in reading order, followed by links
+/// elsewhere (tables, text boxes, headers) sorted by URL.
+pub fn audit_hyperlinks(tree: &DocumentTree) -> Vec<LinkAuditEntry> {
+    let entry = |hyperlink: &Hyperlink| LinkAuditEntry {
+        hyperlink_id: hyperlink.id(),
+        paragraph_id: hyperlink.parent(),
+        display_text: hyperlink
+            .children()
+            .iter()
+            .filter_map(|&id| tree.get_run(id))
+            .map(|run| run.text.as_str())
+            .collect(),
+        url: hyperlink.target_url(),
+        status: link_status(tree, &hyperlink.target),
+    };
+
+    let mut entries: Vec<LinkAuditEntry> = tree
+        .paragraphs()
+        .flat_map(|para| para.children().iter().filter_map(|&id| tree.get_hyperlink(id)))
+        .map(entry)
+        .collect();
+    let mut rest: Vec<LinkAuditEntry> = tree
+        .hyperlinks()
+        .filter(|hyperlink| !entries.iter().any(|e| e.hyperlink_id == hyperlink.id()))
+        .map(entry)
+        .collect();
+    rest.sort_by(|a, b| a.url.cmp(&b.url).then_with(|| a.display_text.cmp(&b.display_text)));
+    entries.extend(rest);
+    entries
+}
+
+// ============================================================================
+// Bulk edit
+// ============================================================================
+
+/// Replace `from` with `to` in a domain, matching subdomains too
+fn replace_in_domain(domain: &str, from: &str, to: &str) -> Option<String> {
+    let lower = domain.to_lowercase();
+    let from = from.to_lowercase();
+    if lower == from {
+        Some(to.to_string())
+    } else if lower.ends_with(&format!(".{}", from)) {
+        Some(format!("{}{}", &domain[..domain.len() - from.len()], to))
+    } else {
+        None
+    }
+}
+
+/// Replace the host of a URL if it is in the domain `from`
+pub fn replace_url_domain(url: &str, from: &str, to: &str) -> Option<String> {
+    let host_start = url.find("://")? + 3;
+    let authority_end = url[host_start..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |i| host_start + i);
+    // Skip user info and leave any port alone
+    let host_start = url[host_start..authority_end]
+        .rfind('@')
+        .map_or(host_start, |i| host_start + i + 1);
+    let host_end = url[host_start..authority_end]
+        .find(':')
+        .map_or(authority_end, |i| host_start + i);
+
+    let host = replace_in_domain(&url[host_start..host_end], from, to)?;
+    Some(format!("{}{}{}", &url[..host_start], host, &url[host_end..]))
+}
+
+/// Move every link in one domain to another, e.g. after a site moves
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplaceHyperlinkDomain {
+    /// Domain to replace, e.g. "old.example.com"
+    pub from: String,
+    /// Replacement domain
+    pub to: String,
+}
+
+impl ReplaceHyperlinkDomain {
+    pub fn new(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+
+    /// The new target for a link, if it is in the domain being replaced
+    fn replace(&self, target: &HyperlinkTarget) -> Option<HyperlinkTarget> {
+        match target {
+            HyperlinkTarget::External(url) => {
+                replace_url_domain(url, &self.from, &self.to).map(HyperlinkTarget::External)
+            }
+            HyperlinkTarget::Email { address, subject } => {
+                let (local, domain) = address.rsplit_once('@')?;
+                let domain = replace_in_domain(domain, &self.from, &self.to)?;
+                Some(HyperlinkTarget::email(format!("{}@{}", local, domain), subject.clone()))
+            }
+            HyperlinkTarget::Internal(_) => None,
+        }
+    }
+
+    /// New targets for every link the command would change
+    pub fn changes(&self, tree: &DocumentTree) -> Vec<(NodeId, HyperlinkTarget)> {
+        audit_hyperlinks(tree)
+            .into_iter()
+            .filter_map(|entry| {
+                let hyperlink = tree.get_hyperlink(entry.hyperlink_id)?;
+                self.replace(&hyperlink.target).map(|target| (entry.hyperlink_id, target))
+            })
+            .collect()
+    }
+}
+
+impl Command for ReplaceHyperlinkDomain {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        if self.from.trim().is_empty() || self.to.trim().is_empty() {
+            return Err(EditError::InvalidCommand("Domain cannot be empty".to_string()));
+        }
+        SetHyperlinkTargets::new(self.changes(tree)).apply(tree, selection)
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        // This will be replaced by the proper inverse in apply()
+        Box::new(SetHyperlinkTargets::new(Vec::new()))
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Change Link Domain"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+/// Set the targets of several hyperlinks at once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetHyperlinkTargets {
+    /// Hyperlink IDs and their new targets
+    pub targets: Vec<(NodeId, HyperlinkTarget)>,
+}
+
+impl SetHyperlinkTargets {
+    pub fn new(targets: Vec<(NodeId, HyperlinkTarget)>) -> Self {
+        Self { targets }
+    }
+}
+
+impl Command for SetHyperlinkTargets {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+
+        let mut old_targets = Vec::with_capacity(self.targets.len());
+        for (hyperlink_id, target) in &self.targets {
+            target
+                .validate()
+                .map_err(|e| EditError::InvalidCommand(format!("Invalid hyperlink target: {}", e)))?;
+            let hyperlink = new_tree
+                .get_hyperlink_mut(*hyperlink_id)
+                .ok_or_else(|| EditError::InvalidCommand(format!("Hyperlink not found: {:?}", hyperlink_id)))?;
+            old_targets.push((*hyperlink_id, hyperlink.target.clone()));
+            hyperlink.set_target(target.clone());
+        }
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse: Box::new(SetHyperlinkTargets::new(old_targets)),
+        })
+    }
+
+    fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
+        let old_targets = self
+            .targets
+            .iter()
+            .filter_map(|(id, _)| tree.get_hyperlink(*id).map(|hyperlink| (*id, hyperlink.target.clone())))
+            .collect();
+        Box::new(SetHyperlinkTargets::new(old_targets))
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Edit Hyperlinks"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::{Paragraph, Position};
+
+    #[test]
+    fn test_detect_links_while_typing() {
+        let text = "See (www.example.com/a_(b)). Mail bob@example.org, or \\\\srv\\share now";
+        let link = detect_link(text, 27).unwrap();
+        assert_eq!(link.text, "www.example.com/a_(b)");
+        assert_eq!(link.target, HyperlinkTarget::external("http://www.example.com/a_(b)"));
+
+        let link = detect_link(text, 50).unwrap();
+        assert_eq!(link.target, HyperlinkTarget::email("bob@example.org", None));
+        assert!(detect_link(text, 65).unwrap().target.is_external());
+        assert!(detect_link(text, 3).is_none());
+        assert!(detect_link("http://", 7).is_none());
+    }
+
+    #[test]
+    fn test_auto_format_audit_and_replace_domain() {
+        let mut tree = DocumentTree::new();
+        let para = Paragraph::new();
+        let para_id = para.id();
+        tree.insert_paragraph(para, tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new("Go to https://old.com/x now"), para_id, None).unwrap();
+        let selection = Selection::collapsed(Position::new(para_id, 23));
+
+        let result = AutoFormatHyperlink::new(para_id, 23).apply(&tree, &selection).unwrap();
+        let tree = result.tree;
+        let children = tree.get_paragraph(para_id).unwrap().children().to_vec();
+        assert_eq!(children.len(), 3);
+        assert_eq!(tree.get_run(children[0]).unwrap().text, "Go to ");
+        assert_eq!(tree.get_run(children[2]).unwrap().text, " now");
+        assert!(AutoFormatHyperlink::new(para_id, 23).detect(&tree).is_none());
+
+        let mut tree = tree;
+        let bad = tree.insert_hyperlink(Hyperlink::new(HyperlinkTarget::internal("missing")), para_id, None).unwrap();
+        tree.insert_run_into_hyperlink(Run::new("here"), bad, None).unwrap();
+        let relative = tree.insert_hyperlink(Hyperlink::new(HyperlinkTarget::external("docs/a.html")), para_id, None).unwrap();
+        tree.document.metadata.hyperlink_base = Some("https://old.com".to_string());
+
+        let audit = audit_hyperlinks(&tree);
+        assert_eq!(audit.len(), 3);
+        assert_eq!(audit[0].display_text, "https://old.com/x");
+        assert_eq!(audit[0].status, LinkStatus::Ok);
+        assert_eq!(audit[1].status, LinkStatus::BrokenBookmark);
+        assert_eq!(
+            audit[2].status,
+            LinkStatus::Relative { resolved: Some("https://old.com/docs/a.html".to_string()) }
+        );
+        assert_eq!(audit[2].hyperlink_id, relative);
+
+        let result = ReplaceHyperlinkDomain::new("old.com", "new.org").apply(&tree, &selection).unwrap();
+        assert_eq!(result.tree.get_hyperlink(children[1]).unwrap().target_url(), "https://new.org/x");
+        let undone = result.inverse.apply(&result.tree, &selection).unwrap();
+        assert_eq!(undone.tree.get_hyperlink(children[1]).unwrap().target_url(), "https://old.com/x");
+    }
+}
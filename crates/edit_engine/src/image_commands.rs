@@ -488,6 +488,9 @@ pub struct UpdateImageProperties {
     pub rotation: Option<f32>,
     /// Lock aspect ratio setting
     pub lock_aspect_ratio: Option<bool>,
+    /// Opt out of picture compression
+    #[serde(default)]
+    pub skip_compression: Option<bool>,
 }
 
 impl UpdateImageProperties {
@@ -498,6 +501,7 @@ impl UpdateImageProperties {
             title: None,
             rotation: None,
             lock_aspect_ratio: None,
+            skip_compression: None,
         }
     }
 
@@ -520,6 +524,11 @@ impl UpdateImageProperties {
         self.lock_aspect_ratio = Some(lock);
         self
     }
+
+    pub fn with_skip_compression(mut self, skip: bool) -> Self {
+        self.skip_compression = Some(skip);
+        self
+    }
 }
 
 impl Command for UpdateImageProperties {
@@ -535,6 +544,7 @@ impl Command for UpdateImageProperties {
         let old_title = image.title.clone();
         let old_rotation = image.properties.rotation;
         let old_lock_aspect_ratio = image.properties.lock_aspect_ratio;
+        let old_skip_compression = image.properties.skip_compression;
 
         // Apply updates
         let image = new_tree
@@ -553,6 +563,9 @@ impl Command for UpdateImageProperties {
         if let Some(lock) = self.lock_aspect_ratio {
             image.properties.lock_aspect_ratio = lock;
         }
+        if let Some(skip) = self.skip_compression {
+            image.properties.skip_compression = skip;
+        }
 
         // Create the inverse command
         let mut inverse = UpdateImageProperties::new(self.image_id);
@@ -568,6 +581,9 @@ impl Command for UpdateImageProperties {
         if self.lock_aspect_ratio.is_some() {
             inverse.lock_aspect_ratio = Some(old_lock_aspect_ratio);
         }
+        if self.skip_compression.is_some() {
+            inverse.skip_compression = Some(old_skip_compression);
+        }
 
         Ok(CommandResult {
             tree: new_tree,
@@ -591,6 +607,9 @@ impl Command for UpdateImageProperties {
             if self.lock_aspect_ratio.is_some() {
                 inverse.lock_aspect_ratio = Some(image.properties.lock_aspect_ratio);
             }
+            if self.skip_compression.is_some() {
+                inverse.skip_compression = Some(image.properties.skip_compression);
+            }
             Box::new(inverse)
         } else {
             Box::new(self.clone())
@@ -0,0 +1,245 @@
+//! Ink commands for drawing, erasing, and moving pen strokes
+
+use crate::{Command, CommandResult, EditError, Result};
+use doc_model::{DocumentTree, InkNode, InkStroke, Node, NodeId, Point, Selection};
+use serde::{Deserialize, Serialize};
+
+/// Anchor new ink to a paragraph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InsertInk {
+    /// Anchor paragraph
+    pub paragraph_id: NodeId,
+    /// Strokes, relative to the top-left of the paragraph
+    pub strokes: Vec<InkStroke>,
+    /// Alternative text for accessibility
+    pub alt_text: Option<String>,
+}
+
+impl InsertInk {
+    pub fn new(paragraph_id: NodeId, strokes: Vec<InkStroke>) -> Self {
+        Self {
+            paragraph_id,
+            strokes,
+            alt_text: None,
+        }
+    }
+}
+
+impl Command for InsertInk {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+
+        let mut ink = InkNode::new(self.strokes.clone());
+        ink.alt_text = self.alt_text.clone();
+        let ink_id = new_tree
+            .insert_ink(ink, self.paragraph_id)
+            .map_err(EditError::DocModel)?;
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse: Box::new(DeleteInk::new(ink_id)),
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        // This will be replaced by the proper inverse in apply()
+        Box::new(DeleteInk::new(NodeId::new()))
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Insert Ink"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+/// Delete ink by ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteInk {
+    /// The ink node ID to delete
+    pub ink_id: NodeId,
+}
+
+impl DeleteInk {
+    pub fn new(ink_id: NodeId) -> Self {
+        Self { ink_id }
+    }
+}
+
+impl Command for DeleteInk {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+
+        let ink = new_tree.remove_ink(self.ink_id).map_err(EditError::DocModel)?;
+        let paragraph_id = ink
+            .parent()
+            .ok_or_else(|| EditError::InvalidCommand("Ink has no anchor paragraph".to_string()))?;
+
+        let inverse = InsertInk {
+            paragraph_id,
+            strokes: ink.strokes,
+            alt_text: ink.alt_text,
+        };
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse: Box::new(inverse),
+        })
+    }
+
+    fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
+        match tree.get_ink(self.ink_id) {
+            Some(ink) => Box::new(InsertInk {
+                paragraph_id: ink.parent().unwrap_or_else(NodeId::new),
+                strokes: ink.strokes.clone(),
+                alt_text: ink.alt_text.clone(),
+            }),
+            None => Box::new(InsertInk::new(NodeId::new(), Vec::new())),
+        }
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Delete Ink"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+/// An edit to the strokes of an ink node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum InkEdit {
+    /// Draw another stroke on top
+    AddStroke(InkStroke),
+    /// Remove selected strokes
+    EraseStrokes(Vec<usize>),
+    /// Stroke eraser: remove every stroke touched by a circle
+    EraseAt { point: Point, radius: f32 },
+    /// Drag selected strokes
+    MoveStrokes { indices: Vec<usize>, dx: f32, dy: f32 },
+    /// Replace all strokes (used for undo)
+    SetStrokes(Vec<InkStroke>),
+}
+
+/// Edit the strokes of an ink node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditInk {
+    /// The ink node ID
+    pub ink_id: NodeId,
+    /// The edit to apply
+    pub edit: InkEdit,
+}
+
+impl EditInk {
+    pub fn new(ink_id: NodeId, edit: InkEdit) -> Self {
+        Self { ink_id, edit }
+    }
+}
+
+impl Command for EditInk {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+
+        let ink = new_tree
+            .get_ink_mut(self.ink_id)
+            .ok_or_else(|| EditError::InvalidCommand(format!("Ink not found: {:?}", self.ink_id)))?;
+        let old_strokes = ink.strokes.clone();
+
+        match &self.edit {
+            InkEdit::AddStroke(stroke) => ink.strokes.push(stroke.clone()),
+            InkEdit::EraseStrokes(indices) => {
+                if let Some(index) = indices.iter().find(|&&index| index >= ink.strokes.len()) {
+                    return Err(EditError::InvalidCommand(format!("Stroke {} out of range", index)));
+                }
+                ink.remove_strokes(indices);
+            }
+            InkEdit::EraseAt { point, radius } => {
+                ink.erase_at(*point, *radius);
+            }
+            InkEdit::MoveStrokes { indices, dx, dy } => ink.move_strokes(indices, *dx, *dy),
+            InkEdit::SetStrokes(strokes) => ink.strokes = strokes.clone(),
+        }
+
+        let inverse = Box::new(EditInk::new(self.ink_id, InkEdit::SetStrokes(old_strokes)));
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse,
+        })
+    }
+
+    fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
+        match tree.get_ink(self.ink_id) {
+            Some(ink) => Box::new(EditInk::new(self.ink_id, InkEdit::SetStrokes(ink.strokes.clone()))),
+            None => Box::new(self.clone()),
+        }
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        match self.edit {
+            InkEdit::AddStroke(_) => "Draw",
+            InkEdit::EraseStrokes(_) | InkEdit::EraseAt { .. } => "Erase Ink",
+            InkEdit::MoveStrokes { .. } => "Move Ink",
+            InkEdit::SetStrokes(_) => "Edit Ink",
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::{InkPoint, Paragraph, Position, ShapeColor};
+
+    #[test]
+    fn test_draw_erase_and_undo_ink() {
+        let mut tree = DocumentTree::new();
+        let para = Paragraph::new();
+        let para_id = para.id();
+        tree.insert_paragraph(para, tree.root_id(), None).unwrap();
+        let selection = Selection::collapsed(Position::new(para_id, 0));
+
+        let stroke = |y: f32| InkStroke::new(vec![InkPoint::at(0.0, y), InkPoint::at(30.0, y)], ShapeColor::RED, 2.0);
+        let result = InsertInk::new(para_id, vec![stroke(5.0)]).apply(&tree, &selection).unwrap();
+        let ink_id = result.tree.inks().next().unwrap().id();
+        assert_eq!(result.tree.inks_in_paragraph(para_id).len(), 1);
+        // Ink isn't part of the text flow
+        assert!(result.tree.get_paragraph(para_id).unwrap().children().is_empty());
+
+        let result = EditInk::new(ink_id, InkEdit::AddStroke(stroke(20.0)))
+            .apply(&result.tree, &selection)
+            .unwrap();
+        let erase = EditInk::new(ink_id, InkEdit::EraseAt { point: Point::new(15.0, 6.0), radius: 1.0 });
+        let erased = erase.apply(&result.tree, &selection).unwrap();
+        assert_eq!(erased.tree.get_ink(ink_id).unwrap().strokes, vec![stroke(20.0)]);
+
+        let undone = erased.inverse.apply(&erased.tree, &selection).unwrap();
+        assert_eq!(undone.tree.get_ink(ink_id).unwrap().strokes.len(), 2);
+
+        let deleted = DeleteInk::new(ink_id).apply(&undone.tree, &selection).unwrap();
+        assert_eq!(deleted.tree.inks().count(), 0);
+        let restored = deleted.inverse.apply(&deleted.tree, &selection).unwrap();
+        assert_eq!(restored.tree.inks().next().unwrap().strokes.len(), 2);
+    }
+}
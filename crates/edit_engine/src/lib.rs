@@ -9,19 +9,37 @@ mod undo;
 mod error;
 mod navigation;
 mod hyperlink_commands;
+mod hyperlink_management;
 mod bookmark_commands;
 mod paragraph_commands;
 mod image_commands;
 mod table_commands;
+mod table_data_commands;
 mod list_commands;
 mod section_commands;
 mod shape_commands;
 mod textbox_commands;
+mod ink_commands;
+mod canvas_commands;
 mod find_replace;
 mod spellcheck_commands;
+mod spellcheck_scheduler;
+mod proofing_commands;
+mod translation;
+mod read_aloud;
+mod summarize;
 mod field_commands;
 mod comment_commands;
 mod footnote_commands;
+mod goto_commands;
+mod format_painter_commands;
+mod multi_range_commands;
+mod macro_recorder;
+mod accessibility;
+mod building_block_commands;
+mod autotext;
+mod composition;
+mod outline_commands;
 
 pub use command::*;
 pub use executor::*;
@@ -29,16 +47,34 @@ pub use undo::*;
 pub use error::*;
 pub use navigation::*;
 pub use hyperlink_commands::*;
+pub use hyperlink_management::*;
 pub use bookmark_commands::*;
 pub use paragraph_commands::*;
 pub use image_commands::*;
 pub use table_commands::*;
+pub use table_data_commands::*;
 pub use list_commands::*;
 pub use section_commands::*;
 pub use shape_commands::*;
 pub use textbox_commands::*;
+pub use ink_commands::*;
+pub use canvas_commands::*;
 pub use find_replace::*;
 pub use spellcheck_commands::*;
+pub use spellcheck_scheduler::*;
+pub use proofing_commands::*;
+pub use translation::*;
+pub use read_aloud::*;
+pub use summarize::*;
 pub use field_commands::*;
 pub use comment_commands::*;
 pub use footnote_commands::*;
+pub use goto_commands::*;
+pub use format_painter_commands::*;
+pub use multi_range_commands::*;
+pub use macro_recorder::*;
+pub use accessibility::*;
+pub use building_block_commands::*;
+pub use autotext::*;
+pub use composition::*;
+pub use outline_commands::*;
@@ -1,6 +1,6 @@
 //! List and numbering commands for toggling, indenting, and managing lists
 
-use crate::{Command, CommandResult, EditError, Result};
+use crate::{Command, CommandResult, EditError, MacroStep, Result};
 use doc_model::{
     DocumentTree, ListProperties, Node, NodeId, NodeType, NumId, NumberingRegistry,
     Position, Selection,
@@ -170,6 +170,10 @@ impl Command for ToggleBulletList {
         *selection
     }
 
+    fn macro_step(&self) -> Option<MacroStep> {
+        MacroStep::command("toggleBulletList", self)
+    }
+
     fn display_name(&self) -> &str {
         "Toggle Bullet List"
     }
@@ -255,6 +259,10 @@ impl Command for ToggleNumberedList {
         *selection
     }
 
+    fn macro_step(&self) -> Option<MacroStep> {
+        MacroStep::command("toggleNumberedList", self)
+    }
+
     fn display_name(&self) -> &str {
         "Toggle Numbered List"
     }
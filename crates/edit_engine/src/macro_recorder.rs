@@ -0,0 +1,226 @@
+//! Keyboard macros - recording executed commands and replaying them
+//!
+//! While recording, the `EditingEngine` appends a `MacroStep` for every
+//! command it executes that can describe itself with `Command::macro_step`,
+//! and for every caret movement made through `EditingEngine::move_selection`.
+//! Commands tied to document-specific node positions do not record, since a
+//! macro must replay against whatever document is open.
+//!
+//! Replay turns each step back into a command through a
+//! `MacroCommandRegistry`, which knows the built-in recordable commands and
+//! can be extended with plugin commands.
+
+use crate::{
+    ApplyCopiedFormatting, ApplyStyle, Command, DeleteSelection, Direction, EditError,
+    MovementUnit, Result, SetParagraphAlignment, SetParagraphDirection, SetParagraphIndent,
+    SetParagraphSpacing, ToggleBulletList, ToggleNumberedList, TypeOver,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Prefix for the command IDs macros are bound to
+pub const MACRO_COMMAND_PREFIX: &str = "macro.";
+
+/// One recorded step of a macro
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum MacroStep {
+    /// Run a named command against the selection
+    Command {
+        /// Registry name of the command
+        name: String,
+        /// The command's parameters
+        #[serde(default)]
+        params: serde_json::Value,
+    },
+    /// Move the caret or extend the selection
+    Move {
+        direction: Direction,
+        unit: MovementUnit,
+        extend: bool,
+    },
+}
+
+impl MacroStep {
+    /// Create a command step from a serializable command
+    pub fn command<T: Serialize>(name: &str, params: &T) -> Option<Self> {
+        Some(MacroStep::Command {
+            name: name.to_string(),
+            params: serde_json::to_value(params).ok()?,
+        })
+    }
+}
+
+/// A named, replayable sequence of editing steps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Macro {
+    /// Macro name (unique within a macro library)
+    pub name: String,
+    /// Recorded steps, in order
+    pub steps: Vec<MacroStep>,
+    /// Keyboard shortcut that runs the macro
+    #[serde(default)]
+    pub keybinding: Option<String>,
+}
+
+impl Macro {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            steps: Vec::new(),
+            keybinding: None,
+        }
+    }
+
+    pub fn with_keybinding(mut self, keybinding: impl Into<String>) -> Self {
+        self.keybinding = Some(keybinding.into());
+        self
+    }
+
+    /// Command ID that runs this macro (e.g. `macro.FormatHeading`)
+    pub fn command_id(&self) -> String {
+        format!("{}{}", MACRO_COMMAND_PREFIX, self.name)
+    }
+
+    /// Check if the macro has no steps
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+/// Builds a command from recorded parameters
+pub type MacroCommandFactory = Box<dyn Fn(&serde_json::Value) -> Result<Box<dyn Command>> + Send + Sync>;
+
+/// Maps macro command names to factories for replay
+pub struct MacroCommandRegistry {
+    factories: HashMap<String, MacroCommandFactory>,
+}
+
+/// Factory for a command that deserializes directly from its parameters
+fn deserialize_factory<T>() -> MacroCommandFactory
+where
+    T: Command + DeserializeOwned + 'static,
+{
+    Box::new(|params| {
+        let command: T = serde_json::from_value(params.clone())
+            .map_err(|e| EditError::InvalidCommand(format!("Invalid macro parameters: {}", e)))?;
+        Ok(Box::new(command))
+    })
+}
+
+impl MacroCommandRegistry {
+    /// Create a registry with the built-in recordable commands
+    pub fn new() -> Self {
+        let mut registry = Self {
+            factories: HashMap::new(),
+        };
+        registry.register("typeOver", deserialize_factory::<TypeOver>());
+        registry.register("deleteSelection", deserialize_factory::<DeleteSelection>());
+        registry.register("setParagraphAlignment", deserialize_factory::<SetParagraphAlignment>());
+        registry.register("setParagraphDirection", deserialize_factory::<SetParagraphDirection>());
+        registry.register("setParagraphIndent", deserialize_factory::<SetParagraphIndent>());
+        registry.register("setParagraphSpacing", deserialize_factory::<SetParagraphSpacing>());
+        registry.register("toggleBulletList", deserialize_factory::<ToggleBulletList>());
+        registry.register("toggleNumberedList", deserialize_factory::<ToggleNumberedList>());
+        registry.register("applyCopiedFormatting", deserialize_factory::<ApplyCopiedFormatting>());
+        registry.register("applyStyle", deserialize_factory::<ApplyStyle>());
+        registry
+    }
+
+    /// Register (or replace) a command factory
+    pub fn register(&mut self, name: impl Into<String>, factory: MacroCommandFactory) {
+        self.factories.insert(name.into(), factory);
+    }
+
+    /// Check if a command name can be replayed
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(name)
+    }
+
+    /// Build the command for a recorded command step
+    pub fn build(&self, name: &str, params: &serde_json::Value) -> Result<Box<dyn Command>> {
+        let factory = self.factories.get(name).ok_or_else(|| {
+            EditError::InvalidCommand(format!("Unknown macro command: {}", name))
+        })?;
+        factory(params)
+    }
+}
+
+impl Default for MacroCommandRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for MacroCommandRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut names: Vec<&String> = self.factories.keys().collect();
+        names.sort();
+        f.debug_struct("MacroCommandRegistry").field("commands", &names).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EditingEngine;
+    use doc_model::{Alignment, DocumentTree, Node, NodeId, Paragraph, Position, Run, Selection};
+
+    fn engine_with(texts: &[&str]) -> (EditingEngine, Vec<NodeId>) {
+        let mut tree = DocumentTree::new();
+        let root_id = tree.root_id();
+        let mut ids = Vec::new();
+        for (i, text) in texts.iter().enumerate() {
+            let para_id = tree.insert_paragraph(Paragraph::new(), root_id, Some(i)).unwrap();
+            tree.insert_run(Run::new(*text), para_id, None).unwrap();
+            ids.push(para_id);
+        }
+        (EditingEngine::with_tree(tree), ids)
+    }
+
+    fn text_of(engine: &EditingEngine, para_id: NodeId) -> String {
+        let para = engine.tree().get_paragraph(para_id).unwrap();
+        para.children()
+            .iter()
+            .filter_map(|&id| engine.tree().get_run(id))
+            .map(|run| run.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_record_and_replay_macro() {
+        let (mut engine, ids) = engine_with(&["alpha", "beta"]);
+        engine.set_selection(Selection::collapsed(Position::new(ids[0], 0)));
+
+        engine.start_macro_recording("Bullet");
+        engine.execute(Box::new(TypeOver::new("- "))).unwrap();
+        engine.execute(Box::new(SetParagraphAlignment::center())).unwrap();
+        engine.move_selection(Direction::Forward, MovementUnit::Paragraph, false);
+        let recorded = engine.stop_macro_recording().unwrap();
+        assert_eq!(recorded.steps.len(), 3);
+        assert!(!engine.is_recording_macro());
+
+        // Macros survive a round trip through JSON
+        let json = serde_json::to_string(&recorded).unwrap();
+        let recorded: Macro = serde_json::from_str(&json).unwrap();
+
+        engine.set_selection(Selection::collapsed(Position::new(ids[1], 0)));
+        engine.run_macro(&recorded, &MacroCommandRegistry::new()).unwrap();
+        assert_eq!(text_of(&engine, ids[1]), "- beta");
+        let para = engine.tree().get_paragraph(ids[1]).unwrap();
+        assert_eq!(para.direct_formatting.alignment, Some(Alignment::Center));
+    }
+
+    #[test]
+    fn test_unknown_macro_command_fails() {
+        let (mut engine, _) = engine_with(&["text"]);
+        let mut bad = Macro::new("Bad").with_keybinding("Ctrl+Alt+B");
+        bad.steps.push(MacroStep::Command {
+            name: "noSuchCommand".to_string(),
+            params: serde_json::Value::Null,
+        });
+        assert_eq!(bad.command_id(), "macro.Bad");
+        assert!(engine.run_macro(&bad, &MacroCommandRegistry::new()).is_err());
+    }
+}
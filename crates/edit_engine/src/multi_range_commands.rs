@@ -0,0 +1,550 @@
+//! Multi-range commands - applying one command across a discontiguous selection
+//!
+//! When the user Ctrl+selects several ranges, commands such as formatting act
+//! on every range. `ApplyToRanges` runs a command once per range and records a
+//! single `CommandSequence` inverse, so the whole operation undoes in one step.
+//!
+//! Ranges are applied from the end of the document backwards so that a command
+//! which changes text length cannot shift the offsets of ranges still to come.
+//!
+//! `DeleteSelection` and `TypeOver` act on the selection they are applied to
+//! rather than on fixed positions, so they work per range - this is how
+//! deletion and typing-over behave in block (column) selection mode.
+
+use crate::{Command, CommandResult, EditError, MacroStep, Result};
+use crate::{paragraphs_between, DeleteRange, InsertText};
+use doc_model::{DocumentTree, Node, NodeId, NodeType, Position, Selection};
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+/// Resolve a position to (paragraph ID, character offset in the paragraph)
+fn paragraph_position(tree: &DocumentTree, position: &Position) -> Option<(NodeId, usize)> {
+    match tree.node_type(position.node_id)? {
+        NodeType::Paragraph => Some((position.node_id, position.offset)),
+        NodeType::Run => {
+            let para_id = tree.get_run(position.node_id)?.parent()?;
+            let before: usize = tree
+                .get_paragraph(para_id)?
+                .children()
+                .iter()
+                .take_while(|&&id| id != position.node_id)
+                .filter_map(|&id| tree.get_run(id))
+                .map(|run| run.text.chars().count())
+                .sum();
+            Some((para_id, before + position.offset))
+        }
+        _ => None,
+    }
+}
+
+/// Sort key placing a position in document order: (paragraph index, offset)
+fn document_order_key(tree: &DocumentTree, position: &Position) -> (usize, usize) {
+    let Some((para_id, offset)) = paragraph_position(tree, position) else {
+        return (usize::MAX, position.offset);
+    };
+
+    let index = tree
+        .paragraphs()
+        .position(|p| p.id() == para_id)
+        .unwrap_or(usize::MAX);
+    (index, offset)
+}
+
+/// Resolve a selection to a paragraph-relative (start, end) pair
+///
+/// Block selections are made of per-line ranges, so both ends must lie in
+/// the same paragraph.
+fn paragraph_range(tree: &DocumentTree, selection: &Selection) -> Result<(Position, Position)> {
+    let resolve = |position: &Position| {
+        paragraph_position(tree, position).ok_or_else(|| {
+            EditError::InvalidCommand(format!("Cannot resolve position: {:?}", position))
+        })
+    };
+    let (anchor_para, anchor) = resolve(&selection.anchor)?;
+    let (focus_para, focus) = resolve(&selection.focus)?;
+    if anchor_para != focus_para {
+        return Err(EditError::InvalidCommand(
+            "Range spans more than one paragraph".to_string(),
+        ));
+    }
+
+    Ok((
+        Position::new(anchor_para, anchor.min(focus)),
+        Position::new(anchor_para, anchor.max(focus)),
+    ))
+}
+
+// =============================================================================
+// Apply To Ranges Command
+// =============================================================================
+
+/// Apply a command to each range of a multi-selection as one undoable step
+#[derive(Debug)]
+pub struct ApplyToRanges {
+    /// The command to apply to every range
+    pub command: Box<dyn Command>,
+    /// The ranges to apply it to
+    pub ranges: Vec<Selection>,
+    /// Index of the primary range (its result becomes the returned selection)
+    pub primary: usize,
+}
+
+impl ApplyToRanges {
+    pub fn new(command: Box<dyn Command>, ranges: Vec<Selection>, primary: usize) -> Self {
+        Self {
+            command,
+            ranges,
+            primary,
+        }
+    }
+
+    /// Apply the command to every range, returning the resulting tree, the
+    /// resulting selection for each range (in the original range order), and
+    /// the inverse for the whole operation
+    pub fn apply_all(&self, tree: &DocumentTree) -> Result<(DocumentTree, Vec<Selection>, CommandSequence)> {
+        if self.ranges.is_empty() {
+            return Err(EditError::InvalidCommand("No ranges selected".to_string()));
+        }
+
+        let mut order: Vec<usize> = (0..self.ranges.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(document_order_key(tree, &self.ranges[i].start())));
+
+        let mut current = tree.clone();
+        let mut selections = self.ranges.clone();
+        let mut steps = Vec::with_capacity(self.ranges.len());
+        for i in order {
+            let result = self.command.apply(&current, &self.ranges[i])?;
+            steps.push((result.inverse, result.selection));
+            selections[i] = result.selection;
+            current = result.tree;
+        }
+
+        // Undo runs the inverses in the opposite order they were applied
+        steps.reverse();
+        Ok((current, selections, CommandSequence::new(steps)))
+    }
+}
+
+impl Clone for ApplyToRanges {
+    fn clone(&self) -> Self {
+        Self {
+            command: self.command.clone_box(),
+            ranges: self.ranges.clone(),
+            primary: self.primary,
+        }
+    }
+}
+
+impl Command for ApplyToRanges {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let (new_tree, selections, inverse) = self.apply_all(tree)?;
+        let selection = selections.get(self.primary).copied().unwrap_or(*selection);
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection,
+            inverse: Box::new(inverse),
+        })
+    }
+
+    fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
+        self.command.invert(tree)
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        self.command.transform_selection(selection)
+    }
+
+    fn changed_paragraphs(&self, tree: &DocumentTree, _selection: &Selection) -> Option<Vec<NodeId>> {
+        let changed: Option<Vec<Vec<NodeId>>> = self
+            .ranges
+            .iter()
+            .map(|range| self.command.changed_paragraphs(tree, range))
+            .collect();
+        changed.map(|changed| changed.concat())
+    }
+
+    fn display_name(&self) -> &str {
+        self.command.display_name()
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+// =============================================================================
+// Command Sequence
+// =============================================================================
+
+/// A sequence of commands applied in order, each with its own selection
+///
+/// This is the inverse recorded by `ApplyToRanges`; its own inverse is the
+/// reversed sequence of each step's inverse.
+#[derive(Debug)]
+pub struct CommandSequence {
+    steps: Vec<(Box<dyn Command>, Selection)>,
+}
+
+impl CommandSequence {
+    pub fn new(steps: Vec<(Box<dyn Command>, Selection)>) -> Self {
+        Self { steps }
+    }
+
+    /// Number of steps in the sequence
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Check if the sequence has no steps
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+impl Clone for CommandSequence {
+    fn clone(&self) -> Self {
+        Self {
+            steps: self
+                .steps
+                .iter()
+                .map(|(command, selection)| (command.clone_box(), *selection))
+                .collect(),
+        }
+    }
+}
+
+impl Command for CommandSequence {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut current = tree.clone();
+        let mut last_selection = *selection;
+        let mut inverses = Vec::with_capacity(self.steps.len());
+        for (command, step_selection) in &self.steps {
+            let result = command.apply(&current, step_selection)?;
+            inverses.push((result.inverse, result.selection));
+            last_selection = result.selection;
+            current = result.tree;
+        }
+        inverses.reverse();
+
+        Ok(CommandResult {
+            tree: current,
+            selection: last_selection,
+            inverse: Box::new(CommandSequence::new(inverses)),
+        })
+    }
+
+    fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
+        let steps = self
+            .steps
+            .iter()
+            .rev()
+            .map(|(command, selection)| (command.invert(tree), *selection))
+            .collect();
+        Box::new(CommandSequence::new(steps))
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        self.steps
+            .iter()
+            .fold(*selection, |sel, (command, _)| command.transform_selection(&sel))
+    }
+
+    fn changed_paragraphs(&self, tree: &DocumentTree, _selection: &Selection) -> Option<Vec<NodeId>> {
+        let changed: Option<Vec<Vec<NodeId>>> = self
+            .steps
+            .iter()
+            .map(|(command, selection)| command.changed_paragraphs(tree, selection))
+            .collect();
+        changed.map(|changed| changed.concat())
+    }
+
+    fn display_name(&self) -> &str {
+        self.steps
+            .first()
+            .map(|(command, _)| command.display_name())
+            .unwrap_or("Command Sequence")
+    }
+
+    fn memory_size(&self) -> usize {
+        std::mem::size_of_val(self)
+            + self
+                .steps
+                .iter()
+                .map(|(command, selection)| command.memory_size() + std::mem::size_of_val(selection))
+                .sum::<usize>()
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+// =============================================================================
+// Delete Selection Command
+// =============================================================================
+
+/// Delete the text of the selection it is applied to
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeleteSelection;
+
+impl DeleteSelection {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Command for DeleteSelection {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let (start, end) = paragraph_range(tree, selection)?;
+        DeleteRange::new(start, end).apply(tree, selection)
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        // Proper inverse created in apply()
+        Box::new(self.clone())
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        selection.collapse_to_anchor()
+    }
+
+    fn macro_step(&self) -> Option<MacroStep> {
+        MacroStep::command("deleteSelection", self)
+    }
+
+    fn changed_paragraphs(&self, tree: &DocumentTree, selection: &Selection) -> Option<Vec<NodeId>> {
+        paragraphs_between(tree, &selection.anchor, &selection.focus)
+    }
+
+    fn display_name(&self) -> &str {
+        "Delete"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+// =============================================================================
+// Type Over Command
+// =============================================================================
+
+/// Replace the text of the selection it is applied to (inserts at a caret)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeOver {
+    /// The text to type
+    pub text: String,
+}
+
+impl TypeOver {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self { text: text.into() }
+    }
+}
+
+impl Command for TypeOver {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let (start, end) = paragraph_range(tree, selection)?;
+
+        let mut steps = Vec::new();
+        let mut current = tree.clone();
+        if start != end {
+            let deleted = DeleteRange::new(start, end).apply(&current, selection)?;
+            steps.push((deleted.inverse, deleted.selection));
+            current = deleted.tree;
+        }
+        let inserted = InsertText::new(start, self.text.clone()).apply(&current, selection)?;
+        steps.push((inserted.inverse, inserted.selection));
+
+        // Undo removes the typed text before restoring the replaced text
+        steps.reverse();
+        Ok(CommandResult {
+            tree: inserted.tree,
+            selection: inserted.selection,
+            inverse: Box::new(CommandSequence::new(steps)),
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        // Proper inverse created in apply()
+        Box::new(self.clone())
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn macro_step(&self) -> Option<MacroStep> {
+        MacroStep::command("typeOver", self)
+    }
+
+    fn changed_paragraphs(&self, tree: &DocumentTree, selection: &Selection) -> Option<Vec<NodeId>> {
+        paragraphs_between(tree, &selection.anchor, &selection.focus)
+    }
+
+    fn is_text_input(&self) -> bool {
+        true
+    }
+
+    fn display_name(&self) -> &str {
+        "Typing"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CopyFormatting, EditingEngine, SetParagraphAlignment};
+    use doc_model::{Alignment, BlockSelection, CharacterProperties, MultiSelection, NodeId, Paragraph, Run};
+
+    fn three_paragraphs() -> (DocumentTree, Vec<NodeId>) {
+        let mut tree = DocumentTree::new();
+        let root_id = tree.root_id();
+        let mut ids = Vec::new();
+        for (i, text) in ["First line", "Second line", "Third line"].iter().enumerate() {
+            let para_id = tree.insert_paragraph(Paragraph::new(), root_id, Some(i)).unwrap();
+            tree.insert_run(Run::new(*text), para_id, None).unwrap();
+            ids.push(para_id);
+        }
+        (tree, ids)
+    }
+
+    fn range(node_id: NodeId, start: usize, end: usize) -> Selection {
+        Selection::new(Position::new(node_id, start), Position::new(node_id, end))
+    }
+
+    fn text_of(engine: &EditingEngine, para_id: NodeId) -> String {
+        engine
+            .tree()
+            .get_paragraph(para_id)
+            .unwrap()
+            .children()
+            .iter()
+            .filter_map(|&id| engine.tree().get_run(id))
+            .map(|run| run.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_command_applies_to_all_ranges_with_single_undo() {
+        let (tree, ids) = three_paragraphs();
+        let mut engine = EditingEngine::with_tree(tree);
+        engine.set_selection(range(ids[0], 0, 5));
+        engine.add_selection_range(range(ids[2], 0, 5));
+
+        engine.execute(Box::new(SetParagraphAlignment::center())).unwrap();
+        let alignment = |engine: &EditingEngine, id| {
+            engine.tree().get_paragraph(id).unwrap().direct_formatting.alignment
+        };
+        assert_eq!(alignment(&engine, ids[0]), Some(Alignment::Center));
+        assert_eq!(alignment(&engine, ids[1]), None);
+        assert_eq!(alignment(&engine, ids[2]), Some(Alignment::Center));
+        assert_eq!(engine.multi_selection().len(), 2);
+
+        engine.undo().unwrap();
+        assert_eq!(alignment(&engine, ids[0]), None);
+        assert_eq!(alignment(&engine, ids[2]), None);
+        assert!(!engine.can_undo());
+    }
+
+    #[test]
+    fn test_format_painter_applies_to_ranges_in_one_paragraph() {
+        let (mut tree, ids) = three_paragraphs();
+        let mut bold = CharacterProperties::new();
+        bold.bold = Some(true);
+        tree.insert_run(Run::with_direct_formatting(" bold", bold), ids[0], None).unwrap();
+
+        let mut engine = EditingEngine::with_tree(tree);
+        engine.set_selection(range(ids[0], 12, 12));
+        engine.copy_formatting(CopyFormatting::new()).unwrap();
+
+        // "Second line": Ctrl+select "Sec" and "line"
+        engine.set_selection(range(ids[1], 0, 3));
+        engine.add_selection_range(range(ids[1], 11, 7));
+        engine.apply_copied_formatting().unwrap();
+
+        let runs: Vec<(String, Option<bool>)> = engine
+            .tree()
+            .get_paragraph(ids[1])
+            .unwrap()
+            .children()
+            .iter()
+            .filter_map(|&id| engine.tree().get_run(id))
+            .map(|run| (run.text.clone(), run.direct_formatting.bold))
+            .collect();
+        let bold_text: Vec<&str> = runs
+            .iter()
+            .filter(|(_, b)| *b == Some(true))
+            .map(|(text, _)| text.as_str())
+            .collect();
+        assert_eq!(bold_text, vec!["Sec", "line"]);
+        assert_eq!(text_of(&engine, ids[1]), "Second line");
+
+        engine.undo().unwrap();
+        assert_eq!(engine.tree().get_paragraph(ids[1]).unwrap().children().len(), 1);
+    }
+
+    #[test]
+    fn test_block_delete_and_type_over() {
+        let (tree, ids) = three_paragraphs();
+        let run_of = |tree: &DocumentTree, id| tree.get_paragraph(id).unwrap().children()[0];
+        let block = BlockSelection::new(0, (40.0, 10.0), (70.0, 50.0));
+        // Column 1..3 on each line, with the runs' own coordinates for the first line
+        let ranges = vec![
+            range(run_of(&tree, ids[0]), 1, 3),
+            range(ids[1], 1, 3),
+            range(ids[2], 1, 3),
+        ];
+
+        let mut engine = EditingEngine::with_tree(tree);
+        engine.set_multi_selection(MultiSelection::from_block(block, ranges).unwrap());
+        engine.execute(Box::new(TypeOver::new("#"))).unwrap();
+        assert_eq!(text_of(&engine, ids[0]), "F#st line");
+        assert_eq!(text_of(&engine, ids[1]), "S#ond line");
+        assert!(engine.multi_selection().is_block());
+        assert_eq!(engine.multi_selection().ranges()[2], range(ids[2], 2, 2));
+
+        engine.undo().unwrap();
+        assert_eq!(text_of(&engine, ids[0]), "First line");
+        assert_eq!(text_of(&engine, ids[2]), "Third line");
+
+        engine.set_multi_selection(
+            MultiSelection::from_block(block, vec![range(ids[0], 0, 6), range(ids[1], 0, 7)]).unwrap(),
+        );
+        engine.execute(Box::new(DeleteSelection::new())).unwrap();
+        assert_eq!(text_of(&engine, ids[0]), "line");
+        assert_eq!(text_of(&engine, ids[1]), "line");
+    }
+
+    #[test]
+    fn test_engine_tracks_changed_paragraphs() {
+        let (tree, ids) = three_paragraphs();
+        let mut engine = EditingEngine::with_tree(tree);
+        assert!(engine.take_changed_paragraphs().is_all());
+        assert!(engine.changed_paragraphs().is_empty());
+
+        engine.set_selection(range(ids[0], 2, 2));
+        engine.execute(Box::new(TypeOver::new("x"))).unwrap();
+        engine.set_selection(range(ids[0], 0, 2));
+        engine.add_selection_range(range(ids[2], 0, 5));
+        engine.execute(Box::new(SetParagraphAlignment::center())).unwrap();
+        assert_eq!(engine.take_changed_paragraphs().paragraphs(), &[ids[0], ids[2]]);
+
+        engine.undo().unwrap();
+        let changed = engine.take_changed_paragraphs();
+        assert!(changed.paragraphs().contains(&ids[2]));
+        assert!(!changed.paragraphs().contains(&ids[1]));
+
+        // A range spanning paragraphs covers the ones in between
+        let (start, end) = (Position::new(ids[2], 1), Position::new(ids[0], 1));
+        assert_eq!(paragraphs_between(engine.tree(), &start, &end), Some(ids));
+    }
+}
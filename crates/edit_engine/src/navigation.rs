@@ -1,13 +1,15 @@
 //! Navigation module for cursor/caret movement in the document
 //!
 //! This module provides character, word, line, and paragraph navigation
-//! with proper Unicode support using grapheme clusters and word boundaries.
+//! with proper Unicode support using cursor clusters (graphemes, with Indic,
+//! Khmer and Myanmar consonant stacks kept whole) and word boundaries.
 
 use doc_model::{DocumentTree, Node, NodeId, NodeType, Position, Selection};
+use serde::{Deserialize, Serialize};
 use unicode_segmentation::UnicodeSegmentation;
 
 /// Direction for navigation operations
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Direction {
     /// Move backward (left in LTR text, up for vertical)
     Backward,
@@ -16,9 +18,9 @@ pub enum Direction {
 }
 
 /// Unit of movement for navigation
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MovementUnit {
-    /// Move by grapheme cluster (single character)
+    /// Move by cursor cluster (single character)
     Character,
     /// Move by word
     Word,
@@ -167,23 +169,21 @@ impl<'a> Navigator<'a> {
     // Character Navigation
     // ========================================================================
 
-    /// Move by one grapheme cluster in the specified direction
+    /// Move by one cursor cluster in the specified direction
     fn move_by_character(&self, pos: &Position, direction: Direction) -> Position {
         match direction {
-            Direction::Forward => self.next_grapheme_position(pos),
-            Direction::Backward => self.prev_grapheme_position(pos),
+            Direction::Forward => self.next_cluster_position(pos),
+            Direction::Backward => self.prev_cluster_position(pos),
         }
     }
 
-    /// Get the position after the next grapheme cluster
-    fn next_grapheme_position(&self, pos: &Position) -> Position {
+    /// Get the position after the next cursor cluster
+    fn next_cluster_position(&self, pos: &Position) -> Position {
         // First, try to get text content at current position
         if let Some(text) = self.get_text_at_position(pos) {
-            let graphemes: Vec<&str> = text.graphemes(true).collect();
-
             // If we can move within this run
-            if pos.offset < graphemes.len() {
-                return Position::new(pos.node_id, pos.offset + 1);
+            if let Some(&stop) = text_engine::cluster_boundaries(text).iter().find(|&&stop| stop > pos.offset) {
+                return Position::new(pos.node_id, stop);
             }
         }
 
@@ -196,11 +196,15 @@ impl<'a> Navigator<'a> {
         *pos
     }
 
-    /// Get the position before the previous grapheme cluster
-    fn prev_grapheme_position(&self, pos: &Position) -> Position {
+    /// Get the position before the previous cursor cluster
+    fn prev_cluster_position(&self, pos: &Position) -> Position {
         // If we can move within this run
         if pos.offset > 0 {
-            return Position::new(pos.node_id, pos.offset - 1);
+            let stop = self
+                .get_text_at_position(pos)
+                .and_then(|text| text_engine::cluster_boundaries(text).into_iter().rev().find(|&stop| stop < pos.offset))
+                .unwrap_or(pos.offset - 1);
+            return Position::new(pos.node_id, stop);
         }
 
         // Otherwise, try to move to the previous run/paragraph
@@ -291,8 +295,8 @@ impl<'a> Navigator<'a> {
                 if run_idx > 0 {
                     let prev_run_id = para.children()[run_idx - 1];
                     let prev_run = self.tree.get_run(prev_run_id)?;
-                    let grapheme_count = prev_run.text.graphemes(true).count();
-                    return Some(Position::new(prev_run_id, grapheme_count));
+                    let char_count = prev_run.text.chars().count();
+                    return Some(Position::new(prev_run_id, char_count));
                 }
 
                 // Move to the previous paragraph's end
@@ -342,8 +346,8 @@ impl<'a> Navigator<'a> {
             // Return position at end of last run, or paragraph itself if empty
             if let Some(&last_run_id) = prev_para.children().last() {
                 let last_run = self.tree.get_run(last_run_id)?;
-                let grapheme_count = last_run.text.graphemes(true).count();
-                Some(Position::new(last_run_id, grapheme_count))
+                let char_count = last_run.text.chars().count();
+                Some(Position::new(last_run_id, char_count))
             } else {
                 Some(Position::new(prev_para_id, 0))
             }
@@ -369,10 +373,8 @@ impl<'a> Navigator<'a> {
         // Get the current run's text
         if let Some(run) = self.tree.get_run(pos.node_id) {
             let text = &run.text;
-            let graphemes: Vec<&str> = text.graphemes(true).collect();
-
-            // Convert grapheme offset to byte offset
-            let byte_offset: usize = graphemes.iter().take(pos.offset).map(|g| g.len()).sum();
+            let char_count = text.chars().count();
+            let byte_offset = char_to_byte_offset(text, pos.offset);
 
             // Get word boundaries
             let word_indices: Vec<usize> = text.split_word_bound_indices().map(|(i, _)| i).collect();
@@ -380,14 +382,12 @@ impl<'a> Navigator<'a> {
             // Find the next word boundary after current position
             for &word_idx in &word_indices {
                 if word_idx > byte_offset {
-                    // Convert byte offset back to grapheme offset
-                    let new_grapheme_offset = self.byte_to_grapheme_offset(text, word_idx);
-                    return Position::new(pos.node_id, new_grapheme_offset);
+                    return Position::new(pos.node_id, text[..word_idx].chars().count());
                 }
             }
 
             // No more word boundaries in this run, go to end of run
-            let end_of_run = Position::new(pos.node_id, graphemes.len());
+            let end_of_run = Position::new(pos.node_id, char_count);
 
             // Then try the next run
             if let Some(next_pos) = self.next_text_position(&end_of_run) {
@@ -414,8 +414,6 @@ impl<'a> Navigator<'a> {
         // Get the current run's text
         if let Some(run) = self.tree.get_run(pos.node_id) {
             let text = &run.text;
-            let graphemes: Vec<&str> = text.graphemes(true).collect();
-
             // Handle position at start of run
             if pos.offset == 0 {
                 if let Some(prev_pos) = self.prev_text_position(pos) {
@@ -424,8 +422,7 @@ impl<'a> Navigator<'a> {
                 return *pos;
             }
 
-            // Convert grapheme offset to byte offset
-            let byte_offset: usize = graphemes.iter().take(pos.offset).map(|g| g.len()).sum();
+            let byte_offset = char_to_byte_offset(text, pos.offset);
 
             // Get word boundaries
             let word_indices: Vec<usize> = text.split_word_bound_indices().map(|(i, _)| i).collect();
@@ -447,9 +444,7 @@ impl<'a> Navigator<'a> {
 
             if let Some(boundary) = prev_boundary {
                 if boundary > 0 {
-                    // Convert byte offset back to grapheme offset
-                    let new_grapheme_offset = self.byte_to_grapheme_offset(text, boundary);
-                    return Position::new(pos.node_id, new_grapheme_offset);
+                    return Position::new(pos.node_id, text[..boundary].chars().count());
                 }
             }
 
@@ -467,43 +462,23 @@ impl<'a> Navigator<'a> {
         *pos
     }
 
-    /// Convert a byte offset to a grapheme offset
-    fn byte_to_grapheme_offset(&self, text: &str, byte_offset: usize) -> usize {
-        let mut grapheme_count = 0;
-        let mut current_byte = 0;
-
-        for grapheme in text.graphemes(true) {
-            if current_byte >= byte_offset {
-                break;
-            }
-            current_byte += grapheme.len();
-            grapheme_count += 1;
-        }
-
-        grapheme_count
-    }
-
     /// Skip whitespace forward
     fn skip_whitespace_forward(&self, pos: &Position) -> Position {
         if let Some(run) = self.tree.get_run(pos.node_id) {
             let text = &run.text;
-            let graphemes: Vec<&str> = text.graphemes(true).collect();
+            let chars: Vec<char> = text.chars().collect();
 
             let mut offset = pos.offset;
-            while offset < graphemes.len() {
-                let grapheme = graphemes[offset];
-                if !grapheme.chars().all(char::is_whitespace) {
-                    break;
-                }
+            while offset < chars.len() && chars[offset].is_whitespace() {
                 offset += 1;
             }
 
-            if offset < graphemes.len() {
+            if offset < chars.len() {
                 return Position::new(pos.node_id, offset);
             }
 
             // End of run, try next
-            if let Some(next_pos) = self.next_text_position(&Position::new(pos.node_id, graphemes.len())) {
+            if let Some(next_pos) = self.next_text_position(&Position::new(pos.node_id, chars.len())) {
                 return self.skip_whitespace_forward(&next_pos);
             }
         }
@@ -515,14 +490,10 @@ impl<'a> Navigator<'a> {
     fn skip_whitespace_backward(&self, pos: &Position) -> Position {
         if let Some(run) = self.tree.get_run(pos.node_id) {
             let text = &run.text;
-            let graphemes: Vec<&str> = text.graphemes(true).collect();
+            let chars: Vec<char> = text.chars().collect();
 
-            let mut offset = pos.offset;
-            while offset > 0 {
-                let grapheme = graphemes[offset - 1];
-                if !grapheme.chars().all(char::is_whitespace) {
-                    break;
-                }
+            let mut offset = pos.offset.min(chars.len());
+            while offset > 0 && chars[offset - 1].is_whitespace() {
                 offset -= 1;
             }
 
@@ -610,8 +581,8 @@ impl<'a> Navigator<'a> {
         let para = self.tree.get_paragraph(para_id)?;
         if let Some(&last_run_id) = para.children().last() {
             let run = self.tree.get_run(last_run_id)?;
-            let grapheme_count = run.text.graphemes(true).count();
-            Some(Position::new(last_run_id, grapheme_count))
+            let char_count = run.text.chars().count();
+            Some(Position::new(last_run_id, char_count))
         } else {
             Some(Position::new(para_id, 0))
         }
@@ -760,6 +731,11 @@ impl SelectionNavigation for Selection {
 // Utility Functions
 // ============================================================================
 
+/// Convert a character offset to a byte offset
+fn char_to_byte_offset(text: &str, offset: usize) -> usize {
+    text.char_indices().nth(offset).map_or(text.len(), |(index, _)| index)
+}
+
 /// Get the linear offset of a position in the document
 /// Useful for comparing positions across different nodes
 pub fn position_to_linear_offset(tree: &DocumentTree, pos: &Position) -> Option<usize> {
@@ -771,7 +747,7 @@ pub fn position_to_linear_offset(tree: &DocumentTree, pos: &Position) -> Option<
                 return Some(offset + pos.offset);
             }
             if let Some(run) = tree.get_run(run_id) {
-                offset += run.text.graphemes(true).count();
+                offset += run.text.chars().count();
             }
         }
         // Account for paragraph break
@@ -1360,7 +1336,7 @@ mod tests {
         assert_eq!(result.selection.focus.node_id, last_run_id);
         assert_eq!(
             result.selection.focus.offset,
-            last_run.text.graphemes(true).count()
+            last_run.text.chars().count()
         );
     }
 
@@ -1395,8 +1371,30 @@ mod tests {
             NavigationOptions::default(),
         );
 
-        // Should be at position 7 (after the emoji grapheme)
-        assert_eq!(result.selection.focus.offset, 7);
+        // Should be at position 11 (after the emoji's five characters)
+        assert_eq!(result.selection.focus.offset, 11);
+    }
+
+    #[test]
+    fn test_stacked_consonants_navigate_as_one() {
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        // Kannada "ಕ್ಷಣ": a conjunct the grapheme rules split, then a consonant
+        let run_id = tree.insert_run(Run::new("ಕ್ಷಣ"), para_id, None).unwrap();
+        let navigator = Navigator::new(&tree);
+        let step = |offset: usize, direction: Direction| {
+            let sel = Selection::collapsed(Position::new(run_id, offset));
+            navigator
+                .navigate(&sel, direction, MovementUnit::Character, NavigationOptions::default())
+                .selection
+                .focus
+                .offset
+        };
+
+        assert_eq!(step(0, Direction::Forward), 3);
+        assert_eq!(step(3, Direction::Forward), 4);
+        assert_eq!(step(4, Direction::Backward), 3);
+        assert_eq!(step(3, Direction::Backward), 0);
     }
 
     // =========================================================================
@@ -0,0 +1,352 @@
+//! Outline commands
+//!
+//! Outline view works on headings together with everything under them. A
+//! heading's subtree runs from the heading up to the next heading at the
+//! same or a higher level, and takes every body block in between (body
+//! paragraphs, tables, and so on). `MoveHeading` moves a whole subtree, by a
+//! drag offset among its sibling headings or to a drop target.
+//!
+//! Moving only reorders body blocks, so node IDs (and with them collapse
+//! state and bookmarks) survive. Heading list numbers follow document order
+//! at layout; SEQ fields cache their number, so captions are renumbered by
+//! `renumber_seq_fields` after each move.
+
+use crate::{Command, CommandResult, EditError, Result};
+use doc_model::{DocumentTree, FieldInstruction, Node, NodeId, Paragraph, Selection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Outline level of a heading paragraph (1-9)
+///
+/// Direct formatting wins over the paragraph style.
+pub fn outline_level(tree: &DocumentTree, para: &Paragraph) -> Option<u8> {
+    para.direct_formatting
+        .outline_level
+        .or_else(|| {
+            para.paragraph_style_id
+                .as_ref()
+                .and_then(|style_id| tree.styles.resolve(style_id))
+                .and_then(|resolved| resolved.paragraph_props.outline_level)
+        })
+        .filter(|level| (1..=9).contains(level))
+}
+
+/// Outline level of a body block, `None` for body text and tables
+fn block_level(tree: &DocumentTree, block_id: NodeId) -> Option<u8> {
+    tree.get_paragraph(block_id).and_then(|para| outline_level(tree, para))
+}
+
+/// Get the body blocks of a heading's subtree, starting with the heading
+pub fn heading_subtree(tree: &DocumentTree, heading_id: NodeId) -> Option<Vec<NodeId>> {
+    let blocks = tree.document.children();
+    let (start, end) = subtree_range(tree, blocks, heading_id)?;
+    Some(blocks[start..end].to_vec())
+}
+
+/// Index range of a heading's subtree within the body blocks
+fn subtree_range(tree: &DocumentTree, blocks: &[NodeId], heading_id: NodeId) -> Option<(usize, usize)> {
+    let start = blocks.iter().position(|&id| id == heading_id)?;
+    let level = block_level(tree, heading_id)?;
+    let end = blocks[start + 1..]
+        .iter()
+        .position(|&id| block_level(tree, id).is_some_and(|l| l <= level))
+        .map_or(blocks.len(), |i| start + 1 + i);
+    Some((start, end))
+}
+
+/// Where a heading's subtree moves to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OutlineMoveTarget {
+    /// Past this many sibling subtrees, negative to move up
+    ///
+    /// Siblings are the headings at the same level under the same parent
+    /// heading; the move stops at the first or last of them.
+    Offset(i32),
+    /// Before a body block, or at the end of the document when `None`
+    Before(Option<NodeId>),
+}
+
+/// Move a heading and its subtree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveHeading {
+    /// The heading paragraph
+    pub heading_id: NodeId,
+    /// Where to move it
+    pub target: OutlineMoveTarget,
+}
+
+impl MoveHeading {
+    /// Move past `offset` sibling subtrees, as when dragging in outline view
+    pub fn by_offset(heading_id: NodeId, offset: i32) -> Self {
+        Self {
+            heading_id,
+            target: OutlineMoveTarget::Offset(offset),
+        }
+    }
+
+    /// Move before a body block, or to the end when `None`
+    pub fn before(heading_id: NodeId, before: Option<NodeId>) -> Self {
+        Self {
+            heading_id,
+            target: OutlineMoveTarget::Before(before),
+        }
+    }
+
+    /// Index in the body blocks (with the subtree still in place) to move to
+    fn destination(&self, tree: &DocumentTree, blocks: &[NodeId], start: usize, end: usize) -> Result<usize> {
+        match self.target {
+            OutlineMoveTarget::Before(None) => Ok(blocks.len()),
+            OutlineMoveTarget::Before(Some(before)) => {
+                let index = blocks
+                    .iter()
+                    .position(|&id| id == before)
+                    .ok_or_else(|| EditError::InvalidCommand(format!("Block not found: {:?}", before)))?;
+                if index > start && index < end {
+                    return Err(EditError::InvalidCommand(
+                        "Cannot move a heading into its own subtree".to_string(),
+                    ));
+                }
+                Ok(index)
+            }
+            OutlineMoveTarget::Offset(offset) => {
+                // The parent's range ends at the next heading above this level
+                let level = block_level(tree, self.heading_id).unwrap_or(1);
+                let is_parent = |id: &NodeId| block_level(tree, *id).is_some_and(|l| l < level);
+                let parent_start = blocks[..start].iter().rposition(is_parent).map_or(0, |i| i + 1);
+                let parent_end = blocks[end..].iter().position(is_parent).map_or(blocks.len(), |i| end + i);
+
+                let siblings: Vec<usize> = (parent_start..parent_end)
+                    .filter(|&i| block_level(tree, blocks[i]) == Some(level))
+                    .collect();
+                let current = siblings.iter().position(|&i| i == start).unwrap_or_default();
+                let target = (current as i64 + offset as i64).clamp(0, siblings.len() as i64 - 1) as usize;
+
+                Ok(match target.cmp(&current) {
+                    std::cmp::Ordering::Less => siblings[target],
+                    std::cmp::Ordering::Equal => start,
+                    // After the target sibling's own subtree
+                    std::cmp::Ordering::Greater => siblings
+                        .get(target + 1)
+                        .copied()
+                        .unwrap_or(parent_end),
+                })
+            }
+        }
+    }
+}
+
+impl Command for MoveHeading {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let blocks = tree.document.children().to_vec();
+        let (start, end) = subtree_range(tree, &blocks, self.heading_id).ok_or_else(|| {
+            EditError::InvalidCommand(format!("Not a heading in the body: {:?}", self.heading_id))
+        })?;
+        let destination = self.destination(tree, &blocks, start, end)?;
+
+        let mut order = blocks.clone();
+        let subtree: Vec<NodeId> = order.drain(start..end).collect();
+        let index = if destination > start { destination - subtree.len() } else { destination };
+        order.splice(index..index, subtree);
+
+        let mut new_tree = tree.clone();
+        reorder_body(&mut new_tree, &order);
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse: Box::new(ReorderBody { order: blocks }),
+        })
+    }
+
+    fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
+        Box::new(ReorderBody {
+            order: tree.document.children().to_vec(),
+        })
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Move Heading"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+/// Put the body blocks back in a recorded order (for undo)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ReorderBody {
+    order: Vec<NodeId>,
+}
+
+impl Command for ReorderBody {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+        reorder_body(&mut new_tree, &self.order);
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse: self.invert(tree),
+        })
+    }
+
+    fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
+        Box::new(ReorderBody {
+            order: tree.document.children().to_vec(),
+        })
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Move Heading"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+/// Set the body blocks' order and renumber SEQ fields to match
+fn reorder_body(tree: &mut DocumentTree, order: &[NodeId]) {
+    for &id in order {
+        tree.document.remove_body_child(id);
+    }
+    for (index, &id) in order.iter().enumerate() {
+        tree.document.insert_body_child(index, id);
+    }
+    renumber_seq_fields(tree);
+}
+
+/// Recompute the results of SEQ fields in body paragraphs in document order
+///
+/// A field with a heading-level reset starts again at 1 after a heading at
+/// or above that level. Locked fields keep their result but still count.
+pub fn renumber_seq_fields(tree: &mut DocumentTree) {
+    let mut counters: HashMap<String, u32> = HashMap::new();
+    // Block index of the last field of each sequence, and of the last
+    // heading at each level
+    let mut last_field: HashMap<String, usize> = HashMap::new();
+    let mut last_heading = [None::<usize>; 10];
+
+    let blocks = tree.document.children().to_vec();
+    for (index, block_id) in blocks.into_iter().enumerate() {
+        let Some(para) = tree.get_paragraph(block_id) else {
+            continue;
+        };
+        if let Some(level) = outline_level(tree, para) {
+            last_heading[level as usize] = Some(index);
+        }
+
+        let children = para.children().to_vec();
+        for field_id in children {
+            let Some(field) = tree.get_field_mut(field_id) else {
+                continue;
+            };
+            let FieldInstruction::Seq { options } = &field.instruction else {
+                continue;
+            };
+
+            let reset = options.reset_at_heading_level.is_some_and(|reset_level| {
+                let since = last_field.get(&options.identifier).copied();
+                last_heading[1..=(reset_level as usize).min(9)]
+                    .iter()
+                    .flatten()
+                    .any(|&heading| since.is_none_or(|field| heading > field))
+            });
+            let counter = counters.entry(options.identifier.clone()).or_default();
+            if reset {
+                *counter = 0;
+            }
+            if let Some(value) = options.reset_to {
+                *counter = value;
+            } else if !options.current_only && !options.repeat_previous {
+                *counter += 1;
+            }
+            last_field.insert(options.identifier.clone(), index);
+
+            let text = options.format.format(*counter);
+            if !field.locked {
+                field.set_result(text);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::{Field, Run};
+
+    /// Body paragraphs from `(text, heading level)`, with a Figure SEQ field
+    /// in paragraphs whose text starts with "Figure"
+    fn outline(items: &[(&str, Option<&str>)]) -> (DocumentTree, Vec<NodeId>) {
+        let mut tree = DocumentTree::new();
+        let ids = items
+            .iter()
+            .map(|&(text, style)| {
+                let para = match style {
+                    Some(style) => Paragraph::with_paragraph_style(style),
+                    None => Paragraph::new(),
+                };
+                let para_id = tree.insert_paragraph(para, tree.root_id(), None).unwrap();
+                tree.insert_run(Run::new(text), para_id, None).unwrap();
+                if text.starts_with("Figure") {
+                    tree.insert_field(Field::seq("Figure"), para_id, None).unwrap();
+                }
+                para_id
+            })
+            .collect();
+        (tree, ids)
+    }
+
+    fn body_texts(tree: &DocumentTree) -> Vec<String> {
+        tree.document
+            .children()
+            .iter()
+            .map(|&id| {
+                let para = tree.get_paragraph(id).unwrap();
+                para.children()
+                    .iter()
+                    .map(|&child| match (tree.get_run(child), tree.get_field(child)) {
+                        (Some(run), _) => run.text.clone(),
+                        (_, Some(field)) => field.display_text(),
+                        _ => String::new(),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_move_heading_by_offset_takes_subtree_and_renumbers() {
+        let (mut tree, ids) = outline(&[
+            ("A", Some("Heading1")),
+            ("Figure ", None),
+            ("B", Some("Heading1")),
+            ("B.1", Some("Heading2")),
+            ("Figure ", None),
+            ("C", Some("Heading1")),
+        ]);
+        renumber_seq_fields(&mut tree);
+        let selection = Selection::collapsed(doc_model::Position::new(ids[0], 0));
+
+        let result = MoveHeading::by_offset(ids[2], -1).apply(&tree, &selection).unwrap();
+        assert_eq!(body_texts(&result.tree), ["B", "B.1", "Figure 1", "A", "Figure 2", "C"]);
+        assert_eq!(heading_subtree(&result.tree, ids[2]).unwrap(), &ids[2..5]);
+
+        // Subheadings stay within their parent
+        let nested = MoveHeading::by_offset(ids[3], 3).apply(&tree, &selection).unwrap();
+        assert_eq!(body_texts(&nested.tree), body_texts(&tree));
+
+        let undone = result.inverse.apply(&result.tree, &selection).unwrap();
+        assert_eq!(body_texts(&undone.tree), ["A", "Figure 1", "B", "B.1", "Figure 2", "C"]);
+        assert!(MoveHeading::before(ids[2], Some(ids[4])).apply(&tree, &selection).is_err());
+    }
+}
@@ -1,9 +1,9 @@
 //! Paragraph formatting commands for setting alignment, indentation, spacing, and borders
 
-use crate::{Command, CommandResult, EditError, Result};
+use crate::{Command, CommandResult, EditError, MacroStep, Result};
 use doc_model::{
     Alignment, DocumentTree, LineSpacing, Node, NodeId, NodeType,
-    ParagraphBorders, ParagraphProperties, Position, Selection,
+    ParagraphBorders, ParagraphProperties, Position, Selection, TextDirection,
     style::{BorderStyle, BorderStyleType},
 };
 use serde::{Deserialize, Serialize};
@@ -169,6 +169,14 @@ impl Command for SetParagraphAlignment {
         *selection
     }
 
+    fn macro_step(&self) -> Option<MacroStep> {
+        MacroStep::command("setParagraphAlignment", self)
+    }
+
+    fn changed_paragraphs(&self, tree: &DocumentTree, selection: &Selection) -> Option<Vec<NodeId>> {
+        get_paragraphs_in_selection(tree, selection).ok()
+    }
+
     fn display_name(&self) -> &str {
         match self.alignment {
             Alignment::Left => "Align Left",
@@ -231,6 +239,10 @@ impl Command for RestoreParagraphAlignments {
         *selection
     }
 
+    fn changed_paragraphs(&self, _tree: &DocumentTree, _selection: &Selection) -> Option<Vec<NodeId>> {
+        Some(self.alignments.iter().map(|entry| entry.0).collect())
+    }
+
     fn display_name(&self) -> &str {
         "Restore Alignment"
     }
@@ -240,6 +252,150 @@ impl Command for RestoreParagraphAlignments {
     }
 }
 
+// =============================================================================
+// Set Paragraph Direction Command
+// =============================================================================
+
+/// Set the base direction of paragraphs (left-to-right, right-to-left, or
+/// from the first strong character)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetParagraphDirection {
+    /// The new direction
+    pub direction: TextDirection,
+}
+
+impl SetParagraphDirection {
+    pub fn new(direction: TextDirection) -> Self {
+        Self { direction }
+    }
+
+    pub fn ltr() -> Self {
+        Self::new(TextDirection::Ltr)
+    }
+
+    pub fn rtl() -> Self {
+        Self::new(TextDirection::Rtl)
+    }
+}
+
+impl Command for SetParagraphDirection {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+        let paragraphs = get_paragraphs_in_selection(&new_tree, selection)?;
+
+        // Store old directions for undo
+        let old_directions: Vec<(NodeId, Option<TextDirection>)> = paragraphs
+            .iter()
+            .filter_map(|&para_id| {
+                new_tree.get_paragraph(para_id)
+                    .map(|p| (para_id, p.direct_formatting.direction))
+            })
+            .collect();
+
+        for &para_id in &paragraphs {
+            if let Some(para) = new_tree.get_paragraph_mut(para_id) {
+                para.direct_formatting.direction = Some(self.direction);
+            }
+        }
+
+        let inverse = Box::new(RestoreParagraphDirections {
+            directions: old_directions,
+        });
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse,
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        // Proper inverse created in apply()
+        Box::new(SetParagraphDirection::ltr())
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn macro_step(&self) -> Option<MacroStep> {
+        MacroStep::command("setParagraphDirection", self)
+    }
+
+    fn changed_paragraphs(&self, tree: &DocumentTree, selection: &Selection) -> Option<Vec<NodeId>> {
+        get_paragraphs_in_selection(tree, selection).ok()
+    }
+
+    fn display_name(&self) -> &str {
+        match self.direction {
+            TextDirection::Ltr => "Left-to-Right Text Direction",
+            TextDirection::Rtl => "Right-to-Left Text Direction",
+            TextDirection::Auto => "Automatic Text Direction",
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+/// Restore paragraph directions (for undo)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RestoreParagraphDirections {
+    directions: Vec<(NodeId, Option<TextDirection>)>,
+}
+
+impl Command for RestoreParagraphDirections {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+
+        // Store current directions for redo
+        let current_directions: Vec<(NodeId, Option<TextDirection>)> = self.directions
+            .iter()
+            .filter_map(|(para_id, _)| {
+                new_tree.get_paragraph(*para_id)
+                    .map(|p| (*para_id, p.direct_formatting.direction))
+            })
+            .collect();
+
+        for (para_id, direction) in &self.directions {
+            if let Some(para) = new_tree.get_paragraph_mut(*para_id) {
+                para.direct_formatting.direction = *direction;
+            }
+        }
+
+        let inverse = Box::new(RestoreParagraphDirections {
+            directions: current_directions,
+        });
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse,
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn changed_paragraphs(&self, _tree: &DocumentTree, _selection: &Selection) -> Option<Vec<NodeId>> {
+        Some(self.directions.iter().map(|entry| entry.0).collect())
+    }
+
+    fn display_name(&self) -> &str {
+        "Restore Text Direction"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
 // =============================================================================
 // Set Paragraph Indent Command
 // =============================================================================
@@ -357,6 +513,14 @@ impl Command for SetParagraphIndent {
         *selection
     }
 
+    fn macro_step(&self) -> Option<MacroStep> {
+        MacroStep::command("setParagraphIndent", self)
+    }
+
+    fn changed_paragraphs(&self, tree: &DocumentTree, selection: &Selection) -> Option<Vec<NodeId>> {
+        get_paragraphs_in_selection(tree, selection).ok()
+    }
+
     fn display_name(&self) -> &str {
         "Set Paragraph Indent"
     }
@@ -422,6 +586,10 @@ impl Command for RestoreParagraphIndents {
         *selection
     }
 
+    fn changed_paragraphs(&self, _tree: &DocumentTree, _selection: &Selection) -> Option<Vec<NodeId>> {
+        Some(self.indents.iter().map(|entry| entry.0).collect())
+    }
+
     fn display_name(&self) -> &str {
         "Restore Indent"
     }
@@ -561,6 +729,14 @@ impl Command for SetParagraphSpacing {
         *selection
     }
 
+    fn macro_step(&self) -> Option<MacroStep> {
+        MacroStep::command("setParagraphSpacing", self)
+    }
+
+    fn changed_paragraphs(&self, tree: &DocumentTree, selection: &Selection) -> Option<Vec<NodeId>> {
+        get_paragraphs_in_selection(tree, selection).ok()
+    }
+
     fn display_name(&self) -> &str {
         "Set Paragraph Spacing"
     }
@@ -626,6 +802,10 @@ impl Command for RestoreParagraphSpacing {
         *selection
     }
 
+    fn changed_paragraphs(&self, _tree: &DocumentTree, _selection: &Selection) -> Option<Vec<NodeId>> {
+        Some(self.spacing.iter().map(|entry| entry.0).collect())
+    }
+
     fn display_name(&self) -> &str {
         "Restore Spacing"
     }
@@ -746,6 +926,10 @@ impl Command for SetParagraphPagination {
         *selection
     }
 
+    fn changed_paragraphs(&self, tree: &DocumentTree, selection: &Selection) -> Option<Vec<NodeId>> {
+        get_paragraphs_in_selection(tree, selection).ok()
+    }
+
     fn display_name(&self) -> &str {
         "Set Paragraph Pagination"
     }
@@ -810,6 +994,10 @@ impl Command for RestoreParagraphPagination {
         *selection
     }
 
+    fn changed_paragraphs(&self, _tree: &DocumentTree, _selection: &Selection) -> Option<Vec<NodeId>> {
+        Some(self.options.iter().map(|entry| entry.0).collect())
+    }
+
     fn display_name(&self) -> &str {
         "Restore Pagination"
     }
@@ -907,6 +1095,10 @@ impl Command for SetParagraphBorders {
         *selection
     }
 
+    fn changed_paragraphs(&self, tree: &DocumentTree, selection: &Selection) -> Option<Vec<NodeId>> {
+        get_paragraphs_in_selection(tree, selection).ok()
+    }
+
     fn display_name(&self) -> &str {
         "Set Paragraph Borders"
     }
@@ -963,6 +1155,10 @@ impl Command for RestoreParagraphBorders {
         *selection
     }
 
+    fn changed_paragraphs(&self, _tree: &DocumentTree, _selection: &Selection) -> Option<Vec<NodeId>> {
+        Some(self.borders.iter().map(|entry| entry.0).collect())
+    }
+
     fn display_name(&self) -> &str {
         "Restore Borders"
     }
@@ -0,0 +1,347 @@
+//! Proofing language commands
+//!
+//! Runs carry an optional proofing language (BCP 47, e.g. "fr-FR") and a
+//! "do not check spelling" flag. `SetProofingLanguage` sets both over the
+//! selection, and `proofing_ranges` splits a paragraph into ranges sharing
+//! the same resolved proofing settings so each range can be checked against
+//! the right dictionary.
+//!
+//! `WordAtCursor` and `ReplaceWithSynonym` support the thesaurus: looking up
+//! the word at the cursor and swapping it for a synonym.
+
+use crate::format_painter_commands::{
+    format_runs, ordered_range, paragraph_text, run_ids, word_around, RestoreParagraphs,
+};
+use crate::{
+    paragraphs_between, Command, CommandResult, CorrectSpellingCommand, EditError, MacroStep, Result,
+};
+use doc_model::{DocumentTree, NodeId, Position, Selection};
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// Set Proofing Language Command
+// =============================================================================
+
+/// Set the proofing language of the selected text
+///
+/// Applies to the selected text, or the word at the cursor when the selection
+/// is collapsed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetProofingLanguage {
+    /// Proofing language, or None to fall back to detection
+    pub language: Option<String>,
+    /// Skip the text when checking spelling
+    pub no_proof: bool,
+}
+
+impl SetProofingLanguage {
+    pub fn new(language: impl Into<String>) -> Self {
+        Self {
+            language: Some(language.into()),
+            no_proof: false,
+        }
+    }
+
+    /// Mark the selection as "do not check spelling"
+    pub fn with_no_proof(mut self, no_proof: bool) -> Self {
+        self.no_proof = no_proof;
+        self
+    }
+}
+
+impl Command for SetProofingLanguage {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+        let ((start_para, mut start), (end_para, mut end)) = ordered_range(tree, selection)?;
+
+        let paragraphs = paragraphs_between(
+            tree,
+            &Position::new(start_para, start),
+            &Position::new(end_para, end),
+        )
+        .ok_or_else(|| EditError::InvalidCommand("Invalid selection".to_string()))?;
+        let inverse = Box::new(RestoreParagraphs::snapshot(tree, &paragraphs));
+
+        if selection.is_collapsed() {
+            (start, end) = word_around(&paragraph_text(tree, start_para), start);
+        }
+
+        for &para_id in &paragraphs {
+            let from = if para_id == start_para { start } else { 0 };
+            let to = if para_id == end_para {
+                end
+            } else {
+                paragraph_text(&new_tree, para_id).chars().count()
+            };
+            format_runs(&mut new_tree, para_id, from, to, |run| {
+                run.direct_formatting.language = self.language.clone();
+                run.direct_formatting.no_proof = self.no_proof.then_some(true);
+            })?;
+        }
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse,
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        // Proper inverse created in apply()
+        Box::new(self.clone())
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn macro_step(&self) -> Option<MacroStep> {
+        MacroStep::command("setProofingLanguage", self)
+    }
+
+    fn display_name(&self) -> &str {
+        "Set Proofing Language"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+// =============================================================================
+// Proofing Ranges
+// =============================================================================
+
+/// A range of a paragraph with the same proofing settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofingRange {
+    /// Start character offset in the paragraph
+    pub start: usize,
+    /// End character offset (exclusive)
+    pub end: usize,
+    /// Resolved proofing language, if any
+    pub language: Option<String>,
+    /// Spelling is not checked in this range
+    pub no_proof: bool,
+}
+
+/// Split a paragraph into ranges by resolved proofing settings
+///
+/// Settings come from the run's styles and direct formatting, so a language
+/// set on a character style applies too, and "do not check spelling" set on
+/// a paragraph style (e.g. for code) covers the whole paragraph. Fields sit
+/// outside the runs' text, so their results are never checked.
+pub fn proofing_ranges(tree: &DocumentTree, para_id: NodeId) -> Vec<ProofingRange> {
+    let mut ranges: Vec<ProofingRange> = Vec::new();
+    let mut offset = 0;
+    for run_id in run_ids(tree, para_id) {
+        let Some(run) = tree.get_run(run_id) else {
+            continue;
+        };
+        let len = run.text.chars().count();
+        if len == 0 {
+            continue;
+        }
+        let props = tree
+            .compute_character_properties(run_id)
+            .unwrap_or_else(|| run.direct_formatting.clone());
+        let language = props.language.filter(|l| !l.is_empty());
+        let no_proof = tree.is_no_proof(run_id);
+
+        match ranges.last_mut() {
+            Some(last) if last.language == language && last.no_proof == no_proof => {
+                last.end = offset + len;
+            }
+            _ => ranges.push(ProofingRange {
+                start: offset,
+                end: offset + len,
+                language,
+                no_proof,
+            }),
+        }
+        offset += len;
+    }
+    ranges
+}
+
+// =============================================================================
+// Thesaurus
+// =============================================================================
+
+/// The word at a position, for thesaurus lookup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordAtCursor {
+    /// Paragraph containing the word
+    pub para_id: NodeId,
+    /// Start character offset in the paragraph
+    pub start: usize,
+    /// End character offset (exclusive)
+    pub end: usize,
+    /// The word
+    pub word: String,
+}
+
+impl WordAtCursor {
+    /// Find the word at or just before a position
+    pub fn find(tree: &DocumentTree, position: &Position) -> Result<Option<Self>> {
+        let ((para_id, offset), _) = ordered_range(tree, &Selection::collapsed(*position))?;
+        let text = paragraph_text(tree, para_id);
+        let (start, end) = word_around(&text, offset);
+        let word: String = text.chars().skip(start).take(end - start).collect();
+
+        // Quotes around the word aren't part of it
+        let leading = word.chars().take_while(|&c| c == '\'').count();
+        let word = word.trim_matches('\'').to_string();
+        let start = start + leading;
+        Ok((!word.is_empty()).then(|| Self {
+            para_id,
+            start,
+            end: start + word.chars().count(),
+            word,
+        }))
+    }
+}
+
+/// Replace the word at the cursor with a synonym
+///
+/// The synonym takes the word's capitalization, and the word's formatting
+/// is kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplaceWithSynonym {
+    /// The replacement word
+    pub synonym: String,
+}
+
+impl ReplaceWithSynonym {
+    pub fn new(synonym: impl Into<String>) -> Self {
+        Self {
+            synonym: synonym.into(),
+        }
+    }
+
+    /// The synonym cased like `original`
+    fn cased_like(&self, original: &str) -> String {
+        let letters = || original.chars().filter(|c| c.is_alphabetic());
+        if letters().count() > 1 && letters().all(char::is_uppercase) {
+            self.synonym.to_uppercase()
+        } else if letters().next().is_some_and(char::is_uppercase) {
+            let mut chars = self.synonym.chars();
+            chars
+                .next()
+                .into_iter()
+                .flat_map(char::to_uppercase)
+                .chain(chars)
+                .collect()
+        } else {
+            self.synonym.clone()
+        }
+    }
+}
+
+impl Command for ReplaceWithSynonym {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let word = WordAtCursor::find(tree, &selection.focus)?
+            .ok_or_else(|| EditError::InvalidCommand("No word at the cursor".to_string()))?;
+        let replacement = self.cased_like(&word.word);
+        CorrectSpellingCommand::new(word.para_id, word.start, word.end, word.word, replacement)
+            .apply(tree, selection)
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        // Proper inverse created in apply()
+        Box::new(self.clone())
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn macro_step(&self) -> Option<MacroStep> {
+        MacroStep::command("replaceWithSynonym", self)
+    }
+
+    fn display_name(&self) -> &str {
+        "Replace with Synonym"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::{Paragraph, Run};
+
+    #[test]
+    fn test_set_proofing_language_and_ranges() {
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new("Hello bonjour world"), para_id, None).unwrap();
+
+        let cursor = Selection::collapsed(Position::new(para_id, 8));
+        let result = SetProofingLanguage::new("fr-FR").apply(&tree, &cursor).unwrap();
+        let selection = Selection::new(Position::new(para_id, 14), Position::new(para_id, 19));
+        let result = SetProofingLanguage { language: None, no_proof: true }
+            .apply(&result.tree, &selection)
+            .unwrap();
+
+        let ranges = proofing_ranges(&result.tree, para_id);
+        assert_eq!(ranges.len(), 4);
+        assert_eq!((ranges[1].start, ranges[1].end), (6, 13));
+        assert_eq!(ranges[1].language.as_deref(), Some("fr-FR"));
+        assert_eq!((ranges[3].start, ranges[3].end, ranges[3].no_proof), (14, 19, true));
+
+        let undone = result.inverse.apply(&result.tree, &selection).unwrap();
+        assert_eq!(proofing_ranges(&undone.tree, para_id).len(), 3);
+    }
+
+    #[test]
+    fn test_code_styles_are_not_proofed() {
+        use doc_model::CharacterProperties;
+
+        let mut tree = DocumentTree::new();
+        let code = tree
+            .insert_paragraph(Paragraph::with_paragraph_style("HTMLPreformatted"), tree.root_id(), None)
+            .unwrap();
+        tree.insert_run(Run::new("let x = vec![];"), code, None).unwrap();
+        let comment = CharacterProperties {
+            no_proof: Some(false),
+            ..Default::default()
+        };
+        tree.insert_run(Run::with_direct_formatting(" // teh comment", comment), code, None)
+            .unwrap();
+
+        let prose = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new("Call "), prose, None).unwrap();
+        tree.insert_run(Run::with_character_style("fn_name()", "HTMLCode"), prose, None)
+            .unwrap();
+
+        let no_proof = |para_id| {
+            proofing_ranges(&tree, para_id)
+                .iter()
+                .map(|range| (range.start, range.end, range.no_proof))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(no_proof(code), [(0, 15, true), (15, 30, false)]);
+        assert_eq!(no_proof(prose), [(0, 5, false), (5, 14, true)]);
+    }
+
+    #[test]
+    fn test_replace_with_synonym_keeps_case() {
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new("A Big house"), para_id, None).unwrap();
+
+        let cursor = Selection::collapsed(Position::new(para_id, 3));
+        assert_eq!(WordAtCursor::find(&tree, &cursor.focus).unwrap().unwrap().word, "Big");
+        let result = ReplaceWithSynonym::new("large").apply(&tree, &cursor).unwrap();
+        assert_eq!(paragraph_text(&result.tree, para_id), "A Large house");
+
+        let undone = result.inverse.apply(&result.tree, &result.selection).unwrap();
+        assert_eq!(paragraph_text(&undone.tree, para_id), "A Big house");
+    }
+}
@@ -0,0 +1,182 @@
+//! Read Aloud
+//!
+//! Turns the document from the cursor onwards into sentences of plain text
+//! for a text-to-speech backend. Every word keeps an anchor back to its
+//! paragraph and character range, so progress reported by the backend (or
+//! estimated from the speaking rate) can be shown by highlighting the word
+//! being spoken.
+
+use crate::format_painter_commands::{ordered_range, paragraph_text};
+use crate::Result;
+use doc_model::{DocumentTree, Node, NodeId, Position, Selection};
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Average characters per word (including the following space), used to
+/// turn a speaking rate into characters per second
+const CHARS_PER_WORD: f64 = 6.0;
+
+/// A word of a sentence with its place in the document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordAnchor {
+    /// Paragraph containing the word
+    pub para_id: NodeId,
+    /// Start character offset in the paragraph
+    pub start: usize,
+    /// End character offset in the paragraph (exclusive)
+    pub end: usize,
+    /// Start character offset in the sentence text
+    pub text_start: usize,
+}
+
+/// A sentence to speak
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeechSegment {
+    /// Position in the reading order
+    pub index: usize,
+    /// Paragraph containing the sentence
+    pub para_id: NodeId,
+    /// Start character offset in the paragraph
+    pub start: usize,
+    /// End character offset in the paragraph (exclusive)
+    pub end: usize,
+    /// Plain text to speak
+    pub text: String,
+    /// Words of the sentence, in order
+    pub words: Vec<WordAnchor>,
+}
+
+impl SpeechSegment {
+    /// The word at a character offset in the sentence text
+    ///
+    /// Offsets between words give the preceding word.
+    pub fn word_at(&self, text_offset: usize) -> Option<&WordAnchor> {
+        self.words
+            .iter()
+            .take_while(|w| w.text_start <= text_offset)
+            .last()
+            .or(self.words.first())
+    }
+
+    /// Estimate the word being spoken after `elapsed_secs` at `words_per_minute`
+    ///
+    /// For backends that don't report word boundaries.
+    pub fn estimated_word(&self, elapsed_secs: f64, words_per_minute: u32) -> Option<&WordAnchor> {
+        let chars_per_second = f64::from(words_per_minute) * CHARS_PER_WORD / 60.0;
+        self.word_at((elapsed_secs * chars_per_second) as usize)
+    }
+}
+
+/// Progress of a read-aloud session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ReadAloudEvent {
+    /// A sentence started
+    #[serde(rename_all = "camelCase")]
+    Sentence { index: usize, para_id: NodeId, start: usize, end: usize },
+    /// A word is being spoken
+    #[serde(rename_all = "camelCase")]
+    Word { sentence: usize, para_id: NodeId, start: usize, end: usize },
+    /// Reading ended, either at the end of the document or when stopped
+    Finished { completed: bool },
+}
+
+impl ReadAloudEvent {
+    /// Event for the start of a sentence
+    pub fn sentence(segment: &SpeechSegment) -> Self {
+        Self::Sentence {
+            index: segment.index,
+            para_id: segment.para_id,
+            start: segment.start,
+            end: segment.end,
+        }
+    }
+
+    /// Event for a word of a sentence
+    pub fn word(segment: &SpeechSegment, word: &WordAnchor) -> Self {
+        Self::Word {
+            sentence: segment.index,
+            para_id: word.para_id,
+            start: word.start,
+            end: word.end,
+        }
+    }
+}
+
+/// Split the document into sentences, starting at `from`
+///
+/// Reading starts at the sentence containing the position. Empty paragraphs
+/// and whitespace-only sentences are skipped.
+pub fn read_aloud_segments(tree: &DocumentTree, from: &Position) -> Result<Vec<SpeechSegment>> {
+    let ((start_para, start_offset), _) = ordered_range(tree, &Selection::collapsed(*from))?;
+
+    let mut segments = Vec::new();
+    for para_id in tree.paragraphs().map(|p| p.id()).skip_while(|&id| id != start_para) {
+        let text = paragraph_text(tree, para_id);
+        let mut char_offset = 0;
+        for sentence in text.split_sentence_bounds() {
+            let (start, len) = (char_offset, sentence.chars().count());
+            char_offset += len;
+            if sentence.trim().is_empty() || (para_id == start_para && start + len <= start_offset) {
+                continue;
+            }
+            segments.push(sentence_segment(segments.len(), para_id, start, sentence));
+        }
+    }
+    Ok(segments)
+}
+
+fn sentence_segment(index: usize, para_id: NodeId, start: usize, sentence: &str) -> SpeechSegment {
+    let text = sentence.trim_end().to_string();
+    let mut words = Vec::new();
+    let mut text_start = 0;
+    for word in text.split_word_bounds() {
+        let len = word.chars().count();
+        if word.chars().any(char::is_alphanumeric) {
+            words.push(WordAnchor {
+                para_id,
+                start: start + text_start,
+                end: start + text_start + len,
+                text_start,
+            });
+        }
+        text_start += len;
+    }
+    SpeechSegment {
+        index,
+        para_id,
+        start,
+        end: start + text.chars().count(),
+        text,
+        words,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::{Paragraph, Run};
+
+    #[test]
+    fn test_segments_start_at_cursor_sentence() {
+        let mut tree = DocumentTree::new();
+        let first = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new("Skip this. Read from here. "), first, None).unwrap();
+        tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        let third = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new("Then this one!"), third, None).unwrap();
+
+        let segments = read_aloud_segments(&tree, &Position::new(first, 14)).unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].text, "Read from here.");
+        assert_eq!((segments[0].start, segments[0].end), (11, 26));
+        assert_eq!(segments[1].para_id, third);
+
+        let here = segments[0].word_at(11).unwrap();
+        assert_eq!((here.start, here.end), (21, 25));
+        assert_eq!(segments[1].words.len(), 3);
+        assert_eq!(segments[1].estimated_word(0.0, 180).map(|w| w.start), Some(0));
+    }
+}
@@ -6,8 +6,8 @@
 use crate::{Command, CommandResult, Result, EditError};
 use doc_model::{
     ColumnConfig, ColumnDef, DocumentTree, FieldCode, GutterPosition, Node, NodeId,
-    Orientation, PageNumberFormat, PageSizePreset, Paragraph, Position,
-    Run, Section, SectionBreakType, Selection,
+    Orientation, PageNumberFormat, PageNumbering, PageSizePreset, Paragraph, Position,
+    Run, Section, SectionBreakType, Selection, Watermark,
 };
 use serde::{Deserialize, Serialize};
 
@@ -44,6 +44,9 @@ pub struct SetPageSetup {
     pub gutter: Option<f32>,
     /// Gutter position
     pub gutter_position: Option<GutterPosition>,
+    /// Mirror margins on facing pages
+    #[serde(default)]
+    pub mirror_margins: Option<bool>,
 }
 
 impl SetPageSetup {
@@ -63,6 +66,7 @@ impl SetPageSetup {
             margin_footer: None,
             gutter: None,
             gutter_position: None,
+            mirror_margins: None,
         }
     }
 
@@ -82,6 +86,7 @@ impl SetPageSetup {
             margin_footer: None,
             gutter: None,
             gutter_position: None,
+            mirror_margins: None,
         }
     }
 
@@ -101,6 +106,7 @@ impl SetPageSetup {
             margin_footer: None,
             gutter: None,
             gutter_position: None,
+            mirror_margins: None,
         }
     }
 
@@ -120,6 +126,46 @@ impl SetPageSetup {
             margin_footer: None,
             gutter: None,
             gutter_position: None,
+            mirror_margins: None,
+        }
+    }
+}
+
+impl SetPageSetup {
+    /// Create a new SetPageSetup command with a binding gutter and
+    /// optionally mirrored margins
+    pub fn with_binding(gutter: f32, gutter_position: GutterPosition, mirror_margins: bool) -> Self {
+        Self {
+            section_id: None,
+            page_size_preset: None,
+            custom_width: None,
+            custom_height: None,
+            orientation: None,
+            margin_top: None,
+            margin_bottom: None,
+            margin_left: None,
+            margin_right: None,
+            margin_header: None,
+            margin_footer: None,
+            gutter: Some(gutter),
+            gutter_position: Some(gutter_position),
+            mirror_margins: Some(mirror_margins),
+        }
+    }
+
+    fn changes_binding(&self) -> bool {
+        self.gutter.is_some() || self.gutter_position.is_some() || self.mirror_margins.is_some()
+    }
+
+    /// The target section's current binding settings, if this command
+    /// changes them
+    fn binding_of(&self, tree: &DocumentTree) -> (Option<f32>, Option<GutterPosition>, Option<bool>) {
+        match section_index(tree, self.section_id).filter(|_| self.changes_binding()) {
+            Some(index) => {
+                let setup = &tree.sections[index].page_setup;
+                (Some(setup.gutter), Some(setup.gutter_position), Some(setup.mirror_margins))
+            }
+            None => (None, None, None),
         }
     }
 }
@@ -130,6 +176,7 @@ impl Command for SetPageSetup {
 
         // Get the current page setup to create inverse command
         let old_setup = new_tree.document.page_setup.clone();
+        let (old_gutter, old_gutter_position, old_mirror_margins) = self.binding_of(&new_tree);
 
         // Apply page size
         if let Some(preset) = &self.page_size_preset {
@@ -166,9 +213,34 @@ impl Command for SetPageSetup {
             new_tree.document.page_setup.margin_right = right;
         }
 
+        // Apply binding settings to the section; a document without
+        // sections gets one covering all of it
+        let mut section_id = self.section_id;
+        if self.changes_binding() {
+            let index = match (section_index(&new_tree, self.section_id), self.section_id) {
+                (Some(index), _) => index,
+                (None, None) => {
+                    new_tree.add_section(Section::new());
+                    0
+                }
+                (None, Some(id)) => return Err(EditError::InvalidCommand(format!("Section not found: {:?}", id))),
+            };
+            let section = &mut new_tree.sections[index];
+            section_id = Some(section.id());
+            if let Some(gutter) = self.gutter {
+                section.page_setup.gutter = gutter.max(0.0);
+            }
+            if let Some(gutter_position) = self.gutter_position {
+                section.page_setup.gutter_position = gutter_position;
+            }
+            if let Some(mirror_margins) = self.mirror_margins {
+                section.page_setup.mirror_margins = mirror_margins;
+            }
+        }
+
         // Create inverse command
         let inverse = Box::new(SetPageSetup {
-            section_id: self.section_id,
+            section_id,
             page_size_preset: Some(PageSizePreset::Custom),
             custom_width: Some(old_setup.width),
             custom_height: Some(old_setup.height),
@@ -179,8 +251,9 @@ impl Command for SetPageSetup {
             margin_right: Some(old_setup.margin_right),
             margin_header: None,
             margin_footer: None,
-            gutter: None,
-            gutter_position: None,
+            gutter: old_gutter.or(self.gutter.map(|_| 0.0)),
+            gutter_position: old_gutter_position.or(self.gutter_position.map(|_| GutterPosition::default())),
+            mirror_margins: old_mirror_margins.or(self.mirror_margins.map(|_| false)),
         });
 
         Ok(CommandResult {
@@ -192,6 +265,7 @@ impl Command for SetPageSetup {
 
     fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
         let old_setup = &tree.document.page_setup;
+        let (gutter, gutter_position, mirror_margins) = self.binding_of(tree);
         Box::new(SetPageSetup {
             section_id: self.section_id,
             page_size_preset: Some(PageSizePreset::Custom),
@@ -204,8 +278,9 @@ impl Command for SetPageSetup {
             margin_right: Some(old_setup.margin_right),
             margin_header: None,
             margin_footer: None,
-            gutter: None,
-            gutter_position: None,
+            gutter,
+            gutter_position,
+            mirror_margins,
         })
     }
 
@@ -682,6 +757,7 @@ pub struct SectionProperties {
     pub column_spacing: f32,
     pub has_different_first_page: bool,
     pub has_different_odd_even: bool,
+    pub page_numbering: PageNumbering,
 }
 
 impl SectionProperties {
@@ -698,6 +774,7 @@ impl SectionProperties {
             column_spacing: section.page_setup.column_config.space,
             has_different_first_page: section.different_first_page,
             has_different_odd_even: section.different_odd_even,
+            page_numbering: section.page_setup.page_numbering,
         }
     }
 
@@ -894,6 +971,186 @@ impl Command for SetDifferentOddEven {
     }
 }
 
+// =============================================================================
+// SetWatermark Command
+// =============================================================================
+
+/// Set or remove a section's watermark
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetWatermark {
+    /// Section ID (None = first/default section)
+    pub section_id: Option<NodeId>,
+    /// The new watermark (None = remove)
+    pub watermark: Option<Watermark>,
+}
+
+impl SetWatermark {
+    /// Set a watermark on the first section
+    pub fn new(watermark: Watermark) -> Self {
+        Self {
+            section_id: None,
+            watermark: Some(watermark),
+        }
+    }
+
+    /// Remove the first section's watermark
+    pub fn remove() -> Self {
+        Self {
+            section_id: None,
+            watermark: None,
+        }
+    }
+
+    /// Target a specific section
+    pub fn for_section(mut self, section_id: NodeId) -> Self {
+        self.section_id = Some(section_id);
+        self
+    }
+
+    fn section_index(&self, tree: &DocumentTree) -> Option<usize> {
+        section_index(tree, self.section_id)
+    }
+}
+
+/// Find a section by ID, or the first section when no ID is given
+fn section_index(tree: &DocumentTree, section_id: Option<NodeId>) -> Option<usize> {
+    match section_id {
+        Some(id) => tree.sections.iter().position(|section| section.id() == id),
+        None => (!tree.sections.is_empty()).then_some(0),
+    }
+}
+
+impl Command for SetWatermark {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+
+        // A document without sections gets one covering all of it
+        let index = match (self.section_index(&new_tree), self.section_id) {
+            (Some(index), _) => index,
+            (None, None) => {
+                new_tree.add_section(Section::new());
+                0
+            }
+            (None, Some(id)) => return Err(EditError::InvalidCommand(format!("Section not found: {:?}", id))),
+        };
+        let section = &mut new_tree.sections[index];
+        let old_watermark = std::mem::replace(&mut section.watermark, self.watermark.clone());
+
+        let inverse = Box::new(SetWatermark {
+            section_id: Some(section.id()),
+            watermark: old_watermark,
+        });
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse,
+        })
+    }
+
+    fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
+        let section = self.section_index(tree).map(|index| &tree.sections[index]);
+        Box::new(SetWatermark {
+            section_id: section.map(|section| section.id()).or(self.section_id),
+            watermark: section.and_then(|section| section.watermark.clone()),
+        })
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        if self.watermark.is_some() {
+            "Set Watermark"
+        } else {
+            "Remove Watermark"
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+// =============================================================================
+// SetPageNumbering Command
+// =============================================================================
+
+/// Set a section's page number format and restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetPageNumbering {
+    /// Section ID (None = first/default section)
+    pub section_id: Option<NodeId>,
+    /// The new page numbering
+    pub page_numbering: PageNumbering,
+}
+
+impl SetPageNumbering {
+    /// Set the first section's page numbering
+    pub fn new(page_numbering: PageNumbering) -> Self {
+        Self {
+            section_id: None,
+            page_numbering,
+        }
+    }
+
+    /// Target a specific section
+    pub fn for_section(mut self, section_id: NodeId) -> Self {
+        self.section_id = Some(section_id);
+        self
+    }
+}
+
+impl Command for SetPageNumbering {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+
+        // A document without sections gets one covering all of it
+        let index = match (section_index(&new_tree, self.section_id), self.section_id) {
+            (Some(index), _) => index,
+            (None, None) => {
+                new_tree.add_section(Section::new());
+                0
+            }
+            (None, Some(id)) => return Err(EditError::InvalidCommand(format!("Section not found: {:?}", id))),
+        };
+        let section = &mut new_tree.sections[index];
+        let old_numbering = std::mem::replace(&mut section.page_setup.page_numbering, self.page_numbering);
+
+        let inverse = Box::new(SetPageNumbering {
+            section_id: Some(section.id()),
+            page_numbering: old_numbering,
+        });
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse,
+        })
+    }
+
+    fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
+        let section = section_index(tree, self.section_id).map(|index| &tree.sections[index]);
+        Box::new(SetPageNumbering {
+            section_id: section.map(|section| section.id()).or(self.section_id),
+            page_numbering: section.map(|section| section.page_setup.page_numbering).unwrap_or_default(),
+        })
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Set Page Numbering"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
 // =============================================================================
 // InsertPageNumber Command
 // =============================================================================
@@ -1275,6 +1532,28 @@ mod tests {
         assert!(result.tree.document.page_setup.width > result.tree.document.page_setup.height);
     }
 
+    #[test]
+    fn test_set_page_setup_binding_and_undo() {
+        let tree = create_test_tree();
+        let selection = Selection::collapsed(Position::new(
+            tree.document.children()[0],
+            0,
+        ));
+
+        let cmd = SetPageSetup::with_binding(36.0, GutterPosition::Left, true);
+        let result = cmd.apply(&tree, &selection).unwrap();
+        let setup = &result.tree.sections[0].page_setup;
+        assert_eq!(setup.gutter, 36.0);
+        assert!(setup.mirror_margins);
+        assert_eq!(setup.horizontal_margins_for_page(0), (setup.margins.left + 36.0, setup.margins.right));
+        assert_eq!(setup.horizontal_margins_for_page(1), (setup.margins.right, setup.margins.left + 36.0));
+
+        let undone = result.inverse.apply(&result.tree, &selection).unwrap();
+        let setup = &undone.tree.sections[0].page_setup;
+        assert_eq!(setup.gutter, 0.0);
+        assert!(!setup.mirror_margins);
+    }
+
     #[test]
     fn test_insert_page_number() {
         let tree = create_test_tree();
@@ -1436,4 +1715,18 @@ mod tests {
         assert_eq!(props.column_count, 2);
         assert!(props.has_different_first_page);
     }
+
+    #[test]
+    fn test_set_watermark_and_undo() {
+        let tree = create_test_tree();
+        let selection = Selection::collapsed(Position::new(tree.document.children()[0], 0));
+
+        let cmd = SetWatermark::new(Watermark::text("CONFIDENTIAL"));
+        let result = cmd.apply(&tree, &selection).unwrap();
+        assert_eq!(result.tree.sections.len(), 1);
+        assert_eq!(result.tree.sections[0].watermark, Some(Watermark::text("CONFIDENTIAL")));
+
+        let undone = result.inverse.apply(&result.tree, &selection).unwrap();
+        assert!(undone.tree.sections[0].watermark.is_none());
+    }
 }
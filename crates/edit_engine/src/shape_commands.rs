@@ -2,9 +2,9 @@
 
 use crate::{Command, CommandResult, EditError, Result};
 use doc_model::{
-    Dimension, DocumentTree, ImagePosition, Node, NodeId, NodeType, Position, Selection,
-    ShapeColor, ShapeEffects, ShapeFill, ShapeNode, ShapeProperties, ShapeStroke, ShapeType,
-    WrapType,
+    Dimension, DocumentTree, FreeformPath, ImagePosition, Node, NodeId, NodeType, Point, Position,
+    SegmentType, Selection, ShapeColor, ShapeEffects, ShapeFill, ShapeNode, ShapeProperties,
+    ShapeStroke, ShapeType, WrapType,
 };
 use serde::{Deserialize, Serialize};
 
@@ -118,6 +118,18 @@ impl InsertShape {
         }
     }
 
+    /// Create a new insert shape command for a freeform or polyline shape,
+    /// sized to its points
+    pub fn freeform(path: FreeformPath) -> Self {
+        let bounds = path.bounds();
+        Self {
+            properties: ShapeProperties::inline(bounds.x + bounds.width, bounds.y + bounds.height),
+            shape_type: path.into(),
+            name: None,
+            alt_text: None,
+        }
+    }
+
     /// Set the name
     pub fn with_name(mut self, name: impl Into<String>) -> Self {
         self.name = Some(name.into());
@@ -918,6 +930,102 @@ impl Command for UpdateShapeProperties {
 }
 
 // ============================================================================
+/// An edit points operation on a freeform shape
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ShapePointEdit {
+    /// Add a point on a segment at parameter `t` (0 to 1)
+    AddPoint { segment: usize, t: f32 },
+    /// Delete a point, joining its neighbors
+    DeletePoint { index: usize },
+    /// Move a point (and its attached control points)
+    MovePoint { index: usize, to: Point },
+    /// Move one of a curved segment's control points (1 or 2)
+    MoveControlPoint { segment: usize, control: u8, to: Point },
+    /// Make a segment straight or curved
+    SetSegmentType { segment: usize, segment_type: SegmentType },
+    /// Replace the whole geometry
+    SetGeometry(FreeformPath),
+}
+
+/// Edit the points of a freeform shape
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditShapePoints {
+    /// The shape node ID
+    pub shape_id: NodeId,
+    /// The edit to apply
+    pub edit: ShapePointEdit,
+}
+
+impl EditShapePoints {
+    pub fn new(shape_id: NodeId, edit: ShapePointEdit) -> Self {
+        Self { shape_id, edit }
+    }
+}
+
+impl Command for EditShapePoints {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+
+        let shape = new_tree
+            .get_shape_mut(self.shape_id)
+            .ok_or_else(|| EditError::InvalidCommand(format!("Shape not found: {:?}", self.shape_id)))?;
+        let old_path = FreeformPath::from_shape_type(&shape.shape_type)
+            .ok_or_else(|| EditError::InvalidCommand("Shape is not a freeform".to_string()))?;
+
+        let mut path = old_path.clone();
+        match &self.edit {
+            ShapePointEdit::AddPoint { segment, t } => path.insert_point(*segment, *t).map(|_| ()),
+            ShapePointEdit::DeletePoint { index } => path.delete_point(*index),
+            ShapePointEdit::MovePoint { index, to } => path.move_point(*index, *to),
+            ShapePointEdit::MoveControlPoint { segment, control, to } => {
+                path.move_control_point(*segment, *control, *to)
+            }
+            ShapePointEdit::SetSegmentType { segment, segment_type } => {
+                path.set_segment_type(*segment, *segment_type)
+            }
+            ShapePointEdit::SetGeometry(geometry) => {
+                path = geometry.clone();
+                Ok(())
+            }
+        }
+        .map_err(EditError::DocModel)?;
+        shape.shape_type = path.into();
+
+        let inverse = Box::new(EditShapePoints::new(
+            self.shape_id,
+            ShapePointEdit::SetGeometry(old_path),
+        ));
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse,
+        })
+    }
+
+    fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
+        match tree
+            .get_shape(self.shape_id)
+            .and_then(|shape| FreeformPath::from_shape_type(&shape.shape_type))
+        {
+            Some(path) => Box::new(EditShapePoints::new(self.shape_id, ShapePointEdit::SetGeometry(path))),
+            None => Box::new(self.clone()),
+        }
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Edit Points"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
 // Helper functions (reused from image_commands)
 // ============================================================================
 
@@ -1156,4 +1264,39 @@ mod tests {
         let updated = result.tree.get_shape(shape_id).unwrap();
         assert_eq!(updated.properties.wrap_type, WrapType::Square);
     }
+
+    #[test]
+    fn test_edit_shape_points() {
+        let (tree, para_id) = create_test_tree();
+        let selection = Selection::collapsed(Position::new(para_id, 0));
+
+        let path = FreeformPath::polyline(vec![Point::new(0.0, 0.0), Point::new(40.0, 20.0)]);
+        let result = InsertShape::freeform(path.clone()).apply(&tree, &selection).unwrap();
+        let tree = result.tree;
+        let shape = tree.shapes().next().unwrap();
+        assert_eq!(shape.properties.width, Dimension::points(40.0));
+        let shape_id = shape.id();
+
+        let add = EditShapePoints::new(shape_id, ShapePointEdit::AddPoint { segment: 0, t: 0.5 });
+        let result = add.apply(&tree, &selection).unwrap();
+        let curve = EditShapePoints::new(
+            shape_id,
+            ShapePointEdit::SetSegmentType { segment: 1, segment_type: SegmentType::Curved },
+        );
+        let result = curve.apply(&result.tree, &selection).unwrap();
+
+        let edited = FreeformPath::from_shape_type(&result.tree.get_shape(shape_id).unwrap().shape_type).unwrap();
+        assert_eq!(edited.points[1], Point::new(20.0, 10.0));
+        assert!(edited.has_curves());
+
+        // Undo restores the previous geometry
+        let undone = result.inverse.apply(&result.tree, &selection).unwrap();
+        let restored = FreeformPath::from_shape_type(&undone.tree.get_shape(shape_id).unwrap().shape_type).unwrap();
+        assert_eq!(restored.points.len(), 3);
+        assert!(!restored.has_curves());
+
+        // Out of range edits fail
+        let delete = EditShapePoints::new(shape_id, ShapePointEdit::DeletePoint { index: 5 });
+        assert!(delete.apply(&undone.tree, &selection).is_err());
+    }
 }
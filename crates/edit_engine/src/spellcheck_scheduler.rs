@@ -0,0 +1,296 @@
+//! Background spellchecking
+//!
+//! Checking a whole document on every edit is too slow for large files, so
+//! only the paragraphs an edit touched are checked again. The scheduler
+//! queues paragraphs reported changed and hands them out as
+//! `SpellcheckJob`s, which capture the paragraph's text so they can run off
+//! the editing thread. Results are cached against the paragraph's revision:
+//! a result for a paragraph that has changed again since its job was taken
+//! is dropped instead of showing stale squiggles.
+
+use crate::{proofing_ranges, ChangedParagraphs, DocumentSpellingError, ProofingRange, SpellcheckResults};
+use doc_model::{DocumentTree, Node, NodeId};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use text_engine::{IgnoreRules, Language, LanguageDetector, SpellChecker};
+
+/// A paragraph's text captured for checking
+#[derive(Debug, Clone)]
+pub struct SpellcheckJob {
+    /// The paragraph to check
+    pub para_id: NodeId,
+    /// Revision of the paragraph the text was captured at
+    pub revision: u64,
+    /// Paragraph text
+    text: String,
+    /// Ranges with the same proofing settings
+    ranges: Vec<ProofingRange>,
+}
+
+impl SpellcheckJob {
+    /// Capture a paragraph's text and proofing settings
+    pub fn capture(tree: &DocumentTree, para_id: NodeId, revision: u64) -> Option<Self> {
+        let para = tree.get_paragraph(para_id)?;
+        let text = para
+            .children()
+            .iter()
+            .filter_map(|&id| tree.get_run(id))
+            .map(|run| run.text.as_str())
+            .collect();
+
+        Some(Self {
+            para_id,
+            revision,
+            text,
+            ranges: proofing_ranges(tree, para_id),
+        })
+    }
+
+    /// Check the paragraph's spelling
+    ///
+    /// Text marked "do not check spelling" is skipped. Text without a
+    /// proofing language is checked in its detected language, or `default`.
+    pub fn run(
+        &self,
+        checker: &dyn SpellChecker,
+        detector: &LanguageDetector,
+        default: Language,
+        rules: &IgnoreRules,
+    ) -> SpellcheckUpdate {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut errors = Vec::new();
+        let mut words_checked = 0;
+
+        for range in self.ranges.iter().filter(|range| !range.no_proof) {
+            let text: String = chars[range.start.min(chars.len())..range.end.min(chars.len())]
+                .iter()
+                .collect();
+            let language = detector.resolve(range.language.as_deref(), &self.text, default);
+            words_checked += text.split_whitespace().count();
+            errors.extend(checker.check_text(&text, language, rules).into_iter().map(|e| {
+                DocumentSpellingError::new(
+                    self.para_id,
+                    range.start + e.start,
+                    range.start + e.end,
+                    e.word,
+                    e.suggestions,
+                )
+            }));
+        }
+
+        SpellcheckUpdate {
+            para_id: self.para_id,
+            revision: self.revision,
+            errors,
+            words_checked,
+        }
+    }
+}
+
+/// Spelling errors found in one paragraph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpellcheckUpdate {
+    /// The checked paragraph
+    pub para_id: NodeId,
+    /// Revision of the paragraph that was checked
+    pub revision: u64,
+    /// Errors found, with paragraph character offsets
+    pub errors: Vec<DocumentSpellingError>,
+    /// Number of words checked
+    pub words_checked: usize,
+}
+
+/// Schedules spellchecking of changed paragraphs and caches the results
+#[derive(Debug, Default)]
+pub struct SpellcheckScheduler {
+    /// Current revision of each known paragraph
+    revisions: HashMap<NodeId, u64>,
+    /// Latest result for each paragraph checked at its current revision
+    cache: HashMap<NodeId, SpellcheckUpdate>,
+    /// Paragraphs waiting to be checked, in the order they changed
+    queue: VecDeque<NodeId>,
+    /// Paragraphs removed since they were last taken
+    removed: Vec<NodeId>,
+    /// Last revision handed out
+    last_revision: u64,
+}
+
+impl SpellcheckScheduler {
+    /// Create a scheduler with nothing checked
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue every paragraph of the document, e.g. when it is opened or the
+    /// dictionaries change
+    pub fn mark_all(&mut self, tree: &DocumentTree) {
+        let removed: Vec<NodeId> = self
+            .revisions
+            .keys()
+            .filter(|id| tree.get_paragraph(**id).is_none())
+            .copied()
+            .collect();
+        for para_id in removed {
+            self.remove(para_id);
+        }
+
+        for para in tree.paragraphs() {
+            self.mark(para.id());
+        }
+    }
+
+    /// Queue the paragraphs an edit changed
+    ///
+    /// Takes the same changes as incremental layout, so callers taking
+    /// `EditingEngine::take_changed_paragraphs` pass them to both.
+    pub fn mark_changed(&mut self, tree: &DocumentTree, changed: &ChangedParagraphs) {
+        if changed.is_all() {
+            self.mark_all(tree);
+            return;
+        }
+        for &para_id in changed.paragraphs() {
+            if tree.get_paragraph(para_id).is_some() {
+                self.mark(para_id);
+            } else {
+                self.remove(para_id);
+            }
+        }
+    }
+
+    fn mark(&mut self, para_id: NodeId) {
+        self.last_revision += 1;
+        self.revisions.insert(para_id, self.last_revision);
+        self.cache.remove(&para_id);
+        if !self.queue.contains(&para_id) {
+            self.queue.push_back(para_id);
+        }
+    }
+
+    fn remove(&mut self, para_id: NodeId) {
+        if self.revisions.remove(&para_id).is_some() {
+            self.removed.push(para_id);
+        }
+        self.cache.remove(&para_id);
+        self.queue.retain(|id| *id != para_id);
+    }
+
+    /// Check if any paragraphs are waiting to be checked
+    pub fn has_pending(&self) -> bool {
+        !self.queue.is_empty()
+    }
+
+    /// Take up to `max` queued paragraphs as jobs
+    pub fn next_jobs(&mut self, tree: &DocumentTree, max: usize) -> Vec<SpellcheckJob> {
+        let mut jobs = Vec::new();
+        while jobs.len() < max {
+            let Some(para_id) = self.queue.pop_front() else {
+                break;
+            };
+            let revision = self.revisions.get(&para_id).copied().unwrap_or_default();
+            match SpellcheckJob::capture(tree, para_id, revision) {
+                Some(job) => jobs.push(job),
+                None => self.remove(para_id),
+            }
+        }
+        jobs
+    }
+
+    /// Record a finished job's result
+    ///
+    /// Returns false, dropping the result, if the paragraph changed or was
+    /// removed after the job was taken.
+    pub fn complete(&mut self, update: SpellcheckUpdate) -> bool {
+        if self.revisions.get(&update.para_id) != Some(&update.revision) {
+            return false;
+        }
+        self.cache.insert(update.para_id, update);
+        true
+    }
+
+    /// Check queued paragraphs on this thread and record the results
+    ///
+    /// Returns the updates to show, in the order they were checked.
+    pub fn run_pending(
+        &mut self,
+        tree: &DocumentTree,
+        max: usize,
+        checker: &dyn SpellChecker,
+        default: Language,
+    ) -> Vec<SpellcheckUpdate> {
+        let detector = LanguageDetector::new();
+        let rules = IgnoreRules::default();
+        self.next_jobs(tree, max)
+            .into_iter()
+            .map(|job| job.run(checker, &detector, default, &rules))
+            .filter(|update| self.complete(update.clone()))
+            .collect()
+    }
+
+    /// Get the cached errors for a paragraph
+    pub fn paragraph_errors(&self, para_id: NodeId) -> &[DocumentSpellingError] {
+        self.cache.get(&para_id).map(|update| update.errors.as_slice()).unwrap_or_default()
+    }
+
+    /// Take the paragraphs removed since the last call, whose squiggles
+    /// should be cleared
+    pub fn take_removed(&mut self) -> Vec<NodeId> {
+        std::mem::take(&mut self.removed)
+    }
+
+    /// Collect the cached errors in document order
+    pub fn results(&self, tree: &DocumentTree) -> SpellcheckResults {
+        let mut errors = Vec::new();
+        let mut words_checked = 0;
+        for para in tree.paragraphs() {
+            if let Some(update) = self.cache.get(&para.id()) {
+                errors.extend(update.errors.iter().cloned());
+                words_checked += update.words_checked;
+            }
+        }
+        SpellcheckResults::from_errors(errors, words_checked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::{Paragraph, Run};
+    use text_engine::DictionarySpellChecker;
+
+    #[test]
+    fn test_scheduler_rechecks_only_changed_paragraphs() {
+        let mut tree = DocumentTree::new();
+        let mut para_ids = Vec::new();
+        for text in ["the test", "teh term", "a test"] {
+            let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+            tree.insert_run(Run::new(text), para_id, None).unwrap();
+            para_ids.push(para_id);
+        }
+        let checker = DictionarySpellChecker::new();
+
+        let mut scheduler = SpellcheckScheduler::new();
+        scheduler.mark_changed(&tree, &ChangedParagraphs::all());
+        let updates = scheduler.run_pending(&tree, 10, &checker, Language::EnUs);
+        assert_eq!(updates.len(), 3);
+        assert!(!scheduler.has_pending());
+        assert_eq!(scheduler.paragraph_errors(para_ids[1])[0].word, "teh");
+
+        // A job taken before a further edit is stale when it finishes
+        let mut changed = ChangedParagraphs::none();
+        changed.record(Some(vec![para_ids[1]]));
+        scheduler.mark_changed(&tree, &changed);
+        let stale = scheduler.next_jobs(&tree, 10);
+        assert_eq!(stale.len(), 1);
+        scheduler.mark_changed(&tree, &changed);
+        let detector = LanguageDetector::new();
+        let result = stale[0].run(&checker, &detector, Language::EnUs, &IgnoreRules::default());
+        assert!(!scheduler.complete(result));
+        assert!(scheduler.paragraph_errors(para_ids[1]).is_empty());
+
+        let updates = scheduler.run_pending(&tree, 10, &checker, Language::EnUs);
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].para_id, para_ids[1]);
+        assert_eq!(scheduler.results(&tree).errors.len(), 1);
+    }
+}
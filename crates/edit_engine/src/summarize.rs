@@ -0,0 +1,373 @@
+//! AutoSummarize
+//!
+//! Extractive summarization: every body sentence is scored by how frequent
+//! its content words are across the document, with a bonus for words that
+//! appear in headings and for the opening sentence of each section. The top
+//! sentences, kept in document order, form the summary.
+//!
+//! `InsertExecutiveSummary` puts the summary at the top of the document
+//! under its own heading. That section is ignored when summarizing again.
+
+use crate::{outline_level, Command, CommandResult, EditError, MacroStep, Result};
+use doc_model::{DocumentTree, Node, NodeId, Paragraph, Run, Selection, StyleId};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Words that carry no topic
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "all", "any", "can", "had", "her", "was",
+    "one", "our", "out", "has", "have", "his", "how", "its", "may", "new", "now", "see", "who",
+    "did", "get", "him", "let", "she", "too", "use", "that", "with", "they", "this", "from",
+    "will", "been", "were", "what", "when", "your", "which", "their", "there", "these", "those",
+    "would", "could", "should", "about", "into", "than", "then", "them", "also", "more", "most",
+    "some", "such", "only", "over", "each", "other", "very", "just", "where", "while",
+];
+
+/// Sentences shorter than this many words are never picked
+const MIN_SENTENCE_WORDS: usize = 4;
+
+/// Score bonus per heading word in a sentence, relative to its frequency score
+const HEADING_WORD_BONUS: f32 = 0.5;
+
+/// Score bonus for the first sentence after a heading
+const SECTION_LEAD_BONUS: f32 = 0.25;
+
+/// Default heading of the inserted summary
+pub const DEFAULT_SUMMARY_HEADING: &str = "Executive Summary";
+
+/// How long the summary should be
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum SummaryLength {
+    /// A fixed number of sentences
+    Sentences(usize),
+    /// A percentage of the document's sentences
+    Percent(u8),
+}
+
+impl Default for SummaryLength {
+    fn default() -> Self {
+        Self::Percent(25)
+    }
+}
+
+impl SummaryLength {
+    /// Number of sentences to keep out of `total`
+    pub fn sentence_count(&self, total: usize) -> usize {
+        match *self {
+            Self::Sentences(count) => count.min(total),
+            Self::Percent(percent) if total > 0 => {
+                (total * usize::from(percent.min(100))).div_ceil(100).max(1)
+            }
+            Self::Percent(_) => 0,
+        }
+    }
+}
+
+/// Summary options
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryOptions {
+    /// Target length
+    pub length: SummaryLength,
+    /// Heading of the inserted summary section
+    pub heading: String,
+}
+
+impl Default for SummaryOptions {
+    fn default() -> Self {
+        Self {
+            length: SummaryLength::default(),
+            heading: DEFAULT_SUMMARY_HEADING.to_string(),
+        }
+    }
+}
+
+impl SummaryOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the target length
+    pub fn with_length(mut self, length: SummaryLength) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Set the summary heading
+    pub fn with_heading(mut self, heading: impl Into<String>) -> Self {
+        self.heading = heading.into();
+        self
+    }
+}
+
+/// A sentence picked for the summary
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummarySentence {
+    /// Paragraph containing the sentence
+    pub para_id: NodeId,
+    /// Start character offset in the paragraph
+    pub start: usize,
+    /// End character offset in the paragraph (exclusive)
+    pub end: usize,
+    /// The sentence
+    pub text: String,
+    /// Relevance score
+    pub score: f32,
+}
+
+/// Result of summarizing a document
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+    /// Picked sentences in document order
+    pub sentences: Vec<SummarySentence>,
+    /// The summary as one string
+    pub text: String,
+}
+
+impl Summary {
+    /// Summarize a document
+    pub fn compute(tree: &DocumentTree, options: &SummaryOptions) -> Self {
+        let mut heading_words = HashSet::new();
+        let mut candidates = Vec::new();
+        let mut in_summary_section = false;
+        let mut section_start = true;
+
+        for para in tree.paragraphs() {
+            let text = paragraph_text(tree, para);
+            if outline_level(tree, para).is_some() {
+                in_summary_section = text.trim().eq_ignore_ascii_case(options.heading.trim());
+                if !in_summary_section {
+                    heading_words.extend(content_words(&text));
+                }
+                section_start = true;
+                continue;
+            }
+            if in_summary_section {
+                continue;
+            }
+
+            let mut offset = 0;
+            for sentence in text.split_sentence_bounds() {
+                let (start, len) = (offset, sentence.chars().count());
+                offset += len;
+                let trimmed = sentence.trim_end();
+                if trimmed.unicode_words().count() < MIN_SENTENCE_WORDS {
+                    continue;
+                }
+                candidates.push((
+                    SummarySentence {
+                        para_id: para.id(),
+                        start,
+                        end: start + trimmed.chars().count(),
+                        text: trimmed.trim_start().to_string(),
+                        score: 0.0,
+                    },
+                    std::mem::take(&mut section_start),
+                ));
+            }
+        }
+
+        let mut frequencies: HashMap<String, usize> = HashMap::new();
+        for (sentence, _) in &candidates {
+            for word in content_words(&sentence.text) {
+                *frequencies.entry(word).or_insert(0) += 1;
+            }
+        }
+        let max_frequency = frequencies.values().copied().max().unwrap_or(1) as f32;
+
+        for (sentence, section_lead) in &mut candidates {
+            let words = content_words(&sentence.text);
+            if words.is_empty() {
+                continue;
+            }
+            let frequency: f32 = words
+                .iter()
+                .map(|w| frequencies.get(w).copied().unwrap_or(0) as f32 / max_frequency)
+                .sum();
+            let in_headings = words.iter().filter(|w| heading_words.contains(*w)).count() as f32;
+            sentence.score = (frequency + HEADING_WORD_BONUS * in_headings) / words.len() as f32
+                + if *section_lead { SECTION_LEAD_BONUS } else { 0.0 };
+        }
+
+        let keep = options.length.sentence_count(candidates.len());
+        let mut ranked: Vec<usize> = (0..candidates.len()).collect();
+        ranked.sort_by(|&a, &b| candidates[b].0.score.total_cmp(&candidates[a].0.score).then(a.cmp(&b)));
+        ranked.truncate(keep);
+        ranked.sort_unstable();
+
+        let sentences: Vec<SummarySentence> =
+            ranked.into_iter().map(|i| candidates[i].0.clone()).collect();
+        let text = sentences.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join(" ");
+        Self { sentences, text }
+    }
+}
+
+fn paragraph_text(tree: &DocumentTree, para: &Paragraph) -> String {
+    para.children()
+        .iter()
+        .filter_map(|&id| tree.get_run(id))
+        .map(|run| run.text.as_str())
+        .collect()
+}
+
+/// Lowercase words of a text, without stop words and short words
+fn content_words(text: &str) -> Vec<String> {
+    text.unicode_words()
+        .map(str::to_lowercase)
+        .filter(|w| w.chars().count() > 2 && !STOP_WORDS.contains(&w.as_str()))
+        .collect()
+}
+
+// =============================================================================
+// Insert Executive Summary Command
+// =============================================================================
+
+/// Insert a summary section at the top of the document
+///
+/// A summary section inserted earlier is left in place and skipped when
+/// summarizing, so running this again adds a fresh summary above it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InsertExecutiveSummary {
+    /// Summary options
+    pub options: SummaryOptions,
+}
+
+impl InsertExecutiveSummary {
+    pub fn new(options: SummaryOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl Command for InsertExecutiveSummary {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let summary = Summary::compute(tree, &self.options);
+        if summary.sentences.is_empty() {
+            return Err(EditError::InvalidCommand("Nothing to summarize".to_string()));
+        }
+
+        let mut new_tree = tree.clone();
+        let root_id = new_tree.root_id();
+
+        let heading_id = new_tree.insert_paragraph(
+            Paragraph::with_paragraph_style(StyleId::new("Heading1")),
+            root_id,
+            Some(0),
+        )?;
+        new_tree.insert_run(Run::new(self.options.heading.clone()), heading_id, None)?;
+
+        let body_id = new_tree.insert_paragraph(Paragraph::new(), root_id, Some(1))?;
+        new_tree.insert_run(Run::new(summary.text), body_id, None)?;
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse: Box::new(RemoveSummary {
+                paragraphs: vec![heading_id, body_id],
+                options: self.options.clone(),
+            }),
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        // Proper inverse created in apply()
+        Box::new(self.clone())
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn macro_step(&self) -> Option<MacroStep> {
+        MacroStep::command("insertExecutiveSummary", self)
+    }
+
+    fn display_name(&self) -> &str {
+        "Insert Executive Summary"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+/// Remove an inserted summary section (for undo)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RemoveSummary {
+    paragraphs: Vec<NodeId>,
+    options: SummaryOptions,
+}
+
+impl Command for RemoveSummary {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+        for &para_id in &self.paragraphs {
+            new_tree.remove_paragraph(para_id)?;
+        }
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse: Box::new(InsertExecutiveSummary::new(self.options.clone())),
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        Box::new(InsertExecutiveSummary::new(self.options.clone()))
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Remove Executive Summary"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_paragraph(tree: &mut DocumentTree, text: &str, style: &str) {
+        let para_id = tree
+            .insert_paragraph(Paragraph::with_paragraph_style(StyleId::new(style)), tree.root_id(), None)
+            .unwrap();
+        tree.insert_run(Run::new(text), para_id, None).unwrap();
+    }
+
+    #[test]
+    fn test_summary_prefers_frequent_and_heading_words() {
+        let mut tree = DocumentTree::new();
+        add_paragraph(&mut tree, "Solar Energy", "Heading1");
+        add_paragraph(
+            &mut tree,
+            "Solar panels turn sunlight into energy for homes. The weather was pleasant on Tuesday afternoon. \
+             Cheap solar energy lowers household energy bills.",
+            "Normal",
+        );
+
+        let summary = Summary::compute(&tree, &SummaryOptions::new().with_length(SummaryLength::Sentences(2)));
+        assert_eq!(summary.sentences.len(), 2);
+        assert!(summary.text.starts_with("Solar panels"));
+        assert!(!summary.text.contains("weather"));
+
+        let result = InsertExecutiveSummary::default()
+            .apply(&tree, &Selection::default())
+            .unwrap();
+        let first = result.tree.paragraphs().next().unwrap();
+        assert_eq!(paragraph_text(&result.tree, first), DEFAULT_SUMMARY_HEADING);
+        // The inserted section is skipped when summarizing again
+        assert_eq!(Summary::compute(&result.tree, &SummaryOptions::new()), Summary::compute(&tree, &SummaryOptions::new()));
+
+        let undone = result.inverse.apply(&result.tree, &result.selection).unwrap();
+        assert_eq!(undone.tree.paragraphs().count(), 2);
+    }
+}
@@ -12,13 +12,14 @@
 //! - SetCellProperties: Configure cell properties including vertical alignment and text direction
 //! - InsertNestedTable: Insert table within a cell
 //! - SetTableAutoFit: Configure auto-fit mode
+//! - SetTableStyle: Apply a table style and its look options
 
 use crate::{Command, CommandResult, Result};
 use doc_model::{
     CellBorders, CellPadding, CellProperties, CellVerticalAlign, CellTextDirection,
     DocumentTree, GridColumn, HorizontalMerge, Node, NodeId, Paragraph, Position, Selection,
-    Table, TableAutoFitMode, TableBorders, TableCell, TableGrid, TableProperties, TableRow,
-    TableWidth, VerticalMerge, MAX_TABLE_NESTING_DEPTH,
+    StyleId, StyleType, Table, TableAutoFitMode, TableBorders, TableCell, TableGrid, TableLayoutMode,
+    TableLook, TableProperties, TableRow, TableWidth, VerticalMerge, MAX_TABLE_NESTING_DEPTH,
 };
 use serde::{Deserialize, Serialize};
 
@@ -1627,15 +1628,37 @@ impl Command for SetCellPadding {
 // =============================================================================
 
 /// Set auto-fit mode for a table
+///
+/// With column widths (computed by layout for the new mode) the table grid
+/// is rewritten to them, and the table's preferred width and layout
+/// algorithm follow the mode.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SetTableAutoFit {
     pub table_id: NodeId,
     pub mode: TableAutoFitMode,
+    pub column_widths: Option<Vec<f32>>,
+    #[serde(skip)]
+    restore_sizing: Option<(TableGrid, Option<TableWidth>, TableLayoutMode)>,
 }
 
 impl SetTableAutoFit {
     pub fn new(table_id: NodeId, mode: TableAutoFitMode) -> Self {
-        Self { table_id, mode }
+        Self { table_id, mode, column_widths: None, restore_sizing: None }
+    }
+
+    /// Recompute the grid with these column widths
+    pub fn with_column_widths(mut self, widths: Vec<f32>) -> Self {
+        self.column_widths = Some(widths);
+        self
+    }
+
+    fn inverse_for(&self, table: &Table) -> SetTableAutoFit {
+        SetTableAutoFit {
+            table_id: self.table_id,
+            mode: table.properties.auto_fit_mode,
+            column_widths: None,
+            restore_sizing: Some((table.grid.clone(), table.properties.width, table.properties.table_layout)),
+        }
     }
 }
 
@@ -1648,13 +1671,27 @@ impl Command for SetTableAutoFit {
                 format!("Table not found: {:?}", self.table_id)
             ))?;
 
-        let previous = table.properties.auto_fit_mode;
+        let inverse = Box::new(self.inverse_for(table));
         table.properties.auto_fit_mode = self.mode;
 
-        let inverse = Box::new(SetTableAutoFit {
-            table_id: self.table_id,
-            mode: previous,
-        });
+        if let Some((grid, width, layout)) = &self.restore_sizing {
+            table.grid = grid.clone();
+            table.properties.width = *width;
+            table.properties.table_layout = *layout;
+        } else if let Some(widths) = &self.column_widths {
+            for (column, &width) in table.grid.columns.iter_mut().zip(widths) {
+                *column = GridColumn::fixed(width);
+            }
+            table.properties.width = match self.mode {
+                TableAutoFitMode::AutoFitContent => None,
+                TableAutoFitMode::AutoFitWindow => Some(TableWidth::percent(100.0)),
+                TableAutoFitMode::FixedWidth => Some(TableWidth::fixed(widths.iter().sum())),
+            };
+            table.properties.table_layout = match self.mode {
+                TableAutoFitMode::FixedWidth => TableLayoutMode::Fixed,
+                _ => TableLayoutMode::Auto,
+            };
+        }
 
         Ok(CommandResult {
             tree: new_tree,
@@ -1664,14 +1701,10 @@ impl Command for SetTableAutoFit {
     }
 
     fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
-        let previous = tree.get_table(self.table_id)
-            .map(|t| t.properties.auto_fit_mode)
-            .unwrap_or_default();
-
-        Box::new(SetTableAutoFit {
-            table_id: self.table_id,
-            mode: previous,
-        })
+        match tree.get_table(self.table_id) {
+            Some(table) => Box::new(self.inverse_for(table)),
+            None => Box::new(SetTableAutoFit::new(self.table_id, TableAutoFitMode::default())),
+        }
     }
 
     fn transform_selection(&self, selection: &Selection) -> Selection {
@@ -1691,6 +1724,88 @@ impl Command for SetTableAutoFit {
     }
 }
 
+// =============================================================================
+// SetTableStyle Command
+// =============================================================================
+
+/// Apply a table style (or clear it) and set which of its regions are used
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetTableStyle {
+    pub table_id: NodeId,
+    pub style_id: Option<StyleId>,
+    pub look: TableLook,
+}
+
+impl SetTableStyle {
+    pub fn new(table_id: NodeId, style_id: Option<StyleId>, look: TableLook) -> Self {
+        Self { table_id, style_id, look }
+    }
+
+    fn inverse_for(&self, table: &Table) -> SetTableStyle {
+        SetTableStyle {
+            table_id: self.table_id,
+            style_id: table.properties.style_id.clone(),
+            look: table.properties.look,
+        }
+    }
+}
+
+impl Command for SetTableStyle {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        if let Some(style_id) = &self.style_id {
+            match tree.style_registry().get(style_id) {
+                Some(style) if style.style_type == StyleType::Table => {}
+                Some(_) => {
+                    return Err(crate::EditError::InvalidCommand(
+                        format!("Not a table style: {}", style_id)
+                    ))
+                }
+                None => {
+                    return Err(crate::EditError::InvalidCommand(
+                        format!("Style not found: {}", style_id)
+                    ))
+                }
+            }
+        }
+
+        let mut new_tree = tree.clone();
+
+        let table = new_tree.get_table_mut(self.table_id)
+            .ok_or_else(|| crate::EditError::InvalidCommand(
+                format!("Table not found: {:?}", self.table_id)
+            ))?;
+
+        let inverse = Box::new(self.inverse_for(table));
+        table.properties.style_id = self.style_id.clone();
+        table.properties.look = self.look;
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse,
+        })
+    }
+
+    fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
+        match tree.get_table(self.table_id) {
+            Some(table) => Box::new(self.inverse_for(table)),
+            None => Box::new(SetTableStyle::new(self.table_id, None, TableLook::default())),
+        }
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Set Table Style"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
 // =============================================================================
 // InsertNestedTable Command
 // =============================================================================
@@ -2120,6 +2235,58 @@ mod tests {
 
         let table = result.tree.get_table(table_id).unwrap();
         assert_eq!(table.properties.auto_fit_mode, TableAutoFitMode::AutoFitWindow);
+
+        // Switching to fixed freezes the computed widths into the grid
+        let cmd = SetTableAutoFit::new(table_id, TableAutoFitMode::FixedWidth)
+            .with_column_widths(vec![120.0, 80.0]);
+        let fixed = cmd.apply(&result.tree, &selection).unwrap();
+        let table = fixed.tree.get_table(table_id).unwrap();
+        assert_eq!(table.grid.columns, vec![GridColumn::fixed(120.0), GridColumn::fixed(80.0)]);
+        assert_eq!(table.properties.width, Some(TableWidth::fixed(200.0)));
+        assert_eq!(table.properties.table_layout, TableLayoutMode::Fixed);
+
+        // Undo restores the auto columns
+        let undone = fixed.inverse.apply(&fixed.tree, &selection).unwrap();
+        let table = undone.tree.get_table(table_id).unwrap();
+        assert_eq!(table.properties.auto_fit_mode, TableAutoFitMode::AutoFitWindow);
+        assert_eq!(table.grid.columns, vec![GridColumn::auto(); 2]);
+        assert_eq!(table.properties.table_layout, TableLayoutMode::Auto);
+    }
+
+    #[test]
+    fn test_set_table_style() {
+        let mut tree = DocumentTree::new();
+
+        let table_id = tree.insert_table(Table::with_grid(doc_model::TableGrid::new(2)), None).unwrap();
+        let mut cells = Vec::new();
+        for _ in 0..3 {
+            let row_id = tree.insert_table_row(TableRow::new(), table_id, None).unwrap();
+            for _ in 0..2 {
+                cells.push(tree.insert_table_cell(TableCell::new(), row_id, None).unwrap());
+            }
+        }
+        tree.get_table_cell_mut(cells[5]).unwrap().properties.shading = Some("#FF0000".to_string());
+
+        let selection = Selection::default();
+        let cmd = SetTableStyle::new(table_id, Some(StyleId::new("GridTable4-Accent1")), TableLook::default());
+        let result = cmd.apply(&tree, &selection).unwrap();
+
+        let header = result.tree.effective_cell_format(cells[1]).unwrap();
+        assert_eq!(header.shading.as_deref(), Some("#4472C4"));
+        assert_eq!(header.character_props.bold, Some(true));
+        let banded = result.tree.effective_cell_format(cells[3]).unwrap();
+        assert_eq!(banded.shading.as_deref(), Some("#D9E2F3"));
+        assert_eq!(result.tree.effective_cell_format(cells[4]).unwrap().character_props.bold, Some(true));
+        // Direct cell shading wins over the style
+        assert_eq!(result.tree.effective_cell_format(cells[5]).unwrap().shading.as_deref(), Some("#FF0000"));
+
+        // Only table styles can be applied
+        let bad = SetTableStyle::new(table_id, Some(StyleId::new("Normal")), TableLook::default());
+        assert!(bad.apply(&tree, &selection).is_err());
+
+        let undone = result.inverse.apply(&result.tree, &selection).unwrap();
+        assert_eq!(undone.tree.get_table(table_id).unwrap().properties.style_id, None);
+        assert_eq!(undone.tree.effective_cell_format(cells[1]).unwrap().shading, None);
     }
 
     #[test]
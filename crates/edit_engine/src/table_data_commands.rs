@@ -0,0 +1,739 @@
+//! Table data commands
+//!
+//! Commands that work on the data held in a table rather than its layout:
+//! - SortTable: Sort rows by one or more columns (text, number or date keys)
+//! - ConvertTextToTable: Turn delimited paragraphs into a table
+//! - ConvertTableToText: Turn a table back into delimited paragraphs
+
+use crate::{Command, CommandResult, EditError, Result};
+use chrono::NaiveDate;
+use doc_model::{
+    CellBorders, CellPadding, CellProperties, DocumentTree, Node, NodeId, Paragraph, Position,
+    Run, Selection, Table, TableBorders, TableCell, TableGrid, TableProperties, TableRow,
+    TableWidth, VerticalMerge,
+};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Default width of a table created from text, in points (6.5 inches)
+const DEFAULT_TABLE_WIDTH: f32 = 468.0;
+
+/// Date formats recognized by date sort keys
+const DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%d",
+    "%m/%d/%Y",
+    "%m/%d/%y",
+    "%d.%m.%Y",
+    "%B %d, %Y",
+    "%b %d, %Y",
+    "%d %B %Y",
+    "%d %b %Y",
+];
+
+// =============================================================================
+// Sort Keys
+// =============================================================================
+
+/// How cell text is compared when sorting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SortKeyType {
+    /// Case-insensitive text
+    #[default]
+    Text,
+    /// The first number in the cell
+    Number,
+    /// A date in one of the common formats
+    Date,
+}
+
+/// Sort direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// One level of a table sort
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SortKey {
+    /// Grid column whose cells are compared
+    pub column: usize,
+    /// How the cell text is interpreted
+    pub key_type: SortKeyType,
+    /// Sort direction
+    pub order: SortOrder,
+}
+
+impl SortKey {
+    /// Sort ascending by a column
+    pub fn new(column: usize, key_type: SortKeyType) -> Self {
+        Self {
+            column,
+            key_type,
+            order: SortOrder::Ascending,
+        }
+    }
+
+    /// Sort descending instead
+    pub fn descending(mut self) -> Self {
+        self.order = SortOrder::Descending;
+        self
+    }
+
+    /// Compare two cell texts under this key
+    ///
+    /// Cells that don't parse as the key type sort last in either direction.
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        let directed = |ordering: Ordering| match self.order {
+            SortOrder::Ascending => ordering,
+            SortOrder::Descending => ordering.reverse(),
+        };
+        match self.key_type {
+            SortKeyType::Text => directed(a.to_lowercase().cmp(&b.to_lowercase())),
+            SortKeyType::Number => compare_parsed(parse_number(a), parse_number(b), |x, y| {
+                directed(x.partial_cmp(y).unwrap_or(Ordering::Equal))
+            }),
+            SortKeyType::Date => compare_parsed(parse_date(a), parse_date(b), |x, y| directed(x.cmp(y))),
+        }
+    }
+}
+
+fn compare_parsed<T>(a: Option<T>, b: Option<T>, cmp: impl Fn(&T, &T) -> Ordering) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => cmp(&a, &b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Parse the first number in some text, ignoring currency signs and
+/// thousands separators
+fn parse_number(text: &str) -> Option<f64> {
+    let chars: Vec<char> = text.chars().collect();
+    let start = chars.iter().position(|c| c.is_ascii_digit())?;
+    let negative = start > 0 && chars[start - 1] == '-'
+        || start > 1 && chars[start - 1] == '.' && chars[start - 2] == '-';
+    let start = if start > 0 && chars[start - 1] == '.' { start - 1 } else { start };
+
+    let mut number = String::new();
+    if negative {
+        number.push('-');
+    }
+    for &c in &chars[start..] {
+        match c {
+            '0'..='9' | '.' => number.push(c),
+            ',' => {}
+            _ => break,
+        }
+    }
+    number.trim_end_matches('.').parse().ok()
+}
+
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    let text = text.trim();
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+}
+
+// =============================================================================
+// Helpers
+// =============================================================================
+
+fn paragraph_runs(tree: &DocumentTree, para_id: NodeId) -> Vec<&Run> {
+    tree.get_paragraph(para_id)
+        .map(|para| para.children().iter().filter_map(|&id| tree.get_run(id)).collect())
+        .unwrap_or_default()
+}
+
+fn cell_text(tree: &DocumentTree, cell: &TableCell) -> String {
+    cell.children()
+        .iter()
+        .map(|&para_id| paragraph_runs(tree, para_id).iter().map(|run| run.text.as_str()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim()
+        .to_string()
+}
+
+/// Text of the cell covering a grid column in a row
+fn row_column_text(tree: &DocumentTree, row: &TableRow, column: usize) -> String {
+    let mut col = 0;
+    for &cell_id in row.children() {
+        if let Some(cell) = tree.get_table_cell(cell_id) {
+            let span = cell.effective_grid_span() as usize;
+            if column < col + span {
+                return cell_text(tree, cell);
+            }
+            col += span;
+        }
+    }
+    String::new()
+}
+
+/// A copy of a run with a fresh ID and different text
+fn run_like(run: &Run, text: &str) -> Run {
+    let mut copy = Run::new(text);
+    copy.style = run.style.clone();
+    copy.character_style_id = run.character_style_id.clone();
+    copy.direct_formatting = run.direct_formatting.clone();
+    copy
+}
+
+/// Replace body blocks in whichever section holds them
+fn replace_section_blocks(tree: &mut DocumentTree, old: &[NodeId], new: &[NodeId]) {
+    for section in &mut tree.sections {
+        if let Some(index) = section.children().iter().position(|id| old.contains(id)) {
+            for &id in old {
+                section.remove_child(id);
+            }
+            for (offset, &id) in new.iter().enumerate() {
+                section.insert_child(index + offset, id);
+            }
+        }
+    }
+}
+
+fn table_not_found(table_id: NodeId) -> EditError {
+    EditError::InvalidCommand(format!("Table not found: {:?}", table_id))
+}
+
+// =============================================================================
+// SortTable Command
+// =============================================================================
+
+/// Sort the rows of a table
+///
+/// Keys apply in order, later keys breaking ties of earlier ones; rows that
+/// tie on every key keep their order. Leading header rows, and the first row
+/// when `has_header` is set, stay in place.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortTable {
+    pub table_id: NodeId,
+    pub keys: Vec<SortKey>,
+    pub has_header: bool,
+}
+
+impl SortTable {
+    pub fn new(table_id: NodeId, keys: Vec<SortKey>) -> Self {
+        Self {
+            table_id,
+            keys,
+            has_header: false,
+        }
+    }
+
+    /// Keep the first row in place as a header
+    pub fn with_header(mut self, has_header: bool) -> Self {
+        self.has_header = has_header;
+        self
+    }
+
+    /// Compute the sorted row order
+    fn sorted_rows(&self, tree: &DocumentTree) -> Result<Vec<NodeId>> {
+        let table = tree.get_table(self.table_id)
+            .ok_or_else(|| table_not_found(self.table_id))?;
+        if self.keys.is_empty() {
+            return Err(EditError::InvalidCommand("No sort keys given".into()));
+        }
+
+        let rows = table.children();
+        let header_rows = rows
+            .iter()
+            .take_while(|&&id| tree.get_table_row(id).is_some_and(|row| row.properties.is_header))
+            .count()
+            .max(usize::from(self.has_header))
+            .min(rows.len());
+
+        let mut body = Vec::new();
+        for &row_id in &rows[header_rows..] {
+            let row = tree.get_table_row(row_id)
+                .ok_or_else(|| EditError::InvalidCommand("Row not found".into()))?;
+            let merged = row.children().iter().any(|&cell_id| {
+                tree.get_table_cell(cell_id).is_some_and(|cell| cell.v_merge != VerticalMerge::None)
+            });
+            if merged {
+                return Err(EditError::InvalidCommand(
+                    "Cannot sort a table with vertically merged cells".into(),
+                ));
+            }
+            let texts: Vec<String> = self.keys.iter().map(|key| row_column_text(tree, row, key.column)).collect();
+            body.push((row_id, texts));
+        }
+
+        body.sort_by(|(_, a), (_, b)| {
+            self.keys
+                .iter()
+                .enumerate()
+                .map(|(i, key)| key.compare(&a[i], &b[i]))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+
+        Ok(rows[..header_rows].iter().copied().chain(body.into_iter().map(|(id, _)| id)).collect())
+    }
+}
+
+impl Command for SortTable {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let order = self.sorted_rows(tree)?;
+        ReorderTableRows {
+            table_id: self.table_id,
+            row_ids: order,
+        }
+        .apply(tree, selection)
+    }
+
+    fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
+        Box::new(ReorderTableRows {
+            table_id: self.table_id,
+            row_ids: tree.get_table(self.table_id).map(|t| t.children().to_vec()).unwrap_or_default(),
+        })
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Sort Table"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+/// Put a table's rows in a given order (used for undoing a sort)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ReorderTableRows {
+    table_id: NodeId,
+    row_ids: Vec<NodeId>,
+}
+
+impl Command for ReorderTableRows {
+    fn apply(&self, tree: &DocumentTree, selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+
+        let table = new_tree.get_table_mut(self.table_id)
+            .ok_or_else(|| table_not_found(self.table_id))?;
+        let previous = table.children().to_vec();
+        if previous.len() != self.row_ids.len() || !self.row_ids.iter().all(|id| previous.contains(id)) {
+            return Err(EditError::InvalidCommand("Table rows have changed".into()));
+        }
+
+        for &row_id in &previous {
+            table.remove_row(row_id);
+        }
+        for &row_id in &self.row_ids {
+            table.add_row(row_id);
+        }
+
+        Ok(CommandResult {
+            tree: new_tree,
+            selection: *selection,
+            inverse: Box::new(ReorderTableRows {
+                table_id: self.table_id,
+                row_ids: previous,
+            }),
+        })
+    }
+
+    fn invert(&self, tree: &DocumentTree) -> Box<dyn Command> {
+        Box::new(ReorderTableRows {
+            table_id: self.table_id,
+            row_ids: tree.get_table(self.table_id).map(|t| t.children().to_vec()).unwrap_or_default(),
+        })
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Sort Table"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+// =============================================================================
+// Text <-> Table Conversion
+// =============================================================================
+
+/// What separates cells when converting between text and tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TableDelimiter {
+    /// Tab characters
+    #[default]
+    Tab,
+    /// Commas
+    Comma,
+    /// Each paragraph is its own cell, in a single column
+    Paragraph,
+    /// Any other character
+    Other(char),
+}
+
+impl TableDelimiter {
+    /// The separating character, if cells share a paragraph
+    fn as_char(self) -> Option<char> {
+        match self {
+            TableDelimiter::Tab => Some('\t'),
+            TableDelimiter::Comma => Some(','),
+            TableDelimiter::Paragraph => None,
+            TableDelimiter::Other(c) => Some(c),
+        }
+    }
+}
+
+/// Convert body paragraphs into a table
+///
+/// Each paragraph becomes a row, split into cells at the delimiter; rows
+/// shorter than the longest are padded with empty cells. Run formatting is
+/// kept. The table takes the place of the first paragraph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConvertTextToTable {
+    pub paragraph_ids: Vec<NodeId>,
+    pub delimiter: TableDelimiter,
+}
+
+impl ConvertTextToTable {
+    pub fn new(paragraph_ids: Vec<NodeId>, delimiter: TableDelimiter) -> Self {
+        Self {
+            paragraph_ids,
+            delimiter,
+        }
+    }
+
+    /// Split a paragraph's runs into per-cell runs
+    fn split_cells(&self, tree: &DocumentTree, para_id: NodeId) -> Vec<Vec<Run>> {
+        let mut cells = vec![Vec::new()];
+        for run in paragraph_runs(tree, para_id) {
+            let pieces: Vec<&str> = match self.delimiter.as_char() {
+                Some(delimiter) => run.text.split(delimiter).collect(),
+                None => vec![run.text.as_str()],
+            };
+            for (i, piece) in pieces.into_iter().enumerate() {
+                if i > 0 {
+                    cells.push(Vec::new());
+                }
+                if !piece.is_empty() {
+                    cells.last_mut().unwrap().push(run_like(run, piece));
+                }
+            }
+        }
+        cells
+    }
+}
+
+impl Command for ConvertTextToTable {
+    fn apply(&self, tree: &DocumentTree, _selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+
+        let body = tree.document.children();
+        let insert_index = self
+            .paragraph_ids
+            .iter()
+            .filter_map(|id| body.iter().position(|b| b == id))
+            .min()
+            .ok_or_else(|| EditError::InvalidCommand("No body paragraphs to convert".into()))?;
+
+        let mut rows = Vec::new();
+        for &para_id in &self.paragraph_ids {
+            let para = tree.get_paragraph(para_id)
+                .ok_or_else(|| EditError::InvalidCommand(format!("Paragraph not found: {:?}", para_id)))?;
+            rows.push((para.clone(), self.split_cells(tree, para_id)));
+        }
+        let cols = rows.iter().map(|(_, cells)| cells.len()).max().unwrap_or(1).max(1);
+
+        for &para_id in &self.paragraph_ids {
+            new_tree.remove_paragraph(para_id)?;
+        }
+
+        let properties = TableProperties::new()
+            .with_width(TableWidth::fixed(DEFAULT_TABLE_WIDTH))
+            .with_borders(TableBorders::default_borders())
+            .with_cell_padding(CellPadding::default());
+        let table = Table::with_grid_and_properties(
+            TableGrid::with_equal_columns(cols, DEFAULT_TABLE_WIDTH),
+            properties,
+        );
+        let table_id = new_tree.insert_table(table, Some(insert_index))?;
+
+        let mut first_para = None;
+        for (source, mut cells) in rows {
+            cells.resize_with(cols, Vec::new);
+            let row_id = new_tree.insert_table_row(TableRow::new(), table_id, None)?;
+            for runs in cells {
+                let cell = TableCell::with_properties(
+                    CellProperties::new().with_borders(CellBorders::default_borders()),
+                );
+                let cell_id = new_tree.insert_table_cell(cell, row_id, None)?;
+                let mut para = Paragraph::new();
+                para.style = source.style.clone();
+                para.paragraph_style_id = source.paragraph_style_id.clone();
+                para.direct_formatting = source.direct_formatting.clone();
+                let para_id = new_tree.insert_paragraph_into_cell(para, cell_id, None)?;
+                first_para.get_or_insert(para_id);
+                for run in runs {
+                    new_tree.insert_run(run, para_id, None)?;
+                }
+            }
+        }
+
+        replace_section_blocks(&mut new_tree, &self.paragraph_ids, &[table_id]);
+
+        let selection = Selection::collapsed(Position::new(first_para.unwrap_or(table_id), 0));
+        Ok(CommandResult {
+            tree: new_tree,
+            selection,
+            inverse: Box::new(ConvertTableToText::new(table_id, self.delimiter)),
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        // The table's ID is only known once applied; undo uses the inverse
+        // from apply
+        Box::new(ConvertTableToText::new(NodeId::new(), self.delimiter))
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Convert Text to Table"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+/// Convert a table into body paragraphs
+///
+/// Each row becomes a paragraph with its cells separated by the delimiter
+/// (or, for the paragraph delimiter, each cell paragraph stands alone). Run
+/// formatting is kept. The paragraphs take the table's place.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConvertTableToText {
+    pub table_id: NodeId,
+    pub delimiter: TableDelimiter,
+}
+
+impl ConvertTableToText {
+    pub fn new(table_id: NodeId, delimiter: TableDelimiter) -> Self {
+        Self { table_id, delimiter }
+    }
+
+    /// The paragraphs (source paragraph, runs) the table turns into
+    fn paragraphs(&self, tree: &DocumentTree, table: &Table) -> Vec<(Option<Paragraph>, Vec<Run>)> {
+        let mut paragraphs = Vec::new();
+        for &row_id in table.children() {
+            let Some(row) = tree.get_table_row(row_id) else { continue };
+            let cells: Vec<&TableCell> = row
+                .children()
+                .iter()
+                .filter_map(|&id| tree.get_table_cell(id))
+                .filter(|cell| !cell.is_covered())
+                .collect();
+
+            match self.delimiter.as_char() {
+                None => {
+                    for cell in cells {
+                        for &para_id in cell.children() {
+                            let runs = paragraph_runs(tree, para_id).into_iter().map(|run| run_like(run, &run.text)).collect();
+                            paragraphs.push((tree.get_paragraph(para_id).cloned(), runs));
+                        }
+                    }
+                }
+                Some(delimiter) => {
+                    let mut source = None;
+                    let mut runs = Vec::new();
+                    for (i, cell) in cells.into_iter().enumerate() {
+                        if i > 0 {
+                            runs.push(Run::new(delimiter.to_string()));
+                        }
+                        for (j, &para_id) in cell.children().iter().enumerate() {
+                            if j > 0 {
+                                runs.push(Run::new(" "));
+                            }
+                            if source.is_none() {
+                                source = tree.get_paragraph(para_id).cloned();
+                            }
+                            runs.extend(paragraph_runs(tree, para_id).into_iter().map(|run| run_like(run, &run.text)));
+                        }
+                    }
+                    paragraphs.push((source, runs));
+                }
+            }
+        }
+        paragraphs
+    }
+}
+
+impl Command for ConvertTableToText {
+    fn apply(&self, tree: &DocumentTree, _selection: &Selection) -> Result<CommandResult> {
+        let mut new_tree = tree.clone();
+
+        let table = tree.get_table(self.table_id)
+            .ok_or_else(|| table_not_found(self.table_id))?;
+        let index = tree.document.children()
+            .iter()
+            .position(|&id| id == self.table_id)
+            .ok_or_else(|| EditError::InvalidCommand("Only body tables can be converted".into()))?;
+
+        let paragraphs = self.paragraphs(tree, table);
+        new_tree.remove_table(self.table_id)?;
+
+        let mut para_ids = Vec::new();
+        let document_id = new_tree.document.id();
+        for (offset, (source, runs)) in paragraphs.into_iter().enumerate() {
+            let mut para = Paragraph::new();
+            if let Some(source) = source {
+                para.style = source.style;
+                para.paragraph_style_id = source.paragraph_style_id;
+                para.direct_formatting = source.direct_formatting;
+            }
+            let para_id = new_tree.insert_paragraph(para, document_id, Some(index + offset))?;
+            for run in runs {
+                new_tree.insert_run(run, para_id, None)?;
+            }
+            para_ids.push(para_id);
+        }
+
+        replace_section_blocks(&mut new_tree, &[self.table_id], &para_ids);
+
+        let selection = match para_ids.first() {
+            Some(&para_id) => Selection::collapsed(Position::new(para_id, 0)),
+            None => Selection::default(),
+        };
+        Ok(CommandResult {
+            tree: new_tree,
+            selection,
+            inverse: Box::new(ConvertTextToTable::new(para_ids, self.delimiter)),
+        })
+    }
+
+    fn invert(&self, _tree: &DocumentTree) -> Box<dyn Command> {
+        // The paragraphs' IDs are only known once applied; undo uses the
+        // inverse from apply
+        Box::new(ConvertTextToTable::new(Vec::new(), self.delimiter))
+    }
+
+    fn transform_selection(&self, selection: &Selection) -> Selection {
+        *selection
+    }
+
+    fn display_name(&self) -> &str {
+        "Convert Table to Text"
+    }
+
+    fn clone_box(&self) -> Box<dyn Command> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::RowProperties;
+
+    fn build_table(tree: &mut DocumentTree, rows: &[&[&str]]) -> NodeId {
+        let cols = rows[0].len();
+        let table = Table::with_grid(TableGrid::with_equal_columns(cols, 300.0));
+        let table_id = tree.insert_table(table, None).unwrap();
+        for (i, cells) in rows.iter().enumerate() {
+            let props = if i == 0 { RowProperties::new().as_header() } else { RowProperties::new() };
+            let row_id = tree.insert_table_row(TableRow::with_properties(props), table_id, None).unwrap();
+            for text in cells.iter() {
+                let cell_id = tree.insert_table_cell(TableCell::new(), row_id, None).unwrap();
+                let para_id = tree.insert_paragraph_into_cell(Paragraph::new(), cell_id, None).unwrap();
+                tree.insert_run(Run::new(*text), para_id, None).unwrap();
+            }
+        }
+        table_id
+    }
+
+    fn column(tree: &DocumentTree, table_id: NodeId, col: usize) -> Vec<String> {
+        tree.get_table(table_id)
+            .unwrap()
+            .children()
+            .iter()
+            .map(|&id| row_column_text(tree, tree.get_table_row(id).unwrap(), col))
+            .collect()
+    }
+
+    #[test]
+    fn test_sort_table_by_keys() {
+        let mut tree = DocumentTree::new();
+        let table_id = build_table(&mut tree, &[
+            &["Name", "Amount", "Date"],
+            &["pear", "$1,200", "03/15/2024"],
+            &["Apple", "95", "2023-12-01"],
+            &["banana", "1,200.50", "January 5, 2024"],
+            &["apple", "n/a", "2024-02-29"],
+        ]);
+        let selection = Selection::default();
+
+        // Numbers descending, header row left in place, non-numbers last
+        let cmd = SortTable::new(table_id, vec![SortKey::new(1, SortKeyType::Number).descending()]);
+        let result = cmd.apply(&tree, &selection).unwrap();
+        assert_eq!(column(&result.tree, table_id, 1), vec!["Amount", "1,200.50", "$1,200", "95", "n/a"]);
+
+        // Dates ascending
+        let cmd = SortTable::new(table_id, vec![SortKey::new(2, SortKeyType::Date)]);
+        let result = cmd.apply(&tree, &selection).unwrap();
+        assert_eq!(column(&result.tree, table_id, 0), vec!["Name", "Apple", "banana", "apple", "pear"]);
+
+        // Case-insensitive text with a second key breaking the tie
+        let cmd = SortTable::new(table_id, vec![
+            SortKey::new(0, SortKeyType::Text),
+            SortKey::new(2, SortKeyType::Date).descending(),
+        ]);
+        let result = cmd.apply(&tree, &selection).unwrap();
+        assert_eq!(column(&result.tree, table_id, 0), vec!["Name", "apple", "Apple", "banana", "pear"]);
+
+        // Undo restores the original order
+        let undone = result.inverse.apply(&result.tree, &selection).unwrap();
+        assert_eq!(column(&undone.tree, table_id, 0), column(&tree, table_id, 0));
+    }
+
+    #[test]
+    fn test_convert_text_to_table_and_back() {
+        let mut tree = DocumentTree::new();
+        let root = tree.root_id();
+        let mut para_ids = Vec::new();
+        for text in ["Name,Qty", "Bolts,40", "Nuts"] {
+            let para_id = tree.insert_paragraph(Paragraph::new(), root, None).unwrap();
+            tree.insert_run(Run::new(text), para_id, None).unwrap();
+            para_ids.push(para_id);
+        }
+
+        let result = ConvertTextToTable::new(para_ids, TableDelimiter::Comma)
+            .apply(&tree, &Selection::default())
+            .unwrap();
+        let table_id = result.tree.document.children()[0];
+        let table = result.tree.get_table(table_id).unwrap();
+        assert_eq!(table.row_count(), 3);
+        assert_eq!(table.column_count(), 2);
+        assert_eq!(column(&result.tree, table_id, 0), vec!["Name", "Bolts", "Nuts"]);
+        assert_eq!(column(&result.tree, table_id, 1), vec!["Qty", "40", ""]);
+
+        // Undo turns the table back into delimited text
+        let undone = result.inverse.apply(&result.tree, &result.selection).unwrap();
+        let texts: Vec<String> = undone
+            .tree
+            .document
+            .children()
+            .iter()
+            .map(|&id| paragraph_runs(&undone.tree, id).iter().map(|r| r.text.as_str()).collect())
+            .collect();
+        assert_eq!(texts, vec!["Name,Qty", "Bolts,40", "Nuts,"]);
+    }
+}
@@ -0,0 +1,373 @@
+//! Translation
+//!
+//! Translation goes through the `TranslationProvider` trait so online
+//! services (e.g. the plugin HTTP provider) and the offline
+//! `DictionaryTranslator` are interchangeable. Text is sent one paragraph at
+//! a time with each run as a separate segment, so run formatting survives
+//! when the provider returns one translation per segment.
+//!
+//! `translate_document` and `translate_selection` produce a new document and
+//! leave the source untouched.
+
+use crate::format_painter_commands::{ordered_range, run_ids};
+use crate::{paragraphs_between, EditError, Result};
+use doc_model::{DocumentTree, Node, NodeId, Paragraph, Position, Run, Selection};
+use std::collections::HashMap;
+
+// =============================================================================
+// Providers
+// =============================================================================
+
+/// A translation service
+pub trait TranslationProvider {
+    /// Stable provider identifier
+    fn id(&self) -> &str;
+
+    /// Display name
+    fn name(&self) -> &str;
+
+    /// Translate segments of text into `to`
+    ///
+    /// `from` is None to let the provider detect the source language.
+    /// Returns one translation per segment, in order.
+    fn translate(&self, segments: &[String], from: Option<&str>, to: &str) -> Result<Vec<String>>;
+}
+
+/// Offline word-by-word translator backed by glossaries
+///
+/// Words missing from the glossary are left as they are. Used when no online
+/// provider is available.
+#[derive(Debug, Clone, Default)]
+pub struct DictionaryTranslator {
+    /// Glossaries keyed by (source, target) primary language subtags
+    glossaries: HashMap<(String, String), HashMap<String, String>>,
+}
+
+impl DictionaryTranslator {
+    /// Provider ID of the dictionary translator
+    pub const ID: &'static str = "dictionary";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a glossary entry
+    pub fn with_entry(mut self, from: &str, to: &str, word: &str, translation: &str) -> Self {
+        self.add_entry(from, to, word, translation);
+        self
+    }
+
+    /// Add a glossary entry
+    pub fn add_entry(&mut self, from: &str, to: &str, word: &str, translation: &str) {
+        self.glossaries
+            .entry((primary_subtag(from), primary_subtag(to)))
+            .or_default()
+            .insert(word.to_lowercase(), translation.to_string());
+    }
+
+    /// Glossaries that translate into `to`, starting with the one from `from`
+    fn glossaries_for(&self, from: Option<&str>, to: &str) -> Vec<&HashMap<String, String>> {
+        let to = primary_subtag(to);
+        match from {
+            Some(from) => self.glossaries.get(&(primary_subtag(from), to)).into_iter().collect(),
+            None => self
+                .glossaries
+                .iter()
+                .filter(|((_, target), _)| *target == to)
+                .map(|(_, glossary)| glossary)
+                .collect(),
+        }
+    }
+
+    fn translate_word(glossaries: &[&HashMap<String, String>], word: &str) -> String {
+        let Some(translation) = glossaries.iter().find_map(|g| g.get(&word.to_lowercase())) else {
+            return word.to_string();
+        };
+        let mut chars = word.chars();
+        if word.chars().all(|c| !c.is_lowercase()) && word.chars().count() > 1 {
+            translation.to_uppercase()
+        } else if chars.next().is_some_and(char::is_uppercase) {
+            let mut out = String::with_capacity(translation.len());
+            let mut translated = translation.chars();
+            out.extend(translated.next().into_iter().flat_map(char::to_uppercase));
+            out.extend(translated);
+            out
+        } else {
+            translation.clone()
+        }
+    }
+}
+
+impl TranslationProvider for DictionaryTranslator {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn name(&self) -> &str {
+        "Offline Dictionary"
+    }
+
+    fn translate(&self, segments: &[String], from: Option<&str>, to: &str) -> Result<Vec<String>> {
+        let glossaries = self.glossaries_for(from, to);
+        Ok(segments
+            .iter()
+            .map(|segment| {
+                let mut out = String::with_capacity(segment.len());
+                let mut word = String::new();
+                for c in segment.chars() {
+                    if c.is_alphanumeric() || c == '\'' {
+                        word.push(c);
+                    } else {
+                        out.push_str(&Self::translate_word(&glossaries, &word));
+                        word.clear();
+                        out.push(c);
+                    }
+                }
+                out.push_str(&Self::translate_word(&glossaries, &word));
+                out
+            })
+            .collect())
+    }
+}
+
+/// Registered translation providers with the dictionary as fallback
+#[derive(Default)]
+pub struct TranslationRegistry {
+    providers: Vec<Box<dyn TranslationProvider>>,
+    fallback: DictionaryTranslator,
+}
+
+impl TranslationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the offline fallback dictionary
+    pub fn with_fallback(mut self, fallback: DictionaryTranslator) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Register a provider, replacing one with the same ID
+    pub fn register(&mut self, provider: Box<dyn TranslationProvider>) {
+        self.providers.retain(|p| p.id() != provider.id());
+        self.providers.push(provider);
+    }
+
+    /// IDs and names of the available providers, including the fallback
+    pub fn providers(&self) -> Vec<(String, String)> {
+        self.providers
+            .iter()
+            .map(|p| p.as_ref() as &dyn TranslationProvider)
+            .chain(std::iter::once(&self.fallback as &dyn TranslationProvider))
+            .map(|p| (p.id().to_string(), p.name().to_string()))
+            .collect()
+    }
+
+    /// Get a provider by ID, or the fallback if it isn't registered
+    pub fn provider(&self, id: Option<&str>) -> &dyn TranslationProvider {
+        id.and_then(|id| self.providers.iter().find(|p| p.id() == id))
+            .map_or(&self.fallback as &dyn TranslationProvider, |p| p.as_ref())
+    }
+
+    /// The offline fallback dictionary
+    pub fn fallback(&self) -> &DictionaryTranslator {
+        &self.fallback
+    }
+}
+
+/// Primary language subtag, e.g. "fr" for "fr-CA"
+fn primary_subtag(language: &str) -> String {
+    language.split(['-', '_']).next().unwrap_or_default().to_lowercase()
+}
+
+// =============================================================================
+// Document Translation
+// =============================================================================
+
+/// Translate a whole document into a new document
+///
+/// Translated runs get `to` as their proofing language.
+pub fn translate_document(
+    tree: &DocumentTree,
+    provider: &dyn TranslationProvider,
+    from: Option<&str>,
+    to: &str,
+) -> Result<DocumentTree> {
+    let mut translated = tree.clone();
+    let para_ids: Vec<NodeId> = translated.nodes.paragraphs.keys().copied().collect();
+    for para_id in para_ids {
+        translate_paragraph(&mut translated, para_id, provider, from, to)?;
+    }
+    Ok(translated)
+}
+
+/// Translate the selected text into a new document
+///
+/// The new document keeps the source's styles, theme and page setup.
+pub fn translate_selection(
+    tree: &DocumentTree,
+    selection: &Selection,
+    provider: &dyn TranslationProvider,
+    from: Option<&str>,
+    to: &str,
+) -> Result<DocumentTree> {
+    if selection.is_collapsed() {
+        return Err(EditError::InvalidCommand("Nothing is selected".to_string()));
+    }
+    let mut extracted = extract_selection(tree, selection)?;
+    let para_ids: Vec<NodeId> = extracted.paragraphs().map(|p| p.id()).collect();
+    for para_id in para_ids {
+        translate_paragraph(&mut extracted, para_id, provider, from, to)?;
+    }
+    Ok(extracted)
+}
+
+/// Runs of a paragraph in order, including hyperlink text
+fn paragraph_runs(tree: &DocumentTree, para_id: NodeId) -> Vec<NodeId> {
+    let Some(para) = tree.get_paragraph(para_id) else {
+        return Vec::new();
+    };
+    para.children()
+        .iter()
+        .flat_map(|&id| match tree.get_hyperlink(id) {
+            Some(link) => link.children().to_vec(),
+            None => vec![id],
+        })
+        .filter(|&id| tree.get_run(id).is_some())
+        .collect()
+}
+
+/// Translate the runs of one paragraph in place
+///
+/// If the provider doesn't return one translation per run, the whole
+/// paragraph is translated as one segment and put in the first run.
+fn translate_paragraph(
+    tree: &mut DocumentTree,
+    para_id: NodeId,
+    provider: &dyn TranslationProvider,
+    from: Option<&str>,
+    to: &str,
+) -> Result<()> {
+    let runs = paragraph_runs(tree, para_id);
+    let segments: Vec<String> = runs
+        .iter()
+        .filter_map(|&id| tree.get_run(id).map(|r| r.text.clone()))
+        .collect();
+    if segments.iter().all(|s| s.trim().is_empty()) {
+        return Ok(());
+    }
+
+    let mut translations = provider.translate(&segments, from, to)?;
+    if translations.len() != segments.len() {
+        let joined = provider.translate(&[segments.concat()], from, to)?;
+        translations = vec![String::new(); segments.len()];
+        translations[0] = joined.concat();
+    }
+
+    for (run_id, text) in runs.into_iter().zip(translations) {
+        if let Some(run) = tree.get_run_mut(run_id) {
+            run.text = text;
+            run.direct_formatting.language = Some(to.to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Copy the selected paragraphs and text into a new document
+fn extract_selection(tree: &DocumentTree, selection: &Selection) -> Result<DocumentTree> {
+    let ((start_para, start), (end_para, end)) = ordered_range(tree, selection)?;
+    let paragraphs = paragraphs_between(
+        tree,
+        &Position::new(start_para, start),
+        &Position::new(end_para, end),
+    )
+    .ok_or_else(|| EditError::InvalidCommand("Invalid selection".to_string()))?;
+
+    let mut extracted = DocumentTree::new();
+    extracted.document.metadata = tree.document.metadata.clone();
+    extracted.styles = tree.styles.clone();
+    extracted.theme = tree.theme.clone();
+    extracted.sections = tree.sections.clone();
+
+    for para_id in paragraphs {
+        let Some(source) = tree.get_paragraph(para_id) else {
+            continue;
+        };
+        let mut para = Paragraph::new();
+        para.style = source.style.clone();
+        para.paragraph_style_id = source.paragraph_style_id.clone();
+        para.direct_formatting = source.direct_formatting.clone();
+        let new_para_id = extracted.insert_paragraph(para, extracted.root_id(), None)?;
+
+        let from = if para_id == start_para { start } else { 0 };
+        let to = if para_id == end_para { end } else { usize::MAX };
+        let mut run_start = 0;
+        for run_id in run_ids(tree, para_id) {
+            let Some(source) = tree.get_run(run_id) else {
+                continue;
+            };
+            let len = source.text.chars().count();
+            let (clip_from, clip_to) = (from.max(run_start), to.min(run_start + len));
+            run_start += len;
+            if clip_from >= clip_to {
+                continue;
+            }
+            let text: String = source
+                .text
+                .chars()
+                .skip(clip_from - (run_start - len))
+                .take(clip_to - clip_from)
+                .collect();
+            let mut run = Run::with_style(text, source.style.clone());
+            run.character_style_id = source.character_style_id.clone();
+            run.direct_formatting = source.direct_formatting.clone();
+            extracted.insert_run(run, new_para_id, None)?;
+        }
+    }
+    Ok(extracted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::CharacterProperties;
+
+    fn glossary() -> DictionaryTranslator {
+        DictionaryTranslator::new()
+            .with_entry("en", "fr", "hello", "bonjour")
+            .with_entry("en", "fr", "world", "monde")
+            .with_entry("en", "fr", "the", "le")
+    }
+
+    #[test]
+    fn test_dictionary_translator_keeps_case_and_punctuation() {
+        let translated = glossary()
+            .translate(&["Hello, the WORLD!".to_string()], Some("en-US"), "fr-FR")
+            .unwrap();
+        assert_eq!(translated, vec!["Bonjour, le MONDE!".to_string()]);
+    }
+
+    #[test]
+    fn test_translate_selection_preserves_runs() {
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        let bold = CharacterProperties {
+            bold: Some(true),
+            ..Default::default()
+        };
+        tree.insert_run(Run::new("Say hello "), para_id, None).unwrap();
+        tree.insert_run(Run::with_direct_formatting("world", bold), para_id, None).unwrap();
+
+        let selection = Selection::new(Position::new(para_id, 4), Position::new(para_id, 15));
+        let translated = translate_selection(&tree, &selection, &glossary(), None, "fr").unwrap();
+
+        let para = translated.paragraphs().next().unwrap();
+        let runs: Vec<&Run> = para.children().iter().filter_map(|&id| translated.get_run(id)).collect();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].text, "bonjour ");
+        assert_eq!(runs[1].text, "monde");
+        assert_eq!(runs[1].direct_formatting.bold, Some(true));
+        assert_eq!(runs[1].direct_formatting.language.as_deref(), Some("fr"));
+    }
+}
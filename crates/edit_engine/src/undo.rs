@@ -1,7 +1,17 @@
-//! Undo/redo manager with command batching
+//! Undo/redo manager with command batching, named groups, and history
+//!
+//! Commands pushed between `begin_group` and `end_group` are recorded as a
+//! single entry under the group's name, so a multi-step operation such as
+//! "Replace All" undoes in one step. The history is bounded both by entry
+//! count and by an estimate of the memory the entries hold.
 
-use crate::{Command, EditError, Result};
-use std::time::{Duration, Instant};
+use crate::{Command, CommandSequence, EditError, Result};
+use doc_model::Selection;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Default memory budget for undo history (64 MiB)
+pub const DEFAULT_UNDO_MEMORY_LIMIT: usize = 64 * 1024 * 1024;
 
 /// An entry in the undo stack
 struct UndoEntry {
@@ -9,8 +19,71 @@ struct UndoEntry {
     command: Box<dyn Command>,
     /// The inverse command (for undo)
     inverse: Box<dyn Command>,
+    /// Name shown in the history
+    name: String,
     /// When this entry was created
     timestamp: Instant,
+    /// Wall-clock creation time (Unix timestamp in ms)
+    created_at: u64,
+    /// Estimated memory held by the entry
+    size: usize,
+}
+
+impl UndoEntry {
+    fn new(name: String, command: Box<dyn Command>, inverse: Box<dyn Command>) -> Self {
+        let size = command.memory_size() + inverse.memory_size();
+        Self {
+            command,
+            inverse,
+            name,
+            timestamp: Instant::now(),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            size,
+        }
+    }
+
+    fn history_entry(&self) -> HistoryEntry {
+        HistoryEntry {
+            name: self.name.clone(),
+            timestamp: self.created_at,
+        }
+    }
+}
+
+/// A named group being recorded
+struct OpenGroup {
+    name: String,
+    /// Nesting depth; only the outermost group's name is kept
+    depth: usize,
+    /// Commands with the selection they were applied to
+    steps: Vec<(Box<dyn Command>, Selection)>,
+    /// Inverses with the selection they apply to
+    inverses: Vec<(Box<dyn Command>, Selection)>,
+}
+
+/// A step in the undo history, for display
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    /// Command or group name (e.g. "Typing", "Replace All")
+    pub name: String,
+    /// When the step was recorded (Unix timestamp in ms)
+    pub timestamp: u64,
+}
+
+/// The undo and redo history, most recent step first
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoHistory {
+    /// Steps that can be undone
+    pub undo: Vec<HistoryEntry>,
+    /// Steps that can be redone
+    pub redo: Vec<HistoryEntry>,
+    /// Estimated memory held by the history, in bytes
+    pub memory_bytes: usize,
 }
 
 /// Manages undo and redo stacks
@@ -18,25 +91,25 @@ pub struct UndoManager {
     /// Stack of commands that can be undone
     undo_stack: Vec<UndoEntry>,
     /// Stack of commands that can be redone
-    redo_stack: Vec<Box<dyn Command>>,
+    redo_stack: Vec<UndoEntry>,
     /// Maximum number of undo entries
     max_entries: usize,
+    /// Maximum estimated memory for both stacks
+    max_memory: usize,
+    /// Estimated memory held by both stacks
+    memory_used: usize,
     /// Time threshold for batching (commands within this time are merged)
     batch_threshold: Duration,
     /// Whether we're currently in an IME composition
     in_composition: bool,
+    /// Group being recorded, if any
+    group: Option<OpenGroup>,
 }
 
 impl UndoManager {
     /// Create a new undo manager
     pub fn new() -> Self {
-        Self {
-            undo_stack: Vec::new(),
-            redo_stack: Vec::new(),
-            max_entries: 100,
-            batch_threshold: Duration::from_millis(500),
-            in_composition: false,
-        }
+        Self::with_limits(100, Duration::from_millis(500))
     }
 
     /// Create with custom limits
@@ -45,15 +118,45 @@ impl UndoManager {
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
             max_entries,
+            max_memory: DEFAULT_UNDO_MEMORY_LIMIT,
+            memory_used: 0,
             batch_threshold,
             in_composition: false,
+            group: None,
         }
     }
 
+    /// Set the memory budget in bytes
+    pub fn with_memory_limit(mut self, max_memory: usize) -> Self {
+        self.max_memory = max_memory;
+        self
+    }
+
     /// Push a command onto the undo stack
+    ///
+    /// Use `push_step` for commands that depend on the selection; inside a
+    /// group this records them against an empty selection.
     pub fn push(&mut self, command: Box<dyn Command>, inverse: Box<dyn Command>) {
+        self.push_step(command, inverse, Selection::default(), Selection::default());
+    }
+
+    /// Push a command with the selection it was applied to and the
+    /// selection it produced
+    pub fn push_step(
+        &mut self,
+        command: Box<dyn Command>,
+        inverse: Box<dyn Command>,
+        before: Selection,
+        after: Selection,
+    ) {
         // Clear redo stack on new command
-        self.redo_stack.clear();
+        self.clear_redo();
+
+        if let Some(group) = self.group.as_mut() {
+            group.steps.push((command, before));
+            group.inverses.push((inverse, after));
+            return;
+        }
 
         let now = Instant::now();
 
@@ -61,42 +164,132 @@ impl UndoManager {
         if let Some(last) = self.undo_stack.last_mut() {
             if !self.in_composition && now.duration_since(last.timestamp) < self.batch_threshold {
                 if let Some(merged) = last.command.merge_with(command.as_ref()) {
+                    let size = merged.memory_size() + inverse.memory_size();
+                    self.memory_used = self.memory_used - last.size + size;
                     last.command = merged;
                     last.inverse = inverse;
                     last.timestamp = now;
+                    last.size = size;
+                    self.enforce_limits();
                     return;
                 }
             }
         }
 
-        // Add new entry
-        self.undo_stack.push(UndoEntry {
-            command,
-            inverse,
-            timestamp: now,
-        });
+        let name = command.display_name().to_string();
+        self.push_entry(UndoEntry::new(name, command, inverse));
+    }
+
+    fn push_entry(&mut self, entry: UndoEntry) {
+        self.memory_used += entry.size;
+        self.undo_stack.push(entry);
+        self.enforce_limits();
+    }
+
+    /// Drop the oldest entries until the history fits its limits
+    ///
+    /// The newest entry is always kept, even if it alone exceeds the
+    /// memory budget.
+    fn enforce_limits(&mut self) {
+        while self.undo_stack.len() > self.max_entries
+            || (self.memory_used > self.max_memory && self.undo_stack.len() > 1)
+        {
+            let entry = self.undo_stack.remove(0);
+            self.memory_used -= entry.size;
+        }
+    }
 
-        // Enforce max entries
-        while self.undo_stack.len() > self.max_entries {
-            self.undo_stack.remove(0);
+    fn clear_redo(&mut self) {
+        for entry in self.redo_stack.drain(..) {
+            self.memory_used -= entry.size;
         }
     }
 
+    /// Start a named group; commands pushed until the matching `end_group`
+    /// are undone as one step
+    ///
+    /// Groups nest: inner groups join the outermost one.
+    pub fn begin_group(&mut self, name: impl Into<String>) {
+        match self.group.as_mut() {
+            Some(group) => group.depth += 1,
+            None => {
+                self.group = Some(OpenGroup {
+                    name: name.into(),
+                    depth: 1,
+                    steps: Vec::new(),
+                    inverses: Vec::new(),
+                })
+            }
+        }
+    }
+
+    /// End the current group
+    ///
+    /// Closing the outermost group records its commands as a single entry;
+    /// an empty group records nothing.
+    pub fn end_group(&mut self) -> Result<()> {
+        let group = self
+            .group
+            .as_mut()
+            .ok_or_else(|| EditError::InvalidCommand("No undo group is open".to_string()))?;
+        group.depth -= 1;
+        if group.depth > 0 {
+            return Ok(());
+        }
+
+        let Some(mut group) = self.group.take() else {
+            return Ok(());
+        };
+        if group.steps.is_empty() {
+            return Ok(());
+        }
+        group.inverses.reverse();
+        self.push_entry(UndoEntry::new(
+            group.name,
+            Box::new(CommandSequence::new(group.steps)),
+            Box::new(CommandSequence::new(group.inverses)),
+        ));
+        Ok(())
+    }
+
+    /// Check if a group is being recorded
+    pub fn in_group(&self) -> bool {
+        self.group.is_some()
+    }
+
     /// Pop the last command for undo
     pub fn pop_undo(&mut self) -> Result<Box<dyn Command>> {
+        if self.group.is_some() {
+            return Err(EditError::InvalidCommand(
+                "Cannot undo while an undo group is open".to_string(),
+            ));
+        }
         let entry = self.undo_stack.pop()
             .ok_or(EditError::UndoStackEmpty)?;
+        let inverse = entry.inverse.clone_box();
 
         // Push to redo stack
-        self.redo_stack.push(entry.command);
+        self.redo_stack.push(entry);
 
-        Ok(entry.inverse)
+        Ok(inverse)
     }
 
-    /// Pop a command for redo
+    /// Pop a command for redo, returning its entry to the undo stack
     pub fn pop_redo(&mut self) -> Result<Box<dyn Command>> {
-        self.redo_stack.pop()
-            .ok_or(EditError::RedoStackEmpty)
+        if self.group.is_some() {
+            return Err(EditError::InvalidCommand(
+                "Cannot redo while an undo group is open".to_string(),
+            ));
+        }
+        let mut entry = self.redo_stack.pop()
+            .ok_or(EditError::RedoStackEmpty)?;
+        let command = entry.command.clone_box();
+
+        // Redone entries never merge with later typing
+        entry.timestamp = Instant::now() - self.batch_threshold;
+        self.undo_stack.push(entry);
+
+        Ok(command)
     }
 
     /// Check if undo is available
@@ -109,6 +302,20 @@ impl UndoManager {
         !self.redo_stack.is_empty()
     }
 
+    /// Get the undo and redo history for display
+    pub fn history(&self) -> UndoHistory {
+        UndoHistory {
+            undo: self.undo_stack.iter().rev().map(UndoEntry::history_entry).collect(),
+            redo: self.redo_stack.iter().rev().map(UndoEntry::history_entry).collect(),
+            memory_bytes: self.memory_used,
+        }
+    }
+
+    /// Get the estimated memory held by the history, in bytes
+    pub fn memory_usage(&self) -> usize {
+        self.memory_used
+    }
+
     /// Start IME composition (disables batching)
     pub fn begin_composition(&mut self) {
         self.in_composition = true;
@@ -128,6 +335,8 @@ impl UndoManager {
     pub fn clear(&mut self) {
         self.undo_stack.clear();
         self.redo_stack.clear();
+        self.memory_used = 0;
+        self.group = None;
     }
 }
 
@@ -136,3 +345,68 @@ impl Default for UndoManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EditingEngine, InsertText};
+
+    fn type_text(engine: &mut EditingEngine, text: &str) {
+        let caret = engine.selection().focus;
+        engine.execute(Box::new(InsertText::new(caret, text))).unwrap();
+    }
+
+    #[test]
+    fn test_named_group_undoes_as_one_step() {
+        let mut engine = EditingEngine::new();
+        type_text(&mut engine, "a");
+
+        engine.begin_transaction("Replace All");
+        engine.begin_transaction("Nested");
+        type_text(&mut engine, "b");
+        engine.end_transaction().unwrap();
+        type_text(&mut engine, "c");
+        assert!(engine.undo().is_err());
+        engine.end_transaction().unwrap();
+        assert!(engine.end_transaction().is_err());
+
+        let history = engine.history();
+        let names: Vec<&str> = history.undo.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["Replace All", "Insert Text"]);
+
+        engine.undo().unwrap();
+        assert_eq!(engine.tree().text_content(), "a\n");
+        assert_eq!(engine.history().redo[0].name, "Replace All");
+
+        engine.redo().unwrap();
+        assert_eq!(engine.tree().text_content(), "abc\n");
+        // Redo puts the step back so it can be undone again
+        engine.undo().unwrap();
+        engine.undo().unwrap();
+        assert_eq!(engine.tree().text_content(), "\n");
+    }
+
+    #[test]
+    fn test_history_is_bounded_by_memory() {
+        let mut manager = UndoManager::with_limits(100, Duration::ZERO);
+        let position = doc_model::Position::new(doc_model::NodeId::new(), 0);
+        let step = || Box::new(InsertText::new(position, "x".repeat(1000))) as Box<dyn Command>;
+
+        manager.push(step(), step());
+        let entry_size = manager.memory_usage();
+        assert!(entry_size > 2000);
+
+        let mut manager = manager.with_memory_limit(entry_size * 3);
+        for _ in 0..5 {
+            manager.push(step(), step());
+        }
+        assert_eq!(manager.history().undo.len(), 3);
+        assert_eq!(manager.memory_usage(), entry_size * 3);
+
+        manager.pop_undo().unwrap();
+        assert_eq!(manager.history().redo.len(), 1);
+        manager.push(step(), step());
+        assert!(manager.history().redo.is_empty());
+        assert_eq!(manager.memory_usage(), entry_size * 3);
+    }
+}
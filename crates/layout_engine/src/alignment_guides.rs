@@ -0,0 +1,373 @@
+//! Alignment Guides
+//!
+//! While a shape is dragged, the frontend asks where it should snap and which
+//! smart guides to draw. Candidates come from the page edges and center, the
+//! page margins, the edges and centers of other shapes, and an optional grid.
+//! Each axis snaps independently to its nearest candidate within the snap
+//! threshold.
+
+use crate::{LayoutTree, Rect};
+use doc_model::NodeId;
+use serde::{Deserialize, Serialize};
+
+/// Default snap distance in points
+pub const DEFAULT_SNAP_THRESHOLD: f32 = 4.0;
+
+/// Candidates closer than this after snapping count as aligned
+const ALIGNED_EPSILON: f32 = 0.01;
+
+/// What the dragged shape snaps to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapSettings {
+    /// Maximum distance in points a shape jumps to a guide
+    pub threshold: f32,
+    /// Snap to the page edges, center, and margins
+    pub snap_to_page: bool,
+    /// Snap to the edges and centers of other shapes
+    pub snap_to_shapes: bool,
+    /// Grid spacing in points, measured from the top-left margin corner
+    pub grid_spacing: Option<f32>,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_SNAP_THRESHOLD,
+            snap_to_page: true,
+            snap_to_shapes: true,
+            grid_spacing: None,
+        }
+    }
+}
+
+impl SnapSettings {
+    /// Also snap to a grid
+    pub fn with_grid(mut self, spacing: f32) -> Self {
+        self.grid_spacing = Some(spacing).filter(|spacing| *spacing > 0.0);
+        self
+    }
+}
+
+/// The space a shape is dragged in
+///
+/// For a shape on a drawing canvas, the page and margins are both the
+/// canvas bounds and the other shapes are in canvas coordinates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapContext {
+    /// Page (or canvas) bounds
+    pub page: Rect,
+    /// Area inside the margins
+    pub margins: Rect,
+    /// Other shapes that can be aligned to
+    pub shapes: Vec<(NodeId, Rect)>,
+}
+
+impl SnapContext {
+    /// Context for dragging over a laid-out page, excluding the dragged shape
+    pub fn for_page(layout: &LayoutTree, page_index: usize, dragged: Option<NodeId>) -> Option<Self> {
+        let page = layout.pages.get(page_index)?;
+        let shapes = layout
+            .floating_shapes_on_page(page_index)
+            .map(|shape| (shape.node_id, shape.bounds))
+            .chain(layout.floating_images_on_page(page_index).map(|image| (image.node_id, image.bounds)))
+            .chain(layout.floating_textboxes_on_page(page_index).map(|textbox| (textbox.node_id, textbox.bounds)))
+            .filter(|(id, _)| Some(*id) != dragged)
+            .collect();
+        Some(Self {
+            page: page.bounds,
+            margins: page.content_area,
+            shapes,
+        })
+    }
+
+    /// Context for dragging on a drawing canvas
+    pub fn for_canvas(width: f32, height: f32, shapes: Vec<(NodeId, Rect)>) -> Self {
+        let bounds = Rect::new(0.0, 0.0, width, height);
+        Self {
+            page: bounds,
+            margins: bounds,
+            shapes,
+        }
+    }
+}
+
+/// Direction of a guide line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GuideOrientation {
+    /// A vertical line; aligns x positions
+    Vertical,
+    /// A horizontal line; aligns y positions
+    Horizontal,
+}
+
+/// Where a snap candidate comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GuideSource {
+    PageEdge,
+    PageCenter,
+    Margin,
+    ShapeEdge(NodeId),
+    ShapeCenter(NodeId),
+    Grid,
+}
+
+/// Which part of the dragged shape lines up with a guide
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnapEdge {
+    /// Left or top edge
+    Start,
+    /// Horizontal or vertical center
+    Center,
+    /// Right or bottom edge
+    End,
+}
+
+/// A position the dragged shape can snap to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapCandidate {
+    pub orientation: GuideOrientation,
+    /// X of a vertical guide, or y of a horizontal one
+    pub position: f32,
+    /// Extent of the guide line along its direction: the target's, widened
+    /// to cover the snapped shape for the guides to draw
+    pub start: f32,
+    pub end: f32,
+    pub source: GuideSource,
+    pub edge: SnapEdge,
+    /// How far the dragged shape moves to snap
+    pub offset: f32,
+}
+
+/// Where a dragged shape ends up and the guides to draw
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapResult {
+    /// Bounds after snapping
+    pub bounds: Rect,
+    /// Guides the snapped shape lines up with
+    pub guides: Vec<SnapCandidate>,
+    /// Every candidate within the threshold of the drag position, nearest
+    /// first
+    pub candidates: Vec<SnapCandidate>,
+}
+
+/// A line the dragged shape can align to on one axis
+struct Target {
+    position: f32,
+    start: f32,
+    end: f32,
+    source: GuideSource,
+    /// Grid lines only take the shape's start edge
+    start_only: bool,
+}
+
+/// Computes snap positions and smart guides for shape drags
+#[derive(Debug, Clone, Default)]
+pub struct AlignmentGuideEngine {
+    settings: SnapSettings,
+}
+
+impl AlignmentGuideEngine {
+    /// Create an engine with the given settings
+    pub fn new(settings: SnapSettings) -> Self {
+        Self { settings }
+    }
+
+    /// Snap a shape dragged to `bounds`
+    pub fn snap(&self, context: &SnapContext, bounds: Rect) -> SnapResult {
+        let mut candidates = Vec::new();
+        let mut snapped = bounds;
+
+        for orientation in [GuideOrientation::Vertical, GuideOrientation::Horizontal] {
+            let targets = self.targets(context, orientation);
+            let (start, size) = match orientation {
+                GuideOrientation::Vertical => (bounds.x, bounds.width),
+                GuideOrientation::Horizontal => (bounds.y, bounds.height),
+            };
+            let edges = [
+                (SnapEdge::Start, start),
+                (SnapEdge::Center, start + size / 2.0),
+                (SnapEdge::End, start + size),
+            ];
+
+            let mut axis = Vec::new();
+            for target in &targets {
+                for (edge, at) in edges {
+                    if target.start_only && edge != SnapEdge::Start {
+                        continue;
+                    }
+                    let offset = target.position - at;
+                    if offset.abs() > self.settings.threshold {
+                        continue;
+                    }
+                    axis.push(SnapCandidate {
+                        orientation,
+                        position: target.position,
+                        start: target.start,
+                        end: target.end,
+                        source: target.source,
+                        edge,
+                        offset,
+                    });
+                }
+            }
+            axis.sort_by(|a, b| a.offset.abs().total_cmp(&b.offset.abs()));
+
+            let Some(best) = axis.first().map(|candidate| candidate.offset) else {
+                continue;
+            };
+            match orientation {
+                GuideOrientation::Vertical => snapped.x += best,
+                GuideOrientation::Horizontal => snapped.y += best,
+            }
+            candidates.extend(axis);
+        }
+
+        // Guides are the candidates the snapped shape lines up with, drawn
+        // across both it and their target
+        let guides = candidates
+            .iter()
+            .filter(|candidate| {
+                let at = edge_position(snapped, candidate.orientation, candidate.edge);
+                (candidate.position - at).abs() < ALIGNED_EPSILON
+            })
+            .map(|candidate| {
+                let (start, end) = match candidate.orientation {
+                    GuideOrientation::Vertical => (snapped.y, snapped.bottom()),
+                    GuideOrientation::Horizontal => (snapped.x, snapped.right()),
+                };
+                SnapCandidate {
+                    start: candidate.start.min(start),
+                    end: candidate.end.max(end),
+                    offset: 0.0,
+                    ..candidate.clone()
+                }
+            })
+            .collect();
+        candidates.sort_by(|a, b| a.offset.abs().total_cmp(&b.offset.abs()));
+
+        SnapResult {
+            bounds: snapped,
+            guides,
+            candidates,
+        }
+    }
+
+    /// Lines to align to on one axis
+    fn targets(&self, context: &SnapContext, orientation: GuideOrientation) -> Vec<Target> {
+        // Start and end on the axis, then start and end across it
+        let span = |rect: Rect| match orientation {
+            GuideOrientation::Vertical => (rect.x, rect.right(), rect.y, rect.bottom()),
+            GuideOrientation::Horizontal => (rect.y, rect.bottom(), rect.x, rect.right()),
+        };
+        let target = |position, (start, end), source| Target {
+            position,
+            start,
+            end,
+            source,
+            start_only: false,
+        };
+
+        let mut targets = Vec::new();
+        let (page_start, page_end, page_across_start, page_across_end) = span(context.page);
+        let page_extent = (page_across_start, page_across_end);
+        if self.settings.snap_to_page {
+            targets.push(target(page_start, page_extent, GuideSource::PageEdge));
+            targets.push(target(page_end, page_extent, GuideSource::PageEdge));
+            targets.push(target((page_start + page_end) / 2.0, page_extent, GuideSource::PageCenter));
+            let (margin_start, margin_end, _, _) = span(context.margins);
+            targets.push(target(margin_start, page_extent, GuideSource::Margin));
+            targets.push(target(margin_end, page_extent, GuideSource::Margin));
+        }
+
+        if self.settings.snap_to_shapes {
+            for &(id, rect) in &context.shapes {
+                let (start, end, across_start, across_end) = span(rect);
+                let extent = (across_start, across_end);
+                targets.push(target(start, extent, GuideSource::ShapeEdge(id)));
+                targets.push(target(end, extent, GuideSource::ShapeEdge(id)));
+                targets.push(target((start + end) / 2.0, extent, GuideSource::ShapeCenter(id)));
+            }
+        }
+
+        if let Some(spacing) = self.settings.grid_spacing {
+            let (origin, _, _, _) = span(context.margins);
+            let first = ((page_start - origin) / spacing).ceil() as i64;
+            let last = ((page_end - origin) / spacing).floor() as i64;
+            for index in first..=last {
+                targets.push(Target {
+                    start_only: true,
+                    ..target(origin + index as f32 * spacing, page_extent, GuideSource::Grid)
+                });
+            }
+        }
+
+        targets
+    }
+}
+
+/// Position of one edge of a rect on an axis
+fn edge_position(rect: Rect, orientation: GuideOrientation, edge: SnapEdge) -> f32 {
+    let (start, size) = match orientation {
+        GuideOrientation::Vertical => (rect.x, rect.width),
+        GuideOrientation::Horizontal => (rect.y, rect.height),
+    };
+    match edge {
+        SnapEdge::Start => start,
+        SnapEdge::Center => start + size / 2.0,
+        SnapEdge::End => start + size,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn letter_page(shapes: Vec<(NodeId, Rect)>) -> SnapContext {
+        SnapContext {
+            page: Rect::new(0.0, 0.0, 612.0, 792.0),
+            margins: Rect::new(72.0, 72.0, 468.0, 648.0),
+            shapes,
+        }
+    }
+
+    #[test]
+    fn test_snaps_to_margin_and_shape_center() {
+        let other = NodeId::new();
+        let context = letter_page(vec![(other, Rect::new(300.0, 400.0, 100.0, 50.0))]);
+        let engine = AlignmentGuideEngine::default();
+
+        // Left edge near the margin, vertical center near the other shape's
+        let result = engine.snap(&context, Rect::new(74.5, 408.0, 40.0, 30.0));
+        assert_eq!(result.bounds.x, 72.0);
+        assert_eq!(result.bounds.y, 410.0);
+
+        let vertical = result.guides.iter().find(|g| g.orientation == GuideOrientation::Vertical).unwrap();
+        assert_eq!(vertical.source, GuideSource::Margin);
+        assert_eq!(vertical.edge, SnapEdge::Start);
+        let horizontal = result.guides.iter().find(|g| g.orientation == GuideOrientation::Horizontal).unwrap();
+        assert_eq!(horizontal.source, GuideSource::ShapeCenter(other));
+        assert_eq!(horizontal.edge, SnapEdge::Center);
+        // The guide spans from the dragged shape to the one it aligns with
+        assert_eq!((horizontal.start, horizontal.end), (72.0, 400.0));
+    }
+
+    #[test]
+    fn test_grid_and_threshold() {
+        let engine = AlignmentGuideEngine::new(SnapSettings {
+            snap_to_page: false,
+            ..SnapSettings::default().with_grid(18.0)
+        });
+        let context = letter_page(Vec::new());
+
+        let result = engine.snap(&context, Rect::new(110.0, 200.0, 40.0, 40.0));
+        assert_eq!(result.bounds.x, 108.0);
+        assert_eq!(result.bounds.y, 198.0);
+        assert!(result.guides.iter().all(|g| g.source == GuideSource::Grid));
+
+        // Nothing within reach leaves the shape where it was dropped
+        let engine = AlignmentGuideEngine::new(SnapSettings { snap_to_page: false, ..SnapSettings::default() });
+        let result = engine.snap(&context, Rect::new(110.0, 200.0, 40.0, 40.0));
+        assert_eq!((result.bounds.x, result.bounds.y), (110.0, 200.0));
+        assert!(result.candidates.is_empty());
+    }
+}
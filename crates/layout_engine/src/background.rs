@@ -0,0 +1,315 @@
+//! Background pagination for long documents
+//!
+//! Paginating a several-hundred-page document takes long enough to stall
+//! typing. `BackgroundPaginator` lays out only the pages the viewport needs
+//! on the calling thread, together with an estimated page count, and
+//! paginates the whole document on a worker thread. When the worker
+//! finishes, page boundaries are final: the full layout becomes available
+//! and listeners receive `PaginationEvent::Stabilized`.
+//!
+//! Every call to `paginate` starts a new revision; the worker abandons a
+//! layout in progress as soon as a newer revision arrives.
+
+use crate::{LayoutTree, PageConfig, Paginator, PartialLayout, Result};
+use doc_model::DocumentTree;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use text_engine::ShapeCache;
+
+/// Notification from background pagination
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaginationEvent {
+    /// The whole document has been paginated and page boundaries won't
+    /// change until the next revision
+    Stabilized {
+        /// Revision the layout belongs to
+        revision: u64,
+        /// Exact page count
+        page_count: usize,
+    },
+}
+
+/// Callback notified of pagination events, usually on the worker thread
+pub type PaginationListener = Box<dyn Fn(&PaginationEvent) + Send + Sync>;
+
+/// A document revision queued for the worker
+struct Job {
+    revision: u64,
+    tree: DocumentTree,
+    config: PageConfig,
+}
+
+/// The newest full layout
+#[derive(Default)]
+struct Stable {
+    revision: u64,
+    layout: Option<LayoutTree>,
+}
+
+/// State shared with the worker thread
+struct Shared {
+    /// Newest revision handed to `paginate`
+    latest: AtomicU64,
+    stable: Mutex<Stable>,
+    stabilized: Condvar,
+    listener: Mutex<Option<PaginationListener>>,
+}
+
+impl Shared {
+    fn stable(&self) -> MutexGuard<'_, Stable> {
+        self.stable.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn is_current(&self, revision: u64) -> bool {
+        self.latest.load(Ordering::SeqCst) == revision
+    }
+
+    /// Record a finished layout and notify waiters and the listener
+    fn finish(&self, revision: u64, layout: Option<LayoutTree>) {
+        let page_count = layout.as_ref().map(LayoutTree::page_count);
+        {
+            let mut stable = self.stable();
+            if revision < stable.revision {
+                return;
+            }
+            *stable = Stable { revision, layout };
+        }
+        self.stabilized.notify_all();
+
+        if let Some(page_count) = page_count {
+            let listener = self.listener.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            if let Some(listener) = listener.as_ref() {
+                listener(&PaginationEvent::Stabilized { revision, page_count });
+            }
+        }
+    }
+}
+
+/// Paginator that lays out the viewport immediately and the rest of the
+/// document on a worker thread
+pub struct BackgroundPaginator {
+    /// Paginator for the viewport pages
+    foreground: Paginator,
+    shared: Arc<Shared>,
+    jobs: Option<Sender<Job>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl BackgroundPaginator {
+    /// Create a background paginator and start its worker thread
+    pub fn new(config: PageConfig) -> Self {
+        let shared = Arc::new(Shared {
+            latest: AtomicU64::new(0),
+            stable: Mutex::new(Stable::default()),
+            stabilized: Condvar::new(),
+            listener: Mutex::new(None),
+        });
+        // The worker shapes the same text as the foreground, so they share
+        // shaped runs
+        let mut foreground = Paginator::new(config);
+        let shape_cache = foreground.line_breaker_mut().shaper_mut().shape_cache().clone();
+
+        let (jobs, queue) = mpsc::channel();
+        let worker = {
+            let shared = Arc::clone(&shared);
+            thread::Builder::new()
+                .name("pagination".to_string())
+                .spawn(move || run_worker(queue, shared, shape_cache))
+                .ok()
+        };
+
+        Self {
+            foreground,
+            shared,
+            jobs: worker.is_some().then_some(jobs),
+            worker,
+        }
+    }
+
+    /// Get the page configuration
+    pub fn config(&self) -> &PageConfig {
+        self.foreground.config()
+    }
+
+    /// Get mutable page configuration (takes effect on the next `paginate`)
+    pub fn config_mut(&mut self) -> &mut PageConfig {
+        self.foreground.config_mut()
+    }
+
+    /// Set the callback notified when pagination stabilizes
+    pub fn set_listener(&self, listener: PaginationListener) {
+        *self.shared.listener.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(listener);
+    }
+
+    /// The newest revision started by `paginate`
+    pub fn revision(&self) -> u64 {
+        self.shared.latest.load(Ordering::SeqCst)
+    }
+
+    /// Start a new revision: lay out the first `viewport_pages` pages now
+    /// and queue the rest of the document for the worker
+    ///
+    /// Documents that fit in the viewport pages are complete right away and
+    /// stabilize without involving the worker.
+    pub fn paginate(&mut self, tree: &DocumentTree, viewport_pages: usize) -> Result<PartialLayout> {
+        let revision = self.shared.latest.fetch_add(1, Ordering::SeqCst) + 1;
+        let partial = self.foreground.layout_pages(tree, viewport_pages)?;
+
+        if partial.complete {
+            self.shared.finish(revision, Some(partial.layout.clone()));
+            return Ok(partial);
+        }
+
+        let job = Job {
+            revision,
+            tree: tree.clone(),
+            config: self.foreground.config().clone(),
+        };
+        match &self.jobs {
+            Some(jobs) if jobs.send(job).is_ok() => {}
+            // Without a worker, paginate the whole document here
+            _ => {
+                let layout = Paginator::new(self.foreground.config().clone()).layout(tree)?;
+                self.shared.finish(revision, Some(layout));
+            }
+        }
+        Ok(partial)
+    }
+
+    /// Check if the full layout of the newest revision is available
+    pub fn is_stable(&self) -> bool {
+        self.shared.stable().revision == self.revision()
+    }
+
+    /// Get the full layout of the newest revision, if it's finished
+    pub fn stable_layout(&self) -> Option<LayoutTree> {
+        let stable = self.shared.stable();
+        if stable.revision == self.revision() {
+            stable.layout.clone()
+        } else {
+            None
+        }
+    }
+
+    /// Wait up to `timeout` for the newest revision to stabilize and return
+    /// its full layout
+    pub fn wait_for_stable_layout(&self, timeout: Duration) -> Option<LayoutTree> {
+        let revision = self.revision();
+        let stable = self.shared.stable();
+        let (stable, _) = self
+            .shared
+            .stabilized
+            .wait_timeout_while(stable, timeout, |stable| stable.revision < revision)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if stable.revision == revision {
+            stable.layout.clone()
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for BackgroundPaginator {
+    fn drop(&mut self) {
+        // Cancel the layout in progress and let the worker exit
+        self.shared.latest.store(u64::MAX, Ordering::SeqCst);
+        self.jobs = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Paginate queued revisions until the sending side is dropped
+fn run_worker(queue: Receiver<Job>, shared: Arc<Shared>, shape_cache: ShapeCache) {
+    let mut paginator: Option<Paginator> = None;
+    while let Ok(mut job) = queue.recv() {
+        // Only the newest queued revision matters
+        while let Ok(newer) = queue.try_recv() {
+            job = newer;
+        }
+        if !shared.is_current(job.revision) {
+            continue;
+        }
+
+        let paginator = paginator.get_or_insert_with(|| {
+            let mut created = Paginator::new(job.config.clone());
+            created.line_breaker_mut().shaper_mut().set_shape_cache(shape_cache.clone());
+            created
+        });
+        *paginator.config_mut() = job.config;
+        match paginator.layout_cancellable(&job.tree, || !shared.is_current(job.revision)) {
+            Ok(Some(layout)) => shared.finish(job.revision, Some(layout)),
+            // Superseded by a newer revision
+            Ok(None) => {}
+            // Nothing better than the viewport pages is coming for this
+            // revision; release anyone waiting
+            Err(_) => shared.finish(job.revision, None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::{Node, Paragraph, Run};
+
+    fn create_document(paragraphs: usize) -> DocumentTree {
+        let mut tree = DocumentTree::new();
+        let text = "The quick brown fox jumps over the lazy dog. ".repeat(12);
+        for _ in 0..paragraphs {
+            let para = Paragraph::new();
+            let para_id = para.id();
+            tree.insert_paragraph(para, tree.root_id(), None).unwrap();
+            tree.insert_run(Run::new(&text), para_id, None).unwrap();
+        }
+        tree
+    }
+
+    #[test]
+    fn test_viewport_then_stable_layout() {
+        let tree = create_document(300);
+        let events = Arc::new(Mutex::new(Vec::new()));
+
+        let mut paginator = BackgroundPaginator::new(PageConfig::letter());
+        let received = Arc::clone(&events);
+        paginator.set_listener(Box::new(move |event| received.lock().unwrap().push(event.clone())));
+
+        let partial = paginator.paginate(&tree, 2).unwrap();
+        assert!(!partial.complete);
+        assert_eq!(partial.layout.page_count(), 2);
+
+        let layout = paginator.wait_for_stable_layout(Duration::from_secs(30)).unwrap();
+        let full = Paginator::new(PageConfig::letter()).layout(&tree).unwrap();
+        assert_eq!(layout.page_count(), full.page_count());
+        assert!(paginator.is_stable());
+
+        // The estimate is in the right ballpark
+        let estimate = partial.estimated_page_count as f32;
+        let actual = full.page_count() as f32;
+        assert!((estimate - actual).abs() / actual < 0.25, "{} vs {}", estimate, actual);
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![PaginationEvent::Stabilized {
+                revision: 1,
+                page_count: full.page_count(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_short_document_is_complete_immediately() {
+        let tree = create_document(3);
+        let mut paginator = BackgroundPaginator::new(PageConfig::letter());
+
+        let partial = paginator.paginate(&tree, 2).unwrap();
+        assert!(partial.complete);
+        assert_eq!(partial.estimated_page_count, partial.layout.page_count());
+        assert!(paginator.is_stable());
+        assert_eq!(paginator.stable_layout().unwrap().page_count(), partial.layout.page_count());
+    }
+}
@@ -439,6 +439,25 @@ impl LayoutCache {
         }
     }
 
+    /// Get cached line boxes for a paragraph laid out at `width`, whatever
+    /// the document version
+    ///
+    /// For callers that invalidate edited paragraphs themselves. Lookups
+    /// don't reorder the LRU queue, so a pass over every paragraph stays
+    /// linear.
+    pub fn get_lines(&mut self, node_id: NodeId, width: f32) -> Option<Vec<LineBox>> {
+        match self.paragraph_cache.get(&node_id) {
+            Some(cached) if (cached.width - width).abs() < 0.01 => {
+                self.stats.paragraph_hits += 1;
+                Some(cached.line_boxes.clone())
+            }
+            _ => {
+                self.stats.paragraph_misses += 1;
+                None
+            }
+        }
+    }
+
     /// Store a paragraph layout in the cache (old API for backward compatibility)
     pub fn store(
         &mut self,
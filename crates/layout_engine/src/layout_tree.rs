@@ -1,7 +1,12 @@
 //! Layout tree structure
 
-use doc_model::NodeId;
-use serde::{Deserialize, Serialize};
+use crate::FootnoteSeparator;
+use doc_model::{NodeId, TabLeader};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
 
 /// A rectangle in layout coordinates
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
@@ -55,6 +60,16 @@ pub struct PageBox {
     /// Whether column separators should be drawn
     #[serde(default)]
     pub draw_column_separators: bool,
+    /// Section watermark drawn behind the page content
+    #[serde(default)]
+    pub watermark: Option<PageWatermark>,
+    /// Separator lines drawn above notes on this page
+    #[serde(default)]
+    pub note_separators: Vec<FootnoteSeparator>,
+    /// Page number as shown in the section's format, e.g. "iv"; None
+    /// if the document has no sections
+    #[serde(default)]
+    pub page_label: Option<String>,
 }
 
 impl PageBox {
@@ -67,6 +82,9 @@ impl PageBox {
             areas: Vec::new(),
             section_id: None,
             draw_column_separators: false,
+            watermark: None,
+            note_separators: Vec::new(),
+            page_label: None,
         }
     }
 
@@ -79,6 +97,9 @@ impl PageBox {
             areas: Vec::new(),
             section_id: Some(section_id),
             draw_column_separators: false,
+            watermark: None,
+            note_separators: Vec::new(),
+            page_label: None,
         }
     }
 
@@ -87,16 +108,22 @@ impl PageBox {
         self.areas.push(area);
     }
 
-    /// Get the content area (first non-header/footer area)
+    /// Get the content area (the body, not a header or footer)
     pub fn content_area_box(&self) -> Option<&AreaBox> {
-        // The content area is typically the second area (after header)
-        // or the first if there's no header
-        self.areas.iter().find(|a| !a.columns.is_empty())
+        self.areas.iter().find(|a| a.area_type == AreaType::Content)
     }
 
     /// Get columns from the content area
     pub fn content_columns(&self) -> impl Iterator<Item = &ColumnBox> {
-        self.areas.iter().flat_map(|a| a.columns.iter())
+        self.areas
+            .iter()
+            .filter(|a| a.area_type == AreaType::Content)
+            .flat_map(|a| a.columns.iter())
+    }
+
+    /// Get the header or footer area of this page
+    pub fn area(&self, area_type: AreaType) -> Option<&AreaBox> {
+        self.areas.iter().find(|a| a.area_type == area_type)
     }
 
     /// Enable column separator drawing
@@ -106,6 +133,34 @@ impl PageBox {
     }
 }
 
+/// What a page watermark draws
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WatermarkContent {
+    /// A line of text, its baseline one font size below the top of the bounds
+    Text {
+        text: String,
+        font_family: String,
+        font_size: f32,
+        /// Hex color string
+        color: String,
+    },
+    /// A stored image
+    Picture { resource_id: String },
+}
+
+/// A watermark placed on a page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageWatermark {
+    /// Unrotated bounds in page coordinates, centered on the rotation point
+    pub bounds: Rect,
+    /// Rotation about the center of the bounds in degrees (clockwise)
+    pub rotation: f32,
+    /// Opacity from 0.0 to 1.0
+    pub opacity: f32,
+    /// What to draw
+    pub content: WatermarkContent,
+}
+
 /// Type of area on a page
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum AreaType {
@@ -304,6 +359,10 @@ pub enum InlineType {
     TextBox,
     /// List marker (bullet or number)
     ListMarker,
+    /// Tab character, stretched to its tab stop
+    Tab,
+    /// Field result (page number, page count, ...)
+    Field,
 }
 
 impl Default for InlineType {
@@ -312,19 +371,141 @@ impl Default for InlineType {
     }
 }
 
+/// An immutable string shared by every layout box that uses it
+///
+/// Serializes as a plain string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SharedStr(Arc<str>);
+
+impl SharedStr {
+    /// Get the string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Check if two strings share one allocation
+    pub fn ptr_eq(&self, other: &SharedStr) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Default for SharedStr {
+    fn default() -> Self {
+        Self(Arc::from(""))
+    }
+}
+
+impl Deref for SharedStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for SharedStr {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SharedStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for SharedStr {
+    fn from(text: &str) -> Self {
+        Self(Arc::from(text))
+    }
+}
+
+impl From<String> for SharedStr {
+    fn from(text: String) -> Self {
+        Self(Arc::from(text))
+    }
+}
+
+impl Serialize for SharedStr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for SharedStr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// Interns strings repeated across a layout, such as list marker text and
+/// font names, so each distinct string is allocated once
+#[derive(Debug, Default)]
+pub struct StrInterner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl StrInterner {
+    /// Create an empty interner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the shared copy of a string, adding it if it's new
+    pub fn intern(&mut self, text: &str) -> SharedStr {
+        if let Some(shared) = self.strings.get(text) {
+            return SharedStr(Arc::clone(shared));
+        }
+        let shared: Arc<str> = Arc::from(text);
+        self.strings.insert(Arc::clone(&shared));
+        SharedStr(shared)
+    }
+
+    /// Number of distinct strings
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Check if no strings are interned
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Drop strings no layout box uses any more
+    pub fn prune(&mut self) {
+        self.strings.retain(|shared| Arc::strong_count(shared) > 1);
+    }
+}
+
 /// List marker information for rendering
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ListMarkerInfo {
     /// The formatted marker text (e.g., "1.", "a)", bullet character)
-    pub text: String,
+    pub text: SharedStr,
     /// The font family for the marker
-    pub font: Option<String>,
+    pub font: Option<SharedStr>,
     /// Whether this is a bullet marker
     pub is_bullet: bool,
     /// The list level (0-8)
     pub level: u8,
 }
 
+/// Ruby text placed over an inline's base text
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RubyText {
+    /// The ruby text
+    pub text: SharedStr,
+    /// Font size in points
+    pub font_size: f32,
+    /// Left edge, in the same coordinates as the inline's bounds
+    pub x: f32,
+    /// Baseline, from the top of the line
+    pub baseline: f32,
+    /// Extra space after each character, for distributed alignment
+    pub letter_spacing: f32,
+}
+
 /// An inline element (text run, inline image)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InlineBox {
@@ -338,9 +519,19 @@ pub struct InlineBox {
     /// Type of inline content
     #[serde(default)]
     pub inline_type: InlineType,
-    /// List marker info (for list markers only)
+    /// List marker info (for list markers only), boxed since most inlines
+    /// have none
+    #[serde(default)]
+    pub list_marker: Option<Box<ListMarkerInfo>>,
+    /// Leader drawn across the tab (for tabs only)
+    #[serde(default)]
+    pub tab_leader: TabLeader,
+    /// Evaluated result text (for fields only)
     #[serde(default)]
-    pub list_marker: Option<ListMarkerInfo>,
+    pub field_text: Option<SharedStr>,
+    /// Ruby set over the text (for text inlines with a ruby annotation)
+    #[serde(default)]
+    pub ruby: Option<Box<RubyText>>,
 }
 
 impl InlineBox {
@@ -354,6 +545,9 @@ impl InlineBox {
             end_offset: end,
             inline_type: InlineType::Text,
             list_marker: None,
+            tab_leader: TabLeader::None,
+            field_text: None,
+            ruby: None,
         }
     }
 
@@ -367,6 +561,9 @@ impl InlineBox {
             end_offset: 0,
             inline_type: InlineType::Image,
             list_marker: None,
+            tab_leader: TabLeader::None,
+            field_text: None,
+            ruby: None,
         }
     }
 
@@ -379,10 +576,55 @@ impl InlineBox {
             start_offset: 0,
             end_offset: 0,
             inline_type: InlineType::ListMarker,
-            list_marker: Some(marker),
+            list_marker: Some(Box::new(marker)),
+            tab_leader: TabLeader::None,
+            field_text: None,
+            ruby: None,
+        }
+    }
+
+    /// Create a new tab inline box for the tab character at `offset` in a run
+    pub fn tab(node_id: NodeId, bounds: Rect, offset: usize, leader: TabLeader) -> Self {
+        Self {
+            node_id,
+            bounds,
+            direction: Direction::Ltr,
+            start_offset: offset,
+            end_offset: offset + 1,
+            inline_type: InlineType::Tab,
+            list_marker: None,
+            tab_leader: leader,
+            field_text: None,
+            ruby: None,
         }
     }
 
+    /// Create a new field inline box showing `text`
+    pub fn field(node_id: NodeId, bounds: Rect, text: SharedStr) -> Self {
+        Self {
+            node_id,
+            bounds,
+            direction: Direction::Ltr,
+            start_offset: 0,
+            end_offset: 0,
+            inline_type: InlineType::Field,
+            list_marker: None,
+            tab_leader: TabLeader::None,
+            field_text: Some(text),
+            ruby: None,
+        }
+    }
+
+    /// Check if this is a field inline
+    pub fn is_field(&self) -> bool {
+        matches!(self.inline_type, InlineType::Field)
+    }
+
+    /// Check if this is a tab inline
+    pub fn is_tab(&self) -> bool {
+        matches!(self.inline_type, InlineType::Tab)
+    }
+
     /// Check if this is an image inline
     pub fn is_image(&self) -> bool {
         matches!(self.inline_type, InlineType::Image)
@@ -413,6 +655,9 @@ impl InlineBox {
             end_offset: 0,
             inline_type: InlineType::Shape,
             list_marker: None,
+            tab_leader: TabLeader::None,
+            field_text: None,
+            ruby: None,
         }
     }
 
@@ -431,6 +676,9 @@ impl InlineBox {
             end_offset: 0,
             inline_type: InlineType::TextBox,
             list_marker: None,
+            tab_leader: TabLeader::None,
+            field_text: None,
+            ruby: None,
         }
     }
 }
@@ -482,6 +730,21 @@ pub struct FloatingTextBox {
     pub blocks: Vec<BlockBox>,
 }
 
+/// Ink placed over a page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FloatingInk {
+    /// The ink node ID
+    pub node_id: NodeId,
+    /// Page position of the anchor paragraph's top-left corner, which
+    /// stroke coordinates are relative to
+    pub origin_x: f32,
+    pub origin_y: f32,
+    /// Bounds of the strokes on the page
+    pub bounds: Rect,
+    /// The page index the ink is on
+    pub page_index: usize,
+}
+
 /// Line number information for a single line
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LineNumberItem {
@@ -508,6 +771,9 @@ pub struct LayoutTree {
     /// Floating text boxes (positioned independently of text flow)
     #[serde(default)]
     pub floating_textboxes: Vec<FloatingTextBox>,
+    /// Ink drawn over the pages
+    #[serde(default)]
+    pub floating_inks: Vec<FloatingInk>,
     /// Line numbers per page (indexed by page index)
     #[serde(default)]
     pub line_numbers: Vec<Vec<LineNumberItem>>,
@@ -520,6 +786,7 @@ impl LayoutTree {
             floating_images: Vec::new(),
             floating_shapes: Vec::new(),
             floating_textboxes: Vec::new(),
+            floating_inks: Vec::new(),
             line_numbers: Vec::new(),
         }
     }
@@ -562,6 +829,16 @@ impl LayoutTree {
         self.floating_textboxes.iter().filter(move |tb| tb.page_index == page_index)
     }
 
+    /// Add ink to the layout
+    pub fn add_floating_ink(&mut self, ink: FloatingInk) {
+        self.floating_inks.push(ink);
+    }
+
+    /// Get the ink on a specific page
+    pub fn floating_inks_on_page(&self, page_index: usize) -> impl Iterator<Item = &FloatingInk> {
+        self.floating_inks.iter().filter(move |ink| ink.page_index == page_index)
+    }
+
     /// Add a line number item for a specific page
     pub fn add_line_number(&mut self, page_index: usize, item: LineNumberItem) {
         // Ensure we have enough page slots
@@ -13,6 +13,10 @@ mod table_layout;
 mod view_mode;
 mod footnote_layout;
 mod line_numbers;
+mod navigation;
+mod background;
+mod alignment_guides;
+mod statistics;
 
 pub use layout_tree::*;
 pub use line_breaker::*;
@@ -24,3 +28,7 @@ pub use table_layout::*;
 pub use view_mode::*;
 pub use footnote_layout::*;
 pub use line_numbers::*;
+pub use navigation::*;
+pub use background::*;
+pub use alignment_guides::*;
+pub use statistics::*;
@@ -11,8 +11,14 @@
 //! 4. Fill lines greedily, breaking at allowed positions
 //! 5. Calculate proper line metrics for mixed content
 
-use crate::{BidiAnalyzer, BidiRun, Direction, InlineBox, LineBox, ListMarkerInfo, Rect, Result};
-use doc_model::{Alignment, DocumentTree, LineSpacing, Node, NodeId, NumId};
+use crate::{
+    is_east_asian, level_for_direction, BidiAnalyzer, BidiRun, Direction, InlineBox, LineBox, ListMarkerInfo, Rect,
+    Result, RubyText, SharedStr, StrInterner,
+};
+use doc_model::{
+    next_tab_stop, Alignment, CharacterProperties, DocumentTree, FieldContext, FieldEvaluator, LineSpacing, Node,
+    NodeId, NumId, RubyAlignment, TabAlignment, TabLeader, TabStop, DEFAULT_TAB_INTERVAL,
+};
 use text_engine::{FontManager, ShapedRun, TextShaper};
 
 /// Unicode line break opportunity types
@@ -70,7 +76,7 @@ pub struct ListMarkerSegment {
     /// The paragraph node ID this marker belongs to
     pub para_id: NodeId,
     /// The formatted marker text
-    pub text: String,
+    pub text: SharedStr,
     /// Width of the marker
     pub width: f32,
     /// Height of the marker
@@ -80,13 +86,117 @@ pub struct ListMarkerSegment {
     /// Descender
     pub descender: f32,
     /// Font family for the marker
-    pub font: Option<String>,
+    pub font: Option<SharedStr>,
     /// Whether this is a bullet marker
     pub is_bullet: bool,
     /// The list level
     pub level: u8,
 }
 
+/// A field result layout item
+#[derive(Debug, Clone)]
+pub struct FieldSegment {
+    /// The field node ID
+    pub field_id: NodeId,
+    /// The evaluated result text
+    pub text: SharedStr,
+    /// Width of the text
+    pub width: f32,
+    /// Ascender
+    pub ascender: f32,
+    /// Descender
+    pub descender: f32,
+}
+
+/// A run with ruby text over it
+///
+/// The run's text is kept on one line, and the item is as wide as the
+/// wider of the base text and the ruby.
+#[derive(Debug, Clone)]
+pub struct RubySegment {
+    /// The run ID
+    pub run_id: NodeId,
+    /// Length of the run's text in bytes
+    pub end_offset: usize,
+    /// Width of the item
+    pub width: f32,
+    /// Width of the base text
+    pub base_width: f32,
+    /// Ascender of the base text
+    pub base_ascender: f32,
+    /// Ascender, including the ruby
+    pub ascender: f32,
+    /// Descender
+    pub descender: f32,
+    /// The ruby text
+    pub ruby_text: SharedStr,
+    /// Ruby font size in points
+    pub ruby_font_size: f32,
+    /// Width of the ruby text
+    pub ruby_width: f32,
+    /// Distance from the base baseline up to the ruby baseline
+    pub ruby_raise: f32,
+    /// Alignment of the ruby over the base
+    pub alignment: RubyAlignment,
+    /// BiDi embedding level
+    pub bidi_level: u8,
+}
+
+impl RubySegment {
+    /// Offset of the ruby from the item's left edge, and the space added
+    /// after each ruby character
+    fn ruby_placement(&self) -> (f32, f32) {
+        let slack = (self.width - self.ruby_width).max(0.0);
+        let count = self.ruby_text.chars().count() as f32;
+        match self.alignment {
+            RubyAlignment::Left => (0.0, 0.0),
+            RubyAlignment::Right | RubyAlignment::RightVertical => (slack, 0.0),
+            RubyAlignment::DistributeLetter if count > 1.0 => (0.0, slack / (count - 1.0)),
+            RubyAlignment::DistributeSpace if count > 0.0 => (slack / count / 2.0, slack / count),
+            _ => (slack / 2.0, 0.0),
+        }
+    }
+}
+
+/// A tab character layout item
+///
+/// A tab's width isn't known until line filling places it: it stretches from
+/// its position on the line to the next tab stop.
+#[derive(Debug, Clone)]
+pub struct TabSegment {
+    /// The run containing the tab
+    pub run_id: NodeId,
+    /// Byte offset of the tab within the run's text
+    pub offset: usize,
+    /// Resolved width in points
+    pub width: f32,
+    /// Ascender height
+    pub ascender: f32,
+    /// Descender depth
+    pub descender: f32,
+    /// Width of the text after the tab, up to the next tab or line end
+    pub following_width: f32,
+    /// Width of the text after the tab up to its decimal separator
+    pub decimal_width: Option<f32>,
+    /// Leader of the stop the tab resolved to
+    pub leader: TabLeader,
+}
+
+impl TabSegment {
+    /// Stretch the tab from `position` to the next tab stop
+    fn resolve(&mut self, position: f32, config: &LineBreakConfig) {
+        let stop = next_tab_stop(&config.tab_stops, position, config.default_tab_interval);
+        let aligned_width = match stop.alignment {
+            TabAlignment::Center => self.following_width / 2.0,
+            TabAlignment::Right => self.following_width,
+            TabAlignment::Decimal => self.decimal_width.unwrap_or(self.following_width),
+            _ => 0.0,
+        };
+        self.width = (stop.position - position - aligned_width).max(0.0);
+        self.leader = stop.leader;
+    }
+}
+
 /// A layout item that can be placed on a line (text or image)
 #[derive(Debug, Clone)]
 pub enum LayoutItem {
@@ -96,6 +206,12 @@ pub enum LayoutItem {
     Image(ImageSegment),
     /// A list marker (bullet or number)
     ListMarker(ListMarkerSegment),
+    /// A tab character
+    Tab(TabSegment),
+    /// A field result
+    Field(FieldSegment),
+    /// A run with ruby text
+    Ruby(RubySegment),
 }
 
 impl LayoutItem {
@@ -105,6 +221,9 @@ impl LayoutItem {
             Self::Text(seg) => seg.width,
             Self::Image(img) => img.width,
             Self::ListMarker(marker) => marker.width,
+            Self::Tab(tab) => tab.width,
+            Self::Field(field) => field.width,
+            Self::Ruby(ruby) => ruby.width,
         }
     }
 
@@ -114,6 +233,9 @@ impl LayoutItem {
             Self::Text(seg) => seg.ascender,
             Self::Image(img) => img.height, // Image sits on baseline
             Self::ListMarker(marker) => marker.ascender,
+            Self::Tab(tab) => tab.ascender,
+            Self::Field(field) => field.ascender,
+            Self::Ruby(ruby) => ruby.ascender,
         }
     }
 
@@ -123,6 +245,9 @@ impl LayoutItem {
             Self::Text(seg) => seg.descender,
             Self::Image(_) => 0.0, // Images sit on the baseline
             Self::ListMarker(marker) => marker.descender,
+            Self::Tab(tab) => tab.descender,
+            Self::Field(field) => field.descender,
+            Self::Ruby(ruby) => ruby.descender,
         }
     }
 
@@ -132,6 +257,9 @@ impl LayoutItem {
             Self::Text(seg) => seg.is_whitespace,
             Self::Image(_) => false,
             Self::ListMarker(_) => false,
+            Self::Tab(_) => false,
+            Self::Field(_) => false,
+            Self::Ruby(_) => false,
         }
     }
 
@@ -141,6 +269,9 @@ impl LayoutItem {
             Self::Text(seg) => seg.break_after,
             Self::Image(img) => img.break_after,
             Self::ListMarker(_) => BreakOpportunity::NoBreak, // No break after marker
+            Self::Tab(_) => BreakOpportunity::Allowed,
+            Self::Field(_) => BreakOpportunity::Allowed,
+            Self::Ruby(_) => BreakOpportunity::Allowed,
         }
     }
 
@@ -150,15 +281,21 @@ impl LayoutItem {
             Self::Text(seg) => seg.run_id,
             Self::Image(img) => img.node_id,
             Self::ListMarker(marker) => marker.para_id,
+            Self::Tab(tab) => tab.run_id,
+            Self::Field(field) => field.field_id,
+            Self::Ruby(ruby) => ruby.run_id,
         }
     }
 
-    /// Get BiDi level (images and markers default to 0 = LTR)
+    /// Get BiDi level (images, markers, tabs, and fields default to 0 = LTR)
     pub fn bidi_level(&self) -> u8 {
         match self {
             Self::Text(seg) => seg.bidi_level,
             Self::Image(_) => 0,
             Self::ListMarker(_) => 0,
+            Self::Tab(_) => 0,
+            Self::Field(_) => 0,
+            Self::Ruby(ruby) => ruby.bidi_level,
         }
     }
 
@@ -168,6 +305,15 @@ impl LayoutItem {
             Self::Text(seg) => seg.direction,
             Self::Image(_) => Direction::Ltr,
             Self::ListMarker(_) => Direction::Ltr,
+            Self::Tab(_) => Direction::Ltr,
+            Self::Field(_) => Direction::Ltr,
+            Self::Ruby(ruby) => {
+                if ruby.bidi_level % 2 == 0 {
+                    Direction::Ltr
+                } else {
+                    Direction::Rtl
+                }
+            }
         }
     }
 
@@ -219,6 +365,19 @@ pub struct LineBreakConfig {
     pub list_marker_font: Option<String>,
     /// Hanging indent for list (space for marker)
     pub list_hanging: f32,
+    /// Custom tab stops (positions from the text column's left edge)
+    pub tab_stops: Vec<TabStop>,
+    /// Interval between automatic tab stops past the custom ones
+    pub default_tab_interval: f32,
+    /// Page and section values for evaluating fields (None = show each
+    /// field's cached result)
+    pub field_context: Option<FieldContext>,
+    /// Line pitch of the section's document grid; lines take a whole
+    /// number of pitches
+    pub line_grid_pitch: Option<f32>,
+    /// Character pitch of the section's document grid; East Asian
+    /// characters take a whole number of pitches
+    pub char_grid_pitch: Option<f32>,
 }
 
 impl Default for LineBreakConfig {
@@ -239,6 +398,11 @@ impl Default for LineBreakConfig {
             list_is_bullet: false,
             list_marker_font: None,
             list_hanging: 0.0,
+            tab_stops: Vec::new(),
+            default_tab_interval: DEFAULT_TAB_INTERVAL,
+            field_context: None,
+            line_grid_pitch: None,
+            char_grid_pitch: None,
         }
     }
 }
@@ -252,6 +416,8 @@ struct RunInfo {
     text_start: usize,
     /// End byte offset in the full paragraph text
     text_end: usize,
+    /// Byte offset of this text within the run (runs are split at tabs)
+    run_offset: usize,
     /// Font size for this run
     font_size: f32,
     /// Font family for this run
@@ -260,6 +426,8 @@ struct RunInfo {
     bold: bool,
     /// Whether italic
     italic: bool,
+    /// Resolved character formatting (caps, spacing, scale, position)
+    format: CharacterProperties,
 }
 
 /// A pending line being built
@@ -337,20 +505,28 @@ pub struct LineBreaker {
     shaper: TextShaper,
     /// Font manager for font metrics
     font_manager: FontManager,
+    /// Strings shared by the lines this breaker produces
+    interner: StrInterner,
 }
 
 impl LineBreaker {
     /// Create a new line breaker
     pub fn new() -> Self {
-        Self {
-            shaper: TextShaper::new(),
-            font_manager: FontManager::new(),
-        }
+        Self::with_engines(TextShaper::new(), FontManager::new())
     }
 
     /// Create a line breaker with custom shaper and font manager
     pub fn with_engines(shaper: TextShaper, font_manager: FontManager) -> Self {
-        Self { shaper, font_manager }
+        Self {
+            shaper,
+            font_manager,
+            interner: StrInterner::new(),
+        }
+    }
+
+    /// Forget interned strings that no line uses any more
+    pub fn prune_strings(&mut self) {
+        self.interner.prune();
     }
 
     /// Get a mutable reference to the text shaper
@@ -396,49 +572,94 @@ impl LineBreaker {
 
                 layout_items.push(LayoutItem::ListMarker(ListMarkerSegment {
                     para_id,
-                    text: marker_text.clone(),
+                    text: self.interner.intern(marker_text),
                     width: marker_shaped.width,
                     height: marker_shaped.ascender + marker_shaped.descender,
                     ascender: marker_shaped.ascender,
                     descender: marker_shaped.descender,
-                    font: config.list_marker_font.clone(),
+                    font: config.list_marker_font.as_deref().map(|font| self.interner.intern(font)),
                     is_bullet: config.list_is_bullet,
                     level: config.list_level.unwrap_or(0),
                 }));
             }
         }
 
+        // Fields take the size of the text before them
+        let mut font_size = config.font_size;
         for &child_id in para.children() {
             // Check if it's a text run
             if let Some(run) = tree.get_run(child_id) {
-                let start = full_text.len();
-                full_text.push_str(&run.text);
-                let end = full_text.len();
-
-                run_infos.push(RunInfo {
-                    run_id: child_id,
-                    text_start: start,
-                    text_end: end,
-                    font_size: run.style.font_size.unwrap_or(config.font_size),
-                    font_family: run.style.font_family.clone(),
-                    bold: run.style.bold.unwrap_or(false),
-                    italic: run.style.italic.unwrap_or(false),
-                });
+                font_size = run.style.font_size.unwrap_or(config.font_size);
+                let format = tree.compute_character_properties(child_id).unwrap_or_default();
+
+                // A run with ruby is laid out whole, with the ruby over it
+                if let Some(ruby) = run.ruby.as_ref().filter(|_| !run.text.is_empty()) {
+                    self.flush_text(&mut full_text, &mut run_infos, &mut layout_items, config)?;
+                    let run_info = RunInfo {
+                        run_id: child_id,
+                        text_start: 0,
+                        text_end: run.text.len(),
+                        run_offset: 0,
+                        font_size,
+                        font_family: run.style.font_family.clone(),
+                        bold: run.style.bold.unwrap_or(false),
+                        italic: run.style.italic.unwrap_or(false),
+                        format,
+                    };
+                    let segment = self.ruby_segment(&run_info, &run.text, ruby, config);
+                    layout_items.push(LayoutItem::Ruby(segment));
+                    continue;
+                }
+
+                // Tabs become their own layout items; the text between them
+                // is shaped as usual
+                let mut piece_start = 0;
+                let piece_ends = run
+                    .text
+                    .match_indices('\t')
+                    .map(|(i, _)| i)
+                    .chain(std::iter::once(run.text.len()));
+                for piece_end in piece_ends {
+                    if piece_end > piece_start {
+                        let start = full_text.len();
+                        full_text.push_str(&run.text[piece_start..piece_end]);
+                        let end = full_text.len();
+
+                        run_infos.push(RunInfo {
+                            run_id: child_id,
+                            text_start: start,
+                            text_end: end,
+                            run_offset: piece_start,
+                            font_size,
+                            font_family: run.style.font_family.clone(),
+                            bold: run.style.bold.unwrap_or(false),
+                            italic: run.style.italic.unwrap_or(false),
+                            format: format.clone(),
+                        });
+                    }
+
+                    if piece_end < run.text.len() {
+                        self.flush_text(&mut full_text, &mut run_infos, &mut layout_items, config)?;
+                        layout_items.push(LayoutItem::Tab(TabSegment {
+                            run_id: child_id,
+                            offset: piece_end,
+                            width: 0.0,
+                            ascender: font_size * 0.8,
+                            descender: font_size * 0.2,
+                            following_width: 0.0,
+                            decimal_width: None,
+                            leader: TabLeader::None,
+                        }));
+                    }
+                    piece_start = piece_end + 1;
+                }
             }
             // Check if it's an inline image
             else if let Some(image) = tree.get_image(child_id) {
                 // Only process inline images here
                 if image.is_inline() {
                     // First, process any pending text runs
-                    if !run_infos.is_empty() {
-                        let break_opportunities = self.find_break_opportunities(&full_text);
-                        let segments = self.create_segments(&full_text, &run_infos, &break_opportunities, config)?;
-                        for seg in segments {
-                            layout_items.push(LayoutItem::Text(seg));
-                        }
-                        run_infos.clear();
-                        full_text.clear();
-                    }
+                    self.flush_text(&mut full_text, &mut run_infos, &mut layout_items, config)?;
 
                     // Add the image as a layout item
                     let img_width = image.effective_width(config.available_width);
@@ -451,17 +672,51 @@ impl LineBreaker {
                     }));
                 }
             }
-        }
+            // Embedded objects are laid out like inline images
+            else if let Some(object) = tree.get_embedded_object(child_id) {
+                self.flush_text(&mut full_text, &mut run_infos, &mut layout_items, config)?;
+
+                layout_items.push(LayoutItem::Image(ImageSegment {
+                    node_id: child_id,
+                    width: object.width.min(config.available_width),
+                    height: object.height,
+                    break_after: BreakOpportunity::Allowed,
+                }));
+            }
+            // Check if it's a field
+            else if let Some(field) = tree.get_field(child_id) {
+                self.flush_text(&mut full_text, &mut run_infos, &mut layout_items, config)?;
 
-        // Process any remaining text runs
-        if !run_infos.is_empty() {
-            let break_opportunities = self.find_break_opportunities(&full_text);
-            let segments = self.create_segments(&full_text, &run_infos, &break_opportunities, config)?;
-            for seg in segments {
-                layout_items.push(LayoutItem::Text(seg));
+                let text = match &config.field_context {
+                    Some(context) if field.auto_updates_on_layout() && !field.show_code => {
+                        FieldEvaluator::evaluate(field, context)
+                    }
+                    _ => field.display_text(),
+                };
+                let shaped = self.shaper.shape(&text, font_size)
+                    .unwrap_or_else(|_| ShapedRun {
+                        glyphs: Vec::new(),
+                        width: text.len() as f32 * font_size * 0.6,
+                        font_size,
+                        units_per_em: 1000,
+                        ascender: font_size * 0.8,
+                        descender: font_size * 0.2,
+                        line_gap: 0.0,
+                    });
+                layout_items.push(LayoutItem::Field(FieldSegment {
+                    field_id: child_id,
+                    text: self.interner.intern(&text),
+                    width: shaped.width,
+                    ascender: shaped.ascender,
+                    descender: shaped.descender,
+                }));
             }
         }
 
+        // Process any remaining text runs
+        self.flush_text(&mut full_text, &mut run_infos, &mut layout_items, config)?;
+        self.measure_tab_targets(tree, &mut layout_items);
+
         // Handle empty paragraph
         if layout_items.is_empty() {
             return self.create_empty_paragraph(config);
@@ -484,10 +739,75 @@ impl LineBreaker {
         Ok(BrokenParagraph { lines, total_height })
     }
 
+    /// Shape pending text runs into layout items
+    fn flush_text(
+        &self,
+        full_text: &mut String,
+        run_infos: &mut Vec<RunInfo>,
+        layout_items: &mut Vec<LayoutItem>,
+        config: &LineBreakConfig,
+    ) -> Result<()> {
+        if run_infos.is_empty() {
+            return Ok(());
+        }
+
+        let mut break_opportunities = self.find_break_opportunities(full_text);
+        // The end of the pending text is only a forced break if the text
+        // ends with a line break; otherwise a tab or image follows on the line
+        if !full_text.ends_with(['\n', '\r', '\u{000B}', '\u{2028}', '\u{2029}']) {
+            if let Some(last) = break_opportunities.last_mut() {
+                *last = BreakOpportunity::Allowed;
+            }
+        }
+        let segments = self.create_segments(full_text, run_infos, &break_opportunities, config)?;
+        layout_items.extend(segments.into_iter().map(LayoutItem::Text));
+        run_infos.clear();
+        full_text.clear();
+        Ok(())
+    }
+
+    /// Measure the text each tab aligns at center, right, and decimal stops
+    fn measure_tab_targets(&self, tree: &DocumentTree, items: &mut [LayoutItem]) {
+        for i in 0..items.len() {
+            if !matches!(items[i], LayoutItem::Tab(_)) {
+                continue;
+            }
+
+            let mut following_width = 0.0;
+            let mut decimal_width = None;
+            for item in &items[i + 1..] {
+                match item {
+                    LayoutItem::Tab(_) => break,
+                    LayoutItem::Text(seg) if decimal_width.is_none() => {
+                        let text = tree
+                            .get_run(seg.run_id)
+                            .and_then(|run| run.text.get(seg.start_offset..seg.end_offset))
+                            .unwrap_or("");
+                        if let Some(pos) = text.find('.') {
+                            let before = text[..pos].chars().count() as f32;
+                            let total = text.chars().count() as f32;
+                            decimal_width = Some(following_width + seg.width * before / total);
+                        }
+                        following_width += seg.width;
+                    }
+                    other => following_width += other.width(),
+                }
+                if item.break_after() == BreakOpportunity::Mandatory {
+                    break;
+                }
+            }
+
+            if let LayoutItem::Tab(tab) = &mut items[i] {
+                tab.following_width = following_width;
+                tab.decimal_width = decimal_width;
+            }
+        }
+    }
+
     /// Create an empty paragraph with a single empty line
     fn create_empty_paragraph(&self, config: &LineBreakConfig) -> Result<BrokenParagraph> {
-        let line_height = config.font_size * config.line_spacing * 1.2;
-        let baseline = config.font_size * 0.8;
+        let (line_height, baseline) =
+            snap_to_line_grid(config.font_size * config.line_spacing * 1.2, config.font_size * 0.8, config);
 
         let line = LineBox {
             bounds: Rect::new(
@@ -545,28 +865,7 @@ impl LineBreaker {
                 continue;
             }
 
-            // Shape the run text
-            let shaped = self.shaper.shape_run(
-                run_text,
-                run_info.font_family.as_deref(),
-                run_info.font_size,
-                run_info.bold,
-                run_info.italic,
-            ).unwrap_or_else(|_| {
-                // Fallback to basic shaping
-                self.shaper.shape(run_text, run_info.font_size).unwrap_or_else(|_| {
-                    // Ultimate fallback
-                    ShapedRun {
-                        glyphs: Vec::new(),
-                        width: run_text.len() as f32 * run_info.font_size * 0.6,
-                        font_size: run_info.font_size,
-                        units_per_em: 1000,
-                        ascender: run_info.font_size * 0.8,
-                        descender: run_info.font_size * 0.2,
-                        line_gap: 0.0,
-                    }
-                })
-            });
+            let shaped = self.shape_run_text(run_text, run_info, config);
 
             // Split run into segments at break opportunities
             let run_segments = self.split_into_segments(
@@ -583,6 +882,84 @@ impl LineBreaker {
         Ok(segments)
     }
 
+    /// Shape a run's text as displayed (caps, spacing and grid applied)
+    fn shape_run_text(&self, run_text: &str, run_info: &RunInfo, config: &LineBreakConfig) -> ShapedRun {
+        let display_text = run_info.format.display_text(run_text);
+        let mut shaped = self.shaper.shape_run_with_kerning(
+            &display_text,
+            run_info.font_family.as_deref(),
+            run_info.font_size,
+            run_info.bold,
+            run_info.italic,
+            run_info.format.kerning_enabled(run_info.font_size),
+        ).unwrap_or_else(|_| {
+            // Fallback to basic shaping
+            self.shaper.shape(&display_text, run_info.font_size).unwrap_or_else(|_| {
+                // Ultimate fallback
+                ShapedRun {
+                    glyphs: Vec::new(),
+                    width: run_text.len() as f32 * run_info.font_size * 0.6,
+                    font_size: run_info.font_size,
+                    units_per_em: 1000,
+                    ascender: run_info.font_size * 0.8,
+                    descender: run_info.font_size * 0.2,
+                    line_gap: 0.0,
+                }
+            })
+        });
+        apply_character_format(&mut shaped, run_text, &display_text, &run_info.format);
+        if let Some(pitch) = config.char_grid_pitch {
+            apply_char_grid(&mut shaped, run_text, pitch);
+        }
+        shaped
+    }
+
+    /// Lay out a run with ruby as one item, the ruby over the base text
+    fn ruby_segment(
+        &mut self,
+        run_info: &RunInfo,
+        run_text: &str,
+        ruby: &doc_model::RubyAnnotation,
+        config: &LineBreakConfig,
+    ) -> RubySegment {
+        let base = self.shape_run_text(run_text, run_info, config);
+        let ruby_font_size = ruby.effective_font_size(run_info.font_size);
+        let ruby_shaped = self.shaper.shape(&ruby.text, ruby_font_size).unwrap_or_else(|_| ShapedRun {
+            glyphs: Vec::new(),
+            width: ruby.text.chars().count() as f32 * ruby_font_size,
+            font_size: ruby_font_size,
+            units_per_em: 1000,
+            ascender: ruby_font_size * 0.8,
+            descender: ruby_font_size * 0.2,
+            line_gap: 0.0,
+        });
+
+        // Without an explicit raise, the ruby's descent rests on the base
+        // text's ascent
+        let ruby_raise = ruby.raise.unwrap_or(base.ascender + ruby_shaped.descender);
+        let bidi_level = BidiAnalyzer::new()
+            .get_levels(run_text, config.direction)
+            .first()
+            .copied()
+            .unwrap_or_else(|| level_for_direction(config.direction));
+
+        RubySegment {
+            run_id: run_info.run_id,
+            end_offset: run_text.len(),
+            width: base.width.max(ruby_shaped.width),
+            base_width: base.width,
+            base_ascender: base.ascender,
+            ascender: base.ascender.max(ruby_raise + ruby_shaped.ascender),
+            descender: base.descender,
+            ruby_text: self.interner.intern(&ruby.text),
+            ruby_font_size,
+            ruby_width: ruby_shaped.width,
+            ruby_raise,
+            alignment: ruby.alignment,
+            bidi_level,
+        }
+    }
+
     /// Split a shaped run into segments at break opportunities
     fn split_into_segments(
         &self,
@@ -643,8 +1020,8 @@ impl LineBreaker {
 
                 segments.push(ShapedSegment {
                     run_id: run_info.run_id,
-                    start_offset: segment_start,
-                    end_offset: segment_end,
+                    start_offset: run_info.run_offset + segment_start,
+                    end_offset: run_info.run_offset + segment_end,
                     width,
                     ascender: shaped.ascender,
                     descender: shaped.descender,
@@ -677,8 +1054,8 @@ impl LineBreaker {
 
             segments.push(ShapedSegment {
                 run_id: run_info.run_id,
-                start_offset: 0,
-                end_offset: run_text.len(),
+                start_offset: run_info.run_offset,
+                end_offset: run_info.run_offset + run_text.len(),
                 width: shaped.width,
                 ascender: shaped.ascender,
                 descender: shaped.descender,
@@ -802,14 +1179,11 @@ impl LineBreaker {
 
         // Ensure at least one line exists
         if lines.is_empty() {
+            let (line_height, baseline) =
+                snap_to_line_grid(config.font_size * line_spacing * 1.2, config.font_size * 0.8, config);
             let empty_line = LineBox {
-                bounds: Rect::new(
-                    config.left_indent,
-                    0.0,
-                    base_available,
-                    config.font_size * line_spacing * 1.2,
-                ),
-                baseline: config.font_size * 0.8,
+                bounds: Rect::new(config.left_indent, 0.0, base_available, line_height),
+                baseline,
                 direction: config.direction,
                 inlines: Vec::new(),
             };
@@ -869,7 +1243,11 @@ impl LineBreaker {
         let extra_space = (available_width - total_content_width).max(0.0);
 
         // Determine starting X position based on alignment and direction
-        let first_line_indent = if pending.is_first_line { config.first_line_indent } else { 0.0 };
+        let first_line_indent = if pending.is_first_line && config.direction == Direction::Ltr {
+            config.first_line_indent
+        } else {
+            0.0
+        };
         let base_x = config.left_indent + first_line_indent;
 
         let (x_start, word_spacing_extra) = match config.alignment {
@@ -894,7 +1272,11 @@ impl LineBreaker {
             Alignment::Justify => {
                 // Don't justify last line or lines with mandatory breaks
                 if is_last_line || reordered_segments.len() <= 1 {
-                    (base_x, 0.0)
+                    if config.direction == Direction::Rtl {
+                        (base_x + extra_space, 0.0)
+                    } else {
+                        (base_x, 0.0)
+                    }
                 } else {
                     // Count word gaps (between segments that are not whitespace)
                     let word_count = reordered_segments.len();
@@ -968,13 +1350,23 @@ impl LineBreaker {
 
         let base_available = config.available_width - config.left_indent - config.right_indent;
 
-        for item in items {
+        for mut item in items {
             let available_width = if is_first_line {
                 base_available - config.first_line_indent
             } else {
                 base_available
             };
 
+            // Tabs stretch from the current position to their stop
+            if let LayoutItem::Tab(tab) = &mut item {
+                let line_start = if is_first_line {
+                    config.left_indent + config.first_line_indent
+                } else {
+                    config.left_indent
+                };
+                tab.resolve(line_start + current_width, config);
+            }
+
             // Check if we need to start a new line
             let would_overflow = current_width + item.width() > available_width
                 && !current_items.is_empty()
@@ -1006,6 +1398,10 @@ impl LineBreaker {
                 current_max_ascender = 0.0;
                 current_max_descender = 0.0;
                 is_first_line = false;
+
+                if let LayoutItem::Tab(tab) = &mut item {
+                    tab.resolve(config.left_indent, config);
+                }
             }
 
             // Update metrics
@@ -1103,6 +1499,7 @@ impl LineBreaker {
         } else {
             max_ascender
         };
+        let (line_height, baseline) = snap_to_line_grid(line_height, baseline, config);
 
         let available_width = config.available_width - config.left_indent - config.right_indent
             - if is_first_line { config.first_line_indent } else { 0.0 };
@@ -1120,15 +1517,24 @@ impl LineBreaker {
             v
         };
 
-        // Calculate total content width
-        let total_content_width: f32 = items_to_render.iter().map(|i| i.width()).sum();
+        // Calculate total content width, including the gap after a list marker
+        let marker_gap = config.list_hanging.max(8.0);
+        let total_content_width: f32 = items_to_render
+            .iter()
+            .map(|i| if i.is_list_marker() { i.width() + marker_gap } else { i.width() })
+            .sum();
 
-        // Reorder for BiDi using item levels
+        // Reorder for BiDi using item levels; the list marker takes the
+        // paragraph level so it leads the line in either direction
         let bidi_analyzer = BidiAnalyzer::new();
+        let base_level = level_for_direction(config.direction);
         let bidi_runs: Vec<BidiRun> = items_to_render
             .iter()
             .enumerate()
-            .map(|(i, item)| BidiRun::new(i, i + 1, item.bidi_level()))
+            .map(|(i, item)| {
+                let level = if item.is_list_marker() { base_level } else { item.bidi_level() };
+                BidiRun::new(i, i + 1, level)
+            })
             .collect();
         let visual_order = bidi_analyzer.visual_order(&bidi_runs);
 
@@ -1140,7 +1546,13 @@ impl LineBreaker {
         // Calculate extra space for alignment
         let extra_space = (available_width - total_content_width).max(0.0);
 
-        let first_line_indent = if is_first_line { config.first_line_indent } else { 0.0 };
+        // An RTL line starts at the right, so its first-line indent is
+        // already taken out of the available width on that side
+        let first_line_indent = if is_first_line && config.direction == Direction::Ltr {
+            config.first_line_indent
+        } else {
+            0.0
+        };
         let base_x = config.left_indent + first_line_indent;
 
         let (x_start, word_spacing_extra) = match config.alignment {
@@ -1161,7 +1573,11 @@ impl LineBreaker {
             }
             Alignment::Justify => {
                 if is_last_line || reordered.len() <= 1 {
-                    (base_x, 0.0)
+                    if config.direction == Direction::Rtl {
+                        (base_x + extra_space, 0.0)
+                    } else {
+                        (base_x, 0.0)
+                    }
                 } else {
                     let gap_count = reordered.len() - 1;
                     let spacing = if gap_count > 0 {
@@ -1189,6 +1605,9 @@ impl LineBreaker {
                         end_offset: seg.end_offset,
                         inline_type: InlineType::Text,
                         list_marker: None,
+                        tab_leader: TabLeader::None,
+                        field_text: None,
+                        ruby: None,
                     });
                     x += seg.width;
                 }
@@ -1211,13 +1630,61 @@ impl LineBreaker {
                         is_bullet: marker.is_bullet,
                         level: marker.level,
                     };
+                    // The gap goes between marker and text: after the
+                    // marker in LTR, before it in RTL
+                    if config.direction == Direction::Rtl {
+                        x += marker_gap;
+                    }
                     inlines.push(InlineBox::list_marker(
                         marker.para_id,
                         Rect::new(x, y_offset_inline, marker.width, marker.height),
                         marker_info,
                     ));
-                    // Add a tab space after the marker
-                    x += marker.width + config.list_hanging.max(8.0);
+                    x += marker.width;
+                    if config.direction == Direction::Ltr {
+                        x += marker_gap;
+                    }
+                }
+                LayoutItem::Tab(tab) => {
+                    let y_offset_inline = baseline - tab.ascender;
+                    inlines.push(InlineBox::tab(
+                        tab.run_id,
+                        Rect::new(x, y_offset_inline, tab.width, tab.ascender + tab.descender),
+                        tab.offset,
+                        tab.leader,
+                    ));
+                    x += tab.width;
+                }
+                LayoutItem::Field(field) => {
+                    let y_offset_inline = baseline - field.ascender;
+                    inlines.push(InlineBox::field(
+                        field.field_id,
+                        Rect::new(x, y_offset_inline, field.width, field.ascender + field.descender),
+                        field.text.clone(),
+                    ));
+                    x += field.width;
+                }
+                LayoutItem::Ruby(ruby) => {
+                    // The base text is centered under a wider ruby
+                    let base_x = x + (ruby.width - ruby.base_width) / 2.0;
+                    let base_height = ruby.base_ascender + ruby.descender;
+                    let (ruby_offset, letter_spacing) = ruby.ruby_placement();
+                    let mut inline = InlineBox::text(
+                        ruby.run_id,
+                        Rect::new(base_x, baseline - ruby.base_ascender, ruby.base_width, base_height),
+                        item.direction(),
+                        0,
+                        ruby.end_offset,
+                    );
+                    inline.ruby = Some(Box::new(RubyText {
+                        text: ruby.ruby_text.clone(),
+                        font_size: ruby.ruby_font_size,
+                        x: x + ruby_offset,
+                        baseline: baseline - ruby.ruby_raise,
+                        letter_spacing,
+                    }));
+                    inlines.push(inline);
+                    x += ruby.width;
                 }
             }
 
@@ -1235,6 +1702,14 @@ impl LineBreaker {
         }
     }
 
+    /// Measure the width of a single line of text
+    pub fn text_width(&self, text: &str, font_size: f32) -> f32 {
+        self.shaper
+            .shape(text, font_size)
+            .map(|shaped| shaped.width)
+            .unwrap_or(text.chars().count() as f32 * font_size * 0.6)
+    }
+
     /// Break text into lines (simpler API for plain text)
     pub fn break_text(
         &self,
@@ -1314,6 +1789,101 @@ impl Default for LineBreaker {
     }
 }
 
+/// Round a line's height up to a whole number of grid lines, centering
+/// the text in the extra space
+fn snap_to_line_grid(line_height: f32, baseline: f32, config: &LineBreakConfig) -> (f32, f32) {
+    match config.line_grid_pitch.filter(|&pitch| pitch > 0.0) {
+        Some(pitch) => {
+            let snapped = (line_height / pitch - 0.01).ceil().max(1.0) * pitch;
+            (snapped, baseline + (snapped - line_height) / 2.0)
+        }
+        None => (line_height, baseline),
+    }
+}
+
+/// Give each East Asian character a whole number of grid cells, centering
+/// the glyph in them
+fn apply_char_grid(shaped: &mut ShapedRun, source: &str, pitch: f32) {
+    if pitch <= 0.0 || shaped.glyphs.is_empty() {
+        return;
+    }
+    let units_per_point = shaped.units_per_em as f32 / shaped.font_size.max(f32::EPSILON);
+    let cell = pitch * units_per_point;
+
+    let mut total_advance = 0i32;
+    for glyph in &mut shaped.glyphs {
+        let on_grid = source
+            .get(glyph.cluster as usize..)
+            .and_then(|rest| rest.chars().next())
+            .is_some_and(takes_grid_cell);
+        if on_grid && glyph.x_advance > 0 {
+            let advance = (glyph.x_advance as f32 / cell - 0.01).ceil().max(1.0) * cell;
+            glyph.x_offset += ((advance - glyph.x_advance as f32) / 2.0).round() as i32;
+            glyph.x_advance = advance.round() as i32;
+        }
+        total_advance += glyph.x_advance;
+    }
+    shaped.width = total_advance as f32 / units_per_point;
+}
+
+/// Check if a character is set on the character grid: ideographs, kana,
+/// hangul, CJK punctuation and fullwidth forms
+fn takes_grid_cell(c: char) -> bool {
+    is_east_asian(c) || matches!(c, '\u{3000}'..='\u{303F}' | '\u{FF00}'..='\u{FFEF}')
+}
+
+/// Adjust shaped glyphs for caps, tracking, horizontal scale, and position
+///
+/// Glyph clusters are mapped back from `display` to byte offsets in `source`,
+/// since uppercasing can change a character's UTF-8 length.
+fn apply_character_format(
+    shaped: &mut ShapedRun,
+    source: &str,
+    display: &str,
+    format: &CharacterProperties,
+) {
+    if let Some(position) = format.position {
+        if position > 0.0 {
+            shaped.ascender += position;
+        } else {
+            shaped.descender -= position;
+        }
+    }
+
+    let tracking = format.spacing.unwrap_or(0.0);
+    let scale = format.scale_factor();
+    let small_caps = format.small_caps == Some(true);
+    if source == display && tracking == 0.0 && scale == 1.0 && !small_caps {
+        return;
+    }
+
+    let units_per_point = shaped.units_per_em as f32 / shaped.font_size.max(f32::EPSILON);
+    let chars: Vec<(usize, usize, char)> = display
+        .char_indices()
+        .zip(source.char_indices())
+        .map(|((display_offset, _), (source_offset, ch))| (display_offset, source_offset, ch))
+        .collect();
+
+    let mut total_advance = 0i32;
+    for glyph in &mut shaped.glyphs {
+        let char_scale = match chars.binary_search_by_key(&(glyph.cluster as usize), |c| c.0) {
+            Ok(i) => {
+                glyph.cluster = chars[i].1 as u32;
+                format.char_scale(chars[i].2)
+            }
+            Err(_) => 1.0,
+        };
+        let advance = glyph.x_advance as f32 * char_scale * scale + tracking * units_per_point;
+        glyph.x_advance = advance.round() as i32;
+        total_advance += glyph.x_advance;
+    }
+    if !shaped.glyphs.is_empty() {
+        shaped.width = total_advance as f32 / units_per_point;
+    } else {
+        shaped.width = shaped.width * scale + tracking * source.chars().count() as f32;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1411,6 +1981,213 @@ mod tests {
         assert_eq!(rtl_segment.bidi_level, 1);
     }
 
+    fn tab_paragraph(text: &str) -> (DocumentTree, NodeId) {
+        let mut tree = DocumentTree::new();
+        let root_id = tree.root_id();
+        let para_id = tree
+            .insert_paragraph(doc_model::Paragraph::new(), root_id, None)
+            .unwrap();
+        tree.insert_run(doc_model::Run::new(text), para_id, None).unwrap();
+        (tree, para_id)
+    }
+
+    #[test]
+    fn test_tab_stop_positioning() {
+        let mut breaker = LineBreaker::new();
+
+        // Right tab: the text after the tab ends at the stop
+        let (tree, para_id) = tab_paragraph("Total\t1234");
+        let config = LineBreakConfig {
+            available_width: 400.0,
+            tab_stops: vec![TabStop::right(300.0).with_leader(TabLeader::Dot)],
+            ..Default::default()
+        };
+        let broken = breaker.break_paragraph(&tree, para_id, &config).unwrap();
+        let inlines = &broken.lines[0].inlines;
+        let tab = inlines.iter().find(|i| i.is_tab()).unwrap();
+        assert_eq!(tab.tab_leader, TabLeader::Dot);
+        assert_eq!((tab.start_offset, tab.end_offset), (5, 6));
+        let last = inlines.last().unwrap();
+        assert_eq!(last.start_offset, 6);
+        assert!((last.bounds.x + last.bounds.width - 300.0).abs() < 0.5);
+
+        // Decimal tab: the separator sits on the stop
+        let (tree, para_id) = tab_paragraph("x\t12.50");
+        let config = LineBreakConfig {
+            available_width: 400.0,
+            tab_stops: vec![TabStop::decimal(200.0)],
+            ..Default::default()
+        };
+        let broken = breaker.break_paragraph(&tree, para_id, &config).unwrap();
+        let tab = broken.lines[0].inlines.iter().find(|i| i.is_tab()).unwrap();
+        let amount = broken.lines[0].inlines.last().unwrap();
+        let whole_width = amount.bounds.width * 2.0 / 5.0;
+        assert!((tab.bounds.x + tab.bounds.width + whole_width - 200.0).abs() < 0.5);
+
+        // Without custom stops, default stops apply
+        let (tree, para_id) = tab_paragraph("\tIndented");
+        let broken = breaker
+            .break_paragraph(&tree, para_id, &LineBreakConfig::default())
+            .unwrap();
+        let tab = &broken.lines[0].inlines[0];
+        assert!(tab.is_tab());
+        assert!((tab.bounds.width - DEFAULT_TAB_INTERVAL).abs() < 0.5);
+    }
+
+    fn formatted_line(text: &str, format: CharacterProperties) -> LineBox {
+        let mut tree = DocumentTree::new();
+        let root_id = tree.root_id();
+        let para_id = tree
+            .insert_paragraph(doc_model::Paragraph::new(), root_id, None)
+            .unwrap();
+        tree.insert_run(doc_model::Run::with_direct_formatting(text, format), para_id, None)
+            .unwrap();
+        let mut breaker = LineBreaker::new();
+        let broken = breaker
+            .break_paragraph(&tree, para_id, &LineBreakConfig::default())
+            .unwrap();
+        broken.lines.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn test_character_format_shaping() {
+        let width = |line: &LineBox| line.inlines.iter().map(|i| i.bounds.width).sum::<f32>();
+        let plain = formatted_line("caps", CharacterProperties::default());
+        let all_caps = formatted_line("caps", CharacterProperties {
+            all_caps: Some(true),
+            ..Default::default()
+        });
+        let small_caps = formatted_line("caps", CharacterProperties {
+            small_caps: Some(true),
+            ..Default::default()
+        });
+        assert!(width(&all_caps) > width(&plain));
+        assert!((width(&small_caps) - width(&all_caps) * doc_model::SMALL_CAPS_SCALE).abs() < 0.5);
+
+        // Tracking adds space after every character; scale stretches glyphs
+        let tracked = formatted_line("caps", CharacterProperties {
+            spacing: Some(2.0),
+            ..Default::default()
+        });
+        assert!((width(&tracked) - width(&plain) - 8.0).abs() < 0.5);
+        let condensed = formatted_line("caps", CharacterProperties {
+            scale: Some(50),
+            ..Default::default()
+        });
+        assert!((width(&condensed) - width(&plain) / 2.0).abs() < 0.5);
+
+        // Raised text makes room above the baseline
+        let raised = formatted_line("caps", CharacterProperties {
+            position: Some(3.0),
+            ..Default::default()
+        });
+        assert!((raised.baseline - plain.baseline - 3.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_list_markers_share_strings() {
+        let mut tree = DocumentTree::new();
+        let root_id = tree.root_id();
+        let mut breaker = LineBreaker::new();
+        let config = LineBreakConfig {
+            list_marker_text: Some("\u{2022}".to_string()),
+            list_marker_font: Some("Symbol".to_string()),
+            list_is_bullet: true,
+            ..Default::default()
+        };
+
+        let mut markers = Vec::new();
+        for _ in 0..2 {
+            let para_id = tree
+                .insert_paragraph(doc_model::Paragraph::new(), root_id, None)
+                .unwrap();
+            tree.insert_run(doc_model::Run::new("item"), para_id, None).unwrap();
+            let broken = breaker.break_paragraph(&tree, para_id, &config).unwrap();
+            let marker = broken.lines[0]
+                .inlines
+                .iter()
+                .find_map(|inline| inline.list_marker.clone())
+                .unwrap();
+            markers.push(marker);
+        }
+
+        assert_eq!(markers[0].text.as_str(), "\u{2022}");
+        assert!(markers[0].text.ptr_eq(&markers[1].text));
+        assert!(markers[0].font.as_ref().unwrap().ptr_eq(markers[1].font.as_ref().unwrap()));
+    }
+
+    #[test]
+    fn test_rtl_paragraph_mirrors_marker_and_indent() {
+        let mut tree = DocumentTree::new();
+        let root_id = tree.root_id();
+        let para_id = tree
+            .insert_paragraph(doc_model::Paragraph::new(), root_id, None)
+            .unwrap();
+        tree.insert_run(doc_model::Run::new("שלום עולם"), para_id, None).unwrap();
+
+        // The start indent is on the right, and the marker leads from there
+        let config = LineBreakConfig {
+            available_width: 400.0,
+            right_indent: 36.0,
+            direction: Direction::Rtl,
+            list_marker_text: Some("1.".to_string()),
+            list_hanging: 18.0,
+            ..Default::default()
+        };
+        let mut breaker = LineBreaker::new();
+        let broken = breaker.break_paragraph(&tree, para_id, &config).unwrap();
+        let inlines = &broken.lines[0].inlines;
+        let marker = inlines.last().unwrap();
+        assert!(marker.list_marker.is_some());
+        assert!((marker.bounds.x + marker.bounds.width - 364.0).abs() < 0.5);
+        let text_end = inlines[..inlines.len() - 1]
+            .iter()
+            .map(|i| i.bounds.x + i.bounds.width)
+            .fold(0.0, f32::max);
+        assert!((marker.bounds.x - text_end - 18.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_ruby_raises_line_and_sits_over_base() {
+        let (plain_tree, plain_id) = tab_paragraph("漢字");
+        let mut tree = DocumentTree::new();
+        let root_id = tree.root_id();
+        let para_id = tree
+            .insert_paragraph(doc_model::Paragraph::new(), root_id, None)
+            .unwrap();
+        let ruby = doc_model::RubyAnnotation::new("かんじ");
+        tree.insert_run(doc_model::Run::new("漢字").with_ruby(ruby), para_id, None).unwrap();
+
+        let config = LineBreakConfig::default();
+        let mut breaker = LineBreaker::new();
+        let plain = breaker.break_paragraph(&plain_tree, plain_id, &config).unwrap();
+        let broken = breaker.break_paragraph(&tree, para_id, &config).unwrap();
+        assert!(broken.lines[0].bounds.height > plain.lines[0].bounds.height);
+
+        let inline = &broken.lines[0].inlines[0];
+        assert_eq!((inline.start_offset, inline.end_offset), (0, "漢字".len()));
+        let ruby = inline.ruby.as_ref().unwrap();
+        assert_eq!(ruby.font_size, 6.0);
+        assert!(ruby.baseline <= inline.bounds.y);
+        assert!(ruby.baseline - ruby.font_size * 0.8 >= -0.01);
+    }
+
+    #[test]
+    fn test_document_grid_snaps_lines_and_characters() {
+        let (tree, para_id) = tab_paragraph("日本語");
+        let config = LineBreakConfig {
+            line_grid_pitch: Some(18.0),
+            char_grid_pitch: Some(20.0),
+            ..Default::default()
+        };
+        let mut breaker = LineBreaker::new();
+        let broken = breaker.break_paragraph(&tree, para_id, &config).unwrap();
+        let line = &broken.lines[0];
+        assert_eq!(line.bounds.height, 18.0);
+        let width: f32 = line.inlines.iter().map(|inline| inline.bounds.width).sum();
+        assert!((width - 60.0).abs() < 0.1, "width {}", width);
+    }
+
     #[test]
     fn test_line_break_config_direction() {
         let ltr_config = LineBreakConfig {
@@ -97,6 +97,22 @@ impl LineNumberTracker {
         self.current_section_id = Some(section_id);
     }
 
+    /// Switch to a new section's configuration
+    ///
+    /// The count restarts unless the section numbers continuously, in which
+    /// case it picks up where the previous section left off.
+    pub fn enter_section(&mut self, section_id: NodeId, config: LineNumbering) {
+        if self.current_section_id == Some(section_id) {
+            return;
+        }
+        let continues = self.current_section_id.is_some() && config.restart == LineNumberRestart::Continuous;
+        self.config = config;
+        if !continues {
+            self.reset();
+        }
+        self.current_section_id = Some(section_id);
+    }
+
     /// Process a line and optionally generate a line number info
     ///
     /// Returns the line number info if this line should display a number.
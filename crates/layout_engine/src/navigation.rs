@@ -0,0 +1,652 @@
+//! Navigation Pane Model
+//!
+//! This module builds the data shown in the navigation sidebar:
+//! - The heading outline, with node IDs and the page each heading starts on
+//! - Page thumbnails as simplified, scaled sketches of each page
+//! - Search hits resolved to the pages they appear on
+//!
+//! `NavigationModel::refresh` rebuilds the model after the document or layout
+//! changes and reports which parts changed, so the frontend only redraws the
+//! headings, thumbnails, or search results that are actually different.
+
+use crate::{AreaType, LayoutTree, OutlineData, OutlineHeading, OutlineViewOptions, PageBox};
+use doc_model::{DocumentTree, Node, NodeId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Default thumbnail width in pixels
+pub const DEFAULT_THUMBNAIL_WIDTH: f32 = 120.0;
+
+// =============================================================================
+// Page Lookup
+// =============================================================================
+
+/// Maps document nodes to the pages they are laid out on
+struct PageLocator {
+    /// Pages each block (paragraph, table) appears on, in order
+    blocks: HashMap<NodeId, Vec<usize>>,
+    /// Text ranges of each run per page: (page, start, end) in run offsets
+    runs: HashMap<NodeId, Vec<(usize, usize, usize)>>,
+}
+
+impl PageLocator {
+    fn new(layout: &LayoutTree) -> Self {
+        let mut blocks: HashMap<NodeId, Vec<usize>> = HashMap::new();
+        let mut runs: HashMap<NodeId, Vec<(usize, usize, usize)>> = HashMap::new();
+
+        for page in &layout.pages {
+            let content = page
+                .areas
+                .iter()
+                .filter(|area| area.area_type == AreaType::Content);
+            for block in content.flat_map(|area| &area.columns).flat_map(|col| &col.blocks) {
+                let pages = blocks.entry(block.node_id).or_default();
+                if pages.last() != Some(&page.index) {
+                    pages.push(page.index);
+                }
+                for inline in block.lines.iter().flat_map(|line| &line.inlines) {
+                    if inline.is_text() {
+                        runs.entry(inline.node_id).or_default().push((
+                            page.index,
+                            inline.start_offset,
+                            inline.end_offset,
+                        ));
+                    }
+                }
+            }
+        }
+
+        Self { blocks, runs }
+    }
+
+    /// First page a block appears on
+    fn page_of_node(&self, node_id: NodeId) -> Option<usize> {
+        self.blocks.get(&node_id).and_then(|pages| pages.first().copied())
+    }
+
+    /// Page of a character offset within a paragraph
+    fn page_of_position(&self, tree: &DocumentTree, para_id: NodeId, offset: usize) -> Option<usize> {
+        let Some(para) = tree.get_paragraph(para_id) else {
+            return self.page_of_node(para_id);
+        };
+
+        let mut run_start = 0;
+        for &run_id in para.children() {
+            let Some(run) = tree.get_run(run_id) else {
+                continue;
+            };
+            let run_len = run.text.chars().count();
+            if offset < run_start + run_len {
+                let in_run = offset - run_start;
+                let page = self.runs.get(&run_id).and_then(|ranges| {
+                    ranges
+                        .iter()
+                        .find(|(_, start, end)| *start <= in_run && in_run < *end)
+                        .map(|(page, _, _)| *page)
+                });
+                return page.or_else(|| self.page_of_node(para_id));
+            }
+            run_start += run_len;
+        }
+
+        // Offsets at or past the end belong to the paragraph's last page
+        self.blocks.get(&para_id).and_then(|pages| pages.last().copied())
+    }
+}
+
+// =============================================================================
+// Headings
+// =============================================================================
+
+/// A heading in the navigation outline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationHeading {
+    /// Heading paragraph ID
+    pub node_id: NodeId,
+    /// Heading level (1-9)
+    pub level: u8,
+    /// Heading text
+    pub text: String,
+    /// Page the heading starts on, if laid out
+    pub page_index: Option<usize>,
+    /// Subheadings
+    pub children: Vec<NavigationHeading>,
+}
+
+impl NavigationHeading {
+    fn from_outline(heading: &OutlineHeading, pages: &PageLocator) -> Self {
+        Self {
+            node_id: heading.node_id,
+            level: heading.level,
+            text: heading.text.clone(),
+            page_index: pages.page_of_node(heading.node_id),
+            children: heading
+                .children
+                .iter()
+                .map(|child| Self::from_outline(child, pages))
+                .collect(),
+        }
+    }
+
+    /// Count this heading and all of its subheadings
+    pub fn total_count(&self) -> usize {
+        1 + self.children.iter().map(|c| c.total_count()).sum::<usize>()
+    }
+
+    /// Visit this heading and its subheadings in document order
+    fn for_each<'a>(&'a self, f: &mut impl FnMut(&'a NavigationHeading)) {
+        f(self);
+        for child in &self.children {
+            child.for_each(f);
+        }
+    }
+}
+
+// =============================================================================
+// Thumbnails
+// =============================================================================
+
+/// Kind of element drawn in a page thumbnail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThumbnailElementKind {
+    /// A line of text, drawn as a bar
+    TextLine,
+    /// An inline or floating image
+    Image,
+    /// A shape
+    Shape,
+    /// A text box
+    TextBox,
+}
+
+/// A rectangle in a page thumbnail, in thumbnail pixels
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThumbnailElement {
+    pub kind: ThumbnailElementKind,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Simplified sketch of a page for the thumbnail strip
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageThumbnail {
+    /// Page index (0-based)
+    pub page_index: usize,
+    /// Thumbnail width in pixels
+    pub width: f32,
+    /// Thumbnail height in pixels
+    pub height: f32,
+    /// Pixels per point
+    pub scale: f32,
+    /// Section the page belongs to
+    pub section_id: Option<NodeId>,
+    /// Elements to draw, back to front
+    pub elements: Vec<ThumbnailElement>,
+}
+
+impl PageThumbnail {
+    /// Build a thumbnail of `width` pixels for a laid out page
+    pub fn from_page(layout: &LayoutTree, page: &PageBox, width: f32) -> Self {
+        let scale = if page.bounds.width > 0.0 {
+            width / page.bounds.width
+        } else {
+            0.0
+        };
+        let element = |kind, x: f32, y: f32, w: f32, h: f32| ThumbnailElement {
+            kind,
+            x: x * scale,
+            y: y * scale,
+            width: w * scale,
+            height: h * scale,
+        };
+
+        let origin = (page.content_area.x, page.content_area.y);
+        let mut elements = Vec::new();
+        let content = page
+            .areas
+            .iter()
+            .filter(|area| area.area_type == AreaType::Content);
+        for line in content
+            .flat_map(|area| &area.columns)
+            .flat_map(|col| &col.blocks)
+            .flat_map(|block| &block.lines)
+        {
+            let y = origin.1 + line.bounds.y;
+            let text = line.inlines.iter().filter(|inline| inline.is_text());
+            let left = text.clone().map(|i| i.bounds.x).fold(f32::INFINITY, f32::min);
+            let right = text.map(|i| i.bounds.right()).fold(f32::NEG_INFINITY, f32::max);
+            if left < right {
+                elements.push(element(
+                    ThumbnailElementKind::TextLine,
+                    origin.0 + left,
+                    y,
+                    right - left,
+                    line.bounds.height,
+                ));
+            }
+
+            for inline in &line.inlines {
+                let kind = if inline.is_image() {
+                    ThumbnailElementKind::Image
+                } else if inline.is_shape() {
+                    ThumbnailElementKind::Shape
+                } else if inline.is_textbox() {
+                    ThumbnailElementKind::TextBox
+                } else {
+                    continue;
+                };
+                elements.push(element(
+                    kind,
+                    origin.0 + inline.bounds.x,
+                    y + inline.bounds.y,
+                    inline.bounds.width,
+                    inline.bounds.height,
+                ));
+            }
+        }
+
+        let floating = layout
+            .floating_images_on_page(page.index)
+            .map(|i| (ThumbnailElementKind::Image, i.bounds))
+            .chain(
+                layout
+                    .floating_shapes_on_page(page.index)
+                    .map(|s| (ThumbnailElementKind::Shape, s.bounds)),
+            )
+            .chain(
+                layout
+                    .floating_textboxes_on_page(page.index)
+                    .map(|t| (ThumbnailElementKind::TextBox, t.bounds)),
+            );
+        for (kind, bounds) in floating {
+            elements.push(element(kind, bounds.x, bounds.y, bounds.width, bounds.height));
+        }
+
+        Self {
+            page_index: page.index,
+            width,
+            height: page.bounds.height * scale,
+            scale,
+            section_id: page.section_id,
+            elements,
+        }
+    }
+}
+
+// =============================================================================
+// Search Results
+// =============================================================================
+
+/// A search match located in the document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    /// Paragraph containing the match
+    pub node_id: NodeId,
+    /// Start offset within the paragraph (character index)
+    pub start_offset: usize,
+    /// End offset within the paragraph (character index)
+    pub end_offset: usize,
+    /// Text around the match for the result list
+    pub context: Option<String>,
+    /// Page the match is on (resolved by the navigation model)
+    pub page_index: Option<usize>,
+}
+
+impl SearchHit {
+    /// Create a search hit for a paragraph range
+    pub fn new(node_id: NodeId, start_offset: usize, end_offset: usize) -> Self {
+        Self {
+            node_id,
+            start_offset,
+            end_offset,
+            context: None,
+            page_index: None,
+        }
+    }
+
+    /// Set the preview context
+    pub fn with_context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+}
+
+/// Search results shown in the navigation pane
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationSearch {
+    /// The search query
+    pub query: String,
+    /// Matches in document order
+    pub hits: Vec<SearchHit>,
+    /// Pages with at least one match, ascending
+    pub pages_with_hits: Vec<usize>,
+}
+
+impl NavigationSearch {
+    fn resolve(&mut self, tree: &DocumentTree, pages: &PageLocator) {
+        for hit in &mut self.hits {
+            hit.page_index = pages.page_of_position(tree, hit.node_id, hit.start_offset);
+        }
+        let mut pages_with_hits: Vec<usize> = self.hits.iter().filter_map(|h| h.page_index).collect();
+        pages_with_hits.sort_unstable();
+        pages_with_hits.dedup();
+        self.pages_with_hits = pages_with_hits;
+    }
+}
+
+// =============================================================================
+// Navigation Model
+// =============================================================================
+
+/// What changed in the navigation model after a refresh
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationUpdate {
+    /// Model revision after the update
+    pub revision: u64,
+    /// Whether the heading outline changed
+    pub headings_changed: bool,
+    /// Pages whose thumbnails changed or were added
+    pub changed_pages: Vec<usize>,
+    /// Total page count after the update
+    pub page_count: usize,
+    /// Whether the search results changed
+    pub search_changed: bool,
+}
+
+impl NavigationUpdate {
+    /// Check if nothing changed
+    pub fn is_empty(&self) -> bool {
+        !self.headings_changed && self.changed_pages.is_empty() && !self.search_changed
+    }
+}
+
+/// Data model for the navigation sidebar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationModel {
+    /// Incremented whenever any part of the model changes
+    pub revision: u64,
+    /// Heading outline
+    pub headings: Vec<NavigationHeading>,
+    /// One thumbnail per page
+    pub thumbnails: Vec<PageThumbnail>,
+    /// Active search results
+    pub search: Option<NavigationSearch>,
+    /// Thumbnail width in pixels
+    pub thumbnail_width: f32,
+    /// Which heading levels to include
+    #[serde(skip)]
+    outline_options: OutlineViewOptions,
+}
+
+impl NavigationModel {
+    /// Create an empty model
+    pub fn new() -> Self {
+        Self {
+            revision: 0,
+            headings: Vec::new(),
+            thumbnails: Vec::new(),
+            search: None,
+            thumbnail_width: DEFAULT_THUMBNAIL_WIDTH,
+            outline_options: OutlineViewOptions::default(),
+        }
+    }
+
+    /// Set the thumbnail width in pixels
+    pub fn with_thumbnail_width(mut self, width: f32) -> Self {
+        self.thumbnail_width = width;
+        self
+    }
+
+    /// Set the outline options (heading levels to include)
+    pub fn with_outline_options(mut self, options: OutlineViewOptions) -> Self {
+        self.outline_options = options;
+        self
+    }
+
+    /// Build a model for a document and its layout
+    pub fn build(tree: &DocumentTree, layout: &LayoutTree) -> Self {
+        let mut model = Self::new();
+        model.refresh(tree, layout);
+        model
+    }
+
+    /// Rebuild the model after the document or layout changed
+    ///
+    /// Only the parts that differ from the previous state are reported; the
+    /// revision is bumped only when something changed.
+    pub fn refresh(&mut self, tree: &DocumentTree, layout: &LayoutTree) -> NavigationUpdate {
+        let pages = PageLocator::new(layout);
+        let mut update = NavigationUpdate {
+            page_count: layout.pages.len(),
+            ..Default::default()
+        };
+
+        let headings: Vec<NavigationHeading> = OutlineData::from_document(tree, &self.outline_options)
+            .headings
+            .iter()
+            .map(|h| NavigationHeading::from_outline(h, &pages))
+            .collect();
+        if headings != self.headings {
+            self.headings = headings;
+            update.headings_changed = true;
+        }
+
+        let thumbnails: Vec<PageThumbnail> = layout
+            .pages
+            .iter()
+            .map(|page| PageThumbnail::from_page(layout, page, self.thumbnail_width))
+            .collect();
+        update.changed_pages = thumbnails
+            .iter()
+            .enumerate()
+            .filter(|(i, thumb)| self.thumbnails.get(*i) != Some(thumb))
+            .map(|(i, _)| i)
+            .collect();
+        let pages_removed = thumbnails.len() < self.thumbnails.len();
+        self.thumbnails = thumbnails;
+
+        if let Some(search) = &mut self.search {
+            let before = search.clone();
+            search.resolve(tree, &pages);
+            update.search_changed = *search != before;
+        }
+
+        if !update.is_empty() || pages_removed {
+            self.revision += 1;
+        }
+        update.revision = self.revision;
+        update
+    }
+
+    /// Show search results, resolving each hit to its page
+    pub fn set_search(
+        &mut self,
+        tree: &DocumentTree,
+        layout: &LayoutTree,
+        query: impl Into<String>,
+        hits: Vec<SearchHit>,
+    ) -> NavigationUpdate {
+        let mut search = NavigationSearch {
+            query: query.into(),
+            hits,
+            pages_with_hits: Vec::new(),
+        };
+        search.resolve(tree, &PageLocator::new(layout));
+
+        let search_changed = self.search.as_ref() != Some(&search);
+        self.search = Some(search);
+        self.search_update(search_changed)
+    }
+
+    /// Clear the search results
+    pub fn clear_search(&mut self) -> NavigationUpdate {
+        let search_changed = self.search.take().is_some();
+        self.search_update(search_changed)
+    }
+
+    fn search_update(&mut self, search_changed: bool) -> NavigationUpdate {
+        if search_changed {
+            self.revision += 1;
+        }
+        NavigationUpdate {
+            revision: self.revision,
+            page_count: self.thumbnails.len(),
+            search_changed,
+            ..Default::default()
+        }
+    }
+
+    /// Total number of headings in the outline
+    pub fn heading_count(&self) -> usize {
+        self.headings.iter().map(|h| h.total_count()).sum()
+    }
+
+    /// The heading whose section contains the given page (for highlighting)
+    pub fn heading_for_page(&self, page_index: usize) -> Option<&NavigationHeading> {
+        let mut current = None;
+        for heading in &self.headings {
+            heading.for_each(&mut |h| {
+                if h.page_index.is_some_and(|p| p <= page_index) {
+                    current = Some(h);
+                }
+            });
+        }
+        current
+    }
+}
+
+impl Default for NavigationModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Paginator;
+    use doc_model::{Paragraph, Run, StyleId};
+
+    fn add_paragraph(tree: &mut DocumentTree, text: &str, style: Option<&str>) -> NodeId {
+        let mut para = Paragraph::new();
+        para.paragraph_style_id = style.map(StyleId::new);
+        let para_id = para.id();
+        tree.nodes.paragraphs.insert(para_id, para);
+        tree.document.add_body_child(para_id);
+
+        let run = Run::new(text);
+        let run_id = run.id();
+        tree.nodes.runs.insert(run_id, run);
+        tree.get_paragraph_mut(para_id).unwrap().add_child(run_id);
+        para_id
+    }
+
+    fn create_document() -> (DocumentTree, Vec<NodeId>) {
+        let mut tree = DocumentTree::new();
+        let mut ids = vec![add_paragraph(&mut tree, "Introduction", Some("Heading1"))];
+        for i in 0..40 {
+            let text = format!(
+                "Paragraph {}. Lorem ipsum dolor sit amet, consectetur adipiscing elit, \
+                 sed do eiusmod tempor incididunt ut labore et dolore magna aliqua.",
+                i
+            );
+            ids.push(add_paragraph(&mut tree, &text, None));
+        }
+        ids.push(add_paragraph(&mut tree, "Details", Some("Heading2")));
+        ids.push(add_paragraph(&mut tree, "Closing needle", None));
+        (tree, ids)
+    }
+
+    #[test]
+    fn test_build_outline_and_thumbnails() {
+        let (tree, _) = create_document();
+        let layout = Paginator::default().layout(&tree).unwrap();
+        let model = NavigationModel::build(&tree, &layout);
+
+        assert_eq!(model.heading_count(), 2);
+        let intro = &model.headings[0];
+        assert_eq!(intro.text, "Introduction");
+        assert_eq!(intro.page_index, Some(0));
+        assert_eq!(intro.children[0].text, "Details");
+
+        assert!(layout.page_count() > 1);
+        assert_eq!(model.thumbnails.len(), layout.page_count());
+        let thumb = &model.thumbnails[0];
+        assert_eq!(thumb.width, DEFAULT_THUMBNAIL_WIDTH);
+        assert!(thumb.height > thumb.width);
+        assert!(thumb
+            .elements
+            .iter()
+            .all(|e| e.kind == ThumbnailElementKind::TextLine && e.x + e.width <= thumb.width));
+
+        let last_page = layout.page_count() - 1;
+        let details_page = intro.children[0].page_index.unwrap();
+        assert_eq!(model.heading_for_page(last_page).unwrap().text, "Details");
+        assert_eq!(model.heading_for_page(0).unwrap().text, "Introduction");
+        assert!(details_page > 0);
+    }
+
+    #[test]
+    fn test_search_hits_resolve_to_pages() {
+        let (tree, ids) = create_document();
+        let layout = Paginator::default().layout(&tree).unwrap();
+        let mut model = NavigationModel::build(&tree, &layout);
+
+        let needle = *ids.last().unwrap();
+        let update = model.set_search(
+            &tree,
+            &layout,
+            "needle",
+            vec![SearchHit::new(needle, 8, 14).with_context("Closing needle")],
+        );
+        assert!(update.search_changed);
+
+        let search = model.search.as_ref().unwrap();
+        let last_page = layout.page_count() - 1;
+        assert_eq!(search.hits[0].page_index, Some(last_page));
+        assert_eq!(search.pages_with_hits, vec![last_page]);
+
+        assert!(model.clear_search().search_changed);
+        assert!(!model.clear_search().search_changed);
+    }
+
+    #[test]
+    fn test_refresh_reports_changes() {
+        let (mut tree, ids) = create_document();
+        let mut paginator = Paginator::default();
+        let layout = paginator.layout(&tree).unwrap();
+        let mut model = NavigationModel::build(&tree, &layout);
+        let revision = model.revision;
+
+        // Nothing changed
+        let update = model.refresh(&tree, &layout);
+        assert!(update.is_empty());
+        assert_eq!(model.revision, revision);
+
+        // Editing the heading text changes the outline but not the first page sketch
+        let run_id = tree.get_paragraph(ids[0]).unwrap().children()[0];
+        tree.get_run_mut(run_id).unwrap().text = "Intro".to_string();
+        paginator.invalidate_paragraph(ids[0]);
+        let layout = paginator.layout(&tree).unwrap();
+        let update = model.refresh(&tree, &layout);
+
+        assert!(update.headings_changed);
+        assert_eq!(model.headings[0].text, "Intro");
+        assert!(model.revision > revision);
+        assert!(update.changed_pages.iter().all(|&p| p == 0));
+    }
+}
@@ -11,11 +11,18 @@
 //! - Layout cache integration
 
 use crate::{
-    AreaBox, BlockBox, ColumnBox, LayoutCache, LayoutTree, LineBox, LineBreakConfig,
-    LineBreaker, LineNumberItem, LineNumberTracker, PageBox, Rect, Result,
+    AreaBox, AreaType, BidiAnalyzer, BlockBox, CacheConfig, ColumnBox, Direction, FloatingInk, FootnoteSeparator,
+    LayoutCache, LayoutTree, LineBox, LineBreakConfig, LineBreaker, LineNumberItem, LineNumberTracker, PageBox,
+    PageWatermark, Rect, Result, WatermarkContent,
 };
-use doc_model::{Alignment, DocumentTree, LineNumbering, LineNumberRestart, Node, NodeId, WidowOrphanControl, ParagraphKeepRules};
-use std::collections::HashSet;
+use doc_model::{
+    Alignment, DocumentTree, EndnotePosition, FieldContext, GutterPosition, HeaderFooter, HeaderFooterSet,
+    LineNumbering, Node, NodeId, Note, PageNumbering, ParagraphKeepRules, Section, SectionBreakType, SectionPageSetup,
+    TextDirection, Watermark, WatermarkLayout, WidowOrphanControl,
+};
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 
 /// Standard page sizes
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -63,6 +70,10 @@ pub struct HeaderFooterConfig {
     pub header_on_first_page: bool,
     /// Whether to include footer on first page
     pub footer_on_first_page: bool,
+    /// Height of the tallest section header, measured by the paginator
+    pub header_content_height: f32,
+    /// Height of the tallest section footer, measured by the paginator
+    pub footer_content_height: f32,
 }
 
 impl Default for HeaderFooterConfig {
@@ -74,10 +85,24 @@ impl Default for HeaderFooterConfig {
             footer_margin: 36.0,  // 0.5 inch from edge
             header_on_first_page: true,
             footer_on_first_page: true,
+            header_content_height: 0.0,
+            footer_content_height: 0.0,
         }
     }
 }
 
+impl HeaderFooterConfig {
+    /// Height reserved for the header: grows to fit the header's content
+    pub fn reserved_header_height(&self) -> f32 {
+        self.header_height.max(self.header_content_height)
+    }
+
+    /// Height reserved for the footer: grows to fit the footer's content
+    pub fn reserved_footer_height(&self) -> f32 {
+        self.footer_height.max(self.footer_content_height)
+    }
+}
+
 /// Column configuration for multi-column layout
 #[derive(Debug, Clone)]
 pub struct ColumnLayout {
@@ -185,6 +210,13 @@ pub struct PageConfig {
     pub columns: ColumnLayout,
     /// Line numbering configuration
     pub line_numbering: LineNumbering,
+    /// Swap left and right margins on even pages, so the inside margins
+    /// face each other when printed on both sides
+    pub mirror_margins: bool,
+    /// Extra binding space in points, added to the left (inside) or top margin
+    pub gutter: f32,
+    /// Which margin the gutter is added to
+    pub gutter_position: GutterPosition,
 }
 
 impl Default for PageConfig {
@@ -209,6 +241,9 @@ impl PageConfig {
             widow_orphan_control: WidowOrphanControl::default(),
             columns: ColumnLayout::default(),
             line_numbering: LineNumbering::default(),
+            mirror_margins: false,
+            gutter: 0.0,
+            gutter_position: GutterPosition::Left,
         }
     }
 
@@ -227,6 +262,9 @@ impl PageConfig {
             widow_orphan_control: WidowOrphanControl::default(),
             columns: ColumnLayout::default(),
             line_numbering: LineNumbering::default(),
+            mirror_margins: false,
+            gutter: 0.0,
+            gutter_position: GutterPosition::Left,
         }
     }
 
@@ -244,6 +282,9 @@ impl PageConfig {
             widow_orphan_control: WidowOrphanControl::default(),
             columns: ColumnLayout::default(),
             line_numbering: LineNumbering::default(),
+            mirror_margins: false,
+            gutter: 0.0,
+            gutter_position: GutterPosition::Left,
         }
     }
 
@@ -274,14 +315,56 @@ impl PageConfig {
         self.columns = columns;
     }
 
-    /// Get the content area width (excluding margins)
+    /// Set whether margins are mirrored on even pages
+    pub fn with_mirror_margins(mut self, mirror_margins: bool) -> Self {
+        self.mirror_margins = mirror_margins;
+        self
+    }
+
+    /// Set the binding gutter
+    pub fn with_gutter(mut self, gutter: f32, gutter_position: GutterPosition) -> Self {
+        self.gutter = gutter.max(0.0);
+        self.gutter_position = gutter_position;
+        self
+    }
+
+    /// Take the gutter and mirrored margins from a section's page setup
+    pub fn with_section_binding(self, page_setup: &SectionPageSetup) -> Self {
+        let mirror_margins = self.mirror_margins || page_setup.mirror_margins;
+        self.with_gutter(page_setup.gutter, page_setup.gutter_position)
+            .with_mirror_margins(mirror_margins)
+    }
+
+    /// Get the left margin for a specific page
+    ///
+    /// With mirrored margins, `margin_left` and a left gutter are the inside
+    /// margin: it's on the left of odd pages and on the right of even pages.
+    pub fn margin_left_for_page(&self, page_index: usize) -> f32 {
+        if self.mirror_margins && page_index % 2 == 1 {
+            self.margin_right
+        } else {
+            self.margin_left + self.left_gutter()
+        }
+    }
+
+    /// Gutter added to the inside margin
+    fn left_gutter(&self) -> f32 {
+        if self.gutter_position == GutterPosition::Left { self.gutter } else { 0.0 }
+    }
+
+    /// Gutter added to the top margin
+    fn top_gutter(&self) -> f32 {
+        if self.gutter_position == GutterPosition::Top { self.gutter } else { 0.0 }
+    }
+
+    /// Get the content area width (excluding margins and gutter)
     pub fn content_width(&self) -> f32 {
-        self.page_width - self.margin_left - self.margin_right
+        self.page_width - self.margin_left - self.margin_right - self.left_gutter()
     }
 
-    /// Get the content area height (excluding margins and header/footer)
+    /// Get the content area height (excluding margins, gutter and header/footer)
     pub fn content_height(&self) -> f32 {
-        self.page_height - self.margin_top - self.margin_bottom
+        self.page_height - self.margin_top - self.margin_bottom - self.top_gutter()
     }
 
     /// Get the content area height for a specific page
@@ -292,14 +375,14 @@ impl PageConfig {
         if page_index == 0 && self.header_footer.header_on_first_page
             || page_index > 0
         {
-            height -= self.header_footer.header_height;
+            height -= self.header_footer.reserved_header_height();
         }
 
         // Subtract footer space if applicable
         if page_index == 0 && self.header_footer.footer_on_first_page
             || page_index > 0
         {
-            height -= self.header_footer.footer_height;
+            height -= self.header_footer.reserved_footer_height();
         }
 
         height
@@ -307,13 +390,13 @@ impl PageConfig {
 
     /// Get the content area top offset for a specific page
     pub fn content_top_for_page(&self, page_index: usize) -> f32 {
-        let mut top = self.margin_top;
+        let mut top = self.margin_top + self.top_gutter();
 
         // Add header space if applicable
         if page_index == 0 && self.header_footer.header_on_first_page
             || page_index > 0
         {
-            top += self.header_footer.header_height;
+            top += self.header_footer.reserved_header_height();
         }
 
         top
@@ -703,6 +786,84 @@ impl ReflowState {
     }
 }
 
+/// Pages changed by an incremental relayout
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirtyPages {
+    /// Indices of pages whose content or position changed, in order
+    pub pages: Vec<usize>,
+    /// Page count after the relayout
+    pub page_count: usize,
+}
+
+impl DirtyPages {
+    /// Check if no page needs redrawing
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    /// Check if a page needs redrawing
+    pub fn contains(&self, page_index: usize) -> bool {
+        self.pages.binary_search(&page_index).is_ok()
+    }
+}
+
+/// A layout of the first pages of a document
+#[derive(Debug, Clone)]
+pub struct PartialLayout {
+    /// The pages laid out so far
+    pub layout: LayoutTree,
+    /// Whether the layout covers the whole document
+    pub complete: bool,
+    /// Page count of the whole document; exact when complete, otherwise
+    /// extrapolated from the text laid out so far
+    pub estimated_page_count: usize,
+}
+
+/// The last layout, kept for incremental relayout
+#[derive(Debug)]
+struct LaidOut {
+    layout: LayoutTree,
+    /// Paragraphs in document order, one block each
+    paragraphs: Vec<NodeId>,
+    /// Index of the paragraph each page starts with, or `None` when the
+    /// page starts partway through a paragraph
+    page_starts: Vec<Option<usize>>,
+    /// Fingerprint of each page, for finding dirty pages
+    signatures: Vec<u64>,
+}
+
+impl LaidOut {
+    fn new(layout: LayoutTree, paragraphs: Vec<NodeId>) -> Self {
+        let mut page_starts = Vec::with_capacity(layout.pages.len());
+        let mut block_index = 0;
+        let mut last_block = None;
+        for page in &layout.pages {
+            let mut start = None;
+            for (position, block) in page_blocks(page).enumerate() {
+                if last_block != Some(block.node_id) {
+                    if position == 0 {
+                        start = Some(block_index);
+                    }
+                    block_index += 1;
+                }
+                last_block = Some(block.node_id);
+            }
+            page_starts.push(start);
+        }
+
+        Self {
+            signatures: page_signatures(&layout),
+            layout,
+            paragraphs,
+            page_starts,
+        }
+    }
+}
+
+/// Paragraph layouts the paginator's cache holds, enough for the lines of
+/// a long document to survive between relayouts
+const PARAGRAPH_CACHE_ENTRIES: usize = 20_000;
+
 /// Paginator that converts a document into pages
 pub struct Paginator {
     /// Line breaker for paragraph layout
@@ -713,6 +874,18 @@ pub struct Paginator {
     cache: LayoutCache,
     /// Reflow state for incremental updates
     reflow_state: ReflowState,
+    /// The last layout, if any
+    laid_out: Option<LaidOut>,
+    /// First paragraphs of sections that start on a new page
+    section_breaks: HashSet<NodeId>,
+    /// Endnote marks, keyed by each note's first paragraph
+    endnote_marks: HashMap<NodeId, String>,
+    /// Separator length (fraction of the column width) and weight, keyed
+    /// by the first paragraph of each run of endnotes
+    endnote_separators: HashMap<NodeId, (f32, f32)>,
+    /// Line and character grid pitches, keyed by the body paragraphs of
+    /// sections with a document grid
+    grid_pitches: HashMap<NodeId, (Option<f32>, Option<f32>)>,
 }
 
 impl Paginator {
@@ -721,8 +894,16 @@ impl Paginator {
         Self {
             line_breaker: LineBreaker::new(),
             config,
-            cache: LayoutCache::new(),
+            cache: LayoutCache::with_config(CacheConfig::with_limits(
+                PARAGRAPH_CACHE_ENTRIES,
+                CacheConfig::default().max_page_entries,
+            )),
             reflow_state: ReflowState::new(),
+            laid_out: None,
+            section_breaks: HashSet::new(),
+            endnote_marks: HashMap::new(),
+            endnote_separators: HashMap::new(),
+            grid_pitches: HashMap::new(),
         }
     }
 
@@ -766,131 +947,645 @@ impl Paginator {
     pub fn invalidate_all(&mut self) {
         self.cache.invalidate_all();
         self.reflow_state = ReflowState::new();
+        self.laid_out = None;
     }
 
     /// Layout the entire document into pages
     pub fn layout(&mut self, tree: &DocumentTree) -> Result<LayoutTree> {
+        self.layout_cancellable(tree, || false)
+            .map(|layout| layout.unwrap_or_default())
+    }
+
+    /// Layout the entire document, giving up with `Ok(None)` as soon as
+    /// `cancelled` returns true (checked between paragraphs)
+    pub fn layout_cancellable(
+        &mut self,
+        tree: &DocumentTree,
+        cancelled: impl Fn() -> bool,
+    ) -> Result<Option<LayoutTree>> {
         let mut layout = LayoutTree::new();
+        self.line_breaker.prune_strings();
+        self.prepare_sections(tree)?;
 
         // Create line break configuration from page config
         let line_config = self.create_line_config(tree);
 
         // Break all paragraphs into lines and create pending blocks
         let mut pending_blocks: Vec<PendingBlock> = Vec::new();
+        for para in flow_paragraphs(tree) {
+            if cancelled() {
+                return Ok(None);
+            }
+            pending_blocks.push(self.pending_block(tree, para, &line_config, false)?);
+        }
 
-        for para in tree.paragraphs() {
-            let para_id = para.id();
+        // Paginate all blocks
+        self.paginate_blocks(&mut layout, pending_blocks)?;
+        self.finish_layout(tree, &mut layout, None)?;
 
-            // Get paragraph spacing
-            let space_before = para.style.space_before.unwrap_or(0.0);
-            let space_after = para.style.space_after.unwrap_or(0.0);
-
-            // Get list properties if paragraph is in a list
-            let (list_marker_text, list_is_bullet, list_marker_font, list_level, list_num_id, list_hanging) =
-                if let Some(list_props) = &para.direct_formatting.list_props {
-                    if let Some(num_id) = list_props.num_id {
-                        let level = list_props.effective_level();
-                        let is_bullet = tree.numbering.is_bullet_list(num_id);
-
-                        // Get the level definition
-                        if let Some(level_def) = tree.numbering.get_effective_level(num_id, level) {
-                            // Build counts array for multi-level formatting
-                            let counts: Vec<u32> = (0..=level)
-                                .map(|l| tree.numbering.get_counter(num_id, l) + 1)
-                                .collect();
-
-                            let marker_text = level_def.format_number(&counts);
-                            let marker_font = level_def.font.clone();
-                            let hanging = level_def.hanging;
-
-                            (Some(marker_text), is_bullet, marker_font, Some(level), Some(num_id), hanging)
-                        } else {
-                            (None, false, None, None, None, 0.0)
-                        }
-                    } else {
-                        (None, false, None, None, None, 0.0)
-                    }
-                } else {
-                    (None, false, None, None, None, 0.0)
-                };
+        self.reflow_state.clear_dirty();
+        self.laid_out = Some(LaidOut::new(
+            layout.clone(),
+            flow_paragraphs(tree).iter().map(|para| para.id()).collect(),
+        ));
+        Ok(Some(layout))
+    }
 
-            // Apply list indent to left indent
-            let list_indent = if let (Some(num_id), Some(level)) = (list_num_id, list_level) {
-                if let Some(level_def) = tree.numbering.get_effective_level(num_id, level) {
-                    level_def.indent
-                } else {
-                    0.0
-                }
-            } else {
-                0.0
-            };
+    /// Layout only the first `page_limit` pages of a document
+    ///
+    /// Paragraphs are broken into lines just far enough to fill those pages,
+    /// so the cost doesn't grow with the document. The rest of the page
+    /// count is estimated from the text per page so far. The full layout
+    /// kept for `relayout` is left alone.
+    pub fn layout_pages(&mut self, tree: &DocumentTree, page_limit: usize) -> Result<PartialLayout> {
+        let page_limit = page_limit.max(1);
+        self.prepare_sections(tree)?;
+        let paragraphs = flow_paragraphs(tree);
+        let line_config = self.create_line_config(tree);
+
+        // Break lines until the blocks are taller than the requested pages
+        // plus one, then paginate; if that runs out before the last page
+        // is full (e.g. a lot of page breaks), break twice as much
+        let mut target_height = self.config.content_height() * (page_limit + 1) as f32;
+        let mut blocks: Vec<PendingBlock> = Vec::new();
+        let mut built_height = 0.0;
+        let mut layout = loop {
+            while blocks.len() < paragraphs.len()
+                && (built_height < target_height
+                    || blocks.last().is_some_and(|block| block.keep_with_next))
+            {
+                let block = self.pending_block(tree, paragraphs[blocks.len()], &line_config, false)?;
+                built_height += block.height;
+                blocks.push(block);
+            }
 
-            // Create custom line config for this paragraph
-            let para_line_config = LineBreakConfig {
-                available_width: line_config.available_width,
-                font_size: line_config.font_size,
-                line_spacing: match para.style.line_spacing {
-                    Some(doc_model::LineSpacing::Multiple(m)) => m,
-                    _ => line_config.line_spacing,
-                },
-                first_line_indent: para.style.indent_first_line.unwrap_or(0.0),
-                left_indent: para.style.indent_left.unwrap_or(0.0) + list_indent,
-                right_indent: para.style.indent_right.unwrap_or(0.0),
-                direction: line_config.direction,
-                allow_hyphenation: line_config.allow_hyphenation,
-                alignment: para.style.alignment.unwrap_or(Alignment::Left),
-                list_num_id,
-                list_level,
-                list_marker_text,
-                list_is_bullet,
-                list_marker_font,
-                list_hanging,
+            let mut layout = LayoutTree::new();
+            let stopped = self.paginate_blocks_from(&mut layout, blocks.clone(), 0, |page, _| page >= page_limit)?;
+            if stopped.is_some() || layout.pages.len() > page_limit || blocks.len() == paragraphs.len() {
+                break layout;
+            }
+            target_height *= 2.0;
+        };
+
+        let complete = layout.pages.len() <= page_limit && blocks.len() == paragraphs.len();
+        layout.pages.truncate(page_limit);
+
+        let estimated_page_count = if complete {
+            layout.page_count().max(1)
+        } else {
+            // Count a paragraph's text plus one for its break, so empty
+            // paragraphs still take room
+            let text_len = |para: &&doc_model::Paragraph| {
+                para.children()
+                    .iter()
+                    .filter_map(|run_id| tree.get_run(*run_id))
+                    .map(|run| run.text.len())
+                    .sum::<usize>()
+                    + 1
             };
+            // Paragraphs up to the last one on the final page
+            let last_para = layout.pages.last().and_then(|page| page_blocks(page).last()).map(|block| block.node_id);
+            let laid_out = paragraphs
+                .iter()
+                .position(|para| Some(para.id()) == last_para)
+                .map_or(0, |index| index + 1);
+            let laid_out_len: usize = paragraphs[..laid_out].iter().map(text_len).sum();
+            let remaining_len: usize = paragraphs[laid_out..].iter().map(text_len).sum();
+            let len_per_page = (laid_out_len / page_limit).max(1);
+            page_limit + remaining_len.div_ceil(len_per_page).max(1)
+        };
+        self.finish_layout(tree, &mut layout, Some(estimated_page_count))?;
 
-            // Break paragraph into lines
-            let broken = self.line_breaker.break_paragraph(tree, para_id, &para_line_config)?;
+        Ok(PartialLayout {
+            layout,
+            complete,
+            estimated_page_count,
+        })
+    }
 
-            // Cache the result
-            self.cache.store(
-                para_id,
-                tree.document.version(),
-                line_config.available_width,
-                &broken.lines,
-                broken.total_height,
-            );
+    /// Re-layout after an edit, re-breaking only the changed paragraphs
+    ///
+    /// Other paragraphs reuse their cached lines. Pagination resumes at the
+    /// page holding the first changed paragraph and stops once pages line up
+    /// with the previous layout again. Returns the new layout and the pages
+    /// that differ from the previous one, so the renderer can redraw just
+    /// those.
+    pub fn relayout(
+        &mut self,
+        tree: &DocumentTree,
+        changed: &[NodeId],
+    ) -> Result<(&LayoutTree, DirtyPages)> {
+        for &para_id in changed {
+            self.invalidate_paragraph(para_id);
+        }
+        self.prepare_sections(tree)?;
+
+        let paragraphs = flow_paragraphs(tree);
+        let para_ids: Vec<NodeId> = paragraphs.iter().map(|para| para.id()).collect();
+        let previous = self.laid_out.take();
+
+        // Paragraphs [0, first_changed) are untouched, as are the ones from
+        // unchanged_tail onward (matched against the previous layout from
+        // the end)
+        let (first_changed, unchanged_tail) = match &previous {
+            Some(previous) => self.changed_span(&paragraphs, &previous.paragraphs),
+            None => (0, paragraphs.len()),
+        };
 
-            // Get paragraph pagination options
-            let keep_with_next = para.style.keep_with_next.unwrap_or(false)
-                || para.direct_formatting.keep_with_next.unwrap_or(false);
-            let keep_together = para.style.keep_together.unwrap_or(false)
-                || para.direct_formatting.keep_together.unwrap_or(false);
-            let page_break_before = para.style.page_break_before.unwrap_or(false)
-                || para.direct_formatting.page_break_before.unwrap_or(false);
-
-            // Create pending block with pagination options
-            pending_blocks.push(PendingBlock::with_pagination_options(
-                para_id,
-                broken.lines,
-                space_before,
-                space_after,
-                keep_with_next,
-                keep_together,
-                page_break_before,
-            ));
+        // Resume at the last page that starts with a whole paragraph at or
+        // before the first change, backing up over keep-with-next chains
+        // that could pull the change onto an earlier page
+        let mut resume_block = first_changed;
+        while resume_block > 0 && keeps_with_next(paragraphs[resume_block - 1]) {
+            resume_block -= 1;
         }
+        let (first_page, start_block) = previous
+            .as_ref()
+            .and_then(|previous| {
+                previous
+                    .page_starts
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .find_map(|(page, start)| start.filter(|&block| block <= resume_block).map(|block| (page, block)))
+            })
+            .unwrap_or((0, 0));
+
+        let (mut previous_pages, previous_signatures, old_starts) = match previous {
+            Some(previous) => {
+                // The previous page starts, as indices into the current
+                // paragraphs (only meaningful in the unchanged tail)
+                let shift = para_ids.len() as isize - previous.paragraphs.len() as isize;
+                let old_starts: Vec<Option<usize>> = previous
+                    .page_starts
+                    .iter()
+                    .map(|start| start.and_then(|block| block.checked_add_signed(shift)))
+                    .collect();
+                (previous.layout.pages, previous.signatures, old_starts)
+            }
+            None => (Vec::new(), Vec::new(), Vec::new()),
+        };
+        let mut reused_pages = previous_pages.split_off(first_page.min(previous_pages.len()));
 
-        // Paginate all blocks
-        self.paginate_blocks(&mut layout, pending_blocks)?;
+        // Pages usually line up again right after the change, so blocks
+        // are first built only to a couple of pages past it
+        let line_config = self.create_line_config(tree);
+        let mut block_end = old_starts
+            .iter()
+            .flatten()
+            .filter(|&&block| block >= unchanged_tail)
+            .nth(1)
+            .map_or(paragraphs.len(), |&block| block + 1);
+        let (new_pages, stopped_at) = loop {
+            // Don't cut a keep-with-next chain short
+            while block_end < paragraphs.len() && keeps_with_next(paragraphs[block_end - 1]) {
+                block_end += 1;
+            }
+
+            let mut pending_blocks = Vec::with_capacity(block_end - start_block);
+            for (index, para) in paragraphs.iter().enumerate().take(block_end).skip(start_block) {
+                // List markers depend on the items before them, so list
+                // items after the first change are re-broken
+                let reuse = !(index >= first_changed && in_list(para));
+                pending_blocks.push(self.pending_block(tree, para, &line_config, reuse)?);
+            }
 
+            let mut pages = LayoutTree::new();
+            let stopped_at = self.paginate_blocks_from(&mut pages, pending_blocks, first_page, |page, block| {
+                let block = start_block + block;
+                block >= unchanged_tail && old_starts.get(page) == Some(&Some(block))
+            })?;
+
+            // Running out of blocks before the pages line up leaves the
+            // last page unfinished; lay out the rest of the document instead
+            if stopped_at.is_some() || block_end == paragraphs.len() {
+                break (pages.pages, stopped_at);
+            }
+            block_end = paragraphs.len();
+        };
+
+        let mut layout = LayoutTree::new();
+        layout.pages = previous_pages;
+        layout.pages.extend(new_pages);
+        let repaginated_to = layout.pages.len();
+        if let Some(page) = stopped_at {
+            layout.pages.extend(reused_pages.drain(page - first_page..));
+        }
+        self.finish_layout(tree, &mut layout, None)?;
+
+        let laid_out = LaidOut::new(layout, para_ids);
+        let pages = laid_out
+            .signatures
+            .iter()
+            .enumerate()
+            .filter(|&(index, signature)| {
+                let page = &laid_out.layout.pages[index];
+                previous_signatures.get(index) != Some(signature)
+                    || ((first_page..repaginated_to).contains(&index)
+                        && page_blocks(page).any(|block| self.reflow_state.is_dirty(block.node_id)))
+                    || header_footer_blocks(page).any(|block| self.reflow_state.is_dirty(block.node_id))
+            })
+            .map(|(index, _)| index)
+            .collect();
+        let dirty = DirtyPages {
+            pages,
+            page_count: laid_out.layout.page_count(),
+        };
+
+        self.reflow_state.clear_dirty();
+        let laid_out = self.laid_out.insert(laid_out);
+        Ok((&laid_out.layout, dirty))
+    }
+
+    /// Find where the document differs from the previously laid out
+    /// paragraphs: the first changed index and the start of the unchanged
+    /// tail
+    fn changed_span(&self, paragraphs: &[&doc_model::Paragraph], previous: &[NodeId]) -> (usize, usize) {
+        let unchanged = |para: &doc_model::Paragraph, old_id: &NodeId| {
+            para.id() == *old_id && !self.reflow_state.is_dirty(para.id())
+        };
+
+        let first_changed = paragraphs
+            .iter()
+            .zip(previous)
+            .position(|(para, old_id)| !unchanged(para, old_id))
+            .unwrap_or(paragraphs.len().min(previous.len()));
+
+        // List items in the tail may be renumbered, so the tail stops
+        // before the last of them
+        let tail_len = paragraphs
+            .iter()
+            .rev()
+            .zip(previous.iter().rev())
+            .take(paragraphs.len() - first_changed)
+            .take_while(|(para, old_id)| unchanged(para, old_id) && !in_list(para))
+            .count();
+
+        (first_changed, paragraphs.len() - tail_len)
+    }
+
+    /// Add the empty page, headers and footers, and line numbers a
+    /// finished layout needs
+    ///
+    /// `page_count` is the document's page count for NUMPAGES fields when
+    /// the layout holds only some of the pages.
+    fn finish_layout(&mut self, tree: &DocumentTree, layout: &mut LayoutTree, page_count: Option<usize>) -> Result<()> {
         // Ensure at least one page
         if layout.pages.is_empty() {
             layout.add_page(self.create_empty_page(0));
         }
 
+        let page_count = page_count.unwrap_or(layout.pages.len());
+        self.layout_headers_footers(tree, layout, page_count)?;
+
         // Generate line numbers if enabled
-        self.generate_line_numbers(&mut layout);
+        self.generate_line_numbers(tree, layout);
+        self.place_endnote_separators(layout);
+        self.place_ink(tree, layout);
+        Ok(())
+    }
 
-        Ok(layout)
+    /// Place ink over the first page fragment of its anchor paragraph
+    fn place_ink(&self, tree: &DocumentTree, layout: &mut LayoutTree) {
+        let mut placed = Vec::new();
+        for ink in tree.inks() {
+            let (Some(para_id), Some(bounds)) = (ink.parent(), ink.bounds()) else {
+                continue;
+            };
+            let anchor = layout.pages.iter().find_map(|page| {
+                page.content_columns()
+                    .flat_map(|column| column.blocks.iter())
+                    .find(|block| block.node_id == para_id)
+                    .map(|block| {
+                        (
+                            page.index,
+                            page.content_area.x + block.bounds.x,
+                            page.content_area.y + block.bounds.y,
+                        )
+                    })
+            });
+            if let Some((page_index, origin_x, origin_y)) = anchor {
+                placed.push(FloatingInk {
+                    node_id: ink.id(),
+                    origin_x,
+                    origin_y,
+                    bounds: Rect::new(origin_x + bounds.x, origin_y + bounds.y, bounds.width, bounds.height),
+                    page_index,
+                });
+            }
+        }
+        // Keep a stable order across layouts
+        placed.sort_by_key(|ink| ink.node_id.as_uuid());
+        for ink in placed {
+            layout.add_floating_ink(ink);
+        }
+    }
+
+    /// Draw a separator above where each run of endnotes begins
+    fn place_endnote_separators(&self, layout: &mut LayoutTree) {
+        if self.endnote_separators.is_empty() {
+            return;
+        }
+        for page in &mut layout.pages {
+            let content_y = page.content_area.y;
+            let separators: Vec<FootnoteSeparator> = page
+                .content_columns()
+                .flat_map(|column| column.blocks.iter().map(move |block| (column, block)))
+                .filter_map(|(column, block)| {
+                    let &(length, weight) = self.endnote_separators.get(&block.node_id)?;
+                    // A continued block doesn't start the endnotes
+                    let first_line = block.lines.first()?;
+                    let separator_space = FootnoteSeparator::new(0.0, 0.0, 0.0, weight).height();
+                    if first_line.bounds.y - block.bounds.y < separator_space {
+                        return None;
+                    }
+                    let mut separator = FootnoteSeparator::new(
+                        column.bounds.x,
+                        0.0,
+                        column.bounds.width * length.clamp(0.0, 1.0),
+                        weight,
+                    );
+                    separator.y = content_y + block.bounds.y + separator.space_above;
+                    Some(separator)
+                })
+                .collect();
+            page.note_separators = separators;
+        }
+    }
+
+    /// Find the pages sections start on and measure section headers and
+    /// footers, so the body area leaves room for them
+    ///
+    /// A change to either makes every page stale, so the kept layout is
+    /// dropped.
+    fn prepare_sections(&mut self, tree: &DocumentTree) -> Result<()> {
+        let block_sections = tree.block_sections();
+        let mut section_breaks = HashSet::new();
+        let mut previous = 0;
+        for &block_id in tree.document.children() {
+            let section = block_sections.get(&block_id).copied().unwrap_or(previous);
+            if section != previous && tree.sections[section].page_setup.section_start != SectionBreakType::Continuous {
+                section_breaks.insert(block_id);
+            }
+            previous = section;
+        }
+
+        let line_config = LineBreakConfig {
+            field_context: Some(FieldContext::new().with_page_info(1, 1).with_section_info(1, 1)),
+            ..self.create_line_config(tree)
+        };
+        let (mut header_height, mut footer_height) = (0.0f32, 0.0f32);
+        for section in &tree.sections {
+            for header in section.headers.iter() {
+                header_height = header_height.max(blocks_height(&self.header_footer_blocks(tree, header, &line_config)?));
+            }
+            for footer in section.footers.iter() {
+                footer_height = footer_height.max(blocks_height(&self.header_footer_blocks(tree, footer, &line_config)?));
+            }
+        }
+
+        let header_footer = &mut self.config.header_footer;
+        if section_breaks != self.section_breaks
+            || header_footer.header_content_height != header_height
+            || header_footer.footer_content_height != footer_height
+        {
+            header_footer.header_content_height = header_height;
+            header_footer.footer_content_height = footer_height;
+            self.section_breaks = section_breaks;
+            self.laid_out = None;
+        }
+
+        // Endnotes whose mark or separator changed are re-broken
+        let mut endnote_marks = HashMap::new();
+        let mut endnote_separators = HashMap::new();
+        for (section, notes) in endnote_groups(tree) {
+            let section_id = section.or(tree.sections.len().checked_sub(1)).map(|index| tree.sections[index].id());
+            let props = tree.get_endnote_properties(section_id);
+            let first_paragraph = notes.iter().find_map(|note| note.content().first());
+            if let (true, Some(&para_id)) = (props.show_separator, first_paragraph) {
+                endnote_separators.insert(para_id, (props.separator_length, props.separator_weight));
+            }
+            for note in notes {
+                if let Some(&para_id) = note.content().first() {
+                    endnote_marks.insert(para_id, note.mark.clone());
+                }
+            }
+        }
+        let changed: HashSet<NodeId> = endnote_marks
+            .iter()
+            .filter(|&(para_id, mark)| self.endnote_marks.get(para_id) != Some(mark))
+            .map(|(&para_id, _)| para_id)
+            .chain(self.endnote_marks.keys().filter(|para_id| !endnote_marks.contains_key(para_id)).copied())
+            .chain(
+                endnote_separators
+                    .keys()
+                    .chain(self.endnote_separators.keys())
+                    .filter(|para_id| endnote_separators.get(para_id) != self.endnote_separators.get(para_id))
+                    .copied(),
+            )
+            .collect();
+        for para_id in changed {
+            self.invalidate_paragraph(para_id);
+        }
+        self.endnote_marks = endnote_marks;
+        self.endnote_separators = endnote_separators;
+
+        // Paragraphs whose section grid changed are re-broken
+        let default_font_size = tree
+            .styles
+            .resolve_character_props(Some(tree.styles.default_paragraph_style()), &Default::default())
+            .font_size
+            .unwrap_or(11.0);
+        let mut grid_pitches = HashMap::new();
+        for &block_id in tree.document.children() {
+            let grid = block_sections
+                .get(&block_id)
+                .and_then(|&section| tree.sections.get(section))
+                .map(|section| section.page_setup.doc_grid)
+                .unwrap_or_default();
+            if grid.snaps_lines() || grid.snaps_chars() {
+                let line_pitch = grid.snaps_lines().then_some(grid.line_pitch);
+                let char_pitch = grid.snaps_chars().then(|| grid.char_pitch(default_font_size));
+                grid_pitches.insert(block_id, (line_pitch, char_pitch));
+            }
+        }
+        let changed: Vec<NodeId> = grid_pitches
+            .keys()
+            .chain(self.grid_pitches.keys())
+            .filter(|block_id| grid_pitches.get(block_id) != self.grid_pitches.get(block_id))
+            .copied()
+            .collect();
+        for block_id in changed {
+            self.invalidate_paragraph(block_id);
+        }
+        self.grid_pitches = grid_pitches;
+        Ok(())
+    }
+
+    /// Lay out the header and footer of each page's section, with page
+    /// fields evaluated for that page
+    ///
+    /// Header and footer lines are positioned relative to the page's
+    /// content area, like body lines, so they draw the same way.
+    fn layout_headers_footers(&mut self, tree: &DocumentTree, layout: &mut LayoutTree, page_count: usize) -> Result<()> {
+        if tree.sections.is_empty() {
+            return Ok(());
+        }
+
+        // A page belongs to the section of its first block; empty pages
+        // continue the section before them
+        let block_sections = tree.block_sections();
+        let mut page_sections = Vec::with_capacity(layout.pages.len());
+        let mut current = 0;
+        for page in &layout.pages {
+            if let Some(&section) = page_blocks(page).next().and_then(|block| block_sections.get(&block.node_id)) {
+                current = section;
+            }
+            page_sections.push(current);
+        }
+
+        let mut section_page_counts: HashMap<usize, usize> = HashMap::new();
+        for &section_index in &page_sections {
+            *section_page_counts.entry(section_index).or_default() += 1;
+        }
+
+        let page_numbers = PageNumbering::number_pages(&tree.sections, &page_sections);
+        let line_config = self.create_line_config(tree);
+        // Headers without page fields look the same on every page, so
+        // they're laid out once
+        let mut header_blocks: HashMap<(NodeId, Option<u32>), Vec<PendingBlock>> = HashMap::new();
+        for (index, &section_index) in page_sections.iter().enumerate() {
+            let is_first_page = index == 0 || page_sections[index - 1] != section_index;
+            let section_pages = section_page_counts[&section_index];
+            let section = &tree.sections[section_index];
+            let page_format = section.page_setup.page_numbering.format;
+            let context = FieldContext::new()
+                .with_page_info(page_numbers[index], page_count as u32)
+                .with_page_number_format(page_format.number_format())
+                .with_section_info(section_index as u32 + 1, section_pages as u32);
+
+            let headers = linked_set(&tree.sections, section_index, |s| &s.headers);
+            let footers = linked_set(&tree.sections, section_index, |s| &s.footers);
+            for (area_type, set) in [(AreaType::Header, headers), (AreaType::Footer, footers)] {
+                let header_footer = set.get_for_page(index, is_first_page, section.different_first_page, section.different_odd_even);
+                let Some(header_footer) = header_footer.filter(|hf| hf.has_content()) else {
+                    continue;
+                };
+
+                let key = (header_footer.id(), has_page_fields(tree, header_footer).then_some(index as u32));
+                if let Entry::Vacant(entry) = header_blocks.entry(key) {
+                    let line_config = LineBreakConfig {
+                        field_context: Some(context.clone()),
+                        ..line_config.clone()
+                    };
+                    entry.insert(self.header_footer_blocks(tree, header_footer, &line_config)?);
+                }
+                let blocks = &header_blocks[&key];
+
+                let page = &mut layout.pages[index];
+                let content_top = page.content_area.y;
+                let Some(area) = page.areas.iter_mut().find(|area| area.area_type == area_type) else {
+                    continue;
+                };
+                // Headers hang from the top of their area, footers sit on
+                // the bottom of theirs
+                let mut y = match area_type {
+                    AreaType::Footer => area.bounds.y + area.bounds.height - blocks_height(blocks),
+                    _ => area.bounds.y,
+                } - content_top;
+                let mut column = ColumnBox::new(area.bounds, 0);
+                for block in blocks {
+                    column.blocks.push(self.create_block_box(block, y));
+                    y += block.height;
+                }
+                area.columns = vec![column];
+            }
+            let page = &mut layout.pages[index];
+            page.section_id = Some(section.id());
+            page.page_label = Some(page_format.format(page_numbers[index]));
+            page.watermark = linked_watermark(&tree.sections, section_index)
+                .map(|watermark| self.place_watermark(watermark, page.content_area));
+        }
+        Ok(())
+    }
+
+    /// Center a watermark on a page's content area
+    fn place_watermark(&self, watermark: &Watermark, content_area: Rect) -> PageWatermark {
+        let center = (
+            content_area.x + content_area.width / 2.0,
+            content_area.y + content_area.height / 2.0,
+        );
+        let centered = |width: f32, height: f32| {
+            Rect::new(center.0 - width / 2.0, center.1 - height / 2.0, width, height)
+        };
+
+        match watermark {
+            Watermark::Text(text) => {
+                let (rotation, available) = match text.layout {
+                    WatermarkLayout::Diagonal => (-45.0, content_area.width.hypot(content_area.height) * 0.7),
+                    WatermarkLayout::Horizontal => (0.0, content_area.width * 0.9),
+                };
+                // Auto size scales the text to span the available length
+                let font_size = text.font_size.unwrap_or_else(|| {
+                    let unit_width = self.line_breaker.text_width(&text.text, 1.0);
+                    if unit_width > 0.0 {
+                        (available / unit_width).clamp(8.0, 400.0)
+                    } else {
+                        72.0
+                    }
+                });
+                let width = self.line_breaker.text_width(&text.text, font_size);
+                PageWatermark {
+                    bounds: centered(width, font_size * 1.2),
+                    rotation,
+                    opacity: 1.0 - text.transparency,
+                    content: WatermarkContent::Text {
+                        text: text.text.clone(),
+                        font_family: text.font_family.clone(),
+                        font_size,
+                        color: text.color.clone(),
+                    },
+                }
+            }
+            Watermark::Picture(picture) => {
+                let scale = picture.scale.unwrap_or_else(|| {
+                    if picture.width > 0.0 && picture.height > 0.0 {
+                        (content_area.width / picture.width).min(content_area.height / picture.height)
+                    } else {
+                        1.0
+                    }
+                });
+                PageWatermark {
+                    bounds: centered(picture.width * scale, picture.height * scale),
+                    rotation: 0.0,
+                    opacity: if picture.washout { 0.3 } else { 1.0 },
+                    content: WatermarkContent::Picture {
+                        resource_id: picture.resource_id.as_str().to_string(),
+                    },
+                }
+            }
+        }
+    }
+
+    /// Break a header's or footer's paragraphs into lines
+    fn header_footer_blocks(
+        &mut self,
+        tree: &DocumentTree,
+        header_footer: &HeaderFooter,
+        line_config: &LineBreakConfig,
+    ) -> Result<Vec<PendingBlock>> {
+        let mut blocks = Vec::new();
+        for para in header_footer.children().iter().filter_map(|&id| tree.get_paragraph(id)) {
+            let para_line_config = self.paragraph_line_config(tree, para, line_config);
+            let broken = self.line_breaker.break_paragraph(tree, para.id(), &para_line_config)?;
+            blocks.push(PendingBlock::new(
+                para.id(),
+                broken.lines,
+                para.style.space_before.unwrap_or(0.0),
+                para.style.space_after.unwrap_or(0.0),
+            ));
+        }
+        Ok(blocks)
     }
 
     /// Perform incremental layout after an edit
@@ -899,19 +1594,192 @@ impl Paginator {
         tree: &DocumentTree,
         edited_para_id: NodeId,
     ) -> Result<LayoutTree> {
-        // Mark the edited paragraph as dirty
-        self.invalidate_paragraph(edited_para_id);
+        self.relayout(tree, &[edited_para_id])
+            .map(|(layout, _)| layout.clone())
+    }
+
+    /// Create the pending block for a paragraph, reusing its cached lines
+    /// when allowed and the paragraph hasn't been invalidated
+    fn pending_block(
+        &mut self,
+        tree: &DocumentTree,
+        para: &doc_model::Paragraph,
+        line_config: &LineBreakConfig,
+        reuse_cached: bool,
+    ) -> Result<PendingBlock> {
+        let para_id = para.id();
+
+        // Get paragraph spacing, leaving room for an endnote separator
+        let separator_space = match self.endnote_separators.get(&para_id) {
+            Some(&(_, weight)) => FootnoteSeparator::new(0.0, 0.0, 0.0, weight).height(),
+            None => 0.0,
+        };
+        let space_before = para.style.space_before.unwrap_or(0.0) + separator_space;
+        let space_after = para.style.space_after.unwrap_or(0.0);
+
+        let cached = if reuse_cached && !self.reflow_state.is_dirty(para_id) {
+            self.cache.get_lines(para_id, line_config.available_width)
+        } else {
+            None
+        };
+        let lines = match cached {
+            Some(lines) => lines,
+            None => self.break_paragraph_lines(tree, para, line_config)?,
+        };
+
+        // Get paragraph pagination options
+        let keep_with_next = keeps_with_next(para);
+        let keep_together = para.style.keep_together.unwrap_or(false)
+            || para.direct_formatting.keep_together.unwrap_or(false);
+        let page_break_before = para.style.page_break_before.unwrap_or(false)
+            || para.direct_formatting.page_break_before.unwrap_or(false)
+            || self.section_breaks.contains(&para_id);
+
+        Ok(PendingBlock::with_pagination_options(
+            para_id,
+            lines,
+            space_before,
+            space_after,
+            keep_with_next,
+            keep_together,
+            page_break_before,
+        ))
+    }
+
+    /// Break a paragraph into lines and cache the result
+    fn break_paragraph_lines(
+        &mut self,
+        tree: &DocumentTree,
+        para: &doc_model::Paragraph,
+        line_config: &LineBreakConfig,
+    ) -> Result<Vec<LineBox>> {
+        let para_id = para.id();
+        let para_line_config = self.paragraph_line_config(tree, para, line_config);
+
+        // Break paragraph into lines
+        let broken = self.line_breaker.break_paragraph(tree, para_id, &para_line_config)?;
+
+        // Cache the result
+        self.cache.store(
+            para_id,
+            tree.document.version(),
+            line_config.available_width,
+            &broken.lines,
+            broken.total_height,
+        );
+
+        Ok(broken.lines)
+    }
+
+    /// Line break configuration for a paragraph: its indents, spacing,
+    /// alignment, list marker, and tab stops
+    fn paragraph_line_config(
+        &self,
+        tree: &DocumentTree,
+        para: &doc_model::Paragraph,
+        line_config: &LineBreakConfig,
+    ) -> LineBreakConfig {
+        // Get list properties if paragraph is in a list
+        let (list_marker_text, list_is_bullet, list_marker_font, list_level, list_num_id, list_hanging) =
+            if let Some(list_props) = &para.direct_formatting.list_props {
+                if let Some(num_id) = list_props.num_id {
+                    let level = list_props.effective_level();
+                    let is_bullet = tree.numbering.is_bullet_list(num_id);
+
+                    // Get the level definition
+                    if let Some(level_def) = tree.numbering.get_effective_level(num_id, level) {
+                        // Build counts array for multi-level formatting
+                        let counts: Vec<u32> = (0..=level)
+                            .map(|l| tree.numbering.get_counter(num_id, l) + 1)
+                            .collect();
+
+                        let marker_text = level_def.format_number(&counts);
+                        let marker_font = level_def.font.clone();
+                        let hanging = level_def.hanging;
+
+                        (Some(marker_text), is_bullet, marker_font, Some(level), Some(num_id), hanging)
+                    } else {
+                        (None, false, None, None, None, 0.0)
+                    }
+                } else {
+                    (None, false, None, None, None, 0.0)
+                }
+            } else {
+                (None, false, None, None, None, 0.0)
+            };
+
+        // An endnote's first paragraph leads with its mark
+        let list_marker_text = list_marker_text.or_else(|| self.endnote_marks.get(&para.id()).cloned());
+
+        // Apply list indent to left indent
+        let list_indent = if let (Some(num_id), Some(level)) = (list_num_id, list_level) {
+            if let Some(level_def) = tree.numbering.get_effective_level(num_id, level) {
+                level_def.indent
+            } else {
+                0.0
+            }
+        } else {
+            0.0
+        };
+
+        let resolved = tree
+            .styles
+            .resolve_paragraph_props(para.paragraph_style_id.as_ref(), &para.direct_formatting);
+        let direction = match resolved.direction {
+            Some(TextDirection::Rtl) => Direction::Rtl,
+            Some(TextDirection::Auto) => {
+                let text: String = para
+                    .children()
+                    .iter()
+                    .filter_map(|&child_id| tree.get_run(child_id))
+                    .map(|run| run.text.as_str())
+                    .collect();
+                BidiAnalyzer::new().detect_base_direction_or(&text, line_config.direction)
+            }
+            Some(TextDirection::Ltr) | None => line_config.direction,
+        };
+
+        // Indents are given from the paragraph's start side, so an RTL
+        // paragraph takes its start indent (and list indent) on the right
+        let start_indent = para.style.indent_left.unwrap_or(0.0) + list_indent;
+        let end_indent = para.style.indent_right.unwrap_or(0.0);
+        let (left_indent, right_indent) = match direction {
+            Direction::Ltr => (start_indent, end_indent),
+            Direction::Rtl => (end_indent, start_indent),
+        };
+
+        let (line_grid_pitch, char_grid_pitch) = self.grid_pitches.get(&para.id()).copied().unwrap_or_default();
 
-        // For now, perform full layout
-        // A more sophisticated implementation would:
-        // 1. Find the page containing the edited paragraph
-        // 2. Re-layout only from that paragraph forward
-        // 3. Stop when page breaks stabilize
-        self.layout(tree)
+        LineBreakConfig {
+            available_width: line_config.available_width,
+            font_size: line_config.font_size,
+            line_spacing: match para.style.line_spacing {
+                Some(doc_model::LineSpacing::Multiple(m)) => m,
+                _ => line_config.line_spacing,
+            },
+            first_line_indent: para.style.indent_first_line.unwrap_or(0.0),
+            left_indent,
+            right_indent,
+            direction,
+            allow_hyphenation: line_config.allow_hyphenation,
+            alignment: para.style.alignment.unwrap_or(Alignment::Left),
+            list_num_id,
+            list_level,
+            list_marker_text,
+            list_is_bullet,
+            list_marker_font,
+            list_hanging,
+            tab_stops: resolved.tab_stops,
+            default_tab_interval: line_config.default_tab_interval,
+            field_context: line_config.field_context.clone(),
+            line_grid_pitch,
+            char_grid_pitch,
+        }
     }
 
     /// Create line break configuration from page config and document
-    fn create_line_config(&self, _tree: &DocumentTree) -> LineBreakConfig {
+    fn create_line_config(&self, tree: &DocumentTree) -> LineBreakConfig {
+        let settings = &tree.document.settings;
         LineBreakConfig {
             available_width: self.config.content_width(),
             font_size: 12.0, // Default font size
@@ -920,7 +1788,7 @@ impl Paginator {
             left_indent: 0.0,
             right_indent: 0.0,
             direction: crate::Direction::Ltr,
-            allow_hyphenation: false,
+            allow_hyphenation: settings.hyphenation.auto_hyphenate,
             alignment: Alignment::Left,
             list_num_id: None,
             list_level: None,
@@ -928,6 +1796,11 @@ impl Paginator {
             list_is_bullet: false,
             list_marker_font: None,
             list_hanging: 0.0,
+            tab_stops: Vec::new(),
+            default_tab_interval: settings.default_tab_stop,
+            field_context: None,
+            line_grid_pitch: None,
+            char_grid_pitch: None,
         }
     }
 
@@ -969,15 +1842,40 @@ impl Paginator {
     fn paginate_blocks(
         &mut self,
         layout: &mut LayoutTree,
-        mut blocks: Vec<PendingBlock>,
+        blocks: Vec<PendingBlock>,
     ) -> Result<()> {
+        self.paginate_blocks_from(layout, blocks, 0, |_, _| false)
+            .map(|_| ())
+    }
+
+    /// Paginate blocks onto pages starting at the top of `first_page`
+    ///
+    /// Before each later page that starts with a whole block, `stop` is
+    /// asked whether the rest of the layout is already known; if it says
+    /// so, pagination ends there and that page's index is returned.
+    fn paginate_blocks_from(
+        &mut self,
+        layout: &mut LayoutTree,
+        mut blocks: Vec<PendingBlock>,
+        first_page: usize,
+        mut stop: impl FnMut(usize, usize) -> bool,
+    ) -> Result<Option<usize>> {
         let mut current_page_blocks: Vec<BlockBox> = Vec::new();
         let mut current_y = 0.0;
-        let mut page_index = 0;
+        let mut page_index = first_page;
 
         let mut block_index = 0;
         while block_index < blocks.len() {
             let block = &blocks[block_index];
+
+            if current_page_blocks.is_empty()
+                && page_index > first_page
+                && !block.is_continuation
+                && stop(page_index, block_index)
+            {
+                return Ok(Some(page_index));
+            }
+
             let page_content_height = self.config.content_height_for_page(page_index);
             let remaining_height = page_content_height - current_y;
 
@@ -1147,7 +2045,7 @@ impl Paginator {
             layout.add_page(self.create_page(page_index, current_page_blocks));
         }
 
-        Ok(())
+        Ok(None)
     }
 
     /// Calculate the minimum height required for keep_with_next chains
@@ -1266,9 +2164,10 @@ impl Paginator {
     fn create_page(&self, index: usize, blocks: Vec<BlockBox>) -> PageBox {
         let content_top = self.config.content_top_for_page(index);
         let content_height = self.config.content_height_for_page(index);
+        let margin_left = self.config.margin_left_for_page(index);
 
         let content_area = Rect::new(
-            self.config.margin_left,
+            margin_left,
             content_top,
             self.config.content_width(),
             content_height,
@@ -1287,7 +2186,7 @@ impl Paginator {
             // Multi-column - create column boxes (blocks will be distributed by caller)
             for (i, (x_offset, width)) in column_bounds.iter().enumerate() {
                 let col_bounds = Rect::new(
-                    self.config.margin_left + x_offset,
+                    margin_left + x_offset,
                     content_top,
                     *width,
                     content_height,
@@ -1307,10 +2206,10 @@ impl Paginator {
             || index > 0;
         if has_header {
             let header_area = Rect::new(
-                self.config.margin_left,
+                margin_left,
                 self.config.header_footer.header_margin,
                 self.config.content_width(),
-                self.config.header_footer.header_height,
+                self.config.header_footer.reserved_header_height(),
             );
             areas.insert(0, AreaBox::header(header_area));
         }
@@ -1320,12 +2219,12 @@ impl Paginator {
             || index > 0;
         if has_footer {
             let footer_area = Rect::new(
-                self.config.margin_left,
+                margin_left,
                 self.config.page_height
                     - self.config.header_footer.footer_margin
-                    - self.config.header_footer.footer_height,
+                    - self.config.header_footer.reserved_footer_height(),
                 self.config.content_width(),
-                self.config.header_footer.footer_height,
+                self.config.header_footer.reserved_footer_height(),
             );
             areas.push(AreaBox::footer(footer_area));
         }
@@ -1347,26 +2246,34 @@ impl Paginator {
 
     /// Generate line numbers for all pages in the layout
     ///
-    /// This method walks through all pages, blocks, and lines in the layout tree
-    /// and generates line number items based on the configuration.
-    fn generate_line_numbers(&self, layout: &mut LayoutTree) {
-        if !self.config.line_numbering.enabled {
+    /// Each section numbers its lines with its own settings, falling back to
+    /// the paginator's. Lines of paragraphs that suppress line numbers are
+    /// neither numbered nor counted.
+    fn generate_line_numbers(&self, tree: &DocumentTree, layout: &mut LayoutTree) {
+        let section_config = |section: &Section| {
+            if section.page_setup.line_numbering.enabled {
+                section.page_setup.line_numbering.clone()
+            } else {
+                self.config.line_numbering.clone()
+            }
+        };
+        if !self.config.line_numbering.enabled
+            && !tree.sections.iter().any(|section| section.page_setup.line_numbering.enabled)
+        {
             return;
         }
 
+        let block_sections = tree.block_sections();
         let mut tracker = LineNumberTracker::new(self.config.line_numbering.clone());
+        let mut section_index = 0;
         let font_size = 10.0; // Default line number font size (slightly smaller than body text)
 
         // Collect all line numbers first to avoid borrow issues
         let mut collected_line_numbers: Vec<(usize, LineNumberItem)> = Vec::new();
 
         for page in &layout.pages {
-            // Handle per-page restart
-            if self.config.line_numbering.restart == LineNumberRestart::PerPage {
-                tracker.reset();
-            }
-
             let page_index = page.index;
+            tracker.on_new_page(page_index);
             let content_area_x = page.content_area.x;
             let content_area_y = page.content_area.y;
 
@@ -1379,6 +2286,18 @@ impl Paginator {
 
                 for column in &area.columns {
                     for block in &column.blocks {
+                        section_index = block_sections.get(&block.node_id).copied().unwrap_or(section_index);
+                        if let Some(section) = tree.sections.get(section_index) {
+                            tracker.enter_section(section.id(), section_config(section));
+                        }
+                        let suppressed = tree
+                            .compute_paragraph_properties(block.node_id)
+                            .and_then(|props| props.suppress_line_numbers)
+                            .unwrap_or(false);
+                        if suppressed || !tracker.is_enabled() {
+                            continue;
+                        }
+
                         for line in &block.lines {
                             // Get current line number before incrementing
                             let line_num = tracker.current_number();
@@ -1387,13 +2306,13 @@ impl Paginator {
                             tracker.process_line_silent();
 
                             // Check if we should display this line number
-                            if !self.config.line_numbering.should_display(line_num) {
+                            if !tracker.config().should_display(line_num) {
                                 continue;
                             }
 
                             // Calculate position
                             // X: right-aligned in the left margin, at distance_from_text from content
-                            let x = content_area_x - self.config.line_numbering.distance_from_text;
+                            let x = content_area_x - tracker.config().distance_from_text;
 
                             // Y: baseline-aligned with the text line
                             let y = content_area_y + block.bounds.y + line.bounds.y + line.baseline;
@@ -1421,6 +2340,170 @@ impl Paginator {
     }
 }
 
+/// Check if a paragraph must stay on the same page as the next one
+fn keeps_with_next(para: &doc_model::Paragraph) -> bool {
+    para.style.keep_with_next.unwrap_or(false)
+        || para.direct_formatting.keep_with_next.unwrap_or(false)
+}
+
+/// Check if a paragraph is a numbered or bulleted list item
+fn in_list(para: &doc_model::Paragraph) -> bool {
+    para.direct_formatting
+        .list_props
+        .as_ref()
+        .is_some_and(|list_props| list_props.num_id.is_some())
+}
+
+/// The paragraphs laid out in order: the body's, with endnotes after
+/// the section or document they're placed at the end of
+fn flow_paragraphs(tree: &DocumentTree) -> Vec<&doc_model::Paragraph> {
+    let note_paragraphs = |notes: &[&Note]| -> Vec<&doc_model::Paragraph> {
+        notes
+            .iter()
+            .flat_map(|note| note.content())
+            .filter_map(|&id| tree.get_paragraph(id))
+            .collect()
+    };
+    let mut groups = endnote_groups(tree);
+    if groups.is_empty() {
+        return tree.paragraphs().collect();
+    }
+
+    // Section endnotes go before the first block of the next section
+    let block_sections = tree.block_sections();
+    let mut paragraphs = Vec::new();
+    let mut section = 0;
+    for &block_id in tree.document.children() {
+        let block_section = block_sections.get(&block_id).copied().unwrap_or(section);
+        for (_, notes) in groups.iter_mut().filter(|(group, _)| group.is_some_and(|group| group < block_section)) {
+            paragraphs.extend(note_paragraphs(&std::mem::take(notes)));
+        }
+        section = block_section;
+        paragraphs.extend(tree.get_paragraph(block_id));
+    }
+    for (_, notes) in &groups {
+        paragraphs.extend(note_paragraphs(notes));
+    }
+    paragraphs
+}
+
+/// Endnotes grouped by where they're placed: after a section (by index)
+/// or after the document (`None`, always last), in reference order
+fn endnote_groups(tree: &DocumentTree) -> Vec<(Option<usize>, Vec<&Note>)> {
+    let mut groups: Vec<(Option<usize>, Vec<&Note>)> = Vec::new();
+    let mut document_end = Vec::new();
+    for (note, section) in tree.endnotes_in_order() {
+        let section_id = tree.sections.get(section).map(|section| section.id());
+        if tree.get_endnote_properties(section_id).position == EndnotePosition::EndOfSection {
+            match groups.last_mut() {
+                Some((Some(group), notes)) if *group == section => notes.push(note),
+                _ => groups.push((Some(section), vec![note])),
+            }
+        } else {
+            document_end.push(note);
+        }
+    }
+    if !document_end.is_empty() {
+        groups.push((None, document_end));
+    }
+    groups
+}
+
+/// Iterate over every body block on a page
+fn page_blocks(page: &PageBox) -> impl Iterator<Item = &BlockBox> {
+    page.content_columns().flat_map(|column| column.blocks.iter())
+}
+
+/// Iterate over the header and footer blocks on a page
+fn header_footer_blocks(page: &PageBox) -> impl Iterator<Item = &BlockBox> {
+    page.areas
+        .iter()
+        .filter(|area| area.area_type != AreaType::Content)
+        .flat_map(|area| area.columns.iter())
+        .flat_map(|column| column.blocks.iter())
+}
+
+/// Total height of pending blocks
+fn blocks_height(blocks: &[PendingBlock]) -> f32 {
+    blocks.iter().map(|block| block.height).sum()
+}
+
+/// The headers or footers a section uses, following "link to previous"
+/// back to the last section that defines its own
+fn linked_set<'a>(
+    sections: &'a [Section],
+    mut index: usize,
+    set: impl Fn(&'a Section) -> &'a HeaderFooterSet,
+) -> &'a HeaderFooterSet {
+    while index > 0 && sections[index].link_to_previous && !set(&sections[index]).has_any() {
+        index -= 1;
+    }
+    set(&sections[index])
+}
+
+/// The watermark a section shows, following "link to previous" back to
+/// the last section that sets its own
+fn linked_watermark(sections: &[Section], mut index: usize) -> Option<&Watermark> {
+    while index > 0 && sections[index].link_to_previous && sections[index].watermark.is_none() {
+        index -= 1;
+    }
+    sections[index].watermark.as_ref()
+}
+
+/// Check if a header or footer shows fields that change from page to page
+fn has_page_fields(tree: &DocumentTree, header_footer: &HeaderFooter) -> bool {
+    header_footer
+        .children()
+        .iter()
+        .filter_map(|&id| tree.get_paragraph(id))
+        .flat_map(|para| para.children())
+        .filter_map(|&id| tree.get_field(id))
+        .any(|field| field.auto_updates_on_layout())
+}
+
+/// Fingerprint each page's block placement, header and footer field
+/// values, and line numbering
+///
+/// Two layouts give a page the same signature when the same paragraph
+/// lines sit in the same places on it.
+fn page_signatures(layout: &LayoutTree) -> Vec<u64> {
+    layout
+        .pages
+        .iter()
+        .map(|page| {
+            let mut hasher = DefaultHasher::new();
+            for block in page_blocks(page) {
+                block.node_id.hash(&mut hasher);
+                for value in [block.bounds.x, block.bounds.y, block.bounds.width, block.bounds.height] {
+                    value.to_bits().hash(&mut hasher);
+                }
+                block.lines.len().hash(&mut hasher);
+
+                let first_inline = block.lines.first().and_then(|line| line.inlines.first());
+                if let Some(inline) = first_inline {
+                    inline.start_offset.hash(&mut hasher);
+                    inline.list_marker.as_ref().map(|marker| &marker.text).hash(&mut hasher);
+                }
+            }
+            for block in header_footer_blocks(page) {
+                block.node_id.hash(&mut hasher);
+                block.bounds.y.to_bits().hash(&mut hasher);
+                let inlines = block.lines.iter().flat_map(|line| line.inlines.iter());
+                for text in inlines.filter_map(|inline| inline.field_text.as_ref()) {
+                    text.hash(&mut hasher);
+                }
+            }
+            layout
+                .line_numbers_on_page(page.index)
+                .first()
+                .map(|item| item.number)
+                .hash(&mut hasher);
+            page.watermark.as_ref().map(|watermark| format!("{:?}", watermark)).hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
 impl Default for Paginator {
     fn default() -> Self {
         Self::new(PageConfig::default())
@@ -1430,7 +2513,10 @@ impl Default for Paginator {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use doc_model::{Paragraph, Run};
+    use doc_model::{
+        EndnoteProperties, InkNode, InkPoint, InkStroke, LineNumberRestart, NumberingScheme, Paragraph, Position,
+        RestartNumbering, Run, ShapeColor,
+    };
 
     fn create_test_document() -> DocumentTree {
         let mut tree = DocumentTree::new();
@@ -1491,6 +2577,44 @@ mod tests {
         assert!((config.page_height - 841.89).abs() < 0.01);
     }
 
+    #[test]
+    fn test_mirror_margins() {
+        let mut config = PageConfig::letter().with_mirror_margins(true);
+        config.margin_left = 90.0;
+        config.margin_right = 54.0;
+        let mut paginator = Paginator::new(config);
+        let layout = paginator.layout(&create_long_document()).unwrap();
+
+        assert!(layout.page_count() > 1);
+        assert_eq!(layout.pages[0].content_area.x, 90.0);
+        assert_eq!(layout.pages[1].content_area.x, 54.0);
+        assert_eq!(layout.pages[0].content_area.width, layout.pages[1].content_area.width);
+    }
+
+    #[test]
+    fn test_gutter_on_inside_of_mirrored_pages() {
+        let mut setup = SectionPageSetup::default();
+        setup.gutter = 36.0;
+        setup.mirror_margins = true;
+        let mut config = PageConfig::letter().with_section_binding(&setup);
+        config.margin_left = 90.0;
+        config.margin_right = 54.0;
+        let mut paginator = Paginator::new(config);
+        let layout = paginator.layout(&create_long_document()).unwrap();
+
+        assert!(layout.page_count() > 1);
+        // Odd pages: inside margin plus gutter on the left
+        assert_eq!(layout.pages[0].content_area.x, 126.0);
+        assert_eq!(layout.pages[0].content_area.width, 612.0 - 90.0 - 54.0 - 36.0);
+        // Even pages: outside margin on the left, inside on the right
+        assert_eq!(layout.pages[1].content_area.x, 54.0);
+        assert_eq!(layout.pages[1].content_area.width, layout.pages[0].content_area.width);
+
+        let top = PageConfig::letter().with_gutter(36.0, GutterPosition::Top);
+        assert_eq!(top.content_top_for_page(1) - PageConfig::letter().content_top_for_page(1), 36.0);
+        assert_eq!(top.content_width(), PageConfig::letter().content_width());
+    }
+
     #[test]
     fn test_single_page_layout() {
         let tree = create_test_document();
@@ -1511,6 +2635,66 @@ mod tests {
         assert!(layout.page_count() > 1, "Expected multiple pages, got {}", layout.page_count());
     }
 
+    #[test]
+    fn test_relayout_reports_dirty_pages() {
+        let mut tree = create_long_document();
+        let mut paginator = Paginator::default();
+        let page_count = paginator.layout(&tree).unwrap().page_count();
+
+        let page_of = |layout: &LayoutTree, para_id: NodeId| {
+            layout
+                .pages
+                .iter()
+                .position(|page| page_blocks(page).any(|block| block.node_id == para_id))
+                .unwrap()
+        };
+        let para_ids: Vec<NodeId> = tree.paragraphs().map(|para| para.id()).collect();
+        let set_text = |tree: &mut DocumentTree, para_id: NodeId, text: &str| {
+            let run_id = tree.get_paragraph(para_id).unwrap().children()[0];
+            tree.get_run_mut(run_id).unwrap().text = text.to_string();
+        };
+
+        // A same-length edit only touches its own page
+        let middle = para_ids[para_ids.len() / 2];
+        set_text(&mut tree, middle, "Paragraph X.");
+        paginator.relayout(&tree, &[middle]).unwrap();
+        set_text(&mut tree, middle, "Paragraph Y.");
+        let (layout, dirty) = paginator.relayout(&tree, &[middle]).unwrap();
+        assert_eq!(dirty.pages, vec![page_of(layout, middle)]);
+
+        // Nothing changed, nothing to redraw
+        let (_, dirty) = paginator.relayout(&tree, &[]).unwrap();
+        assert!(dirty.is_empty());
+
+        // Growing the first paragraph pushes content onto later pages
+        let long_text = "Lorem ipsum dolor sit amet. ".repeat(40);
+        set_text(&mut tree, para_ids[0], &long_text);
+        let (layout, dirty) = paginator.relayout(&tree, &[para_ids[0]]).unwrap();
+        assert!(dirty.contains(0));
+        assert!(dirty.contains(page_count - 1));
+        assert_eq!(dirty.page_count, layout.page_count());
+
+        // Matches a layout from scratch
+        let full = Paginator::default().layout(&tree).unwrap();
+        assert_eq!(page_signatures(layout), page_signatures(&full));
+    }
+
+    #[test]
+    fn test_relayout_after_removing_paragraph() {
+        let mut tree = create_long_document();
+        let mut paginator = Paginator::default();
+        paginator.layout(&tree).unwrap();
+
+        let para_id = tree.paragraphs().nth(3).unwrap().id();
+        tree.remove_paragraph(para_id).unwrap();
+        let (layout, dirty) = paginator.relayout(&tree, &[]).unwrap();
+
+        let full = Paginator::default().layout(&tree).unwrap();
+        assert_eq!(page_signatures(layout), page_signatures(&full));
+        assert!(dirty.contains(0));
+        assert!(dirty.contains(full.page_count() - 1));
+    }
+
     #[test]
     fn test_empty_document_has_one_page() {
         let tree = DocumentTree::new();
@@ -1633,6 +2817,290 @@ mod tests {
         assert!(content_height_page_0 < config.content_height());
     }
 
+    /// Add a section whose headers and footers hold "Page {PAGE} of
+    /// {NUMPAGES}", with a separate first page header
+    fn add_page_number_section(tree: &mut DocumentTree) -> (NodeId, NodeId) {
+        let mut section = doc_model::Section::new();
+        section.different_first_page = true;
+        let (header, first_header) = (doc_model::HeaderFooter::new(), doc_model::HeaderFooter::new());
+        let (header_id, first_header_id) = (header.id(), first_header.id());
+        section.set_default_header(header);
+        section.set_first_page_header(first_header);
+        section.set_default_footer(doc_model::HeaderFooter::new());
+        let footer_id = section.footers.default.as_ref().unwrap().id();
+        tree.add_section(section);
+
+        for hf_id in [header_id, first_header_id, footer_id] {
+            let para_id = tree.insert_paragraph_into_header_footer(Paragraph::new(), hf_id, None).unwrap();
+            tree.insert_run(Run::new("Page "), para_id, None).unwrap();
+            tree.insert_field(doc_model::Field::page(), para_id, None).unwrap();
+            tree.insert_run(Run::new(" of "), para_id, None).unwrap();
+            tree.insert_field(doc_model::Field::num_pages(), para_id, None).unwrap();
+        }
+        (header_id, first_header_id)
+    }
+
+    /// Field values in a page's header or footer
+    fn field_texts(page: &PageBox, area_type: AreaType) -> Vec<String> {
+        page.area(area_type)
+            .into_iter()
+            .flat_map(|area| area.columns.iter())
+            .flat_map(|column| column.blocks.iter())
+            .flat_map(|block| block.lines.iter())
+            .flat_map(|line| line.inlines.iter())
+            .filter_map(|inline| inline.field_text.as_ref().map(|text| text.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_section_headers_evaluate_page_fields() {
+        let mut tree = create_long_document();
+        let (header_id, first_header_id) = add_page_number_section(&mut tree);
+        let header_para = |hf_id| tree.get_header_footer(hf_id).unwrap().children()[0];
+
+        let mut paginator = Paginator::letter();
+        let layout = paginator.layout(&tree).unwrap();
+        let total = layout.page_count();
+        assert!(total > 1);
+
+        for page in &layout.pages {
+            let numbers = vec![(page.index + 1).to_string(), total.to_string()];
+            assert_eq!(field_texts(page, AreaType::Header), numbers);
+            assert_eq!(field_texts(page, AreaType::Footer), numbers);
+
+            // The first page uses the first page header
+            let header = page.area(AreaType::Header).unwrap();
+            let expected = if page.index == 0 { first_header_id } else { header_id };
+            assert_eq!(header.columns[0].blocks[0].node_id, header_para(expected));
+        }
+
+        // Header lines sit in the header area, above the body
+        let page = &layout.pages[0];
+        let header = page.area(AreaType::Header).unwrap();
+        let line = &header.columns[0].blocks[0].lines[0];
+        let line_top = page.content_area.y + line.bounds.y;
+        assert!(line_top >= header.bounds.y && line_top < page.content_area.y);
+    }
+
+    #[test]
+    fn test_tall_header_reserves_body_space() {
+        let mut tree = create_test_document();
+        let mut section = doc_model::Section::new();
+        let header = doc_model::HeaderFooter::new();
+        let header_id = header.id();
+        section.set_default_header(header);
+        tree.add_section(section);
+
+        let mut paginator = Paginator::letter();
+        let default_top = paginator.layout(&tree).unwrap().pages[0].content_area.y;
+        for _ in 0..6 {
+            let para_id = tree.insert_paragraph_into_header_footer(Paragraph::new(), header_id, None).unwrap();
+            tree.insert_run(Run::new("Letterhead"), para_id, None).unwrap();
+        }
+
+        let layout = paginator.layout(&tree).unwrap();
+        let page = &layout.pages[0];
+        let header_area = page.area(AreaType::Header).unwrap();
+        let header_height: f32 = header_area.columns[0].blocks.iter().map(|block| block.bounds.height).sum();
+        assert!(header_height > paginator.config().header_footer.header_height);
+        assert!(page.content_area.y > default_top);
+        assert!(page.content_area.y >= header_area.bounds.y + header_height);
+    }
+
+    #[test]
+    fn test_page_numbers_restart_per_section() {
+        let mut tree = create_long_document();
+        let blocks = tree.document.children().to_vec();
+
+        let mut front = Section::new();
+        front.page_setup.page_numbering.format = doc_model::PageNumberFormat::LowercaseRoman;
+        let footer = doc_model::HeaderFooter::new();
+        let footer_id = footer.id();
+        front.set_default_footer(footer);
+        front.add_child(blocks[0]);
+        let mut body = Section::new();
+        body.link_to_previous = true;
+        body.page_setup.page_numbering = PageNumbering::restart(doc_model::PageNumberFormat::Arabic, 1);
+        for &block_id in &blocks[1..] {
+            body.add_child(block_id);
+        }
+        tree.add_section(front);
+        tree.add_section(body);
+        let para_id = tree.insert_paragraph_into_header_footer(Paragraph::new(), footer_id, None).unwrap();
+        tree.insert_field(doc_model::Field::page(), para_id, None).unwrap();
+
+        let layout = Paginator::letter().layout(&tree).unwrap();
+        assert!(layout.page_count() > 2);
+        let labels: Vec<_> = layout.pages.iter().map(|page| page.page_label.clone().unwrap()).collect();
+        assert_eq!(labels[..3], ["i", "1", "2"]);
+        for (page, label) in layout.pages.iter().zip(&labels) {
+            assert_eq!(field_texts(page, AreaType::Footer), vec![label.clone()]);
+        }
+    }
+
+    #[test]
+    fn test_section_watermark_on_every_page() {
+        let mut tree = create_long_document();
+        tree.add_section(doc_model::Section::new().with_watermark(doc_model::Watermark::text("DRAFT")));
+
+        let mut paginator = Paginator::letter();
+        let layout = paginator.layout(&tree).unwrap();
+        assert!(layout.page_count() > 1);
+        for page in &layout.pages {
+            let watermark = page.watermark.as_ref().unwrap();
+            assert_eq!(watermark.rotation, -45.0);
+            assert!((watermark.opacity - 0.5).abs() < 1e-6);
+            let center_x = watermark.bounds.x + watermark.bounds.width / 2.0;
+            let content_center_x = page.content_area.x + page.content_area.width / 2.0;
+            assert!((center_x - content_center_x).abs() < 0.01);
+            match &watermark.content {
+                WatermarkContent::Text { text, font_size, .. } => {
+                    assert_eq!(text, "DRAFT");
+                    assert!(*font_size > 8.0);
+                }
+                other => panic!("expected a text watermark, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_section_line_numbering_continues_and_skips_suppressed() {
+        let numbers = |restart: LineNumberRestart| {
+            let mut tree = DocumentTree::new();
+            let root_id = tree.root_id();
+            let mut blocks = Vec::new();
+            for _ in 0..4 {
+                let para_id = tree.insert_paragraph(Paragraph::new(), root_id, None).unwrap();
+                tree.insert_run(Run::new("Short line"), para_id, None).unwrap();
+                blocks.push(para_id);
+            }
+            tree.get_paragraph_mut(blocks[1]).unwrap().direct_formatting.suppress_line_numbers = Some(true);
+
+            let mut first = Section::new();
+            first.page_setup.line_numbering = LineNumbering::enabled();
+            first.add_child(blocks[0]);
+            first.add_child(blocks[1]);
+            let mut second = Section::new();
+            second.page_setup.section_start = SectionBreakType::Continuous;
+            second.page_setup.line_numbering = LineNumbering::enabled().with_restart(restart);
+            second.add_child(blocks[2]);
+            second.add_child(blocks[3]);
+            tree.add_section(first);
+            tree.add_section(second);
+
+            let layout = Paginator::letter().layout(&tree).unwrap();
+            layout.line_numbers_on_page(0).iter().map(|item| item.number).collect::<Vec<_>>()
+        };
+
+        assert_eq!(numbers(LineNumberRestart::Continuous), vec![1, 2, 3]);
+        assert_eq!(numbers(LineNumberRestart::PerSection), vec![1, 1, 2]);
+    }
+
+    #[test]
+    fn test_ink_placed_over_anchor_paragraph() {
+        let mut tree = DocumentTree::new();
+        let root_id = tree.root_id();
+        let mut blocks = Vec::new();
+        for _ in 0..2 {
+            let para_id = tree.insert_paragraph(Paragraph::new(), root_id, None).unwrap();
+            tree.insert_run(Run::new("Body text"), para_id, None).unwrap();
+            blocks.push(para_id);
+        }
+        let stroke = InkStroke::new(vec![InkPoint::at(10.0, 4.0), InkPoint::at(50.0, 4.0)], ShapeColor::BLUE, 2.0);
+        let ink_id = tree.insert_ink(InkNode::new(vec![stroke]), blocks[1]).unwrap();
+
+        let layout = Paginator::letter().layout(&tree).unwrap();
+        let page = &layout.pages[0];
+        let block = page_blocks(page).find(|block| block.node_id == blocks[1]).unwrap();
+        let ink = layout.floating_inks_on_page(0).next().unwrap();
+        assert_eq!(ink.node_id, ink_id);
+        assert_eq!(ink.origin_y, page.content_area.y + block.bounds.y);
+        assert_eq!(ink.bounds.x, ink.origin_x + 9.0);
+        assert_eq!(ink.bounds.width, 42.0);
+    }
+
+    #[test]
+    fn test_endnotes_at_end_of_section_or_document() {
+        let layout_endnotes = |props: EndnoteProperties| {
+            let mut tree = DocumentTree::new();
+            let root_id = tree.root_id();
+            let mut blocks = Vec::new();
+            for _ in 0..4 {
+                let para_id = tree.insert_paragraph(Paragraph::new(), root_id, None).unwrap();
+                tree.insert_run(Run::new("Body text"), para_id, None).unwrap();
+                blocks.push(para_id);
+            }
+            let mut first = Section::new();
+            first.add_child(blocks[0]);
+            first.add_child(blocks[1]);
+            let mut second = Section::new();
+            second.page_setup.section_start = SectionBreakType::Continuous;
+            second.add_child(blocks[2]);
+            second.add_child(blocks[3]);
+            tree.add_section(first);
+            tree.add_section(second);
+            tree.set_endnote_properties(None, props);
+
+            let mut notes = Vec::new();
+            for &block in [blocks[0], blocks[2]].iter() {
+                let (note_id, _) = tree.insert_endnote(Position::new(block, 4), None);
+                let para_id = tree.insert_paragraph_into_endnote(Paragraph::new(), note_id, None).unwrap();
+                tree.insert_run(Run::new("Note text"), para_id, None).unwrap();
+                notes.push(para_id);
+            }
+
+            let layout = Paginator::letter().layout(&tree).unwrap();
+            let order: Vec<NodeId> = page_blocks(&layout.pages[0]).map(|block| block.node_id).collect();
+            let marks: Vec<String> = notes
+                .iter()
+                .map(|&para_id| {
+                    let block = page_blocks(&layout.pages[0]).find(|block| block.node_id == para_id).unwrap();
+                    block.lines[0].inlines[0].list_marker.as_ref().unwrap().text.to_string()
+                })
+                .collect();
+            let separators = layout.pages[0].note_separators.len();
+            (blocks, notes, order, marks, separators)
+        };
+
+        let (blocks, notes, order, marks, separators) = layout_endnotes(EndnoteProperties::default());
+        assert_eq!(order, vec![blocks[0], blocks[1], blocks[2], blocks[3], notes[0], notes[1]]);
+        assert_eq!(marks, vec!["i", "ii"]);
+        assert_eq!(separators, 1);
+
+        let (blocks, notes, order, marks, separators) = layout_endnotes(EndnoteProperties {
+            position: EndnotePosition::EndOfSection,
+            restart: RestartNumbering::PerSection,
+            numbering: NumberingScheme::Arabic,
+            ..Default::default()
+        });
+        assert_eq!(order, vec![blocks[0], blocks[1], notes[0], blocks[2], blocks[3], notes[1]]);
+        assert_eq!(marks, vec!["1", "1"]);
+        assert_eq!(separators, 2);
+    }
+
+    #[test]
+    fn test_relayout_updates_page_count_fields() {
+        let mut tree = create_long_document();
+        add_page_number_section(&mut tree);
+        let mut paginator = Paginator::letter();
+        let total = paginator.layout(&tree).unwrap().page_count();
+
+        // Adding paragraphs adds pages; the NUMPAGES field on the
+        // untouched first page changes too
+        let root_id = tree.root_id();
+        let mut added = Vec::new();
+        for _ in 0..100 {
+            let para_id = tree.insert_paragraph(Paragraph::new(), root_id, None).unwrap();
+            tree.insert_run(Run::new("More text at the end of the document."), para_id, None).unwrap();
+            added.push(para_id);
+        }
+        let (layout, dirty) = paginator.relayout(&tree, &added).unwrap();
+        let new_total = layout.page_count();
+        assert!(new_total > total);
+        assert_eq!(field_texts(&layout.pages[0], AreaType::Footer)[1], new_total.to_string());
+        assert!(dirty.contains(0));
+    }
+
     // =============================================================================
     // Advanced Pagination Rule Tests
     // =============================================================================
@@ -0,0 +1,423 @@
+//! Document Statistics
+//!
+//! Counts shown in the word count dialog: pages, words, characters,
+//! paragraphs, and lines, plus Flesch readability scores. Text counts come
+//! from the document; pages and lines come from the layout. Counts cover
+//! body text, including table cells, but not headers, footers, notes, or
+//! text boxes.
+
+use crate::{AreaType, LayoutTree};
+use doc_model::{DocumentTree, Node, NodeId, NodeType, Position, Selection};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Range;
+
+/// Readability scores for English text
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Readability {
+    /// Flesch Reading Ease, 0 (hard) to 100 (easy)
+    pub flesch_reading_ease: f32,
+    /// Flesch-Kincaid Grade Level (US school grade)
+    pub flesch_kincaid_grade: f32,
+    /// Average words per sentence
+    pub words_per_sentence: f32,
+    /// Average syllables per word
+    pub syllables_per_word: f32,
+}
+
+impl Readability {
+    /// Compute the scores from raw counts; None if there are no words
+    pub fn from_counts(words: usize, sentences: usize, syllables: usize) -> Option<Self> {
+        if words == 0 {
+            return None;
+        }
+        let words_per_sentence = words as f32 / sentences.max(1) as f32;
+        let syllables_per_word = syllables as f32 / words as f32;
+        Some(Self {
+            flesch_reading_ease: (206.835 - 1.015 * words_per_sentence - 84.6 * syllables_per_word)
+                .clamp(0.0, 100.0),
+            flesch_kincaid_grade: (0.39 * words_per_sentence + 11.8 * syllables_per_word - 15.59).max(0.0),
+            words_per_sentence,
+            syllables_per_word,
+        })
+    }
+}
+
+/// Counts for a document or selection
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentStatistics {
+    /// Pages the text is laid out on
+    pub pages: usize,
+    /// Words (each East Asian character counts as a word)
+    pub words: usize,
+    /// Characters, including spaces
+    pub characters: usize,
+    /// Characters, excluding spaces
+    pub characters_no_spaces: usize,
+    /// East Asian characters
+    pub east_asian_characters: usize,
+    /// Non-empty paragraphs
+    pub paragraphs: usize,
+    /// Laid-out lines of text
+    pub lines: usize,
+    /// Sentences
+    pub sentences: usize,
+    /// Syllables in words made of letters
+    pub syllables: usize,
+    /// Readability scores; None if there are no words
+    pub readability: Option<Readability>,
+}
+
+impl DocumentStatistics {
+    /// Statistics for the whole document
+    pub fn compute(tree: &DocumentTree, layout: &LayoutTree) -> Self {
+        Self::collect(tree, layout, None)
+    }
+
+    /// Statistics for a selection, or the whole document if it's collapsed
+    pub fn for_selection(tree: &DocumentTree, layout: &LayoutTree, selection: &Selection) -> Self {
+        if selection.is_collapsed() {
+            return Self::compute(tree, layout);
+        }
+        Self::collect(tree, layout, Some(selection))
+    }
+
+    fn collect(tree: &DocumentTree, layout: &LayoutTree, selection: Option<&Selection>) -> Self {
+        let paragraphs = body_paragraphs(tree);
+        let ranges = selected_ranges(tree, &paragraphs, selection);
+
+        let mut stats = Self::default();
+        for (&para_id, range) in paragraphs.iter().zip(&ranges) {
+            let Some(range) = range else { continue };
+            let text: String = paragraph_text(tree, para_id)
+                .chars()
+                .skip(range.start)
+                .take(range.end - range.start)
+                .collect();
+            stats.add_text(&text);
+        }
+
+        let selected: HashMap<NodeId, Range<usize>> = paragraphs
+            .iter()
+            .zip(ranges)
+            .filter_map(|(&id, range)| range.map(|range| (id, range)))
+            .collect();
+        let (lines, pages) = count_lines(tree, layout, &selected);
+        stats.lines = lines;
+        stats.pages = if selection.is_some() { pages } else { layout.page_count() };
+        stats.readability = Readability::from_counts(stats.words, stats.sentences, stats.syllables);
+        stats
+    }
+
+    /// Add the counts for the text of one paragraph
+    fn add_text(&mut self, text: &str) {
+        if text.trim().is_empty() {
+            return;
+        }
+        self.paragraphs += 1;
+        self.characters += text.chars().count();
+        self.characters_no_spaces += text.chars().filter(|c| !c.is_whitespace()).count();
+
+        for token in text.split_whitespace() {
+            let mut word = String::new();
+            for c in token.chars() {
+                if is_east_asian(c) {
+                    self.east_asian_characters += 1;
+                    self.words += 1;
+                    self.add_word(&word);
+                    word.clear();
+                } else {
+                    word.push(c);
+                }
+            }
+            self.add_word(&word);
+        }
+
+        let sentence_ends = text.split_whitespace().filter(|token| ends_sentence(token)).count();
+        let ends_with_terminator = text.split_whitespace().last().is_some_and(ends_sentence);
+        // A paragraph ends a sentence even without punctuation (headings)
+        self.sentences += sentence_ends + usize::from(!ends_with_terminator);
+    }
+
+    /// Count a word; punctuation-only tokens aren't words
+    fn add_word(&mut self, word: &str) {
+        if word.chars().any(char::is_alphanumeric) {
+            self.words += 1;
+            self.syllables += count_syllables(word);
+        }
+    }
+}
+
+/// Check if a word ends a sentence, allowing closing quotes and brackets
+fn ends_sentence(token: &str) -> bool {
+    token
+        .trim_end_matches(['"', '\'', ')', ']', '\u{201D}', '\u{2019}'])
+        .ends_with(['.', '!', '?', '\u{3002}'])
+}
+
+/// Estimate the syllables in an English word
+///
+/// Counts vowel groups, treating a final silent "e" as no syllable. Words
+/// with no letters count as one syllable.
+pub fn count_syllables(word: &str) -> usize {
+    let letters: Vec<char> = word
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if letters.is_empty() {
+        return 1;
+    }
+
+    let is_vowel = |c: char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+    let mut count = 0;
+    let mut previous_vowel = false;
+    for &c in &letters {
+        let vowel = is_vowel(c);
+        if vowel && !previous_vowel {
+            count += 1;
+        }
+        previous_vowel = vowel;
+    }
+
+    // Silent final "e", as in "make", but not "le" as in "table"
+    let n = letters.len();
+    if n > 2 && letters[n - 1] == 'e' && !is_vowel(letters[n - 2]) {
+        let consonant_le = letters[n - 2] == 'l' && !is_vowel(letters[n - 3]);
+        count -= usize::from(!consonant_le);
+    }
+    count.max(1)
+}
+
+/// Check if a character is an East Asian ideograph, kana, or hangul
+pub(crate) fn is_east_asian(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF     // Hiragana, Katakana
+        | 0x3400..=0x4DBF   // CJK Extension A
+        | 0x4E00..=0x9FFF   // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF   // Hangul Syllables
+        | 0xF900..=0xFAFF   // CJK Compatibility Ideographs
+        | 0x20000..=0x2FA1F // CJK Extensions B-F
+    )
+}
+
+/// Body paragraphs in reading order, including those in table cells
+fn body_paragraphs(tree: &DocumentTree) -> Vec<NodeId> {
+    fn visit(tree: &DocumentTree, id: NodeId, out: &mut Vec<NodeId>) {
+        match tree.node_type(id) {
+            Some(NodeType::Paragraph) => out.push(id),
+            Some(NodeType::Table) => {
+                if let Some(table) = tree.get_table(id) {
+                    for &row_id in table.children() {
+                        visit(tree, row_id, out);
+                    }
+                }
+            }
+            Some(NodeType::TableRow) => {
+                if let Some(row) = tree.get_table_row(id) {
+                    for &cell_id in row.children() {
+                        visit(tree, cell_id, out);
+                    }
+                }
+            }
+            Some(NodeType::TableCell) => {
+                if let Some(cell) = tree.get_table_cell(id) {
+                    for &child_id in cell.children() {
+                        visit(tree, child_id, out);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut out = Vec::new();
+    for &id in tree.document.children() {
+        visit(tree, id, &mut out);
+    }
+    out
+}
+
+/// Runs of a paragraph in order, including those inside hyperlinks
+fn paragraph_runs(tree: &DocumentTree, para_id: NodeId) -> Vec<NodeId> {
+    let Some(para) = tree.get_paragraph(para_id) else {
+        return Vec::new();
+    };
+    para.children()
+        .iter()
+        .flat_map(|&id| match tree.get_hyperlink(id) {
+            Some(hyperlink) => hyperlink.children().to_vec(),
+            None => vec![id],
+        })
+        .filter(|&id| tree.get_run(id).is_some())
+        .collect()
+}
+
+fn paragraph_text(tree: &DocumentTree, para_id: NodeId) -> String {
+    paragraph_runs(tree, para_id)
+        .into_iter()
+        .filter_map(|id| tree.get_run(id))
+        .map(|run| run.text.as_str())
+        .collect()
+}
+
+/// Resolve a position to a paragraph and character offset in it
+fn resolve_position(tree: &DocumentTree, position: &Position) -> Option<(NodeId, usize)> {
+    if tree.get_paragraph(position.node_id).is_some() {
+        return Some((position.node_id, position.offset));
+    }
+    let run = tree.get_run(position.node_id)?;
+    let mut para_id = run.parent()?;
+    if let Some(hyperlink) = tree.get_hyperlink(para_id) {
+        para_id = hyperlink.parent()?;
+    }
+    let run_start: usize = paragraph_runs(tree, para_id)
+        .into_iter()
+        .take_while(|&id| id != position.node_id)
+        .filter_map(|id| tree.get_run(id))
+        .map(|run| run.text.chars().count())
+        .sum();
+    Some((para_id, run_start + position.offset))
+}
+
+/// The selected character range of each paragraph (all of it when there's
+/// no selection)
+fn selected_ranges(
+    tree: &DocumentTree,
+    paragraphs: &[NodeId],
+    selection: Option<&Selection>,
+) -> Vec<Option<Range<usize>>> {
+    let whole = |id| 0..paragraph_text(tree, id).chars().count();
+    let Some(selection) = selection else {
+        return paragraphs.iter().map(|&id| Some(whole(id))).collect();
+    };
+
+    let index_of = |position: &Position| {
+        let (para_id, offset) = resolve_position(tree, position)?;
+        let index = paragraphs.iter().position(|&id| id == para_id)?;
+        Some((index, offset))
+    };
+    let (Some(anchor), Some(focus)) = (index_of(&selection.anchor), index_of(&selection.focus)) else {
+        return vec![None; paragraphs.len()];
+    };
+    let (start, end) = if anchor <= focus { (anchor, focus) } else { (focus, anchor) };
+
+    paragraphs
+        .iter()
+        .enumerate()
+        .map(|(index, &id)| {
+            if index < start.0 || index > end.0 {
+                return None;
+            }
+            let range = whole(id);
+            let from = if index == start.0 { start.1.min(range.end) } else { 0 };
+            let to = if index == end.0 { end.1.min(range.end) } else { range.end };
+            Some(from..to.max(from))
+        })
+        .collect()
+}
+
+/// Count laid-out lines overlapping the selected ranges, and the pages they
+/// are on
+fn count_lines(
+    tree: &DocumentTree,
+    layout: &LayoutTree,
+    selected: &HashMap<NodeId, Range<usize>>,
+) -> (usize, usize) {
+    // Start offset of each run within its paragraph
+    let mut run_starts: HashMap<NodeId, usize> = HashMap::new();
+    for &para_id in selected.keys() {
+        let mut start = 0;
+        for run_id in paragraph_runs(tree, para_id) {
+            run_starts.insert(run_id, start);
+            start += tree.get_run(run_id).map_or(0, |run| run.text.chars().count());
+        }
+    }
+
+    let mut lines = 0;
+    let mut pages = BTreeSet::new();
+    for page in &layout.pages {
+        let content = page.areas.iter().filter(|area| area.area_type == AreaType::Content);
+        for block in content.flat_map(|area| &area.columns).flat_map(|column| &column.blocks) {
+            let Some(range) = selected.get(&block.node_id) else {
+                continue;
+            };
+            for line in &block.lines {
+                let text = line.inlines.iter().filter(|inline| inline.is_text()).filter_map(|inline| {
+                    let run_start = run_starts.get(&inline.node_id)?;
+                    Some(run_start + inline.start_offset..run_start + inline.end_offset)
+                });
+                let (start, end) = text.fold((usize::MAX, 0), |(start, end), r| (start.min(r.start), end.max(r.end)));
+                // Empty lines only count when the whole paragraph is selected
+                let overlaps = if start > end {
+                    range.start == 0
+                } else {
+                    start < range.end && range.start < end
+                };
+                if overlaps {
+                    lines += 1;
+                    pages.insert(page.index);
+                }
+            }
+        }
+    }
+    (lines, pages.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Paginator;
+    use doc_model::{Paragraph, Run};
+
+    fn add_paragraph(tree: &mut DocumentTree, text: &str) -> NodeId {
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new(text), para_id, None).unwrap();
+        para_id
+    }
+
+    #[test]
+    fn test_syllables_and_readability() {
+        assert_eq!(count_syllables("cat"), 1);
+        assert_eq!(count_syllables("make"), 1);
+        assert_eq!(count_syllables("table"), 2);
+        assert_eq!(count_syllables("readability"), 5);
+
+        // "The cat sat on the mat." - 6 words, 1 sentence, 6 syllables
+        let score = Readability::from_counts(6, 1, 6).unwrap();
+        assert_eq!(score.flesch_reading_ease, 100.0);
+        assert_eq!(score.flesch_kincaid_grade, 0.0);
+        assert!(Readability::from_counts(0, 0, 0).is_none());
+    }
+
+    #[test]
+    fn test_document_and_selection_statistics() {
+        let mut tree = DocumentTree::new();
+        let first = add_paragraph(&mut tree, "The quick brown fox. It jumps!");
+        add_paragraph(&mut tree, "");
+        let last = add_paragraph(&mut tree, "Heading without stop \u{6F22}\u{5B57}");
+        let layout = Paginator::default().layout(&tree).unwrap();
+
+        let stats = DocumentStatistics::compute(&tree, &layout);
+        assert_eq!(stats.paragraphs, 2);
+        assert_eq!(stats.words, 11);
+        assert_eq!(stats.east_asian_characters, 2);
+        assert_eq!(stats.characters, 30 + 23);
+        assert_eq!(stats.characters_no_spaces, 25 + 20);
+        assert_eq!(stats.sentences, 3);
+        assert_eq!(stats.lines, 3);
+        assert_eq!(stats.pages, 1);
+        assert!(stats.readability.is_some());
+
+        let selection = Selection::new(Position::new(first, 4), Position::new(last, 7));
+        let stats = DocumentStatistics::for_selection(&tree, &layout, &selection);
+        assert_eq!(stats.words, 6);
+        assert_eq!(stats.characters, 26 + 7);
+        assert_eq!(stats.paragraphs, 2);
+        // The empty paragraph in between is a line too
+        assert_eq!(stats.lines, 3);
+        assert_eq!(stats.pages, 1);
+    }
+}
@@ -7,6 +7,7 @@
 //! - Cell positioning in the grid
 //! - Border and padding handling
 //! - Cell merging (horizontal and vertical spans)
+//! - Vertical cell text and vertical alignment
 //! - Header row repeat across pages
 //! - Row breaking control
 //! - Nested tables
@@ -88,6 +89,191 @@ pub struct RowSplitResult {
     pub split_height: f32,
 }
 
+/// The rows of a table that land on one page
+#[derive(Debug, Clone, Default)]
+pub struct TablePageSlice {
+    /// Rows on this page, positioned relative to the top of the slice
+    pub rows: Vec<RowLayout>,
+    /// Number of leading rows that are repeated header rows
+    pub repeated_header_count: usize,
+    /// Total height of the rows on this page
+    pub height: f32,
+}
+
+impl TablePageSlice {
+    /// Whether the slice holds nothing besides repeated header rows
+    fn has_body_rows(&self) -> bool {
+        self.rows.len() > self.repeated_header_count
+    }
+
+    /// Append a row below the rows already on the page
+    fn push_row(&mut self, mut row: RowLayout) {
+        row.bounds.y = self.height;
+        self.height += row.bounds.height;
+        self.rows.push(row);
+    }
+}
+
+impl TableLayout {
+    /// The leading header rows, which repeat at the top of every page the table spans
+    pub fn repeating_header_rows(&self) -> &[RowLayout] {
+        let count = self.rows.iter().take_while(|row| row.is_header).count();
+        &self.rows[..count]
+    }
+
+    /// Break the table into one slice per page
+    ///
+    /// Rows that may not break across pages move whole to the next page;
+    /// splittable rows are divided between lines of their cell content.
+    /// Continuation pages start with the repeating header rows when they
+    /// leave room for content. A row that does not fit even on an empty page
+    /// overflows it.
+    pub fn paginate(&self, first_page_height: f32, page_height: f32) -> Vec<TablePageSlice> {
+        let headers = self.repeating_header_rows();
+        let header_height: f32 = headers.iter().map(|row| row.bounds.height).sum();
+        let repeat_headers = !headers.is_empty() && header_height < page_height;
+
+        let mut slices = Vec::new();
+        let mut current = TablePageSlice::default();
+        let mut available = first_page_height;
+        let mut pending: std::collections::VecDeque<RowLayout> = self.rows.iter().cloned().collect();
+
+        while let Some(row) = pending.pop_front() {
+            let remaining = available - current.height;
+            if row.bounds.height <= remaining {
+                current.push_row(row);
+                continue;
+            }
+
+            if row.can_split {
+                if let Some((first, rest)) = split_row(&row, remaining) {
+                    current.push_row(first);
+                    pending.push_front(rest);
+                    slices.push(std::mem::take(&mut current));
+                    available = page_height;
+                    if repeat_headers && row.row_index >= headers.len() {
+                        for header in headers {
+                            current.push_row(header.clone());
+                        }
+                        current.repeated_header_count = headers.len();
+                    }
+                    continue;
+                }
+            }
+
+            // Nothing more fits on a fresh page, so let the row overflow it
+            if !current.has_body_rows() && available >= page_height {
+                current.push_row(row);
+                continue;
+            }
+
+            if !current.rows.is_empty() {
+                slices.push(std::mem::take(&mut current));
+            }
+            available = page_height;
+            if repeat_headers && row.row_index >= headers.len() {
+                for header in headers {
+                    current.push_row(header.clone());
+                }
+                current.repeated_header_count = headers.len();
+            }
+            pending.push_front(row);
+        }
+
+        if !current.rows.is_empty() {
+            slices.push(current);
+        }
+        slices
+    }
+}
+
+/// Split a row so that its first part is at most `height` tall
+///
+/// Cell content breaks between lines. Returns `None` when no line fits or
+/// nothing is left over.
+fn split_row(row: &RowLayout, height: f32) -> Option<(RowLayout, RowLayout)> {
+    let mut first = row.clone();
+    let mut rest = row.clone();
+    let mut placed_any = false;
+    let mut left_any = false;
+    let mut rest_height: f32 = 0.0;
+
+    for (index, cell) in row.cells.iter().enumerate() {
+        if cell.is_covered {
+            continue;
+        }
+        let top = cell.content_bounds.y;
+        let (fits, remainder) = split_cell_content(&cell.content, height, top);
+        placed_any |= fits.iter().any(|block| !block.lines.is_empty());
+        left_any |= remainder.iter().any(|block| !block.lines.is_empty());
+        if let Some(last) = remainder.last() {
+            rest_height = rest_height.max(last.bounds.y + last.bounds.height + top);
+        }
+        first.cells[index].content = fits;
+        rest.cells[index].content = remainder;
+    }
+
+    if !placed_any || !left_any {
+        return None;
+    }
+
+    first.bounds.height = height;
+    rest.bounds.height = rest_height;
+    for cell in &mut first.cells {
+        cell.bounds.height = height;
+    }
+    for cell in &mut rest.cells {
+        cell.bounds.height = rest_height;
+        cell.nested_tables.clear();
+    }
+    Some((first, rest))
+}
+
+/// Divide a cell's blocks into the lines ending above `limit` and the rest,
+/// restacking the remainder from `top`
+fn split_cell_content(content: &[BlockBox], limit: f32, top: f32) -> (Vec<BlockBox>, Vec<BlockBox>) {
+    let mut fits = Vec::new();
+    let mut remainder: Vec<BlockBox> = Vec::new();
+    let mut rest_y = top;
+
+    for block in content {
+        let split_at = if remainder.is_empty() {
+            block
+                .lines
+                .iter()
+                .take_while(|line| block.bounds.y + line.bounds.y + line.bounds.height <= limit)
+                .count()
+        } else {
+            0
+        };
+
+        if split_at == block.lines.len() && remainder.is_empty() {
+            fits.push(block.clone());
+            continue;
+        }
+
+        if split_at > 0 {
+            let mut head = block.clone();
+            head.lines.truncate(split_at);
+            head.bounds.height = head.lines.last().map(|line| line.bounds.y + line.bounds.height).unwrap_or(0.0);
+            fits.push(head);
+        }
+
+        let mut tail = block.clone();
+        tail.lines.drain(..split_at);
+        let shift = tail.lines.first().map(|line| line.bounds.y).unwrap_or(0.0);
+        for line in &mut tail.lines {
+            line.bounds.y -= shift;
+        }
+        tail.bounds.y = rest_y;
+        tail.bounds.height = (block.bounds.height - shift).max(0.0);
+        rest_y += tail.bounds.height;
+        remainder.push(tail);
+    }
+
+    (fits, remainder)
+}
+
 /// A laid-out cell
 #[derive(Debug, Clone)]
 pub struct CellLayout {
@@ -335,110 +521,137 @@ impl TableLayoutEngine {
     }
 
     /// Calculate column widths to fit content
+    ///
+    /// Each column gets its widest unwrapped content when the table fits;
+    /// otherwise the space above the columns' longest words is shared in
+    /// proportion to how much more each column would like.
     fn calculate_column_widths_auto_content(
         &self,
         tree: &DocumentTree,
         table: &Table,
         config: &TableLayoutConfig,
     ) -> Result<Vec<f32>> {
-        let col_count = table.column_count();
-        if col_count == 0 {
+        let ranges = self.measure_column_ranges(tree, table, config);
+        if ranges.is_empty() {
             return Ok(Vec::new());
         }
 
-        let mut widths = vec![config.min_cell_width; col_count];
-
-        // Measure content in each column
-        for &row_id in table.children() {
-            if let Some(row) = tree.get_table_row(row_id) {
-                let mut col_idx = 0;
-                for &cell_id in row.children() {
-                    if let Some(cell) = tree.get_table_cell(cell_id) {
-                        // Skip covered cells
-                        if cell.h_merge == HorizontalMerge::Continue {
-                            col_idx += 1;
-                            continue;
-                        }
-
-                        let span = cell.effective_grid_span() as usize;
-                        let cell_width = self.measure_cell_content_width(tree, cell, config);
-
-                        // Distribute width across spanned columns
-                        if span == 1 {
-                            widths[col_idx] = widths[col_idx].max(cell_width);
-                        } else {
-                            // For spanning cells, distribute evenly
-                            let per_col = cell_width / span as f32;
-                            for i in 0..span.min(col_count - col_idx) {
-                                widths[col_idx + i] = widths[col_idx + i].max(per_col);
-                            }
-                        }
-
-                        col_idx += span;
-                    }
-                }
-            }
-        }
-
-        // Ensure total doesn't exceed available width
-        let total: f32 = widths.iter().sum();
-        if total > config.available_width {
-            let scale = config.available_width / total;
-            for w in &mut widths {
-                *w = (*w * scale).max(config.min_cell_width);
-            }
-        }
+        let available = self.preferred_table_width(table, config);
+        let total_min: f32 = ranges.iter().map(|r| r.0).sum();
+        let total_max: f32 = ranges.iter().map(|r| r.1).sum();
+
+        let widths = if total_max <= available {
+            ranges.iter().map(|r| r.1).collect()
+        } else if total_min >= available {
+            // Even the longest words don't fit; shrink them to the available width
+            let scale = available / total_min;
+            ranges.iter().map(|r| (r.0 * scale).max(config.min_cell_width)).collect()
+        } else {
+            let share = (available - total_min) / (total_max - total_min);
+            ranges.iter().map(|r| r.0 + (r.1 - r.0) * share).collect()
+        };
 
         Ok(widths)
     }
 
     /// Calculate column widths to fit window/page width
+    ///
+    /// The table fills its preferred width (the whole available width by
+    /// default). Columns keep the proportions of their grid widths; auto
+    /// columns weigh in with their content width.
     fn calculate_column_widths_auto_window(
         &self,
         tree: &DocumentTree,
         table: &Table,
         config: &TableLayoutConfig,
     ) -> Result<Vec<f32>> {
-        let col_count = table.column_count();
-        if col_count == 0 {
+        let ranges = self.measure_column_ranges(tree, table, config);
+        if ranges.is_empty() {
             return Ok(Vec::new());
         }
 
-        // Start with equal distribution
-        let base_width = config.available_width / col_count as f32;
-        let mut widths = vec![base_width.max(config.min_cell_width); col_count];
+        let target = match table.properties.width {
+            Some(width) if width.width_type == WidthType::Percent => config.available_width * (width.value / 100.0),
+            _ => config.available_width,
+        };
 
-        // Adjust based on column definitions if present
-        let mut fixed_width = 0.0f32;
-        let mut flex_columns = Vec::new();
+        let weights: Vec<f32> = ranges
+            .iter()
+            .enumerate()
+            .map(|(i, range)| match table.grid.columns.get(i).map(|col| col.width) {
+                Some(width) if width.width_type == WidthType::Fixed => width.value,
+                Some(width) if width.width_type == WidthType::Percent => target * (width.value / 100.0),
+                _ => range.1,
+            })
+            .collect();
 
-        for (i, col) in table.grid.columns.iter().enumerate() {
-            match col.width.width_type {
-                WidthType::Fixed => {
-                    widths[i] = col.width.value.max(config.min_cell_width);
-                    fixed_width += widths[i];
-                }
-                WidthType::Percent => {
-                    let width = config.available_width * (col.width.value / 100.0);
-                    widths[i] = width.max(config.min_cell_width);
-                    fixed_width += widths[i];
-                }
-                WidthType::Auto => {
-                    flex_columns.push(i);
-                }
-            }
+        let total_weight: f32 = weights.iter().sum();
+        let widths = if total_weight > 0.0 {
+            weights
+                .iter()
+                .map(|w| (target * w / total_weight).max(config.min_cell_width))
+                .collect()
+        } else {
+            vec![(target / ranges.len() as f32).max(config.min_cell_width); ranges.len()]
+        };
+
+        Ok(widths)
+    }
+
+    /// The width the table should take, from its preferred width
+    fn preferred_table_width(&self, table: &Table, config: &TableLayoutConfig) -> f32 {
+        match &table.properties.width {
+            Some(w) => match w.width_type {
+                WidthType::Fixed => w.value.min(config.available_width),
+                WidthType::Percent => config.available_width * (w.value / 100.0),
+                WidthType::Auto => config.available_width,
+            },
+            None => config.available_width,
         }
+    }
 
-        // Distribute remaining space to flex columns
-        if !flex_columns.is_empty() {
-            let remaining = (config.available_width - fixed_width).max(0.0);
-            let per_col = remaining / flex_columns.len() as f32;
-            for &col_idx in &flex_columns {
-                widths[col_idx] = per_col.max(config.min_cell_width);
+    /// Measure each column's content as (minimum, maximum) widths
+    ///
+    /// The minimum is the longest unbreakable word, the maximum the widest
+    /// paragraph laid out on one line. Content in cells spanning several
+    /// columns is shared evenly among them.
+    fn measure_column_ranges(
+        &self,
+        tree: &DocumentTree,
+        table: &Table,
+        config: &TableLayoutConfig,
+    ) -> Vec<(f32, f32)> {
+        let col_count = table.column_count();
+        let mut ranges = vec![(config.min_cell_width, config.min_cell_width); col_count];
+
+        for &row_id in table.children() {
+            if let Some(row) = tree.get_table_row(row_id) {
+                let mut col_idx = 0;
+                for &cell_id in row.children() {
+                    if col_idx >= col_count {
+                        break;
+                    }
+                    if let Some(cell) = tree.get_table_cell(cell_id) {
+                        // Skip covered cells
+                        if cell.h_merge == HorizontalMerge::Continue {
+                            col_idx += 1;
+                            continue;
+                        }
+
+                        let span = (cell.effective_grid_span() as usize).min(col_count - col_idx);
+                        let (min, max) = self.measure_cell_content_range(tree, cell, config);
+                        for range in &mut ranges[col_idx..col_idx + span] {
+                            range.0 = range.0.max(min / span as f32);
+                            range.1 = range.1.max(max / span as f32);
+                        }
+
+                        col_idx += span;
+                    }
+                }
             }
         }
 
-        Ok(widths)
+        ranges
     }
 
     /// Build merged regions from table structure
@@ -562,7 +775,7 @@ impl TableLayoutEngine {
     /// Calculate column widths based on the grid definition and table properties
     fn calculate_column_widths(
         &self,
-        tree: &DocumentTree,
+        _tree: &DocumentTree,
         table: &Table,
         config: &TableLayoutConfig,
     ) -> Result<Vec<f32>> {
@@ -608,88 +821,72 @@ impl TableLayoutEngine {
         let percent_width: f32 = widths.iter().sum::<f32>() - fixed_width;
         let remaining = (available - fixed_width - percent_width).max(0.0);
 
-        // Distribute remaining width to auto columns
+        // Distribute remaining width evenly to auto columns; fixed layout
+        // doesn't look at content
         if !auto_columns.is_empty() {
-            // First, try to measure content to get optimal widths
-            let auto_widths = self.measure_auto_columns(tree, table, &auto_columns, config);
-
-            let total_auto_desired: f32 = auto_widths.iter().sum();
-
-            if total_auto_desired <= remaining {
-                // Use desired widths
-                for (i, &col_idx) in auto_columns.iter().enumerate() {
-                    widths[col_idx] = auto_widths[i].max(config.min_cell_width);
-                }
-            } else {
-                // Proportionally distribute remaining space
-                let scale = remaining / total_auto_desired.max(1.0);
-                for (i, &col_idx) in auto_columns.iter().enumerate() {
-                    widths[col_idx] = (auto_widths[i] * scale).max(config.min_cell_width);
-                }
+            let per_col = remaining / auto_columns.len() as f32;
+            for &col_idx in &auto_columns {
+                widths[col_idx] = per_col.max(config.min_cell_width);
             }
         }
 
         Ok(widths)
     }
 
-    /// Measure optimal widths for auto columns based on content
-    fn measure_auto_columns(
-        &self,
-        tree: &DocumentTree,
-        table: &Table,
-        auto_columns: &[usize],
-        config: &TableLayoutConfig,
-    ) -> Vec<f32> {
-        let mut widths = vec![config.default_column_width; auto_columns.len()];
-
-        // For each row, measure content in auto columns
-        for &row_id in table.children() {
-            if let Some(row) = tree.get_table_row(row_id) {
-                for (auto_idx, &col_idx) in auto_columns.iter().enumerate() {
-                    if let Some(&cell_id) = row.children().get(col_idx) {
-                        if let Some(cell) = tree.get_table_cell(cell_id) {
-                            // Skip cells that span multiple columns for now
-                            if cell.grid_span <= 1 {
-                                let cell_width = self.measure_cell_content_width(tree, cell, config);
-                                widths[auto_idx] = widths[auto_idx].max(cell_width);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        widths
-    }
-
-    /// Measure the natural width of cell content
-    fn measure_cell_content_width(
+    /// Measure the (minimum, maximum) width of cell content
+    ///
+    /// The maximum is each paragraph on a single line, the minimum its
+    /// longest word; both include the cell padding.
+    fn measure_cell_content_range(
         &self,
         tree: &DocumentTree,
         cell: &TableCell,
         config: &TableLayoutConfig,
-    ) -> f32 {
+    ) -> (f32, f32) {
         let padding = cell.properties.padding
             .unwrap_or_default();
 
+        let mut min_width = 0.0_f32;
         let mut max_width = 0.0_f32;
 
-        // Measure each paragraph's natural width (single line)
         for &child_id in cell.children() {
             if let Some(para) = tree.get_paragraph(child_id) {
                 let mut para_width = 0.0_f32;
+                let mut word_width = 0.0_f32;
                 for &run_id in para.children() {
                     if let Some(run) = tree.get_run(run_id) {
                         // Estimate width based on character count and font size
                         let font_size = run.style.font_size.unwrap_or(config.font_size);
-                        para_width += run.text.chars().count() as f32 * font_size * 0.5;
+                        let char_width = font_size * 0.5;
+                        for ch in run.text.chars() {
+                            para_width += char_width;
+                            if ch.is_whitespace() {
+                                word_width = 0.0;
+                            } else {
+                                word_width += char_width;
+                                min_width = min_width.max(word_width);
+                            }
+                        }
                     }
                 }
                 max_width = max_width.max(para_width);
             }
         }
 
-        max_width + padding.left + padding.right
+        let padding_width = padding.left + padding.right;
+        (min_width + padding_width, max_width + padding_width)
+    }
+
+    /// Compute a table's column widths under its auto-fit mode
+    pub fn compute_column_widths(
+        &self,
+        tree: &DocumentTree,
+        table_id: NodeId,
+        config: &TableLayoutConfig,
+    ) -> Result<Vec<f32>> {
+        let table = tree.get_table(table_id)
+            .ok_or_else(|| crate::LayoutError::LayoutFailed("Table not found".into()))?;
+        self.calculate_column_widths_with_mode(tree, table, config)
     }
 
     /// Layout a single row (original method for backward compatibility)
@@ -780,6 +977,11 @@ impl TableLayoutEngine {
             if let Some(table_cell) = tree.get_table_cell(cell.cell_id) {
                 let padding = table_cell.properties.padding.unwrap_or_default();
 
+                if cell.text_direction.is_vertical() {
+                    align_vertical_text(cell, &padding, final_height);
+                    continue;
+                }
+
                 let content_y_offset = match v_align {
                     CellVerticalAlign::Top => padding.top,
                     CellVerticalAlign::Center => {
@@ -806,7 +1008,7 @@ impl TableLayoutEngine {
             bounds: Rect::new(0.0, y_offset, total_width, final_height),
             cells,
             is_header: row.properties.is_header,
-            can_split: row.properties.can_split && !row.properties.cant_split,
+            can_split: row.properties.allows_split(),
             keep_with_next: row.properties.keep_with_next,
             row_index,
         })
@@ -842,7 +1044,7 @@ impl TableLayoutEngine {
         config: &TableLayoutConfig,
     ) -> Result<RowSplitResult> {
         // Check if row can be split
-        if row.properties.cant_split || !row.properties.can_split {
+        if !row.properties.allows_split() {
             return Ok(RowSplitResult {
                 first_part: None,
                 second_part: None,
@@ -859,8 +1061,6 @@ impl TableLayoutEngine {
             });
         }
 
-        // For now, simple implementation: if row doesn't fit, don't split
-        // A more sophisticated implementation would split individual cell content
         if row_layout.bounds.height <= available_height {
             return Ok(RowSplitResult {
                 first_part: Some(row_layout.clone()),
@@ -869,14 +1069,19 @@ impl TableLayoutEngine {
             });
         }
 
-        // Row is taller than available space - check if we can split cell content
-        // This is a complex operation that requires splitting paragraphs within cells
-        // For this implementation, we'll allow the row to overflow if cant_split is false
-        Ok(RowSplitResult {
-            first_part: None,
-            second_part: Some(row_layout.clone()),
-            split_height: 0.0,
-        })
+        // Row is taller than available space - break its cell content between lines
+        match split_row(row_layout, available_height) {
+            Some((first, second)) => Ok(RowSplitResult {
+                split_height: first.bounds.height,
+                first_part: Some(first),
+                second_part: Some(second),
+            }),
+            None => Ok(RowSplitResult {
+                first_part: None,
+                second_part: Some(row_layout.clone()),
+                split_height: 0.0,
+            }),
+        }
     }
 
     /// Get header rows that should be repeated on a new page
@@ -944,17 +1149,33 @@ impl TableLayoutEngine {
         let content_y = padding.top;
         let content_width = (width - padding.left - padding.right).max(config.min_cell_width);
 
-        // Determine text direction for line breaking
+        // Determine text direction for line breaking (vertical text is broken
+        // as left-to-right lines and rotated when rendered)
         let direction = match text_direction {
-            CellTextDirection::Ltr => Direction::Ltr,
             CellTextDirection::Rtl => Direction::Rtl,
-            CellTextDirection::TbLr | CellTextDirection::TbRl => Direction::Ltr, // Vertical text not yet supported
+            _ => Direction::Ltr,
+        };
+
+        // Vertical text runs along the cell's height: lines break against a
+        // fixed row height (or stay unwrapped) and stack across the cell width
+        let vertical = text_direction.is_vertical();
+        let line_width = if vertical {
+            cell.parent()
+                .and_then(|row_id| tree.get_table_row(row_id))
+                .filter(|row| row.properties.height_rule != HeightRule::Auto)
+                .and_then(|row| row.properties.height)
+                .map(|height| (height - padding.top - padding.bottom).max(config.min_cell_width))
+                .unwrap_or(UNWRAPPED_VERTICAL_LENGTH)
+        } else {
+            content_width
         };
 
         // Layout cell content (paragraphs and nested tables)
         let mut content = Vec::new();
         let mut nested_tables = Vec::new();
         let mut content_y_offset = content_y;
+        let mut vertical_extent: f32 = 0.0;
+        let mut vertical_thickness = 0.0;
 
         for &child_id in cell.children() {
             // Check for nested table
@@ -973,17 +1194,17 @@ impl TableLayoutEngine {
             }
 
             // Regular paragraph content
-            if let Some(_para) = tree.get_paragraph(child_id) {
+            if let Some(para) = tree.get_paragraph(child_id) {
                 // Create line break config for this cell
                 let line_config = LineBreakConfig {
-                    available_width: content_width,
+                    available_width: line_width,
                     font_size: config.font_size,
                     line_spacing: config.line_spacing,
                     first_line_indent: 0.0,
                     left_indent: 0.0,
                     right_indent: 0.0,
                     direction,
-                    allow_hyphenation: false,
+                    allow_hyphenation: tree.document.settings.hyphenation.auto_hyphenate,
                     alignment: doc_model::Alignment::Left,
                     list_num_id: None,
                     list_level: None,
@@ -991,11 +1212,33 @@ impl TableLayoutEngine {
                     list_is_bullet: false,
                     list_marker_font: None,
                     list_hanging: 0.0,
+                    tab_stops: tree
+                        .styles
+                        .resolve_paragraph_props(para.paragraph_style_id.as_ref(), &para.direct_formatting)
+                        .tab_stops,
+                    default_tab_interval: tree.document.settings.default_tab_stop,
+                    field_context: None,
+                    line_grid_pitch: None,
+                    char_grid_pitch: None,
                 };
 
                 // Break paragraph into lines
                 let broken = self.line_breaker.break_paragraph(tree, child_id, &line_config)?;
 
+                if vertical {
+                    // Block bounds are the rotated box; the row pass positions
+                    // it across the cell by vertical alignment
+                    let extent = text_extent(&broken.lines).min(line_width);
+                    content.push(BlockBox {
+                        node_id: child_id,
+                        bounds: Rect::new(content_x + vertical_thickness, content_y, broken.total_height, extent),
+                        lines: broken.lines,
+                    });
+                    vertical_thickness += broken.total_height;
+                    vertical_extent = vertical_extent.max(extent);
+                    continue;
+                }
+
                 // Create block box for the paragraph
                 let block = BlockBox {
                     node_id: child_id,
@@ -1009,7 +1252,7 @@ impl TableLayoutEngine {
         }
 
         // Calculate total height
-        let content_height = content_y_offset - content_y;
+        let content_height = content_y_offset - content_y + vertical_extent;
         let total_height = content_height + padding.top + padding.bottom;
         let final_height = total_height.max(config.min_cell_height);
 
@@ -1031,6 +1274,46 @@ impl TableLayoutEngine {
     }
 }
 
+/// Line length used for vertical cell text when the row height is not fixed
+const UNWRAPPED_VERTICAL_LENGTH: f32 = 10_000.0;
+
+/// Length of the longest line's content
+fn text_extent(lines: &[LineBox]) -> f32 {
+    lines
+        .iter()
+        .flat_map(|line| line.inlines.iter())
+        .map(|inline| inline.bounds.x + inline.bounds.width)
+        .fold(0.0, f32::max)
+}
+
+/// Position a vertical-text cell's blocks once the row height is known.
+///
+/// The text's top edge faces right for 90° text and left for 270° text, so
+/// top/center/bottom alignment places the lines across the cell width;
+/// successive paragraphs stack away from that edge.
+fn align_vertical_text(cell: &mut CellLayout, padding: &CellPadding, row_height: f32) {
+    let available = cell.bounds.width - padding.left - padding.right;
+    let thickness: f32 = cell.content.iter().map(|b| b.bounds.width).sum();
+    let from_top = match cell.vertical_align {
+        CellVerticalAlign::Top => 0.0,
+        CellVerticalAlign::Center => (available - thickness) / 2.0,
+        CellVerticalAlign::Bottom => available - thickness,
+    };
+    let rotated_clockwise = cell.text_direction != CellTextDirection::BtLr;
+
+    let mut offset = from_top;
+    for block in &mut cell.content {
+        if rotated_clockwise {
+            block.bounds.x = cell.bounds.x + cell.bounds.width - padding.right - offset - block.bounds.width;
+            block.bounds.y = padding.top;
+        } else {
+            block.bounds.x = cell.bounds.x + padding.left + offset;
+            block.bounds.y = row_height - padding.bottom - block.bounds.height;
+        }
+        offset += block.bounds.width;
+    }
+}
+
 impl Default for TableLayoutEngine {
     fn default() -> Self {
         Self::new()
@@ -1043,10 +1326,23 @@ pub fn table_layout_to_blocks(
     x_offset: f32,
     y_offset: f32,
 ) -> Vec<BlockBox> {
+    rows_to_blocks(&layout.rows, x_offset, y_offset)
+}
+
+/// Convert one page's slice of a table to block boxes for rendering
+pub fn table_slice_to_blocks(
+    slice: &TablePageSlice,
+    x_offset: f32,
+    y_offset: f32,
+) -> Vec<BlockBox> {
+    rows_to_blocks(&slice.rows, x_offset, y_offset)
+}
+
+fn rows_to_blocks(rows: &[RowLayout], x_offset: f32, y_offset: f32) -> Vec<BlockBox> {
     let mut blocks = Vec::new();
 
     // Each cell's content becomes block boxes
-    for row in &layout.rows {
+    for row in rows {
         for cell in &row.cells {
             for block in &cell.content {
                 let mut adjusted_block = block.clone();
@@ -1289,6 +1585,65 @@ mod tests {
         assert!((total_width - 300.0).abs() < 1.0);
     }
 
+    #[test]
+    fn test_auto_fit_content_shares_space_by_content() {
+        let build = |texts: [&str; 2]| {
+            let mut tree = DocumentTree::new();
+            let props = TableProperties::new().with_auto_fit(TableAutoFitMode::AutoFitContent);
+            let table = Table::with_grid_and_properties(TableGrid::new(2), props);
+            let table_id = tree.insert_table(table, None).unwrap();
+            let row_id = tree.insert_table_row(TableRow::new(), table_id, None).unwrap();
+            for text in texts {
+                let cell_id = tree.insert_table_cell(TableCell::new(), row_id, None).unwrap();
+                let para_id = tree.insert_paragraph_into_cell(Paragraph::new(), cell_id, None).unwrap();
+                tree.insert_run(Run::new(text), para_id, None).unwrap();
+            }
+            (tree, table_id)
+        };
+        let engine = TableLayoutEngine::new();
+        let config = TableLayoutConfig {
+            available_width: 300.0,
+            ..Default::default()
+        };
+
+        // Short content keeps its natural widths
+        let (tree, table_id) = build(["Name", "Value here"]);
+        let widths = engine.compute_column_widths(&tree, table_id, &config).unwrap();
+        assert!(widths[0] < widths[1]);
+        assert!(widths.iter().sum::<f32>() < 300.0);
+
+        // Long content takes the space the short column doesn't need
+        let long = "word ".repeat(40);
+        let (tree, table_id) = build(["Hi", &long]);
+        let widths = engine.compute_column_widths(&tree, table_id, &config).unwrap();
+        assert!(widths[0] < 30.0);
+        assert!((widths.iter().sum::<f32>() - 300.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_auto_fit_window_keeps_grid_proportions() {
+        let mut tree = DocumentTree::new();
+        let props = TableProperties::new().with_auto_fit(TableAutoFitMode::AutoFitWindow);
+        let table = Table::with_grid_and_properties(TableGrid::with_fixed_columns(&[100.0, 300.0]), props);
+        let table_id = tree.insert_table(table, None).unwrap();
+
+        let engine = TableLayoutEngine::new();
+        let config = TableLayoutConfig {
+            available_width: 600.0,
+            ..Default::default()
+        };
+        let widths = engine.compute_column_widths(&tree, table_id, &config).unwrap();
+        assert_eq!(widths, vec![150.0, 450.0]);
+
+        // Fixed layout uses the grid as is
+        let config = TableLayoutConfig {
+            auto_fit_mode: Some(TableAutoFitMode::FixedWidth),
+            ..config
+        };
+        let widths = engine.compute_column_widths(&tree, table_id, &config).unwrap();
+        assert_eq!(widths, vec![100.0, 300.0]);
+    }
+
     #[test]
     fn test_nested_config() {
         let config = TableLayoutConfig::default();
@@ -1331,4 +1686,103 @@ mod tests {
 
         assert!(!layout.rows[0].can_split);
     }
+
+    fn insert_row_with_lines(tree: &mut DocumentTree, table_id: NodeId, props: RowProperties, lines: usize) {
+        let row_id = tree.insert_table_row(TableRow::with_properties(props), table_id, None).unwrap();
+        let cell_id = tree.insert_table_cell(TableCell::new(), row_id, None).unwrap();
+        for _ in 0..lines {
+            let para_id = tree.insert_paragraph_into_cell(Paragraph::new(), cell_id, None).unwrap();
+            tree.insert_run(Run::new("Line"), para_id, None).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_header_rows_repeat_on_each_page() {
+        let mut tree = DocumentTree::new();
+        let table = Table::with_grid(TableGrid::with_fixed_columns(&[200.0]));
+        let table_id = tree.insert_table(table, None).unwrap();
+        insert_row_with_lines(&mut tree, table_id, RowProperties::new().as_header(), 1);
+        for _ in 0..6 {
+            insert_row_with_lines(&mut tree, table_id, RowProperties::new(), 1);
+        }
+
+        let mut engine = TableLayoutEngine::new();
+        let layout = engine.layout_table(&tree, table_id, &TableLayoutConfig::default()).unwrap();
+        assert_eq!(layout.repeating_header_rows().len(), 1);
+
+        // Room for the header plus two and a half data rows
+        let row_height = layout.rows[1].bounds.height;
+        let page_height = layout.rows[0].bounds.height + row_height * 2.5;
+        let slices = layout.paginate(page_height, page_height);
+
+        assert_eq!(slices.len(), 3);
+        assert_eq!(slices[0].repeated_header_count, 0);
+        for slice in &slices[1..] {
+            assert_eq!(slice.repeated_header_count, 1);
+            assert_eq!(slice.rows[0].row_index, 0);
+            assert_eq!(slice.rows[0].bounds.y, 0.0);
+        }
+        let body_rows: usize = slices.iter().map(|s| s.rows.len() - s.repeated_header_count).sum();
+        assert_eq!(body_rows, 7);
+        assert!(slices.iter().all(|s| s.height <= page_height));
+    }
+
+    #[test]
+    fn test_cant_split_row_moves_to_next_page() {
+        let build = |props: RowProperties| {
+            let mut tree = DocumentTree::new();
+            let table = Table::with_grid(TableGrid::with_fixed_columns(&[200.0]));
+            let table_id = tree.insert_table(table, None).unwrap();
+            insert_row_with_lines(&mut tree, table_id, RowProperties::new(), 1);
+            insert_row_with_lines(&mut tree, table_id, props, 4);
+            let mut engine = TableLayoutEngine::new();
+            engine.layout_table(&tree, table_id, &TableLayoutConfig::default()).unwrap()
+        };
+
+        // The tall row only half fits below the first row
+        let layout = build(RowProperties::new());
+        let page_height = layout.rows[0].bounds.height + layout.rows[1].bounds.height / 2.0;
+
+        let slices = layout.paginate(page_height, page_height * 2.0);
+        assert_eq!(slices.len(), 2);
+        assert_eq!(slices[0].rows.len(), 2);
+        let split_lines = |row: &RowLayout| row.cells[0].content.iter().map(|b| b.lines.len()).sum::<usize>();
+        assert_eq!(split_lines(&slices[0].rows[1]) + split_lines(&slices[1].rows[0]), 4);
+
+        let layout = build(RowProperties::new().prevent_split());
+        let slices = layout.paginate(page_height, page_height * 2.0);
+        assert_eq!(slices.len(), 2);
+        assert_eq!(slices[0].rows.len(), 1);
+        assert_eq!(slices[1].rows[0].row_index, 1);
+        assert_eq!(slices[1].rows[0].bounds.height, layout.rows[1].bounds.height);
+    }
+
+    #[test]
+    fn test_vertical_cell_text_grows_row_and_aligns_across() {
+        let mut tree = DocumentTree::new();
+        let table = Table::with_grid(TableGrid::with_fixed_columns(&[200.0]));
+        let table_id = tree.insert_table(table, None).unwrap();
+        let row_id = tree.insert_table_row(TableRow::new(), table_id, None).unwrap();
+        let mut cell = TableCell::new();
+        cell.properties.text_direction = Some(CellTextDirection::BtLr);
+        cell.properties.vertical_align = Some(CellVerticalAlign::Center);
+        let cell_id = tree.insert_table_cell(cell, row_id, None).unwrap();
+        let para_id = tree.insert_paragraph_into_cell(Paragraph::new(), cell_id, None).unwrap();
+        tree.insert_run(Run::new("A rather long vertical heading"), para_id, None).unwrap();
+
+        let mut engine = TableLayoutEngine::new();
+        let layout = engine.layout_table(&tree, table_id, &TableLayoutConfig::default()).unwrap();
+        let cell = &layout.rows[0].cells[0];
+        let block = &cell.content[0];
+
+        // One unwrapped line whose length sets the row height
+        assert_eq!(block.lines.len(), 1);
+        assert!(block.bounds.height > block.bounds.width);
+        assert!(layout.rows[0].bounds.height >= block.bounds.height);
+
+        // Centered across the cell width
+        let left_gap = block.bounds.x - cell.bounds.x;
+        let right_gap = cell.bounds.x + cell.bounds.width - (block.bounds.x + block.bounds.width);
+        assert!((left_gap - right_gap).abs() < 0.01);
+    }
 }
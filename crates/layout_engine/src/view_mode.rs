@@ -9,6 +9,7 @@
 use crate::{BlockBox, LayoutTree, LineBox, Rect};
 use doc_model::{DocumentTree, Node, NodeId};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::ops::Range;
 
 // =============================================================================
@@ -213,6 +214,9 @@ pub struct ViewModeConfig {
     pub draft_options: DraftViewOptions,
     /// Outline view options
     pub outline_options: OutlineViewOptions,
+    /// Collapsed outline headings
+    #[serde(default)]
+    pub outline_collapse: OutlineCollapseState,
 }
 
 impl Default for ViewModeConfig {
@@ -221,6 +225,7 @@ impl Default for ViewModeConfig {
             mode: ViewMode::PrintLayout,
             draft_options: DraftViewOptions::default(),
             outline_options: OutlineViewOptions::default(),
+            outline_collapse: OutlineCollapseState::default(),
         }
     }
 }
@@ -548,6 +553,46 @@ impl OutlineData {
     }
 }
 
+/// Which outline headings are collapsed in a document
+///
+/// Kept by node ID, so headings stay collapsed when they are moved.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlineCollapseState {
+    /// Collapsed heading paragraphs
+    pub collapsed: HashSet<NodeId>,
+}
+
+impl OutlineCollapseState {
+    /// Collapse a heading
+    pub fn collapse(&mut self, node_id: NodeId) {
+        self.collapsed.insert(node_id);
+    }
+
+    /// Expand a heading
+    pub fn expand(&mut self, node_id: NodeId) {
+        self.collapsed.remove(&node_id);
+    }
+
+    /// Check if a heading is collapsed
+    pub fn is_collapsed(&self, node_id: NodeId) -> bool {
+        self.collapsed.contains(&node_id)
+    }
+
+    /// Set the expanded flag of every heading in an outline
+    pub fn apply(&self, outline: &mut OutlineData) {
+        fn apply_to(state: &OutlineCollapseState, heading: &mut OutlineHeading) {
+            heading.expanded = !state.is_collapsed(heading.node_id);
+            for child in &mut heading.children {
+                apply_to(state, child);
+            }
+        }
+        for heading in &mut outline.headings {
+            apply_to(self, heading);
+        }
+    }
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -622,4 +667,26 @@ mod tests {
 
         assert_eq!(heading.total_count(), 3);
     }
+
+    #[test]
+    fn test_outline_collapse_state_follows_node_ids() {
+        let chapter = NodeId::new();
+        let mut heading = OutlineHeading::new("a".to_string(), chapter, 1, "Chapter 1".to_string());
+        let section = NodeId::new();
+        heading.children.push(OutlineHeading::new("b".to_string(), section, 2, "Section 1.1".to_string()));
+        let mut outline = OutlineData {
+            headings: vec![heading],
+            total_count: 2,
+        };
+
+        let mut state = OutlineCollapseState::default();
+        state.collapse(section);
+        state.apply(&mut outline);
+        assert!(outline.headings[0].expanded);
+        assert!(!outline.headings[0].children[0].expanded);
+
+        state.expand(section);
+        state.apply(&mut outline);
+        assert!(outline.headings[0].children[0].expanded);
+    }
 }
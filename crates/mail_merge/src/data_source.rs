@@ -116,6 +116,15 @@ pub enum DataSourceType {
         /// Inline data records
         data: Vec<Record>,
     },
+    /// Data supplied by a plugin-contributed provider
+    Plugin {
+        /// Plugin that contributed the provider
+        plugin_id: String,
+        /// Provider identifier (e.g. "salesforce")
+        provider: String,
+        /// Provider-specific connection settings
+        config: serde_json::Value,
+    },
 }
 
 /// Column definition
@@ -322,6 +331,12 @@ impl Default for Value {
     }
 }
 
+impl From<&serde_json::Value> for Value {
+    fn from(json: &serde_json::Value) -> Self {
+        crate::json_parser::json_value_to_value(json)
+    }
+}
+
 impl From<String> for Value {
     fn from(s: String) -> Self {
         Value::Text(s)
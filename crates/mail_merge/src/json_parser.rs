@@ -327,7 +327,7 @@ fn navigate_to_path<'a>(json: &'a JsonValue, path: &str) -> Result<&'a JsonValue
 }
 
 /// Convert a JSON value to our Value type
-fn json_value_to_value(json: &JsonValue) -> Value {
+pub(crate) fn json_value_to_value(json: &JsonValue) -> Value {
     match json {
         JsonValue::Null => Value::Null,
         JsonValue::Bool(b) => Value::Boolean(*b),
@@ -9,6 +9,7 @@
 //! - JSON parsing with nested object support and configurable root paths
 //! - XLSX/XLS parsing with sheet selection and cell range support
 //! - Automatic data type detection
+//! - Pluggable providers for external data sources
 //! - Column mapping and field access
 //!
 //! # Example
@@ -40,6 +41,7 @@ mod csv_parser;
 mod data_source;
 mod error;
 mod json_parser;
+mod provider;
 mod xlsx_parser;
 pub mod merge_field;
 pub mod merge_engine;
@@ -49,6 +51,7 @@ pub use csv_parser::{CsvConfig, CsvParser, detect_delimiter, detect_has_header};
 pub use data_source::{ColumnDef, DataSource, DataSourceType, DataType, Record, Value};
 pub use error::{MailMergeError, Result};
 pub use json_parser::{JsonConfig, JsonParser, get_nested_value};
+pub use provider::{DataSourceProvider, ProviderDescriptor, ProviderRegistry, PROVIDER_PAGE_SIZE};
 pub use xlsx_parser::{XlsxConfig, XlsxParser, SheetSelector, CellRange, get_sheet_names, get_sheet_names_from_bytes};
 pub use merge_field::{MergeField, MergeFieldInstruction, ComparisonOperator, ConditionalField};
 pub use merge_engine::{MergeEngine, MergeOptions, MergeOutputType, RecordRange, MergeResult, MergedRecord, MergeStatus, MergeProgress, MergeError as MergeExecutionError};
@@ -0,0 +1,200 @@
+//! Data source providers
+//!
+//! Providers supply data from outside the file parsers, such as the
+//! "Salesforce" or "Airtable" sources contributed by plugins. A provider
+//! lists its columns and returns records a page at a time; the registry
+//! pages through them to build a `DataSource`.
+
+use std::sync::Arc;
+
+use serde_json::Value as JsonValue;
+
+use crate::data_source::{ColumnDef, DataSource, DataSourceType, Record};
+use crate::error::{MailMergeError, Result};
+
+/// Records requested from a provider per page
+pub const PROVIDER_PAGE_SIZE: usize = 500;
+
+/// Supplies columns and records for a data source type
+pub trait DataSourceProvider: Send + Sync {
+    /// List the columns available with the given settings
+    fn columns(&self, config: &JsonValue) -> Result<Vec<ColumnDef>>;
+
+    /// Fetch up to `limit` records starting at `offset`
+    ///
+    /// Returning fewer than `limit` records ends the data.
+    fn records(&self, config: &JsonValue, offset: usize, limit: usize) -> Result<Vec<Record>>;
+}
+
+/// Description of a registered provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderDescriptor {
+    /// Provider identifier
+    pub id: String,
+    /// Display name (e.g. "Salesforce")
+    pub name: String,
+    /// Plugin that contributed the provider
+    pub plugin_id: String,
+}
+
+impl ProviderDescriptor {
+    /// Create a new provider descriptor
+    pub fn new(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        plugin_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            plugin_id: plugin_id.into(),
+        }
+    }
+}
+
+/// Registry of data source providers
+#[derive(Clone, Default)]
+pub struct ProviderRegistry {
+    providers: Vec<(ProviderDescriptor, Arc<dyn DataSourceProvider>)>,
+}
+
+impl ProviderRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a provider
+    ///
+    /// Fails if a provider with the same ID is already registered.
+    pub fn register(
+        &mut self,
+        descriptor: ProviderDescriptor,
+        provider: Arc<dyn DataSourceProvider>,
+    ) -> Result<()> {
+        if let Some(existing) = self.get(&descriptor.id) {
+            return Err(MailMergeError::InvalidDataSource(format!(
+                "Provider '{}' is already registered by {}",
+                descriptor.id, existing.plugin_id
+            )));
+        }
+        self.providers.push((descriptor, provider));
+        Ok(())
+    }
+
+    /// Remove every provider contributed by a plugin
+    pub fn unregister_plugin(&mut self, plugin_id: &str) {
+        self.providers
+            .retain(|(descriptor, _)| descriptor.plugin_id != plugin_id);
+    }
+
+    /// Get all registered providers
+    pub fn providers(&self) -> impl Iterator<Item = &ProviderDescriptor> {
+        self.providers.iter().map(|(descriptor, _)| descriptor)
+    }
+
+    /// Get a provider by ID
+    pub fn get(&self, id: &str) -> Option<&ProviderDescriptor> {
+        self.providers().find(|descriptor| descriptor.id == id)
+    }
+
+    fn provider(&self, id: &str) -> Result<(&ProviderDescriptor, &dyn DataSourceProvider)> {
+        self.providers
+            .iter()
+            .find(|(descriptor, _)| descriptor.id == id)
+            .map(|(descriptor, provider)| (descriptor, provider.as_ref()))
+            .ok_or_else(|| {
+                MailMergeError::InvalidDataSource(format!("Unknown data source provider: {}", id))
+            })
+    }
+
+    /// List the columns a provider offers
+    pub fn columns(&self, provider_id: &str, config: &JsonValue) -> Result<Vec<ColumnDef>> {
+        self.provider(provider_id)?.1.columns(config)
+    }
+
+    /// Load a data source from a provider, fetching every record
+    pub fn load(
+        &self,
+        id: impl Into<String>,
+        provider_id: &str,
+        config: JsonValue,
+    ) -> Result<DataSource> {
+        let (descriptor, provider) = self.provider(provider_id)?;
+
+        let mut data_source = DataSource::new(
+            id,
+            DataSourceType::Plugin {
+                plugin_id: descriptor.plugin_id.clone(),
+                provider: descriptor.id.clone(),
+                config: config.clone(),
+            },
+        );
+        for column in provider.columns(&config)? {
+            data_source.add_column(column);
+        }
+
+        loop {
+            let page = provider.records(&config, data_source.record_count(), PROVIDER_PAGE_SIZE)?;
+            let done = page.len() < PROVIDER_PAGE_SIZE;
+            data_source.records.extend(page);
+            if done {
+                break;
+            }
+        }
+
+        Ok(data_source)
+    }
+}
+
+impl std::fmt::Debug for ProviderRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.providers()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::{DataType, Value};
+
+    /// Numbers 0..count, one record each
+    struct Counter {
+        count: usize,
+    }
+
+    impl DataSourceProvider for Counter {
+        fn columns(&self, _config: &JsonValue) -> Result<Vec<ColumnDef>> {
+            Ok(vec![ColumnDef::new("n", DataType::Number)])
+        }
+
+        fn records(&self, _config: &JsonValue, offset: usize, limit: usize) -> Result<Vec<Record>> {
+            Ok((offset..self.count.min(offset + limit))
+                .map(|n| Record::from([("n".to_string(), Value::Number(n as f64))]))
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_provider_registry_pages_through_records() {
+        let mut registry = ProviderRegistry::new();
+        let descriptor = ProviderDescriptor::new("counter", "Counter", "com.example.counter");
+        registry
+            .register(descriptor.clone(), Arc::new(Counter { count: 1200 }))
+            .unwrap();
+        assert!(registry.register(descriptor, Arc::new(Counter { count: 1 })).is_err());
+
+        let config = serde_json::json!({ "table": "numbers" });
+        let ds = registry.load("numbers", "counter", config).unwrap();
+        assert_eq!(ds.record_count(), 1200);
+        assert_eq!(ds.column_names(), vec!["n"]);
+        assert_eq!(ds.get_value(1199, "n").unwrap().as_number(), Some(1199.0));
+        assert!(matches!(
+            ds.source_type,
+            DataSourceType::Plugin { ref provider, .. } if provider == "counter"
+        ));
+
+        registry.unregister_plugin("com.example.counter");
+        assert!(registry.load("again", "counter", JsonValue::Null).is_err());
+    }
+}
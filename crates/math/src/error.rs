@@ -17,6 +17,18 @@ pub enum MathError {
     #[error("Linear notation parsing error: {0}")]
     LinearParse(String),
 
+    /// Error parsing LaTeX input
+    #[error("LaTeX parsing error: {0}")]
+    LatexParse(String),
+
+    /// Error parsing MathML
+    #[error("MathML parsing error: {0}")]
+    MathmlParse(String),
+
+    /// Error writing MathML
+    #[error("MathML writing error: {0}")]
+    MathmlWrite(String),
+
     /// Error during layout calculation
     #[error("Layout error: {0}")]
     Layout(String),
@@ -457,6 +457,12 @@ pub enum SymbolPaletteCategory {
     NaryOperators,
     /// Accents
     Accents,
+    /// Variant forms of Greek letters
+    GreekVariants,
+    /// Chemistry arrows and reaction notation
+    Chemistry,
+    /// SI units and unit prefixes
+    Units,
 }
 
 /// A symbol entry in a palette
@@ -511,6 +517,7 @@ pub fn symbols_for_category(category: SymbolPaletteCategory) -> Vec<SymbolEntry>
             SymbolEntry { char: symbols::SIGMA_UPPER, name: "Sigma", command: "Sigma", category },
             SymbolEntry { char: symbols::PHI_UPPER, name: "Phi", command: "Phi", category },
             SymbolEntry { char: symbols::PSI_UPPER, name: "Psi", command: "Psi", category },
+            SymbolEntry { char: '\u{03A5}', name: "Upsilon", command: "Upsilon", category },
             SymbolEntry { char: symbols::OMEGA_UPPER, name: "Omega", command: "Omega", category },
         ],
         SymbolPaletteCategory::Operators => vec![
@@ -616,6 +623,43 @@ pub fn symbols_for_category(category: SymbolPaletteCategory) -> Vec<SymbolEntry>
             SymbolEntry { char: '\u{0306}', name: "breve", command: "breve", category },
             SymbolEntry { char: '\u{030C}', name: "check", command: "check", category },
         ],
+        SymbolPaletteCategory::GreekVariants => vec![
+            SymbolEntry { char: '\u{03D1}', name: "theta variant", command: "vartheta", category },
+            SymbolEntry { char: '\u{03D5}', name: "phi variant", command: "varphi", category },
+            SymbolEntry { char: '\u{03D6}', name: "pi variant", command: "varpi", category },
+            SymbolEntry { char: '\u{03F1}', name: "rho variant", command: "varrho", category },
+            SymbolEntry { char: '\u{03C2}', name: "final sigma", command: "varsigma", category },
+            SymbolEntry { char: '\u{03F0}', name: "kappa variant", command: "varkappa", category },
+            SymbolEntry { char: '\u{03F5}', name: "lunate epsilon", command: "straightepsilon", category },
+            SymbolEntry { char: '\u{03D2}', name: "Upsilon with hook", command: "varUpsilon", category },
+            SymbolEntry { char: '\u{03DD}', name: "digamma", command: "digamma", category },
+        ],
+        SymbolPaletteCategory::Chemistry => vec![
+            SymbolEntry { char: '\u{27F6}', name: "reaction arrow", command: "longrightarrow", category },
+            SymbolEntry { char: '\u{21CC}', name: "equilibrium", command: "rightleftharpoons", category },
+            SymbolEntry { char: '\u{21C4}', name: "reversible reaction", command: "rightleftarrows", category },
+            SymbolEntry { char: '\u{2194}', name: "resonance", command: "leftrightarrow", category },
+            SymbolEntry { char: symbols::UP_ARROW, name: "gas evolved", command: "uparrow", category },
+            SymbolEntry { char: symbols::DOWN_ARROW, name: "precipitate", command: "downarrow", category },
+            SymbolEntry { char: '\u{00B7}', name: "hydrate dot", command: "cdotp", category },
+            SymbolEntry { char: '\u{2021}', name: "transition state", command: "ddagger", category },
+            SymbolEntry { char: '\u{2206}', name: "heat", command: "Delta", category },
+            SymbolEntry { char: '\u{29B5}', name: "standard state", command: "standardstate", category },
+        ],
+        SymbolPaletteCategory::Units => vec![
+            SymbolEntry { char: '\u{00B5}', name: "micro", command: "micro", category },
+            SymbolEntry { char: '\u{2126}', name: "ohm", command: "ohm", category },
+            SymbolEntry { char: '\u{212B}', name: "angstrom", command: "angstrom", category },
+            SymbolEntry { char: '\u{2103}', name: "degree Celsius", command: "celsius", category },
+            SymbolEntry { char: '\u{2109}', name: "degree Fahrenheit", command: "fahrenheit", category },
+            SymbolEntry { char: '\u{212A}', name: "kelvin", command: "kelvin", category },
+            SymbolEntry { char: symbols::DEGREE, name: "degree", command: "degree", category },
+            SymbolEntry { char: symbols::PRIME, name: "arcminute", command: "arcminute", category },
+            SymbolEntry { char: symbols::DOUBLE_PRIME, name: "arcsecond", command: "arcsecond", category },
+            SymbolEntry { char: '\u{2030}', name: "per mille", command: "permille", category },
+            SymbolEntry { char: '\u{2113}', name: "litre", command: "litre", category },
+            SymbolEntry { char: '\u{2127}', name: "mho", command: "mho", category },
+        ],
     }
 }
 
@@ -624,6 +668,7 @@ pub fn all_symbol_categories() -> Vec<SymbolPaletteCategory> {
     vec![
         SymbolPaletteCategory::GreekLowercase,
         SymbolPaletteCategory::GreekUppercase,
+        SymbolPaletteCategory::GreekVariants,
         SymbolPaletteCategory::Operators,
         SymbolPaletteCategory::Relations,
         SymbolPaletteCategory::Arrows,
@@ -631,6 +676,8 @@ pub fn all_symbol_categories() -> Vec<SymbolPaletteCategory> {
         SymbolPaletteCategory::Logic,
         SymbolPaletteCategory::NaryOperators,
         SymbolPaletteCategory::Accents,
+        SymbolPaletteCategory::Chemistry,
+        SymbolPaletteCategory::Units,
         SymbolPaletteCategory::Miscellaneous,
     ]
 }
@@ -653,6 +700,186 @@ pub fn search_symbols(query: &str) -> Vec<SymbolEntry> {
     results
 }
 
+// =============================================================================
+// Custom Symbol Palettes
+// =============================================================================
+
+/// A symbol in a custom palette
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomSymbol {
+    /// The Unicode character
+    pub char: char,
+    /// Display name
+    pub name: String,
+    /// LaTeX-style command (without backslash)
+    pub command: String,
+}
+
+/// A symbol palette registered by a plugin or user configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomSymbolPalette {
+    /// Unique identifier
+    pub id: String,
+    /// Display name shown in the gallery
+    pub name: String,
+    /// Symbols in display order
+    pub symbols: Vec<CustomSymbol>,
+}
+
+impl CustomSymbolPalette {
+    /// Create an empty palette
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            symbols: Vec::new(),
+        }
+    }
+
+    /// Add a symbol to the palette
+    pub fn with_symbol(
+        mut self,
+        char: char,
+        name: impl Into<String>,
+        command: impl Into<String>,
+    ) -> Self {
+        self.symbols.push(CustomSymbol {
+            char,
+            name: name.into(),
+            command: command.into(),
+        });
+        self
+    }
+}
+
+/// Identifies a palette in the symbol gallery
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PaletteId {
+    /// A built-in palette
+    Builtin(SymbolPaletteCategory),
+    /// A registered custom palette, by id
+    Custom(String),
+}
+
+/// A symbol as shown in the gallery, from either a built-in or custom palette
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GallerySymbol {
+    /// The Unicode character
+    pub char: char,
+    /// Display name
+    pub name: String,
+    /// LaTeX-style command (without backslash)
+    pub command: String,
+    /// Palette the symbol belongs to
+    pub palette: PaletteId,
+}
+
+impl From<SymbolEntry> for GallerySymbol {
+    fn from(entry: SymbolEntry) -> Self {
+        Self {
+            char: entry.char,
+            name: entry.name.to_string(),
+            command: entry.command.to_string(),
+            palette: PaletteId::Builtin(entry.category),
+        }
+    }
+}
+
+/// Registry of the palettes shown in the symbol gallery
+///
+/// Built-in palettes are always present; custom palettes are listed after them
+/// in registration order and are included in gallery searches.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SymbolPaletteRegistry {
+    custom: Vec<CustomSymbolPalette>,
+}
+
+impl SymbolPaletteRegistry {
+    /// Create a registry with only the built-in palettes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a custom palette, replacing any palette with the same id
+    ///
+    /// Returns the replaced palette, if any.
+    pub fn register(&mut self, palette: CustomSymbolPalette) -> Option<CustomSymbolPalette> {
+        match self.custom.iter_mut().find(|p| p.id == palette.id) {
+            Some(existing) => Some(std::mem::replace(existing, palette)),
+            None => {
+                self.custom.push(palette);
+                None
+            }
+        }
+    }
+
+    /// Remove a custom palette
+    pub fn unregister(&mut self, id: &str) -> Option<CustomSymbolPalette> {
+        let pos = self.custom.iter().position(|p| p.id == id)?;
+        Some(self.custom.remove(pos))
+    }
+
+    /// Get a custom palette by id
+    pub fn custom_palette(&self, id: &str) -> Option<&CustomSymbolPalette> {
+        self.custom.iter().find(|p| p.id == id)
+    }
+
+    /// Get the registered custom palettes
+    pub fn custom_palettes(&self) -> &[CustomSymbolPalette] {
+        &self.custom
+    }
+
+    /// Get all palettes in display order
+    pub fn palettes(&self) -> Vec<PaletteId> {
+        all_symbol_categories()
+            .into_iter()
+            .map(PaletteId::Builtin)
+            .chain(self.custom.iter().map(|p| PaletteId::Custom(p.id.clone())))
+            .collect()
+    }
+
+    /// Get the symbols of a palette
+    pub fn symbols(&self, palette: &PaletteId) -> Vec<GallerySymbol> {
+        match palette {
+            PaletteId::Builtin(category) => symbols_for_category(*category)
+                .into_iter()
+                .map(GallerySymbol::from)
+                .collect(),
+            PaletteId::Custom(id) => self
+                .custom_palette(id)
+                .map(|p| custom_gallery_symbols(p).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Search built-in and custom palettes by name or command
+    pub fn search(&self, query: &str) -> Vec<GallerySymbol> {
+        let query_lower = query.to_lowercase();
+        let mut results: Vec<GallerySymbol> = search_symbols(query)
+            .into_iter()
+            .map(GallerySymbol::from)
+            .collect();
+
+        for palette in &self.custom {
+            results.extend(custom_gallery_symbols(palette).filter(|symbol| {
+                symbol.name.to_lowercase().contains(&query_lower)
+                    || symbol.command.to_lowercase().contains(&query_lower)
+            }));
+        }
+
+        results
+    }
+}
+
+fn custom_gallery_symbols(palette: &CustomSymbolPalette) -> impl Iterator<Item = GallerySymbol> + '_ {
+    palette.symbols.iter().map(|symbol| GallerySymbol {
+        char: symbol.char,
+        name: symbol.name.clone(),
+        command: symbol.command.clone(),
+        palette: PaletteId::Custom(palette.id.clone()),
+    })
+}
+
 // =============================================================================
 // Recently Used Tracking
 // =============================================================================
@@ -753,6 +980,8 @@ pub enum StructureCategory {
     Limits,
     /// Matrices
     Matrices,
+    /// Chemical formulas and isotopes
+    Chemistry,
 }
 
 /// A structure in the gallery
@@ -833,6 +1062,12 @@ pub fn structures_for_category(category: StructureCategory) -> Vec<StructureEntr
             StructureEntry { id: "column_vector", name: "Column Vector", category, preview: "col" },
             StructureEntry { id: "row_vector", name: "Row Vector", category, preview: "row" },
         ],
+        StructureCategory::Chemistry => vec![
+            StructureEntry { id: "isotope", name: "Isotope", category, preview: "^A_Z X" },
+            StructureEntry { id: "chemical_formula", name: "Chemical Formula", category, preview: "H_2O" },
+            StructureEntry { id: "ion", name: "Ion", category, preview: "SO_4^2-" },
+            StructureEntry { id: "reaction_conditions", name: "Reaction Conditions", category, preview: "->[Δ]" },
+        ],
     }
 }
 
@@ -848,6 +1083,7 @@ pub fn all_structure_categories() -> Vec<StructureCategory> {
         StructureCategory::Accents,
         StructureCategory::Limits,
         StructureCategory::Matrices,
+        StructureCategory::Chemistry,
     ]
 }
 
@@ -914,6 +1150,46 @@ mod tests {
         assert!(results.iter().any(|s| s.char == symbols::ALPHA));
     }
 
+    #[test]
+    fn test_chemistry_and_unit_palettes() {
+        let chemistry = symbols_for_category(SymbolPaletteCategory::Chemistry);
+        assert!(chemistry.iter().any(|s| s.char == '\u{21CC}'));
+
+        let results = search_symbols("ohm");
+        assert!(results.iter().any(|s| s.category == SymbolPaletteCategory::Units));
+
+        let variants = search_symbols("vartheta");
+        assert_eq!(variants[0].category, SymbolPaletteCategory::GreekVariants);
+    }
+
+    #[test]
+    fn test_custom_palette_registry() {
+        let mut registry = SymbolPaletteRegistry::new();
+        let palette = CustomSymbolPalette::new("currency", "Currency")
+            .with_symbol('\u{20AC}', "euro", "euro")
+            .with_symbol('\u{00A3}', "pound", "pounds");
+        assert!(registry.register(palette).is_none());
+
+        let palettes = registry.palettes();
+        assert_eq!(palettes.last(), Some(&PaletteId::Custom("currency".to_string())));
+        assert_eq!(registry.symbols(&PaletteId::Custom("currency".to_string())).len(), 2);
+
+        let results = registry.search("euro");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].palette, PaletteId::Custom("currency".to_string()));
+
+        // Built-in symbols are still found
+        assert!(registry.search("alpha").iter().any(|s| s.char == symbols::ALPHA));
+
+        // Re-registering replaces the palette
+        let replaced = registry.register(CustomSymbolPalette::new("currency", "Money"));
+        assert_eq!(replaced.map(|p| p.symbols.len()), Some(2));
+        assert!(registry.search("euro").is_empty());
+
+        assert!(registry.unregister("currency").is_some());
+        assert_eq!(registry.palettes().len(), all_symbol_categories().len());
+    }
+
     #[test]
     fn test_recently_used() {
         let mut recent = RecentlyUsed::new();
@@ -0,0 +1,1784 @@
+//! LaTeX Support - Parse and write the math subset of LaTeX
+//!
+//! This module converts between LaTeX math markup and MathNode trees so users
+//! can paste LaTeX equations into the editor and copy equations back out.
+//!
+//! Supported input includes fractions, radicals, scripts, n-ary operators,
+//! `\left...\right` delimiters, accents, font commands, `\text{...}`, the Greek
+//! alphabet and common operators, and the environments `matrix`, `pmatrix`,
+//! `bmatrix`, `Bmatrix`, `vmatrix`, `Vmatrix`, `cases`, and `aligned`/`align`.
+
+use crate::error::{MathError, MathResult};
+use crate::model::*;
+
+// =============================================================================
+// Symbol Table
+// =============================================================================
+
+/// How a symbol command maps into the math tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SymbolKind {
+    /// Ordinary identifier (Greek letters, infinity, ...)
+    Ordinary,
+    /// Operator or relation
+    Operator,
+}
+
+/// LaTeX symbol commands and their Unicode characters
+///
+/// When several commands map to the same character, the first one is used by
+/// the writer.
+const SYMBOLS: &[(&str, char, SymbolKind)] = &[
+    // Greek lowercase
+    ("alpha", symbols::ALPHA, SymbolKind::Ordinary),
+    ("beta", symbols::BETA, SymbolKind::Ordinary),
+    ("gamma", symbols::GAMMA, SymbolKind::Ordinary),
+    ("delta", symbols::DELTA, SymbolKind::Ordinary),
+    ("epsilon", symbols::EPSILON, SymbolKind::Ordinary),
+    ("varepsilon", '\u{03B5}', SymbolKind::Ordinary),
+    ("zeta", symbols::ZETA, SymbolKind::Ordinary),
+    ("eta", symbols::ETA, SymbolKind::Ordinary),
+    ("theta", symbols::THETA, SymbolKind::Ordinary),
+    ("vartheta", '\u{03D1}', SymbolKind::Ordinary),
+    ("iota", symbols::IOTA, SymbolKind::Ordinary),
+    ("kappa", symbols::KAPPA, SymbolKind::Ordinary),
+    ("lambda", symbols::LAMBDA, SymbolKind::Ordinary),
+    ("mu", symbols::MU, SymbolKind::Ordinary),
+    ("nu", symbols::NU, SymbolKind::Ordinary),
+    ("xi", symbols::XI, SymbolKind::Ordinary),
+    ("omicron", symbols::OMICRON, SymbolKind::Ordinary),
+    ("pi", symbols::PI, SymbolKind::Ordinary),
+    ("varpi", '\u{03D6}', SymbolKind::Ordinary),
+    ("rho", symbols::RHO, SymbolKind::Ordinary),
+    ("varrho", '\u{03F1}', SymbolKind::Ordinary),
+    ("sigma", symbols::SIGMA, SymbolKind::Ordinary),
+    ("varsigma", '\u{03C2}', SymbolKind::Ordinary),
+    ("tau", symbols::TAU, SymbolKind::Ordinary),
+    ("upsilon", symbols::UPSILON, SymbolKind::Ordinary),
+    ("phi", symbols::PHI, SymbolKind::Ordinary),
+    ("varphi", '\u{03D5}', SymbolKind::Ordinary),
+    ("chi", symbols::CHI, SymbolKind::Ordinary),
+    ("psi", symbols::PSI, SymbolKind::Ordinary),
+    ("omega", symbols::OMEGA, SymbolKind::Ordinary),
+    // Greek uppercase
+    ("Gamma", symbols::GAMMA_UPPER, SymbolKind::Ordinary),
+    ("Delta", symbols::DELTA_UPPER, SymbolKind::Ordinary),
+    ("Theta", symbols::THETA_UPPER, SymbolKind::Ordinary),
+    ("Lambda", symbols::LAMBDA_UPPER, SymbolKind::Ordinary),
+    ("Xi", symbols::XI_UPPER, SymbolKind::Ordinary),
+    ("Pi", symbols::PI_UPPER, SymbolKind::Ordinary),
+    ("Sigma", symbols::SIGMA_UPPER, SymbolKind::Ordinary),
+    ("Upsilon", '\u{03A5}', SymbolKind::Ordinary),
+    ("Phi", symbols::PHI_UPPER, SymbolKind::Ordinary),
+    ("Psi", symbols::PSI_UPPER, SymbolKind::Ordinary),
+    ("Omega", symbols::OMEGA_UPPER, SymbolKind::Ordinary),
+    // Miscellaneous ordinary symbols
+    ("infty", symbols::INFINITY, SymbolKind::Ordinary),
+    ("partial", symbols::PARTIAL, SymbolKind::Ordinary),
+    ("nabla", symbols::NABLA, SymbolKind::Ordinary),
+    ("emptyset", symbols::EMPTY_SET, SymbolKind::Ordinary),
+    ("varnothing", symbols::EMPTY_SET, SymbolKind::Ordinary),
+    ("prime", symbols::PRIME, SymbolKind::Ordinary),
+    ("hbar", '\u{210F}', SymbolKind::Ordinary),
+    ("ell", '\u{2113}', SymbolKind::Ordinary),
+    ("Re", '\u{211C}', SymbolKind::Ordinary),
+    ("Im", '\u{2111}', SymbolKind::Ordinary),
+    ("aleph", '\u{2135}', SymbolKind::Ordinary),
+    ("angle", '\u{2220}', SymbolKind::Ordinary),
+    ("ldots", '\u{2026}', SymbolKind::Ordinary),
+    ("dots", '\u{2026}', SymbolKind::Ordinary),
+    ("cdots", '\u{22EF}', SymbolKind::Ordinary),
+    ("vdots", '\u{22EE}', SymbolKind::Ordinary),
+    ("ddots", '\u{22F1}', SymbolKind::Ordinary),
+    // Binary operators
+    ("pm", symbols::PLUS_MINUS, SymbolKind::Operator),
+    ("mp", symbols::MINUS_PLUS, SymbolKind::Operator),
+    ("times", symbols::TIMES, SymbolKind::Operator),
+    ("div", symbols::DIVIDE, SymbolKind::Operator),
+    ("cdot", symbols::DOT, SymbolKind::Operator),
+    ("ast", '\u{2217}', SymbolKind::Operator),
+    ("circ", '\u{2218}', SymbolKind::Operator),
+    ("bullet", '\u{2219}', SymbolKind::Operator),
+    ("oplus", '\u{2295}', SymbolKind::Operator),
+    ("otimes", '\u{2297}', SymbolKind::Operator),
+    ("cup", '\u{222A}', SymbolKind::Operator),
+    ("cap", '\u{2229}', SymbolKind::Operator),
+    ("setminus", '\u{2216}', SymbolKind::Operator),
+    ("wedge", symbols::LOGICAL_AND, SymbolKind::Operator),
+    ("land", symbols::LOGICAL_AND, SymbolKind::Operator),
+    ("vee", symbols::LOGICAL_OR, SymbolKind::Operator),
+    ("lor", symbols::LOGICAL_OR, SymbolKind::Operator),
+    ("neg", symbols::LOGICAL_NOT, SymbolKind::Operator),
+    ("lnot", symbols::LOGICAL_NOT, SymbolKind::Operator),
+    // Relations
+    ("leq", symbols::LESS_EQUAL, SymbolKind::Operator),
+    ("le", symbols::LESS_EQUAL, SymbolKind::Operator),
+    ("geq", symbols::GREATER_EQUAL, SymbolKind::Operator),
+    ("ge", symbols::GREATER_EQUAL, SymbolKind::Operator),
+    ("neq", symbols::NOT_EQUAL, SymbolKind::Operator),
+    ("ne", symbols::NOT_EQUAL, SymbolKind::Operator),
+    ("approx", symbols::APPROX, SymbolKind::Operator),
+    ("equiv", symbols::EQUIV, SymbolKind::Operator),
+    ("sim", '\u{223C}', SymbolKind::Operator),
+    ("simeq", '\u{2243}', SymbolKind::Operator),
+    ("cong", '\u{2245}', SymbolKind::Operator),
+    ("propto", symbols::PROPORTIONAL, SymbolKind::Operator),
+    ("ll", '\u{226A}', SymbolKind::Operator),
+    ("gg", '\u{226B}', SymbolKind::Operator),
+    ("perp", '\u{22A5}', SymbolKind::Operator),
+    ("parallel", '\u{2225}', SymbolKind::Operator),
+    ("mid", '\u{2223}', SymbolKind::Operator),
+    ("in", symbols::ELEMENT_OF, SymbolKind::Operator),
+    ("notin", symbols::NOT_ELEMENT_OF, SymbolKind::Operator),
+    ("ni", '\u{220B}', SymbolKind::Operator),
+    ("subset", symbols::SUBSET, SymbolKind::Operator),
+    ("supset", symbols::SUPERSET, SymbolKind::Operator),
+    ("subseteq", symbols::SUBSET_EQUAL, SymbolKind::Operator),
+    ("supseteq", symbols::SUPERSET_EQUAL, SymbolKind::Operator),
+    ("forall", symbols::FOR_ALL, SymbolKind::Operator),
+    ("exists", symbols::EXISTS, SymbolKind::Operator),
+    ("nexists", symbols::NOT_EXISTS, SymbolKind::Operator),
+    // Arrows
+    ("rightarrow", symbols::RIGHT_ARROW, SymbolKind::Operator),
+    ("to", symbols::RIGHT_ARROW, SymbolKind::Operator),
+    ("leftarrow", symbols::LEFT_ARROW, SymbolKind::Operator),
+    ("gets", symbols::LEFT_ARROW, SymbolKind::Operator),
+    ("uparrow", symbols::UP_ARROW, SymbolKind::Operator),
+    ("downarrow", symbols::DOWN_ARROW, SymbolKind::Operator),
+    ("leftrightarrow", symbols::LEFT_RIGHT_ARROW, SymbolKind::Operator),
+    ("Rightarrow", symbols::DOUBLE_RIGHT_ARROW, SymbolKind::Operator),
+    ("implies", symbols::DOUBLE_RIGHT_ARROW, SymbolKind::Operator),
+    ("Leftarrow", symbols::DOUBLE_LEFT_ARROW, SymbolKind::Operator),
+    ("Leftrightarrow", symbols::IFF, SymbolKind::Operator),
+    ("iff", symbols::IFF, SymbolKind::Operator),
+    ("mapsto", '\u{21A6}', SymbolKind::Operator),
+];
+
+/// N-ary operator commands, their characters, and default limit placement
+const NARY_OPERATORS: &[(&str, char, SubSupPlacement)] = &[
+    ("sum", symbols::SUM, SubSupPlacement::AboveBelow),
+    ("prod", symbols::PRODUCT, SubSupPlacement::AboveBelow),
+    ("coprod", symbols::COPRODUCT, SubSupPlacement::AboveBelow),
+    ("bigcup", symbols::UNION, SubSupPlacement::AboveBelow),
+    ("bigcap", symbols::INTERSECTION, SubSupPlacement::AboveBelow),
+    ("int", symbols::INTEGRAL, SubSupPlacement::Inline),
+    ("iint", symbols::DOUBLE_INTEGRAL, SubSupPlacement::Inline),
+    ("iiint", symbols::TRIPLE_INTEGRAL, SubSupPlacement::Inline),
+    ("oint", symbols::CONTOUR_INTEGRAL, SubSupPlacement::Inline),
+];
+
+/// Accent commands and their combining characters
+const ACCENTS: &[(&str, char)] = &[
+    ("hat", '\u{0302}'),
+    ("widehat", '\u{0302}'),
+    ("tilde", '\u{0303}'),
+    ("widetilde", '\u{0303}'),
+    ("bar", '\u{0304}'),
+    ("vec", '\u{20D7}'),
+    ("dot", '\u{0307}'),
+    ("ddot", '\u{0308}'),
+    ("check", '\u{030C}'),
+    ("breve", '\u{0306}'),
+    ("acute", '\u{0301}'),
+    ("grave", '\u{0300}'),
+];
+
+/// Named operators written as `\name`
+const FUNCTION_NAMES: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh",
+    "tanh", "coth", "log", "ln", "lg", "exp", "min", "max", "sup", "inf", "det", "dim", "ker",
+    "deg", "gcd", "arg", "Pr", "hom",
+];
+
+/// Operators whose scripts are written as limits below/above (e.g., `\lim_{x\to 0}`)
+const LIMIT_FUNCTIONS: &[&str] = &["lim", "liminf", "limsup", "max", "min", "sup", "inf"];
+
+/// Font commands and the math font style they select
+const FONT_COMMANDS: &[(&str, MathFontStyle)] = &[
+    ("mathrm", MathFontStyle::Normal),
+    ("mathit", MathFontStyle::Italic),
+    ("mathbf", MathFontStyle::Bold),
+    ("boldsymbol", MathFontStyle::BoldItalic),
+    ("mathcal", MathFontStyle::Script),
+    ("mathscr", MathFontStyle::Script),
+    ("mathfrak", MathFontStyle::Fraktur),
+    ("mathbb", MathFontStyle::DoubleStruck),
+    ("mathsf", MathFontStyle::SansSerif),
+    ("mathtt", MathFontStyle::Monospace),
+];
+
+/// Spacing commands and the characters that represent them
+const SPACES: &[(&str, char)] = &[
+    (",", '\u{2009}'),
+    (":", '\u{205F}'),
+    (";", '\u{2004}'),
+    ("quad", '\u{2003}'),
+    ("qquad", '\u{2001}'),
+    (" ", ' '),
+];
+
+/// Matrix environments and the delimiters wrapped around them
+const MATRIX_ENVIRONMENTS: &[(&str, Option<(char, char)>)] = &[
+    ("matrix", None),
+    ("smallmatrix", None),
+    ("array", None),
+    ("pmatrix", Some(('(', ')'))),
+    ("bmatrix", Some(('[', ']'))),
+    ("Bmatrix", Some(('{', '}'))),
+    ("vmatrix", Some(('|', '|'))),
+    ("Vmatrix", Some(('\u{2016}', '\u{2016}'))),
+];
+
+/// Environments that produce equation arrays
+const ALIGNED_ENVIRONMENTS: &[&str] = &[
+    "aligned", "align", "align*", "split", "gathered", "gather", "gather*", "eqnarray",
+    "eqnarray*",
+];
+
+fn lookup_symbol(name: &str) -> Option<(char, SymbolKind)> {
+    SYMBOLS
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, c, k)| (*c, *k))
+}
+
+// =============================================================================
+// Parser
+// =============================================================================
+
+/// Parser for LaTeX math markup
+pub struct LatexParser {
+    chars: Vec<char>,
+    position: usize,
+    /// Nesting depth of `[...]` optional arguments, where `]` ends a sequence
+    optional_depth: usize,
+}
+
+impl LatexParser {
+    /// Create a new parser for the given input
+    ///
+    /// Surrounding `$...$`, `$$...$$`, `\(...\)`, and `\[...\]` math delimiters
+    /// are stripped.
+    pub fn new(input: &str) -> Self {
+        Self {
+            chars: strip_math_delimiters(input).chars().collect(),
+            position: 0,
+            optional_depth: 0,
+        }
+    }
+
+    /// Parse the input into a MathNode tree
+    pub fn parse(&mut self) -> MathResult<MathNode> {
+        let rows = self.parse_rows()?;
+        if !self.is_at_end() {
+            return Err(self.error(format!("Unexpected '{}'", self.peek_char().unwrap_or(' '))));
+        }
+
+        // A bare `&` or `\\` at the top level is treated as an aligned block
+        if rows.len() > 1 || rows.iter().any(|r| r.len() > 1) {
+            return Ok(eq_array_from_rows(rows));
+        }
+
+        let nodes = rows.into_iter().flatten().flatten().collect::<Vec<_>>();
+        Ok(group(nodes))
+    }
+
+    // -------------------------------------------------------------------------
+    // Sequences
+    // -------------------------------------------------------------------------
+
+    /// Parse rows of `&`-separated cells until the end of the enclosing scope
+    fn parse_rows(&mut self) -> MathResult<Vec<Vec<Vec<MathNode>>>> {
+        let mut rows = Vec::new();
+        let mut cells = Vec::new();
+
+        loop {
+            cells.push(self.parse_sequence()?);
+            self.skip_whitespace();
+            if self.eat_char('&') {
+                continue;
+            }
+            if self.eat_str("\\\\") {
+                rows.push(std::mem::take(&mut cells));
+                continue;
+            }
+            break;
+        }
+        rows.push(cells);
+
+        // LaTeX allows a trailing `\\` before `\end`
+        if rows.len() > 1 && rows.last().is_some_and(|r| r.iter().all(|c| c.is_empty())) {
+            rows.pop();
+        }
+
+        Ok(rows)
+    }
+
+    /// Parse a sequence of atoms until a scope terminator
+    fn parse_sequence(&mut self) -> MathResult<Vec<MathNode>> {
+        let mut nodes = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            if self.is_sequence_end() {
+                break;
+            }
+            if let Some(node) = self.parse_atom()? {
+                merge_adjacent(&mut nodes, node);
+            }
+        }
+
+        Ok(nodes)
+    }
+
+    fn is_sequence_end(&self) -> bool {
+        match self.peek_char() {
+            None | Some('}') | Some('&') => true,
+            Some(']') => self.optional_depth > 0,
+            Some('\\') => {
+                self.starts_with("\\\\")
+                    || self.starts_with_command("end")
+                    || self.starts_with_command("right")
+                    || self.starts_with_command("middle")
+            }
+            _ => false,
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Atoms
+    // -------------------------------------------------------------------------
+
+    /// Parse a primary element followed by optional scripts
+    fn parse_atom(&mut self) -> MathResult<Option<MathNode>> {
+        let primary = match self.parse_primary()? {
+            Some(node) => node,
+            None => return Ok(None),
+        };
+        self.parse_scripts(primary).map(Some)
+    }
+
+    /// Attach `^` and `_` scripts (in either order) and primes to a base
+    fn parse_scripts(&mut self, base: MathNode) -> MathResult<MathNode> {
+        let mut base = base;
+        let mut sub = None;
+        let mut sup: Option<MathNode> = None;
+
+        loop {
+            self.skip_whitespace();
+            match self.peek_char() {
+                Some('^') if sup.is_none() => {
+                    self.position += 1;
+                    sup = Some(self.parse_script_arg()?);
+                }
+                Some('_') if sub.is_none() => {
+                    self.position += 1;
+                    sub = Some(self.parse_script_arg()?);
+                }
+                Some('\'') if sup.is_none() => {
+                    let mut primes = String::new();
+                    while self.eat_char('\'') {
+                        primes.push(symbols::PRIME);
+                    }
+                    sup = Some(MathNode::run(primes));
+                }
+                Some('^') | Some('_') => {
+                    return Err(self.error("Double script"));
+                }
+                _ => break,
+            }
+        }
+
+        // Limits on n-ary operators and limit functions attach to the operator
+        if let MathNode::Nary {
+            sub: nary_sub,
+            sup: nary_sup,
+            ..
+        } = &mut base
+        {
+            if sub.is_some() || sup.is_some() {
+                *nary_sub = sub.map(Box::new);
+                *nary_sup = sup.map(Box::new);
+            }
+            return Ok(base);
+        }
+        if let MathNode::Function { name, .. } = &base {
+            if LIMIT_FUNCTIONS.contains(&name.as_str()) && sup.is_none() {
+                if let Some(limit) = sub {
+                    return Ok(MathNode::Limit {
+                        func: Box::new(MathNode::Text(name.clone())),
+                        limit: Box::new(limit),
+                        position: LimitPosition::Lower,
+                    });
+                }
+            }
+        }
+
+        Ok(match (sub, sup) {
+            (Some(sub), Some(sup)) => MathNode::sub_superscript(base, sub, sup),
+            (Some(sub), None) => MathNode::subscript(base, sub),
+            (None, Some(sup)) => MathNode::superscript(base, sup),
+            (None, None) => base,
+        })
+    }
+
+    /// Parse a script argument: a braced group, a command, or a single character
+    fn parse_script_arg(&mut self) -> MathResult<MathNode> {
+        self.skip_whitespace();
+        match self.peek_char() {
+            Some('{') => self.parse_group(),
+            Some('\\') => self
+                .parse_primary()?
+                .ok_or_else(|| self.error("Missing script argument")),
+            Some(c) if c.is_ascii_digit() => {
+                self.position += 1;
+                Ok(MathNode::Number(c.to_string()))
+            }
+            Some(c) if c.is_alphabetic() => {
+                self.position += 1;
+                Ok(MathNode::run(c.to_string()))
+            }
+            Some(c) if !"}&]^_".contains(c) => {
+                self.position += 1;
+                Ok(MathNode::operator(c))
+            }
+            _ => Err(self.error("Missing script argument")),
+        }
+    }
+
+    /// Parse a primary element; returns `None` for elements that produce no node
+    fn parse_primary(&mut self) -> MathResult<Option<MathNode>> {
+        let c = match self.peek_char() {
+            Some(c) => c,
+            None => return Err(self.error("Unexpected end of input")),
+        };
+
+        let node = match c {
+            '{' => self.parse_group()?,
+            '\\' => return self.parse_command(),
+            '^' | '_' => {
+                // Script without a base (e.g., a leading ^{2})
+                MathNode::run("")
+            }
+            '~' => {
+                self.position += 1;
+                MathNode::Text(" ".to_string())
+            }
+            c if c.is_ascii_digit() || (c == '.' && self.peek_next_is_digit()) => {
+                self.parse_number()
+            }
+            c if c.is_alphabetic() => {
+                self.position += 1;
+                MathNode::run(c.to_string())
+            }
+            '-' => {
+                self.position += 1;
+                MathNode::operator(symbols::MINUS)
+            }
+            _ => {
+                self.position += 1;
+                MathNode::operator(c)
+            }
+        };
+
+        Ok(Some(node))
+    }
+
+    /// Parse a run of digits with an optional decimal point
+    fn parse_number(&mut self) -> MathNode {
+        let mut num = String::new();
+        let mut has_dot = false;
+        while let Some(c) = self.peek_char() {
+            if c.is_ascii_digit() {
+                num.push(c);
+            } else if c == '.' && !has_dot && self.peek_next_is_digit() {
+                has_dot = true;
+                num.push(c);
+            } else {
+                break;
+            }
+            self.position += 1;
+        }
+        MathNode::Number(num)
+    }
+
+    /// Parse `{...}` into a single node
+    fn parse_group(&mut self) -> MathResult<MathNode> {
+        self.expect_char('{')?;
+        let content = self.parse_sequence()?;
+        self.expect_char('}')?;
+        Ok(group(content))
+    }
+
+    /// Parse a mandatory argument: a braced group or a single atom
+    fn parse_arg(&mut self) -> MathResult<MathNode> {
+        self.skip_whitespace();
+        if self.peek_char() == Some('{') {
+            return self.parse_group();
+        }
+        if self.is_sequence_end() {
+            return Err(self.error("Missing argument"));
+        }
+        self.parse_primary()?
+            .ok_or_else(|| self.error("Missing argument"))
+    }
+
+    /// Parse an optional `[...]` argument
+    fn parse_optional_arg(&mut self) -> MathResult<Option<MathNode>> {
+        self.skip_whitespace();
+        if !self.eat_char('[') {
+            return Ok(None);
+        }
+        self.optional_depth += 1;
+        let content = self.parse_sequence();
+        self.optional_depth -= 1;
+        let content = content?;
+        self.expect_char(']')?;
+        Ok(Some(group(content)))
+    }
+
+    /// Read the raw text of a braced argument (for `\text`, environment names)
+    fn read_raw_group(&mut self) -> MathResult<String> {
+        self.skip_whitespace();
+        self.expect_char('{')?;
+        let mut depth = 1;
+        let mut text = String::new();
+        while let Some(c) = self.peek_char() {
+            self.position += 1;
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(text);
+                    }
+                }
+                '\\' => {
+                    // Keep escaped characters literally (e.g., \{ or \%)
+                    if let Some(next) = self.peek_char() {
+                        self.position += 1;
+                        text.push(next);
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+            text.push(c);
+        }
+        Err(self.error("Unterminated group"))
+    }
+
+    // -------------------------------------------------------------------------
+    // Commands
+    // -------------------------------------------------------------------------
+
+    fn parse_command(&mut self) -> MathResult<Option<MathNode>> {
+        let name = self.read_command_name()?;
+
+        if let Some((_, chr)) = SPACES.iter().find(|(n, _)| *n == name) {
+            return Ok(Some(MathNode::Text(chr.to_string())));
+        }
+        if let Some((chr, kind)) = lookup_symbol(&name) {
+            return Ok(Some(match kind {
+                SymbolKind::Ordinary => MathNode::run(chr.to_string()),
+                SymbolKind::Operator => MathNode::operator(chr),
+            }));
+        }
+        if let Some((_, op, placement)) = NARY_OPERATORS.iter().find(|(n, _, _)| *n == name) {
+            return self.parse_nary(*op, *placement).map(Some);
+        }
+        if let Some((_, accent)) = ACCENTS.iter().find(|(n, _)| *n == name) {
+            let base = self.parse_arg()?;
+            return Ok(Some(MathNode::Accent {
+                base: Box::new(base),
+                accent_char: *accent,
+            }));
+        }
+        if let Some((_, style)) = FONT_COMMANDS.iter().find(|(n, _)| *n == name) {
+            let arg = self.parse_arg()?;
+            return Ok(Some(apply_font_style(arg, *style)));
+        }
+        if FUNCTION_NAMES.contains(&name.as_str()) || LIMIT_FUNCTIONS.contains(&name.as_str()) {
+            return self.parse_function(name).map(Some);
+        }
+
+        let node = match name.as_str() {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let num = self.parse_arg()?;
+                let den = self.parse_arg()?;
+                MathNode::fraction(num, den)
+            }
+            "binom" | "dbinom" | "tbinom" => {
+                let top = self.parse_arg()?;
+                let bottom = self.parse_arg()?;
+                MathNode::parens(vec![MathNode::stacked(top, bottom)])
+            }
+            "sqrt" => {
+                let degree = self.parse_optional_arg()?;
+                let base = self.parse_arg()?;
+                MathNode::Radical {
+                    degree: degree.map(Box::new),
+                    base: Box::new(base),
+                }
+            }
+            "left" => self.parse_left_right()?,
+            "begin" => self.parse_environment()?,
+            "overline" => MathNode::overline(self.parse_arg()?),
+            "underline" => MathNode::underline(self.parse_arg()?),
+            "overbrace" => MathNode::GroupChar {
+                base: Box::new(self.parse_arg()?),
+                chr: '\u{23DE}',
+                position: BarPosition::Top,
+            },
+            "underbrace" => MathNode::GroupChar {
+                base: Box::new(self.parse_arg()?),
+                chr: '\u{23DF}',
+                position: BarPosition::Bottom,
+            },
+            "boxed" => MathNode::BorderBox {
+                base: Box::new(self.parse_arg()?),
+                hide_top: false,
+                hide_bottom: false,
+                hide_left: false,
+                hide_right: false,
+            },
+            "phantom" => MathNode::Phantom {
+                base: Box::new(self.parse_arg()?),
+                zero_width: false,
+                zero_height: false,
+            },
+            "text" | "textrm" | "mbox" | "textnormal" => MathNode::Text(self.read_raw_group()?),
+            "operatorname" => {
+                let name = self.read_raw_group()?;
+                return self.parse_function(name).map(Some);
+            }
+            "limits" | "nolimits" | "displaystyle" | "textstyle" | "scriptstyle" | "!" => {
+                return Ok(None);
+            }
+            "{" | "}" | "%" | "$" | "#" | "_" | "|" => {
+                let chr = if name == "|" { '\u{2016}' } else { name.chars().next().unwrap_or(' ') };
+                MathNode::operator(chr)
+            }
+            "lbrace" => MathNode::operator('{'),
+            "rbrace" => MathNode::operator('}'),
+            "langle" => MathNode::operator('\u{27E8}'),
+            "rangle" => MathNode::operator('\u{27E9}'),
+            "end" => return Err(self.error("Unexpected \\end")),
+            "middle" => return Err(self.error("\\middle outside \\left...\\right")),
+            "right" => return Err(self.error("Unexpected \\right")),
+            _ => MathNode::Text(format!("\\{}", name)),
+        };
+
+        Ok(Some(node))
+    }
+
+    /// Parse an n-ary operator; limits are attached by `parse_scripts`
+    fn parse_nary(&mut self, op: char, placement: SubSupPlacement) -> MathResult<MathNode> {
+        self.skip_whitespace();
+        let placement = if self.eat_command("nolimits") {
+            SubSupPlacement::Inline
+        } else if self.eat_command("limits") {
+            SubSupPlacement::AboveBelow
+        } else {
+            placement
+        };
+
+        // Scripts come before the operand in LaTeX; parse them onto a placeholder
+        let limits = self.parse_scripts(MathNode::Nary {
+            op,
+            sub_sup_placement: placement,
+            sub: None,
+            sup: None,
+            base: Box::new(MathNode::run("")),
+        })?;
+
+        self.skip_whitespace();
+        let base = if self.is_sequence_end() {
+            MathNode::run("")
+        } else {
+            self.parse_atom()?.unwrap_or_else(|| MathNode::run(""))
+        };
+
+        match limits {
+            MathNode::Nary {
+                op,
+                sub_sup_placement,
+                sub,
+                sup,
+                ..
+            } => Ok(MathNode::Nary {
+                op,
+                sub_sup_placement,
+                sub,
+                sup,
+                base: Box::new(base),
+            }),
+            other => Ok(other),
+        }
+    }
+
+    /// Parse a named function applied to the following atom
+    fn parse_function(&mut self, name: String) -> MathResult<MathNode> {
+        // Limit-style scripts directly follow the function name
+        self.skip_whitespace();
+        if LIMIT_FUNCTIONS.contains(&name.as_str()) && self.peek_char() == Some('_') {
+            let limited = self.parse_scripts(MathNode::Function {
+                name: name.clone(),
+                base: Box::new(MathNode::run("")),
+            })?;
+            if matches!(limited, MathNode::Limit { .. }) {
+                return Ok(limited);
+            }
+        }
+
+        self.skip_whitespace();
+        let base = if self.is_sequence_end() {
+            MathNode::run("")
+        } else {
+            self.parse_atom()?.unwrap_or_else(|| MathNode::run(""))
+        };
+        Ok(MathNode::Function {
+            name,
+            base: Box::new(base),
+        })
+    }
+
+    /// Parse `\left<delim> ... \right<delim>`
+    fn parse_left_right(&mut self) -> MathResult<MathNode> {
+        let open = self.read_delimiter()?;
+        let mut segments = vec![self.parse_sequence()?];
+        let mut separators = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            if self.eat_command("middle") {
+                separators.push(self.read_delimiter()?);
+                segments.push(self.parse_sequence()?);
+                continue;
+            }
+            if self.eat_command("right") {
+                break;
+            }
+            return Err(self.error("Missing \\right"));
+        }
+        let close = self.read_delimiter()?;
+
+        // Each `\middle` separator starts a new delimiter element
+        let content = if separators.is_empty() {
+            segments.remove(0)
+        } else {
+            segments.into_iter().map(group).collect()
+        };
+
+        Ok(MathNode::Delimiter {
+            open,
+            close,
+            separators,
+            content,
+            grow: true,
+        })
+    }
+
+    /// Read the delimiter following `\left` or `\right`
+    fn read_delimiter(&mut self) -> MathResult<char> {
+        self.skip_whitespace();
+        match self.peek_char() {
+            Some('.') => {
+                self.position += 1;
+                Ok(symbols::EMPTY_DELIMITER)
+            }
+            Some('\\') => {
+                let name = self.read_command_name()?;
+                match name.as_str() {
+                    "{" | "lbrace" => Ok('{'),
+                    "}" | "rbrace" => Ok('}'),
+                    "|" | "Vert" => Ok('\u{2016}'),
+                    "vert" => Ok('|'),
+                    "langle" => Ok('\u{27E8}'),
+                    "rangle" => Ok('\u{27E9}'),
+                    "lfloor" => Ok('\u{230A}'),
+                    "rfloor" => Ok('\u{230B}'),
+                    "lceil" => Ok('\u{2308}'),
+                    "rceil" => Ok('\u{2309}'),
+                    _ => Err(self.error(format!("Unknown delimiter \\{}", name))),
+                }
+            }
+            Some(c) if "()[]|/".contains(c) => {
+                self.position += 1;
+                Ok(c)
+            }
+            _ => Err(self.error("Missing delimiter")),
+        }
+    }
+
+    /// Parse `\begin{env} ... \end{env}`
+    fn parse_environment(&mut self) -> MathResult<MathNode> {
+        let env = self.read_raw_group()?;
+        if env == "array" {
+            // Column specification is not needed for the math tree
+            self.read_raw_group()?;
+        }
+
+        let rows = self.parse_rows()?;
+
+        self.skip_whitespace();
+        if !self.eat_command("end") {
+            return Err(self.error(format!("Missing \\end{{{}}}", env)));
+        }
+        let end_env = self.read_raw_group()?;
+        if end_env != env {
+            return Err(self.error(format!(
+                "\\begin{{{}}} ended by \\end{{{}}}",
+                env, end_env
+            )));
+        }
+
+        if let Some((_, delims)) = MATRIX_ENVIRONMENTS.iter().find(|(n, _)| *n == env) {
+            let matrix = matrix_from_rows(rows);
+            return Ok(match delims {
+                Some((open, close)) => MathNode::Delimiter {
+                    open: *open,
+                    close: *close,
+                    separators: vec![],
+                    content: vec![matrix],
+                    grow: true,
+                },
+                None => matrix,
+            });
+        }
+        if env == "cases" {
+            return Ok(MathNode::Delimiter {
+                open: '{',
+                close: symbols::EMPTY_DELIMITER,
+                separators: vec![],
+                content: vec![eq_array_from_rows(rows)],
+                grow: true,
+            });
+        }
+        if ALIGNED_ENVIRONMENTS.contains(&env.as_str()) {
+            return Ok(eq_array_from_rows(rows));
+        }
+
+        Err(self.error(format!("Unsupported environment '{}'", env)))
+    }
+
+    // -------------------------------------------------------------------------
+    // Character helpers
+    // -------------------------------------------------------------------------
+
+    /// Read a command name after a backslash: letters, or a single symbol
+    fn read_command_name(&mut self) -> MathResult<String> {
+        self.expect_char('\\')?;
+        let mut name = String::new();
+        while let Some(c) = self.peek_char() {
+            if c.is_ascii_alphabetic() {
+                name.push(c);
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+        if name.is_empty() {
+            match self.peek_char() {
+                Some(c) => {
+                    self.position += 1;
+                    name.push(c);
+                }
+                None => return Err(self.error("Trailing backslash")),
+            }
+        }
+        Ok(name)
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn peek_next_is_digit(&self) -> bool {
+        self.chars
+            .get(self.position + 1)
+            .is_some_and(|c| c.is_ascii_digit())
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.position + i) == Some(&c))
+    }
+
+    /// Check for `\name` not followed by another letter
+    fn starts_with_command(&self, name: &str) -> bool {
+        if self.peek_char() != Some('\\') {
+            return false;
+        }
+        let start = self.position + 1;
+        let end = start + name.chars().count();
+        let matches = name
+            .chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(start + i) == Some(&c));
+        matches
+            && !self
+                .chars
+                .get(end)
+                .is_some_and(|c| c.is_ascii_alphabetic())
+    }
+
+    fn eat_command(&mut self, name: &str) -> bool {
+        if self.starts_with_command(name) {
+            self.position += 1 + name.chars().count();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_char(&mut self, c: char) -> bool {
+        if self.peek_char() == Some(c) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        if self.starts_with(s) {
+            self.position += s.chars().count();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_char(&mut self, c: char) -> MathResult<()> {
+        self.skip_whitespace();
+        if self.eat_char(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected '{}'", c)))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek_char() {
+            if c.is_whitespace() {
+                self.position += 1;
+            } else if c == '%' {
+                // Comments run to the end of the line
+                while let Some(c) = self.peek_char() {
+                    self.position += 1;
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.position >= self.chars.len()
+    }
+
+    fn error(&self, message: impl Into<String>) -> MathError {
+        MathError::LatexParse(format!("{} at position {}", message.into(), self.position))
+    }
+}
+
+/// Strip surrounding math-mode delimiters from pasted LaTeX
+fn strip_math_delimiters(input: &str) -> &str {
+    let trimmed = input.trim();
+    for (open, close) in [("$$", "$$"), ("\\[", "\\]"), ("\\(", "\\)"), ("$", "$")] {
+        if trimmed.len() >= open.len() + close.len()
+            && trimmed.starts_with(open)
+            && trimmed.ends_with(close)
+        {
+            return &trimmed[open.len()..trimmed.len() - close.len()];
+        }
+    }
+    trimmed
+}
+
+/// Collapse a node list into a single node
+fn group(mut nodes: Vec<MathNode>) -> MathNode {
+    if nodes.len() == 1 {
+        nodes.remove(0)
+    } else {
+        MathNode::OMath(nodes)
+    }
+}
+
+/// Append a node, merging adjacent single letters into one run
+fn merge_adjacent(nodes: &mut Vec<MathNode>, node: MathNode) {
+    if let (
+        Some(MathNode::Run {
+            text: prev,
+            style: prev_style,
+        }),
+        MathNode::Run { text, style },
+    ) = (nodes.last_mut(), &node)
+    {
+        let letters = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphabetic());
+        if prev_style == style && letters(prev) && letters(text) {
+            prev.push_str(text);
+            return;
+        }
+    }
+    nodes.push(node);
+}
+
+/// Build a matrix from rows of cells
+fn matrix_from_rows(rows: Vec<Vec<Vec<MathNode>>>) -> MathNode {
+    MathNode::matrix(
+        rows.into_iter()
+            .map(|cells| cells.into_iter().map(group).collect())
+            .collect(),
+    )
+}
+
+/// Build an equation array, keeping `&` alignment points inside each row
+fn eq_array_from_rows(rows: Vec<Vec<Vec<MathNode>>>) -> MathNode {
+    MathNode::EqArray(
+        rows.into_iter()
+            .map(|cells| {
+                let mut row = Vec::new();
+                for (i, cell) in cells.into_iter().enumerate() {
+                    if i > 0 {
+                        row.push(MathNode::operator(symbols::ALIGNMENT_POINT));
+                    }
+                    row.extend(cell);
+                }
+                row
+            })
+            .collect(),
+    )
+}
+
+/// Apply a font style to every run inside a node
+fn apply_font_style(node: MathNode, font_style: MathFontStyle) -> MathNode {
+    match node {
+        MathNode::Run { text, style } => MathNode::Run {
+            text,
+            style: MathStyle { font_style, ..style },
+        },
+        MathNode::Number(n) => MathNode::Run {
+            text: n,
+            style: MathStyle {
+                font_style,
+                ..MathStyle::default()
+            },
+        },
+        MathNode::OMath(children) => MathNode::OMath(
+            children
+                .into_iter()
+                .map(|c| apply_font_style(c, font_style))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Parse LaTeX math markup into a MathNode
+pub fn parse_latex(input: &str) -> MathResult<MathNode> {
+    LatexParser::new(input).parse()
+}
+
+// =============================================================================
+// Writer
+// =============================================================================
+
+/// Writer that serializes MathNode trees as LaTeX
+#[derive(Debug, Default)]
+pub struct LatexWriter {
+    output: String,
+    /// Whether the last command name needs a space before a following letter
+    pending_space: bool,
+}
+
+impl LatexWriter {
+    /// Create a new writer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write a node and return the LaTeX string
+    pub fn write(mut self, node: &MathNode) -> MathResult<String> {
+        self.write_node(node)?;
+        Ok(self.output.trim().to_string())
+    }
+
+    fn write_node(&mut self, node: &MathNode) -> MathResult<()> {
+        match node {
+            MathNode::OMath(children) | MathNode::OMathPara(children) => {
+                for child in children {
+                    self.write_node(child)?;
+                }
+            }
+            MathNode::Fraction {
+                num,
+                den,
+                bar_visible,
+            } => {
+                if *bar_visible {
+                    self.push_command("frac");
+                    self.write_braced(num)?;
+                    self.write_braced(den)?;
+                } else {
+                    self.emit_char('{');
+                    self.write_node(num)?;
+                    self.push_command("atop");
+                    self.write_node(den)?;
+                    self.emit_char('}');
+                }
+            }
+            MathNode::Radical { degree, base } => {
+                self.push_command("sqrt");
+                if let Some(degree) = degree {
+                    self.emit_char('[');
+                    self.write_node(degree)?;
+                    self.emit_char(']');
+                }
+                self.write_braced(base)?;
+            }
+            MathNode::Subscript { base, sub } => {
+                self.write_script_base(base)?;
+                self.write_script('_', sub)?;
+            }
+            MathNode::Superscript { base, sup } => {
+                self.write_script_base(base)?;
+                self.write_script('^', sup)?;
+            }
+            MathNode::SubSuperscript { base, sub, sup } => {
+                self.write_script_base(base)?;
+                self.write_script('_', sub)?;
+                self.write_script('^', sup)?;
+            }
+            MathNode::Nary {
+                op,
+                sub_sup_placement,
+                sub,
+                sup,
+                base,
+            } => self.write_nary(*op, *sub_sup_placement, sub.as_deref(), sup.as_deref(), base)?,
+            MathNode::Delimiter {
+                open,
+                close,
+                separators,
+                content,
+                grow,
+            } => self.write_delimiter(*open, *close, separators, content, *grow)?,
+            MathNode::Matrix { rows, .. } => self.write_environment("matrix", rows)?,
+            MathNode::EqArray(rows) => self.write_eq_array("aligned", rows)?,
+            MathNode::Box(base) => self.write_node(base)?,
+            MathNode::Bar { base, position } => {
+                let cmd = match position {
+                    BarPosition::Top => "overline",
+                    BarPosition::Bottom => "underline",
+                };
+                self.push_command(cmd);
+                self.write_braced(base)?;
+            }
+            MathNode::Accent { base, accent_char } => {
+                let cmd = ACCENTS
+                    .iter()
+                    .find(|(_, c)| c == accent_char)
+                    .map(|(n, _)| *n)
+                    .unwrap_or("hat");
+                self.push_command(cmd);
+                self.write_braced(base)?;
+            }
+            MathNode::Limit {
+                func,
+                limit,
+                position,
+            } => {
+                match func.as_ref() {
+                    MathNode::Text(name) | MathNode::Run { text: name, .. }
+                        if LIMIT_FUNCTIONS.contains(&name.as_str()) =>
+                    {
+                        self.push_command(name)
+                    }
+                    other => self.write_node(other)?,
+                }
+                let marker = match position {
+                    LimitPosition::Lower => '_',
+                    LimitPosition::Upper => '^',
+                };
+                self.write_script(marker, limit)?;
+            }
+            MathNode::Function { name, base } => {
+                if FUNCTION_NAMES.contains(&name.as_str())
+                    || LIMIT_FUNCTIONS.contains(&name.as_str())
+                {
+                    self.push_command(name);
+                } else {
+                    self.emit("\\operatorname{");
+                    self.emit(name);
+                    self.emit_char('}');
+                }
+                if !base.is_empty() {
+                    self.write_node(base)?;
+                }
+            }
+            MathNode::GroupChar {
+                base,
+                chr: _,
+                position,
+            } => {
+                let cmd = match position {
+                    BarPosition::Top => "overbrace",
+                    BarPosition::Bottom => "underbrace",
+                };
+                self.push_command(cmd);
+                self.write_braced(base)?;
+            }
+            MathNode::BorderBox { base, .. } => {
+                self.push_command("boxed");
+                self.write_braced(base)?;
+            }
+            MathNode::Phantom { base, .. } => {
+                self.push_command("phantom");
+                self.write_braced(base)?;
+            }
+            MathNode::Run { text, style } => self.write_run(text, style),
+            MathNode::Operator { chr, .. } => self.write_char(*chr, true),
+            MathNode::Text(text) => self.write_text(text),
+            MathNode::Number(num) => self.emit(num),
+            MathNode::Unknown { .. } => {}
+        }
+        Ok(())
+    }
+
+    fn write_nary(
+        &mut self,
+        op: char,
+        placement: SubSupPlacement,
+        sub: Option<&MathNode>,
+        sup: Option<&MathNode>,
+        base: &MathNode,
+    ) -> MathResult<()> {
+        match NARY_OPERATORS.iter().find(|(_, c, _)| *c == op) {
+            Some((name, _, default_placement)) => {
+                self.push_command(name);
+                if placement != *default_placement {
+                    match placement {
+                        SubSupPlacement::AboveBelow => self.push_command("limits"),
+                        SubSupPlacement::Inline => self.push_command("nolimits"),
+                    }
+                }
+            }
+            None => self.write_char(op, true),
+        }
+        if let Some(sub) = sub {
+            self.write_script('_', sub)?;
+        }
+        if let Some(sup) = sup {
+            self.write_script('^', sup)?;
+        }
+        if !base.is_empty() {
+            self.emit_char(' ');
+            self.write_node(base)?;
+        }
+        Ok(())
+    }
+
+    fn write_delimiter(
+        &mut self,
+        open: char,
+        close: char,
+        separators: &[char],
+        content: &[MathNode],
+        grow: bool,
+    ) -> MathResult<()> {
+        // Environments wrapped in delimiters
+        if let [single] = content {
+            if let MathNode::Matrix { rows, .. } = single {
+                let env = MATRIX_ENVIRONMENTS
+                    .iter()
+                    .find(|(_, d)| *d == Some((open, close)))
+                    .map(|(n, _)| *n);
+                if let Some(env) = env {
+                    return self.write_environment(env, rows);
+                }
+            }
+            if let MathNode::EqArray(rows) = single {
+                if open == '{' && close == symbols::EMPTY_DELIMITER {
+                    return self.write_eq_array("cases", rows);
+                }
+            }
+            if let MathNode::Fraction {
+                num,
+                den,
+                bar_visible: false,
+            } = single
+            {
+                if open == '(' && close == ')' {
+                    self.push_command("binom");
+                    self.write_braced(num)?;
+                    return self.write_braced(den);
+                }
+            }
+        }
+
+        if grow {
+            self.push_command("left");
+            self.write_delimiter_char(open);
+        } else {
+            self.write_delimiter_char(open);
+        }
+
+        for (i, child) in content.iter().enumerate() {
+            if i > 0 && !separators.is_empty() {
+                let sep = separators
+                    .get(i - 1)
+                    .or(separators.last())
+                    .copied()
+                    .unwrap_or('|');
+                if grow {
+                    self.push_command("middle");
+                }
+                self.write_delimiter_char(sep);
+            }
+            self.write_node(child)?;
+        }
+
+        if grow {
+            self.push_command("right");
+        }
+        self.write_delimiter_char(close);
+        Ok(())
+    }
+
+    fn write_delimiter_char(&mut self, c: char) {
+        match c {
+            symbols::EMPTY_DELIMITER => self.emit_char('.'),
+            '{' => self.emit("\\{"),
+            '}' => self.emit("\\}"),
+            '\u{2016}' => self.emit("\\|"),
+            '\u{27E8}' => self.push_command("langle"),
+            '\u{27E9}' => self.push_command("rangle"),
+            '\u{230A}' => self.push_command("lfloor"),
+            '\u{230B}' => self.push_command("rfloor"),
+            '\u{2308}' => self.push_command("lceil"),
+            '\u{2309}' => self.push_command("rceil"),
+            other => self.emit_char(other),
+        }
+    }
+
+    fn write_environment(&mut self, env: &str, rows: &[Vec<MathNode>]) -> MathResult<()> {
+        self.emit(&format!("\\begin{{{}}}", env));
+        for (r, row) in rows.iter().enumerate() {
+            if r > 0 {
+                self.emit(" \\\\ ");
+            }
+            for (c, cell) in row.iter().enumerate() {
+                if c > 0 {
+                    self.emit(" & ");
+                }
+                self.write_node(cell)?;
+            }
+        }
+        self.emit(&format!("\\end{{{}}}", env));
+        Ok(())
+    }
+
+    fn write_eq_array(&mut self, env: &str, rows: &[Vec<MathNode>]) -> MathResult<()> {
+        self.emit(&format!("\\begin{{{}}}", env));
+        for (r, row) in rows.iter().enumerate() {
+            if r > 0 {
+                self.emit(" \\\\ ");
+            }
+            for node in row {
+                if let MathNode::Operator {
+                    chr: symbols::ALIGNMENT_POINT,
+                    ..
+                } = node
+                {
+                    self.emit(" & ");
+                } else {
+                    self.write_node(node)?;
+                }
+            }
+        }
+        self.emit(&format!("\\end{{{}}}", env));
+        Ok(())
+    }
+
+    fn write_run(&mut self, text: &str, style: &MathStyle) {
+        let font_cmd = match style.font_style {
+            MathFontStyle::Italic => None,
+            other => FONT_COMMANDS
+                .iter()
+                .find(|(_, s)| *s == other)
+                .map(|(n, _)| *n),
+        };
+        if let Some(cmd) = font_cmd {
+            self.push_command(cmd);
+            self.emit_char('{');
+        }
+        for c in text.chars() {
+            self.write_char(c, false);
+        }
+        if font_cmd.is_some() {
+            self.emit_char('}');
+        }
+    }
+
+    fn write_text(&mut self, text: &str) {
+        if let Some((name, _)) = SPACES
+            .iter()
+            .find(|(_, c)| text.chars().count() == 1 && text.starts_with(*c))
+        {
+            if *name == " " {
+                self.emit_char('~');
+            } else {
+                self.push_command(name);
+            }
+            return;
+        }
+        if text.starts_with('\\') {
+            // Unknown commands are preserved verbatim
+            self.emit(text);
+            return;
+        }
+        if FUNCTION_NAMES.contains(&text) || LIMIT_FUNCTIONS.contains(&text) {
+            self.push_command(text);
+            return;
+        }
+        self.emit("\\text{");
+        for c in text.chars() {
+            match c {
+                '{' | '}' | '%' | '$' | '#' | '_' | '&' => {
+                    self.emit_char('\\');
+                    self.emit_char(c);
+                }
+                _ => self.emit_char(c),
+            }
+        }
+        self.emit_char('}');
+    }
+
+    /// Write a single character, using a command for known symbols
+    ///
+    /// Operators (`spaced`) are surrounded by spaces when they are relations or
+    /// binary operators.
+    fn write_char(&mut self, c: char, spaced: bool) {
+        if c == symbols::MINUS {
+            self.emit_char('-');
+            return;
+        }
+        if c == symbols::PRIME {
+            self.emit_char('\'');
+            return;
+        }
+        if let Some((name, _, kind)) = SYMBOLS.iter().find(|(_, ch, _)| *ch == c) {
+            let spaced = spaced && *kind == SymbolKind::Operator;
+            if spaced {
+                self.emit(" ");
+            }
+            self.push_command(name);
+            if spaced {
+                self.emit(" ");
+            }
+            return;
+        }
+        match c {
+            '{' | '}' | '%' | '$' | '#' | '&' => {
+                self.emit_char('\\');
+                self.emit_char(c);
+            }
+            '=' | '<' | '>' | '+' if spaced => {
+                self.emit(&format!(" {} ", c));
+            }
+            _ => self.emit_char(c),
+        }
+    }
+
+    fn write_braced(&mut self, node: &MathNode) -> MathResult<()> {
+        self.emit_char('{');
+        self.write_node(node)?;
+        self.emit_char('}');
+        Ok(())
+    }
+
+    fn write_script_base(&mut self, base: &MathNode) -> MathResult<()> {
+        let simple = match base {
+            MathNode::Run { text, .. } => text.chars().count() <= 1,
+            MathNode::Number(_) | MathNode::Operator { .. } => true,
+            MathNode::Delimiter { .. } | MathNode::Radical { .. } => true,
+            _ => false,
+        };
+        if simple {
+            self.write_node(base)
+        } else {
+            self.write_braced(base)
+        }
+    }
+
+    fn write_script(&mut self, marker: char, script: &MathNode) -> MathResult<()> {
+        self.emit_char(marker);
+        self.write_braced(script)
+    }
+
+    /// Push a command name; a space is inserted later if a letter follows
+    fn push_command(&mut self, name: &str) {
+        self.emit(&format!("\\{}", name));
+        self.pending_space = name.chars().all(|c| c.is_ascii_alphabetic());
+    }
+
+    /// Append text, separating it from a preceding command name if needed and
+    /// collapsing doubled spaces
+    fn emit(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if std::mem::take(&mut self.pending_space)
+            && text.starts_with(|c: char| c.is_ascii_alphabetic())
+        {
+            self.output.push(' ');
+        }
+        match text.strip_prefix(' ') {
+            Some(rest) if self.output.is_empty() || self.output.ends_with(' ') => {
+                self.output.push_str(rest)
+            }
+            _ => self.output.push_str(text),
+        }
+    }
+
+    fn emit_char(&mut self, c: char) {
+        let mut buf = [0u8; 4];
+        self.emit(c.encode_utf8(&mut buf));
+    }
+}
+
+/// Convert a MathNode to LaTeX markup
+pub fn to_latex(node: &MathNode) -> MathResult<String> {
+    LatexWriter::new().write(node)
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fraction() {
+        let node = parse_latex("\\frac{a}{b}").unwrap();
+        assert_eq!(node, MathNode::fraction(MathNode::run("a"), MathNode::run("b")));
+    }
+
+    #[test]
+    fn test_parse_scripts_any_order() {
+        let a = parse_latex("x_i^2").unwrap();
+        let b = parse_latex("x^2_i").unwrap();
+        assert_eq!(a, b);
+        assert!(matches!(a, MathNode::SubSuperscript { .. }));
+    }
+
+    #[test]
+    fn test_parse_single_char_script() {
+        // x^23 is x^{2} followed by 3
+        let node = parse_latex("x^23").unwrap();
+        if let MathNode::OMath(children) = node {
+            assert_eq!(children.len(), 2);
+            assert_eq!(children[1], MathNode::number("3"));
+        } else {
+            panic!("Expected OMath");
+        }
+    }
+
+    #[test]
+    fn test_parse_sqrt_with_degree() {
+        let node = parse_latex("\\sqrt[3]{x}").unwrap();
+        assert_eq!(node, MathNode::nthroot(MathNode::number("3"), MathNode::run("x")));
+    }
+
+    #[test]
+    fn test_parse_sum_with_limits() {
+        let node = parse_latex("\\sum_{i=1}^{n} i").unwrap();
+        match node {
+            MathNode::Nary { op, sub, sup, base, .. } => {
+                assert_eq!(op, symbols::SUM);
+                assert!(sub.is_some());
+                assert_eq!(sup.as_deref(), Some(&MathNode::run("n")));
+                assert_eq!(*base, MathNode::run("i"));
+            }
+            other => panic!("Expected Nary, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_left_right() {
+        let node = parse_latex("\\left( \\frac{1}{2} \\right)").unwrap();
+        assert!(matches!(node, MathNode::Delimiter { open: '(', close: ')', grow: true, .. }));
+    }
+
+    #[test]
+    fn test_parse_greek_and_operators() {
+        let node = parse_latex("\\alpha \\leq \\beta").unwrap();
+        assert_eq!(
+            node,
+            MathNode::OMath(vec![
+                MathNode::run("\u{03B1}"),
+                MathNode::operator(symbols::LESS_EQUAL),
+                MathNode::run("\u{03B2}"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_pmatrix() {
+        let node = parse_latex("\\begin{pmatrix} 1 & 0 \\\\ 0 & 1 \\end{pmatrix}").unwrap();
+        match node {
+            MathNode::Delimiter { open: '(', close: ')', content, .. } => {
+                assert_eq!(
+                    content[0],
+                    MathNode::matrix(vec![
+                        vec![MathNode::number("1"), MathNode::number("0")],
+                        vec![MathNode::number("0"), MathNode::number("1")],
+                    ])
+                );
+            }
+            other => panic!("Expected delimited matrix, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_cases() {
+        let node =
+            parse_latex("f(x) = \\begin{cases} x & x \\geq 0 \\\\ -x & x < 0 \\end{cases}")
+                .unwrap();
+        let MathNode::OMath(children) = node else {
+            panic!("Expected OMath");
+        };
+        match children.last().unwrap() {
+            MathNode::Delimiter { open: '{', close, content, .. } => {
+                assert_eq!(*close, symbols::EMPTY_DELIMITER);
+                let MathNode::EqArray(rows) = &content[0] else {
+                    panic!("Expected EqArray");
+                };
+                assert_eq!(rows.len(), 2);
+                assert!(rows[0].contains(&MathNode::operator(symbols::ALIGNMENT_POINT)));
+            }
+            other => panic!("Expected cases delimiter, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_aligned_with_trailing_row_separator() {
+        let node = parse_latex("\\begin{aligned} a &= b \\\\ c &= d \\\\ \\end{aligned}").unwrap();
+        let MathNode::EqArray(rows) = node else {
+            panic!("Expected EqArray");
+        };
+        assert_eq!(rows.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_text_and_fonts() {
+        let node = parse_latex("\\text{if } \\mathbb{R}").unwrap();
+        let MathNode::OMath(children) = node else {
+            panic!("Expected OMath");
+        };
+        assert_eq!(children[0], MathNode::Text("if ".to_string()));
+        assert!(matches!(
+            &children[1],
+            MathNode::Run { style, .. } if style.font_style == MathFontStyle::DoubleStruck
+        ));
+    }
+
+    #[test]
+    fn test_parse_limit() {
+        let node = parse_latex("\\lim_{x \\to 0} f").unwrap();
+        let MathNode::OMath(children) = node else {
+            panic!("Expected OMath");
+        };
+        assert!(matches!(children[0], MathNode::Limit { .. }));
+    }
+
+    #[test]
+    fn test_parse_strips_math_delimiters() {
+        assert_eq!(parse_latex("$x$").unwrap(), MathNode::run("x"));
+        assert_eq!(parse_latex("\\[x\\]").unwrap(), MathNode::run("x"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_latex("\\frac{a}{b").is_err());
+        assert!(parse_latex("\\left( x").is_err());
+        assert!(parse_latex("\\begin{matrix} a \\end{pmatrix}").is_err());
+        assert!(parse_latex("\\begin{tabular} a \\end{tabular}").is_err());
+        assert!(parse_latex("x^^2").is_err());
+        assert!(matches!(
+            parse_latex("}"),
+            Err(MathError::LatexParse(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_basic() {
+        let node = MathNode::OMath(vec![
+            MathNode::superscript(MathNode::run("x"), MathNode::number("2")),
+            MathNode::operator('+'),
+            MathNode::fraction(MathNode::run("a"), MathNode::run("b")),
+        ]);
+        assert_eq!(to_latex(&node).unwrap(), "x^{2} + \\frac{a}{b}");
+    }
+
+    #[test]
+    fn test_write_symbols() {
+        let node = MathNode::OMath(vec![
+            MathNode::run("\u{03B1}"),
+            MathNode::operator(symbols::LESS_EQUAL),
+            MathNode::run("x"),
+        ]);
+        assert_eq!(to_latex(&node).unwrap(), "\\alpha \\leq x");
+    }
+
+    #[test]
+    fn test_write_matrix_environment() {
+        let node = MathNode::brackets(vec![MathNode::matrix(vec![
+            vec![MathNode::number("1"), MathNode::number("2")],
+            vec![MathNode::number("3"), MathNode::number("4")],
+        ])]);
+        assert_eq!(
+            to_latex(&node).unwrap(),
+            "\\begin{bmatrix}1 & 2 \\\\ 3 & 4\\end{bmatrix}"
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let inputs = [
+            "\\frac{-b \\pm \\sqrt{b^{2} - 4ac}}{2a}",
+            "\\sum_{i=1}^{n} i^{2}",
+            "\\int_{0}^{1} x dx",
+            "\\left( x + y \\right)",
+            "\\begin{pmatrix}a & b \\\\ c & d\\end{pmatrix}",
+            "f(x) = \\begin{cases}x & x \\geq 0 \\\\ -x & x < 0\\end{cases}",
+            "\\begin{aligned}a & = b \\\\ c & = d\\end{aligned}",
+            "\\hat{x} + \\overline{y}",
+            "\\mathbf{v} \\cdot \\mathbf{w}",
+            "\\sin x + \\lim_{n \\to \\infty} a_{n}",
+            "\\binom{n}{k}",
+            "\\text{speed} = \\frac{d}{t}",
+        ];
+        for input in inputs {
+            let node = parse_latex(input).unwrap();
+            let latex = to_latex(&node).unwrap();
+            let reparsed = parse_latex(&latex).unwrap();
+            assert_eq!(node, reparsed, "roundtrip mismatch for {input} -> {latex}");
+        }
+    }
+
+    #[test]
+    fn test_parse_then_layout() {
+        let node = parse_latex("\\begin{bmatrix} 1 & 2 \\\\ 3 & 4 \\end{bmatrix}").unwrap();
+        let layout = crate::LayoutEngine::new().layout(&node).unwrap();
+        assert!(layout.width() > 0.0);
+    }
+}
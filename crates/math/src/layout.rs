@@ -6,6 +6,7 @@
 use crate::error::MathResult;
 use crate::model::*;
 use serde::{Deserialize, Serialize};
+use text_engine::{FontManager, GlyphStretch, MathTable};
 
 // =============================================================================
 // Layout Types
@@ -111,6 +112,8 @@ pub struct MathFontMetrics {
     pub font_size: f32,
     /// x-height (height of lowercase x)
     pub x_height: f32,
+    /// Height of the math axis (fraction bars, centered operators)
+    pub axis_height: f32,
     /// Ascent above baseline
     pub ascent: f32,
     /// Descent below baseline
@@ -133,6 +136,8 @@ pub struct MathFontMetrics {
     pub superscript_shift_up: f32,
     /// Scale factor for sub/superscript
     pub script_scale: f32,
+    /// Scale factor for scripts of scripts
+    pub script_script_scale: f32,
     /// Delimiter extension size
     pub delimiter_factor: f32,
 }
@@ -151,6 +156,7 @@ impl MathFontMetrics {
         Self {
             font_size,
             x_height: em * 0.45,
+            axis_height: em * 0.225,
             ascent: em * 0.8,
             descent: em * 0.2,
             char_width: em * 0.5,
@@ -162,19 +168,69 @@ impl MathFontMetrics {
             subscript_shift_down: em * 0.25,
             superscript_shift_up: em * 0.4,
             script_scale: 0.7,
+            script_script_scale: 0.5,
             delimiter_factor: 0.9,
         }
     }
 
+    /// Create metrics for a given font size from a font's MATH table
+    ///
+    /// Values the table does not provide keep their synthetic defaults.
+    pub fn from_math_table(table: &MathTable, font_size: f32) -> Self {
+        let mut metrics = Self::for_size(font_size);
+        let Some(constants) = &table.constants else {
+            return metrics;
+        };
+        let scale = table.scale(font_size);
+        let units = |value: i16| value as f32 * scale;
+
+        metrics.axis_height = units(constants.axis_height);
+        metrics.fraction_rule_thickness = units(constants.fraction_rule_thickness);
+        metrics.fraction_num_gap = units(constants.fraction_numerator_gap_min);
+        metrics.fraction_den_gap = units(constants.fraction_denominator_gap_min);
+        metrics.radical_rule_thickness = units(constants.radical_rule_thickness);
+        metrics.radical_vertical_gap = units(constants.radical_vertical_gap);
+        metrics.subscript_shift_down = units(constants.subscript_shift_down);
+        metrics.superscript_shift_up = units(constants.superscript_shift_up);
+        if constants.script_percent_scale_down > 0 {
+            metrics.script_scale = constants.script_percent_scale_down as f32 / 100.0;
+        }
+        if constants.script_script_percent_scale_down > 0 {
+            metrics.script_script_scale = constants.script_script_percent_scale_down as f32 / 100.0;
+        }
+        metrics
+    }
+
+    /// Scale every length by `factor`, keeping the ratios
+    pub fn scaled(&self, factor: f32) -> Self {
+        Self {
+            font_size: self.font_size * factor,
+            x_height: self.x_height * factor,
+            axis_height: self.axis_height * factor,
+            ascent: self.ascent * factor,
+            descent: self.descent * factor,
+            char_width: self.char_width * factor,
+            fraction_rule_thickness: self.fraction_rule_thickness * factor,
+            fraction_num_gap: self.fraction_num_gap * factor,
+            fraction_den_gap: self.fraction_den_gap * factor,
+            radical_rule_thickness: self.radical_rule_thickness * factor,
+            radical_vertical_gap: self.radical_vertical_gap * factor,
+            subscript_shift_down: self.subscript_shift_down * factor,
+            superscript_shift_up: self.superscript_shift_up * factor,
+            script_scale: self.script_scale,
+            script_script_scale: self.script_script_scale,
+            delimiter_factor: self.delimiter_factor,
+        }
+    }
+
     /// Scale metrics for script (sub/superscript) size
     pub fn script_metrics(&self) -> Self {
-        Self::for_size(self.font_size * self.script_scale)
+        self.scaled(self.script_scale)
     }
 
     /// Scale metrics for scriptscript size (nested scripts)
     pub fn scriptscript_metrics(&self) -> Self {
-        let script = self.script_metrics();
-        Self::for_size(script.font_size * self.script_scale)
+        self.scaled(self.script_script_scale)
     }
 }
 
@@ -264,6 +320,21 @@ pub enum LayoutContent {
     Accent {
         char: char,
     },
+    /// Glyph stretched with a math font's size variants or glyph assembly
+    StretchedGlyph {
+        char: char,
+        stretch: GlyphStretch,
+        /// Whether the glyph grows vertically (delimiters) or horizontally
+        vertical: bool,
+        /// Font size of the glyphs in points
+        font_size: f32,
+        /// Points per font design unit
+        scale: f32,
+        /// Family of the math font, if known
+        font_family: Option<String>,
+        /// Face index of the math font within its file
+        font_index: u32,
+    },
     /// Empty space
     Space,
 }
@@ -273,8 +344,12 @@ pub enum LayoutContent {
 // =============================================================================
 
 /// Engine for computing math layout
+///
+/// Without a math font the engine uses synthetic metrics and draws stretched
+/// delimiters and radicals as paths.
 pub struct LayoutEngine {
     metrics: MathFontMetrics,
+    math_table: Option<MathTable>,
 }
 
 impl LayoutEngine {
@@ -282,12 +357,75 @@ impl LayoutEngine {
     pub fn new() -> Self {
         Self {
             metrics: MathFontMetrics::default(),
+            math_table: None,
         }
     }
 
     /// Create with specific metrics
     pub fn with_metrics(metrics: MathFontMetrics) -> Self {
-        Self { metrics }
+        Self {
+            metrics,
+            math_table: None,
+        }
+    }
+
+    /// Create from a math font's MATH table at the given font size
+    pub fn with_math_table(table: MathTable, font_size: f32) -> Self {
+        Self {
+            metrics: MathFontMetrics::from_math_table(&table, font_size),
+            math_table: Some(table),
+        }
+    }
+
+    /// Create for the given font size from the first installed math font
+    ///
+    /// Tries `families` in order, then well-known math fonts, and falls
+    /// back to synthetic metrics when none has a MATH table.
+    pub fn with_font_manager(fonts: &FontManager, families: &[&str], font_size: f32) -> Self {
+        match fonts.load_math_table(families) {
+            Some(table) => Self::with_math_table(table, font_size),
+            None => Self::with_metrics(MathFontMetrics::for_size(font_size)),
+        }
+    }
+
+    /// Get the math font table, if one is in use
+    pub fn math_table(&self) -> Option<&MathTable> {
+        self.math_table.as_ref()
+    }
+
+    /// Italic correction of a single-character base, in points
+    fn italic_correction(&self, base: &MathNode, metrics: &MathFontMetrics) -> f32 {
+        let (Some(table), MathNode::Run { text, .. }) = (&self.math_table, base) else {
+            return 0.0;
+        };
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => table
+                .italic_correction(c)
+                .map_or(0.0, |value| value as f32 * table.scale(metrics.font_size)),
+            _ => 0.0,
+        }
+    }
+
+    /// Stretch a glyph vertically to `height` points using the math font
+    fn stretch_vertical(
+        &self,
+        c: char,
+        height: f32,
+        metrics: &MathFontMetrics,
+    ) -> Option<LayoutContent> {
+        let table = self.math_table.as_ref()?;
+        let scale = table.scale(metrics.font_size);
+        let stretch = table.stretch_vertical(c, height / scale)?;
+        Some(LayoutContent::StretchedGlyph {
+            char: c,
+            stretch,
+            vertical: true,
+            font_size: metrics.font_size,
+            scale,
+            font_family: table.font_family.clone(),
+            font_index: table.font_index,
+        })
     }
 
     /// Layout a math node and return the layout tree
@@ -422,7 +560,7 @@ impl LayoutEngine {
         let den_box = self.layout_node(den, metrics)?;
 
         let width = num_box.width().max(den_box.width());
-        let bar_y = metrics.x_height / 2.0; // Axis height
+        let bar_y = metrics.axis_height;
 
         // Position numerator above bar
         let num_bottom = bar_y - metrics.fraction_num_gap;
@@ -443,7 +581,7 @@ impl LayoutEngine {
         // Shift everything so top is at y=0
         num_layout.bounds.origin.y -= top;
         den_layout.bounds.origin.y -= top;
-        let baseline_offset = bar_y - top + metrics.x_height / 2.0;
+        let baseline_offset = bar_y - top + metrics.axis_height;
 
         let bounds = Rect::new(0.0, 0.0, width, height);
 
@@ -486,6 +624,11 @@ impl LayoutEngine {
         // Radical symbol size
         let radical_height = base_box.height() + metrics.radical_vertical_gap;
         let radical_width = metrics.char_width * 0.8;
+        let radical_content = self
+            .stretch_vertical('\u{221A}', radical_height, metrics)
+            .unwrap_or(LayoutContent::Radical {
+                degree_present: degree.is_some(),
+            });
 
         let mut children = Vec::new();
         let mut total_width = radical_width + base_box.width();
@@ -516,9 +659,7 @@ impl LayoutEngine {
                 radical_height,
             ),
             radical_height,
-            LayoutContent::Radical {
-                degree_present: degree.is_some(),
-            },
+            radical_content,
         );
         children.push(radical_box);
 
@@ -598,8 +739,9 @@ impl LayoutEngine {
         let base_box = self.layout_node(base, metrics)?;
         let script_metrics = metrics.script_metrics();
         let sup_box = self.layout_node(sup, &script_metrics)?;
+        let italic_correction = self.italic_correction(base, metrics);
 
-        let width = base_box.width() + sup_box.width();
+        let width = base_box.width() + italic_correction + sup_box.width();
 
         // Superscript is raised
         let sup_bottom = base_box.baseline_offset - metrics.superscript_shift_up;
@@ -611,7 +753,7 @@ impl LayoutEngine {
         base_layout.bounds.origin.y = top_offset;
 
         let mut sup_layout = sup_box;
-        sup_layout.bounds.origin.x = base_layout.width();
+        sup_layout.bounds.origin.x = base_layout.width() + italic_correction;
         sup_layout.bounds.origin.y = top_offset + sup_top;
 
         let height = (base_layout.bounds.bottom()).max(sup_layout.bounds.bottom());
@@ -640,7 +782,8 @@ impl LayoutEngine {
         let sub_box = self.layout_node(sub, &script_metrics)?;
         let sup_box = self.layout_node(sup, &script_metrics)?;
 
-        let script_width = sub_box.width().max(sup_box.width());
+        let italic_correction = self.italic_correction(base, metrics);
+        let script_width = sub_box.width().max(sup_box.width() + italic_correction);
         let width = base_box.width() + script_width;
 
         // Position superscript
@@ -653,7 +796,7 @@ impl LayoutEngine {
         base_layout.bounds.origin.y = top_offset;
 
         let mut sup_layout = sup_box;
-        sup_layout.bounds.origin.x = base_layout.width();
+        sup_layout.bounds.origin.x = base_layout.width() + italic_correction;
         sup_layout.bounds.origin.y = top_offset + sup_top;
 
         let mut sub_layout = sub_box;
@@ -784,10 +927,7 @@ impl LayoutEngine {
         let open_box = LayoutBox::new(
             Rect::new(0.0, 0.0, delim_width, delim_height),
             delim_height * 0.6,
-            LayoutContent::Delimiter {
-                char: open,
-                stretched: grow,
-            },
+            self.delimiter_content(open, delim_height, grow, metrics),
         );
         children.push(open_box);
 
@@ -803,10 +943,7 @@ impl LayoutEngine {
         let close_box = LayoutBox::new(
             Rect::new(content_right, 0.0, delim_width, delim_height),
             delim_height * 0.6,
-            LayoutContent::Delimiter {
-                char: close,
-                stretched: grow,
-            },
+            self.delimiter_content(close, delim_height, grow, metrics),
         );
         children.push(close_box);
 
@@ -821,6 +958,22 @@ impl LayoutEngine {
         ))
     }
 
+    /// Content of a delimiter box, using the math font's variants when growing
+    fn delimiter_content(
+        &self,
+        c: char,
+        height: f32,
+        grow: bool,
+        metrics: &MathFontMetrics,
+    ) -> LayoutContent {
+        grow.then(|| self.stretch_vertical(c, height, metrics))
+            .flatten()
+            .unwrap_or(LayoutContent::Delimiter {
+                char: c,
+                stretched: grow,
+            })
+    }
+
     /// Layout matrix
     fn layout_matrix(
         &self,
@@ -873,7 +1026,7 @@ impl LayoutEngine {
 
         let total_width: f32 = col_widths.iter().sum::<f32>() + col_gap * (num_cols as f32 - 1.0);
         let total_height: f32 = row_heights.iter().sum::<f32>() + row_gap * (rows.len() as f32 - 1.0);
-        let baseline_offset = total_height / 2.0 + metrics.x_height / 2.0;
+        let baseline_offset = total_height / 2.0 + metrics.axis_height;
 
         let bounds = Rect::new(0.0, 0.0, total_width, total_height);
 
@@ -912,7 +1065,7 @@ impl LayoutEngine {
         }
 
         let total_height = y - row_gap;
-        let baseline_offset = total_height / 2.0 + metrics.x_height / 2.0;
+        let baseline_offset = total_height / 2.0 + metrics.axis_height;
         let bounds = Rect::new(0.0, 0.0, max_width, total_height);
 
         Ok(LayoutBox::with_children(
@@ -1349,4 +1502,88 @@ mod tests {
         let script = metrics.script_metrics();
         assert!(script.font_size < metrics.font_size);
     }
+
+    fn test_math_table() -> MathTable {
+        use text_engine::{MathConstants, MathGlyphConstruction, MathGlyphVariant};
+
+        let mut table = MathTable::new(1000);
+        table.constants = Some(MathConstants {
+            script_percent_scale_down: 80,
+            script_script_percent_scale_down: 60,
+            axis_height: 250,
+            subscript_shift_down: 200,
+            superscript_shift_up: 350,
+            sub_superscript_gap_min: 150,
+            space_after_script: 50,
+            fraction_rule_thickness: 60,
+            fraction_numerator_gap_min: 100,
+            fraction_denominator_gap_min: 100,
+            radical_vertical_gap: 80,
+            radical_rule_thickness: 60,
+            radical_extra_ascender: 60,
+            upper_limit_gap_min: 100,
+            lower_limit_gap_min: 100,
+            delimited_sub_formula_min_height: 1300,
+            display_operator_min_height: 1300,
+        });
+        table.set_italic_correction('f', 100);
+        let construction = MathGlyphConstruction {
+            variants: vec![MathGlyphVariant { glyph_id: 7, advance: 5000 }],
+            assembly: Vec::new(),
+        };
+        table.set_vertical_construction('(', construction.clone());
+        table.set_vertical_construction(')', construction);
+        table
+    }
+
+    #[test]
+    fn test_metrics_from_math_table() {
+        let metrics = MathFontMetrics::from_math_table(&test_math_table(), 10.0);
+        assert!((metrics.axis_height - 2.5).abs() < 0.001);
+        assert!((metrics.superscript_shift_up - 3.5).abs() < 0.001);
+        assert!((metrics.script_metrics().font_size - 8.0).abs() < 0.001);
+        assert!((metrics.scriptscript_metrics().font_size - 6.0).abs() < 0.001);
+
+        // Without constants the synthetic metrics are kept
+        let fallback = MathFontMetrics::from_math_table(&MathTable::new(1000), 10.0);
+        assert_eq!(fallback.axis_height, MathFontMetrics::for_size(10.0).axis_height);
+    }
+
+    #[test]
+    fn test_italic_correction_shifts_superscript() {
+        let node = MathNode::superscript(MathNode::run("f"), MathNode::run("2"));
+        let plain = LayoutEngine::new().layout(&node).unwrap();
+        let engine = LayoutEngine::with_math_table(test_math_table(), 11.0);
+        let corrected = engine.layout(&node).unwrap();
+
+        let plain_gap = plain.children[1].bounds.x() - plain.children[0].width();
+        let corrected_gap = corrected.children[1].bounds.x() - corrected.children[0].width();
+        assert_eq!(plain_gap, 0.0);
+        assert!((corrected_gap - 1.1).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_delimiters_use_math_font_variants() {
+        let node = MathNode::parens(vec![MathNode::run("x")]);
+        let engine = LayoutEngine::with_math_table(test_math_table(), 11.0);
+        let layout = engine.layout(&node).unwrap();
+        assert!(matches!(
+            layout.children[0].content,
+            LayoutContent::StretchedGlyph { char: '(', .. }
+        ));
+
+        // Characters missing from the table fall back to drawn delimiters
+        let node = MathNode::Delimiter {
+            open: '[',
+            close: ']',
+            separators: Vec::new(),
+            grow: true,
+            content: vec![MathNode::run("x")],
+        };
+        let layout = engine.layout(&node).unwrap();
+        assert!(matches!(
+            layout.children[0].content,
+            LayoutContent::Delimiter { char: '[', stretched: true }
+        ));
+    }
 }
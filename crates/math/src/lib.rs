@@ -3,19 +3,26 @@
 //! This crate provides comprehensive support for mathematical equations including:
 //! - A math AST (Abstract Syntax Tree) for representing equations
 //! - OMML (Office Math Markup Language) parsing and writing
-//! - Layout calculation for positioning math elements
+//! - Layout calculation for positioning math elements, using a font's OpenType MATH
+//!   table when one is available
 //! - Rendering to primitives for display
 //! - Linear notation parsing for user input
+//! - LaTeX parsing and writing for paste/copy interop
+//! - Presentation MathML parsing and writing for ODF, HTML, and accessibility interop
 //! - Equation editing commands and state management
 //! - Equation templates and symbol galleries
+//! - Equation numbering with per-chapter labels
 
 pub mod commands;
 pub mod editor;
 pub mod error;
 pub mod gallery;
+pub mod latex;
 pub mod layout;
 pub mod linear;
+pub mod mathml;
 pub mod model;
+pub mod numbering;
 pub mod omml_parser;
 pub mod omml_writer;
 pub mod render;
@@ -29,12 +36,18 @@ pub use error::*;
 pub use gallery::{
     all_structure_categories, all_symbol_categories, builtin_templates, search_symbols,
     search_templates, structures_for_category, symbols_for_category, templates_by_category,
-    EquationTemplate, RecentlyUsed, StructureCategory, StructureEntry, SymbolEntry,
-    SymbolPaletteCategory, TemplateCategory,
+    CustomSymbol, CustomSymbolPalette, EquationTemplate, GallerySymbol, PaletteId, RecentlyUsed,
+    StructureCategory, StructureEntry, SymbolEntry, SymbolPaletteCategory, SymbolPaletteRegistry,
+    TemplateCategory,
 };
+pub use latex::{parse_latex, to_latex, LatexParser, LatexWriter};
 pub use layout::{LayoutBox, LayoutContent, LayoutEngine, MathFontMetrics, Point, Rect, Size};
 pub use linear::parse_linear;
+pub use mathml::{parse_mathml, to_mathml, MathmlParser, MathmlWriter};
 pub use model::*;
+pub use numbering::{
+    EquationNumberFormat, EquationNumbering, NumberedEquation, NumberedEquationLayout,
+};
 pub use omml_parser::{parse_omml, OmmlParser};
 pub use omml_writer::{to_omml, OmmlWriter};
 pub use render::{Color, RenderConfig, RenderOutput, RenderPrimitive, Renderer, TextStyle};
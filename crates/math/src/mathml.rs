@@ -0,0 +1,1368 @@
+//! MathML - Parse and write Presentation MathML
+//!
+//! This module converts between Presentation MathML and MathNode trees. MathML is
+//! the equation format used by ODF formula objects, HTML clipboard content, and
+//! accessibility tooling such as screen readers.
+//!
+//! Parsing builds a small element tree first because MathML structures are
+//! positional (e.g., the first child of `<mfrac>` is the numerator). Elements
+//! without a MathNode equivalent are kept as `MathNode::Unknown`.
+
+use crate::error::{MathError, MathResult};
+use crate::model::*;
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+
+/// MathML namespace URI
+pub const MATHML_NS_URI: &str = "http://www.w3.org/1998/Math/MathML";
+
+/// Invisible function application operator (U+2061)
+const APPLY_FUNCTION: char = '\u{2061}';
+
+/// N-ary operators and their default limit placement
+const NARY_OPERATORS: &[(char, SubSupPlacement)] = &[
+    (symbols::SUM, SubSupPlacement::AboveBelow),
+    (symbols::PRODUCT, SubSupPlacement::AboveBelow),
+    (symbols::COPRODUCT, SubSupPlacement::AboveBelow),
+    (symbols::UNION, SubSupPlacement::AboveBelow),
+    (symbols::INTERSECTION, SubSupPlacement::AboveBelow),
+    (symbols::INTEGRAL, SubSupPlacement::Inline),
+    (symbols::DOUBLE_INTEGRAL, SubSupPlacement::Inline),
+    (symbols::TRIPLE_INTEGRAL, SubSupPlacement::Inline),
+    (symbols::CONTOUR_INTEGRAL, SubSupPlacement::Inline),
+];
+
+/// Accent combining characters and their spacing forms used in MathML
+const ACCENTS: &[(char, char)] = &[
+    ('\u{0302}', '^'),
+    ('\u{0303}', '~'),
+    ('\u{0304}', '\u{00AF}'),
+    ('\u{20D7}', '\u{2192}'),
+    ('\u{0307}', '\u{02D9}'),
+    ('\u{0308}', '\u{00A8}'),
+    ('\u{030C}', '\u{02C7}'),
+    ('\u{0306}', '\u{02D8}'),
+    ('\u{0301}', '\u{00B4}'),
+    ('\u{0300}', '`'),
+];
+
+/// Alternative spacing accents that map onto the same combining characters
+const ACCENT_ALIASES: &[(char, char)] = &[
+    ('\u{02C6}', '\u{0302}'),
+    ('\u{02DC}', '\u{0303}'),
+    ('\u{20D7}', '\u{20D7}'),
+];
+
+/// Characters drawn as an overline
+const OVERLINE_CHARS: &[char] = &['\u{203E}', '\u{0305}'];
+
+/// Characters drawn as an underline
+const UNDERLINE_CHARS: &[char] = &['_', '\u{0332}', '\u{FE4D}'];
+
+/// Horizontal braces and brackets drawn above or below an expression
+const GROUP_CHARS_TOP: &[char] = &['\u{23DE}', '\u{23DC}', '\u{23B4}'];
+const GROUP_CHARS_BOTTOM: &[char] = &['\u{23DF}', '\u{23DD}', '\u{23B5}'];
+
+/// Characters recognised as opening fences in an `<mrow>`
+const OPEN_FENCES: &[char] = &[
+    '(', '[', '{', '\u{27E8}', '\u{230A}', '\u{2308}', '|', '\u{2016}',
+];
+
+/// Characters recognised as closing fences in an `<mrow>`
+const CLOSE_FENCES: &[char] = &[
+    ')', ']', '}', '\u{27E9}', '\u{230B}', '\u{2309}', '|', '\u{2016}',
+];
+
+/// Named entities commonly found in MathML that XML does not predefine
+const ENTITIES: &[(&str, &str)] = &[
+    ("nbsp", "\u{00A0}"),
+    ("ApplyFunction", "\u{2061}"),
+    ("af", "\u{2061}"),
+    ("InvisibleTimes", "\u{2062}"),
+    ("it", "\u{2062}"),
+    ("InvisibleComma", "\u{2063}"),
+    ("ic", "\u{2063}"),
+    ("PlusMinus", "\u{00B1}"),
+    ("pm", "\u{00B1}"),
+    ("MinusPlus", "\u{2213}"),
+    ("times", "\u{00D7}"),
+    ("divide", "\u{00F7}"),
+    ("minus", "\u{2212}"),
+    ("sdot", "\u{22C5}"),
+    ("ne", "\u{2260}"),
+    ("le", "\u{2264}"),
+    ("ge", "\u{2265}"),
+    ("infin", "\u{221E}"),
+    ("sum", "\u{2211}"),
+    ("prod", "\u{220F}"),
+    ("int", "\u{222B}"),
+    ("Integral", "\u{222B}"),
+    ("partial", "\u{2202}"),
+    ("nabla", "\u{2207}"),
+    ("rarr", "\u{2192}"),
+    ("larr", "\u{2190}"),
+    ("rArr", "\u{21D2}"),
+    ("OverBar", "\u{00AF}"),
+    ("UnderBar", "_"),
+    ("OverBrace", "\u{23DE}"),
+    ("UnderBrace", "\u{23DF}"),
+    ("lang", "\u{27E8}"),
+    ("rang", "\u{27E9}"),
+];
+
+// =============================================================================
+// Element Tree
+// =============================================================================
+
+/// A parsed MathML element
+#[derive(Debug, Clone, Default)]
+struct Element {
+    /// Local element name (namespace prefix removed)
+    name: String,
+    /// Attributes by local name
+    attrs: Vec<(String, String)>,
+    /// Child elements in document order
+    children: Vec<Element>,
+    /// Concatenated text content of token elements
+    text: String,
+}
+
+impl Element {
+    fn from_start(e: &BytesStart) -> Self {
+        let attrs = e
+            .attributes()
+            .flatten()
+            .map(|attr| {
+                let key = local_name_from_bytes(attr.key.as_ref());
+                let value = attr
+                    .unescape_value()
+                    .map(|v| v.into_owned())
+                    .unwrap_or_else(|_| String::from_utf8_lossy(&attr.value).into_owned());
+                (key, value)
+            })
+            .collect();
+        Self {
+            name: local_name_from_bytes(e.name().as_ref()),
+            attrs,
+            ..Default::default()
+        }
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Token text with surrounding whitespace removed, as MathML specifies
+    fn token_text(&self) -> &str {
+        self.text.trim()
+    }
+
+    /// The single character of an `<mo>`, if the element is one
+    fn operator_char(&self) -> Option<char> {
+        if self.name != "mo" {
+            return None;
+        }
+        single_char(self.token_text())
+    }
+}
+
+// =============================================================================
+// Parser
+// =============================================================================
+
+/// Parse MathML from a string, returning one node per `<math>` element
+///
+/// Inline equations become `MathNode::OMath`; `display="block"` equations become
+/// `MathNode::OMathPara`.
+pub fn parse_mathml(xml: &str) -> MathResult<Vec<MathNode>> {
+    let mut parser = MathmlParser::new(xml);
+    parser.parse()
+}
+
+/// Parser for Presentation MathML content
+pub struct MathmlParser<'a> {
+    reader: Reader<&'a [u8]>,
+}
+
+impl<'a> MathmlParser<'a> {
+    /// Create a new parser from XML string
+    pub fn new(xml: &'a str) -> Self {
+        let reader = Reader::from_str(xml);
+        Self { reader }
+    }
+
+    /// Parse the entire content and return MathNode trees
+    pub fn parse(&mut self) -> MathResult<Vec<MathNode>> {
+        let roots = self.read_tree()?;
+        let mut results = Vec::new();
+        for root in &roots {
+            collect_math(root, &mut results);
+        }
+        Ok(results)
+    }
+
+    /// Read the document into an element tree
+    fn read_tree(&mut self) -> MathResult<Vec<Element>> {
+        let mut roots = Vec::new();
+        let mut stack: Vec<Element> = Vec::new();
+        let mut buf = Vec::new();
+
+        loop {
+            buf.clear();
+            match self.reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) => stack.push(Element::from_start(e)),
+                Ok(Event::Empty(ref e)) => {
+                    let element = Element::from_start(e);
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => roots.push(element),
+                    }
+                }
+                Ok(Event::End(_)) => {
+                    let element = stack
+                        .pop()
+                        .ok_or_else(|| MathError::MathmlParse("unbalanced end tag".to_string()))?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => roots.push(element),
+                    }
+                }
+                Ok(Event::Text(ref e)) => {
+                    if let Some(current) = stack.last_mut() {
+                        current.text.push_str(&unescape_text(e));
+                    }
+                }
+                Ok(Event::CData(ref e)) => {
+                    if let Some(current) = stack.last_mut() {
+                        current.text.push_str(&String::from_utf8_lossy(e.as_ref()));
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(MathError::Xml(e)),
+                _ => {}
+            }
+        }
+
+        if !stack.is_empty() {
+            return Err(MathError::MathmlParse(format!(
+                "unclosed element <{}>",
+                stack[stack.len() - 1].name
+            )));
+        }
+        Ok(roots)
+    }
+}
+
+/// Find `<math>` elements anywhere in the tree and convert them
+fn collect_math(element: &Element, results: &mut Vec<MathNode>) {
+    if element.name == "math" {
+        let children = convert_sequence(&element.children);
+        let block =
+            element.attr("display") == Some("block") || element.attr("mode") == Some("display");
+        results.push(if block {
+            MathNode::OMathPara(vec![MathNode::OMath(children)])
+        } else {
+            MathNode::OMath(children)
+        });
+        return;
+    }
+    for child in &element.children {
+        collect_math(child, results);
+    }
+}
+
+/// Convert a list of sibling elements, folding function applications, n-ary
+/// operands, and fences into their structures
+fn convert_sequence(elements: &[Element]) -> Vec<MathNode> {
+    if let Some(delimiter) = convert_fenced_row(elements) {
+        return vec![delimiter];
+    }
+
+    let mut nodes = Vec::new();
+    let mut i = 0;
+    while i < elements.len() {
+        let element = &elements[i];
+        i += 1;
+
+        // Invisible operators only carry semantics
+        if matches!(element.operator_char(), Some('\u{2061}'..='\u{2064}')) {
+            continue;
+        }
+
+        // <mi>sin</mi><mo>&#x2061;</mo><mi>x</mi>
+        if element.name == "mi"
+            && elements.get(i).and_then(Element::operator_char) == Some(APPLY_FUNCTION)
+        {
+            i += 1;
+            let base = match elements.get(i) {
+                Some(arg) => {
+                    i += 1;
+                    convert_element(arg)
+                }
+                None => MathNode::run(""),
+            };
+            nodes.push(MathNode::Function {
+                name: element.token_text().to_string(),
+                base: Box::new(base),
+            });
+            continue;
+        }
+
+        let node = convert_element(element);
+
+        // An n-ary operator takes the following sibling as its operand
+        let node = match node {
+            MathNode::Nary {
+                op,
+                sub_sup_placement,
+                sub,
+                sup,
+                base,
+            } if base.is_empty() && i < elements.len() => {
+                let operand = convert_element(&elements[i]);
+                i += 1;
+                MathNode::Nary {
+                    op,
+                    sub_sup_placement,
+                    sub,
+                    sup,
+                    base: Box::new(operand),
+                }
+            }
+            other => other,
+        };
+        nodes.push(node);
+    }
+    nodes
+}
+
+/// Recognise `<mo>(</mo> ... <mo>)</mo>` rows as delimiters
+fn convert_fenced_row(elements: &[Element]) -> Option<MathNode> {
+    if elements.len() < 2 {
+        return None;
+    }
+    let open = elements[0].operator_char()?;
+    let close = elements[elements.len() - 1].operator_char()?;
+    if !OPEN_FENCES.contains(&open) || !CLOSE_FENCES.contains(&close) {
+        return None;
+    }
+
+    let inner = &elements[1..elements.len() - 1];
+
+    // "(a)+(b)" is two groups, not one delimiter around "a)+(b"
+    let mut depth = 0i32;
+    for element in inner {
+        match element.operator_char() {
+            Some(c) if OPEN_FENCES.contains(&c) && !CLOSE_FENCES.contains(&c) => depth += 1,
+            Some(c) if CLOSE_FENCES.contains(&c) && !OPEN_FENCES.contains(&c) => {
+                depth -= 1;
+                if depth < 0 {
+                    return None;
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut separators = Vec::new();
+    let mut segments: Vec<&[Element]> = Vec::new();
+    let mut start = 0;
+    for (i, element) in inner.iter().enumerate() {
+        if element.attr("separator") == Some("true") {
+            if let Some(c) = element.operator_char() {
+                separators.push(c);
+                segments.push(&inner[start..i]);
+                start = i + 1;
+            }
+        }
+    }
+    segments.push(&inner[start..]);
+
+    Some(MathNode::Delimiter {
+        open,
+        close,
+        separators,
+        content: segments
+            .into_iter()
+            .map(|segment| group(convert_sequence(segment)))
+            .collect(),
+        grow: true,
+    })
+}
+
+/// Convert a single element
+fn convert_element(element: &Element) -> MathNode {
+    let children = &element.children;
+    match element.name.as_str() {
+        "mi" => convert_identifier(element),
+        "mn" => MathNode::Number(element.token_text().to_string()),
+        "mo" => convert_operator(element),
+        "mtext" | "ms" => MathNode::Text(element.text.clone()),
+        "mspace" => MathNode::Text(" ".to_string()),
+        "mrow" | "mstyle" | "mpadded" | "math" => group(convert_sequence(children)),
+        "semantics" => children
+            .first()
+            .map(convert_element)
+            .unwrap_or_else(|| MathNode::run("")),
+        "mfrac" => MathNode::Fraction {
+            num: Box::new(nth(children, 0)),
+            den: Box::new(nth(children, 1)),
+            bar_visible: !matches!(
+                element.attr("linethickness"),
+                Some("0") | Some("0px") | Some("0pt") | Some("0em")
+            ),
+        },
+        "msqrt" => MathNode::sqrt(group(convert_sequence(children))),
+        "mroot" => MathNode::nthroot(nth(children, 1), nth(children, 0)),
+        "msub" => convert_scripts(children, Some(1), None, SubSupPlacement::Inline),
+        "msup" => convert_scripts(children, None, Some(1), SubSupPlacement::Inline),
+        "msubsup" => convert_scripts(children, Some(1), Some(2), SubSupPlacement::Inline),
+        "munder" => convert_under_over(element, Some(1), None),
+        "mover" => convert_under_over(element, None, Some(1)),
+        "munderover" => convert_under_over(element, Some(1), Some(2)),
+        "mfenced" => convert_mfenced(element),
+        "mtable" => MathNode::matrix(
+            children
+                .iter()
+                .filter(|row| row.name == "mtr" || row.name == "mlabeledtr")
+                .map(|row| {
+                    // The first cell of a labeled row is the equation label
+                    let skip = usize::from(row.name == "mlabeledtr");
+                    row.children
+                        .iter()
+                        .filter(|cell| cell.name == "mtd")
+                        .skip(skip)
+                        .map(|cell| group(convert_sequence(&cell.children)))
+                        .collect()
+                })
+                .collect(),
+        ),
+        "menclose" => convert_menclose(element),
+        "mphantom" => MathNode::Phantom {
+            base: Box::new(group(convert_sequence(children))),
+            zero_width: false,
+            zero_height: false,
+        },
+        "annotation" | "annotation-xml" | "none" | "mprescripts" | "maligngroup" | "malignmark" => {
+            MathNode::run("")
+        }
+        _ => MathNode::Unknown {
+            tag: element.name.clone(),
+            content: element.text.clone(),
+        },
+    }
+}
+
+/// Convert an `<mi>`, honouring `mathvariant`
+fn convert_identifier(element: &Element) -> MathNode {
+    let text = element.token_text();
+    // Single-character identifiers default to italic, longer ones to normal
+    let default_style = if text.chars().count() == 1 {
+        MathFontStyle::Italic
+    } else {
+        MathFontStyle::Normal
+    };
+    let font_style = element
+        .attr("mathvariant")
+        .and_then(font_style_from_variant)
+        .unwrap_or(default_style);
+    MathNode::Run {
+        text: text.to_string(),
+        style: MathStyle {
+            font_style,
+            ..Default::default()
+        },
+    }
+}
+
+/// Convert an `<mo>`
+fn convert_operator(element: &Element) -> MathNode {
+    let text = element.token_text();
+    match single_char(text) {
+        Some(chr) => {
+            if let Some((_, placement)) = NARY_OPERATORS.iter().find(|(c, _)| *c == chr) {
+                return MathNode::Nary {
+                    op: chr,
+                    sub_sup_placement: *placement,
+                    sub: None,
+                    sup: None,
+                    base: Box::new(MathNode::run("")),
+                };
+            }
+            let form = match element.attr("form") {
+                Some("prefix") => OperatorForm::Prefix,
+                Some("postfix") => OperatorForm::Postfix,
+                _ => OperatorForm::Infix,
+            };
+            MathNode::Operator { chr, form }
+        }
+        None => MathNode::styled_run(text, MathStyle::normal()),
+    }
+}
+
+/// Convert `<msub>`, `<msup>`, and `<msubsup>`
+fn convert_scripts(
+    children: &[Element],
+    sub: Option<usize>,
+    sup: Option<usize>,
+    placement: SubSupPlacement,
+) -> MathNode {
+    let base = nth(children, 0);
+    let sub = sub.map(|i| nth(children, i));
+    let sup = sup.map(|i| nth(children, i));
+
+    if let MathNode::Nary { op, base, .. } = base {
+        return MathNode::Nary {
+            op,
+            sub_sup_placement: placement,
+            sub: sub.map(Box::new),
+            sup: sup.map(Box::new),
+            base,
+        };
+    }
+
+    match (sub, sup) {
+        (Some(sub), Some(sup)) => MathNode::sub_superscript(base, sub, sup),
+        (Some(sub), None) => MathNode::subscript(base, sub),
+        (None, Some(sup)) => MathNode::superscript(base, sup),
+        (None, None) => base,
+    }
+}
+
+/// Convert `<munder>`, `<mover>`, and `<munderover>`
+fn convert_under_over(element: &Element, under: Option<usize>, over: Option<usize>) -> MathNode {
+    let children = &element.children;
+    if matches!(nth(children, 0), MathNode::Nary { .. }) {
+        return convert_scripts(children, under, over, SubSupPlacement::AboveBelow);
+    }
+
+    // A single decoration character above or below the base
+    if let (Some(index), None) | (None, Some(index)) = (under, over) {
+        let base = Box::new(nth(children, 0));
+        let is_over = over.is_some();
+        if let Some(chr) = children.get(index).and_then(Element::operator_char) {
+            if is_over && OVERLINE_CHARS.contains(&chr) {
+                return MathNode::Bar {
+                    base,
+                    position: BarPosition::Top,
+                };
+            }
+            if !is_over && UNDERLINE_CHARS.contains(&chr) {
+                return MathNode::Bar {
+                    base,
+                    position: BarPosition::Bottom,
+                };
+            }
+            if is_over && GROUP_CHARS_TOP.contains(&chr) {
+                return MathNode::GroupChar {
+                    base,
+                    chr,
+                    position: BarPosition::Top,
+                };
+            }
+            if !is_over && GROUP_CHARS_BOTTOM.contains(&chr) {
+                return MathNode::GroupChar {
+                    base,
+                    chr,
+                    position: BarPosition::Bottom,
+                };
+            }
+            if is_over {
+                if let Some(accent_char) = accent_from_spacing(chr) {
+                    return MathNode::Accent { base, accent_char };
+                }
+            }
+        }
+
+        return MathNode::Limit {
+            func: base,
+            limit: Box::new(nth(children, index)),
+            position: if is_over {
+                LimitPosition::Upper
+            } else {
+                LimitPosition::Lower
+            },
+        };
+    }
+
+    // Both limits on a non-operator base: nest them
+    let lower = MathNode::Limit {
+        func: Box::new(nth(children, 0)),
+        limit: Box::new(nth(children, 1)),
+        position: LimitPosition::Lower,
+    };
+    MathNode::Limit {
+        func: Box::new(lower),
+        limit: Box::new(nth(children, 2)),
+        position: LimitPosition::Upper,
+    }
+}
+
+/// Convert the deprecated `<mfenced>` element
+fn convert_mfenced(element: &Element) -> MathNode {
+    let open = element.attr("open").map_or(Some('('), single_char);
+    let close = element.attr("close").map_or(Some(')'), single_char);
+    let separators: Vec<char> = element
+        .attr("separators")
+        .unwrap_or(",")
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let content: Vec<MathNode> = element.children.iter().map(convert_element).collect();
+    let separators = (0..content.len().saturating_sub(1))
+        .filter_map(|i| separators.get(i).or(separators.last()).copied())
+        .collect();
+
+    MathNode::Delimiter {
+        open: open.unwrap_or(symbols::EMPTY_DELIMITER),
+        close: close.unwrap_or(symbols::EMPTY_DELIMITER),
+        separators,
+        content,
+        grow: true,
+    }
+}
+
+/// Convert `<menclose>` based on its notation
+fn convert_menclose(element: &Element) -> MathNode {
+    let base = Box::new(group(convert_sequence(&element.children)));
+    let notation: Vec<&str> = element
+        .attr("notation")
+        .unwrap_or("longdiv")
+        .split_whitespace()
+        .collect();
+
+    if notation.iter().any(|n| matches!(*n, "box" | "roundedbox")) {
+        return MathNode::BorderBox {
+            base,
+            hide_top: false,
+            hide_bottom: false,
+            hide_left: false,
+            hide_right: false,
+        };
+    }
+
+    let has = |side: &str| notation.contains(&side);
+    if has("top") || has("bottom") || has("left") || has("right") {
+        return MathNode::BorderBox {
+            base,
+            hide_top: !has("top"),
+            hide_bottom: !has("bottom"),
+            hide_left: !has("left"),
+            hide_right: !has("right"),
+        };
+    }
+
+    MathNode::Box(base)
+}
+
+/// Convert the nth child, or an empty run if missing
+fn nth(children: &[Element], index: usize) -> MathNode {
+    children
+        .get(index)
+        .map(convert_element)
+        .unwrap_or_else(|| MathNode::run(""))
+}
+
+/// Collapse a node list into a single node
+fn group(mut nodes: Vec<MathNode>) -> MathNode {
+    if nodes.len() == 1 {
+        nodes.remove(0)
+    } else {
+        MathNode::OMath(nodes)
+    }
+}
+
+fn single_char(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+fn accent_from_spacing(chr: char) -> Option<char> {
+    ACCENTS
+        .iter()
+        .find(|(_, spacing)| *spacing == chr)
+        .map(|(combining, _)| *combining)
+        .or_else(|| {
+            ACCENT_ALIASES
+                .iter()
+                .find(|(alias, _)| *alias == chr)
+                .map(|(_, combining)| *combining)
+        })
+}
+
+fn font_style_from_variant(variant: &str) -> Option<MathFontStyle> {
+    let style = match variant {
+        "normal" => MathFontStyle::Normal,
+        "italic" => MathFontStyle::Italic,
+        "bold" => MathFontStyle::Bold,
+        "bold-italic" => MathFontStyle::BoldItalic,
+        "script" => MathFontStyle::Script,
+        "bold-script" => MathFontStyle::BoldScript,
+        "fraktur" => MathFontStyle::Fraktur,
+        "bold-fraktur" => MathFontStyle::BoldFraktur,
+        "double-struck" => MathFontStyle::DoubleStruck,
+        "sans-serif" => MathFontStyle::SansSerif,
+        "bold-sans-serif" => MathFontStyle::SansSerifBold,
+        "sans-serif-italic" => MathFontStyle::SansSerifItalic,
+        "sans-serif-bold-italic" => MathFontStyle::SansSerifBoldItalic,
+        "monospace" => MathFontStyle::Monospace,
+        _ => return None,
+    };
+    Some(style)
+}
+
+fn variant_from_font_style(style: MathFontStyle) -> &'static str {
+    match style {
+        MathFontStyle::Normal => "normal",
+        MathFontStyle::Italic => "italic",
+        MathFontStyle::Bold => "bold",
+        MathFontStyle::BoldItalic => "bold-italic",
+        MathFontStyle::Script => "script",
+        MathFontStyle::BoldScript => "bold-script",
+        MathFontStyle::Fraktur => "fraktur",
+        MathFontStyle::BoldFraktur => "bold-fraktur",
+        MathFontStyle::DoubleStruck => "double-struck",
+        MathFontStyle::SansSerif => "sans-serif",
+        MathFontStyle::SansSerifBold => "bold-sans-serif",
+        MathFontStyle::SansSerifItalic => "sans-serif-italic",
+        MathFontStyle::SansSerifBoldItalic => "sans-serif-bold-italic",
+        MathFontStyle::Monospace => "monospace",
+    }
+}
+
+/// Unescape text, resolving MathML named entities
+fn unescape_text(e: &BytesText) -> String {
+    let resolve = |name: &str| {
+        ENTITIES
+            .iter()
+            .find(|(entity, _)| *entity == name)
+            .map(|(_, value)| *value)
+    };
+    e.unescape_with(resolve)
+        .map(|text| text.into_owned())
+        .unwrap_or_else(|_| String::from_utf8_lossy(e.as_ref()).into_owned())
+}
+
+/// Extract local name from a potentially namespaced tag
+fn local_name_from_bytes(name: &[u8]) -> String {
+    let name_str = std::str::from_utf8(name).unwrap_or("");
+    match name_str.rfind(':') {
+        Some(pos) => name_str[pos + 1..].to_string(),
+        None => name_str.to_string(),
+    }
+}
+
+// =============================================================================
+// Writer
+// =============================================================================
+
+/// Writer for converting MathNode to Presentation MathML
+pub struct MathmlWriter {
+    writer: Writer<Vec<u8>>,
+}
+
+impl MathmlWriter {
+    /// Create a new MathML writer
+    pub fn new() -> Self {
+        Self {
+            writer: Writer::new(Vec::new()),
+        }
+    }
+
+    /// Write a node as a complete `<math>` element
+    pub fn write(mut self, node: &MathNode) -> MathResult<String> {
+        let mut math = BytesStart::new("math");
+        math.push_attribute(("xmlns", MATHML_NS_URI));
+
+        let equations = match node {
+            MathNode::OMathPara(children) => {
+                math.push_attribute(("display", "block"));
+                children.as_slice()
+            }
+            other => std::slice::from_ref(other),
+        };
+
+        self.start(math)?;
+        if equations.len() > 1 {
+            // Several equations in one display paragraph stack as rows
+            self.start_tag("mtable")?;
+            for equation in equations {
+                self.start_tag("mtr")?;
+                self.start_tag("mtd")?;
+                self.write_contents(equation)?;
+                self.end("mtd")?;
+                self.end("mtr")?;
+            }
+            self.end("mtable")?;
+        } else if let Some(equation) = equations.first() {
+            self.write_contents(equation)?;
+        }
+        self.end("math")?;
+
+        String::from_utf8(self.writer.into_inner())
+            .map_err(|e| MathError::MathmlWrite(e.to_string()))
+    }
+
+    /// Write the children of an equation without an enclosing `<mrow>`
+    fn write_contents(&mut self, node: &MathNode) -> MathResult<()> {
+        match node {
+            MathNode::OMath(children) => {
+                for child in children {
+                    self.write_node(child)?;
+                }
+                Ok(())
+            }
+            other => self.write_node(other),
+        }
+    }
+
+    /// Write a single node
+    fn write_node(&mut self, node: &MathNode) -> MathResult<()> {
+        match node {
+            MathNode::OMath(children) | MathNode::OMathPara(children) => {
+                self.start_tag("mrow")?;
+                for child in children {
+                    self.write_node(child)?;
+                }
+                self.end("mrow")?;
+            }
+            MathNode::Fraction {
+                num,
+                den,
+                bar_visible,
+            } => {
+                let mut start = BytesStart::new("mfrac");
+                if !bar_visible {
+                    start.push_attribute(("linethickness", "0"));
+                }
+                self.start(start)?;
+                self.write_node(num)?;
+                self.write_node(den)?;
+                self.end("mfrac")?;
+            }
+            MathNode::Radical { degree, base } => match degree {
+                Some(degree) => self.write_element("mroot", &[base, degree])?,
+                None => self.write_element("msqrt", &[base])?,
+            },
+            MathNode::Subscript { base, sub } => self.write_element("msub", &[base, sub])?,
+            MathNode::Superscript { base, sup } => self.write_element("msup", &[base, sup])?,
+            MathNode::SubSuperscript { base, sub, sup } => {
+                self.write_element("msubsup", &[base, sub, sup])?
+            }
+            MathNode::Nary {
+                op,
+                sub_sup_placement,
+                sub,
+                sup,
+                base,
+            } => self.write_nary(
+                *op,
+                *sub_sup_placement,
+                sub.as_deref(),
+                sup.as_deref(),
+                base,
+            )?,
+            MathNode::Delimiter {
+                open,
+                close,
+                separators,
+                content,
+                ..
+            } => self.write_delimiter(*open, *close, separators, content)?,
+            MathNode::Matrix { rows, .. } | MathNode::EqArray(rows) => {
+                self.start_tag("mtable")?;
+                for row in rows {
+                    self.start_tag("mtr")?;
+                    for cell in row {
+                        self.start_tag("mtd")?;
+                        self.write_node(cell)?;
+                        self.end("mtd")?;
+                    }
+                    self.end("mtr")?;
+                }
+                self.end("mtable")?;
+            }
+            MathNode::Box(base) => self.write_element("mrow", &[base])?,
+            MathNode::Bar { base, position } => match position {
+                BarPosition::Top => {
+                    self.write_decorated("mover", base, OVERLINE_CHARS[0], false)?
+                }
+                BarPosition::Bottom => {
+                    self.write_decorated("munder", base, UNDERLINE_CHARS[0], false)?
+                }
+            },
+            MathNode::Accent { base, accent_char } => {
+                let spacing = ACCENTS
+                    .iter()
+                    .find(|(combining, _)| combining == accent_char)
+                    .map_or(*accent_char, |(_, spacing)| *spacing);
+                self.write_decorated("mover", base, spacing, true)?;
+            }
+            MathNode::Limit {
+                func,
+                limit,
+                position,
+            } => {
+                let tag = match position {
+                    LimitPosition::Lower => "munder",
+                    LimitPosition::Upper => "mover",
+                };
+                self.write_element(tag, &[func, limit])?;
+            }
+            MathNode::Function { name, base } => {
+                self.start_tag("mrow")?;
+                self.write_token("mi", name, None)?;
+                if !base.is_empty() {
+                    self.write_token("mo", &APPLY_FUNCTION.to_string(), None)?;
+                    self.write_node(base)?;
+                }
+                self.end("mrow")?;
+            }
+            MathNode::GroupChar {
+                base,
+                chr,
+                position,
+            } => {
+                let tag = match position {
+                    BarPosition::Top => "mover",
+                    BarPosition::Bottom => "munder",
+                };
+                self.write_decorated(tag, base, *chr, false)?;
+            }
+            MathNode::BorderBox {
+                base,
+                hide_top,
+                hide_bottom,
+                hide_left,
+                hide_right,
+            } => {
+                let sides: Vec<&str> = [
+                    (*hide_top, "top"),
+                    (*hide_bottom, "bottom"),
+                    (*hide_left, "left"),
+                    (*hide_right, "right"),
+                ]
+                .iter()
+                .filter(|(hidden, _)| !hidden)
+                .map(|(_, side)| *side)
+                .collect();
+                let notation = if sides.len() == 4 {
+                    "box".to_string()
+                } else {
+                    sides.join(" ")
+                };
+                let mut start = BytesStart::new("menclose");
+                start.push_attribute(("notation", notation.as_str()));
+                self.start(start)?;
+                self.write_node(base)?;
+                self.end("menclose")?;
+            }
+            MathNode::Phantom { base, .. } => self.write_element("mphantom", &[base])?,
+            MathNode::Run { text, style } => self.write_run(text, style)?,
+            MathNode::Operator { chr, form } => {
+                let form = match form {
+                    OperatorForm::Prefix => Some("prefix"),
+                    OperatorForm::Postfix => Some("postfix"),
+                    OperatorForm::Infix => None,
+                };
+                let attrs: Vec<(&str, &str)> = form.map(|f| ("form", f)).into_iter().collect();
+                self.write_token_with("mo", &chr.to_string(), &attrs)?;
+            }
+            MathNode::Text(text) => self.write_token("mtext", text, None)?,
+            MathNode::Number(num) => self.write_token("mn", num, None)?,
+            MathNode::Unknown { .. } => {}
+        }
+        Ok(())
+    }
+
+    fn write_nary(
+        &mut self,
+        op: char,
+        placement: SubSupPlacement,
+        sub: Option<&MathNode>,
+        sup: Option<&MathNode>,
+        base: &MathNode,
+    ) -> MathResult<()> {
+        let tag = match (placement, sub.is_some(), sup.is_some()) {
+            (_, false, false) => None,
+            (SubSupPlacement::AboveBelow, true, true) => Some("munderover"),
+            (SubSupPlacement::AboveBelow, true, false) => Some("munder"),
+            (SubSupPlacement::AboveBelow, false, true) => Some("mover"),
+            (SubSupPlacement::Inline, true, true) => Some("msubsup"),
+            (SubSupPlacement::Inline, true, false) => Some("msub"),
+            (SubSupPlacement::Inline, false, true) => Some("msup"),
+        };
+
+        self.start_tag("mrow")?;
+        if let Some(tag) = tag {
+            self.start_tag(tag)?;
+        }
+        self.write_token_with("mo", &op.to_string(), &[("largeop", "true")])?;
+        if let Some(sub) = sub {
+            self.write_node(sub)?;
+        }
+        if let Some(sup) = sup {
+            self.write_node(sup)?;
+        }
+        if let Some(tag) = tag {
+            self.end(tag)?;
+        }
+        if !base.is_empty() {
+            self.write_node(base)?;
+        }
+        self.end("mrow")
+    }
+
+    fn write_delimiter(
+        &mut self,
+        open: char,
+        close: char,
+        separators: &[char],
+        content: &[MathNode],
+    ) -> MathResult<()> {
+        let fence = [("fence", "true")];
+        self.start_tag("mrow")?;
+        if open != symbols::EMPTY_DELIMITER {
+            self.write_token_with("mo", &open.to_string(), &fence)?;
+        }
+        for (i, item) in content.iter().enumerate() {
+            if i > 0 {
+                let sep = separators
+                    .get(i - 1)
+                    .or(separators.last())
+                    .copied()
+                    .unwrap_or(',');
+                self.write_token_with("mo", &sep.to_string(), &[("separator", "true")])?;
+            }
+            self.write_node(item)?;
+        }
+        if close != symbols::EMPTY_DELIMITER {
+            self.write_token_with("mo", &close.to_string(), &fence)?;
+        }
+        self.end("mrow")
+    }
+
+    /// Write a base with a single decoration character above or below it
+    fn write_decorated(
+        &mut self,
+        tag: &str,
+        base: &MathNode,
+        chr: char,
+        accent: bool,
+    ) -> MathResult<()> {
+        let mut start = BytesStart::new(tag);
+        if accent {
+            start.push_attribute(("accent", "true"));
+        }
+        self.start(start)?;
+        self.write_node(base)?;
+        self.write_token("mo", &chr.to_string(), None)?;
+        self.end(tag)
+    }
+
+    fn write_run(&mut self, text: &str, style: &MathStyle) -> MathResult<()> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        if text.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            return self.write_token("mn", text, None);
+        }
+
+        // Only spell out the variant when it differs from the MathML default
+        let default_style = if text.chars().count() == 1 {
+            MathFontStyle::Italic
+        } else {
+            MathFontStyle::Normal
+        };
+        let variant =
+            (style.font_style != default_style).then(|| variant_from_font_style(style.font_style));
+        self.write_token("mi", text, variant)
+    }
+
+    fn write_token(&mut self, tag: &str, text: &str, variant: Option<&str>) -> MathResult<()> {
+        let attrs: Vec<(&str, &str)> = variant.map(|v| ("mathvariant", v)).into_iter().collect();
+        self.write_token_with(tag, text, &attrs)
+    }
+
+    fn write_token_with(
+        &mut self,
+        tag: &str,
+        text: &str,
+        attrs: &[(&str, &str)],
+    ) -> MathResult<()> {
+        let mut start = BytesStart::new(tag);
+        for attr in attrs {
+            start.push_attribute(*attr);
+        }
+        self.start(start)?;
+        self.writer
+            .write_event(Event::Text(BytesText::new(text)))
+            .map_err(|e| MathError::MathmlWrite(e.to_string()))?;
+        self.end(tag)
+    }
+
+    /// Write an element whose children are the given nodes in order
+    fn write_element(&mut self, tag: &str, children: &[&MathNode]) -> MathResult<()> {
+        self.start_tag(tag)?;
+        for child in children {
+            self.write_node(child)?;
+        }
+        self.end(tag)
+    }
+
+    fn start_tag(&mut self, tag: &str) -> MathResult<()> {
+        self.start(BytesStart::new(tag))
+    }
+
+    fn start(&mut self, start: BytesStart) -> MathResult<()> {
+        self.writer
+            .write_event(Event::Start(start))
+            .map_err(|e| MathError::MathmlWrite(e.to_string()))
+    }
+
+    fn end(&mut self, tag: &str) -> MathResult<()> {
+        self.writer
+            .write_event(Event::End(BytesEnd::new(tag)))
+            .map_err(|e| MathError::MathmlWrite(e.to_string()))
+    }
+}
+
+impl Default for MathmlWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Convert a MathNode to a Presentation MathML `<math>` element
+pub fn to_mathml(node: &MathNode) -> MathResult<String> {
+    MathmlWriter::new().write(node)
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_one(xml: &str) -> Vec<MathNode> {
+        match parse_mathml(xml).unwrap().remove(0) {
+            MathNode::OMath(children) => children,
+            other => panic!("Expected OMath, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_fraction() {
+        let nodes = parse_one(
+            r#"<math xmlns="http://www.w3.org/1998/Math/MathML">
+                <mfrac><mi>a</mi><mn>2</mn></mfrac>
+            </math>"#,
+        );
+        assert_eq!(
+            nodes,
+            vec![MathNode::fraction(
+                MathNode::run("a"),
+                MathNode::number("2")
+            )]
+        );
+    }
+
+    #[test]
+    fn test_parse_namespaced_and_display_block() {
+        let result = parse_mathml(
+            r#"<math:math xmlns:math="http://www.w3.org/1998/Math/MathML" display="block">
+                <math:msup><math:mi>x</math:mi><math:mn>2</math:mn></math:msup>
+            </math:math>"#,
+        )
+        .unwrap();
+        assert_eq!(result.len(), 1);
+        let MathNode::OMathPara(children) = &result[0] else {
+            panic!("Expected OMathPara");
+        };
+        assert_eq!(
+            children[0],
+            MathNode::omath(vec![MathNode::superscript(
+                MathNode::run("x"),
+                MathNode::number("2")
+            )])
+        );
+    }
+
+    #[test]
+    fn test_parse_sum_takes_following_operand() {
+        let nodes = parse_one(
+            "<math><munderover><mo>&#x2211;</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow>\
+             <mi>n</mi></munderover><mi>i</mi></math>",
+        );
+        assert_eq!(nodes.len(), 1);
+        let MathNode::Nary {
+            op,
+            sub_sup_placement,
+            sub,
+            sup,
+            base,
+        } = &nodes[0]
+        else {
+            panic!("Expected Nary");
+        };
+        assert_eq!(*op, symbols::SUM);
+        assert_eq!(*sub_sup_placement, SubSupPlacement::AboveBelow);
+        assert!(sub.is_some() && sup.is_some());
+        assert_eq!(**base, MathNode::run("i"));
+    }
+
+    #[test]
+    fn test_parse_function_application_and_entities() {
+        let nodes = parse_one("<math><mi>sin</mi><mo>&ApplyFunction;</mo><mi>x</mi></math>");
+        assert_eq!(
+            nodes,
+            vec![MathNode::Function {
+                name: "sin".to_string(),
+                base: Box::new(MathNode::run("x")),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_fenced_row_and_mfenced() {
+        let row = parse_one(
+            "<math><mrow><mo>(</mo><mi>a</mi><mo separator=\"true\">,</mo><mi>b</mi><mo>)</mo></mrow></math>",
+        );
+        let fenced = parse_one("<math><mfenced><mi>a</mi><mi>b</mi></mfenced></math>");
+        assert_eq!(row, fenced);
+        let MathNode::Delimiter {
+            open,
+            close,
+            separators,
+            content,
+            ..
+        } = &row[0]
+        else {
+            panic!("Expected Delimiter");
+        };
+        assert_eq!((*open, *close), ('(', ')'));
+        assert_eq!(separators, &vec![',']);
+        assert_eq!(content.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_accents_bars_and_limits() {
+        let nodes = parse_one(
+            "<math><mover accent=\"true\"><mi>x</mi><mo>^</mo></mover>\
+             <mover><mi>y</mi><mo>&#x203E;</mo></mover>\
+             <munder><mi>lim</mi><mrow><mi>x</mi><mo>&#x2192;</mo><mn>0</mn></mrow></munder></math>",
+        );
+        assert!(matches!(
+            nodes[0],
+            MathNode::Accent {
+                accent_char: '\u{0302}',
+                ..
+            }
+        ));
+        assert!(matches!(
+            nodes[1],
+            MathNode::Bar {
+                position: BarPosition::Top,
+                ..
+            }
+        ));
+        assert!(matches!(
+            nodes[2],
+            MathNode::Limit {
+                position: LimitPosition::Lower,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_table_and_mathvariant() {
+        let nodes = parse_one(
+            "<math><mtable><mtr><mtd><mn>1</mn></mtd><mtd><mn>0</mn></mtd></mtr>\
+             <mtr><mtd><mn>0</mn></mtd><mtd><mi mathvariant=\"bold\">x</mi></mtd></mtr></mtable></math>",
+        );
+        let MathNode::Matrix { rows, .. } = &nodes[0] else {
+            panic!("Expected Matrix");
+        };
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1][1], MathNode::styled_run("x", MathStyle::bold()));
+    }
+
+    #[test]
+    fn test_semantics_ignores_annotation() {
+        let nodes = parse_one(
+            "<math><semantics><mi>x</mi><annotation encoding=\"StarMath 5.0\">x</annotation></semantics></math>",
+        );
+        assert_eq!(nodes, vec![MathNode::run("x")]);
+    }
+
+    #[test]
+    fn test_unclosed_element_is_error() {
+        assert!(parse_mathml("<math><mi>x</mi>").is_err());
+    }
+
+    #[test]
+    fn test_write_fraction() {
+        let xml = to_mathml(&MathNode::omath(vec![MathNode::fraction(
+            MathNode::run("a"),
+            MathNode::number("2"),
+        )]))
+        .unwrap();
+        assert_eq!(
+            xml,
+            "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mfrac><mi>a</mi><mn>2</mn></mfrac></math>"
+        );
+    }
+
+    #[test]
+    fn test_write_display_and_escaping() {
+        let xml = to_mathml(&MathNode::omath_para(vec![MathNode::omath(vec![
+            MathNode::run("a"),
+            MathNode::operator('<'),
+            MathNode::run("b"),
+        ])]))
+        .unwrap();
+        assert!(xml.contains("display=\"block\""));
+        assert!(xml.contains("<mo>&lt;</mo>"));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let original = MathNode::omath(vec![
+            MathNode::sum(
+                Some(MathNode::run("i")),
+                Some(MathNode::run("n")),
+                MathNode::superscript(MathNode::run("x"), MathNode::number("2")),
+            ),
+            MathNode::operator('='),
+            MathNode::nthroot(MathNode::number("3"), MathNode::run("y")),
+            MathNode::parens(vec![MathNode::Function {
+                name: "cos".to_string(),
+                base: Box::new(MathNode::run("t")),
+            }]),
+            MathNode::Accent {
+                base: Box::new(MathNode::run("v")),
+                accent_char: '\u{20D7}',
+            },
+            MathNode::underline(MathNode::run("u")),
+            MathNode::styled_run(
+                "R",
+                MathStyle {
+                    font_style: MathFontStyle::DoubleStruck,
+                    ..Default::default()
+                },
+            ),
+        ]);
+
+        let xml = to_mathml(&original).unwrap();
+        let parsed = parse_mathml(&xml).unwrap();
+        assert_eq!(parsed, vec![original]);
+    }
+}
@@ -681,6 +681,12 @@ pub mod symbols {
     pub const DOUBLE_PRIME: char = '\u{2033}';
     pub const DEGREE: char = '\u{00B0}';
     pub const PERCENT: char = '%';
+
+    // Structural markers
+    /// Alignment point inside equation array rows (OMML uses a literal '&')
+    pub const ALIGNMENT_POINT: char = '&';
+    /// Invisible delimiter (e.g., the missing closing side of a cases block)
+    pub const EMPTY_DELIMITER: char = '\u{200B}';
 }
 
 // =============================================================================
@@ -0,0 +1,391 @@
+//! Equation Numbering - Auto-numbered display equations
+//!
+//! This module tracks the sequence of numbered display equations in a document
+//! and produces their labels (e.g., "(3.2)" for the second equation in chapter 3).
+//! It also positions a numbered equation on its line: the equation is centered in
+//! the available width and the number is right-aligned, wrapping below the
+//! equation when both do not fit on one line.
+
+use crate::error::{MathError, MathResult};
+use crate::layout::{LayoutBox, LayoutContent, LayoutEngine, Rect};
+use crate::model::{MathNode, MathStyle};
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// Number Format
+// =============================================================================
+
+/// How an equation number is rendered
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EquationNumberFormat {
+    /// Whether the chapter number is prefixed (e.g., "3.2" instead of "2")
+    pub include_chapter: bool,
+    /// Separator between chapter and equation number
+    pub chapter_separator: String,
+    /// Opening bracket placed before the number
+    pub open: String,
+    /// Closing bracket placed after the number
+    pub close: String,
+}
+
+impl EquationNumberFormat {
+    /// Create the default format: "(n)"
+    pub fn new() -> Self {
+        Self {
+            include_chapter: false,
+            chapter_separator: ".".to_string(),
+            open: "(".to_string(),
+            close: ")".to_string(),
+        }
+    }
+
+    /// Create a per-chapter format: "(c.n)"
+    pub fn per_chapter() -> Self {
+        Self::new().with_chapter(".")
+    }
+
+    /// Enable chapter prefixes with the given separator
+    pub fn with_chapter(mut self, separator: impl Into<String>) -> Self {
+        self.include_chapter = true;
+        self.chapter_separator = separator.into();
+        self
+    }
+
+    /// Set the enclosing brackets
+    pub fn with_brackets(mut self, open: impl Into<String>, close: impl Into<String>) -> Self {
+        self.open = open.into();
+        self.close = close.into();
+        self
+    }
+
+    /// Format the bare number (e.g., "3.2") without brackets
+    pub fn format_number(&self, chapter: Option<u32>, number: u32) -> String {
+        match chapter {
+            Some(ch) if self.include_chapter => {
+                format!("{}{}{}", ch, self.chapter_separator, number)
+            }
+            _ => number.to_string(),
+        }
+    }
+
+    /// Format the full label (e.g., "(3.2)")
+    pub fn format_label(&self, chapter: Option<u32>, number: u32) -> String {
+        format!(
+            "{}{}{}",
+            self.open,
+            self.format_number(chapter, number),
+            self.close
+        )
+    }
+}
+
+impl Default for EquationNumberFormat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// =============================================================================
+// Numbering Sequence
+// =============================================================================
+
+/// A numbered equation entry in document order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NumberedEquation {
+    /// Identifier of the equation (typically the bookmark name used by REF fields)
+    pub id: String,
+    /// Chapter the equation belongs to, if chapter numbering is in use
+    pub chapter: Option<u32>,
+}
+
+/// Tracks numbered equations in document order and assigns their numbers
+///
+/// Numbers restart at 1 whenever the chapter changes when the format includes
+/// the chapter number, matching Word's `SEQ Equation \s 1` behavior.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EquationNumbering {
+    /// Number format
+    pub format: EquationNumberFormat,
+    /// Equations in document order
+    entries: Vec<NumberedEquation>,
+}
+
+impl EquationNumbering {
+    /// Create an empty numbering sequence with the default format
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty numbering sequence with the given format
+    pub fn with_format(format: EquationNumberFormat) -> Self {
+        Self {
+            format,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Append an equation at the end of the document
+    pub fn push(&mut self, id: impl Into<String>, chapter: Option<u32>) -> MathResult<()> {
+        let index = self.entries.len();
+        self.insert(index, id, chapter)
+    }
+
+    /// Insert an equation at the given document-order index
+    pub fn insert(
+        &mut self,
+        index: usize,
+        id: impl Into<String>,
+        chapter: Option<u32>,
+    ) -> MathResult<()> {
+        let id = id.into();
+        if self.contains(&id) {
+            return Err(MathError::InvalidStructure(format!(
+                "Equation '{}' is already numbered",
+                id
+            )));
+        }
+        let index = index.min(self.entries.len());
+        self.entries.insert(index, NumberedEquation { id, chapter });
+        Ok(())
+    }
+
+    /// Remove an equation from the sequence
+    pub fn remove(&mut self, id: &str) -> Option<NumberedEquation> {
+        let pos = self.entries.iter().position(|e| e.id == id)?;
+        Some(self.entries.remove(pos))
+    }
+
+    /// Change the chapter an equation belongs to
+    pub fn set_chapter(&mut self, id: &str, chapter: Option<u32>) -> bool {
+        match self.entries.iter_mut().find(|e| e.id == id) {
+            Some(entry) => {
+                entry.chapter = chapter;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Check whether an equation is numbered
+    pub fn contains(&self, id: &str) -> bool {
+        self.entries.iter().any(|e| e.id == id)
+    }
+
+    /// Get the equations in document order
+    pub fn entries(&self) -> &[NumberedEquation] {
+        &self.entries
+    }
+
+    /// Number of numbered equations
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if there are no numbered equations
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get the sequence number of an equation (1-based, per chapter if enabled)
+    pub fn number_of(&self, id: &str) -> Option<u32> {
+        let pos = self.entries.iter().position(|e| e.id == id)?;
+        let chapter = self.entries[pos].chapter;
+
+        let count = self.entries[..=pos]
+            .iter()
+            .filter(|e| !self.format.include_chapter || e.chapter == chapter)
+            .count();
+        Some(count as u32)
+    }
+
+    /// Get the formatted label of an equation (e.g., "(3.2)")
+    pub fn label_of(&self, id: &str) -> Option<String> {
+        let number = self.number_of(id)?;
+        let chapter = self.entries.iter().find(|e| e.id == id)?.chapter;
+        Some(self.format.format_label(chapter, number))
+    }
+
+    /// Get all labels in document order
+    pub fn labels(&self) -> Vec<(String, String)> {
+        self.entries
+            .iter()
+            .filter_map(|e| self.label_of(&e.id).map(|label| (e.id.clone(), label)))
+            .collect()
+    }
+}
+
+// =============================================================================
+// Numbered Equation Layout
+// =============================================================================
+
+/// Minimum gap between the equation and its number, in points
+const MIN_NUMBER_GAP: f32 = 12.0;
+
+/// Layout of a display equation with its number
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NumberedEquationLayout {
+    /// Container box spanning the available width
+    pub layout: LayoutBox,
+    /// Bounds of the equation within the container
+    pub equation_bounds: Rect,
+    /// Bounds of the number within the container
+    pub number_bounds: Rect,
+    /// Whether the number had to wrap below the equation
+    pub number_wrapped: bool,
+}
+
+impl LayoutEngine {
+    /// Layout a display equation centered in `available_width` with its number
+    /// right-aligned on the same baseline
+    ///
+    /// If the centered equation would collide with the number, the number is
+    /// moved to its own line below the equation, still right-aligned.
+    pub fn layout_numbered(
+        &self,
+        node: &MathNode,
+        label: &str,
+        available_width: f32,
+    ) -> MathResult<NumberedEquationLayout> {
+        let mut equation = self.layout(node)?;
+        let mut number = self.layout(&MathNode::Run {
+            text: label.to_string(),
+            style: MathStyle::normal(),
+        })?;
+
+        let eq_width = equation.width();
+        let num_width = number.width();
+        let eq_x = ((available_width - eq_width) / 2.0).max(0.0);
+        let num_x = (available_width - num_width).max(0.0);
+
+        let number_wrapped = eq_x + eq_width + MIN_NUMBER_GAP > num_x;
+
+        let (eq_y, num_y, height, baseline) = if number_wrapped {
+            let eq_height = equation.height();
+            (
+                0.0,
+                eq_height,
+                eq_height + number.height(),
+                equation.baseline_offset,
+            )
+        } else {
+            let ascent = equation.baseline_offset.max(number.baseline_offset);
+            let descent = (equation.height() - equation.baseline_offset)
+                .max(number.height() - number.baseline_offset);
+            (
+                ascent - equation.baseline_offset,
+                ascent - number.baseline_offset,
+                ascent + descent,
+                ascent,
+            )
+        };
+
+        equation.bounds.origin.x = eq_x;
+        equation.bounds.origin.y = eq_y;
+        number.bounds.origin.x = num_x;
+        number.bounds.origin.y = num_y;
+
+        let equation_bounds = equation.bounds;
+        let number_bounds = number.bounds;
+        let width = available_width.max(eq_width);
+
+        Ok(NumberedEquationLayout {
+            layout: LayoutBox::with_children(
+                Rect::new(0.0, 0.0, width, height),
+                baseline,
+                LayoutContent::Container,
+                vec![equation, number],
+            ),
+            equation_bounds,
+            number_bounds,
+            number_wrapped,
+        })
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_label() {
+        let format = EquationNumberFormat::new();
+        assert_eq!(format.format_label(Some(3), 2), "(2)");
+
+        let format = EquationNumberFormat::per_chapter();
+        assert_eq!(format.format_label(Some(3), 2), "(3.2)");
+        assert_eq!(format.format_label(None, 2), "(2)");
+
+        let format = EquationNumberFormat::per_chapter().with_brackets("[", "]");
+        assert_eq!(format.format_label(Some(1), 4), "[1.4]");
+    }
+
+    #[test]
+    fn test_numbering_restarts_per_chapter() {
+        let mut numbering = EquationNumbering::with_format(EquationNumberFormat::per_chapter());
+        numbering.push("eq1", Some(1)).unwrap();
+        numbering.push("eq2", Some(1)).unwrap();
+        numbering.push("eq3", Some(2)).unwrap();
+
+        assert_eq!(numbering.label_of("eq1").as_deref(), Some("(1.1)"));
+        assert_eq!(numbering.label_of("eq2").as_deref(), Some("(1.2)"));
+        assert_eq!(numbering.label_of("eq3").as_deref(), Some("(2.1)"));
+    }
+
+    #[test]
+    fn test_numbering_without_chapters_is_continuous() {
+        let mut numbering = EquationNumbering::new();
+        numbering.push("a", Some(1)).unwrap();
+        numbering.push("b", Some(2)).unwrap();
+
+        assert_eq!(numbering.number_of("b"), Some(2));
+    }
+
+    #[test]
+    fn test_insert_and_remove_renumber() {
+        let mut numbering = EquationNumbering::new();
+        numbering.push("a", None).unwrap();
+        numbering.push("c", None).unwrap();
+        numbering.insert(1, "b", None).unwrap();
+
+        assert_eq!(numbering.label_of("c").as_deref(), Some("(3)"));
+
+        numbering.remove("a");
+        assert_eq!(numbering.label_of("b").as_deref(), Some("(1)"));
+        assert_eq!(numbering.label_of("c").as_deref(), Some("(2)"));
+        assert!(numbering.label_of("a").is_none());
+    }
+
+    #[test]
+    fn test_duplicate_id_rejected() {
+        let mut numbering = EquationNumbering::new();
+        numbering.push("a", None).unwrap();
+        assert!(numbering.push("a", None).is_err());
+    }
+
+    #[test]
+    fn test_layout_numbered_centers_equation() {
+        let engine = LayoutEngine::new();
+        let node = MathNode::run("x");
+        let result = engine.layout_numbered(&node, "(1.1)", 400.0).unwrap();
+
+        assert!(!result.number_wrapped);
+        let eq_center = result.equation_bounds.center_x();
+        assert!((eq_center - 200.0).abs() < 0.01);
+        assert!((result.number_bounds.right() - 400.0).abs() < 0.01);
+        assert_eq!(result.layout.children.len(), 2);
+    }
+
+    #[test]
+    fn test_layout_numbered_wraps_when_too_wide() {
+        let engine = LayoutEngine::new();
+        let node = MathNode::run("abcdefghijklmnop");
+        let result = engine.layout_numbered(&node, "(12.10)", 100.0).unwrap();
+
+        assert!(result.number_wrapped);
+        assert!(result.number_bounds.y() >= result.equation_bounds.bottom());
+    }
+}
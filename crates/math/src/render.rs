@@ -7,6 +7,7 @@ use crate::error::MathResult;
 use crate::layout::{LayoutBox, LayoutContent, Point, Rect};
 use crate::model::MathFontStyle;
 use serde::{Deserialize, Serialize};
+use text_engine::GlyphStretch;
 
 // =============================================================================
 // Render Primitives
@@ -158,6 +159,17 @@ pub enum RenderPrimitive {
         size: f32,
         color: Color,
     },
+    /// Draw a glyph by its ID in the math font (size variants, assembly parts)
+    FontGlyph {
+        glyph_id: u16,
+        /// Family of the font the glyph ID belongs to
+        font_family: String,
+        /// Face index within the font file (for TTC files)
+        font_index: u32,
+        position: Point,
+        size: f32,
+        color: Color,
+    },
     /// A group of primitives with a transform
     Group {
         transform: Transform,
@@ -410,11 +422,69 @@ impl Renderer {
             LayoutContent::Space => {
                 // Nothing to render
             }
+            LayoutContent::StretchedGlyph { .. } => {
+                self.render_stretched_glyph(layout, pos, primitives);
+                for child in &layout.children {
+                    self.render_box(child, pos, primitives)?;
+                }
+            }
         }
 
         Ok(())
     }
 
+    /// Emit the glyphs of a math font size variant or glyph assembly
+    ///
+    /// Assembly parts are placed from the bottom (vertical) or left
+    /// (horizontal) edge of the box, as ordered in the MATH table.
+    fn render_stretched_glyph(
+        &self,
+        layout: &LayoutBox,
+        pos: Point,
+        primitives: &mut Vec<RenderPrimitive>,
+    ) {
+        let LayoutContent::StretchedGlyph {
+            stretch,
+            vertical,
+            font_size,
+            scale,
+            font_family,
+            font_index,
+            ..
+        } = &layout.content
+        else {
+            return;
+        };
+        let font_family = font_family.as_ref().unwrap_or(&self.config.font_family);
+        let glyph = |glyph_id, position| RenderPrimitive::FontGlyph {
+            glyph_id,
+            font_family: font_family.clone(),
+            font_index: *font_index,
+            position,
+            size: *font_size,
+            color: self.config.color,
+        };
+        match stretch {
+            GlyphStretch::Variant { glyph_id, .. } => {
+                primitives.push(glyph(
+                    *glyph_id,
+                    Point::new(pos.x, pos.y + layout.baseline_offset),
+                ));
+            }
+            GlyphStretch::Assembly { parts, .. } => {
+                for part in parts {
+                    let offset = part.offset * scale;
+                    let position = if *vertical {
+                        Point::new(pos.x, pos.y + layout.height() - offset)
+                    } else {
+                        Point::new(pos.x + offset, pos.y + layout.baseline_offset)
+                    };
+                    primitives.push(glyph(part.glyph_id, position));
+                }
+            }
+        }
+    }
+
     /// Generate path commands for a radical symbol
     fn radical_path(&self, pos: Point, width: f32, height: f32) -> Vec<PathCommand> {
         let x = pos.x;
@@ -630,4 +700,42 @@ mod tests {
         let layout = layout_engine.layout(&node).unwrap();
         let _output = renderer.render(&layout).unwrap();
     }
+
+    #[test]
+    fn test_render_glyph_assembly() {
+        use crate::layout::{LayoutBox, Rect};
+        use text_engine::AssembledPart;
+
+        let part = |glyph_id, offset| AssembledPart { glyph_id, offset };
+        let layout = LayoutBox::new(
+            Rect::new(0.0, 0.0, 5.0, 30.0),
+            18.0,
+            LayoutContent::StretchedGlyph {
+                char: '(',
+                stretch: GlyphStretch::Assembly {
+                    parts: vec![part(1, 0.0), part(2, 1000.0), part(3, 2000.0)],
+                    advance: 3000.0,
+                },
+                vertical: true,
+                font_size: 10.0,
+                scale: 0.01,
+                font_family: Some("STIX Two Math".to_string()),
+                font_index: 0,
+            },
+        );
+        let output = Renderer::new().render(&layout).unwrap();
+
+        let positions: Vec<(u16, f32)> = output
+            .primitives
+            .iter()
+            .filter_map(|p| match p {
+                RenderPrimitive::FontGlyph { glyph_id, position, font_family, .. } => {
+                    assert_eq!(font_family, "STIX Two Math");
+                    Some((*glyph_id, position.y))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(positions, vec![(1, 30.0), (2, 20.0), (3, 10.0)]);
+    }
 }
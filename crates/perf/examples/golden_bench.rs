@@ -0,0 +1,197 @@
+//! Run the regression harness on the golden documents.
+//!
+//! ```text
+//! cargo run -p perf --example golden_bench --release -- [--quick] [--baseline FILE] [--out FILE]
+//! ```
+//!
+//! `--quick` benchmarks only the 10 page document once. The report is
+//! printed as JSON unless `--out` is given. Exits with status 1 if any
+//! result regressed, went over budget or failed.
+
+use doc_model::{
+    DocumentTree, ImageNode, Node, Paragraph, Position, ResourceId, Run, Selection, Table, TableCell, TableRow,
+};
+use edit_engine::{Command, InsertText};
+use layout_engine::Paginator;
+use perf::{BenchOperation, BenchmarkHarness, GoldenDocument, RegressionReport, Workload};
+use render_model::{RenderConfig, RenderConverter};
+use store::pdf::PdfExportOptions;
+
+/// Roughly one page of 60-word paragraphs on Letter paper.
+const PARAGRAPHS_PER_PAGE: u32 = 8;
+const TABLE_COUNT: u32 = 50;
+const TABLE_ROWS: u32 = 20;
+const TABLE_COLUMNS: u32 = 5;
+const IMAGE_COUNT: u32 = 200;
+const KEYSTROKES: usize = 50;
+
+const FILLER: &str = "The quick brown fox jumps over the lazy dog while the committee reviews the quarterly \
+    report, noting that revenue grew in every region and that the new layout engine shortened the time \
+    needed to paginate long documents by a considerable margin across the board this year.";
+
+/// Builds golden documents and runs operations on the real engines.
+struct EngineWorkload {
+    tree: DocumentTree,
+    saved: String,
+    pages: usize,
+}
+
+impl EngineWorkload {
+    fn new() -> Self {
+        Self {
+            tree: DocumentTree::new(),
+            saved: String::new(),
+            pages: 0,
+        }
+    }
+
+    fn add_paragraph(tree: &mut DocumentTree, text: &str) -> Result<doc_model::NodeId, String> {
+        let para_id = tree
+            .insert_paragraph(Paragraph::new(), tree.root_id(), None)
+            .map_err(|e| e.to_string())?;
+        tree.insert_run(Run::new(text), para_id, None).map_err(|e| e.to_string())?;
+        Ok(para_id)
+    }
+
+    fn build(document: GoldenDocument) -> Result<(DocumentTree, usize), String> {
+        let mut tree = DocumentTree::new();
+        let pages = match document {
+            GoldenDocument::Pages(pages) => {
+                for _ in 0..pages * PARAGRAPHS_PER_PAGE {
+                    Self::add_paragraph(&mut tree, FILLER)?;
+                }
+                pages
+            }
+            GoldenDocument::HeavyTables => {
+                for _ in 0..TABLE_COUNT {
+                    Self::add_paragraph(&mut tree, FILLER)?;
+                    let table_id = tree.insert_table(Table::new(), None).map_err(|e| e.to_string())?;
+                    for _ in 0..TABLE_ROWS {
+                        let row_id = tree
+                            .insert_table_row(TableRow::new(), table_id, None)
+                            .map_err(|e| e.to_string())?;
+                        for column in 0..TABLE_COLUMNS {
+                            let cell_id = tree
+                                .insert_table_cell(TableCell::new(), row_id, None)
+                                .map_err(|e| e.to_string())?;
+                            let para_id = tree
+                                .insert_paragraph_into_cell(Paragraph::new(), cell_id, None)
+                                .map_err(|e| e.to_string())?;
+                            tree.insert_run(Run::new(format!("Cell {}", column)), para_id, None)
+                                .map_err(|e| e.to_string())?;
+                        }
+                    }
+                }
+                // About one table per page
+                TABLE_COUNT
+            }
+            GoldenDocument::HeavyImages => {
+                for i in 0..IMAGE_COUNT {
+                    let para_id = Self::add_paragraph(&mut tree, FILLER)?;
+                    let image = ImageNode::new(ResourceId::new(format!("image{}", i)), 1200, 800);
+                    tree.insert_image(image, para_id, None).map_err(|e| e.to_string())?;
+                }
+                // About two images per page
+                IMAGE_COUNT / 2
+            }
+        };
+        Ok((tree, pages as usize))
+    }
+}
+
+impl Workload for EngineWorkload {
+    fn prepare(&mut self, document: GoldenDocument) -> Result<(), String> {
+        let (tree, pages) = Self::build(document)?;
+        self.saved = store::serialize(&tree).map_err(|e| e.to_string())?;
+        self.tree = tree;
+        self.pages = pages;
+        Ok(())
+    }
+
+    fn run(&mut self, operation: BenchOperation) -> Result<usize, String> {
+        match operation {
+            BenchOperation::Open => {
+                self.tree = store::deserialize(&self.saved).map_err(|e| e.to_string())?;
+                Ok(self.pages)
+            }
+            BenchOperation::Type => {
+                let para_id = self
+                    .tree
+                    .paragraphs()
+                    .nth(self.tree.paragraphs().count() / 2)
+                    .map(|para| para.id())
+                    .ok_or("document has no paragraphs")?;
+                let mut selection = Selection::collapsed(Position::new(para_id, 0));
+                for offset in 0..KEYSTROKES {
+                    let command = InsertText::new(Position::new(para_id, offset), "a");
+                    let result = command.apply(&self.tree, &selection).map_err(|e| e.to_string())?;
+                    self.tree = result.tree;
+                    selection = result.selection;
+                }
+                Ok(KEYSTROKES)
+            }
+            BenchOperation::Paginate => {
+                Paginator::letter().layout(&self.tree).map_err(|e| e.to_string())?;
+                Ok(self.tree.paragraphs().count())
+            }
+            BenchOperation::ExportPdf => {
+                let layout = Paginator::letter().layout(&self.tree).map_err(|e| e.to_string())?;
+                let model = RenderConverter::new(RenderConfig::default())
+                    .convert(&layout, &self.tree)
+                    .map_err(|e| e.to_string())?;
+                store::pdf::export_pdf_bytes(&model.pages, PdfExportOptions::default())
+                    .map_err(|e| e.to_string())?;
+                Ok(model.pages.len())
+            }
+        }
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut harness = BenchmarkHarness::new();
+    let mut baseline = None;
+    let mut out = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--quick" => {
+                harness = harness
+                    .with_documents(vec![GoldenDocument::Pages(10)])
+                    .with_iterations(1, 0);
+            }
+            "--baseline" => baseline = args.next(),
+            "--out" => out = args.next(),
+            other => {
+                eprintln!("unknown argument: {}", other);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let mut report = harness.run(&mut EngineWorkload::new());
+    if let Some(path) = baseline {
+        let json = std::fs::read_to_string(&path).expect("failed to read baseline");
+        report.compare_to(&RegressionReport::from_json(&json).expect("invalid baseline"));
+    }
+
+    let json = report.to_json().expect("failed to serialize report");
+    match out {
+        Some(path) => std::fs::write(&path, json).expect("failed to write report"),
+        None => println!("{}", json),
+    }
+
+    for failure in report.failures() {
+        eprintln!(
+            "FAIL {}/{}: {:.3} ms per unit (budget {:.3}, baseline {:?}){}",
+            failure.document,
+            failure.operation,
+            failure.per_unit_ms,
+            failure.budget_ms,
+            failure.baseline_ms,
+            failure.error.as_deref().map(|e| format!(": {}", e)).unwrap_or_default()
+        );
+    }
+    if !report.passed() {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,387 @@
+//! Regression harness for golden benchmark documents
+//!
+//! The harness runs standard operations against a set of synthetic golden
+//! documents, checks the timings against a [`PerfBudget`] and an optional
+//! baseline report, and produces a [`RegressionReport`] that CI can store
+//! and compare against on the next run.
+//!
+//! The perf crate doesn't depend on the document engines, so the work
+//! itself is supplied by a [`Workload`]. The `golden_bench` example
+//! implements one on top of the real engines.
+
+use crate::budget::PerfBudget;
+use crate::metrics::TimingStats;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// A synthetic document used for benchmarking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GoldenDocument {
+    /// Plain text paragraphs filling this many pages
+    Pages(u32),
+    /// Many tables with several rows each
+    HeavyTables,
+    /// Many inline images between paragraphs
+    HeavyImages,
+}
+
+impl GoldenDocument {
+    /// The standard set: 10, 100 and 1000 pages, heavy tables and heavy images.
+    pub fn standard() -> Vec<Self> {
+        vec![
+            GoldenDocument::Pages(10),
+            GoldenDocument::Pages(100),
+            GoldenDocument::Pages(1000),
+            GoldenDocument::HeavyTables,
+            GoldenDocument::HeavyImages,
+        ]
+    }
+
+    /// Stable name used in reports.
+    pub fn name(&self) -> String {
+        match self {
+            GoldenDocument::Pages(pages) => format!("pages_{}", pages),
+            GoldenDocument::HeavyTables => "heavy_tables".to_string(),
+            GoldenDocument::HeavyImages => "heavy_images".to_string(),
+        }
+    }
+}
+
+/// An operation timed by the harness.
+///
+/// Each operation reports how many units it processed, and its budget
+/// applies per unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BenchOperation {
+    /// Load the document from its saved form (units: pages)
+    Open,
+    /// Type text one keystroke at a time (units: keystrokes)
+    Type,
+    /// Lay out the whole document (units: paragraphs)
+    Paginate,
+    /// Render and export the document to PDF (units: pages)
+    ExportPdf,
+}
+
+impl BenchOperation {
+    /// All operations, in the order they are run.
+    pub const ALL: [BenchOperation; 4] = [
+        BenchOperation::Open,
+        BenchOperation::Type,
+        BenchOperation::Paginate,
+        BenchOperation::ExportPdf,
+    ];
+
+    /// Stable name used in reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BenchOperation::Open => "open",
+            BenchOperation::Type => "type",
+            BenchOperation::Paginate => "paginate",
+            BenchOperation::ExportPdf => "export_pdf",
+        }
+    }
+
+    /// The per-unit budget for this operation.
+    pub fn budget_ms(&self, budget: &PerfBudget) -> f64 {
+        match self {
+            BenchOperation::Open => budget.max_command_time_ms,
+            BenchOperation::Type => budget.max_input_latency_ms,
+            BenchOperation::Paginate => budget.max_layout_time_ms,
+            BenchOperation::ExportPdf => budget.max_render_time_ms,
+        }
+    }
+}
+
+/// The work measured by the harness.
+pub trait Workload {
+    /// Build a golden document so operations can run against it.
+    ///
+    /// Called before each operation; not timed.
+    fn prepare(&mut self, document: GoldenDocument) -> Result<(), String>;
+
+    /// Run an operation once, returning the number of units processed.
+    fn run(&mut self, operation: BenchOperation) -> Result<usize, String>;
+}
+
+/// Timings for one operation on one document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkResult {
+    /// Document name
+    pub document: String,
+    /// Operation name
+    pub operation: String,
+    /// Statistics over the timed iterations
+    pub stats: TimingStats,
+    /// Units processed per iteration
+    pub units: usize,
+    /// Median time per unit in milliseconds
+    pub per_unit_ms: f64,
+    /// Budget per unit in milliseconds
+    pub budget_ms: f64,
+    /// Whether the per-unit time is within budget
+    pub within_budget: bool,
+    /// Per-unit time in the baseline report, if any
+    pub baseline_ms: Option<f64>,
+    /// Change from the baseline in percent
+    pub change_percent: Option<f64>,
+    /// Whether the change exceeds the regression threshold
+    pub regressed: bool,
+    /// Error if the operation failed
+    pub error: Option<String>,
+}
+
+impl BenchmarkResult {
+    fn key(&self) -> (String, String) {
+        (self.document.clone(), self.operation.clone())
+    }
+
+    /// Whether the result should fail a CI run.
+    pub fn failed(&self) -> bool {
+        self.error.is_some() || self.regressed || !self.within_budget
+    }
+}
+
+/// Machine-readable results of a harness run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegressionReport {
+    /// When the run finished, in seconds since the Unix epoch
+    pub generated_at: u64,
+    /// Timed iterations per operation
+    pub iterations: usize,
+    /// Threshold in percent above the baseline that counts as a regression
+    pub regression_threshold_percent: f64,
+    /// One result per document and operation
+    pub results: Vec<BenchmarkResult>,
+}
+
+impl RegressionReport {
+    /// Compare against a baseline report, marking slower results as regressed.
+    pub fn compare_to(&mut self, baseline: &RegressionReport) {
+        let baseline: HashMap<_, _> = baseline
+            .results
+            .iter()
+            .filter(|r| r.error.is_none())
+            .map(|r| (r.key(), r.per_unit_ms))
+            .collect();
+
+        for result in &mut self.results {
+            let Some(&base) = baseline.get(&result.key()) else {
+                continue;
+            };
+            result.baseline_ms = Some(base);
+            if base > 0.0 && result.error.is_none() {
+                let change = (result.per_unit_ms - base) / base * 100.0;
+                result.change_percent = Some(change);
+                result.regressed = change > self.regression_threshold_percent;
+            }
+        }
+    }
+
+    /// Results that regressed, went over budget or failed.
+    pub fn failures(&self) -> Vec<&BenchmarkResult> {
+        self.results.iter().filter(|r| r.failed()).collect()
+    }
+
+    /// Whether every result passed.
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|r| !r.failed())
+    }
+
+    /// Serialize to pretty JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Parse a report, e.g. a stored baseline.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Runs operations on golden documents and reports the timings.
+#[derive(Debug, Clone)]
+pub struct BenchmarkHarness {
+    documents: Vec<GoldenDocument>,
+    operations: Vec<BenchOperation>,
+    iterations: usize,
+    warmup: usize,
+    budget: PerfBudget,
+    regression_threshold_percent: f64,
+}
+
+impl BenchmarkHarness {
+    /// Create a harness for the standard documents and all operations.
+    pub fn new() -> Self {
+        Self {
+            documents: GoldenDocument::standard(),
+            operations: BenchOperation::ALL.to_vec(),
+            iterations: 3,
+            warmup: 1,
+            budget: PerfBudget::default(),
+            regression_threshold_percent: 10.0,
+        }
+    }
+
+    /// Set the documents to benchmark.
+    pub fn with_documents(mut self, documents: Vec<GoldenDocument>) -> Self {
+        self.documents = documents;
+        self
+    }
+
+    /// Set the operations to run.
+    pub fn with_operations(mut self, operations: Vec<BenchOperation>) -> Self {
+        self.operations = operations;
+        self
+    }
+
+    /// Set the number of timed and warmup iterations.
+    pub fn with_iterations(mut self, iterations: usize, warmup: usize) -> Self {
+        self.iterations = iterations.max(1);
+        self.warmup = warmup;
+        self
+    }
+
+    /// Set the budget results are checked against.
+    pub fn with_budget(mut self, budget: PerfBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Set how much slower than the baseline, in percent, counts as a regression.
+    pub fn with_regression_threshold(mut self, percent: f64) -> Self {
+        self.regression_threshold_percent = percent;
+        self
+    }
+
+    /// Run every operation on every document.
+    pub fn run<W: Workload>(&self, workload: &mut W) -> RegressionReport {
+        let mut results = Vec::new();
+        for &document in &self.documents {
+            for &operation in &self.operations {
+                results.push(self.run_one(workload, document, operation));
+            }
+        }
+
+        RegressionReport {
+            generated_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            iterations: self.iterations,
+            regression_threshold_percent: self.regression_threshold_percent,
+            results,
+        }
+    }
+
+    fn run_one<W: Workload>(
+        &self,
+        workload: &mut W,
+        document: GoldenDocument,
+        operation: BenchOperation,
+    ) -> BenchmarkResult {
+        let budget_ms = operation.budget_ms(&self.budget);
+        let mut result = BenchmarkResult {
+            document: document.name(),
+            operation: operation.name().to_string(),
+            stats: TimingStats::default(),
+            units: 0,
+            per_unit_ms: 0.0,
+            budget_ms,
+            within_budget: true,
+            baseline_ms: None,
+            change_percent: None,
+            regressed: false,
+            error: None,
+        };
+
+        let mut samples = Vec::with_capacity(self.iterations);
+        for i in 0..self.warmup + self.iterations {
+            let timed = (|| {
+                workload.prepare(document)?;
+                let start = Instant::now();
+                let units = workload.run(operation)?;
+                Ok::<_, String>((start.elapsed().as_secs_f64() * 1000.0, units))
+            })();
+            match timed {
+                Ok((ms, units)) if i >= self.warmup => {
+                    samples.push(ms);
+                    result.units = units;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    result.error = Some(e);
+                    return result;
+                }
+            }
+        }
+
+        result.stats = TimingStats::from_samples(&samples);
+        result.per_unit_ms = result.stats.median_ms / result.units.max(1) as f64;
+        result.within_budget = result.per_unit_ms <= budget_ms;
+        result
+    }
+}
+
+impl Default for BenchmarkHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sleeps in proportion to the document size.
+    struct SleepWorkload {
+        pages: u32,
+        scale: f64,
+    }
+
+    impl Workload for SleepWorkload {
+        fn prepare(&mut self, document: GoldenDocument) -> Result<(), String> {
+            self.pages = match document {
+                GoldenDocument::Pages(pages) => pages,
+                _ => return Err("unsupported".to_string()),
+            };
+            Ok(())
+        }
+
+        fn run(&mut self, _operation: BenchOperation) -> Result<usize, String> {
+            std::thread::sleep(std::time::Duration::from_secs_f64(self.pages as f64 * self.scale / 1000.0));
+            Ok(self.pages as usize)
+        }
+    }
+
+    #[test]
+    fn test_harness_budget_and_regressions() {
+        let harness = BenchmarkHarness::new()
+            .with_documents(vec![GoldenDocument::Pages(2), GoldenDocument::HeavyTables])
+            .with_operations(vec![BenchOperation::Paginate])
+            .with_iterations(2, 0)
+            .with_budget(PerfBudget::new(50.0, 50.0, 50.0, 50.0));
+
+        let baseline = harness.run(&mut SleepWorkload { pages: 0, scale: 1.0 });
+        assert_eq!(baseline.results.len(), 2);
+        assert!(baseline.results[0].within_budget);
+        assert_eq!(baseline.results[0].units, 2);
+        assert!(baseline.results[1].error.is_some());
+        assert!(!baseline.passed());
+
+        let json = baseline.to_json().unwrap();
+        let baseline = RegressionReport::from_json(&json).unwrap();
+
+        let mut report = harness.run(&mut SleepWorkload { pages: 0, scale: 5.0 });
+        report.compare_to(&baseline);
+        let result = &report.results[0];
+        assert!(result.baseline_ms.is_some());
+        assert!(result.regressed, "{:?}", result.change_percent);
+        assert_eq!(report.failures().len(), 2);
+    }
+}
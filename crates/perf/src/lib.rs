@@ -4,6 +4,8 @@
 //! - Timing utilities with RAII-based scope timing
 //! - Metrics collection for commands, layout, rendering, and input latency
 //! - Performance budgets with violation detection
+//! - A per-frame timeline correlating commands with layout and render times
+//! - A regression harness that benchmarks golden documents against the budget
 //!
 //! # Feature Flags
 //!
@@ -28,10 +30,14 @@
 mod timing;
 mod metrics;
 mod budget;
+mod harness;
+mod timeline;
 
 pub use timing::*;
 pub use metrics::*;
 pub use budget::*;
+pub use harness::*;
+pub use timeline::*;
 
 /// Re-export for convenience
 pub use std::time::{Duration, Instant};
@@ -1,6 +1,7 @@
 //! Metrics collection for performance measurement
 
 use crate::budget::{BudgetViolation, PerfBudget};
+use crate::timeline::{FrameRecord, FrameTimeline};
 use crate::timing::TimerCategory;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -45,6 +46,8 @@ pub struct PerfMetrics {
     max_samples: usize,
     /// Whether metrics collection is enabled
     enabled: bool,
+    /// Per-frame timeline of the same timings
+    timeline: FrameTimeline,
 }
 
 impl PerfMetrics {
@@ -59,6 +62,7 @@ impl PerfMetrics {
             budget: PerfBudget::default(),
             max_samples: 1000,
             enabled: true,
+            timeline: FrameTimeline::default(),
         }
     }
 
@@ -76,6 +80,12 @@ impl PerfMetrics {
         self
     }
 
+    /// Set the number of frames kept in the timeline.
+    pub fn with_timeline_frames(mut self, frames: usize) -> Self {
+        self.timeline = FrameTimeline::new(frames);
+        self
+    }
+
     /// Enable or disable metrics collection.
     pub fn set_enabled(&mut self, enabled: bool) {
         self.enabled = enabled;
@@ -122,6 +132,7 @@ impl PerfMetrics {
             times.remove(0);
         }
         times.push(duration_ms);
+        self.timeline.record_command(name, duration_ms);
 
         tracing::trace!(
             target: "perf::command",
@@ -141,6 +152,7 @@ impl PerfMetrics {
             self.layout_times.remove(0);
         }
         self.layout_times.push(duration_ms);
+        self.timeline.record_layout(duration_ms);
 
         tracing::trace!(
             target: "perf::layout",
@@ -150,6 +162,9 @@ impl PerfMetrics {
     }
 
     /// Record a render time.
+    ///
+    /// A render completes a frame, so this also closes the current frame
+    /// in the timeline.
     pub fn record_render(&mut self, duration_ms: f64) {
         if !self.enabled {
             return;
@@ -159,6 +174,8 @@ impl PerfMetrics {
             self.render_times.remove(0);
         }
         self.render_times.push(duration_ms);
+        self.timeline.record_render(duration_ms);
+        self.timeline.end_frame();
 
         tracing::trace!(
             target: "perf::render",
@@ -177,6 +194,7 @@ impl PerfMetrics {
             self.input_latencies.remove(0);
         }
         self.input_latencies.push(duration_ms);
+        self.timeline.record_input_latency(duration_ms);
 
         tracing::trace!(
             target: "perf::input",
@@ -282,6 +300,24 @@ impl PerfMetrics {
         violations
     }
 
+    /// Close the current frame without a render, e.g. for frames where
+    /// nothing was redrawn.
+    pub fn end_frame(&mut self) {
+        if self.enabled {
+            self.timeline.end_frame();
+        }
+    }
+
+    /// Get the frame timeline.
+    pub fn timeline(&self) -> &FrameTimeline {
+        &self.timeline
+    }
+
+    /// Get frames numbered `frame` or later.
+    pub fn frames_since(&self, frame: u64) -> Vec<FrameRecord> {
+        self.timeline.frames_since(frame)
+    }
+
     /// Get the current performance budget.
     pub fn budget(&self) -> &PerfBudget {
         &self.budget
@@ -299,6 +335,7 @@ impl PerfMetrics {
         self.render_times.clear();
         self.input_latencies.clear();
         self.general_times.clear();
+        self.timeline.clear();
     }
 
     /// Get raw command times.
@@ -517,6 +554,27 @@ mod tests {
         assert_eq!(metrics.layout_times, vec![2.0, 3.0, 4.0]);
     }
 
+    #[test]
+    fn test_metrics_frame_timeline() {
+        let mut metrics = PerfMetrics::new();
+        metrics.record_input_latency(12.0);
+        metrics.record_command("bold", 2.0);
+        metrics.record_layout(4.0);
+        metrics.record_render(8.0);
+        metrics.record_command("italic", 1.0);
+        metrics.end_frame();
+
+        let frames = metrics.frames_since(0);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].commands[0].name, "bold");
+        assert_eq!(frames[0].render_ms, 8.0);
+        assert_eq!(frames[1].commands[0].name, "italic");
+        assert_eq!(frames[1].input_latency_ms, None);
+
+        metrics.reset();
+        assert!(metrics.timeline().is_empty());
+    }
+
     #[test]
     fn test_metrics_disabled() {
         let mut metrics = PerfMetrics::new();
@@ -0,0 +1,191 @@
+//! Per-frame timeline of recorded timings
+//!
+//! Aggregate statistics hide which command caused a slow frame. The
+//! timeline keeps the last few hundred frames, each with the commands run
+//! during it and its layout, render and input latency times, so a
+//! performance HUD can plot them as a time series.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Default number of frames kept in the timeline.
+pub const DEFAULT_TIMELINE_FRAMES: usize = 300;
+
+/// A command executed during a frame.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameCommand {
+    /// Command name
+    pub name: String,
+    /// Execution time in milliseconds
+    pub duration_ms: f64,
+}
+
+/// Timings recorded during one frame.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameRecord {
+    /// Frame number, increasing from zero
+    pub frame: u64,
+    /// When the frame ended, in milliseconds since the timeline started
+    pub timestamp_ms: f64,
+    /// Commands executed during the frame
+    pub commands: Vec<FrameCommand>,
+    /// Total layout time in milliseconds
+    pub layout_ms: f64,
+    /// Total render time in milliseconds
+    pub render_ms: f64,
+    /// Worst input latency in milliseconds, if input arrived
+    pub input_latency_ms: Option<f64>,
+}
+
+impl FrameRecord {
+    /// Total time spent in commands, layout and rendering.
+    pub fn total_ms(&self) -> f64 {
+        self.commands.iter().map(|c| c.duration_ms).sum::<f64>() + self.layout_ms + self.render_ms
+    }
+
+    fn is_empty(&self) -> bool {
+        self.commands.is_empty() && self.layout_ms == 0.0 && self.render_ms == 0.0 && self.input_latency_ms.is_none()
+    }
+}
+
+/// Ring buffer of the most recent frames.
+///
+/// Timings are added to the current frame until [`FrameTimeline::end_frame`]
+/// is called, which moves it into the buffer.
+#[derive(Debug, Clone)]
+pub struct FrameTimeline {
+    frames: VecDeque<FrameRecord>,
+    current: FrameRecord,
+    capacity: usize,
+    origin: Instant,
+}
+
+impl FrameTimeline {
+    /// Create a timeline keeping up to `capacity` frames.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            frames: VecDeque::with_capacity(capacity),
+            current: FrameRecord::default(),
+            capacity: capacity.max(1),
+            origin: Instant::now(),
+        }
+    }
+
+    /// Add a command to the current frame.
+    pub fn record_command(&mut self, name: &str, duration_ms: f64) {
+        self.current.commands.push(FrameCommand {
+            name: name.to_string(),
+            duration_ms,
+        });
+    }
+
+    /// Add layout time to the current frame.
+    pub fn record_layout(&mut self, duration_ms: f64) {
+        self.current.layout_ms += duration_ms;
+    }
+
+    /// Add render time to the current frame.
+    pub fn record_render(&mut self, duration_ms: f64) {
+        self.current.render_ms += duration_ms;
+    }
+
+    /// Record input latency in the current frame, keeping the worst.
+    pub fn record_input_latency(&mut self, duration_ms: f64) {
+        let latency = self.current.input_latency_ms.get_or_insert(duration_ms);
+        *latency = latency.max(duration_ms);
+    }
+
+    /// Close the current frame and start the next one.
+    ///
+    /// Frames with nothing recorded are dropped.
+    pub fn end_frame(&mut self) {
+        if self.current.is_empty() {
+            return;
+        }
+        let next = self.current.frame + 1;
+        let mut frame = std::mem::replace(&mut self.current, FrameRecord { frame: next, ..Default::default() });
+        frame.timestamp_ms = self.origin.elapsed().as_secs_f64() * 1000.0;
+
+        if self.frames.len() >= self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    /// The recorded frames, oldest first.
+    pub fn frames(&self) -> impl Iterator<Item = &FrameRecord> {
+        self.frames.iter()
+    }
+
+    /// Frames numbered `frame` or later, for polling.
+    pub fn frames_since(&self, frame: u64) -> Vec<FrameRecord> {
+        self.frames.iter().filter(|f| f.frame >= frame).cloned().collect()
+    }
+
+    /// The most recent complete frame.
+    pub fn last_frame(&self) -> Option<&FrameRecord> {
+        self.frames.back()
+    }
+
+    /// Number of frames kept.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Check if no frames have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Remove all frames.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.current = FrameRecord {
+            frame: self.current.frame,
+            ..Default::default()
+        };
+    }
+}
+
+impl Default for FrameTimeline {
+    fn default() -> Self {
+        Self::new(DEFAULT_TIMELINE_FRAMES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeline_frames() {
+        let mut timeline = FrameTimeline::new(2);
+        timeline.record_input_latency(4.0);
+        timeline.record_input_latency(9.0);
+        timeline.record_command("insert_text", 1.5);
+        timeline.record_layout(3.0);
+        timeline.record_render(6.0);
+        timeline.end_frame();
+
+        // Empty frames are skipped
+        timeline.end_frame();
+        assert_eq!(timeline.len(), 1);
+
+        let frame = timeline.last_frame().unwrap();
+        assert_eq!(frame.frame, 0);
+        assert_eq!(frame.commands[0].name, "insert_text");
+        assert_eq!(frame.input_latency_ms, Some(9.0));
+        assert_eq!(frame.total_ms(), 10.5);
+
+        for _ in 0..2 {
+            timeline.record_render(1.0);
+            timeline.end_frame();
+        }
+        let numbers: Vec<_> = timeline.frames().map(|f| f.frame).collect();
+        assert_eq!(numbers, [1, 2]);
+        assert_eq!(timeline.frames_since(2).len(), 1);
+    }
+}
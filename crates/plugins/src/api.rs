@@ -21,8 +21,11 @@
 //! let snapshot = ctx.get_content()?;
 //! ```
 
+use crate::live_document::LiveDocument;
 use crate::manifest::Permission;
+use crate::messages::host_methods;
 use crate::permissions::PermissionManager;
+use crate::sandbox::ApiRestrictions;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
@@ -213,8 +216,10 @@ impl Selection {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Position {
     /// Line number (0-indexed)
+    #[serde(default)]
     pub line: usize,
     /// Column/character offset within the line (0-indexed)
+    #[serde(default)]
     pub column: usize,
     /// Absolute character offset from document start
     pub offset: usize,
@@ -1039,8 +1044,12 @@ pub struct PluginApiContext {
     plugin_id: String,
     /// Permission manager for checking permissions
     permissions: Arc<RwLock<PermissionManager>>,
-    /// Document state (mock for now)
+    /// Document state used when no live document is attached
     document: Arc<RwLock<MockDocument>>,
+    /// The editor's document, when attached
+    live_document: Option<LiveDocument>,
+    /// Sandbox API restrictions, checked in addition to permissions
+    restrictions: Option<ApiRestrictions>,
     /// Registered commands
     commands: Arc<RwLock<HashMap<String, CommandHandler>>>,
     /// Toolbar items
@@ -1111,6 +1120,8 @@ impl PluginApiContext {
             plugin_id: plugin_id.into(),
             permissions,
             document: Arc::new(RwLock::new(MockDocument::default())),
+            live_document: None,
+            restrictions: None,
             commands: Arc::new(RwLock::new(HashMap::new())),
             toolbar_items: Arc::new(RwLock::new(HashMap::new())),
             panels: Arc::new(RwLock::new(HashMap::new())),
@@ -1144,6 +1155,8 @@ impl PluginApiContext {
             plugin_id,
             permissions: Arc::new(RwLock::new(permissions)),
             document: Arc::new(RwLock::new(MockDocument::default())),
+            live_document: None,
+            restrictions: None,
             commands: Arc::new(RwLock::new(HashMap::new())),
             toolbar_items: Arc::new(RwLock::new(HashMap::new())),
             panels: Arc::new(RwLock::new(HashMap::new())),
@@ -1158,6 +1171,16 @@ impl PluginApiContext {
         *self.document.write().unwrap() = document;
     }
 
+    /// Attach the editor's document; document calls go to it instead of the mock
+    pub fn set_live_document(&mut self, document: LiveDocument) {
+        self.live_document = Some(document);
+    }
+
+    /// Restrict document calls to the methods allowed by the sandbox
+    pub fn set_restrictions(&mut self, restrictions: ApiRestrictions) {
+        self.restrictions = Some(restrictions);
+    }
+
     /// Get the plugin ID
     pub fn plugin_id(&self) -> &str {
         &self.plugin_id
@@ -1179,6 +1202,21 @@ impl PluginApiContext {
         }
     }
 
+    /// Check a permission and, if sandboxed, that the API method is allowed
+    fn check_access(&self, method: &str, permission: Permission) -> ApiResult<()> {
+        self.check_permission(permission)?;
+
+        match &self.restrictions {
+            Some(restrictions) if !restrictions.is_method_allowed(method) => {
+                Err(ApiError::PermissionDenied(format!(
+                    "Plugin '{}' is not allowed to call '{}'",
+                    self.plugin_id, method
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Validate a position is within document bounds
     fn validate_position(&self, position: &Position) -> ApiResult<()> {
         let doc = self.document.read().map_err(|e| {
@@ -1220,7 +1258,10 @@ impl PluginApiContext {
 
 impl DocumentApi for PluginApiContext {
     fn get_content(&self) -> ApiResult<DocumentSnapshot> {
-        self.check_permission(Permission::DocumentRead)?;
+        self.check_access(host_methods::GET_DOCUMENT, Permission::DocumentRead)?;
+        if let Some(live) = &self.live_document {
+            return live.snapshot();
+        }
 
         let doc = self.document.read().map_err(|e| {
             ApiError::Internal(format!("Failed to read document: {}", e))
@@ -1230,7 +1271,10 @@ impl DocumentApi for PluginApiContext {
     }
 
     fn get_selection(&self) -> ApiResult<Selection> {
-        self.check_permission(Permission::DocumentRead)?;
+        self.check_access(host_methods::GET_SELECTION, Permission::DocumentRead)?;
+        if let Some(live) = &self.live_document {
+            return live.selection();
+        }
 
         let doc = self.document.read().map_err(|e| {
             ApiError::Internal(format!("Failed to read document: {}", e))
@@ -1240,7 +1284,10 @@ impl DocumentApi for PluginApiContext {
     }
 
     fn insert_text(&mut self, position: Position, text: &str) -> ApiResult<()> {
-        self.check_permission(Permission::DocumentWrite)?;
+        self.check_access(host_methods::INSERT_TEXT, Permission::DocumentWrite)?;
+        if let Some(live) = &self.live_document {
+            return live.insert_text(&position, text);
+        }
         self.validate_position(&position)?;
 
         let mut doc = self.document.write().map_err(|e| {
@@ -1252,7 +1299,10 @@ impl DocumentApi for PluginApiContext {
     }
 
     fn apply_style(&mut self, range: Range, style_id: &str) -> ApiResult<()> {
-        self.check_permission(Permission::DocumentWrite)?;
+        self.check_access(host_methods::FORMAT, Permission::DocumentWrite)?;
+        if let Some(live) = &self.live_document {
+            return live.apply_style(&range, style_id);
+        }
         self.validate_range(&range)?;
 
         let mut doc = self.document.write().map_err(|e| {
@@ -1267,7 +1317,12 @@ impl DocumentApi for PluginApiContext {
     }
 
     fn insert_image(&mut self, position: Position, image_data: &[u8]) -> ApiResult<()> {
-        self.check_permission(Permission::DocumentWrite)?;
+        self.check_access(host_methods::INSERT_TEXT, Permission::DocumentWrite)?;
+        if self.live_document.is_some() {
+            return Err(ApiError::OperationFailed(
+                "Image insertion is not supported on live documents".to_string(),
+            ));
+        }
         self.validate_position(&position)?;
 
         if image_data.is_empty() {
@@ -1286,7 +1341,10 @@ impl DocumentApi for PluginApiContext {
     }
 
     fn delete_text(&mut self, range: Range) -> ApiResult<()> {
-        self.check_permission(Permission::DocumentWrite)?;
+        self.check_access(host_methods::DELETE_TEXT, Permission::DocumentWrite)?;
+        if let Some(live) = &self.live_document {
+            return live.delete_text(&range);
+        }
         self.validate_range(&range)?;
 
         let mut doc = self.document.write().map_err(|e| {
@@ -1298,7 +1356,10 @@ impl DocumentApi for PluginApiContext {
     }
 
     fn replace_text(&mut self, range: Range, text: &str) -> ApiResult<()> {
-        self.check_permission(Permission::DocumentWrite)?;
+        self.check_access(host_methods::REPLACE_TEXT, Permission::DocumentWrite)?;
+        if let Some(live) = &self.live_document {
+            return live.replace_text(&range, text);
+        }
         self.validate_range(&range)?;
 
         let mut doc = self.document.write().map_err(|e| {
@@ -1310,7 +1371,10 @@ impl DocumentApi for PluginApiContext {
     }
 
     fn get_text(&self, range: Range) -> ApiResult<String> {
-        self.check_permission(Permission::DocumentRead)?;
+        self.check_access(host_methods::GET_TEXT, Permission::DocumentRead)?;
+        if let Some(live) = &self.live_document {
+            return live.text(&range);
+        }
         self.validate_range(&range)?;
 
         let doc = self.document.read().map_err(|e| {
@@ -1321,7 +1385,10 @@ impl DocumentApi for PluginApiContext {
     }
 
     fn set_selection(&mut self, selection: Selection) -> ApiResult<()> {
-        self.check_permission(Permission::DocumentWrite)?;
+        self.check_access(host_methods::SET_SELECTION, Permission::DocumentWrite)?;
+        if let Some(live) = &self.live_document {
+            return live.set_selection(&selection);
+        }
         self.validate_position(&selection.start)?;
         self.validate_position(&selection.end)?;
 
@@ -1334,11 +1401,14 @@ impl DocumentApi for PluginApiContext {
     }
 
     fn search(&self, query: &str, options: SearchOptions) -> ApiResult<Vec<Range>> {
-        self.check_permission(Permission::DocumentRead)?;
+        self.check_access(host_methods::SEARCH, Permission::DocumentRead)?;
 
         if query.is_empty() {
             return Err(ApiError::InvalidArgument("Search query cannot be empty".to_string()));
         }
+        if let Some(live) = &self.live_document {
+            return live.search(query, &options);
+        }
 
         let doc = self.document.read().map_err(|e| {
             ApiError::Internal(format!("Failed to read document: {}", e))
@@ -1373,6 +1443,56 @@ impl DocumentApi for PluginApiContext {
     }
 }
 
+/// Read a named parameter from a protocol request
+fn request_param<T: serde::de::DeserializeOwned>(params: Option<&Value>, key: &str) -> ApiResult<T> {
+    let value = params
+        .and_then(|params| params.get(key))
+        .ok_or_else(|| ApiError::InvalidArgument(format!("Missing parameter '{}'", key)))?;
+    serde_json::from_value(value.clone())
+        .map_err(|e| ApiError::InvalidArgument(format!("Invalid parameter '{}': {}", key, e)))
+}
+
+impl PluginApiContext {
+    /// Handle a document request from the message protocol
+    ///
+    /// `method` is one of the `host_methods` document or selection methods;
+    /// the reply is the call's result as JSON.
+    pub fn handle_document_request(&mut self, method: &str, params: Option<&Value>) -> ApiResult<Value> {
+        fn json<T: Serialize>(value: T) -> ApiResult<Value> {
+            serde_json::to_value(value).map_err(|e| ApiError::Internal(e.to_string()))
+        }
+
+        match method {
+            host_methods::GET_DOCUMENT => json(self.get_content()?),
+            host_methods::GET_SELECTION => json(self.get_selection()?),
+            host_methods::SET_SELECTION => {
+                let range: Range = request_param(params, "range")?;
+                json(self.set_selection(Selection::new(range.start, range.end, ""))?)
+            }
+            host_methods::GET_TEXT => json(self.get_text(request_param(params, "range")?)?),
+            host_methods::INSERT_TEXT => {
+                let text: String = request_param(params, "text")?;
+                json(self.insert_text(request_param(params, "position")?, &text)?)
+            }
+            host_methods::DELETE_TEXT => json(self.delete_text(request_param(params, "range")?)?),
+            host_methods::REPLACE_TEXT => {
+                let text: String = request_param(params, "text")?;
+                json(self.replace_text(request_param(params, "range")?, &text)?)
+            }
+            host_methods::FORMAT => {
+                let style_id: String = request_param(params, "styleId")?;
+                json(self.apply_style(request_param(params, "range")?, &style_id)?)
+            }
+            host_methods::SEARCH => {
+                let query: String = request_param(params, "query")?;
+                let options = request_param(params, "options").unwrap_or_default();
+                json(self.search(&query, options)?)
+            }
+            _ => Err(ApiError::NotFound(format!("Unknown document method '{}'", method))),
+        }
+    }
+}
+
 impl CommandApi for PluginApiContext {
     fn register_command(
         &mut self,
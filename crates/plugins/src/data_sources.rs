@@ -0,0 +1,125 @@
+//! Plugin-contributed mail merge data sources
+//!
+//! Plugins declare providers under `contributes.dataSources`. Each one
+//! becomes a `mail_merge::DataSourceProvider` that forwards requests to the
+//! plugin:
+//!
+//! - `dataSource.listColumns` receives `{ "provider", "config" }` and
+//!   answers `{ "columns": [{ "name", "dataType", "displayName" }] }`.
+//! - `dataSource.fetchRecords` receives `{ "provider", "config", "offset",
+//!   "limit" }` and answers `{ "records": [{ <column>: <value> }] }`. A page
+//!   shorter than `limit` ends the data.
+
+use crate::file_formats::PluginCall;
+use crate::manifest::DataSourceContribution;
+use crate::messages::plugin_methods;
+use mail_merge::{
+    ColumnDef, DataSourceProvider, DataType, MailMergeError, ProviderDescriptor, Record, Value,
+};
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+
+impl DataSourceContribution {
+    /// Describe the provider for the mail merge provider registry
+    pub fn descriptor(&self, plugin_id: &str) -> ProviderDescriptor {
+        ProviderDescriptor::new(&self.id, &self.name, plugin_id)
+    }
+}
+
+/// Column as described by a plugin
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ColumnReply {
+    name: String,
+    #[serde(default)]
+    data_type: Option<DataType>,
+    #[serde(default)]
+    display_name: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+impl From<ColumnReply> for ColumnDef {
+    fn from(reply: ColumnReply) -> Self {
+        ColumnDef {
+            name: reply.name,
+            data_type: reply.data_type.unwrap_or(DataType::Text),
+            display_name: reply.display_name,
+            description: reply.description,
+        }
+    }
+}
+
+/// Data source provider that delegates to a plugin
+pub struct PluginDataSourceProvider {
+    plugin_id: String,
+    provider_id: String,
+    call: PluginCall,
+}
+
+impl PluginDataSourceProvider {
+    pub fn new(plugin_id: impl Into<String>, provider_id: impl Into<String>, call: PluginCall) -> Self {
+        Self {
+            plugin_id: plugin_id.into(),
+            provider_id: provider_id.into(),
+            call,
+        }
+    }
+
+    fn request(&self, method: &str, params: JsonValue) -> mail_merge::Result<JsonValue> {
+        (self.call)(&self.plugin_id, method, params).map_err(|e| {
+            MailMergeError::InvalidDataSource(format!("Plugin {} failed: {}", self.plugin_id, e))
+        })
+    }
+
+    fn invalid_reply(&self, expected: &str) -> MailMergeError {
+        MailMergeError::InvalidDataSource(format!(
+            "Plugin {} did not return {} for provider '{}'",
+            self.plugin_id, expected, self.provider_id
+        ))
+    }
+}
+
+impl DataSourceProvider for PluginDataSourceProvider {
+    fn columns(&self, config: &JsonValue) -> mail_merge::Result<Vec<ColumnDef>> {
+        let params = json!({ "provider": self.provider_id, "config": config });
+        let mut reply = self.request(plugin_methods::LIST_COLUMNS, params)?;
+
+        let columns = reply
+            .get_mut("columns")
+            .map(JsonValue::take)
+            .ok_or_else(|| self.invalid_reply("columns"))?;
+        let columns: Vec<ColumnReply> = serde_json::from_value(columns)?;
+        Ok(columns.into_iter().map(ColumnDef::from).collect())
+    }
+
+    fn records(
+        &self,
+        config: &JsonValue,
+        offset: usize,
+        limit: usize,
+    ) -> mail_merge::Result<Vec<Record>> {
+        let params = json!({
+            "provider": self.provider_id,
+            "config": config,
+            "offset": offset,
+            "limit": limit,
+        });
+        let reply = self.request(plugin_methods::FETCH_RECORDS, params)?;
+
+        let records = reply
+            .get("records")
+            .and_then(JsonValue::as_array)
+            .ok_or_else(|| self.invalid_reply("records"))?;
+        records
+            .iter()
+            .map(|record| {
+                let fields = record.as_object().ok_or_else(|| self.invalid_reply("record objects"))?;
+                Ok(fields
+                    .iter()
+                    .map(|(column, value)| (column.clone(), Value::from(value)))
+                    .collect())
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,207 @@
+//! Editor events for plugins
+//!
+//! The `EventQueue` collects document lifecycle events from the editing
+//! engine and autosave, coalescing bursts: while an event is pending, a new
+//! occurrence replaces its payload and restarts its debounce delay. The
+//! plugin host drains due events with `PluginHost::flush_events`.
+//!
+//! Payload fields that reveal document content are scrubbed for plugins
+//! without the permission that covers them (see `scrub_payload`).
+
+use crate::manifest::Permission;
+use crate::messages::plugin_events;
+use edit_engine::{EditorEvent, EditorEventListener};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use store::{SaveEvent, SaveListener};
+
+/// Default delay before a document change is delivered
+pub const DOCUMENT_CHANGED_DEBOUNCE: Duration = Duration::from_millis(250);
+/// Default delay before a selection change is delivered
+pub const SELECTION_CHANGED_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Payload fields and the permission a plugin needs to receive them
+const SCRUBBED_FIELDS: &[(&str, Permission)] = &[("path", Permission::DocumentRead)];
+
+#[derive(Debug)]
+struct PendingEvent {
+    name: String,
+    payload: Value,
+    due: Instant,
+}
+
+#[derive(Debug)]
+struct QueueState {
+    pending: Vec<PendingEvent>,
+    debounce: HashMap<String, Duration>,
+}
+
+/// Shared queue of debounced plugin events
+#[derive(Debug, Clone)]
+pub struct EventQueue {
+    state: Arc<Mutex<QueueState>>,
+}
+
+impl EventQueue {
+    /// Create a queue with the default debounce delays
+    pub fn new() -> Self {
+        let debounce = HashMap::from([
+            (plugin_events::DOCUMENT_CHANGED.to_string(), DOCUMENT_CHANGED_DEBOUNCE),
+            (plugin_events::SELECTION_CHANGED.to_string(), SELECTION_CHANGED_DEBOUNCE),
+        ]);
+        Self {
+            state: Arc::new(Mutex::new(QueueState {
+                pending: Vec::new(),
+                debounce,
+            })),
+        }
+    }
+
+    /// Set the debounce delay for an event (zero delivers on the next flush)
+    pub fn set_debounce(&self, event: &str, delay: Duration) {
+        self.lock().debounce.insert(event.to_string(), delay);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Queue an event
+    pub fn push(&self, event: &str, payload: Value) {
+        self.push_at(event, payload, Instant::now());
+    }
+
+    /// Queue an event that occurred at `now`
+    pub fn push_at(&self, event: &str, payload: Value, now: Instant) {
+        let mut state = self.lock();
+        let due = now + state.debounce.get(event).copied().unwrap_or_default();
+
+        match state.pending.iter_mut().find(|pending| pending.name == event) {
+            Some(pending) => {
+                pending.payload = payload;
+                pending.due = due;
+            }
+            None => state.pending.push(PendingEvent {
+                name: event.to_string(),
+                payload,
+                due,
+            }),
+        }
+    }
+
+    /// Remove and return the events whose debounce delay has passed
+    pub fn take_due(&self, now: Instant) -> Vec<(String, Value)> {
+        let mut state = self.lock();
+        let (due, waiting) = std::mem::take(&mut state.pending)
+            .into_iter()
+            .partition(|pending| pending.due <= now);
+        state.pending = waiting;
+
+        due.into_iter()
+            .map(|pending: PendingEvent| (pending.name, pending.payload))
+            .collect()
+    }
+
+    /// Check if any events are waiting
+    pub fn is_empty(&self) -> bool {
+        self.lock().pending.is_empty()
+    }
+
+    /// Create an editing engine listener that feeds this queue
+    pub fn editor_listener(&self) -> EditorEventListener {
+        let queue = self.clone();
+        Box::new(move |event| {
+            let (name, payload) = editor_event_payload(event);
+            queue.push(name, payload);
+        })
+    }
+
+    /// Create an autosave listener that feeds this queue
+    pub fn save_listener(&self) -> SaveListener {
+        let queue = self.clone();
+        Box::new(move |event| queue.push(plugin_events::DOCUMENT_SAVED, save_event_payload(event)))
+    }
+}
+
+impl Default for EventQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Convert an editor event to a plugin event name and payload
+pub fn editor_event_payload(event: &EditorEvent) -> (&'static str, Value) {
+    match event {
+        EditorEvent::DocumentChanged { command } => {
+            (plugin_events::DOCUMENT_CHANGED, json!({ "command": command }))
+        }
+        EditorEvent::SelectionChanged { selection } => (
+            plugin_events::SELECTION_CHANGED,
+            json!({
+                "anchor": { "nodeId": selection.anchor.node_id.to_string(), "offset": selection.anchor.offset },
+                "focus": { "nodeId": selection.focus.node_id.to_string(), "offset": selection.focus.offset },
+                "collapsed": selection.is_collapsed(),
+            }),
+        ),
+        EditorEvent::AutoTextSuggestion { suggestion } => (
+            plugin_events::AUTOTEXT_SUGGESTED,
+            json!({ "suggestion": suggestion }),
+        ),
+    }
+}
+
+/// Convert a save notification to a `document.saved` payload
+pub fn save_event_payload(event: &SaveEvent) -> Value {
+    json!({
+        "documentId": event.document_id,
+        "path": event.path.display().to_string(),
+        "autosave": event.autosave,
+    })
+}
+
+/// Remove the payload fields a plugin lacks permission to see
+pub fn scrub_payload(payload: &Value, granted: &[Permission]) -> Value {
+    let mut payload = payload.clone();
+    if let Value::Object(fields) = &mut payload {
+        for (field, permission) in SCRUBBED_FIELDS {
+            if !granted.contains(permission) {
+                fields.remove(*field);
+            }
+        }
+    }
+    payload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_queue_debounces_bursts() {
+        let queue = EventQueue::new();
+        let start = Instant::now();
+
+        queue.push_at(plugin_events::DOCUMENT_CHANGED, json!({ "command": "a" }), start);
+        queue.push_at(plugin_events::DOCUMENT_SAVED, json!({}), start);
+        let later = start + Duration::from_millis(200);
+        queue.push_at(plugin_events::DOCUMENT_CHANGED, json!({ "command": "b" }), later);
+
+        // Saves aren't debounced; the change waits for a quiet period
+        let due = queue.take_due(start + Duration::from_millis(300));
+        assert_eq!(due, vec![(plugin_events::DOCUMENT_SAVED.to_string(), json!({}))]);
+
+        let due = queue.take_due(later + DOCUMENT_CHANGED_DEBOUNCE);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].1["command"], "b");
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_scrub_payload() {
+        let payload = json!({ "documentId": "doc", "path": "/home/me/doc.wdj" });
+        assert_eq!(scrub_payload(&payload, &[]), json!({ "documentId": "doc" }));
+        assert_eq!(scrub_payload(&payload, &[Permission::DocumentRead]), payload);
+    }
+}
@@ -0,0 +1,109 @@
+//! Plugin-contributed file formats
+//!
+//! Plugins declare importers and exporters under `contributes.fileFormats`.
+//! Each contribution becomes a `store::FormatHandler` that forwards the
+//! conversion to the plugin:
+//!
+//! - `fileFormat.import` receives `{ "format", "data" }` with the file
+//!   contents base64-encoded, and answers `{ "document": <tree> }` or
+//!   `{ "text": "..." }` (one paragraph per line).
+//! - `fileFormat.export` receives `{ "format", "document", "text" }` and
+//!   answers `{ "data": <base64> }`.
+
+use crate::error::Result;
+use crate::manifest::FileFormatContribution;
+use crate::messages::plugin_methods;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use doc_model::{DocumentTree, Paragraph, Run};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use store::{FormatDescriptor, FormatHandler, StoreError};
+
+/// Calls a method on a plugin: `(plugin_id, method, params)`
+pub type PluginCall = Arc<dyn Fn(&str, &str, Value) -> Result<Value> + Send + Sync>;
+
+impl FileFormatContribution {
+    /// Describe the format for the store's format registry
+    pub fn descriptor(&self, plugin_id: &str) -> FormatDescriptor {
+        let extensions: Vec<&str> = self.extensions.iter().map(String::as_str).collect();
+        FormatDescriptor::new(&self.id, &extensions, &self.mime_type, &self.name)
+            .with_import(self.import)
+            .with_export(self.export)
+            .with_provider(plugin_id)
+    }
+}
+
+/// Format handler that delegates conversion to a plugin
+pub struct PluginFormatHandler {
+    plugin_id: String,
+    format_id: String,
+    call: PluginCall,
+}
+
+impl PluginFormatHandler {
+    pub fn new(plugin_id: impl Into<String>, format_id: impl Into<String>, call: PluginCall) -> Self {
+        Self {
+            plugin_id: plugin_id.into(),
+            format_id: format_id.into(),
+            call,
+        }
+    }
+
+    fn request(&self, method: &str, params: Value) -> store::Result<Value> {
+        (self.call)(&self.plugin_id, method, params).map_err(|e| {
+            StoreError::InvalidFormat(format!("Plugin {} failed: {}", self.plugin_id, e))
+        })
+    }
+
+    fn invalid_reply(&self, expected: &str) -> StoreError {
+        StoreError::InvalidFormat(format!(
+            "Plugin {} did not return {} for format '{}'",
+            self.plugin_id, expected, self.format_id
+        ))
+    }
+}
+
+impl FormatHandler for PluginFormatHandler {
+    fn import(&self, bytes: &[u8]) -> store::Result<DocumentTree> {
+        let params = json!({ "format": self.format_id, "data": STANDARD.encode(bytes) });
+        let mut reply = self.request(plugin_methods::IMPORT_FILE, params)?;
+
+        if let Some(document) = reply.get_mut("document").map(Value::take) {
+            return Ok(serde_json::from_value(document)?);
+        }
+        let text = reply
+            .get("text")
+            .and_then(Value::as_str)
+            .ok_or_else(|| self.invalid_reply("a document or text"))?;
+        text_to_tree(text)
+    }
+
+    fn export(&self, tree: &DocumentTree) -> store::Result<Vec<u8>> {
+        let params = json!({
+            "format": self.format_id,
+            "document": serde_json::to_value(tree)?,
+            "text": tree.text_content(),
+        });
+        let reply = self.request(plugin_methods::EXPORT_FILE, params)?;
+
+        let data = reply
+            .get("data")
+            .and_then(Value::as_str)
+            .ok_or_else(|| self.invalid_reply("data"))?;
+        STANDARD
+            .decode(data)
+            .map_err(|e| StoreError::InvalidFormat(format!("Invalid base64 from plugin: {}", e)))
+    }
+}
+
+fn text_to_tree(text: &str) -> store::Result<DocumentTree> {
+    let mut tree = DocumentTree::new();
+    let root_id = tree.root_id();
+    for (index, line) in text.lines().enumerate() {
+        let para_id = tree.insert_paragraph(Paragraph::new(), root_id, Some(index))?;
+        if !line.is_empty() {
+            tree.insert_run(Run::new(line), para_id, None)?;
+        }
+    }
+    Ok(tree)
+}
@@ -3,14 +3,22 @@
 //! This module provides the main PluginHost struct that manages
 //! loading, unloading, and communicating with plugins.
 
+use crate::data_sources::PluginDataSourceProvider;
 use crate::error::{PluginError, Result};
-use crate::manifest::PluginManifest;
+use crate::events::{scrub_payload, EventQueue};
+use crate::file_formats::{PluginCall, PluginFormatHandler};
+use crate::manifest::{Contributions, PluginManifest};
 use crate::messages::{HostMessage, PluginMessage};
 use crate::permissions::PermissionManager;
 use crate::sandbox::{ResourceUsage, SandboxConfig};
+use crate::wasm_runtime::{HostCallHandler, WasmPlugin};
 use serde_json::Value;
 use std::collections::HashMap;
+use mail_merge::ProviderRegistry;
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+use store::FormatRegistry;
 use tokio::sync::mpsc;
 
 /// Unique identifier for a plugin instance
@@ -48,6 +56,8 @@ pub struct LoadedPlugin {
     pub sandbox_config: SandboxConfig,
     /// Whether the plugin is enabled
     pub enabled: bool,
+    /// WebAssembly instance, for plugins with a `.wasm` entry
+    pub runtime: Option<WasmPlugin>,
 }
 
 impl LoadedPlugin {
@@ -60,6 +70,7 @@ impl LoadedPlugin {
             resource_usage: ResourceUsage::new(),
             sandbox_config: SandboxConfig::default(),
             enabled: true,
+            runtime: None,
         }
     }
 
@@ -78,6 +89,26 @@ impl LoadedPlugin {
         self.enabled && matches!(self.state, PluginState::Ready | PluginState::Running)
     }
 
+    /// Check if the plugin has been activated
+    pub fn is_activated(&self) -> bool {
+        self.runtime.as_ref().is_some_and(|runtime| runtime.is_activated())
+    }
+
+    /// Deliver a message to the plugin's WebAssembly instance
+    ///
+    /// Activates the plugin first if needed, and records the resources the
+    /// call used.
+    fn deliver(&mut self, message: &HostMessage) -> Result<Option<PluginMessage>> {
+        let runtime = self
+            .runtime
+            .as_mut()
+            .ok_or_else(|| PluginError::InvalidState(format!("Plugin {} has no runtime", self.manifest.id)))?;
+
+        let result = runtime.activate().and_then(|_| runtime.handle_message(message));
+        self.resource_usage = runtime.usage().clone();
+        result
+    }
+
     /// Set the plugin state
     pub fn set_state(&mut self, state: PluginState) {
         self.state = state;
@@ -108,6 +139,8 @@ pub struct PluginHost {
     pending_requests: HashMap<u64, PendingRequest>,
     /// Plugin load order for deterministic iteration
     load_order: Vec<String>,
+    /// Debounced editor events awaiting delivery
+    events: EventQueue,
 }
 
 /// A pending request waiting for a response
@@ -133,6 +166,7 @@ impl PluginHost {
             message_rx,
             pending_requests: HashMap::new(),
             load_order: Vec::new(),
+            events: EventQueue::new(),
         }
     }
 
@@ -148,6 +182,7 @@ impl PluginHost {
             message_rx,
             pending_requests: HashMap::new(),
             load_order: Vec::new(),
+            events: EventQueue::new(),
         }
     }
 
@@ -172,6 +207,13 @@ impl PluginHost {
 
         // Create loaded plugin
         let mut plugin = LoadedPlugin::new(manifest, path.to_string_lossy().to_string());
+
+        // Instantiate WebAssembly entry points in the sandbox
+        if plugin.manifest.entry.ends_with(".wasm") {
+            let wasm = std::fs::read(path.join(&plugin.manifest.entry))
+                .map_err(|e| PluginError::Io(format!("Failed to read plugin module: {}", e)))?;
+            plugin.runtime = Some(WasmPlugin::new(&wasm, &plugin.sandbox_config)?);
+        }
         plugin.set_state(PluginState::Ready);
 
         // Request permissions for the plugin
@@ -234,9 +276,64 @@ impl PluginHost {
         Ok(())
     }
 
+    /// Attach a WebAssembly module to a loaded plugin
+    pub fn attach_wasm_module(&mut self, id: &str, wasm: &[u8]) -> Result<()> {
+        let plugin = self
+            .plugins
+            .get_mut(id)
+            .ok_or_else(|| PluginError::not_found(id))?;
+
+        plugin.runtime = Some(WasmPlugin::new(wasm, &plugin.sandbox_config)?);
+        Ok(())
+    }
+
+    /// Call a method on a WebAssembly plugin and wait for its response
+    pub fn request(&mut self, id: &str, method: &str, args: Value) -> Result<Value> {
+        let plugin = self
+            .plugins
+            .get_mut(id)
+            .ok_or_else(|| PluginError::not_found(id))?;
+
+        if !plugin.can_execute() {
+            return Err(PluginError::InvalidState(format!(
+                "Plugin {} is not ready for execution",
+                id
+            )));
+        }
+        if plugin.runtime.is_none() {
+            return Err(PluginError::InvalidState(format!("Plugin {} has no runtime", id)));
+        }
+
+        let message = HostMessage::request(method, Some(args));
+        match plugin.deliver(&message)? {
+            Some(response) => match response.error {
+                Some(error) => Err(PluginError::execution(error.message)),
+                None => Ok(response.result.unwrap_or(Value::Null)),
+            },
+            None => Ok(Value::Null),
+        }
+    }
+
+    /// Set the handler for requests a WebAssembly plugin sends to the host
+    pub fn set_host_call_handler(&mut self, id: &str, handler: HostCallHandler) -> Result<()> {
+        let runtime = self
+            .plugins
+            .get_mut(id)
+            .ok_or_else(|| PluginError::not_found(id))?
+            .runtime
+            .as_mut()
+            .ok_or_else(|| PluginError::InvalidState(format!("Plugin {} has no runtime", id)))?;
+
+        runtime.set_host_handler(handler);
+        Ok(())
+    }
+
     /// Call a method on a plugin
+    ///
+    /// WebAssembly plugins are called directly and their response returned;
+    /// other plugins are sent the request over the message channel.
     pub async fn call_plugin(
-        &self,
+        &mut self,
         id: &str,
         method: &str,
         args: Value,
@@ -246,6 +343,10 @@ impl PluginHost {
             .get(id)
             .ok_or_else(|| PluginError::not_found(id))?;
 
+        if plugin.runtime.is_some() {
+            return self.request(id, method, args);
+        }
+
         if !plugin.can_execute() {
             return Err(PluginError::InvalidState(format!(
                 "Plugin {} is not ready for execution",
@@ -267,13 +368,20 @@ impl PluginHost {
     }
 
     /// Send an event to all plugins
-    pub async fn broadcast_event(&self, event: &str, data: Option<Value>) -> Result<()> {
+    ///
+    /// WebAssembly plugins only receive events once activated.
+    pub async fn broadcast_event(&mut self, event: &str, data: Option<Value>) -> Result<()> {
         let message = HostMessage::event(event, data);
 
         for plugin_id in &self.load_order {
-            if let Some(plugin) = self.plugins.get(plugin_id) {
-                if plugin.can_execute() {
+            if let Some(plugin) = self.plugins.get_mut(plugin_id) {
+                if !plugin.can_execute() {
+                    continue;
+                }
+                if plugin.runtime.is_none() {
                     let _ = self.message_tx.send(message.clone()).await;
+                } else if plugin.is_activated() {
+                    let _ = plugin.deliver(&message);
                 }
             }
         }
@@ -281,6 +389,49 @@ impl PluginHost {
         Ok(())
     }
 
+    /// Get the queue that collects editor events for plugins
+    ///
+    /// Hook it up with `EditingEngine::add_event_listener(queue.editor_listener())`
+    /// and `AutosaveManager::set_save_listener(queue.save_listener())`.
+    pub fn event_queue(&self) -> EventQueue {
+        self.events.clone()
+    }
+
+    /// Deliver the queued events whose debounce delay has passed
+    ///
+    /// Returns the number of events delivered.
+    pub async fn flush_events(&mut self) -> Result<usize> {
+        let due = self.events.take_due(Instant::now());
+        for (event, payload) in &due {
+            self.dispatch_event(event, payload).await?;
+        }
+        Ok(due.len())
+    }
+
+    /// Send an event to all plugins, scrubbing the payload per plugin
+    ///
+    /// Fields a plugin lacks the permission for are removed from its copy.
+    pub async fn dispatch_event(&mut self, event: &str, payload: &Value) -> Result<()> {
+        for plugin_id in &self.load_order {
+            let Some(plugin) = self.plugins.get_mut(plugin_id) else {
+                continue;
+            };
+            if !plugin.can_execute() {
+                continue;
+            }
+
+            let granted = self.permissions.get_granted_permissions(plugin_id);
+            let message = HostMessage::event(event, Some(scrub_payload(payload, &granted)));
+            if plugin.runtime.is_none() {
+                let _ = self.message_tx.send(message).await;
+            } else if plugin.is_activated() {
+                let _ = plugin.deliver(&message);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Send an event to plugins that registered for it
     pub async fn send_event_to_interested(
         &self,
@@ -411,6 +562,160 @@ impl PluginHost {
             .collect()
     }
 
+    /// Map keybindings to the command IDs they run
+    ///
+    /// Commands contributed by enabled plugins are bound in load order,
+    /// followed by `extra` (e.g. the user's macros). When two commands claim
+    /// the same keybinding, the later one wins.
+    pub fn keybindings(&self, extra: &Contributions) -> HashMap<String, String> {
+        let plugin_commands = self
+            .load_order
+            .iter()
+            .filter_map(|id| self.plugins.get(id))
+            .filter(|p| p.enabled)
+            .flat_map(|p| &p.manifest.contributes.commands);
+
+        plugin_commands
+            .chain(&extra.commands)
+            .filter_map(|command| {
+                let keybinding = command.keybinding.as_ref()?;
+                Some((keybinding.clone(), command.id.clone()))
+            })
+            .collect()
+    }
+
+    /// Register the file formats contributed by enabled plugins
+    ///
+    /// Conversions are forwarded to the plugin through `call`. Formats whose
+    /// extensions are already taken are skipped and reported in the result.
+    pub fn register_file_formats(
+        &self,
+        registry: &mut FormatRegistry,
+        call: PluginCall,
+    ) -> Vec<PluginError> {
+        let mut errors = Vec::new();
+        for id in &self.load_order {
+            let Some(plugin) = self.plugins.get(id).filter(|p| p.enabled) else {
+                continue;
+            };
+            for format in &plugin.manifest.contributes.file_formats {
+                let handler = PluginFormatHandler::new(id.as_str(), format.id.as_str(), call.clone());
+                if let Err(e) = registry.register(format.descriptor(id), Arc::new(handler)) {
+                    errors.push(PluginError::Registry(format!("{}: {}", id, e)));
+                }
+            }
+        }
+        errors
+    }
+
+    /// Register the mail merge data sources contributed by enabled plugins
+    ///
+    /// Requests are forwarded to the plugin through `call`. Providers whose
+    /// IDs are already taken are skipped and reported in the result.
+    pub fn register_data_sources(
+        &self,
+        registry: &mut ProviderRegistry,
+        call: PluginCall,
+    ) -> Vec<PluginError> {
+        let mut errors = Vec::new();
+        for id in &self.load_order {
+            let Some(plugin) = self.plugins.get(id).filter(|p| p.enabled) else {
+                continue;
+            };
+            for source in &plugin.manifest.contributes.data_sources {
+                let provider = PluginDataSourceProvider::new(id.as_str(), source.id.as_str(), call.clone());
+                if let Err(e) = registry.register(source.descriptor(id), Arc::new(provider)) {
+                    errors.push(PluginError::Registry(format!("{}: {}", id, e)));
+                }
+            }
+        }
+        errors
+    }
+
+    /// Get plugins that should activate for a document language
+    pub fn get_plugins_for_language(&self, language: &str) -> Vec<&str> {
+        self.load_order
+            .iter()
+            .filter_map(|id| {
+                self.plugins.get(id).and_then(|p| {
+                    if p.manifest.activation_events.iter().any(|e| e.matches_language(language)) {
+                        Some(id.as_str())
+                    } else {
+                        None
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Activate a plugin, running its WebAssembly `activate` export
+    pub fn activate_plugin(&mut self, id: &str) -> Result<()> {
+        let plugin = self
+            .plugins
+            .get_mut(id)
+            .ok_or_else(|| PluginError::not_found(id))?;
+
+        if !plugin.can_execute() {
+            return Err(PluginError::InvalidState(format!(
+                "Plugin {} is not ready for execution",
+                id
+            )));
+        }
+
+        if let Some(runtime) = plugin.runtime.as_mut() {
+            let result = runtime.activate();
+            plugin.resource_usage = runtime.usage().clone();
+            result?;
+        }
+        Ok(())
+    }
+
+    /// Activate the given plugins, returning the IDs that activated
+    ///
+    /// Disabled plugins are skipped. A plugin that fails to activate is put
+    /// into the error state rather than stopping the others.
+    fn activate_plugins(&mut self, ids: Vec<String>) -> Vec<PluginId> {
+        let mut activated = Vec::new();
+        for id in ids {
+            if !self.plugins.get(&id).is_some_and(|p| p.enabled) {
+                continue;
+            }
+            match self.activate_plugin(&id) {
+                Ok(()) => activated.push(id),
+                Err(_) => {
+                    if let Some(plugin) = self.plugins.get_mut(&id) {
+                        plugin.set_state(PluginState::Error);
+                    }
+                }
+            }
+        }
+        activated
+    }
+
+    /// Activate plugins with an `onStartup` activation event
+    pub fn activate_startup_plugins(&mut self) -> Vec<PluginId> {
+        let ids = self.get_startup_plugins().into_iter().map(String::from).collect();
+        self.activate_plugins(ids)
+    }
+
+    /// Activate plugins with an `onCommand` event for a command
+    pub fn activate_for_command(&mut self, command: &str) -> Vec<PluginId> {
+        let ids = self.get_plugins_for_command(command).into_iter().map(String::from).collect();
+        self.activate_plugins(ids)
+    }
+
+    /// Activate plugins with an `onDocumentOpen` event matching a path
+    pub fn activate_for_document(&mut self, path: &str) -> Vec<PluginId> {
+        let ids = self.get_plugins_for_document(path).into_iter().map(String::from).collect();
+        self.activate_plugins(ids)
+    }
+
+    /// Activate plugins with an `onLanguage` event for a language
+    pub fn activate_for_language(&mut self, language: &str) -> Vec<PluginId> {
+        let ids = self.get_plugins_for_language(language).into_iter().map(String::from).collect();
+        self.activate_plugins(ids)
+    }
+
     /// Get plugins that should activate for a document
     pub fn get_plugins_for_document(&self, path: &str) -> Vec<&str> {
         self.load_order
@@ -654,4 +959,222 @@ mod tests {
         assert_eq!(plugins[1].id, "com.test.second");
         assert_eq!(plugins[2].id, "com.test.third");
     }
+
+    #[test]
+    fn test_keybindings_include_plugin_commands_and_macros() {
+        use crate::manifest::CommandContribution;
+        use edit_engine::Macro;
+
+        let mut host = PluginHost::new();
+        let manifest = create_test_manifest("com.test.plugin").with_contributions(
+            Contributions::new()
+                .with_command(CommandContribution::new("test.count", "Count").with_keybinding("Ctrl+Alt+C"))
+                .with_command(CommandContribution::new("test.none", "No Shortcut")),
+        );
+        host.load_plugin_from_manifest(manifest, "/path/to/plugin").unwrap();
+
+        let macros = vec![
+            Macro::new("Heading").with_keybinding("Ctrl+Alt+H"),
+            Macro::new("Recount").with_keybinding("Ctrl+Alt+C"),
+            Macro::new("Unbound"),
+        ];
+        let bindings = host.keybindings(&Contributions::from_macros(&macros));
+
+        assert_eq!(bindings.len(), 2);
+        assert_eq!(bindings["Ctrl+Alt+H"], "macro.Heading");
+        assert_eq!(bindings["Ctrl+Alt+C"], "macro.Recount");
+
+        host.disable_plugin("com.test.plugin").unwrap();
+        assert!(host.keybindings(&Contributions::new()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_wasm_plugins_activate_on_events_and_answer_calls() {
+        // Counts activations and replies to every message with a host call
+        // carrying that count
+        let wasm = wat::parse_str(
+            r#"
+            (module
+              (import "go_word" "host_call" (func $host_call (param i32 i32) (result i64)))
+              (memory (export "memory") 1)
+              (global $heap (mut i32) (i32.const 1024))
+              (data (i32.const 0) "{\"id\":3,\"method\":\"log\",\"params\":null}")
+              (func (export "alloc") (param $len i32) (result i32)
+                (local $ptr i32)
+                (local.set $ptr (global.get $heap))
+                (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+                (local.get $ptr))
+              (func (export "activate"))
+              (func (export "on_message") (param i32 i32) (result i64)
+                (call $host_call (i32.const 0) (i32.const 37))))
+            "#,
+        )
+        .unwrap();
+        let broken = wat::parse_str(
+            r#"
+            (module
+              (memory (export "memory") 1)
+              (func (export "alloc") (param i32) (result i32) (i32.const 0))
+              (func (export "activate") unreachable)
+              (func (export "on_message") (param i32 i32) (result i64) (i64.const 0)))
+            "#,
+        )
+        .unwrap();
+
+        let mut host = PluginHost::new();
+        for (id, module) in [("com.test.count", &wasm), ("com.test.broken", &broken)] {
+            let manifest = create_test_manifest(id)
+                .with_entry("plugin.wasm")
+                .with_activation_event(ActivationEvent::OnCommand("count.run".to_string()));
+            host.load_plugin_from_manifest(manifest, "/path/to/plugin").unwrap();
+            host.attach_wasm_module(id, module).unwrap();
+        }
+        host.set_host_call_handler(
+            "com.test.count",
+            Box::new(|request| Ok(Value::String(request.method.clone()))),
+        )
+        .unwrap();
+
+        assert!(host.activate_for_command("other.command").is_empty());
+        assert_eq!(host.activate_for_command("count.run"), vec!["com.test.count".to_string()]);
+        assert!(host.get_plugin("com.test.count").unwrap().is_activated());
+        assert_eq!(host.get_plugin("com.test.broken").unwrap().state, PluginState::Error);
+
+        let result = host.call_plugin("com.test.count", "count.run", Value::Null).await.unwrap();
+        assert_eq!(result, Value::String("log".to_string()));
+        let usage = &host.get_plugin("com.test.count").unwrap().resource_usage;
+        assert_eq!(usage.api_calls_this_minute, 1);
+    }
+
+    #[test]
+    fn test_register_file_formats_routes_to_plugin() {
+        use crate::manifest::FileFormatContribution;
+        use crate::messages::plugin_methods;
+
+        let mut host = PluginHost::new();
+        let format = FileFormatContribution::new("upper", "Shouting Text", &["shout"])
+            .with_export(true);
+        let manifest = create_test_manifest("com.test.shout")
+            .with_contributions(Contributions::new().with_file_format(format.clone()));
+        host.load_plugin_from_manifest(manifest, "/path/to/plugin").unwrap();
+        let manifest = create_test_manifest("com.test.clash")
+            .with_contributions(Contributions::new().with_file_format(format));
+        host.load_plugin_from_manifest(manifest, "/path/to/plugin").unwrap();
+
+        // Stands in for a plugin that upper-cases text on import
+        let call: PluginCall = Arc::new(|plugin_id, method, params| {
+            assert_eq!(plugin_id, "com.test.shout");
+            match method {
+                plugin_methods::IMPORT_FILE => {
+                    use base64::Engine;
+                    let data = params["data"].as_str().unwrap();
+                    let bytes = base64::engine::general_purpose::STANDARD.decode(data).unwrap();
+                    let text = String::from_utf8(bytes).unwrap().to_uppercase();
+                    Ok(serde_json::json!({ "text": text }))
+                }
+                _ => Err(PluginError::MethodNotFound(method.to_string())),
+            }
+        });
+
+        let mut registry = FormatRegistry::new();
+        let errors = host.register_file_formats(&mut registry, call);
+        assert_eq!(errors.len(), 1);
+
+        let tree = registry.load_from_bytes("shout", b"hello\nworld").unwrap();
+        assert_eq!(tree.text_content(), "HELLO\nWORLD\n");
+        assert_eq!(
+            registry.for_extension("shout").unwrap().provider.as_deref(),
+            Some("com.test.shout")
+        );
+        assert!(registry.export_to_bytes("shout", &tree).is_err());
+    }
+
+    #[test]
+    fn test_register_data_sources_routes_to_plugin() {
+        use crate::manifest::DataSourceContribution;
+        use crate::messages::plugin_methods;
+
+        let mut host = PluginHost::new();
+        let manifest = create_test_manifest("com.test.crm").with_contributions(
+            Contributions::new().with_data_source(DataSourceContribution::new("crm", "CRM")),
+        );
+        host.load_plugin_from_manifest(manifest, "/path/to/plugin").unwrap();
+
+        // Stands in for a plugin serving three contacts
+        let call: PluginCall = Arc::new(|plugin_id, method, params| {
+            assert_eq!(plugin_id, "com.test.crm");
+            assert_eq!(params["config"]["list"], "customers");
+            match method {
+                plugin_methods::LIST_COLUMNS => Ok(serde_json::json!({
+                    "columns": [
+                        { "name": "name", "displayName": "Full Name" },
+                        { "name": "orders", "dataType": "number" },
+                    ]
+                })),
+                plugin_methods::FETCH_RECORDS => {
+                    let offset = params["offset"].as_u64().unwrap();
+                    let records: Vec<Value> = (offset..3)
+                        .map(|n| serde_json::json!({ "name": format!("Contact {}", n), "orders": n }))
+                        .collect();
+                    Ok(serde_json::json!({ "records": records }))
+                }
+                _ => Err(PluginError::MethodNotFound(method.to_string())),
+            }
+        });
+
+        let mut registry = ProviderRegistry::new();
+        assert!(host.register_data_sources(&mut registry, call).is_empty());
+        assert_eq!(registry.get("crm").unwrap().plugin_id, "com.test.crm");
+
+        let config = serde_json::json!({ "list": "customers" });
+        let ds = registry.load("contacts", "crm", config).unwrap();
+        assert_eq!(ds.record_count(), 3);
+        assert_eq!(ds.get_column("name").unwrap().display(), "Full Name");
+        assert_eq!(ds.get_value(2, "orders").unwrap().as_number(), Some(2.0));
+    }
+
+    #[tokio::test]
+    async fn test_editor_events_are_debounced_and_scrubbed() {
+        use crate::messages::plugin_events;
+        use edit_engine::{EditingEngine, InsertText};
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let (_, plugin_rx) = mpsc::channel(16);
+        let mut host = PluginHost::with_channels(tx, plugin_rx);
+        host.load_plugin_from_manifest(create_test_manifest("com.test.reader"), "/path").unwrap();
+        host.load_plugin_from_manifest(create_test_manifest("com.test.other"), "/path").unwrap();
+        host.permissions_mut().grant_permission("com.test.reader", Permission::DocumentRead);
+
+        let queue = host.event_queue();
+        queue.set_debounce(plugin_events::SELECTION_CHANGED, std::time::Duration::ZERO);
+        let mut engine = EditingEngine::new();
+        engine.add_event_listener(queue.editor_listener());
+
+        for text in ["a", "b"] {
+            let caret = engine.selection().focus;
+            engine.execute(Box::new(InsertText::new(caret, text))).unwrap();
+        }
+        // Only the (undebounced) selection change is due; the burst of
+        // selection changes is coalesced into one
+        assert_eq!(host.flush_events().await.unwrap(), 1);
+        for _ in 0..2 {
+            let message = rx.try_recv().unwrap();
+            assert_eq!(message.method, plugin_events::SELECTION_CHANGED);
+            assert_eq!(message.params.unwrap()["focus"]["offset"], 2);
+        }
+        assert!(rx.try_recv().is_err());
+        assert!(!queue.is_empty());
+
+        let save = store::SaveEvent {
+            document_id: "doc".to_string(),
+            path: "/docs/report.wdj".into(),
+            autosave: true,
+        };
+        queue.save_listener()(&save);
+        host.flush_events().await.unwrap();
+        let reader = rx.try_recv().unwrap().params.unwrap();
+        let other = rx.try_recv().unwrap().params.unwrap();
+        assert_eq!(reader["path"], "/docs/report.wdj");
+        assert!(other.get("path").is_none());
+    }
 }
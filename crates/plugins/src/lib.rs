@@ -6,8 +6,14 @@
 //! - **Host**: Plugin lifecycle management (load, unload, enable, disable)
 //! - **Permissions**: Fine-grained permission control for plugin capabilities
 //! - **Messages**: Communication protocol between host and plugins
+//! - **Events**: Debounced document, selection, and save events
 //! - **Sandbox**: Resource limits and API restrictions for security
+//! - **WASM runtime**: Sandboxed execution of WebAssembly plugin modules
 //! - **Registry**: Plugin discovery, installation, and updates
+//! - **Live document**: Plugin API access to the editor's document tree
+//! - **File formats**: Plugin-contributed importers and exporters
+//! - **Data sources**: Plugin-contributed mail merge data providers
+//! - **Translation**: HTTP translation provider gated by the Network permission
 //!
 //! # Example
 //!
@@ -38,27 +44,40 @@
 //! ```
 
 pub mod api;
+pub mod data_sources;
 pub mod error;
+pub mod events;
+pub mod file_formats;
 pub mod host;
 pub mod manifest;
 pub mod messages;
 pub mod permissions;
 pub mod registry;
 pub mod installation;
+pub mod live_document;
 pub mod sandbox;
+pub mod translation;
+pub mod wasm_runtime;
 
 // Re-export main types for convenience
 pub use error::{PluginError, PluginErrorCode, Result, SerializablePluginError};
+pub use events::EventQueue;
 pub use host::{LoadedPlugin, PluginHost, PluginId, PluginState};
 pub use manifest::{
-    ActivationEvent, CommandContribution, Contributions, MenuContribution, PanelContribution,
-    PanelLocation, Permission, PluginManifest, ToolbarContribution,
+    ActivationEvent, CommandContribution, Contributions, DataSourceContribution,
+    FileFormatContribution, MenuContribution, PanelContribution, PanelLocation, Permission,
+    PluginManifest, ToolbarContribution,
 };
 pub use messages::{HostMessage, HostMessageType, PluginMessage, PluginMessageType, PluginRequest};
 pub use permissions::{PermissionManager, PermissionRequest, PermissionState};
 pub use registry::{DiscoveredPlugin, PluginMetadata, PluginRegistry, PluginUpdate, RegistryState};
 pub use installation::{InstallationManager, InstalledPlugin, InstallationState};
+pub use data_sources::PluginDataSourceProvider;
+pub use file_formats::{PluginCall, PluginFormatHandler};
+pub use translation::HttpTranslationProvider;
+pub use live_document::LiveDocument;
 pub use sandbox::{ApiRestrictions, ResourceLimitViolation, ResourceUsage, SandboxConfig};
+pub use wasm_runtime::{HostCallHandler, WasmPlugin};
 pub use api::{
     // Error types
     ApiError, ApiResult,
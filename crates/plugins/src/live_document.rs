@@ -0,0 +1,477 @@
+//! Live document backend for the plugin document API
+//!
+//! `LiveDocument` gives plugins access to the editor's real `DocumentTree`.
+//! Plugins see the body as plain text with paragraphs joined by `'\n'`, and
+//! API offsets are character offsets into that text. Edits are executed as
+//! editing commands through the shared `EditingEngine`, so they land on the
+//! undo stack like any user edit.
+
+use crate::api::{
+    ApiError, ApiResult, DocumentMetadata, DocumentSnapshot, Paragraph, Position, Range,
+    SearchOptions, Selection,
+};
+use doc_model::{DocumentTree, Node, NodeId, Position as DocPosition, Selection as DocSelection};
+use edit_engine::{
+    ApplyStyle, Command, DeleteSelection, EditingEngine, InsertText, SplitParagraph, TypeOver,
+};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// The plain-text view of a document tree
+struct TextMap {
+    /// Body paragraphs with their start offset and character length
+    paragraphs: Vec<(NodeId, usize, usize)>,
+    /// Paragraph text joined by newlines
+    text: String,
+}
+
+impl TextMap {
+    fn build(tree: &DocumentTree) -> Self {
+        let mut paragraphs = Vec::new();
+        let mut text = String::new();
+        let mut offset = 0;
+
+        for (i, para) in tree.paragraphs().enumerate() {
+            if i > 0 {
+                text.push('\n');
+                offset += 1;
+            }
+            let para_text: String = para
+                .children()
+                .iter()
+                .filter_map(|&id| tree.get_run(id))
+                .map(|run| run.text.as_str())
+                .collect();
+            let len = para_text.chars().count();
+            paragraphs.push((para.id(), offset, len));
+            text.push_str(&para_text);
+            offset += len;
+        }
+
+        Self { paragraphs, text }
+    }
+
+    /// Total length in characters
+    fn len(&self) -> usize {
+        self.paragraphs
+            .last()
+            .map(|&(_, start, len)| start + len)
+            .unwrap_or(0)
+    }
+
+    /// Map a document offset to a paragraph position
+    fn locate(&self, offset: usize) -> ApiResult<(usize, DocPosition)> {
+        self.paragraphs
+            .iter()
+            .enumerate()
+            .find(|(_, &(_, start, len))| offset >= start && offset <= start + len)
+            .map(|(index, &(para_id, start, _))| (index, DocPosition::new(para_id, offset - start)))
+            .ok_or_else(|| {
+                ApiError::OutOfBounds(format!(
+                    "Position {} is beyond document length {}",
+                    offset,
+                    self.len()
+                ))
+            })
+    }
+
+    /// Map a paragraph or run position back to a document offset
+    fn offset_of(&self, tree: &DocumentTree, position: &DocPosition) -> Option<usize> {
+        let (para_id, offset) = match tree.get_run(position.node_id) {
+            Some(run) => {
+                let para_id = run.parent()?;
+                let before: usize = tree
+                    .get_paragraph(para_id)?
+                    .children()
+                    .iter()
+                    .take_while(|&&id| id != position.node_id)
+                    .filter_map(|&id| tree.get_run(id))
+                    .map(|run| run.text.chars().count())
+                    .sum();
+                (para_id, before + position.offset)
+            }
+            None => (position.node_id, position.offset),
+        };
+
+        self.paragraphs
+            .iter()
+            .find(|&&(id, _, _)| id == para_id)
+            .map(|&(_, start, _)| start + offset)
+    }
+
+    /// Build an API position (line = paragraph index) for an offset
+    fn position(&self, offset: usize) -> Position {
+        match self.locate(offset) {
+            Ok((line, position)) => Position::new(line, position.offset, offset),
+            Err(_) => Position::from_offset(offset),
+        }
+    }
+
+    /// Text between two character offsets
+    fn slice(&self, start: usize, end: usize) -> String {
+        self.text.chars().skip(start).take(end - start).collect()
+    }
+
+    /// Resolve a range to a document selection
+    fn selection(&self, range: &Range) -> ApiResult<DocSelection> {
+        if !range.is_valid() {
+            return Err(ApiError::InvalidRange(
+                "Range start must be before or equal to end".to_string(),
+            ));
+        }
+        let (_, start) = self.locate(range.start.offset)?;
+        let (_, end) = self.locate(range.end.offset)?;
+        Ok(DocSelection::new(start, end))
+    }
+
+    /// Resolve a range that must stay inside one paragraph
+    fn paragraph_selection(&self, range: &Range) -> ApiResult<DocSelection> {
+        let selection = self.selection(range)?;
+        if selection.anchor.node_id != selection.focus.node_id {
+            return Err(ApiError::InvalidRange(
+                "Range spans more than one paragraph".to_string(),
+            ));
+        }
+        Ok(selection)
+    }
+}
+
+/// Live document backed by the editor's editing engine
+#[derive(Clone)]
+pub struct LiveDocument {
+    /// Document ID
+    id: String,
+    /// Document title
+    title: String,
+    /// The engine owning the document tree
+    engine: Arc<RwLock<EditingEngine>>,
+}
+
+impl LiveDocument {
+    /// Create a live document over a shared editing engine
+    pub fn new(
+        id: impl Into<String>,
+        title: impl Into<String>,
+        engine: Arc<RwLock<EditingEngine>>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            engine,
+        }
+    }
+
+    /// Get the shared editing engine
+    pub fn engine(&self) -> &Arc<RwLock<EditingEngine>> {
+        &self.engine
+    }
+
+    fn read(&self) -> ApiResult<RwLockReadGuard<'_, EditingEngine>> {
+        self.engine
+            .read()
+            .map_err(|e| ApiError::Internal(format!("Failed to read document: {}", e)))
+    }
+
+    fn write(&self) -> ApiResult<RwLockWriteGuard<'_, EditingEngine>> {
+        self.engine
+            .write()
+            .map_err(|e| ApiError::Internal(format!("Failed to write document: {}", e)))
+    }
+
+    /// Run a command against a range without disturbing the user's selection
+    fn execute(
+        engine: &mut EditingEngine,
+        command: Box<dyn Command>,
+        selection: &DocSelection,
+    ) -> ApiResult<()> {
+        engine
+            .execute_at(command, selection)
+            .map_err(|e| ApiError::OperationFailed(e.to_string()))
+    }
+
+    /// Snapshot of the document text, paragraphs, and counts
+    pub fn snapshot(&self) -> ApiResult<DocumentSnapshot> {
+        let engine = self.read()?;
+        let tree = engine.tree();
+        let map = TextMap::build(tree);
+
+        let paragraphs = map
+            .paragraphs
+            .iter()
+            .map(|&(para_id, start, len)| Paragraph {
+                start,
+                end: start + len,
+                style: tree
+                    .get_paragraph(para_id)
+                    .and_then(|para| para.paragraph_style_id.as_ref())
+                    .map(|style| style.as_str().to_string()),
+            })
+            .collect();
+        let metadata = DocumentMetadata {
+            word_count: map.text.split_whitespace().count(),
+            char_count: map.len(),
+            ..Default::default()
+        };
+
+        Ok(DocumentSnapshot::new(&self.id, &self.title, map.text)
+            .with_metadata(metadata)
+            .with_paragraphs(paragraphs))
+    }
+
+    /// The user's primary selection
+    pub fn selection(&self) -> ApiResult<Selection> {
+        let engine = self.read()?;
+        let tree = engine.tree();
+        let map = TextMap::build(tree);
+        let selection = engine.selection();
+
+        let resolve = |position: &DocPosition| {
+            map.offset_of(tree, position).ok_or_else(|| {
+                ApiError::Internal(format!("Selection is outside the body: {:?}", position))
+            })
+        };
+        let anchor = resolve(&selection.anchor)?;
+        let focus = resolve(&selection.focus)?;
+        let (start, end) = (anchor.min(focus), anchor.max(focus));
+
+        Ok(Selection::new(
+            map.position(start),
+            map.position(end),
+            map.slice(start, end),
+        ))
+    }
+
+    /// Text in a range
+    pub fn text(&self, range: &Range) -> ApiResult<String> {
+        let engine = self.read()?;
+        let map = TextMap::build(engine.tree());
+        map.selection(range)?;
+        Ok(map.slice(range.start.offset, range.end.offset))
+    }
+
+    /// Insert text, starting a new paragraph at each `'\n'`
+    pub fn insert_text(&self, position: &Position, text: &str) -> ApiResult<()> {
+        let mut engine = self.write()?;
+        let map = TextMap::build(engine.tree());
+        let (mut index, mut at) = map.locate(position.offset)?;
+
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                let split = DocSelection::collapsed(at);
+                Self::execute(&mut engine, Box::new(SplitParagraph::new(at)), &split)?;
+                index += 1;
+                let para_id = engine
+                    .tree()
+                    .paragraphs()
+                    .nth(index)
+                    .map(|para| para.id())
+                    .ok_or_else(|| ApiError::Internal("Split paragraph not found".to_string()))?;
+                at = DocPosition::new(para_id, 0);
+            }
+            if !line.is_empty() {
+                let insert = DocSelection::collapsed(at);
+                Self::execute(&mut engine, Box::new(InsertText::new(at, line)), &insert)?;
+                at = DocPosition::new(at.node_id, at.offset + line.chars().count());
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete the text in a range within one paragraph
+    pub fn delete_text(&self, range: &Range) -> ApiResult<()> {
+        let mut engine = self.write()?;
+        let selection = TextMap::build(engine.tree()).paragraph_selection(range)?;
+        if selection.is_collapsed() {
+            return Ok(());
+        }
+        Self::execute(&mut engine, Box::new(DeleteSelection), &selection)
+    }
+
+    /// Replace the text in a range within one paragraph
+    pub fn replace_text(&self, range: &Range, text: &str) -> ApiResult<()> {
+        if text.contains('\n') {
+            return Err(ApiError::InvalidArgument(
+                "Replacement text cannot contain paragraph breaks".to_string(),
+            ));
+        }
+        let mut engine = self.write()?;
+        let selection = TextMap::build(engine.tree()).paragraph_selection(range)?;
+        Self::execute(&mut engine, Box::new(TypeOver::new(text)), &selection)
+    }
+
+    /// Apply a paragraph or character style from the document's style sheet
+    pub fn apply_style(&self, range: &Range, style_id: &str) -> ApiResult<()> {
+        let mut engine = self.write()?;
+        if engine.tree().styles.get(&style_id.into()).is_none() {
+            return Err(ApiError::NotFound(format!("Style '{}'", style_id)));
+        }
+        let selection = TextMap::build(engine.tree()).selection(range)?;
+        Self::execute(&mut engine, Box::new(ApplyStyle::new(style_id)), &selection)
+    }
+
+    /// Move the user's selection
+    pub fn set_selection(&self, selection: &Selection) -> ApiResult<()> {
+        let mut engine = self.write()?;
+        let map = TextMap::build(engine.tree());
+        let (_, anchor) = map.locate(selection.start.offset)?;
+        let (_, focus) = map.locate(selection.end.offset)?;
+        engine.set_selection(DocSelection::new(anchor, focus));
+        Ok(())
+    }
+
+    /// Find occurrences of a query, as character ranges
+    pub fn search(&self, query: &str, options: &SearchOptions) -> ApiResult<Vec<Range>> {
+        let engine = self.read()?;
+        let map = TextMap::build(engine.tree());
+        let (text, query) = if options.case_sensitive {
+            (map.text, query.to_string())
+        } else {
+            (map.text.to_lowercase(), query.to_lowercase())
+        };
+        let query_len = query.chars().count();
+
+        let mut results = Vec::new();
+        for (byte_start, _) in text.match_indices(&query) {
+            let start = text[..byte_start].chars().count();
+            results.push(Range::from_offsets(start, start + query_len));
+            if options.max_results.is_some_and(|max| results.len() >= max) {
+                break;
+            }
+        }
+        Ok(results)
+    }
+}
+
+impl std::fmt::Debug for LiveDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LiveDocument")
+            .field("id", &self.id)
+            .field("title", &self.title)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{DocumentApi, PluginApiContext};
+    use crate::manifest::Permission;
+    use crate::messages::host_methods;
+    use crate::sandbox::ApiRestrictions;
+    use doc_model::{Paragraph as DocParagraph, Run, Style, StyleId};
+    use serde_json::json;
+
+    fn live_context(texts: &[&str]) -> (PluginApiContext, Arc<RwLock<EditingEngine>>) {
+        let mut tree = DocumentTree::new();
+        let root_id = tree.root_id();
+        for (i, text) in texts.iter().enumerate() {
+            let para_id = tree
+                .insert_paragraph(DocParagraph::new(), root_id, Some(i))
+                .unwrap();
+            tree.insert_run(Run::new(*text), para_id, None).unwrap();
+        }
+        tree.styles
+            .register(Style::paragraph("Heading1", "Heading 1"));
+        let engine = Arc::new(RwLock::new(EditingEngine::with_tree(tree)));
+
+        let mut ctx = PluginApiContext::new_for_testing("test.plugin");
+        ctx.set_live_document(LiveDocument::new("doc-1", "Report", engine.clone()));
+        (ctx, engine)
+    }
+
+    #[test]
+    fn test_live_document_edits_go_through_engine() {
+        let (mut ctx, engine) = live_context(&["Hello world", "Second"]);
+
+        let snapshot = ctx.get_content().unwrap();
+        assert_eq!(snapshot.text, "Hello world\nSecond");
+        assert_eq!(snapshot.paragraphs[1].start, 12);
+        assert_eq!(snapshot.metadata.word_count, 3);
+
+        ctx.replace_text(Range::from_offsets(6, 11), "there")
+            .unwrap();
+        ctx.insert_text(Position::from_offset(18), "!\nThird")
+            .unwrap();
+        ctx.apply_style(Range::from_offsets(0, 3), "Heading1")
+            .unwrap();
+        assert_eq!(
+            ctx.get_content().unwrap().text,
+            "Hello there\nSecond!\nThird"
+        );
+        assert_eq!(ctx.get_text(Range::from_offsets(12, 18)).unwrap(), "Second");
+        assert_eq!(
+            ctx.search("THIRD", Default::default()).unwrap(),
+            vec![Range::from_offsets(20, 25)]
+        );
+
+        let first_style = || {
+            engine
+                .read()
+                .unwrap()
+                .tree()
+                .paragraphs()
+                .next()
+                .unwrap()
+                .paragraph_style_id
+                .clone()
+        };
+        assert_eq!(first_style(), Some(StyleId::new("Heading1")));
+        assert_eq!(
+            ctx.get_content().unwrap().paragraphs[0].style.as_deref(),
+            Some("Heading1")
+        );
+
+        // Plugin edits are ordinary undo steps
+        engine.write().unwrap().undo().unwrap();
+        assert_ne!(first_style(), Some(StyleId::new("Heading1")));
+        assert_eq!(
+            ctx.get_content().unwrap().text,
+            "Hello there\nSecond!\nThird"
+        );
+
+        assert!(matches!(
+            ctx.delete_text(Range::from_offsets(3, 14)),
+            Err(ApiError::InvalidRange(_))
+        ));
+        assert!(matches!(
+            ctx.apply_style(Range::from_offsets(0, 1), "Missing"),
+            Err(ApiError::NotFound(_))
+        ));
+
+        ctx.set_selection(Selection::new(
+            Position::from_offset(14),
+            Position::from_offset(12),
+            "",
+        ))
+        .unwrap();
+        let selection = ctx.get_selection().unwrap();
+        assert_eq!(selection.text, "Se");
+        assert_eq!((selection.start.line, selection.start.column), (1, 0));
+    }
+
+    #[test]
+    fn test_document_requests_honor_sandbox() {
+        let (mut ctx, _engine) = live_context(&["Hello"]);
+        ctx.set_restrictions(ApiRestrictions::from_permissions(&[
+            Permission::DocumentRead,
+        ]));
+
+        let text = ctx
+            .handle_document_request(
+                host_methods::GET_TEXT,
+                Some(&json!({ "range": Range::from_offsets(1, 4) })),
+            )
+            .unwrap();
+        assert_eq!(text, json!("ell"));
+
+        let insert = ctx.handle_document_request(
+            host_methods::INSERT_TEXT,
+            Some(&json!({ "position": { "offset": 0 }, "text": "Oh, " })),
+        );
+        assert!(matches!(insert, Err(ApiError::PermissionDenied(_))));
+        assert!(matches!(
+            ctx.handle_document_request("document.explode", None),
+            Err(ApiError::NotFound(_))
+        ));
+        assert_eq!(ctx.get_content().unwrap().text, "Hello");
+    }
+}
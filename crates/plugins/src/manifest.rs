@@ -3,6 +3,7 @@
 //! This module defines the structure of plugin manifests, which describe
 //! plugin metadata, permissions, activation events, and contributions.
 
+use edit_engine::Macro;
 use serde::{Deserialize, Serialize};
 
 /// Plugin manifest containing all metadata and configuration
@@ -96,6 +97,11 @@ impl PluginManifest {
         if self.entry.is_empty() {
             return Err(ManifestValidationError::EmptyEntry);
         }
+        for format in &self.contributes.file_formats {
+            if format.extensions.is_empty() || !(format.import || format.export) {
+                return Err(ManifestValidationError::InvalidFileFormat(format.id.clone()));
+            }
+        }
         Ok(())
     }
 }
@@ -113,6 +119,8 @@ pub enum ManifestValidationError {
     EmptyVersion,
     #[error("Plugin entry point cannot be empty")]
     EmptyEntry,
+    #[error("File format needs an extension and import or export support: {0}")]
+    InvalidFileFormat(String),
 }
 
 /// Permissions that plugins can request
@@ -230,6 +238,12 @@ pub struct Contributions {
     pub panels: Vec<PanelContribution>,
     /// Menu entries
     pub menus: Vec<MenuContribution>,
+    /// File format importers/exporters
+    #[serde(default, rename = "fileFormats")]
+    pub file_formats: Vec<FileFormatContribution>,
+    /// Mail merge data source providers
+    #[serde(default, rename = "dataSources")]
+    pub data_sources: Vec<DataSourceContribution>,
 }
 
 impl Contributions {
@@ -238,6 +252,14 @@ impl Contributions {
         Self::default()
     }
 
+    /// Contribute one command per recorded macro, bound to its keybinding
+    pub fn from_macros(macros: &[Macro]) -> Self {
+        Self {
+            commands: macros.iter().map(CommandContribution::from).collect(),
+            ..Self::default()
+        }
+    }
+
     /// Add a command contribution
     pub fn with_command(mut self, command: CommandContribution) -> Self {
         self.commands.push(command);
@@ -261,6 +283,18 @@ impl Contributions {
         self.menus.push(menu);
         self
     }
+
+    /// Add a file format
+    pub fn with_file_format(mut self, format: FileFormatContribution) -> Self {
+        self.file_formats.push(format);
+        self
+    }
+
+    /// Add a mail merge data source provider
+    pub fn with_data_source(mut self, data_source: DataSourceContribution) -> Self {
+        self.data_sources.push(data_source);
+        self
+    }
 }
 
 /// A command contribution from a plugin
@@ -300,6 +334,17 @@ impl CommandContribution {
     }
 }
 
+impl From<&Macro> for CommandContribution {
+    fn from(macro_def: &Macro) -> Self {
+        Self {
+            id: macro_def.command_id(),
+            title: macro_def.name.clone(),
+            keybinding: macro_def.keybinding.clone(),
+            icon: None,
+        }
+    }
+}
+
 /// A toolbar contribution from a plugin
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ToolbarContribution {
@@ -403,6 +448,79 @@ impl MenuContribution {
     }
 }
 
+/// A file format contribution from a plugin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileFormatContribution {
+    /// Format identifier
+    pub id: String,
+    /// Display name (e.g. "Markdown")
+    pub name: String,
+    /// File extensions handled, without the dot
+    pub extensions: Vec<String>,
+    /// MIME type
+    #[serde(default)]
+    pub mime_type: String,
+    /// Whether the plugin can open files in this format
+    pub import: bool,
+    /// Whether the plugin can save files in this format
+    pub export: bool,
+}
+
+impl FileFormatContribution {
+    /// Create an import-only file format contribution
+    pub fn new(id: impl Into<String>, name: impl Into<String>, extensions: &[&str]) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            extensions: extensions.iter().map(|ext| ext.to_string()).collect(),
+            mime_type: String::new(),
+            import: true,
+            export: false,
+        }
+    }
+
+    /// Set the MIME type
+    pub fn with_mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = mime_type.into();
+        self
+    }
+
+    /// Set whether the format can be exported
+    pub fn with_export(mut self, export: bool) -> Self {
+        self.export = export;
+        self
+    }
+}
+
+/// A mail merge data source provider contribution from a plugin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataSourceContribution {
+    /// Provider identifier
+    pub id: String,
+    /// Display name (e.g. "Airtable")
+    pub name: String,
+    /// Description shown when choosing a data source
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl DataSourceContribution {
+    /// Create a new data source contribution
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            description: None,
+        }
+    }
+
+    /// Set the description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -654,6 +772,31 @@ mod tests {
         assert_eq!(menu.group, Some("export".to_string()));
     }
 
+    #[test]
+    fn test_file_format_contribution() {
+        let json = r#"{
+            "commands": [], "toolbar_items": [], "panels": [], "menus": [],
+            "fileFormats": [{
+                "id": "markdown", "name": "Markdown", "extensions": ["md"],
+                "import": true, "export": false
+            }]
+        }"#;
+        let contributions: Contributions = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            contributions.file_formats,
+            vec![FileFormatContribution::new("markdown", "Markdown", &["md"])]
+        );
+
+        let mut manifest = PluginManifest::new("com.example.md", "Markdown", "1.0.0", "Author")
+            .with_contributions(contributions);
+        assert!(manifest.validate().is_ok());
+        manifest.contributes.file_formats[0].import = false;
+        assert_eq!(
+            manifest.validate(),
+            Err(ManifestValidationError::InvalidFileFormat("markdown".to_string()))
+        );
+    }
+
     #[test]
     fn test_panel_location_default() {
         assert_eq!(PanelLocation::default(), PanelLocation::Right);
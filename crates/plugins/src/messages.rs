@@ -203,6 +203,18 @@ pub mod host_methods {
     pub const GET_DOCUMENT: &str = "document.get";
     /// Set document content
     pub const SET_DOCUMENT: &str = "document.set";
+    /// Get the text in a range
+    pub const GET_TEXT: &str = "document.getText";
+    /// Insert text at a position
+    pub const INSERT_TEXT: &str = "document.insert";
+    /// Delete the text in a range
+    pub const DELETE_TEXT: &str = "document.delete";
+    /// Replace the text in a range
+    pub const REPLACE_TEXT: &str = "document.replace";
+    /// Apply a style to a range
+    pub const FORMAT: &str = "document.format";
+    /// Search the document text
+    pub const SEARCH: &str = "document.search";
     /// Get selection
     pub const GET_SELECTION: &str = "selection.get";
     /// Set selection
@@ -237,14 +249,30 @@ pub mod plugin_events {
     pub const DOCUMENT_CLOSED: &str = "document.closed";
     /// Document changed
     pub const DOCUMENT_CHANGED: &str = "document.changed";
+    /// Document saved
+    pub const DOCUMENT_SAVED: &str = "document.saved";
     /// Selection changed
     pub const SELECTION_CHANGED: &str = "selection.changed";
+    /// AutoText suggestion offered or dismissed
+    pub const AUTOTEXT_SUGGESTED: &str = "autotext.suggested";
     /// Plugin activated
     pub const ACTIVATED: &str = "plugin.activated";
     /// Plugin deactivated
     pub const DEACTIVATED: &str = "plugin.deactivated";
 }
 
+/// Standard methods the host calls on plugins
+pub mod plugin_methods {
+    /// Convert file contents to a document
+    pub const IMPORT_FILE: &str = "fileFormat.import";
+    /// Convert a document to file contents
+    pub const EXPORT_FILE: &str = "fileFormat.export";
+    /// List a data source's columns
+    pub const LIST_COLUMNS: &str = "dataSource.listColumns";
+    /// Fetch a page of data source records
+    pub const FETCH_RECORDS: &str = "dataSource.fetchRecords";
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -166,6 +166,7 @@ impl ApiRestrictions {
                     methods.insert("document.get".to_string());
                     methods.insert("document.getText".to_string());
                     methods.insert("document.getSelection".to_string());
+                    methods.insert("selection.get".to_string());
                     methods.insert("document.search".to_string());
                 }
                 Permission::DocumentWrite => {
@@ -174,6 +175,7 @@ impl ApiRestrictions {
                     methods.insert("document.delete".to_string());
                     methods.insert("document.replace".to_string());
                     methods.insert("document.format".to_string());
+                    methods.insert("selection.set".to_string());
                 }
                 Permission::UiToolbar => {
                     methods.insert("ui.addToolbarItem".to_string());
@@ -0,0 +1,135 @@
+//! HTTP translation provider
+//!
+//! Sends text to a translation web service through the plugin `NetworkApi`,
+//! so requests are only made for plugins granted the Network permission.
+//!
+//! The service receives a POST with `{ "q": [segments], "source", "target",
+//! "format": "text" }` (`source` is null to auto-detect) and answers
+//! `{ "translations": [text] }` with one entry per segment.
+
+use crate::api::{FetchOptions, HttpMethod, NetworkApi};
+use edit_engine::{EditError, TranslationProvider};
+use serde::Deserialize;
+use serde_json::json;
+
+/// Reply from a translation service
+#[derive(Deserialize)]
+struct TranslationReply {
+    translations: Vec<String>,
+}
+
+/// Translation provider backed by a web service
+pub struct HttpTranslationProvider<N: NetworkApi> {
+    id: String,
+    name: String,
+    endpoint: String,
+    api_key: Option<String>,
+    network: N,
+}
+
+impl<N: NetworkApi> HttpTranslationProvider<N> {
+    pub fn new(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        endpoint: impl Into<String>,
+        network: N,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            endpoint: endpoint.into(),
+            api_key: None,
+            network,
+        }
+    }
+
+    /// Send an API key as a bearer token
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+}
+
+impl<N: NetworkApi> TranslationProvider for HttpTranslationProvider<N> {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn translate(
+        &self,
+        segments: &[String],
+        from: Option<&str>,
+        to: &str,
+    ) -> edit_engine::Result<Vec<String>> {
+        let body = json!({ "q": segments, "source": from, "target": to, "format": "text" });
+        let mut options = FetchOptions::new().with_method(HttpMethod::Post).with_json_body(&body);
+        if let Some(api_key) = &self.api_key {
+            options = options.with_header("Authorization", format!("Bearer {}", api_key));
+        }
+
+        let failed = |reason: String| {
+            EditError::ExecutionFailed(format!("Translation with {} failed: {}", self.name, reason))
+        };
+        let response = self
+            .network
+            .fetch(&self.endpoint, options)
+            .map_err(|e| failed(e.to_string()))?;
+        if !response.ok {
+            return Err(failed(format!("{} {}", response.status, response.status_text)));
+        }
+        let reply: TranslationReply =
+            serde_json::from_str(&response.body).map_err(|e| failed(e.to_string()))?;
+        if reply.translations.len() != segments.len() {
+            return Err(failed(format!(
+                "expected {} translations, got {}",
+                segments.len(),
+                reply.translations.len()
+            )));
+        }
+        Ok(reply.translations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{ApiResult, PluginApiContext, Response};
+    use crate::permissions::PermissionManager;
+    use std::sync::{Arc, RwLock};
+
+    struct EchoService;
+
+    impl NetworkApi for EchoService {
+        fn fetch(&self, _url: &str, options: FetchOptions) -> ApiResult<Response> {
+            let request: serde_json::Value = serde_json::from_str(&options.body.unwrap()).unwrap();
+            let translations: Vec<String> = request["q"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|s| s.as_str().unwrap().to_uppercase())
+                .collect();
+            Ok(Response::new(200, "OK", json!({ "translations": translations }).to_string()))
+        }
+    }
+
+    #[test]
+    fn test_http_provider_translates_segments() {
+        let provider = HttpTranslationProvider::new("echo", "Echo", "https://translate.test", EchoService);
+        let translated = provider
+            .translate(&["hello ".to_string(), "world".to_string()], None, "fr")
+            .unwrap();
+        assert_eq!(translated, vec!["HELLO ".to_string(), "WORLD".to_string()]);
+    }
+
+    #[test]
+    fn test_http_provider_requires_network_permission() {
+        let context = PluginApiContext::new("com.test.translate", Arc::new(RwLock::new(PermissionManager::new())));
+        let provider = HttpTranslationProvider::new("web", "Web", "https://translate.test", context);
+        let err = provider.translate(&["hello".to_string()], Some("en"), "fr").unwrap_err();
+        assert!(err.to_string().contains("Permission denied"));
+    }
+}
@@ -0,0 +1,416 @@
+//! WebAssembly plugin runtime
+//!
+//! Plugins compiled to WebAssembly run in a `wasmi` interpreter with the
+//! plugin's `SandboxConfig` enforced by the engine: linear memory cannot
+//! grow beyond `max_memory_bytes`, and every call into the plugin gets a
+//! fuel budget derived from `max_cpu_time`.
+//!
+//! # ABI
+//!
+//! Messages cross the boundary as JSON in the plugin's linear memory,
+//! addressed by a pointer and length packed into an `i64` as
+//! `(ptr << 32) | len`. A plugin module exports:
+//!
+//! - `memory`: its linear memory
+//! - `alloc(len: i32) -> i32`: reserve `len` bytes for the host to write
+//! - `on_message(ptr: i32, len: i32) -> i64`: handle a `HostMessage` and
+//!   return a `PluginMessage` response, or 0 for none
+//! - `activate()` (optional): run once when an activation event fires
+//!
+//! It may import `go_word.host_call(ptr: i32, len: i32) -> i64`, which
+//! sends a `PluginRequest` to the host and returns the `PluginMessage`
+//! response, written into memory reserved through `alloc`.
+
+use crate::error::{PluginError, PluginErrorCode, Result, SerializablePluginError};
+use crate::messages::{HostMessage, PluginMessage, PluginRequest};
+use crate::sandbox::{ResourceUsage, SandboxConfig};
+use serde_json::Value;
+use std::time::Duration;
+use wasmi::core::TrapCode;
+use wasmi::{
+    AsContext, AsContextMut, Caller, Config, Engine, Instance, Linker, Memory, Module, Store,
+    StoreLimits, StoreLimitsBuilder, TypedFunc,
+};
+
+/// Import module name for host functions
+pub const HOST_MODULE: &str = "go_word";
+
+/// Fuel granted per millisecond of allowed CPU time
+pub const FUEL_PER_MILLISECOND: u64 = 100_000;
+
+/// Handles requests a plugin sends to the host
+pub type HostCallHandler = Box<dyn FnMut(&PluginRequest) -> Result<Value> + Send>;
+
+/// Store data shared with host functions
+struct HostState {
+    limits: StoreLimits,
+    config: SandboxConfig,
+    usage: ResourceUsage,
+    handler: Option<HostCallHandler>,
+}
+
+impl HostState {
+    fn call_host(&mut self, request: &PluginRequest) -> Result<Value> {
+        self.usage.record_api_call();
+        if self.usage.is_rate_limit_exceeded(&self.config) {
+            return Err(PluginError::resource_limit_exceeded(format!(
+                "API rate limit of {} calls/minute exceeded",
+                self.config.max_api_calls_per_minute
+            )));
+        }
+
+        match self.handler.as_mut() {
+            Some(handler) => handler(request),
+            None => Err(PluginError::MethodNotFound(request.method.clone())),
+        }
+    }
+}
+
+/// Pack a pointer and length into the ABI's `i64`
+fn pack(ptr: i32, len: i32) -> i64 {
+    (((ptr as u32 as u64) << 32) | len as u32 as u64) as i64
+}
+
+/// Split an ABI `i64` into a pointer and length
+fn unpack(packed: i64) -> (i32, i32) {
+    let packed = packed as u64;
+    ((packed >> 32) as u32 as i32, packed as u32 as i32)
+}
+
+/// Read `len` bytes at `ptr` from plugin memory
+fn read_bytes(
+    ctx: impl AsContext,
+    memory: Memory,
+    ptr: i32,
+    len: i32,
+) -> std::result::Result<Vec<u8>, wasmi::Error> {
+    let mut buffer = vec![0; len as u32 as usize];
+    memory
+        .read(ctx, ptr as u32 as usize, &mut buffer)
+        .map_err(|e| wasmi::Error::new(e.to_string()))?;
+    Ok(buffer)
+}
+
+/// Copy bytes into memory reserved by the plugin's `alloc`
+fn write_bytes(
+    mut ctx: impl AsContextMut,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    bytes: &[u8],
+) -> std::result::Result<(i32, i32), wasmi::Error> {
+    let len = i32::try_from(bytes.len()).map_err(|_| wasmi::Error::new("Message too large"))?;
+    let ptr = alloc.call(&mut ctx, len)?;
+    memory
+        .write(&mut ctx, ptr as u32 as usize, bytes)
+        .map_err(|e| wasmi::Error::new(e.to_string()))?;
+    Ok((ptr, len))
+}
+
+/// The `go_word.host_call` import
+fn host_call(
+    mut caller: Caller<'_, HostState>,
+    ptr: i32,
+    len: i32,
+) -> std::result::Result<i64, wasmi::Error> {
+    let memory = caller
+        .get_export("memory")
+        .and_then(|export| export.into_memory())
+        .ok_or_else(|| wasmi::Error::new("Plugin does not export 'memory'"))?;
+    let alloc = caller
+        .get_export("alloc")
+        .and_then(|export| export.into_func())
+        .ok_or_else(|| wasmi::Error::new("Plugin does not export 'alloc'"))?
+        .typed::<i32, i32>(&caller)?;
+
+    let bytes = read_bytes(&caller, memory, ptr, len)?;
+    let response = match serde_json::from_slice::<PluginRequest>(&bytes) {
+        Ok(request) => match caller.data_mut().call_host(&request) {
+            Ok(result) => PluginMessage::response(request.id, result),
+            Err(e) => PluginMessage::error_response(request.id, (&e).into()),
+        },
+        Err(e) => PluginMessage::error_response(
+            0,
+            SerializablePluginError::new(
+                PluginErrorCode::CommunicationError,
+                format!("Malformed request: {}", e),
+            ),
+        ),
+    };
+
+    let bytes = serde_json::to_vec(&response).map_err(|e| wasmi::Error::new(e.to_string()))?;
+    let (ptr, len) = write_bytes(&mut caller, memory, alloc, &bytes)?;
+    Ok(pack(ptr, len))
+}
+
+/// Map an engine error, reporting fuel exhaustion as a CPU limit violation
+fn engine_error(error: wasmi::Error) -> PluginError {
+    if error.as_trap_code() == Some(TrapCode::OutOfFuel) {
+        PluginError::resource_limit_exceeded("CPU time limit exceeded")
+    } else {
+        PluginError::execution(error.to_string())
+    }
+}
+
+/// Fuel budget for one call into a plugin
+fn fuel_budget(config: &SandboxConfig) -> u64 {
+    let millis = u64::try_from(config.max_cpu_time.as_millis()).unwrap_or(u64::MAX);
+    millis.saturating_mul(FUEL_PER_MILLISECOND)
+}
+
+/// An instantiated WebAssembly plugin
+pub struct WasmPlugin {
+    store: Store<HostState>,
+    instance: Instance,
+    activated: bool,
+}
+
+impl WasmPlugin {
+    /// Compile and instantiate a plugin module under a sandbox configuration
+    pub fn new(wasm: &[u8], config: &SandboxConfig) -> Result<Self> {
+        let mut engine_config = Config::default();
+        engine_config.consume_fuel(true);
+        let engine = Engine::new(&engine_config);
+        let module = Module::new(&engine, wasm)
+            .map_err(|e| PluginError::execution(format!("Invalid WebAssembly module: {}", e)))?;
+
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(usize::try_from(config.max_memory_bytes).unwrap_or(usize::MAX))
+            .instances(1)
+            .memories(1)
+            .build();
+        let state = HostState {
+            limits,
+            config: config.clone(),
+            usage: ResourceUsage::new(),
+            handler: None,
+        };
+        let mut store = Store::new(&engine, state);
+        store.limiter(|state| &mut state.limits);
+
+        let mut linker = Linker::new(&engine);
+        linker
+            .func_wrap(HOST_MODULE, "host_call", host_call)
+            .map_err(|e| PluginError::execution(e.to_string()))?;
+
+        store
+            .set_fuel(fuel_budget(config))
+            .map_err(|e| PluginError::execution(e.to_string()))?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(engine_error)?;
+
+        let mut plugin = Self {
+            store,
+            instance,
+            activated: false,
+        };
+        plugin.charge()?;
+
+        for export in ["memory", "alloc", "on_message"] {
+            if plugin.instance.get_export(&plugin.store, export).is_none() {
+                return Err(PluginError::execution(format!(
+                    "Plugin module does not export '{}'",
+                    export
+                )));
+            }
+        }
+        Ok(plugin)
+    }
+
+    /// Set the handler for requests the plugin sends to the host
+    pub fn set_host_handler(&mut self, handler: HostCallHandler) {
+        self.store.data_mut().handler = Some(handler);
+    }
+
+    /// Resources used so far
+    pub fn usage(&self) -> &ResourceUsage {
+        &self.store.data().usage
+    }
+
+    /// Check if the plugin's `activate` export has run
+    pub fn is_activated(&self) -> bool {
+        self.activated
+    }
+
+    /// Run the plugin's `activate` export, once
+    pub fn activate(&mut self) -> Result<()> {
+        if self.activated {
+            return Ok(());
+        }
+
+        if let Ok(activate) = self
+            .instance
+            .get_typed_func::<(), ()>(&self.store, "activate")
+        {
+            self.refuel()?;
+            let result = activate.call(&mut self.store, ());
+            self.charge()?;
+            result.map_err(engine_error)?;
+        }
+        self.activated = true;
+        Ok(())
+    }
+
+    /// Deliver a message to the plugin and return its response, if any
+    pub fn handle_message(&mut self, message: &HostMessage) -> Result<Option<PluginMessage>> {
+        let bytes =
+            serde_json::to_vec(message).map_err(|e| PluginError::communication(e.to_string()))?;
+        let memory = self.memory()?;
+        let alloc = self.typed_export::<i32, i32>("alloc")?;
+        let on_message = self.typed_export::<(i32, i32), i64>("on_message")?;
+
+        self.refuel()?;
+        let result = write_bytes(&mut self.store, memory, alloc, &bytes)
+            .and_then(|(ptr, len)| on_message.call(&mut self.store, (ptr, len)));
+        self.charge()?;
+        let packed = result.map_err(engine_error)?;
+        if packed == 0 {
+            return Ok(None);
+        }
+
+        let (ptr, len) = unpack(packed);
+        let bytes = read_bytes(&self.store, memory, ptr, len).map_err(engine_error)?;
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| PluginError::communication(format!("Malformed response: {}", e)))
+    }
+
+    fn memory(&self) -> Result<Memory> {
+        self.instance
+            .get_memory(&self.store, "memory")
+            .ok_or_else(|| PluginError::execution("Plugin module does not export 'memory'"))
+    }
+
+    fn typed_export<Params, Results>(&self, name: &str) -> Result<TypedFunc<Params, Results>>
+    where
+        Params: wasmi::WasmParams,
+        Results: wasmi::WasmResults,
+    {
+        self.instance
+            .get_typed_func::<Params, Results>(&self.store, name)
+            .map_err(|e| PluginError::execution(format!("Invalid export '{}': {}", name, e)))
+    }
+
+    /// Reset the fuel budget before a call
+    fn refuel(&mut self) -> Result<()> {
+        let budget = fuel_budget(&self.store.data().config);
+        self.store
+            .set_fuel(budget)
+            .map_err(|e| PluginError::execution(e.to_string()))
+    }
+
+    /// Record the fuel and memory used by the last call
+    fn charge(&mut self) -> Result<()> {
+        let remaining = self
+            .store
+            .get_fuel()
+            .map_err(|e| PluginError::execution(e.to_string()))?;
+        let consumed = fuel_budget(&self.store.data().config).saturating_sub(remaining);
+        let memory_bytes = self
+            .instance
+            .get_memory(&self.store, "memory")
+            .map(|memory| memory.data(&self.store).len() as u64)
+            .unwrap_or(0);
+
+        let usage = &mut self.store.data_mut().usage;
+        usage.add_cpu_time(Duration::from_nanos(
+            consumed.saturating_mul(1_000_000) / FUEL_PER_MILLISECOND,
+        ));
+        usage.update_memory(memory_bytes);
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for WasmPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WasmPlugin")
+            .field("activated", &self.activated)
+            .field("usage", self.usage())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Forwards every message to the host as a `document.get` request and
+    /// replies with the host's response
+    const FORWARDING_PLUGIN: &str = r#"
+        (module
+          (import "go_word" "host_call" (func $host_call (param i32 i32) (result i64)))
+          (memory (export "memory") 1)
+          (global $heap (mut i32) (i32.const 1024))
+          (data (i32.const 0) "{\"id\":7,\"method\":\"document.get\",\"params\":null}")
+          (func (export "alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            (local.set $ptr (global.get $heap))
+            (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+            (local.get $ptr))
+          (func (export "on_message") (param i32 i32) (result i64)
+            (call $host_call (i32.const 0) (i32.const 46))))
+    "#;
+
+    /// Never returns from a message
+    const SPINNING_PLUGIN: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (func (export "alloc") (param i32) (result i32) (i32.const 0))
+          (func (export "on_message") (param i32 i32) (result i64)
+            (loop $spin (br $spin))
+            (i64.const 0)))
+    "#;
+
+    fn compile(source: &str) -> Vec<u8> {
+        wat::parse_str(source).unwrap()
+    }
+
+    #[test]
+    fn test_messages_round_trip_through_host_calls() {
+        let mut plugin =
+            WasmPlugin::new(&compile(FORWARDING_PLUGIN), &SandboxConfig::default()).unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        plugin.set_host_handler(Box::new(move |request| {
+            log.lock().unwrap().push(request.method.clone());
+            Ok(serde_json::json!({ "text": "Hello" }))
+        }));
+
+        let reply = plugin
+            .handle_message(&HostMessage::request("command.run", None))
+            .unwrap()
+            .unwrap();
+        assert_eq!(reply.id, 7);
+        assert_eq!(
+            reply.get_result(),
+            Some(&serde_json::json!({ "text": "Hello" }))
+        );
+        assert_eq!(*seen.lock().unwrap(), vec!["document.get".to_string()]);
+        assert_eq!(plugin.usage().api_calls_this_minute, 1);
+        assert_eq!(plugin.usage().memory_bytes, 64 * 1024);
+        assert!(plugin.usage().cpu_time > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_sandbox_limits_are_enforced() {
+        let config = SandboxConfig::default().with_max_cpu_time(Duration::from_millis(1));
+        let mut plugin = WasmPlugin::new(&compile(SPINNING_PLUGIN), &config).unwrap();
+        let result = plugin.handle_message(&HostMessage::event("document.changed", None));
+        assert!(matches!(result, Err(PluginError::ResourceLimitExceeded(_))));
+
+        // Initial memory beyond the limit fails to instantiate
+        let small = SandboxConfig::default().with_max_memory(32 * 1024);
+        assert!(WasmPlugin::new(&compile(SPINNING_PLUGIN), &small).is_err());
+
+        // Without a handler host calls fail inside the plugin, not the host
+        let mut plugin =
+            WasmPlugin::new(&compile(FORWARDING_PLUGIN), &SandboxConfig::default()).unwrap();
+        let reply = plugin
+            .handle_message(&HostMessage::event("document.changed", None))
+            .unwrap()
+            .unwrap();
+        assert!(reply.is_error());
+    }
+}
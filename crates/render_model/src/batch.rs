@@ -0,0 +1,200 @@
+//! Render item batching
+//!
+//! Drawing render items one at a time costs a draw call each. `batch_items`
+//! groups a page's items into batches a canvas or WebGL renderer can draw
+//! together: glyph runs sharing a font and color, fills of one color, and
+//! so on. Drawing the batches in order looks the same as drawing the items
+//! in order.
+
+use crate::{font_key, Color, PageRender, RenderItem};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// What a batch draws, in the order batches of one z-order are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum BatchKind {
+    /// Filled areas: page backgrounds, table cells, highlights, selections
+    Fill,
+    /// Lines and table borders
+    Stroke,
+    /// Glyph runs and line numbers
+    Text,
+    /// Squiggly underlines
+    Decoration,
+    /// An image, shape, text box, or watermark (one per batch)
+    Object,
+    /// The caret
+    Overlay,
+}
+
+/// Render items that can be drawn together
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenderBatch {
+    /// Stacking order; batches are drawn by z-order, then kind
+    pub z_order: u32,
+    /// What the batch draws
+    pub kind: BatchKind,
+    /// Font of every item, for text batches
+    pub font_key: Option<u32>,
+    /// Color of every item, when the items have one color
+    pub color: Option<Color>,
+    /// Indices of the items in the page's item list, in paint order
+    pub items: Vec<usize>,
+}
+
+/// Group render items into batches
+///
+/// Images, shapes, text boxes, and watermarks can cover what's painted before them and
+/// be covered by what's painted after, so each gets a z-order of its own.
+/// The items between them share a z-order and are batched freely: fills
+/// under strokes under text.
+pub fn batch_items(items: &[RenderItem]) -> Vec<RenderBatch> {
+    let mut batches: Vec<RenderBatch> = Vec::new();
+    let mut open: HashMap<(BatchKind, Option<u32>, Option<Color>), usize> = HashMap::new();
+    let mut z_order = 0;
+
+    for (index, item) in items.iter().enumerate() {
+        let (kind, font, color) = batch_key(item);
+        if kind == BatchKind::Object {
+            batches.push(RenderBatch {
+                z_order: z_order + 1,
+                kind,
+                font_key: None,
+                color: None,
+                items: vec![index],
+            });
+            z_order += 2;
+            open.clear();
+            continue;
+        }
+
+        match open.get(&(kind, font, color)) {
+            Some(&batch) => batches[batch].items.push(index),
+            None => {
+                open.insert((kind, font, color), batches.len());
+                batches.push(RenderBatch {
+                    z_order,
+                    kind,
+                    font_key: font,
+                    color,
+                    items: vec![index],
+                });
+            }
+        }
+    }
+
+    // Stable, so batches of one z-order and kind stay in paint order
+    batches.sort_by_key(|batch| (batch.z_order, batch.kind));
+    batches
+}
+
+/// The kind, font, and color an item is batched by
+fn batch_key(item: &RenderItem) -> (BatchKind, Option<u32>, Option<Color>) {
+    match item {
+        RenderItem::GlyphRun(run) => {
+            // A fill effect replaces the run's color
+            let color = run.effects.fill.is_none().then_some(run.color);
+            (BatchKind::Text, Some(font_key(&run.font_family, run.bold, run.italic)), color)
+        }
+        RenderItem::LineNumber(number) => (
+            BatchKind::Text,
+            Some(font_key(&number.font_family, false, false)),
+            Some(number.color),
+        ),
+        RenderItem::Rectangle { fill, stroke, .. } => (BatchKind::Fill, None, fill.or(*stroke)),
+        RenderItem::TableCell(cell) => (BatchKind::Fill, None, cell.background),
+        RenderItem::FindHighlight { color, .. } | RenderItem::Selection { color, .. } => {
+            (BatchKind::Fill, None, Some(*color))
+        }
+        RenderItem::Line { color, .. } => (BatchKind::Stroke, None, Some(*color)),
+        RenderItem::TableBorder(border) => (BatchKind::Stroke, None, Some(border.color)),
+        RenderItem::Squiggly(squiggly) => (BatchKind::Decoration, None, Some(squiggly.color)),
+        RenderItem::Image(_)
+        | RenderItem::Shape(_)
+        | RenderItem::TextBox(_)
+        | RenderItem::Watermark(_)
+        | RenderItem::Ink(_)
+        | RenderItem::EmbeddedObject(_) => {
+            (BatchKind::Object, None, None)
+        }
+        RenderItem::RemoteSelection(selection) => (BatchKind::Fill, None, Some(selection.color)),
+        RenderItem::Caret { color, .. } => (BatchKind::Overlay, None, Some(*color)),
+        RenderItem::RemoteCaret(caret) => (BatchKind::Overlay, None, Some(caret.color)),
+    }
+}
+
+impl PageRender {
+    /// Group this page's items into draw batches
+    pub fn batches(&self) -> Vec<RenderBatch> {
+        batch_items(&self.items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GlyphRun, ImageRenderInfo, Rect, TextEffects};
+
+    fn text(font_family: &str, color: Color) -> RenderItem {
+        RenderItem::GlyphRun(GlyphRun {
+            text: "text".to_string(),
+            font_family: font_family.to_string(),
+            font_size: 12.0,
+            bold: false,
+            italic: false,
+            underline: false,
+            color,
+            x: 0.0,
+            y: 0.0,
+            hyperlink: None,
+            effects: TextEffects::default(),
+            font_key: 0,
+            glyphs: Vec::new(),
+        })
+    }
+
+    fn background() -> RenderItem {
+        RenderItem::Rectangle {
+            bounds: Rect::new(0.0, 0.0, 612.0, 792.0),
+            fill: Some(Color::WHITE),
+            stroke: None,
+            stroke_width: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_batches_group_text_by_font_and_color() {
+        let red = Color::rgb(255, 0, 0);
+        let items = vec![
+            background(),
+            text("Arial", Color::BLACK),
+            text("Times", Color::BLACK),
+            text("Arial", red),
+            text("Arial", Color::BLACK),
+            background(),
+        ];
+
+        let batches = batch_items(&items);
+        let groups: Vec<(BatchKind, Vec<usize>)> = batches.iter().map(|b| (b.kind, b.items.clone())).collect();
+        assert_eq!(
+            groups,
+            vec![
+                (BatchKind::Fill, vec![0, 5]),
+                (BatchKind::Text, vec![1, 4]),
+                (BatchKind::Text, vec![2]),
+                (BatchKind::Text, vec![3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_objects_split_z_order() {
+        let image = RenderItem::Image(ImageRenderInfo::new("image", "resource", Rect::new(0.0, 0.0, 10.0, 10.0)));
+        let items = vec![text("Arial", Color::BLACK), image, text("Arial", Color::BLACK)];
+
+        let batches = batch_items(&items);
+        let order: Vec<(u32, Vec<usize>)> = batches.iter().map(|b| (b.z_order, b.items.clone())).collect();
+        // Text painted over the image stays above it
+        assert_eq!(order, vec![(0, vec![0]), (1, vec![1]), (2, vec![2])]);
+    }
+}
@@ -1,13 +1,22 @@
 //! Convert layout tree to render model
 
+use crate::viewport::overscanned_pages;
 use crate::{
-    Color, DashStyleRender, GlyphRun, HyperlinkRenderInfo, HyperlinkType, ImageRenderInfo,
-    LineNumberRenderInfo, PageRender, Rect, RenderItem, RenderModel, Result, ShapeFillRender,
-    ShapeRenderInfo, ShapeRenderType, ShapeStrokeRender, ShadowRender, TextBoxBorderEdgeRender,
-    TextBoxBorderRender, TextBoxFillRender, TextBoxRenderInfo,
+    font_key, glyph_atlas_key, stacked_page_tops, Color, DashStyleRender, EmbeddedObjectRenderInfo, GlyphRun, HyperlinkRenderInfo, HyperlinkType,
+    ImageRenderInfo, InkRenderInfo, InkStrokeRender, LineNumberRenderInfo, PageRender, Rect, RenderError, RenderItem, RenderModel,
+    Result, ShapeFillRender, ShapeRenderInfo, ShapeRenderType, ShapeStrokeRender, ShadowRender,
+    TextBoxBorderEdgeRender, TextBoxBorderRender, TextBoxFillRender, TextBoxRenderInfo,
+    RenderGlyph, TableBorderRenderInfo, TableCellRenderInfo, TextEffects, Viewport, ViewportFrame,
+    WatermarkRenderContent, WatermarkRenderInfo,
 };
-use doc_model::{BorderLineStyle, DashStyle, DocumentTree, FillStyle, HyperlinkTarget, ShapeFill, ShapeType, TextBox};
-use layout_engine::{InlineType, LayoutTree};
+use doc_model::{
+    BorderLineStyle, DashStyle, DocumentTree, FillStyle, HyperlinkTarget, Node, ShapeFill, ShapeType,
+    TableBorder, TableBorderStyle, TextBox,
+};
+use layout_engine::{
+    DirtyPages, InlineType, LayoutTree, PageBox, PageWatermark, TableLayout, WatermarkContent,
+};
+use text_engine::{ShapeCache, TextShaper};
 
 /// Configuration for render conversion
 #[derive(Debug, Clone)]
@@ -36,11 +45,27 @@ impl Default for RenderConfig {
 /// Converts layout tree to render model
 pub struct RenderConverter {
     config: RenderConfig,
+    /// Shapes glyph runs into glyph IDs
+    shaper: TextShaper,
 }
 
 impl RenderConverter {
     pub fn new(config: RenderConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            shaper: TextShaper::new(),
+        }
+    }
+
+    /// Share shaped runs with the layout engine's shaper
+    pub fn with_shape_cache(mut self, cache: ShapeCache) -> Self {
+        self.shaper.set_shape_cache(cache);
+        self
+    }
+
+    /// Get the text shaper, e.g. to load fonts
+    pub fn shaper_mut(&mut self) -> &mut TextShaper {
+        &mut self.shaper
     }
 
     /// Convert a layout tree to a render model
@@ -48,112 +73,273 @@ impl RenderConverter {
         let mut model = RenderModel::new();
 
         for page in &layout.pages {
-            let mut page_render = PageRender {
-                page_index: page.index as u32,
-                width: page.bounds.width as f64,
-                height: page.bounds.height as f64,
-                items: Vec::new(),
-            };
-
-            // Add page background
-            page_render.items.push(RenderItem::Rectangle {
-                bounds: Rect::from(page.bounds),
-                fill: Some(self.config.page_background),
-                stroke: Some(Color::rgb(200, 200, 200)),
-                stroke_width: 1.0,
+            model.add_page(self.convert_page(layout, page, tree));
+        }
+
+        Ok(model)
+    }
+
+    /// Bring a render model up to date after an incremental relayout,
+    /// converting only the dirty pages
+    pub fn update(
+        &self,
+        model: &mut RenderModel,
+        layout: &LayoutTree,
+        tree: &DocumentTree,
+        dirty: &DirtyPages,
+    ) -> Result<()> {
+        model.pages.truncate(dirty.page_count);
+        for &page_index in &dirty.pages {
+            let page = layout.pages.get(page_index).ok_or_else(|| {
+                RenderError::InvalidLayout(format!("Dirty page {} is not in the layout", page_index))
+            })?;
+            let page_render = self.convert_page(layout, page, tree);
+
+            if page_index < model.pages.len() {
+                model.pages[page_index] = page_render;
+            } else if page_index == model.pages.len() {
+                model.add_page(page_render);
+            } else {
+                return Err(RenderError::InvalidLayout(format!(
+                    "Page {} is dirty but page {} was never rendered",
+                    page_index,
+                    model.pages.len()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Convert only what a viewport shows: the pages intersecting it and its
+    /// overscan, filtered down to the items that do
+    ///
+    /// Pages are stacked vertically with `page_gap` between them.
+    pub fn convert_viewport(
+        &self,
+        layout: &LayoutTree,
+        tree: &DocumentTree,
+        viewport: &Viewport,
+        page_gap: f64,
+    ) -> Result<ViewportFrame> {
+        let heights: Vec<f64> = layout.pages.iter().map(|page| page.bounds.height as f64).collect();
+        let tops = stacked_page_tops(&heights, page_gap);
+
+        let mut frame = ViewportFrame::new(&tops, &heights, page_gap);
+        for index in overscanned_pages(&tops, &heights, viewport) {
+            let page = self.convert_page(layout, &layout.pages[index], tree);
+            frame.add_page(&page, tops[index], viewport);
+        }
+        Ok(frame)
+    }
+
+    /// Convert a single laid out page
+    fn convert_page(&self, layout: &LayoutTree, page: &PageBox, tree: &DocumentTree) -> PageRender {
+        let mut page_render = PageRender {
+            page_index: page.index as u32,
+            width: page.bounds.width as f64,
+            height: page.bounds.height as f64,
+            items: Vec::new(),
+        };
+
+        // Add page background
+        page_render.items.push(RenderItem::Rectangle {
+            bounds: Rect::from(page.bounds),
+            fill: Some(self.config.page_background),
+            stroke: Some(Color::rgb(200, 200, 200)),
+            stroke_width: 1.0,
+        });
+
+        // The watermark sits on the background, under everything else
+        if let Some(watermark) = &page.watermark {
+            page_render.items.push(RenderItem::Watermark(convert_watermark(watermark)));
+        }
+
+        for separator in &page.note_separators {
+            page_render.items.push(RenderItem::Line {
+                x1: separator.x as f64,
+                y1: separator.y as f64,
+                x2: (separator.x + separator.width) as f64,
+                y2: separator.y as f64,
+                color: self.config.text_color,
+                width: separator.weight as f64,
             });
+        }
 
-            // Render each area
-            for area in &page.areas {
-                for column in &area.columns {
-                    for block in &column.blocks {
-                        // Render each line in the block
-                        for line in &block.lines {
-                            // Render each inline in the line
-                            for inline in &line.inlines {
-                                match inline.inline_type {
-                                    InlineType::Text => {
-                                        // Get the text from the run
-                                        if let Some(run) = tree.get_run(inline.node_id) {
-                                            let text = if inline.end_offset <= run.text.len() {
-                                                &run.text[inline.start_offset..inline.end_offset]
+        // Render each area
+        for area in &page.areas {
+            for column in &area.columns {
+                for block in &column.blocks {
+                    // Render each line in the block
+                    for line in &block.lines {
+                        // Render each inline in the line
+                        for inline in &line.inlines {
+                            match inline.inline_type {
+                                InlineType::Text => {
+                                    // Get the text from the run
+                                    if let Some(run) = tree.get_run(inline.node_id) {
+                                        let text = if inline.end_offset <= run.text.len() {
+                                            &run.text[inline.start_offset..inline.end_offset]
+                                        } else {
+                                            &run.text
+                                        };
+
+                                        if !text.is_empty() {
+                                            let format = tree.compute_character_properties(inline.node_id).unwrap_or_default();
+                                            let baseline_y = page.content_area.y + line.bounds.y + line.baseline
+                                                - format.position.unwrap_or(0.0);
+                                            let text = if format.all_caps == Some(true) {
+                                                format.display_text(text)
                                             } else {
-                                                &run.text
+                                                text.to_string()
                                             };
 
-                                            if !text.is_empty() {
-                                                let baseline_y = page.content_area.y + line.bounds.y + line.baseline;
+                                            // Check if this run is inside a hyperlink
+                                            let hyperlink_info = self.get_hyperlink_info(tree, inline.node_id);
+                                            let (text_color, is_underline) = if hyperlink_info.is_some() {
+                                                // Hyperlink styling: blue and underlined
+                                                (Color::rgb(0, 0, 255), true)
+                                            } else {
+                                                // Use run style color or default
+                                                let color = run.style.color.as_ref()
+                                                    .and_then(|c| parse_color(c))
+                                                    .unwrap_or(self.config.text_color);
+                                                (color, run.style.underline.unwrap_or(false))
+                                            };
 
-                                                // Check if this run is inside a hyperlink
-                                                let hyperlink_info = self.get_hyperlink_info(tree, inline.node_id);
-                                                let (text_color, is_underline) = if hyperlink_info.is_some() {
-                                                    // Hyperlink styling: blue and underlined
-                                                    (Color::rgb(0, 0, 255), true)
-                                                } else {
-                                                    // Use run style color or default
-                                                    let color = run.style.color.as_ref()
-                                                        .and_then(|c| parse_color(c))
-                                                        .unwrap_or(self.config.text_color);
-                                                    (color, run.style.underline.unwrap_or(false))
-                                                };
+                                            page_render.items.push(RenderItem::GlyphRun(GlyphRun {
+                                                text,
+                                                font_family: run.style.font_family
+                                                    .as_ref()
+                                                    .unwrap_or(&self.config.font_family)
+                                                    .clone(),
+                                                font_size: run.style.font_size
+                                                    .map(|s| s as f64)
+                                                    .unwrap_or(self.config.font_size),
+                                                bold: run.style.bold.unwrap_or(false),
+                                                italic: run.style.italic.unwrap_or(false),
+                                                underline: is_underline,
+                                                color: text_color,
+                                                x: (page.content_area.x + inline.bounds.x) as f64,
+                                                y: baseline_y as f64,
+                                                hyperlink: hyperlink_info,
+                                                effects: TextEffects::from_properties(&format, &tree.theme),
+                                                font_key: 0,
+                                                glyphs: Vec::new(),
+                                            }));
 
+                                            // Ruby over the base text
+                                            if let Some(ruby) = &inline.ruby {
                                                 page_render.items.push(RenderItem::GlyphRun(GlyphRun {
-                                                    text: text.to_string(),
+                                                    text: ruby.text.to_string(),
                                                     font_family: run.style.font_family
                                                         .as_ref()
                                                         .unwrap_or(&self.config.font_family)
                                                         .clone(),
-                                                    font_size: run.style.font_size
-                                                        .map(|s| s as f64)
-                                                        .unwrap_or(self.config.font_size),
-                                                    bold: run.style.bold.unwrap_or(false),
-                                                    italic: run.style.italic.unwrap_or(false),
-                                                    underline: is_underline,
+                                                    font_size: ruby.font_size as f64,
+                                                    bold: false,
+                                                    italic: false,
+                                                    underline: false,
                                                     color: text_color,
-                                                    x: (page.content_area.x + inline.bounds.x) as f64,
-                                                    y: baseline_y as f64,
-                                                    hyperlink: hyperlink_info,
+                                                    x: (page.content_area.x + ruby.x) as f64,
+                                                    y: (page.content_area.y + line.bounds.y + ruby.baseline) as f64,
+                                                    hyperlink: None,
+                                                    effects: TextEffects {
+                                                        letter_spacing: ruby.letter_spacing as f64,
+                                                        ..TextEffects::default()
+                                                    },
+                                                    font_key: 0,
+                                                    glyphs: Vec::new(),
                                                 }));
                                             }
                                         }
                                     }
-                                    InlineType::Image => {
-                                        // Render inline image
-                                        if let Some(image) = tree.get_image(inline.node_id) {
-                                            let image_x = page.content_area.x + inline.bounds.x;
-                                            let image_y = page.content_area.y + line.bounds.y + inline.bounds.y;
-
-                                            page_render.items.push(RenderItem::Image(ImageRenderInfo {
-                                                node_id: inline.node_id.to_string(),
-                                                resource_id: image.resource_id.to_string(),
-                                                bounds: Rect::new(
-                                                    image_x as f64,
-                                                    image_y as f64,
-                                                    inline.bounds.width as f64,
-                                                    inline.bounds.height as f64,
-                                                ),
-                                                rotation: image.properties.rotation as f64,
-                                                alt_text: image.alt_text.clone(),
-                                                title: image.title.clone(),
-                                                selected: false,
-                                            }));
-                                        }
+                                }
+                                InlineType::Image => {
+                                    // Render inline image
+                                    if let Some(image) = tree.get_image(inline.node_id) {
+                                        let image_x = page.content_area.x + inline.bounds.x;
+                                        let image_y = page.content_area.y + line.bounds.y + inline.bounds.y;
+
+                                        page_render.items.push(RenderItem::Image(ImageRenderInfo {
+                                            node_id: inline.node_id.to_string(),
+                                            resource_id: image.resource_id.to_string(),
+                                            bounds: Rect::new(
+                                                image_x as f64,
+                                                image_y as f64,
+                                                inline.bounds.width as f64,
+                                                inline.bounds.height as f64,
+                                            ),
+                                            rotation: image.properties.rotation as f64,
+                                            alt_text: image.alt_text.clone(),
+                                            title: image.title.clone(),
+                                            selected: false,
+                                        }));
+                                    } else if let Some(object) = tree.get_embedded_object(inline.node_id) {
+                                        let object_x = page.content_area.x + inline.bounds.x;
+                                        let object_y = page.content_area.y + line.bounds.y + inline.bounds.y;
+
+                                        page_render.items.push(RenderItem::EmbeddedObject(EmbeddedObjectRenderInfo {
+                                            node_id: inline.node_id.to_string(),
+                                            bounds: Rect::new(
+                                                object_x as f64,
+                                                object_y as f64,
+                                                inline.bounds.width as f64,
+                                                inline.bounds.height as f64,
+                                            ),
+                                            label: object.display_name(),
+                                            preview_content_type: object.preview.as_ref().map(|p| p.content_type.clone()),
+                                            linked: object.is_linked(),
+                                            selected: false,
+                                        }));
+                                    }
+                                }
+                                InlineType::ListMarker => {
+                                    // Render list marker (bullet or number)
+                                    if let Some(marker) = &inline.list_marker {
+                                        let baseline_y = page.content_area.y + line.bounds.y + line.baseline;
+
+                                        page_render.items.push(RenderItem::GlyphRun(GlyphRun {
+                                            text: marker.text.to_string(),
+                                            font_family: marker.font.as_deref().map(str::to_string).unwrap_or_else(|| {
+                                                if marker.is_bullet {
+                                                    "Symbol".to_string()
+                                                } else {
+                                                    self.config.font_family.clone()
+                                                }
+                                            }),
+                                            font_size: self.config.font_size,
+                                            bold: false,
+                                            italic: false,
+                                            underline: false,
+                                            color: self.config.text_color,
+                                            x: (page.content_area.x + inline.bounds.x) as f64,
+                                            y: baseline_y as f64,
+                                            hyperlink: None,
+                                            effects: TextEffects::default(),
+                                            font_key: 0,
+                                            glyphs: Vec::new(),
+                                        }));
                                     }
-                                    InlineType::ListMarker => {
-                                        // Render list marker (bullet or number)
-                                        if let Some(marker) = &inline.list_marker {
+                                }
+                                InlineType::Tab => {
+                                    // Render the tab leader, if any, across the tab's span
+                                    if let Some(leader) = inline.tab_leader.character() {
+                                        let run = tree.get_run(inline.node_id);
+                                        let font_size = run
+                                            .and_then(|r| r.style.font_size)
+                                            .map(|s| s as f64)
+                                            .unwrap_or(self.config.font_size);
+                                        let count = (inline.bounds.width as f64 / (font_size * 0.3)).floor() as usize;
+
+                                        if count > 0 {
                                             let baseline_y = page.content_area.y + line.bounds.y + line.baseline;
 
                                             page_render.items.push(RenderItem::GlyphRun(GlyphRun {
-                                                text: marker.text.clone(),
-                                                font_family: marker.font.clone().unwrap_or_else(|| {
-                                                    if marker.is_bullet {
-                                                        "Symbol".to_string()
-                                                    } else {
-                                                        self.config.font_family.clone()
-                                                    }
-                                                }),
-                                                font_size: self.config.font_size,
+                                                text: leader.to_string().repeat(count),
+                                                font_family: run
+                                                    .and_then(|r| r.style.font_family.clone())
+                                                    .unwrap_or_else(|| self.config.font_family.clone()),
+                                                font_size,
                                                 bold: false,
                                                 italic: false,
                                                 underline: false,
@@ -161,42 +347,76 @@ impl RenderConverter {
                                                 x: (page.content_area.x + inline.bounds.x) as f64,
                                                 y: baseline_y as f64,
                                                 hyperlink: None,
+                                                effects: TextEffects::default(),
+                                                font_key: 0,
+                                                glyphs: Vec::new(),
                                             }));
                                         }
                                     }
-                                    InlineType::Shape => {
-                                        // Render inline shape
-                                        if let Some(shape) = tree.get_shape(inline.node_id) {
-                                            let shape_x = page.content_area.x + inline.bounds.x;
-                                            let shape_y = page.content_area.y + line.bounds.y + inline.bounds.y;
-
-                                            page_render.items.push(RenderItem::Shape(self.convert_shape_to_render_info(
-                                                inline.node_id,
-                                                shape,
-                                                shape_x as f64,
-                                                shape_y as f64,
-                                                inline.bounds.width as f64,
-                                                inline.bounds.height as f64,
-                                                false,
-                                            )));
-                                        }
+                                }
+                                InlineType::Field => {
+                                    // Render the field result in the style of the text before it
+                                    if let Some(text) = inline.field_text.as_ref().filter(|text| !text.is_empty()) {
+                                        let run = field_style_run(tree, inline.node_id);
+                                        let baseline_y = page.content_area.y + line.bounds.y + line.baseline;
+
+                                        page_render.items.push(RenderItem::GlyphRun(GlyphRun {
+                                            text: text.to_string(),
+                                            font_family: run
+                                                .and_then(|r| r.style.font_family.clone())
+                                                .unwrap_or_else(|| self.config.font_family.clone()),
+                                            font_size: run
+                                                .and_then(|r| r.style.font_size)
+                                                .map(|s| s as f64)
+                                                .unwrap_or(self.config.font_size),
+                                            bold: run.and_then(|r| r.style.bold).unwrap_or(false),
+                                            italic: run.and_then(|r| r.style.italic).unwrap_or(false),
+                                            underline: false,
+                                            color: run
+                                                .and_then(|r| r.style.color.as_ref())
+                                                .and_then(|c| parse_color(c))
+                                                .unwrap_or(self.config.text_color),
+                                            x: (page.content_area.x + inline.bounds.x) as f64,
+                                            y: baseline_y as f64,
+                                            hyperlink: None,
+                                            effects: TextEffects::default(),
+                                            font_key: 0,
+                                            glyphs: Vec::new(),
+                                        }));
                                     }
-                                    InlineType::TextBox => {
-                                        // Render inline text box
-                                        if let Some(textbox) = tree.get_textbox(inline.node_id) {
-                                            let tb_x = page.content_area.x + inline.bounds.x;
-                                            let tb_y = page.content_area.y + line.bounds.y + inline.bounds.y;
-
-                                            page_render.items.push(RenderItem::TextBox(self.convert_textbox_to_render_info(
-                                                inline.node_id,
-                                                textbox,
-                                                tb_x as f64,
-                                                tb_y as f64,
-                                                inline.bounds.width as f64,
-                                                inline.bounds.height as f64,
-                                                false,
-                                            )));
-                                        }
+                                }
+                                InlineType::Shape => {
+                                    // Render inline shape
+                                    if let Some(shape) = tree.get_shape(inline.node_id) {
+                                        let shape_x = page.content_area.x + inline.bounds.x;
+                                        let shape_y = page.content_area.y + line.bounds.y + inline.bounds.y;
+
+                                        page_render.items.push(RenderItem::Shape(self.convert_shape_to_render_info(
+                                            inline.node_id,
+                                            shape,
+                                            shape_x as f64,
+                                            shape_y as f64,
+                                            inline.bounds.width as f64,
+                                            inline.bounds.height as f64,
+                                            false,
+                                        )));
+                                    }
+                                }
+                                InlineType::TextBox => {
+                                    // Render inline text box
+                                    if let Some(textbox) = tree.get_textbox(inline.node_id) {
+                                        let tb_x = page.content_area.x + inline.bounds.x;
+                                        let tb_y = page.content_area.y + line.bounds.y + inline.bounds.y;
+
+                                        page_render.items.push(RenderItem::TextBox(self.convert_textbox_to_render_info(
+                                            inline.node_id,
+                                            textbox,
+                                            tb_x as f64,
+                                            tb_y as f64,
+                                            inline.bounds.width as f64,
+                                            inline.bounds.height as f64,
+                                            false,
+                                        )));
                                     }
                                 }
                             }
@@ -204,51 +424,108 @@ impl RenderConverter {
                     }
                 }
             }
+        }
 
-            // Render floating images for this page
-            for floating_img in layout.floating_images_on_page(page.index) {
-                if let Some(image) = tree.get_image(floating_img.node_id) {
-                    page_render.items.push(RenderItem::Image(ImageRenderInfo {
-                        node_id: floating_img.node_id.to_string(),
-                        resource_id: image.resource_id.to_string(),
-                        bounds: Rect::from(floating_img.bounds),
-                        rotation: image.properties.rotation as f64,
-                        alt_text: image.alt_text.clone(),
-                        title: image.title.clone(),
-                        selected: false,
-                    }));
-                }
+        // Render floating images for this page
+        for floating_img in layout.floating_images_on_page(page.index) {
+            if let Some(image) = tree.get_image(floating_img.node_id) {
+                page_render.items.push(RenderItem::Image(ImageRenderInfo {
+                    node_id: floating_img.node_id.to_string(),
+                    resource_id: image.resource_id.to_string(),
+                    bounds: Rect::from(floating_img.bounds),
+                    rotation: image.properties.rotation as f64,
+                    alt_text: image.alt_text.clone(),
+                    title: image.title.clone(),
+                    selected: false,
+                }));
             }
+        }
 
-            // Render floating shapes for this page
-            for floating_shape in layout.floating_shapes_on_page(page.index) {
-                if let Some(shape) = tree.get_shape(floating_shape.node_id) {
-                    page_render.items.push(RenderItem::Shape(self.convert_shape_to_render_info(
-                        floating_shape.node_id,
-                        shape,
-                        floating_shape.bounds.x as f64,
-                        floating_shape.bounds.y as f64,
-                        floating_shape.bounds.width as f64,
-                        floating_shape.bounds.height as f64,
-                        false,
-                    )));
-                }
+        // Render floating shapes for this page
+        for floating_shape in layout.floating_shapes_on_page(page.index) {
+            if let Some(shape) = tree.get_shape(floating_shape.node_id) {
+                page_render.items.push(RenderItem::Shape(self.convert_shape_to_render_info(
+                    floating_shape.node_id,
+                    shape,
+                    floating_shape.bounds.x as f64,
+                    floating_shape.bounds.y as f64,
+                    floating_shape.bounds.width as f64,
+                    floating_shape.bounds.height as f64,
+                    false,
+                )));
             }
+        }
 
-            // Render line numbers for this page
-            for line_num in layout.line_numbers_on_page(page.index) {
-                page_render.items.push(RenderItem::LineNumber(LineNumberRenderInfo::new(
-                    line_num.number,
-                    line_num.x as f64,
-                    line_num.y as f64,
-                    line_num.font_size as f64,
-                )));
+        // Render ink over the page content
+        for floating_ink in layout.floating_inks_on_page(page.index) {
+            if let Some(ink) = tree.get_ink(floating_ink.node_id) {
+                page_render.items.push(RenderItem::Ink(convert_ink(ink, floating_ink)));
             }
+        }
 
-            model.add_page(page_render);
+        // Render line numbers for this page
+        for line_num in layout.line_numbers_on_page(page.index) {
+            page_render.items.push(RenderItem::LineNumber(LineNumberRenderInfo::new(
+                line_num.number,
+                line_num.x as f64,
+                line_num.y as f64,
+                line_num.font_size as f64,
+            )));
         }
 
-        Ok(model)
+        self.attach_glyphs(&mut page_render.items);
+        page_render
+    }
+
+    /// Shape the text of glyph runs into glyphs with atlas keys
+    fn attach_glyphs(&self, items: &mut [RenderItem]) {
+        for item in items {
+            match item {
+                RenderItem::GlyphRun(run) => {
+                    run.font_key = font_key(&run.font_family, run.bold, run.italic);
+                    run.glyphs = self.shape_glyphs(run);
+                }
+                RenderItem::TextBox(textbox) => self.attach_glyphs(&mut textbox.content_items),
+                _ => {}
+            }
+        }
+    }
+
+    /// Position a glyph run's glyphs relative to its origin
+    fn shape_glyphs(&self, run: &GlyphRun) -> Vec<RenderGlyph> {
+        let Ok(shaped) = self.shaper.shape_run(
+            &run.text,
+            Some(&run.font_family),
+            run.font_size as f32,
+            run.bold,
+            run.italic,
+        ) else {
+            return Vec::new();
+        };
+
+        let scale = run.font_size / shaped.units_per_em as f64;
+        let x_scale = scale * run.effects.horizontal_scale;
+        let mut pen_x = 0.0;
+        let mut glyphs = Vec::with_capacity(shaped.glyphs.len());
+        for (index, glyph) in shaped.glyphs.iter().enumerate() {
+            glyphs.push(RenderGlyph {
+                glyph_id: glyph.glyph_id,
+                atlas_key: glyph_atlas_key(run.font_key, run.font_size, glyph.glyph_id),
+                x: pen_x + glyph.x_offset as f64 * x_scale,
+                y: -(glyph.y_offset as f64 * scale),
+            });
+            pen_x += glyph.x_advance as f64 * x_scale;
+
+            // Letter spacing goes after each character, not each glyph
+            let cluster_ends = shaped
+                .glyphs
+                .get(index + 1)
+                .is_none_or(|next| next.cluster != glyph.cluster);
+            if cluster_ends {
+                pen_x += run.effects.letter_spacing;
+            }
+        }
+        glyphs
     }
 }
 
@@ -620,7 +897,150 @@ fn convert_border_edge(edge: &doc_model::BorderEdge) -> TextBoxBorderEdgeRender
     }
 }
 
+/// The run a field takes its formatting from: the last run before it in
+/// its paragraph
+fn field_style_run(tree: &DocumentTree, field_id: doc_model::NodeId) -> Option<&doc_model::Run> {
+    let para = tree.get_paragraph(tree.get_field(field_id)?.parent()?)?;
+    let position = para.children().iter().position(|&id| id == field_id)?;
+    para.children()[..position].iter().rev().find_map(|&id| tree.get_run(id))
+}
+
 /// Parse a CSS color string to a Color
+/// Convert a laid out watermark
+fn convert_watermark(watermark: &PageWatermark) -> WatermarkRenderInfo {
+    let content = match &watermark.content {
+        WatermarkContent::Text { text, font_family, font_size, color } => WatermarkRenderContent::Text {
+            text: text.clone(),
+            font_family: font_family.clone(),
+            font_size: *font_size as f64,
+            color: parse_color(color).unwrap_or(Color::rgb(192, 192, 192)),
+            baseline: *font_size as f64,
+        },
+        WatermarkContent::Picture { resource_id } => WatermarkRenderContent::Image {
+            resource_id: resource_id.clone(),
+        },
+    };
+    WatermarkRenderInfo {
+        bounds: Rect::from(watermark.bounds),
+        rotation: watermark.rotation as f64,
+        opacity: watermark.opacity as f64,
+        content,
+    }
+}
+
+impl RenderConverter {
+    /// Convert a laid-out table placed at (x, y) into cell backgrounds and
+    /// borders, including diagonal borders. Shading and borders come from the
+    /// table style combined with each cell's direct formatting.
+    pub fn convert_table(&self, table: &TableLayout, tree: &DocumentTree, x: f32, y: f32) -> Vec<RenderItem> {
+        let table_borders = tree
+            .get_table(table.table_id)
+            .and_then(|t| t.properties.borders.clone())
+            .unwrap_or_default();
+        let row_count = table.rows.len();
+        let column_count = table.column_widths.len();
+
+        let mut backgrounds = Vec::new();
+        let mut borders = Vec::new();
+        for (row_index, row) in table.rows.iter().enumerate() {
+            for cell in row.cells.iter().filter(|c| !c.is_covered) {
+                let left = (x + cell.bounds.x) as f64;
+                let top = (y + row.bounds.y) as f64;
+                let right = left + cell.bounds.width as f64;
+                let bottom = top + row.bounds.height as f64;
+
+                let format = tree.effective_cell_format(cell.cell_id).unwrap_or_default();
+                backgrounds.push(RenderItem::TableCell(TableCellRenderInfo {
+                    cell_id: cell.cell_id.to_string(),
+                    bounds: Rect::new(left, top, right - left, bottom - top),
+                    background: format.shading.as_deref().and_then(parse_color),
+                    selected: false,
+                    text_rotation: cell.text_direction.rotation_degrees() as f64,
+                }));
+
+                // Cell borders win over the table's outer and inside borders
+                let cell_borders = format.borders.unwrap_or_default();
+                let last_column = cell.col_index + cell.grid_span as usize >= column_count;
+                let table_side = |outer: bool, edge: &Option<TableBorder>, inside: &Option<TableBorder>| {
+                    if outer { edge.clone() } else { inside.clone() }
+                };
+                let sides = [
+                    (
+                        cell_borders.top.or_else(|| table_side(row_index == 0, &table_borders.top, &table_borders.inside_h)),
+                        (left, top, right, top),
+                    ),
+                    (
+                        cell_borders.bottom.or_else(|| table_side(row_index + 1 == row_count, &table_borders.bottom, &table_borders.inside_h)),
+                        (left, bottom, right, bottom),
+                    ),
+                    (
+                        cell_borders.left.or_else(|| table_side(cell.col_index == 0, &table_borders.left, &table_borders.inside_v)),
+                        (left, top, left, bottom),
+                    ),
+                    (
+                        cell_borders.right.or_else(|| table_side(last_column, &table_borders.right, &table_borders.inside_v)),
+                        (right, top, right, bottom),
+                    ),
+                    (cell_borders.diagonal_down, (left, top, right, bottom)),
+                    (cell_borders.diagonal_up, (left, bottom, right, top)),
+                ];
+                for (border, (x1, y1, x2, y2)) in sides {
+                    if let Some(border) = border.filter(|b| b.style != TableBorderStyle::None) {
+                        borders.push(RenderItem::TableBorder(convert_table_border(&border, x1, y1, x2, y2)));
+                    }
+                }
+            }
+        }
+
+        // Backgrounds first so borders draw on top
+        backgrounds.extend(borders);
+        backgrounds
+    }
+}
+
+fn convert_table_border(border: &TableBorder, x1: f64, y1: f64, x2: f64, y2: f64) -> TableBorderRenderInfo {
+    let mut info = TableBorderRenderInfo::new(
+        x1,
+        y1,
+        x2,
+        y2,
+        parse_color(&border.color).unwrap_or(Color::BLACK),
+        border.width as f64,
+    );
+    info.style = match border.style {
+        TableBorderStyle::None => "none",
+        TableBorderStyle::Single => "single",
+        TableBorderStyle::Double => "double",
+        TableBorderStyle::Dotted => "dotted",
+        TableBorderStyle::Dashed => "dashed",
+        TableBorderStyle::Thick => "thick",
+    }
+    .to_string();
+    info
+}
+
+/// Smooth ink strokes into outlines placed on the page
+fn convert_ink(ink: &doc_model::InkNode, placement: &layout_engine::FloatingInk) -> InkRenderInfo {
+    let (x, y) = (placement.origin_x as f64, placement.origin_y as f64);
+    InkRenderInfo {
+        node_id: placement.node_id.to_string(),
+        bounds: Rect::from(placement.bounds),
+        strokes: ink
+            .strokes
+            .iter()
+            .map(|stroke| InkStrokeRender {
+                outline: stroke
+                    .outline()
+                    .into_iter()
+                    .map(|point| (x + point.x as f64, y + point.y as f64))
+                    .collect(),
+                color: Color::rgba(stroke.color.r, stroke.color.g, stroke.color.b, stroke.color.a),
+            })
+            .collect(),
+        selected: false,
+    }
+}
+
 fn parse_color(color_str: &str) -> Option<Color> {
     if color_str.starts_with('#') {
         let hex = &color_str[1..];
@@ -0,0 +1,71 @@
+//! Glyph atlas keys
+//!
+//! GPU renderers rasterize each glyph once into an atlas texture and draw
+//! text as textured quads. Glyph runs therefore carry shaped glyph IDs with
+//! an atlas key per glyph: the font face, the size, and the glyph ID packed
+//! into one integer. Keys fit in 53 bits so they survive the trip through
+//! JSON into JavaScript numbers.
+
+use serde::{Deserialize, Serialize};
+
+/// Bits of the font key kept in an atlas key
+const FONT_KEY_BITS: u32 = 21;
+
+/// A shaped glyph in a glyph run
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RenderGlyph {
+    /// Glyph ID in the run's font (the code point when no font was loaded)
+    pub glyph_id: u16,
+    /// Key of the rasterized glyph in a glyph atlas
+    pub atlas_key: u64,
+    /// Horizontal offset from the run's origin
+    pub x: f64,
+    /// Vertical offset from the run's baseline
+    pub y: f64,
+}
+
+/// Key of a font face in glyph atlases, stable across pages and sessions
+pub fn font_key(family: &str, bold: bool, italic: bool) -> u32 {
+    // FNV-1a over the family name and style
+    let mut hash: u32 = 0x811c_9dc5;
+    for byte in family.bytes().chain([bold as u8, italic as u8]) {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    // Fold the dropped high bits back in
+    (hash ^ (hash >> FONT_KEY_BITS)) & ((1 << FONT_KEY_BITS) - 1)
+}
+
+/// Atlas key for a glyph of a font face at a size
+///
+/// The size is quantized to quarter points, so sizes that rasterize the
+/// same share atlas entries.
+pub fn glyph_atlas_key(font_key: u32, font_size: f64, glyph_id: u16) -> u64 {
+    let quarter_points = (font_size * 4.0).round().clamp(0.0, u16::MAX as f64) as u64;
+    ((font_key as u64 & ((1 << FONT_KEY_BITS) - 1)) << 32) | (quarter_points << 16) | glyph_id as u64
+}
+
+/// Split an atlas key into font key, size in points, and glyph ID
+pub fn split_atlas_key(atlas_key: u64) -> (u32, f64, u16) {
+    (
+        (atlas_key >> 32) as u32,
+        ((atlas_key >> 16) & 0xffff) as f64 / 4.0,
+        (atlas_key & 0xffff) as u16,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atlas_key_round_trip() {
+        let font = font_key("Calibri", true, false);
+        assert_ne!(font, font_key("Calibri", false, false));
+        assert_eq!(font, font_key("Calibri", true, false));
+
+        let key = glyph_atlas_key(font, 10.5, 1234);
+        assert!(key < 1 << 53);
+        assert_eq!(split_atlas_key(key), (font, 10.5, 1234));
+    }
+}
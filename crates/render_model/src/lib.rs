@@ -10,6 +10,9 @@ mod selection;
 mod error;
 mod squiggly;
 mod viewport;
+mod glyph_atlas;
+mod batch;
+mod remote_presence;
 
 pub use render_item::*;
 pub use converter::*;
@@ -18,3 +21,6 @@ pub use selection::*;
 pub use error::*;
 pub use squiggly::*;
 pub use viewport::*;
+pub use glyph_atlas::*;
+pub use batch::*;
+pub use remote_presence::*;
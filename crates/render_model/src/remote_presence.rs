@@ -0,0 +1,346 @@
+//! Collaborator carets and selections
+//!
+//! Remote cursors arrive as presence positions (node ID string and offset).
+//! They're resolved against the collaborative document's position map and
+//! then placed on the current layout, so re-applying after a relayout moves
+//! them with the text.
+
+use crate::{Color, PageRender, Rect, RemoteCaretRenderInfo, RemoteSelectionRenderInfo, RenderItem, RenderModel};
+use collab::{PositionMap, RemoteCursor, RemoteSelection};
+use doc_model::Position;
+use layout_engine::{Direction, InlineBox, LayoutTree, LineBox};
+use std::collections::BTreeMap;
+
+/// Remote presence rendering configuration
+#[derive(Debug, Clone)]
+pub struct RemotePresenceConfig {
+    /// Caret width in pixels
+    pub caret_width: f64,
+    /// Alpha applied to a collaborator's color for selection highlights
+    pub selection_alpha: u8,
+    /// Whether carets carry the collaborator's name
+    pub show_labels: bool,
+    /// Color used when a collaborator's color can't be parsed
+    pub fallback_color: Color,
+}
+
+impl Default for RemotePresenceConfig {
+    fn default() -> Self {
+        Self {
+            caret_width: 2.0,
+            selection_alpha: 64,
+            show_labels: true,
+            fallback_color: Color::rgb(128, 128, 128),
+        }
+    }
+}
+
+/// A line of text on a page, with its position in page coordinates
+struct PlacedLine<'a> {
+    page_index: usize,
+    x: f32,
+    y: f32,
+    line: &'a LineBox,
+}
+
+/// Renders collaborators' carets and selections
+pub struct RemotePresenceRenderer {
+    config: RemotePresenceConfig,
+}
+
+impl RemotePresenceRenderer {
+    pub fn new(config: RemotePresenceConfig) -> Self {
+        Self { config }
+    }
+
+    /// Render remote cursors and selections as `(page index, item)` pairs
+    ///
+    /// Selections are drawn first so carets sit on top of them. Positions
+    /// that don't resolve or aren't laid out are skipped.
+    pub fn render(
+        &self,
+        cursors: &[RemoteCursor],
+        selections: &[RemoteSelection],
+        positions: &PositionMap,
+        layout: &LayoutTree,
+    ) -> Vec<(usize, RenderItem)> {
+        let lines = placed_lines(layout);
+        let mut items = Vec::new();
+
+        for selection in selections {
+            if selection.selection.is_collapsed() {
+                continue;
+            }
+            let (Some(start), Some(end)) = (
+                positions.resolve(&selection.selection.start),
+                positions.resolve(&selection.selection.end),
+            ) else {
+                continue;
+            };
+            let color = self.color(&selection.color).with_alpha(self.config.selection_alpha);
+            for (page_index, rects) in selection_rects(&lines, &start, &end) {
+                items.push((
+                    page_index,
+                    RenderItem::RemoteSelection(RemoteSelectionRenderInfo {
+                        user_id: selection.user_id.clone(),
+                        rects,
+                        color,
+                    }),
+                ));
+            }
+        }
+
+        for cursor in cursors {
+            let Some(position) = positions.resolve(&cursor.position) else {
+                continue;
+            };
+            let Some((page_index, x, y, height)) = caret_position(&lines, &position) else {
+                continue;
+            };
+            items.push((
+                page_index,
+                RenderItem::RemoteCaret(RemoteCaretRenderInfo {
+                    user_id: cursor.user_id.clone(),
+                    label: self.config.show_labels.then(|| cursor.display_name.clone()),
+                    x,
+                    y,
+                    height,
+                    width: self.config.caret_width,
+                    color: self.color(&cursor.color),
+                    is_typing: cursor.is_typing,
+                }),
+            ));
+        }
+
+        items
+    }
+
+    /// Replace the remote presence items in a render model
+    pub fn apply(
+        &self,
+        model: &mut RenderModel,
+        cursors: &[RemoteCursor],
+        selections: &[RemoteSelection],
+        positions: &PositionMap,
+        layout: &LayoutTree,
+    ) {
+        clear_remote_presence(model);
+        for (page_index, item) in self.render(cursors, selections, positions, layout) {
+            if let Some(page) = model.pages.get_mut(page_index) {
+                page.items.push(item);
+            }
+        }
+    }
+
+    fn color(&self, hex: &str) -> Color {
+        Color::from_hex(hex).unwrap_or(self.config.fallback_color)
+    }
+}
+
+impl Default for RemotePresenceRenderer {
+    fn default() -> Self {
+        Self::new(RemotePresenceConfig::default())
+    }
+}
+
+/// Remove all collaborator carets and selections from a render model
+pub fn clear_remote_presence(model: &mut RenderModel) {
+    for page in &mut model.pages {
+        remove_remote_items(page);
+    }
+}
+
+fn remove_remote_items(page: &mut PageRender) {
+    page.items
+        .retain(|item| !matches!(item, RenderItem::RemoteCaret(_) | RenderItem::RemoteSelection(_)));
+}
+
+/// Every line in the layout, in document order
+fn placed_lines(layout: &LayoutTree) -> Vec<PlacedLine<'_>> {
+    let mut lines = Vec::new();
+    for (page_index, page) in layout.pages.iter().enumerate() {
+        for block in page.areas.iter().flat_map(|area| &area.columns).flat_map(|column| &column.blocks) {
+            for line in &block.lines {
+                lines.push(PlacedLine {
+                    page_index,
+                    x: page.content_area.x,
+                    y: block.bounds.y + line.bounds.y,
+                    line,
+                });
+            }
+        }
+    }
+    lines
+}
+
+fn contains(inline: &InlineBox, position: &Position) -> bool {
+    inline.node_id == position.node_id
+        && position.offset >= inline.start_offset
+        && position.offset <= inline.end_offset
+}
+
+/// X-coordinate of an offset within an inline, relative to the content area
+fn offset_x(inline: &InlineBox, offset: usize) -> f32 {
+    let total = inline.end_offset.saturating_sub(inline.start_offset);
+    if total == 0 {
+        return inline.bounds.x;
+    }
+    let ratio = offset.saturating_sub(inline.start_offset).min(total) as f32 / total as f32;
+    if inline.direction == Direction::Rtl {
+        inline.bounds.x + inline.bounds.width * (1.0 - ratio)
+    } else {
+        inline.bounds.x + inline.bounds.width * ratio
+    }
+}
+
+/// Page index, x, y, and height of a caret at a position
+fn caret_position(lines: &[PlacedLine], position: &Position) -> Option<(usize, f64, f64, f64)> {
+    lines.iter().find_map(|placed| {
+        let inline = placed.line.inlines.iter().find(|inline| contains(inline, position))?;
+        Some((
+            placed.page_index,
+            (placed.x + offset_x(inline, position.offset)) as f64,
+            placed.y as f64,
+            placed.line.bounds.height as f64,
+        ))
+    })
+}
+
+/// Highlight rectangles between two positions, grouped by page
+///
+/// The positions may be in either order; whichever is laid out first
+/// starts the highlight.
+fn selection_rects(lines: &[PlacedLine], a: &Position, b: &Position) -> BTreeMap<usize, Vec<Rect>> {
+    let mut rects: BTreeMap<usize, Vec<Rect>> = BTreeMap::new();
+    let mut end: Option<&Position> = None;
+
+    for placed in lines {
+        for inline in &placed.line.inlines {
+            let mut from = inline.start_offset;
+            if end.is_none() {
+                let first = [a, b]
+                    .into_iter()
+                    .filter(|position| contains(inline, position))
+                    .min_by_key(|position| position.offset);
+                let Some(first) = first else {
+                    continue;
+                };
+                from = first.offset;
+                end = Some(if std::ptr::eq(first, a) { b } else { a });
+            }
+
+            let last = end.filter(|end| contains(inline, end) && end.offset >= from);
+            let to = last.map_or(inline.end_offset, |end| end.offset);
+            let (x1, x2) = (offset_x(inline, from), offset_x(inline, to));
+            if x1 != x2 {
+                rects.entry(placed.page_index).or_default().push(Rect::new(
+                    (placed.x + x1.min(x2)) as f64,
+                    placed.y as f64,
+                    (x1 - x2).abs() as f64,
+                    placed.line.bounds.height as f64,
+                ));
+            }
+            if last.is_some() {
+                return rects;
+            }
+        }
+    }
+
+    rects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use collab::{Position as PresencePosition, SelectionRange};
+    use doc_model::NodeId;
+    use layout_engine::{AreaBox, BlockBox, ColumnBox, PageBox};
+
+    /// One page per line, each line a single inline of 10pt per character
+    fn layout(node_id: NodeId, lines: &[(usize, usize)]) -> LayoutTree {
+        let mut layout = LayoutTree::new();
+        for (index, &(start, end)) in lines.iter().enumerate() {
+            let page_rect = layout_engine::Rect::new(0.0, 0.0, 200.0, 200.0);
+            let content = layout_engine::Rect::new(10.0, 20.0, 180.0, 160.0);
+            let bounds = layout_engine::Rect::new(0.0, 0.0, ((end - start) * 10) as f32, 14.0);
+            let line = LineBox {
+                bounds: layout_engine::Rect::new(0.0, 5.0, 180.0, 14.0),
+                baseline: 11.0,
+                direction: Direction::Ltr,
+                inlines: vec![InlineBox::text(node_id, bounds, Direction::Ltr, start, end)],
+            };
+            let mut column = ColumnBox::new(content, 0);
+            column.add_block(BlockBox { node_id, bounds: content, lines: vec![line] });
+            let mut area = AreaBox::content(content);
+            area.add_column(column);
+            let mut page = PageBox::new(index, page_rect, content);
+            page.add_area(area);
+            layout.add_page(page);
+        }
+        layout
+    }
+
+    fn positions(node_id: NodeId, len: usize) -> PositionMap {
+        let mut rga = collab::Rga::new(collab::ClientId::new(1));
+        let mut last = None;
+        for _ in 0..len {
+            last = Some(rga.insert(last, 'x'));
+        }
+        let mut map = PositionMap::new();
+        map.update(node_id, &rga);
+        map
+    }
+
+    #[test]
+    fn test_remote_presence_follows_layout() {
+        let node_id = NodeId::new();
+        let map = positions(node_id, 10);
+        let at = |offset| PresencePosition::new(node_id.to_string(), offset);
+        let cursor = RemoteCursor {
+            user_id: "bob".into(),
+            display_name: "Bob".into(),
+            color: "#FF6B6B".into(),
+            position: at(7),
+            is_typing: true,
+        };
+        let selection = RemoteSelection {
+            user_id: "bob".into(),
+            color: "#FF6B6B".into(),
+            selection: SelectionRange::new(at(7), at(2)),
+        };
+
+        // Text split across two pages: 0..5 and 5..10
+        let mut model = RenderModel::new();
+        for index in 0..2 {
+            model.add_page(PageRender { page_index: index, width: 200.0, height: 200.0, items: Vec::new() });
+        }
+        let renderer = RemotePresenceRenderer::default();
+        renderer.apply(&mut model, &[cursor.clone()], &[selection.clone()], &map, &layout(node_id, &[(0, 5), (5, 10)]));
+
+        match model.pages[0].items.as_slice() {
+            [RenderItem::RemoteSelection(highlight)] => {
+                let rect = highlight.rects[0];
+                assert_eq!((highlight.rects.len(), rect.x, rect.y, rect.width), (1, 30.0, 25.0, 30.0));
+                assert_eq!(highlight.color, Color::rgba(0xFF, 0x6B, 0x6B, 64));
+            }
+            other => panic!("unexpected items {:?}", other),
+        }
+        match model.pages[1].items.as_slice() {
+            [RenderItem::RemoteSelection(highlight), RenderItem::RemoteCaret(caret)] => {
+                let rect = highlight.rects[0];
+                assert_eq!((highlight.rects.len(), rect.x, rect.width), (1, 10.0, 20.0));
+                assert_eq!((caret.x, caret.y, caret.label.as_deref()), (30.0, 25.0, Some("Bob")));
+            }
+            other => panic!("unexpected items {:?}", other),
+        }
+
+        // After a relayout onto one page the old items are replaced
+        renderer.apply(&mut model, &[cursor], &[selection], &map, &layout(node_id, &[(0, 10)]));
+        assert_eq!(model.pages[0].items.len(), 2);
+        assert!(model.pages[1].items.is_empty());
+        match &model.pages[0].items[1] {
+            RenderItem::RemoteCaret(caret) => assert_eq!(caret.x, 80.0),
+            other => panic!("unexpected item {:?}", other),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 //! Render item types
 
+use crate::RenderGlyph;
 use serde::{Deserialize, Serialize};
 
 /// A rectangle in render coordinates
@@ -29,7 +30,7 @@ impl From<layout_engine::Rect> for Rect {
 }
 
 /// Color representation
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -46,6 +47,16 @@ impl Color {
         Self { r, g, b, a }
     }
 
+    /// Parse a `#rrggbb` or `#rrggbbaa` color
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        doc_model::ShapeColor::from_hex(hex).map(|c| Self::rgba(c.r, c.g, c.b, c.a))
+    }
+
+    /// The same color with a different alpha
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+
     pub const BLACK: Color = Color::rgb(0, 0, 0);
     pub const WHITE: Color = Color::rgb(255, 255, 255);
     pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
@@ -79,6 +90,158 @@ pub struct GlyphRun {
     pub y: f64,
     /// Optional hyperlink info (target URL and tooltip)
     pub hyperlink: Option<HyperlinkRenderInfo>,
+    /// Advanced character effects
+    #[serde(default)]
+    pub effects: TextEffects,
+    /// Key of the font face in glyph atlases (see `font_key`)
+    #[serde(default)]
+    pub font_key: u32,
+    /// Shaped glyphs, for renderers that draw from a glyph atlas; `text`
+    /// stays for renderers that shape themselves, exporters, and copying
+    #[serde(default)]
+    pub glyphs: Vec<RenderGlyph>,
+}
+
+/// Advanced character effects for a glyph run
+///
+/// All-caps text is already uppercased in `GlyphRun::text` and raised or
+/// lowered text already has its baseline moved; these cover the rest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextEffects {
+    /// Draw lowercase letters as reduced-size capitals
+    pub small_caps: bool,
+    /// Extra space after each character in points
+    pub letter_spacing: f64,
+    /// Horizontal scale factor (1.0 is normal width)
+    pub horizontal_scale: f64,
+    /// Outline (hollow) glyphs
+    pub outline: bool,
+    /// Drop shadow
+    pub shadow: bool,
+    /// Embossed (raised) glyphs
+    pub emboss: bool,
+    /// Engraved (imprinted) glyphs
+    pub imprint: bool,
+    /// Glyph fill; replaces `GlyphRun::color` when set
+    #[serde(default)]
+    pub fill: Option<ShapeFillRender>,
+    /// Glyph outline stroke
+    #[serde(default)]
+    pub stroke: Option<TextStrokeRender>,
+    /// Glow around the glyphs
+    #[serde(default)]
+    pub glow: Option<TextGlowRender>,
+    /// Fading mirror image below the baseline
+    #[serde(default)]
+    pub reflection: Option<TextReflectionRender>,
+}
+
+/// Outline stroke around glyphs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextStrokeRender {
+    pub width: f64,
+    pub fill: ShapeFillRender,
+}
+
+/// Glow around glyphs
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TextGlowRender {
+    pub radius: f64,
+    pub color: Color,
+}
+
+/// Reflection of glyphs below the baseline
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TextReflectionRender {
+    pub blur_radius: f64,
+    pub start_opacity: f64,
+    pub end_opacity: f64,
+    /// Fraction of the text height the fade covers
+    pub end_position: f64,
+    pub distance: f64,
+}
+
+impl Default for TextEffects {
+    fn default() -> Self {
+        Self {
+            small_caps: false,
+            letter_spacing: 0.0,
+            horizontal_scale: 1.0,
+            outline: false,
+            shadow: false,
+            emboss: false,
+            imprint: false,
+            fill: None,
+            stroke: None,
+            glow: None,
+            reflection: None,
+        }
+    }
+}
+
+impl TextEffects {
+    /// Effects for resolved character properties
+    pub fn from_properties(props: &doc_model::CharacterProperties, theme: &doc_model::DocumentTheme) -> Self {
+        let effects = props.text_effects.as_ref();
+        Self {
+            small_caps: props.small_caps == Some(true) && props.all_caps != Some(true),
+            letter_spacing: props.spacing.unwrap_or(0.0) as f64,
+            horizontal_scale: props.scale_factor() as f64,
+            outline: props.outline == Some(true),
+            shadow: props.shadow == Some(true),
+            emboss: props.emboss == Some(true),
+            imprint: props.imprint == Some(true),
+            fill: effects.and_then(|e| e.fill.as_ref()).map(|f| text_fill_render(f, theme)),
+            stroke: effects.and_then(|e| e.outline.as_ref()).map(|o| TextStrokeRender {
+                width: o.width as f64,
+                fill: text_fill_render(&o.fill, theme),
+            }),
+            glow: effects.and_then(|e| e.glow).map(|g| TextGlowRender {
+                radius: g.radius as f64,
+                color: effect_color(&g.color, g.transparency, theme),
+            }),
+            reflection: effects.and_then(|e| e.reflection).map(|r| TextReflectionRender {
+                blur_radius: r.blur_radius as f64,
+                start_opacity: r.start_opacity as f64,
+                end_opacity: r.end_opacity as f64,
+                end_position: r.end_position as f64,
+                distance: r.distance as f64,
+            }),
+        }
+    }
+}
+
+/// Resolve a text effect color, folding transparency into alpha
+fn effect_color(color: &doc_model::EffectColor, transparency: f32, theme: &doc_model::DocumentTheme) -> Color {
+    let c = color.resolve(theme);
+    let alpha = c.a as f32 * (1.0 - transparency.clamp(0.0, 1.0));
+    Color::rgba(c.r, c.g, c.b, alpha.round() as u8)
+}
+
+/// Convert a text fill to a render fill
+fn text_fill_render(fill: &doc_model::TextFill, theme: &doc_model::DocumentTheme) -> ShapeFillRender {
+    use doc_model::TextFill;
+
+    let stops = |stops: &[doc_model::TextGradientStop]| {
+        stops
+            .iter()
+            .map(|s| (effect_color(&s.color, s.transparency, theme), s.position as f64))
+            .collect()
+    };
+    match fill {
+        TextFill::NoFill => ShapeFillRender::None,
+        TextFill::Solid { color, transparency } => ShapeFillRender::Solid {
+            color: effect_color(color, *transparency, theme),
+        },
+        TextFill::LinearGradient { angle, stops: s } => ShapeFillRender::Gradient {
+            colors: stops(s),
+            angle: *angle as f64,
+        },
+        TextFill::RadialGradient { stops: s } => ShapeFillRender::Gradient {
+            colors: stops(s),
+            angle: 0.0, // Radial gradients don't have angle
+        },
+    }
 }
 
 /// Hyperlink information for rendering
@@ -335,6 +498,9 @@ pub struct TableCellRenderInfo {
     pub background: Option<Color>,
     /// Whether this cell is selected
     pub selected: bool,
+    /// Clockwise rotation of the cell's text in degrees (vertical text)
+    #[serde(default)]
+    pub text_rotation: f64,
 }
 
 /// Table border render information
@@ -452,6 +618,108 @@ impl SquigglyRenderInfo {
     }
 }
 
+/// What a watermark draws
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum WatermarkRenderContent {
+    /// A line of text
+    Text {
+        text: String,
+        font_family: String,
+        font_size: f64,
+        color: Color,
+        /// Baseline offset from the top of the bounds
+        baseline: f64,
+    },
+    /// An image
+    Image { resource_id: String },
+}
+
+/// Section watermark render info, drawn behind the page content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatermarkRenderInfo {
+    /// Unrotated bounds
+    pub bounds: Rect,
+    /// Rotation about the center of the bounds in degrees (clockwise)
+    pub rotation: f64,
+    /// Opacity from 0.0 to 1.0
+    pub opacity: f64,
+    /// What to draw
+    pub content: WatermarkRenderContent,
+}
+
+/// A smoothed ink stroke, as a closed outline in page coordinates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InkStrokeRender {
+    /// Outline vertices; fill with the nonzero rule
+    pub outline: Vec<(f64, f64)>,
+    pub color: Color,
+}
+
+/// Ink render info
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InkRenderInfo {
+    /// The ink node ID (for selection handling)
+    pub node_id: String,
+    /// Bounds of all strokes
+    pub bounds: Rect,
+    /// Strokes in drawing order
+    pub strokes: Vec<InkStrokeRender>,
+    /// Whether this ink is selected
+    pub selected: bool,
+}
+
+/// Embedded object render info
+///
+/// The object's cached preview picture is drawn in the bounds when there
+/// is one; otherwise a framed placeholder with the label.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddedObjectRenderInfo {
+    /// The object node ID (for selection handling and fetching the preview)
+    pub node_id: String,
+    /// Bounds where the object should be rendered
+    pub bounds: Rect,
+    /// Name of the object's kind, e.g. "Microsoft Excel Worksheet"
+    pub label: String,
+    /// Content type of the cached preview picture, if there is one
+    pub preview_content_type: Option<String>,
+    /// Whether the object is linked to an external file
+    pub linked: bool,
+    /// Whether this object is selected
+    pub selected: bool,
+}
+
+/// A collaborator's caret, with the label drawn beside it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteCaretRenderInfo {
+    /// The collaborator's user ID
+    pub user_id: String,
+    /// Name shown in the caret's flag, if labels are enabled
+    pub label: Option<String>,
+    /// Position of the caret's top
+    pub x: f64,
+    pub y: f64,
+    /// Caret height
+    pub height: f64,
+    /// Caret width
+    pub width: f64,
+    /// The collaborator's color
+    pub color: Color,
+    /// Whether the collaborator is typing
+    pub is_typing: bool,
+}
+
+/// A collaborator's selection highlight
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteSelectionRenderInfo {
+    /// The collaborator's user ID
+    pub user_id: String,
+    /// Highlighted rectangles on the page
+    pub rects: Vec<Rect>,
+    /// The collaborator's color, made translucent
+    pub color: Color,
+}
+
 /// Render item types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -477,6 +745,10 @@ pub enum RenderItem {
         rects: Vec<Rect>,
         color: Color,
     },
+    /// A collaborator's caret
+    RemoteCaret(RemoteCaretRenderInfo),
+    /// A collaborator's selection highlight
+    RemoteSelection(RemoteSelectionRenderInfo),
     /// A line
     Line {
         x1: f64,
@@ -506,6 +778,12 @@ pub enum RenderItem {
     },
     /// Line number in the margin
     LineNumber(LineNumberRenderInfo),
+    /// Section watermark
+    Watermark(WatermarkRenderInfo),
+    /// Ink strokes
+    Ink(InkRenderInfo),
+    /// Embedded (OLE) object
+    EmbeddedObject(EmbeddedObjectRenderInfo),
 }
 
 /// A rendered page
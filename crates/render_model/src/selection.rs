@@ -5,7 +5,8 @@
 //! non-contiguous visual rectangles.
 
 use crate::{Color, Rect, RenderItem};
-use doc_model::Selection;
+use doc_model::{BlockSelection, MultiSelection, Position, Selection};
+use layout_engine::InlineType;
 use layout_engine::{Direction, LayoutTree, LineBox, InlineBox};
 
 /// Selection rendering configuration
@@ -13,12 +14,15 @@ use layout_engine::{Direction, LayoutTree, LineBox, InlineBox};
 pub struct SelectionConfig {
     /// Selection highlight color
     pub color: Color,
+    /// Underline color for IME composition text
+    pub composition_color: Color,
 }
 
 impl Default for SelectionConfig {
     fn default() -> Self {
         Self {
             color: Color::rgba(51, 153, 255, 128), // Light blue with transparency
+            composition_color: Color::rgb(0, 0, 0),
         }
     }
 }
@@ -65,6 +69,100 @@ impl SelectionRenderer {
         })
     }
 
+    /// Render highlights for every range of a multi-selection
+    ///
+    /// All ranges share one selection item; collapsed ranges contribute no
+    /// rectangles.
+    pub fn render_multi(&self, selection: &MultiSelection, layout: &LayoutTree) -> Option<RenderItem> {
+        if let Some(block) = selection.block() {
+            return self.render_block(block, layout);
+        }
+        if !selection.is_multi() {
+            return self.render(&selection.primary(), layout);
+        }
+
+        let rects: Vec<Rect> = selection
+            .ranges()
+            .iter()
+            .filter(|range| !range.is_collapsed())
+            .flat_map(|range| self.calculate_selection_rects(range, layout))
+            .collect();
+
+        if rects.is_empty() {
+            return None;
+        }
+
+        Some(RenderItem::Selection {
+            rects,
+            color: self.config.color,
+        })
+    }
+
+    /// Underline IME composition text, one line per visual span
+    ///
+    /// The underline sits just above the bottom of each line box the
+    /// provisional text covers.
+    pub fn render_composition(&self, composition: &Selection, layout: &LayoutTree) -> Vec<RenderItem> {
+        if composition.is_collapsed() {
+            return Vec::new();
+        }
+
+        self.calculate_selection_rects(composition, layout)
+            .into_iter()
+            .map(|rect| {
+                let y = rect.y + rect.height - 1.0;
+                RenderItem::Line {
+                    x1: rect.x,
+                    y1: y,
+                    x2: rect.x + rect.width,
+                    y2: y,
+                    color: self.config.composition_color,
+                    width: 1.0,
+                }
+            })
+            .collect()
+    }
+
+    /// Render a block (column) selection as one rectangle per covered line
+    ///
+    /// The highlight spans the block's full width on every line it touches,
+    /// even where a line's text ends before the block does.
+    pub fn render_block(&self, block: &BlockSelection, layout: &LayoutTree) -> Option<RenderItem> {
+        let width = block.right() - block.left();
+        let rects: Vec<Rect> = block_lines(block, layout)
+            .map(|(_, line_y, height)| {
+                Rect::new(block.left() as f64, line_y as f64, width as f64, height as f64)
+            })
+            .collect();
+
+        if rects.is_empty() || width <= 0.0 {
+            return None;
+        }
+
+        Some(RenderItem::Selection {
+            rects,
+            color: self.config.color,
+        })
+    }
+
+    /// Map a block rectangle to the text it covers, one range per line
+    ///
+    /// Each range runs from the block's left edge to its right edge within
+    /// the line; a line whose text ends before the block starts gets a
+    /// collapsed range at its end. Returns `None` if no line is covered.
+    pub fn block_selection(&self, block: &BlockSelection, layout: &LayoutTree) -> Option<MultiSelection> {
+        let page_x = layout.pages.get(block.page_index)?.content_area.x;
+        let ranges: Vec<Selection> = block_lines(block, layout)
+            .filter_map(|(line, _, _)| {
+                let anchor = position_at_x(line, block.left() - page_x)?;
+                let focus = position_at_x(line, block.right() - page_x)?;
+                Some(Selection::new(anchor, focus))
+            })
+            .collect();
+
+        MultiSelection::from_block(*block, ranges)
+    }
+
     /// Calculate selection rectangles for potentially mixed BiDi text
     fn calculate_selection_rects(&self, selection: &Selection, layout: &LayoutTree) -> Vec<Rect> {
         let mut rects = Vec::new();
@@ -221,6 +319,75 @@ impl SelectionRenderer {
     }
 }
 
+/// Lines on the block's page that overlap it vertically, with their page
+/// y-coordinate and height
+fn block_lines<'a>(
+    block: &'a BlockSelection,
+    layout: &'a LayoutTree,
+) -> impl Iterator<Item = (&'a LineBox, f32, f32)> + 'a {
+    layout
+        .pages
+        .get(block.page_index)
+        .into_iter()
+        .flat_map(|page| &page.areas)
+        .flat_map(|area| &area.columns)
+        .flat_map(|column| &column.blocks)
+        .flat_map(|b| b.lines.iter().map(move |line| (line, b.bounds.y + line.bounds.y)))
+        .filter(|(line, line_y)| line_y + line.bounds.height > block.top() && *line_y < block.bottom())
+        .map(|(line, line_y)| (line, line_y, line.bounds.height))
+}
+
+/// Hit-test a line at an x-coordinate (relative to the content area)
+///
+/// Within a text inline the offset is interpolated across its characters;
+/// positions left of the text snap to its start and right of it to its end.
+fn position_at_x(line: &LineBox, x: f32) -> Option<Position> {
+    let texts: Vec<&InlineBox> = line
+        .inlines
+        .iter()
+        .filter(|inline| inline.inline_type == InlineType::Text)
+        .collect();
+    let first = texts.iter().min_by(|a, b| a.bounds.x.total_cmp(&b.bounds.x))?;
+    let last = texts
+        .iter()
+        .max_by(|a, b| (a.bounds.x + a.bounds.width).total_cmp(&(b.bounds.x + b.bounds.width)))?;
+
+    let at_edge = |inline: &InlineBox, right: bool| {
+        let offset = if right == (inline.direction == Direction::Rtl) {
+            inline.start_offset
+        } else {
+            inline.end_offset
+        };
+        Position::new(inline.node_id, offset)
+    };
+
+    if x <= first.bounds.x {
+        return Some(at_edge(first, false));
+    }
+    if x >= last.bounds.x + last.bounds.width {
+        return Some(at_edge(last, true));
+    }
+
+    // In a gap between inlines, snap to the start of the next one
+    let inline = texts
+        .iter()
+        .filter(|inline| inline.bounds.x + inline.bounds.width > x)
+        .min_by(|a, b| a.bounds.x.total_cmp(&b.bounds.x))?;
+    if x < inline.bounds.x {
+        return Some(at_edge(inline, false));
+    }
+
+    let len = inline.end_offset - inline.start_offset;
+    let fraction = ((x - inline.bounds.x) / inline.bounds.width.max(f32::EPSILON)).clamp(0.0, 1.0);
+    let chars = (fraction * len as f32).round() as usize;
+    let offset = if inline.direction == Direction::Rtl {
+        inline.end_offset - chars
+    } else {
+        inline.start_offset + chars
+    };
+    Some(Position::new(inline.node_id, offset))
+}
+
 impl Default for SelectionRenderer {
     fn default() -> Self {
         Self::new(SelectionConfig::default())
@@ -245,4 +412,111 @@ mod tests {
         };
         assert_eq!(region.bidi_level % 2, 0); // LTR
     }
+
+    /// One page with a single block of lines, each line made of `(x, width, start, end)` inlines
+    fn layout_with_lines(node_id: doc_model::NodeId, lines: &[&[(f32, f32, usize, usize)]]) -> LayoutTree {
+        use layout_engine::{AreaBox, BlockBox, ColumnBox, PageBox};
+
+        let page_rect = layout_engine::Rect::new(0.0, 0.0, 200.0, 200.0);
+        let lines = lines
+            .iter()
+            .enumerate()
+            .map(|(i, inlines)| LineBox {
+                bounds: layout_engine::Rect::new(0.0, i as f32 * 14.0, 200.0, 14.0),
+                baseline: 11.0,
+                direction: Direction::Ltr,
+                inlines: inlines
+                    .iter()
+                    .map(|&(x, width, start, end)| {
+                        let bounds = layout_engine::Rect::new(x, 0.0, width, 14.0);
+                        InlineBox::text(node_id, bounds, Direction::Ltr, start, end)
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let mut column = ColumnBox::new(page_rect, 0);
+        column.add_block(BlockBox { node_id, bounds: page_rect, lines });
+        let mut area = AreaBox::content(page_rect);
+        area.add_column(column);
+        let mut page = PageBox::new(0, page_rect, page_rect);
+        page.add_area(area);
+        let mut layout = LayoutTree::new();
+        layout.add_page(page);
+        layout
+    }
+
+    #[test]
+    fn test_multi_selection_highlights_every_range() {
+        use doc_model::NodeId;
+
+        let node_id = NodeId::new();
+        let layout = layout_with_lines(node_id, &[&[(0.0, 50.0, 0, 5), (60.0, 50.0, 6, 11)]]);
+
+        let range = |start, end| Selection::new(Position::new(node_id, start), Position::new(node_id, end));
+        let mut multi = MultiSelection::new(range(1, 3));
+        multi.add_range(range(7, 9));
+        multi.add_range(Selection::collapsed(Position::new(node_id, 10)));
+
+        let renderer = SelectionRenderer::default();
+        match renderer.render_multi(&multi, &layout) {
+            Some(RenderItem::Selection { rects, .. }) => {
+                assert_eq!(rects.len(), 2);
+                assert_eq!(rects[1].x, 60.0);
+            }
+            other => panic!("expected selection, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_composition_underlines_each_covered_line() {
+        use doc_model::NodeId;
+
+        let node_id = NodeId::new();
+        let layout = layout_with_lines(node_id, &[&[(0.0, 100.0, 0, 10)], &[(0.0, 100.0, 10, 20)]]);
+        let composition = Selection::new(Position::new(node_id, 8), Position::new(node_id, 12));
+
+        let items = SelectionRenderer::default().render_composition(&composition, &layout);
+        assert_eq!(items.len(), 2);
+        match &items[1] {
+            RenderItem::Line { y1, y2, .. } => {
+                assert_eq!(*y1, 27.0);
+                assert_eq!(y1, y2);
+            }
+            other => panic!("expected line, got {:?}", other),
+        }
+        assert!(SelectionRenderer::default()
+            .render_composition(&Selection::collapsed(Position::new(node_id, 8)), &layout)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_block_selection_maps_columns_across_lines() {
+        use doc_model::NodeId;
+
+        let node_id = NodeId::new();
+        // 10pt per character; the third line is short
+        let layout = layout_with_lines(
+            node_id,
+            &[&[(0.0, 100.0, 0, 10)], &[(0.0, 100.0, 10, 20)], &[(0.0, 20.0, 20, 22)]],
+        );
+
+        let block = BlockSelection::new(0, (30.0, 5.0), (60.0, 40.0));
+        let renderer = SelectionRenderer::default();
+        let multi = renderer.block_selection(&block, &layout).unwrap();
+        let range = |start, end| Selection::new(Position::new(node_id, start), Position::new(node_id, end));
+        assert_eq!(multi.ranges(), &[range(3, 6), range(13, 16), range(22, 22)]);
+        assert!(multi.is_block());
+
+        match renderer.render_multi(&multi, &layout) {
+            Some(RenderItem::Selection { rects, .. }) => {
+                assert_eq!(rects.len(), 3);
+                assert!(rects.iter().all(|r| r.x == 30.0 && r.width == 30.0));
+            }
+            other => panic!("expected selection, got {:?}", other),
+        }
+
+        let below = BlockSelection::new(0, (0.0, 100.0), (50.0, 120.0));
+        assert!(renderer.block_selection(&below, &layout).is_none());
+    }
 }
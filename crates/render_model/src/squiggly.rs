@@ -4,7 +4,10 @@
 //! underlines that indicate spelling errors.
 
 use crate::render_item::{Color, Rect, RenderItem, SquigglyRenderInfo};
+use doc_model::{DocumentTree, Node, NodeId};
+use layout_engine::{Direction, LayoutTree};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Style of squiggly underline
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -260,6 +263,87 @@ impl SquigglyLayer {
         self.remove_for_node(node_id);
         self.underlines.extend(new_underlines);
     }
+
+    /// Replace a paragraph's spelling squiggles with ones under the given
+    /// character ranges, as spellcheck results for it arrive
+    ///
+    /// Ranges are paragraph character offsets; a range that wraps gets one
+    /// underline per line.
+    pub fn update_spelling(
+        &mut self,
+        para_id: NodeId,
+        ranges: &[(usize, usize)],
+        layout: &LayoutTree,
+        tree: &DocumentTree,
+    ) {
+        let node_id = para_id.to_string();
+        self.remove_for_node(&node_id);
+        let Some(para) = tree.get_paragraph(para_id) else {
+            return;
+        };
+
+        // Layout offsets are bytes within a run; map each run to its text
+        // and its first character's paragraph offset
+        let mut runs = HashMap::new();
+        let mut offset = 0;
+        for &run_id in para.children() {
+            if let Some(run) = tree.get_run(run_id) {
+                runs.insert(run_id, (offset, run.text.as_str()));
+                offset += run.text.chars().count();
+            }
+        }
+
+        for page in &layout.pages {
+            for area in &page.areas {
+                for column in &area.columns {
+                    for block in column.blocks.iter().filter(|block| block.node_id == para_id) {
+                        for line in &block.lines {
+                            let y = block.bounds.y + line.bounds.y + line.baseline + 1.0;
+                            for &(start, end) in ranges {
+                                let mut extent: Option<(f32, f32)> = None;
+                                for inline in &line.inlines {
+                                    let Some(&(run_start, text)) = runs.get(&inline.node_id) else {
+                                        continue;
+                                    };
+                                    let chars_before =
+                                        |byte: usize| text.get(..byte).map_or(0, |t| t.chars().count());
+                                    let inline_start = run_start + chars_before(inline.start_offset);
+                                    let inline_end = run_start + chars_before(inline.end_offset);
+                                    if inline_end <= start || inline_start >= end {
+                                        continue;
+                                    }
+
+                                    let len = (inline_end - inline_start) as f32;
+                                    let x_at = |offset: usize| {
+                                        let ratio = (offset.clamp(inline_start, inline_end) - inline_start) as f32 / len;
+                                        let ratio = if inline.direction == Direction::Rtl { 1.0 - ratio } else { ratio };
+                                        page.content_area.x + inline.bounds.x + inline.bounds.width * ratio
+                                    };
+                                    let (a, b) = (x_at(start), x_at(end));
+                                    let (left, right) = (a.min(b), a.max(b));
+                                    extent = Some(match extent {
+                                        Some((l, r)) => (l.min(left), r.max(right)),
+                                        None => (left, right),
+                                    });
+                                }
+
+                                if let Some((left, right)) = extent {
+                                    self.add_underline(SquigglyUnderline::spelling(
+                                        left as f64,
+                                        y as f64,
+                                        (right - left) as f64,
+                                        node_id.clone(),
+                                        start,
+                                        end,
+                                    ));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
 
 /// Error marker type for categorization
@@ -365,6 +449,8 @@ impl ErrorMarker {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use doc_model::{Paragraph, Run};
+    use layout_engine::{AreaBox, BlockBox, ColumnBox, InlineBox, LineBox, PageBox};
 
     #[test]
     fn test_squiggly_spelling() {
@@ -517,4 +603,46 @@ mod tests {
         assert!(!marker.uses_squiggly());
         assert!(marker.uses_highlight());
     }
+
+    #[test]
+    fn test_update_spelling_places_squiggles_per_line() {
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        let run_id = tree.insert_run(Run::new("teh test"), para_id, None).unwrap();
+
+        // "teh " on the first line and "test" wrapped onto the second, 10pt per char
+        let page_rect = layout_engine::Rect::new(0.0, 0.0, 200.0, 200.0);
+        let line = |i: usize, start: usize, end: usize| LineBox {
+            bounds: layout_engine::Rect::new(0.0, i as f32 * 14.0, 200.0, 14.0),
+            baseline: 11.0,
+            direction: Direction::Ltr,
+            inlines: vec![InlineBox::text(
+                run_id,
+                layout_engine::Rect::new(0.0, 0.0, (end - start) as f32 * 10.0, 14.0),
+                Direction::Ltr,
+                start,
+                end,
+            )],
+        };
+        let mut column = ColumnBox::new(page_rect, 0);
+        column.add_block(BlockBox {
+            node_id: para_id,
+            bounds: page_rect,
+            lines: vec![line(0, 0, 4), line(1, 4, 8)],
+        });
+        let mut area = AreaBox::content(page_rect);
+        area.add_column(column);
+        let mut page = PageBox::new(0, page_rect, page_rect);
+        page.add_area(area);
+        let mut layout = LayoutTree::new();
+        layout.add_page(page);
+
+        let mut layer = SquigglyLayer::new();
+        layer.update_spelling(para_id, &[(0, 3), (2, 6)], &layout, &tree);
+        let bounds: Vec<_> = layer.underlines.iter().map(|s| (s.bounds.x, s.bounds.y, s.bounds.width)).collect();
+        assert_eq!(bounds, [(0.0, 12.0, 30.0), (20.0, 12.0, 20.0), (0.0, 26.0, 20.0)]);
+
+        layer.update_spelling(para_id, &[], &layout, &tree);
+        assert!(layer.is_empty());
+    }
 }
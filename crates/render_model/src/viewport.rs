@@ -3,8 +3,14 @@
 //! This module provides viewport tracking and page visibility calculations
 //! to enable render virtualization - only rendering pages that are visible
 //! or within a buffer zone of the viewport.
+//!
+//! `ViewportFrame` goes further: it holds only the render items that
+//! intersect the visible area plus an overscan margin, each with an ID that
+//! stays the same from frame to frame so the frontend can diff them.
 
+use crate::{PageRender, Rect, RenderItem, RenderModel};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::ops::Range;
 
 /// Represents the current viewport state
@@ -20,6 +26,10 @@ pub struct Viewport {
     pub scroll_x: f64,
     /// Width of the visible area
     pub visible_width: f64,
+    /// Distance above and below the visible area whose items are included
+    /// in a `ViewportFrame`, so short scrolls don't show blank space
+    #[serde(default)]
+    pub overscan: f64,
 }
 
 impl Viewport {
@@ -31,6 +41,7 @@ impl Viewport {
             buffer_pages: 2,
             scroll_x: 0.0,
             visible_width: 0.0,
+            overscan: 0.0,
         }
     }
 
@@ -42,9 +53,16 @@ impl Viewport {
             buffer_pages,
             scroll_x: 0.0,
             visible_width: 0.0,
+            overscan: 0.0,
         }
     }
 
+    /// Set the overscan distance
+    pub fn with_overscan(mut self, overscan: f64) -> Self {
+        self.overscan = overscan;
+        self
+    }
+
     /// Update the scroll position
     pub fn set_scroll(&mut self, scroll_y: f64) {
         self.scroll_y = scroll_y;
@@ -71,6 +89,11 @@ impl Viewport {
         self.scroll_y + self.visible_height
     }
 
+    /// Check if a vertical span intersects the viewport grown by its overscan
+    pub fn intersects_overscan(&self, top: f64, bottom: f64) -> bool {
+        bottom > self.top() - self.overscan && top < self.bottom() + self.overscan
+    }
+
     /// Calculate the range of page indices that should be rendered
     ///
     /// Returns a range of page indices that are either visible or within
@@ -311,6 +334,200 @@ impl VirtualizedRenderResult {
     }
 }
 
+/// Top edge of each page when pages are stacked vertically with `page_gap`
+/// between them (and above the first)
+pub fn stacked_page_tops(page_heights: &[f64], page_gap: f64) -> Vec<f64> {
+    let mut top = page_gap;
+    page_heights
+        .iter()
+        .map(|height| {
+            let page_top = top;
+            top += height + page_gap;
+            page_top
+        })
+        .collect()
+}
+
+/// A page that has items in a viewport frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewportPage {
+    /// Index of the page
+    pub page_index: u32,
+    /// Top edge of the page in document coordinates
+    pub top: f64,
+    /// Page width
+    pub width: f64,
+    /// Page height
+    pub height: f64,
+}
+
+/// A render item with an ID that stays the same from frame to frame
+///
+/// Item coordinates are relative to the page, as in `PageRender`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewportItem {
+    /// Stable ID: page, item kind, owning node (when the item has one), and
+    /// the item's ordinal among items of the same kind and node on the page
+    pub id: String,
+    /// Page the item is on
+    pub page_index: u32,
+    /// The item
+    pub item: RenderItem,
+}
+
+/// The render items one viewport position needs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ViewportFrame {
+    /// Pages intersecting the viewport and its overscan
+    pub pages: Vec<ViewportPage>,
+    /// Items intersecting the viewport and its overscan, in paint order
+    pub items: Vec<ViewportItem>,
+    /// Total height of all pages (for the scroll container)
+    pub total_height: f64,
+}
+
+impl ViewportFrame {
+    /// Extract a frame from a fully converted render model
+    pub fn from_model(model: &RenderModel, viewport: &Viewport, page_gap: f64) -> Self {
+        let heights: Vec<f64> = model.pages.iter().map(|page| page.height).collect();
+        let tops = stacked_page_tops(&heights, page_gap);
+
+        let mut frame = Self::new(&tops, &heights, page_gap);
+        for page in overscanned_pages(&tops, &heights, viewport) {
+            frame.add_page(&model.pages[page], tops[page], viewport);
+        }
+        frame
+    }
+
+    /// Create an empty frame for stacked pages
+    pub(crate) fn new(page_tops: &[f64], page_heights: &[f64], page_gap: f64) -> Self {
+        let total_height = page_tops
+            .last()
+            .zip(page_heights.last())
+            .map_or(0.0, |(top, height)| top + height + page_gap);
+        Self {
+            pages: Vec::new(),
+            items: Vec::new(),
+            total_height,
+        }
+    }
+
+    /// Add the items of a page whose top edge is at `top` that intersect
+    /// the viewport and its overscan
+    pub(crate) fn add_page(&mut self, page: &PageRender, top: f64, viewport: &Viewport) {
+        self.pages.push(ViewportPage {
+            page_index: page.page_index,
+            top,
+            width: page.width,
+            height: page.height,
+        });
+
+        // Ordinals count every item on the page, visible or not, so an
+        // item keeps its ID however the page is scrolled
+        let mut ordinals: HashMap<(&'static str, Option<&str>), usize> = HashMap::new();
+        for item in &page.items {
+            let (kind, node_id) = item_identity(item);
+            let ordinal = ordinals.entry((kind, node_id)).or_default();
+            let id = format!("{}:{}:{}:{}", page.page_index, kind, node_id.unwrap_or(""), ordinal);
+            *ordinal += 1;
+
+            let visible = match item_vertical_extent(item) {
+                Some((item_top, item_bottom)) => viewport.intersects_overscan(top + item_top, top + item_bottom),
+                None => true,
+            };
+            if visible {
+                self.items.push(ViewportItem {
+                    id,
+                    page_index: page.page_index,
+                    item: item.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Indices of the stacked pages intersecting the viewport and its overscan
+pub(crate) fn overscanned_pages(page_tops: &[f64], page_heights: &[f64], viewport: &Viewport) -> Range<usize> {
+    let mut visible = page_tops
+        .iter()
+        .zip(page_heights)
+        .enumerate()
+        .filter(|(_, (&top, &height))| viewport.intersects_overscan(top, top + height))
+        .map(|(index, _)| index);
+    match visible.next() {
+        Some(first) => first..visible.next_back().unwrap_or(first) + 1,
+        None => 0..0,
+    }
+}
+
+/// The kind of a render item and the node it belongs to, if any
+fn item_identity(item: &RenderItem) -> (&'static str, Option<&str>) {
+    match item {
+        RenderItem::GlyphRun(run) => (
+            "text",
+            run.hyperlink.as_ref().map(|link| link.node_id.as_str()),
+        ),
+        RenderItem::Rectangle { .. } => ("rect", None),
+        RenderItem::Caret { .. } => ("caret", None),
+        RenderItem::Selection { .. } => ("selection", None),
+        RenderItem::RemoteCaret(_) => ("remote_caret", None),
+        RenderItem::RemoteSelection(_) => ("remote_selection", None),
+        RenderItem::Line { .. } => ("line", None),
+        RenderItem::Image(image) => ("image", Some(image.node_id.as_str())),
+        RenderItem::Shape(shape) => ("shape", Some(shape.node_id.as_str())),
+        RenderItem::TextBox(textbox) => ("textbox", Some(textbox.node_id.as_str())),
+        RenderItem::TableCell(_) => ("cell", None),
+        RenderItem::TableBorder(_) => ("border", None),
+        RenderItem::Squiggly(squiggly) => ("squiggly", Some(squiggly.node_id.as_str())),
+        RenderItem::FindHighlight { .. } => ("find", None),
+        RenderItem::LineNumber(_) => ("linenum", None),
+        RenderItem::Watermark(_) => ("watermark", None),
+        RenderItem::Ink(ink) => ("ink", Some(ink.node_id.as_str())),
+        RenderItem::EmbeddedObject(object) => ("object", Some(object.node_id.as_str())),
+    }
+}
+
+/// The top and bottom of a render item on its page, or `None` if unknown
+fn item_vertical_extent(item: &RenderItem) -> Option<(f64, f64)> {
+    let rect_extent = |bounds: &Rect| (bounds.y, bounds.y + bounds.height);
+    // A rotated box stays within the circle around its center
+    let rotated_extent = |bounds: &Rect, rotation: f64| {
+        if rotation == 0.0 {
+            rect_extent(bounds)
+        } else {
+            let center = bounds.y + bounds.height / 2.0;
+            let radius = bounds.width.hypot(bounds.height) / 2.0;
+            (center - radius, center + radius)
+        }
+    };
+
+    match item {
+        // Glyph runs are positioned at their baseline
+        RenderItem::GlyphRun(run) => Some((run.y - run.font_size, run.y + run.font_size * 0.5)),
+        RenderItem::Rectangle { bounds, .. } | RenderItem::FindHighlight { bounds, .. } => Some(rect_extent(bounds)),
+        RenderItem::Caret { y, height, .. } => Some((*y, y + height)),
+        RenderItem::Selection { rects, .. } => rects.iter().map(rect_extent).reduce(|a, b| (a.0.min(b.0), a.1.max(b.1))),
+        RenderItem::RemoteCaret(caret) => Some((caret.y, caret.y + caret.height)),
+        RenderItem::RemoteSelection(selection) => {
+            selection.rects.iter().map(rect_extent).reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
+        }
+        RenderItem::Line { y1, y2, width, .. } => Some((y1.min(*y2) - width / 2.0, y1.max(*y2) + width / 2.0)),
+        RenderItem::Image(image) => Some(rotated_extent(&image.bounds, image.rotation)),
+        RenderItem::Shape(shape) => Some(rotated_extent(&shape.bounds, shape.rotation)),
+        RenderItem::TextBox(textbox) => Some(rotated_extent(&textbox.bounds, textbox.rotation)),
+        RenderItem::TableCell(cell) => Some(rect_extent(&cell.bounds)),
+        RenderItem::TableBorder(border) => Some((
+            border.y1.min(border.y2) - border.width / 2.0,
+            border.y1.max(border.y2) + border.width / 2.0,
+        )),
+        RenderItem::Squiggly(squiggly) => Some(rect_extent(&squiggly.bounds)),
+        RenderItem::LineNumber(number) => Some((number.y - number.font_size, number.y + number.font_size * 0.5)),
+        RenderItem::Watermark(watermark) => Some(rotated_extent(&watermark.bounds, watermark.rotation)),
+        RenderItem::Ink(ink) => Some(rect_extent(&ink.bounds)),
+        RenderItem::EmbeddedObject(object) => Some(rect_extent(&object.bounds)),
+    }
+}
+
 /// Configuration for virtualized rendering
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VirtualizationConfig {
@@ -427,6 +644,43 @@ mod tests {
         assert!(result.total_height > 0.0);
     }
 
+    fn text_item(y: f64) -> RenderItem {
+        RenderItem::Caret {
+            x: 72.0,
+            y,
+            height: 12.0,
+            color: crate::Color::BLACK,
+        }
+    }
+
+    #[test]
+    fn test_viewport_frame_culls_items() {
+        let mut model = RenderModel::new();
+        for index in 0..3 {
+            model.add_page(PageRender {
+                page_index: index,
+                width: 600.0,
+                height: 800.0,
+                items: vec![text_item(100.0), text_item(700.0)],
+            });
+        }
+
+        // Pages: 20-820, 840-1640, 1660-2460; viewport 900-1300 sees only
+        // the first item of page 1
+        let viewport = Viewport::with_buffer(900.0, 400.0, 0);
+        let frame = ViewportFrame::from_model(&model, &viewport, 20.0);
+        assert_eq!(frame.pages.len(), 1);
+        assert_eq!(frame.pages[0].top, 840.0);
+        assert_eq!(frame.items.len(), 1);
+        assert_eq!(frame.items[0].id, "1:caret::0");
+        assert_eq!(frame.total_height, 2480.0);
+
+        // Overscan reaches the last item of page 0 and the second of page 1
+        let frame = ViewportFrame::from_model(&model, &viewport.clone().with_overscan(450.0), 20.0);
+        let ids: Vec<&str> = frame.items.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(ids, vec!["0:caret::1", "1:caret::0", "1:caret::1"]);
+    }
+
     #[test]
     fn test_empty_pages() {
         let viewport = Viewport::new(0.0, 1000.0);
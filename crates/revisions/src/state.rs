@@ -463,6 +463,21 @@ impl RevisionState {
             .collect()
     }
 
+    /// Replace the author of every revision with a placeholder name
+    ///
+    /// Returns the number of revisions changed.
+    pub fn anonymize_authors(&mut self, name: &str) -> usize {
+        let mut changed = 0;
+        for revision in self.revisions.values_mut() {
+            if revision.author != name {
+                revision.author = name.to_string();
+                changed += 1;
+            }
+        }
+        self.colors.author_colors.clear();
+        changed
+    }
+
     /// Count total revisions
     pub fn revision_count(&self) -> usize {
         self.revisions.len()
@@ -2,8 +2,12 @@
 //!
 //! This module provides automatic document saving to a recovery location,
 //! separate from the original file, to prevent data loss.
+//!
+//! Autosaves are incremental: a full checkpoint is written on the first save
+//! and every `checkpoint_every` saves after that, and the saves in between
+//! only append the changed nodes to a delta log (see [`crate::TreeDelta`]).
 
-use crate::Result;
+use crate::{DeltaTracker, Result};
 use doc_model::DocumentTree;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -25,6 +29,13 @@ pub struct AutosaveConfig {
     pub location: PathBuf,
     /// Minimum time between saves to debounce rapid changes (in milliseconds)
     pub debounce_ms: u64,
+    /// Number of incremental saves between full checkpoints
+    #[serde(default = "default_checkpoint_every")]
+    pub checkpoint_every: u64,
+}
+
+fn default_checkpoint_every() -> u64 {
+    20
 }
 
 impl Default for AutosaveConfig {
@@ -35,6 +46,7 @@ impl Default for AutosaveConfig {
             max_versions: 5,
             location: PathBuf::from(".autosave"),
             debounce_ms: 1000, // 1 second debounce
+            checkpoint_every: default_checkpoint_every(),
         }
     }
 }
@@ -52,6 +64,13 @@ impl AutosaveConfig {
         self
     }
 
+    /// Create a new config with a custom number of incremental saves
+    /// between full checkpoints (0 writes a full checkpoint every time)
+    pub fn with_checkpoint_every(mut self, saves: u64) -> Self {
+        self.checkpoint_every = saves;
+        self
+    }
+
     /// Create a new config with autosave disabled
     pub fn disabled() -> Self {
         Self {
@@ -78,6 +97,20 @@ pub struct AutosaveStatus {
     pub next_save_in_secs: Option<u64>,
 }
 
+/// Notification that a document was saved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SaveEvent {
+    /// Document that was saved
+    pub document_id: String,
+    /// File the document was written to
+    pub path: PathBuf,
+    /// Whether this was an autosave to the recovery location
+    pub autosave: bool,
+}
+
+/// Callback notified after a successful save
+pub type SaveListener = Box<dyn Fn(&SaveEvent) + Send + Sync>;
+
 /// Autosave manager with debouncing and background saving
 pub struct AutosaveManager {
     /// Configuration
@@ -98,6 +131,10 @@ pub struct AutosaveManager {
     last_error: Arc<RwLock<Option<String>>>,
     /// Change counter for debouncing
     change_counter: Arc<AtomicU64>,
+    /// Callback notified after each autosave
+    save_listener: Option<SaveListener>,
+    /// Fingerprints of the last autosaved state
+    delta_tracker: Arc<RwLock<DeltaTracker>>,
 }
 
 impl AutosaveManager {
@@ -112,10 +149,17 @@ impl AutosaveManager {
             last_save_time: Arc::new(AtomicU64::new(0)),
             is_saving: Arc::new(AtomicBool::new(false)),
             last_error: Arc::new(RwLock::new(None)),
+            delta_tracker: Arc::new(RwLock::new(DeltaTracker::new())),
             change_counter: Arc::new(AtomicU64::new(0)),
+            save_listener: None,
         }
     }
 
+    /// Set the callback notified after each successful autosave
+    pub fn set_save_listener(&mut self, listener: SaveListener) {
+        self.save_listener = Some(listener);
+    }
+
     /// Set the original file path
     pub fn set_original_path(&mut self, path: Option<PathBuf>) {
         self.original_path = path;
@@ -212,6 +256,11 @@ impl AutosaveManager {
             .join(format!("{}.autosave.wdj", self.document_id))
     }
 
+    /// Get the delta log path for incremental autosaves
+    pub fn delta_path(&self) -> PathBuf {
+        crate::delta_log_path(self.autosave_path())
+    }
+
     /// Get the autosave metadata file path
     pub fn metadata_path(&self) -> PathBuf {
         self.config
@@ -261,6 +310,13 @@ impl AutosaveManager {
                 self.mark_clean();
                 let mut error = self.last_error.write().await;
                 *error = None;
+                if let Some(listener) = &self.save_listener {
+                    listener(&SaveEvent {
+                        document_id: self.document_id.clone(),
+                        path: self.autosave_path(),
+                        autosave: true,
+                    });
+                }
                 Ok(true)
             }
             Err(e) => {
@@ -288,8 +344,28 @@ impl AutosaveManager {
         let meta_json = serde_json::to_string_pretty(&metadata)?;
         tokio::fs::write(self.metadata_path(), meta_json).await?;
 
-        // Save document
-        crate::save_document(tree, self.autosave_path()).await?;
+        // Save the changes since the last autosave, or a full checkpoint
+        let mut tracker = self.delta_tracker.write().await;
+        let full = !tracker.has_checkpoint()
+            || tracker.deltas_since_checkpoint() >= self.config.checkpoint_every
+            || !self.autosave_path().exists();
+        if full {
+            // Drop the old deltas first so they are never replayed onto the
+            // new checkpoint
+            tracker.reset();
+            let delta_path = self.delta_path();
+            if delta_path.exists() {
+                tokio::fs::remove_file(&delta_path).await?;
+            }
+            crate::save_document(tree, self.autosave_path()).await?;
+            tracker.checkpoint(tree)?;
+        } else if let Some(delta) = tracker.delta(tree)? {
+            if let Err(e) = crate::append_delta(self.delta_path(), &delta).await {
+                // The next save must be a full one to include these changes
+                tracker.reset();
+                return Err(e);
+            }
+        }
 
         // Clean up old versions if needed
         self.cleanup_old_versions().await?;
@@ -316,6 +392,12 @@ impl AutosaveManager {
             tokio::fs::remove_file(&meta_path).await?;
         }
 
+        let delta_path = self.delta_path();
+        if delta_path.exists() {
+            tokio::fs::remove_file(&delta_path).await?;
+        }
+        self.delta_tracker.write().await.reset();
+
         Ok(())
     }
 
@@ -324,9 +406,9 @@ impl AutosaveManager {
         self.autosave_path().exists()
     }
 
-    /// Load the autosave file for recovery
+    /// Load the autosave file for recovery, replaying incremental saves
     pub async fn recover(&self) -> Result<DocumentTree> {
-        crate::load_document(self.autosave_path()).await
+        crate::load_document_with_deltas(self.autosave_path()).await
     }
 
     /// Start the autosave background task
@@ -542,6 +624,7 @@ mod tests {
             max_versions: 5,
             location: temp_dir.path().to_path_buf(),
             debounce_ms: 500,
+            checkpoint_every: 20,
         };
 
         let manager = AutosaveManager::new("test-doc", config);
@@ -575,4 +658,39 @@ mod tests {
             Some(&PathBuf::from("/path/to/doc.wdj"))
         );
     }
+
+    #[tokio::test]
+    async fn test_autosave_incremental_recover() {
+        use doc_model::{Node, Run};
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = AutosaveConfig::default()
+            .with_location(temp_dir.path().to_path_buf())
+            .with_checkpoint_every(2);
+        config.debounce_ms = 0;
+
+        let manager = AutosaveManager::new("test-doc", config);
+        let mut tree = DocumentTree::with_empty_paragraph();
+        let para_id = tree.paragraphs().next().unwrap().id();
+
+        // A full checkpoint, then two incremental saves
+        for text in ["one", "two", "three"] {
+            tree.insert_run(Run::new(text), para_id, None).unwrap();
+            manager.mark_dirty();
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            assert!(manager.autosave(&tree).await.unwrap());
+        }
+        let checkpoint = crate::load_document(manager.autosave_path()).await.unwrap();
+        assert_eq!(checkpoint.text_content(), "one\n");
+        assert_eq!(crate::read_delta_log(manager.delta_path()).await.unwrap().len(), 2);
+        assert_eq!(manager.recover().await.unwrap().text_content(), tree.text_content());
+
+        // The next save is a full checkpoint again
+        tree.insert_run(Run::new("four"), para_id, None).unwrap();
+        manager.mark_dirty();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        manager.autosave(&tree).await.unwrap();
+        assert!(!manager.delta_path().exists());
+        assert_eq!(manager.recover().await.unwrap().text_content(), tree.text_content());
+    }
 }
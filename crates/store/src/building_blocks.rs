@@ -0,0 +1,226 @@
+//! User building block library
+//!
+//! Building blocks the user saves for use in any document are kept in the
+//! user profile as JSON. A document's own blocks (e.g. those that came with
+//! its template) travel inside the document. The gallery shows both, with
+//! the document's blocks first.
+
+use crate::clipboard::fragment_text;
+use crate::Result;
+use doc_model::{BuildingBlock, BuildingBlockCollection, BuildingBlockGallery, DocumentTree};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// File name of the library in the app data directory
+pub const BUILDING_BLOCKS_FILE: &str = "building_blocks.json";
+
+/// Number of characters of content shown as a preview
+const PREVIEW_CHARS: usize = 80;
+
+/// Where a gallery entry is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BuildingBlockSource {
+    /// The user's library
+    User,
+    /// The document (or the template it was created from)
+    Document,
+}
+
+/// A building block as listed in a gallery
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildingBlockEntry {
+    /// Block ID
+    pub id: String,
+    /// Name
+    pub name: String,
+    /// Gallery
+    pub gallery: BuildingBlockGallery,
+    /// Category within the gallery
+    pub category: String,
+    /// Description
+    pub description: String,
+    /// Start of the block's text
+    pub preview_text: String,
+    /// Where the block is stored
+    pub source: BuildingBlockSource,
+}
+
+impl BuildingBlockEntry {
+    fn new(block: &BuildingBlock, source: BuildingBlockSource) -> Self {
+        let text = fragment_text(&block.content);
+        Self {
+            id: block.id.clone(),
+            name: block.name.clone(),
+            gallery: block.gallery,
+            category: block.category.clone(),
+            description: block.description.clone(),
+            preview_text: text.split_whitespace().collect::<Vec<_>>().join(" ").chars().take(PREVIEW_CHARS).collect(),
+            source,
+        }
+    }
+}
+
+/// The user's building blocks
+pub struct BuildingBlockLibrary {
+    /// Path to the library file
+    path: PathBuf,
+    /// The blocks
+    blocks: BuildingBlockCollection,
+}
+
+impl BuildingBlockLibrary {
+    /// Create a library stored in the given app data directory
+    pub fn new(app_data_dir: PathBuf) -> Self {
+        Self {
+            path: app_data_dir.join(BUILDING_BLOCKS_FILE),
+            blocks: BuildingBlockCollection::new(),
+        }
+    }
+
+    /// Load the library from disk
+    ///
+    /// A missing or unreadable library gives an empty one.
+    pub fn load_sync(&mut self) -> Result<&BuildingBlockCollection> {
+        self.blocks = match std::fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                tracing::warn!("Failed to parse building blocks, starting empty: {}", e);
+                BuildingBlockCollection::new()
+            }),
+            Err(_) => BuildingBlockCollection::new(),
+        };
+        Ok(&self.blocks)
+    }
+
+    /// Save the library to disk
+    pub fn save_sync(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string(&self.blocks)?)?;
+        Ok(())
+    }
+
+    /// The user's blocks
+    pub fn blocks(&self) -> &BuildingBlockCollection {
+        &self.blocks
+    }
+
+    /// Add a block, replacing one with the same name in the same gallery
+    pub fn add(&mut self, block: BuildingBlock) -> Option<BuildingBlock> {
+        self.blocks.add(block)
+    }
+
+    /// Remove a block by ID
+    pub fn remove(&mut self, id: &str) -> Option<BuildingBlock> {
+        self.blocks.remove(id)
+    }
+
+    /// Find a block by ID in the document or the library
+    pub fn get<'a>(&'a self, id: &str, document: Option<&'a DocumentTree>) -> Option<&'a BuildingBlock> {
+        document
+            .and_then(|doc| doc.building_blocks.get(id))
+            .or_else(|| self.blocks.get(id))
+    }
+
+    /// Find a block by gallery and name, preferring the document's
+    pub fn find<'a>(
+        &'a self,
+        gallery: BuildingBlockGallery,
+        name: &str,
+        document: Option<&'a DocumentTree>,
+    ) -> Option<&'a BuildingBlock> {
+        document
+            .and_then(|doc| doc.building_blocks.find(gallery, name))
+            .or_else(|| self.blocks.find(gallery, name))
+    }
+
+    /// List a gallery: the document's blocks, then the user's
+    ///
+    /// A user block with the same name as a document block is hidden.
+    pub fn gallery(&self, gallery: BuildingBlockGallery, document: Option<&DocumentTree>) -> Vec<BuildingBlockEntry> {
+        let mut entries: Vec<_> = document
+            .map(|doc| doc.building_blocks.in_gallery(gallery))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|block| BuildingBlockEntry::new(block, BuildingBlockSource::Document))
+            .collect();
+        for block in self.blocks.in_gallery(gallery) {
+            if !entries.iter().any(|e| e.name.eq_ignore_ascii_case(&block.name)) {
+                entries.push(BuildingBlockEntry::new(block, BuildingBlockSource::User));
+            }
+        }
+        entries
+    }
+
+    /// AutoText entries for typing in a document: the document's, then the
+    /// user's not shadowed by one of the same name
+    pub fn autotext_entries(&self, document: Option<&DocumentTree>) -> Vec<BuildingBlock> {
+        let gallery = BuildingBlockGallery::AutoText;
+        let mut entries: Vec<BuildingBlock> = document
+            .map(|doc| doc.building_blocks.in_gallery(gallery))
+            .unwrap_or_default()
+            .into_iter()
+            .cloned()
+            .collect();
+        for block in self.blocks.in_gallery(gallery) {
+            if !entries.iter().any(|e| e.name.eq_ignore_ascii_case(&block.name)) {
+                entries.push(block.clone());
+            }
+        }
+        entries
+    }
+
+    /// Categories used in a gallery by the document or the library, sorted
+    pub fn categories(&self, gallery: BuildingBlockGallery, document: Option<&DocumentTree>) -> Vec<String> {
+        let mut categories: Vec<String> = self
+            .blocks
+            .categories(gallery)
+            .into_iter()
+            .chain(document.map(|doc| doc.building_blocks.categories(gallery)).unwrap_or_default())
+            .map(str::to_string)
+            .collect();
+        categories.sort();
+        categories.dedup();
+        categories
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::{Paragraph, Run};
+
+    fn block(name: &str, text: &str) -> BuildingBlock {
+        let mut content = DocumentTree::new();
+        let para_id = content.insert_paragraph(Paragraph::new(), content.root_id(), None).unwrap();
+        content.insert_run(Run::new(text), para_id, None).unwrap();
+        BuildingBlock::new(name, BuildingBlockGallery::QuickParts, content)
+    }
+
+    #[test]
+    fn test_library_persistence_and_gallery() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut library = BuildingBlockLibrary::new(dir.path().to_path_buf());
+        library.add(block("Address", "1 Main St"));
+        library.add(block("Disclaimer", "No warranty").with_category("Legal"));
+        library.save_sync().unwrap();
+
+        let mut library = BuildingBlockLibrary::new(dir.path().to_path_buf());
+        assert_eq!(library.load_sync().unwrap().len(), 2);
+
+        // The document's block shadows the user's block of the same name
+        let mut document = DocumentTree::new();
+        document.building_blocks.add(block("address", "42 Template Rd"));
+        let entries = library.gallery(BuildingBlockGallery::QuickParts, Some(&document));
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].source, BuildingBlockSource::Document);
+        assert_eq!(entries[0].preview_text, "42 Template Rd");
+        assert_eq!(entries[1].name, "Disclaimer");
+
+        let found = library.find(BuildingBlockGallery::QuickParts, "Address", Some(&document)).unwrap();
+        assert_eq!(found.name, "address");
+        assert_eq!(library.categories(BuildingBlockGallery::QuickParts, None), ["General", "Legal"]);
+    }
+}
@@ -0,0 +1,298 @@
+//! Public API for copy and paste
+
+use super::error::{ClipboardError, ClipboardResult};
+use super::fragment::{extract_fragment, fragment_from_text, fragment_text};
+use super::html::{fragment_to_html, html_to_fragment};
+use crate::rtf::{export_rtf_bytes, import_rtf_bytes};
+use crate::{deserialize, serialize};
+use doc_model::{CharacterProperties, DocumentTree, Selection};
+use serde::{Deserialize, Serialize};
+
+/// MIME type of the internal (lossless) clipboard format
+pub const MIME_INTERNAL: &str = "application/x-go-word-fragment+json";
+/// MIME type of the RTF clipboard format
+pub const MIME_RTF: &str = "text/rtf";
+/// MIME type of the HTML clipboard format
+pub const MIME_HTML: &str = "text/html";
+/// MIME type of the plain text clipboard format
+pub const MIME_PLAIN_TEXT: &str = "text/plain";
+
+/// Clipboard format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ClipboardFormat {
+    Internal,
+    Rtf,
+    Html,
+    PlainText,
+}
+
+impl ClipboardFormat {
+    /// MIME type for this format
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ClipboardFormat::Internal => MIME_INTERNAL,
+            ClipboardFormat::Rtf => MIME_RTF,
+            ClipboardFormat::Html => MIME_HTML,
+            ClipboardFormat::PlainText => MIME_PLAIN_TEXT,
+        }
+    }
+}
+
+/// Clipboard contents in every available format
+///
+/// Copy fills all formats; on paste, whichever formats the system clipboard
+/// offered are set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardData {
+    /// Internal JSON fragment
+    pub internal: Option<String>,
+    /// RTF
+    pub rtf: Option<String>,
+    /// HTML
+    pub html: Option<String>,
+    /// Plain text
+    pub plain_text: Option<String>,
+}
+
+impl ClipboardData {
+    /// Clipboard data holding only plain text
+    pub fn from_text(text: impl Into<String>) -> Self {
+        Self {
+            plain_text: Some(text.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Available formats with their MIME types, richest first
+    pub fn formats(&self) -> Vec<(ClipboardFormat, &str)> {
+        [
+            (ClipboardFormat::Internal, &self.internal),
+            (ClipboardFormat::Rtf, &self.rtf),
+            (ClipboardFormat::Html, &self.html),
+            (ClipboardFormat::PlainText, &self.plain_text),
+        ]
+        .into_iter()
+        .filter_map(|(format, data)| data.as_deref().map(|data| (format, data)))
+        .collect()
+    }
+
+    /// Check if no format is available
+    pub fn is_empty(&self) -> bool {
+        self.formats().is_empty()
+    }
+}
+
+/// How pasted content is formatted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PasteMode {
+    /// Keep the source's fonts, styles, and paragraph formatting
+    #[default]
+    KeepSourceFormatting,
+    /// Keep emphasis (bold, italic, underline...) but take the destination's
+    /// fonts, colors, and paragraph formatting
+    MergeFormatting,
+    /// Paste unformatted text
+    TextOnly,
+}
+
+/// Paste options
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasteOptions {
+    /// How pasted content is formatted
+    pub mode: PasteMode,
+}
+
+impl PasteOptions {
+    /// Create paste options with the given mode
+    pub fn new(mode: PasteMode) -> Self {
+        Self { mode }
+    }
+}
+
+/// A parsed paste, ready to insert
+#[derive(Debug)]
+pub struct PasteFragment {
+    /// The content to insert
+    pub tree: DocumentTree,
+    /// The format it was read from
+    pub format: ClipboardFormat,
+}
+
+/// Serialize the selected content to every clipboard format
+pub fn copy_selection(tree: &DocumentTree, selection: &Selection) -> ClipboardResult<ClipboardData> {
+    let fragment = extract_fragment(tree, selection)?;
+    let rtf = export_rtf_bytes(&fragment)?;
+
+    Ok(ClipboardData {
+        internal: Some(serialize(&fragment)?),
+        rtf: Some(String::from_utf8_lossy(&rtf).into_owned()),
+        html: Some(fragment_to_html(&fragment)),
+        plain_text: Some(fragment_text(&fragment)),
+    })
+}
+
+/// Parse clipboard data into a fragment using the richest available format
+pub fn paste(data: &ClipboardData, options: &PasteOptions) -> ClipboardResult<PasteFragment> {
+    let (format, raw) = *data.formats().first().ok_or(ClipboardError::Empty)?;
+
+    if options.mode == PasteMode::TextOnly {
+        let text = match &data.plain_text {
+            Some(text) => text.clone(),
+            None => fragment_text(&parse_format(format, raw)?),
+        };
+        return Ok(PasteFragment {
+            tree: fragment_from_text(&text),
+            format: ClipboardFormat::PlainText,
+        });
+    }
+
+    let mut tree = parse_format(format, raw)?;
+    if options.mode == PasteMode::MergeFormatting {
+        merge_formatting(&mut tree);
+    }
+    Ok(PasteFragment { tree, format })
+}
+
+fn parse_format(format: ClipboardFormat, raw: &str) -> ClipboardResult<DocumentTree> {
+    Ok(match format {
+        ClipboardFormat::Internal => deserialize(raw)?,
+        ClipboardFormat::Rtf => import_rtf_bytes(raw.as_bytes())?.tree,
+        ClipboardFormat::Html => html_to_fragment(raw),
+        ClipboardFormat::PlainText => fragment_from_text(raw),
+    })
+}
+
+/// Strip everything but emphasis so pasted text picks up the destination's
+/// formatting
+fn merge_formatting(tree: &mut DocumentTree) {
+    let para_ids: Vec<_> = tree.nodes.paragraphs.keys().copied().collect();
+    for para_id in para_ids {
+        if let Some(para) = tree.get_paragraph_mut(para_id) {
+            para.direct_formatting = Default::default();
+            para.paragraph_style_id = doc_model::Paragraph::new().paragraph_style_id;
+        }
+    }
+    for run in tree.nodes.runs.values_mut() {
+        let source = &run.direct_formatting;
+        run.direct_formatting = CharacterProperties {
+            bold: source.bold,
+            italic: source.italic,
+            underline: source.underline,
+            strikethrough: source.strikethrough,
+            vertical_align: source.vertical_align,
+            all_caps: source.all_caps,
+            small_caps: source.small_caps,
+            ..Default::default()
+        };
+        run.character_style_id = None;
+        run.style = Default::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::{Node, Paragraph, Position, Run};
+
+    fn create_test_tree() -> (DocumentTree, Vec<doc_model::NodeId>) {
+        let mut tree = DocumentTree::new();
+        let mut ids = Vec::new();
+        for text in ["First paragraph", "Second paragraph", "Third paragraph"] {
+            let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+            let mut props = CharacterProperties::new();
+            props.bold = Some(text.starts_with("Second"));
+            props.font_family = Some("Georgia".to_string());
+            tree.insert_run(Run::with_direct_formatting(text, props), para_id, None).unwrap();
+            ids.push(para_id);
+        }
+        (tree, ids)
+    }
+
+    #[test]
+    fn test_copy_produces_all_formats() {
+        let (tree, ids) = create_test_tree();
+        let selection = Selection::new(Position::new(ids[0], 6), Position::new(ids[2], 5));
+
+        let data = copy_selection(&tree, &selection).unwrap();
+        assert_eq!(data.formats().len(), 4);
+        assert_eq!(
+            data.plain_text.as_deref(),
+            Some("paragraph\nSecond paragraph\nThird\n")
+        );
+        assert!(data.rtf.as_deref().unwrap().starts_with("{\\rtf1"));
+        assert!(data.html.as_deref().unwrap().contains("font-weight:bold"));
+
+        // Backwards selections copy the same content
+        let backwards = Selection::new(Position::new(ids[2], 5), Position::new(ids[0], 6));
+        let data_back = copy_selection(&tree, &backwards).unwrap();
+        assert_eq!(data_back.plain_text, data.plain_text);
+    }
+
+    #[test]
+    fn test_paste_prefers_internal_format() {
+        let (tree, ids) = create_test_tree();
+        let selection = Selection::new(Position::new(ids[1], 0), Position::new(ids[1], 6));
+        let data = copy_selection(&tree, &selection).unwrap();
+
+        let pasted = paste(&data, &PasteOptions::default()).unwrap();
+        assert_eq!(pasted.format, ClipboardFormat::Internal);
+        let run = pasted.tree.nodes.runs.values().next().unwrap();
+        assert_eq!(run.text, "Second");
+        assert_eq!(run.direct_formatting.bold, Some(true));
+        assert_eq!(run.direct_formatting.font_family.as_deref(), Some("Georgia"));
+    }
+
+    #[test]
+    fn test_paste_modes() {
+        let data = ClipboardData {
+            html: Some("<p style=\"text-align:right\"><b style=\"font-family:Arial\">Bold</b> text</p>".to_string()),
+            plain_text: Some("Bold text".to_string()),
+            ..Default::default()
+        };
+
+        let keep = paste(&data, &PasteOptions::new(PasteMode::KeepSourceFormatting)).unwrap();
+        assert_eq!(keep.format, ClipboardFormat::Html);
+        let para = keep.tree.paragraphs().next().unwrap();
+        assert!(para.direct_formatting.alignment.is_some());
+        let bold = keep.tree.get_run(para.children()[0]).unwrap();
+        assert_eq!(bold.direct_formatting.font_family.as_deref(), Some("Arial"));
+
+        let merged = paste(&data, &PasteOptions::new(PasteMode::MergeFormatting)).unwrap();
+        let para = merged.tree.paragraphs().next().unwrap();
+        assert!(para.direct_formatting.alignment.is_none());
+        let bold = merged.tree.get_run(para.children()[0]).unwrap();
+        assert_eq!(bold.direct_formatting.bold, Some(true));
+        assert!(bold.direct_formatting.font_family.is_none());
+
+        let text = paste(&data, &PasteOptions::new(PasteMode::TextOnly)).unwrap();
+        assert_eq!(text.format, ClipboardFormat::PlainText);
+        assert_eq!(text.tree.text_content(), "Bold text\n");
+    }
+
+    #[test]
+    fn test_paste_rtf_and_plain_text() {
+        let (tree, ids) = create_test_tree();
+        let selection = Selection::new(Position::new(ids[0], 0), Position::new(ids[1], 6));
+        let data = copy_selection(&tree, &selection).unwrap();
+
+        let rtf_only = ClipboardData {
+            rtf: data.rtf.clone(),
+            ..Default::default()
+        };
+        let pasted = paste(&rtf_only, &PasteOptions::default()).unwrap();
+        assert_eq!(pasted.format, ClipboardFormat::Rtf);
+        assert!(pasted.tree.text_content().contains("First paragraph"));
+
+        let text = paste(&ClipboardData::from_text("one\r\ntwo\r\n"), &PasteOptions::default()).unwrap();
+        assert_eq!(text.tree.paragraphs().count(), 2);
+
+        assert!(matches!(
+            paste(&ClipboardData::default(), &PasteOptions::default()),
+            Err(ClipboardError::Empty)
+        ));
+    }
+}
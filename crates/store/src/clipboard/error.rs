@@ -0,0 +1,34 @@
+//! Error types for clipboard operations
+
+use thiserror::Error;
+
+/// Errors that can occur while copying or pasting
+#[derive(Debug, Error)]
+pub enum ClipboardError {
+    /// The selection does not refer to copyable content
+    #[error("Invalid selection: {0}")]
+    InvalidSelection(String),
+
+    /// None of the clipboard formats could be used
+    #[error("Clipboard is empty")]
+    Empty,
+
+    /// RTF conversion failed
+    #[error("RTF error: {0}")]
+    Rtf(#[from] crate::rtf::RtfError),
+
+    /// Building the fragment failed
+    #[error("Document model error: {0}")]
+    DocModel(#[from] doc_model::DocModelError),
+
+    /// Storing or converting a pasted image failed
+    #[error("Image error: {0}")]
+    Image(#[from] crate::ImageStoreError),
+
+    /// Internal format (de)serialization failed
+    #[error("Serialization error: {0}")]
+    Store(#[from] crate::StoreError),
+}
+
+/// Result type for clipboard operations
+pub type ClipboardResult<T> = std::result::Result<T, ClipboardError>;
@@ -0,0 +1,204 @@
+//! Document fragments
+//!
+//! A fragment is a standalone `DocumentTree` holding the copied content. Runs
+//! carry their resolved formatting as direct formatting so the content looks
+//! the same when pasted into a document with different styles.
+
+use super::error::{ClipboardError, ClipboardResult};
+use doc_model::{DocumentTree, Node, NodeId, Paragraph, Run, Selection};
+
+/// Copy the selected part of a document into a standalone fragment
+///
+/// Paragraphs at either end are clipped to the selection; tables between the
+/// endpoints are copied whole.
+pub fn extract_fragment(tree: &DocumentTree, selection: &Selection) -> ClipboardResult<DocumentTree> {
+    let mut fragment = DocumentTree::new();
+
+    // Selection inside a single paragraph (body or table cell)
+    if selection.anchor.node_id == selection.focus.node_id {
+        let start = selection.anchor.offset.min(selection.focus.offset);
+        let end = selection.anchor.offset.max(selection.focus.offset);
+        copy_paragraph(tree, &mut fragment, selection.anchor.node_id, start, Some(end))?;
+        return Ok(fragment);
+    }
+
+    let anchor_index = body_index(tree, selection.anchor.node_id)?;
+    let focus_index = body_index(tree, selection.focus.node_id)?;
+    let (start, end) = if anchor_index <= focus_index {
+        (selection.anchor, selection.focus)
+    } else {
+        (selection.focus, selection.anchor)
+    };
+
+    let body = tree.document.children();
+    for &block_id in &body[anchor_index.min(focus_index)..=anchor_index.max(focus_index)] {
+        if tree.get_paragraph(block_id).is_some() {
+            let from = if block_id == start.node_id { start.offset } else { 0 };
+            let to = (block_id == end.node_id).then_some(end.offset);
+            copy_paragraph(tree, &mut fragment, block_id, from, to)?;
+        } else if tree.get_table(block_id).is_some() {
+            copy_table(tree, &mut fragment, block_id);
+        }
+    }
+
+    Ok(fragment)
+}
+
+/// Index of the body block containing a paragraph
+fn body_index(tree: &DocumentTree, node_id: NodeId) -> ClipboardResult<usize> {
+    let block_id = tree.find_table_for_node(node_id).unwrap_or(node_id);
+    tree.document
+        .children()
+        .iter()
+        .position(|&id| id == block_id)
+        .ok_or_else(|| ClipboardError::InvalidSelection(format!("{} is not in the document body", node_id)))
+}
+
+/// Runs of a paragraph, including runs nested in hyperlinks
+fn paragraph_runs(tree: &DocumentTree, para: &Paragraph) -> Vec<NodeId> {
+    let mut runs = Vec::new();
+    for &child_id in para.children() {
+        if tree.get_run(child_id).is_some() {
+            runs.push(child_id);
+        } else if let Some(link) = tree.get_hyperlink(child_id) {
+            runs.extend(link.children().iter().filter(|&&id| tree.get_run(id).is_some()));
+        }
+    }
+    runs
+}
+
+/// Copy characters `start..end` of a paragraph into the fragment body
+fn copy_paragraph(
+    source: &DocumentTree,
+    fragment: &mut DocumentTree,
+    para_id: NodeId,
+    start: usize,
+    end: Option<usize>,
+) -> ClipboardResult<()> {
+    let para = source
+        .get_paragraph(para_id)
+        .ok_or_else(|| ClipboardError::InvalidSelection(format!("Paragraph {} not found", para_id)))?;
+
+    let mut copy = Paragraph::with_direct_formatting(
+        source
+            .compute_paragraph_properties(para_id)
+            .unwrap_or_else(|| para.direct_formatting.clone()),
+    );
+    copy.paragraph_style_id = para.paragraph_style_id.clone();
+    let copy_id = fragment.insert_paragraph(copy, fragment.root_id(), None)?;
+
+    let mut run_start = 0;
+    for run_id in paragraph_runs(source, para) {
+        let Some(run) = source.get_run(run_id) else {
+            continue;
+        };
+        let len = run.text.chars().count();
+        let from = start.max(run_start);
+        let to = end.map_or(run_start + len, |end| end.min(run_start + len));
+        if from < to {
+            let text: String = run.text.chars().skip(from - run_start).take(to - from).collect();
+            let formatting = source
+                .compute_character_properties(run_id)
+                .unwrap_or_else(|| run.direct_formatting.clone());
+            let mut copied = Run::with_direct_formatting(text, formatting);
+            copied.character_style_id = run.character_style_id.clone();
+            fragment.insert_run(copied, copy_id, None)?;
+        }
+        run_start += len;
+    }
+
+    Ok(())
+}
+
+/// Copy a whole table (rows, cells, and cell content) into the fragment body
+fn copy_table(source: &DocumentTree, fragment: &mut DocumentTree, table_id: NodeId) {
+    let Some(table) = source.get_table(table_id) else {
+        return;
+    };
+
+    let mut table = table.clone();
+    table.set_parent(Some(fragment.root_id()));
+    for &row_id in table.children() {
+        let Some(row) = source.get_table_row(row_id) else {
+            continue;
+        };
+        for &cell_id in row.children() {
+            let Some(cell) = source.get_table_cell(cell_id) else {
+                continue;
+            };
+            for &para_id in cell.children() {
+                if let Some(para) = source.get_paragraph(para_id) {
+                    for &run_id in para.children() {
+                        if let Some(run) = source.get_run(run_id) {
+                            fragment.nodes.runs.insert(run_id, run.clone());
+                        }
+                    }
+                    fragment.nodes.paragraphs.insert(para_id, para.clone());
+                }
+            }
+            fragment.nodes.table_cells.insert(cell_id, cell.clone());
+        }
+        fragment.nodes.table_rows.insert(row_id, row.clone());
+    }
+    fragment.nodes.tables.insert(table_id, table);
+    fragment.document.add_body_child(table_id);
+}
+
+/// Plain text of a fragment
+///
+/// Paragraphs end with a newline; table cells are separated by tabs and rows
+/// by newlines.
+pub fn fragment_text(tree: &DocumentTree) -> String {
+    let mut text = String::new();
+    for &block_id in tree.document.children() {
+        if let Some(para) = tree.get_paragraph(block_id) {
+            text.push_str(&paragraph_text(tree, para));
+            text.push('\n');
+        } else if let Some(table) = tree.get_table(block_id) {
+            for row in table.children().iter().filter_map(|&id| tree.get_table_row(id)) {
+                let cells: Vec<String> = row
+                    .children()
+                    .iter()
+                    .filter_map(|&id| tree.get_table_cell(id))
+                    .map(|cell| {
+                        cell.children()
+                            .iter()
+                            .filter_map(|&id| tree.get_paragraph(id))
+                            .map(|para| paragraph_text(tree, para))
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    })
+                    .collect();
+                text.push_str(&cells.join("\t"));
+                text.push('\n');
+            }
+        }
+    }
+    text
+}
+
+fn paragraph_text(tree: &DocumentTree, para: &Paragraph) -> String {
+    paragraph_runs(tree, para)
+        .into_iter()
+        .filter_map(|id| tree.get_run(id))
+        .map(|run| run.text.as_str())
+        .collect()
+}
+
+/// Build a fragment from plain text, one paragraph per line
+pub fn fragment_from_text(text: &str) -> DocumentTree {
+    let mut tree = DocumentTree::new();
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let text = text.strip_suffix('\n').unwrap_or(&text);
+
+    for line in text.split('\n') {
+        let root_id = tree.root_id();
+        let Ok(para_id) = tree.insert_paragraph(Paragraph::new(), root_id, None) else {
+            continue;
+        };
+        if !line.is_empty() {
+            let _ = tree.insert_run(Run::new(line), para_id, None);
+        }
+    }
+    tree
+}
@@ -0,0 +1,780 @@
+//! HTML clipboard format
+//!
+//! Writes fragments as simple inline-styled HTML and reads HTML pasted from
+//! browsers and other word processors. The reader is deliberately tolerant:
+//! unknown tags are ignored, unclosed tags are closed at the end of their
+//! block, and only the content between `StartFragment`/`EndFragment` markers
+//! is used when present.
+
+use doc_model::{
+    Alignment, CharacterProperties, DocumentTree, Node, NodeId, Paragraph, ParagraphProperties,
+    Run, StyleId, Table, TableCell, TableGrid, TableRow, VerticalAlign,
+};
+use std::fmt::Write;
+
+const START_FRAGMENT: &str = "<!--StartFragment-->";
+const END_FRAGMENT: &str = "<!--EndFragment-->";
+
+// =============================================================================
+// Writer
+// =============================================================================
+
+/// Write a fragment as an HTML document
+pub fn fragment_to_html(tree: &DocumentTree) -> String {
+    let mut html = String::from("<html><body>\n");
+    html.push_str(START_FRAGMENT);
+    for &block_id in tree.document.children() {
+        if tree.get_paragraph(block_id).is_some() {
+            write_paragraph(tree, block_id, &mut html);
+        } else if let Some(table) = tree.get_table(block_id) {
+            html.push_str("<table>");
+            for row in table.children().iter().filter_map(|&id| tree.get_table_row(id)) {
+                html.push_str("<tr>");
+                for cell in row.children().iter().filter_map(|&id| tree.get_table_cell(id)) {
+                    html.push_str("<td>");
+                    for &para_id in cell.children() {
+                        write_paragraph(tree, para_id, &mut html);
+                    }
+                    html.push_str("</td>");
+                }
+                html.push_str("</tr>");
+            }
+            html.push_str("</table>");
+        }
+    }
+    html.push_str(END_FRAGMENT);
+    html.push_str("\n</body></html>");
+    html
+}
+
+fn heading_level(style_id: Option<&StyleId>) -> Option<u8> {
+    let name = style_id?.to_string().to_lowercase();
+    let level: u8 = name.strip_prefix("heading")?.trim().parse().ok()?;
+    (1..=6).contains(&level).then_some(level)
+}
+
+fn write_paragraph(tree: &DocumentTree, para_id: NodeId, html: &mut String) {
+    let Some(para) = tree.get_paragraph(para_id) else {
+        return;
+    };
+    let tag = match heading_level(para.paragraph_style_id.as_ref()) {
+        Some(level) => format!("h{}", level),
+        None => "p".to_string(),
+    };
+
+    let css = paragraph_css(&para.direct_formatting);
+    if css.is_empty() {
+        let _ = write!(html, "<{}>", tag);
+    } else {
+        let _ = write!(html, "<{} style=\"{}\">", tag, css);
+    }
+
+    let mut empty = true;
+    for run in para.children().iter().filter_map(|&id| tree.get_run(id)) {
+        if run.text.is_empty() {
+            continue;
+        }
+        empty = false;
+        let text = escape(&run.text).replace('\n', "<br>");
+        let css = character_css(&run.direct_formatting);
+        if css.is_empty() {
+            html.push_str(&text);
+        } else {
+            let _ = write!(html, "<span style=\"{}\">{}</span>", css, text);
+        }
+    }
+    if empty {
+        html.push_str("<br>");
+    }
+    let _ = write!(html, "</{}>", tag);
+}
+
+fn paragraph_css(props: &ParagraphProperties) -> String {
+    let mut css = Vec::new();
+    if let Some(alignment) = props.alignment {
+        let value = match alignment {
+            Alignment::Left => "left",
+            Alignment::Center => "center",
+            Alignment::Right => "right",
+            Alignment::Justify => "justify",
+        };
+        css.push(format!("text-align:{}", value));
+    }
+    if let Some(v) = props.indent_left {
+        css.push(format!("margin-left:{}pt", v));
+    }
+    if let Some(v) = props.indent_right {
+        css.push(format!("margin-right:{}pt", v));
+    }
+    if let Some(v) = props.indent_first_line {
+        css.push(format!("text-indent:{}pt", v));
+    }
+    if let Some(v) = props.space_before {
+        css.push(format!("margin-top:{}pt", v));
+    }
+    if let Some(v) = props.space_after {
+        css.push(format!("margin-bottom:{}pt", v));
+    }
+    if let Some(ref color) = props.background_color {
+        css.push(format!("background-color:{}", color));
+    }
+    css.join(";")
+}
+
+fn character_css(props: &CharacterProperties) -> String {
+    let mut css = Vec::new();
+    if let Some(ref family) = props.font_family {
+        css.push(format!("font-family:'{}'", family.replace('\'', "")));
+    }
+    if let Some(size) = props.font_size {
+        css.push(format!("font-size:{}pt", size));
+    }
+    if props.bold == Some(true) {
+        css.push("font-weight:bold".to_string());
+    }
+    if props.italic == Some(true) {
+        css.push("font-style:italic".to_string());
+    }
+    let decorations: Vec<&str> = [
+        (props.underline == Some(true), "underline"),
+        (props.strikethrough == Some(true), "line-through"),
+    ]
+    .into_iter()
+    .filter_map(|(on, value)| on.then_some(value))
+    .collect();
+    if !decorations.is_empty() {
+        css.push(format!("text-decoration:{}", decorations.join(" ")));
+    }
+    if let Some(ref color) = props.color {
+        css.push(format!("color:{}", color));
+    }
+    if let Some(ref color) = props.highlight {
+        css.push(format!("background-color:{}", color));
+    }
+    match props.vertical_align {
+        Some(VerticalAlign::Superscript) => css.push("vertical-align:super".to_string()),
+        Some(VerticalAlign::Subscript) => css.push("vertical-align:sub".to_string()),
+        _ => {}
+    }
+    if props.all_caps == Some(true) {
+        css.push("text-transform:uppercase".to_string());
+    }
+    if props.small_caps == Some(true) {
+        css.push("font-variant:small-caps".to_string());
+    }
+    css.join(";")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// =============================================================================
+// Tokenizer
+// =============================================================================
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Start { name: String, attrs: Vec<(String, String)> },
+    End(String),
+    Text(String),
+}
+
+fn tokenize(html: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            tokens.push(Token::Text(decode_entities(rest)));
+            break;
+        };
+        if lt > 0 {
+            tokens.push(Token::Text(decode_entities(&rest[..lt])));
+        }
+        rest = &rest[lt..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(gt) = rest.find('>') else {
+            tokens.push(Token::Text(decode_entities(rest)));
+            break;
+        };
+        let tag = &rest[1..gt];
+        rest = &rest[gt + 1..];
+
+        if tag.starts_with('!') || tag.starts_with('?') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            tokens.push(Token::End(name.trim().to_lowercase()));
+            continue;
+        }
+
+        let tag = tag.trim_end_matches('/');
+        let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+        let name = tag[..name_end].to_lowercase();
+        if name.is_empty() {
+            continue;
+        }
+        let attrs = parse_attributes(&tag[name_end..]);
+
+        // Raw text elements: skip their content entirely
+        if matches!(name.as_str(), "script" | "style" | "title") {
+            let close = format!("</{}", name);
+            let lower = rest.to_ascii_lowercase();
+            rest = match lower.find(&close) {
+                Some(end) => rest[end..].find('>').map_or("", |gt| &rest[end + gt + 1..]),
+                None => "",
+            };
+            continue;
+        }
+        tokens.push(Token::Start { name, attrs });
+    }
+
+    tokens
+}
+
+fn parse_attributes(mut s: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    loop {
+        s = s.trim_start();
+        if s.is_empty() {
+            break;
+        }
+        let name_end = s
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(s.len());
+        let name = s[..name_end].to_lowercase();
+        s = s[name_end..].trim_start();
+
+        let value = if let Some(after) = s.strip_prefix('=') {
+            let after = after.trim_start();
+            match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let body = &after[1..];
+                    let end = body.find(quote).unwrap_or(body.len());
+                    s = body.get(end + 1..).unwrap_or("");
+                    &body[..end]
+                }
+                _ => {
+                    let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                    s = &after[end..];
+                    &after[..end]
+                }
+            }
+        } else {
+            ""
+        };
+        if !name.is_empty() {
+            attrs.push((name, decode_entities(value)));
+        }
+    }
+    attrs
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let window = rest.char_indices().nth(12).map_or(rest.len(), |(i, _)| i);
+        let Some(semi) = rest[..window].find(';') else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{00A0}'),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// =============================================================================
+// Reader
+// =============================================================================
+
+/// Parse a CSS length into points
+fn css_points(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let (number, factor) = if let Some(n) = value.strip_suffix("pt") {
+        (n, 1.0)
+    } else if let Some(n) = value.strip_suffix("px") {
+        (n, 0.75)
+    } else if let Some(n) = value.strip_suffix("in") {
+        (n, 72.0)
+    } else if let Some(n) = value.strip_suffix("cm") {
+        (n, 72.0 / 2.54)
+    } else if let Some(n) = value.strip_suffix("em") {
+        (n, 12.0)
+    } else {
+        (value, 0.75)
+    };
+    number.trim().parse::<f32>().ok().map(|n| n * factor)
+}
+
+fn css_declarations(style: &str) -> impl Iterator<Item = (String, &str)> {
+    style.split(';').filter_map(|decl| {
+        let (name, value) = decl.split_once(':')?;
+        Some((name.trim().to_lowercase(), value.trim()))
+    })
+}
+
+fn apply_character_css(props: &mut CharacterProperties, style: &str) {
+    for (name, value) in css_declarations(style) {
+        let lower = value.to_lowercase();
+        match name.as_str() {
+            "font-weight" => {
+                props.bold = Some(lower == "bold" || lower == "bolder" || lower.parse::<u32>().is_ok_and(|w| w >= 600));
+            }
+            "font-style" => props.italic = Some(lower == "italic" || lower == "oblique"),
+            "text-decoration" | "text-decoration-line" => {
+                props.underline = Some(lower.contains("underline"));
+                props.strikethrough = Some(lower.contains("line-through"));
+            }
+            "color" => props.color = Some(value.to_string()),
+            "background-color" | "background" => props.highlight = Some(value.to_string()),
+            "font-family" => {
+                let family = value.split(',').next().unwrap_or(value);
+                props.font_family = Some(family.trim().trim_matches(['\'', '"']).to_string());
+            }
+            "font-size" => props.font_size = css_points(value),
+            "vertical-align" => {
+                props.vertical_align = match lower.as_str() {
+                    "super" => Some(VerticalAlign::Superscript),
+                    "sub" => Some(VerticalAlign::Subscript),
+                    _ => Some(VerticalAlign::Baseline),
+                }
+            }
+            "text-transform" => props.all_caps = Some(lower == "uppercase"),
+            "font-variant" => props.small_caps = Some(lower == "small-caps"),
+            _ => {}
+        }
+    }
+}
+
+fn apply_paragraph_css(props: &mut ParagraphProperties, style: &str) {
+    for (name, value) in css_declarations(style) {
+        match name.as_str() {
+            "text-align" => {
+                props.alignment = match value.to_lowercase().as_str() {
+                    "center" => Some(Alignment::Center),
+                    "right" | "end" => Some(Alignment::Right),
+                    "justify" => Some(Alignment::Justify),
+                    _ => Some(Alignment::Left),
+                }
+            }
+            "margin-left" => props.indent_left = css_points(value),
+            "margin-right" => props.indent_right = css_points(value),
+            "text-indent" => props.indent_first_line = css_points(value),
+            "margin-top" => props.space_before = css_points(value),
+            "margin-bottom" => props.space_after = css_points(value),
+            "background-color" => props.background_color = Some(value.to_string()),
+            _ => {}
+        }
+    }
+}
+
+fn attr<'a>(attrs: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attrs.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+}
+
+fn is_block(name: &str) -> bool {
+    matches!(
+        name,
+        "p" | "div" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "li" | "blockquote" | "pre"
+            | "ul" | "ol" | "body" | "section" | "article" | "header" | "footer"
+    )
+}
+
+/// A paragraph being built
+#[derive(Default)]
+struct PendingParagraph {
+    style_id: Option<StyleId>,
+    props: ParagraphProperties,
+    runs: Vec<Run>,
+    /// Whether the last character written was collapsible whitespace
+    trailing_space: bool,
+}
+
+impl PendingParagraph {
+    fn push_text(&mut self, text: &str, props: &CharacterProperties, preformatted: bool) {
+        let mut buffer = String::new();
+        for c in text.chars() {
+            if !preformatted && c.is_whitespace() && c != '\u{00A0}' {
+                if self.trailing_space || (buffer.is_empty() && self.is_empty()) {
+                    continue;
+                }
+                buffer.push(' ');
+                self.trailing_space = true;
+            } else {
+                buffer.push(c);
+                self.trailing_space = false;
+            }
+        }
+        self.push_raw(&buffer, props);
+    }
+
+    fn push_raw(&mut self, text: &str, props: &CharacterProperties) {
+        if text.is_empty() {
+            return;
+        }
+        match self.runs.last_mut() {
+            Some(run) if run.direct_formatting == *props => run.text.push_str(text),
+            _ => self.runs.push(Run::with_direct_formatting(text, props.clone())),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.runs.iter().all(|run| run.text.is_empty())
+    }
+
+    fn finish(mut self) -> (Paragraph, Vec<Run>) {
+        if let Some(run) = self.runs.last_mut() {
+            if self.trailing_space {
+                run.text.pop();
+            }
+        }
+        self.runs.retain(|run| !run.text.is_empty());
+        let mut para = Paragraph::with_direct_formatting(self.props);
+        if self.style_id.is_some() {
+            para.paragraph_style_id = self.style_id;
+        }
+        (para, self.runs)
+    }
+}
+
+type Block = (Paragraph, Vec<Run>);
+
+enum BodyItem {
+    Paragraph(Box<Block>),
+    Table(Vec<Vec<Vec<Block>>>),
+}
+
+#[derive(Default)]
+struct HtmlReader {
+    items: Vec<BodyItem>,
+    /// Rows of the table being read (cells hold their paragraphs)
+    table: Option<Vec<Vec<Vec<Block>>>>,
+    /// Nesting depth of tables (inner tables are flattened)
+    table_depth: usize,
+    current: PendingParagraph,
+    /// Character formatting stack: (tag name, properties)
+    formats: Vec<(String, CharacterProperties)>,
+    preformatted: usize,
+}
+
+impl HtmlReader {
+    fn char_props(&self) -> CharacterProperties {
+        self.formats
+            .iter()
+            .fold(CharacterProperties::default(), |acc, (_, props)| acc.merge(props))
+    }
+
+    /// End the current paragraph, keeping it only if it has content
+    fn flush(&mut self, keep_empty: bool) {
+        let pending = std::mem::take(&mut self.current);
+        if pending.is_empty() && !keep_empty {
+            return;
+        }
+        let block = pending.finish();
+        match self.table.as_mut().and_then(|rows| rows.last_mut()).and_then(|cells| cells.last_mut()) {
+            Some(cell) => cell.push(block),
+            None => self.items.push(BodyItem::Paragraph(Box::new(block))),
+        }
+    }
+
+    fn start_block(&mut self, name: &str, attrs: &[(String, String)]) {
+        self.flush(false);
+        if let Some(level) = name.strip_prefix('h').and_then(|l| l.parse::<u8>().ok()) {
+            self.current.style_id = Some(StyleId::new(format!("Heading{}", level)));
+        }
+        if let Some(style) = attr(attrs, "style") {
+            apply_paragraph_css(&mut self.current.props, style);
+        }
+        if let Some(align) = attr(attrs, "align") {
+            apply_paragraph_css(&mut self.current.props, &format!("text-align:{}", align));
+        }
+        if name == "pre" {
+            self.preformatted += 1;
+        }
+    }
+
+    fn start_inline(&mut self, name: &str, attrs: &[(String, String)]) {
+        let mut props = CharacterProperties::default();
+        match name {
+            "b" | "strong" => props.bold = Some(true),
+            "i" | "em" | "cite" | "var" => props.italic = Some(true),
+            "u" | "ins" => props.underline = Some(true),
+            "s" | "strike" | "del" => props.strikethrough = Some(true),
+            "sup" => props.vertical_align = Some(VerticalAlign::Superscript),
+            "sub" => props.vertical_align = Some(VerticalAlign::Subscript),
+            "code" | "tt" | "kbd" => props.font_family = Some("Courier New".to_string()),
+            "font" => {
+                props.font_family = attr(attrs, "face").map(|f| f.to_string());
+                props.color = attr(attrs, "color").map(|c| c.to_string());
+            }
+            "span" | "a" | "mark" | "small" | "big" | "abbr" | "label" => {}
+            _ => return,
+        }
+        if name == "mark" {
+            props.highlight = Some("yellow".to_string());
+        }
+        if let Some(style) = attr(attrs, "style") {
+            apply_character_css(&mut props, style);
+        }
+        self.formats.push((name.to_string(), props));
+    }
+
+    fn end_inline(&mut self, name: &str) {
+        if let Some(index) = self.formats.iter().rposition(|(n, _)| n == name) {
+            self.formats.truncate(index);
+        }
+    }
+
+    fn handle(&mut self, token: Token) {
+        match token {
+            Token::Text(text) => {
+                let props = self.char_props();
+                let preformatted = self.preformatted > 0;
+                self.current.push_text(&text, &props, preformatted);
+            }
+            Token::Start { name, attrs } => match name.as_str() {
+                "br" => {
+                    let props = self.char_props();
+                    self.current.push_raw("\n", &props);
+                    self.current.trailing_space = true;
+                }
+                "table" => {
+                    self.flush(false);
+                    self.table_depth += 1;
+                    if self.table_depth == 1 {
+                        self.table = Some(Vec::new());
+                    }
+                }
+                "tr" if self.table_depth == 1 => {
+                    self.flush(false);
+                    if let Some(rows) = self.table.as_mut() {
+                        rows.push(Vec::new());
+                    }
+                }
+                "td" | "th" if self.table_depth == 1 => {
+                    self.flush(false);
+                    if let Some(rows) = self.table.as_mut() {
+                        if rows.is_empty() {
+                            rows.push(Vec::new());
+                        }
+                        if let Some(cells) = rows.last_mut() {
+                            cells.push(Vec::new());
+                        }
+                    }
+                    if name == "th" {
+                        self.formats.push((name, CharacterProperties { bold: Some(true), ..Default::default() }));
+                    }
+                }
+                "tr" | "td" | "th" => self.flush(false),
+                "head" => {}
+                _ if is_block(&name) => self.start_block(&name, &attrs),
+                _ => self.start_inline(&name, &attrs),
+            },
+            Token::End(name) => match name.as_str() {
+                "table" => {
+                    self.flush(false);
+                    self.table_depth = self.table_depth.saturating_sub(1);
+                    if self.table_depth == 0 {
+                        if let Some(rows) = self.table.take() {
+                            self.items.push(BodyItem::Table(rows));
+                        }
+                    }
+                }
+                "td" | "th" | "tr" => {
+                    self.flush(false);
+                    if name == "th" {
+                        self.end_inline("th");
+                    }
+                }
+                "p" => self.flush(true),
+                _ if is_block(&name) => {
+                    self.flush(false);
+                    if name == "pre" {
+                        self.preformatted = self.preformatted.saturating_sub(1);
+                    }
+                }
+                _ => self.end_inline(&name),
+            },
+        }
+    }
+
+    fn finish(mut self) -> DocumentTree {
+        self.flush(false);
+        if let Some(rows) = self.table.take() {
+            self.items.push(BodyItem::Table(rows));
+        }
+
+        let mut tree = DocumentTree::new();
+        for item in self.items {
+            match item {
+                BodyItem::Paragraph(block) => {
+                    let (para, runs) = *block;
+                    let root_id = tree.root_id();
+                    if let Ok(para_id) = tree.insert_paragraph(para, root_id, None) {
+                        for run in runs {
+                            let _ = tree.insert_run(run, para_id, None);
+                        }
+                    }
+                }
+                BodyItem::Table(rows) => insert_table(&mut tree, rows),
+            }
+        }
+        tree
+    }
+}
+
+fn insert_table(tree: &mut DocumentTree, rows: Vec<Vec<Vec<Block>>>) {
+    let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    if columns == 0 {
+        return;
+    }
+    let table = Table::with_grid(TableGrid::with_equal_columns(columns, 468.0));
+    let Ok(table_id) = tree.insert_table(table, None) else {
+        return;
+    };
+    for mut cells in rows {
+        let Ok(row_id) = tree.insert_table_row(TableRow::new(), table_id, None) else {
+            continue;
+        };
+        cells.resize_with(columns, Vec::new);
+        for mut blocks in cells {
+            let Ok(cell_id) = tree.insert_table_cell(TableCell::new(), row_id, None) else {
+                continue;
+            };
+            if blocks.is_empty() {
+                blocks.push((Paragraph::new(), Vec::new()));
+            }
+            for (para, runs) in blocks {
+                if let Ok(para_id) = tree.insert_paragraph_into_cell(para, cell_id, None) {
+                    for run in runs {
+                        let _ = tree.insert_run(run, para_id, None);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Parse pasted HTML into a fragment
+pub fn html_to_fragment(html: &str) -> DocumentTree {
+    let html = match (html.find(START_FRAGMENT), html.find(END_FRAGMENT)) {
+        (Some(start), Some(end)) if start < end => &html[start + START_FRAGMENT.len()..end],
+        _ => html,
+    };
+
+    let mut reader = HtmlReader::default();
+    for token in tokenize(html) {
+        reader.handle(token);
+    }
+    reader.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paragraph_texts(tree: &DocumentTree) -> Vec<String> {
+        tree.paragraphs()
+            .map(|p| {
+                p.children()
+                    .iter()
+                    .filter_map(|&id| tree.get_run(id))
+                    .map(|r| r.text.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_formatting_and_whitespace() {
+        let tree = html_to_fragment(
+            "<html><head><style>p { color: red }</style></head><body>\
+             <p style=\"text-align:center\">Hello   <b>bold</b>\n <span style=\"font-style:italic;font-size:16px\">it&amp;al</span></p>\
+             <h2>Title</h2></body></html>",
+        );
+
+        assert_eq!(paragraph_texts(&tree), vec!["Hello bold it&al", "Title"]);
+
+        let first = tree.paragraphs().next().unwrap();
+        assert_eq!(first.direct_formatting.alignment, Some(Alignment::Center));
+        let runs: Vec<&Run> = first.children().iter().filter_map(|&id| tree.get_run(id)).collect();
+        assert_eq!(runs[1].text, "bold");
+        assert_eq!(runs[1].direct_formatting.bold, Some(true));
+        assert_eq!(runs[3].direct_formatting.italic, Some(true));
+        assert_eq!(runs[3].direct_formatting.font_size, Some(12.0));
+
+        let heading = tree.paragraphs().nth(1).unwrap();
+        assert_eq!(heading.paragraph_style_id, Some(StyleId::new("Heading2")));
+    }
+
+    #[test]
+    fn test_parse_table_and_fragment_markers() {
+        let tree = html_to_fragment(
+            "<p>outside</p><!--StartFragment--><table><tr><th>A</th><td>B</td></tr>\
+             <tr><td>C</td></tr></table><!--EndFragment-->",
+        );
+
+        assert_eq!(tree.paragraphs().count(), 0);
+        let table = tree.tables().next().unwrap();
+        assert_eq!(table.row_count(), 2);
+        assert_eq!(table.column_count(), 2);
+    }
+
+    #[test]
+    fn test_html_round_trip() {
+        let source = "<p>Plain <span style=\"font-weight:bold;color:#ff0000\">red</span><br>next</p><h1>Head</h1>";
+        let tree = html_to_fragment(source);
+        let html = fragment_to_html(&tree);
+        assert!(html.contains("<h1>Head</h1>"));
+
+        let reparsed = html_to_fragment(&html);
+        assert_eq!(paragraph_texts(&reparsed), vec!["Plain red\nnext", "Head"]);
+        let para = reparsed.paragraphs().next().unwrap();
+        let red = reparsed.get_run(para.children()[1]).unwrap();
+        assert_eq!(red.direct_formatting.bold, Some(true));
+        assert_eq!(red.direct_formatting.color.as_deref(), Some("#ff0000"));
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(decode_entities("a &lt;b&gt; &#65;&#x42; &bogus; &"), "a <b> AB &bogus; &");
+        assert_eq!(decode_entities("&ééééééééééé;"), "&ééééééééééé;");
+    }
+}
@@ -0,0 +1,147 @@
+//! Pasting bitmaps (screenshots) from the clipboard
+//!
+//! Clipboards hand over pictures as PNG or as a BMP/DIB. Both are stored
+//! as PNG and sized from the resolution recorded in the bitmap, shrinking
+//! to fit the available width.
+
+use super::error::ClipboardResult;
+use crate::image_codec;
+use crate::image_store::{ImageFormat, ImageStore};
+use doc_model::ResourceId;
+use edit_engine::InsertImage;
+use serde::{Deserialize, Serialize};
+
+/// Resolution assumed when the bitmap doesn't record one
+pub const DEFAULT_CLIPBOARD_DPI: f32 = 96.0;
+
+/// A clipboard bitmap added to the image store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PastedImage {
+    /// Resource ID of the stored image
+    pub resource_id: ResourceId,
+    /// Width in pixels
+    pub pixel_width: u32,
+    /// Height in pixels
+    pub pixel_height: u32,
+    /// Resolution used for sizing
+    pub dpi: f32,
+    /// Display width in points
+    pub width: f32,
+    /// Display height in points
+    pub height: f32,
+}
+
+impl PastedImage {
+    /// Command inserting the image inline at the selection
+    pub fn insert_command(&self) -> InsertImage {
+        InsertImage::inline(
+            self.resource_id.clone(),
+            self.pixel_width,
+            self.pixel_height,
+            self.width,
+            self.height,
+        )
+    }
+}
+
+/// Store clipboard bitmap bytes, converting BMP/DIB data to PNG, and size
+/// the picture for display no wider than `max_width` points
+pub fn paste_image(store: &ImageStore, data: &[u8], max_width: f32) -> ClipboardResult<PastedImage> {
+    let (bytes, dpi) = match ImageFormat::from_bytes(data) {
+        ImageFormat::Png => (data.to_vec(), image_codec::png_dpi(data)),
+        // Anything else recognizable is stored as-is
+        ImageFormat::Jpeg | ImageFormat::Gif | ImageFormat::WebP | ImageFormat::Svg => (data.to_vec(), None),
+        // BMP files and bare DIBs
+        ImageFormat::Bmp | ImageFormat::Unknown => {
+            let (image, dpi) = image_codec::decode_bmp(data)?;
+            (image_codec::encode_png(&image)?, dpi)
+        }
+    };
+    // Ignore implausible resolutions (some tools write 1 or 72000)
+    let dpi = dpi.filter(|dpi| (24.0..=1200.0).contains(dpi)).unwrap_or(DEFAULT_CLIPBOARD_DPI);
+
+    let filename = format!("pasted-image.{}", ImageFormat::from_bytes(&bytes).extension());
+    let resource_id = store.store_image(bytes, Some(filename))?;
+    let image = store.get_image(&resource_id)?;
+
+    let mut width = image.width as f32 / dpi * 72.0;
+    let mut height = image.height as f32 / dpi * 72.0;
+    if max_width > 0.0 && width > max_width {
+        height = max_width * height / width;
+        width = max_width;
+    }
+
+    Ok(PastedImage {
+        resource_id,
+        pixel_width: image.width,
+        pixel_height: image.height,
+        dpi,
+        width,
+        height,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::{DocumentTree, Node, Position, Selection};
+    use edit_engine::Command;
+
+    /// A bare 24-bit DIB, bottom-up, at the given pixels per meter
+    fn dib(width: u32, height: u32, per_meter: u32) -> Vec<u8> {
+        let stride = (width * 3).div_ceil(4) * 4;
+        let mut data = Vec::new();
+        data.extend_from_slice(&40u32.to_le_bytes());
+        data.extend_from_slice(&width.to_le_bytes());
+        data.extend_from_slice(&height.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&24u16.to_le_bytes());
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&per_meter.to_le_bytes());
+        data.extend_from_slice(&per_meter.to_le_bytes());
+        data.extend_from_slice(&[0; 8]);
+        for y in 0..height {
+            for _ in 0..width {
+                // Bottom row blue, everything else red (BGR order)
+                data.extend_from_slice(if y == 0 { &[255, 0, 0] } else { &[0, 0, 255] });
+            }
+            data.extend(std::iter::repeat_n(0, (stride - width * 3) as usize));
+        }
+        data
+    }
+
+    #[test]
+    fn test_paste_dib_converts_to_png_and_sizes_from_dpi() {
+        let store = ImageStore::new();
+        // 3780 pixels per meter is 96 dpi
+        let pasted = paste_image(&store, &dib(5, 3, 3780), 468.0).unwrap();
+        assert_eq!((pasted.pixel_width, pasted.pixel_height), (5, 3));
+        assert!((pasted.dpi - 96.0).abs() < 0.1);
+        assert!((pasted.width - 3.75).abs() < 0.01);
+
+        let image = store.get_image(&pasted.resource_id).unwrap();
+        assert_eq!(image.format, ImageFormat::Png);
+        let decoded = image_codec::decode_png(&image.data).unwrap();
+        assert_eq!(&decoded.data[..4], &[255, 0, 0, 255]);
+        assert_eq!(&decoded.data[decoded.data.len() - 4..], &[0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_paste_image_fits_width_and_inserts() {
+        let store = ImageStore::new();
+        // No recorded resolution: 960 pixels at 96 dpi is 720pt
+        let pasted = paste_image(&store, &dib(960, 480, 0), 468.0).unwrap();
+        assert_eq!(pasted.dpi, DEFAULT_CLIPBOARD_DPI);
+        assert_eq!(pasted.width, 468.0);
+        assert_eq!(pasted.height, 234.0);
+
+        let tree = DocumentTree::with_empty_paragraph();
+        let para_id = tree.document.children()[0];
+        let selection = Selection::collapsed(Position::new(para_id, 0));
+        let result = pasted.insert_command().apply(&tree, &selection).unwrap();
+        let image = result.tree.images().next().unwrap();
+        assert_eq!(image.resource_id, pasted.resource_id);
+        assert_eq!(image.effective_width(468.0), 468.0);
+    }
+}
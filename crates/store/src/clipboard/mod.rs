@@ -0,0 +1,26 @@
+//! Clipboard Module
+//!
+//! Copy serializes a selection to several formats at once so any target
+//! application can take the richest one it understands:
+//! - Internal JSON (lossless, used between go-word documents)
+//! - RTF (via the `rtf` module)
+//! - HTML
+//! - Plain text
+//!
+//! Paste parses the richest available format into a document fragment and
+//! applies the chosen paste mode (keep source formatting, merge formatting,
+//! or text only). Bitmaps such as screenshots are pasted through the
+//! `image` module into the image store.
+
+mod error;
+mod fragment;
+mod html;
+mod api;
+mod image;
+
+pub use error::{ClipboardError, ClipboardResult};
+pub use fragment::{extract_fragment, fragment_from_text, fragment_text};
+pub use html::{fragment_to_html, html_to_fragment};
+pub use api::{copy_selection, paste, ClipboardData, ClipboardFormat, PasteFragment, PasteMode, PasteOptions};
+pub use image::{paste_image, PastedImage, DEFAULT_CLIPBOARD_DPI};
+pub use api::{MIME_HTML, MIME_INTERNAL, MIME_PLAIN_TEXT, MIME_RTF};
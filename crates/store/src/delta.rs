@@ -0,0 +1,317 @@
+//! Differential document snapshots
+//!
+//! Autosave writes a full checkpoint of the document now and then, and in
+//! between appends deltas that hold only the nodes and top-level parts that
+//! changed since the previous save. Changes are found by fingerprinting the
+//! serialized form of each node, so no edit tracking is needed. Replaying the
+//! deltas onto the checkpoint gives back the latest saved state.
+
+use crate::Result;
+use doc_model::DocumentTree;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// Top-level field of the serialized tree that holds the node storages
+const NODES_FIELD: &str = "nodes";
+
+/// Changes to a document since the previous save
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TreeDelta {
+    /// Position of the delta after its checkpoint (1-based)
+    pub sequence: u64,
+    /// Replaced top-level parts of the tree (document, styles, ...), by field name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub parts: BTreeMap<String, Value>,
+    /// Added or changed nodes, by storage name and node ID
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub nodes: BTreeMap<String, BTreeMap<String, Value>>,
+    /// Removed nodes, by storage name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub removed: BTreeMap<String, Vec<String>>,
+}
+
+impl TreeDelta {
+    /// Check if the delta has no changes
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty() && self.nodes.is_empty() && self.removed.is_empty()
+    }
+
+    /// Number of changed nodes and parts
+    pub fn len(&self) -> usize {
+        self.parts.len()
+            + self.nodes.values().map(BTreeMap::len).sum::<usize>()
+            + self.removed.values().map(Vec::len).sum::<usize>()
+    }
+
+    /// Apply the delta to a serialized document tree
+    pub fn apply_to(&self, tree: &mut Value) {
+        let Some(root) = tree.as_object_mut() else {
+            return;
+        };
+        for (name, part) in &self.parts {
+            root.insert(name.clone(), part.clone());
+        }
+
+        let storages = root
+            .entry(NODES_FIELD)
+            .or_insert_with(|| Value::Object(Map::new()));
+        let Some(storages) = storages.as_object_mut() else {
+            return;
+        };
+        for (storage, nodes) in &self.nodes {
+            let entry = storages
+                .entry(storage.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Some(entry) = entry.as_object_mut() {
+                for (id, node) in nodes {
+                    entry.insert(id.clone(), node.clone());
+                }
+            }
+        }
+        for (storage, ids) in &self.removed {
+            if let Some(entry) = storages.get_mut(storage).and_then(Value::as_object_mut) {
+                for id in ids {
+                    entry.remove(id);
+                }
+            }
+        }
+    }
+}
+
+/// Fingerprints of the last saved state, used to find what changed
+#[derive(Debug, Clone, Default)]
+pub struct DeltaTracker {
+    /// Fingerprint of each top-level part
+    parts: HashMap<String, u64>,
+    /// Fingerprint of each node, by storage name and node ID
+    nodes: HashMap<String, HashMap<String, u64>>,
+    /// Deltas taken since the last checkpoint
+    sequence: u64,
+}
+
+impl DeltaTracker {
+    /// Create a tracker with no checkpoint
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if a checkpoint has been recorded
+    pub fn has_checkpoint(&self) -> bool {
+        !self.parts.is_empty()
+    }
+
+    /// Number of deltas taken since the last checkpoint
+    pub fn deltas_since_checkpoint(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Forget the checkpoint, so the next save is a full one
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Record a full checkpoint of the tree
+    pub fn checkpoint(&mut self, tree: &DocumentTree) -> Result<()> {
+        let (parts, nodes) = fingerprints(&serde_json::to_value(tree)?);
+        self.parts = parts;
+        self.nodes = nodes;
+        self.sequence = 0;
+        Ok(())
+    }
+
+    /// Find what changed since the previous checkpoint or delta and record
+    /// the tree as saved
+    ///
+    /// Returns None if nothing changed.
+    pub fn delta(&mut self, tree: &DocumentTree) -> Result<Option<TreeDelta>> {
+        let value = serde_json::to_value(tree)?;
+        let (parts, nodes) = fingerprints(&value);
+        let root = value.as_object();
+        let field = |name: &str| root.and_then(|r| r.get(name));
+
+        let mut delta = TreeDelta::default();
+        for (name, hash) in &parts {
+            if self.parts.get(name) != Some(hash) {
+                if let Some(part) = field(name) {
+                    delta.parts.insert(name.clone(), part.clone());
+                }
+            }
+        }
+
+        let storages = field(NODES_FIELD).and_then(Value::as_object);
+        for (storage, hashes) in &nodes {
+            let previous = self.nodes.get(storage);
+            let entries = storages.and_then(|s| s.get(storage)).and_then(Value::as_object);
+            for (id, hash) in hashes {
+                if previous.and_then(|p| p.get(id)) != Some(hash) {
+                    if let Some(node) = entries.and_then(|e| e.get(id)) {
+                        delta
+                            .nodes
+                            .entry(storage.clone())
+                            .or_default()
+                            .insert(id.clone(), node.clone());
+                    }
+                }
+            }
+        }
+        for (storage, previous) in &self.nodes {
+            let current = nodes.get(storage);
+            let mut removed: Vec<String> = previous
+                .keys()
+                .filter(|id| current.is_none_or(|c| !c.contains_key(*id)))
+                .cloned()
+                .collect();
+            if !removed.is_empty() {
+                removed.sort();
+                delta.removed.insert(storage.clone(), removed);
+            }
+        }
+
+        self.parts = parts;
+        self.nodes = nodes;
+        if delta.is_empty() {
+            return Ok(None);
+        }
+        self.sequence += 1;
+        delta.sequence = self.sequence;
+        Ok(Some(delta))
+    }
+}
+
+/// Fingerprint the top-level parts and the nodes of a serialized tree
+#[allow(clippy::type_complexity)]
+fn fingerprints(tree: &Value) -> (HashMap<String, u64>, HashMap<String, HashMap<String, u64>>) {
+    let mut parts = HashMap::new();
+    let mut nodes = HashMap::new();
+    let Some(root) = tree.as_object() else {
+        return (parts, nodes);
+    };
+    for (name, value) in root {
+        if name == NODES_FIELD {
+            for (storage, entries) in value.as_object().into_iter().flatten() {
+                let hashes = entries
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(id, node)| (id.clone(), fingerprint(node)))
+                    .collect();
+                nodes.insert(storage.clone(), hashes);
+            }
+        } else {
+            parts.insert(name.clone(), fingerprint(value));
+        }
+    }
+    (parts, nodes)
+}
+
+fn fingerprint(value: &Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.to_string().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Apply deltas, in order, to a document tree
+pub fn replay_deltas(tree: &DocumentTree, deltas: &[TreeDelta]) -> Result<DocumentTree> {
+    let mut value = serde_json::to_value(tree)?;
+    for delta in deltas {
+        delta.apply_to(&mut value);
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Path of the delta log that goes with a checkpoint file
+pub fn delta_log_path(checkpoint_path: impl AsRef<Path>) -> PathBuf {
+    checkpoint_path.as_ref().with_extension("delta")
+}
+
+/// Append a delta to a delta log, one JSON object per line
+pub async fn append_delta(log_path: impl AsRef<Path>, delta: &TreeDelta) -> Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut line = serde_json::to_string(delta)?;
+    line.push('\n');
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)
+        .await?;
+    file.write_all(line.as_bytes()).await?;
+    file.sync_data().await?;
+    Ok(())
+}
+
+/// Read the deltas of a delta log
+///
+/// Reading stops at the first line that can't be parsed, which is where a
+/// crash interrupted an append.
+pub async fn read_delta_log(log_path: impl AsRef<Path>) -> Result<Vec<TreeDelta>> {
+    let log_path = log_path.as_ref();
+    if !log_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = tokio::fs::read_to_string(log_path).await?;
+    let mut deltas = Vec::new();
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(delta) => deltas.push(delta),
+            Err(e) => {
+                tracing::warn!("Ignoring truncated delta log {}: {}", log_path.display(), e);
+                break;
+            }
+        }
+    }
+    Ok(deltas)
+}
+
+/// Load a checkpoint file and replay its delta log, if any
+pub async fn load_document_with_deltas(path: impl AsRef<Path>) -> Result<DocumentTree> {
+    let path = path.as_ref();
+    let tree = crate::load_document(path).await?;
+    let deltas = read_delta_log(delta_log_path(path)).await?;
+    if deltas.is_empty() {
+        return Ok(tree);
+    }
+    replay_deltas(&tree, &deltas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::{Node, Paragraph, Run};
+
+    #[test]
+    fn test_delta_holds_only_changes_and_replays() {
+        let mut tree = DocumentTree::with_empty_paragraph();
+        let para_id = tree.paragraphs().next().unwrap().id();
+        let run_id = tree.insert_run(Run::new("Hello"), para_id, None).unwrap();
+        for _ in 0..20 {
+            let para = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+            tree.insert_run(Run::new("Unchanged text"), para, None).unwrap();
+        }
+        let checkpoint = tree.clone();
+
+        let mut tracker = DeltaTracker::new();
+        tracker.checkpoint(&tree).unwrap();
+        assert!(tracker.delta(&tree).unwrap().is_none());
+
+        tree.get_run_mut(run_id).unwrap().text = "Hello world".to_string();
+        let first = tracker.delta(&tree).unwrap().unwrap();
+        assert_eq!(first.sequence, 1);
+        assert_eq!(first.nodes.get("runs").map(BTreeMap::len), Some(1));
+
+        let new_para = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new("Added"), new_para, None).unwrap();
+        tree.remove_run(run_id).unwrap();
+        let second = tracker.delta(&tree).unwrap().unwrap();
+        assert_eq!(second.removed.get("runs").map(Vec::len), Some(1));
+
+        let replayed = replay_deltas(&checkpoint, &[first, second]).unwrap();
+        assert_eq!(replayed.text_content(), tree.text_content());
+        assert_eq!(tracker.deltas_since_checkpoint(), 2);
+    }
+}
@@ -0,0 +1,85 @@
+//! Alt text reading and writing
+//!
+//! Images and shapes keep their alt text in the `descr` attribute of
+//! `<wp:docPr>`. Word marks an object decorative with an
+//! `<adec:decorative val="1"/>` extension inside the docPr's `<a:extLst>`.
+//! Tables keep theirs in `<w:tblDescription>`, with `<w:tblCaption>` as a
+//! short title. Word has no decorative flag for tables, so a layout table
+//! carries the same extension element inside its `<w:tblPr>`, marked
+//! ignorable so other readers skip it.
+
+use crate::docx::reader::XmlParser;
+use quick_xml::events::BytesStart;
+
+/// Extension URI of the decorative flag
+const DECORATIVE_EXT_URI: &str = "{C183D7F6-B498-43B3-948B-1728B52AA6E4}";
+
+/// Namespace of the decorative flag
+const DECORATIVE_NS: &str = "http://schemas.microsoft.com/office/drawing/2017/decorative";
+
+/// Read the alt text of a `<wp:docPr>`
+pub(crate) fn read_doc_pr_alt_text(e: &BytesStart) -> Option<String> {
+    attribute(e, b"descr")
+}
+
+/// Read the value of a `<w:tblCaption>` or `<w:tblDescription>`
+pub(crate) fn read_table_alt_text(e: &BytesStart) -> Option<String> {
+    attribute(e, b"w:val").or_else(|| attribute(e, b"val"))
+}
+
+/// Whether an element is a decorative flag that is switched on
+pub(crate) fn read_decorative(e: &BytesStart) -> bool {
+    XmlParser::matches_element(e.name().as_ref(), "decorative")
+        && XmlParser::get_attribute(e, b"val").is_some_and(|val| XmlParser::parse_bool(&val))
+}
+
+/// Write a `<wp:docPr>` with the object's alt text and decorative flag
+///
+/// Decorative objects are written without a description.
+pub(crate) fn write_doc_pr(xml: &mut String, id: u32, name: &str, alt_text: Option<&str>, decorative: bool) {
+    xml.push_str(&format!("<wp:docPr id=\"{}\" name=\"{}\"", id, escape_xml(name)));
+    if let Some(alt_text) = alt_text.filter(|_| !decorative) {
+        xml.push_str(&format!(" descr=\"{}\"", escape_xml(alt_text)));
+    }
+    if !decorative {
+        xml.push_str("/>");
+        return;
+    }
+
+    xml.push('>');
+    xml.push_str("<a:extLst xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\">");
+    xml.push_str(&format!("<a:ext uri=\"{}\">", DECORATIVE_EXT_URI));
+    xml.push_str(&format!("<adec:decorative xmlns:adec=\"{}\" val=\"1\"/>", DECORATIVE_NS));
+    xml.push_str("</a:ext></a:extLst></wp:docPr>");
+}
+
+/// Write a table's alt text and decorative flag inside `<w:tblPr>`
+///
+/// Decorative tables are written without a description.
+pub(crate) fn write_table_alt_text(xml: &mut String, alt_text: Option<&str>, decorative: bool) {
+    if decorative {
+        xml.push_str(&format!(
+            "<adec:decorative xmlns:adec=\"{}\" mc:Ignorable=\"adec\" val=\"1\"/>",
+            DECORATIVE_NS
+        ));
+    } else if let Some(alt_text) = alt_text {
+        xml.push_str(&format!("<w:tblDescription w:val=\"{}\"/>", escape_xml(alt_text)));
+    }
+}
+
+/// Get an attribute value with entities unescaped
+fn attribute(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .filter_map(|a| a.ok())
+        .find(|a| a.key.as_ref() == name)
+        .and_then(|a| a.unescape_value().ok().map(|value| value.into_owned()))
+}
+
+/// Escape special XML characters
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
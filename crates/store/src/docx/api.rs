@@ -5,6 +5,7 @@
 use crate::docx::error::{DocxError, DocxResult};
 use crate::docx::parser::DocxParser;
 use crate::docx::writer::DocxWriter;
+use crate::image_store::{ImageOptimizationOptions, ImageOptimizationReport, ImageStore};
 use doc_model::DocumentTree;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Cursor};
@@ -170,6 +171,63 @@ pub fn export_docx_bytes(tree: &DocumentTree) -> DocxResult<Vec<u8>> {
     Ok(buffer)
 }
 
+/// Export a DocumentTree with its images to a DOCX file on disk
+///
+/// # Arguments
+///
+/// * `tree` - The document tree to export
+/// * `images` - The store holding the document's image data
+/// * `optimization` - Picture compression for the written images; the
+///   store itself is left at full quality
+/// * `path` - Path where the DOCX file will be saved
+///
+/// # Returns
+///
+/// * `Ok(ImageOptimizationReport)` - The space saved by compression
+/// * `Err(DocxError)` - If export fails
+pub fn export_docx_with_images(
+    tree: &DocumentTree,
+    images: &ImageStore,
+    optimization: Option<&ImageOptimizationOptions>,
+    path: &Path,
+) -> DocxResult<ImageOptimizationReport> {
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    let file = File::create(path)?;
+    image_docx_writer(BufWriter::new(file), images, optimization).write_with_report(tree)
+}
+
+/// Export a DocumentTree with its images to an in-memory byte vector
+///
+/// Returns the DOCX file content and the space saved by compressing
+/// pictures.
+pub fn export_docx_bytes_with_images(
+    tree: &DocumentTree,
+    images: &ImageStore,
+    optimization: Option<&ImageOptimizationOptions>,
+) -> DocxResult<(Vec<u8>, ImageOptimizationReport)> {
+    let mut buffer = Vec::new();
+    let report = image_docx_writer(Cursor::new(&mut buffer), images, optimization).write_with_report(tree)?;
+    Ok((buffer, report))
+}
+
+/// Create a DOCX writer that writes images from a store
+fn image_docx_writer<W: std::io::Write + std::io::Seek>(
+    writer: W,
+    images: &ImageStore,
+    optimization: Option<&ImageOptimizationOptions>,
+) -> DocxWriter<W> {
+    let docx_writer = DocxWriter::new(writer).with_images(images.clone());
+    match optimization {
+        Some(options) => docx_writer.with_image_optimization(options.clone()),
+        None => docx_writer,
+    }
+}
+
 /// Supported file formats for import/export
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FileFormat {
@@ -279,7 +337,7 @@ pub fn get_export_formats() -> Vec<FileFormat> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use doc_model::{Paragraph, Run};
+    use doc_model::{Node, Paragraph, Run};
 
     #[test]
     fn test_file_format_extension() {
@@ -337,4 +395,797 @@ mod tests {
         let result = import_docx(Path::new("/nonexistent/path/document.docx"));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_theme_round_trip() {
+        use doc_model::{CharacterProperties, ThemeColor, ThemeColorRef, ThemeFont};
+
+        let mut tree = DocumentTree::new();
+        tree.theme = tree
+            .theme
+            .clone()
+            .with_color(ThemeColor::Accent1, "#C00000")
+            .with_fonts("Georgia", "Verdana");
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        let props = CharacterProperties {
+            theme_color: Some(ThemeColorRef::new(ThemeColor::Accent1).with_shade(0xBF)),
+            theme_font: Some(ThemeFont::Major),
+            ..Default::default()
+        };
+        let run_id = tree.insert_run(Run::with_direct_formatting("Themed", props), para_id, None).unwrap();
+        let expected = tree.compute_character_properties(run_id).unwrap();
+
+        let bytes = export_docx_bytes(&tree).unwrap();
+        let mut imported = import_docx_bytes(&bytes).unwrap();
+        assert_eq!(imported.theme.colors, tree.theme.colors);
+        assert_eq!(imported.theme.fonts, tree.theme.fonts);
+
+        let run_id = imported.nodes.runs.keys().copied().next().unwrap();
+        let computed = imported.compute_character_properties(run_id).unwrap();
+        assert_eq!(computed.color, expected.color);
+        assert_eq!(computed.font_family.as_deref(), Some("Georgia"));
+
+        // Changing the theme recolors the run
+        imported.theme.set_color(ThemeColor::Accent1, "#000000");
+        let recolored = imported.compute_character_properties(run_id).unwrap();
+        assert_eq!(recolored.color.as_deref(), Some("#000000"));
+    }
+
+    #[test]
+    fn test_tab_stops_round_trip() {
+        use doc_model::{Node, ParagraphProperties, Style, StyleId, TabLeader, TabStop};
+
+        let mut tree = DocumentTree::new();
+        tree.styles.register(
+            Style::paragraph("PriceList", "Price List").with_paragraph_props(ParagraphProperties {
+                tab_stops: vec![TabStop::decimal(360.0)],
+                ..Default::default()
+            }),
+        );
+        let mut para = Paragraph::with_paragraph_style(StyleId::new("PriceList"));
+        para.direct_formatting.tab_stops = vec![
+            TabStop::left(72.0),
+            TabStop::right(468.0).with_leader(TabLeader::Dot),
+        ];
+        let para_id = tree.insert_paragraph(para, tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new("Widget\t\t9.99"), para_id, None).unwrap();
+
+        let bytes = export_docx_bytes(&tree).unwrap();
+        let imported = import_docx_bytes(&bytes).unwrap();
+
+        let style = imported.styles.get(&StyleId::new("PriceList")).unwrap();
+        assert_eq!(style.paragraph_props.tab_stops, vec![TabStop::decimal(360.0)]);
+
+        let para = imported.paragraphs().next().unwrap();
+        assert_eq!(
+            para.direct_formatting.tab_stops,
+            vec![TabStop::left(72.0), TabStop::right(468.0).with_leader(TabLeader::Dot)]
+        );
+        let run = imported.get_run(para.children()[0]).unwrap();
+        assert_eq!(run.text, "Widget\t\t9.99");
+    }
+
+    #[test]
+    fn test_character_effects_round_trip() {
+        use doc_model::CharacterProperties;
+
+        let props = CharacterProperties {
+            all_caps: Some(true),
+            small_caps: Some(true),
+            spacing: Some(1.5),
+            position: Some(-3.0),
+            scale: Some(150),
+            kerning: Some(14.0),
+            outline: Some(true),
+            shadow: Some(true),
+            emboss: Some(false),
+            imprint: Some(true),
+            ..Default::default()
+        };
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.insert_run(Run::with_direct_formatting("Effects", props.clone()), para_id, None)
+            .unwrap();
+
+        let bytes = export_docx_bytes(&tree).unwrap();
+        let imported = import_docx_bytes(&bytes).unwrap();
+        let run = imported.nodes.runs.values().next().unwrap();
+        assert_eq!(run.direct_formatting, props);
+    }
+
+    #[test]
+    fn test_text_effects_round_trip() {
+        use doc_model::{
+            CharacterProperties, ShapeColor, Style, StyleId, TextEffects, TextFill, TextGlow,
+            TextOutline, ThemeColor,
+        };
+
+        let title_effects = TextEffects::new()
+            .with_fill(TextFill::linear(90.0, ThemeColor::Accent1, ShapeColor::PURPLE))
+            .with_glow(TextGlow::new(5.0, ThemeColor::Accent1).with_transparency(0.6));
+        let mut tree = DocumentTree::new();
+        tree.styles.register(Style::character("FancyTitle", "Fancy Title").with_character_props(
+            CharacterProperties {
+                text_effects: Some(title_effects.clone()),
+                ..Default::default()
+            },
+        ));
+
+        let outline =
+            TextEffects::new().with_outline(TextOutline::new(1.0, TextFill::solid(ShapeColor::RED)));
+        let mut run = Run::with_direct_formatting(
+            "Title",
+            CharacterProperties {
+                text_effects: Some(outline.clone()),
+                ..Default::default()
+            },
+        );
+        run.set_character_style(Some(StyleId::new("FancyTitle")));
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.insert_run(run, para_id, None).unwrap();
+
+        let bytes = export_docx_bytes(&tree).unwrap();
+        let imported = import_docx_bytes(&bytes).unwrap();
+
+        let style = imported.styles.get(&StyleId::new("FancyTitle")).unwrap();
+        assert_eq!(style.character_props.text_effects, Some(title_effects.clone()));
+        let (&run_id, run) = imported.nodes.runs.iter().next().unwrap();
+        assert_eq!(run.direct_formatting.text_effects, Some(outline));
+
+        // Direct effects layer over the style's
+        let computed = imported.compute_character_properties(run_id).unwrap();
+        let effects = computed.text_effects.unwrap();
+        assert_eq!(effects.fill, title_effects.fill);
+        assert!(effects.outline.is_some() && effects.glow.is_some());
+    }
+
+    #[test]
+    fn test_section_watermarks_round_trip() {
+        use doc_model::{Node, Section, SectionBreakType, TextWatermark, Watermark, WatermarkLayout};
+
+        let mut tree = DocumentTree::new();
+        let mut paragraphs = Vec::new();
+        for text in ["Draft section", "Final section"] {
+            let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+            tree.insert_run(Run::new(text), para_id, None).unwrap();
+            paragraphs.push(para_id);
+        }
+        let draft = TextWatermark::new("DRAFT")
+            .with_font_family("Arial")
+            .with_color("#FF0000")
+            .with_transparency(0.25);
+        let mut first = Section::new().with_watermark(Watermark::Text(draft.clone()));
+        first.add_child(paragraphs[0]);
+        let mut second = Section::new();
+        second.page_setup.section_start = SectionBreakType::Continuous;
+        second.add_child(paragraphs[1]);
+        tree.add_section(first);
+        tree.add_section(second);
+
+        let bytes = export_docx_bytes(&tree).unwrap();
+        let imported = import_docx_bytes(&bytes).unwrap();
+
+        assert_eq!(imported.sections.len(), 2);
+        let (first, second) = (&imported.sections[0], &imported.sections[1]);
+        assert_eq!(first.children().len(), 1);
+        assert_eq!(second.children().len(), 1);
+        assert_eq!(second.page_setup.section_start, SectionBreakType::Continuous);
+        match &first.watermark {
+            Some(Watermark::Text(text)) => {
+                assert_eq!(text.text, "DRAFT");
+                assert_eq!(text.font_family, "Arial");
+                assert_eq!(text.color, "#FF0000");
+                assert_eq!(text.font_size, None);
+                assert_eq!(text.layout, WatermarkLayout::Diagonal);
+                assert!((text.transparency - 0.25).abs() < 1e-6);
+            }
+            other => panic!("expected a text watermark, got {:?}", other),
+        }
+        // The second section clears the watermark instead of inheriting it
+        assert!(second.watermark.is_none());
+        assert!(!second.link_to_previous);
+    }
+
+    #[test]
+    fn test_page_numbering_round_trip() {
+        use doc_model::{PageNumberFormat, PageNumbering, Section};
+
+        let mut tree = DocumentTree::new();
+        let mut front = Section::new();
+        front.page_setup.page_numbering = PageNumbering::restart(PageNumberFormat::LowercaseRoman, 1);
+        let mut body = Section::new();
+        body.page_setup.page_numbering = PageNumbering::restart(PageNumberFormat::Arabic, 1);
+        for section in [&mut front, &mut body] {
+            let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+            section.add_child(para_id);
+        }
+        tree.add_section(front);
+        tree.add_section(body);
+
+        let imported = import_docx_bytes(&export_docx_bytes(&tree).unwrap()).unwrap();
+        let numbering: Vec<_> = imported.sections.iter().map(|section| section.page_setup.page_numbering).collect();
+        assert_eq!(
+            numbering,
+            [
+                PageNumbering::restart(PageNumberFormat::LowercaseRoman, 1),
+                PageNumbering::restart(PageNumberFormat::Arabic, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line_numbering_round_trip() {
+        use doc_model::{LineNumberRestart, LineNumbering, Node, Section, SectionBreakType};
+
+        let mut tree = DocumentTree::new();
+        let mut paragraphs = Vec::new();
+        for text in ["Numbered", "Not numbered", "Continued"] {
+            let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+            tree.insert_run(Run::new(text), para_id, None).unwrap();
+            paragraphs.push(para_id);
+        }
+        tree.get_paragraph_mut(paragraphs[1]).unwrap().direct_formatting.suppress_line_numbers = Some(true);
+
+        let mut first = Section::new();
+        first.page_setup.line_numbering = LineNumbering::every_n_lines(5)
+            .with_start_at(10)
+            .with_distance(27.0)
+            .with_restart(LineNumberRestart::PerSection);
+        first.add_child(paragraphs[0]);
+        first.add_child(paragraphs[1]);
+        let mut second = Section::new();
+        second.page_setup.section_start = SectionBreakType::Continuous;
+        second.page_setup.line_numbering = LineNumbering::enabled().with_restart(LineNumberRestart::Continuous);
+        second.add_child(paragraphs[2]);
+        tree.add_section(first);
+        tree.add_section(second);
+
+        let bytes = export_docx_bytes(&tree).unwrap();
+        let imported = import_docx_bytes(&bytes).unwrap();
+
+        let first = &imported.sections[0].page_setup.line_numbering;
+        assert!(first.enabled);
+        assert_eq!(first.count_by, 5);
+        assert_eq!(first.start_at, 10);
+        assert_eq!(first.restart, LineNumberRestart::PerSection);
+        assert!((first.distance_from_text - 27.0).abs() < 1e-6);
+        let second = &imported.sections[1].page_setup.line_numbering;
+        assert!(second.enabled);
+        assert_eq!(second.restart, LineNumberRestart::Continuous);
+
+        let suppressed: Vec<_> = imported
+            .document
+            .children()
+            .iter()
+            .map(|&id| imported.get_paragraph(id).unwrap().direct_formatting.suppress_line_numbers)
+            .collect();
+        assert_eq!(suppressed, vec![None, Some(true), None]);
+    }
+
+    #[test]
+    fn test_endnotes_round_trip() {
+        use doc_model::{EndnotePosition, EndnoteProperties, Node, NumberingScheme, Position, RestartNumbering, Section};
+
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new("Cited"), para_id, None).unwrap();
+        tree.insert_run(Run::new(" text"), para_id, None).unwrap();
+        let mut section = Section::new();
+        section.add_child(para_id);
+        let section_id = section.id();
+        tree.add_section(section);
+        tree.set_endnote_properties(Some(section_id), EndnoteProperties {
+            position: EndnotePosition::EndOfSection,
+            restart: RestartNumbering::PerSection,
+            numbering: NumberingScheme::UpperLetter,
+            start_at: 2,
+            ..Default::default()
+        });
+
+        let (note_id, _) = tree.insert_endnote(Position::new(para_id, 5), None);
+        for text in ["First line", "Second line"] {
+            let note_para = tree.insert_paragraph_into_endnote(Paragraph::new(), note_id, None).unwrap();
+            tree.insert_run(Run::new(text), note_para, None).unwrap();
+        }
+
+        let bytes = export_docx_bytes(&tree).unwrap();
+        let imported = import_docx_bytes(&bytes).unwrap();
+
+        let props = imported.get_endnote_properties(Some(imported.sections[0].id()));
+        assert_eq!(props.position, EndnotePosition::EndOfSection);
+        assert_eq!(props.restart, RestartNumbering::PerSection);
+        assert_eq!(props.numbering, NumberingScheme::UpperLetter);
+        assert_eq!(props.start_at, 2);
+
+        let notes = imported.endnotes_in_order();
+        assert_eq!(notes.len(), 1);
+        let (note, _) = notes[0];
+        assert_eq!(note.mark, "B");
+        let position = note.reference_position.unwrap();
+        assert_eq!(position.node_id, imported.document.children()[0]);
+        assert_eq!(position.offset, 5);
+        let lines: Vec<String> = note
+            .content()
+            .iter()
+            .map(|&id| {
+                let para = imported.get_paragraph(id).unwrap();
+                para.children().iter().map(|&run_id| imported.get_run(run_id).unwrap().text.clone()).collect()
+            })
+            .collect();
+        assert_eq!(lines, vec!["First line", "Second line"]);
+    }
+
+    #[test]
+    fn test_table_header_and_cant_split_round_trip() {
+        use doc_model::{Node, RowProperties, Table, TableCell, TableGrid, TableRow};
+
+        let mut tree = DocumentTree::new();
+        let table_id = tree.insert_table(Table::with_grid(TableGrid::with_fixed_columns(&[100.0])), None).unwrap();
+        for props in [RowProperties::new().as_header(), RowProperties::new().prevent_split(), RowProperties::new()] {
+            let row_id = tree.insert_table_row(TableRow::with_properties(props), table_id, None).unwrap();
+            let cell_id = tree.insert_table_cell(TableCell::new(), row_id, None).unwrap();
+            let para_id = tree.insert_paragraph_into_cell(Paragraph::new(), cell_id, None).unwrap();
+            tree.insert_run(Run::new("Cell"), para_id, None).unwrap();
+        }
+
+        let bytes = export_docx_bytes(&tree).unwrap();
+        let imported = import_docx_bytes(&bytes).unwrap();
+
+        let table = imported
+            .document
+            .children()
+            .iter()
+            .find_map(|&id| imported.get_table(id))
+            .unwrap();
+        let rows: Vec<&RowProperties> = table
+            .children()
+            .iter()
+            .map(|&id| &imported.get_table_row(id).unwrap().properties)
+            .collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].is_header && rows[0].allows_split());
+        assert!(!rows[1].is_header && !rows[1].allows_split());
+        assert!(!rows[2].is_header && rows[2].allows_split());
+    }
+
+    #[test]
+    fn test_table_alt_text_round_trip() {
+        use doc_model::{Node, Table, TableGrid, TableProperties};
+
+        let mut tree = DocumentTree::new();
+        let mut props = TableProperties::new();
+        props.alt_text = Some("Sales by \"region\" & quarter".to_string());
+        tree.insert_table(Table::with_grid_and_properties(TableGrid::with_fixed_columns(&[100.0]), props), None)
+            .unwrap();
+
+        let bytes = export_docx_bytes(&tree).unwrap();
+        let imported = import_docx_bytes(&bytes).unwrap();
+
+        let table = imported
+            .document
+            .children()
+            .iter()
+            .find_map(|&id| imported.get_table(id))
+            .unwrap();
+        assert_eq!(table.properties.alt_text.as_deref(), Some("Sales by \"region\" & quarter"));
+        assert!(!table.properties.decorative);
+    }
+
+    #[test]
+    fn test_decorative_table_round_trip() {
+        use doc_model::{Node, Table, TableGrid, TableProperties};
+
+        let mut tree = DocumentTree::new();
+        let mut props = TableProperties::new();
+        props.decorative = true;
+        tree.insert_table(Table::with_grid_and_properties(TableGrid::with_fixed_columns(&[100.0]), props), None)
+            .unwrap();
+
+        let bytes = export_docx_bytes(&tree).unwrap();
+        let imported = import_docx_bytes(&bytes).unwrap();
+
+        let table = imported
+            .document
+            .children()
+            .iter()
+            .find_map(|&id| imported.get_table(id))
+            .unwrap();
+        assert!(table.properties.decorative);
+        assert!(table.properties.alt_text.is_none());
+    }
+
+    #[test]
+    fn test_table_style_and_look_round_trip() {
+        use doc_model::{
+            CharacterProperties, Node, Style, StyleId, Table, TableBorders, TableCellPosition, TableGrid,
+            TableLook,
+            TableProperties, TableRegionFormat, TableStyleProperties, TableStyleRegion,
+        };
+
+        let mut tree = DocumentTree::new();
+        let style = Style::table("Banded", "Banded")
+            .with_based_on("TableNormal")
+            .with_table_props(
+                TableStyleProperties::new()
+                    .with_borders(TableBorders::default_borders())
+                    .with_region(
+                        TableStyleRegion::FirstRow,
+                        TableRegionFormat::new().with_shading("#112233").with_character_props(
+                            CharacterProperties { bold: Some(true), ..Default::default() },
+                        ),
+                    )
+                    .with_region(TableStyleRegion::Band2Horizontal, TableRegionFormat::new().with_shading("#EEEEEE")),
+            );
+        tree.style_registry_mut().register(style);
+
+        let mut props = TableProperties::new();
+        props.style_id = Some(StyleId::new("Banded"));
+        props.look = TableLook { last_row: true, banded_columns: true, ..TableLook::default() };
+        tree.insert_table(Table::with_grid_and_properties(TableGrid::with_fixed_columns(&[100.0]), props), None)
+            .unwrap();
+
+        let bytes = export_docx_bytes(&tree).unwrap();
+        let imported = import_docx_bytes(&bytes).unwrap();
+
+        let table = imported
+            .document
+            .children()
+            .iter()
+            .find_map(|&id| imported.get_table(id))
+            .unwrap();
+        assert_eq!(table.properties.style_id, Some(StyleId::new("Banded")));
+        assert_eq!(table.properties.look, TableLook { last_row: true, banded_columns: true, ..TableLook::default() });
+
+        let registry = imported.style_registry();
+        let look = TableLook::default();
+        let header = registry
+            .resolve_table_cell_format(&StyleId::new("Banded"), &TableCellPosition::new(0, 0, 4, 2), &look)
+            .unwrap();
+        assert_eq!(header.shading.as_deref(), Some("#112233"));
+        assert_eq!(header.character_props.bold, Some(true));
+        let band = registry
+            .resolve_table_cell_format(&StyleId::new("Banded"), &TableCellPosition::new(2, 1, 4, 2), &look)
+            .unwrap();
+        assert_eq!(band.shading.as_deref(), Some("#EEEEEE"));
+        assert!(registry.resolve_table_style(&StyleId::new("Banded")).unwrap().borders.is_some());
+    }
+
+    #[test]
+    fn test_cell_diagonals_direction_and_alignment_round_trip() {
+        use doc_model::{
+            CellBorders, CellTextDirection, CellVerticalAlign, Table, TableBorder, TableBorderStyle, TableCell,
+            TableGrid, TableRow,
+        };
+
+        let mut tree = DocumentTree::new();
+        let table_id = tree.insert_table(Table::with_grid(TableGrid::with_fixed_columns(&[100.0])), None).unwrap();
+        let row_id = tree.insert_table_row(TableRow::new(), table_id, None).unwrap();
+        let mut cell = TableCell::new();
+        cell.properties.borders = Some(
+            CellBorders::default()
+                .with_diagonal_down(TableBorder { style: TableBorderStyle::Double, width: 1.0, color: "#FF0000".into() })
+                .with_diagonal_up(TableBorder::default()),
+        );
+        cell.properties.text_direction = Some(CellTextDirection::BtLr);
+        cell.properties.vertical_align = Some(CellVerticalAlign::Bottom);
+        let cell_id = tree.insert_table_cell(cell, row_id, None).unwrap();
+        tree.insert_paragraph_into_cell(Paragraph::new(), cell_id, None).unwrap();
+
+        let bytes = export_docx_bytes(&tree).unwrap();
+        let imported = import_docx_bytes(&bytes).unwrap();
+
+        let cell = imported.nodes.table_cells.values().next().unwrap();
+        assert_eq!(cell.properties.text_direction, Some(CellTextDirection::BtLr));
+        assert_eq!(cell.properties.vertical_align, Some(CellVerticalAlign::Bottom));
+        let borders = cell.properties.borders.as_ref().unwrap();
+        let down = borders.diagonal_down.as_ref().unwrap();
+        assert_eq!(down.style, TableBorderStyle::Double);
+        assert_eq!(down.color, "#FF0000");
+        assert!((down.width - 1.0).abs() < 0.01);
+        assert_eq!(borders.diagonal_up.as_ref().unwrap().style, TableBorderStyle::Single);
+        assert!(borders.top.is_none());
+    }
+
+    #[test]
+    fn test_ink_round_trip() {
+        use doc_model::{InkNode, InkPoint, InkStroke, Node, ShapeColor};
+
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new("Signed"), para_id, None).unwrap();
+        let pen = InkStroke::new(
+            vec![InkPoint::new(10.0, 5.0, 0.25), InkPoint::new(40.0, 12.0, 0.75)],
+            ShapeColor::BLUE,
+            2.0,
+        );
+        let highlighter = InkStroke::highlighter(vec![InkPoint::at(0.0, 8.0), InkPoint::at(60.0, 8.0)], ShapeColor::YELLOW, 12.0);
+        tree.insert_ink(InkNode::new(vec![pen, highlighter]), para_id).unwrap();
+
+        let bytes = export_docx_bytes(&tree).unwrap();
+        let imported = import_docx_bytes(&bytes).unwrap();
+
+        let para_id = imported.document.children()[0];
+        let inks = imported.inks_in_paragraph(para_id);
+        assert_eq!(inks.len(), 1);
+        let strokes = &inks[0].strokes;
+        assert_eq!(strokes.len(), 2);
+        assert_eq!(strokes[0].color, ShapeColor::BLUE);
+        assert!((strokes[0].width - 2.0).abs() < 0.01);
+        let last = strokes[0].points[1];
+        assert!((last.x - 40.0).abs() < 0.05 && (last.y - 12.0).abs() < 0.05);
+        assert!((last.pressure - 0.75).abs() < 0.001);
+        assert_eq!(strokes[1].color.a, 128);
+        assert!((strokes[1].width - 12.0).abs() < 0.01);
+        // Ink doesn't add text
+        assert_eq!(imported.get_paragraph(para_id).unwrap().children().len(), 1);
+    }
+
+    #[test]
+    fn test_hyperlink_base_round_trip() {
+        let mut tree = DocumentTree::new();
+        tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.document.metadata.hyperlink_base = Some("https://example.com/docs?a&b".to_string());
+
+        let imported = import_docx_bytes(&export_docx_bytes(&tree).unwrap()).unwrap();
+        assert_eq!(imported.document.metadata.hyperlink_base.as_deref(), Some("https://example.com/docs?a&b"));
+    }
+
+    #[test]
+    fn test_proofing_language_round_trip() {
+        use doc_model::CharacterProperties;
+
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        let props = CharacterProperties {
+            language: Some("fr-FR".to_string()),
+            no_proof: Some(true),
+            ..Default::default()
+        };
+        tree.insert_run(Run::with_direct_formatting("Bonjour", props), para_id, None).unwrap();
+
+        let imported = import_docx_bytes(&export_docx_bytes(&tree).unwrap()).unwrap();
+        let run = imported.nodes.runs.values().next().unwrap();
+        assert_eq!(run.direct_formatting.language.as_deref(), Some("fr-FR"));
+        assert_eq!(run.direct_formatting.no_proof, Some(true));
+    }
+
+    #[test]
+    fn test_hidden_text_and_document_variables_round_trip() {
+        use doc_model::CharacterProperties;
+
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        let props = CharacterProperties { hidden: Some(true), ..Default::default() };
+        tree.insert_run(Run::with_direct_formatting("Hidden", props), para_id, None).unwrap();
+        tree.document.metadata.variables.insert("Client".to_string(), "A & \"B\"".to_string());
+
+        let imported = import_docx_bytes(&export_docx_bytes(&tree).unwrap()).unwrap();
+        let run = imported.nodes.runs.values().next().unwrap();
+        assert_eq!(run.direct_formatting.hidden, Some(true));
+        assert_eq!(imported.document.metadata.variables, tree.document.metadata.variables);
+    }
+
+    #[test]
+    fn test_embedded_object_round_trip() {
+        use crate::docx::{embedded_object_warnings, WarningSeverity};
+        use doc_model::{EmbeddedObject, EmbeddedPart};
+
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        tree.insert_run(Run::new("Figures: "), para_id, None).unwrap();
+        let sheet = EmbeddedPart::new(
+            "Microsoft_Excel_Worksheet.xlsx",
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            b"PK\x03\x04sheet".to_vec(),
+        );
+        let object = EmbeddedObject::new(Some("Excel.Sheet.12".to_string()), Some(sheet.clone()), 300.0, 120.0)
+            .with_preview(EmbeddedPart::new("image1.emf", "image/x-emf", vec![1, 0, 0, 0, 0x20]));
+        tree.insert_embedded_object(object, para_id, None).unwrap();
+
+        let imported = import_docx_bytes(&export_docx_bytes(&tree).unwrap()).unwrap();
+        let object = imported.embedded_objects().next().unwrap();
+        assert_eq!(object.prog_id.as_deref(), Some("Excel.Sheet.12"));
+        assert_eq!((object.width, object.height), (300.0, 120.0));
+        assert_eq!(object.embedding.as_ref().unwrap().data, sheet.data);
+        assert_eq!(object.preview.as_ref().unwrap().data, vec![1, 0, 0, 0, 0x20]);
+        let para = imported.get_paragraph(imported.document.children()[0]).unwrap();
+        assert_eq!(para.children().len(), 2);
+
+        let warnings = embedded_object_warnings(&imported);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].severity, WarningSeverity::Info);
+    }
+
+    #[test]
+    fn test_document_settings_round_trip() {
+        use doc_model::{CompatSetting, DocumentProtection, PasswordProtection};
+
+        let mut tree = DocumentTree::new();
+        tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        let settings = &mut tree.document.settings;
+        settings.default_tab_stop = 42.5;
+        settings.hyphenation.auto_hyphenate = true;
+        settings.hyphenation.hyphenate_caps = false;
+        settings.hyphenation.zone = Some(18.0);
+        settings.hyphenation.consecutive_limit = Some(2);
+        settings.track_revisions = true;
+        settings.even_and_odd_headers = true;
+        settings.compatibility.mode = Some(14);
+        settings.compatibility.options = vec!["useFELayout".to_string()];
+        settings.compatibility.settings.push(CompatSetting {
+            name: "overrideTableStyleFontSizeAndJustification".to_string(),
+            uri: "http://schemas.microsoft.com/office/word".to_string(),
+            value: "1".to_string(),
+        });
+        tree.document.protection =
+            DocumentProtection::read_only().with_password(PasswordProtection::new("aGFzaA==", "c2FsdA==", 100000));
+
+        let imported = import_docx_bytes(&export_docx_bytes(&tree).unwrap()).unwrap();
+        assert_eq!(imported.document.settings, tree.document.settings);
+        assert_eq!(imported.document.protection, tree.document.protection);
+        assert!(imported.sections.iter().all(|section| section.different_odd_even));
+    }
+
+    #[test]
+    fn test_building_blocks_round_trip() {
+        use doc_model::{BuildingBlock, BuildingBlockGallery};
+
+        let mut tree = DocumentTree::new();
+        tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+
+        let mut content = DocumentTree::new();
+        for text in ["Annual Report", "Prepared by <Finance>"] {
+            let para_id = content.insert_paragraph(Paragraph::new(), content.root_id(), None).unwrap();
+            content.insert_run(Run::new(text), para_id, None).unwrap();
+        }
+        let block = BuildingBlock::new("Formal", BuildingBlockGallery::CoverPages, content)
+            .with_category("Reports")
+            .with_description("Title & byline");
+        let id = block.id.clone();
+        tree.building_blocks.add(block);
+        tree.building_blocks
+            .add(BuildingBlock::new("sig", BuildingBlockGallery::AutoText, DocumentTree::new()));
+
+        let imported = import_docx_bytes(&export_docx_bytes(&tree).unwrap()).unwrap();
+        assert_eq!(imported.document.children().len(), 1);
+        assert_eq!(imported.building_blocks.len(), 2);
+
+        let block = imported.building_blocks.get(&id).unwrap();
+        assert_eq!(block.name, "Formal");
+        assert_eq!(block.gallery, BuildingBlockGallery::CoverPages);
+        assert_eq!(block.category, "Reports");
+        assert_eq!(block.description, "Title & byline");
+        let texts: Vec<_> = block.content.nodes.runs.values().map(|run| run.text.as_str()).collect();
+        assert_eq!(block.content.document.children().len(), 2);
+        assert!(texts.contains(&"Prepared by <Finance>"));
+        assert!(imported.building_blocks.find(BuildingBlockGallery::AutoText, "SIG").is_some());
+    }
+
+    #[test]
+    fn test_paragraph_direction_round_trip() {
+        use doc_model::TextDirection;
+
+        let mut tree = DocumentTree::new();
+        let mut ids = Vec::new();
+        for direction in [Some(TextDirection::Rtl), Some(TextDirection::Ltr), None] {
+            let mut para = Paragraph::new();
+            para.direct_formatting.direction = direction;
+            let para_id = tree.insert_paragraph(para, tree.root_id(), None).unwrap();
+            tree.insert_run(Run::new("שלום"), para_id, None).unwrap();
+            ids.push(para_id);
+        }
+
+        let imported = import_docx_bytes(&export_docx_bytes(&tree).unwrap()).unwrap();
+        let directions: Vec<_> = imported
+            .document
+            .children()
+            .iter()
+            .map(|&id| imported.get_paragraph(id).unwrap().direct_formatting.direction)
+            .collect();
+        assert_eq!(directions, [Some(TextDirection::Rtl), Some(TextDirection::Ltr), None]);
+        assert!(imported.preserved.is_empty());
+    }
+
+    #[test]
+    fn test_ruby_and_document_grid_round_trip() {
+        use doc_model::{DocumentGrid, RubyAlignment, RubyAnnotation, Section};
+
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        let ruby = RubyAnnotation::new("かんじ")
+            .with_alignment(RubyAlignment::DistributeSpace)
+            .with_font_size(5.0)
+            .with_raise(10.0)
+            .with_language("ja-JP");
+        tree.insert_run(Run::new("漢字").with_ruby(ruby.clone()), para_id, None).unwrap();
+        tree.insert_run(Run::new("です"), para_id, None).unwrap();
+        let mut section = Section::new();
+        section.page_setup.doc_grid = DocumentGrid::lines_and_chars(18.0, 0.5);
+        section.add_child(para_id);
+        tree.add_section(section);
+
+        let imported = import_docx_bytes(&export_docx_bytes(&tree).unwrap()).unwrap();
+        let para_id = imported.document.children()[0];
+        let runs: Vec<_> = imported
+            .get_paragraph(para_id)
+            .unwrap()
+            .children()
+            .iter()
+            .map(|&id| imported.get_run(id).unwrap())
+            .collect();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].text, "漢字");
+        assert_eq!(runs[0].ruby.as_ref(), Some(&ruby));
+        assert_eq!(runs[1].text, "です");
+        assert!(runs[1].ruby.is_none());
+        assert_eq!(imported.sections[0].page_setup.doc_grid, DocumentGrid::lines_and_chars(18.0, 0.5));
+    }
+
+    #[test]
+    fn test_export_pictures_with_compression() {
+        use crate::image_codec::{encode_png, RgbaImage};
+        use doc_model::ImageNode;
+        use std::io::{Cursor, Read};
+
+        let images = ImageStore::new();
+        let data = (0..400 * 400).flat_map(|i| [(i % 256) as u8, (i / 400) as u8, 64, 255]).collect();
+        let png = encode_png(&RgbaImage { width: 400, height: 400, data }).unwrap();
+        let resource_id = images.store_image(png, None).unwrap();
+
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        let node = ImageNode::with_size(resource_id.clone(), 400, 400, 72.0, 72.0);
+        tree.insert_image(node, para_id, None).unwrap();
+
+        let (full, report) = export_docx_bytes_with_images(&tree, &images, None).unwrap();
+        assert_eq!(report.compressed_count(), 0);
+        let options = ImageOptimizationOptions::email();
+        let (small, report) = export_docx_bytes_with_images(&tree, &images, Some(&options)).unwrap();
+        assert_eq!(report.compressed_count(), 1);
+        assert!(report.bytes_saved() > 0);
+        assert!(small.len() < full.len());
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(small.clone())).unwrap();
+        let mut media = Vec::new();
+        archive.by_name("word/media/image1.png").unwrap().read_to_end(&mut media).unwrap();
+        assert_eq!(crate::image_codec::decode_png(&media).unwrap().width, 96);
+        let mut document = String::new();
+        archive.by_name("word/document.xml").unwrap().read_to_string(&mut document).unwrap();
+        assert!(document.contains("<a:blip r:embed=\""));
+        assert!(import_docx_bytes(&small).is_ok());
+    }
+
+    #[test]
+    fn test_svg_picture_round_trip_with_png_fallback() {
+        use doc_model::ImageNode;
+        use std::io::{Cursor, Read};
+
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" width="10pt" height="10pt" viewBox="0 0 10 10">
+            <rect width="10" height="10" fill="#00ff00"/>
+        </svg>"##;
+        let images = ImageStore::new();
+        let resource_id = images.store_image(svg.as_bytes().to_vec(), None).unwrap();
+
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        let node = ImageNode::with_size(resource_id, 13, 13, 10.0, 10.0);
+        tree.insert_image(node, para_id, None).unwrap();
+
+        let (bytes, _) = export_docx_bytes_with_images(&tree, &images, None).unwrap();
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes.clone())).unwrap();
+        let mut fallback = Vec::new();
+        archive.by_name("word/media/image1.png").unwrap().read_to_end(&mut fallback).unwrap();
+        assert!(crate::image_codec::decode_png(&fallback).is_ok());
+        let mut original = String::new();
+        archive.by_name("word/media/image1.svg").unwrap().read_to_string(&mut original).unwrap();
+        assert_eq!(original, svg);
+
+        let mut document = String::new();
+        archive.by_name("word/document.xml").unwrap().read_to_string(&mut document).unwrap();
+        assert!(document.contains("<asvg:svgBlip"));
+        let mut content_types = String::new();
+        archive.by_name("[Content_Types].xml").unwrap().read_to_string(&mut content_types).unwrap();
+        assert!(content_types.contains("image/svg+xml"));
+        assert!(import_docx_bytes(&bytes).is_ok());
+    }
 }
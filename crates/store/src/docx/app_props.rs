@@ -0,0 +1,63 @@
+//! Extended (application) properties
+//!
+//! `docProps/app.xml` holds document-wide settings Word keeps outside the
+//! main part. Only the hyperlink base is read and written.
+
+use crate::docx::error::{DocxError, DocxResult};
+use crate::docx::reader::XmlParser;
+use doc_model::DocumentMetadata;
+use quick_xml::events::Event;
+
+/// Path of the extended properties part
+pub const APP_PROPS_PART: &str = "docProps/app.xml";
+
+/// Extended properties namespace
+const EXTENDED_PROPERTIES_NAMESPACE: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/extended-properties";
+
+/// Write `docProps/app.xml` for a document
+///
+/// Returns None when there is nothing to store.
+pub fn write_app_props(metadata: &DocumentMetadata) -> Option<String> {
+    let base = metadata.hyperlink_base.as_deref().filter(|base| !base.is_empty())?;
+    Some(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <Properties xmlns=\"{}\"><Application>go-word</Application><HyperlinkBase>{}</HyperlinkBase></Properties>",
+        EXTENDED_PROPERTIES_NAMESPACE,
+        escape_xml(base)
+    ))
+}
+
+/// Read the hyperlink base from `docProps/app.xml`
+pub fn parse_hyperlink_base(content: &str) -> DocxResult<Option<String>> {
+    let mut reader = XmlParser::from_string(content);
+    let mut buf = Vec::new();
+    let mut in_base = false;
+    let mut base = None;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => {
+                in_base = XmlParser::matches_element(e.name().as_ref(), "HyperlinkBase");
+            }
+            Ok(Event::Text(ref e)) if in_base => {
+                let text = e.unescape().map_err(|e| DocxError::XmlParse(e.to_string()))?;
+                if !text.trim().is_empty() {
+                    base = Some(text.trim().to_string());
+                }
+            }
+            Ok(Event::End(_)) => in_base = false,
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(DocxError::XmlParse(e.to_string())),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(base)
+}
+
+/// Escape a string for XML text content
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -2,32 +2,84 @@
 //!
 //! Parses the main document content including paragraphs, runs, and text.
 
+use crate::docx::embedded_objects_io::{ObjectParser, ParsedObject};
 use crate::docx::error::{DocxError, DocxResult};
+use crate::docx::footnotes_io::ParsedNote;
+use crate::docx::ink_io::InkParser;
+use crate::docx::parser::ImageData;
+use crate::docx::preserved_io::{
+    element_attributes, is_dropped, is_known_paragraph_property, is_transparent, read_root_namespaces,
+};
 use crate::docx::reader::XmlParser;
 use crate::docx::relationships::Relationships;
+use crate::docx::ruby_io::read_ruby_property;
+use crate::docx::sections_io::ParsedSection;
+use crate::docx::tables::TableParser;
+use crate::docx::tabs_io::parse_tab_stop;
+use crate::docx::text_effects_io::{is_w14_element, TextEffectsReader};
+use crate::docx::theme_io::{parse_theme_color, parse_theme_font};
 use doc_model::{
-    Alignment, CharacterProperties, DocumentTree, Hyperlink, HyperlinkTarget, LineSpacing,
-    Node, Paragraph, ParagraphProperties, Run, StyleId,
+    Alignment, CharacterProperties, DocumentTree, EmbeddedObject, EmbeddedPart, Hyperlink, HyperlinkTarget,
+    InkNode, LineSpacing, MarkupPlacement, Node, Paragraph, ParagraphProperties, Position, PreservedElement,
+    RubyAnnotation, Run, StyleId, TextDirection,
 };
-use quick_xml::events::Event;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
 use std::collections::HashMap;
 
+/// Size of an object with no size of its own, in points
+const DEFAULT_OBJECT_WIDTH: f32 = 72.0;
+const DEFAULT_OBJECT_HEIGHT: f32 = 72.0;
+
 /// Parser for document.xml
 pub struct DocumentParser<'a> {
     /// Document relationships (for hyperlinks, images, etc.)
     doc_rels: &'a Relationships,
     /// External hyperlink targets by relationship ID
     hyperlinks: &'a HashMap<String, String>,
+    /// Endnotes from the endnotes part, for the references to them
+    endnotes: &'a [ParsedNote],
+    /// Raw InkML parts by relationship ID
+    inks: Option<&'a HashMap<String, String>>,
+    /// Image parts (object previews) by relationship ID
+    images: Option<&'a HashMap<String, ImageData>>,
+    /// Embedded object parts by relationship ID
+    embeddings: Option<&'a HashMap<String, ImageData>>,
 }
 
 impl<'a> DocumentParser<'a> {
     /// Create a new document parser
     pub fn new(doc_rels: &'a Relationships, hyperlinks: &'a HashMap<String, String>) -> Self {
-        Self { doc_rels, hyperlinks }
+        Self { doc_rels, hyperlinks, endnotes: &[], inks: None, images: None, embeddings: None }
+    }
+
+    /// Set the endnotes the document's endnote references point to
+    pub fn with_endnotes(mut self, endnotes: &'a [ParsedNote]) -> Self {
+        self.endnotes = endnotes;
+        self
+    }
+
+    /// Set the InkML parts the document's ink anchors point to
+    pub fn with_inks(mut self, inks: &'a HashMap<String, String>) -> Self {
+        self.inks = Some(inks);
+        self
+    }
+
+    /// Set the image and embedding parts the document's objects point to
+    pub fn with_object_parts(
+        mut self,
+        images: &'a HashMap<String, ImageData>,
+        embeddings: &'a HashMap<String, ImageData>,
+    ) -> Self {
+        self.images = Some(images);
+        self.embeddings = Some(embeddings);
+        self
     }
 
     /// Parse document.xml and populate the DocumentTree
-    pub fn parse(&self, content: &str, tree: &mut DocumentTree) -> DocxResult<()> {
+    ///
+    /// Returns the document's sections in order, each ending at a body block.
+    pub fn parse(&self, content: &str, tree: &mut DocumentTree) -> DocxResult<Vec<ParsedSection>> {
         let mut reader = XmlParser::from_string(content);
         let mut buf = Vec::new();
 
@@ -39,31 +91,112 @@ impl<'a> DocumentParser<'a> {
         let mut in_text = false;
         let mut in_para_props = false;
         let mut in_run_props = false;
+        let mut text_effects = TextEffectsReader::new();
+        let mut sections = Vec::new();
+        let mut current_section: Option<ParsedSection> = None;
+        // A section break in a paragraph ends its section after the paragraph
+        let mut para_section: Option<ParsedSection> = None;
+        // Unrecognized markup is preserved: body-level elements go before
+        // the next block, and only direct children of the body, paragraph
+        // and paragraph properties are captured
+        let mut pending_body_markup: Vec<String> = Vec::new();
+        let mut container_depth: usize = 0;
+        let mut para_base_depth = 0;
+        let mut props_depth: usize = 0;
 
         loop {
+            let position = reader.buffer_position() as usize;
             match reader.read_event_into(&mut buf) {
                 Ok(Event::Start(ref e)) => {
                     let name = e.name();
                     let name_ref = name.as_ref();
 
-                    if XmlParser::matches_element(name_ref, "body") {
+                    let at_para_level = current_para.is_some()
+                        && current_run.is_none()
+                        && current_hyperlink.is_none()
+                        && !in_para_props;
+
+                    if XmlParser::matches_element(name_ref, "document") {
+                        read_root_namespaces(e, &mut tree.preserved);
+                    } else if XmlParser::matches_element(name_ref, "body") {
                         in_body = true;
+                    } else if in_body && XmlParser::matches_element(name_ref, "sectPr") {
+                        current_section = Some(ParsedSection::default());
+                    } else if let Some(section) = current_section.as_mut() {
+                        section.read_property(e);
+                    } else if in_body && current_para.is_none() && XmlParser::matches_element(name_ref, "tbl") {
+                        // Tables are parsed from their own markup
+                        let end = e.to_end().into_owned();
+                        reader.read_to_end_into(end.name(), &mut Vec::new())?;
+                        let markup = &content[position..reader.buffer_position() as usize];
+                        let table_parser = TableParser::new();
+                        let parsed = table_parser.parse_table(markup)?;
+                        table_parser.commit_table(parsed, tree)?;
+                        attach_body_markup(&mut pending_body_markup, MarkupPlacement::Before, tree);
                     } else if in_body && XmlParser::matches_element(name_ref, "p") {
-                        current_para = Some(ParsedParagraph::new());
+                        let mut para = ParsedParagraph::new();
+                        para.attributes = element_attributes(e);
+                        current_para = Some(para);
+                        para_base_depth = container_depth;
+                    } else if ((in_body && current_para.is_none()) || at_para_level) && is_transparent(name_ref) {
+                        container_depth += 1;
+                    } else if in_body && current_para.is_none() && container_depth == 0 {
+                        // Body-level element we don't model
+                        let markup = read_element(&mut reader, content, position, e)?;
+                        if !is_dropped(name_ref) {
+                            pending_body_markup.push(markup.to_string());
+                        }
+                    } else if at_para_level && container_depth == para_base_depth && !is_paragraph_child(name_ref) {
+                        // Paragraph content we don't model, kept at its text offset
+                        let markup = read_element(&mut reader, content, position, e)?;
+                        if let Some(para) = current_para.as_mut().filter(|_| !is_dropped(name_ref)) {
+                            let offset = para.text_len();
+                            para.preserved.push(PreservedElement::new(MarkupPlacement::Content(offset), markup));
+                        }
                     } else if current_para.is_some() && XmlParser::matches_element(name_ref, "pPr") {
                         in_para_props = true;
+                        props_depth = 0;
                     } else if current_para.is_some() && XmlParser::matches_element(name_ref, "r") {
                         current_run = Some(ParsedRun::new());
                     } else if current_run.is_some() && XmlParser::matches_element(name_ref, "rPr") {
                         in_run_props = true;
+                        text_effects = TextEffectsReader::new();
+                    } else if in_run_props && is_w14_element(name_ref) {
+                        text_effects.start(e);
                     } else if current_run.is_some() && XmlParser::matches_element(name_ref, "t") {
                         in_text = true;
+                    } else if current_run.is_some() && XmlParser::matches_element(name_ref, "object") {
+                        // Embedded (OLE) objects are parsed from their own markup
+                        let end = e.to_end().into_owned();
+                        reader.read_to_end_into(end.name(), &mut Vec::new())?;
+                        let markup = &content[position..reader.buffer_position() as usize];
+                        if let Some(ref mut run) = current_run {
+                            run.objects.push(ObjectParser::new().parse(markup)?);
+                        }
+                    } else if current_run.is_some() && XmlParser::matches_element(name_ref, "ruby") {
+                        // Ruby is parsed from its own markup
+                        let markup = read_element(&mut reader, content, position, e)?;
+                        if let Some(ref mut run) = current_run {
+                            self.parse_ruby(markup, run)?;
+                        }
+                    } else if current_run.is_some() && XmlParser::matches_element(name_ref, "contentPart") {
+                        // Ink anchored to the paragraph
+                        if let (Some(rel_id), Some(para)) = (XmlParser::get_r_attribute(e, "id"), current_para.as_mut()) {
+                            para.ink_rel_ids.push(rel_id);
+                        }
                     } else if current_para.is_some() && XmlParser::matches_element(name_ref, "hyperlink") {
                         // Start of hyperlink
                         let rel_id = XmlParser::get_r_attribute(e, "id");
                         let anchor = XmlParser::get_w_attribute(e, "anchor");
                         current_hyperlink = Some(ParsedHyperlink::new(rel_id, anchor));
+                    } else if in_para_props && props_depth == 0 && !is_known_paragraph_property(name_ref) {
+                        // Paragraph property we don't model
+                        let markup = read_element(&mut reader, content, position, e)?;
+                        if let Some(para) = current_para.as_mut() {
+                            para.preserved.push(PreservedElement::new(MarkupPlacement::Properties, markup));
+                        }
                     } else if in_para_props {
+                        props_depth += 1;
                         self.parse_para_property(e, current_para.as_mut().unwrap())?;
                     } else if in_run_props {
                         self.parse_run_property(e, current_run.as_mut().unwrap())?;
@@ -73,8 +206,42 @@ impl<'a> DocumentParser<'a> {
                     let name = e.name();
                     let name_ref = name.as_ref();
 
-                    if in_para_props && current_para.is_some() {
+                    if let Some(section) = current_section.as_mut() {
+                        section.read_property(e);
+                    } else if in_body && XmlParser::matches_element(name_ref, "sectPr") {
+                        let section = ParsedSection::default();
+                        if current_para.is_some() {
+                            para_section = Some(section);
+                        } else {
+                            sections.push(ParsedSection { end_block: tree.document.children().len(), ..section });
+                        }
+                    } else if in_body && current_para.is_none() && container_depth == 0 {
+                        if !is_dropped(name_ref) && !XmlParser::matches_element(name_ref, "p") {
+                            pending_body_markup.push(read_element(&mut reader, content, position, e)?.to_string());
+                        }
+                    } else if current_para.is_some()
+                        && current_run.is_none()
+                        && current_hyperlink.is_none()
+                        && !in_para_props
+                        && container_depth == para_base_depth
+                        && !is_paragraph_child(name_ref)
+                        && !is_dropped(name_ref)
+                    {
+                        let markup = read_element(&mut reader, content, position, e)?;
+                        if let Some(para) = current_para.as_mut() {
+                            let offset = para.text_len();
+                            para.preserved.push(PreservedElement::new(MarkupPlacement::Content(offset), markup));
+                        }
+                    } else if in_para_props && props_depth == 0 && !is_known_paragraph_property(name_ref) {
+                        let markup = read_element(&mut reader, content, position, e)?;
+                        if let Some(para) = current_para.as_mut() {
+                            para.preserved.push(PreservedElement::new(MarkupPlacement::Properties, markup));
+                        }
+                    } else if in_para_props && current_para.is_some() {
                         self.parse_para_property(e, current_para.as_mut().unwrap())?;
+                    } else if in_run_props && is_w14_element(name_ref) {
+                        text_effects.start(e);
+                        text_effects.end(name_ref);
                     } else if in_run_props && current_run.is_some() {
                         self.parse_run_property(e, current_run.as_mut().unwrap())?;
                     } else if current_run.is_some() && XmlParser::matches_element(name_ref, "br") {
@@ -87,6 +254,13 @@ impl<'a> DocumentParser<'a> {
                         if let Some(ref mut run) = current_run {
                             run.text.push('\t');
                         }
+                    } else if current_run.is_some() && XmlParser::matches_element(name_ref, "endnoteReference") {
+                        // Endnote reference, after the text so far
+                        let docx_id = XmlParser::get_w_attribute(e, "id").and_then(|id| id.parse().ok());
+                        if let (Some(docx_id), Some(para)) = (docx_id, current_para.as_mut()) {
+                            let offset = para.text_len() + current_run.as_ref().map_or(0, |run| run.text.chars().count());
+                            para.endnote_refs.push((offset, docx_id));
+                        }
                     }
                 }
                 Ok(Event::End(ref e)) => {
@@ -95,13 +269,31 @@ impl<'a> DocumentParser<'a> {
 
                     if XmlParser::matches_element(name_ref, "body") {
                         in_body = false;
+                    } else if XmlParser::matches_element(name_ref, "sectPr") {
+                        if let Some(section) = current_section.take() {
+                            if current_para.is_some() {
+                                para_section = Some(section);
+                            } else {
+                                sections.push(ParsedSection { end_block: tree.document.children().len(), ..section });
+                            }
+                        }
+                    } else if current_section.is_some() {
+                        // Inside section properties
                     } else if XmlParser::matches_element(name_ref, "p") {
                         // End of paragraph - commit it
                         if let Some(parsed_para) = current_para.take() {
                             self.commit_paragraph(parsed_para, tree)?;
+                            attach_body_markup(&mut pending_body_markup, MarkupPlacement::Before, tree);
+                        }
+                        if let Some(section) = para_section.take() {
+                            sections.push(ParsedSection { end_block: tree.document.children().len(), ..section });
                         }
                     } else if XmlParser::matches_element(name_ref, "pPr") {
                         in_para_props = false;
+                    } else if in_para_props {
+                        props_depth = props_depth.saturating_sub(1);
+                    } else if is_transparent(name_ref) && current_run.is_none() && current_hyperlink.is_none() {
+                        container_depth = container_depth.saturating_sub(1);
                     } else if XmlParser::matches_element(name_ref, "r") {
                         // End of run - add it to paragraph or hyperlink
                         if let Some(parsed_run) = current_run.take() {
@@ -111,7 +303,14 @@ impl<'a> DocumentParser<'a> {
                                 para.runs.push(parsed_run);
                             }
                         }
+                    } else if in_run_props && is_w14_element(name_ref) {
+                        text_effects.end(name_ref);
                     } else if XmlParser::matches_element(name_ref, "rPr") {
+                        if in_run_props {
+                            if let Some(ref mut run) = current_run {
+                                run.props.text_effects = std::mem::take(&mut text_effects).finish();
+                            }
+                        }
                         in_run_props = false;
                     } else if XmlParser::matches_element(name_ref, "t") {
                         in_text = false;
@@ -139,7 +338,13 @@ impl<'a> DocumentParser<'a> {
             buf.clear();
         }
 
-        Ok(())
+        // Markup after the last block goes after it
+        attach_body_markup(&mut pending_body_markup, MarkupPlacement::After, tree);
+        if tree.preserved.is_empty() {
+            tree.preserved.clear();
+        }
+
+        Ok(sections)
     }
 
     /// Parse a paragraph property element
@@ -185,12 +390,23 @@ impl<'a> DocumentParser<'a> {
                     .unwrap_or_else(|| "auto".to_string());
                 para.props.line_spacing = Some(parse_line_spacing(&val, &line_rule));
             }
+        } else if XmlParser::matches_element(name_ref, "tab") {
+            // Tab stop inside w:tabs
+            if let Some(stop) = parse_tab_stop(e) {
+                para.props.tab_stops.push(stop);
+            }
         } else if XmlParser::matches_element(name_ref, "keepNext") {
             para.props.keep_with_next = Some(true);
         } else if XmlParser::matches_element(name_ref, "keepLines") {
             para.props.keep_together = Some(true);
         } else if XmlParser::matches_element(name_ref, "pageBreakBefore") {
             para.props.page_break_before = Some(true);
+        } else if XmlParser::matches_element(name_ref, "suppressLineNumbers") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            para.props.suppress_line_numbers = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
+        } else if XmlParser::matches_element(name_ref, "bidi") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            para.props.direction = Some(parse_bidi(val.as_deref()));
         }
 
         Ok(())
@@ -229,16 +445,62 @@ impl<'a> DocumentParser<'a> {
             {
                 run.props.font_family = Some(font);
             }
+            run.props.theme_font = parse_theme_font(e);
         } else if XmlParser::matches_element(name_ref, "color") {
             if let Some(val) = XmlParser::get_w_attribute(e, "val") {
                 if val != "auto" {
                     run.props.color = Some(format!("#{}", val));
                 }
             }
+            run.props.theme_color = parse_theme_color(e);
         } else if XmlParser::matches_element(name_ref, "highlight") {
             if let Some(val) = XmlParser::get_w_attribute(e, "val") {
                 run.props.highlight = Some(highlight_to_color(&val));
             }
+        } else if XmlParser::matches_element(name_ref, "caps") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            run.props.all_caps = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
+        } else if XmlParser::matches_element(name_ref, "smallCaps") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            run.props.small_caps = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
+        } else if XmlParser::matches_element(name_ref, "spacing") {
+            if let Some(val) = XmlParser::get_w_attribute(e, "val") {
+                run.props.spacing = XmlParser::parse_twips(&val);
+            }
+        } else if XmlParser::matches_element(name_ref, "position") {
+            if let Some(val) = XmlParser::get_w_attribute(e, "val") {
+                run.props.position = XmlParser::parse_half_points(&val);
+            }
+        } else if XmlParser::matches_element(name_ref, "w") {
+            if let Some(val) = XmlParser::get_w_attribute(e, "val") {
+                run.props.scale = val.trim_end_matches('%').parse().ok();
+            }
+        } else if XmlParser::matches_element(name_ref, "kern") {
+            if let Some(val) = XmlParser::get_w_attribute(e, "val") {
+                run.props.kerning = XmlParser::parse_half_points(&val);
+            }
+        } else if XmlParser::matches_element(name_ref, "outline") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            run.props.outline = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
+        } else if XmlParser::matches_element(name_ref, "shadow") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            run.props.shadow = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
+        } else if XmlParser::matches_element(name_ref, "emboss") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            run.props.emboss = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
+        } else if XmlParser::matches_element(name_ref, "imprint") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            run.props.imprint = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
+        } else if XmlParser::matches_element(name_ref, "noProof") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            run.props.no_proof = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
+        } else if XmlParser::matches_element(name_ref, "vanish") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            run.props.hidden = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
+        } else if XmlParser::matches_element(name_ref, "lang") {
+            if let Some(val) = XmlParser::get_w_attribute(e, "val") {
+                run.props.language = Some(val);
+            }
         }
 
         Ok(())
@@ -263,6 +525,13 @@ impl<'a> DocumentParser<'a> {
         tree.nodes.paragraphs.insert(para_id, para);
         tree.document.add_body_child(para_id);
 
+        for (name, value) in parsed.attributes {
+            tree.preserved.add_attribute(para_id, name, value);
+        }
+        for element in parsed.preserved {
+            tree.preserved.add_element(para_id, element);
+        }
+
         // Add runs directly to paragraph
         for parsed_run in parsed.runs {
             self.commit_run(parsed_run, para_id, tree)?;
@@ -273,17 +542,125 @@ impl<'a> DocumentParser<'a> {
             self.commit_hyperlink(parsed_hyperlink, para_id, tree)?;
         }
 
+        for (offset, docx_id) in parsed.endnote_refs {
+            self.commit_endnote(Position::new(para_id, offset), docx_id, tree)?;
+        }
+
+        for rel_id in parsed.ink_rel_ids {
+            let Some(inkml) = self.inks.and_then(|inks| inks.get(&rel_id)) else {
+                continue;
+            };
+            let strokes = InkParser::new().parse(inkml)?;
+            if !strokes.is_empty() {
+                tree.insert_ink(InkNode::new(strokes), para_id)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Commit the endnote a reference points to, one paragraph per line of
+    /// its text
+    fn commit_endnote(&self, position: Position, docx_id: i64, tree: &mut DocumentTree) -> DocxResult<()> {
+        let Some(parsed) = self.endnotes.iter().find(|note| note.id == docx_id) else {
+            return Ok(());
+        };
+
+        let (note_id, _) = tree.insert_endnote(position, None);
+        // The space after the note's mark isn't part of its text
+        for line in parsed.content.trim_start().split('\n') {
+            let para_id = tree.insert_paragraph_into_endnote(Paragraph::new(), note_id, None)?;
+            if !line.is_empty() {
+                tree.insert_run(Run::new(line), para_id, None)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse a `w:ruby`: the ruby goes on the run, and the base text and
+    /// its formatting into the run
+    fn parse_ruby(&self, markup: &str, run: &mut ParsedRun) -> DocxResult<()> {
+        let mut reader = XmlParser::from_string(markup);
+        let mut buf = Vec::new();
+        let mut ruby = RubyAnnotation::default();
+        let (mut in_ruby_props, mut in_ruby_text, mut in_base) = (false, false, false);
+        let (mut in_run_props, mut in_text) = (false, false);
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) => {
+                    let name = e.name();
+                    let name_ref = name.as_ref();
+                    if XmlParser::matches_element(name_ref, "rubyPr") {
+                        in_ruby_props = true;
+                    } else if XmlParser::matches_element(name_ref, "rt") {
+                        in_ruby_text = true;
+                    } else if XmlParser::matches_element(name_ref, "rubyBase") {
+                        in_base = true;
+                    } else if XmlParser::matches_element(name_ref, "rPr") {
+                        in_run_props = true;
+                    } else if XmlParser::matches_element(name_ref, "t") {
+                        in_text = true;
+                    } else if in_base && in_run_props {
+                        self.parse_run_property(e, run)?;
+                    }
+                }
+                Ok(Event::Empty(ref e)) => {
+                    if in_ruby_props {
+                        read_ruby_property(e, &mut ruby);
+                    } else if in_base && in_run_props {
+                        self.parse_run_property(e, run)?;
+                    } else if in_base && XmlParser::matches_element(e.name().as_ref(), "tab") {
+                        run.text.push('\t');
+                    }
+                }
+                Ok(Event::End(ref e)) => {
+                    let name = e.name();
+                    let name_ref = name.as_ref();
+                    if XmlParser::matches_element(name_ref, "rubyPr") {
+                        in_ruby_props = false;
+                    } else if XmlParser::matches_element(name_ref, "rt") {
+                        in_ruby_text = false;
+                    } else if XmlParser::matches_element(name_ref, "rubyBase") {
+                        in_base = false;
+                    } else if XmlParser::matches_element(name_ref, "rPr") {
+                        in_run_props = false;
+                    } else if XmlParser::matches_element(name_ref, "t") {
+                        in_text = false;
+                    }
+                }
+                Ok(Event::Text(ref e)) if in_text => {
+                    let text = e.unescape().map_err(|e| DocxError::XmlParse(e.to_string()))?;
+                    if in_base {
+                        run.text.push_str(&text);
+                    } else if in_ruby_text {
+                        ruby.text.push_str(&text);
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(DocxError::from(e)),
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        run.ruby = Some(ruby);
         Ok(())
     }
 
     /// Commit a parsed run to the tree
-    fn commit_run(&self, parsed: ParsedRun, parent_id: doc_model::NodeId, tree: &mut DocumentTree) -> DocxResult<()> {
+    fn commit_run(&self, mut parsed: ParsedRun, parent_id: doc_model::NodeId, tree: &mut DocumentTree) -> DocxResult<()> {
+        for object in std::mem::take(&mut parsed.objects) {
+            self.commit_object(object, parent_id, tree)?;
+        }
+
         // Don't create empty runs
         if parsed.text.is_empty() {
             return Ok(());
         }
 
         let mut run = Run::new(&parsed.text);
+        run.ruby = parsed.ruby;
 
         // Apply style
         if let Some(style_id) = &parsed.style_id {
@@ -299,6 +676,37 @@ impl<'a> DocumentParser<'a> {
         Ok(())
     }
 
+    /// Commit a parsed object to the paragraph, with its parts
+    fn commit_object(&self, parsed: ParsedObject, para_id: doc_model::NodeId, tree: &mut DocumentTree) -> DocxResult<()> {
+        let part = |parts: Option<&HashMap<String, ImageData>>, rel_id: Option<&String>| {
+            let data = parts?.get(rel_id?)?;
+            let file_name = data.path.rsplit('/').next().unwrap_or(&data.path);
+            Some(EmbeddedPart::new(file_name, data.content_type.clone(), data.data.clone()))
+        };
+        let preview = part(self.images, parsed.preview_rel_id.as_ref());
+        let width = parsed.width.unwrap_or(DEFAULT_OBJECT_WIDTH);
+        let height = parsed.height.unwrap_or(DEFAULT_OBJECT_HEIGHT);
+
+        let mut object = if parsed.linked {
+            let target = parsed.object_rel_id.as_deref().and_then(|id| self.doc_rels.get_target(id));
+            let Some(target) = target else {
+                return Ok(());
+            };
+            EmbeddedObject::linked(parsed.prog_id, target, width, height)
+        } else {
+            let embedding = part(self.embeddings, parsed.object_rel_id.as_ref());
+            if embedding.is_none() && preview.is_none() {
+                return Ok(());
+            }
+            EmbeddedObject::new(parsed.prog_id, embedding, width, height)
+        };
+        object.show_as_icon = parsed.show_as_icon;
+        object.preview = preview;
+
+        tree.insert_embedded_object(object, para_id, None)?;
+        Ok(())
+    }
+
     /// Commit a parsed hyperlink to the tree
     fn commit_hyperlink(&self, parsed: ParsedHyperlink, para_id: doc_model::NodeId, tree: &mut DocumentTree) -> DocxResult<()> {
         // Determine the hyperlink target
@@ -335,6 +743,7 @@ impl<'a> DocumentParser<'a> {
         for parsed_run in parsed.runs {
             if !parsed_run.text.is_empty() {
                 let mut run = Run::new(&parsed_run.text);
+                run.ruby = parsed_run.ruby;
                 if let Some(style_id) = &parsed_run.style_id {
                     run.set_character_style(Some(StyleId::new(style_id)));
                 }
@@ -356,6 +765,14 @@ struct ParsedParagraph {
     props: ParagraphProperties,
     runs: Vec<ParsedRun>,
     hyperlinks: Vec<ParsedHyperlink>,
+    /// Endnote references: text offset and DOCX note ID
+    endnote_refs: Vec<(usize, i64)>,
+    /// Relationship IDs of the InkML parts anchored to the paragraph
+    ink_rel_ids: Vec<String>,
+    /// Attributes of the `w:p` element
+    attributes: Vec<(String, String)>,
+    /// Unrecognized properties and content
+    preserved: Vec<PreservedElement>,
 }
 
 impl ParsedParagraph {
@@ -365,8 +782,17 @@ impl ParsedParagraph {
             props: ParagraphProperties::default(),
             runs: Vec::new(),
             hyperlinks: Vec::new(),
+            endnote_refs: Vec::new(),
+            ink_rel_ids: Vec::new(),
+            attributes: Vec::new(),
+            preserved: Vec::new(),
         }
     }
+
+    /// Characters of run text so far
+    fn text_len(&self) -> usize {
+        self.runs.iter().map(|run| run.text.chars().count()).sum()
+    }
 }
 
 /// Parsed run data
@@ -375,6 +801,10 @@ struct ParsedRun {
     style_id: Option<String>,
     props: CharacterProperties,
     text: String,
+    /// Embedded objects in the run
+    objects: Vec<ParsedObject>,
+    /// Ruby over the run's text
+    ruby: Option<RubyAnnotation>,
 }
 
 impl ParsedRun {
@@ -383,6 +813,8 @@ impl ParsedRun {
             style_id: None,
             props: CharacterProperties::default(),
             text: String::new(),
+            objects: Vec::new(),
+            ruby: None,
         }
     }
 }
@@ -405,16 +837,55 @@ impl ParsedHyperlink {
     }
 }
 
+/// Check if an element is a paragraph child the parser reads
+fn is_paragraph_child(name: &[u8]) -> bool {
+    ["pPr", "r", "hyperlink"].iter().any(|known| XmlParser::matches_element(name, known))
+}
+
+/// Read an element's whole markup, from its start tag at `position`
+fn read_element<'c>(
+    reader: &mut Reader<&[u8]>,
+    content: &'c str,
+    position: usize,
+    e: &BytesStart,
+) -> DocxResult<&'c str> {
+    if content[position..reader.buffer_position() as usize].trim_end().ends_with("/>") {
+        return Ok(content[position..reader.buffer_position() as usize].trim());
+    }
+    let end = e.to_end().into_owned();
+    reader.read_to_end_into(end.name(), &mut Vec::new())?;
+    Ok(content[position..reader.buffer_position() as usize].trim())
+}
+
+/// Attach pending body-level markup to the last block
+fn attach_body_markup(pending: &mut Vec<String>, placement: MarkupPlacement, tree: &mut DocumentTree) {
+    let Some(&block_id) = tree.document.children().last() else {
+        return;
+    };
+    for markup in pending.drain(..) {
+        tree.preserved.add_element(block_id, PreservedElement::new(placement, markup));
+    }
+}
+
 /// Parse alignment value
 fn parse_alignment(value: &str) -> Alignment {
     match value {
         "center" => Alignment::Center,
-        "right" => Alignment::Right,
+        "right" | "end" => Alignment::Right,
         "both" | "justify" => Alignment::Justify,
         _ => Alignment::Left,
     }
 }
 
+/// Parse the `w:val` of `w:bidi` into a paragraph base direction
+pub(crate) fn parse_bidi(value: Option<&str>) -> TextDirection {
+    if value.is_none_or(XmlParser::parse_bool) {
+        TextDirection::Rtl
+    } else {
+        TextDirection::Ltr
+    }
+}
+
 /// Parse line spacing value
 fn parse_line_spacing(value: &str, line_rule: &str) -> LineSpacing {
     let val: f32 = value.parse().unwrap_or(240.0);
@@ -2,19 +2,49 @@
 //!
 //! Converts the DocumentTree to DOCX document.xml format.
 
+use crate::docx::embedded_objects_io::{ObjectRelationships, ObjectWriter};
 use crate::docx::error::DocxResult;
+use crate::docx::footnotes_io::NotesWriter;
+use crate::docx::ink_io::InkWriter;
+use crate::docx::media_writer::{generate_inline_drawing, points_to_emu, MediaRelationships};
 use crate::docx::namespaces;
+use crate::docx::preserved_io::{root_namespace_attributes, write_attributes};
+use crate::docx::ruby_io::write_ruby;
+use crate::docx::sections_io::write_section_properties;
 use crate::docx::tables_writer::TableWriter;
+use crate::docx::tabs_io::write_tab_stops;
+use crate::docx::text_effects_io::write_text_effects;
+use crate::docx::theme_io::{theme_color_attributes, theme_font_attributes};
 use doc_model::{
-    Alignment, CharacterProperties, DocumentTree, Hyperlink, HyperlinkTarget, LineSpacing,
-    Node, NodeType, Paragraph, ParagraphProperties, Run,
+    Alignment, CharacterProperties, DocumentTree, Hyperlink, HyperlinkTarget, LineSpacing, MarkupPlacement,
+    Node, NodeId, NodeType, Paragraph, ParagraphProperties, Run, TextDirection,
 };
+use std::collections::HashMap;
+
+/// Size of text with no size of its own, in points
+const DEFAULT_FONT_SIZE: f32 = 11.0;
+
+/// Width percentage image sizes resolve against (6.5" text column), in points
+const IMAGE_CONTAINER_SIZE: f32 = 468.0;
 
 /// Writer for document.xml
 pub struct DocumentWriter {
     /// External hyperlinks to be added to relationships
     pub hyperlinks: Vec<(String, String)>,
     next_hyperlink_id: u32,
+    /// Header relationship IDs by section ID
+    section_headers: HashMap<NodeId, String>,
+    /// Endnote references by paragraph ID: text offset and DOCX note ID
+    endnote_references: HashMap<NodeId, Vec<(usize, i64)>>,
+    /// InkML part relationship IDs by ink node ID
+    ink_parts: HashMap<NodeId, String>,
+    next_ink_id: u32,
+    /// Part relationship IDs by embedded object ID
+    object_parts: HashMap<NodeId, ObjectRelationships>,
+    next_object_id: u32,
+    /// Media relationships by image resource ID
+    image_parts: HashMap<String, MediaRelationships>,
+    next_image_id: u32,
 }
 
 impl DocumentWriter {
@@ -23,9 +53,48 @@ impl DocumentWriter {
         Self {
             hyperlinks: Vec::new(),
             next_hyperlink_id: 1,
+            section_headers: HashMap::new(),
+            endnote_references: HashMap::new(),
+            ink_parts: HashMap::new(),
+            next_ink_id: 1,
+            object_parts: HashMap::new(),
+            next_object_id: 1,
+            image_parts: HashMap::new(),
+            next_image_id: 1,
         }
     }
 
+    /// Set the header relationship each section references
+    pub fn with_section_headers(mut self, section_headers: HashMap<NodeId, String>) -> Self {
+        self.section_headers = section_headers;
+        self
+    }
+
+    /// Set the endnote references each paragraph holds, as text offsets
+    /// and the DOCX IDs of the notes
+    pub fn with_endnote_references(mut self, endnote_references: HashMap<NodeId, Vec<(usize, i64)>>) -> Self {
+        self.endnote_references = endnote_references;
+        self
+    }
+
+    /// Set the InkML part relationship each ink node references
+    pub fn with_ink_parts(mut self, ink_parts: HashMap<NodeId, String>) -> Self {
+        self.ink_parts = ink_parts;
+        self
+    }
+
+    /// Set the part relationships each embedded object references
+    pub fn with_object_parts(mut self, object_parts: HashMap<NodeId, ObjectRelationships>) -> Self {
+        self.object_parts = object_parts;
+        self
+    }
+
+    /// Set the media relationships each image resource references
+    pub fn with_image_parts(mut self, image_parts: HashMap<String, MediaRelationships>) -> Self {
+        self.image_parts = image_parts;
+        self
+    }
+
     /// Generate document.xml content
     pub fn write(&mut self, tree: &DocumentTree) -> DocxResult<String> {
         let mut xml = String::new();
@@ -36,19 +105,37 @@ impl DocumentWriter {
 
         // Document element with namespaces
         xml.push_str(&format!(
-            r#"<w:document xmlns:w="{}" xmlns:r="{}" xmlns:wp="{}" xmlns:a="{}">"#,
+            r#"<w:document xmlns:w="{}" xmlns:r="{}" xmlns:wp="{}" xmlns:a="{}" xmlns:w14="{}" xmlns:mc="{}"{}>"#,
             namespaces::W,
             namespaces::R,
             namespaces::WP,
             namespaces::A,
+            namespaces::W14,
+            namespaces::MC,
+            root_namespace_attributes(&tree.preserved),
         ));
 
         // Body
         xml.push_str("<w:body>");
 
-        // Write body content
-        for child_id in tree.document.children() {
-            self.write_body_element(&mut xml, tree, *child_id)?;
+        // Write body content; a section break goes in the last paragraph of
+        // each section but the final one
+        let block_sections = tree.block_sections();
+        let children = tree.document.children();
+        for (index, child_id) in children.iter().enumerate() {
+            let section_index = block_sections[child_id];
+            let section_break = children
+                .get(index + 1)
+                .filter(|next| block_sections[*next] != section_index)
+                .and_then(|_| tree.sections.get(section_index))
+                .map(|section| self.section_properties(tree, section));
+            self.write_body_element(&mut xml, tree, *child_id, section_break.as_deref())?;
+        }
+
+        // The final section's properties close the body
+        let last_section = children.last().map_or(0, |id| block_sections[id]);
+        if let Some(section) = tree.sections.get(last_section) {
+            xml.push_str(&self.section_properties(tree, section));
         }
 
         // Close body and document
@@ -58,18 +145,44 @@ impl DocumentWriter {
         Ok(xml)
     }
 
+    /// A section's `w:sectPr`
+    fn section_properties(&self, tree: &DocumentTree, section: &doc_model::Section) -> String {
+        let mut xml = String::new();
+        write_section_properties(
+            &mut xml,
+            section,
+            self.section_headers.get(&section.id()).map(String::as_str),
+            Some(tree.get_endnote_properties(Some(section.id()))),
+        );
+        xml
+    }
+
     /// Write a body-level element (paragraph, table, etc.)
     fn write_body_element(
         &mut self,
         xml: &mut String,
         tree: &DocumentTree,
         node_id: doc_model::NodeId,
+        section_break: Option<&str>,
     ) -> DocxResult<()> {
+        for markup in tree.preserved.elements_at(node_id, MarkupPlacement::Before) {
+            xml.push_str(markup);
+        }
+
         // Determine node type and write accordingly
         if let Some(para) = tree.nodes.paragraphs.get(&node_id) {
-            self.write_paragraph(xml, tree, para)?;
+            self.write_paragraph(xml, tree, para, section_break)?;
         } else if let Some(table) = tree.nodes.tables.get(&node_id) {
             TableWriter::new().write_table(xml, tree, table)?;
+            // A section can't end in a table; an empty paragraph carries
+            // the break
+            if let Some(section_break) = section_break {
+                xml.push_str(&format!("<w:p><w:pPr>{}</w:pPr></w:p>", section_break));
+            }
+        }
+
+        for markup in tree.preserved.elements_at(node_id, MarkupPlacement::After) {
+            xml.push_str(markup);
         }
 
         Ok(())
@@ -81,18 +194,80 @@ impl DocumentWriter {
         xml: &mut String,
         tree: &DocumentTree,
         para: &Paragraph,
+        section_break: Option<&str>,
     ) -> DocxResult<()> {
-        xml.push_str("<w:p>");
+        xml.push_str("<w:p");
+        write_attributes(xml, tree.preserved.attributes(para.id()));
+        xml.push('>');
 
         // Paragraph properties
-        self.write_paragraph_properties(xml, para)?;
-
-        // Paragraph content (runs and hyperlinks)
+        self.write_paragraph_properties(xml, tree, para, section_break)?;
+
+        // Paragraph content (runs and hyperlinks), with endnote references
+        // and preserved content at the first run boundary at or after their
+        // offset
+        let mut references = self.endnote_references.get(&para.id()).cloned().unwrap_or_default();
+        references.sort();
+        let mut references = references.into_iter().peekable();
+        let mut preserved = tree
+            .preserved
+            .elements(para.id())
+            .iter()
+            .filter_map(|element| match element.placement {
+                MarkupPlacement::Content(at) => Some((at, element.markup.as_str())),
+                _ => None,
+            })
+            .peekable();
+        let mut offset = 0;
         for child_id in para.children() {
+            while let Some((_, markup)) = preserved.next_if(|&(at, _)| at <= offset) {
+                xml.push_str(markup);
+            }
+            while let Some((_, docx_id)) = references.next_if(|&(at, _)| at <= offset) {
+                NotesWriter::write_endnote_reference(xml, docx_id);
+            }
             if let Some(run) = tree.nodes.runs.get(child_id) {
                 self.write_run(xml, run)?;
+                offset += run.text.chars().count();
             } else if let Some(hyperlink) = tree.nodes.hyperlinks.get(child_id) {
                 self.write_hyperlink(xml, tree, hyperlink)?;
+            } else if let Some(object) = tree.get_embedded_object(*child_id) {
+                let rels = self.object_parts.get(child_id).cloned().unwrap_or_default();
+                ObjectWriter::new().write_object(xml, object, &rels, self.next_object_id);
+                self.next_object_id += 1;
+            } else if let Some(image) = tree.nodes.images.get(child_id) {
+                // Images whose data wasn't written are dropped
+                if let Some(rels) = self.image_parts.get(image.resource_id.as_str()) {
+                    let name = image.title.clone().unwrap_or_else(|| format!("Picture {}", self.next_image_id));
+                    xml.push_str("<w:r>");
+                    xml.push_str(&generate_inline_drawing(
+                        rels,
+                        self.next_image_id,
+                        points_to_emu(image.effective_width(IMAGE_CONTAINER_SIZE)),
+                        points_to_emu(image.effective_height(IMAGE_CONTAINER_SIZE)),
+                        &name,
+                        image.alt_text.as_deref(),
+                        image.decorative,
+                    ));
+                    xml.push_str("</w:r>");
+                    self.next_image_id += 1;
+                }
+            }
+        }
+        for (_, markup) in preserved {
+            xml.push_str(markup);
+        }
+        for (_, docx_id) in references {
+            NotesWriter::write_endnote_reference(xml, docx_id);
+        }
+
+        // Ink anchored to the paragraph floats over it
+        let mut inks = tree.inks_in_paragraph(para.id());
+        inks.sort_by_key(|ink| ink.id().as_uuid());
+        for ink in inks {
+            if let Some(rel_id) = self.ink_parts.get(&ink.id()) {
+                InkWriter::new().write_anchor(xml, ink, rel_id, self.next_ink_id);
+                self.next_ink_id += 1;
             }
         }
 
@@ -101,15 +276,22 @@ impl DocumentWriter {
     }
 
     /// Write paragraph properties
-    fn write_paragraph_properties(&self, xml: &mut String, para: &Paragraph) -> DocxResult<()> {
+    fn write_paragraph_properties(
+        &self,
+        xml: &mut String,
+        tree: &DocumentTree,
+        para: &Paragraph,
+        section_break: Option<&str>,
+    ) -> DocxResult<()> {
         let props = &para.direct_formatting;
         let style_id = para.paragraph_style_id.as_ref();
+        let mut preserved = tree.preserved.elements_at(para.id(), MarkupPlacement::Properties).peekable();
 
         // Only write pPr if there's something to write
         let has_style = style_id.is_some();
         let has_props = !props.is_empty();
 
-        if !has_style && !has_props {
+        if !has_style && !has_props && section_break.is_none() && preserved.peek().is_none() {
             return Ok(());
         }
 
@@ -175,6 +357,9 @@ impl DocumentWriter {
             xml.push_str("/>");
         }
 
+        // Tab stops
+        write_tab_stops(xml, &props.tab_stops);
+
         // Keep with next
         if props.keep_with_next == Some(true) {
             xml.push_str("<w:keepNext/>");
@@ -190,6 +375,30 @@ impl DocumentWriter {
             xml.push_str("<w:pageBreakBefore/>");
         }
 
+        // Line number suppression
+        match props.suppress_line_numbers {
+            Some(true) => xml.push_str("<w:suppressLineNumbers/>"),
+            Some(false) => xml.push_str(r#"<w:suppressLineNumbers w:val="0"/>"#),
+            None => {}
+        }
+
+        // Base direction; Auto has no markup and is left to the reader
+        match props.direction {
+            Some(TextDirection::Rtl) => xml.push_str("<w:bidi/>"),
+            Some(TextDirection::Ltr) => xml.push_str(r#"<w:bidi w:val="0"/>"#),
+            Some(TextDirection::Auto) | None => {}
+        }
+
+        // Properties we don't model, as imported
+        for markup in preserved {
+            xml.push_str(markup);
+        }
+
+        // Section break
+        if let Some(section_break) = section_break {
+            xml.push_str(section_break);
+        }
+
         xml.push_str("</w:pPr>");
         Ok(())
     }
@@ -201,31 +410,16 @@ impl DocumentWriter {
         // Run properties
         self.write_run_properties(xml, run)?;
 
-        // Text content - handle special characters
-        let text = &run.text;
-        for part in text.split('\n') {
-            // Handle tabs
-            let parts: Vec<&str> = part.split('\t').collect();
-            for (i, segment) in parts.iter().enumerate() {
-                if !segment.is_empty() {
-                    // Write text with xml:space="preserve" for leading/trailing spaces
-                    let needs_preserve = segment.starts_with(' ') || segment.ends_with(' ');
-                    if needs_preserve {
-                        xml.push_str(r#"<w:t xml:space="preserve">"#);
-                    } else {
-                        xml.push_str("<w:t>");
-                    }
-                    xml.push_str(&escape_xml(segment));
-                    xml.push_str("</w:t>");
-                }
-                if i < parts.len() - 1 {
-                    xml.push_str("<w:tab/>");
-                }
-            }
-            // Add line break if not the last part
-            if part != text.split('\n').last().unwrap_or("") {
-                xml.push_str("<w:br/>");
-            }
+        if let Some(ruby) = &run.ruby {
+            // The base text goes in its own run inside the ruby
+            let mut base = String::from("<w:r>");
+            self.write_run_properties(&mut base, run)?;
+            write_run_text(&mut base, &run.text);
+            base.push_str("</w:r>");
+            let base_font_size = run.direct_formatting.font_size.or(run.style.font_size).unwrap_or(DEFAULT_FONT_SIZE);
+            write_ruby(xml, ruby, base_font_size, &base);
+        } else {
+            write_run_text(xml, &run.text);
         }
 
         xml.push_str("</w:r>");
@@ -252,12 +446,19 @@ impl DocumentWriter {
         }
 
         // Font family
-        if let Some(ref font) = props.font_family {
-            xml.push_str(&format!(
-                r#"<w:rFonts w:ascii="{}" w:hAnsi="{}"/>"#,
-                escape_xml(font),
-                escape_xml(font)
-            ));
+        if props.font_family.is_some() || props.theme_font.is_some() {
+            xml.push_str("<w:rFonts");
+            if let Some(ref font) = props.font_family {
+                xml.push_str(&format!(
+                    r#" w:ascii="{}" w:hAnsi="{}""#,
+                    escape_xml(font),
+                    escape_xml(font)
+                ));
+            }
+            if let Some(font) = props.theme_font {
+                xml.push_str(&theme_font_attributes(font));
+            }
+            xml.push_str("/>");
         }
 
         // Font size (in half-points)
@@ -304,9 +505,10 @@ impl DocumentWriter {
         }
 
         // Color
-        if let Some(ref color) = props.color {
-            let color_val = color.trim_start_matches('#');
-            xml.push_str(&format!(r#"<w:color w:val="{}"/>"#, color_val));
+        if props.color.is_some() || props.theme_color.is_some() {
+            let color_val = props.color.as_deref().map_or("auto", |c| c.trim_start_matches('#'));
+            let theme_attrs = props.theme_color.as_ref().map(theme_color_attributes).unwrap_or_default();
+            xml.push_str(&format!(r#"<w:color w:val="{}"{}/>"#, color_val, theme_attrs));
         }
 
         // Highlight
@@ -329,6 +531,52 @@ impl DocumentWriter {
             }
         }
 
+        // Character spacing
+        if let Some(spacing) = props.spacing {
+            xml.push_str(&format!(r#"<w:spacing w:val="{}"/>"#, (spacing * 20.0) as i32));
+        }
+
+        // Horizontal scale
+        if let Some(scale) = props.scale {
+            xml.push_str(&format!(r#"<w:w w:val="{}"/>"#, scale));
+        }
+
+        // Kerning threshold (in half-points)
+        if let Some(kerning) = props.kerning {
+            xml.push_str(&format!(r#"<w:kern w:val="{}"/>"#, (kerning * 2.0) as i32));
+        }
+
+        // Raised/lowered position (in half-points)
+        if let Some(position) = props.position {
+            xml.push_str(&format!(r#"<w:position w:val="{}"/>"#, (position * 2.0) as i32));
+        }
+
+        // Text effects
+        for (flag, name) in [
+            (props.outline, "outline"),
+            (props.shadow, "shadow"),
+            (props.emboss, "emboss"),
+            (props.imprint, "imprint"),
+            (props.no_proof, "noProof"),
+            (props.hidden, "vanish"),
+        ] {
+            match flag {
+                Some(true) => xml.push_str(&format!("<w:{}/>", name)),
+                Some(false) => xml.push_str(&format!(r#"<w:{} w:val="0"/>"#, name)),
+                None => {}
+            }
+        }
+
+        // Proofing language
+        if let Some(ref language) = props.language {
+            xml.push_str(&format!(r#"<w:lang w:val="{}"/>"#, escape_xml(language)));
+        }
+
+        // Word 2010 text effects
+        if let Some(ref effects) = props.text_effects {
+            write_text_effects(xml, effects);
+        }
+
         xml.push_str("</w:rPr>");
         Ok(())
     }
@@ -376,6 +624,35 @@ impl DocumentWriter {
     }
 }
 
+/// Write a run's text, with tabs and line breaks as elements
+fn write_run_text(xml: &mut String, text: &str) {
+    // Text content - handle special characters
+    for part in text.split('\n') {
+        // Handle tabs
+        let parts: Vec<&str> = part.split('\t').collect();
+        for (i, segment) in parts.iter().enumerate() {
+            if !segment.is_empty() {
+                // Write text with xml:space="preserve" for leading/trailing spaces
+                let needs_preserve = segment.starts_with(' ') || segment.ends_with(' ');
+                if needs_preserve {
+                    xml.push_str(r#"<w:t xml:space="preserve">"#);
+                } else {
+                    xml.push_str("<w:t>");
+                }
+                xml.push_str(&escape_xml(segment));
+                xml.push_str("</w:t>");
+            }
+            if i < parts.len() - 1 {
+                xml.push_str("<w:tab/>");
+            }
+        }
+        // Add line break if not the last part
+        if part != text.split('\n').last().unwrap_or("") {
+            xml.push_str("<w:br/>");
+        }
+    }
+}
+
 /// Escape special XML characters
 fn escape_xml(s: &str) -> String {
     s.replace('&', "&amp;")
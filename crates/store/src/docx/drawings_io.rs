@@ -2,16 +2,17 @@
 //!
 //! Handles DrawingML elements including:
 //! - Text boxes (w:txbxContent in drawing ML)
-//! - Shapes with geometry
+//! - Shapes with geometry, including freeform custom geometry (a:custGeom)
 //! - Shape groups (wpg:wgp)
 //! - Connectors between shapes
 //! - Shape effects (shadow, 3D, glow)
 //! - Gradient and pattern fills
 
+use crate::docx::alt_text_io::{read_decorative, read_doc_pr_alt_text, write_doc_pr};
 use crate::docx::error::{DocxError, DocxResult};
 use crate::docx::namespaces;
 use crate::docx::reader::XmlParser;
-use doc_model::{HorizontalAnchor, VerticalAnchor, WrapType};
+use doc_model::{FreeformPath, HorizontalAnchor, PathSegment, Point, VerticalAnchor, WrapType};
 use quick_xml::events::Event;
 use std::collections::HashMap;
 
@@ -105,6 +106,7 @@ impl DrawingParser {
         let mut in_sp_pr = false;
         let mut in_text = false;
         let mut text_content = String::new();
+        let mut geometry: Option<CustomGeometry> = None;
 
         loop {
             match reader.read_event_into(&mut buf) {
@@ -132,6 +134,10 @@ impl DrawingParser {
                     else if XmlParser::matches_element(name_ref, "inline") {
                         drawing.is_inline = true;
                     }
+                    // Document properties (alt text)
+                    else if XmlParser::matches_element(name_ref, "docPr") {
+                        drawing.alt_text = read_doc_pr_alt_text(e);
+                    }
                     // Extent (size)
                     else if XmlParser::matches_element(name_ref, "extent") {
                         if let Some(cx) = XmlParser::get_attribute(e, b"cx") {
@@ -184,6 +190,11 @@ impl DrawingParser {
                     // Custom geometry
                     else if in_sp_pr && XmlParser::matches_element(name_ref, "custGeom") {
                         drawing.drawing_type = DrawingType::CustomShape;
+                        geometry = Some(CustomGeometry::default());
+                    }
+                    // Custom geometry path and its commands
+                    else if let Some(geometry) = geometry.as_mut().filter(|g| g.is_open()) {
+                        geometry.start_element(e);
                     }
                     // Fill types
                     else if in_sp_pr && XmlParser::matches_element(name_ref, "solidFill") {
@@ -225,6 +236,10 @@ impl DrawingParser {
                         if let Some(cy) = XmlParser::get_attribute(e, b"cy") {
                             drawing.height = XmlParser::parse_emu(&cy);
                         }
+                    } else if XmlParser::matches_element(name_ref, "docPr") {
+                        drawing.alt_text = read_doc_pr_alt_text(e);
+                    } else if read_decorative(e) {
+                        drawing.decorative = true;
                     } else if XmlParser::matches_element(name_ref, "prstGeom") {
                         if let Some(prst) = XmlParser::get_attribute(e, b"prst") {
                             drawing.shape_type = Some(parse_preset_shape(&prst));
@@ -239,6 +254,8 @@ impl DrawingParser {
                         if let Some(val) = XmlParser::get_attribute(e, b"val") {
                             drawing.fill_color = ShapeColor::from_hex(&val);
                         }
+                    } else if let Some(geometry) = geometry.as_mut().filter(|g| g.is_open()) {
+                        geometry.empty_element(e);
                     }
                 }
                 Ok(Event::Text(ref e)) => {
@@ -269,6 +286,8 @@ impl DrawingParser {
                         in_txbx = false;
                     } else if XmlParser::matches_element(name_ref, "spPr") {
                         in_sp_pr = false;
+                    } else if let Some(geometry) = geometry.as_mut().filter(|g| g.is_open()) {
+                        geometry.end_element(name_ref);
                     } else if XmlParser::matches_element(name_ref, "t") {
                         in_text = false;
                     }
@@ -281,6 +300,9 @@ impl DrawingParser {
         }
 
         drawing.text_content = if text_content.is_empty() { None } else { Some(text_content) };
+        if let Some(geometry) = geometry {
+            drawing.freeform = geometry.into_path(drawing.width, drawing.height);
+        }
         self.drawings.push(drawing.clone());
 
         Ok(drawing)
@@ -297,6 +319,123 @@ impl DrawingParser {
     }
 }
 
+/// Custom geometry (a:custGeom) being read. Only the first path is kept;
+/// arcs are skipped.
+#[derive(Debug, Default)]
+struct CustomGeometry {
+    /// Coordinate space of the path (a:path w/h)
+    size: Option<(f32, f32)>,
+    /// Number of a:path elements seen
+    path_count: usize,
+    /// Points of the current path command
+    pts: Vec<Point>,
+    /// The path in path coordinates
+    path: FreeformPath,
+    /// Whether a:custGeom has ended
+    done: bool,
+}
+
+impl CustomGeometry {
+    fn is_open(&self) -> bool {
+        !self.done
+    }
+
+    fn start_element(&mut self, e: &quick_xml::events::BytesStart) {
+        let name = e.name();
+        if XmlParser::matches_element(name.as_ref(), "path") {
+            self.path_count += 1;
+            let dimension = |key: &[u8]| XmlParser::get_attribute(e, key).and_then(|v| v.parse::<f32>().ok());
+            if let (1, Some(w), Some(h)) = (self.path_count, dimension(b"w"), dimension(b"h")) {
+                self.size = Some((w, h));
+            }
+        }
+        self.pts.clear();
+    }
+
+    fn empty_element(&mut self, e: &quick_xml::events::BytesStart) {
+        let name = e.name();
+        if self.path_count != 1 {
+            return;
+        }
+        if XmlParser::matches_element(name.as_ref(), "pt") {
+            let coordinate = |key: &[u8]| {
+                XmlParser::get_attribute(e, key)
+                    .and_then(|v| v.parse::<f32>().ok())
+                    .unwrap_or(0.0)
+            };
+            self.pts.push(Point::new(coordinate(b"x"), coordinate(b"y")));
+        } else if XmlParser::matches_element(name.as_ref(), "close") {
+            self.path.closed = true;
+        }
+    }
+
+    fn end_element(&mut self, name: &[u8]) {
+        if XmlParser::matches_element(name, "custGeom") {
+            self.done = true;
+            return;
+        }
+        if self.path_count != 1 {
+            return;
+        }
+        let pts = std::mem::take(&mut self.pts);
+        let Some(&end) = pts.last() else {
+            return;
+        };
+        let segment = if XmlParser::matches_element(name, "moveTo") {
+            if self.path.points.is_empty() {
+                self.path.points.push(end);
+            }
+            return;
+        } else if XmlParser::matches_element(name, "lnTo") {
+            PathSegment::Line
+        } else if XmlParser::matches_element(name, "cubicBezTo") && pts.len() == 3 {
+            PathSegment::Cubic { control1: pts[0], control2: pts[1] }
+        } else if XmlParser::matches_element(name, "quadBezTo") && pts.len() == 2 {
+            // Raise to a cubic
+            let start = self.path.points.last().copied().unwrap_or(pts[0]);
+            let toward = |from: Point| Point::new(from.x + (pts[0].x - from.x) * 2.0 / 3.0, from.y + (pts[0].y - from.y) * 2.0 / 3.0);
+            PathSegment::Cubic { control1: toward(start), control2: toward(end) }
+        } else {
+            return;
+        };
+        if !self.path.points.is_empty() {
+            self.path.segments.push(segment);
+            self.path.points.push(end);
+        }
+    }
+
+    /// The path in points, scaled from the path coordinate space to the
+    /// drawing's size (or from EMUs when either is unknown)
+    fn into_path(self, width: Option<f32>, height: Option<f32>) -> Option<FreeformPath> {
+        let mut path = self.path;
+        if path.points.is_empty() {
+            return None;
+        }
+        let (sx, sy) = match (self.size, width, height) {
+            (Some((w, h)), Some(width), Some(height)) if w > 0.0 && h > 0.0 => (width / w, height / h),
+            _ => (1.0 / 12700.0, 1.0 / 12700.0),
+        };
+        let scale = |p: &mut Point| {
+            p.x *= sx;
+            p.y *= sy;
+        };
+        path.points.iter_mut().for_each(scale);
+        for segment in &mut path.segments {
+            if let PathSegment::Cubic { control1, control2 } = segment {
+                scale(control1);
+                scale(control2);
+            }
+        }
+
+        // An explicit segment back to the start is the closing segment
+        if path.closed && path.points.len() > 2 && path.points.first() == path.points.last() {
+            path.points.pop();
+        }
+        path.segments.truncate(path.segment_count());
+        Some(path)
+    }
+}
+
 // =============================================================================
 // Drawing Writer
 // =============================================================================
@@ -316,6 +455,112 @@ impl DrawingWriter {
         let width_emu = (width * 12700.0) as i64;
         let height_emu = (height * 12700.0) as i64;
 
+        Self::write_drawing_start(xml, width_emu, height_emu, is_inline, "Text Box", None, false);
+        xml.push_str("<a:graphic xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\">");
+        xml.push_str("<a:graphicData uri=\"http://schemas.microsoft.com/office/word/2010/wordprocessingShape\">");
+        xml.push_str("<wps:wsp xmlns:wps=\"http://schemas.microsoft.com/office/word/2010/wordprocessingShape\">");
+        xml.push_str("<wps:cNvSpPr txBox=\"1\"/>");
+        xml.push_str("<wps:spPr>");
+        xml.push_str(&format!(
+            "<a:xfrm><a:off x=\"0\" y=\"0\"/><a:ext cx=\"{}\" cy=\"{}\"/></a:xfrm>",
+            width_emu, height_emu
+        ));
+        xml.push_str("<a:prstGeom prst=\"rect\"><a:avLst/></a:prstGeom>");
+        xml.push_str("<a:solidFill><a:srgbClr val=\"FFFFFF\"/></a:solidFill>");
+        xml.push_str("<a:ln><a:solidFill><a:srgbClr val=\"000000\"/></a:solidFill></a:ln>");
+        xml.push_str("</wps:spPr>");
+        xml.push_str("<wps:txbx><w:txbxContent xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">");
+        xml.push_str("<w:p><w:r><w:t>");
+        xml.push_str(&escape_xml(text));
+        xml.push_str("</w:t></w:r></w:p>");
+        xml.push_str("</w:txbxContent></wps:txbx>");
+        xml.push_str("<wps:bodyPr anchor=\"t\" lIns=\"91440\" tIns=\"45720\" rIns=\"91440\" bIns=\"45720\"/>");
+        xml.push_str("</wps:wsp>");
+        xml.push_str("</a:graphicData>");
+        xml.push_str("</a:graphic>");
+
+        Self::write_drawing_end(xml, is_inline);
+    }
+
+    /// Write a freeform shape as DrawingML custom geometry. Coordinates are
+    /// written in EMUs, with the path's coordinate space matching the extent.
+    pub fn write_freeform_shape(
+        xml: &mut String,
+        path: &FreeformPath,
+        width: f32,
+        height: f32,
+        is_inline: bool,
+        alt_text: Option<&str>,
+        decorative: bool,
+    ) {
+        let emu = |points: f32| (points * 12700.0).round() as i64;
+        let (width_emu, height_emu) = (emu(width), emu(height));
+
+        Self::write_drawing_start(xml, width_emu, height_emu, is_inline, "Freeform", alt_text, decorative);
+        xml.push_str("<a:graphic xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\">");
+        xml.push_str("<a:graphicData uri=\"http://schemas.microsoft.com/office/word/2010/wordprocessingShape\">");
+        xml.push_str("<wps:wsp xmlns:wps=\"http://schemas.microsoft.com/office/word/2010/wordprocessingShape\">");
+        xml.push_str("<wps:cNvSpPr/>");
+        xml.push_str("<wps:spPr>");
+        xml.push_str(&format!(
+            "<a:xfrm><a:off x=\"0\" y=\"0\"/><a:ext cx=\"{}\" cy=\"{}\"/></a:xfrm>",
+            width_emu, height_emu
+        ));
+        xml.push_str("<a:custGeom><a:avLst/><a:gdLst/><a:ahLst/><a:cxnLst/>");
+        xml.push_str("<a:rect l=\"0\" t=\"0\" r=\"r\" b=\"b\"/>");
+        xml.push_str("<a:pathLst>");
+        xml.push_str(&format!(
+            "<a:path w=\"{}\" h=\"{}\"{}>",
+            width_emu,
+            height_emu,
+            // Open paths (polylines) have no interior
+            if path.closed { "" } else { " fill=\"none\"" }
+        ));
+        let pt = |p: Point| format!("<a:pt x=\"{}\" y=\"{}\"/>", emu(p.x), emu(p.y));
+        if let Some(&first) = path.points.first() {
+            xml.push_str(&format!("<a:moveTo>{}</a:moveTo>", pt(first)));
+        }
+        for index in 0..path.segment_count() {
+            let (_, end) = path.segment_ends(index);
+            match path.segment(index) {
+                PathSegment::Line => xml.push_str(&format!("<a:lnTo>{}</a:lnTo>", pt(end))),
+                PathSegment::Cubic { control1, control2 } => xml.push_str(&format!(
+                    "<a:cubicBezTo>{}{}{}</a:cubicBezTo>",
+                    pt(control1),
+                    pt(control2),
+                    pt(end)
+                )),
+            }
+        }
+        if path.closed {
+            xml.push_str("<a:close/>");
+        }
+        xml.push_str("</a:path></a:pathLst></a:custGeom>");
+        if path.closed {
+            xml.push_str("<a:solidFill><a:srgbClr val=\"FFFFFF\"/></a:solidFill>");
+        } else {
+            xml.push_str("<a:noFill/>");
+        }
+        xml.push_str("<a:ln><a:solidFill><a:srgbClr val=\"000000\"/></a:solidFill></a:ln>");
+        xml.push_str("</wps:spPr>");
+        xml.push_str("<wps:bodyPr/>");
+        xml.push_str("</wps:wsp>");
+        xml.push_str("</a:graphicData>");
+        xml.push_str("</a:graphic>");
+        Self::write_drawing_end(xml, is_inline);
+    }
+
+    /// Open w:drawing and write the inline/anchor positioning, extent and
+    /// document properties
+    fn write_drawing_start(
+        xml: &mut String,
+        width_emu: i64,
+        height_emu: i64,
+        is_inline: bool,
+        name: &str,
+        alt_text: Option<&str>,
+        decorative: bool,
+    ) {
         xml.push_str("<w:drawing>");
 
         if is_inline {
@@ -345,30 +590,11 @@ impl DrawingWriter {
             xml.push_str("<wp:wrapSquare wrapText=\"bothSides\"/>");
         }
 
-        xml.push_str("<wp:docPr id=\"1\" name=\"Text Box\"/>");
-        xml.push_str("<a:graphic xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\">");
-        xml.push_str("<a:graphicData uri=\"http://schemas.microsoft.com/office/word/2010/wordprocessingShape\">");
-        xml.push_str("<wps:wsp xmlns:wps=\"http://schemas.microsoft.com/office/word/2010/wordprocessingShape\">");
-        xml.push_str("<wps:cNvSpPr txBox=\"1\"/>");
-        xml.push_str("<wps:spPr>");
-        xml.push_str(&format!(
-            "<a:xfrm><a:off x=\"0\" y=\"0\"/><a:ext cx=\"{}\" cy=\"{}\"/></a:xfrm>",
-            width_emu, height_emu
-        ));
-        xml.push_str("<a:prstGeom prst=\"rect\"><a:avLst/></a:prstGeom>");
-        xml.push_str("<a:solidFill><a:srgbClr val=\"FFFFFF\"/></a:solidFill>");
-        xml.push_str("<a:ln><a:solidFill><a:srgbClr val=\"000000\"/></a:solidFill></a:ln>");
-        xml.push_str("</wps:spPr>");
-        xml.push_str("<wps:txbx><w:txbxContent xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">");
-        xml.push_str("<w:p><w:r><w:t>");
-        xml.push_str(&escape_xml(text));
-        xml.push_str("</w:t></w:r></w:p>");
-        xml.push_str("</w:txbxContent></wps:txbx>");
-        xml.push_str("<wps:bodyPr anchor=\"t\" lIns=\"91440\" tIns=\"45720\" rIns=\"91440\" bIns=\"45720\"/>");
-        xml.push_str("</wps:wsp>");
-        xml.push_str("</a:graphicData>");
-        xml.push_str("</a:graphic>");
+        write_doc_pr(xml, 1, name, alt_text, decorative);
+    }
 
+    /// Close the elements opened by `write_drawing_start`
+    fn write_drawing_end(xml: &mut String, is_inline: bool) {
         if is_inline {
             xml.push_str("</wp:inline>");
         } else {
@@ -394,6 +620,10 @@ pub struct ParsedDrawing {
     pub width: Option<f32>,
     /// Height in points
     pub height: Option<f32>,
+    /// Alternative text
+    pub alt_text: Option<String>,
+    /// Whether the drawing is decorative
+    pub decorative: bool,
     /// Horizontal position offset in points
     pub offset_x: Option<f32>,
     /// Vertical position offset in points
@@ -420,6 +650,8 @@ pub struct ParsedDrawing {
     pub fill_type: Option<FillType>,
     /// Fill color
     pub fill_color: Option<ShapeColor>,
+    /// Freeform geometry in points (for custom shapes)
+    pub freeform: Option<FreeformPath>,
     /// Text content (for text boxes)
     pub text_content: Option<String>,
     /// Group members (for groups)
@@ -565,4 +797,61 @@ mod tests {
         assert!(matches!(parse_preset_shape("ellipse"), ShapeType::Oval));
         assert!(matches!(parse_preset_shape("star5"), ShapeType::Star5));
     }
+
+    #[test]
+    fn test_freeform_cust_geom_round_trip() {
+        use doc_model::SegmentType;
+
+        let mut path = FreeformPath::polygon(vec![
+            Point::new(0.0, 0.0),
+            Point::new(72.0, 0.0),
+            Point::new(72.0, 36.0),
+            Point::new(0.0, 36.0),
+        ]);
+        path.set_segment_type(1, SegmentType::Curved).unwrap();
+        path.move_control_point(1, 1, Point::new(90.0, 10.0)).unwrap();
+
+        let mut xml = String::new();
+        DrawingWriter::write_freeform_shape(&mut xml, &path, 90.0, 36.0, true, Some("Arrow"), false);
+        assert!(xml.contains("<a:cubicBezTo>"));
+
+        let mut parser = DrawingParser::new();
+        let drawing = parser.parse_drawing(&xml).unwrap();
+        assert!(matches!(drawing.drawing_type, DrawingType::CustomShape));
+        assert_eq!(drawing.alt_text.as_deref(), Some("Arrow"));
+        assert!(!drawing.decorative);
+        assert_eq!(drawing.freeform, Some(FreeformPath { segments: vec![
+            PathSegment::Line,
+            path.segment(1),
+            PathSegment::Line,
+            PathSegment::Line,
+        ], ..path.clone() }));
+
+        // Path coordinates are scaled to the drawing extent
+        let xml = r#"<wp:inline><wp:extent cx="254000" cy="254000"/><a:graphic><a:graphicData><wps:wsp><wps:spPr>
+            <a:custGeom><a:pathLst><a:path w="100" h="100" fill="none">
+            <a:moveTo><a:pt x="0" y="0"/></a:moveTo><a:lnTo><a:pt x="100" y="50"/></a:lnTo>
+            <a:quadBezTo><a:pt x="100" y="100"/><a:pt x="0" y="100"/></a:quadBezTo>
+            </a:path></a:pathLst></a:custGeom><a:solidFill><a:srgbClr val="FF0000"/></a:solidFill>
+            </wps:spPr></wps:wsp></a:graphicData></a:graphic></wp:inline>"#;
+        let drawing = parser.parse_drawing(xml).unwrap();
+        let freeform = drawing.freeform.unwrap();
+        assert!(!freeform.closed);
+        assert_eq!(freeform.points, vec![Point::new(0.0, 0.0), Point::new(20.0, 10.0), Point::new(0.0, 20.0)]);
+        assert!(matches!(freeform.segment(1), PathSegment::Cubic { .. }));
+        assert!(matches!(drawing.fill_type, Some(FillType::Solid)));
+    }
+
+    #[test]
+    fn test_decorative_drawing_round_trip() {
+        let path = FreeformPath::polygon(vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(0.0, 10.0)]);
+        let mut xml = String::new();
+        DrawingWriter::write_freeform_shape(&mut xml, &path, 10.0, 10.0, false, Some("Ignored"), true);
+        assert!(!xml.contains("descr="));
+
+        let drawing = DrawingParser::new().parse_drawing(&xml).unwrap();
+        assert!(drawing.decorative);
+        assert!(drawing.alt_text.is_none());
+        assert!(drawing.freeform.is_some());
+    }
 }
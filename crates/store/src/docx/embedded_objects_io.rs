@@ -0,0 +1,306 @@
+//! Embedded (OLE) Object Import/Export for DOCX
+//!
+//! An object is a `w:object` in a run: a VML shape whose image data is the
+//! cached preview (`word/media/*.emf`), and an `o:OLEObject` pointing at
+//! the object's part (`word/embeddings/*`). Both parts are carried through
+//! unchanged. Linked objects point at an external file instead.
+
+use crate::docx::error::{DocxError, DocxResult};
+use crate::docx::fidelity::{warning_codes, FeatureCategory, FidelityWarning, WarningSeverity};
+use crate::docx::namespaces;
+use crate::docx::reader::XmlParser;
+use doc_model::{DocumentTree, EmbeddedObject};
+use quick_xml::events::{BytesStart, Event};
+
+/// Twips per point
+const TWIPS_PER_POINT: f32 = 20.0;
+
+/// Path of the Nth embedding part within the package
+pub fn embedding_part(index: usize, extension: &str) -> String {
+    format!("word/embeddings/oleObject{}.{}", index, extension_or(extension, "bin"))
+}
+
+/// Path of the Nth object preview part within the package
+pub fn object_preview_part(index: usize, extension: &str) -> String {
+    format!("word/media/oleObject{}.{}", index, extension_or(extension, "emf"))
+}
+
+fn extension_or<'a>(extension: &'a str, default: &'a str) -> &'a str {
+    if extension.is_empty() {
+        default
+    } else {
+        extension
+    }
+}
+
+/// Check if a part is an Office Open XML package rather than an OLE
+/// compound file; packages use the package relationship type
+pub fn is_package_part(extension: &str) -> bool {
+    matches!(
+        extension,
+        "xlsx" | "xlsm" | "xlsb" | "docx" | "docm" | "pptx" | "pptm" | "vsdx" | "sldx"
+    )
+}
+
+/// Content type for a preview picture with no content type of its own
+pub fn preview_content_type(extension: &str) -> &'static str {
+    match extension {
+        "wmf" => "image/x-wmf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        _ => "image/x-emf",
+    }
+}
+
+// =============================================================================
+// Object Parser
+// =============================================================================
+
+/// A `w:object` read from a run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedObject {
+    /// OLE program ID
+    pub prog_id: Option<String>,
+    /// Displayed width in points
+    pub width: Option<f32>,
+    /// Displayed height in points
+    pub height: Option<f32>,
+    /// Whether the object is drawn as an icon
+    pub show_as_icon: bool,
+    /// Whether the object links to an external file
+    pub linked: bool,
+    /// Relationship ID of the object's part (or link)
+    pub object_rel_id: Option<String>,
+    /// Relationship ID of the preview picture
+    pub preview_rel_id: Option<String>,
+}
+
+/// Parser for `w:object` markup
+pub struct ObjectParser;
+
+impl ObjectParser {
+    /// Create a new object parser
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Parse a `w:object` element and its contents
+    pub fn parse(&self, markup: &str) -> DocxResult<ParsedObject> {
+        let mut reader = XmlParser::from_string(markup);
+        let mut buf = Vec::new();
+        let mut parsed = ParsedObject::default();
+        // Original size from w:object, used when the shape has none
+        let mut original_size = (None, None);
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                    let name = e.name();
+                    let name_ref = name.as_ref();
+                    if XmlParser::matches_element(name_ref, "object") {
+                        original_size = (
+                            XmlParser::get_w_attribute(e, "dxaOrig").and_then(|v| XmlParser::parse_twips(&v)),
+                            XmlParser::get_w_attribute(e, "dyaOrig").and_then(|v| XmlParser::parse_twips(&v)),
+                        );
+                    } else if name_ref == b"v:shape" {
+                        if let Some(style) = XmlParser::get_attribute(e, b"style") {
+                            parsed.width = style_length(&style, "width");
+                            parsed.height = style_length(&style, "height");
+                        }
+                    } else if name_ref == b"v:imagedata" {
+                        parsed.preview_rel_id = XmlParser::get_r_attribute(e, "id");
+                    } else if XmlParser::matches_element(name_ref, "OLEObject") {
+                        parsed.prog_id = attribute(e, b"ProgID");
+                        parsed.linked = attribute(e, b"Type").as_deref() == Some("Link");
+                        parsed.show_as_icon = attribute(e, b"DrawAspect").as_deref() == Some("Icon");
+                        parsed.object_rel_id = XmlParser::get_r_attribute(e, "id");
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(DocxError::XmlParse(e.to_string())),
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        parsed.width = parsed.width.or(original_size.0);
+        parsed.height = parsed.height.or(original_size.1);
+        Ok(parsed)
+    }
+}
+
+impl Default for ObjectParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Read a length in points from a CSS-style `key:value;...` list
+fn style_length(style: &str, key: &str) -> Option<f32> {
+    let value = style.split(';').find_map(|declaration| {
+        let (name, value) = declaration.split_once(':')?;
+        (name.trim() == key).then(|| value.trim())
+    })?;
+    if let Some(points) = value.strip_suffix("pt") {
+        points.parse().ok()
+    } else if let Some(inches) = value.strip_suffix("in") {
+        inches.parse::<f32>().ok().map(|v| v * 72.0)
+    } else if let Some(pixels) = value.strip_suffix("px") {
+        pixels.parse::<f32>().ok().map(|v| v * 0.75)
+    } else {
+        value.parse().ok()
+    }
+}
+
+/// Get an attribute value with entities unescaped
+fn attribute(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .filter_map(|a| a.ok())
+        .find(|a| a.key.as_ref() == name)
+        .and_then(|a| a.unescape_value().ok().map(|value| value.into_owned()))
+}
+
+// =============================================================================
+// Object Writer
+// =============================================================================
+
+/// Relationship IDs of an object's parts in the written package
+#[derive(Debug, Clone, Default)]
+pub struct ObjectRelationships {
+    /// The object's part, or its link
+    pub object: Option<String>,
+    /// The preview picture
+    pub preview: Option<String>,
+}
+
+/// Writer for `w:object` elements
+pub struct ObjectWriter;
+
+impl ObjectWriter {
+    /// Create a new object writer
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Write an object as a run
+    pub fn write_object(&self, xml: &mut String, object: &EmbeddedObject, rels: &ObjectRelationships, index: u32) {
+        let shape_id = format!("_x0000_i{}", 1024 + index);
+        xml.push_str("<w:r>");
+        xml.push_str(&format!(
+            r#"<w:object xmlns:v="{}" xmlns:o="{}" w:dxaOrig="{}" w:dyaOrig="{}">"#,
+            namespaces::V,
+            namespaces::O,
+            (object.width * TWIPS_PER_POINT).round() as i64,
+            (object.height * TWIPS_PER_POINT).round() as i64,
+        ));
+        xml.push_str(&format!(
+            r##"<v:shape id="{}" type="#_x0000_t75" style="width:{}pt;height:{}pt" o:ole="">"##,
+            shape_id, object.width, object.height
+        ));
+        if let Some(preview) = &rels.preview {
+            xml.push_str(&format!(r#"<v:imagedata r:id="{}" o:title=""/>"#, preview));
+        }
+        xml.push_str("</v:shape>");
+        if let Some(rel_id) = &rels.object {
+            xml.push_str(&format!(
+                r#"<o:OLEObject Type="{}" ProgID="{}" ShapeID="{}" DrawAspect="{}" ObjectID="_{}" r:id="{}"{}/>"#,
+                if object.is_linked() { "Link" } else { "Embed" },
+                escape_attr(object.prog_id.as_deref().unwrap_or("Package")),
+                shape_id,
+                if object.show_as_icon { "Icon" } else { "Content" },
+                1_000_000_000 + index,
+                rel_id,
+                if object.is_linked() { r#" UpdateMode="Always""# } else { "" },
+            ));
+        }
+        xml.push_str("</w:object></w:r>");
+    }
+}
+
+impl Default for ObjectWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Escape a string for an XML attribute value
+fn escape_attr(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// =============================================================================
+// Fidelity
+// =============================================================================
+
+/// Fidelity warnings for the embedded objects in a document
+///
+/// Objects are kept but can't be edited, so each one is reported; objects
+/// whose data or preview is missing are reported at a higher severity.
+pub fn embedded_object_warnings(tree: &DocumentTree) -> Vec<FidelityWarning> {
+    let mut objects: Vec<_> = tree.embedded_objects().collect();
+    objects.sort_by_key(|object| doc_model::Node::id(*object).as_uuid());
+
+    let mut warnings = Vec::new();
+    for object in objects {
+        let name = object.display_name();
+        let warning = if object.is_linked() {
+            FidelityWarning::new(
+                warning_codes::EMBEDDED_OBJECT_LINKED,
+                format!("Linked object '{}' is kept but won't be updated from its source", name),
+                WarningSeverity::Moderate,
+                FeatureCategory::EmbeddedObjects,
+            )
+            .with_suggestion("Open the document in the source application to refresh the link")
+        } else if object.embedding.is_none() {
+            FidelityWarning::new(
+                warning_codes::EMBEDDED_OBJECT_REMOVED,
+                format!("The data of object '{}' is missing; only its picture is kept", name),
+                WarningSeverity::Major,
+                FeatureCategory::EmbeddedObjects,
+            )
+        } else {
+            FidelityWarning::new(
+                warning_codes::EMBEDDED_OBJECT_PRESERVED,
+                format!("Object '{}' is kept unchanged but can't be edited here", name),
+                WarningSeverity::Info,
+                FeatureCategory::EmbeddedObjects,
+            )
+            .with_suggestion("Edit the object in the application that created it")
+        };
+        warnings.push(warning);
+        if object.preview.is_none() {
+            warnings.push(FidelityWarning::new(
+                warning_codes::EMBEDDED_OBJECT_NO_PREVIEW,
+                format!("Object '{}' has no saved picture and is shown as a placeholder", name),
+                WarningSeverity::Minor,
+                FeatureCategory::EmbeddedObjects,
+            ));
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_object() {
+        let markup = r##"<w:object xmlns:w="w" xmlns:v="v" xmlns:o="o" xmlns:r="r" w:dxaOrig="9000" w:dyaOrig="3000">
+            <v:shape id="_x0000_i1025" type="#_x0000_t75" style="width:415.5pt;height:150pt" o:ole="">
+                <v:imagedata r:id="rId5" o:title=""/>
+            </v:shape>
+            <o:OLEObject Type="Embed" ProgID="Excel.Sheet.12" ShapeID="_x0000_i1025" DrawAspect="Content" ObjectID="_1" r:id="rId6"/>
+        </w:object>"##;
+        let parsed = ObjectParser::new().parse(markup).unwrap();
+        assert_eq!(parsed.prog_id.as_deref(), Some("Excel.Sheet.12"));
+        assert_eq!((parsed.width, parsed.height), (Some(415.5), Some(150.0)));
+        assert_eq!(parsed.preview_rel_id.as_deref(), Some("rId5"));
+        assert_eq!(parsed.object_rel_id.as_deref(), Some("rId6"));
+        assert!(!parsed.linked && !parsed.show_as_icon);
+    }
+}
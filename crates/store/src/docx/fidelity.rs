@@ -519,6 +519,9 @@ pub mod warning_codes {
     pub const STYLE_SIMPLIFIED: &str = "STYLE_002";
     pub const MACRO_REMOVED: &str = "MACRO_001";
     pub const EMBEDDED_OBJECT_REMOVED: &str = "EMBED_001";
+    pub const EMBEDDED_OBJECT_PRESERVED: &str = "EMBED_002";
+    pub const EMBEDDED_OBJECT_LINKED: &str = "EMBED_003";
+    pub const EMBEDDED_OBJECT_NO_PREVIEW: &str = "EMBED_004";
     pub const EQUATION_CONVERTED: &str = "MATH_001";
     pub const CHART_STATIC: &str = "CHART_001";
     pub const SMARTART_CONVERTED: &str = "SMARTART_001";
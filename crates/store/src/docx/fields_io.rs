@@ -25,6 +25,11 @@ pub enum NumberFormat {
     Ordinal,
     CardinalText,
     OrdinalText,
+    Hebrew,
+    ArabicIndic,
+    ChineseCounting,
+    JapaneseCounting,
+    Thai,
 }
 
 /// Type of REF display
@@ -385,6 +390,11 @@ impl FieldParser {
                 "ordinal" => NumberFormat::Ordinal,
                 "cardtext" => NumberFormat::CardinalText,
                 "ordtext" => NumberFormat::OrdinalText,
+                "hebrew1" => NumberFormat::Hebrew,
+                "hindiarabic" => NumberFormat::ArabicIndic,
+                "chinesenum1" => NumberFormat::ChineseCounting,
+                "kanjinum1" => NumberFormat::JapaneseCounting,
+                "thaiarabic" => NumberFormat::Thai,
                 _ => NumberFormat::Arabic,
             }
         } else {
@@ -740,6 +750,11 @@ impl FieldWriter {
             NumberFormat::Ordinal => "Ordinal",
             NumberFormat::CardinalText => "CardText",
             NumberFormat::OrdinalText => "OrdText",
+            NumberFormat::Hebrew => "Hebrew1",
+            NumberFormat::ArabicIndic => "HindiArabic",
+            NumberFormat::ChineseCounting => "CHINESENUM1",
+            NumberFormat::JapaneseCounting => "KANJINUM1",
+            NumberFormat::Thai => "ThaiArabic",
         }
     }
 }
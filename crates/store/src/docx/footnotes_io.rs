@@ -62,6 +62,7 @@ impl NotesParser {
         let mut notes = Vec::new();
         let mut current_note: Option<ParsedNote> = None;
         let mut in_note = false;
+        let mut in_later_para = false;
         let mut in_para = false;
         let mut in_run = false;
         let mut in_text = false;
@@ -94,8 +95,14 @@ impl NotesParser {
                             custom_mark: None,
                         });
                         in_note = true;
+                        in_later_para = false;
                     } else if in_note && XmlParser::matches_element(name_ref, "p") {
+                        // Paragraphs after the first start on a new line
+                        if let Some(note) = current_note.as_mut().filter(|_| in_later_para) {
+                            note.content.push('\n');
+                        }
                         in_para = true;
+                        in_later_para = true;
                     } else if in_para && XmlParser::matches_element(name_ref, "r") {
                         in_run = true;
                     } else if in_run && XmlParser::matches_element(name_ref, "t") {
@@ -249,15 +256,22 @@ impl NotesWriter {
             let docx_id = self.next_endnote_id;
             self.next_endnote_id += 1;
 
+            // Each line of the content is a paragraph; the first leads
+            // with the note's mark
             xml.push_str(&format!(r#"<w:endnote w:id="{}">"#, docx_id));
-            xml.push_str("<w:p><w:pPr><w:pStyle w:val=\"EndnoteText\"/></w:pPr>");
-            xml.push_str("<w:r><w:rPr><w:rStyle w:val=\"EndnoteReference\"/></w:rPr>");
-            xml.push_str("<w:endnoteRef/></w:r>");
-            xml.push_str("<w:r><w:t xml:space=\"preserve\"> </w:t></w:r>");
-            xml.push_str("<w:r><w:t>");
-            xml.push_str(&escape_xml(&note.content));
-            xml.push_str("</w:t></w:r>");
-            xml.push_str("</w:p></w:endnote>");
+            for (index, line) in note.content.split('\n').enumerate() {
+                xml.push_str("<w:p><w:pPr><w:pStyle w:val=\"EndnoteText\"/></w:pPr>");
+                if index == 0 {
+                    xml.push_str("<w:r><w:rPr><w:rStyle w:val=\"EndnoteReference\"/></w:rPr>");
+                    xml.push_str("<w:endnoteRef/></w:r>");
+                    xml.push_str("<w:r><w:t xml:space=\"preserve\"> </w:t></w:r>");
+                }
+                xml.push_str("<w:r><w:t xml:space=\"preserve\">");
+                xml.push_str(&escape_xml(line));
+                xml.push_str("</w:t></w:r>");
+                xml.push_str("</w:p>");
+            }
+            xml.push_str("</w:endnote>");
         }
 
         xml.push_str("</w:endnotes>");
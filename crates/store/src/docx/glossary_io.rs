@@ -0,0 +1,205 @@
+//! Glossary document part (building blocks)
+//!
+//! `word/glossary/document.xml` holds the document's building blocks as
+//! `w:docPart` elements: the name, gallery and category in `w:docPartPr`
+//! and the content in `w:docPartBody`. The body is ordinary body markup,
+//! so it is written and read with the main document writer and parser.
+
+use crate::docx::document::DocumentParser;
+use crate::docx::document_writer::DocumentWriter;
+use crate::docx::error::{DocxError, DocxResult};
+use crate::docx::reader::XmlParser;
+use crate::docx::relationships::Relationships;
+use crate::docx::namespaces;
+use doc_model::{BuildingBlock, BuildingBlockCollection, BuildingBlockGallery, DocumentTree};
+use quick_xml::events::{BytesStart, Event};
+use std::collections::HashMap;
+
+/// Path of the glossary document part
+pub const GLOSSARY_PART: &str = "word/glossary/document.xml";
+
+/// The `w:gallery` value for a gallery
+fn gallery_value(gallery: BuildingBlockGallery) -> &'static str {
+    match gallery {
+        BuildingBlockGallery::CoverPages => "coverPg",
+        BuildingBlockGallery::Headers => "hdrs",
+        BuildingBlockGallery::Footers => "ftrs",
+        BuildingBlockGallery::TextBoxes => "txtBox",
+        BuildingBlockGallery::Equations => "eq",
+        BuildingBlockGallery::Tables => "tbls",
+        BuildingBlockGallery::QuickParts => "docParts",
+        BuildingBlockGallery::AutoText => "autoTxt",
+    }
+}
+
+/// The gallery for a `w:gallery` value; custom galleries map to their base
+fn parse_gallery(value: &str) -> BuildingBlockGallery {
+    match value {
+        "coverPg" | "custCoverPg" => BuildingBlockGallery::CoverPages,
+        "hdrs" | "custHdrs" => BuildingBlockGallery::Headers,
+        "ftrs" | "custFtrs" => BuildingBlockGallery::Footers,
+        "txtBox" | "custTxtBox" => BuildingBlockGallery::TextBoxes,
+        "eq" | "custEq" => BuildingBlockGallery::Equations,
+        "tbls" | "custTbls" => BuildingBlockGallery::Tables,
+        "autoTxt" | "custAutoTxt" => BuildingBlockGallery::AutoText,
+        _ => BuildingBlockGallery::QuickParts,
+    }
+}
+
+/// Write the glossary document for a set of building blocks
+pub fn write_glossary(blocks: &BuildingBlockCollection) -> DocxResult<String> {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <w:glossaryDocument xmlns:w=\"{}\" xmlns:r=\"{}\" xmlns:wp=\"{}\" xmlns:a=\"{}\" xmlns:w14=\"{}\" \
+         xmlns:mc=\"{}\"><w:docParts>",
+        namespaces::W,
+        namespaces::R,
+        namespaces::WP,
+        namespaces::A,
+        namespaces::W14,
+        namespaces::MC,
+    );
+
+    for block in blocks.iter() {
+        xml.push_str("<w:docPart><w:docPartPr>");
+        xml.push_str(&format!(r#"<w:name w:val="{}"/>"#, escape_attr(&block.name)));
+        xml.push_str(&format!(
+            r#"<w:category><w:name w:val="{}"/><w:gallery w:val="{}"/></w:category>"#,
+            escape_attr(&block.category),
+            gallery_value(block.gallery)
+        ));
+        if !block.description.is_empty() {
+            xml.push_str(&format!(r#"<w:description w:val="{}"/>"#, escape_attr(&block.description)));
+        }
+        xml.push_str(&format!(r#"<w:guid w:val="{{{}}}"/>"#, escape_attr(&block.id.to_uppercase())));
+        xml.push_str("</w:docPartPr><w:docPartBody>");
+
+        let document_xml = DocumentWriter::new().write(&block.content)?;
+        let body = body_content(&document_xml);
+        // A part body needs at least one paragraph
+        xml.push_str(if body.is_empty() { "<w:p/>" } else { body });
+        xml.push_str("</w:docPartBody></w:docPart>");
+    }
+
+    xml.push_str("</w:docParts></w:glossaryDocument>");
+    Ok(xml)
+}
+
+/// The markup between `<w:body>` and `</w:body>`
+fn body_content(document_xml: &str) -> &str {
+    const BODY_START: &str = "<w:body>";
+    match (document_xml.find(BODY_START), document_xml.rfind("</w:body>")) {
+        (Some(start), Some(end)) if start + BODY_START.len() <= end => &document_xml[start + BODY_START.len()..end],
+        _ => "",
+    }
+}
+
+/// Properties of a `w:docPart` read so far
+#[derive(Default)]
+struct PartProperties {
+    name: String,
+    category: Option<String>,
+    gallery: Option<String>,
+    description: String,
+    guid: Option<String>,
+}
+
+/// Parse the glossary document into building blocks
+pub fn parse_glossary(content: &str) -> DocxResult<Vec<BuildingBlock>> {
+    let mut reader = XmlParser::from_string(content);
+    let mut buf = Vec::new();
+    let mut root_attributes = String::new();
+    let mut blocks = Vec::new();
+    let mut properties = PartProperties::default();
+    let mut in_properties = false;
+    let mut in_category = false;
+    let mut body_start = None;
+
+    loop {
+        let before = reader.buffer_position() as usize;
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => match local_name(e.name().as_ref()) {
+                "glossaryDocument" => {
+                    root_attributes = String::from_utf8_lossy(e.attributes_raw()).into_owned();
+                }
+                "docPart" => properties = PartProperties::default(),
+                "docPartPr" => in_properties = true,
+                "category" if in_properties => in_category = true,
+                "docPartBody" => body_start = Some(reader.buffer_position() as usize),
+                _ if in_properties => read_property(e, &mut properties, in_category),
+                _ => {}
+            },
+            Ok(Event::Empty(ref e)) if in_properties => read_property(e, &mut properties, in_category),
+            Ok(Event::End(ref e)) => match local_name(e.name().as_ref()) {
+                "docPartPr" => in_properties = false,
+                "category" => in_category = false,
+                "docPartBody" => {
+                    if let Some(start) = body_start.take() {
+                        let body = content.get(start..before).unwrap_or_default();
+                        let part = std::mem::take(&mut properties);
+                        blocks.push(build_block(part, &root_attributes, body)?);
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(DocxError::XmlParse(e.to_string())),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(blocks)
+}
+
+fn read_property(e: &BytesStart, properties: &mut PartProperties, in_category: bool) {
+    let value = attribute(e, b"w:val");
+    match local_name(e.name().as_ref()) {
+        "name" if in_category => properties.category = value,
+        "name" => properties.name = value.unwrap_or_default(),
+        "gallery" => properties.gallery = value,
+        "description" => properties.description = value.unwrap_or_default(),
+        "guid" => properties.guid = value,
+        _ => {}
+    }
+}
+
+/// Parse a part body as a standalone fragment
+fn build_block(properties: PartProperties, root_attributes: &str, body: &str) -> DocxResult<BuildingBlock> {
+    let document_xml = format!("<w:document {}><w:body>{}</w:body></w:document>", root_attributes, body);
+    let mut content = DocumentTree::new();
+    let rels = Relationships::new();
+    let hyperlinks = HashMap::new();
+    DocumentParser::new(&rels, &hyperlinks).parse(&document_xml, &mut content)?;
+
+    let gallery = parse_gallery(properties.gallery.as_deref().unwrap_or_default());
+    let mut block = BuildingBlock::new(properties.name, gallery, content).with_description(properties.description);
+    if let Some(category) = properties.category.filter(|c| !c.is_empty()) {
+        block = block.with_category(category);
+    }
+    if let Some(guid) = properties.guid {
+        block = block.with_id(guid.trim_matches(|c| c == '{' || c == '}').to_lowercase());
+    }
+    Ok(block)
+}
+
+fn local_name(name: &[u8]) -> &str {
+    let name = std::str::from_utf8(name).unwrap_or("");
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
+
+/// Get an attribute value with entities unescaped
+fn attribute(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .filter_map(|a| a.ok())
+        .find(|a| a.key.as_ref() == name)
+        .and_then(|a| a.unescape_value().ok().map(|value| value.into_owned()))
+}
+
+/// Escape a string for an XML attribute value
+fn escape_attr(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
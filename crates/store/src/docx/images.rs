@@ -2,6 +2,7 @@
 //!
 //! Handles w:drawing and embedded image elements.
 
+use crate::docx::alt_text_io::{read_decorative, read_doc_pr_alt_text};
 use crate::docx::error::{DocxError, DocxResult};
 use crate::docx::parser::ImageData;
 use crate::docx::reader::XmlParser;
@@ -73,12 +74,14 @@ impl ImageParser {
                         }
                     } else if XmlParser::matches_element(name_ref, "docPr") {
                         // Document properties (alt text, title)
-                        if let Some(descr) = XmlParser::get_attribute(e, b"descr") {
+                        if let Some(descr) = read_doc_pr_alt_text(e) {
                             parsed.alt_text = Some(descr);
                         }
                         if let Some(name) = XmlParser::get_attribute(e, b"name") {
                             parsed.title = Some(name);
                         }
+                    } else if read_decorative(e) {
+                        parsed.decorative = true;
                     } else if in_anchor {
                         self.parse_anchor_properties(e, &mut parsed)?;
                     }
@@ -175,6 +178,7 @@ impl ImageParser {
         if let Some(ref title) = parsed.title {
             node.set_title(title);
         }
+        node.decorative = parsed.decorative;
 
         node
     }
@@ -248,6 +252,7 @@ pub struct ParsedImage {
     pub height: Option<f32>,
     pub alt_text: Option<String>,
     pub title: Option<String>,
+    pub decorative: bool,
     pub wrap_type: WrapType,
     pub position: ImagePosition,
     pub h_anchor: Option<HorizontalAnchor>,
@@ -0,0 +1,318 @@
+//! Ink Import/Export for DOCX
+//!
+//! Each ink node is stored as an InkML part (`word/ink/inkN.xml`) referenced
+//! from a `w14:contentPart` inside a floating drawing anchored to its
+//! paragraph. Trace coordinates are HIMETRIC (1/1000 cm) relative to the
+//! top-left of the anchor paragraph, with pressure as a force channel.
+
+use crate::docx::error::{DocxError, DocxResult};
+use crate::docx::reader::XmlParser;
+use doc_model::{InkNode, InkPoint, InkStroke, ShapeColor};
+use quick_xml::events::Event;
+use std::collections::HashMap;
+
+/// InkML namespace
+pub const INKML_NAMESPACE: &str = "http://www.w3.org/2003/InkML";
+/// Word 2010 ink namespace, required by the anchor's content part
+pub const WPI_NAMESPACE: &str = "http://schemas.microsoft.com/office/word/2010/wordprocessingInk";
+
+/// Path of the Nth ink part within the package
+pub fn ink_part(index: usize) -> String {
+    format!("word/ink/ink{}.xml", index)
+}
+
+/// HIMETRIC units per point
+const HIMETRIC_PER_POINT: f32 = 2540.0 / 72.0;
+/// Maximum value of the force channel
+const MAX_FORCE: f32 = 32767.0;
+/// EMUs per point
+const EMU_PER_POINT: f32 = 12700.0;
+
+// =============================================================================
+// InkML Writer
+// =============================================================================
+
+/// Writer for InkML parts and their drawing anchors
+pub struct InkWriter;
+
+impl InkWriter {
+    /// Create a new ink writer
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Generate the InkML part for an ink node
+    pub fn write_inkml(&self, ink: &InkNode) -> String {
+        let mut xml = String::new();
+        xml.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        xml.push('\n');
+        xml.push_str(&format!(r#"<inkml:ink xmlns:inkml="{}">"#, INKML_NAMESPACE));
+
+        xml.push_str("<inkml:definitions>");
+        xml.push_str(r#"<inkml:context xml:id="ctx0"><inkml:inkSource xml:id="inkSrc0"><inkml:traceFormat>"#);
+        xml.push_str(r#"<inkml:channel name="X" type="integer" units="cm"/>"#);
+        xml.push_str(r#"<inkml:channel name="Y" type="integer" units="cm"/>"#);
+        xml.push_str(&format!(r#"<inkml:channel name="F" type="integer" max="{}" units="dev"/>"#, MAX_FORCE));
+        xml.push_str("</inkml:traceFormat><inkml:channelProperties>");
+        xml.push_str(r#"<inkml:channelProperty channel="X" name="resolution" value="1000" units="1/cm"/>"#);
+        xml.push_str(r#"<inkml:channelProperty channel="Y" name="resolution" value="1000" units="1/cm"/>"#);
+        xml.push_str("</inkml:channelProperties></inkml:inkSource></inkml:context>");
+        for (index, stroke) in ink.strokes.iter().enumerate() {
+            let width_cm = stroke.width / 72.0 * 2.54;
+            xml.push_str(&format!(r#"<inkml:brush xml:id="br{}">"#, index));
+            xml.push_str(&format!(r#"<inkml:brushProperty name="width" value="{:.5}" units="cm"/>"#, width_cm));
+            xml.push_str(&format!(r#"<inkml:brushProperty name="height" value="{:.5}" units="cm"/>"#, width_cm));
+            xml.push_str(&format!(
+                r#"<inkml:brushProperty name="color" value="{}"/>"#,
+                ShapeColor::rgb(stroke.color.r, stroke.color.g, stroke.color.b).to_hex()
+            ));
+            if stroke.color.a < 255 {
+                xml.push_str(&format!(
+                    r#"<inkml:brushProperty name="transparency" value="{}"/>"#,
+                    255 - stroke.color.a
+                ));
+            }
+            xml.push_str("</inkml:brush>");
+        }
+        xml.push_str("</inkml:definitions>");
+
+        for (index, stroke) in ink.strokes.iter().enumerate() {
+            let samples: Vec<String> = stroke
+                .points
+                .iter()
+                .map(|point| {
+                    format!(
+                        "{} {} {}",
+                        (point.x * HIMETRIC_PER_POINT).round() as i64,
+                        (point.y * HIMETRIC_PER_POINT).round() as i64,
+                        (point.pressure.clamp(0.0, 1.0) * MAX_FORCE).round() as i64
+                    )
+                })
+                .collect();
+            xml.push_str(&format!(
+                r##"<inkml:trace contextRef="#ctx0" brushRef="#br{}">{}</inkml:trace>"##,
+                index,
+                samples.join(", ")
+            ));
+        }
+
+        xml.push_str("</inkml:ink>");
+        xml
+    }
+
+    /// Write the run holding the floating anchor for an ink part
+    ///
+    /// `doc_pr_id` must be unique among the document's drawings. Ink
+    /// without strokes writes nothing.
+    pub fn write_anchor(&self, xml: &mut String, ink: &InkNode, rel_id: &str, doc_pr_id: u32) {
+        let Some(bounds) = ink.bounds() else {
+            return;
+        };
+        let emu = |points: f32| (points * EMU_PER_POINT).round().max(0.0) as i64;
+        let cx = emu(bounds.width).max(1);
+        let cy = emu(bounds.height).max(1);
+
+        xml.push_str("<w:r>");
+        xml.push_str(&format!(r#"<mc:AlternateContent xmlns:wpi="{}"><mc:Choice Requires="wpi">"#, WPI_NAMESPACE));
+        xml.push_str("<w:drawing>");
+        xml.push_str(r#"<wp:anchor distT="0" distB="0" distL="0" distR="0" simplePos="0" relativeHeight="251659264" behindDoc="0" locked="0" layoutInCell="1" allowOverlap="1">"#);
+        xml.push_str(r#"<wp:simplePos x="0" y="0"/>"#);
+        xml.push_str(&format!(
+            r#"<wp:positionH relativeFrom="column"><wp:posOffset>{}</wp:posOffset></wp:positionH>"#,
+            (bounds.x * EMU_PER_POINT).round() as i64
+        ));
+        xml.push_str(&format!(
+            r#"<wp:positionV relativeFrom="paragraph"><wp:posOffset>{}</wp:posOffset></wp:positionV>"#,
+            (bounds.y * EMU_PER_POINT).round() as i64
+        ));
+        xml.push_str(&format!(r#"<wp:extent cx="{}" cy="{}"/>"#, cx, cy));
+        xml.push_str(r#"<wp:effectExtent l="0" t="0" r="0" b="0"/>"#);
+        xml.push_str("<wp:wrapNone/>");
+        match &ink.alt_text {
+            Some(alt_text) => xml.push_str(&format!(
+                r#"<wp:docPr id="{}" name="Ink {}" descr="{}"/>"#,
+                doc_pr_id,
+                doc_pr_id,
+                escape_attr(alt_text)
+            )),
+            None => xml.push_str(&format!(r#"<wp:docPr id="{}" name="Ink {}"/>"#, doc_pr_id, doc_pr_id)),
+        }
+        xml.push_str("<wp:cNvGraphicFramePr/>");
+        xml.push_str(&format!(r#"<a:graphic><a:graphicData uri="{}">"#, WPI_NAMESPACE));
+        xml.push_str(&format!(r#"<w14:contentPart bwMode="auto" r:id="{}">"#, rel_id));
+        xml.push_str("<w14:nvContentPartPr><w14:cNvContentPartPr/></w14:nvContentPartPr>");
+        xml.push_str(&format!(
+            r#"<w14:xfrm><a:off x="0" y="0"/><a:ext cx="{}" cy="{}"/></w14:xfrm>"#,
+            cx, cy
+        ));
+        xml.push_str("</w14:contentPart>");
+        xml.push_str("</a:graphicData></a:graphic>");
+        xml.push_str("</wp:anchor></w:drawing></mc:Choice></mc:AlternateContent>");
+        xml.push_str("</w:r>");
+    }
+}
+
+impl Default for InkWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// =============================================================================
+// InkML Parser
+// =============================================================================
+
+/// Brush properties read from an InkML part
+#[derive(Clone, Copy)]
+struct Brush {
+    color: ShapeColor,
+    width: f32,
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self {
+            color: ShapeColor::BLACK,
+            width: 1.0,
+        }
+    }
+}
+
+/// Parser for InkML parts
+pub struct InkParser;
+
+impl InkParser {
+    /// Create a new ink parser
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Parse the strokes of an InkML part
+    ///
+    /// Traces without a force channel get the default pressure.
+    pub fn parse(&self, content: &str) -> DocxResult<Vec<InkStroke>> {
+        let mut reader = XmlParser::from_string(content);
+        let mut buf = Vec::new();
+
+        let mut brushes: HashMap<String, Brush> = HashMap::new();
+        let mut current_brush: Option<(String, Brush)> = None;
+        let mut current_trace: Option<Brush> = None;
+        let mut strokes = Vec::new();
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) => {
+                    let name = e.name();
+                    let name_ref = name.as_ref();
+
+                    if XmlParser::matches_element(name_ref, "brush") {
+                        let id = XmlParser::get_attribute(e, b"xml:id").unwrap_or_default();
+                        current_brush = Some((id, Brush::default()));
+                    } else if XmlParser::matches_element(name_ref, "trace") {
+                        let brush = XmlParser::get_attribute(e, b"brushRef")
+                            .and_then(|id| brushes.get(id.trim_start_matches('#')).copied())
+                            .unwrap_or_default();
+                        current_trace = Some(brush);
+                    }
+                }
+                Ok(Event::Empty(ref e)) => {
+                    let name = e.name();
+                    if XmlParser::matches_element(name.as_ref(), "brushProperty") {
+                        if let Some((_, brush)) = current_brush.as_mut() {
+                            let name = XmlParser::get_attribute(e, b"name").unwrap_or_default();
+                            let value = XmlParser::get_attribute(e, b"value").unwrap_or_default();
+                            match name.as_str() {
+                                "width" => {
+                                    let units = XmlParser::get_attribute(e, b"units");
+                                    if let Some(width) = parse_length(&value, units.as_deref()) {
+                                        brush.width = width;
+                                    }
+                                }
+                                "color" => {
+                                    if let Some(color) = ShapeColor::from_hex(&value) {
+                                        brush.color = ShapeColor { a: brush.color.a, ..color };
+                                    }
+                                }
+                                "transparency" => {
+                                    if let Ok(transparency) = value.parse::<u8>() {
+                                        brush.color.a = 255 - transparency;
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                Ok(Event::Text(ref t)) => {
+                    if let Some(brush) = current_trace {
+                        let text = t.unescape().map_err(|e| DocxError::XmlParse(e.to_string()))?;
+                        let points = parse_trace(&text);
+                        if !points.is_empty() {
+                            strokes.push(InkStroke::new(points, brush.color, brush.width));
+                        }
+                    }
+                }
+                Ok(Event::End(ref e)) => {
+                    let name = e.name();
+                    let name_ref = name.as_ref();
+
+                    if XmlParser::matches_element(name_ref, "brush") {
+                        if let Some((id, brush)) = current_brush.take() {
+                            brushes.insert(id, brush);
+                        }
+                    } else if XmlParser::matches_element(name_ref, "trace") {
+                        current_trace = None;
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(DocxError::from(e)),
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        Ok(strokes)
+    }
+}
+
+impl Default for InkParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse a brush length to points
+fn parse_length(value: &str, units: Option<&str>) -> Option<f32> {
+    let value = value.parse::<f32>().ok()?;
+    Some(match units {
+        Some("mm") => value / 25.4 * 72.0,
+        Some("in") => value * 72.0,
+        Some("pt") => value,
+        Some("himetric") => value / HIMETRIC_PER_POINT,
+        _ => value / 2.54 * 72.0,
+    })
+}
+
+/// Parse comma-separated `X Y [F]` samples in HIMETRIC
+fn parse_trace(text: &str) -> Vec<InkPoint> {
+    text.split(',')
+        .filter_map(|sample| {
+            let mut values = sample.split_whitespace().map(|v| v.parse::<f32>().ok());
+            let x = values.next()??;
+            let y = values.next()??;
+            let mut point = InkPoint::at(x / HIMETRIC_PER_POINT, y / HIMETRIC_PER_POINT);
+            if let Some(Some(force)) = values.next() {
+                point.pressure = (force / MAX_FORCE).clamp(0.0, 1.0);
+            }
+            Some(point)
+        })
+        .collect()
+}
+
+/// Escape a string for an XML attribute
+fn escape_attr(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
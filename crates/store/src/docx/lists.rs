@@ -310,6 +310,13 @@ fn parse_number_format(value: &str) -> NumberFormat {
         "ordinal" => NumberFormat::Ordinal,
         "cardinalText" => NumberFormat::CardinalText,
         "ordinalText" => NumberFormat::OrdinalText,
+        "hebrew1" => NumberFormat::Hebrew,
+        "hindiNumbers" => NumberFormat::ArabicIndic,
+        "chineseCounting" | "chineseCountingThousand" | "taiwaneseCounting" | "taiwaneseCountingThousand" => {
+            NumberFormat::ChineseCounting
+        }
+        "japaneseCounting" => NumberFormat::JapaneseCounting,
+        "thaiNumbers" => NumberFormat::Thai,
         _ => NumberFormat::Decimal,
     }
 }
@@ -352,6 +359,8 @@ mod tests {
         assert_eq!(parse_number_format("bullet"), NumberFormat::Bullet);
         assert_eq!(parse_number_format("lowerRoman"), NumberFormat::LowerRoman);
         assert_eq!(parse_number_format("upperLetter"), NumberFormat::UpperLetter);
+        assert_eq!(parse_number_format("hebrew1"), NumberFormat::Hebrew);
+        assert_eq!(parse_number_format("taiwaneseCounting"), NumberFormat::ChineseCounting);
         assert_eq!(parse_number_format("unknown"), NumberFormat::Decimal);
     }
 
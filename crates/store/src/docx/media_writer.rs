@@ -2,53 +2,116 @@
 //!
 //! Handles embedding images and other media files.
 
+use crate::docx::alt_text_io::write_doc_pr;
 use crate::docx::error::DocxResult;
+use crate::docx::relationship_types;
+use crate::docx::relationships::TargetMode;
+use crate::image_store::{ImageFormat, ImageOptimizationOptions, ImageOptimizationReport, ImageStore};
 use doc_model::DocumentTree;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::io::{Seek, Write};
 
+/// Extension URI of the SVG picture referenced alongside a blip
+const SVG_BLIP_EXT_URI: &str = "{96DAC541-7B7A-43D3-8B79-37D633B846F1}";
+
+/// Namespace of the SVG blip extension
+const SVG_BLIP_NS: &str = "http://schemas.microsoft.com/office/drawing/2016/SVG/main";
+
+/// Relationship IDs of an image's parts
+#[derive(Debug, Clone, Default)]
+pub struct MediaRelationships {
+    /// The picture every reader draws
+    pub blip: String,
+    /// The original SVG, drawn instead of `blip` by readers that support it
+    pub svg: Option<String>,
+}
+
+/// Media written to a package
+#[derive(Debug, Default)]
+pub struct WrittenMedia {
+    /// Relationships of each image by resource ID
+    pub images: HashMap<String, MediaRelationships>,
+    /// Space saved by compressing pictures (empty without compression)
+    pub report: ImageOptimizationReport,
+}
+
 /// Writer for media files (images)
-pub struct MediaWriter;
+#[derive(Default)]
+pub struct MediaWriter {
+    /// Picture compression applied to the written copies
+    optimization: Option<ImageOptimizationOptions>,
+}
 
 impl MediaWriter {
     /// Create a new media writer
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Compress pictures for the package, leaving the image store untouched
+    pub fn with_optimization(mut self, options: ImageOptimizationOptions) -> Self {
+        self.optimization = Some(options);
+        self
     }
 
-    /// Write all media files to the DOCX archive
-    /// Returns a map of relationship IDs to paths
+    /// Write every image the document uses to `word/media/`, with its
+    /// relationship and content type
+    ///
+    /// SVG images are written with a PNG rendering for readers without SVG
+    /// support. Images missing from the store are left out.
     pub fn write_media<W: Write + Seek>(
         &self,
         tree: &DocumentTree,
-        _writer: &mut crate::docx::writer::DocxWriter<W>,
-    ) -> DocxResult<HashMap<String, String>> {
-        let mut relationships = HashMap::new();
-
-        // Collect all images from the document
-        // For now, we don't store image data in the tree, so this is a placeholder
-        // In a full implementation, we would:
-        // 1. Iterate through all ImageNode instances in the tree
-        // 2. Look up the image data from the image store
-        // 3. Write each image to word/media/
-        // 4. Create relationships for each image
-
-        // TODO: Implement when image store integration is complete
-        // for (id, image) in tree.nodes.images.iter() {
-        //     let image_data = image_store.get(&image.resource_id)?;
-        //     let filename = generate_media_filename(&image.resource_id, &image_data.content_type);
-        //     let path = format!("word/media/{}", filename);
-        //     writer.write_binary(&path, &image_data.data)?;
-        //
-        //     let rel_id = writer.doc_rels_mut().add(
-        //         relationship_types::IMAGE,
-        //         &format!("media/{}", filename),
-        //         TargetMode::Internal,
-        //     );
-        //     relationships.insert(image.resource_id.as_str().to_string(), rel_id);
-        // }
-
-        Ok(relationships)
+        images: &ImageStore,
+        writer: &mut crate::docx::writer::DocxWriter<W>,
+    ) -> DocxResult<WrittenMedia> {
+        let mut written = WrittenMedia::default();
+        let optimized;
+        let images = match &self.optimization {
+            Some(options) => {
+                let (store, report) = images.optimized_for_export(tree, options);
+                written.report = report;
+                optimized = store;
+                &optimized
+            }
+            None => images,
+        };
+
+        let resource_ids: BTreeSet<&str> = tree.images().map(|image| image.resource_id.as_str()).collect();
+        for resource_id in resource_ids {
+            let Ok(media) = images.docx_media(&resource_id.into()) else {
+                continue;
+            };
+            let number = written.images.len() + 1;
+            let blip = Self::write_part(writer, number, media.format, &media.data)?;
+            let svg = match media.svg {
+                Some(svg) => Some(Self::write_part(writer, number, ImageFormat::Svg, &svg)?),
+                None => None,
+            };
+            written.images.insert(resource_id.to_string(), MediaRelationships { blip, svg });
+        }
+
+        Ok(written)
+    }
+
+    /// Write one media part, returning its relationship ID
+    fn write_part<W: Write + Seek>(
+        writer: &mut crate::docx::writer::DocxWriter<W>,
+        number: usize,
+        format: ImageFormat,
+        data: &[u8],
+    ) -> DocxResult<String> {
+        let filename = format!("image{}.{}", number, format.extension());
+        writer.write_binary(&format!("word/media/{}", filename), data)?;
+        writer
+            .content_types_mut()
+            .defaults
+            .insert(format.extension().to_string(), format.mime_type().to_string());
+        Ok(writer.doc_rels_mut().add(
+            relationship_types::IMAGE,
+            &format!("media/{}", filename),
+            TargetMode::Internal,
+        ))
     }
 
     /// Generate a filename for a media file based on content type
@@ -95,21 +158,34 @@ impl MediaWriter {
 }
 
 /// Generate a w:drawing element for an inline image
+///
+/// An SVG part is referenced through the `asvg:svgBlip` extension, with the
+/// blip as the fallback picture.
 pub fn generate_inline_drawing(
-    rel_id: &str,
+    media: &MediaRelationships,
+    doc_pr_id: u32,
     width_emu: i64,
     height_emu: i64,
     name: &str,
     alt_text: Option<&str>,
+    decorative: bool,
 ) -> String {
-    let alt = alt_text.unwrap_or(name);
+    let mut doc_pr = String::new();
+    write_doc_pr(&mut doc_pr, doc_pr_id, name, alt_text, decorative);
+    let blip = match &media.svg {
+        Some(svg) => format!(
+            r#"<a:blip r:embed="{}"><a:extLst><a:ext uri="{}"><asvg:svgBlip xmlns:asvg="{}" r:embed="{}"/></a:ext></a:extLst></a:blip>"#,
+            media.blip, SVG_BLIP_EXT_URI, SVG_BLIP_NS, svg
+        ),
+        None => format!(r#"<a:blip r:embed="{}"/>"#, media.blip),
+    };
 
     format!(
         r#"<w:drawing>
     <wp:inline distT="0" distB="0" distL="0" distR="0">
         <wp:extent cx="{}" cy="{}"/>
         <wp:effectExtent l="0" t="0" r="0" b="0"/>
-        <wp:docPr id="1" name="{}" descr="{}"/>
+        {}
         <wp:cNvGraphicFramePr>
             <a:graphicFrameLocks xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" noChangeAspect="1"/>
         </wp:cNvGraphicFramePr>
@@ -121,7 +197,7 @@ pub fn generate_inline_drawing(
                         <pic:cNvPicPr/>
                     </pic:nvPicPr>
                     <pic:blipFill>
-                        <a:blip r:embed="{}"/>
+                        {}
                         <a:stretch>
                             <a:fillRect/>
                         </a:stretch>
@@ -141,9 +217,9 @@ pub fn generate_inline_drawing(
     </wp:inline>
 </w:drawing>"#,
         width_emu, height_emu,
-        escape_xml(name), escape_xml(alt),
+        doc_pr,
         escape_xml(name),
-        rel_id,
+        blip,
         width_emu, height_emu
     )
 }
@@ -198,11 +274,62 @@ mod tests {
         assert_eq!(points_to_emu(36.0), 457200);
     }
 
+    fn png_media() -> MediaRelationships {
+        MediaRelationships {
+            blip: "rId1".to_string(),
+            svg: None,
+        }
+    }
+
     #[test]
     fn test_generate_inline_drawing() {
-        let xml = generate_inline_drawing("rId1", 914400, 914400, "test.png", Some("Test image"));
+        let xml = generate_inline_drawing(&png_media(), 1, 914400, 914400, "test.png", Some("Test image"), false);
         assert!(xml.contains("w:drawing"));
         assert!(xml.contains("wp:inline"));
         assert!(xml.contains("r:embed=\"rId1\""));
+        assert!(!xml.contains("svgBlip"));
+    }
+
+    #[test]
+    fn test_inline_drawing_svg_blip() {
+        use crate::docx::images::ImageParser;
+
+        let media = MediaRelationships {
+            blip: "rId1".to_string(),
+            svg: Some("rId2".to_string()),
+        };
+        let xml = generate_inline_drawing(&media, 1, 914400, 914400, "logo.svg", None, false);
+        assert!(xml.contains("<asvg:svgBlip"));
+        assert!(xml.contains("r:embed=\"rId2\""));
+
+        // Readers without SVG support draw the PNG fallback
+        let parsed = ImageParser::new().parse_drawing(&xml).unwrap().unwrap();
+        assert_eq!(parsed.rel_id.as_deref(), Some("rId1"));
+    }
+
+    #[test]
+    fn test_inline_drawing_alt_text_round_trip() {
+        use crate::docx::images::ImageParser;
+        use crate::docx::parser::ImageData;
+
+        let image_data = ImageData {
+            rel_id: "rId1".to_string(),
+            path: "media/image1.png".to_string(),
+            content_type: "image/png".to_string(),
+            data: Vec::new(),
+        };
+        let parser = ImageParser::new();
+
+        let xml = generate_inline_drawing(&png_media(), 1, 914400, 914400, "chart.png", Some("Sales <2024>"), false);
+        let parsed = parser.parse_drawing(&xml).unwrap().unwrap();
+        let node = parser.create_image_node(&parsed, &image_data);
+        assert_eq!(node.alt_text.as_deref(), Some("Sales <2024>"));
+        assert!(!node.decorative);
+
+        let xml = generate_inline_drawing(&png_media(), 1, 914400, 914400, "border.png", None, true);
+        let parsed = parser.parse_drawing(&xml).unwrap().unwrap();
+        let node = parser.create_image_node(&parsed, &image_data);
+        assert!(node.alt_text.is_none());
+        assert!(node.decorative);
     }
 }
@@ -11,11 +11,16 @@
 //! - `word/document.xml` - Main document content
 //! - `word/styles.xml` - Style definitions
 //! - `word/numbering.xml` - List/numbering definitions
+//! - `word/theme/theme1.xml` - Theme colors, fonts, and effects
 //! - `word/_rels/document.xml.rels` - Document relationships
 //! - `word/media/` - Embedded images and media
 //! - `word/footnotes.xml` - Footnotes content
 //! - `word/endnotes.xml` - Endnotes content
 //! - `word/comments.xml` - Comments content
+//! - `word/settings.xml` - Document settings (tab stop, hyphenation, protection, compatibility)
+//! - `word/header1.xml`, ... - Section headers (watermarks)
+//! - `docProps/app.xml` - Extended properties (hyperlink base)
+//! - `word/glossary/document.xml` - Building blocks (Quick Parts)
 //!
 //! ## Phase 2 Features
 //!
@@ -27,6 +32,7 @@
 //! - Advanced tables (cell merging, row breaks, nested tables)
 //! - Text boxes and shapes
 //! - Shape groups and connectors
+//! - Unrecognized body, paragraph, and paragraph property markup kept for round trips
 
 mod error;
 mod reader;
@@ -57,9 +63,24 @@ mod tables_io;
 mod fidelity;
 mod content_controls;
 mod content_controls_writer;
+mod theme_io;
+mod tabs_io;
+mod ruby_io;
+mod text_effects_io;
+mod sections_io;
+mod ink_io;
+mod app_props;
+mod settings_io;
+mod embedded_objects_io;
+mod preserved_io;
+mod glossary_io;
+mod alt_text_io;
 
 pub use error::{DocxError, DocxResult};
-pub use api::{import_docx, export_docx, import_docx_bytes, export_docx_bytes};
+pub use api::{
+    import_docx, export_docx, import_docx_bytes, export_docx_bytes, export_docx_with_images,
+    export_docx_bytes_with_images,
+};
 pub use api::{FileFormat, get_supported_formats, get_import_formats, get_export_formats};
 
 // Re-export Phase 2 types for external use
@@ -78,6 +99,9 @@ pub use content_controls::{
     ParsedDataBinding, ParsedListItem, LockSettings, CheckboxState,
 };
 pub use content_controls_writer::ContentControlWriter;
+pub use theme_io::{ThemeParser, ThemeWriter};
+pub use ink_io::{InkParser, InkWriter};
+pub use embedded_objects_io::{embedded_object_warnings, ObjectParser, ObjectWriter, ParsedObject};
 
 /// XML namespaces used in DOCX files
 pub mod namespaces {
@@ -97,6 +121,12 @@ pub mod namespaces {
     pub const PIC: &str = "http://schemas.openxmlformats.org/drawingml/2006/picture";
     /// VML namespace
     pub const V: &str = "urn:schemas-microsoft-com:vml";
+    /// Office VML extensions namespace (OLE objects)
+    pub const O: &str = "urn:schemas-microsoft-com:office:office";
+    /// Word 2010 extensions namespace (text effects)
+    pub const W14: &str = "http://schemas.microsoft.com/office/word/2010/wordml";
+    /// Markup compatibility namespace
+    pub const MC: &str = "http://schemas.openxmlformats.org/markup-compatibility/2006";
 }
 
 /// Relationship types used in DOCX
@@ -115,6 +145,11 @@ pub mod relationship_types {
     pub const THEME: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/theme";
     pub const FONT_TABLE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/fontTable";
     pub const WEB_SETTINGS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/webSettings";
+    pub const CUSTOM_XML: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/customXml";
+    pub const EXTENDED_PROPERTIES: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/extended-properties";
+    pub const OLE_OBJECT: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/oleObject";
+    pub const PACKAGE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/package";
+    pub const GLOSSARY_DOCUMENT: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/glossaryDocument";
 }
 
 /// Content types for DOCX parts
@@ -130,6 +165,10 @@ pub mod content_type_values {
     pub const THEME: &str = "application/vnd.openxmlformats-officedocument.theme+xml";
     pub const FONT_TABLE: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.fontTable+xml";
     pub const WEB_SETTINGS: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.webSettings+xml";
+    pub const HEADER: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.header+xml";
+    pub const INKML: &str = "application/inkml+xml";
+    pub const EXTENDED_PROPERTIES: &str = "application/vnd.openxmlformats-officedocument.extended-properties+xml";
+    pub const GLOSSARY: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.document.glossary+xml";
 }
 
 #[cfg(test)]
@@ -96,6 +96,11 @@ impl NumberingWriter {
             NumberFormat::Ordinal => "ordinal",
             NumberFormat::CardinalText => "cardinalText",
             NumberFormat::OrdinalText => "ordinalText",
+            NumberFormat::Hebrew => "hebrew1",
+            NumberFormat::ArabicIndic => "hindiNumbers",
+            NumberFormat::ChineseCounting => "chineseCounting",
+            NumberFormat::JapaneseCounting => "japaneseCounting",
+            NumberFormat::Thai => "thaiNumbers",
         };
         xml.push_str(&format!(r#"<w:numFmt w:val="{}"/>"#, fmt));
 
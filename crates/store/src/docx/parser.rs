@@ -2,16 +2,22 @@
 //!
 //! This module coordinates parsing of all DOCX parts and builds the DocumentTree.
 
+use crate::docx::app_props::parse_hyperlink_base;
 use crate::docx::content_types::ContentTypes;
 use crate::docx::document::DocumentParser;
 use crate::docx::error::{DocxError, DocxResult};
+use crate::docx::footnotes_io::NotesParser;
+use crate::docx::glossary_io::parse_glossary;
 use crate::docx::images::ImageParser;
 use crate::docx::lists::NumberingParser;
 use crate::docx::reader::DocxReader;
-use crate::docx::relationships::Relationships;
-use crate::docx::relationship_types;
+use crate::docx::relationships::{Relationships, TargetMode};
+use crate::docx::{content_type_values, relationship_types};
+use crate::docx::sections_io::WatermarkParser;
+use crate::docx::settings_io::parse_settings;
 use crate::docx::styles::StylesParser;
-use doc_model::DocumentTree;
+use crate::docx::theme_io::{ThemeParser, THEME_PART};
+use doc_model::{DocumentTree, Node, Section};
 use std::collections::HashMap;
 use std::io::{Read, Seek};
 
@@ -30,10 +36,26 @@ pub struct ParsedDocx {
     pub styles_xml: Option<String>,
     /// Raw numbering.xml content (if present)
     pub numbering_xml: Option<String>,
+    /// Raw theme1.xml content (if present)
+    pub theme_xml: Option<String>,
+    /// Raw endnotes.xml content (if present)
+    pub endnotes_xml: Option<String>,
     /// Image data keyed by relationship ID
     pub images: HashMap<String, ImageData>,
+    /// Embedded object parts keyed by relationship ID
+    pub embeddings: HashMap<String, ImageData>,
     /// External hyperlink targets keyed by relationship ID
     pub hyperlinks: HashMap<String, String>,
+    /// Raw header part content keyed by relationship ID
+    pub headers: HashMap<String, String>,
+    /// Raw InkML part content keyed by relationship ID
+    pub inks: HashMap<String, String>,
+    /// Raw docProps/app.xml content (if present)
+    pub app_xml: Option<String>,
+    /// Raw settings.xml content (if present)
+    pub settings_xml: Option<String>,
+    /// Raw glossary document content (if present)
+    pub glossary_xml: Option<String>,
 }
 
 /// Image data from the DOCX
@@ -124,6 +146,32 @@ impl DocxParser {
             None
         };
 
+        // Parse theme1.xml (if exists)
+        let theme_xml = if let Some(theme_rel) = doc_rels.get_by_type(relationship_types::THEME) {
+            let path = format!("word/{}", theme_rel.target);
+            if docx.file_exists(&path) {
+                Some(docx.read_file_as_string(&path)?)
+            } else {
+                None
+            }
+        } else if docx.file_exists(THEME_PART) {
+            Some(docx.read_file_as_string(THEME_PART)?)
+        } else {
+            None
+        };
+
+        // Parse endnotes.xml (if exists)
+        let endnotes_xml = if let Some(endnotes_rel) = doc_rels.get_by_type(relationship_types::ENDNOTES) {
+            let path = format!("word/{}", endnotes_rel.target);
+            if docx.file_exists(&path) {
+                Some(docx.read_file_as_string(&path)?)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
         // Load images
         let mut images = HashMap::new();
         for rel in doc_rels.get_all_by_type(relationship_types::IMAGE) {
@@ -148,12 +196,78 @@ impl DocxParser {
             }
         }
 
+        // Load embedded object parts (linked objects have external targets)
+        let mut embeddings = HashMap::new();
+        let object_rels = doc_rels
+            .get_all_by_type(relationship_types::OLE_OBJECT)
+            .into_iter()
+            .chain(doc_rels.get_all_by_type(relationship_types::PACKAGE));
+        for rel in object_rels.filter(|rel| rel.target_mode == TargetMode::Internal) {
+            let path = format!("word/{}", rel.target);
+            if docx.file_exists(&path) {
+                let data = docx.read_file_as_bytes(&path)?;
+                let content_type = content_types.get_content_type(&path)
+                    .cloned()
+                    .unwrap_or_else(|| "application/octet-stream".to_string());
+
+                embeddings.insert(rel.id.clone(), ImageData {
+                    rel_id: rel.id.clone(),
+                    path,
+                    data,
+                    content_type,
+                });
+            }
+        }
+
         // Collect hyperlink targets
         let mut hyperlinks = HashMap::new();
         for rel in doc_rels.get_all_by_type(relationship_types::HYPERLINK) {
             hyperlinks.insert(rel.id.clone(), rel.target.clone());
         }
 
+        // Read headers (section watermarks live there)
+        let mut headers = HashMap::new();
+        for rel in doc_rels.get_all_by_type(relationship_types::HEADER) {
+            let path = format!("word/{}", rel.target);
+            if docx.file_exists(&path) {
+                headers.insert(rel.id.clone(), docx.read_file_as_string(&path)?);
+            }
+        }
+
+        // Read InkML parts (ink annotations)
+        let mut inks = HashMap::new();
+        for rel in doc_rels.get_all_by_type(relationship_types::CUSTOM_XML) {
+            let path = format!("word/{}", rel.target);
+            let is_inkml = content_types.get_content_type(&path).is_some_and(|ct| ct == content_type_values::INKML);
+            if is_inkml && docx.file_exists(&path) {
+                inks.insert(rel.id.clone(), docx.read_file_as_string(&path)?);
+            }
+        }
+
+        // Read extended properties (hyperlink base)
+        let app_xml = match root_rels.get_by_type(relationship_types::EXTENDED_PROPERTIES) {
+            Some(rel) if docx.file_exists(rel.target.trim_start_matches('/')) => {
+                Some(docx.read_file_as_string(rel.target.trim_start_matches('/'))?)
+            }
+            _ => None,
+        };
+
+        // Read document settings
+        let settings_xml = match doc_rels.get_by_type(relationship_types::SETTINGS) {
+            Some(rel) if docx.file_exists(&format!("word/{}", rel.target)) => {
+                Some(docx.read_file_as_string(&format!("word/{}", rel.target))?)
+            }
+            _ => None,
+        };
+
+        // Read the glossary document (building blocks)
+        let glossary_xml = match doc_rels.get_by_type(relationship_types::GLOSSARY_DOCUMENT) {
+            Some(rel) if docx.file_exists(&format!("word/{}", rel.target)) => {
+                Some(docx.read_file_as_string(&format!("word/{}", rel.target))?)
+            }
+            _ => None,
+        };
+
         Ok(ParsedDocx {
             content_types,
             root_rels,
@@ -161,8 +275,16 @@ impl DocxParser {
             document_xml,
             styles_xml,
             numbering_xml,
+            theme_xml,
+            endnotes_xml,
             images,
+            embeddings,
             hyperlinks,
+            headers,
+            inks,
+            app_xml,
+            settings_xml,
+            glossary_xml,
         })
     }
 
@@ -170,6 +292,18 @@ impl DocxParser {
     fn build_tree(parsed: ParsedDocx) -> DocxResult<DocumentTree> {
         let mut tree = DocumentTree::new();
 
+        if let Some(ref app_xml) = parsed.app_xml {
+            tree.document.metadata.hyperlink_base = parse_hyperlink_base(app_xml)?;
+        }
+        if let Some(ref settings_xml) = parsed.settings_xml {
+            parse_settings(settings_xml, &mut tree.document)?;
+        }
+
+        // Parse the theme (styles and runs may reference its colors and fonts)
+        if let Some(ref theme_xml) = parsed.theme_xml {
+            tree.theme = ThemeParser::new().parse(theme_xml)?;
+        }
+
         // Parse styles first (needed for document parsing)
         if let Some(ref styles_xml) = parsed.styles_xml {
             let styles_parser = StylesParser::new();
@@ -192,9 +326,47 @@ impl DocxParser {
             }
         }
 
+        // Parse endnotes (the document's references pick them up)
+        let endnotes = match &parsed.endnotes_xml {
+            Some(endnotes_xml) => NotesParser::new().parse_endnotes_xml(endnotes_xml)?,
+            None => Vec::new(),
+        };
+
         // Parse the main document
-        let doc_parser = DocumentParser::new(&parsed.doc_rels, &parsed.hyperlinks);
-        doc_parser.parse(&parsed.document_xml, &mut tree)?;
+        let doc_parser = DocumentParser::new(&parsed.doc_rels, &parsed.hyperlinks)
+            .with_endnotes(&endnotes)
+            .with_inks(&parsed.inks)
+            .with_object_parts(&parsed.images, &parsed.embeddings);
+        let sections = doc_parser.parse(&parsed.document_xml, &mut tree)?;
+
+        // Build sections from the section breaks; a section without its own
+        // header uses the previous section's
+        let blocks = tree.document.children().to_vec();
+        let watermark_parser = WatermarkParser::new();
+        let mut start = 0;
+        for (index, parsed_section) in sections.iter().enumerate() {
+            let mut section = Section::new();
+            section.page_setup.section_start = parsed_section.start;
+            section.page_setup.line_numbering = parsed_section.line_numbering.clone();
+            section.page_setup.page_numbering = parsed_section.page_numbering;
+            section.page_setup.doc_grid = parsed_section.doc_grid;
+            section.different_odd_even = tree.document.settings.even_and_odd_headers;
+            match parsed_section.header_rel_id.as_ref().and_then(|id| parsed.headers.get(id)) {
+                Some(header_xml) => section.watermark = watermark_parser.parse(header_xml)?,
+                None => section.link_to_previous = index > 0,
+            }
+            let end = parsed_section.end_block.clamp(start, blocks.len());
+            for &block_id in &blocks[start..end] {
+                section.add_child(block_id);
+            }
+            start = end;
+            let section_id = section.id();
+            tree.add_section(section);
+            if let Some(props) = &parsed_section.endnote_props {
+                tree.set_endnote_properties(Some(section_id), props.clone());
+            }
+        }
+        tree.renumber_endnotes();
 
         // Process images
         let image_parser = ImageParser::new();
@@ -202,6 +374,12 @@ impl DocxParser {
             image_parser.process_image(rel_id, image_data, &mut tree)?;
         }
 
+        if let Some(ref glossary_xml) = parsed.glossary_xml {
+            for block in parse_glossary(glossary_xml)? {
+                tree.building_blocks.add(block);
+            }
+        }
+
         Ok(tree)
     }
 }
@@ -220,8 +398,16 @@ mod tests {
             document_xml: String::new(),
             styles_xml: None,
             numbering_xml: None,
+            theme_xml: None,
+            endnotes_xml: None,
             images: HashMap::new(),
+            embeddings: HashMap::new(),
             hyperlinks: HashMap::new(),
+            headers: HashMap::new(),
+            inks: HashMap::new(),
+            app_xml: None,
+            settings_xml: None,
+            glossary_xml: None,
         };
 
         assert!(parsed.styles_xml.is_none());
@@ -0,0 +1,181 @@
+//! Preservation of unrecognized WordprocessingML
+//!
+//! Body-level elements, paragraph children, and paragraph properties the
+//! parser doesn't understand are captured verbatim into the document's
+//! [`PreservedMarkup`] and written back in the same place on export, along
+//! with the paragraph's own attributes and the namespaces the markup uses.
+
+use crate::docx::reader::XmlParser;
+use doc_model::PreservedMarkup;
+use quick_xml::events::BytesStart;
+
+/// Paragraph properties the parser reads and the writer regenerates
+const KNOWN_PARAGRAPH_PROPERTIES: &[&str] = &[
+    "pStyle",
+    "keepNext",
+    "keepLines",
+    "pageBreakBefore",
+    "suppressLineNumbers",
+    "bidi",
+    "tabs",
+    "spacing",
+    "ind",
+    "jc",
+    "sectPr",
+];
+
+/// Containers whose content is parsed as if they weren't there
+const TRANSPARENT_ELEMENTS: &[&str] = &[
+    "ins",
+    "del",
+    "moveFrom",
+    "moveTo",
+    "smartTag",
+    "customXml",
+    "sdt",
+    "sdtContent",
+    "fldSimple",
+];
+
+/// Elements that are dropped rather than preserved: proofing marks, and
+/// comment anchors, which would dangle without the comments part
+const DROPPED_ELEMENTS: &[&str] = &["proofErr", "commentRangeStart", "commentRangeEnd"];
+
+/// Namespace prefixes the document writer always declares
+const WRITER_PREFIXES: &[&str] = &["w", "r", "wp", "a", "w14", "mc"];
+
+/// Check if a paragraph property element is read by the parser
+pub fn is_known_paragraph_property(name: &[u8]) -> bool {
+    KNOWN_PARAGRAPH_PROPERTIES.iter().any(|known| XmlParser::matches_element(name, known))
+}
+
+/// Check if an element's content is parsed through it
+pub fn is_transparent(name: &[u8]) -> bool {
+    TRANSPARENT_ELEMENTS.iter().any(|known| XmlParser::matches_element(name, known))
+}
+
+/// Check if an element is dropped instead of preserved
+pub fn is_dropped(name: &[u8]) -> bool {
+    DROPPED_ELEMENTS.iter().any(|known| XmlParser::matches_element(name, known))
+}
+
+/// Record the namespaces and ignorable prefixes declared on `w:document`
+pub fn read_root_namespaces(e: &BytesStart, preserved: &mut PreservedMarkup) {
+    for attr in e.attributes().filter_map(|a| a.ok()) {
+        let Ok(value) = attr.unescape_value() else {
+            continue;
+        };
+        let key = String::from_utf8_lossy(attr.key.as_ref());
+        if let Some(prefix) = key.strip_prefix("xmlns:") {
+            preserved.declare_namespace(prefix, value.as_ref());
+        } else if key == "mc:Ignorable" {
+            for prefix in value.split_whitespace() {
+                preserved.add_ignorable(prefix);
+            }
+        }
+    }
+}
+
+/// An element's attributes other than namespace declarations, unescaped
+pub fn element_attributes(e: &BytesStart) -> Vec<(String, String)> {
+    e.attributes()
+        .filter_map(|a| a.ok())
+        .filter(|a| !a.key.as_ref().starts_with(b"xmlns"))
+        .filter_map(|a| {
+            let value = a.unescape_value().ok()?.into_owned();
+            Some((String::from_utf8_lossy(a.key.as_ref()).into_owned(), value))
+        })
+        .collect()
+}
+
+/// Write preserved attributes into an element's start tag
+pub fn write_attributes(xml: &mut String, attributes: &[(String, String)]) {
+    for (name, value) in attributes {
+        xml.push_str(&format!(r#" {}="{}""#, name, escape_attr(value)));
+    }
+}
+
+/// Namespace declarations for `w:document`: the writer's own, plus those
+/// the preserved markup needs, and the matching `mc:Ignorable` list
+pub fn root_namespace_attributes(preserved: &PreservedMarkup) -> String {
+    let extra: Vec<(&String, &String)> = if preserved.is_empty() {
+        Vec::new()
+    } else {
+        preserved
+            .namespaces()
+            .iter()
+            .filter(|(prefix, _)| !WRITER_PREFIXES.contains(&prefix.as_str()))
+            .collect()
+    };
+
+    let mut xml = String::new();
+    for (prefix, uri) in &extra {
+        xml.push_str(&format!(r#" xmlns:{}="{}""#, prefix, escape_attr(uri)));
+    }
+    let mut ignorable = vec!["w14"];
+    ignorable.extend(
+        preserved
+            .ignorable()
+            .iter()
+            .map(String::as_str)
+            .filter(|prefix| *prefix != "w14" && extra.iter().any(|(declared, _)| declared.as_str() == *prefix)),
+    );
+    xml.push_str(&format!(r#" mc:Ignorable="{}""#, ignorable.join(" ")));
+    xml
+}
+
+/// Escape a string for an XML attribute value
+fn escape_attr(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::docx::document::DocumentParser;
+    use crate::docx::document_writer::DocumentWriter;
+    use crate::docx::relationships::Relationships;
+    use doc_model::DocumentTree;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_unknown_markup_round_trip() {
+        let content = r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+            xmlns:w14="http://schemas.microsoft.com/office/word/2010/wordml"
+            xmlns:w15="http://schemas.microsoft.com/office/word/2012/wordml"
+            xmlns:mc="http://schemas.openxmlformats.org/markup-compatibility/2006" mc:Ignorable="w14 w15">
+            <w:body>
+                <w:bookmarkStart w:id="0" w:name="Top"/>
+                <w:p w14:paraId="1A2B3C4D">
+                    <w:pPr><w:framePr w:w="2000"/><w:tabs><w:tab w:val="left" w:pos="720"/></w:tabs></w:pPr>
+                    <w:r><w:t>Hello</w:t></w:r>
+                    <w:proofErr w:type="spellStart"/>
+                    <w15:mark w15:val="a &amp; b"><w15:inner/></w15:mark>
+                    <w:r><w:t>world</w:t></w:r>
+                </w:p>
+                <w:bookmarkEnd w:id="0"/>
+                <w:sectPr/>
+            </w:body>
+        </w:document>"#;
+
+        let rels = Relationships::new();
+        let hyperlinks = HashMap::new();
+        let mut tree = DocumentTree::new();
+        DocumentParser::new(&rels, &hyperlinks).parse(content, &mut tree).unwrap();
+        let para = tree.nodes.paragraphs.values().next().unwrap();
+        assert_eq!(para.direct_formatting.tab_stops.len(), 1);
+
+        let xml = DocumentWriter::new().write(&tree).unwrap();
+        assert!(xml.contains(r#"xmlns:w15="http://schemas.microsoft.com/office/word/2012/wordml""#));
+        assert!(xml.contains(r#"mc:Ignorable="w14 w15""#));
+        assert!(xml.contains(r#"<w:bookmarkStart w:id="0" w:name="Top"/><w:p w14:paraId="1A2B3C4D">"#));
+        assert!(xml.contains(r#"<w:framePr w:w="2000"/>"#));
+        assert!(xml.contains(r#"<w15:mark w15:val="a &amp; b"><w15:inner/></w15:mark>"#));
+        assert!(xml.find("Hello").unwrap() < xml.find("<w15:mark").unwrap());
+        assert!(xml.find("<w15:mark").unwrap() < xml.find("world").unwrap());
+        assert!(xml.contains(r#"</w:p><w:bookmarkEnd w:id="0"/>"#));
+        assert!(!xml.contains("proofErr"));
+    }
+}
@@ -0,0 +1,84 @@
+//! Ruby (w:ruby) reading and writing
+//!
+//! A run with ruby is written as a `<w:r>` holding a `<w:ruby>`: the
+//! `<w:rubyPr>` settings (alignment, ruby and base sizes, raise, language),
+//! `<w:rt>` with the ruby text's runs and `<w:rubyBase>` with the base
+//! text's runs. Sizes are in half-points.
+
+use crate::docx::reader::XmlParser;
+use doc_model::{RubyAlignment, RubyAnnotation};
+use quick_xml::events::BytesStart;
+
+/// Read a child of `<w:rubyPr>`
+pub(crate) fn read_ruby_property(e: &BytesStart, ruby: &mut RubyAnnotation) {
+    let name = e.name();
+    let name_ref = name.as_ref();
+    let Some(val) = XmlParser::get_w_attribute(e, "val") else {
+        return;
+    };
+
+    if XmlParser::matches_element(name_ref, "rubyAlign") {
+        ruby.alignment = match val.as_str() {
+            "distributeLetter" => RubyAlignment::DistributeLetter,
+            "distributeSpace" => RubyAlignment::DistributeSpace,
+            "left" => RubyAlignment::Left,
+            "right" => RubyAlignment::Right,
+            "rightVertical" => RubyAlignment::RightVertical,
+            _ => RubyAlignment::Center,
+        };
+    } else if XmlParser::matches_element(name_ref, "hps") {
+        ruby.font_size = XmlParser::parse_half_points(&val);
+    } else if XmlParser::matches_element(name_ref, "hpsRaise") {
+        ruby.raise = XmlParser::parse_half_points(&val);
+    } else if XmlParser::matches_element(name_ref, "lid") {
+        ruby.language = Some(val);
+    }
+}
+
+/// Write a `<w:ruby>` over base text already written as runs
+///
+/// The sizes Word requires are filled in from the base font size when the
+/// annotation leaves them unset.
+pub(crate) fn write_ruby(xml: &mut String, ruby: &RubyAnnotation, base_font_size: f32, base_runs: &str) {
+    let alignment = match ruby.alignment {
+        RubyAlignment::Center => "center",
+        RubyAlignment::DistributeLetter => "distributeLetter",
+        RubyAlignment::DistributeSpace => "distributeSpace",
+        RubyAlignment::Left => "left",
+        RubyAlignment::Right => "right",
+        RubyAlignment::RightVertical => "rightVertical",
+    };
+    let half_points = |points: f32| (points * 2.0).round() as i64;
+    let ruby_size = half_points(ruby.effective_font_size(base_font_size));
+
+    xml.push_str("<w:ruby><w:rubyPr>");
+    xml.push_str(&format!(r#"<w:rubyAlign w:val="{}"/>"#, alignment));
+    xml.push_str(&format!(r#"<w:hps w:val="{}"/>"#, ruby_size));
+    xml.push_str(&format!(
+        r#"<w:hpsRaise w:val="{}"/>"#,
+        half_points(ruby.raise.unwrap_or(base_font_size))
+    ));
+    xml.push_str(&format!(r#"<w:hpsBaseText w:val="{}"/>"#, half_points(base_font_size)));
+    if let Some(language) = &ruby.language {
+        xml.push_str(&format!(r#"<w:lid w:val="{}"/>"#, escape_xml(language)));
+    }
+    xml.push_str("</w:rubyPr>");
+
+    xml.push_str(&format!(
+        r#"<w:rt><w:r><w:rPr><w:sz w:val="{0}"/><w:szCs w:val="{0}"/></w:rPr><w:t>{1}</w:t></w:r></w:rt>"#,
+        ruby_size,
+        escape_xml(&ruby.text)
+    ));
+    xml.push_str("<w:rubyBase>");
+    xml.push_str(base_runs);
+    xml.push_str("</w:rubyBase></w:ruby>");
+}
+
+/// Escape special XML characters
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,620 @@
+//! Section Properties and Watermarks for DOCX
+//!
+//! Handles `w:sectPr` (how each section starts, its line and page
+//! numbering, document grid and endnote settings, and which header it
+//! uses) and section watermarks. Word keeps a watermark in the section's header
+//! as a VML shape: a text path for text watermarks, image data for
+//! picture watermarks.
+
+use crate::docx::error::{DocxError, DocxResult};
+use crate::docx::namespaces;
+use crate::docx::reader::XmlParser;
+use doc_model::{
+    DocumentGrid, DocumentGridType, EndnotePosition, EndnoteProperties, LineNumberRestart, LineNumbering, NumberingScheme, PageNumberFormat, PageNumbering, RestartNumbering, PictureWatermark, ResourceId, Section, SectionBreakType, SectionPageSetup, TextWatermark,
+    Watermark, WatermarkLayout,
+};
+use quick_xml::events::{BytesStart, Event};
+
+/// Units of `w:charSpace` per point
+const CHAR_SPACE_PER_POINT: f32 = 4096.0;
+
+/// Washout image adjustments Word applies to picture watermarks
+const WASHOUT_GAIN: &str = "19661f";
+const WASHOUT_BLACK_LEVEL: &str = "22938f";
+
+/// Path of the header part for the nth section watermark
+pub fn header_part(number: usize) -> String {
+    format!("word/header{}.xml", number)
+}
+
+// =============================================================================
+// Section Properties
+// =============================================================================
+
+/// Section properties read from a `w:sectPr`
+#[derive(Debug, Clone, Default)]
+pub struct ParsedSection {
+    /// How the section starts
+    pub start: SectionBreakType,
+    /// Line numbering from `w:lnNumType`
+    pub line_numbering: LineNumbering,
+    /// Page numbering from `w:pgNumType`
+    pub page_numbering: PageNumbering,
+    /// Document grid from `w:docGrid`
+    pub doc_grid: DocumentGrid,
+    /// Endnote settings from `w:endnotePr`
+    pub endnote_props: Option<EndnoteProperties>,
+    /// Relationship ID of the section's default header
+    pub header_rel_id: Option<String>,
+    /// Index one past the section's last body block
+    pub end_block: usize,
+    /// Whether note settings being read are the endnotes' (the same
+    /// elements also appear in `w:footnotePr`)
+    pub(crate) in_endnote_props: bool,
+}
+
+impl ParsedSection {
+    /// Read a child element of `w:sectPr`
+    pub fn read_property(&mut self, e: &BytesStart) {
+        let name = e.name();
+        let name_ref = name.as_ref();
+
+        if XmlParser::matches_element(name_ref, "type") {
+            self.start = match XmlParser::get_w_attribute(e, "val").as_deref() {
+                Some("continuous") => SectionBreakType::Continuous,
+                Some("evenPage") => SectionBreakType::EvenPage,
+                Some("oddPage") => SectionBreakType::OddPage,
+                _ => SectionBreakType::NextPage,
+            };
+        } else if XmlParser::matches_element(name_ref, "lnNumType") {
+            self.line_numbering = read_line_numbering(e);
+        } else if XmlParser::matches_element(name_ref, "pgNumType") {
+            self.page_numbering = read_page_numbering(e);
+        } else if XmlParser::matches_element(name_ref, "docGrid") {
+            self.doc_grid = read_doc_grid(e);
+        } else if XmlParser::matches_element(name_ref, "endnotePr") {
+            self.in_endnote_props = true;
+            self.endnote_props.get_or_insert_with(EndnoteProperties::default);
+        } else if XmlParser::matches_element(name_ref, "footnotePr") {
+            self.in_endnote_props = false;
+        } else if XmlParser::matches_element(name_ref, "headerReference") {
+            let header_type = XmlParser::get_w_attribute(e, "type");
+            if header_type.as_deref().unwrap_or("default") == "default" {
+                self.header_rel_id = XmlParser::get_r_attribute(e, "id");
+            }
+        } else if let (true, Some(props)) = (self.in_endnote_props, self.endnote_props.as_mut()) {
+            read_endnote_property(e, props);
+        }
+    }
+}
+
+/// Write a `w:sectPr` for a section
+///
+/// `endnote_props` are written as `w:endnotePr` when they differ from the
+/// defaults.
+pub fn write_section_properties(
+    xml: &mut String,
+    section: &Section,
+    header_rel_id: Option<&str>,
+    endnote_props: Option<&EndnoteProperties>,
+) {
+    xml.push_str("<w:sectPr>");
+    if let Some(rel_id) = header_rel_id {
+        xml.push_str(&format!(r#"<w:headerReference w:type="default" r:id="{}"/>"#, rel_id));
+    }
+    if let Some(props) = endnote_props.filter(|props| **props != EndnoteProperties::default()) {
+        write_endnote_properties(xml, props);
+    }
+    let start = match section.page_setup.section_start {
+        SectionBreakType::NextPage => None,
+        SectionBreakType::Continuous => Some("continuous"),
+        SectionBreakType::EvenPage => Some("evenPage"),
+        SectionBreakType::OddPage => Some("oddPage"),
+    };
+    if let Some(start) = start {
+        xml.push_str(&format!(r#"<w:type w:val="{}"/>"#, start));
+    }
+    write_line_numbering(xml, &section.page_setup.line_numbering);
+    write_page_numbering(xml, &section.page_setup.page_numbering);
+    write_doc_grid(xml, &section.page_setup.doc_grid);
+    xml.push_str("</w:sectPr>");
+}
+
+/// Read a child of `w:endnotePr`
+fn read_endnote_property(e: &BytesStart, props: &mut EndnoteProperties) {
+    let name = e.name();
+    let name_ref = name.as_ref();
+    let Some(val) = XmlParser::get_w_attribute(e, "val") else {
+        return;
+    };
+
+    if XmlParser::matches_element(name_ref, "pos") {
+        props.position = match val.as_str() {
+            "sectEnd" => EndnotePosition::EndOfSection,
+            _ => EndnotePosition::EndOfDocument,
+        };
+    } else if XmlParser::matches_element(name_ref, "numFmt") {
+        props.numbering = match val.as_str() {
+            "lowerRoman" => NumberingScheme::LowerRoman,
+            "upperRoman" => NumberingScheme::UpperRoman,
+            "lowerLetter" => NumberingScheme::LowerLetter,
+            "upperLetter" => NumberingScheme::UpperLetter,
+            "chicago" => NumberingScheme::Symbols,
+            "hebrew1" => NumberingScheme::Hebrew,
+            "hindiNumbers" => NumberingScheme::ArabicIndic,
+            "chineseCounting" | "chineseCountingThousand" => NumberingScheme::ChineseCounting,
+            "japaneseCounting" => NumberingScheme::JapaneseCounting,
+            "thaiNumbers" => NumberingScheme::Thai,
+            _ => NumberingScheme::Arabic,
+        };
+    } else if XmlParser::matches_element(name_ref, "numStart") {
+        props.start_at = val.parse().unwrap_or(1);
+    } else if XmlParser::matches_element(name_ref, "numRestart") {
+        props.restart = match val.as_str() {
+            "eachSect" => RestartNumbering::PerSection,
+            _ => RestartNumbering::Continuous,
+        };
+    }
+}
+
+/// Write a `w:endnotePr`
+fn write_endnote_properties(xml: &mut String, props: &EndnoteProperties) {
+    let position = match props.position {
+        EndnotePosition::EndOfSection => "sectEnd",
+        EndnotePosition::EndOfDocument => "docEnd",
+    };
+    let format = match props.numbering {
+        NumberingScheme::Arabic => "decimal",
+        NumberingScheme::LowerRoman => "lowerRoman",
+        NumberingScheme::UpperRoman => "upperRoman",
+        NumberingScheme::LowerLetter => "lowerLetter",
+        NumberingScheme::UpperLetter => "upperLetter",
+        NumberingScheme::Symbols => "chicago",
+        NumberingScheme::Hebrew => "hebrew1",
+        NumberingScheme::ArabicIndic => "hindiNumbers",
+        NumberingScheme::ChineseCounting => "chineseCounting",
+        NumberingScheme::JapaneseCounting => "japaneseCounting",
+        NumberingScheme::Thai => "thaiNumbers",
+    };
+    let restart = match props.restart {
+        RestartNumbering::PerSection => "eachSect",
+        _ => "continuous",
+    };
+    xml.push_str(&format!(
+        r#"<w:endnotePr><w:pos w:val="{}"/><w:numFmt w:val="{}"/><w:numStart w:val="{}"/><w:numRestart w:val="{}"/></w:endnotePr>"#,
+        position, format, props.start_at, restart
+    ));
+}
+
+/// Read a `w:lnNumType`
+///
+/// Numbering is on only when `w:countBy` is set. `w:start` is the number
+/// before the first line, so numbering from 1 is `w:start="0"`.
+fn read_line_numbering(e: &BytesStart) -> LineNumbering {
+    let count_by = XmlParser::get_w_attribute(e, "countBy")
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(0);
+    let start = XmlParser::get_w_attribute(e, "start")
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(0);
+    let restart = match XmlParser::get_w_attribute(e, "restart").as_deref() {
+        Some("newSection") => LineNumberRestart::PerSection,
+        Some("continuous") => LineNumberRestart::Continuous,
+        _ => LineNumberRestart::PerPage,
+    };
+    let mut line_numbering = LineNumbering::new()
+        .with_enabled(count_by > 0)
+        .with_count_by(count_by)
+        .with_start_at(start + 1)
+        .with_restart(restart);
+    if let Some(distance) = XmlParser::get_w_attribute(e, "distance").and_then(|v| XmlParser::parse_twips(&v)) {
+        line_numbering = line_numbering.with_distance(distance);
+    }
+    line_numbering
+}
+
+/// Write a `w:lnNumType` if line numbering is on
+fn write_line_numbering(xml: &mut String, line_numbering: &LineNumbering) {
+    if !line_numbering.enabled {
+        return;
+    }
+    let restart = match line_numbering.restart {
+        LineNumberRestart::PerPage => "newPage",
+        LineNumberRestart::PerSection => "newSection",
+        LineNumberRestart::Continuous => "continuous",
+    };
+    xml.push_str(&format!(
+        r#"<w:lnNumType w:countBy="{}" w:start="{}" w:distance="{}" w:restart="{}"/>"#,
+        line_numbering.effective_count_by(),
+        line_numbering.start_at.saturating_sub(1),
+        (line_numbering.distance_from_text * 20.0).round() as i64,
+        restart
+    ));
+}
+
+/// Read a `w:pgNumType`
+fn read_page_numbering(e: &BytesStart) -> PageNumbering {
+    let format = match XmlParser::get_w_attribute(e, "fmt").as_deref() {
+        Some("lowerRoman") => PageNumberFormat::LowercaseRoman,
+        Some("upperRoman") => PageNumberFormat::UppercaseRoman,
+        Some("lowerLetter") => PageNumberFormat::LowercaseLetter,
+        Some("upperLetter") => PageNumberFormat::UppercaseLetter,
+        Some("hebrew1") => PageNumberFormat::Hebrew,
+        Some("hindiNumbers") => PageNumberFormat::ArabicIndic,
+        Some("chineseCounting" | "chineseCountingThousand") => PageNumberFormat::ChineseCounting,
+        Some("japaneseCounting") => PageNumberFormat::JapaneseCounting,
+        Some("thaiNumbers") => PageNumberFormat::Thai,
+        _ => PageNumberFormat::Arabic,
+    };
+    PageNumbering {
+        format,
+        start_at: XmlParser::get_w_attribute(e, "start").and_then(|v| v.parse().ok()),
+    }
+}
+
+/// Write a `w:pgNumType` if the section numbers its pages other than
+/// continuing in Arabic numerals
+fn write_page_numbering(xml: &mut String, page_numbering: &PageNumbering) {
+    if *page_numbering == PageNumbering::default() {
+        return;
+    }
+    let format = match page_numbering.format {
+        PageNumberFormat::Arabic => "decimal",
+        PageNumberFormat::LowercaseRoman => "lowerRoman",
+        PageNumberFormat::UppercaseRoman => "upperRoman",
+        PageNumberFormat::LowercaseLetter => "lowerLetter",
+        PageNumberFormat::UppercaseLetter => "upperLetter",
+        PageNumberFormat::Hebrew => "hebrew1",
+        PageNumberFormat::ArabicIndic => "hindiNumbers",
+        PageNumberFormat::ChineseCounting => "chineseCounting",
+        PageNumberFormat::JapaneseCounting => "japaneseCounting",
+        PageNumberFormat::Thai => "thaiNumbers",
+    };
+    xml.push_str(&format!(r#"<w:pgNumType w:fmt="{}""#, format));
+    if let Some(start) = page_numbering.start_at {
+        xml.push_str(&format!(r#" w:start="{}""#, start));
+    }
+    xml.push_str("/>");
+}
+
+/// Read a `w:docGrid`
+///
+/// `w:charSpace` is in 4096ths of a point, added to the default font size.
+fn read_doc_grid(e: &BytesStart) -> DocumentGrid {
+    let grid_type = match XmlParser::get_w_attribute(e, "type").as_deref() {
+        Some("lines") => DocumentGridType::Lines,
+        Some("linesAndChars") => DocumentGridType::LinesAndChars,
+        Some("snapToChars") => DocumentGridType::SnapToChars,
+        _ => DocumentGridType::None,
+    };
+    let default = DocumentGrid::default();
+    DocumentGrid {
+        grid_type,
+        line_pitch: XmlParser::get_w_attribute(e, "linePitch")
+            .and_then(|v| XmlParser::parse_twips(&v))
+            .unwrap_or(default.line_pitch),
+        char_space: XmlParser::get_w_attribute(e, "charSpace")
+            .and_then(|v| v.parse::<f32>().ok())
+            .map_or(default.char_space, |v| v / CHAR_SPACE_PER_POINT),
+    }
+}
+
+/// Write a `w:docGrid` if the section has a grid
+fn write_doc_grid(xml: &mut String, grid: &DocumentGrid) {
+    let grid_type = match grid.grid_type {
+        DocumentGridType::None => return,
+        DocumentGridType::Lines => "lines",
+        DocumentGridType::LinesAndChars => "linesAndChars",
+        DocumentGridType::SnapToChars => "snapToChars",
+    };
+    xml.push_str(&format!(
+        r#"<w:docGrid w:type="{}" w:linePitch="{}""#,
+        grid_type,
+        (grid.line_pitch * 20.0).round() as i64
+    ));
+    if grid.grid_type != DocumentGridType::Lines {
+        xml.push_str(&format!(
+            r#" w:charSpace="{}""#,
+            (grid.char_space * CHAR_SPACE_PER_POINT).round() as i64
+        ));
+    }
+    xml.push_str("/>");
+}
+
+// =============================================================================
+// Watermark Parser
+// =============================================================================
+
+/// Parser for watermarks in header parts
+pub struct WatermarkParser;
+
+impl WatermarkParser {
+    /// Create a new watermark parser
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Find the watermark in a header part, if it has one
+    pub fn parse(&self, content: &str) -> DocxResult<Option<Watermark>> {
+        let mut reader = XmlParser::from_string(content);
+        let mut buf = Vec::new();
+        let mut shape: Option<ShapeStyle> = None;
+        let mut watermark = None;
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                    let name = e.name();
+                    let name_ref = name.as_ref();
+
+                    if XmlParser::matches_element(name_ref, "shape") {
+                        shape = Some(ShapeStyle::read(e));
+                    } else if let Some(shape) = shape.as_mut() {
+                        if XmlParser::matches_element(name_ref, "fill") {
+                            if let Some(opacity) = XmlParser::get_attribute(e, b"opacity") {
+                                shape.opacity = parse_fraction(&opacity);
+                            }
+                        } else if XmlParser::matches_element(name_ref, "textpath") {
+                            watermark = Some(shape.text_watermark(e));
+                        } else if XmlParser::matches_element(name_ref, "imagedata") {
+                            watermark = Some(shape.picture_watermark(e));
+                        }
+                    }
+                }
+                Ok(Event::End(ref e)) if XmlParser::matches_element(e.name().as_ref(), "shape") => {
+                    shape = None;
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(DocxError::from(e)),
+                _ => {}
+            }
+            if watermark.is_some() {
+                break;
+            }
+            buf.clear();
+        }
+
+        Ok(watermark)
+    }
+}
+
+impl Default for WatermarkParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Attributes of a VML shape that holds a watermark
+struct ShapeStyle {
+    width: f32,
+    height: f32,
+    rotation: f32,
+    fill_color: Option<String>,
+    opacity: Option<f32>,
+}
+
+impl ShapeStyle {
+    fn read(e: &BytesStart) -> Self {
+        let style = XmlParser::get_attribute(e, b"style").unwrap_or_default();
+        let property = |key: &str| style_property(&style, key);
+        Self {
+            width: property("width").and_then(|v| parse_points(&v)).unwrap_or(0.0),
+            height: property("height").and_then(|v| parse_points(&v)).unwrap_or(0.0),
+            rotation: property("rotation").and_then(|v| v.parse().ok()).unwrap_or(0.0),
+            fill_color: XmlParser::get_attribute(e, b"fillcolor"),
+            opacity: None,
+        }
+    }
+
+    fn text_watermark(&self, e: &BytesStart) -> Watermark {
+        let mut text = TextWatermark::new(attribute(e, b"string").unwrap_or_default());
+        let style = attribute(e, b"style").unwrap_or_default();
+        if let Some(family) = style_property(&style, "font-family") {
+            text = text.with_font_family(family.trim_matches(|c| c == '"' || c == '\''));
+        }
+        // Word writes 1pt for text sized to fit the shape
+        if let Some(size) = style_property(&style, "font-size").and_then(|v| parse_points(&v)) {
+            if size > 1.0 {
+                text = text.with_font_size(size);
+            }
+        }
+        if let Some(color) = &self.fill_color {
+            text = text.with_color(parse_vml_color(color));
+        }
+        text = text.with_transparency(1.0 - self.opacity.unwrap_or(1.0));
+        let layout = if self.rotation.rem_euclid(360.0) == 0.0 {
+            WatermarkLayout::Horizontal
+        } else {
+            WatermarkLayout::Diagonal
+        };
+        Watermark::Text(text.with_layout(layout))
+    }
+
+    fn picture_watermark(&self, e: &BytesStart) -> Watermark {
+        // Image bytes aren't kept in the tree; the title names the resource
+        let resource_id = attribute(e, b"o:title")
+            .or_else(|| XmlParser::get_r_attribute(e, "id"))
+            .unwrap_or_default();
+        let washout = XmlParser::get_attribute(e, b"gain").is_some()
+            && XmlParser::get_attribute(e, b"blacklevel").is_some();
+        Watermark::Picture(
+            PictureWatermark::new(ResourceId::new(resource_id), self.width, self.height)
+                .with_scale(1.0)
+                .with_washout(washout),
+        )
+    }
+}
+
+/// Get an attribute value with entities unescaped
+fn attribute(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .filter_map(|a| a.ok())
+        .find(|a| a.key.as_ref() == name)
+        .and_then(|a| a.unescape_value().ok().map(|value| value.into_owned()))
+}
+
+/// Look up a property in a CSS-style `key:value;key:value` list
+fn style_property(style: &str, key: &str) -> Option<String> {
+    style.split(';').find_map(|declaration| {
+        let (name, value) = declaration.split_once(':')?;
+        (name.trim() == key).then(|| value.trim().to_string())
+    })
+}
+
+/// Parse a VML length (`12pt`, `1in`, `2.54cm`, `100px`) to points
+fn parse_points(value: &str) -> Option<f32> {
+    let split = value.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f32 = number.parse().ok()?;
+    let factor = match unit {
+        "" | "pt" => 1.0,
+        "in" => 72.0,
+        "cm" => 72.0 / 2.54,
+        "mm" => 72.0 / 25.4,
+        "px" => 0.75,
+        _ => return None,
+    };
+    Some(number * factor)
+}
+
+/// Parse a VML fraction (`.5` or `32768f`, in 65536ths)
+fn parse_fraction(value: &str) -> Option<f32> {
+    match value.strip_suffix('f') {
+        Some(fixed) => fixed.parse::<f32>().ok().map(|v| v / 65536.0),
+        None => value.parse().ok(),
+    }
+}
+
+/// Convert a VML color (`silver`, `#c0c0c0`) to a hex string
+fn parse_vml_color(value: &str) -> String {
+    let value = value.split_whitespace().next().unwrap_or(value);
+    match value.to_ascii_lowercase().as_str() {
+        "silver" => "#C0C0C0".to_string(),
+        "gray" | "grey" => "#808080".to_string(),
+        "black" => "#000000".to_string(),
+        "white" => "#FFFFFF".to_string(),
+        "red" => "#FF0000".to_string(),
+        "blue" => "#0000FF".to_string(),
+        hex if hex.starts_with('#') => hex.to_ascii_uppercase(),
+        hex => format!("#{}", hex.to_ascii_uppercase()),
+    }
+}
+
+// =============================================================================
+// Watermark Writer
+// =============================================================================
+
+/// Writer for header parts that carry watermarks
+pub struct WatermarkWriter;
+
+impl WatermarkWriter {
+    /// Create a new watermark writer
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Generate a header part holding a watermark
+    ///
+    /// Without a watermark the header is empty; that still stops the
+    /// section from inheriting the previous section's header.
+    pub fn write_header(&self, watermark: Option<&Watermark>, page_setup: &SectionPageSetup) -> String {
+        let mut xml = String::new();
+        xml.push_str(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#);
+        xml.push('\n');
+        xml.push_str(&format!(
+            r#"<w:hdr xmlns:w="{}" xmlns:r="{}" xmlns:v="{}" xmlns:o="urn:schemas-microsoft-com:office:office">"#,
+            namespaces::W,
+            namespaces::R,
+            namespaces::V,
+        ));
+        xml.push_str("<w:p>");
+        if let Some(watermark) = watermark {
+            xml.push_str("<w:r><w:pict>");
+            match watermark {
+                Watermark::Text(text) => write_text_shape(&mut xml, text, page_setup),
+                Watermark::Picture(picture) => write_picture_shape(&mut xml, picture, page_setup),
+            }
+            xml.push_str("</w:pict></w:r>");
+        }
+        xml.push_str("</w:p>");
+        xml.push_str("</w:hdr>");
+        xml
+    }
+}
+
+impl Default for WatermarkWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Style of a watermark shape centered on the page margins
+fn shape_style(width: f32, height: f32, rotation: Option<u32>) -> String {
+    let rotation = rotation.map(|r| format!("rotation:{};", r)).unwrap_or_default();
+    format!(
+        "position:absolute;margin-left:0;margin-top:0;width:{:.2}pt;height:{:.2}pt;{}z-index:-251657216;\
+         mso-position-horizontal:center;mso-position-horizontal-relative:margin;\
+         mso-position-vertical:center;mso-position-vertical-relative:margin",
+        width, height, rotation
+    )
+}
+
+fn write_text_shape(xml: &mut String, text: &TextWatermark, page_setup: &SectionPageSetup) {
+    // Text is stretched to fill the shape, so size it to the text
+    let chars = text.text.chars().count().max(1) as f32;
+    let font_size = text.font_size.unwrap_or_else(|| {
+        let available = match text.layout {
+            WatermarkLayout::Diagonal => page_setup.content_width().hypot(page_setup.content_height()) * 0.7,
+            WatermarkLayout::Horizontal => page_setup.content_width() * 0.9,
+        };
+        available / (chars * 0.6)
+    });
+    let rotation = (text.layout == WatermarkLayout::Diagonal).then_some(315);
+    xml.push_str(&format!(
+        r##"<v:shape id="PowerPlusWaterMarkObject" type="#_x0000_t136" style="{}" o:allowincell="f" fillcolor="{}" stroked="f">"##,
+        shape_style(chars * font_size * 0.6, font_size, rotation),
+        escape_xml_attr(&text.color),
+    ));
+    xml.push_str(&format!(r#"<v:fill opacity="{}"/>"#, 1.0 - text.transparency));
+    let size = match text.font_size {
+        Some(size) => format!("{}pt", size),
+        None => "1pt".to_string(),
+    };
+    xml.push_str(&format!(
+        r#"<v:textpath style="font-family:&quot;{}&quot;;font-size:{}" string="{}"/>"#,
+        escape_xml_attr(&text.font_family),
+        size,
+        escape_xml_attr(&text.text),
+    ));
+    xml.push_str("</v:shape>");
+}
+
+fn write_picture_shape(xml: &mut String, picture: &PictureWatermark, page_setup: &SectionPageSetup) {
+    // Word stores the final size; fit-to-page is resolved against the
+    // section's content area
+    let scale = picture.scale.unwrap_or_else(|| {
+        if picture.width > 0.0 && picture.height > 0.0 {
+            (page_setup.content_width() / picture.width).min(page_setup.content_height() / picture.height)
+        } else {
+            1.0
+        }
+    });
+    xml.push_str(&format!(
+        r##"<v:shape id="WordPictureWatermark" type="#_x0000_t75" style="{}" o:allowincell="f">"##,
+        shape_style(picture.width * scale, picture.height * scale, None),
+    ));
+    xml.push_str(&format!(r#"<v:imagedata o:title="{}""#, escape_xml_attr(picture.resource_id.as_str())));
+    if picture.washout {
+        xml.push_str(&format!(r#" gain="{}" blacklevel="{}""#, WASHOUT_GAIN, WASHOUT_BLACK_LEVEL));
+    }
+    xml.push_str("/>");
+    xml.push_str("</v:shape>");
+}
+
+/// Escape XML attribute value
+fn escape_xml_attr(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
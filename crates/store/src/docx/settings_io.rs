@@ -0,0 +1,296 @@
+//! Document settings part
+//!
+//! `word/settings.xml` holds document-wide settings: change tracking,
+//! editing restrictions, the default tab stop, hyphenation, even/odd
+//! headers, compatibility options, and the document variables
+//! (`w:docVars`). Other settings are regenerated by Word on save.
+
+use crate::docx::error::{DocxError, DocxResult};
+use crate::docx::reader::XmlParser;
+use doc_model::{
+    CompatSetting, CompatibilitySettings, Document, DocumentProtection, HashAlgorithm, PasswordProtection,
+    ProtectionType,
+};
+use quick_xml::events::{BytesStart, Event};
+
+/// Path of the settings part
+pub const SETTINGS_PART: &str = "word/settings.xml";
+
+/// Namespace of Word's own compatibility settings
+const WORD_COMPAT_URI: &str = "http://schemas.microsoft.com/office/word";
+
+/// Name of the compatibility setting that holds the compatibility mode
+const COMPATIBILITY_MODE: &str = "compatibilityMode";
+
+/// Twips per point
+const TWIPS_PER_POINT: f32 = 20.0;
+
+/// Write `word/settings.xml` for a document
+///
+/// `even_and_odd_headers` is set if the document or any of its sections
+/// uses different headers on odd and even pages.
+pub fn write_settings(document: &Document, even_and_odd_headers: bool) -> String {
+    let settings = &document.settings;
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+         <w:settings xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">",
+    );
+
+    if settings.track_revisions {
+        xml.push_str("<w:trackRevisions/>");
+    }
+    write_protection(&mut xml, &document.protection);
+    xml.push_str(&format!(r#"<w:defaultTabStop w:val="{}"/>"#, twips(settings.default_tab_stop)));
+
+    let hyphenation = &settings.hyphenation;
+    if hyphenation.auto_hyphenate {
+        xml.push_str("<w:autoHyphenation/>");
+    }
+    if let Some(limit) = hyphenation.consecutive_limit {
+        xml.push_str(&format!(r#"<w:consecutiveHyphenLimit w:val="{}"/>"#, limit));
+    }
+    if let Some(zone) = hyphenation.zone {
+        xml.push_str(&format!(r#"<w:hyphenationZone w:val="{}"/>"#, twips(zone)));
+    }
+    if !hyphenation.hyphenate_caps {
+        xml.push_str("<w:doNotHyphenateCaps/>");
+    }
+
+    if settings.even_and_odd_headers || even_and_odd_headers {
+        xml.push_str("<w:evenAndOddHeaders/>");
+    }
+
+    write_compatibility(&mut xml, &settings.compatibility);
+
+    let variables = &document.metadata.variables;
+    if !variables.is_empty() {
+        xml.push_str("<w:docVars>");
+        for (name, value) in variables {
+            xml.push_str(&format!(
+                r#"<w:docVar w:name="{}" w:val="{}"/>"#,
+                escape_attr(name),
+                escape_attr(value)
+            ));
+        }
+        xml.push_str("</w:docVars>");
+    }
+
+    xml.push_str("</w:settings>");
+    xml
+}
+
+/// Write `w:documentProtection`, using the hash attributes Word writes
+fn write_protection(xml: &mut String, protection: &DocumentProtection) {
+    if protection.protection_type == ProtectionType::None && !protection.formatting_restricted {
+        return;
+    }
+
+    xml.push_str(&format!(
+        r#"<w:documentProtection w:edit="{}""#,
+        protection.protection_type.ooxml_value()
+    ));
+    if protection.formatting_restricted {
+        xml.push_str(r#" w:formatting="1""#);
+    }
+    xml.push_str(&format!(r#" w:enforcement="{}""#, if protection.enforced { 1 } else { 0 }));
+    if let Some(password) = &protection.password {
+        match algorithm_sid(password.algorithm) {
+            Some(sid) => xml.push_str(&format!(
+                r#" w:cryptProviderType="rsaAES" w:cryptAlgorithmClass="hash" w:cryptAlgorithmType="typeAny" w:cryptAlgorithmSid="{}" w:cryptSpinCount="{}" w:hash="{}" w:salt="{}""#,
+                sid,
+                password.spin_count,
+                escape_attr(&password.hash_value),
+                escape_attr(&password.salt_value)
+            )),
+            None => xml.push_str(&format!(r#" w:hash="{}""#, escape_attr(&password.hash_value))),
+        }
+    }
+    xml.push_str("/>");
+}
+
+/// Write `w:compat`: the legacy options, then the named settings
+fn write_compatibility(xml: &mut String, compatibility: &CompatibilitySettings) {
+    if compatibility.mode.is_none() && compatibility.options.is_empty() && compatibility.settings.is_empty() {
+        return;
+    }
+
+    xml.push_str("<w:compat>");
+    for option in &compatibility.options {
+        xml.push_str(&format!("<w:{}/>", option));
+    }
+    if let Some(mode) = compatibility.mode {
+        xml.push_str(&format!(
+            r#"<w:compatSetting w:name="{}" w:uri="{}" w:val="{}"/>"#,
+            COMPATIBILITY_MODE, WORD_COMPAT_URI, mode
+        ));
+    }
+    for setting in &compatibility.settings {
+        xml.push_str(&format!(
+            r#"<w:compatSetting w:name="{}" w:uri="{}" w:val="{}"/>"#,
+            escape_attr(&setting.name),
+            escape_attr(&setting.uri),
+            escape_attr(&setting.value)
+        ));
+    }
+    xml.push_str("</w:compat>");
+}
+
+/// Read `word/settings.xml` into a document's settings, protection, and
+/// variables
+pub fn parse_settings(content: &str, document: &mut Document) -> DocxResult<()> {
+    let mut reader = XmlParser::from_string(content);
+    let mut buf = Vec::new();
+    let mut in_compat = false;
+    // The compatibility mode is only what the file says; absent means the
+    // oldest layout rules
+    let mut compatibility = CompatibilitySettings {
+        mode: None,
+        ..Default::default()
+    };
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                let name = e.name();
+                let local = local_name(name.as_ref());
+                let settings = &mut document.settings;
+                match local {
+                    "compat" => in_compat = true,
+                    "compatSetting" => {
+                        let name = attribute(e, b"w:name").unwrap_or_default();
+                        let value = attribute(e, b"w:val").unwrap_or_default();
+                        if name == COMPATIBILITY_MODE {
+                            compatibility.mode = value.parse().ok();
+                        } else {
+                            compatibility.settings.push(CompatSetting {
+                                name,
+                                uri: attribute(e, b"w:uri").unwrap_or_default(),
+                                value,
+                            });
+                        }
+                    }
+                    _ if in_compat && is_on(e) => compatibility.options.push(local.to_string()),
+                    _ if in_compat => {}
+                    "trackRevisions" => settings.track_revisions = is_on(e),
+                    "documentProtection" => document.protection = parse_protection(e),
+                    "defaultTabStop" => {
+                        if let Some(interval) = attribute(e, b"w:val").and_then(|v| XmlParser::parse_twips(&v)) {
+                            if interval > 0.0 {
+                                settings.default_tab_stop = interval;
+                            }
+                        }
+                    }
+                    "autoHyphenation" => settings.hyphenation.auto_hyphenate = is_on(e),
+                    "consecutiveHyphenLimit" => {
+                        // Zero means no limit
+                        settings.hyphenation.consecutive_limit =
+                            attribute(e, b"w:val").and_then(|v| v.parse().ok()).filter(|limit| *limit > 0);
+                    }
+                    "hyphenationZone" => {
+                        settings.hyphenation.zone = attribute(e, b"w:val").and_then(|v| XmlParser::parse_twips(&v));
+                    }
+                    "doNotHyphenateCaps" => settings.hyphenation.hyphenate_caps = !is_on(e),
+                    "evenAndOddHeaders" => settings.even_and_odd_headers = is_on(e),
+                    "docVar" => {
+                        if let Some(name) = attribute(e, b"w:name") {
+                            let value = attribute(e, b"w:val").unwrap_or_default();
+                            document.metadata.variables.insert(name, value);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::End(ref e)) if XmlParser::matches_element(e.name().as_ref(), "compat") => {
+                in_compat = false;
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(DocxError::XmlParse(e.to_string())),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    document.settings.compatibility = compatibility;
+    Ok(())
+}
+
+/// Read `w:documentProtection`, in either the transitional (`w:hash`) or
+/// the strict (`w:hashValue`) form
+fn parse_protection(e: &BytesStart) -> DocumentProtection {
+    let mut protection = DocumentProtection {
+        protection_type: attribute(e, b"w:edit")
+            .map(|value| ProtectionType::from_ooxml(&value))
+            .unwrap_or_default(),
+        enforced: attribute(e, b"w:enforcement").is_some_and(|value| XmlParser::parse_bool(&value)),
+        formatting_restricted: attribute(e, b"w:formatting").is_some_and(|value| XmlParser::parse_bool(&value)),
+        ..Default::default()
+    };
+
+    let hash = attribute(e, b"w:hashValue").or_else(|| attribute(e, b"w:hash"));
+    if let Some(hash) = hash {
+        let salt = attribute(e, b"w:saltValue")
+            .or_else(|| attribute(e, b"w:salt"))
+            .unwrap_or_default();
+        let spin_count = attribute(e, b"w:spinCount")
+            .or_else(|| attribute(e, b"w:cryptSpinCount"))
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0);
+        let algorithm = match attribute(e, b"w:algorithmName") {
+            Some(name) => HashAlgorithm::from_ooxml(&name),
+            None => match attribute(e, b"w:cryptAlgorithmSid").and_then(|sid| sid.parse().ok()) {
+                Some(4) => HashAlgorithm::Sha1,
+                Some(12) => HashAlgorithm::Sha256,
+                Some(13) => HashAlgorithm::Sha384,
+                Some(14) => HashAlgorithm::Sha512,
+                _ if salt.is_empty() => HashAlgorithm::LegacyXor,
+                _ => HashAlgorithm::Sha1,
+            },
+        };
+        protection.password = Some(PasswordProtection::new(hash, salt, spin_count).with_algorithm(algorithm));
+    }
+    protection
+}
+
+/// Windows CryptoAPI algorithm ID for a hash algorithm; None for the
+/// legacy hash, which has none
+fn algorithm_sid(algorithm: HashAlgorithm) -> Option<u32> {
+    match algorithm {
+        HashAlgorithm::LegacyXor => None,
+        HashAlgorithm::Sha1 => Some(4),
+        HashAlgorithm::Sha256 => Some(12),
+        HashAlgorithm::Sha384 => Some(13),
+        HashAlgorithm::Sha512 => Some(14),
+    }
+}
+
+/// Check an on/off element: present and not switched off by `w:val`
+fn is_on(e: &BytesStart) -> bool {
+    attribute(e, b"w:val").is_none_or(|value| XmlParser::parse_bool(&value))
+}
+
+/// Element name without its namespace prefix
+fn local_name(name: &[u8]) -> &str {
+    let name = std::str::from_utf8(name).unwrap_or("");
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
+
+/// Convert points to whole twips
+fn twips(points: f32) -> i64 {
+    (points * TWIPS_PER_POINT).round() as i64
+}
+
+/// Get an attribute value with entities unescaped
+fn attribute(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .filter_map(|a| a.ok())
+        .find(|a| a.key.as_ref() == name)
+        .and_then(|a| a.unescape_value().ok().map(|value| value.into_owned()))
+}
+
+/// Escape a string for an XML attribute value
+fn escape_attr(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
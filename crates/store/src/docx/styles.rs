@@ -4,8 +4,14 @@
 
 use crate::docx::error::{DocxError, DocxResult};
 use crate::docx::reader::XmlParser;
+use crate::docx::tables::parse_border;
+use crate::docx::document::parse_bidi;
+use crate::docx::tabs_io::parse_tab_stop;
+use crate::docx::text_effects_io::{is_w14_element, TextEffectsReader};
+use crate::docx::theme_io::{parse_theme_color, parse_theme_font};
 use doc_model::{
-    Alignment, CharacterProperties, LineSpacing, ParagraphProperties, Style, StyleId, StyleType,
+    Alignment, CellBorders, CharacterProperties, LineSpacing, ParagraphProperties, Style, StyleId,
+    StyleType, TableBorders, TableRegionFormat, TableStyleProperties, TableStyleRegion,
 };
 use quick_xml::events::Event;
 
@@ -29,6 +35,10 @@ impl StylesParser {
         let mut in_style = false;
         let mut in_para_props = false;
         let mut in_run_props = false;
+        let mut in_table_props = false;
+        let mut in_cell_props = false;
+        let mut in_borders = false;
+        let mut text_effects = TextEffectsReader::new();
 
         loop {
             match reader.read_event_into(&mut buf) {
@@ -52,6 +62,24 @@ impl StylesParser {
                         in_para_props = true;
                     } else if in_style && XmlParser::matches_element(name_ref, "rPr") {
                         in_run_props = true;
+                        text_effects = TextEffectsReader::new();
+                    } else if in_run_props && is_w14_element(name_ref) {
+                        text_effects.start(e);
+                    } else if in_style && XmlParser::matches_element(name_ref, "tblStylePr") {
+                        if let Some(region) = XmlParser::get_w_attribute(e, "type")
+                            .and_then(|t| TableStyleRegion::from_docx_name(&t))
+                        {
+                            current_style.as_mut().unwrap().begin_region(region);
+                        }
+                    } else if in_style && XmlParser::matches_element(name_ref, "tblPr") {
+                        in_table_props = true;
+                    } else if in_style && XmlParser::matches_element(name_ref, "tcPr") {
+                        in_cell_props = true;
+                    } else if (in_table_props || in_cell_props)
+                        && (XmlParser::matches_element(name_ref, "tblBorders")
+                            || XmlParser::matches_element(name_ref, "tcBorders"))
+                    {
+                        in_borders = true;
                     } else if in_style {
                         self.parse_style_element(e, current_style.as_mut().unwrap())?;
                     } else if in_para_props && current_style.is_some() {
@@ -64,7 +92,17 @@ impl StylesParser {
                     let name = e.name();
                     let name_ref = name.as_ref();
 
-                    if in_style && !in_para_props && !in_run_props {
+                    if in_run_props && is_w14_element(name_ref) {
+                        text_effects.start(e);
+                        text_effects.end(name_ref);
+                    } else if (in_table_props || in_cell_props) && current_style.is_some() {
+                        self.parse_table_style_property(
+                            e,
+                            current_style.as_mut().unwrap(),
+                            in_cell_props,
+                            in_borders,
+                        )?;
+                    } else if in_style && !in_para_props && !in_run_props {
                         self.parse_style_element(e, current_style.as_mut().unwrap())?;
                     } else if in_para_props && current_style.is_some() {
                         self.parse_para_property(e, current_style.as_mut().unwrap())?;
@@ -84,9 +122,26 @@ impl StylesParser {
                             }
                         }
                         in_style = false;
+                    } else if XmlParser::matches_element(name_ref, "tblStylePr") {
+                        if let Some(ref mut style) = current_style {
+                            style.end_region();
+                        }
+                    } else if XmlParser::matches_element(name_ref, "tblPr") {
+                        in_table_props = false;
+                    } else if XmlParser::matches_element(name_ref, "tcPr") {
+                        in_cell_props = false;
+                    } else if XmlParser::matches_element(name_ref, "tblBorders")
+                        || XmlParser::matches_element(name_ref, "tcBorders")
+                    {
+                        in_borders = false;
                     } else if XmlParser::matches_element(name_ref, "pPr") {
                         in_para_props = false;
+                    } else if in_run_props && is_w14_element(name_ref) {
+                        text_effects.end(name_ref);
                     } else if XmlParser::matches_element(name_ref, "rPr") {
+                        if let Some(ref mut style) = current_style {
+                            style.char_props.text_effects = std::mem::take(&mut text_effects).finish();
+                        }
                         in_run_props = false;
                     }
                 }
@@ -130,6 +185,69 @@ impl StylesParser {
         Ok(())
     }
 
+    /// Parse a table (w:tblPr) or cell (w:tcPr) property element of a table style
+    fn parse_table_style_property(
+        &self,
+        e: &quick_xml::events::BytesStart,
+        style: &mut ParsedStyle,
+        in_cell_props: bool,
+        in_borders: bool,
+    ) -> DocxResult<()> {
+        let name = e.name();
+        let name_ref = name.as_ref();
+        let side = std::str::from_utf8(name_ref)
+            .unwrap_or("")
+            .rsplit(':')
+            .next()
+            .unwrap_or("");
+
+        if in_cell_props {
+            let format = style.cell_format_mut();
+            if in_borders {
+                let borders = format.borders.get_or_insert_with(CellBorders::default);
+                match side {
+                    "top" => borders.top = Some(parse_border(e)),
+                    "bottom" => borders.bottom = Some(parse_border(e)),
+                    "left" | "start" => borders.left = Some(parse_border(e)),
+                    "right" | "end" => borders.right = Some(parse_border(e)),
+                    "tl2br" => borders.diagonal_down = Some(parse_border(e)),
+                    "tr2bl" => borders.diagonal_up = Some(parse_border(e)),
+                    _ => {}
+                }
+            } else if side == "shd" {
+                if let Some(fill) = XmlParser::get_w_attribute(e, "fill") {
+                    if fill != "auto" {
+                        format.shading = Some(format!("#{}", fill));
+                    }
+                }
+            }
+        } else if style.region.is_none() {
+            // Table-level properties only come from the style itself
+            if in_borders {
+                let borders = style.table_props.borders.get_or_insert_with(TableBorders::default);
+                match side {
+                    "top" => borders.top = Some(parse_border(e)),
+                    "bottom" => borders.bottom = Some(parse_border(e)),
+                    "left" | "start" => borders.left = Some(parse_border(e)),
+                    "right" | "end" => borders.right = Some(parse_border(e)),
+                    "insideH" => borders.inside_h = Some(parse_border(e)),
+                    "insideV" => borders.inside_v = Some(parse_border(e)),
+                    _ => {}
+                }
+            } else if side == "tblStyleRowBandSize" {
+                if let Some(val) = XmlParser::get_w_attribute(e, "val").and_then(|v| v.parse().ok()) {
+                    style.table_props.row_band_size = val;
+                }
+            } else if side == "tblStyleColBandSize" {
+                if let Some(val) = XmlParser::get_w_attribute(e, "val").and_then(|v| v.parse().ok()) {
+                    style.table_props.column_band_size = val;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Parse a paragraph property element
     fn parse_para_property(&self, e: &quick_xml::events::BytesStart, style: &mut ParsedStyle) -> DocxResult<()> {
         let name = e.name();
@@ -166,12 +284,22 @@ impl StylesParser {
                     .unwrap_or_else(|| "auto".to_string());
                 style.para_props.line_spacing = Some(parse_line_spacing(&val, &line_rule));
             }
+        } else if XmlParser::matches_element(name_ref, "tab") {
+            if let Some(stop) = parse_tab_stop(e) {
+                style.para_props.tab_stops.push(stop);
+            }
         } else if XmlParser::matches_element(name_ref, "keepNext") {
             style.para_props.keep_with_next = Some(true);
         } else if XmlParser::matches_element(name_ref, "keepLines") {
             style.para_props.keep_together = Some(true);
         } else if XmlParser::matches_element(name_ref, "pageBreakBefore") {
             style.para_props.page_break_before = Some(true);
+        } else if XmlParser::matches_element(name_ref, "suppressLineNumbers") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            style.para_props.suppress_line_numbers = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
+        } else if XmlParser::matches_element(name_ref, "bidi") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            style.para_props.direction = Some(parse_bidi(val.as_deref()));
         } else if XmlParser::matches_element(name_ref, "outlineLvl") {
             if let Some(val) = XmlParser::get_w_attribute(e, "val") {
                 style.para_props.outline_level = val.parse().ok();
@@ -209,12 +337,14 @@ impl StylesParser {
             {
                 style.char_props.font_family = Some(font);
             }
+            style.char_props.theme_font = parse_theme_font(e);
         } else if XmlParser::matches_element(name_ref, "color") {
             if let Some(val) = XmlParser::get_w_attribute(e, "val") {
                 if val != "auto" {
                     style.char_props.color = Some(format!("#{}", val));
                 }
             }
+            style.char_props.theme_color = parse_theme_color(e);
         } else if XmlParser::matches_element(name_ref, "caps") {
             let val = XmlParser::get_w_attribute(e, "val");
             style.char_props.all_caps = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
@@ -225,6 +355,40 @@ impl StylesParser {
             if let Some(val) = XmlParser::get_w_attribute(e, "val") {
                 style.char_props.spacing = XmlParser::parse_twips(&val);
             }
+        } else if XmlParser::matches_element(name_ref, "position") {
+            if let Some(val) = XmlParser::get_w_attribute(e, "val") {
+                style.char_props.position = XmlParser::parse_half_points(&val);
+            }
+        } else if XmlParser::matches_element(name_ref, "w") {
+            if let Some(val) = XmlParser::get_w_attribute(e, "val") {
+                style.char_props.scale = val.trim_end_matches('%').parse().ok();
+            }
+        } else if XmlParser::matches_element(name_ref, "kern") {
+            if let Some(val) = XmlParser::get_w_attribute(e, "val") {
+                style.char_props.kerning = XmlParser::parse_half_points(&val);
+            }
+        } else if XmlParser::matches_element(name_ref, "outline") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            style.char_props.outline = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
+        } else if XmlParser::matches_element(name_ref, "shadow") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            style.char_props.shadow = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
+        } else if XmlParser::matches_element(name_ref, "emboss") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            style.char_props.emboss = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
+        } else if XmlParser::matches_element(name_ref, "imprint") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            style.char_props.imprint = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
+        } else if XmlParser::matches_element(name_ref, "noProof") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            style.char_props.no_proof = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
+        } else if XmlParser::matches_element(name_ref, "vanish") {
+            let val = XmlParser::get_w_attribute(e, "val");
+            style.char_props.hidden = Some(val.map(|v| XmlParser::parse_bool(&v)).unwrap_or(true));
+        } else if XmlParser::matches_element(name_ref, "lang") {
+            if let Some(val) = XmlParser::get_w_attribute(e, "val") {
+                style.char_props.language = Some(val);
+            }
         }
 
         Ok(())
@@ -245,6 +409,13 @@ struct ParsedStyle {
     priority: u32,
     para_props: ParagraphProperties,
     char_props: CharacterProperties,
+    table_props: TableStyleProperties,
+    /// Conditional region being parsed (w:tblStylePr)
+    region: Option<TableStyleRegion>,
+    /// Cell formatting of the region being parsed
+    region_format: TableRegionFormat,
+    /// The style's own properties, set aside while a region's are parsed
+    outer_props: Option<(ParagraphProperties, CharacterProperties)>,
 }
 
 impl ParsedStyle {
@@ -261,6 +432,49 @@ impl ParsedStyle {
             priority: 99,
             para_props: ParagraphProperties::default(),
             char_props: CharacterProperties::default(),
+            table_props: TableStyleProperties::default(),
+            region: None,
+            region_format: TableRegionFormat::default(),
+            outer_props: None,
+        }
+    }
+
+    /// Start a conditional region; its pPr/rPr are collected in place of the style's
+    fn begin_region(&mut self, region: TableStyleRegion) {
+        self.outer_props = Some((
+            std::mem::take(&mut self.para_props),
+            std::mem::take(&mut self.char_props),
+        ));
+        self.region = Some(region);
+        self.region_format = TableRegionFormat::default();
+    }
+
+    /// Finish the current conditional region and restore the style's properties
+    fn end_region(&mut self) {
+        let Some(region) = self.region.take() else {
+            return;
+        };
+        let (para_props, char_props) = self.outer_props.take().unwrap_or_default();
+        let mut format = std::mem::take(&mut self.region_format);
+        format.paragraph_props = std::mem::replace(&mut self.para_props, para_props);
+        format.character_props = std::mem::replace(&mut self.char_props, char_props);
+
+        let merged = match self.table_props.region(region) {
+            Some(existing) => existing.merge(&format),
+            None => format,
+        };
+        self.table_props.regions.insert(region, merged);
+    }
+
+    /// Cell formatting target: the current region, or the whole table
+    fn cell_format_mut(&mut self) -> &mut TableRegionFormat {
+        if self.region.is_some() {
+            &mut self.region_format
+        } else {
+            self.table_props
+                .regions
+                .entry(TableStyleRegion::WholeTable)
+                .or_default()
         }
     }
 
@@ -278,7 +492,8 @@ impl ParsedStyle {
         let mut style = match style_type {
             StyleType::Paragraph => Style::paragraph(self.id.as_str(), &name),
             StyleType::Character => Style::character(self.id.as_str(), &name),
-            _ => return None, // Skip table/numbering for now
+            StyleType::Table => Style::table(self.id.as_str(), &name).with_table_props(self.table_props),
+            _ => return None, // Skip numbering for now
         };
 
         // Apply base style
@@ -314,7 +529,7 @@ impl ParsedStyle {
 fn parse_alignment(value: &str) -> Alignment {
     match value {
         "center" => Alignment::Center,
-        "right" => Alignment::Right,
+        "right" | "end" => Alignment::Right,
         "both" | "justify" => Alignment::Justify,
         _ => Alignment::Left,
     }
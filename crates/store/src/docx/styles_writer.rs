@@ -4,9 +4,13 @@
 
 use crate::docx::error::DocxResult;
 use crate::docx::namespaces;
+use crate::docx::tables_writer::{write_border, write_cell_borders};
+use crate::docx::tabs_io::write_tab_stops;
+use crate::docx::text_effects_io::write_text_effects;
+use crate::docx::theme_io::{theme_color_attributes, theme_font_attributes};
 use doc_model::{
-    Alignment, CharacterProperties, DocumentTree, LineSpacing, ParagraphProperties, Style,
-    StyleType,
+    Alignment, CharacterProperties, DocumentTree, LineSpacing, ParagraphProperties,
+    Style, StyleType, TableRegionFormat, TableStyleProperties, TableStyleRegion, TextDirection,
 };
 
 /// Writer for styles.xml
@@ -28,9 +32,11 @@ impl StylesWriter {
 
         // Styles element with namespace
         xml.push_str(&format!(
-            r#"<w:styles xmlns:w="{}" xmlns:r="{}">"#,
+            r#"<w:styles xmlns:w="{}" xmlns:r="{}" xmlns:w14="{}" xmlns:mc="{}" mc:Ignorable="w14">"#,
             namespaces::W,
             namespaces::R,
+            namespaces::W14,
+            namespaces::MC,
         ));
 
         // Write default styles (docDefaults)
@@ -127,7 +133,8 @@ impl StylesWriter {
         }
 
         // Paragraph properties
-        if style.style_type == StyleType::Paragraph && !style.paragraph_props.is_empty() {
+        let has_paragraph_props = matches!(style.style_type, StyleType::Paragraph | StyleType::Table);
+        if has_paragraph_props && !style.paragraph_props.is_empty() {
             self.write_paragraph_properties(xml, &style.paragraph_props)?;
         }
 
@@ -136,10 +143,81 @@ impl StylesWriter {
             self.write_character_properties(xml, &style.character_props)?;
         }
 
+        // Table properties and conditional regions
+        if style.style_type == StyleType::Table {
+            self.write_table_style_properties(xml, &style.table_props)?;
+        }
+
         xml.push_str("</w:style>");
         Ok(())
     }
 
+    /// Write a table style's w:tblPr and one w:tblStylePr per region
+    fn write_table_style_properties(
+        &self,
+        xml: &mut String,
+        props: &TableStyleProperties,
+    ) -> DocxResult<()> {
+        xml.push_str("<w:tblPr>");
+        if props.row_band_size != 1 {
+            xml.push_str(&format!(r#"<w:tblStyleRowBandSize w:val="{}"/>"#, props.row_band_size));
+        }
+        if props.column_band_size != 1 {
+            xml.push_str(&format!(r#"<w:tblStyleColBandSize w:val="{}"/>"#, props.column_band_size));
+        }
+        if let Some(ref borders) = props.borders {
+            xml.push_str("<w:tblBorders>");
+            let sides = [
+                ("top", &borders.top),
+                ("left", &borders.left),
+                ("bottom", &borders.bottom),
+                ("right", &borders.right),
+                ("insideH", &borders.inside_h),
+                ("insideV", &borders.inside_v),
+            ];
+            for (side, border) in sides {
+                if let Some(border) = border {
+                    write_border(xml, side, border);
+                }
+            }
+            xml.push_str("</w:tblBorders>");
+        }
+        xml.push_str("</w:tblPr>");
+
+        for region in TableStyleRegion::PRECEDENCE {
+            if let Some(format) = props.region(region) {
+                xml.push_str(&format!(r#"<w:tblStylePr w:type="{}">"#, region.docx_name()));
+                self.write_region_format(xml, format)?;
+                xml.push_str("</w:tblStylePr>");
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the contents of a w:tblStylePr element
+    fn write_region_format(&self, xml: &mut String, format: &TableRegionFormat) -> DocxResult<()> {
+        if !format.paragraph_props.is_empty() {
+            self.write_paragraph_properties(xml, &format.paragraph_props)?;
+        }
+        if !format.character_props.is_empty() {
+            self.write_character_properties(xml, &format.character_props)?;
+        }
+        if format.borders.is_some() || format.shading.is_some() {
+            xml.push_str("<w:tcPr>");
+            if let Some(ref borders) = format.borders {
+                write_cell_borders(xml, borders);
+            }
+            if let Some(ref shading) = format.shading {
+                xml.push_str(&format!(
+                    r#"<w:shd w:val="clear" w:color="auto" w:fill="{}"/>"#,
+                    shading.trim_start_matches('#')
+                ));
+            }
+            xml.push_str("</w:tcPr>");
+        }
+        Ok(())
+    }
+
     /// Write paragraph properties
     fn write_paragraph_properties(
         &self,
@@ -203,6 +281,9 @@ impl StylesWriter {
             xml.push_str("/>");
         }
 
+        // Tab stops
+        write_tab_stops(xml, &props.tab_stops);
+
         // Keep with next
         if props.keep_with_next == Some(true) {
             xml.push_str("<w:keepNext/>");
@@ -218,6 +299,20 @@ impl StylesWriter {
             xml.push_str("<w:pageBreakBefore/>");
         }
 
+        // Line number suppression
+        match props.suppress_line_numbers {
+            Some(true) => xml.push_str("<w:suppressLineNumbers/>"),
+            Some(false) => xml.push_str(r#"<w:suppressLineNumbers w:val="0"/>"#),
+            None => {}
+        }
+
+        // Base direction; Auto has no markup and is left to the reader
+        match props.direction {
+            Some(TextDirection::Rtl) => xml.push_str("<w:bidi/>"),
+            Some(TextDirection::Ltr) => xml.push_str(r#"<w:bidi w:val="0"/>"#),
+            Some(TextDirection::Auto) | None => {}
+        }
+
         // Outline level
         if let Some(level) = props.outline_level {
             xml.push_str(&format!(r#"<w:outlineLvl w:val="{}"/>"#, level));
@@ -236,13 +331,20 @@ impl StylesWriter {
         xml.push_str("<w:rPr>");
 
         // Font family
-        if let Some(ref font) = props.font_family {
-            xml.push_str(&format!(
-                r#"<w:rFonts w:ascii="{}" w:hAnsi="{}" w:cs="{}"/>"#,
-                escape_xml(font),
-                escape_xml(font),
-                escape_xml(font)
-            ));
+        if props.font_family.is_some() || props.theme_font.is_some() {
+            xml.push_str("<w:rFonts");
+            if let Some(ref font) = props.font_family {
+                xml.push_str(&format!(
+                    r#" w:ascii="{}" w:hAnsi="{}" w:cs="{}""#,
+                    escape_xml(font),
+                    escape_xml(font),
+                    escape_xml(font)
+                ));
+            }
+            if let Some(font) = props.theme_font {
+                xml.push_str(&theme_font_attributes(font));
+            }
+            xml.push_str("/>");
         }
 
         // Font size
@@ -283,9 +385,10 @@ impl StylesWriter {
         }
 
         // Color
-        if let Some(ref color) = props.color {
-            let color_val = color.trim_start_matches('#');
-            xml.push_str(&format!(r#"<w:color w:val="{}"/>"#, color_val));
+        if props.color.is_some() || props.theme_color.is_some() {
+            let color_val = props.color.as_deref().map_or("auto", |c| c.trim_start_matches('#'));
+            let theme_attrs = props.theme_color.as_ref().map(theme_color_attributes).unwrap_or_default();
+            xml.push_str(&format!(r#"<w:color w:val="{}"{}/>"#, color_val, theme_attrs));
         }
 
         // All caps
@@ -307,6 +410,47 @@ impl StylesWriter {
             xml.push_str(&format!(r#"<w:spacing w:val="{}"/>"#, (spacing * 20.0) as i32));
         }
 
+        // Horizontal scale
+        if let Some(scale) = props.scale {
+            xml.push_str(&format!(r#"<w:w w:val="{}"/>"#, scale));
+        }
+
+        // Kerning threshold (in half-points)
+        if let Some(kerning) = props.kerning {
+            xml.push_str(&format!(r#"<w:kern w:val="{}"/>"#, (kerning * 2.0) as i32));
+        }
+
+        // Raised/lowered position (in half-points)
+        if let Some(position) = props.position {
+            xml.push_str(&format!(r#"<w:position w:val="{}"/>"#, (position * 2.0) as i32));
+        }
+
+        // Text effects
+        for (flag, name) in [
+            (props.outline, "outline"),
+            (props.shadow, "shadow"),
+            (props.emboss, "emboss"),
+            (props.imprint, "imprint"),
+            (props.no_proof, "noProof"),
+            (props.hidden, "vanish"),
+        ] {
+            match flag {
+                Some(true) => xml.push_str(&format!("<w:{}/>", name)),
+                Some(false) => xml.push_str(&format!(r#"<w:{} w:val="0"/>"#, name)),
+                None => {}
+            }
+        }
+
+        // Proofing language
+        if let Some(ref language) = props.language {
+            xml.push_str(&format!(r#"<w:lang w:val="{}"/>"#, escape_xml(language)));
+        }
+
+        // Word 2010 text effects
+        if let Some(ref effects) = props.text_effects {
+            write_text_effects(xml, effects);
+        }
+
         xml.push_str("</w:rPr>");
         Ok(())
     }
@@ -2,13 +2,14 @@
 //!
 //! Handles w:tbl, w:tr, w:tc elements and their properties.
 
+use crate::docx::alt_text_io::{read_decorative, read_table_alt_text};
 use crate::docx::error::{DocxError, DocxResult};
 use crate::docx::reader::XmlParser;
 use doc_model::{
-    CellBorders, CellPadding, CellProperties, CellVerticalAlign, DocumentTree, GridColumn,
+    CellBorders, CellPadding, CellProperties, CellTextDirection, CellVerticalAlign, DocumentTree, GridColumn,
     HeightRule, Node, Paragraph, RowProperties, Run, Table, TableAlignment, TableBorder,
-    TableBorderStyle, TableBorders, TableCell, TableGrid, TableProperties, TableRow, TableWidth,
-    WidthType,
+    TableBorderStyle, TableBorders, TableCell, TableGrid, TableLook, TableProperties, TableRow,
+    TableWidth, WidthType,
 };
 use quick_xml::events::Event;
 
@@ -36,6 +37,7 @@ impl TableParser {
         let mut in_tbl_grid = false;
         let mut in_tr_pr = false;
         let mut in_tc_pr = false;
+        let mut in_tc_borders = false;
         let mut in_text = false;
 
         loop {
@@ -56,6 +58,8 @@ impl TableParser {
                         current_cell = Some(ParsedCell::new());
                     } else if XmlParser::matches_element(name_ref, "tcPr") {
                         in_tc_pr = true;
+                    } else if in_tc_pr && XmlParser::matches_element(name_ref, "tcBorders") {
+                        in_tc_borders = true;
                     } else if current_cell.is_some() && XmlParser::matches_element(name_ref, "p") {
                         current_para = Some(ParsedParagraph::new());
                     } else if current_para.is_some() && XmlParser::matches_element(name_ref, "r") {
@@ -83,6 +87,8 @@ impl TableParser {
                         self.parse_table_property(e, &mut table)?;
                     } else if in_tr_pr && current_row.is_some() {
                         self.parse_row_property(e, current_row.as_mut().unwrap())?;
+                    } else if in_tc_borders && current_cell.is_some() {
+                        parse_cell_border(e, current_cell.as_mut().unwrap());
                     } else if in_tc_pr && current_cell.is_some() {
                         self.parse_cell_property(e, current_cell.as_mut().unwrap())?;
                     }
@@ -109,6 +115,8 @@ impl TableParser {
                         }
                     } else if XmlParser::matches_element(name_ref, "tcPr") {
                         in_tc_pr = false;
+                    } else if XmlParser::matches_element(name_ref, "tcBorders") {
+                        in_tc_borders = false;
                     } else if XmlParser::matches_element(name_ref, "p") {
                         if let Some(para) = current_para.take() {
                             if let Some(ref mut cell) = current_cell {
@@ -162,6 +170,16 @@ impl TableParser {
             if let Some(w) = XmlParser::get_w_attribute(e, "w") {
                 table.indent = XmlParser::parse_twips(&w);
             }
+        } else if XmlParser::matches_element(name_ref, "tblStyle") {
+            table.style_id = XmlParser::get_w_attribute(e, "val");
+        } else if XmlParser::matches_element(name_ref, "tblLook") {
+            table.look = Some(parse_table_look(e));
+        } else if XmlParser::matches_element(name_ref, "tblCaption") {
+            table.caption = read_table_alt_text(e);
+        } else if XmlParser::matches_element(name_ref, "tblDescription") {
+            table.description = read_table_alt_text(e);
+        } else if read_decorative(e) {
+            table.decorative = true;
         }
 
         Ok(())
@@ -180,9 +198,13 @@ impl TableParser {
                 row.height_rule = parse_height_rule(&rule);
             }
         } else if XmlParser::matches_element(name_ref, "tblHeader") {
-            row.is_header = true;
+            row.is_header = XmlParser::get_w_attribute(e, "val")
+                .map(|v| XmlParser::parse_bool(&v))
+                .unwrap_or(true);
         } else if XmlParser::matches_element(name_ref, "cantSplit") {
-            row.can_split = false;
+            row.can_split = XmlParser::get_w_attribute(e, "val")
+                .map(|v| !XmlParser::parse_bool(&v))
+                .unwrap_or(false);
         }
 
         Ok(())
@@ -220,6 +242,10 @@ impl TableParser {
                     cell.shading = Some(format!("#{}", fill));
                 }
             }
+        } else if XmlParser::matches_element(name_ref, "textDirection") {
+            if let Some(val) = XmlParser::get_w_attribute(e, "val") {
+                cell.text_direction = parse_cell_text_direction(&val);
+            }
         }
 
         Ok(())
@@ -252,6 +278,15 @@ impl TableParser {
         if let Some(indent) = parsed.indent {
             props.indent_left = Some(indent);
         }
+        if let Some(style_id) = parsed.style_id {
+            props.style_id = Some(style_id.into());
+        }
+        if let Some(look) = parsed.look {
+            props.look = look;
+        }
+        // Word's alt text title is only used when there is no description
+        props.alt_text = parsed.description.or(parsed.caption);
+        props.decorative = parsed.decorative;
 
         // Create the table
         let table = Table::with_grid_and_properties(grid, props);
@@ -276,6 +311,8 @@ impl TableParser {
                 let cell_props = CellProperties {
                     width: parsed_cell.width,
                     vertical_align: parsed_cell.vertical_align,
+                    text_direction: parsed_cell.text_direction,
+                    borders: parsed_cell.borders,
                     shading: parsed_cell.shading,
                     ..Default::default()
                 };
@@ -321,6 +358,11 @@ pub struct ParsedTable {
     pub width: Option<TableWidth>,
     pub alignment: Option<TableAlignment>,
     pub indent: Option<f32>,
+    pub style_id: Option<String>,
+    pub look: Option<TableLook>,
+    pub caption: Option<String>,
+    pub description: Option<String>,
+    pub decorative: bool,
     pub grid: Vec<f32>,
     pub rows: Vec<ParsedRow>,
 }
@@ -366,6 +408,8 @@ pub struct ParsedCell {
     pub grid_span: u32,
     pub v_merge: VMerge,
     pub vertical_align: Option<CellVerticalAlign>,
+    pub text_direction: Option<CellTextDirection>,
+    pub borders: Option<CellBorders>,
     pub shading: Option<String>,
     pub paragraphs: Vec<ParsedParagraph>,
 }
@@ -432,6 +476,93 @@ fn parse_height_rule(value: &str) -> HeightRule {
     }
 }
 
+/// Parse a w:tblLook element
+///
+/// Newer files use one attribute per flag; older ones only carry the hex
+/// `w:val` bitmask, which the attributes override when both are present.
+fn parse_table_look(e: &quick_xml::events::BytesStart) -> TableLook {
+    let mut look = XmlParser::get_w_attribute(e, "val")
+        .and_then(|v| u16::from_str_radix(&v, 16).ok())
+        .map(TableLook::from_bits)
+        .unwrap_or_default();
+    let flag = |name: &str| XmlParser::get_w_attribute(e, name).map(|v| XmlParser::parse_bool(&v));
+
+    if let Some(v) = flag("firstRow") {
+        look.first_row = v;
+    }
+    if let Some(v) = flag("lastRow") {
+        look.last_row = v;
+    }
+    if let Some(v) = flag("firstColumn") {
+        look.first_column = v;
+    }
+    if let Some(v) = flag("lastColumn") {
+        look.last_column = v;
+    }
+    if let Some(v) = flag("noHBand") {
+        look.banded_rows = !v;
+    }
+    if let Some(v) = flag("noVBand") {
+        look.banded_columns = !v;
+    }
+    look
+}
+
+/// Parse a side of a cell's w:tcBorders
+fn parse_cell_border(e: &quick_xml::events::BytesStart, cell: &mut ParsedCell) {
+    let name = e.name();
+    let name_ref = name.as_ref();
+    let borders = cell.borders.get_or_insert_with(CellBorders::default);
+
+    if XmlParser::matches_element(name_ref, "top") {
+        borders.top = Some(parse_border(e));
+    } else if XmlParser::matches_element(name_ref, "bottom") {
+        borders.bottom = Some(parse_border(e));
+    } else if XmlParser::matches_element(name_ref, "left") || XmlParser::matches_element(name_ref, "start") {
+        borders.left = Some(parse_border(e));
+    } else if XmlParser::matches_element(name_ref, "right") || XmlParser::matches_element(name_ref, "end") {
+        borders.right = Some(parse_border(e));
+    } else if XmlParser::matches_element(name_ref, "tl2br") {
+        borders.diagonal_down = Some(parse_border(e));
+    } else if XmlParser::matches_element(name_ref, "tr2bl") {
+        borders.diagonal_up = Some(parse_border(e));
+    }
+}
+
+/// Parse a cell text direction (w:textDirection)
+fn parse_cell_text_direction(value: &str) -> Option<CellTextDirection> {
+    match value {
+        "lrTb" | "lrTbV" => Some(CellTextDirection::Ltr),
+        "tbRl" | "tbRlV" => Some(CellTextDirection::TbRl),
+        "btLr" => Some(CellTextDirection::BtLr),
+        "tbLrV" => Some(CellTextDirection::TbLr),
+        _ => None,
+    }
+}
+
+/// Parse a border side element (w:top, w:insideH, ...)
+pub(crate) fn parse_border(e: &quick_xml::events::BytesStart) -> TableBorder {
+    let style = match XmlParser::get_w_attribute(e, "val").as_deref() {
+        Some("single") => TableBorderStyle::Single,
+        Some("double") => TableBorderStyle::Double,
+        Some("dotted") => TableBorderStyle::Dotted,
+        Some("dashed") => TableBorderStyle::Dashed,
+        Some("thick") => TableBorderStyle::Thick,
+        _ => TableBorderStyle::None,
+    };
+    // Border sizes are in eighths of a point
+    let width = XmlParser::get_w_attribute(e, "sz")
+        .and_then(|v| v.parse::<f32>().ok())
+        .map(|sz| sz / 8.0)
+        .unwrap_or(0.0);
+    let color = XmlParser::get_w_attribute(e, "color")
+        .filter(|c| c != "auto")
+        .map(|c| format!("#{}", c))
+        .unwrap_or_else(|| "#000000".to_string());
+
+    TableBorder { style, width, color }
+}
+
 /// Parse vertical alignment
 fn parse_vertical_align(value: &str) -> CellVerticalAlign {
     match value {
@@ -466,6 +597,22 @@ mod tests {
         assert_eq!(parse_table_alignment("right"), TableAlignment::Right);
     }
 
+    #[test]
+    fn test_parse_table_look() {
+        let xml = r#"<w:tblLook xmlns:w="w" w:val="04A0" w:lastRow="1" w:noVBand="0"/>"#;
+        let mut reader = XmlParser::from_string(xml);
+        let mut buf = Vec::new();
+        let Ok(Event::Empty(e)) = reader.read_event_into(&mut buf) else {
+            panic!("expected an empty element");
+        };
+
+        let look = parse_table_look(&e);
+        assert!(look.first_row && look.first_column && look.banded_rows);
+        assert!(look.last_row);
+        assert!(look.banded_columns);
+        assert!(!look.last_column);
+    }
+
     #[test]
     fn test_parse_vertical_align() {
         assert_eq!(parse_vertical_align("top"), CellVerticalAlign::Top);
@@ -2,10 +2,11 @@
 //!
 //! Generates w:tbl elements from doc_model tables.
 
+use crate::docx::alt_text_io::write_table_alt_text;
 use crate::docx::error::DocxResult;
 use doc_model::{
-    CellVerticalAlign, DocumentTree, HeightRule, Node, Paragraph, Run, Table, TableAlignment,
-    TableCell, TableRow, TableWidth, WidthType,
+    CellBorders, CellTextDirection, CellVerticalAlign, DocumentTree, HeightRule, Node, Paragraph, Run, Table, TableAlignment,
+    TableBorder, TableBorderStyle, TableCell, TableRow, TableWidth, WidthType,
 };
 
 /// Writer for table elements
@@ -84,7 +85,19 @@ impl TableWriter {
         xml.push_str("</w:tblCellMar>");
 
         // Look (first row, last row, etc.)
-        xml.push_str(r#"<w:tblLook w:val="04A0" w:firstRow="1" w:lastRow="0" w:firstColumn="1" w:lastColumn="0" w:noHBand="0" w:noVBand="1"/>"#);
+        let look = &props.look;
+        xml.push_str(&format!(
+            r#"<w:tblLook w:val="{:04X}" w:firstRow="{}" w:lastRow="{}" w:firstColumn="{}" w:lastColumn="{}" w:noHBand="{}" w:noVBand="{}"/>"#,
+            look.to_bits(),
+            u8::from(look.first_row),
+            u8::from(look.last_row),
+            u8::from(look.first_column),
+            u8::from(look.last_column),
+            u8::from(!look.banded_rows),
+            u8::from(!look.banded_columns),
+        ));
+
+        write_table_alt_text(xml, props.alt_text.as_deref(), props.decorative);
 
         xml.push_str("</w:tblPr>");
         Ok(())
@@ -133,7 +146,7 @@ impl TableWriter {
         // Only write if there are properties
         let has_height = props.height.is_some();
         let has_header = props.is_header;
-        let has_cant_split = !props.can_split;
+        let has_cant_split = !props.allows_split();
 
         if !has_height && !has_header && !has_cant_split {
             return Ok(());
@@ -158,7 +171,7 @@ impl TableWriter {
         }
 
         // Can't split
-        if !props.can_split {
+        if has_cant_split {
             xml.push_str("<w:cantSplit/>");
         }
 
@@ -218,14 +231,9 @@ impl TableWriter {
             xml.push_str("<w:vMerge/>");
         }
 
-        // Vertical alignment
-        if let Some(valign) = props.vertical_align {
-            let val = match valign {
-                CellVerticalAlign::Top => "top",
-                CellVerticalAlign::Center => "center",
-                CellVerticalAlign::Bottom => "bottom",
-            };
-            xml.push_str(&format!(r#"<w:vAlign w:val="{}"/>"#, val));
+        // Borders (including diagonals)
+        if let Some(ref borders) = props.borders {
+            write_cell_borders(xml, borders);
         }
 
         // Shading
@@ -237,6 +245,27 @@ impl TableWriter {
             ));
         }
 
+        // Text direction (right-to-left is paragraph bidi, not a cell direction)
+        let direction = match props.text_direction {
+            Some(CellTextDirection::TbRl) => Some("tbRl"),
+            Some(CellTextDirection::BtLr) => Some("btLr"),
+            Some(CellTextDirection::TbLr) => Some("tbLrV"),
+            _ => None,
+        };
+        if let Some(val) = direction {
+            xml.push_str(&format!(r#"<w:textDirection w:val="{}"/>"#, val));
+        }
+
+        // Vertical alignment
+        if let Some(valign) = props.vertical_align {
+            let val = match valign {
+                CellVerticalAlign::Top => "top",
+                CellVerticalAlign::Center => "center",
+                CellVerticalAlign::Bottom => "bottom",
+            };
+            xml.push_str(&format!(r#"<w:vAlign w:val="{}"/>"#, val));
+        }
+
         xml.push_str("</w:tcPr>");
         Ok(())
     }
@@ -281,6 +310,45 @@ fn format_table_width(width: &TableWidth) -> (i32, &'static str) {
     }
 }
 
+/// Write a w:tcBorders element
+pub(crate) fn write_cell_borders(xml: &mut String, borders: &CellBorders) {
+    xml.push_str("<w:tcBorders>");
+    let sides = [
+        ("top", &borders.top),
+        ("left", &borders.left),
+        ("bottom", &borders.bottom),
+        ("right", &borders.right),
+        ("tl2br", &borders.diagonal_down),
+        ("tr2bl", &borders.diagonal_up),
+    ];
+    for (side, border) in sides {
+        if let Some(border) = border {
+            write_border(xml, side, border);
+        }
+    }
+    xml.push_str("</w:tcBorders>");
+}
+
+/// Write a border side element (w:top, w:insideH, ...)
+pub(crate) fn write_border(xml: &mut String, side: &str, border: &TableBorder) {
+    let val = match border.style {
+        TableBorderStyle::None => "nil",
+        TableBorderStyle::Single => "single",
+        TableBorderStyle::Double => "double",
+        TableBorderStyle::Dotted => "dotted",
+        TableBorderStyle::Dashed => "dashed",
+        TableBorderStyle::Thick => "thick",
+    };
+    // Border sizes are in eighths of a point
+    xml.push_str(&format!(
+        r#"<w:{} w:val="{}" w:sz="{}" w:space="0" w:color="{}"/>"#,
+        side,
+        val,
+        (border.width * 8.0).round() as i32,
+        border.color.trim_start_matches('#'),
+    ));
+}
+
 /// Escape special XML characters
 fn escape_xml(s: &str) -> String {
     s.replace('&', "&amp;")
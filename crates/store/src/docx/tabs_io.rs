@@ -0,0 +1,104 @@
+//! Tab stop (w:tabs) reading and writing
+//!
+//! Tab stops appear in both document.xml and styles.xml paragraph
+//! properties as `<w:tabs>` containing one `<w:tab>` per stop.
+
+use crate::docx::reader::XmlParser;
+use doc_model::{TabAlignment, TabLeader, TabStop};
+use quick_xml::events::BytesStart;
+
+/// Parse a `<w:tab>` element inside `<w:tabs>`
+pub(crate) fn parse_tab_stop(e: &BytesStart) -> Option<TabStop> {
+    let position = XmlParser::parse_twips(&XmlParser::get_w_attribute(e, "pos")?)?;
+    let alignment = match XmlParser::get_w_attribute(e, "val")?.as_str() {
+        "center" => TabAlignment::Center,
+        "right" | "end" => TabAlignment::Right,
+        "decimal" => TabAlignment::Decimal,
+        "bar" => TabAlignment::Bar,
+        "clear" => TabAlignment::Clear,
+        // left, start, and num (list tab) position text like a left stop
+        _ => TabAlignment::Left,
+    };
+    let leader = match XmlParser::get_w_attribute(e, "leader").as_deref() {
+        Some("dot") => TabLeader::Dot,
+        Some("hyphen") => TabLeader::Hyphen,
+        Some("underscore") => TabLeader::Underscore,
+        Some("middleDot") => TabLeader::MiddleDot,
+        Some("heavy") => TabLeader::Heavy,
+        _ => TabLeader::None,
+    };
+
+    Some(TabStop::new(position, alignment).with_leader(leader))
+}
+
+/// Write `<w:tabs>` for a paragraph's tab stops (nothing if there are none)
+pub(crate) fn write_tab_stops(xml: &mut String, stops: &[TabStop]) {
+    if stops.is_empty() {
+        return;
+    }
+
+    xml.push_str("<w:tabs>");
+    for stop in stops {
+        let val = match stop.alignment {
+            TabAlignment::Left => "left",
+            TabAlignment::Center => "center",
+            TabAlignment::Right => "right",
+            TabAlignment::Decimal => "decimal",
+            TabAlignment::Bar => "bar",
+            TabAlignment::Clear => "clear",
+        };
+        xml.push_str(&format!(r#"<w:tab w:val="{}""#, val));
+
+        let leader = match stop.leader {
+            TabLeader::None => None,
+            TabLeader::Dot => Some("dot"),
+            TabLeader::Hyphen => Some("hyphen"),
+            TabLeader::Underscore => Some("underscore"),
+            TabLeader::MiddleDot => Some("middleDot"),
+            TabLeader::Heavy => Some("heavy"),
+        };
+        if let Some(leader) = leader {
+            xml.push_str(&format!(r#" w:leader="{}""#, leader));
+        }
+
+        xml.push_str(&format!(r#" w:pos="{}"/>"#, (stop.position * 20.0).round() as i32));
+    }
+    xml.push_str("</w:tabs>");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    #[test]
+    fn test_tab_stops_round_trip() {
+        let stops = vec![
+            TabStop::left(36.0),
+            TabStop::decimal(216.0).with_leader(TabLeader::MiddleDot),
+            TabStop::right(468.0).with_leader(TabLeader::Dot),
+            TabStop::clear(504.0),
+        ];
+
+        let mut xml = String::new();
+        write_tab_stops(&mut xml, &stops);
+        assert!(xml.contains(r#"<w:tab w:val="right" w:leader="dot" w:pos="9360"/>"#));
+
+        let mut reader = Reader::from_str(&xml);
+        let mut parsed = Vec::new();
+        loop {
+            match reader.read_event() {
+                Ok(Event::Empty(ref e)) => parsed.extend(parse_tab_stop(e)),
+                Ok(Event::Eof) => break,
+                Ok(_) => {}
+                Err(e) => panic!("{}", e),
+            }
+        }
+        assert_eq!(parsed, stops);
+
+        let mut empty = String::new();
+        write_tab_stops(&mut empty, &[]);
+        assert!(empty.is_empty());
+    }
+}
@@ -0,0 +1,333 @@
+//! Word 2010 text effects (w14) reading and writing
+//!
+//! Text fill, outline, glow, and reflection live in run properties as
+//! `w14:` extension elements. Color modifiers other than `w14:alpha` (such as
+//! `w14:lumMod`) are not modeled and are dropped on import.
+
+use crate::docx::reader::XmlParser;
+use doc_model::{
+    EffectColor, ShapeColor, TextEffects, TextFill, TextGlow, TextGradientStop, TextOutline,
+    TextReflection, ThemeColor,
+};
+use quick_xml::events::BytesStart;
+
+/// EMUs per point
+const EMU_PER_POINT: f32 = 12700.0;
+/// DrawingML percentages are in thousandths of a percent
+const PERCENT_SCALE: f32 = 100_000.0;
+/// DrawingML angles are in 60,000ths of a degree
+const ANGLE_SCALE: f32 = 60_000.0;
+
+/// Check if an element belongs to the w14 namespace
+pub(crate) fn is_w14_element(name: &[u8]) -> bool {
+    name.starts_with(b"w14:")
+}
+
+fn w14_attribute(e: &BytesStart, name: &str) -> Option<f32> {
+    XmlParser::get_prefixed_attribute(e, "w14", name).and_then(|v| v.parse().ok())
+}
+
+/// Builds `TextEffects` from the w14 elements of one run properties block
+///
+/// Feed every w14 start/empty/end event in order, then call `finish`.
+#[derive(Debug, Default)]
+pub(crate) struct TextEffectsReader {
+    effects: TextEffects,
+    /// Fill being built for the open textFill or textOutline
+    fill: Option<TextFill>,
+    /// Most recent color and its transparency
+    color: Option<(EffectColor, f32)>,
+    outline_width: f32,
+    glow_radius: f32,
+    stop_position: f32,
+}
+
+impl TextEffectsReader {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle a w14 start (or empty) element
+    pub(crate) fn start(&mut self, e: &BytesStart) {
+        let name = e.name();
+        let local = local_name(name.as_ref());
+
+        match local {
+            "glow" => {
+                self.glow_radius = w14_attribute(e, "rad").unwrap_or(0.0) / EMU_PER_POINT;
+                self.color = None;
+            }
+            "reflection" => {
+                self.effects.reflection = Some(TextReflection {
+                    blur_radius: w14_attribute(e, "blurRad").unwrap_or(0.0) / EMU_PER_POINT,
+                    start_opacity: w14_attribute(e, "stA").unwrap_or(PERCENT_SCALE) / PERCENT_SCALE,
+                    end_opacity: w14_attribute(e, "endA").unwrap_or(0.0) / PERCENT_SCALE,
+                    end_position: w14_attribute(e, "endPos").unwrap_or(PERCENT_SCALE) / PERCENT_SCALE,
+                    distance: w14_attribute(e, "dist").unwrap_or(0.0) / EMU_PER_POINT,
+                });
+            }
+            "textOutline" => {
+                self.outline_width = w14_attribute(e, "w").unwrap_or(0.0) / EMU_PER_POINT;
+                self.fill = None;
+            }
+            "textFill" => self.fill = None,
+            "noFill" => self.fill = Some(TextFill::NoFill),
+            "solidFill" => self.color = None,
+            "gradFill" => {
+                self.fill = Some(TextFill::LinearGradient {
+                    angle: 0.0,
+                    stops: Vec::new(),
+                })
+            }
+            "gs" => {
+                self.stop_position = w14_attribute(e, "pos").unwrap_or(0.0) / PERCENT_SCALE;
+                self.color = None;
+            }
+            "lin" => {
+                if let Some(TextFill::LinearGradient { angle, .. }) = &mut self.fill {
+                    *angle = w14_attribute(e, "ang").unwrap_or(0.0) / ANGLE_SCALE;
+                }
+            }
+            "path" => {
+                if let Some(TextFill::LinearGradient { stops, .. }) = &mut self.fill {
+                    self.fill = Some(TextFill::RadialGradient {
+                        stops: std::mem::take(stops),
+                    });
+                }
+            }
+            "srgbClr" => {
+                let color = XmlParser::get_prefixed_attribute(e, "w14", "val")
+                    .and_then(|v| ShapeColor::from_hex(&v))
+                    .unwrap_or(ShapeColor::BLACK);
+                self.color = Some((EffectColor::Rgb(color), 0.0));
+            }
+            "schemeClr" => {
+                let color = XmlParser::get_prefixed_attribute(e, "w14", "val")
+                    .and_then(|v| ThemeColor::from_name(&v))
+                    .unwrap_or(ThemeColor::Dark1);
+                self.color = Some((EffectColor::Theme(color), 0.0));
+            }
+            "alpha" => {
+                if let Some((_, transparency)) = &mut self.color {
+                    *transparency = w14_attribute(e, "val").unwrap_or(0.0) / PERCENT_SCALE;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Handle a w14 end element (call after `start` for empty elements too)
+    pub(crate) fn end(&mut self, name: &[u8]) {
+        match local_name(name) {
+            "solidFill" => {
+                if let Some((color, transparency)) = self.color.take() {
+                    self.fill = Some(TextFill::Solid { color, transparency });
+                }
+            }
+            "gs" => {
+                let stop = self.color.take().map(|(color, transparency)| TextGradientStop {
+                    color,
+                    position: self.stop_position,
+                    transparency,
+                });
+                if let (
+                    Some(stop),
+                    Some(TextFill::LinearGradient { stops, .. } | TextFill::RadialGradient { stops }),
+                ) = (stop, &mut self.fill)
+                {
+                    stops.push(stop);
+                }
+            }
+            "glow" => {
+                if let Some((color, transparency)) = self.color.take() {
+                    self.effects.glow = Some(TextGlow {
+                        radius: self.glow_radius,
+                        color,
+                        transparency,
+                    });
+                }
+            }
+            "textOutline" => {
+                let fill = self.fill.take().unwrap_or(TextFill::NoFill);
+                self.effects.outline = Some(TextOutline::new(self.outline_width, fill));
+            }
+            "textFill" => {
+                if let Some(fill) = self.fill.take() {
+                    self.effects.fill = Some(fill);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The effects read, if any
+    pub(crate) fn finish(self) -> Option<TextEffects> {
+        (!self.effects.is_empty()).then_some(self.effects)
+    }
+}
+
+fn local_name(name: &[u8]) -> &str {
+    let name = std::str::from_utf8(name).unwrap_or("");
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/// Write text effects as w14 run property elements
+pub(crate) fn write_text_effects(xml: &mut String, effects: &TextEffects) {
+    if let Some(glow) = &effects.glow {
+        xml.push_str(&format!(r#"<w14:glow w14:rad="{}">"#, emu(glow.radius)));
+        write_color(xml, &glow.color, glow.transparency);
+        xml.push_str("</w14:glow>");
+    }
+
+    if let Some(reflection) = &effects.reflection {
+        xml.push_str(&format!(
+            concat!(
+                r#"<w14:reflection w14:blurRad="{}" w14:stA="{}" w14:stPos="0" w14:endA="{}" w14:endPos="{}""#,
+                r#" w14:dist="{}" w14:dir="5400000" w14:fadeDir="5400000" w14:sx="100000" w14:sy="-100000""#,
+                r#" w14:kx="0" w14:ky="0" w14:algn="bl"/>"#,
+            ),
+            emu(reflection.blur_radius),
+            percent(reflection.start_opacity),
+            percent(reflection.end_opacity),
+            percent(reflection.end_position),
+            emu(reflection.distance),
+        ));
+    }
+
+    if let Some(outline) = &effects.outline {
+        xml.push_str(&format!(
+            r#"<w14:textOutline w14:w="{}" w14:cap="flat" w14:cmpd="sng" w14:algn="ctr">"#,
+            emu(outline.width)
+        ));
+        write_fill(xml, &outline.fill);
+        xml.push_str(r#"<w14:prstDash w14:val="solid"/><w14:round/></w14:textOutline>"#);
+    }
+
+    if let Some(fill) = &effects.fill {
+        xml.push_str("<w14:textFill>");
+        write_fill(xml, fill);
+        xml.push_str("</w14:textFill>");
+    }
+}
+
+fn write_fill(xml: &mut String, fill: &TextFill) {
+    let write_stops = |xml: &mut String, stops: &[TextGradientStop]| {
+        xml.push_str("<w14:gradFill><w14:gsLst>");
+        for stop in stops {
+            xml.push_str(&format!(r#"<w14:gs w14:pos="{}">"#, percent(stop.position)));
+            write_color(xml, &stop.color, stop.transparency);
+            xml.push_str("</w14:gs>");
+        }
+        xml.push_str("</w14:gsLst>");
+    };
+
+    match fill {
+        TextFill::NoFill => xml.push_str("<w14:noFill/>"),
+        TextFill::Solid { color, transparency } => {
+            xml.push_str("<w14:solidFill>");
+            write_color(xml, color, *transparency);
+            xml.push_str("</w14:solidFill>");
+        }
+        TextFill::LinearGradient { angle, stops } => {
+            write_stops(xml, stops);
+            xml.push_str(&format!(
+                r#"<w14:lin w14:ang="{}" w14:scaled="0"/></w14:gradFill>"#,
+                (angle * ANGLE_SCALE).round() as i64
+            ));
+        }
+        TextFill::RadialGradient { stops } => {
+            write_stops(xml, stops);
+            xml.push_str(concat!(
+                r#"<w14:path w14:path="circle">"#,
+                r#"<w14:fillToRect w14:l="50000" w14:t="50000" w14:r="50000" w14:b="50000"/>"#,
+                "</w14:path></w14:gradFill>",
+            ));
+        }
+    }
+}
+
+fn write_color(xml: &mut String, color: &EffectColor, transparency: f32) {
+    let (element, val) = match color {
+        EffectColor::Rgb(c) => ("srgbClr", format!("{:02X}{:02X}{:02X}", c.r, c.g, c.b)),
+        EffectColor::Theme(slot) => ("schemeClr", slot.scheme_name().to_string()),
+    };
+    if transparency > 0.0 {
+        xml.push_str(&format!(
+            r#"<w14:{} w14:val="{}"><w14:alpha w14:val="{}"/></w14:{}>"#,
+            element,
+            val,
+            percent(transparency),
+            element
+        ));
+    } else {
+        xml.push_str(&format!(r#"<w14:{} w14:val="{}"/>"#, element, val));
+    }
+}
+
+fn emu(points: f32) -> i64 {
+    (points * EMU_PER_POINT).round() as i64
+}
+
+fn percent(fraction: f32) -> i64 {
+    (fraction * PERCENT_SCALE).round() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    fn read(xml: &str) -> Option<TextEffects> {
+        let mut reader = Reader::from_str(xml);
+        let mut effects = TextEffectsReader::new();
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(ref e)) => effects.start(e),
+                Ok(Event::Empty(ref e)) => {
+                    effects.start(e);
+                    effects.end(e.name().as_ref());
+                }
+                Ok(Event::End(ref e)) => effects.end(e.name().as_ref()),
+                Ok(Event::Eof) => break,
+                Ok(_) => {}
+                Err(e) => panic!("{}", e),
+            }
+        }
+        effects.finish()
+    }
+
+    #[test]
+    fn test_text_effects_round_trip() {
+        let effects = TextEffects::new()
+            .with_fill(TextFill::LinearGradient {
+                angle: 90.0,
+                stops: vec![
+                    TextGradientStop::new(ThemeColor::Accent1, 0.0),
+                    TextGradientStop {
+                        transparency: 0.25,
+                        ..TextGradientStop::new(ShapeColor::ORANGE, 1.0)
+                    },
+                ],
+            })
+            .with_outline(TextOutline::new(0.75, TextFill::solid(ShapeColor::BLUE)))
+            .with_glow(TextGlow::new(5.0, ThemeColor::Accent2).with_transparency(0.6))
+            .with_reflection(TextReflection::default());
+
+        let mut xml = String::new();
+        write_text_effects(&mut xml, &effects);
+        assert!(xml.contains(r#"<w14:glow w14:rad="63500">"#));
+        assert_eq!(read(&xml), Some(effects));
+
+        let radial = TextEffects::new().with_fill(TextFill::RadialGradient {
+            stops: vec![
+                TextGradientStop::new(ShapeColor::WHITE, 0.0),
+                TextGradientStop::new(ShapeColor::RED, 1.0),
+            ],
+        });
+        let mut xml = String::new();
+        write_text_effects(&mut xml, &radial);
+        assert_eq!(read(&xml), Some(radial));
+        assert_eq!(read("<w14:ligatures w14:val=\"standard\"/>"), None);
+    }
+}
@@ -0,0 +1,366 @@
+//! Theme Import/Export for DOCX
+//!
+//! Handles word/theme/theme1.xml: the color scheme, font scheme, and format
+//! (effects) scheme. The format scheme is kept as raw markup so imported
+//! effects survive a round trip unchanged.
+
+use crate::docx::error::{DocxError, DocxResult};
+use crate::docx::namespaces;
+use crate::docx::reader::XmlParser;
+use doc_model::{DocumentTheme, EffectScheme, ThemeColor, ThemeColorRef, ThemeFont, ThemeFontSet};
+use quick_xml::events::{BytesStart, Event};
+
+/// Path of the theme part within the package
+pub const THEME_PART: &str = "word/theme/theme1.xml";
+
+// =============================================================================
+// Theme Parser
+// =============================================================================
+
+/// Which font slot is being parsed
+#[derive(Clone, Copy)]
+enum FontSlot {
+    Major,
+    Minor,
+}
+
+/// Parser for theme1.xml
+pub struct ThemeParser;
+
+impl ThemeParser {
+    /// Create a new theme parser
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Parse theme1.xml
+    ///
+    /// Anything the file doesn't define keeps the Office theme's value.
+    pub fn parse(&self, content: &str) -> DocxResult<DocumentTheme> {
+        let mut theme = DocumentTheme::office();
+        let mut reader = XmlParser::from_string(content);
+        let mut buf = Vec::new();
+
+        let mut current_color: Option<ThemeColor> = None;
+        // Only the first color scheme counts; later ones are alternates
+        let mut in_color_scheme = false;
+        let mut color_scheme_seen = false;
+        let mut font_slot: Option<FontSlot> = None;
+        let mut effects_start: Option<usize> = None;
+
+        loop {
+            let position = reader.buffer_position() as usize;
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) => {
+                    let name = e.name();
+                    let name_ref = name.as_ref();
+
+                    if XmlParser::matches_element(name_ref, "theme") {
+                        if let Some(name) = XmlParser::get_attribute(e, b"name") {
+                            theme.name = name;
+                        }
+                    } else if XmlParser::matches_element(name_ref, "clrScheme") {
+                        if !color_scheme_seen {
+                            if let Some(name) = XmlParser::get_attribute(e, b"name") {
+                                theme.colors.name = name;
+                            }
+                            in_color_scheme = true;
+                            color_scheme_seen = true;
+                        }
+                    } else if XmlParser::matches_element(name_ref, "fontScheme") {
+                        if let Some(name) = XmlParser::get_attribute(e, b"name") {
+                            theme.fonts.name = name;
+                        }
+                    } else if XmlParser::matches_element(name_ref, "fmtScheme") {
+                        theme.effects.name = XmlParser::get_attribute(e, b"name").unwrap_or_default();
+                        effects_start = Some(position);
+                    } else if XmlParser::matches_element(name_ref, "majorFont") {
+                        font_slot = Some(FontSlot::Major);
+                    } else if XmlParser::matches_element(name_ref, "minorFont") {
+                        font_slot = Some(FontSlot::Minor);
+                    } else if in_color_scheme {
+                        current_color = local_name(name_ref).and_then(ThemeColor::from_name);
+                    }
+                }
+                Ok(Event::Empty(ref e)) => {
+                    let name = e.name();
+                    let name_ref = name.as_ref();
+
+                    if let Some(slot) = current_color {
+                        if let Some(color) = Self::parse_color_value(name_ref, e) {
+                            theme.set_color(slot, color);
+                        }
+                    } else if let Some(slot) = font_slot {
+                        let fonts = match slot {
+                            FontSlot::Major => &mut theme.fonts.major,
+                            FontSlot::Minor => &mut theme.fonts.minor,
+                        };
+                        Self::parse_font(name_ref, e, fonts);
+                    }
+                }
+                Ok(Event::End(ref e)) => {
+                    let name = e.name();
+                    let name_ref = name.as_ref();
+
+                    if XmlParser::matches_element(name_ref, "majorFont")
+                        || XmlParser::matches_element(name_ref, "minorFont")
+                    {
+                        font_slot = None;
+                    } else if XmlParser::matches_element(name_ref, "fmtScheme") {
+                        if let Some(start) = effects_start.take() {
+                            let end = reader.buffer_position() as usize;
+                            theme.effects.source = content
+                                .get(start..end)
+                                .map(|markup| markup.trim().to_string());
+                        }
+                    } else if XmlParser::matches_element(name_ref, "clrScheme") {
+                        in_color_scheme = false;
+                    } else if in_color_scheme {
+                        current_color = None;
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(DocxError::from(e)),
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        Ok(theme)
+    }
+
+    /// Parse `a:srgbClr` / `a:sysClr` into `#RRGGBB`
+    fn parse_color_value(name: &[u8], e: &BytesStart) -> Option<String> {
+        let hex = if XmlParser::matches_element(name, "srgbClr") {
+            XmlParser::get_attribute(e, b"val")?
+        } else if XmlParser::matches_element(name, "sysClr") {
+            XmlParser::get_attribute(e, b"lastClr")?
+        } else {
+            return None;
+        };
+        Some(format!("#{}", hex.to_uppercase()))
+    }
+
+    /// Parse `a:latin` / `a:ea` / `a:cs` typefaces
+    fn parse_font(name: &[u8], e: &BytesStart, fonts: &mut ThemeFontSet) {
+        let Some(typeface) = XmlParser::get_attribute(e, b"typeface") else {
+            return;
+        };
+        if XmlParser::matches_element(name, "latin") {
+            fonts.latin = typeface;
+        } else if XmlParser::matches_element(name, "ea") {
+            fonts.east_asian = typeface;
+        } else if XmlParser::matches_element(name, "cs") {
+            fonts.complex_script = typeface;
+        }
+    }
+}
+
+impl Default for ThemeParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// =============================================================================
+// Theme References in Run Properties
+// =============================================================================
+
+/// Read `w:themeColor` / `w:themeTint` / `w:themeShade` from a `w:color` element
+pub(crate) fn parse_theme_color(e: &BytesStart) -> Option<ThemeColorRef> {
+    let color = ThemeColor::from_name(&XmlParser::get_w_attribute(e, "themeColor")?)?;
+    let byte = |name: &str| {
+        XmlParser::get_w_attribute(e, name).and_then(|v| u8::from_str_radix(&v, 16).ok())
+    };
+    Some(ThemeColorRef {
+        color,
+        tint: byte("themeTint"),
+        shade: byte("themeShade"),
+    })
+}
+
+/// Read `w:asciiTheme` / `w:hAnsiTheme` from a `w:rFonts` element
+pub(crate) fn parse_theme_font(e: &BytesStart) -> Option<ThemeFont> {
+    let value = XmlParser::get_w_attribute(e, "asciiTheme")
+        .or_else(|| XmlParser::get_w_attribute(e, "hAnsiTheme"))?;
+    if value.starts_with("major") {
+        Some(ThemeFont::Major)
+    } else if value.starts_with("minor") {
+        Some(ThemeFont::Minor)
+    } else {
+        None
+    }
+}
+
+/// `w:themeColor` (plus tint/shade) attributes for a `w:color` element
+pub(crate) fn theme_color_attributes(color_ref: &ThemeColorRef) -> String {
+    let mut attrs = format!(r#" w:themeColor="{}""#, color_ref.color.reference_name());
+    if let Some(tint) = color_ref.tint {
+        attrs.push_str(&format!(r#" w:themeTint="{:02X}""#, tint));
+    }
+    if let Some(shade) = color_ref.shade {
+        attrs.push_str(&format!(r#" w:themeShade="{:02X}""#, shade));
+    }
+    attrs
+}
+
+/// `w:asciiTheme` / `w:hAnsiTheme` attributes for a `w:rFonts` element
+pub(crate) fn theme_font_attributes(font: ThemeFont) -> String {
+    let value = match font {
+        ThemeFont::Major => "majorHAnsi",
+        ThemeFont::Minor => "minorHAnsi",
+    };
+    format!(r#" w:asciiTheme="{0}" w:hAnsiTheme="{0}""#, value)
+}
+
+/// Element name without its namespace prefix
+fn local_name(name: &[u8]) -> Option<&str> {
+    let name = std::str::from_utf8(name).ok()?;
+    Some(name.rsplit(':').next().unwrap_or(name))
+}
+
+// =============================================================================
+// Theme Writer
+// =============================================================================
+
+/// Writer for theme1.xml
+pub struct ThemeWriter;
+
+impl ThemeWriter {
+    /// Create a new theme writer
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Generate theme1.xml
+    pub fn write(&self, theme: &DocumentTheme) -> String {
+        let mut xml = String::new();
+        xml.push_str(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#);
+        xml.push_str(&format!(
+            r#"<a:theme xmlns:a="{}" name="{}">"#,
+            namespaces::A,
+            escape_xml(&theme.name)
+        ));
+        xml.push_str("<a:themeElements>");
+
+        // Color scheme
+        xml.push_str(&format!(r#"<a:clrScheme name="{}">"#, escape_xml(&theme.colors.name)));
+        for slot in ThemeColor::ALL {
+            let color = theme.color(slot);
+            xml.push_str(&format!(
+                r#"<a:{0}><a:srgbClr val="{1}"/></a:{0}>"#,
+                slot.scheme_name(),
+                escape_xml(color.trim_start_matches('#'))
+            ));
+        }
+        xml.push_str("</a:clrScheme>");
+
+        // Font scheme
+        xml.push_str(&format!(r#"<a:fontScheme name="{}">"#, escape_xml(&theme.fonts.name)));
+        Self::write_fonts(&mut xml, "majorFont", &theme.fonts.major);
+        Self::write_fonts(&mut xml, "minorFont", &theme.fonts.minor);
+        xml.push_str("</a:fontScheme>");
+
+        // Format scheme (fills, lines, effects)
+        match &theme.effects.source {
+            Some(source) => xml.push_str(source),
+            None => xml.push_str(&default_format_scheme(&theme.effects)),
+        }
+
+        xml.push_str("</a:themeElements>");
+        xml.push_str("</a:theme>");
+        xml
+    }
+
+    fn write_fonts(xml: &mut String, element: &str, fonts: &ThemeFontSet) {
+        xml.push_str(&format!("<a:{}>", element));
+        xml.push_str(&format!(r#"<a:latin typeface="{}"/>"#, escape_xml(&fonts.latin)));
+        xml.push_str(&format!(r#"<a:ea typeface="{}"/>"#, escape_xml(&fonts.east_asian)));
+        xml.push_str(&format!(r#"<a:cs typeface="{}"/>"#, escape_xml(&fonts.complex_script)));
+        xml.push_str(&format!("</a:{}>", element));
+    }
+}
+
+impl Default for ThemeWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Minimal format scheme: three fills, lines, effects, and background fills
+fn default_format_scheme(effects: &EffectScheme) -> String {
+    let fill = r#"<a:solidFill><a:schemeClr val="phClr"/></a:solidFill>"#;
+    let line = format!(r#"<a:ln w="6350">{}</a:ln>"#, fill);
+    let effect = "<a:effectStyle><a:effectLst/></a:effectStyle>";
+
+    format!(
+        r#"<a:fmtScheme name="{}"><a:fillStyleLst>{}</a:fillStyleLst><a:lnStyleLst>{}</a:lnStyleLst><a:effectStyleLst>{}</a:effectStyleLst><a:bgFillStyleLst>{}</a:bgFillStyleLst></a:fmtScheme>"#,
+        escape_xml(&effects.name),
+        fill.repeat(3),
+        line.repeat(3),
+        effect.repeat(3),
+        fill.repeat(3)
+    )
+}
+
+/// Escape special XML characters
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THEME_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<a:theme xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" name="Retrospect">
+  <a:themeElements>
+    <a:clrScheme name="Retrospect">
+      <a:dk1><a:sysClr val="windowText" lastClr="000000"/></a:dk1>
+      <a:lt1><a:sysClr val="window" lastClr="FFFFFF"/></a:lt1>
+      <a:dk2><a:srgbClr val="637052"/></a:dk2>
+      <a:accent1><a:srgbClr val="e48312"/></a:accent1>
+    </a:clrScheme>
+    <a:fontScheme name="Retrospect">
+      <a:majorFont><a:latin typeface="Calibri Light"/><a:ea typeface=""/><a:cs typeface=""/></a:majorFont>
+      <a:minorFont><a:latin typeface="Cambria"/><a:ea typeface=""/><a:cs typeface=""/></a:minorFont>
+    </a:fontScheme>
+    <a:fmtScheme name="Retrospect"><a:fillStyleLst><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:fillStyleLst></a:fmtScheme>
+  </a:themeElements>
+</a:theme>"#;
+
+    #[test]
+    fn test_parse_theme() {
+        let theme = ThemeParser::new().parse(THEME_XML).unwrap();
+
+        assert_eq!(theme.name, "Retrospect");
+        assert_eq!(theme.color(ThemeColor::Dark1), "#000000");
+        assert_eq!(theme.color(ThemeColor::Dark2), "#637052");
+        assert_eq!(theme.color(ThemeColor::Accent1), "#E48312");
+        // Missing slots keep the Office value
+        assert_eq!(theme.color(ThemeColor::Accent2), "#ED7D31");
+        assert_eq!(theme.fonts.minor.latin, "Cambria");
+        assert_eq!(theme.effects.name, "Retrospect");
+        assert!(theme.effects.source.as_deref().unwrap().starts_with("<a:fmtScheme"));
+        assert!(theme.effects.source.as_deref().unwrap().ends_with("</a:fmtScheme>"));
+    }
+
+    #[test]
+    fn test_theme_round_trip() {
+        let theme = ThemeParser::new().parse(THEME_XML).unwrap();
+        let xml = ThemeWriter::new().write(&theme);
+        let reparsed = ThemeParser::new().parse(&xml).unwrap();
+        assert_eq!(reparsed, theme);
+
+        // A theme without imported effects gets a default format scheme
+        let office = DocumentTheme::office();
+        let xml = ThemeWriter::new().write(&office);
+        assert!(xml.contains("<a:effectStyleLst>"));
+        let reparsed = ThemeParser::new().parse(&xml).unwrap();
+        assert_eq!(reparsed.colors, office.colors);
+        assert_eq!(reparsed.fonts, office.fonts);
+    }
+}
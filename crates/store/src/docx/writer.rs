@@ -2,15 +2,27 @@
 //!
 //! Creates ZIP archives with correct DOCX structure.
 
+use crate::docx::app_props::{write_app_props, APP_PROPS_PART};
 use crate::docx::content_types::{create_default_content_types, ContentTypes};
 use crate::docx::document_writer::DocumentWriter;
+use crate::docx::embedded_objects_io::{
+    embedding_part, is_package_part, object_preview_part, preview_content_type, ObjectRelationships,
+};
 use crate::docx::error::{DocxError, DocxResult};
+use crate::docx::footnotes_io::{NoteType, NotesWriter, ParsedNote};
+use crate::docx::glossary_io::{write_glossary, GLOSSARY_PART};
+use crate::docx::ink_io::{ink_part, InkWriter};
 use crate::docx::media_writer::MediaWriter;
 use crate::docx::numbering_writer::NumberingWriter;
 use crate::docx::relationships::{create_document_rels, create_root_rels, Relationships, TargetMode};
-use crate::docx::relationship_types;
+use crate::docx::sections_io::{header_part, WatermarkWriter};
+use crate::docx::settings_io::{write_settings, SETTINGS_PART};
+use crate::docx::{content_type_values, relationship_types};
 use crate::docx::styles_writer::StylesWriter;
-use doc_model::DocumentTree;
+use crate::docx::theme_io::{ThemeWriter, THEME_PART};
+use crate::image_store::{ImageOptimizationOptions, ImageOptimizationReport, ImageStore};
+use doc_model::{Document, DocumentTree, Node, NodeId};
+use std::collections::HashMap;
 use std::io::{Seek, Write};
 use zip::write::SimpleFileOptions;
 use zip::ZipWriter;
@@ -21,6 +33,10 @@ pub struct DocxWriter<W: Write + Seek> {
     content_types: ContentTypes,
     root_rels: Relationships,
     doc_rels: Relationships,
+    /// Image data for the document's images
+    images: ImageStore,
+    /// Picture compression applied to the written images
+    image_optimization: Option<ImageOptimizationOptions>,
 }
 
 impl<W: Write + Seek> DocxWriter<W> {
@@ -31,36 +47,96 @@ impl<W: Write + Seek> DocxWriter<W> {
             content_types: create_default_content_types(),
             root_rels: create_root_rels(),
             doc_rels: create_document_rels(),
+            images: ImageStore::new(),
+            image_optimization: None,
         }
     }
 
+    /// Set the store the document's image data is read from
+    pub fn with_images(mut self, images: ImageStore) -> Self {
+        self.images = images;
+        self
+    }
+
+    /// Compress pictures as they're written (like Word's Compress Pictures)
+    pub fn with_image_optimization(mut self, options: ImageOptimizationOptions) -> Self {
+        self.image_optimization = Some(options);
+        self
+    }
+
     /// Write a complete DOCX file from a DocumentTree
-    pub fn write(mut self, tree: &DocumentTree) -> DocxResult<()> {
+    pub fn write(self, tree: &DocumentTree) -> DocxResult<()> {
+        self.write_with_report(tree).map(|_| ())
+    }
+
+    /// Write a complete DOCX file, returning the space saved by compressing
+    /// pictures
+    pub fn write_with_report(mut self, tree: &DocumentTree) -> DocxResult<ImageOptimizationReport> {
+        // Write headers for section watermarks
+        let section_headers = self.write_section_headers(tree)?;
+
+        // Write endnotes.xml if there are endnotes
+        let endnote_references = self.write_endnotes(tree)?;
+
+        // Write an InkML part per ink node
+        let ink_parts = self.write_ink_parts(tree)?;
+
+        // Write the parts of embedded objects unchanged
+        let object_parts = self.write_object_parts(tree)?;
+
+        // Write media files (images)
+        let mut media_writer = MediaWriter::new();
+        if let Some(options) = self.image_optimization.clone() {
+            media_writer = media_writer.with_optimization(options);
+        }
+        let images = std::mem::take(&mut self.images);
+        let media = media_writer.write_media(tree, &images, &mut self)?;
+
         // Write document.xml
-        let doc_xml = DocumentWriter::new().write(tree)?;
+        let doc_xml = DocumentWriter::new()
+            .with_section_headers(section_headers)
+            .with_endnote_references(endnote_references)
+            .with_ink_parts(ink_parts)
+            .with_object_parts(object_parts)
+            .with_image_parts(media.images)
+            .write(tree)?;
         self.write_file("word/document.xml", &doc_xml)?;
 
         // Write styles.xml
         let styles_xml = StylesWriter::new().write(tree)?;
         self.write_file("word/styles.xml", &styles_xml)?;
 
+        // Write theme1.xml
+        let theme_xml = ThemeWriter::new().write(&tree.theme);
+        self.write_file(THEME_PART, &theme_xml)?;
+        self.doc_rels.add(relationship_types::THEME, "theme/theme1.xml", TargetMode::Internal);
+        self.content_types.add_override(THEME_PART, content_type_values::THEME);
+
         // Write numbering.xml if there are list definitions
         if tree.numbering_registry().all_abstract_nums().next().is_some() {
             let numbering_xml = NumberingWriter::new().write(tree)?;
             self.write_file("word/numbering.xml", &numbering_xml)?;
         }
 
-        // Write media files (images)
-        let media_writer = MediaWriter::new();
-        let media_rels = media_writer.write_media(tree, &mut self)?;
-
-        // Add image relationships
-        for (_rel_id, path) in media_rels {
-            self.doc_rels.add(
-                relationship_types::IMAGE,
-                &path,
-                TargetMode::Internal,
-            );
+        // Write docProps/app.xml if there is a hyperlink base
+        if let Some(app_xml) = write_app_props(&tree.document.metadata) {
+            self.write_file(APP_PROPS_PART, &app_xml)?;
+            self.root_rels.add(relationship_types::EXTENDED_PROPERTIES, APP_PROPS_PART, TargetMode::Internal);
+            self.content_types.add_override(APP_PROPS_PART, content_type_values::EXTENDED_PROPERTIES);
+        }
+
+        // Write settings.xml
+        let even_and_odd_headers = tree.sections.iter().any(|section| section.different_odd_even);
+        let settings_xml = write_settings(&tree.document, even_and_odd_headers);
+        self.write_file(SETTINGS_PART, &settings_xml)?;
+        self.doc_rels.add(relationship_types::SETTINGS, "settings.xml", TargetMode::Internal);
+
+        // Write the glossary document if there are building blocks
+        if !tree.building_blocks.is_empty() {
+            let glossary_xml = write_glossary(&tree.building_blocks)?;
+            self.write_file(GLOSSARY_PART, &glossary_xml)?;
+            self.doc_rels.add(relationship_types::GLOSSARY_DOCUMENT, "glossary/document.xml", TargetMode::Internal);
+            self.content_types.add_override(GLOSSARY_PART, content_type_values::GLOSSARY);
         }
 
         // Add hyperlink relationships from the document
@@ -81,7 +157,144 @@ impl<W: Write + Seek> DocxWriter<W> {
         // Finish the ZIP archive
         self.zip.finish()?;
 
-        Ok(())
+        Ok(media.report)
+    }
+
+    /// Write a header part for each section with its own watermark
+    ///
+    /// Returns the header relationship ID for each section that has one.
+    /// A section without a watermark after one that has one gets an empty
+    /// header, unless it's linked to the previous section.
+    fn write_section_headers(&mut self, tree: &DocumentTree) -> DocxResult<HashMap<NodeId, String>> {
+        let mut section_headers = HashMap::new();
+        let mut inherited = false;
+        let watermark_writer = WatermarkWriter::new();
+        for section in &tree.sections {
+            let watermark = section.watermark.as_ref();
+            if watermark.is_none() && (section.link_to_previous || !inherited) {
+                continue;
+            }
+            inherited = watermark.is_some();
+
+            let path = header_part(section_headers.len() + 1);
+            let header_xml = watermark_writer.write_header(watermark, &section.page_setup);
+            self.write_file(&path, &header_xml)?;
+            let target = path.trim_start_matches("word/");
+            let rel_id = self.doc_rels.add(relationship_types::HEADER, target, TargetMode::Internal);
+            self.content_types.add_override(&path, content_type_values::HEADER);
+            section_headers.insert(section.id(), rel_id);
+        }
+        Ok(section_headers)
+    }
+
+    /// Write the endnotes part, one paragraph per line of each note's text
+    ///
+    /// Returns the endnote references in each paragraph, as text offsets
+    /// and the DOCX note IDs.
+    fn write_endnotes(&mut self, tree: &DocumentTree) -> DocxResult<HashMap<NodeId, Vec<(usize, i64)>>> {
+        let mut references: HashMap<NodeId, Vec<(usize, i64)>> = HashMap::new();
+        let mut notes = Vec::new();
+        for (index, (note, _)) in tree.endnotes_in_order().into_iter().enumerate() {
+            // The notes writer numbers notes from 1 in order
+            let docx_id = index as i64 + 1;
+            if let Some(position) = note.reference_position {
+                references.entry(position.node_id).or_default().push((position.offset, docx_id));
+            }
+            let content: Vec<String> = note
+                .content()
+                .iter()
+                .filter_map(|&para_id| tree.get_paragraph(para_id))
+                .map(|para| {
+                    para.children()
+                        .iter()
+                        .filter_map(|&run_id| tree.get_run(run_id))
+                        .map(|run| run.text.as_str())
+                        .collect()
+                })
+                .collect();
+            notes.push(ParsedNote {
+                id: docx_id,
+                note_type: NoteType::Endnote,
+                content: content.join("\n"),
+                is_separator: false,
+                custom_mark: None,
+            });
+        }
+        if notes.is_empty() {
+            return Ok(references);
+        }
+
+        let endnotes_xml = NotesWriter::new().write_endnotes_xml(&notes)?;
+        self.write_file("word/endnotes.xml", &endnotes_xml)?;
+        self.doc_rels.add(relationship_types::ENDNOTES, "endnotes.xml", TargetMode::Internal);
+        self.content_types.add_override("word/endnotes.xml", content_type_values::ENDNOTES);
+        Ok(references)
+    }
+
+    /// Write an InkML part for each ink node with strokes
+    ///
+    /// Returns the part's relationship ID for each ink node.
+    fn write_ink_parts(&mut self, tree: &DocumentTree) -> DocxResult<HashMap<NodeId, String>> {
+        let mut inks: Vec<_> = tree.inks().filter(|ink| !ink.is_empty()).collect();
+        inks.sort_by_key(|ink| ink.id().as_uuid());
+
+        let ink_writer = InkWriter::new();
+        let mut ink_parts = HashMap::new();
+        for ink in inks {
+            let path = ink_part(ink_parts.len() + 1);
+            self.write_file(&path, &ink_writer.write_inkml(ink))?;
+            let target = path.trim_start_matches("word/");
+            let rel_id = self.doc_rels.add(relationship_types::CUSTOM_XML, target, TargetMode::Internal);
+            self.content_types.add_override(&path, content_type_values::INKML);
+            ink_parts.insert(ink.id(), rel_id);
+        }
+        Ok(ink_parts)
+    }
+
+    /// Write the data and preview parts of each embedded object
+    ///
+    /// Returns the parts' relationship IDs for each object. Linked objects
+    /// get an external relationship to their source file instead of data.
+    fn write_object_parts(&mut self, tree: &DocumentTree) -> DocxResult<HashMap<NodeId, ObjectRelationships>> {
+        let mut objects: Vec<_> = tree.embedded_objects().collect();
+        objects.sort_by_key(|object| object.id().as_uuid());
+
+        let mut object_parts = HashMap::new();
+        for (index, object) in objects.into_iter().enumerate() {
+            let mut rels = ObjectRelationships::default();
+            if let Some(target) = &object.link_target {
+                rels.object = Some(self.doc_rels.add(relationship_types::OLE_OBJECT, target, TargetMode::External));
+            } else if let Some(embedding) = &object.embedding {
+                let extension = embedding.extension();
+                let path = embedding_part(index + 1, &extension);
+                self.write_binary(&path, &embedding.data)?;
+                let rel_type = if is_package_part(&extension) {
+                    relationship_types::PACKAGE
+                } else {
+                    relationship_types::OLE_OBJECT
+                };
+                rels.object = Some(self.doc_rels.add(rel_type, path.trim_start_matches("word/"), TargetMode::Internal));
+                self.content_types.add_override(&path, &embedding.content_type);
+            }
+            if let Some(preview) = &object.preview {
+                let extension = preview.extension();
+                let path = object_preview_part(index + 1, &extension);
+                self.write_binary(&path, &preview.data)?;
+                rels.preview = Some(self.doc_rels.add(
+                    relationship_types::IMAGE,
+                    path.trim_start_matches("word/"),
+                    TargetMode::Internal,
+                ));
+                let content_type = if preview.content_type.is_empty() {
+                    preview_content_type(&extension)
+                } else {
+                    &preview.content_type
+                };
+                self.content_types.add_override(&path, content_type);
+            }
+            object_parts.insert(object.id(), rels);
+        }
+        Ok(object_parts)
     }
 
     /// Write a file to the ZIP archive
@@ -126,14 +339,9 @@ impl<W: Write + Seek> DocxWriter<W> {
     }
 }
 
-/// Generate a minimal settings.xml
+/// Generate settings.xml for a new document
 pub fn generate_settings_xml() -> String {
-    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
-<w:settings xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
-    <w:compat>
-        <w:compatSetting w:name="compatibilityMode" w:uri="http://schemas.microsoft.com/office/word" w:val="15"/>
-    </w:compat>
-</w:settings>"#.to_string()
+    write_settings(&Document::new(), false)
 }
 
 #[cfg(test)]
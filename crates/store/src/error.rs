@@ -18,6 +18,9 @@ pub enum StoreError {
 
     #[error("File not found: {0}")]
     FileNotFound(String),
+
+    #[error("Document is damaged: {0}")]
+    Corrupted(String),
 }
 
 pub type Result<T> = std::result::Result<T, StoreError>;
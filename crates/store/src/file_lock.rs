@@ -0,0 +1,370 @@
+//! Document locking
+//!
+//! An open document is locked with a sidecar file next to it, named like
+//! Word's owner files (`~$report.wdj`). The sidecar records who holds the
+//! lock, so another instance (on this or another machine sharing the drive)
+//! can show who has the document open and offer to open it read-only, wait
+//! for it to be released, or edit a copy instead.
+//!
+//! The holder also keeps an OS lock on the sidecar. The OS drops it when the
+//! process dies, so a sidecar left behind by a crash on this machine is
+//! recognised as stale and taken over. A stale sidecar from another machine
+//! can't be told apart from a live one and has to be broken explicitly.
+
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Prefix of lock sidecar file names
+pub const LOCK_FILE_PREFIX: &str = "~$";
+
+/// How long an unreadable sidecar is assumed to be mid-write by its holder
+const LOCK_WRITE_GRACE: Duration = Duration::from_secs(10);
+
+/// Who holds a document lock
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockOwner {
+    /// User name
+    pub user: String,
+    /// Machine name
+    pub host: String,
+    /// Process ID of the holding instance
+    pub pid: u32,
+    /// ID of the holding instance, unique per process run
+    pub instance_id: String,
+    /// When the lock was taken (Unix timestamp in ms)
+    pub acquired_at: u64,
+}
+
+impl LockOwner {
+    /// Owner details for this instance
+    pub fn current() -> Self {
+        static INSTANCE_ID: OnceLock<String> = OnceLock::new();
+        Self {
+            user: env_first(&["USER", "USERNAME"]).unwrap_or_else(|| "unknown".to_string()),
+            host: host_name(),
+            pid: std::process::id(),
+            instance_id: INSTANCE_ID
+                .get_or_init(|| uuid::Uuid::new_v4().to_string())
+                .clone(),
+            acquired_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+        }
+    }
+
+    /// Check if this instance is the owner
+    pub fn is_current_instance(&self) -> bool {
+        self.instance_id == Self::current().instance_id
+    }
+}
+
+/// Lock state of a document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum LockStatus {
+    /// No one holds a lock
+    Unlocked,
+    /// Another instance, or this one, holds the lock
+    Locked {
+        /// The holder, if the sidecar could be read
+        owner: Option<LockOwner>,
+    },
+    /// A lock was left behind by an instance on this machine that is gone
+    Stale {
+        /// The former holder, if the sidecar could be read
+        owner: Option<LockOwner>,
+    },
+}
+
+/// What to do when a document is locked by someone else
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LockConflictChoice {
+    /// Open the document without the ability to save over it
+    ReadOnly,
+    /// Open read-only and notify when the lock is released
+    Notify,
+    /// Edit a copy of the document
+    OpenCopy,
+}
+
+/// Result of trying to lock a document
+#[derive(Debug)]
+pub enum LockAttempt {
+    /// The lock is held by this instance until released or dropped
+    Acquired(DocumentLock),
+    /// Someone else holds the lock
+    Locked(Option<LockOwner>),
+}
+
+/// A lock held on a document
+///
+/// Dropping it releases the lock.
+#[derive(Debug)]
+pub struct DocumentLock {
+    document_path: PathBuf,
+    lock_path: PathBuf,
+    owner: LockOwner,
+    file: Option<File>,
+}
+
+impl DocumentLock {
+    /// Lock a document, taking over a stale lock
+    pub fn acquire(path: impl AsRef<Path>) -> Result<LockAttempt> {
+        let path = path.as_ref();
+        match lock_status(path) {
+            LockStatus::Locked { owner } => return Ok(LockAttempt::Locked(owner)),
+            LockStatus::Stale { owner } => {
+                tracing::info!("Taking over stale lock on {} held by {:?}", path.display(), owner);
+                remove_lock_file(&lock_file_path(path))?;
+            }
+            LockStatus::Unlocked => {}
+        }
+
+        match Self::create(path) {
+            Ok(lock) => Ok(LockAttempt::Acquired(lock)),
+            // Another instance created the sidecar first
+            Err(crate::StoreError::Io(e)) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                Ok(LockAttempt::Locked(read_owner(&lock_file_path(path))))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Lock a document, breaking any lock another instance holds
+    pub fn force_acquire(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        remove_lock_file(&lock_file_path(path))?;
+        Self::create(path)
+    }
+
+    fn create(path: &Path) -> Result<Self> {
+        let lock_path = lock_file_path(path);
+        let owner = LockOwner::current();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&lock_path)?;
+        // A shared lock still lets others read the sidecar on Windows
+        if let Err(e) = file.try_lock_shared() {
+            tracing::warn!("No OS lock on {}: {}", lock_path.display(), e);
+        }
+        file.write_all(serde_json::to_string(&owner)?.as_bytes())?;
+        file.sync_data()?;
+        Ok(Self {
+            document_path: path.to_path_buf(),
+            lock_path,
+            owner,
+            file: Some(file),
+        })
+    }
+
+    /// Path of the locked document
+    pub fn document_path(&self) -> &Path {
+        &self.document_path
+    }
+
+    /// Path of the lock sidecar
+    pub fn lock_path(&self) -> &Path {
+        &self.lock_path
+    }
+
+    /// Details recorded in the lock
+    pub fn owner(&self) -> &LockOwner {
+        &self.owner
+    }
+
+    /// Release the lock
+    pub fn release(mut self) -> Result<()> {
+        self.release_inner()
+    }
+
+    fn release_inner(&mut self) -> Result<()> {
+        let Some(file) = self.file.take() else {
+            return Ok(());
+        };
+        // Only remove the sidecar if it is still ours; the lock may have been broken
+        let ours = read_owner(&self.lock_path).is_some_and(|o| o.instance_id == self.owner.instance_id);
+        let _ = file.unlock();
+        drop(file);
+        if ours {
+            remove_lock_file(&self.lock_path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for DocumentLock {
+    fn drop(&mut self) {
+        if let Err(e) = self.release_inner() {
+            tracing::warn!("Failed to release lock {}: {}", self.lock_path.display(), e);
+        }
+    }
+}
+
+/// Path of the lock sidecar for a document
+pub fn lock_file_path(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}{}", LOCK_FILE_PREFIX, name))
+}
+
+/// Check whether a document is locked
+pub fn lock_status(path: impl AsRef<Path>) -> LockStatus {
+    let lock_path = lock_file_path(path);
+    let Ok(file) = File::open(&lock_path) else {
+        return LockStatus::Unlocked;
+    };
+    let owner = read_owner(&lock_path);
+
+    // A live holder keeps an OS lock on the sidecar
+    let held = match file.try_lock() {
+        Ok(()) => {
+            let _ = file.unlock();
+            false
+        }
+        Err(TryLockError::WouldBlock) => true,
+        Err(TryLockError::Error(_)) => true,
+    };
+    if held {
+        return LockStatus::Locked { owner };
+    }
+
+    let stale = match &owner {
+        // OS locks on network drives may not be seen across machines
+        Some(owner) => owner.host == host_name(),
+        // The holder may not have written its details yet
+        None => lock_file_age(&lock_path).is_some_and(|age| age > LOCK_WRITE_GRACE),
+    };
+    if stale {
+        LockStatus::Stale { owner }
+    } else {
+        LockStatus::Locked { owner }
+    }
+}
+
+/// Remove a lock left behind by a crashed instance on this machine
+///
+/// Returns true if a stale lock was removed. Live locks are left alone.
+pub fn release_stale_lock(path: impl AsRef<Path>) -> Result<bool> {
+    let path = path.as_ref();
+    match lock_status(path) {
+        LockStatus::Stale { .. } => {
+            remove_lock_file(&lock_file_path(path))?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Copy a locked document so the copy can be edited
+///
+/// The copy is named `<name> (Copy).<ext>` in `dest_dir`, numbered if that
+/// name is taken.
+pub fn copy_for_editing(path: impl AsRef<Path>, dest_dir: impl AsRef<Path>) -> Result<PathBuf> {
+    let path = path.as_ref();
+    let dest_dir = dest_dir.as_ref();
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "Document".to_string());
+    let extension = path.extension().map(|e| e.to_string_lossy().into_owned());
+
+    std::fs::create_dir_all(dest_dir)?;
+    let mut number = 1;
+    let copy_path = loop {
+        let suffix = if number == 1 { " (Copy)".to_string() } else { format!(" (Copy {})", number) };
+        let name = match &extension {
+            Some(extension) => format!("{}{}.{}", stem, suffix, extension),
+            None => format!("{}{}", stem, suffix),
+        };
+        let candidate = dest_dir.join(name);
+        if !candidate.exists() {
+            break candidate;
+        }
+        number += 1;
+    };
+    std::fs::copy(path, &copy_path)?;
+    Ok(copy_path)
+}
+
+fn read_owner(lock_path: &Path) -> Option<LockOwner> {
+    let content = std::fs::read_to_string(lock_path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn remove_lock_file(lock_path: &Path) -> Result<()> {
+    match std::fs::remove_file(lock_path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn lock_file_age(lock_path: &Path) -> Option<Duration> {
+    let modified = std::fs::metadata(lock_path).ok()?.modified().ok()?;
+    SystemTime::now().duration_since(modified).ok()
+}
+
+fn env_first(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|v| !v.is_empty()))
+}
+
+fn host_name() -> String {
+    env_first(&["COMPUTERNAME", "HOSTNAME"])
+        .or_else(|| {
+            std::fs::read_to_string("/etc/hostname")
+                .ok()
+                .map(|h| h.trim().to_string())
+                .filter(|h| !h.is_empty())
+        })
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock_conflict_release_and_stale_takeover() {
+        let temp_dir = TempDir::new().unwrap();
+        let doc = temp_dir.path().join("report.wdj");
+        std::fs::write(&doc, "{}").unwrap();
+
+        let LockAttempt::Acquired(lock) = DocumentLock::acquire(&doc).unwrap() else {
+            panic!("expected to acquire the lock");
+        };
+        assert_eq!(lock.lock_path(), temp_dir.path().join("~$report.wdj"));
+        match DocumentLock::acquire(&doc).unwrap() {
+            LockAttempt::Locked(owner) => assert_eq!(owner.unwrap().pid, std::process::id()),
+            LockAttempt::Acquired(_) => panic!("document should be locked"),
+        }
+        lock.release().unwrap();
+        assert_eq!(lock_status(&doc), LockStatus::Unlocked);
+
+        // A sidecar with no OS lock, as left by a crash on this machine
+        let mut crashed = LockOwner::current();
+        crashed.instance_id = "crashed".to_string();
+        std::fs::write(lock_file_path(&doc), serde_json::to_string(&crashed).unwrap()).unwrap();
+        assert!(matches!(lock_status(&doc), LockStatus::Stale { .. }));
+        assert!(matches!(DocumentLock::acquire(&doc).unwrap(), LockAttempt::Acquired(_)));
+        assert!(!lock_file_path(&doc).exists());
+
+        let copy = copy_for_editing(&doc, temp_dir.path()).unwrap();
+        assert_eq!(copy.file_name().unwrap(), "report (Copy).wdj");
+    }
+}
@@ -1,6 +1,7 @@
 //! Internal document format specification
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// File format version
 pub const FORMAT_VERSION: u32 = 1;
@@ -54,6 +55,13 @@ mod chrono_lite {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentFile {
     pub header: FileHeader,
+    /// Checksum of each part of the document (see [`crate::part_checksums`]),
+    /// written before the document so a truncated file still has them
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub checksums: BTreeMap<String, String>,
+    /// Digital signatures over the document
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signatures: Vec<crate::signatures::NativeSignature>,
     pub document: doc_model::DocumentTree,
 }
 
@@ -61,6 +69,8 @@ impl DocumentFile {
     pub fn new(document: doc_model::DocumentTree) -> Self {
         Self {
             header: FileHeader::new(document.root_id().to_string()),
+            checksums: BTreeMap::new(),
+            signatures: Vec::new(),
             document,
         }
     }
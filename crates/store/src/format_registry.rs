@@ -0,0 +1,369 @@
+//! File format dispatch
+//!
+//! The `FormatRegistry` maps file extensions to import/export handlers. It
+//! starts with the built-in formats (native `.wdj`, DOCX, RTF, and ODT) and
+//! accepts additional handlers, such as formats contributed by plugins.
+//! An extension belongs to the first handler registered for it, so added
+//! formats cannot shadow the built-in ones.
+
+use crate::{Result, StoreError, FILE_EXTENSION};
+use doc_model::DocumentTree;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Converts between a file format's bytes and a document tree
+pub trait FormatHandler: Send + Sync {
+    /// Parse a document from file contents
+    fn import(&self, bytes: &[u8]) -> Result<DocumentTree>;
+
+    /// Serialize a document to file contents
+    fn export(&self, tree: &DocumentTree) -> Result<Vec<u8>>;
+}
+
+/// Description of a registered file format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatDescriptor {
+    /// Format identifier (e.g. `docx`)
+    pub id: String,
+    /// File extensions, lowercase without the dot
+    pub extensions: Vec<String>,
+    /// MIME type
+    pub mime_type: String,
+    /// Human-readable name
+    pub display_name: String,
+    /// Whether documents can be opened from this format
+    pub supports_import: bool,
+    /// Whether documents can be saved to this format
+    pub supports_export: bool,
+    /// Plugin that contributed the format (`None` for built-ins)
+    pub provider: Option<String>,
+}
+
+impl FormatDescriptor {
+    pub fn new(
+        id: impl Into<String>,
+        extensions: &[&str],
+        mime_type: impl Into<String>,
+        display_name: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            extensions: extensions.iter().map(|ext| normalize_extension(ext)).collect(),
+            mime_type: mime_type.into(),
+            display_name: display_name.into(),
+            supports_import: true,
+            supports_export: true,
+            provider: None,
+        }
+    }
+
+    pub fn with_import(mut self, supported: bool) -> Self {
+        self.supports_import = supported;
+        self
+    }
+
+    pub fn with_export(mut self, supported: bool) -> Self {
+        self.supports_export = supported;
+        self
+    }
+
+    pub fn with_provider(mut self, provider: impl Into<String>) -> Self {
+        self.provider = Some(provider.into());
+        self
+    }
+
+    /// Check if the format handles an extension
+    pub fn handles(&self, extension: &str) -> bool {
+        let extension = normalize_extension(extension);
+        self.extensions.contains(&extension)
+    }
+}
+
+fn normalize_extension(extension: &str) -> String {
+    extension.trim_start_matches('.').to_lowercase()
+}
+
+// =============================================================================
+// Built-in Handlers
+// =============================================================================
+
+struct NativeFormat;
+
+impl FormatHandler for NativeFormat {
+    fn import(&self, bytes: &[u8]) -> Result<DocumentTree> {
+        let json = std::str::from_utf8(bytes)
+            .map_err(|e| StoreError::InvalidFormat(format!("Document is not UTF-8: {}", e)))?;
+        crate::deserialize(json)
+    }
+
+    fn export(&self, tree: &DocumentTree) -> Result<Vec<u8>> {
+        Ok(crate::serialize(tree)?.into_bytes())
+    }
+}
+
+struct DocxFormat;
+
+impl FormatHandler for DocxFormat {
+    fn import(&self, bytes: &[u8]) -> Result<DocumentTree> {
+        crate::import_docx_bytes(bytes).map_err(|e| StoreError::InvalidFormat(e.to_string()))
+    }
+
+    fn export(&self, tree: &DocumentTree) -> Result<Vec<u8>> {
+        crate::export_docx_bytes(tree).map_err(|e| StoreError::InvalidFormat(e.to_string()))
+    }
+}
+
+struct RtfFormat;
+
+impl FormatHandler for RtfFormat {
+    fn import(&self, bytes: &[u8]) -> Result<DocumentTree> {
+        crate::import_rtf_bytes(bytes)
+            .map(|result| result.tree)
+            .map_err(|e| StoreError::InvalidFormat(e.to_string()))
+    }
+
+    fn export(&self, tree: &DocumentTree) -> Result<Vec<u8>> {
+        crate::export_rtf_bytes(tree).map_err(|e| StoreError::InvalidFormat(e.to_string()))
+    }
+}
+
+struct OdtFormat;
+
+impl FormatHandler for OdtFormat {
+    fn import(&self, bytes: &[u8]) -> Result<DocumentTree> {
+        crate::import_odt_bytes(bytes)
+            .map(|result| result.tree)
+            .map_err(|e| StoreError::InvalidFormat(e.to_string()))
+    }
+
+    fn export(&self, _tree: &DocumentTree) -> Result<Vec<u8>> {
+        Err(StoreError::InvalidFormat("ODT export is not supported".to_string()))
+    }
+}
+
+// =============================================================================
+// Format Registry
+// =============================================================================
+
+/// Registry of file formats keyed by extension
+#[derive(Clone)]
+pub struct FormatRegistry {
+    formats: Vec<(FormatDescriptor, Arc<dyn FormatHandler>)>,
+}
+
+impl FormatRegistry {
+    /// Create a registry with the built-in formats
+    pub fn new() -> Self {
+        let formats: Vec<(FormatDescriptor, Arc<dyn FormatHandler>)> = vec![
+            (
+                FormatDescriptor::new(FILE_EXTENSION, &[FILE_EXTENSION], "application/json", "Go Word Document"),
+                Arc::new(NativeFormat),
+            ),
+            (
+                FormatDescriptor::new(
+                    "docx",
+                    &["docx"],
+                    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+                    "Word Document",
+                ),
+                Arc::new(DocxFormat),
+            ),
+            (
+                FormatDescriptor::new("rtf", &["rtf"], "application/rtf", "Rich Text Format"),
+                Arc::new(RtfFormat),
+            ),
+            (
+                FormatDescriptor::new(
+                    "odt",
+                    &["odt"],
+                    "application/vnd.oasis.opendocument.text",
+                    "OpenDocument Text",
+                )
+                .with_export(false),
+                Arc::new(OdtFormat),
+            ),
+        ];
+        Self { formats }
+    }
+
+    /// Register a format handler
+    ///
+    /// Fails if another format already handles one of its extensions.
+    pub fn register(
+        &mut self,
+        descriptor: FormatDescriptor,
+        handler: Arc<dyn FormatHandler>,
+    ) -> Result<()> {
+        if descriptor.extensions.is_empty() {
+            return Err(StoreError::InvalidFormat(format!(
+                "Format '{}' has no extensions",
+                descriptor.id
+            )));
+        }
+        for extension in &descriptor.extensions {
+            if let Some(existing) = self.for_extension(extension) {
+                return Err(StoreError::InvalidFormat(format!(
+                    "Extension '{}' is already handled by '{}'",
+                    extension, existing.id
+                )));
+            }
+        }
+
+        self.formats.push((descriptor, handler));
+        Ok(())
+    }
+
+    /// Remove every format contributed by a provider
+    pub fn unregister_provider(&mut self, provider: &str) {
+        self.formats
+            .retain(|(descriptor, _)| descriptor.provider.as_deref() != Some(provider));
+    }
+
+    /// Get all registered formats
+    pub fn formats(&self) -> impl Iterator<Item = &FormatDescriptor> {
+        self.formats.iter().map(|(descriptor, _)| descriptor)
+    }
+
+    /// Get formats that support import
+    pub fn import_formats(&self) -> Vec<&FormatDescriptor> {
+        self.formats().filter(|f| f.supports_import).collect()
+    }
+
+    /// Get formats that support export
+    pub fn export_formats(&self) -> Vec<&FormatDescriptor> {
+        self.formats().filter(|f| f.supports_export).collect()
+    }
+
+    /// Find the format for an extension
+    pub fn for_extension(&self, extension: &str) -> Option<&FormatDescriptor> {
+        self.formats().find(|f| f.handles(extension))
+    }
+
+    fn handler(&self, extension: &str) -> Result<(&FormatDescriptor, &dyn FormatHandler)> {
+        self.formats
+            .iter()
+            .find(|(descriptor, _)| descriptor.handles(extension))
+            .map(|(descriptor, handler)| (descriptor, handler.as_ref()))
+            .ok_or_else(|| {
+                StoreError::InvalidFormat(format!("Unsupported file extension: {}", extension))
+            })
+    }
+
+    /// Import a document from bytes in the format for an extension
+    pub fn load_from_bytes(&self, extension: &str, bytes: &[u8]) -> Result<DocumentTree> {
+        let (descriptor, handler) = self.handler(extension)?;
+        if !descriptor.supports_import {
+            return Err(StoreError::InvalidFormat(format!(
+                "{} files cannot be opened",
+                descriptor.display_name
+            )));
+        }
+        handler.import(bytes)
+    }
+
+    /// Export a document to bytes in the format for an extension
+    pub fn export_to_bytes(&self, extension: &str, tree: &DocumentTree) -> Result<Vec<u8>> {
+        let (descriptor, handler) = self.handler(extension)?;
+        if !descriptor.supports_export {
+            return Err(StoreError::InvalidFormat(format!(
+                "{} files cannot be saved",
+                descriptor.display_name
+            )));
+        }
+        handler.export(tree)
+    }
+
+    /// Open a document, choosing the format by file extension
+    pub fn load_from_file(&self, path: impl AsRef<Path>) -> Result<DocumentTree> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(StoreError::FileNotFound(path.display().to_string()));
+        }
+
+        let bytes = std::fs::read(path)?;
+        self.load_from_bytes(&path_extension(path)?, &bytes)
+    }
+
+    /// Save a document, choosing the format by file extension
+    pub fn save_to_file(&self, tree: &DocumentTree, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let bytes = self.export_to_bytes(&path_extension(path)?, tree)?;
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+}
+
+fn path_extension(path: &Path) -> Result<String> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(normalize_extension)
+        .ok_or_else(|| StoreError::InvalidFormat(format!("No file extension: {}", path.display())))
+}
+
+impl Default for FormatRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for FormatRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.formats()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::{Node, Paragraph, Run};
+
+    /// Plain text, one paragraph per line
+    struct TextFormat;
+
+    impl FormatHandler for TextFormat {
+        fn import(&self, bytes: &[u8]) -> Result<DocumentTree> {
+            let mut tree = DocumentTree::new();
+            let root_id = tree.root_id();
+            for (i, line) in String::from_utf8_lossy(bytes).lines().enumerate() {
+                let para_id = tree.insert_paragraph(Paragraph::new(), root_id, Some(i))?;
+                tree.insert_run(Run::new(line), para_id, None)?;
+            }
+            Ok(tree)
+        }
+
+        fn export(&self, tree: &DocumentTree) -> Result<Vec<u8>> {
+            Ok(tree.text_content().into_bytes())
+        }
+    }
+
+    #[test]
+    fn test_registered_formats_dispatch_by_extension() {
+        let mut registry = FormatRegistry::new();
+        assert!(registry.for_extension(".DOCX").is_some());
+        assert!(registry.export_formats().iter().all(|f| f.id != "odt"));
+
+        let text = FormatDescriptor::new("plain", &["txt", ".text"], "text/plain", "Plain Text")
+            .with_provider("com.example.text");
+        registry.register(text, Arc::new(TextFormat)).unwrap();
+        assert!(registry.import_formats().iter().any(|f| f.id == "plain"));
+
+        // Built-in extensions can't be taken over
+        let docx = FormatDescriptor::new("fake", &["docx"], "text/plain", "Fake");
+        assert!(registry.register(docx, Arc::new(TextFormat)).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.TXT");
+        std::fs::write(&path, "one\ntwo").unwrap();
+        let tree = registry.load_from_file(&path).unwrap();
+        assert_eq!(tree.document.children().len(), 2);
+
+        let native = dir.path().join("notes.wdj");
+        registry.save_to_file(&tree, &native).unwrap();
+        let reloaded = registry.load_from_file(&native).unwrap();
+        assert_eq!(reloaded.text_content(), "one\ntwo\n");
+
+        registry.unregister_provider("com.example.text");
+        assert!(registry.load_from_file(&path).is_err());
+        assert!(registry.export_to_bytes("odt", &tree).is_err());
+    }
+}
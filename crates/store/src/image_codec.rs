@@ -0,0 +1,449 @@
+//! Pixel decoding, resampling and encoding for picture compression
+//!
+//! PNG is handled here directly on top of flate2 (8-bit, non-interlaced
+//! images); JPEG goes through jpeg-decoder and jpeg-encoder, and WebP
+//! through image-webp.
+
+use crate::image_store::{ImageStoreError, Result};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+/// PNG file signature
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// A decoded image with straight RGBA pixels
+#[derive(Debug, Clone)]
+pub(crate) struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Whether every pixel is fully opaque
+    pub fn is_opaque(&self) -> bool {
+        self.data.chunks_exact(4).all(|p| p[3] == 255)
+    }
+
+    /// Downsample with an area-averaging (box) filter
+    pub fn resample(&self, width: u32, height: u32) -> RgbaImage {
+        let (src_w, src_h) = (self.width as usize, self.height as usize);
+        let (dst_w, dst_h) = (width.max(1) as usize, height.max(1) as usize);
+        let mut data = Vec::with_capacity(dst_w * dst_h * 4);
+
+        for dy in 0..dst_h {
+            let y0 = dy * src_h / dst_h;
+            let y1 = ((dy + 1) * src_h / dst_h).max(y0 + 1);
+            for dx in 0..dst_w {
+                let x0 = dx * src_w / dst_w;
+                let x1 = ((dx + 1) * src_w / dst_w).max(x0 + 1);
+
+                // Weight color by alpha so transparent pixels don't bleed
+                let mut sums = [0u64; 4];
+                for y in y0..y1 {
+                    for x in x0..x1 {
+                        let p = &self.data[(y * src_w + x) * 4..][..4];
+                        let alpha = p[3] as u64;
+                        sums[0] += p[0] as u64 * alpha;
+                        sums[1] += p[1] as u64 * alpha;
+                        sums[2] += p[2] as u64 * alpha;
+                        sums[3] += alpha;
+                    }
+                }
+                // Fully transparent areas come out as transparent black
+                let count = ((y1 - y0) * (x1 - x0)) as u64;
+                let alpha_total = sums[3].max(1);
+                for sum in &sums[..3] {
+                    data.push(((sum + alpha_total / 2) / alpha_total) as u8);
+                }
+                data.push(((sums[3] + count / 2) / count) as u8);
+            }
+        }
+
+        RgbaImage {
+            width: dst_w as u32,
+            height: dst_h as u32,
+            data,
+        }
+    }
+}
+
+/// Decode an 8-bit, non-interlaced PNG
+pub(crate) fn decode_png(data: &[u8]) -> Result<RgbaImage> {
+    let unsupported = |what: &str| ImageStoreError::DecodeError(format!("Unsupported PNG: {}", what));
+    if !data.starts_with(&PNG_SIGNATURE) {
+        return Err(ImageStoreError::DecodeError("Missing PNG signature".into()));
+    }
+
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut compressed = Vec::new();
+    let mut pos = PNG_SIGNATURE.len();
+    while pos + 8 <= data.len() {
+        let len = u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+        let kind = &data[pos + 4..pos + 8];
+        let body = data
+            .get(pos + 8..pos + 8 + len)
+            .ok_or_else(|| ImageStoreError::DecodeError("Truncated PNG chunk".into()))?;
+        match kind {
+            b"IHDR" if body.len() >= 13 => header = Some(body),
+            b"PLTE" => palette = body,
+            b"tRNS" => transparency = body,
+            b"IDAT" => compressed.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        pos += 12 + len;
+    }
+
+    let header = header.ok_or_else(|| ImageStoreError::DecodeError("Missing IHDR".into()))?;
+    let width = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+    let height = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+    let (bit_depth, color_type, interlace) = (header[8], header[9], header[12]);
+    if bit_depth != 8 {
+        return Err(unsupported("bit depth"));
+    }
+    if interlace != 0 {
+        return Err(unsupported("interlacing"));
+    }
+    let channels = match color_type {
+        0 | 3 => 1,
+        4 => 2,
+        2 => 3,
+        6 => 4,
+        _ => return Err(unsupported("color type")),
+    };
+    if width == 0 || height == 0 {
+        return Err(ImageStoreError::DecodeError("Empty PNG".into()));
+    }
+
+    let mut raw = Vec::new();
+    ZlibDecoder::new(compressed.as_slice()).read_to_end(&mut raw)?;
+    let stride = width as usize * channels;
+    if raw.len() < (stride + 1) * height as usize {
+        return Err(ImageStoreError::DecodeError("Truncated PNG image data".into()));
+    }
+
+    let mut pixels = vec![0u8; stride * height as usize];
+    for y in 0..height as usize {
+        let filter = raw[y * (stride + 1)];
+        let line = &raw[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        let (done, rest) = pixels.split_at_mut(y * stride);
+        let previous = if y == 0 { None } else { Some(&done[(y - 1) * stride..]) };
+        unfilter(filter, line, previous, &mut rest[..stride], channels)?;
+    }
+
+    let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+    for p in pixels.chunks_exact(channels) {
+        match color_type {
+            0 => rgba.extend_from_slice(&[p[0], p[0], p[0], 255]),
+            4 => rgba.extend_from_slice(&[p[0], p[0], p[0], p[1]]),
+            2 => rgba.extend_from_slice(&[p[0], p[1], p[2], 255]),
+            6 => rgba.extend_from_slice(p),
+            _ => {
+                let index = p[0] as usize;
+                let rgb = palette
+                    .get(index * 3..index * 3 + 3)
+                    .ok_or_else(|| ImageStoreError::DecodeError("Palette index out of range".into()))?;
+                let alpha = transparency.get(index).copied().unwrap_or(255);
+                rgba.extend_from_slice(&[rgb[0], rgb[1], rgb[2], alpha]);
+            }
+        }
+    }
+
+    Ok(RgbaImage {
+        width,
+        height,
+        data: rgba,
+    })
+}
+
+/// Reverse one PNG scanline filter
+fn unfilter(filter: u8, line: &[u8], previous: Option<&[u8]>, out: &mut [u8], bpp: usize) -> Result<()> {
+    for i in 0..line.len() {
+        let left = if i >= bpp { out[i - bpp] } else { 0 };
+        let up = previous.map_or(0, |row| row[i]);
+        let up_left = if i >= bpp { previous.map_or(0, |row| row[i - bpp]) } else { 0 };
+        let predictor = match filter {
+            0 => 0,
+            1 => left,
+            2 => up,
+            3 => ((left as u16 + up as u16) / 2) as u8,
+            4 => paeth(left, up, up_left),
+            _ => return Err(ImageStoreError::DecodeError(format!("Invalid PNG filter {}", filter))),
+        };
+        out[i] = line[i].wrapping_add(predictor);
+    }
+    Ok(())
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Encode as an 8-bit PNG, dropping the alpha channel when fully opaque
+pub(crate) fn encode_png(image: &RgbaImage) -> Result<Vec<u8>> {
+    let opaque = image.is_opaque();
+    let channels = if opaque { 3 } else { 4 };
+    let stride = image.width as usize * channels;
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    let mut previous = vec![0u8; stride];
+    let mut line = Vec::with_capacity(stride);
+    let mut filtered = Vec::with_capacity(stride + 1);
+    for row in image.data.chunks_exact(image.width as usize * 4) {
+        line.clear();
+        for p in row.chunks_exact(4) {
+            line.extend_from_slice(&p[..channels]);
+        }
+        // Photographs compress best with the Paeth predictor
+        filtered.clear();
+        filtered.push(4);
+        for i in 0..stride {
+            let left = if i >= channels { line[i - channels] } else { 0 };
+            let up_left = if i >= channels { previous[i - channels] } else { 0 };
+            filtered.push(line[i].wrapping_sub(paeth(left, previous[i], up_left)));
+        }
+        encoder.write_all(&filtered)?;
+        std::mem::swap(&mut previous, &mut line);
+    }
+    let compressed = encoder.finish()?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&image.width.to_be_bytes());
+    header.extend_from_slice(&image.height.to_be_bytes());
+    header.extend_from_slice(&[8, if opaque { 2 } else { 6 }, 0, 0, 0]);
+
+    let mut png = Vec::with_capacity(compressed.len() + 64);
+    png.extend_from_slice(&PNG_SIGNATURE);
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &compressed);
+    write_chunk(&mut png, b"IEND", &[]);
+    Ok(png)
+}
+
+/// Append a PNG chunk with its length and CRC
+fn write_chunk(output: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    output.extend_from_slice(kind);
+    output.extend_from_slice(data);
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(kind);
+    crc.update(data);
+    output.extend_from_slice(&crc.finalize().to_be_bytes());
+}
+
+/// Decode a grayscale or RGB JPEG
+pub(crate) fn decode_jpeg(data: &[u8]) -> Result<RgbaImage> {
+    let mut decoder = jpeg_decoder::Decoder::new(data);
+    let pixels = decoder
+        .decode()
+        .map_err(|e| ImageStoreError::DecodeError(e.to_string()))?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| ImageStoreError::DecodeError("Missing JPEG header".into()))?;
+
+    let mut rgba = Vec::with_capacity(info.width as usize * info.height as usize * 4);
+    match info.pixel_format {
+        jpeg_decoder::PixelFormat::L8 => {
+            for &l in &pixels {
+                rgba.extend_from_slice(&[l, l, l, 255]);
+            }
+        }
+        jpeg_decoder::PixelFormat::RGB24 => {
+            for p in pixels.chunks_exact(3) {
+                rgba.extend_from_slice(&[p[0], p[1], p[2], 255]);
+            }
+        }
+        _ => return Err(ImageStoreError::DecodeError("Unsupported JPEG color space".into())),
+    }
+
+    Ok(RgbaImage {
+        width: info.width as u32,
+        height: info.height as u32,
+        data: rgba,
+    })
+}
+
+/// Encode as a baseline JPEG at the given quality (1-100)
+pub(crate) fn encode_jpeg(image: &RgbaImage, quality: u8) -> Result<Vec<u8>> {
+    let width = u16::try_from(image.width).map_err(|_| ImageStoreError::EncodeError("JPEG too wide".into()))?;
+    let height = u16::try_from(image.height).map_err(|_| ImageStoreError::EncodeError("JPEG too tall".into()))?;
+    let rgb: Vec<u8> = image.data.chunks_exact(4).flat_map(|p| [p[0], p[1], p[2]]).collect();
+
+    let mut output = Vec::new();
+    jpeg_encoder::Encoder::new(&mut output, quality.clamp(1, 100))
+        .encode(&rgb, width, height, jpeg_encoder::ColorType::Rgb)
+        .map_err(|e| ImageStoreError::EncodeError(e.to_string()))?;
+    Ok(output)
+}
+
+/// Decode a lossy or lossless WebP (the first frame if animated)
+pub(crate) fn decode_webp(data: &[u8]) -> Result<RgbaImage> {
+    let error = |e: image_webp::DecodingError| ImageStoreError::DecodeError(e.to_string());
+    let mut decoder = image_webp::WebPDecoder::new(std::io::Cursor::new(data)).map_err(error)?;
+    let (width, height) = decoder.dimensions();
+    let size = decoder
+        .output_buffer_size()
+        .ok_or_else(|| ImageStoreError::DecodeError("WebP too large".into()))?;
+    let mut pixels = vec![0u8; size];
+    decoder.read_image(&mut pixels).map_err(error)?;
+
+    let data = if decoder.has_alpha() {
+        pixels
+    } else {
+        pixels.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect()
+    };
+    Ok(RgbaImage { width, height, data })
+}
+
+/// Width and height from a WebP header
+pub(crate) fn webp_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    image_webp::WebPDecoder::new(std::io::Cursor::new(data))
+        .ok()
+        .map(|decoder| decoder.dimensions())
+}
+
+/// Horizontal resolution from a PNG pHYs chunk, if given in pixels per meter
+pub(crate) fn png_dpi(data: &[u8]) -> Option<f32> {
+    let mut pos = PNG_SIGNATURE.len();
+    while pos + 8 <= data.len() {
+        let len = u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+        match &data[pos + 4..pos + 8] {
+            b"pHYs" => {
+                let body = data.get(pos + 8..pos + 8 + len).filter(|body| body.len() >= 9)?;
+                let per_meter = u32::from_be_bytes([body[0], body[1], body[2], body[3]]);
+                return (body[8] == 1 && per_meter > 0).then_some(per_meter as f32 * 0.0254);
+            }
+            b"IDAT" | b"IEND" => return None,
+            _ => pos += 12 + len,
+        }
+    }
+    None
+}
+
+/// Decode an uncompressed BMP, either a file or a bare DIB (the clipboard
+/// form, without the file header). Returns the image and its resolution
+/// in dots per inch if recorded.
+pub(crate) fn decode_bmp(data: &[u8]) -> Result<(RgbaImage, Option<f32>)> {
+    let invalid = |what: &str| ImageStoreError::DecodeError(format!("Invalid BMP: {}", what));
+    let u16_at = |pos: usize| data.get(pos..pos + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let u32_at = |pos: usize| data.get(pos..pos + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+
+    // File header, then the info header
+    let (info, pixel_offset) = if data.starts_with(b"BM") {
+        (14, u32_at(10).map(|offset| offset as usize))
+    } else {
+        (0, None)
+    };
+    let header_size = u32_at(info).ok_or_else(|| invalid("truncated header"))? as usize;
+    if header_size < 40 {
+        return Err(invalid("unsupported header"));
+    }
+    let width = u32_at(info + 4).ok_or_else(|| invalid("truncated header"))? as i32;
+    let height = u32_at(info + 8).ok_or_else(|| invalid("truncated header"))? as i32;
+    let bit_count = u16_at(info + 14).ok_or_else(|| invalid("truncated header"))?;
+    let compression = u32_at(info + 16).ok_or_else(|| invalid("truncated header"))?;
+    let dpi = u32_at(info + 24).filter(|&per_meter| per_meter > 0).map(|per_meter| per_meter as f32 * 0.0254);
+    let colors_used = u32_at(info + 32).unwrap_or(0) as usize;
+    if width <= 0 || height == 0 {
+        return Err(invalid("empty image"));
+    }
+    let (width, top_down) = (width as usize, height < 0);
+    let height = height.unsigned_abs() as usize;
+
+    // BI_RGB, or BI_BITFIELDS with masks after a 40-byte header
+    let mut table = info + header_size;
+    let masks = match (compression, bit_count) {
+        (0, 32) => [0x00FF_0000, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000],
+        (0, _) => [0; 4],
+        (3, 32) => {
+            let at = if header_size == 40 { table } else { info + 40 };
+            let mask = |i: usize| u32_at(at + i * 4).unwrap_or(0);
+            let alpha = if header_size >= 56 { u32_at(info + 52).unwrap_or(0) } else { 0 };
+            if header_size == 40 {
+                table += 12;
+            }
+            [mask(0), mask(1), mask(2), alpha]
+        }
+        _ => return Err(invalid("compressed images aren't supported")),
+    };
+
+    let palette_len = match bit_count {
+        1 | 4 | 8 if colors_used == 0 => 1 << bit_count,
+        1 | 4 | 8 => colors_used,
+        24 | 32 => 0,
+        _ => return Err(invalid("unsupported bit depth")),
+    };
+    let palette = data
+        .get(table..table + palette_len * 4)
+        .ok_or_else(|| invalid("truncated palette"))?;
+    let pixel_offset = pixel_offset.unwrap_or(table + palette_len * 4);
+
+    let stride = (width * bit_count as usize).div_ceil(32) * 4;
+    let pixels = data
+        .get(pixel_offset..pixel_offset + stride * height)
+        .ok_or_else(|| invalid("truncated pixel data"))?;
+
+    let channel = |value: u32, mask: u32| -> u8 {
+        if mask == 0 {
+            return 0;
+        }
+        let value = (value & mask) >> mask.trailing_zeros();
+        let max = mask >> mask.trailing_zeros();
+        ((value * 255 + max / 2) / max) as u8
+    };
+
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        let row = if top_down { y } else { height - 1 - y };
+        let line = &pixels[row * stride..(row + 1) * stride];
+        for x in 0..width {
+            match bit_count {
+                32 => {
+                    let p = u32::from_le_bytes([line[x * 4], line[x * 4 + 1], line[x * 4 + 2], line[x * 4 + 3]]);
+                    rgba.extend_from_slice(&[
+                        channel(p, masks[0]),
+                        channel(p, masks[1]),
+                        channel(p, masks[2]),
+                        if masks[3] == 0 { 255 } else { channel(p, masks[3]) },
+                    ]);
+                }
+                24 => rgba.extend_from_slice(&[line[x * 3 + 2], line[x * 3 + 1], line[x * 3], 255]),
+                _ => {
+                    let bits = bit_count as usize;
+                    let byte = line[x * bits / 8];
+                    let shift = 8 - bits - (x * bits % 8);
+                    let index = ((byte >> shift) as usize) & ((1 << bits) - 1);
+                    let entry = palette
+                        .get(index * 4..index * 4 + 3)
+                        .ok_or_else(|| invalid("palette index out of range"))?;
+                    rgba.extend_from_slice(&[entry[2], entry[1], entry[0], 255]);
+                }
+            }
+        }
+    }
+
+    // Many screenshot tools leave the alpha byte of 32-bit pixels zeroed
+    if bit_count == 32 && rgba.chunks_exact(4).all(|p| p[3] == 0) {
+        rgba.chunks_exact_mut(4).for_each(|p| p[3] = 255);
+    }
+
+    let image = RgbaImage {
+        width: width as u32,
+        height: height as u32,
+        data: rgba,
+    };
+    Ok((image, dpi))
+}
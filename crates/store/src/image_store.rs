@@ -2,8 +2,16 @@
 //!
 //! This module handles storing, retrieving, and caching image data for the document.
 //! Images are stored as binary blobs with unique resource IDs.
-
-use doc_model::ResourceId;
+//!
+//! It also provides picture compression: images are downsampled to a target
+//! resolution for the size they're displayed at and JPEGs are recompressed,
+//! typically on DOCX/PDF export. SVGs are kept as vectors and rasterized on
+//! demand at the resolution needed; WebP is decoded for rendering and
+//! converted to PNG for DOCX.
+
+use crate::image_codec::{self, RgbaImage};
+use crate::svg_render;
+use doc_model::{DocumentTree, ResourceId};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
@@ -234,49 +242,41 @@ impl ImageData {
     }
 
     fn webp_dimensions(data: &[u8]) -> Result<(u32, u32)> {
-        // WebP has complex format, simplified detection
-        if data.len() < 30 {
-            return Ok((0, 0));
-        }
-        // VP8 (lossy) format
-        if data.len() > 23 && &data[12..16] == b"VP8 " {
-            // Dimensions at bytes 26-27 (width) and 28-29 (height)
-            if data.len() >= 30 {
-                let width = u16::from_le_bytes([data[26], data[27]]) as u32 & 0x3FFF;
-                let height = u16::from_le_bytes([data[28], data[29]]) as u32 & 0x3FFF;
-                return Ok((width, height));
-            }
-        }
-        Ok((0, 0))
+        Ok(image_codec::webp_dimensions(data).unwrap_or((0, 0)))
     }
 
     fn svg_dimensions(data: &[u8]) -> Result<(u32, u32)> {
-        // SVG dimensions need XML parsing, return 0 for now
-        // In a real implementation, we'd parse the SVG to get viewBox or width/height
-        if let Ok(text) = std::str::from_utf8(data) {
-            // Simple regex-free parsing for width and height attributes
-            if let (Some(w), Some(h)) = (
-                Self::extract_svg_attr(text, "width"),
-                Self::extract_svg_attr(text, "height"),
-            ) {
-                return Ok((w, h));
-            }
-        }
-        Ok((300, 150)) // Default SVG dimensions
-    }
-
-    fn extract_svg_attr(text: &str, attr: &str) -> Option<u32> {
-        let pattern = format!("{}=\"", attr);
-        if let Some(start) = text.find(&pattern) {
-            let value_start = start + pattern.len();
-            if let Some(end) = text[value_start..].find('"') {
-                let value = &text[value_start..value_start + end];
-                // Remove units like "px", "pt", etc.
-                let numeric: String = value.chars().take_while(|c| c.is_ascii_digit()).collect();
-                return numeric.parse().ok();
+        // Default SVG viewport size when width/height/viewBox are missing
+        let (width, height) = svg_render::svg_size(data).unwrap_or((300.0, 150.0));
+        Ok((width.round().max(1.0) as u32, height.round().max(1.0) as u32))
+    }
+
+    /// Render as a PNG of the given pixel size. SVGs are drawn from the
+    /// vector at that size; raster images are decoded and resampled.
+    pub fn render_png(&self, width: u32, height: u32) -> Result<Vec<u8>> {
+        let (width, height) = (width.max(1), height.max(1));
+        let image = match self.format {
+            ImageFormat::Svg => svg_render::rasterize_svg(&self.data, width, height)?,
+            _ => {
+                let decoded = self.decode()?;
+                if (decoded.width, decoded.height) == (width, height) {
+                    decoded
+                } else {
+                    decoded.resample(width, height)
+                }
             }
+        };
+        image_codec::encode_png(&image)
+    }
+
+    /// Decode a raster image to pixels
+    fn decode(&self) -> Result<RgbaImage> {
+        match self.format {
+            ImageFormat::Png => image_codec::decode_png(&self.data),
+            ImageFormat::Jpeg => image_codec::decode_jpeg(&self.data),
+            ImageFormat::WebP => image_codec::decode_webp(&self.data),
+            _ => Err(ImageStoreError::InvalidFormat(self.format.mime_type().into())),
         }
-        None
     }
 
     /// Convert to data URL for frontend rendering
@@ -287,6 +287,21 @@ impl ImageData {
     }
 }
 
+/// Resolution SVG fallback pictures are rasterized at for DOCX
+pub const DOCX_SVG_FALLBACK_DPI: f32 = 192.0;
+
+/// An image prepared for embedding in a DOCX package
+#[derive(Debug, Clone)]
+pub struct DocxImageMedia {
+    /// Picture bytes in a format every Word version reads
+    pub data: Vec<u8>,
+    /// Format of `data`
+    pub format: ImageFormat,
+    /// Original SVG, referenced through the svgBlip extension with `data`
+    /// as the fallback
+    pub svg: Option<Vec<u8>>,
+}
+
 /// Configuration for the image store
 #[derive(Debug, Clone)]
 pub struct ImageStoreConfig {
@@ -311,6 +326,141 @@ impl Default for ImageStoreConfig {
     }
 }
 
+/// Settings for compressing pictures (like Word's Compress Pictures)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageOptimizationOptions {
+    /// Target resolution in pixels per inch of displayed size
+    pub target_dpi: f32,
+    /// JPEG quality used when recompressing (1-100)
+    pub jpeg_quality: u8,
+}
+
+impl ImageOptimizationOptions {
+    /// High fidelity print resolution (220 ppi)
+    pub fn print() -> Self {
+        Self {
+            target_dpi: 220.0,
+            jpeg_quality: 85,
+        }
+    }
+
+    /// Web resolution (150 ppi)
+    pub fn web() -> Self {
+        Self {
+            target_dpi: 150.0,
+            jpeg_quality: 80,
+        }
+    }
+
+    /// Smallest documents for sharing (96 ppi)
+    pub fn email() -> Self {
+        Self {
+            target_dpi: 96.0,
+            jpeg_quality: 75,
+        }
+    }
+
+    /// Set the target resolution
+    pub fn with_target_dpi(mut self, dpi: f32) -> Self {
+        self.target_dpi = dpi.max(1.0);
+        self
+    }
+
+    /// Set the JPEG quality
+    pub fn with_jpeg_quality(mut self, quality: u8) -> Self {
+        self.jpeg_quality = quality.clamp(1, 100);
+        self
+    }
+}
+
+impl Default for ImageOptimizationOptions {
+    fn default() -> Self {
+        Self::print()
+    }
+}
+
+/// What happened to one image during compression
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImageOptimizationOutcome {
+    /// Replaced with a smaller version
+    Compressed,
+    /// Already at or below the target; recompressing wouldn't help
+    AlreadyOptimal,
+    /// An image in the document opts out of compression
+    OptedOut,
+    /// Not used by the document
+    Unused,
+    /// Format that can't be recompressed (GIF, SVG, ...)
+    UnsupportedFormat,
+    /// The image data couldn't be decoded or re-encoded
+    Failed(String),
+}
+
+/// Compression result for one image
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageOptimizationEntry {
+    pub resource_id: ResourceId,
+    pub outcome: ImageOptimizationOutcome,
+    /// Size in bytes before compression
+    pub original_size: usize,
+    /// Size in bytes after compression (same as original when unchanged)
+    pub optimized_size: usize,
+    /// Pixel dimensions before compression
+    pub original_dimensions: (u32, u32),
+    /// Pixel dimensions after compression
+    pub optimized_dimensions: (u32, u32),
+}
+
+/// Report of the space saved by compressing pictures
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImageOptimizationReport {
+    pub entries: Vec<ImageOptimizationEntry>,
+}
+
+impl ImageOptimizationReport {
+    /// Total size of the images before compression
+    pub fn original_size(&self) -> usize {
+        self.entries.iter().map(|e| e.original_size).sum()
+    }
+
+    /// Total size of the images after compression
+    pub fn optimized_size(&self) -> usize {
+        self.entries.iter().map(|e| e.optimized_size).sum()
+    }
+
+    /// Bytes saved by compression
+    pub fn bytes_saved(&self) -> usize {
+        self.original_size().saturating_sub(self.optimized_size())
+    }
+
+    /// Number of images that were replaced
+    pub fn compressed_count(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| e.outcome == ImageOptimizationOutcome::Compressed)
+            .count()
+    }
+
+    /// Look up the entry for an image
+    pub fn entry(&self, resource_id: &ResourceId) -> Option<&ImageOptimizationEntry> {
+        self.entries.iter().find(|e| &e.resource_id == resource_id)
+    }
+}
+
+/// Container size used to resolve percentage image sizes (6.5" text column)
+const REFERENCE_CONTAINER_SIZE: f32 = 468.0;
+
+/// How a resource is used by the document
+#[derive(Debug, Clone, Copy, Default)]
+struct ImageUsage {
+    /// Largest displayed size of the full (uncropped) image, in points
+    width: f32,
+    height: f32,
+    opted_out: bool,
+}
+
 /// Image resource manager
 ///
 /// Stores and retrieves image data by resource ID. Thread-safe via RwLock.
@@ -452,6 +602,163 @@ impl ImageStore {
         let image = self.get_image(resource_id)?;
         Ok(image.to_data_url())
     }
+
+    /// Rasterize an image as a PNG for display at `width` x `height` points
+    /// at the given resolution (for rendering and PDF output)
+    pub fn rasterize(&self, resource_id: &ResourceId, width: f32, height: f32, dpi: f32) -> Result<Vec<u8>> {
+        let image = self.get_image(resource_id)?;
+        let pixels = |points: f32| (points / 72.0 * dpi).ceil().max(1.0) as u32;
+        image.render_png(pixels(width), pixels(height))
+    }
+
+    /// Prepare an image for DOCX export. WebP is converted to PNG since
+    /// older Word versions can't read it; SVG is kept alongside a PNG
+    /// rendering for readers without SVG support.
+    pub fn docx_media(&self, resource_id: &ResourceId) -> Result<DocxImageMedia> {
+        let image = self.get_image(resource_id)?;
+        let media = match image.format {
+            ImageFormat::WebP => DocxImageMedia {
+                data: image.render_png(image.width, image.height)?,
+                format: ImageFormat::Png,
+                svg: None,
+            },
+            ImageFormat::Svg => {
+                let scale = DOCX_SVG_FALLBACK_DPI / 96.0;
+                let width = (image.width as f32 * scale).round() as u32;
+                let height = (image.height as f32 * scale).round() as u32;
+                DocxImageMedia {
+                    data: image.render_png(width, height)?,
+                    format: ImageFormat::Png,
+                    svg: Some(image.data.clone()),
+                }
+            }
+            format => DocxImageMedia {
+                data: image.data.clone(),
+                format,
+                svg: None,
+            },
+        };
+        Ok(media)
+    }
+
+    /// Compress pictures in place: downsample each image to the target
+    /// resolution for the largest size the document displays it at and
+    /// recompress JPEGs. Images are only replaced when the result is smaller;
+    /// images opted out via `ImageProperties::skip_compression` are left alone.
+    pub fn optimize_images(
+        &self,
+        tree: &DocumentTree,
+        options: &ImageOptimizationOptions,
+    ) -> ImageOptimizationReport {
+        let mut usage: HashMap<&str, ImageUsage> = HashMap::new();
+        for image in tree.images() {
+            let entry = usage.entry(image.resource_id.as_str()).or_default();
+            let crop = image.properties.crop.unwrap_or_default();
+            let width = image.effective_width(REFERENCE_CONTAINER_SIZE) / crop.visible_width_fraction().max(0.01);
+            let height = image.effective_height(REFERENCE_CONTAINER_SIZE) / crop.visible_height_fraction().max(0.01);
+            entry.width = entry.width.max(width);
+            entry.height = entry.height.max(height);
+            entry.opted_out |= image.properties.skip_compression;
+        }
+
+        let mut ids = self.resource_ids();
+        ids.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+        let mut report = ImageOptimizationReport::default();
+        for resource_id in ids {
+            let Ok(image) = self.get_image(&resource_id) else {
+                continue;
+            };
+            let mut entry = ImageOptimizationEntry {
+                resource_id: resource_id.clone(),
+                outcome: ImageOptimizationOutcome::AlreadyOptimal,
+                original_size: image.size,
+                optimized_size: image.size,
+                original_dimensions: (image.width, image.height),
+                optimized_dimensions: (image.width, image.height),
+            };
+
+            entry.outcome = match usage.get(resource_id.as_str()) {
+                None => ImageOptimizationOutcome::Unused,
+                Some(usage) if usage.opted_out => ImageOptimizationOutcome::OptedOut,
+                Some(usage) => match Self::compress_image(&image, usage, options) {
+                    Ok(Some(compressed)) => {
+                        entry.optimized_size = compressed.size;
+                        entry.optimized_dimensions = (compressed.width, compressed.height);
+                        let mut images = self.images.write().unwrap();
+                        images.insert(resource_id.as_str().to_string(), Arc::new(compressed));
+                        ImageOptimizationOutcome::Compressed
+                    }
+                    Ok(None) => ImageOptimizationOutcome::AlreadyOptimal,
+                    Err(ImageStoreError::InvalidFormat(_)) => ImageOptimizationOutcome::UnsupportedFormat,
+                    Err(e) => ImageOptimizationOutcome::Failed(e.to_string()),
+                },
+            };
+            report.entries.push(entry);
+        }
+        report
+    }
+
+    /// Compress a copy of the store for export, leaving the editing copy at
+    /// full quality
+    pub fn optimized_for_export(
+        &self,
+        tree: &DocumentTree,
+        options: &ImageOptimizationOptions,
+    ) -> (ImageStore, ImageOptimizationReport) {
+        let store = self.clone();
+        let report = store.optimize_images(tree, options);
+        (store, report)
+    }
+
+    /// Produce a smaller version of an image, or None if it can't be improved
+    fn compress_image(
+        image: &ImageData,
+        usage: &ImageUsage,
+        options: &ImageOptimizationOptions,
+    ) -> Result<Option<ImageData>> {
+        let decoded = match image.format {
+            ImageFormat::Png => image_codec::decode_png(&image.data)?,
+            ImageFormat::Jpeg => image_codec::decode_jpeg(&image.data)?,
+            _ => return Err(ImageStoreError::InvalidFormat(image.format.mime_type().into())),
+        };
+
+        // Pixels needed for the displayed size at the target resolution
+        let target_width = (usage.width / 72.0 * options.target_dpi).ceil();
+        let target_height = (usage.height / 72.0 * options.target_dpi).ceil();
+        let scale = (target_width / decoded.width as f32)
+            .max(target_height / decoded.height as f32)
+            .min(1.0);
+        let resampled: RgbaImage = if scale < 1.0 {
+            decoded.resample(
+                (decoded.width as f32 * scale).round() as u32,
+                (decoded.height as f32 * scale).round() as u32,
+            )
+        } else if image.format == ImageFormat::Png {
+            // Lossless and already small enough
+            return Ok(None);
+        } else {
+            decoded
+        };
+
+        let data = match image.format {
+            ImageFormat::Jpeg => image_codec::encode_jpeg(&resampled, options.jpeg_quality)?,
+            _ => image_codec::encode_png(&resampled)?,
+        };
+        if data.len() >= image.size {
+            return Ok(None);
+        }
+
+        Ok(Some(ImageData {
+            resource_id: image.resource_id.clone(),
+            size: data.len(),
+            data,
+            format: image.format,
+            width: resampled.width,
+            height: resampled.height,
+            filename: image.filename.clone(),
+        }))
+    }
 }
 
 impl Default for ImageStore {
@@ -545,4 +852,136 @@ mod tests {
         store.remove_image(&resource_id).unwrap();
         assert!(!store.contains(&resource_id));
     }
+
+    /// Store a smooth gradient image and show it at the given size in points
+    fn insert_gradient(
+        store: &ImageStore,
+        tree: &mut DocumentTree,
+        jpeg: bool,
+        display: f32,
+    ) -> (ResourceId, doc_model::NodeId) {
+        use doc_model::{ImageNode, Paragraph};
+
+        let size = 400u32;
+        let data = (0..size * size)
+            .flat_map(|i| [(i % size) as u8, (i / size) as u8, 128, 255])
+            .collect();
+        let image = image_codec::RgbaImage { width: size, height: size, data };
+        let bytes = if jpeg {
+            image_codec::encode_jpeg(&image, 100).unwrap()
+        } else {
+            image_codec::encode_png(&image).unwrap()
+        };
+        let resource_id = store.store_image(bytes, None).unwrap();
+
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        let node = ImageNode::with_size(resource_id.clone(), size, size, display, display);
+        let image_id = tree.insert_image(node, para_id, None).unwrap();
+        (resource_id, image_id)
+    }
+
+    #[test]
+    fn test_optimize_downsamples_to_target_dpi() {
+        let store = ImageStore::new();
+        let mut tree = DocumentTree::new();
+        // One inch on the page at 96 ppi needs 96 pixels
+        let (png_id, _) = insert_gradient(&store, &mut tree, false, 72.0);
+        let (jpeg_id, _) = insert_gradient(&store, &mut tree, true, 72.0);
+        let unused = store.store_image(TINY_PNG.to_vec(), None).unwrap();
+
+        let (optimized, report) = store.optimized_for_export(&tree, &ImageOptimizationOptions::email());
+
+        for id in [&png_id, &jpeg_id] {
+            let entry = report.entry(id).unwrap();
+            assert_eq!(entry.outcome, ImageOptimizationOutcome::Compressed);
+            assert_eq!(entry.optimized_dimensions, (96, 96));
+            assert!(entry.optimized_size < entry.original_size);
+            assert_eq!(optimized.get_image(id).unwrap().width, 96);
+            // The editing copy keeps full quality
+            assert_eq!(store.get_image(id).unwrap().width, 400);
+        }
+        assert_eq!(report.entry(&unused).unwrap().outcome, ImageOptimizationOutcome::Unused);
+        assert_eq!(report.compressed_count(), 2);
+        assert_eq!(report.bytes_saved(), store.total_size() - optimized.total_size());
+
+        // The downsampled PNG still decodes
+        let decoded = image_codec::decode_png(&optimized.get_image(&png_id).unwrap().data).unwrap();
+        assert_eq!((decoded.width, decoded.height), (96, 96));
+    }
+
+    #[test]
+    fn test_optimize_respects_opt_out_and_large_display() {
+        let store = ImageStore::new();
+        let mut tree = DocumentTree::new();
+        let (opted_out, image_id) = insert_gradient(&store, &mut tree, true, 72.0);
+        tree.get_image_mut(image_id).unwrap().properties.skip_compression = true;
+        // Shown at 5.5" wide, 400 pixels is already below 96 ppi
+        let (large, _) = insert_gradient(&store, &mut tree, false, 400.0);
+
+        let report = store.optimize_images(&tree, &ImageOptimizationOptions::email());
+
+        assert_eq!(report.entry(&opted_out).unwrap().outcome, ImageOptimizationOutcome::OptedOut);
+        assert_eq!(report.entry(&large).unwrap().outcome, ImageOptimizationOutcome::AlreadyOptimal);
+        assert_eq!(report.bytes_saved(), 0);
+        assert_eq!(store.get_image(&opted_out).unwrap().width, 400);
+    }
+
+    const TEST_SVG: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" width="10pt" height="10pt" viewBox="0 0 10 10">
+        <rect width="5" height="10" fill="#ff0000"/>
+        <g transform="translate(5 0)" style="fill: blue">
+            <path d="M0 0 h5 v5 h-5 z"/>
+        </g>
+    </svg>"##;
+
+    fn pixel(image: &image_codec::RgbaImage, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * image.width + x) * 4) as usize;
+        image.data[i..i + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn test_svg_rasterized_at_requested_resolution() {
+        let store = ImageStore::new();
+        let id = store.store_image(TEST_SVG.as_bytes().to_vec(), None).unwrap();
+        let image = store.get_image(&id).unwrap();
+        assert_eq!(image.format, ImageFormat::Svg);
+        // 10pt is 13.33 CSS pixels
+        assert_eq!((image.width, image.height), (13, 13));
+
+        // 20pt square at 144 dpi
+        let png = store.rasterize(&id, 20.0, 20.0, 144.0).unwrap();
+        let raster = image_codec::decode_png(&png).unwrap();
+        assert_eq!((raster.width, raster.height), (40, 40));
+        assert_eq!(pixel(&raster, 5, 30), [255, 0, 0, 255]);
+        assert_eq!(pixel(&raster, 35, 5), [0, 0, 255, 255]);
+        assert_eq!(pixel(&raster, 35, 35)[3], 0);
+    }
+
+    #[test]
+    fn test_docx_media_falls_back_to_png() {
+        let store = ImageStore::new();
+
+        let svg_id = store.store_image(TEST_SVG.as_bytes().to_vec(), None).unwrap();
+        let media = store.docx_media(&svg_id).unwrap();
+        assert_eq!(media.format, ImageFormat::Png);
+        assert_eq!(media.svg.as_deref(), Some(TEST_SVG.as_bytes()));
+        let fallback = image_codec::decode_png(&media.data).unwrap();
+        assert_eq!(fallback.width, 26);
+
+        let pixels: Vec<u8> = (0..8 * 4).flat_map(|i| [i as u8 * 8, 64, 192]).collect();
+        let mut webp = Vec::new();
+        image_webp::WebPEncoder::new(&mut webp)
+            .encode(&pixels, 8, 4, image_webp::ColorType::Rgb8)
+            .unwrap();
+        let webp_id = store.store_image(webp, None).unwrap();
+        assert_eq!(store.get_image(&webp_id).unwrap().width, 8);
+        let media = store.docx_media(&webp_id).unwrap();
+        assert_eq!(media.format, ImageFormat::Png);
+        assert!(media.svg.is_none());
+        let decoded = image_codec::decode_png(&media.data).unwrap();
+        assert_eq!((decoded.width, decoded.height), (8, 4));
+        assert_eq!(pixel(&decoded, 3, 0), [24, 64, 192, 255]);
+
+        let png_id = store.store_image(TINY_PNG.to_vec(), None).unwrap();
+        assert_eq!(store.docx_media(&png_id).unwrap().data, TINY_PNG);
+    }
 }
@@ -0,0 +1,338 @@
+//! Document inspector
+//!
+//! Finds information a document carries that its author may not want to
+//! share — comments, revision authors, document variables, hidden text,
+//! headers and footers, and personal details in the document properties —
+//! and removes the categories the user picks. Both steps produce an
+//! [`InspectionReport`] listing what was found and what was removed.
+
+use doc_model::{DocumentTree, HeaderFooterSet, Node, NodeId};
+use revisions::RevisionState;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Name that replaces people's names when personal information is removed
+pub const ANONYMOUS_AUTHOR: &str = "Author";
+
+/// Kind of information the inspector looks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InspectionCategory {
+    /// Comments and their replies
+    Comments,
+    /// Author names on tracked changes
+    RevisionAuthors,
+    /// Document variables
+    DocumentVariables,
+    /// Text formatted as hidden
+    HiddenText,
+    /// Headers, footers, and watermarks
+    HeadersFooters,
+    /// Author and dates in the document properties, and names on comments
+    PersonalInfo,
+}
+
+impl InspectionCategory {
+    /// All categories, in the order they are reported
+    pub const ALL: [InspectionCategory; 6] = [
+        InspectionCategory::Comments,
+        InspectionCategory::RevisionAuthors,
+        InspectionCategory::DocumentVariables,
+        InspectionCategory::HiddenText,
+        InspectionCategory::HeadersFooters,
+        InspectionCategory::PersonalInfo,
+    ];
+
+    /// Human-readable name
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            InspectionCategory::Comments => "Comments",
+            InspectionCategory::RevisionAuthors => "Revision Authors",
+            InspectionCategory::DocumentVariables => "Document Variables",
+            InspectionCategory::HiddenText => "Hidden Text",
+            InspectionCategory::HeadersFooters => "Headers and Footers",
+            InspectionCategory::PersonalInfo => "Personal Information",
+        }
+    }
+}
+
+/// Information found in one category
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectionFinding {
+    /// The category
+    pub category: InspectionCategory,
+    /// Number of items found
+    pub count: usize,
+    /// Short descriptions of what was found (names, variable names, ...)
+    pub details: Vec<String>,
+}
+
+/// What the inspector found and removed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectionReport {
+    /// Categories with something in them
+    pub found: Vec<InspectionFinding>,
+    /// Categories that were removed
+    pub removed: Vec<InspectionFinding>,
+}
+
+impl InspectionReport {
+    /// Check if nothing was found
+    pub fn is_clean(&self) -> bool {
+        self.found.is_empty()
+    }
+
+    /// The finding for a category, if anything was found
+    pub fn finding(&self, category: InspectionCategory) -> Option<&InspectionFinding> {
+        self.found.iter().find(|f| f.category == category)
+    }
+}
+
+/// Inspect a document and its tracked changes
+pub fn inspect_document(tree: &DocumentTree, revisions: Option<&RevisionState>) -> InspectionReport {
+    InspectionReport {
+        found: InspectionCategory::ALL
+            .iter()
+            .filter_map(|&category| inspect_category(tree, revisions, category))
+            .collect(),
+        removed: Vec::new(),
+    }
+}
+
+/// Remove the given categories of information from a document
+///
+/// Revision authors are only removed when `revisions` is given. The report
+/// lists everything found before removal, and which of it was removed.
+pub fn remove_document_info(
+    tree: &mut DocumentTree,
+    mut revisions: Option<&mut RevisionState>,
+    categories: &[InspectionCategory],
+) -> InspectionReport {
+    let mut report = inspect_document(tree, revisions.as_deref());
+    for finding in &report.found {
+        if !categories.contains(&finding.category) {
+            continue;
+        }
+        match finding.category {
+            InspectionCategory::Comments => tree.comments.clear(),
+            InspectionCategory::RevisionAuthors => match revisions.as_deref_mut() {
+                Some(state) => {
+                    state.anonymize_authors(ANONYMOUS_AUTHOR);
+                }
+                None => continue,
+            },
+            InspectionCategory::DocumentVariables => tree.document.metadata.variables.clear(),
+            InspectionCategory::HiddenText => remove_hidden_runs(tree),
+            InspectionCategory::HeadersFooters => remove_headers_footers(tree),
+            InspectionCategory::PersonalInfo => remove_personal_info(tree),
+        }
+        report.removed.push(finding.clone());
+    }
+    report
+}
+
+fn inspect_category(
+    tree: &DocumentTree,
+    revisions: Option<&RevisionState>,
+    category: InspectionCategory,
+) -> Option<InspectionFinding> {
+    let (count, details) = match category {
+        InspectionCategory::Comments => {
+            let authors: BTreeSet<String> = tree.comments.all().map(|c| c.author().to_string()).collect();
+            (tree.comments.len(), authors.into_iter().collect())
+        }
+        InspectionCategory::RevisionAuthors => {
+            let authors = revisions.map(|state| state.all_authors()).unwrap_or_default();
+            let authors: Vec<String> = authors
+                .into_iter()
+                .filter(|&author| author != ANONYMOUS_AUTHOR)
+                .map(str::to_string)
+                .collect();
+            (authors.len(), authors)
+        }
+        InspectionCategory::DocumentVariables => {
+            let names: Vec<String> = tree.document.metadata.variables.keys().cloned().collect();
+            (names.len(), names)
+        }
+        InspectionCategory::HiddenText => {
+            let runs = hidden_runs(tree);
+            let details = runs
+                .iter()
+                .filter_map(|id| tree.get_run(*id))
+                .map(|run| run.text.clone())
+                .collect();
+            (runs.len(), details)
+        }
+        InspectionCategory::HeadersFooters => {
+            let mut count = 0;
+            let mut details = Vec::new();
+            for (index, section) in tree.sections.iter().enumerate() {
+                let headers = section.headers.iter().filter(|hf| hf.has_content()).count();
+                let footers = section.footers.iter().filter(|hf| hf.has_content()).count();
+                let watermark = usize::from(section.watermark.is_some());
+                for (n, what) in [(headers, "header"), (footers, "footer"), (watermark, "watermark")] {
+                    if n > 0 {
+                        details.push(format!("Section {}: {} {}(s)", index + 1, n, what));
+                    }
+                }
+                count += headers + footers + watermark;
+            }
+            (count, details)
+        }
+        InspectionCategory::PersonalInfo => {
+            let metadata = &tree.document.metadata;
+            let mut details = Vec::new();
+            if let Some(author) = &metadata.author {
+                details.push(format!("Author: {}", author));
+            }
+            if let Some(created) = &metadata.created {
+                details.push(format!("Created: {}", created));
+            }
+            if let Some(modified) = &metadata.modified {
+                details.push(format!("Modified: {}", modified));
+            }
+            let names: BTreeSet<&str> = tree
+                .comments
+                .all()
+                .flat_map(|c| {
+                    std::iter::once(c.author())
+                        .chain(c.replies().iter().map(|r| r.author()))
+                        .chain(c.resolved_by())
+                })
+                .filter(|&name| name != ANONYMOUS_AUTHOR)
+                .collect();
+            details.extend(names.into_iter().map(|name| format!("Comment author: {}", name)));
+            (details.len(), details)
+        }
+    };
+    (count > 0).then_some(InspectionFinding { category, count, details })
+}
+
+/// Runs whose resolved formatting is hidden
+fn hidden_runs(tree: &DocumentTree) -> Vec<NodeId> {
+    let mut runs: Vec<NodeId> = tree
+        .nodes
+        .runs
+        .keys()
+        .copied()
+        .filter(|&id| {
+            tree.compute_character_properties(id)
+                .is_some_and(|props| props.hidden == Some(true))
+        })
+        .collect();
+    runs.sort_by_key(|id| id.as_uuid());
+    runs
+}
+
+fn remove_hidden_runs(tree: &mut DocumentTree) {
+    for run_id in hidden_runs(tree) {
+        let Some(run) = tree.nodes.runs.remove(&run_id) else {
+            continue;
+        };
+        if let Some(parent_id) = run.parent() {
+            if let Some(para) = tree.nodes.paragraphs.get_mut(&parent_id) {
+                para.remove_child(run_id);
+            } else if let Some(hyperlink) = tree.nodes.hyperlinks.get_mut(&parent_id) {
+                hyperlink.remove_child(run_id);
+            }
+        }
+    }
+}
+
+fn remove_headers_footers(tree: &mut DocumentTree) {
+    let mut paragraphs = Vec::new();
+    for section in &mut tree.sections {
+        for set in [&mut section.headers, &mut section.footers] {
+            paragraphs.extend(set.iter().flat_map(|hf| hf.children().to_vec()));
+            *set = HeaderFooterSet::new();
+        }
+        section.watermark = None;
+    }
+    for para_id in paragraphs {
+        let Some(para) = tree.nodes.paragraphs.remove(&para_id) else {
+            continue;
+        };
+        for child_id in para.children() {
+            if let Some(hyperlink) = tree.nodes.hyperlinks.remove(child_id) {
+                for run_id in hyperlink.children() {
+                    tree.nodes.runs.remove(run_id);
+                }
+            }
+            tree.nodes.runs.remove(child_id);
+            tree.nodes.images.remove(child_id);
+            tree.nodes.shapes.remove(child_id);
+            tree.nodes.fields.remove(child_id);
+            tree.nodes.embedded_objects.remove(child_id);
+        }
+    }
+}
+
+fn remove_personal_info(tree: &mut DocumentTree) {
+    let metadata = &mut tree.document.metadata;
+    metadata.author = None;
+    metadata.created = None;
+    metadata.modified = None;
+    for comment in tree.comments.all_mut() {
+        comment.anonymize(ANONYMOUS_AUTHOR);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use doc_model::{CharacterProperties, Comment, CommentAnchor, HeaderFooter, Paragraph, Position, Run, Section};
+
+    #[test]
+    fn test_inspect_and_remove() {
+        let mut tree = DocumentTree::with_empty_paragraph();
+        let para_id = tree.document.children()[0];
+        tree.insert_run(Run::new("Shown"), para_id, None).unwrap();
+        let hidden = CharacterProperties { hidden: Some(true), ..Default::default() };
+        let hidden_id = tree.insert_run(Run::with_direct_formatting(" secret", hidden), para_id, None).unwrap();
+
+        tree.document.metadata.author = Some("Alice".to_string());
+        tree.document.metadata.variables.insert("ClientName".to_string(), "Acme".to_string());
+        let anchor = CommentAnchor::new(Position::new(para_id, 0), Position::new(para_id, 5));
+        tree.comments.insert(Comment::new(anchor, "Bob", "Check this"));
+
+        let mut section = Section::new();
+        let header = HeaderFooter::new();
+        let header_id = header.id();
+        section.set_default_header(header);
+        tree.sections.push(section);
+        tree.insert_paragraph_into_header_footer(Paragraph::new(), header_id, None).unwrap();
+
+        let mut revisions = RevisionState::with_author("Carol");
+        revisions.enable_tracking().unwrap();
+        revisions
+            .record_insert(revisions::RevisionRange::new(para_id, 0, 5))
+            .unwrap();
+
+        let report = inspect_document(&tree, Some(&revisions));
+        assert_eq!(report.found.len(), 6);
+        assert_eq!(report.finding(InspectionCategory::HiddenText).unwrap().details, vec![" secret"]);
+
+        let report = remove_document_info(
+            &mut tree,
+            Some(&mut revisions),
+            &[
+                InspectionCategory::RevisionAuthors,
+                InspectionCategory::DocumentVariables,
+                InspectionCategory::HiddenText,
+                InspectionCategory::HeadersFooters,
+                InspectionCategory::PersonalInfo,
+            ],
+        );
+        assert_eq!(report.removed.len(), 5);
+        assert!(tree.get_run(hidden_id).is_none());
+        assert_eq!(tree.get_paragraph(para_id).unwrap().children().len(), 1);
+        assert!(!tree.sections[0].headers.has_any());
+        assert_eq!(tree.comments.all().next().unwrap().author(), ANONYMOUS_AUTHOR);
+
+        let remaining = inspect_document(&tree, Some(&revisions));
+        let categories: Vec<_> = remaining.found.iter().map(|f| f.category).collect();
+        assert_eq!(categories, vec![InspectionCategory::Comments]);
+    }
+}
@@ -2,14 +2,24 @@
 //!
 //! This module provides functionality to validate document structure,
 //! compute checksums, detect corruption, and attempt repairs.
+//!
+//! Saved documents carry a checksum for each part so damage can be found
+//! on load. "Open and Repair" ([`repair_document_json`]) reads whatever is
+//! still readable from a damaged file, part by part and node by node, and
+//! reports what was lost instead of failing the whole load.
 
-use crate::Result;
+use crate::{Result, StoreError};
 use doc_model::{DocumentTree, Node, NodeId};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 use uuid::Uuid;
 
+/// Field of the serialized tree that holds the node storages
+const NODES_FIELD: &str = "nodes";
+
 /// Integrity check result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IntegrityReport {
@@ -538,6 +548,310 @@ pub enum RepairAction {
     AddedEmptyParagraph,
     /// Removed an invalid reference
     RemovedInvalidReference { parent_id: String, child_id: String },
+    /// Moved a node whose container was lost to the end of the body
+    ReattachedToBody { node_id: String, node_type: String },
+}
+
+/// Checksum of each part of a serialized document tree
+///
+/// Parts are the top-level fields of the tree, except that each node
+/// storage is its own part (`nodes.paragraphs`, `nodes.runs`, ...).
+pub fn part_checksums(document: &Value) -> BTreeMap<String, String> {
+    let mut checksums = BTreeMap::new();
+    for (name, value) in document.as_object().into_iter().flatten() {
+        if name == NODES_FIELD {
+            for (storage, nodes) in value.as_object().into_iter().flatten() {
+                checksums.insert(format!("{}.{}", NODES_FIELD, storage), part_checksum(nodes));
+            }
+        } else {
+            checksums.insert(name.clone(), part_checksum(value));
+        }
+    }
+    checksums
+}
+
+/// Names of the parts that are missing or don't match their checksum
+pub fn verify_part_checksums(document: &Value, expected: &BTreeMap<String, String>) -> Vec<String> {
+    let actual = part_checksums(document);
+    expected
+        .iter()
+        .filter(|(name, checksum)| actual.get(*name) != Some(*checksum))
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+fn part_checksum(value: &Value) -> String {
+    format!("{:08x}", crc32fast::hash(value.to_string().as_bytes()))
+}
+
+/// Content that couldn't be recovered from a damaged document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum LostContent {
+    /// A part of the document, such as the styles, was unreadable
+    Part { name: String, reason: String },
+    /// A node was unreadable
+    Node { storage: String, node_id: String, reason: String },
+}
+
+/// What happened when a damaged document was opened with repair
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepairReport {
+    /// Whether the file was cut short or unreadable past some point
+    pub truncated: bool,
+    /// Parts that didn't match their checksum; their readable content was kept
+    pub damaged_parts: Vec<String>,
+    /// Content that was dropped
+    pub lost: Vec<LostContent>,
+    /// Repairs made to the recovered document
+    pub actions: Vec<RepairAction>,
+    /// Number of paragraphs recovered
+    pub recovered_paragraphs: usize,
+}
+
+impl RepairReport {
+    /// Check if the document was undamaged
+    pub fn is_clean(&self) -> bool {
+        !self.truncated && self.damaged_parts.is_empty() && self.lost.is_empty() && self.actions.is_empty()
+    }
+}
+
+/// Open a damaged document file, recovering what is readable
+pub async fn open_and_repair(path: impl AsRef<Path>) -> Result<(DocumentTree, RepairReport)> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Err(StoreError::FileNotFound(path.display().to_string()));
+    }
+    let bytes = tokio::fs::read(path).await?;
+    repair_document_json(&String::from_utf8_lossy(&bytes))
+}
+
+/// Recover what is readable from a damaged document file's contents
+///
+/// Fails only if no document content can be found at all.
+pub fn repair_document_json(json: &str) -> Result<(DocumentTree, RepairReport)> {
+    let mut report = RepairReport::default();
+
+    let file: Value = match serde_json::from_str(json) {
+        Ok(file) => file,
+        Err(e) => {
+            // Keep everything before the damage and close what was left open
+            report.truncated = true;
+            let end = error_offset(json, e.line(), e.column());
+            serde_json::from_str(&close_truncated_json(&json[..end]))
+                .map_err(|e| StoreError::Corrupted(format!("no readable content: {}", e)))?
+        }
+    };
+    let document = file
+        .get("document")
+        .filter(|d| d.is_object())
+        .ok_or_else(|| StoreError::Corrupted("no document content".to_string()))?;
+    let expected: BTreeMap<String, String> = file
+        .get("checksums")
+        .and_then(|c| serde_json::from_value(c.clone()).ok())
+        .unwrap_or_default();
+    report.damaged_parts = verify_part_checksums(document, &expected);
+
+    let mut tree = DocumentTree::new();
+    let document_read = salvage_part(document, "document", &mut tree.document, &mut report);
+    salvage_part(document, "styles", &mut tree.styles, &mut report);
+    salvage_part(document, "bookmarks", &mut tree.bookmarks, &mut report);
+    salvage_part(document, "numbering", &mut tree.numbering, &mut report);
+    salvage_part(document, "comments", &mut tree.comments, &mut report);
+    salvage_part(document, "notes", &mut tree.notes, &mut report);
+    salvage_part(document, "theme", &mut tree.theme, &mut report);
+    salvage_part(document, "sections", &mut tree.sections, &mut report);
+
+    let storages = document.get(NODES_FIELD).and_then(Value::as_object);
+    let nodes = &mut tree.nodes;
+    salvage_nodes(storages, "paragraphs", &mut nodes.paragraphs, &mut report);
+    salvage_nodes(storages, "runs", &mut nodes.runs, &mut report);
+    salvage_nodes(storages, "hyperlinks", &mut nodes.hyperlinks, &mut report);
+    salvage_nodes(storages, "images", &mut nodes.images, &mut report);
+    salvage_nodes(storages, "shapes", &mut nodes.shapes, &mut report);
+    salvage_nodes(storages, "textboxes", &mut nodes.textboxes, &mut report);
+    salvage_nodes(storages, "tables", &mut nodes.tables, &mut report);
+    salvage_nodes(storages, "table_rows", &mut nodes.table_rows, &mut report);
+    salvage_nodes(storages, "table_cells", &mut nodes.table_cells, &mut report);
+    salvage_nodes(storages, "fields", &mut nodes.fields, &mut report);
+    salvage_nodes(storages, "inks", &mut nodes.inks, &mut report);
+    salvage_nodes(storages, "canvases", &mut nodes.canvases, &mut report);
+
+    reattach_lost_blocks(&mut tree, document_read, &mut report);
+
+    // Drop text whose paragraph was lost
+    let orphan_runs: Vec<NodeId> = tree
+        .nodes
+        .runs
+        .iter()
+        .filter(|(_, run)| run.parent().is_none_or(|p| tree.node_type(p).is_none()))
+        .map(|(&id, _)| id)
+        .collect();
+    for id in orphan_runs {
+        tree.nodes.runs.remove(&id);
+        report.actions.push(RepairAction::RemovedOrphanNode {
+            node_id: id.to_string(),
+            node_type: "Run".to_string(),
+        });
+    }
+
+    // Remove references to nodes that were lost
+    let issues: Vec<IntegrityIssue> = IntegrityChecker::new()
+        .check(&tree)
+        .issues
+        .into_iter()
+        .filter(|i| matches!(i, IntegrityIssue::InvalidChildReference { .. } | IntegrityIssue::EmptyDocument))
+        .collect();
+    let actions = DocumentRepairer::new().repair(&mut tree, &issues);
+    report.actions.extend(actions);
+
+    report.recovered_paragraphs = tree.nodes.paragraphs.len();
+    Ok((tree, report))
+}
+
+/// Read one top-level part of the tree, keeping the default if it's unreadable
+///
+/// Returns whether the part was read.
+fn salvage_part<T: DeserializeOwned>(document: &Value, name: &str, target: &mut T, report: &mut RepairReport) -> bool {
+    let Some(value) = document.get(name) else {
+        if report.truncated {
+            report.lost.push(LostContent::Part {
+                name: name.to_string(),
+                reason: "missing".to_string(),
+            });
+        }
+        return false;
+    };
+    match serde_json::from_value(value.clone()) {
+        Ok(part) => {
+            *target = part;
+            true
+        }
+        Err(e) => {
+            report.lost.push(LostContent::Part {
+                name: name.to_string(),
+                reason: e.to_string(),
+            });
+            false
+        }
+    }
+}
+
+/// Read the nodes of one storage, skipping unreadable ones
+fn salvage_nodes<T: DeserializeOwned>(
+    storages: Option<&Map<String, Value>>,
+    storage: &str,
+    target: &mut HashMap<NodeId, T>,
+    report: &mut RepairReport,
+) {
+    let entries = storages.and_then(|s| s.get(storage)).and_then(Value::as_object);
+    for (id, value) in entries.into_iter().flatten() {
+        let node = NodeId::from_string(id)
+            .ok_or_else(|| "invalid node ID".to_string())
+            .and_then(|node_id| {
+                serde_json::from_value(value.clone())
+                    .map(|node| (node_id, node))
+                    .map_err(|e| e.to_string())
+            });
+        match node {
+            Ok((node_id, node)) => {
+                target.insert(node_id, node);
+            }
+            Err(reason) => report.lost.push(LostContent::Node {
+                storage: storage.to_string(),
+                node_id: id.clone(),
+                reason,
+            }),
+        }
+    }
+}
+
+/// Put paragraphs and tables whose container was lost at the end of the body
+///
+/// If the document part itself was lost, this rebuilds the body, though the
+/// original order of its blocks can't be known.
+fn reattach_lost_blocks(tree: &mut DocumentTree, document_read: bool, report: &mut RepairReport) {
+    let root = tree.document.id();
+    let lost_container = |parent: Option<NodeId>| parent.is_none_or(|p| tree.node_type(p).is_none());
+    let body: HashSet<NodeId> = tree.document.children().iter().copied().collect();
+
+    let mut blocks: Vec<(NodeId, &str)> = Vec::new();
+    for (&id, para) in &tree.nodes.paragraphs {
+        if !body.contains(&id) && (lost_container(para.parent()) || (!document_read && para.parent() != Some(root))) {
+            blocks.push((id, "Paragraph"));
+        }
+    }
+    for (&id, table) in &tree.nodes.tables {
+        if !body.contains(&id) && lost_container(table.parent()) {
+            blocks.push((id, "Table"));
+        }
+    }
+
+    for (id, node_type) in blocks {
+        if let Some(para) = tree.nodes.paragraphs.get_mut(&id) {
+            para.set_parent(Some(root));
+        } else if let Some(table) = tree.nodes.tables.get_mut(&id) {
+            table.set_parent(Some(root));
+        }
+        tree.document.add_body_child(id);
+        report.actions.push(RepairAction::ReattachedToBody {
+            node_id: id.to_string(),
+            node_type: node_type.to_string(),
+        });
+    }
+}
+
+/// Byte offset of a serde_json error position
+fn error_offset(json: &str, line: usize, column: usize) -> usize {
+    let line_start: usize = json
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let mut offset = (line_start + column.saturating_sub(1)).min(json.len());
+    while !json.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+/// Cut JSON text back to its last complete value and close the objects and
+/// arrays left open, so the readable part of a damaged file can be parsed
+fn close_truncated_json(json: &str) -> String {
+    let closing = |open: &[char]| open.iter().rev().collect::<String>();
+    let mut open: Vec<char> = Vec::new();
+    let mut cut = (0, String::new());
+    let (mut in_string, mut escaped) = (false, false);
+
+    for (i, c) in json.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => {
+                open.push(if c == '{' { '}' } else { ']' });
+                cut = (i + 1, closing(&open));
+            }
+            '}' | ']' => {
+                open.pop();
+                if open.is_empty() {
+                    return json[..=i].to_string();
+                }
+                cut = (i + 1, closing(&open));
+            }
+            // Everything before a comma is a complete member or element
+            ',' => cut = (i, closing(&open)),
+            _ => {}
+        }
+    }
+    format!("{}{}", &json[..cut.0], cut.1)
 }
 
 #[cfg(test)]
@@ -726,4 +1040,37 @@ mod tests {
         // Orphan should be removed
         assert!(!tree.nodes.runs.contains_key(&orphan_id));
     }
+
+    #[test]
+    fn test_open_and_repair_salvages_truncated_file() {
+        let mut tree = DocumentTree::new();
+        for text in ["First paragraph", "Second paragraph", "Third paragraph"] {
+            let para = tree.insert_paragraph(doc_model::Paragraph::new(), tree.root_id(), None).unwrap();
+            tree.insert_run(Run::new(text), para, None).unwrap();
+        }
+        let json = crate::serialize(&tree).unwrap();
+
+        let (clean, report) = repair_document_json(&json).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(clean.text_content(), tree.text_content());
+
+        // Cut the file inside the node storages
+        let cut = json.find("\"runs\"").unwrap() + 20;
+        let (repaired, report) = repair_document_json(&json[..cut]).unwrap();
+        assert!(report.truncated);
+        assert!(report.damaged_parts.contains(&"nodes.runs".to_string()));
+        assert_eq!(report.recovered_paragraphs, 3);
+        assert!(report.lost.iter().any(|l| matches!(l, LostContent::Part { name, .. } if name == "styles")));
+        assert!(checker_is_sound(&repaired));
+
+        assert!(repair_document_json("not a document").is_err());
+    }
+
+    fn checker_is_sound(tree: &DocumentTree) -> bool {
+        !IntegrityChecker::new()
+            .check(tree)
+            .issues
+            .iter()
+            .any(|i| matches!(i, IntegrityIssue::InvalidChildReference { .. }))
+    }
 }
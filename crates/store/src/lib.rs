@@ -1,45 +1,67 @@
 //! Store - Persistence, autosave, and file I/O
 //!
-//! This crate handles document serialization, file operations,
+//! This crate handles document serialization, file operations, file locking,
 //! autosave functionality, recovery, integrity checking, version tracking,
-//! application settings, PDF export, DOCX import/export, RTF import/export,
-//! ODT import, and templates.
+//! application settings, recent documents, PDF export, page rasterization,
+//! native printing, text-to-speech, DOCX import/export, RTF import/export,
+//! ODT import, templates, multi-format clipboard copy/paste, digital
+//! signatures, the document inspector, and the user's building block library.
 
 mod format;
+mod format_registry;
 mod serializer;
 mod file_io;
+mod file_lock;
 mod autosave;
+mod delta;
 mod recovery;
 mod integrity;
 mod versions;
+mod recent;
 mod error;
 mod settings;
+mod inspector;
+mod building_blocks;
 mod image_store;
+mod image_codec;
+mod svg_render;
 pub mod pdf;
+pub mod raster;
+pub mod print;
+pub mod speech;
 pub mod docx;
 pub mod rtf;
 pub mod odt;
 pub mod templates;
+pub mod clipboard;
+pub mod signatures;
 
 pub use format::*;
+pub use format_registry::*;
 pub use serializer::*;
 pub use file_io::*;
+pub use file_lock::*;
 pub use autosave::*;
+pub use delta::*;
 pub use recovery::*;
 pub use integrity::*;
 pub use versions::*;
+pub use recent::*;
 pub use error::*;
 pub use settings::*;
+pub use inspector::*;
+pub use building_blocks::*;
 
 // Re-export image store types explicitly to avoid Result conflict
 pub use image_store::{
-    ImageData, ImageFormat, ImageStore, ImageStoreConfig, ImageStoreError,
+    DocxImageMedia, ImageData, ImageFormat, ImageOptimizationEntry, ImageOptimizationOptions, ImageOptimizationOutcome,
+    ImageOptimizationReport, ImageStore, ImageStoreConfig, ImageStoreError, DOCX_SVG_FALLBACK_DPI,
 };
 
 // Re-export DOCX functionality
 pub use docx::{
-    import_docx, export_docx, import_docx_bytes, export_docx_bytes,
-    DocxError, DocxResult,
+    import_docx, export_docx, import_docx_bytes, export_docx_bytes, export_docx_with_images,
+    export_docx_bytes_with_images, DocxError, DocxResult,
 };
 
 // Re-export RTF functionality
@@ -52,14 +74,20 @@ pub use rtf::{
 // Re-export ODT functionality (read-only)
 pub use odt::{
     import_odt, import_odt_bytes,
-    OdtError, OdtResult, OdtImportResult, OdtWarning, OdtWarningKind,
+    OdtError, OdtResult, OdtEquation, OdtImportResult, OdtWarning, OdtWarningKind,
 };
 
 // Re-export template functionality
 pub use templates::{
-    LockedRegion, LockedRegionManager, StylePack, StylePackApplyOptions,
-    TemplateCategory, TemplateError, TemplateManager, TemplateMetadata,
-    TemplatePackage, TemplateResult, TemplateSummary,
+    GalleryCategory, GalleryEntry, GalleryFetcher, GalleryIndex, RemoteGallery, LockedRegion, LockedRegionManager, PropertyDiff, StyleChange, StyleDiff, StylePack,
+    StylePackApplyOptions, StylePackDiff, TemplateCategory, TemplateError, TemplateManager, TemplateMetadata,
+    TemplatePackage, TemplatePlaceholder, PlaceholderKind, TemplateResult, TemplateSummary,
     TEMPLATE_EXTENSION, read_metadata as read_template_metadata,
     read_thumbnail as read_template_thumbnail,
 };
+
+// Re-export clipboard functionality
+pub use clipboard::{
+    copy_selection, paste, paste_image, ClipboardData, ClipboardError, ClipboardFormat, ClipboardResult,
+    PasteFragment, PasteMode, PasteOptions, PastedImage,
+};
@@ -5,7 +5,8 @@
 
 use crate::odt::error::{OdtError, OdtResult};
 use crate::odt::reader::OdtReader;
-use doc_model::DocumentTree;
+use doc_model::{DocumentTree, NodeId};
+use math::MathNode;
 use std::fs::File;
 use std::io::{BufReader, Cursor};
 use std::path::Path;
@@ -52,6 +53,18 @@ impl std::fmt::Display for OdtWarning {
     }
 }
 
+/// An equation imported from an embedded formula object
+///
+/// The equation is placed in the document as a run holding its LaTeX source;
+/// the parsed math tree is kept so callers can render or re-export it.
+#[derive(Debug, Clone)]
+pub struct OdtEquation {
+    /// Run that holds the equation's LaTeX text
+    pub run_id: NodeId,
+    /// The parsed equation
+    pub math: MathNode,
+}
+
 /// Result of importing an ODT file
 #[derive(Debug)]
 pub struct OdtImportResult {
@@ -59,6 +72,8 @@ pub struct OdtImportResult {
     pub tree: DocumentTree,
     /// Warnings encountered during import
     pub warnings: Vec<OdtWarning>,
+    /// Equations imported from formula objects, in document order
+    pub equations: Vec<OdtEquation>,
 }
 
 impl OdtImportResult {
@@ -119,9 +134,7 @@ pub fn import_odt(path: &Path) -> OdtResult<OdtImportResult> {
 
     // Create ODT reader and parse
     let odt_reader = OdtReader::new(reader)?;
-    let (tree, warnings) = odt_reader.parse()?;
-
-    Ok(OdtImportResult { tree, warnings })
+    odt_reader.parse()
 }
 
 /// Import ODT from an in-memory byte slice
@@ -150,9 +163,7 @@ pub fn import_odt(path: &Path) -> OdtResult<OdtImportResult> {
 pub fn import_odt_bytes(bytes: &[u8]) -> OdtResult<OdtImportResult> {
     let cursor = Cursor::new(bytes);
     let odt_reader = OdtReader::new(cursor)?;
-    let (tree, warnings) = odt_reader.parse()?;
-
-    Ok(OdtImportResult { tree, warnings })
+    odt_reader.parse()
 }
 
 #[cfg(test)]
@@ -173,6 +184,7 @@ mod tests {
                     message: "Another warning".to_string(),
                 },
             ],
+            equations: Vec::new(),
         };
 
         assert!(result.has_warnings());
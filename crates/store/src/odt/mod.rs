@@ -12,6 +12,7 @@
 //! - `meta.xml` - Metadata (title, author, etc.)
 //! - `settings.xml` - Application settings
 //! - `Pictures/` - Embedded images
+//! - `Object N/content.xml` - Embedded objects such as MathML formulas
 //! - `META-INF/manifest.xml` - Package manifest
 //!
 //! ## Note
@@ -24,7 +25,9 @@ mod reader;
 mod api;
 
 pub use error::{OdtError, OdtResult};
-pub use api::{import_odt, import_odt_bytes, OdtImportResult, OdtWarning, OdtWarningKind};
+pub use api::{
+    import_odt, import_odt_bytes, OdtEquation, OdtImportResult, OdtWarning, OdtWarningKind,
+};
 
 /// ODF XML namespaces
 pub mod namespaces {
@@ -82,6 +85,10 @@ pub mod elements {
     // Drawing elements
     pub const FRAME: &str = "frame";
     pub const IMAGE: &str = "image";
+    pub const OBJECT: &str = "object";
+
+    // Embedded formulas (MathML)
+    pub const MATH: &str = "math";
 
     // Style elements
     pub const STYLES: &str = "styles";
@@ -138,6 +145,9 @@ pub mod attributes {
     pub const HEIGHT: &str = "height";
     pub const HREF: &str = "href";
 
+    // MathML attributes
+    pub const DISPLAY: &str = "display";
+
     // Space count
     pub const C: &str = "c";
 
@@ -10,7 +10,7 @@ use crate::odt::attributes::*;
 use crate::odt::elements::*;
 use crate::odt::error::{OdtError, OdtResult};
 use crate::odt::namespaces;
-use crate::odt::api::{OdtWarning, OdtWarningKind};
+use crate::odt::api::{OdtEquation, OdtImportResult, OdtWarning, OdtWarningKind};
 use doc_model::{
     Alignment, CharacterProperties, DocumentMetadata, DocumentTree, ImageNode,
     ImageProperties, LineSpacing, Node, Paragraph, ParagraphProperties, ResourceId, Run,
@@ -44,6 +44,8 @@ pub struct OdtReader<R: Read + Seek> {
     images: HashMap<String, Vec<u8>>,
     /// Image counter for resource IDs
     image_counter: u32,
+    /// Equations imported from formula objects
+    equations: Vec<OdtEquation>,
 }
 
 impl<R: Read + Seek> OdtReader<R> {
@@ -56,6 +58,7 @@ impl<R: Read + Seek> OdtReader<R> {
             warnings: Vec::new(),
             images: HashMap::new(),
             image_counter: 0,
+            equations: Vec::new(),
         })
     }
 
@@ -65,8 +68,8 @@ impl<R: Read + Seek> OdtReader<R> {
         self.archive.file_names().any(|name| name == "content.xml")
     }
 
-    /// Parse the ODT file and return a DocumentTree
-    pub fn parse(mut self) -> OdtResult<(DocumentTree, Vec<OdtWarning>)> {
+    /// Parse the ODT file and return the imported document
+    pub fn parse(mut self) -> OdtResult<OdtImportResult> {
         if !self.is_valid_odt() {
             return Err(OdtError::invalid_structure("Missing content.xml"));
         }
@@ -90,7 +93,11 @@ impl<R: Read + Seek> OdtReader<R> {
         // Load images
         self.load_images()?;
 
-        Ok((tree, self.warnings))
+        Ok(OdtImportResult {
+            tree,
+            warnings: self.warnings,
+            equations: self.equations,
+        })
     }
 
     /// Read a file from the archive as string
@@ -441,6 +448,28 @@ impl<R: Read + Seek> OdtReader<R> {
                             // Drawing frame - might contain an image
                             // We'll handle images in the image element
                         }
+                        OBJECT if current_para.is_some() => {
+                            if let Some(href) = get_attribute(&e, HREF) {
+                                self.import_formula_object(
+                                    &href, &mut current_runs, &mut current_text,
+                                    &current_char_props,
+                                );
+                            }
+                        }
+                        MATH if current_para.is_some() => {
+                            // Formula stored inline in a flat ODF document
+                            let display = get_attribute(&e, DISPLAY);
+                            let span = reader.read_to_end_into(e.name(), &mut Vec::new())?;
+                            let inner = &content[span.start as usize..span.end as usize];
+                            let xml = match display {
+                                Some(display) => format!("<math display=\"{}\">{}</math>", display, inner),
+                                None => format!("<math>{}</math>", inner),
+                            };
+                            self.import_mathml(
+                                &xml, "inline formula", &mut current_runs, &mut current_text,
+                                &current_char_props,
+                            );
+                        }
                         IMAGE => {
                             // Get image href
                             if let Some(href) = get_attribute(&e, HREF) {
@@ -521,6 +550,14 @@ impl<R: Read + Seek> OdtReader<R> {
                         SOFT_PAGE_BREAK => {
                             // Soft page break - we can ignore this
                         }
+                        OBJECT if current_para.is_some() => {
+                            if let Some(href) = get_attribute(&e, HREF) {
+                                self.import_formula_object(
+                                    &href, &mut current_runs, &mut current_text,
+                                    &current_char_props,
+                                );
+                            }
+                        }
                         TABLE_COLUMN if in_table => {
                             let width = get_attribute(&e, COLUMN_WIDTH)
                                 .and_then(|w| parse_length(&w))
@@ -569,6 +606,86 @@ impl<R: Read + Seek> OdtReader<R> {
         Ok(())
     }
 
+    /// Import the formula stored in an embedded object sub-document
+    fn import_formula_object(
+        &mut self,
+        href: &str,
+        current_runs: &mut Vec<Run>,
+        current_text: &mut String,
+        current_char_props: &CharacterProperties,
+    ) {
+        let object_dir = href.trim_start_matches("./").trim_end_matches('/');
+        match self.read_file_as_string(&format!("{}/content.xml", object_dir)) {
+            Ok(xml) => {
+                self.import_mathml(&xml, object_dir, current_runs, current_text, current_char_props)
+            }
+            Err(_) => self.warnings.push(OdtWarning {
+                kind: OdtWarningKind::UnsupportedFeature,
+                message: format!("Embedded object '{}' could not be read and was skipped", object_dir),
+            }),
+        }
+    }
+
+    /// Parse MathML and insert the equation as a run at the current position
+    ///
+    /// The document model has no equation node, so the run holds the LaTeX source
+    /// and the parsed tree is reported in the import result.
+    fn import_mathml(
+        &mut self,
+        xml: &str,
+        source: &str,
+        current_runs: &mut Vec<Run>,
+        current_text: &mut String,
+        current_char_props: &CharacterProperties,
+    ) {
+        let node = match math::parse_mathml(xml) {
+            Ok(mut nodes) if !nodes.is_empty() => nodes.remove(0),
+            Ok(_) => {
+                self.warnings.push(OdtWarning {
+                    kind: OdtWarningKind::UnsupportedFeature,
+                    message: format!("Embedded object '{}' is not a formula and was skipped", source),
+                });
+                return;
+            }
+            Err(e) => {
+                self.warnings.push(OdtWarning {
+                    kind: OdtWarningKind::DataLoss,
+                    message: format!("Formula '{}' could not be parsed: {}", source, e),
+                });
+                return;
+            }
+        };
+
+        let latex = match math::to_latex(&node) {
+            Ok(latex) => latex,
+            Err(e) => {
+                self.warnings.push(OdtWarning {
+                    kind: OdtWarningKind::DataLoss,
+                    message: format!("Formula '{}' could not be converted: {}", source, e),
+                });
+                return;
+            }
+        };
+
+        if !current_text.is_empty() {
+            current_runs.push(Run::with_direct_formatting(
+                std::mem::take(current_text),
+                current_char_props.clone(),
+            ));
+        }
+        let run = Run::with_direct_formatting(latex, current_char_props.clone());
+        self.equations.push(OdtEquation {
+            run_id: run.id(),
+            math: node,
+        });
+        current_runs.push(run);
+
+        self.warnings.push(OdtWarning {
+            kind: OdtWarningKind::PartialSupport,
+            message: format!("Formula '{}' imported as LaTeX text", source),
+        });
+    }
+
     /// Resolve paragraph style by name
     fn resolve_paragraph_style(&self, style_name: &Option<String>) -> ParagraphProperties {
         let mut props = ParagraphProperties::default();
@@ -786,4 +903,92 @@ mod tests {
         // This would require constructing a BytesStart which is complex
         // In practice, this is tested through the full parse tests
     }
+
+    fn build_odt(files: &[(&str, &str)]) -> Vec<u8> {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, content) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    const CONTENT_WITH_OBJECT: &str = r#"<office:document-content
+        xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+        xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0"
+        xmlns:draw="urn:oasis:names:tc:opendocument:xmlns:drawing:1.0"
+        xmlns:xlink="http://www.w3.org/1999/xlink">
+        <office:body><office:text>
+            <text:p>Area is <draw:frame><draw:object xlink:href="./Object 1"/></draw:frame> here</text:p>
+        </office:text></office:body>
+    </office:document-content>"#;
+
+    #[test]
+    fn test_formula_object_imported() {
+        let bytes = build_odt(&[
+            ("content.xml", CONTENT_WITH_OBJECT),
+            (
+                "Object 1/content.xml",
+                r#"<math xmlns="http://www.w3.org/1998/Math/MathML"><mi>&#x3C0;</mi><msup><mi>r</mi><mn>2</mn></msup></math>"#,
+            ),
+        ]);
+
+        let result = OdtReader::new(std::io::Cursor::new(bytes))
+            .unwrap()
+            .parse()
+            .unwrap();
+
+        assert_eq!(result.equations.len(), 1);
+        let equation = &result.equations[0];
+        let run = result.tree.nodes.runs.get(&equation.run_id).unwrap();
+        assert_eq!(run.text, "\\pi r^{2}");
+        assert!(result.tree.text_content().contains("\\pi r^{2}"));
+    }
+
+    #[test]
+    fn test_missing_formula_object_warns() {
+        let bytes = build_odt(&[("content.xml", CONTENT_WITH_OBJECT)]);
+
+        let result = OdtReader::new(std::io::Cursor::new(bytes))
+            .unwrap()
+            .parse()
+            .unwrap();
+
+        assert!(result.equations.is_empty());
+        assert_eq!(
+            result
+                .warnings
+                .iter()
+                .filter(|w| w.kind == OdtWarningKind::UnsupportedFeature)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_inline_formula_imported() {
+        let content = r#"<office:document-content
+            xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+            xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0"
+            xmlns:draw="urn:oasis:names:tc:opendocument:xmlns:drawing:1.0"
+            xmlns:math="http://www.w3.org/1998/Math/MathML">
+            <office:body><office:text>
+                <text:p><draw:frame><draw:object><math:math display="block"><math:mfrac><math:mi>a</math:mi><math:mi>b</math:mi></math:mfrac></math:math></draw:object></draw:frame></text:p>
+            </office:text></office:body>
+        </office:document-content>"#;
+        let bytes = build_odt(&[("content.xml", content)]);
+
+        let result = OdtReader::new(std::io::Cursor::new(bytes))
+            .unwrap()
+            .parse()
+            .unwrap();
+
+        assert_eq!(result.equations.len(), 1);
+        assert!(matches!(result.equations[0].math, math::MathNode::OMathPara(_)));
+        let run = result.tree.nodes.runs.get(&result.equations[0].run_id).unwrap();
+        assert_eq!(run.text, "\\frac{a}{b}");
+    }
 }
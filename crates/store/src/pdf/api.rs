@@ -2,10 +2,16 @@
 //!
 //! This module provides the public API for PDF export functionality.
 
+use super::imposition::impose_pages;
 use super::options::PdfExportOptions;
 use super::pdfa::{ComplianceReport, PdfAConformance, PdfAValidator};
-use super::renderer::{convert, PageRenderInfo, PdfRenderer};
+use super::renderer::{
+    convert, BitmapRenderInfo, PageRenderInfo, PdfRenderItem, PdfRenderer, OBJECT_LABEL_FONT,
+};
 use super::writer::{PdfDocumentWriter, PdfError, Result};
+use crate::image_codec;
+use crate::image_store::{ImageOptimizationReport, ImageStore};
+use doc_model::{DocumentTree, ResourceId};
 use std::fs::File;
 use std::io::BufWriter;
 use std::path::Path;
@@ -35,8 +41,18 @@ pub fn export_pdf(
     path: impl AsRef<Path>,
     options: PdfExportOptions,
 ) -> Result<()> {
-    // Convert render pages to PDF page info
-    let page_infos: Vec<PageRenderInfo> = pages.iter().map(convert::convert_page).collect();
+    export_pdf_drawing_images(pages, None, path, options)
+}
+
+/// Export render pages to a PDF file, drawing pictures from the image store
+/// at full resolution when one is given
+pub(crate) fn export_pdf_drawing_images(
+    pages: &[render_model::PageRender],
+    images: Option<&ImageStore>,
+    path: impl AsRef<Path>,
+    mut options: PdfExportOptions,
+) -> Result<()> {
+    let page_infos = prepare_pages(pages, &mut options, images.map(|images| (images, PDF_IMAGE_DPI)));
 
     // Open file for writing
     let file = File::create(path)?;
@@ -72,16 +88,114 @@ pub fn export_pdf(
 /// ```
 pub fn export_pdf_bytes(
     pages: &[render_model::PageRender],
-    options: PdfExportOptions,
+    mut options: PdfExportOptions,
 ) -> Result<Vec<u8>> {
-    // Convert render pages to PDF page info
-    let page_infos: Vec<PageRenderInfo> = pages.iter().map(convert::convert_page).collect();
+    let page_infos = prepare_pages(pages, &mut options, None);
 
     // Write PDF to memory
     let doc_writer = PdfDocumentWriter::new(options);
     doc_writer.write_to_bytes(&page_infos)
 }
 
+/// Resolution pictures are drawn at when they aren't compressed
+pub const PDF_IMAGE_DPI: f32 = 300.0;
+
+/// Export render pages to a PDF file, drawing pictures from the image store
+///
+/// With `options.image_optimization` set, pictures are compressed in a copy
+/// of the store first and drawn at the target resolution. Returns the space
+/// the compression saved.
+pub fn export_pdf_with_images(
+    pages: &[render_model::PageRender],
+    tree: &DocumentTree,
+    images: &ImageStore,
+    path: impl AsRef<Path>,
+    options: PdfExportOptions,
+) -> Result<ImageOptimizationReport> {
+    let (bytes, report) = export_pdf_bytes_with_images(pages, tree, images, options)?;
+    std::fs::write(path, bytes)?;
+    Ok(report)
+}
+
+/// Export render pages to PDF bytes, drawing pictures from the image store
+///
+/// Returns the PDF and the space saved by compressing pictures.
+pub fn export_pdf_bytes_with_images(
+    pages: &[render_model::PageRender],
+    tree: &DocumentTree,
+    images: &ImageStore,
+    mut options: PdfExportOptions,
+) -> Result<(Vec<u8>, ImageOptimizationReport)> {
+    let (page_infos, report) = match options.image_optimization.clone() {
+        Some(optimization) => {
+            let (optimized, report) = images.optimized_for_export(tree, &optimization);
+            let dpi = optimization.target_dpi;
+            (prepare_pages(pages, &mut options, Some((&optimized, dpi))), report)
+        }
+        None => (
+            prepare_pages(pages, &mut options, Some((images, PDF_IMAGE_DPI))),
+            ImageOptimizationReport::default(),
+        ),
+    };
+
+    let doc_writer = PdfDocumentWriter::new(options);
+    Ok((doc_writer.write_to_bytes(&page_infos)?, report))
+}
+
+/// Convert render pages to PDF page info and arrange them on sheets
+///
+/// The page range selects document pages, so it's applied before imposition
+/// and cleared from the options. With an image store, pictures are drawn
+/// from it at the given resolution.
+fn prepare_pages(
+    pages: &[render_model::PageRender],
+    options: &mut PdfExportOptions,
+    images: Option<(&ImageStore, f32)>,
+) -> Vec<PageRenderInfo> {
+    let mut page_infos: Vec<PageRenderInfo> = pages.iter().map(convert::convert_page).collect();
+    if let Some((images, dpi)) = images {
+        embed_images(&mut page_infos, images, dpi);
+    }
+    if options.imposition.is_identity() {
+        return page_infos;
+    }
+    let selected: Vec<PageRenderInfo> = page_infos
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| options.should_include_page(*index))
+        .map(|(_, page)| page)
+        .collect();
+    options.page_range = None;
+    impose_pages(&selected, &options.imposition)
+}
+
+/// Replace each picture found in the image store with a bitmap rasterized
+/// for its size on the page; pictures that can't be drawn keep their frame
+fn embed_images(pages: &mut [PageRenderInfo], images: &ImageStore, dpi: f32) {
+    for item in pages.iter_mut().flat_map(|page| page.items.iter_mut()) {
+        let PdfRenderItem::Image(image) = item else {
+            continue;
+        };
+        let resource_id = ResourceId::new(image.resource_id.as_str());
+        let (width, height) = (image.width as f32, image.height as f32);
+        let Ok(png) = images.rasterize(&resource_id, width, height, dpi) else {
+            continue;
+        };
+        let Ok(pixels) = image_codec::decode_png(&png) else {
+            continue;
+        };
+        *item = PdfRenderItem::Bitmap(BitmapRenderInfo {
+            pixel_width: pixels.width,
+            pixel_height: pixels.height,
+            rgba: pixels.data,
+            x: image.x,
+            y: image.y,
+            width: image.width,
+            height: image.height,
+        });
+    }
+}
+
 /// Export a single page to PDF bytes
 ///
 /// Convenience function for exporting a single page.
@@ -253,6 +367,11 @@ fn analyze_render_item_for_compliance(
         render_model::RenderItem::Image(_) => {
             validator.add_color_space("DeviceRGB");
         }
+        render_model::RenderItem::EmbeddedObject(_) => {
+            // Objects are drawn as a labelled frame
+            validator.add_font(OBJECT_LABEL_FONT, false);
+            validator.add_color_space("DeviceRGB");
+        }
         render_model::RenderItem::Shape(shape) => {
             // Check for transparency
             if let Some(render_model::ShapeFillRender::Gradient { .. }) = &shape.fill {
@@ -263,6 +382,8 @@ fn analyze_render_item_for_compliance(
         // UI elements are not exported to PDF
         render_model::RenderItem::Caret { .. }
         | render_model::RenderItem::Selection { .. }
+        | render_model::RenderItem::RemoteCaret(_)
+        | render_model::RenderItem::RemoteSelection(_)
         | render_model::RenderItem::Squiggly(_)
         | render_model::RenderItem::FindHighlight { .. } => {}
         render_model::RenderItem::TableBorder(_) | render_model::RenderItem::TableCell(_) => {
@@ -276,11 +397,23 @@ fn analyze_render_item_for_compliance(
                 validator.set_has_transparency(true);
             }
         }
+        render_model::RenderItem::Ink(_) => {
+            // Ink is flattened to bitmaps with an alpha mask
+            validator.set_has_transparency(true);
+            validator.add_color_space("DeviceRGB");
+        }
         render_model::RenderItem::LineNumber(info) => {
             // Line numbers are rendered as text with a font
             validator.add_font(&info.font_family, false);
             validator.add_color_space("DeviceRGB");
         }
+        render_model::RenderItem::Watermark(watermark) => {
+            // Opacity is blended into the color, not drawn as transparency
+            if let render_model::WatermarkRenderContent::Text { font_family, .. } = &watermark.content {
+                validator.add_font(font_family, false);
+            }
+            validator.add_color_space("DeviceRGB");
+        }
     }
 }
 
@@ -308,7 +441,7 @@ pub fn is_pdfa_conformance_supported(conformance: PdfAConformance) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use render_model::{Color, GlyphRun, PageRender, RenderItem};
+    use render_model::{Color, GlyphRun, PageRender, RenderItem, TextEffects};
 
     fn create_test_page() -> PageRender {
         PageRender {
@@ -326,6 +459,9 @@ mod tests {
                 x: 72.0,
                 y: 720.0,
                 hyperlink: None,
+                effects: TextEffects::default(),
+                font_key: 0,
+                glyphs: Vec::new(),
             })],
         }
     }
@@ -424,6 +560,9 @@ mod tests {
                     x: 72.0,
                     y: 720.0,
                     hyperlink: None,
+                    effects: TextEffects::default(),
+                    font_key: 0,
+                    glyphs: Vec::new(),
                 })],
             },
         ];
@@ -469,4 +608,78 @@ mod tests {
         let pdf_str = String::from_utf8_lossy(&pdf_bytes);
         assert!(pdf_str.contains("re")); // Rectangle operator
     }
+
+    #[test]
+    fn test_export_pictures_from_image_store() {
+        use crate::image_codec::{encode_png, RgbaImage};
+        use crate::image_store::ImageOptimizationOptions;
+        use doc_model::{ImageNode, Paragraph};
+
+        let store = ImageStore::new();
+        let data = (0..400 * 400).flat_map(|i| [(i % 256) as u8, (i / 400) as u8, 64, 255]).collect();
+        let png = encode_png(&RgbaImage { width: 400, height: 400, data }).unwrap();
+        let resource_id = store.store_image(png, None).unwrap();
+
+        let mut tree = DocumentTree::new();
+        let para_id = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+        let node = ImageNode::with_size(resource_id.clone(), 400, 400, 72.0, 72.0);
+        tree.insert_image(node, para_id, None).unwrap();
+
+        let page = PageRender {
+            page_index: 0,
+            width: 612.0,
+            height: 792.0,
+            items: vec![RenderItem::Image(render_model::ImageRenderInfo::new(
+                "image",
+                resource_id.as_str(),
+                render_model::Rect::new(72.0, 72.0, 72.0, 72.0),
+            ))],
+        };
+
+        let options = PdfExportOptions::new().with_compression(false);
+        let (bytes, report) = export_pdf_bytes_with_images(std::slice::from_ref(&page), &tree, &store, options).unwrap();
+        assert!(String::from_utf8_lossy(&bytes).contains("/Subtype /Image"));
+        assert_eq!(report.compressed_count(), 0);
+
+        let options = PdfExportOptions::new()
+            .with_compression(false)
+            .with_image_optimization(ImageOptimizationOptions::email());
+        let (small, report) = export_pdf_bytes_with_images(&[page], &tree, &store, options).unwrap();
+        assert!(String::from_utf8_lossy(&small).contains("/Subtype /Image"));
+        assert_eq!(report.compressed_count(), 1);
+        assert!(small.len() < bytes.len());
+    }
+
+    #[test]
+    fn test_export_svg_picture_as_bitmap() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" width="10pt" height="10pt" viewBox="0 0 10 10">
+            <rect width="10" height="10" fill="#00ff00"/>
+        </svg>"##;
+        let store = ImageStore::new();
+        let resource_id = store.store_image(svg.as_bytes().to_vec(), None).unwrap();
+        let item = RenderItem::Image(render_model::ImageRenderInfo::new(
+            "image",
+            resource_id.as_str(),
+            render_model::Rect::new(72.0, 72.0, 36.0, 36.0),
+        ));
+        let pages = vec![PageRender {
+            page_index: 0,
+            width: 612.0,
+            height: 792.0,
+            items: vec![item],
+        }];
+
+        let mut options = PdfExportOptions::default();
+        let page_infos = prepare_pages(&pages, &mut options, Some((&store, 144.0)));
+        let PdfRenderItem::Bitmap(bitmap) = &page_infos[0].items[0] else {
+            panic!("SVG picture wasn't rasterized");
+        };
+        // Half an inch at 144 dpi
+        assert_eq!((bitmap.pixel_width, bitmap.pixel_height), (72, 72));
+        assert_eq!(&bitmap.rgba[..4], &[0, 255, 0, 255]);
+
+        // Without the image store the picture keeps its frame
+        let page_infos = prepare_pages(&pages, &mut options, None);
+        assert!(matches!(page_infos[0].items[0], PdfRenderItem::Image(_)));
+    }
 }
@@ -0,0 +1,325 @@
+//! Print imposition
+//!
+//! Arranges document pages on printer sheets: several pages per sheet,
+//! booklets for saddle-stitch binding, and scaling to a paper size. The
+//! layout is pure geometry (`Sheet`, `Placement`) so both the PDF writer
+//! and the raster renderer can draw it.
+
+use super::renderer::{PageRenderInfo, PdfRenderItem};
+use serde::{Deserialize, Serialize};
+
+/// Number of document pages printed on each side of a sheet
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PagesPerSheet {
+    #[default]
+    One,
+    Two,
+    Four,
+    Six,
+}
+
+impl PagesPerSheet {
+    /// Number of pages per sheet side
+    pub fn count(&self) -> usize {
+        match self {
+            Self::One => 1,
+            Self::Two => 2,
+            Self::Four => 4,
+            Self::Six => 6,
+        }
+    }
+
+    /// Grid shapes (columns, rows) that fit this many pages
+    fn grids(&self) -> &'static [(usize, usize)] {
+        match self {
+            Self::One => &[(1, 1)],
+            Self::Two => &[(2, 1), (1, 2)],
+            Self::Four => &[(2, 2)],
+            Self::Six => &[(3, 2), (2, 3)],
+        }
+    }
+}
+
+/// Paper size in points
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PaperSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl PaperSize {
+    /// Create a paper size
+    pub fn new(width: f64, height: f64) -> Self {
+        Self { width, height }
+    }
+}
+
+/// How document pages are arranged on printer sheets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Imposition {
+    /// Pages per sheet side; ignored for booklets
+    #[serde(default)]
+    pub pages_per_sheet: PagesPerSheet,
+    /// Reorder pages into folded sheets for saddle-stitch binding, two
+    /// pages per side; print duplex, flipping on the short edge
+    #[serde(default)]
+    pub booklet: bool,
+    /// Paper to print on (None = the size of the first page)
+    #[serde(default)]
+    pub paper: Option<PaperSize>,
+    /// Scale single pages to the paper instead of printing at actual size
+    #[serde(default)]
+    pub scale_to_paper: bool,
+}
+
+/// Where a document page goes on a sheet
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    /// Index of the page in the input
+    pub page: usize,
+    /// Left edge on the sheet in points
+    pub x: f64,
+    /// Top edge on the sheet in points
+    pub y: f64,
+    /// Scale factor applied to the page
+    pub scale: f64,
+}
+
+/// One side of a printer sheet
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sheet {
+    /// Sheet width in points
+    pub width: f64,
+    /// Sheet height in points
+    pub height: f64,
+    /// Pages on this side; empty cells (booklet padding) have no placement
+    pub placements: Vec<Placement>,
+}
+
+impl Imposition {
+    /// Create an imposition that prints pages unchanged
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of pages per sheet
+    pub fn with_pages_per_sheet(mut self, pages_per_sheet: PagesPerSheet) -> Self {
+        self.pages_per_sheet = pages_per_sheet;
+        self
+    }
+
+    /// Set booklet printing
+    pub fn with_booklet(mut self, booklet: bool) -> Self {
+        self.booklet = booklet;
+        self
+    }
+
+    /// Set the paper size
+    pub fn with_paper(mut self, paper: PaperSize) -> Self {
+        self.paper = Some(paper);
+        self
+    }
+
+    /// Set whether single pages are scaled to the paper
+    pub fn with_scale_to_paper(mut self, scale_to_paper: bool) -> Self {
+        self.scale_to_paper = scale_to_paper;
+        self
+    }
+
+    /// Check if pages are printed one per sheet at their own size
+    pub fn is_identity(&self) -> bool {
+        !self.booklet && self.pages_per_sheet == PagesPerSheet::One && self.paper.is_none()
+    }
+
+    /// Lay out pages with the given sizes (width, height) on sheets
+    pub fn sheets(&self, page_sizes: &[(f64, f64)]) -> Vec<Sheet> {
+        let Some(&first) = page_sizes.first() else {
+            return Vec::new();
+        };
+        let paper = self.paper.map(|p| (p.width, p.height));
+
+        if self.booklet {
+            // Two pages side by side on a sheet turned sideways
+            let (width, height) = paper.map_or((first.0 * 2.0, first.1), |(w, h)| (w.max(h), w.min(h)));
+            return booklet_order(page_sizes.len())
+                .chunks(2)
+                .map(|pair| {
+                    let mut sheet = Sheet { width, height, placements: Vec::new() };
+                    for (column, page) in pair.iter().enumerate() {
+                        if let Some(page) = *page {
+                            let cell = (column as f64 * width / 2.0, 0.0, width / 2.0, height);
+                            sheet.placements.push(fit_in_cell(page, page_sizes[page], cell));
+                        }
+                    }
+                    sheet
+                })
+                .collect();
+        }
+
+        let per_sheet = self.pages_per_sheet.count();
+        if per_sheet == 1 {
+            return page_sizes
+                .iter()
+                .enumerate()
+                .map(|(page, &size)| self.single_sheet(page, size, paper))
+                .collect();
+        }
+
+        let (width, height, columns, rows) = grid_layout(self.pages_per_sheet, first, paper.unwrap_or(first));
+        let (cell_width, cell_height) = (width / columns as f64, height / rows as f64);
+        (0..page_sizes.len())
+            .collect::<Vec<_>>()
+            .chunks(per_sheet)
+            .map(|pages| Sheet {
+                width,
+                height,
+                placements: pages
+                    .iter()
+                    .enumerate()
+                    .map(|(slot, &page)| {
+                        let (column, row) = (slot % columns, slot / columns);
+                        let cell = (column as f64 * cell_width, row as f64 * cell_height, cell_width, cell_height);
+                        fit_in_cell(page, page_sizes[page], cell)
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// A sheet holding one page
+    fn single_sheet(&self, page: usize, size: (f64, f64), paper: Option<(f64, f64)>) -> Sheet {
+        let Some((width, height)) = paper else {
+            return Sheet {
+                width: size.0,
+                height: size.1,
+                placements: vec![Placement { page, x: 0.0, y: 0.0, scale: 1.0 }],
+            };
+        };
+        // Turn the paper to match the page
+        let (width, height) = if (size.0 > size.1) == (width > height) {
+            (width, height)
+        } else {
+            (height, width)
+        };
+        let placement = if self.scale_to_paper {
+            fit_in_cell(page, size, (0.0, 0.0, width, height))
+        } else {
+            Placement {
+                page,
+                x: (width - size.0) / 2.0,
+                y: (height - size.1) / 2.0,
+                scale: 1.0,
+            }
+        };
+        Sheet { width, height, placements: vec![placement] }
+    }
+}
+
+/// Pick the sheet orientation and grid that print pages largest
+fn grid_layout(
+    pages_per_sheet: PagesPerSheet,
+    page: (f64, f64),
+    paper: (f64, f64),
+) -> (f64, f64, usize, usize) {
+    let mut best = (paper.0, paper.1, 1, 1);
+    let mut best_scale = 0.0;
+    for (width, height) in [paper, (paper.1, paper.0)] {
+        for &(columns, rows) in pages_per_sheet.grids() {
+            let scale = (width / columns as f64 / page.0).min(height / rows as f64 / page.1);
+            if scale > best_scale + f64::EPSILON {
+                best = (width, height, columns, rows);
+                best_scale = scale;
+            }
+        }
+    }
+    best
+}
+
+/// Scale a page to fit a cell (x, y, width, height), centered
+fn fit_in_cell(page: usize, size: (f64, f64), cell: (f64, f64, f64, f64)) -> Placement {
+    let (x, y, width, height) = cell;
+    let scale = (width / size.0).min(height / size.1);
+    Placement {
+        page,
+        x: x + (width - size.0 * scale) / 2.0,
+        y: y + (height - size.1 * scale) / 2.0,
+        scale,
+    }
+}
+
+/// Page order for a saddle-stitched booklet
+///
+/// Returns page indices in sheet-side order, left then right, padded to a
+/// multiple of four with indices past the end for blank pages. Each sheet
+/// has a front side (last, first) and a back side (second, second to last).
+pub fn booklet_order(page_count: usize) -> Vec<Option<usize>> {
+    let total = page_count.div_ceil(4) * 4;
+    let mut order = Vec::with_capacity(total);
+    for sheet in 0..total / 4 {
+        let (low, high) = (2 * sheet, total - 1 - 2 * sheet);
+        order.extend([high, low, low + 1, high - 1]);
+    }
+    order.into_iter().map(|page| (page < page_count).then_some(page)).collect()
+}
+
+/// Arrange converted pages on sheets
+pub(crate) fn impose_pages(pages: &[PageRenderInfo], imposition: &Imposition) -> Vec<PageRenderInfo> {
+    let sizes: Vec<_> = pages.iter().map(|page| (page.width, page.height)).collect();
+    imposition
+        .sheets(&sizes)
+        .into_iter()
+        .map(|sheet| {
+            let mut info = PageRenderInfo::new(sheet.width, sheet.height);
+            for placement in &sheet.placements {
+                for item in &pages[placement.page].items {
+                    info.add_item(place_item(item, placement));
+                }
+            }
+            info
+        })
+        .collect()
+}
+
+/// Move and scale an item onto a sheet
+fn place_item(item: &PdfRenderItem, placement: &Placement) -> PdfRenderItem {
+    let s = placement.scale;
+    let x = |value: f64| placement.x + value * s;
+    let y = |value: f64| placement.y + value * s;
+    let mut item = item.clone();
+    match &mut item {
+        PdfRenderItem::Text(text) => {
+            text.x = x(text.x);
+            text.y = y(text.y);
+            text.font_size *= s;
+        }
+        PdfRenderItem::Line(line) => {
+            line.x1 = x(line.x1);
+            line.y1 = y(line.y1);
+            line.x2 = x(line.x2);
+            line.y2 = y(line.y2);
+            line.width *= s;
+        }
+        PdfRenderItem::Rectangle(rect) => {
+            rect.x = x(rect.x);
+            rect.y = y(rect.y);
+            rect.width *= s;
+            rect.height *= s;
+            rect.stroke_width *= s;
+        }
+        PdfRenderItem::Image(image) => {
+            image.x = x(image.x);
+            image.y = y(image.y);
+            image.width *= s;
+            image.height *= s;
+        }
+        PdfRenderItem::Bitmap(bitmap) => {
+            bitmap.x = x(bitmap.x);
+            bitmap.y = y(bitmap.y);
+            bitmap.width *= s;
+            bitmap.height *= s;
+        }
+    }
+    item
+}
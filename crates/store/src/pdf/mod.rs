@@ -12,6 +12,7 @@
 //! - `images`: Image XObject generation
 //! - `renderer`: Converts RenderPage to PDF
 //! - `options`: PDF export configuration
+//! - `imposition`: Pages per sheet, booklets, and scaling to paper
 //! - `pdfa`: PDF/A compliance support (PDF/A-1b, PDF/A-2b)
 //! - `api`: Public API for PDF export
 
@@ -20,6 +21,7 @@ mod content;
 mod document;
 mod fonts;
 mod images;
+mod imposition;
 mod objects;
 mod options;
 pub mod pdfa;
@@ -27,6 +29,7 @@ mod renderer;
 mod writer;
 
 pub use api::*;
+pub use imposition::{booklet_order, Imposition, PagesPerSheet, PaperSize, Placement, Sheet};
 pub use options::*;
 pub use pdfa::{
     ComplianceIssue, ComplianceReport, IssueCategory, IssueSeverity,
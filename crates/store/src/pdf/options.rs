@@ -3,7 +3,9 @@
 //! This module defines configuration options for PDF export.
 
 use super::document::PdfVersion;
+use super::imposition::Imposition;
 use super::pdfa::PdfAConformance;
+use crate::image_store::ImageOptimizationOptions;
 use serde::{Deserialize, Serialize};
 use std::ops::Range;
 
@@ -47,6 +49,13 @@ pub struct PdfExportOptions {
     /// PDF/A conformance level (None for standard PDF)
     #[serde(default)]
     pub pdfa_conformance: PdfAConformance,
+    /// Arrangement of pages on printer sheets
+    #[serde(default)]
+    pub imposition: Imposition,
+    /// Picture compression for pictures drawn from the image store
+    /// (None = full resolution)
+    #[serde(default)]
+    pub image_optimization: Option<ImageOptimizationOptions>,
 }
 
 fn default_compress() -> bool {
@@ -152,6 +161,8 @@ impl Default for PdfExportOptions {
             include_outline: true,
             include_links: true,
             pdfa_conformance: PdfAConformance::default(),
+            imposition: Imposition::default(),
+            image_optimization: None,
         }
     }
 }
@@ -230,6 +241,18 @@ impl PdfExportOptions {
         self
     }
 
+    /// Set how pages are arranged on printer sheets
+    pub fn with_imposition(mut self, imposition: Imposition) -> Self {
+        self.imposition = imposition;
+        self
+    }
+
+    /// Compress pictures drawn from the image store
+    pub fn with_image_optimization(mut self, options: ImageOptimizationOptions) -> Self {
+        self.image_optimization = Some(options);
+        self
+    }
+
     /// Check if PDF/A compliance is enabled
     pub fn is_pdfa(&self) -> bool {
         self.pdfa_conformance.is_pdfa()
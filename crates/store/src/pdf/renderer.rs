@@ -9,6 +9,14 @@ use super::fonts::{FontKey, FontManager, StandardFont};
 use super::images::ImageManager;
 use super::options::PdfExportOptions;
 
+/// Resolution ink is flattened to, since strokes are drawn as bitmaps
+pub const INK_FLATTEN_DPI: f64 = 300.0;
+
+/// Font of the label drawn on embedded objects
+pub const OBJECT_LABEL_FONT: &str = "Helvetica";
+/// Largest size of the label drawn on embedded objects, in points
+const OBJECT_LABEL_SIZE: f64 = 9.0;
+
 /// A color in RGB format (0.0 to 1.0)
 #[derive(Debug, Clone, Copy)]
 pub struct RgbColor {
@@ -58,6 +66,8 @@ pub struct TextRenderInfo {
     pub italic: bool,
     /// Text color
     pub color: RgbColor,
+    /// Rotation about (`x`, `y`) in degrees (clockwise)
+    pub rotation: f64,
 }
 
 /// Line rendering info
@@ -111,6 +121,25 @@ pub struct ImageRenderInfo {
     pub height: f64,
 }
 
+/// A bitmap drawn from its pixels rather than from the image store
+#[derive(Debug, Clone)]
+pub struct BitmapRenderInfo {
+    /// Width in pixels
+    pub pixel_width: u32,
+    /// Height in pixels
+    pub pixel_height: u32,
+    /// RGBA pixels, row by row, with straight alpha
+    pub rgba: Vec<u8>,
+    /// X position
+    pub x: f64,
+    /// Y position
+    pub y: f64,
+    /// Display width
+    pub width: f64,
+    /// Display height
+    pub height: f64,
+}
+
 /// Abstract render item for PDF generation
 #[derive(Debug, Clone)]
 pub enum PdfRenderItem {
@@ -122,6 +151,8 @@ pub enum PdfRenderItem {
     Rectangle(RectRenderInfo),
     /// Image element
     Image(ImageRenderInfo),
+    /// Bitmap element (flattened ink)
+    Bitmap(BitmapRenderInfo),
 }
 
 /// Page rendering info
@@ -159,6 +190,10 @@ pub struct PdfRenderer {
     image_manager: ImageManager,
     /// Export options
     options: PdfExportOptions,
+    /// Bitmaps drawn since the last `take_bitmaps`, with their XObject names
+    bitmaps: Vec<(String, BitmapRenderInfo)>,
+    /// Bitmaps named so far
+    bitmap_count: usize,
 }
 
 impl PdfRenderer {
@@ -168,6 +203,8 @@ impl PdfRenderer {
             font_manager: FontManager::new(),
             image_manager: ImageManager::new(),
             options,
+            bitmaps: Vec::new(),
+            bitmap_count: 0,
         }
     }
 
@@ -219,6 +256,9 @@ impl PdfRenderer {
                 PdfRenderItem::Image(image) => {
                     self.render_image(&mut content, image, page_height);
                 }
+                PdfRenderItem::Bitmap(bitmap) => {
+                    self.render_bitmap(&mut content, bitmap, page_height);
+                }
                 _ => {}
             }
         }
@@ -255,8 +295,9 @@ impl PdfRenderer {
                 // Convert Y coordinate (PDF origin is bottom-left)
                 let pdf_y = page_height - text.y;
 
-                // Position and show text
-                content.set_text_matrix(1.0, 0.0, 0.0, 1.0, text.x, pdf_y);
+                // Position and show text; PDF angles run counterclockwise
+                let (sin, cos) = (-text.rotation).to_radians().sin_cos();
+                content.set_text_matrix(cos, sin, -sin, cos, text.x, pdf_y);
                 content.show_text(&text.text);
             }
 
@@ -338,6 +379,26 @@ impl PdfRenderer {
         content.restore_state();
     }
 
+    /// Render a bitmap, naming it for the page's XObject resources
+    fn render_bitmap(&mut self, content: &mut ContentStream, bitmap: &BitmapRenderInfo, page_height: f64) {
+        let name = format!("Bm{}", self.bitmap_count);
+        self.bitmap_count += 1;
+
+        content.save_state();
+        let pdf_y = page_height - bitmap.y - bitmap.height;
+        content.transform(bitmap.width, 0.0, 0.0, bitmap.height, bitmap.x, pdf_y);
+        content.draw_xobject(&name);
+        content.restore_state();
+
+        self.bitmaps.push((name, bitmap.clone()));
+    }
+
+    /// Take the bitmaps drawn by the pages rendered since the last call,
+    /// which the writer embeds as image XObjects
+    pub fn take_bitmaps(&mut self) -> Vec<(String, BitmapRenderInfo)> {
+        std::mem::take(&mut self.bitmaps)
+    }
+
     /// Create a PDF page object from page info
     pub fn create_page_object(&self, page_info: &PageRenderInfo) -> PdfPage {
         let mut page = PdfPage::new(MediaBox::from_dimensions(page_info.width, page_info.height));
@@ -373,6 +434,7 @@ pub mod convert {
             bold: glyph.bold,
             italic: glyph.italic,
             color: convert_color(&glyph.color),
+            rotation: 0.0,
         }
     }
 
@@ -440,6 +502,8 @@ pub mod convert {
             // Skip UI-only elements
             render_model::RenderItem::Caret { .. } |
             render_model::RenderItem::Selection { .. } |
+            render_model::RenderItem::RemoteCaret(_) |
+            render_model::RenderItem::RemoteSelection(_) |
             render_model::RenderItem::Squiggly(_) |
             render_model::RenderItem::FindHighlight { .. } => {
                 vec![]
@@ -497,6 +561,89 @@ pub mod convert {
                     bold: false,
                     italic: false,
                     color: convert_color(&info.color),
+                    rotation: 0.0,
+                })]
+            }
+            render_model::RenderItem::Watermark(watermark) => convert_watermark(watermark),
+            render_model::RenderItem::Ink(ink) => convert_ink(ink),
+            render_model::RenderItem::EmbeddedObject(object) => convert_embedded_object(object),
+        }
+    }
+
+    /// Draw an embedded object as a frame with its label, as its preview
+    /// picture is usually a metafile
+    fn convert_embedded_object(object: &render_model::EmbeddedObjectRenderInfo) -> Vec<PdfRenderItem> {
+        let bounds = &object.bounds;
+        let font_size = (bounds.height * 0.5).min(OBJECT_LABEL_SIZE);
+        vec![
+            PdfRenderItem::Rectangle(RectRenderInfo {
+                x: bounds.x,
+                y: bounds.y,
+                width: bounds.width,
+                height: bounds.height,
+                fill: Some(RgbColor::new(0.92, 0.92, 0.92)),
+                stroke: Some(RgbColor::new(0.75, 0.75, 0.75)),
+                stroke_width: 0.75,
+            }),
+            PdfRenderItem::Text(TextRenderInfo {
+                text: object.label.clone(),
+                x: bounds.x + 4.0,
+                y: bounds.y + (bounds.height + font_size) / 2.0,
+                font_family: OBJECT_LABEL_FONT.to_string(),
+                font_size,
+                bold: false,
+                italic: false,
+                color: RgbColor::new(0.35, 0.35, 0.35),
+                rotation: 0.0,
+            }),
+        ]
+    }
+
+    /// Flatten ink into a bitmap, as PDF has no variable-width strokes
+    fn convert_ink(ink: &render_model::InkRenderInfo) -> Vec<PdfRenderItem> {
+        match crate::raster::render_ink(ink, INK_FLATTEN_DPI) {
+            Ok(pixmap) => vec![PdfRenderItem::Bitmap(BitmapRenderInfo {
+                pixel_width: pixmap.width(),
+                pixel_height: pixmap.height(),
+                rgba: pixmap.data().to_vec(),
+                x: ink.bounds.x,
+                y: ink.bounds.y,
+                width: ink.bounds.width,
+                height: ink.bounds.height,
+            })],
+            Err(_) => vec![],
+        }
+    }
+
+    /// Convert a watermark, drawn on the white page with its opacity
+    /// blended into its color
+    fn convert_watermark(watermark: &render_model::WatermarkRenderInfo) -> Vec<PdfRenderItem> {
+        let bounds = &watermark.bounds;
+        match &watermark.content {
+            render_model::WatermarkRenderContent::Text { text, font_family, font_size, color, baseline } => {
+                // Rotate the baseline origin about the center of the bounds
+                let (sin, cos) = watermark.rotation.to_radians().sin_cos();
+                let (dx, dy) = (-bounds.width / 2.0, baseline - bounds.height / 2.0);
+                let blend = |channel: u8| 255.0 - (255.0 - channel as f64) * watermark.opacity;
+                vec![PdfRenderItem::Text(TextRenderInfo {
+                    text: text.clone(),
+                    x: bounds.x + bounds.width / 2.0 + dx * cos - dy * sin,
+                    y: bounds.y + bounds.height / 2.0 + dx * sin + dy * cos,
+                    font_family: font_family.clone(),
+                    font_size: *font_size,
+                    bold: false,
+                    italic: false,
+                    color: RgbColor::new(blend(color.r) / 255.0, blend(color.g) / 255.0, blend(color.b) / 255.0),
+                    rotation: watermark.rotation,
+                })]
+            }
+            render_model::WatermarkRenderContent::Image { resource_id } => {
+                vec![PdfRenderItem::Image(ImageRenderInfo {
+                    resource_id: resource_id.clone(),
+                    x: bounds.x,
+                    y: bounds.y,
+                    width: bounds.width,
+                    height: bounds.height,
                 })]
             }
         }
@@ -540,6 +687,7 @@ mod tests {
             bold: false,
             italic: false,
             color: RgbColor::black(),
+            rotation: 0.0,
         }));
 
         assert_eq!(page.width, 612.0);
@@ -562,6 +710,7 @@ mod tests {
             bold: false,
             italic: false,
             color: RgbColor::black(),
+            rotation: 0.0,
         }));
 
         let content = renderer.render_page(&page);
@@ -1,7 +1,7 @@
 //! Comprehensive tests for PDF export functionality
 
 use super::*;
-use render_model::{Color, GlyphRun, PageRender, Rect, RenderItem};
+use render_model::{Color, GlyphRun, PageRender, Rect, RenderItem, TextEffects};
 
 // Helper to create a basic test page
 fn create_basic_page() -> PageRender {
@@ -20,6 +20,9 @@ fn create_basic_page() -> PageRender {
             x: 72.0,
             y: 720.0,
             hyperlink: None,
+            effects: TextEffects::default(),
+            font_key: 0,
+            glyphs: Vec::new(),
         })],
     }
 }
@@ -43,6 +46,9 @@ fn create_complex_page() -> PageRender {
                 x: 72.0,
                 y: 72.0,
                 hyperlink: None,
+                effects: TextEffects::default(),
+                font_key: 0,
+                glyphs: Vec::new(),
             }),
             RenderItem::GlyphRun(GlyphRun {
                 text: "Body text paragraph.".to_string(),
@@ -55,6 +61,9 @@ fn create_complex_page() -> PageRender {
                 x: 72.0,
                 y: 120.0,
                 hyperlink: None,
+                effects: TextEffects::default(),
+                font_key: 0,
+                glyphs: Vec::new(),
             }),
             // Rectangle
             RenderItem::Rectangle {
@@ -147,6 +156,9 @@ fn test_pdf_multiple_pages() {
                 x: 72.0,
                 y: 720.0,
                 hyperlink: None,
+                effects: TextEffects::default(),
+                font_key: 0,
+                glyphs: Vec::new(),
             })],
         },
         PageRender {
@@ -164,6 +176,9 @@ fn test_pdf_multiple_pages() {
                 x: 72.0,
                 y: 720.0,
                 hyperlink: None,
+                effects: TextEffects::default(),
+                font_key: 0,
+                glyphs: Vec::new(),
             })],
         },
     ];
@@ -259,6 +274,9 @@ fn test_pdf_font_variants() {
                 x: 72.0,
                 y: 100.0,
                 hyperlink: None,
+                effects: TextEffects::default(),
+                font_key: 0,
+                glyphs: Vec::new(),
             }),
             // Bold
             RenderItem::GlyphRun(GlyphRun {
@@ -272,6 +290,9 @@ fn test_pdf_font_variants() {
                 x: 72.0,
                 y: 120.0,
                 hyperlink: None,
+                effects: TextEffects::default(),
+                font_key: 0,
+                glyphs: Vec::new(),
             }),
             // Italic
             RenderItem::GlyphRun(GlyphRun {
@@ -285,6 +306,9 @@ fn test_pdf_font_variants() {
                 x: 72.0,
                 y: 140.0,
                 hyperlink: None,
+                effects: TextEffects::default(),
+                font_key: 0,
+                glyphs: Vec::new(),
             }),
             // Bold Italic
             RenderItem::GlyphRun(GlyphRun {
@@ -298,6 +322,9 @@ fn test_pdf_font_variants() {
                 x: 72.0,
                 y: 160.0,
                 hyperlink: None,
+                effects: TextEffects::default(),
+                font_key: 0,
+                glyphs: Vec::new(),
             }),
         ],
     };
@@ -332,6 +359,9 @@ fn test_pdf_colors() {
                 x: 72.0,
                 y: 100.0,
                 hyperlink: None,
+                effects: TextEffects::default(),
+                font_key: 0,
+                glyphs: Vec::new(),
             }),
             // Green rectangle
             RenderItem::Rectangle {
@@ -457,6 +487,9 @@ fn test_page_range_option() {
                 x: 72.0,
                 y: 720.0,
                 hyperlink: None,
+                effects: TextEffects::default(),
+                font_key: 0,
+                glyphs: Vec::new(),
             })],
         },
         PageRender {
@@ -474,6 +507,9 @@ fn test_page_range_option() {
                 x: 72.0,
                 y: 720.0,
                 hyperlink: None,
+                effects: TextEffects::default(),
+                font_key: 0,
+                glyphs: Vec::new(),
             })],
         },
     ];
@@ -672,6 +708,9 @@ fn test_pdfa_multiple_pages() {
                 x: 72.0,
                 y: 720.0,
                 hyperlink: None,
+                effects: TextEffects::default(),
+                font_key: 0,
+                glyphs: Vec::new(),
             })],
         },
     ];
@@ -779,3 +818,84 @@ fn test_pdfa_conformance_properties() {
     assert!(!PdfAConformance::None.requires_font_embedding());
     assert!(!PdfAConformance::None.is_pdfa());
 }
+
+fn numbered_pages(count: usize) -> Vec<PageRender> {
+    (0..count)
+        .map(|index| {
+            let mut page = create_basic_page();
+            page.page_index = index as u32;
+            page
+        })
+        .collect()
+}
+
+#[test]
+fn test_booklet_order() {
+    assert_eq!(
+        booklet_order(4),
+        vec![Some(3), Some(0), Some(1), Some(2)]
+    );
+    // Padded to 8 with blanks at the end of the booklet
+    assert_eq!(
+        booklet_order(6),
+        vec![None, Some(0), Some(1), None, Some(5), Some(2), Some(3), Some(4)]
+    );
+}
+
+#[test]
+fn test_imposition_two_up_turns_sheet() {
+    let imposition = Imposition::new().with_pages_per_sheet(PagesPerSheet::Two);
+    let sheets = imposition.sheets(&[(612.0, 792.0); 3]);
+
+    assert_eq!(sheets.len(), 2);
+    // Portrait pages side by side on a landscape sheet
+    assert_eq!((sheets[0].width, sheets[0].height), (792.0, 612.0));
+    assert_eq!(sheets[0].placements.len(), 2);
+    assert_eq!(sheets[1].placements.len(), 1);
+    let (left, right) = (sheets[0].placements[0], sheets[0].placements[1]);
+    assert!((left.scale - 396.0 / 612.0).abs() < 1e-9);
+    assert!(right.x >= 396.0);
+}
+
+#[test]
+fn test_imposition_six_up() {
+    let imposition = Imposition::new().with_pages_per_sheet(PagesPerSheet::Six);
+    let sheets = imposition.sheets(&[(612.0, 792.0); 6]);
+
+    assert_eq!(sheets.len(), 1);
+    assert_eq!(sheets[0].placements.len(), 6);
+    for placement in &sheets[0].placements {
+        assert!(placement.x + 612.0 * placement.scale <= sheets[0].width + 1e-6);
+        assert!(placement.y + 792.0 * placement.scale <= sheets[0].height + 1e-6);
+    }
+}
+
+#[test]
+fn test_imposition_scale_to_paper() {
+    let a4 = PaperSize::new(595.0, 842.0);
+    let actual = Imposition::new().with_paper(a4).sheets(&[(612.0, 792.0)]);
+    assert_eq!(actual[0].placements[0].scale, 1.0);
+
+    let fitted = Imposition::new()
+        .with_paper(a4)
+        .with_scale_to_paper(true)
+        .sheets(&[(612.0, 792.0)]);
+    assert_eq!((fitted[0].width, fitted[0].height), (595.0, 842.0));
+    assert!((fitted[0].placements[0].scale - 595.0 / 612.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_pdf_export_with_imposition() {
+    let pages = numbered_pages(5);
+
+    let options = PdfExportOptions::new()
+        .with_imposition(Imposition::new().with_pages_per_sheet(PagesPerSheet::Four));
+    let pdf_str = String::from_utf8_lossy(&export_pdf_bytes(&pages, options).unwrap()).into_owned();
+    assert!(pdf_str.contains("/Count 2"));
+
+    let options = PdfExportOptions::new().with_imposition(Imposition::new().with_booklet(true));
+    let pdf_str = String::from_utf8_lossy(&export_pdf_bytes(&pages, options).unwrap()).into_owned();
+    // Eight booklet pages on two sheets, both sides
+    assert!(pdf_str.contains("/Count 4"));
+    assert!(pdf_str.contains("1224")); // Two Letter pages wide
+}
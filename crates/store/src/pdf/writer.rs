@@ -10,6 +10,7 @@ use super::content::ContentStream;
 use super::document::{create_catalog, create_pages, DocumentInfo, PdfDocumentBuilder, PdfPage, PdfVersion};
 use super::fonts::{create_standard_font_dict, FontManager};
 use super::objects::{PdfDictionary, PdfObject, PdfSerializer, PdfStream};
+use super::images::{create_soft_mask, ImageData};
 use super::options::PdfExportOptions;
 use super::pdfa::{
     create_mark_info, create_srgb_icc_profile, create_srgb_output_intent,
@@ -300,7 +301,8 @@ impl PdfDocumentWriter {
                 continue;
             }
             let content = renderer.render_page(page_info);
-            content_streams.push((page_info, content));
+            let bitmaps = renderer.take_bitmaps();
+            content_streams.push((page_info, content, bitmaps));
         }
 
         // Allocate font objects
@@ -397,7 +399,7 @@ impl PdfDocumentWriter {
         }
 
         // Write page and content objects
-        for (i, (page_info, content)) in content_streams.into_iter().enumerate() {
+        for (i, (page_info, content, bitmaps)) in content_streams.into_iter().enumerate() {
             let page_ref = page_refs[i];
             let content_ref = content_refs[i];
 
@@ -431,6 +433,29 @@ impl PdfDocumentWriter {
                 resources.insert("Font", PdfObject::Dictionary(font_dict));
             }
 
+            // Add bitmaps as image XObjects, their alpha as a soft mask
+            if !bitmaps.is_empty() {
+                let mut xobject_dict = PdfDictionary::new();
+                for (name, bitmap) in bitmaps {
+                    let (rgb, alpha): (Vec<[u8; 3]>, Vec<u8>) = bitmap
+                        .rgba
+                        .chunks_exact(4)
+                        .map(|pixel| ([pixel[0], pixel[1], pixel[2]], pixel[3]))
+                        .unzip();
+                    let mask_ref = pdf.allocate_object();
+                    pdf.write_stream_object(
+                        mask_ref,
+                        create_soft_mask(alpha, bitmap.pixel_width, bitmap.pixel_height),
+                    )?;
+                    let image_ref = pdf.allocate_object();
+                    let image = ImageData::from_raw_rgb(rgb.concat(), bitmap.pixel_width, bitmap.pixel_height)
+                        .with_soft_mask(mask_ref);
+                    pdf.write_stream_object(image_ref, image.to_xobject())?;
+                    xobject_dict.insert(name, PdfObject::Reference(image_ref, 0));
+                }
+                resources.insert("XObject", PdfObject::Dictionary(xobject_dict));
+            }
+
             // Add ProcSet
             resources.insert(
                 "ProcSet",
@@ -478,6 +503,7 @@ mod tests {
             bold: false,
             italic: false,
             color: RgbColor::black(),
+            rotation: 0.0,
         }));
         page
     }
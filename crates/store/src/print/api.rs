@@ -0,0 +1,23 @@
+//! Printing Public API
+
+use super::backend::{PrintBackend, Result};
+
+/// Get the print backend for this platform
+#[cfg(unix)]
+pub fn default_backend() -> Result<Box<dyn PrintBackend>> {
+    Ok(Box::new(super::cups::CupsBackend::new()?))
+}
+
+/// Get the print backend for this platform
+#[cfg(windows)]
+pub fn default_backend() -> Result<Box<dyn PrintBackend>> {
+    Ok(Box::new(super::winspool::WinspoolBackend::new()?))
+}
+
+/// Get the print backend for this platform
+#[cfg(not(any(unix, windows)))]
+pub fn default_backend() -> Result<Box<dyn PrintBackend>> {
+    Err(super::backend::PrintError::Unavailable(
+        "Printing is not supported on this platform".to_string(),
+    ))
+}
@@ -0,0 +1,118 @@
+//! Print backend abstraction
+
+use super::settings::PrintSettings;
+use crate::raster::PageRasterizer;
+use render_model::PageRender;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Error type for printing
+#[derive(Debug, Error)]
+pub enum PrintError {
+    /// The platform's print system couldn't be loaded
+    #[error("Print system unavailable: {0}")]
+    Unavailable(String),
+
+    /// The printer doesn't exist or can't be opened
+    #[error("Printer not found: {0}")]
+    PrinterNotFound(String),
+
+    /// The job has nothing to print
+    #[error("No pages to print")]
+    NoPages,
+
+    /// The print system rejected the job or a request about it
+    #[error("Print system error: {0}")]
+    System(String),
+
+    /// The job isn't known to the printer
+    #[error("Print job {0} not found")]
+    JobNotFound(u32),
+
+    /// Generating the print data failed
+    #[error("Failed to prepare print data: {0}")]
+    Render(String),
+
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Result type for printing
+pub type Result<T> = std::result::Result<T, PrintError>;
+
+/// A submitted print job
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrintJob {
+    /// Printer the job was sent to
+    pub printer: String,
+    /// Job ID assigned by the print system
+    pub id: u32,
+}
+
+/// Stage of a print job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PrintJobState {
+    /// Waiting in the queue
+    Pending,
+    /// Held until released
+    Held,
+    /// Printing
+    Processing,
+    /// Stopped by a printer problem (out of paper, jam, offline)
+    Stopped,
+    /// Canceled by a user
+    Canceled,
+    /// Failed
+    Aborted,
+    /// Printed
+    Completed,
+}
+
+impl PrintJobState {
+    /// Check if the job won't change state again
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Canceled | Self::Aborted | Self::Completed)
+    }
+}
+
+/// Status of a print job
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrintJobStatus {
+    /// The job
+    pub job: PrintJob,
+    /// Current stage
+    pub state: PrintJobState,
+    /// Pages printed so far, if the print system reports it
+    pub pages_printed: Option<u32>,
+    /// Explanation from the print system, e.g. why a job stopped
+    pub message: Option<String>,
+}
+
+/// A platform print system
+pub trait PrintBackend: Send + Sync {
+    /// Name of the print system
+    fn name(&self) -> &'static str;
+
+    /// Send pages to a printer
+    ///
+    /// Backends that print bitmaps draw the pages with `rasterizer`, which
+    /// should have the document's fonts loaded and its pictures set. Backends
+    /// that print PDF draw pictures from the rasterizer's image store.
+    fn submit(
+        &self,
+        printer: &str,
+        pages: &[PageRender],
+        settings: &PrintSettings,
+        rasterizer: &PageRasterizer,
+    ) -> Result<PrintJob>;
+
+    /// Get the status of a submitted job
+    fn status(&self, job: &PrintJob) -> Result<PrintJobStatus>;
+
+    /// Cancel a submitted job
+    fn cancel(&self, job: &PrintJob) -> Result<()>;
+}
@@ -0,0 +1,305 @@
+//! CUPS print backend (macOS, Linux)
+//!
+//! libcups is loaded at runtime, so the application still starts on systems
+//! without it and reports printing as unavailable instead. Jobs are sent as
+//! PDF; CUPS filters convert them for the printer.
+
+use super::backend::{PrintBackend, PrintError, PrintJob, PrintJobState, PrintJobStatus, Result};
+use super::settings::{ColorMode, DuplexMode, PageScaling, PrintSettings};
+use crate::pdf::{export_pdf_drawing_images, PdfExportOptions};
+use crate::raster::PageRasterizer;
+use libloading::Library;
+use render_model::PageRender;
+use std::ffi::{c_char, c_int, c_long, CStr, CString};
+use std::path::PathBuf;
+use std::ptr;
+
+/// Library names to try, most specific first
+#[cfg(target_os = "macos")]
+const LIBRARY_NAMES: &[&str] = &["libcups.2.dylib", "/usr/lib/libcups.2.dylib"];
+#[cfg(not(target_os = "macos"))]
+const LIBRARY_NAMES: &[&str] = &["libcups.so.2", "libcups.so"];
+
+/// `CUPS_WHICHJOBS_ALL`
+const WHICH_JOBS_ALL: c_int = -1;
+
+/// `cups_option_t`
+#[repr(C)]
+struct CupsOption {
+    name: *mut c_char,
+    value: *mut c_char,
+}
+
+/// `cups_job_t`
+#[repr(C)]
+struct CupsJob {
+    id: c_int,
+    dest: *mut c_char,
+    title: *mut c_char,
+    user: *mut c_char,
+    format: *mut c_char,
+    state: c_int,
+    size: c_int,
+    priority: c_int,
+    completed_time: c_long,
+    creation_time: c_long,
+    processing_time: c_long,
+}
+
+type AddOptionFn =
+    unsafe extern "C" fn(*const c_char, *const c_char, c_int, *mut *mut CupsOption) -> c_int;
+type FreeOptionsFn = unsafe extern "C" fn(c_int, *mut CupsOption);
+type PrintFileFn = unsafe extern "C" fn(
+    *const c_char,
+    *const c_char,
+    *const c_char,
+    c_int,
+    *mut CupsOption,
+) -> c_int;
+type GetJobsFn = unsafe extern "C" fn(*mut *mut CupsJob, *const c_char, c_int, c_int) -> c_int;
+type FreeJobsFn = unsafe extern "C" fn(c_int, *mut CupsJob);
+type CancelJobFn = unsafe extern "C" fn(*const c_char, c_int) -> c_int;
+type LastErrorStringFn = unsafe extern "C" fn() -> *const c_char;
+
+/// Print backend using the CUPS client library
+pub struct CupsBackend {
+    library: Library,
+}
+
+impl CupsBackend {
+    /// Load libcups
+    pub fn new() -> Result<Self> {
+        let mut last_error = None;
+        for name in LIBRARY_NAMES {
+            // SAFETY: libcups has no initialization routines with
+            // preconditions
+            match unsafe { Library::new(name) } {
+                Ok(library) => return Ok(Self { library }),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(PrintError::Unavailable(format!(
+            "libcups could not be loaded: {}",
+            last_error.map(|e| e.to_string()).unwrap_or_default()
+        )))
+    }
+
+    /// Look up a libcups function
+    fn function<T: Copy>(&self, name: &[u8]) -> Result<T> {
+        // SAFETY: callers pick `T` to match the C declaration of `name`
+        unsafe { self.library.get::<T>(name) }
+            .map(|symbol| *symbol)
+            .map_err(|e| PrintError::Unavailable(e.to_string()))
+    }
+
+    /// The message for the last failed CUPS request
+    fn last_error(&self) -> String {
+        let message = self
+            .function::<LastErrorStringFn>(b"cupsLastErrorString\0")
+            // SAFETY: CUPS returns a NUL-terminated string or null
+            .map(|last_error| unsafe { last_error() })
+            .ok()
+            .filter(|message| !message.is_null())
+            .map(|message| {
+                unsafe { CStr::from_ptr(message) }
+                    .to_string_lossy()
+                    .into_owned()
+            });
+        message.unwrap_or_else(|| "unknown error".to_string())
+    }
+}
+
+impl PrintBackend for CupsBackend {
+    fn name(&self) -> &'static str {
+        "CUPS"
+    }
+
+    fn submit(
+        &self,
+        printer: &str,
+        pages: &[PageRender],
+        settings: &PrintSettings,
+        rasterizer: &PageRasterizer,
+    ) -> Result<PrintJob> {
+        if pages.is_empty() {
+            return Err(PrintError::NoPages);
+        }
+        let add_option: AddOptionFn = self.function(b"cupsAddOption\0")?;
+        let free_options: FreeOptionsFn = self.function(b"cupsFreeOptions\0")?;
+        let print_file: PrintFileFn = self.function(b"cupsPrintFile\0")?;
+
+        let pdf_path =
+            std::env::temp_dir().join(format!("go-word-print-{}.pdf", uuid::Uuid::new_v4()));
+        let c_printer = c_string(printer)?;
+        let c_path = c_string(&pdf_path.to_string_lossy())?;
+        let c_title = c_string(&settings.title)?;
+        let c_options = cups_options(settings)
+            .into_iter()
+            .map(|(name, value)| Ok((c_string(name)?, c_string(&value)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut options = CupsOptions {
+            count: 0,
+            options: ptr::null_mut(),
+            free: free_options,
+        };
+        for (name, value) in &c_options {
+            // SAFETY: CUPS copies the strings into the options array it owns
+            options.count = unsafe {
+                add_option(
+                    name.as_ptr(),
+                    value.as_ptr(),
+                    options.count,
+                    &mut options.options,
+                )
+            };
+        }
+
+        // Removed on every return; CUPS has sent the file by the time
+        // cupsPrintFile returns
+        let _pdf_file = TempFile(pdf_path.clone());
+        let pdf_options = PdfExportOptions::new()
+            .with_title(&settings.title)
+            .with_imposition(settings.imposition);
+        export_pdf_drawing_images(pages, rasterizer.images(), &pdf_path, pdf_options)
+            .map_err(|e| PrintError::Render(e.to_string()))?;
+
+        // SAFETY: all strings are NUL-terminated and the options array came
+        // from cupsAddOption
+        let job_id = unsafe {
+            print_file(
+                c_printer.as_ptr(),
+                c_path.as_ptr(),
+                c_title.as_ptr(),
+                options.count,
+                options.options,
+            )
+        };
+
+        if job_id <= 0 {
+            return Err(PrintError::System(self.last_error()));
+        }
+        Ok(PrintJob {
+            printer: printer.to_string(),
+            id: job_id as u32,
+        })
+    }
+
+    fn status(&self, job: &PrintJob) -> Result<PrintJobStatus> {
+        let get_jobs: GetJobsFn = self.function(b"cupsGetJobs\0")?;
+        let free_jobs: FreeJobsFn = self.function(b"cupsFreeJobs\0")?;
+        let c_printer = c_string(&job.printer)?;
+
+        let mut jobs: *mut CupsJob = ptr::null_mut();
+        // SAFETY: CUPS allocates `count` jobs, freed below
+        let count = unsafe { get_jobs(&mut jobs, c_printer.as_ptr(), 0, WHICH_JOBS_ALL) };
+        if count < 0 {
+            return Err(PrintError::System(self.last_error()));
+        }
+        let state = if jobs.is_null() {
+            None
+        } else {
+            // SAFETY: `jobs` points to `count` initialized entries
+            let entries = unsafe { std::slice::from_raw_parts(jobs, count as usize) };
+            let state = entries
+                .iter()
+                .find(|entry| entry.id as u32 == job.id)
+                .map(|entry| job_state(entry.state));
+            unsafe { free_jobs(count, jobs) };
+            state
+        };
+
+        let state = state.ok_or(PrintError::JobNotFound(job.id))?;
+        Ok(PrintJobStatus {
+            job: job.clone(),
+            state,
+            pages_printed: None,
+            message: None,
+        })
+    }
+
+    fn cancel(&self, job: &PrintJob) -> Result<()> {
+        let cancel_job: CancelJobFn = self.function(b"cupsCancelJob\0")?;
+        let c_printer = c_string(&job.printer)?;
+        // SAFETY: the printer name is NUL-terminated
+        if unsafe { cancel_job(c_printer.as_ptr(), job.id as c_int) } == 0 {
+            return Err(PrintError::System(self.last_error()));
+        }
+        Ok(())
+    }
+}
+
+/// Options array built with `cupsAddOption`, freed when dropped
+struct CupsOptions {
+    count: c_int,
+    options: *mut CupsOption,
+    free: FreeOptionsFn,
+}
+
+impl Drop for CupsOptions {
+    fn drop(&mut self) {
+        // SAFETY: the array came from cupsAddOption and is freed only here
+        unsafe { (self.free)(self.count, self.options) };
+    }
+}
+
+/// A temporary file removed when dropped
+struct TempFile(PathBuf);
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn c_string(value: &str) -> Result<CString> {
+    CString::new(value).map_err(|_| PrintError::System(format!("Invalid string: {:?}", value)))
+}
+
+/// CUPS job options for print settings
+pub(crate) fn cups_options(settings: &PrintSettings) -> Vec<(&'static str, String)> {
+    let mut options = Vec::new();
+    if settings.copies > 1 {
+        options.push(("copies", settings.copies.to_string()));
+        options.push(("collate", settings.collate.to_string()));
+    }
+    let sides = match settings.duplex {
+        DuplexMode::Simplex => "one-sided",
+        DuplexMode::LongEdge => "two-sided-long-edge",
+        DuplexMode::ShortEdge => "two-sided-short-edge",
+    };
+    options.push(("sides", sides.to_string()));
+    if let Some(tray) = &settings.paper_tray {
+        options.push(("media-source", tray.clone()));
+    }
+    let color = match settings.color_mode {
+        ColorMode::Color => "color",
+        ColorMode::Monochrome => "monochrome",
+    };
+    options.push(("print-color-mode", color.to_string()));
+    match settings.scaling {
+        PageScaling::ActualSize => options.push(("print-scaling", "none".to_string())),
+        PageScaling::FitToPaper => {
+            options.push(("print-scaling", "fit".to_string()));
+            options.push(("fit-to-page", "true".to_string()));
+        }
+        PageScaling::Percent { percent } => {
+            options.push(("print-scaling", "none".to_string()));
+            options.push(("natural-scaling", percent.to_string()));
+        }
+    }
+    options
+}
+
+/// Map an IPP job state (`ipp_jstate_t`)
+pub(crate) fn job_state(state: c_int) -> PrintJobState {
+    match state {
+        4 => PrintJobState::Held,
+        5 => PrintJobState::Processing,
+        6 => PrintJobState::Stopped,
+        7 => PrintJobState::Canceled,
+        8 => PrintJobState::Aborted,
+        9 => PrintJobState::Completed,
+        _ => PrintJobState::Pending,
+    }
+}
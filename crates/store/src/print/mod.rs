@@ -0,0 +1,32 @@
+//! Printing Module
+//!
+//! This module sends pages to the platform print system with per-job
+//! options (copies, collation, duplex, paper tray, color, scaling) and
+//! reports job status.
+//!
+//! # Architecture
+//!
+//! - `settings`: Per-job print settings
+//! - `backend`: Print backend trait, jobs, and job status
+//! - `cups`: CUPS backend (macOS, Linux), sends PDF
+//! - `winspool`: Windows spooler backend, draws rasterized pages with GDI
+//! - `api`: Public API for picking the platform backend
+
+mod api;
+mod backend;
+#[cfg(unix)]
+mod cups;
+mod settings;
+#[cfg(windows)]
+mod winspool;
+
+pub use api::*;
+pub use backend::*;
+#[cfg(unix)]
+pub use cups::CupsBackend;
+pub use settings::*;
+#[cfg(windows)]
+pub use winspool::WinspoolBackend;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,142 @@
+//! Per-job print settings
+
+use crate::pdf::Imposition;
+use serde::{Deserialize, Serialize};
+
+/// Double-sided printing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DuplexMode {
+    /// Print on one side of the paper
+    #[default]
+    Simplex,
+    /// Print on both sides, flipping on the long edge (portrait books)
+    LongEdge,
+    /// Print on both sides, flipping on the short edge (landscape pads)
+    ShortEdge,
+}
+
+/// Color or grayscale output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ColorMode {
+    /// Print in color
+    #[default]
+    Color,
+    /// Print in grayscale
+    Monochrome,
+}
+
+/// How pages are sized on the paper
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum PageScaling {
+    /// Print at actual size
+    #[default]
+    ActualSize,
+    /// Shrink or enlarge pages to fit the printable area
+    FitToPaper,
+    /// Print at a percentage of actual size
+    Percent { percent: u32 },
+}
+
+/// Options for one print job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrintSettings {
+    /// Job title shown in the printer queue
+    pub title: String,
+    /// Number of copies
+    pub copies: u32,
+    /// Print complete copies one after another instead of grouping each page
+    pub collate: bool,
+    /// Double-sided printing
+    pub duplex: DuplexMode,
+    /// Paper tray (input slot) name; `None` uses the printer's default
+    pub paper_tray: Option<String>,
+    /// Color or grayscale output
+    pub color_mode: ColorMode,
+    /// How pages are sized on the paper
+    pub scaling: PageScaling,
+    /// Pages per sheet, booklet order, and paper size
+    pub imposition: Imposition,
+    /// Highest resolution pages are rasterized at, for backends that send
+    /// bitmaps
+    pub max_dpi: u32,
+}
+
+impl Default for PrintSettings {
+    fn default() -> Self {
+        Self {
+            title: "Document".to_string(),
+            copies: 1,
+            collate: true,
+            duplex: DuplexMode::default(),
+            paper_tray: None,
+            color_mode: ColorMode::default(),
+            scaling: PageScaling::default(),
+            imposition: Imposition::default(),
+            max_dpi: 300,
+        }
+    }
+}
+
+impl PrintSettings {
+    /// Create settings with defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the job title
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Set the number of copies
+    pub fn with_copies(mut self, copies: u32) -> Self {
+        self.copies = copies.max(1);
+        self
+    }
+
+    /// Set whether copies are collated
+    pub fn with_collate(mut self, collate: bool) -> Self {
+        self.collate = collate;
+        self
+    }
+
+    /// Set double-sided printing
+    pub fn with_duplex(mut self, duplex: DuplexMode) -> Self {
+        self.duplex = duplex;
+        self
+    }
+
+    /// Set the paper tray
+    pub fn with_paper_tray(mut self, tray: impl Into<String>) -> Self {
+        self.paper_tray = Some(tray.into());
+        self
+    }
+
+    /// Set color or grayscale output
+    pub fn with_color_mode(mut self, color_mode: ColorMode) -> Self {
+        self.color_mode = color_mode;
+        self
+    }
+
+    /// Set page scaling
+    pub fn with_scaling(mut self, scaling: PageScaling) -> Self {
+        self.scaling = scaling;
+        self
+    }
+
+    /// Set how pages are arranged on sheets
+    pub fn with_imposition(mut self, imposition: Imposition) -> Self {
+        self.imposition = imposition;
+        self
+    }
+
+    /// Set the highest raster resolution
+    pub fn with_max_dpi(mut self, max_dpi: u32) -> Self {
+        self.max_dpi = max_dpi;
+        self
+    }
+}
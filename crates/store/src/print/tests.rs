@@ -0,0 +1,76 @@
+//! Tests for printing
+
+use super::*;
+
+#[test]
+fn test_settings_defaults() {
+    let settings = PrintSettings::new();
+    assert_eq!(settings.copies, 1);
+    assert!(settings.collate);
+    assert_eq!(settings.duplex, DuplexMode::Simplex);
+    assert_eq!(settings.color_mode, ColorMode::Color);
+    assert_eq!(settings.scaling, PageScaling::ActualSize);
+    assert!(settings.paper_tray.is_none());
+}
+
+#[test]
+fn test_settings_builders() {
+    let settings = PrintSettings::new()
+        .with_title("Report")
+        .with_copies(0)
+        .with_duplex(DuplexMode::LongEdge)
+        .with_paper_tray("tray-2")
+        .with_color_mode(ColorMode::Monochrome)
+        .with_scaling(PageScaling::Percent { percent: 50 });
+    assert_eq!(settings.title, "Report");
+    assert_eq!(settings.copies, 1);
+    assert_eq!(settings.paper_tray.as_deref(), Some("tray-2"));
+    assert_eq!(settings.scaling, PageScaling::Percent { percent: 50 });
+}
+
+#[test]
+fn test_page_scaling_serde() {
+    let json = serde_json::to_string(&PageScaling::Percent { percent: 80 }).unwrap();
+    assert_eq!(json, r#"{"type":"percent","percent":80}"#);
+    let scaling: PageScaling = serde_json::from_str(r#"{"type":"fitToPaper"}"#).unwrap();
+    assert_eq!(scaling, PageScaling::FitToPaper);
+}
+
+#[test]
+fn test_job_state_finished() {
+    assert!(PrintJobState::Completed.is_finished());
+    assert!(PrintJobState::Canceled.is_finished());
+    assert!(!PrintJobState::Processing.is_finished());
+    assert!(!PrintJobState::Stopped.is_finished());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cups_options() {
+    let settings = PrintSettings::new()
+        .with_copies(3)
+        .with_collate(false)
+        .with_duplex(DuplexMode::ShortEdge)
+        .with_paper_tray("manual")
+        .with_color_mode(ColorMode::Monochrome)
+        .with_scaling(PageScaling::FitToPaper);
+    let options = super::cups::cups_options(&settings);
+    let get = |name: &str| options.iter().find(|(n, _)| *n == name).map(|(_, v)| v.as_str());
+    assert_eq!(get("copies"), Some("3"));
+    assert_eq!(get("collate"), Some("false"));
+    assert_eq!(get("sides"), Some("two-sided-short-edge"));
+    assert_eq!(get("media-source"), Some("manual"));
+    assert_eq!(get("print-color-mode"), Some("monochrome"));
+    assert_eq!(get("print-scaling"), Some("fit"));
+
+    let options = super::cups::cups_options(&PrintSettings::new());
+    assert!(options.iter().all(|(name, _)| *name != "copies"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cups_job_state() {
+    assert_eq!(super::cups::job_state(3), PrintJobState::Pending);
+    assert_eq!(super::cups::job_state(5), PrintJobState::Processing);
+    assert_eq!(super::cups::job_state(9), PrintJobState::Completed);
+}
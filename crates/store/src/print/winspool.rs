@@ -0,0 +1,521 @@
+//! Windows print spooler backend
+//!
+//! Sheets of imposed pages are rasterized and drawn into a printer device context with GDI;
+//! job options go into the printer's DEVMODE, so the driver applies duplex,
+//! trays, color, and collation itself.
+
+use super::backend::{PrintBackend, PrintError, PrintJob, PrintJobState, PrintJobStatus, Result};
+use super::settings::{ColorMode, DuplexMode, PageScaling, PrintSettings};
+use crate::pdf::Sheet;
+use crate::raster::{PageRasterizer, RasterOptions, POINTS_PER_INCH};
+use render_model::PageRender;
+use std::ffi::c_void;
+use std::ptr;
+
+type Handle = *mut c_void;
+
+/// `DEVMODEW`
+#[repr(C)]
+struct DevMode {
+    device_name: [u16; 32],
+    spec_version: u16,
+    driver_version: u16,
+    size: u16,
+    driver_extra: u16,
+    fields: u32,
+    orientation: i16,
+    paper_size: i16,
+    paper_length: i16,
+    paper_width: i16,
+    scale: i16,
+    copies: i16,
+    default_source: i16,
+    print_quality: i16,
+    color: i16,
+    duplex: i16,
+    y_resolution: i16,
+    tt_option: i16,
+    collate: i16,
+    form_name: [u16; 32],
+    log_pixels: u16,
+    bits_per_pel: u32,
+    pels_width: u32,
+    pels_height: u32,
+    display_flags: u32,
+    display_frequency: u32,
+    icm_method: u32,
+    icm_intent: u32,
+    media_type: u32,
+    dither_type: u32,
+    reserved1: u32,
+    reserved2: u32,
+    panning_width: u32,
+    panning_height: u32,
+}
+
+/// `DOCINFOW`
+#[repr(C)]
+struct DocInfo {
+    size: i32,
+    doc_name: *const u16,
+    output: *const u16,
+    datatype: *const u16,
+    flags: u32,
+}
+
+/// `BITMAPINFO` with a `BITMAPINFOHEADER`
+#[repr(C)]
+struct BitmapInfo {
+    size: u32,
+    width: i32,
+    height: i32,
+    planes: u16,
+    bit_count: u16,
+    compression: u32,
+    size_image: u32,
+    x_pels_per_meter: i32,
+    y_pels_per_meter: i32,
+    colors_used: u32,
+    colors_important: u32,
+    colors: [u32; 1],
+}
+
+/// `JOB_INFO_1W`
+#[repr(C)]
+struct JobInfo1 {
+    job_id: u32,
+    printer_name: *mut u16,
+    machine_name: *mut u16,
+    user_name: *mut u16,
+    document: *mut u16,
+    datatype: *mut u16,
+    status_text: *mut u16,
+    status: u32,
+    priority: u32,
+    position: u32,
+    total_pages: u32,
+    pages_printed: u32,
+    submitted: [u16; 8],
+}
+
+#[link(name = "winspool")]
+extern "system" {
+    fn OpenPrinterW(name: *const u16, printer: *mut Handle, defaults: *mut c_void) -> i32;
+    fn ClosePrinter(printer: Handle) -> i32;
+    fn DocumentPropertiesW(
+        window: Handle,
+        printer: Handle,
+        device_name: *const u16,
+        output: *mut DevMode,
+        input: *const DevMode,
+        mode: u32,
+    ) -> i32;
+    fn DeviceCapabilitiesW(
+        device: *const u16,
+        port: *const u16,
+        capability: u16,
+        output: *mut u16,
+        dev_mode: *const DevMode,
+    ) -> i32;
+    fn GetJobW(printer: Handle, job_id: u32, level: u32, job: *mut u8, size: u32, needed: *mut u32) -> i32;
+    fn SetJobW(printer: Handle, job_id: u32, level: u32, job: *mut u8, command: u32) -> i32;
+}
+
+#[link(name = "gdi32")]
+extern "system" {
+    fn CreateDCW(driver: *const u16, device: *const u16, port: *const u16, dev_mode: *const DevMode) -> Handle;
+    fn DeleteDC(dc: Handle) -> i32;
+    fn StartDocW(dc: Handle, info: *const DocInfo) -> i32;
+    fn EndDoc(dc: Handle) -> i32;
+    fn AbortDoc(dc: Handle) -> i32;
+    fn StartPage(dc: Handle) -> i32;
+    fn EndPage(dc: Handle) -> i32;
+    fn GetDeviceCaps(dc: Handle, index: i32) -> i32;
+    fn SetStretchBltMode(dc: Handle, mode: i32) -> i32;
+    #[allow(clippy::too_many_arguments)]
+    fn StretchDIBits(
+        dc: Handle,
+        x_dest: i32,
+        y_dest: i32,
+        dest_width: i32,
+        dest_height: i32,
+        x_src: i32,
+        y_src: i32,
+        src_width: i32,
+        src_height: i32,
+        bits: *const c_void,
+        info: *const BitmapInfo,
+        usage: u32,
+        rop: u32,
+    ) -> i32;
+}
+
+const DM_OUT_BUFFER: u32 = 2;
+const DM_IN_BUFFER: u32 = 8;
+const DM_SCALE: u32 = 0x10;
+const DM_COPIES: u32 = 0x100;
+const DM_DEFAULTSOURCE: u32 = 0x200;
+const DM_COLOR: u32 = 0x800;
+const DM_DUPLEX: u32 = 0x1000;
+const DM_COLLATE: u32 = 0x8000;
+const DC_BINS: u16 = 6;
+const DC_BINNAMES: u16 = 12;
+/// Characters per bin name returned for `DC_BINNAMES`
+const BIN_NAME_LEN: usize = 24;
+
+const HORZRES: i32 = 8;
+const VERTRES: i32 = 10;
+const LOGPIXELSX: i32 = 88;
+const LOGPIXELSY: i32 = 90;
+const PHYSICALOFFSETX: i32 = 112;
+const PHYSICALOFFSETY: i32 = 113;
+const HALFTONE: i32 = 4;
+const DIB_RGB_COLORS: u32 = 0;
+const SRCCOPY: u32 = 0x00CC_0020;
+
+const JOB_CONTROL_DELETE: u32 = 5;
+const JOB_STATUS_PAUSED: u32 = 0x1;
+const JOB_STATUS_ERROR: u32 = 0x2;
+const JOB_STATUS_DELETING: u32 = 0x4;
+const JOB_STATUS_SPOOLING: u32 = 0x8;
+const JOB_STATUS_PRINTING: u32 = 0x10;
+const JOB_STATUS_OFFLINE: u32 = 0x20;
+const JOB_STATUS_PAPEROUT: u32 = 0x40;
+const JOB_STATUS_PRINTED: u32 = 0x80;
+const JOB_STATUS_DELETED: u32 = 0x100;
+const JOB_STATUS_BLOCKED_DEVQ: u32 = 0x200;
+const JOB_STATUS_USER_INTERVENTION: u32 = 0x400;
+const JOB_STATUS_COMPLETE: u32 = 0x1000;
+
+fn wide(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain(Some(0)).collect()
+}
+
+fn from_wide(value: *const u16) -> Option<String> {
+    if value.is_null() {
+        return None;
+    }
+    // SAFETY: the spooler returns NUL-terminated strings
+    let len = (0..).take_while(|&i| unsafe { *value.add(i) } != 0).count();
+    let text = String::from_utf16_lossy(unsafe { std::slice::from_raw_parts(value, len) });
+    (!text.is_empty()).then_some(text)
+}
+
+/// An open printer handle, closed on drop
+struct Printer(Handle);
+
+impl Printer {
+    fn open(name: &[u16]) -> Result<Self> {
+        let mut handle = ptr::null_mut();
+        // SAFETY: `name` is NUL-terminated
+        if unsafe { OpenPrinterW(name.as_ptr(), &mut handle, ptr::null_mut()) } == 0 {
+            let printer = String::from_utf16_lossy(&name[..name.len() - 1]);
+            return Err(PrintError::PrinterNotFound(printer));
+        }
+        Ok(Self(handle))
+    }
+}
+
+impl Drop for Printer {
+    fn drop(&mut self) {
+        // SAFETY: the handle came from OpenPrinterW
+        unsafe { ClosePrinter(self.0) };
+    }
+}
+
+/// A printer device context, deleted on drop
+struct DeviceContext(Handle);
+
+impl Drop for DeviceContext {
+    fn drop(&mut self) {
+        // SAFETY: the handle came from CreateDCW
+        unsafe { DeleteDC(self.0) };
+    }
+}
+
+/// Print backend using the Windows spooler and GDI
+#[derive(Debug, Default)]
+pub struct WinspoolBackend;
+
+impl WinspoolBackend {
+    /// Create the backend
+    pub fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    /// Get the printer's DEVMODE with the job settings applied
+    fn dev_mode(&self, name: &[u16], settings: &PrintSettings) -> Result<Vec<u8>> {
+        let printer = Printer::open(name)?;
+        // SAFETY: a null output asks for the buffer size
+        let size = unsafe { DocumentPropertiesW(ptr::null_mut(), printer.0, name.as_ptr(), ptr::null_mut(), ptr::null(), 0) };
+        if size < std::mem::size_of::<DevMode>() as i32 {
+            return Err(PrintError::System("Printer driver returned no settings".to_string()));
+        }
+        // u64 storage keeps the DEVMODE aligned
+        let mut buffer = vec![0u64; (size as usize).div_ceil(8)];
+        let dev_mode = buffer.as_mut_ptr() as *mut DevMode;
+        // SAFETY: the buffer holds `size` bytes as requested by the driver
+        if unsafe { DocumentPropertiesW(ptr::null_mut(), printer.0, name.as_ptr(), dev_mode, ptr::null(), DM_OUT_BUFFER) } < 0 {
+            return Err(PrintError::System("Failed to read printer settings".to_string()));
+        }
+
+        let tray = settings.paper_tray.as_deref().and_then(|tray| paper_bin(name, tray));
+        // SAFETY: the driver filled in a DEVMODE at the start of the buffer
+        unsafe {
+            let mode = &mut *dev_mode;
+            mode.copies = settings.copies.clamp(1, i16::MAX as u32) as i16;
+            mode.collate = settings.collate as i16;
+            mode.duplex = match settings.duplex {
+                DuplexMode::Simplex => 1,
+                DuplexMode::LongEdge => 2,
+                DuplexMode::ShortEdge => 3,
+            };
+            mode.color = match settings.color_mode {
+                ColorMode::Monochrome => 1,
+                ColorMode::Color => 2,
+            };
+            // Scaling is applied when drawing pages
+            mode.scale = 100;
+            mode.fields |= DM_COPIES | DM_COLLATE | DM_DUPLEX | DM_COLOR | DM_SCALE;
+            if let Some(bin) = tray {
+                mode.default_source = bin;
+                mode.fields |= DM_DEFAULTSOURCE;
+            }
+            // Let the driver validate and merge the changes
+            DocumentPropertiesW(ptr::null_mut(), printer.0, name.as_ptr(), dev_mode, dev_mode, DM_IN_BUFFER | DM_OUT_BUFFER);
+        }
+
+        let bytes = buffer.len() * 8;
+        // SAFETY: reinterpreting the u64 buffer as bytes
+        Ok(unsafe { std::slice::from_raw_parts(buffer.as_ptr() as *const u8, bytes) }.to_vec())
+    }
+
+    /// Draw one sheet of imposed pages into the device context
+    fn print_sheet(
+        &self,
+        dc: &DeviceContext,
+        pages: &[PageRender],
+        sheet: &Sheet,
+        settings: &PrintSettings,
+        rasterizer: &PageRasterizer,
+    ) -> Result<()> {
+        // SAFETY: valid printer DC
+        let (dpi_x, dpi_y, printable_width, printable_height, offset_x, offset_y) = unsafe {
+            (
+                GetDeviceCaps(dc.0, LOGPIXELSX) as f64,
+                GetDeviceCaps(dc.0, LOGPIXELSY) as f64,
+                GetDeviceCaps(dc.0, HORZRES) as f64,
+                GetDeviceCaps(dc.0, VERTRES) as f64,
+                GetDeviceCaps(dc.0, PHYSICALOFFSETX) as f64,
+                GetDeviceCaps(dc.0, PHYSICALOFFSETY) as f64,
+            )
+        };
+        let natural_width = sheet.width / POINTS_PER_INCH * dpi_x;
+        let natural_height = sheet.height / POINTS_PER_INCH * dpi_y;
+        let (factor, x, y) = match settings.scaling {
+            // Device coordinates start at the printable area, not the paper edge
+            PageScaling::ActualSize => (1.0, -offset_x, -offset_y),
+            PageScaling::Percent { percent } => (percent as f64 / 100.0, -offset_x, -offset_y),
+            PageScaling::FitToPaper => {
+                let factor = (printable_width / natural_width).min(printable_height / natural_height);
+                (
+                    factor,
+                    (printable_width - natural_width * factor) / 2.0,
+                    (printable_height - natural_height * factor) / 2.0,
+                )
+            }
+        };
+
+        let raster_dpi = (dpi_x * factor).min(settings.max_dpi as f64).max(1.0);
+        let pixmap = rasterizer
+            .render_sheet_with_options(pages, sheet, &RasterOptions::print(raster_dpi))
+            .map_err(|e| PrintError::Render(e.to_string()))?;
+        let mut bgra = pixmap.data().to_vec();
+        for pixel in bgra.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+        let info = BitmapInfo {
+            size: 40,
+            width: pixmap.width() as i32,
+            // Negative height: rows run top to bottom
+            height: -(pixmap.height() as i32),
+            planes: 1,
+            bit_count: 32,
+            compression: 0,
+            size_image: 0,
+            x_pels_per_meter: 0,
+            y_pels_per_meter: 0,
+            colors_used: 0,
+            colors_important: 0,
+            colors: [0],
+        };
+
+        // SAFETY: valid DC, bitmap, and header describing it
+        unsafe {
+            if StartPage(dc.0) <= 0 {
+                return Err(PrintError::System("StartPage failed".to_string()));
+            }
+            SetStretchBltMode(dc.0, HALFTONE);
+            let drawn = StretchDIBits(
+                dc.0,
+                x.round() as i32,
+                y.round() as i32,
+                (natural_width * factor).round() as i32,
+                (natural_height * factor).round() as i32,
+                0,
+                0,
+                pixmap.width() as i32,
+                pixmap.height() as i32,
+                bgra.as_ptr() as *const c_void,
+                &info,
+                DIB_RGB_COLORS,
+                SRCCOPY,
+            );
+            if EndPage(dc.0) <= 0 || drawn == 0 {
+                return Err(PrintError::System("Failed to draw page".to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PrintBackend for WinspoolBackend {
+    fn name(&self) -> &'static str {
+        "Windows spooler"
+    }
+
+    fn submit(
+        &self,
+        printer: &str,
+        pages: &[PageRender],
+        settings: &PrintSettings,
+        rasterizer: &PageRasterizer,
+    ) -> Result<PrintJob> {
+        if pages.is_empty() {
+            return Err(PrintError::NoPages);
+        }
+        let name = wide(printer);
+        let dev_mode = self.dev_mode(&name, settings)?;
+
+        // SAFETY: `name` is NUL-terminated and `dev_mode` came from the driver
+        let dc = unsafe { CreateDCW(ptr::null(), name.as_ptr(), ptr::null(), dev_mode.as_ptr() as *const DevMode) };
+        if dc.is_null() {
+            return Err(PrintError::PrinterNotFound(printer.to_string()));
+        }
+        let dc = DeviceContext(dc);
+
+        let title = wide(&settings.title);
+        let info = DocInfo {
+            size: std::mem::size_of::<DocInfo>() as i32,
+            doc_name: title.as_ptr(),
+            output: ptr::null(),
+            datatype: ptr::null(),
+            flags: 0,
+        };
+        // SAFETY: valid DC and document info
+        let job_id = unsafe { StartDocW(dc.0, &info) };
+        if job_id <= 0 {
+            return Err(PrintError::System("StartDoc failed".to_string()));
+        }
+
+        let sizes: Vec<_> = pages.iter().map(|page| (page.width, page.height)).collect();
+        for sheet in settings.imposition.sheets(&sizes) {
+            if let Err(e) = self.print_sheet(&dc, pages, &sheet, settings, rasterizer) {
+                // SAFETY: a document was started on this DC
+                unsafe { AbortDoc(dc.0) };
+                return Err(e);
+            }
+        }
+        // SAFETY: a document was started on this DC
+        if unsafe { EndDoc(dc.0) } <= 0 {
+            return Err(PrintError::System("EndDoc failed".to_string()));
+        }
+
+        Ok(PrintJob {
+            printer: printer.to_string(),
+            id: job_id as u32,
+        })
+    }
+
+    fn status(&self, job: &PrintJob) -> Result<PrintJobStatus> {
+        let printer = Printer::open(&wide(&job.printer))?;
+        let mut needed = 0u32;
+        // SAFETY: a null buffer asks for the required size
+        unsafe { GetJobW(printer.0, job.id, 1, ptr::null_mut(), 0, &mut needed) };
+        if needed == 0 {
+            // The spooler drops jobs once they've been printed
+            return Ok(PrintJobStatus {
+                job: job.clone(),
+                state: PrintJobState::Completed,
+                pages_printed: None,
+                message: None,
+            });
+        }
+
+        let mut buffer = vec![0u64; (needed as usize).div_ceil(8)];
+        // SAFETY: the buffer holds `needed` bytes
+        if unsafe { GetJobW(printer.0, job.id, 1, buffer.as_mut_ptr() as *mut u8, needed, &mut needed) } == 0 {
+            return Err(PrintError::JobNotFound(job.id));
+        }
+        // SAFETY: GetJobW filled in a JOB_INFO_1W
+        let info = unsafe { &*(buffer.as_ptr() as *const JobInfo1) };
+        Ok(PrintJobStatus {
+            job: job.clone(),
+            state: job_state(info.status),
+            pages_printed: Some(info.pages_printed),
+            message: from_wide(info.status_text),
+        })
+    }
+
+    fn cancel(&self, job: &PrintJob) -> Result<()> {
+        let printer = Printer::open(&wide(&job.printer))?;
+        // SAFETY: valid printer handle
+        if unsafe { SetJobW(printer.0, job.id, 0, ptr::null_mut(), JOB_CONTROL_DELETE) } == 0 {
+            return Err(PrintError::JobNotFound(job.id));
+        }
+        Ok(())
+    }
+}
+
+/// Find the bin number for a paper tray name, or take a numeric bin
+fn paper_bin(name: &[u16], tray: &str) -> Option<i16> {
+    // SAFETY: null outputs ask for the number of bins
+    let count = unsafe { DeviceCapabilitiesW(name.as_ptr(), ptr::null(), DC_BINS, ptr::null_mut(), ptr::null()) };
+    if count > 0 {
+        let count = count as usize;
+        let mut bins = vec![0u16; count];
+        let mut names = vec![0u16; count * BIN_NAME_LEN];
+        // SAFETY: buffers sized for `count` entries
+        unsafe {
+            DeviceCapabilitiesW(name.as_ptr(), ptr::null(), DC_BINS, bins.as_mut_ptr(), ptr::null());
+            DeviceCapabilitiesW(name.as_ptr(), ptr::null(), DC_BINNAMES, names.as_mut_ptr(), ptr::null());
+        }
+        for (bin, bin_name) in bins.iter().zip(names.chunks(BIN_NAME_LEN)) {
+            let len = bin_name.iter().position(|&c| c == 0).unwrap_or(BIN_NAME_LEN);
+            if String::from_utf16_lossy(&bin_name[..len]).eq_ignore_ascii_case(tray) {
+                return Some(*bin as i16);
+            }
+        }
+    }
+    tray.parse().ok()
+}
+
+/// Map spooler job status bits
+fn job_state(status: u32) -> PrintJobState {
+    if status & (JOB_STATUS_PRINTED | JOB_STATUS_COMPLETE) != 0 {
+        PrintJobState::Completed
+    } else if status & (JOB_STATUS_DELETING | JOB_STATUS_DELETED) != 0 {
+        PrintJobState::Canceled
+    } else if status
+        & (JOB_STATUS_ERROR | JOB_STATUS_OFFLINE | JOB_STATUS_PAPEROUT | JOB_STATUS_BLOCKED_DEVQ | JOB_STATUS_USER_INTERVENTION)
+        != 0
+    {
+        PrintJobState::Stopped
+    } else if status & JOB_STATUS_PAUSED != 0 {
+        PrintJobState::Held
+    } else if status & (JOB_STATUS_PRINTING | JOB_STATUS_SPOOLING) != 0 {
+        PrintJobState::Processing
+    } else {
+        PrintJobState::Pending
+    }
+}
@@ -0,0 +1,35 @@
+//! Raster Rendering Public API
+
+use super::error::Result;
+use super::options::RasterOptions;
+use super::path::Transform;
+use super::pixmap::Pixmap;
+use super::renderer::{fill_ink, PageRasterizer};
+use render_model::{InkRenderInfo, PageRender};
+
+/// Render a page to PNG bytes
+///
+/// Text is drawn as placeholder bars because no fonts are loaded; use a
+/// `PageRasterizer` with fonts for real glyphs.
+pub fn render_page_png(page: &PageRender, options: RasterOptions) -> Result<Vec<u8>> {
+    PageRasterizer::new(options).render_png(page)
+}
+
+/// Render ink onto a transparent bitmap covering its bounds, for output
+/// that can't draw it as vectors
+pub fn render_ink(ink: &InkRenderInfo, dpi: f64) -> Result<Pixmap> {
+    let scale = dpi / 72.0;
+    let bounds = ink.bounds;
+    let width = (bounds.width * scale).ceil().max(1.0) as u32;
+    let height = (bounds.height * scale).ceil().max(1.0) as u32;
+    let mut pixmap = Pixmap::new(width, height)?;
+    let transform = Transform::translate(-bounds.x, -bounds.y).then(&Transform::scale(scale, scale));
+    fill_ink(&mut pixmap, ink, &transform, 1.0);
+    Ok(pixmap)
+}
+
+/// Encode PNG bytes as a data URL, as print preview displays them
+pub fn png_data_url(png: &[u8]) -> String {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    format!("data:image/png;base64,{}", STANDARD.encode(png))
+}
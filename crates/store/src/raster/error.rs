@@ -0,0 +1,19 @@
+//! Raster rendering errors
+
+use std::io;
+use thiserror::Error;
+
+/// Error type for raster rendering
+#[derive(Debug, Error)]
+pub enum RasterError {
+    /// The page or bitmap has no area or is too large to allocate
+    #[error("Invalid bitmap size: {width}x{height}")]
+    InvalidSize { width: u32, height: u32 },
+
+    /// PNG encoding failed
+    #[error("PNG encoding error: {0}")]
+    Encode(#[from] io::Error),
+}
+
+/// Result type for raster rendering
+pub type Result<T> = std::result::Result<T, RasterError>;
@@ -0,0 +1,29 @@
+//! Raster Rendering Module
+//!
+//! This module draws render pages into bitmaps for print preview,
+//! thumbnails, and printers that take images instead of PDF.
+//!
+//! # Architecture
+//!
+//! - `path`: Paths, transforms, and anti-aliased scan conversion
+//! - `pixmap`: RGBA bitmap with compositing and PNG encoding
+//! - `renderer`: Draws render items (text, shapes, tables, text boxes, pictures)
+//! - `options`: Resolution and anti-aliasing settings
+//! - `api`: Public API for page rendering
+
+mod api;
+mod error;
+mod options;
+mod path;
+mod pixmap;
+mod renderer;
+
+pub use api::*;
+pub use error::{RasterError, Result};
+pub use options::*;
+pub use path::{Path, Transform};
+pub use pixmap::Pixmap;
+pub use renderer::PageRasterizer;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,92 @@
+//! Raster rendering options
+
+use render_model::Color;
+
+/// Resolution where one pixel is one point
+pub const POINTS_PER_INCH: f64 = 72.0;
+
+/// Options for rendering pages to bitmaps
+#[derive(Debug, Clone)]
+pub struct RasterOptions {
+    /// Output resolution in dots per inch
+    pub dpi: f64,
+    /// Smooth glyph edges; printers that dither gray badly want it off
+    pub text_antialiasing: bool,
+    /// Page background
+    pub background: Color,
+    /// Largest bitmap side in pixels; higher resolutions are scaled down
+    pub max_dimension: u32,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        Self {
+            dpi: 96.0,
+            text_antialiasing: true,
+            background: Color::WHITE,
+            max_dimension: 10_000,
+        }
+    }
+}
+
+impl RasterOptions {
+    /// Create options with default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Options for sending pages to a printer
+    pub fn print(dpi: f64) -> Self {
+        Self::new().with_dpi(dpi)
+    }
+
+    /// Set the resolution in dots per inch
+    pub fn with_dpi(mut self, dpi: f64) -> Self {
+        self.dpi = dpi;
+        self
+    }
+
+    /// Set the resolution as a zoom factor (1.0 is one pixel per point)
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.dpi = scale * POINTS_PER_INCH;
+        self
+    }
+
+    /// Enable or disable text anti-aliasing
+    pub fn with_text_antialiasing(mut self, enabled: bool) -> Self {
+        self.text_antialiasing = enabled;
+        self
+    }
+
+    /// Set the page background
+    pub fn with_background(mut self, background: Color) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Set the largest bitmap side in pixels
+    pub fn with_max_dimension(mut self, max_dimension: u32) -> Self {
+        self.max_dimension = max_dimension;
+        self
+    }
+
+    /// Pixels per point for a page, after applying `max_dimension`
+    pub fn scale_for(&self, page_width: f64, page_height: f64) -> f64 {
+        let scale = (self.dpi / POINTS_PER_INCH).max(0.0);
+        let largest = page_width.max(page_height) * scale;
+        if largest > self.max_dimension as f64 && largest > 0.0 {
+            scale * self.max_dimension as f64 / largest
+        } else {
+            scale
+        }
+    }
+
+    /// Bitmap size in pixels for a page
+    pub fn pixel_size(&self, page_width: f64, page_height: f64) -> (u32, u32) {
+        let scale = self.scale_for(page_width, page_height);
+        (
+            (page_width * scale).round().max(1.0) as u32,
+            (page_height * scale).round().max(1.0) as u32,
+        )
+    }
+}
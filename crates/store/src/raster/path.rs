@@ -0,0 +1,433 @@
+//! Paths and anti-aliased scan conversion
+//!
+//! Paths are flattened to polylines in pixel space as they're built. Filling
+//! accumulates the signed area each edge covers in every pixel and sums it
+//! along the row, which gives exact coverage for edges without supersampling.
+
+/// Affine transform from page points to pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Transform {
+    /// The identity transform
+    pub const IDENTITY: Transform = Transform { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: 0.0, f: 0.0 };
+
+    /// Uniform scale
+    pub fn scale(sx: f64, sy: f64) -> Self {
+        Self { a: sx, b: 0.0, c: 0.0, d: sy, e: 0.0, f: 0.0 }
+    }
+
+    /// Translation
+    pub fn translate(tx: f64, ty: f64) -> Self {
+        Self { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: tx, f: ty }
+    }
+
+    /// Clockwise rotation (y points down) by `degrees` around a point
+    pub fn rotate_around(degrees: f64, cx: f64, cy: f64) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self::translate(-cx, -cy)
+            .then(&Self { a: cos, b: sin, c: -sin, d: cos, e: 0.0, f: 0.0 })
+            .then(&Self::translate(cx, cy))
+    }
+
+    /// Apply `self` first, then `next`
+    pub fn then(&self, next: &Transform) -> Transform {
+        Transform {
+            a: self.a * next.a + self.b * next.c,
+            b: self.a * next.b + self.b * next.d,
+            c: self.c * next.a + self.d * next.c,
+            d: self.c * next.b + self.d * next.d,
+            e: self.e * next.a + self.f * next.c + next.e,
+            f: self.e * next.b + self.f * next.d + next.f,
+        }
+    }
+
+    /// Transform a point
+    pub fn apply(&self, x: f64, y: f64) -> (f32, f32) {
+        (
+            (self.a * x + self.c * y + self.e) as f32,
+            (self.b * x + self.d * y + self.f) as f32,
+        )
+    }
+
+    /// Average scale factor, for converting widths to pixels
+    pub fn scale_factor(&self) -> f64 {
+        (self.a * self.d - self.b * self.c).abs().sqrt()
+    }
+
+    /// The transform that undoes this one, if it doesn't collapse the plane
+    pub fn invert(&self) -> Option<Transform> {
+        let det = self.a * self.d - self.b * self.c;
+        if det.abs() < f64::EPSILON {
+            return None;
+        }
+        Some(Transform {
+            a: self.d / det,
+            b: -self.b / det,
+            c: -self.c / det,
+            d: self.a / det,
+            e: (self.c * self.f - self.d * self.e) / det,
+            f: (self.b * self.e - self.a * self.f) / det,
+        })
+    }
+}
+
+/// Maximum distance in pixels between a curve and its flattened polyline
+const FLATTEN_TOLERANCE: f32 = 0.2;
+
+/// A path flattened to closed and open polylines in pixel space
+#[derive(Debug, Clone, Default)]
+pub struct Path {
+    contours: Vec<Vec<(f32, f32)>>,
+    closed: Vec<bool>,
+}
+
+impl Path {
+    /// Create an empty path
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new contour
+    pub fn move_to(&mut self, point: (f32, f32)) {
+        self.contours.push(vec![point]);
+        self.closed.push(false);
+    }
+
+    /// Add a straight segment
+    pub fn line_to(&mut self, point: (f32, f32)) {
+        match self.contours.last_mut() {
+            Some(contour) => contour.push(point),
+            None => self.move_to(point),
+        }
+    }
+
+    /// Add a quadratic curve
+    pub fn quad_to(&mut self, control: (f32, f32), to: (f32, f32)) {
+        let from = self.current();
+        let steps = curve_steps(from, to, &[control]);
+        for step in 1..=steps {
+            let t = step as f32 / steps as f32;
+            let mt = 1.0 - t;
+            self.line_to((
+                mt * mt * from.0 + 2.0 * mt * t * control.0 + t * t * to.0,
+                mt * mt * from.1 + 2.0 * mt * t * control.1 + t * t * to.1,
+            ));
+        }
+    }
+
+    /// Add a cubic curve
+    pub fn cubic_to(&mut self, control1: (f32, f32), control2: (f32, f32), to: (f32, f32)) {
+        let from = self.current();
+        let steps = curve_steps(from, to, &[control1, control2]);
+        for step in 1..=steps {
+            let t = step as f32 / steps as f32;
+            let mt = 1.0 - t;
+            let (w0, w1, w2, w3) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+            self.line_to((
+                w0 * from.0 + w1 * control1.0 + w2 * control2.0 + w3 * to.0,
+                w0 * from.1 + w1 * control1.1 + w2 * control2.1 + w3 * to.1,
+            ));
+        }
+    }
+
+    /// Close the current contour
+    pub fn close(&mut self) {
+        if let Some(closed) = self.closed.last_mut() {
+            *closed = true;
+        }
+    }
+
+    /// Add a closed polygon through page-space points
+    pub fn polygon(&mut self, points: &[(f64, f64)], transform: &Transform) {
+        let mut points = points.iter().map(|&(x, y)| transform.apply(x, y));
+        if let Some(first) = points.next() {
+            self.move_to(first);
+            points.for_each(|point| self.line_to(point));
+            self.close();
+        }
+    }
+
+    /// Add a closed rectangle in page space
+    pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, transform: &Transform) {
+        self.polygon(
+            &[(x, y), (x + width, y), (x + width, y + height), (x, y + height)],
+            transform,
+        );
+    }
+
+    /// Add a closed ellipse inscribed in a page-space rectangle
+    pub fn ellipse(&mut self, x: f64, y: f64, width: f64, height: f64, transform: &Transform) {
+        let (rx, ry) = (width / 2.0, height / 2.0);
+        let (cx, cy) = (x + rx, y + ry);
+        let radius_px = rx.max(ry) * transform.scale_factor();
+        let steps = ((radius_px.sqrt() * 8.0) as usize).clamp(16, 256);
+        let points: Vec<(f64, f64)> = (0..steps)
+            .map(|step| {
+                let angle = step as f64 / steps as f64 * std::f64::consts::TAU;
+                (cx + rx * angle.cos(), cy + ry * angle.sin())
+            })
+            .collect();
+        self.polygon(&points, transform);
+    }
+
+    /// Check if the path has no contours
+    pub fn is_empty(&self) -> bool {
+        self.contours.is_empty()
+    }
+
+    fn current(&self) -> (f32, f32) {
+        self.contours
+            .last()
+            .and_then(|contour| contour.last().copied())
+            .unwrap_or((0.0, 0.0))
+    }
+
+    /// Edges of the path, with every contour implicitly closed for filling
+    fn edges(&self) -> impl Iterator<Item = ((f32, f32), (f32, f32))> + '_ {
+        self.contours.iter().flat_map(|contour| {
+            let closing = match (contour.first(), contour.last()) {
+                (Some(&first), Some(&last)) if contour.len() > 2 => Some((last, first)),
+                _ => None,
+            };
+            contour.windows(2).map(|pair| (pair[0], pair[1])).chain(closing)
+        })
+    }
+
+    /// Outline the path with a stroke of `width` pixels
+    ///
+    /// Each segment becomes a quad with the same winding, so overlapping
+    /// pieces merge instead of cancelling. `dashes` alternates on and off
+    /// lengths in pixels; an empty pattern draws a solid stroke.
+    pub fn stroke(&self, width: f32, dashes: &[f32]) -> Path {
+        let half = width.max(0.0) / 2.0;
+        let mut outline = Path::new();
+        for (contour, &closed) in self.contours.iter().zip(&self.closed) {
+            let mut points = contour.clone();
+            if closed && points.len() > 2 {
+                points.push(points[0]);
+            }
+            for (from, to) in dash_segments(&points, dashes) {
+                let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+                let length = (dx * dx + dy * dy).sqrt();
+                if length <= f32::EPSILON {
+                    continue;
+                }
+                // Square caps so joins between segments have no notches
+                let (ux, uy) = (dx / length * half, dy / length * half);
+                let (nx, ny) = (-uy, ux);
+                let start = (from.0 - ux, from.1 - uy);
+                let end = (to.0 + ux, to.1 + uy);
+                outline.move_to((start.0 + nx, start.1 + ny));
+                outline.line_to((end.0 + nx, end.1 + ny));
+                outline.line_to((end.0 - nx, end.1 - ny));
+                outline.line_to((start.0 - nx, start.1 - ny));
+                outline.close();
+            }
+        }
+        outline
+    }
+
+    /// Compute per-pixel coverage of the filled path within a
+    /// `width` x `height` pixel area
+    pub(crate) fn coverage(&self, width: usize, height: usize) -> Option<Coverage> {
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+        for &(x, y) in self.contours.iter().flatten() {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+        if !min_x.is_finite() || !max_x.is_finite() || !min_y.is_finite() || !max_y.is_finite() {
+            return None;
+        }
+
+        // Only rasterize the part of the canvas the path touches
+        let left = (min_x.floor().max(0.0) as usize).min(width);
+        let top = (min_y.floor().max(0.0) as usize).min(height);
+        let right = (max_x.ceil().max(0.0) as usize).min(width);
+        let bottom = (max_y.ceil().max(0.0) as usize).min(height);
+        if left >= right || top >= bottom {
+            return None;
+        }
+
+        let mut accumulator = Accumulator::new(right - left, bottom - top);
+        for (from, to) in self.edges() {
+            accumulator.add_line(
+                (from.0 - left as f32, from.1 - top as f32),
+                (to.0 - left as f32, to.1 - top as f32),
+            );
+        }
+        Some(accumulator.into_coverage(left, top))
+    }
+}
+
+/// Number of line segments to approximate a curve within the tolerance
+fn curve_steps(from: (f32, f32), to: (f32, f32), controls: &[(f32, f32)]) -> usize {
+    // The control polygon's deviation from the chord bounds the curve's
+    let deviation = controls
+        .iter()
+        .map(|&(x, y)| {
+            let (mx, my) = ((from.0 + to.0) / 2.0, (from.1 + to.1) / 2.0);
+            ((x - mx).powi(2) + (y - my).powi(2)).sqrt()
+        })
+        .fold(0.0f32, f32::max);
+    ((deviation / FLATTEN_TOLERANCE).sqrt().ceil() as usize).clamp(1, 64)
+}
+
+/// Split a polyline into the segments drawn by a dash pattern
+fn dash_segments(points: &[(f32, f32)], dashes: &[f32]) -> Vec<((f32, f32), (f32, f32))> {
+    let solid = dashes.is_empty() || dashes.iter().all(|&dash| dash <= 0.0);
+    if solid {
+        return points.windows(2).map(|pair| (pair[0], pair[1])).collect();
+    }
+
+    let mut segments = Vec::new();
+    let mut dash_index = 0;
+    let mut remaining = dashes[0];
+    for pair in points.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        let length = ((to.0 - from.0).powi(2) + (to.1 - from.1).powi(2)).sqrt();
+        let mut position = 0.0;
+        while position < length {
+            let step = remaining.min(length - position);
+            if dash_index % 2 == 0 {
+                let at = |distance: f32| {
+                    let t = distance / length;
+                    (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t)
+                };
+                segments.push((at(position), at(position + step)));
+            }
+            position += step;
+            remaining -= step;
+            if remaining <= f32::EPSILON {
+                dash_index = (dash_index + 1) % dashes.len();
+                remaining = dashes[dash_index].max(0.1);
+            }
+        }
+    }
+    segments
+}
+
+/// Signed area accumulation buffer
+struct Accumulator {
+    width: usize,
+    height: usize,
+    /// Row stride leaves room for contributions just right of the area
+    stride: usize,
+    cells: Vec<f32>,
+}
+
+impl Accumulator {
+    fn new(width: usize, height: usize) -> Self {
+        let stride = width + 2;
+        Self {
+            width,
+            height,
+            stride,
+            cells: vec![0.0; stride * height],
+        }
+    }
+
+    /// Add an edge, in coordinates relative to the area
+    fn add_line(&mut self, from: (f32, f32), to: (f32, f32)) {
+        if (from.1 - to.1).abs() <= f32::EPSILON {
+            return;
+        }
+        let (direction, top, bottom) = if from.1 < to.1 { (1.0, from, to) } else { (-1.0, to, from) };
+        let dxdy = (bottom.0 - top.0) / (bottom.1 - top.1);
+        let y_start = top.1.max(0.0);
+        let y_end = bottom.1.min(self.height as f32);
+        if y_start >= y_end {
+            return;
+        }
+
+        let mut x = top.0 + (y_start - top.1) * dxdy;
+        let mut y = y_start;
+        while y < y_end {
+            let row = y as usize;
+            let row_end = ((row + 1) as f32).min(y_end);
+            let dy = row_end - y;
+            let x_next = x + dxdy * dy;
+            self.add_span(row, x, x_next, dy * direction);
+            x = x_next;
+            y = row_end;
+        }
+    }
+
+    /// Distribute the area of an edge crossing one row between cells
+    fn add_span(&mut self, row: usize, x_from: f32, x_to: f32, delta: f32) {
+        // Edges left of the area cover all of it; edges right of it none
+        let limit = self.width as f32;
+        let x_from = x_from.clamp(0.0, limit);
+        let x_to = x_to.clamp(0.0, limit);
+        let line = row * self.stride;
+        let (x0, x1) = if x_from < x_to { (x_from, x_to) } else { (x_to, x_from) };
+        let x0_floor = x0.floor();
+        let x0_index = x0_floor as usize;
+        let x1_ceil = x1.ceil();
+        let x1_index = x1_ceil as usize;
+
+        if x1_index <= x0_index + 1 {
+            // The edge stays within one pixel column
+            let mid = 0.5 * (x_from + x_to) - x0_floor;
+            self.cells[line + x0_index] += delta - delta * mid;
+            self.cells[line + x0_index + 1] += delta * mid;
+        } else {
+            let inverse_width = (x1 - x0).recip();
+            let x0_fraction = x0 - x0_floor;
+            let first = 0.5 * inverse_width * (1.0 - x0_fraction) * (1.0 - x0_fraction);
+            let x1_fraction = x1 - x1_ceil + 1.0;
+            let last = 0.5 * inverse_width * x1_fraction * x1_fraction;
+            self.cells[line + x0_index] += delta * first;
+            if x1_index == x0_index + 2 {
+                self.cells[line + x0_index + 1] += delta * (1.0 - first - last);
+            } else {
+                let second = inverse_width * (1.5 - x0_fraction);
+                self.cells[line + x0_index + 1] += delta * (second - first);
+                for index in x0_index + 2..x1_index - 1 {
+                    self.cells[line + index] += delta * inverse_width;
+                }
+                let before_last = second + (x1_index - x0_index - 3) as f32 * inverse_width;
+                self.cells[line + x1_index - 1] += delta * (1.0 - before_last - last);
+            }
+            self.cells[line + x1_index] += delta * last;
+        }
+    }
+
+    /// Sum the cells along each row into coverage
+    fn into_coverage(self, left: usize, top: usize) -> Coverage {
+        let mut alpha = Vec::with_capacity(self.width * self.height);
+        for row in self.cells.chunks(self.stride) {
+            let mut sum = 0.0f32;
+            for &cell in &row[..self.width] {
+                sum += cell;
+                alpha.push(sum.abs().min(1.0));
+            }
+        }
+        Coverage {
+            left,
+            top,
+            width: self.width,
+            height: self.height,
+            alpha,
+        }
+    }
+}
+
+/// Coverage of a filled path over part of the canvas
+pub(crate) struct Coverage {
+    pub left: usize,
+    pub top: usize,
+    pub width: usize,
+    pub height: usize,
+    /// Coverage from 0 to 1, row by row
+    pub alpha: Vec<f32>,
+}
@@ -0,0 +1,199 @@
+//! RGBA bitmap and PNG encoding
+
+use super::error::{RasterError, Result};
+use super::path::{Path, Transform};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use render_model::Color;
+use std::io::Write;
+
+/// PNG file signature
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// An RGBA bitmap with straight (not premultiplied) alpha
+#[derive(Debug, Clone)]
+pub struct Pixmap {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+impl Pixmap {
+    /// Create a transparent bitmap
+    pub fn new(width: u32, height: u32) -> Result<Self> {
+        if width == 0 || height == 0 {
+            return Err(RasterError::InvalidSize { width, height });
+        }
+        let len = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .ok_or(RasterError::InvalidSize { width, height })?;
+        Ok(Self {
+            width,
+            height,
+            data: vec![0; len],
+        })
+    }
+
+    /// Width in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// RGBA bytes, row by row
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Get the color of a pixel
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        let pixel = &self.data[index..index + 4];
+        Some(Color::rgba(pixel[0], pixel[1], pixel[2], pixel[3]))
+    }
+
+    /// Fill the whole bitmap with a color
+    pub fn fill(&mut self, color: Color) {
+        for pixel in self.data.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+
+    /// Fill a path, blending with what's already drawn
+    ///
+    /// Without anti-aliasing, pixels are either fully painted or untouched.
+    pub fn fill_path(&mut self, path: &Path, color: Color, antialias: bool) {
+        if color.a == 0 || path.is_empty() {
+            return;
+        }
+        let Some(coverage) = path.coverage(self.width as usize, self.height as usize) else {
+            return;
+        };
+        for row in 0..coverage.height {
+            let line = (coverage.top + row) * self.width as usize + coverage.left;
+            let alphas = &coverage.alpha[row * coverage.width..(row + 1) * coverage.width];
+            for (column, &alpha) in alphas.iter().enumerate() {
+                let alpha = if antialias {
+                    alpha
+                } else if alpha >= 0.5 {
+                    1.0
+                } else {
+                    0.0
+                };
+                if alpha > 0.0 {
+                    self.blend((line + column) * 4, color, alpha);
+                }
+            }
+        }
+    }
+
+    /// Draw an RGBA image, blending with what's already drawn
+    ///
+    /// `transform` maps image pixels to bitmap pixels. Each bitmap pixel
+    /// takes the image pixel under its center.
+    pub fn draw_image(&mut self, rgba: &[u8], width: u32, height: u32, transform: &Transform, opacity: f32) {
+        let Some(inverse) = transform.invert() else {
+            return;
+        };
+        if rgba.len() < width as usize * height as usize * 4 {
+            return;
+        }
+        let corners = [(0.0, 0.0), (width as f64, 0.0), (0.0, height as f64), (width as f64, height as f64)]
+            .map(|(x, y)| transform.apply(x, y));
+        let (mut left, mut top, mut right, mut bottom) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+        for (x, y) in corners {
+            left = left.min(x);
+            top = top.min(y);
+            right = right.max(x);
+            bottom = bottom.max(y);
+        }
+        let left = left.floor().max(0.0) as u32;
+        let top = top.floor().max(0.0) as u32;
+        let right = (right.ceil().max(0.0) as u32).min(self.width);
+        let bottom = (bottom.ceil().max(0.0) as u32).min(self.height);
+
+        for y in top..bottom {
+            for x in left..right {
+                let (u, v) = inverse.apply(x as f64 + 0.5, y as f64 + 0.5);
+                if u < 0.0 || v < 0.0 || u >= width as f32 || v >= height as f32 {
+                    continue;
+                }
+                let source = (v as usize * width as usize + u as usize) * 4;
+                let pixel = &rgba[source..source + 4];
+                if pixel[3] == 0 {
+                    continue;
+                }
+                let color = Color::rgba(pixel[0], pixel[1], pixel[2], pixel[3]);
+                self.blend((y as usize * self.width as usize + x as usize) * 4, color, opacity);
+            }
+        }
+    }
+
+    /// Blend a color over one pixel with source-over compositing
+    fn blend(&mut self, index: usize, color: Color, coverage: f32) {
+        let pixel = &mut self.data[index..index + 4];
+        let source_alpha = color.a as f32 / 255.0 * coverage;
+        let dest_alpha = pixel[3] as f32 / 255.0;
+        let out_alpha = source_alpha + dest_alpha * (1.0 - source_alpha);
+        if out_alpha <= 0.0 {
+            return;
+        }
+        for (channel, source) in pixel[..3].iter_mut().zip([color.r, color.g, color.b]) {
+            let blended = (source as f32 * source_alpha
+                + *channel as f32 * dest_alpha * (1.0 - source_alpha))
+                / out_alpha;
+            *channel = blended.round().clamp(0.0, 255.0) as u8;
+        }
+        pixel[3] = (out_alpha * 255.0).round() as u8;
+    }
+
+    /// Encode the bitmap as a PNG file
+    pub fn encode_png(&self) -> Result<Vec<u8>> {
+        let row_len = self.width as usize * 4;
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        let mut previous = vec![0u8; row_len];
+        let mut filtered = Vec::with_capacity(row_len + 1);
+        for row in self.data.chunks_exact(row_len) {
+            // The "up" filter turns the runs of identical rows that make up
+            // most of a page into zeros
+            filtered.clear();
+            filtered.push(2);
+            filtered.extend(row.iter().zip(&previous).map(|(byte, above)| byte.wrapping_sub(*above)));
+            encoder.write_all(&filtered)?;
+            previous.copy_from_slice(row);
+        }
+        let compressed = encoder.finish()?;
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&self.width.to_be_bytes());
+        header.extend_from_slice(&self.height.to_be_bytes());
+        // 8-bit RGBA, deflate, adaptive filtering, no interlace
+        header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+        let mut png = Vec::with_capacity(compressed.len() + 64);
+        png.extend_from_slice(&PNG_SIGNATURE);
+        write_chunk(&mut png, b"IHDR", &header);
+        write_chunk(&mut png, b"IDAT", &compressed);
+        write_chunk(&mut png, b"IEND", &[]);
+        Ok(png)
+    }
+}
+
+/// Append a PNG chunk with its length and CRC
+fn write_chunk(output: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    output.extend_from_slice(kind);
+    output.extend_from_slice(data);
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(kind);
+    crc.update(data);
+    output.extend_from_slice(&crc.finalize().to_be_bytes());
+}
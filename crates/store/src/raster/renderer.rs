@@ -0,0 +1,835 @@
+//! Draws render pages into bitmaps
+
+use super::error::{RasterError, Result};
+use super::options::RasterOptions;
+use super::path::{Path, Transform};
+use super::pixmap::Pixmap;
+use crate::image_codec;
+use crate::image_store::ImageStore;
+use crate::pdf::Sheet;
+use doc_model::ResourceId;
+use render_model::{
+    Color, DashStyleRender, GlyphRun, InkRenderInfo, LineNumberRenderInfo, PageRender, Rect, RenderItem, RenderGlyph,
+    ShapeFillRender, ShapeRenderInfo, ShapeRenderType, TextBoxFillRender, TextBoxRenderInfo,
+    TextEffects, WatermarkRenderContent, WatermarkRenderInfo,
+};
+use text_engine::{FontId, FontStyle, FontWeight, OutlineSegment, TextShaper};
+
+/// Frames drawn for pictures that aren't in the image store and for
+/// embedded objects, whose previews aren't decoded here
+const IMAGE_PLACEHOLDER_FILL: Color = Color::rgb(235, 235, 235);
+const IMAGE_PLACEHOLDER_STROKE: Color = Color::rgb(190, 190, 190);
+
+/// Renders pages to bitmaps
+///
+/// Text is drawn from glyph outlines of fonts loaded into the rasterizer's
+/// shaper. Runs in fonts that aren't loaded are drawn as gray bars with the
+/// estimated width of each character, which is what small thumbnails need
+/// anyway. Pictures are drawn from the image store set with `set_images`,
+/// or as frames without one, and UI-only items (caret, selection, find
+/// highlights, spelling squiggles) aren't drawn.
+pub struct PageRasterizer {
+    options: RasterOptions,
+    shaper: TextShaper,
+    images: Option<ImageStore>,
+}
+
+impl PageRasterizer {
+    /// Create a rasterizer
+    pub fn new(options: RasterOptions) -> Self {
+        Self {
+            options,
+            shaper: TextShaper::new(),
+            images: None,
+        }
+    }
+
+    /// Get the options
+    pub fn options(&self) -> &RasterOptions {
+        &self.options
+    }
+
+    /// Get mutable options
+    pub fn options_mut(&mut self) -> &mut RasterOptions {
+        &mut self.options
+    }
+
+    /// Get the shaper, e.g. to share a shape cache with layout
+    pub fn shaper_mut(&mut self) -> &mut TextShaper {
+        &mut self.shaper
+    }
+
+    /// Load a font used by glyph runs
+    pub fn load_font(&mut self, font_id: FontId, data: Vec<u8>) -> text_engine::Result<()> {
+        self.shaper.load_font(font_id, data)
+    }
+
+    /// Check if glyph runs in a font are drawn from outlines
+    pub fn has_font(&self, font_id: &FontId) -> bool {
+        self.shaper.has_font(font_id)
+    }
+
+    /// Draw pictures from an image store
+    pub fn set_images(&mut self, images: ImageStore) {
+        self.images = Some(images);
+    }
+
+    /// Get the image store pictures are drawn from
+    pub fn images(&self) -> Option<&ImageStore> {
+        self.images.as_ref()
+    }
+
+    /// Render a page to a bitmap
+    pub fn render(&self, page: &PageRender) -> Result<Pixmap> {
+        self.render_with_options(page, &self.options)
+    }
+
+    /// Render a page with different options, e.g. a printer's resolution
+    pub fn render_with_options(&self, page: &PageRender, options: &RasterOptions) -> Result<Pixmap> {
+        if !(page.width > 0.0 && page.height > 0.0) {
+            return Err(RasterError::InvalidSize {
+                width: page.width.max(0.0) as u32,
+                height: page.height.max(0.0) as u32,
+            });
+        }
+        let (width, height) = options.pixel_size(page.width, page.height);
+        let mut pixmap = Pixmap::new(width, height)?;
+        pixmap.fill(options.background);
+
+        let scale = options.scale_for(page.width, page.height);
+        let mut canvas = Canvas {
+            pixmap: &mut pixmap,
+            shaper: &self.shaper,
+            images: self.images.as_ref(),
+            text_antialiasing: options.text_antialiasing,
+        };
+        canvas.draw_items(&page.items, &Transform::scale(scale, scale), 1.0);
+        Ok(pixmap)
+    }
+
+    /// Render a printer sheet holding imposed pages
+    ///
+    /// `pages` are the pages the sheet's placements index into.
+    pub fn render_sheet_with_options(
+        &self,
+        pages: &[PageRender],
+        sheet: &Sheet,
+        options: &RasterOptions,
+    ) -> Result<Pixmap> {
+        if !(sheet.width > 0.0 && sheet.height > 0.0) {
+            return Err(RasterError::InvalidSize {
+                width: sheet.width.max(0.0) as u32,
+                height: sheet.height.max(0.0) as u32,
+            });
+        }
+        let (width, height) = options.pixel_size(sheet.width, sheet.height);
+        let mut pixmap = Pixmap::new(width, height)?;
+        pixmap.fill(options.background);
+
+        let scale = options.scale_for(sheet.width, sheet.height);
+        let mut canvas = Canvas {
+            pixmap: &mut pixmap,
+            shaper: &self.shaper,
+            images: self.images.as_ref(),
+            text_antialiasing: options.text_antialiasing,
+        };
+        for placement in &sheet.placements {
+            let Some(page) = pages.get(placement.page) else {
+                continue;
+            };
+            let transform = Transform::scale(placement.scale, placement.scale)
+                .then(&Transform::translate(placement.x, placement.y))
+                .then(&Transform::scale(scale, scale));
+            canvas.draw_items(&page.items, &transform, 1.0);
+        }
+        Ok(pixmap)
+    }
+
+    /// Render a page to PNG bytes
+    pub fn render_png(&self, page: &PageRender) -> Result<Vec<u8>> {
+        self.render(page)?.encode_png()
+    }
+}
+
+/// Drawing state for one page
+struct Canvas<'a> {
+    pixmap: &'a mut Pixmap,
+    shaper: &'a TextShaper,
+    images: Option<&'a ImageStore>,
+    text_antialiasing: bool,
+}
+
+impl Canvas<'_> {
+    fn draw_items(&mut self, items: &[RenderItem], transform: &Transform, opacity: f64) {
+        for item in items {
+            self.draw_item(item, transform, opacity);
+        }
+    }
+
+    fn draw_item(&mut self, item: &RenderItem, transform: &Transform, opacity: f64) {
+        match item {
+            RenderItem::GlyphRun(run) => self.draw_glyph_run(run, transform, opacity),
+            RenderItem::Rectangle { bounds, fill, stroke, stroke_width } => {
+                let mut path = Path::new();
+                path.rect(bounds.x, bounds.y, bounds.width, bounds.height, transform);
+                if let Some(fill) = fill {
+                    self.fill(&path, *fill, opacity);
+                }
+                if let Some(stroke) = stroke {
+                    self.stroke(&path, *stroke, *stroke_width, &[], transform, opacity);
+                }
+            }
+            RenderItem::Line { x1, y1, x2, y2, color, width } => {
+                self.draw_line((*x1, *y1), (*x2, *y2), *color, *width, "single", transform, opacity);
+            }
+            RenderItem::TableBorder(border) => {
+                self.draw_line(
+                    (border.x1, border.y1),
+                    (border.x2, border.y2),
+                    border.color,
+                    border.width,
+                    &border.style,
+                    transform,
+                    opacity,
+                );
+            }
+            RenderItem::TableCell(cell) => {
+                if let Some(background) = cell.background {
+                    let mut path = Path::new();
+                    let bounds = cell.bounds;
+                    path.rect(bounds.x, bounds.y, bounds.width, bounds.height, transform);
+                    self.fill(&path, background, opacity);
+                }
+            }
+            RenderItem::Image(image) => {
+                let bounds = image.bounds;
+                let transform = rotated(transform, image.rotation, bounds.x, bounds.y, bounds.width, bounds.height);
+                if !self.draw_picture(&image.resource_id, bounds, &transform, opacity) {
+                    let mut path = Path::new();
+                    path.rect(bounds.x, bounds.y, bounds.width, bounds.height, &transform);
+                    self.fill(&path, IMAGE_PLACEHOLDER_FILL, opacity);
+                    self.stroke(&path, IMAGE_PLACEHOLDER_STROKE, 0.75, &[], &transform, opacity);
+                }
+            }
+            RenderItem::Shape(shape) => self.draw_shape(shape, transform, opacity),
+            RenderItem::TextBox(text_box) => self.draw_text_box(text_box, transform, opacity),
+            RenderItem::LineNumber(number) => self.draw_line_number(number, transform, opacity),
+            RenderItem::Watermark(watermark) => self.draw_watermark(watermark, transform, opacity),
+            RenderItem::Ink(ink) => fill_ink(self.pixmap, ink, transform, opacity),
+            RenderItem::EmbeddedObject(object) => {
+                // Preview pictures aren't decoded here; draw the object's frame
+                let bounds = object.bounds;
+                let mut path = Path::new();
+                path.rect(bounds.x, bounds.y, bounds.width, bounds.height, transform);
+                self.fill(&path, IMAGE_PLACEHOLDER_FILL, opacity);
+                self.stroke(&path, IMAGE_PLACEHOLDER_STROKE, 0.75, &[], transform, opacity);
+            }
+            // Editing aids aren't part of the printed page
+            RenderItem::Caret { .. }
+            | RenderItem::Selection { .. }
+            | RenderItem::RemoteCaret(_)
+            | RenderItem::RemoteSelection(_)
+            | RenderItem::Squiggly(_)
+            | RenderItem::FindHighlight { .. } => {}
+        }
+    }
+
+    fn fill(&mut self, path: &Path, color: Color, opacity: f64) {
+        self.pixmap.fill_path(path, with_opacity(color, opacity), true);
+    }
+
+    /// Stroke a path; `width` and `dashes` are in points
+    fn stroke(
+        &mut self,
+        path: &Path,
+        color: Color,
+        width: f64,
+        dashes: &[f64],
+        transform: &Transform,
+        opacity: f64,
+    ) {
+        if width <= 0.0 {
+            return;
+        }
+        let scale = transform.scale_factor();
+        // Hairlines stay visible at low resolutions
+        let pixels = (width * scale).max(0.5) as f32;
+        let dashes: Vec<f32> = dashes.iter().map(|dash| (dash * width * scale) as f32).collect();
+        self.fill(&path.stroke(pixels, &dashes), color, opacity);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_line(
+        &mut self,
+        from: (f64, f64),
+        to: (f64, f64),
+        color: Color,
+        width: f64,
+        style: &str,
+        transform: &Transform,
+        opacity: f64,
+    ) {
+        let line = |offset_x: f64, offset_y: f64| {
+            let mut path = Path::new();
+            path.move_to(transform.apply(from.0 + offset_x, from.1 + offset_y));
+            path.line_to(transform.apply(to.0 + offset_x, to.1 + offset_y));
+            path
+        };
+        match style {
+            "double" => {
+                // Two thin lines across the border's width
+                let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+                let length = (dx * dx + dy * dy).sqrt().max(f64::EPSILON);
+                let (nx, ny) = (-dy / length * width / 3.0, dx / length * width / 3.0);
+                self.stroke(&line(nx, ny), color, width / 3.0, &[], transform, opacity);
+                self.stroke(&line(-nx, -ny), color, width / 3.0, &[], transform, opacity);
+            }
+            style => {
+                let dashes = line_style_dashes(style);
+                self.stroke(&line(0.0, 0.0), color, width, dashes, transform, opacity);
+            }
+        }
+    }
+
+    fn draw_shape(&mut self, shape: &ShapeRenderInfo, transform: &Transform, opacity: f64) {
+        let bounds = shape.bounds;
+        let opacity = opacity * shape.opacity.clamp(0.0, 1.0);
+        let mut local = Transform::IDENTITY;
+        if shape.flip_horizontal {
+            local = local.then(&Transform { a: -1.0, e: bounds.width, ..Transform::IDENTITY });
+        }
+        if shape.flip_vertical {
+            local = local.then(&Transform { d: -1.0, f: bounds.height, ..Transform::IDENTITY });
+        }
+        let placed = local
+            .then(&Transform::translate(bounds.x, bounds.y))
+            .then(&rotated(transform, shape.rotation, bounds.x, bounds.y, bounds.width, bounds.height));
+
+        let (path, fillable) = shape_path(&shape.shape_type, bounds.width, bounds.height, &placed);
+
+        if let Some(shadow) = &shape.shadow {
+            if fillable {
+                let offset = Transform::translate(shadow.offset_x, shadow.offset_y).then(&placed);
+                let (shadow_path, _) = shape_path(&shape.shape_type, bounds.width, bounds.height, &offset);
+                self.fill(&shadow_path, shadow.color, opacity);
+            }
+        }
+        if fillable {
+            if let Some(color) = shape.fill.as_ref().and_then(fill_color) {
+                self.fill(&path, color, opacity);
+            }
+        }
+        if let Some(stroke) = &shape.stroke {
+            let dashes: &[f64] = match stroke.dash_style {
+                DashStyleRender::Solid => &[],
+                DashStyleRender::Dash => &[4.0, 2.0],
+                DashStyleRender::Dot => &[1.0, 1.0],
+                DashStyleRender::DashDot => &[4.0, 2.0, 1.0, 2.0],
+                DashStyleRender::DashDotDot => &[4.0, 2.0, 1.0, 2.0, 1.0, 2.0],
+            };
+            self.stroke(&path, stroke.color, stroke.width, dashes, &placed, opacity);
+        }
+    }
+
+    fn draw_text_box(&mut self, text_box: &TextBoxRenderInfo, transform: &Transform, opacity: f64) {
+        let bounds = text_box.bounds;
+        let opacity = opacity * text_box.opacity.clamp(0.0, 1.0);
+        let transform = rotated(transform, text_box.rotation, bounds.x, bounds.y, bounds.width, bounds.height);
+
+        let mut path = Path::new();
+        path.rect(bounds.x, bounds.y, bounds.width, bounds.height, &transform);
+        let fill = match &text_box.fill {
+            Some(TextBoxFillRender::Solid { color }) => Some(*color),
+            Some(TextBoxFillRender::Gradient { colors, .. }) => average_color(colors),
+            Some(TextBoxFillRender::None) | None => None,
+        };
+        if let Some(fill) = fill {
+            self.fill(&path, fill, opacity);
+        }
+
+        if let Some(border) = &text_box.border {
+            let (left, top) = (bounds.x, bounds.y);
+            let (right, bottom) = (bounds.x + bounds.width, bounds.y + bounds.height);
+            let edges = [
+                (&border.top, (left, top), (right, top)),
+                (&border.right, (right, top), (right, bottom)),
+                (&border.bottom, (right, bottom), (left, bottom)),
+                (&border.left, (left, bottom), (left, top)),
+            ];
+            for (edge, from, to) in edges {
+                if edge.style != "none" {
+                    self.draw_line(from, to, edge.color, edge.width, &edge.style, &transform, opacity);
+                }
+            }
+        }
+
+        self.draw_items(&text_box.content_items, &transform, opacity);
+    }
+
+    fn draw_glyph_run(&mut self, run: &GlyphRun, transform: &Transform, opacity: f64) {
+        if run.text.is_empty() {
+            return;
+        }
+        let effects = &run.effects;
+        let text = Text {
+            text: &run.text,
+            family: &run.font_family,
+            size: run.font_size,
+            bold: run.bold,
+            italic: run.italic,
+            glyphs: &run.glyphs,
+            effects,
+        };
+
+        let fill = match &effects.fill {
+            Some(fill) => fill_color(fill),
+            None => Some(run.color),
+        };
+
+        if let Some(glow) = &effects.glow {
+            let (path, _) = self.text_path(&text, run.x, run.y, transform);
+            self.stroke(&path, glow.color, glow.radius * 2.0, &[], transform, opacity * 0.5);
+        }
+        if effects.shadow {
+            let offset = run.font_size * 0.06;
+            let (path, _) = self.text_path(&text, run.x + offset, run.y + offset, transform);
+            self.fill_text(&path, Color::rgba(0, 0, 0, 96), opacity);
+        }
+        if effects.emboss || effects.imprint {
+            // Light and dark copies offset in opposite directions
+            let offset = (run.font_size * 0.04).max(0.5);
+            let (light, dark) = if effects.emboss { (-offset, offset) } else { (offset, -offset) };
+            let (path, _) = self.text_path(&text, run.x + light, run.y + light, transform);
+            self.fill_text(&path, Color::rgb(230, 230, 230), opacity);
+            let (path, _) = self.text_path(&text, run.x + dark, run.y + dark, transform);
+            self.fill_text(&path, Color::rgb(110, 110, 110), opacity);
+        }
+
+        let (path, advance) = self.text_path(&text, run.x, run.y, transform);
+        if effects.outline {
+            let width = (run.font_size * 0.04).max(0.25);
+            self.stroke(&path, run.color, width, &[], transform, opacity);
+        } else if let Some(fill) = fill {
+            self.fill_text(&path, fill, opacity);
+        }
+        if let Some(stroke) = &effects.stroke {
+            if let Some(color) = fill_color(&stroke.fill) {
+                self.stroke(&path, color, stroke.width, &[], transform, opacity);
+            }
+        }
+
+        if run.underline {
+            let thickness = (run.font_size * 0.06).max(0.5);
+            let mut underline = Path::new();
+            underline.rect(run.x, run.y + run.font_size * 0.12, advance, thickness, transform);
+            self.fill_text(&underline, fill.unwrap_or(run.color), opacity);
+        }
+    }
+
+    fn draw_line_number(&mut self, number: &LineNumberRenderInfo, transform: &Transform, opacity: f64) {
+        let label = number.number.to_string();
+        let effects = TextEffects::default();
+        let text = Text {
+            text: &label,
+            family: &number.font_family,
+            size: number.font_size,
+            bold: false,
+            italic: false,
+            glyphs: &[],
+            effects: &effects,
+        };
+        // The position is the right edge of the number
+        let (_, width) = self.text_path(&text, 0.0, 0.0, &Transform::IDENTITY);
+        let (path, _) = self.text_path(&text, number.x - width, number.y, transform);
+        self.fill_text(&path, number.color, opacity);
+    }
+
+    fn draw_watermark(&mut self, watermark: &WatermarkRenderInfo, transform: &Transform, opacity: f64) {
+        let bounds = watermark.bounds;
+        let transform = rotated(transform, watermark.rotation, bounds.x, bounds.y, bounds.width, bounds.height);
+        let opacity = opacity * watermark.opacity;
+        match &watermark.content {
+            WatermarkRenderContent::Text { text, font_family, font_size, color, baseline } => {
+                let effects = TextEffects::default();
+                let text = Text {
+                    text,
+                    family: font_family,
+                    size: *font_size,
+                    bold: false,
+                    italic: false,
+                    glyphs: &[],
+                    effects: &effects,
+                };
+                let (path, _) = self.text_path(&text, bounds.x, bounds.y + baseline, &transform);
+                self.fill_text(&path, *color, opacity);
+            }
+            WatermarkRenderContent::Image { resource_id } => {
+                if !self.draw_picture(resource_id, bounds, &transform, opacity) {
+                    let mut path = Path::new();
+                    path.rect(bounds.x, bounds.y, bounds.width, bounds.height, &transform);
+                    self.fill(&path, IMAGE_PLACEHOLDER_FILL, opacity);
+                }
+            }
+        }
+    }
+
+    /// Draw a picture from the image store into `bounds`, rasterized at the
+    /// bitmap's resolution so SVGs stay sharp
+    ///
+    /// Returns false if there's no store or the picture can't be drawn.
+    fn draw_picture(&mut self, resource_id: &str, bounds: Rect, transform: &Transform, opacity: f64) -> bool {
+        let Some(images) = self.images else {
+            return false;
+        };
+        if !(bounds.width > 0.0 && bounds.height > 0.0) {
+            return false;
+        }
+        let dpi = (72.0 * transform.scale_factor()) as f32;
+        let resource_id = ResourceId::new(resource_id);
+        let Ok(png) = images.rasterize(&resource_id, bounds.width as f32, bounds.height as f32, dpi) else {
+            return false;
+        };
+        let Ok(pixels) = image_codec::decode_png(&png) else {
+            return false;
+        };
+        let placement = Transform::scale(
+            bounds.width / pixels.width as f64,
+            bounds.height / pixels.height as f64,
+        )
+        .then(&Transform::translate(bounds.x, bounds.y))
+        .then(transform);
+        self.pixmap.draw_image(&pixels.data, pixels.width, pixels.height, &placement, opacity as f32);
+        true
+    }
+
+    fn fill_text(&mut self, path: &Path, color: Color, opacity: f64) {
+        self.pixmap.fill_path(path, with_opacity(color, opacity), self.text_antialiasing);
+    }
+
+    /// Build the outline of a piece of text with its baseline at (`x`, `y`)
+    ///
+    /// Returns the path and the advance width in points.
+    fn text_path(&self, text: &Text<'_>, x: f64, y: f64, transform: &Transform) -> (Path, f64) {
+        let mut path = Path::new();
+        let size = text.size;
+        let exact = FontId::new(text.family)
+            .with_weight(if text.bold { FontWeight::Bold } else { FontWeight::Normal })
+            .with_style(if text.italic { FontStyle::Italic } else { FontStyle::Normal });
+        let font = [exact, FontId::new(text.family)]
+            .into_iter()
+            .find(|font_id| self.shaper.has_font(font_id));
+
+        let Ok(shaped) = self.shaper.shape_with_font(text.text, size as f32, font.as_ref()) else {
+            return (path, 0.0);
+        };
+        let units = size / shaped.units_per_em.max(1) as f64;
+        let horizontal_scale = if text.effects.horizontal_scale > 0.0 {
+            text.effects.horizontal_scale
+        } else {
+            1.0
+        };
+        // Positions from layout already include spacing and scaling
+        let positioned = text.glyphs.len() == shaped.glyphs.len();
+
+        let mut pen = 0.0;
+        for (index, glyph) in shaped.glyphs.iter().enumerate() {
+            let advance = glyph.x_advance as f64 * units * horizontal_scale;
+            let (glyph_x, glyph_y) = match text.glyphs.get(index).filter(|_| positioned) {
+                Some(placed) => (placed.x, placed.y),
+                None => (pen, 0.0),
+            };
+            let origin_x = x + glyph_x + glyph.x_offset as f64 * units * horizontal_scale;
+            let origin_y = y + glyph_y - glyph.y_offset as f64 * units;
+
+            match &font {
+                Some(font_id) => {
+                    if let Some(outline) = self.shaper.glyph_outline(font_id, glyph.glyph_id) {
+                        let point = |fx: f32, fy: f32| {
+                            transform.apply(
+                                origin_x + fx as f64 * units * horizontal_scale,
+                                origin_y - fy as f64 * units,
+                            )
+                        };
+                        append_outline(&mut path, &outline.segments, point);
+                    }
+                }
+                None => {
+                    // No font to draw from: a bar the height of lowercase letters
+                    if glyph.character.is_some_and(|ch| !ch.is_whitespace()) {
+                        let height = size * 0.45;
+                        path.rect(origin_x + advance * 0.08, origin_y - height, advance * 0.84, height, transform);
+                    }
+                }
+            }
+            pen += advance + text.effects.letter_spacing;
+        }
+
+        let advance = match text.glyphs.last().filter(|_| positioned) {
+            Some(last) => {
+                let last_advance = shaped.glyphs.last().map_or(0.0, |glyph| glyph.x_advance as f64);
+                last.x + last_advance * units * horizontal_scale + text.effects.letter_spacing
+            }
+            None => pen,
+        };
+        (path, advance)
+    }
+}
+
+/// A piece of text to draw
+struct Text<'a> {
+    text: &'a str,
+    family: &'a str,
+    size: f64,
+    bold: bool,
+    italic: bool,
+    /// Glyph positions from layout, if any
+    glyphs: &'a [RenderGlyph],
+    effects: &'a TextEffects,
+}
+
+/// Add a glyph outline to a path, mapping font units through `point`
+fn append_outline(path: &mut Path, segments: &[OutlineSegment], point: impl Fn(f32, f32) -> (f32, f32)) {
+    for segment in segments {
+        match *segment {
+            OutlineSegment::MoveTo(x, y) => path.move_to(point(x, y)),
+            OutlineSegment::LineTo(x, y) => path.line_to(point(x, y)),
+            OutlineSegment::QuadTo(x1, y1, x, y) => path.quad_to(point(x1, y1), point(x, y)),
+            OutlineSegment::CurveTo(x1, y1, x2, y2, x, y) => {
+                path.cubic_to(point(x1, y1), point(x2, y2), point(x, y))
+            }
+            OutlineSegment::Close => path.close(),
+        }
+    }
+}
+
+/// Apply a clockwise rotation around the center of a box before `transform`
+fn rotated(transform: &Transform, degrees: f64, x: f64, y: f64, width: f64, height: f64) -> Transform {
+    if degrees == 0.0 {
+        *transform
+    } else {
+        Transform::rotate_around(degrees, x + width / 2.0, y + height / 2.0).then(transform)
+    }
+}
+
+/// Start and end point of a straight line
+type LineSegment = ((f64, f64), (f64, f64));
+
+/// Outline of a shape in a `width` x `height` box, and whether it encloses
+/// an area that can be filled
+fn shape_path(shape_type: &ShapeRenderType, width: f64, height: f64, transform: &Transform) -> (Path, bool) {
+    let (w, h) = (width, height);
+    let mut path = Path::new();
+    let regular = |sides: usize, inner_ratio: Option<f64>| -> Vec<(f64, f64)> {
+        let radius = w.min(h) / 2.0;
+        let points = if inner_ratio.is_some() { sides * 2 } else { sides };
+        (0..points)
+            .map(|i| {
+                let angle = i as f64 * std::f64::consts::TAU / points as f64 - std::f64::consts::FRAC_PI_2;
+                let r = match inner_ratio {
+                    Some(ratio) if i % 2 == 1 => radius * ratio,
+                    _ => radius,
+                };
+                (w / 2.0 + r * angle.cos(), h / 2.0 + r * angle.sin())
+            })
+            .collect()
+    };
+    let lines = |path: &mut Path, points: &[LineSegment]| {
+        for &(from, to) in points {
+            path.move_to(transform.apply(from.0, from.1));
+            path.line_to(transform.apply(to.0, to.1));
+        }
+    };
+
+    let fillable = match shape_type {
+        ShapeRenderType::Rectangle | ShapeRenderType::TextBox => {
+            path.rect(0.0, 0.0, w, h, transform);
+            true
+        }
+        ShapeRenderType::RoundedRectangle { corner_radius } => {
+            let r = corner_radius.min(w / 2.0).min(h / 2.0).max(0.0);
+            let mut points = Vec::new();
+            for (cx, cy, start) in [(w - r, r, -90.0f64), (w - r, h - r, 0.0), (r, h - r, 90.0), (r, r, 180.0)] {
+                for step in 0..=8 {
+                    let angle = (start + step as f64 * 90.0 / 8.0).to_radians();
+                    points.push((cx + r * angle.cos(), cy + r * angle.sin()));
+                }
+            }
+            path.polygon(&points, transform);
+            true
+        }
+        ShapeRenderType::Oval => {
+            path.ellipse(0.0, 0.0, w, h, transform);
+            true
+        }
+        ShapeRenderType::Line => {
+            lines(&mut path, &[((0.0, h), (w, 0.0))]);
+            false
+        }
+        ShapeRenderType::Arrow => {
+            lines(
+                &mut path,
+                &[
+                    ((0.0, h / 2.0), (w - 10.0, h / 2.0)),
+                    ((w, h / 2.0), (w - 10.0, h / 2.0 - 5.0)),
+                    ((w, h / 2.0), (w - 10.0, h / 2.0 + 5.0)),
+                ],
+            );
+            false
+        }
+        ShapeRenderType::DoubleArrow => {
+            lines(
+                &mut path,
+                &[
+                    ((10.0, h / 2.0), (w - 10.0, h / 2.0)),
+                    ((w, h / 2.0), (w - 10.0, h / 2.0 - 5.0)),
+                    ((w, h / 2.0), (w - 10.0, h / 2.0 + 5.0)),
+                    ((0.0, h / 2.0), (10.0, h / 2.0 - 5.0)),
+                    ((0.0, h / 2.0), (10.0, h / 2.0 + 5.0)),
+                ],
+            );
+            false
+        }
+        ShapeRenderType::Triangle => {
+            path.polygon(&[(w / 2.0, 0.0), (w, h), (0.0, h)], transform);
+            true
+        }
+        ShapeRenderType::Diamond => {
+            path.polygon(&[(w / 2.0, 0.0), (w, h / 2.0), (w / 2.0, h), (0.0, h / 2.0)], transform);
+            true
+        }
+        ShapeRenderType::Pentagon => {
+            path.polygon(&regular(5, None), transform);
+            true
+        }
+        ShapeRenderType::Hexagon => {
+            path.polygon(&regular(6, None), transform);
+            true
+        }
+        ShapeRenderType::Star { points, inner_radius_ratio } => {
+            path.polygon(&regular((*points).max(2) as usize, Some(*inner_radius_ratio)), transform);
+            true
+        }
+        ShapeRenderType::Callout { tail_position, .. } => {
+            path.rect(0.0, 0.0, w, h * 0.8, transform);
+            path.polygon(
+                &[(w * 0.4, h * 0.8), (tail_position.0 * w, tail_position.1 * h), (w * 0.5, h * 0.8)],
+                transform,
+            );
+            true
+        }
+        ShapeRenderType::RightArrowBlock => {
+            path.polygon(
+                &[
+                    (0.0, h * 0.25),
+                    (w * 0.6, h * 0.25),
+                    (w * 0.6, 0.0),
+                    (w, h / 2.0),
+                    (w * 0.6, h),
+                    (w * 0.6, h * 0.75),
+                    (0.0, h * 0.75),
+                ],
+                transform,
+            );
+            true
+        }
+        ShapeRenderType::LeftArrowBlock => {
+            path.polygon(
+                &[
+                    (w, h * 0.25),
+                    (w * 0.4, h * 0.25),
+                    (w * 0.4, 0.0),
+                    (0.0, h / 2.0),
+                    (w * 0.4, h),
+                    (w * 0.4, h * 0.75),
+                    (w, h * 0.75),
+                ],
+                transform,
+            );
+            true
+        }
+        ShapeRenderType::UpArrowBlock => {
+            path.polygon(
+                &[
+                    (w * 0.25, h),
+                    (w * 0.25, h * 0.4),
+                    (0.0, h * 0.4),
+                    (w / 2.0, 0.0),
+                    (w, h * 0.4),
+                    (w * 0.75, h * 0.4),
+                    (w * 0.75, h),
+                ],
+                transform,
+            );
+            true
+        }
+        ShapeRenderType::DownArrowBlock => {
+            path.polygon(
+                &[
+                    (w * 0.25, 0.0),
+                    (w * 0.25, h * 0.6),
+                    (0.0, h * 0.6),
+                    (w / 2.0, h),
+                    (w, h * 0.6),
+                    (w * 0.75, h * 0.6),
+                    (w * 0.75, 0.0),
+                ],
+                transform,
+            );
+            true
+        }
+    };
+    (path, fillable)
+}
+
+/// Dash pattern for a border style, in multiples of the line width
+fn line_style_dashes(style: &str) -> &'static [f64] {
+    match style {
+        "dashed" | "dash" => &[3.0, 2.0],
+        "dotted" | "dot" => &[1.0, 1.0],
+        _ => &[],
+    }
+}
+
+/// Color of a fill; gradients are drawn with their average color
+fn fill_color(fill: &ShapeFillRender) -> Option<Color> {
+    match fill {
+        ShapeFillRender::Solid { color } => Some(*color),
+        ShapeFillRender::Gradient { colors, .. } => average_color(colors),
+        ShapeFillRender::None => None,
+    }
+}
+
+/// Average of gradient stop colors
+fn average_color(stops: &[(Color, f64)]) -> Option<Color> {
+    if stops.is_empty() {
+        return None;
+    }
+    let mut sums = [0u32; 4];
+    for (color, _) in stops {
+        for (sum, channel) in sums.iter_mut().zip([color.r, color.g, color.b, color.a]) {
+            *sum += channel as u32;
+        }
+    }
+    let count = stops.len() as u32;
+    Some(Color::rgba(
+        (sums[0] / count) as u8,
+        (sums[1] / count) as u8,
+        (sums[2] / count) as u8,
+        (sums[3] / count) as u8,
+    ))
+}
+
+/// Scale a color's alpha by an opacity
+/// Fill the outlines of ink strokes
+pub(super) fn fill_ink(pixmap: &mut Pixmap, ink: &InkRenderInfo, transform: &Transform, opacity: f64) {
+    for stroke in &ink.strokes {
+        let mut path = Path::new();
+        path.polygon(&stroke.outline, transform);
+        pixmap.fill_path(&path, with_opacity(stroke.color, opacity), true);
+    }
+}
+
+fn with_opacity(color: Color, opacity: f64) -> Color {
+    let alpha = (color.a as f64 * opacity.clamp(0.0, 1.0)).round() as u8;
+    Color::rgba(color.r, color.g, color.b, alpha)
+}
@@ -0,0 +1,234 @@
+//! Tests for raster page rendering
+
+use super::*;
+use render_model::{
+    Color, GlyphRun, PageRender, Rect, RenderItem, ShapeFillRender, ShapeRenderInfo, ShapeRenderType,
+    TextEffects,
+};
+use std::io::Read;
+
+fn page(items: Vec<RenderItem>) -> PageRender {
+    PageRender {
+        page_index: 0,
+        width: 100.0,
+        height: 100.0,
+        items,
+    }
+}
+
+fn filled_rect(x: f64, y: f64, width: f64, height: f64, color: Color) -> RenderItem {
+    RenderItem::Rectangle {
+        bounds: Rect::new(x, y, width, height),
+        fill: Some(color),
+        stroke: None,
+        stroke_width: 0.0,
+    }
+}
+
+fn glyph_run(text: &str, x: f64, y: f64) -> RenderItem {
+    RenderItem::GlyphRun(GlyphRun {
+        text: text.to_string(),
+        font_family: "Not A Loaded Font".to_string(),
+        font_size: 20.0,
+        bold: false,
+        italic: false,
+        underline: false,
+        color: Color::BLACK,
+        x,
+        y,
+        hyperlink: None,
+        effects: TextEffects::default(),
+        font_key: 0,
+        glyphs: Vec::new(),
+    })
+}
+
+fn render(page: &PageRender, options: RasterOptions) -> Pixmap {
+    PageRasterizer::new(options).render(page).unwrap()
+}
+
+fn is_white(color: Color) -> bool {
+    color == Color::WHITE
+}
+
+#[test]
+fn test_size_follows_dpi() {
+    let page = page(vec![]);
+    let pixmap = render(&page, RasterOptions::new().with_dpi(72.0));
+    assert_eq!((pixmap.width(), pixmap.height()), (100, 100));
+
+    let pixmap = render(&page, RasterOptions::new().with_dpi(144.0));
+    assert_eq!((pixmap.width(), pixmap.height()), (200, 200));
+
+    let pixmap = render(&page, RasterOptions::new().with_scale(0.2));
+    assert_eq!((pixmap.width(), pixmap.height()), (20, 20));
+
+    // Huge resolutions are capped
+    let pixmap = render(&page, RasterOptions::new().with_dpi(7200.0).with_max_dimension(500));
+    assert_eq!((pixmap.width(), pixmap.height()), (500, 500));
+}
+
+#[test]
+fn test_rectangle_fill_and_antialiased_edge() {
+    let red = Color::rgb(255, 0, 0);
+    let page = page(vec![filled_rect(10.5, 10.0, 20.0, 20.0, red)]);
+    let pixmap = render(&page, RasterOptions::new().with_dpi(72.0));
+
+    assert_eq!(pixmap.pixel(20, 20), Some(red));
+    assert!(is_white(pixmap.pixel(5, 5).unwrap()));
+    // The left edge covers half of column 10
+    let edge = pixmap.pixel(10, 20).unwrap();
+    assert_eq!(edge.r, 255);
+    assert!((edge.g as i32 - 128).abs() <= 2, "{:?}", edge);
+}
+
+#[test]
+fn test_rectangles_outside_the_page_are_clipped() {
+    let blue = Color::rgb(0, 0, 255);
+    let page = page(vec![filled_rect(-50.0, 90.0, 500.0, 500.0, blue)]);
+    let pixmap = render(&page, RasterOptions::new().with_dpi(72.0));
+
+    assert_eq!(pixmap.pixel(0, 99), Some(blue));
+    assert_eq!(pixmap.pixel(99, 95), Some(blue));
+    assert!(is_white(pixmap.pixel(50, 50).unwrap()));
+}
+
+#[test]
+fn test_oval_shape() {
+    let green = Color::rgb(0, 160, 0);
+    let shape = ShapeRenderInfo {
+        node_id: "shape".to_string(),
+        shape_type: ShapeRenderType::Oval,
+        bounds: Rect::new(0.0, 0.0, 100.0, 100.0),
+        rotation: 0.0,
+        fill: Some(ShapeFillRender::Solid { color: green }),
+        stroke: None,
+        shadow: None,
+        opacity: 1.0,
+        selected: false,
+        flip_horizontal: false,
+        flip_vertical: false,
+    };
+    let pixmap = render(&page(vec![RenderItem::Shape(shape)]), RasterOptions::new().with_dpi(72.0));
+
+    assert_eq!(pixmap.pixel(50, 50), Some(green));
+    assert!(is_white(pixmap.pixel(2, 2).unwrap()));
+    assert!(is_white(pixmap.pixel(97, 97).unwrap()));
+}
+
+#[test]
+fn test_text_without_fonts_is_drawn_as_bars() {
+    let pixmap = render(&page(vec![glyph_run("Hello", 5.0, 50.0)]), RasterOptions::new().with_dpi(72.0));
+
+    let inked = |x0: u32, x1: u32| (x0..x1).any(|x| (40..50).any(|y| !is_white(pixmap.pixel(x, y).unwrap())));
+    assert!(inked(5, 50));
+    // Nothing above the x-height or right of the text
+    assert!(!(5..60).any(|x| !is_white(pixmap.pixel(x, 30).unwrap())));
+    assert!(!inked(60, 100));
+}
+
+#[test]
+fn test_text_antialiasing_can_be_disabled() {
+    let page = page(vec![glyph_run("Hello", 5.3, 50.3)]);
+    let colors = |pixmap: &Pixmap| {
+        let mut colors: Vec<Color> = (0..100)
+            .flat_map(|y| (0..100).map(move |x| (x, y)))
+            .map(|(x, y)| pixmap.pixel(x, y).unwrap())
+            .collect();
+        colors.sort_by_key(|color| (color.r, color.g, color.b));
+        colors.dedup();
+        colors.len()
+    };
+
+    let smooth = render(&page, RasterOptions::new().with_dpi(72.0));
+    let crisp = render(&page, RasterOptions::new().with_dpi(72.0).with_text_antialiasing(false));
+    assert!(colors(&smooth) > 2);
+    assert_eq!(colors(&crisp), 2);
+}
+
+#[test]
+fn test_editing_aids_are_not_drawn() {
+    let page = page(vec![
+        RenderItem::Selection {
+            rects: vec![Rect::new(0.0, 0.0, 100.0, 100.0)],
+            color: Color::rgba(0, 0, 255, 128),
+        },
+        RenderItem::Caret {
+            x: 50.0,
+            y: 0.0,
+            height: 100.0,
+            color: Color::BLACK,
+        },
+    ]);
+    let pixmap = render(&page, RasterOptions::new().with_dpi(72.0));
+    assert!(pixmap.data().iter().all(|&byte| byte == 255));
+}
+
+#[test]
+fn test_svg_picture_drawn_from_image_store() {
+    use crate::image_store::ImageStore;
+    use render_model::ImageRenderInfo;
+
+    let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" width="10pt" height="10pt" viewBox="0 0 10 10">
+        <rect width="5" height="10" fill="#ff0000"/>
+        <rect x="5" width="5" height="10" fill="#0000ff"/>
+    </svg>"##;
+    let store = ImageStore::new();
+    let resource_id = store.store_image(svg.as_bytes().to_vec(), None).unwrap();
+    let page = page(vec![
+        RenderItem::Image(ImageRenderInfo::new("image", resource_id.as_str(), Rect::new(20.0, 20.0, 40.0, 40.0))),
+        RenderItem::Image(ImageRenderInfo::new("missing", "missing", Rect::new(70.0, 70.0, 20.0, 20.0))),
+    ]);
+
+    let mut rasterizer = PageRasterizer::new(RasterOptions::new().with_dpi(144.0));
+    let placeholder = rasterizer.render(&page).unwrap();
+    assert_eq!(placeholder.pixel(50, 80), Some(Color::rgb(235, 235, 235)));
+
+    rasterizer.set_images(store);
+    let pixmap = rasterizer.render(&page).unwrap();
+    assert_eq!(pixmap.pixel(50, 80), Some(Color::rgb(255, 0, 0)));
+    assert_eq!(pixmap.pixel(110, 80), Some(Color::rgb(0, 0, 255)));
+    assert_eq!(pixmap.pixel(30, 30), Some(Color::WHITE));
+    // Pictures missing from the store keep their frame
+    assert_eq!(pixmap.pixel(160, 160), Some(Color::rgb(235, 235, 235)));
+}
+
+#[test]
+fn test_png_encoding() {
+    let red = Color::rgb(255, 0, 0);
+    let png = render_page_png(&page(vec![filled_rect(0.0, 0.0, 50.0, 100.0, red)]), RasterOptions::new().with_dpi(36.0))
+        .unwrap();
+
+    assert_eq!(&png[..8], &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]);
+    assert_eq!(&png[12..16], b"IHDR");
+    assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 50);
+    assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 50);
+    assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+
+    // Chunk CRCs check out and the image data inflates to every row
+    let mut offset = 8;
+    let mut image_data = Vec::new();
+    while offset < png.len() {
+        let len = u32::from_be_bytes(png[offset..offset + 4].try_into().unwrap()) as usize;
+        let chunk = &png[offset + 4..offset + 8 + len];
+        let crc = u32::from_be_bytes(png[offset + 8 + len..offset + 12 + len].try_into().unwrap());
+        assert_eq!(crc32fast::hash(chunk), crc);
+        if &chunk[..4] == b"IDAT" {
+            image_data.extend_from_slice(&chunk[4..]);
+        }
+        offset += 12 + len;
+    }
+    let mut raw = Vec::new();
+    flate2::read::ZlibDecoder::new(&image_data[..]).read_to_end(&mut raw).unwrap();
+    assert_eq!(raw.len(), 50 * (1 + 50 * 4));
+
+    assert!(png_data_url(&png).starts_with("data:image/png;base64,iVBORw0KGgo"));
+}
+
+#[test]
+fn test_empty_page_is_an_error() {
+    let mut empty = page(vec![]);
+    empty.width = 0.0;
+    assert!(PageRasterizer::new(RasterOptions::new()).render(&empty).is_err());
+}
+
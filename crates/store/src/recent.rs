@@ -0,0 +1,318 @@
+//! Recent documents
+//!
+//! Keeps the list of recently opened documents for the start screen, with
+//! the time each was last opened, pinned items that stay at the top and are
+//! never dropped, and a small thumbnail of each document's first page.
+//! Thumbnails are PNG files stored next to the list, named after a hash of
+//! the document path.
+
+use crate::raster::RasterOptions;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Default number of unpinned documents kept
+pub const DEFAULT_RECENT_LIMIT: usize = 10;
+
+/// Largest side of a thumbnail in pixels
+pub const THUMBNAIL_MAX_DIMENSION: u32 = 256;
+
+/// Raster options for first-page thumbnails
+pub fn thumbnail_options() -> RasterOptions {
+    RasterOptions::new().with_max_dimension(THUMBNAIL_MAX_DIMENSION)
+}
+
+/// A recently opened document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentDocument {
+    /// Path of the document
+    pub path: PathBuf,
+    /// File name shown on the start screen
+    pub name: String,
+    /// When the document was last opened (Unix timestamp in ms)
+    pub last_opened: u64,
+    /// Whether the document is pinned
+    #[serde(default)]
+    pub pinned: bool,
+    /// File name of the thumbnail, if one was stored
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+    /// Whether the file existed when last checked
+    #[serde(default = "default_true")]
+    pub exists: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Manages the recent documents list
+pub struct RecentFilesManager {
+    /// Path to the list file
+    list_path: PathBuf,
+    /// Directory holding thumbnails
+    thumbnails_dir: PathBuf,
+    /// Number of unpinned documents kept
+    limit: usize,
+    /// Documents, most recently opened first
+    entries: Vec<RecentDocument>,
+}
+
+impl RecentFilesManager {
+    /// Create a manager storing its list in the given app data directory
+    pub fn new(app_data_dir: PathBuf) -> Self {
+        Self {
+            list_path: app_data_dir.join("recent.json"),
+            thumbnails_dir: app_data_dir.join("thumbnails"),
+            limit: DEFAULT_RECENT_LIMIT,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Set the number of unpinned documents kept
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Change the number of unpinned documents kept, dropping the oldest
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.trim();
+    }
+
+    /// Load the list from disk
+    ///
+    /// A missing or unreadable list gives an empty one.
+    pub fn load_sync(&mut self) -> Result<&[RecentDocument]> {
+        self.entries = match std::fs::read_to_string(&self.list_path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                tracing::warn!("Failed to parse recent documents, starting empty: {}", e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        self.sort();
+        Ok(&self.entries)
+    }
+
+    /// Save the list to disk
+    pub fn save_sync(&self) -> Result<()> {
+        if let Some(parent) = self.list_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.list_path, serde_json::to_string_pretty(&self.entries)?)?;
+        Ok(())
+    }
+
+    /// Documents, pinned first, then most recently opened first
+    pub fn list(&self) -> &[RecentDocument] {
+        &self.entries
+    }
+
+    /// Get the entry for a path
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&RecentDocument> {
+        let path = path.as_ref();
+        self.entries.iter().find(|e| e.path == path)
+    }
+
+    /// Record that a document was opened
+    pub fn add(&mut self, path: impl Into<PathBuf>) -> &RecentDocument {
+        let path = path.into();
+        let now = now_ms();
+        match self.entries.iter_mut().find(|e| e.path == path) {
+            Some(entry) => {
+                entry.last_opened = now;
+                entry.exists = true;
+            }
+            None => {
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.display().to_string());
+                self.entries.push(RecentDocument {
+                    path: path.clone(),
+                    name,
+                    last_opened: now,
+                    pinned: false,
+                    thumbnail: None,
+                    exists: true,
+                });
+            }
+        }
+        self.sort();
+        self.trim();
+        self.entries.iter().find(|e| e.path == path).expect("entry was just added")
+    }
+
+    /// Remove a document from the list, with its thumbnail
+    pub fn remove(&mut self, path: impl AsRef<Path>) -> bool {
+        let path = path.as_ref();
+        match self.entries.iter().position(|e| e.path == path) {
+            Some(index) => {
+                let entry = self.entries.remove(index);
+                self.delete_thumbnail(&entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove every unpinned document
+    pub fn clear_unpinned(&mut self) {
+        let (pinned, unpinned): (Vec<_>, Vec<_>) = self.entries.drain(..).partition(|e| e.pinned);
+        for entry in &unpinned {
+            self.delete_thumbnail(entry);
+        }
+        self.entries = pinned;
+    }
+
+    /// Pin or unpin a document
+    ///
+    /// Returns false if the document isn't in the list.
+    pub fn set_pinned(&mut self, path: impl AsRef<Path>, pinned: bool) -> bool {
+        let path = path.as_ref();
+        let Some(entry) = self.entries.iter_mut().find(|e| e.path == path) else {
+            return false;
+        };
+        entry.pinned = pinned;
+        self.sort();
+        self.trim();
+        true
+    }
+
+    /// Check which documents still exist
+    ///
+    /// Returns the paths of documents that are missing. They stay in the
+    /// list, marked as missing, so a file on an unplugged drive comes back.
+    pub fn validate(&mut self) -> Vec<PathBuf> {
+        let mut missing = Vec::new();
+        for entry in &mut self.entries {
+            entry.exists = entry.path.exists();
+            if !entry.exists {
+                missing.push(entry.path.clone());
+            }
+        }
+        missing
+    }
+
+    /// Remove unpinned documents that were missing when last checked
+    pub fn remove_missing(&mut self) -> usize {
+        let missing: Vec<PathBuf> = self
+            .entries
+            .iter()
+            .filter(|e| !e.exists && !e.pinned)
+            .map(|e| e.path.clone())
+            .collect();
+        for path in &missing {
+            self.remove(path);
+        }
+        missing.len()
+    }
+
+    /// Store the first-page thumbnail of a document in the list
+    pub fn set_thumbnail(&mut self, path: impl AsRef<Path>, png: &[u8]) -> Result<bool> {
+        let path = path.as_ref();
+        let Some(index) = self.entries.iter().position(|e| e.path == path) else {
+            return Ok(false);
+        };
+        std::fs::create_dir_all(&self.thumbnails_dir)?;
+        let file_name = thumbnail_file_name(path);
+        std::fs::write(self.thumbnails_dir.join(&file_name), png)?;
+        self.entries[index].thumbnail = Some(file_name);
+        Ok(true)
+    }
+
+    /// Read the thumbnail of a document, as PNG bytes
+    pub fn thumbnail(&self, path: impl AsRef<Path>) -> Option<Vec<u8>> {
+        let file_name = self.get(path)?.thumbnail.as_ref()?;
+        std::fs::read(self.thumbnails_dir.join(file_name)).ok()
+    }
+
+    fn delete_thumbnail(&self, entry: &RecentDocument) {
+        if let Some(file_name) = &entry.thumbnail {
+            let _ = std::fs::remove_file(self.thumbnails_dir.join(file_name));
+        }
+    }
+
+    fn sort(&mut self) {
+        self.entries
+            .sort_by(|a, b| b.pinned.cmp(&a.pinned).then(b.last_opened.cmp(&a.last_opened)));
+    }
+
+    /// Drop the oldest unpinned documents beyond the limit
+    fn trim(&mut self) {
+        let mut unpinned = 0;
+        let mut dropped = Vec::new();
+        self.entries.retain(|entry| {
+            if entry.pinned {
+                return true;
+            }
+            unpinned += 1;
+            if unpinned > self.limit {
+                dropped.push(entry.clone());
+                return false;
+            }
+            true
+        });
+        for entry in &dropped {
+            self.delete_thumbnail(entry);
+        }
+    }
+}
+
+/// Thumbnail file name for a document path
+fn thumbnail_file_name(path: &Path) -> String {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    format!("{:016x}.png", hasher.finish())
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_recent_pins_limits_and_validation() {
+        let temp_dir = TempDir::new().unwrap();
+        let docs: Vec<PathBuf> = (0..4).map(|i| temp_dir.path().join(format!("doc{}.wdj", i))).collect();
+        for doc in &docs[..3] {
+            std::fs::write(doc, "{}").unwrap();
+        }
+
+        let mut manager = RecentFilesManager::new(temp_dir.path().join("app")).with_limit(2);
+        manager.add(&docs[0]);
+        assert!(manager.set_pinned(&docs[0], true));
+        for doc in &docs[1..] {
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            manager.add(doc);
+        }
+        manager.set_thumbnail(&docs[3], b"png").unwrap();
+
+        let paths: Vec<&PathBuf> = manager.list().iter().map(|e| &e.path).collect();
+        assert_eq!(paths, [&docs[0], &docs[3], &docs[2]]);
+        assert_eq!(manager.list()[1].name, "doc3.wdj");
+
+        assert_eq!(manager.validate(), vec![docs[3].clone()]);
+        manager.save_sync().unwrap();
+        assert_eq!(manager.thumbnail(&docs[3]), Some(b"png".to_vec()));
+        assert_eq!(manager.remove_missing(), 1);
+        assert!(manager.thumbnail(&docs[3]).is_none());
+
+        let mut reloaded = RecentFilesManager::new(temp_dir.path().join("app"));
+        assert_eq!(reloaded.load_sync().unwrap().len(), 3);
+        assert!(reloaded.get(&docs[0]).unwrap().pinned);
+    }
+}
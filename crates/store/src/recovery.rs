@@ -200,7 +200,9 @@ impl RecoveryManager {
             .await?
             .ok_or_else(|| StoreError::FileNotFound(recovery_id.to_string()))?;
 
-        crate::load_document(&file.path).await
+        let tree = crate::load_document_with_deltas(&file.path).await?;
+        release_original_lock(&file);
+        Ok(tree)
     }
 
     /// Discard a recovery file (delete it)
@@ -221,6 +223,13 @@ impl RecoveryManager {
             tokio::fs::remove_file(&meta_path).await?;
         }
 
+        // Delete the incremental saves
+        let delta_path = crate::delta_log_path(&file.path);
+        if delta_path.exists() {
+            tokio::fs::remove_file(&delta_path).await?;
+        }
+        release_original_lock(&file);
+
         Ok(())
     }
 
@@ -284,6 +293,15 @@ impl RecoveryManager {
     }
 }
 
+/// Remove the lock the crashed instance left on the original document
+fn release_original_lock(file: &RecoveryFile) {
+    if let Some(original) = &file.original_path {
+        if let Err(e) = crate::release_stale_lock(original) {
+            tracing::warn!("Failed to release lock on {}: {}", original.display(), e);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
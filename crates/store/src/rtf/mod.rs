@@ -16,7 +16,7 @@
 //!
 //! - Text formatting (bold, italic, underline, font size, font family)
 //! - Paragraph formatting (alignment, indentation, spacing)
-//! - Tables (basic support)
+//! - Tables (cell borders including diagonals, shading, vertical alignment and text direction)
 //! - Images (embedded pictures)
 //! - Character encoding (ANSI, Unicode escapes)
 
@@ -108,6 +108,13 @@ pub mod control_words {
     pub const CLMRG: &str = "clmrg";
     pub const CLVMGF: &str = "clvmgf";
     pub const CLVMRG: &str = "clvmrg";
+    pub const CLDGLL: &str = "cldgll";
+    pub const CLDGLU: &str = "cldglu";
+    pub const CLTXLRTB: &str = "cltxlrtb";
+    pub const CLTXTBRL: &str = "cltxtbrl";
+    pub const CLTXBTLR: &str = "cltxbtlr";
+    pub const CLTXLRTBV: &str = "cltxlrtbv";
+    pub const CLTXTBRLV: &str = "cltxtbrlv";
 
     // Image formatting
     pub const PICT: &str = "pict";
@@ -137,6 +144,7 @@ pub mod control_words {
     pub const BRDRDOT: &str = "brdrdot";
     pub const BRDRDASH: &str = "brdrdash";
     pub const BRDRDB: &str = "brdrdb";
+    pub const BRDRTH: &str = "brdrth";
     pub const BRDRW: &str = "brdrw";
     pub const BRDRCF: &str = "brdrcf";
     pub const BRDRNONE: &str = "brdrnone";
@@ -13,7 +13,8 @@ use doc_model::{
     Alignment, CharacterProperties, DocumentTree, ImageNode, ImageProperties,
     LineSpacing, Node, Paragraph, ParagraphProperties, ResourceId, Run, StyleId,
     Table, TableCell, TableGrid, TableRow, GridColumn, TableWidth, WidthType,
-    CellProperties, RowProperties,
+    CellBorders, CellProperties, CellTextDirection, CellVerticalAlign, RowProperties, TableBorder,
+    TableBorderStyle,
 };
 use std::collections::HashMap;
 
@@ -347,7 +348,18 @@ impl ParaState {
 #[derive(Debug, Clone, Default)]
 struct CellDef {
     right_boundary: i32, // In twips
-    // Cell properties can be expanded
+    properties: CellProperties,
+}
+
+/// Cell border a following \brdr* word describes
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CellBorderSide {
+    Top,
+    Bottom,
+    Left,
+    Right,
+    DiagonalDown,
+    DiagonalUp,
 }
 
 /// Table row state
@@ -356,6 +368,81 @@ struct TableState {
     cell_defs: Vec<CellDef>,
     current_cell: usize,
     row_height: Option<f32>,
+    /// Properties of the cell being defined (applied at its \cellx)
+    pending_cell: CellProperties,
+    /// Border being described by \brdr* words
+    border_side: Option<CellBorderSide>,
+}
+
+impl TableState {
+    /// Apply a cell formatting control word to the cell being defined
+    fn apply_cell_word(&mut self, name: &str, param: Option<i32>, colors: &[String]) {
+        let side = match name {
+            CLBRDRT => Some(CellBorderSide::Top),
+            CLBRDRB => Some(CellBorderSide::Bottom),
+            CLBRDRL => Some(CellBorderSide::Left),
+            CLBRDRR => Some(CellBorderSide::Right),
+            CLDGLL => Some(CellBorderSide::DiagonalDown),
+            CLDGLU => Some(CellBorderSide::DiagonalUp),
+            _ => None,
+        };
+        if let Some(side) = side {
+            self.border_side = Some(side);
+            *self.border_mut(side) = Some(TableBorder::default());
+            return;
+        }
+
+        match name {
+            CLVERTALT => self.pending_cell.vertical_align = Some(CellVerticalAlign::Top),
+            CLVERTALC => self.pending_cell.vertical_align = Some(CellVerticalAlign::Center),
+            CLVERTALB => self.pending_cell.vertical_align = Some(CellVerticalAlign::Bottom),
+            CLTXLRTB | CLTXLRTBV => self.pending_cell.text_direction = Some(CellTextDirection::Ltr),
+            CLTXTBRL | CLTXTBRLV => self.pending_cell.text_direction = Some(CellTextDirection::TbRl),
+            CLTXBTLR => self.pending_cell.text_direction = Some(CellTextDirection::BtLr),
+            CLCBPAT => {
+                self.pending_cell.shading = param.and_then(|i| colors.get(i as usize).cloned());
+            }
+            _ => {
+                let Some(side) = self.border_side else {
+                    return;
+                };
+                let Some(border) = self.border_mut(side).as_mut() else {
+                    return;
+                };
+                match name {
+                    BRDRS => border.style = TableBorderStyle::Single,
+                    BRDRDB => border.style = TableBorderStyle::Double,
+                    BRDRDOT => border.style = TableBorderStyle::Dotted,
+                    BRDRDASH => border.style = TableBorderStyle::Dashed,
+                    BRDRTH => border.style = TableBorderStyle::Thick,
+                    BRDRNONE => border.style = TableBorderStyle::None,
+                    BRDRW => {
+                        if let Some(twips) = param {
+                            border.width = twips as f32 / 20.0;
+                        }
+                    }
+                    BRDRCF => {
+                        if let Some(color) = param.and_then(|i| colors.get(i as usize)) {
+                            border.color = color.clone();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn border_mut(&mut self, side: CellBorderSide) -> &mut Option<TableBorder> {
+        let borders = self.pending_cell.borders.get_or_insert_with(CellBorders::default);
+        match side {
+            CellBorderSide::Top => &mut borders.top,
+            CellBorderSide::Bottom => &mut borders.bottom,
+            CellBorderSide::Left => &mut borders.left,
+            CellBorderSide::Right => &mut borders.right,
+            CellBorderSide::DiagonalDown => &mut borders.diagonal_down,
+            CellBorderSide::DiagonalUp => &mut borders.diagonal_up,
+        }
+    }
 }
 
 /// Parser state
@@ -642,8 +729,12 @@ impl RtfParser {
                             // Cell right boundary
                             if let Some(ref mut ts) = self.current_state.table_state {
                                 if let Some(twips) = param {
-                                    ts.cell_defs.push(CellDef { right_boundary: twips });
+                                    ts.cell_defs.push(CellDef {
+                                        right_boundary: twips,
+                                        properties: std::mem::take(&mut ts.pending_cell),
+                                    });
                                 }
+                                ts.border_side = None;
                             }
                         }
                         INTBL => {
@@ -667,8 +758,15 @@ impl RtfParser {
                                 current_cell_paras.push((para, Vec::new()));
                             }
 
-                            // Create cell and store paragraphs
-                            let cell = TableCell::new();
+                            // Create cell (with its \cellx definition) and store paragraphs
+                            let cell = match self.current_state.table_state.as_mut() {
+                                Some(ts) => {
+                                    let props = ts.cell_defs.get(ts.current_cell).map(|d| d.properties.clone());
+                                    ts.current_cell += 1;
+                                    props.map(TableCell::with_properties).unwrap_or_default()
+                                }
+                                None => TableCell::new(),
+                            };
                             current_table_cells.push((cell, std::mem::take(&mut current_cell_paras)));
                         }
                         ROW => {
@@ -745,8 +843,12 @@ impl RtfParser {
                             self.skip_group(&mut tokenizer)?;
                         }
                         _ => {
-                            // Unknown control word - skip
-                            // This is common for extended RTF features
+                            // Cell formatting inside a row definition; any
+                            // other unknown control word is skipped (common
+                            // for extended RTF features)
+                            if let Some(ts) = self.current_state.table_state.as_mut() {
+                                ts.apply_cell_word(&name, param, &self.colors);
+                            }
                         }
                     }
                 }
@@ -861,6 +963,7 @@ impl RtfParser {
         let mut g: u8 = 0;
         let mut b: u8 = 0;
         let mut has_color = false;
+        let mut first_entry = true;
 
         // First entry is the "auto" color (index 0)
         // Don't overwrite it
@@ -896,8 +999,12 @@ impl RtfParser {
                     if text.contains(';') {
                         if has_color {
                             let color = format!("#{:02X}{:02X}{:02X}", r, g, b);
-                            self.colors.push(color);
-                        } else {
+                            if first_entry {
+                                self.colors[0] = color;
+                            } else {
+                                self.colors.push(color);
+                            }
+                        } else if !first_entry {
                             // Auto color entry
                             self.colors.push("#000000".to_string());
                         }
@@ -905,6 +1012,7 @@ impl RtfParser {
                         g = 0;
                         b = 0;
                         has_color = false;
+                        first_entry = false;
                     }
                 }
                 None => break,
@@ -1072,6 +1180,30 @@ mod tests {
         assert!(tree.paragraphs().count() >= 2);
     }
 
+    #[test]
+    fn test_parse_cell_formatting() {
+        let rtf = b"{\\rtf1\\ansi{\\colortbl;\\red0\\green0\\blue0;\\red255\\green0\\blue0;}\
+\\trowd\\cldgll\\brdrdb\\brdrw20\\brdrcf2\\clcbpat2\\clvertalc\\cltxbtlr\\cellx1440\\cellx2880\n\
+\\intbl A\\cell\\intbl B\\cell\\row\n}";
+        let mut parser = RtfParser::new();
+        let (tree, _) = parser.parse(rtf).unwrap();
+
+        let table = tree.nodes.tables.values().next().expect("table");
+        let row = &tree.nodes.table_rows[&table.children()[0]];
+        let first = &tree.nodes.table_cells[&row.children()[0]];
+        let diagonal = first.properties.borders.as_ref().unwrap().diagonal_down.as_ref().unwrap();
+        assert_eq!(diagonal.style, TableBorderStyle::Double);
+        assert_eq!(diagonal.width, 1.0);
+        assert_eq!(diagonal.color, "#FF0000");
+        assert_eq!(first.properties.shading.as_deref(), Some("#FF0000"));
+        assert_eq!(first.properties.vertical_align, Some(CellVerticalAlign::Center));
+        assert_eq!(first.properties.text_direction, Some(CellTextDirection::BtLr));
+
+        let second = &tree.nodes.table_cells[&row.children()[1]];
+        assert!(second.properties.borders.is_none());
+        assert!(second.properties.text_direction.is_none());
+    }
+
     #[test]
     fn test_unicode_escape() {
         let rtf = b"{\\rtf1\\ansi Test \\u8364? euro}"; // Euro sign
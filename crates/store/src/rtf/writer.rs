@@ -6,8 +6,9 @@
 use crate::rtf::control_words::*;
 use crate::rtf::error::{RtfError, RtfResult};
 use doc_model::{
-    Alignment, CharacterProperties, DocumentTree, ImageNode, LineSpacing,
-    Node, Paragraph, ParagraphProperties, Run, Table, TableCell, TableRow,
+    Alignment, CellTextDirection, CellVerticalAlign, CharacterProperties, DocumentTree, ImageNode,
+    LineSpacing, Node, Paragraph, ParagraphProperties, Run, Table, TableBorder, TableBorderStyle,
+    TableCell, TableRow,
 };
 use std::collections::HashMap;
 use std::io::Write;
@@ -123,6 +124,21 @@ impl<W: Write> RtfWriter<W> {
                 }
             }
         }
+
+        // Collect from table cells (shading and border colors)
+        for cell in tree.nodes.table_cells.values() {
+            let border_colors = cell.properties.borders.iter().flat_map(|b| {
+                [&b.top, &b.bottom, &b.left, &b.right, &b.diagonal_down, &b.diagonal_up]
+                    .into_iter()
+                    .flatten()
+                    .map(|border| &border.color)
+            });
+            for color in cell.properties.shading.iter().chain(border_colors) {
+                if !self.used_colors.contains(color) {
+                    self.used_colors.push(color.clone());
+                }
+            }
+        }
     }
 
     /// Build font and color lookup tables
@@ -130,8 +146,9 @@ impl<W: Write> RtfWriter<W> {
         for (idx, font) in self.used_fonts.iter().enumerate() {
             self.fonts.insert(font.clone(), idx as u32);
         }
+        // Color table index 0 is the "auto" entry
         for (idx, color) in self.used_colors.iter().enumerate() {
-            self.colors.insert(color.clone(), idx as u32);
+            self.colors.insert(color.clone(), idx as u32 + 1);
         }
     }
 
@@ -431,19 +448,60 @@ impl<W: Write> RtfWriter<W> {
             let width = col_widths.get(idx).copied().unwrap_or(1440);
             right_boundary += width;
 
-            // Cell borders (default)
-            self.write_str("\\clbrdrt\\brdrs\\brdrw10")?;
-            self.write_str("\\clbrdrb\\brdrs\\brdrw10")?;
-            self.write_str("\\clbrdrl\\brdrs\\brdrw10")?;
-            self.write_str("\\clbrdrr\\brdrs\\brdrw10")?;
+            let cell = tree.nodes.table_cells.get(&cell_id);
+            let borders = cell.and_then(|c| c.properties.borders.as_ref());
+
+            // Cell borders (single lines unless the cell sets its own)
+            match borders {
+                Some(borders) => {
+                    let sides = [
+                        (CLBRDRT, &borders.top),
+                        (CLBRDRB, &borders.bottom),
+                        (CLBRDRL, &borders.left),
+                        (CLBRDRR, &borders.right),
+                        (CLDGLL, &borders.diagonal_down),
+                        (CLDGLU, &borders.diagonal_up),
+                    ];
+                    for (word, border) in sides {
+                        if let Some(border) = border {
+                            self.write_cell_border(word, border)?;
+                        }
+                    }
+                }
+                None => {
+                    self.write_str("\\clbrdrt\\brdrs\\brdrw10")?;
+                    self.write_str("\\clbrdrb\\brdrs\\brdrw10")?;
+                    self.write_str("\\clbrdrl\\brdrs\\brdrw10")?;
+                    self.write_str("\\clbrdrr\\brdrs\\brdrw10")?;
+                }
+            }
 
-            // Cell shading
-            if let Some(cell) = tree.nodes.table_cells.get(&cell_id) {
+            if let Some(cell) = cell {
+                // Cell shading
                 if let Some(ref shading) = cell.properties.shading {
                     if let Some(&idx) = self.colors.get(shading) {
                         write!(self.writer, "\\clcbpat{}", idx)?;
                     }
                 }
+
+                // Vertical alignment
+                match cell.properties.vertical_align {
+                    Some(CellVerticalAlign::Top) => write!(self.writer, "\\{}", CLVERTALT)?,
+                    Some(CellVerticalAlign::Center) => write!(self.writer, "\\{}", CLVERTALC)?,
+                    Some(CellVerticalAlign::Bottom) => write!(self.writer, "\\{}", CLVERTALB)?,
+                    None => {}
+                }
+
+                // Text flow
+                let text_flow = match cell.properties.text_direction {
+                    Some(CellTextDirection::TbRl) => Some(CLTXTBRL),
+                    Some(CellTextDirection::BtLr) => Some(CLTXBTLR),
+                    Some(CellTextDirection::TbLr) => Some(CLTXLRTBV),
+                    _ => None,
+                };
+                if let Some(word) = text_flow {
+                    write!(self.writer, "\\{}", word)?;
+                }
             }
 
             write!(self.writer, "\\cellx{}", right_boundary)?;
@@ -464,6 +522,23 @@ impl<W: Write> RtfWriter<W> {
         Ok(())
     }
 
+    /// Write one cell border definition (\clbrdrX or \cldglX plus its \brdr* words)
+    fn write_cell_border(&mut self, word: &str, border: &TableBorder) -> RtfResult<()> {
+        let style = match border.style {
+            TableBorderStyle::None => BRDRNONE,
+            TableBorderStyle::Single => BRDRS,
+            TableBorderStyle::Double => BRDRDB,
+            TableBorderStyle::Dotted => BRDRDOT,
+            TableBorderStyle::Dashed => BRDRDASH,
+            TableBorderStyle::Thick => BRDRTH,
+        };
+        write!(self.writer, "\\{}\\{}\\{}{}", word, style, BRDRW, (border.width * 20.0).round() as i32)?;
+        if let Some(&idx) = self.colors.get(&border.color) {
+            write!(self.writer, "\\{}{}", BRDRCF, idx)?;
+        }
+        Ok(())
+    }
+
     /// Write a table cell
     fn write_table_cell(&mut self, tree: &DocumentTree, cell: &TableCell) -> RtfResult<()> {
         self.write_str("\\intbl ")?;
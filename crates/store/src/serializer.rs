@@ -1,18 +1,50 @@
 //! Document serialization
 
-use crate::{DocumentFile, Result};
+use crate::signatures::NativeSignature;
+use crate::{FileHeader, Result, StoreError};
 use doc_model::DocumentTree;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// File layout used while reading and writing, with the document kept as
+/// JSON so its part checksums match exactly what is on disk
+#[derive(Serialize, Deserialize)]
+pub(crate) struct RawDocumentFile {
+    pub(crate) header: FileHeader,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) checksums: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) signatures: Vec<NativeSignature>,
+    pub(crate) document: Value,
+}
 
 /// Serialize a document tree to JSON
 pub fn serialize(tree: &DocumentTree) -> Result<String> {
-    let file = DocumentFile::new(tree.clone());
+    let document = serde_json::to_value(tree)?;
+    let file = RawDocumentFile {
+        header: FileHeader::new(tree.root_id().to_string()),
+        checksums: crate::part_checksums(&document),
+        signatures: Vec::new(),
+        document,
+    };
     let json = serde_json::to_string_pretty(&file)?;
     Ok(json)
 }
 
 /// Deserialize a document tree from JSON
+///
+/// Fails with [`StoreError::Corrupted`] if the file is truncated or a part
+/// doesn't match its checksum; [`crate::repair_document_json`] recovers
+/// what it can from such files.
 pub fn deserialize(json: &str) -> Result<DocumentTree> {
-    let file: DocumentFile = serde_json::from_str(json)?;
+    let file: RawDocumentFile = serde_json::from_str(json).map_err(|e| {
+        if e.is_eof() {
+            StoreError::Corrupted("the file is truncated".to_string())
+        } else {
+            StoreError::Serialization(e)
+        }
+    })?;
 
     if !file.header.is_valid() {
         return Err(crate::StoreError::InvalidFormat(
@@ -20,7 +52,15 @@ pub fn deserialize(json: &str) -> Result<DocumentTree> {
         ));
     }
 
-    Ok(file.document)
+    let damaged = crate::verify_part_checksums(&file.document, &file.checksums);
+    if !damaged.is_empty() {
+        return Err(StoreError::Corrupted(format!(
+            "checksum mismatch in {}",
+            damaged.join(", ")
+        )));
+    }
+
+    Ok(serde_json::from_value(file.document)?)
 }
 
 #[cfg(test)]
@@ -35,4 +75,17 @@ mod tests {
 
         assert_eq!(tree.root_id(), loaded.root_id());
     }
+
+    #[test]
+    fn test_detects_damage() {
+        let tree = DocumentTree::with_empty_paragraph();
+        let json = serialize(&tree).unwrap();
+        assert!(json.contains("\"nodes.paragraphs\""));
+
+        let truncated = &json[..json.len() / 2];
+        assert!(matches!(deserialize(truncated), Err(StoreError::Corrupted(_))));
+
+        let tampered = json.replacen("\"margin_top\": 72.0", "\"margin_top\": 71.0", 1);
+        assert!(matches!(deserialize(&tampered), Err(StoreError::Corrupted(_))));
+    }
 }
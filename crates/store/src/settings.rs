@@ -2,10 +2,19 @@
 //!
 //! This module provides settings persistence, loading, and updating
 //! for the Go Word application.
-
-use crate::Result;
+//!
+//! Settings are resolved from layers, each overriding the one below:
+//! built-in defaults < machine < user profile < per-document overrides.
+//! Each layer above the defaults only stores the values it changes (see
+//! [`SettingsOverrides`]). Listeners are told about every change, and the
+//! user profile can be exported and imported as a portable JSON bundle.
+
+use crate::{Result, StoreError};
+use edit_engine::Macro;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
 /// Main application settings container
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -64,6 +73,13 @@ pub struct EditingSettings {
     pub show_spelling_errors: bool,
     /// Whether to show grammar error indicators
     pub show_grammar_errors: bool,
+    /// Whether typed URLs and email addresses become hyperlinks
+    #[serde(default = "default_true")]
+    pub auto_format_hyperlinks: bool,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 impl Default for EditingSettings {
@@ -75,6 +91,7 @@ impl Default for EditingSettings {
             default_font_size: 12.0,
             show_spelling_errors: true,
             show_grammar_errors: true,
+            auto_format_hyperlinks: true,
         }
     }
 }
@@ -112,71 +129,330 @@ impl Default for Theme {
     }
 }
 
+/// The user's saved keyboard macros
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MacroLibrary {
+    /// Saved macros, in creation order
+    pub macros: Vec<Macro>,
+}
+
+impl MacroLibrary {
+    /// Get a macro by name
+    pub fn get(&self, name: &str) -> Option<&Macro> {
+        self.macros.iter().find(|m| m.name == name)
+    }
+
+    /// Get the macro bound to a command ID (`macro.<name>`)
+    pub fn for_command(&self, command_id: &str) -> Option<&Macro> {
+        self.macros.iter().find(|m| m.command_id() == command_id)
+    }
+
+    /// Add a macro, replacing any macro with the same name
+    pub fn insert(&mut self, macro_def: Macro) {
+        match self.macros.iter_mut().find(|m| m.name == macro_def.name) {
+            Some(existing) => *existing = macro_def,
+            None => self.macros.push(macro_def),
+        }
+    }
+
+    /// Remove a macro by name
+    pub fn remove(&mut self, name: &str) -> Option<Macro> {
+        let index = self.macros.iter().position(|m| m.name == name)?;
+        Some(self.macros.remove(index))
+    }
+}
+
+/// A layer of settings, from lowest to highest precedence
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingsLayer {
+    /// Built-in defaults
+    Default,
+    /// Settings for every user of this installation
+    Machine,
+    /// The user's profile
+    User,
+    /// Overrides for a single document
+    Document,
+}
+
+/// The values a settings layer changes, as nested JSON objects keyed like
+/// [`AppSettings`]
+///
+/// Values are addressed by dotted paths such as `"editing.default_font_size"`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(transparent)]
+pub struct SettingsOverrides(Map<String, Value>);
+
+impl SettingsOverrides {
+    /// Create empty overrides
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overrides for every value of `settings` that differs from `base`
+    pub fn diff(base: &AppSettings, settings: &AppSettings) -> Self {
+        let base = flatten_settings(base);
+        let mut overrides = Self::new();
+        for (path, value) in flatten_settings(settings) {
+            if base.get(&path) != Some(&value) {
+                overrides.set(&path, value);
+            }
+        }
+        overrides
+    }
+
+    /// Check if no values are overridden
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Get an overridden value
+    pub fn get(&self, path: &str) -> Option<&Value> {
+        let mut parts = path.split('.');
+        let mut value = self.0.get(parts.next()?)?;
+        for part in parts {
+            value = value.as_object()?.get(part)?;
+        }
+        Some(value)
+    }
+
+    /// Override a value
+    pub fn set(&mut self, path: &str, value: Value) {
+        let mut parts: Vec<&str> = path.split('.').collect();
+        let Some(last) = parts.pop() else {
+            return;
+        };
+        let mut map = &mut self.0;
+        for part in parts {
+            let entry = map.entry(part).or_insert_with(|| Value::Object(Map::new()));
+            if !entry.is_object() {
+                *entry = Value::Object(Map::new());
+            }
+            let Value::Object(next) = entry else {
+                return;
+            };
+            map = next;
+        }
+        map.insert(last.to_string(), value);
+    }
+
+    /// Stop overriding a value
+    pub fn remove(&mut self, path: &str) -> Option<Value> {
+        let (parent, last) = match path.rsplit_once('.') {
+            Some((parent, last)) => (Some(parent), last),
+            None => (None, path),
+        };
+        let removed = match parent {
+            Some(parent) => {
+                let mut map = &mut self.0;
+                for part in parent.split('.') {
+                    map = map.get_mut(part)?.as_object_mut()?;
+                }
+                map.remove(last)
+            }
+            None => self.0.remove(last),
+        };
+        // Drop objects left empty
+        self.0.retain(|_, v| v.as_object().is_none_or(|o| !o.is_empty()));
+        removed
+    }
+
+    /// Apply the overrides on top of `settings`
+    ///
+    /// Values that don't fit the setting they name are ignored.
+    pub fn apply(&self, settings: &AppSettings) -> AppSettings {
+        let mut result = settings.clone();
+        let mut leaves = BTreeMap::new();
+        flatten_value(&Value::Object(self.0.clone()), "", &mut leaves);
+        for (path, value) in leaves {
+            let mut candidate = match serde_json::to_value(&result) {
+                Ok(candidate) => candidate,
+                Err(_) => break,
+            };
+            let known = set_existing(&mut candidate, &path, value);
+            match serde_json::from_value(candidate) {
+                Ok(updated) if known => result = updated,
+                _ => tracing::warn!("Ignoring invalid setting override: {}", path),
+            }
+        }
+        result
+    }
+}
+
+/// Replace a value that already exists in a settings object
+fn set_existing(settings: &mut Value, path: &str, value: Value) -> bool {
+    let mut target = settings;
+    for part in path.split('.') {
+        match target.get_mut(part) {
+            Some(next) => target = next,
+            None => return false,
+        }
+    }
+    *target = value;
+    true
+}
+
+/// Every setting as a dotted path and value
+fn flatten_settings(settings: &AppSettings) -> BTreeMap<String, Value> {
+    let mut leaves = BTreeMap::new();
+    if let Ok(value) = serde_json::to_value(settings) {
+        flatten_value(&value, "", &mut leaves);
+    }
+    leaves
+}
+
+fn flatten_value(value: &Value, prefix: &str, leaves: &mut BTreeMap<String, Value>) {
+    match value.as_object() {
+        Some(map) => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten_value(child, &path, leaves);
+            }
+        }
+        None => {
+            leaves.insert(prefix.to_string(), value.clone());
+        }
+    }
+}
+
+/// Paths of the settings that differ between two resolved settings
+fn changed_paths(before: &AppSettings, after: &AppSettings) -> Vec<String> {
+    let before = flatten_settings(before);
+    flatten_settings(after)
+        .into_iter()
+        .filter(|(path, value)| before.get(path) != Some(value))
+        .map(|(path, _)| path)
+        .collect()
+}
+
+/// Notification that settings changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsChange {
+    /// Layer that was changed
+    pub layer: SettingsLayer,
+    /// Document whose overrides changed, for the document layer
+    pub document_id: Option<String>,
+    /// Dotted paths of the settings whose resolved value changed
+    pub changed: Vec<String>,
+}
+
+/// Callback notified when settings change
+pub type SettingsListener = Box<dyn Fn(&SettingsChange) + Send + Sync>;
+
+/// Identifies the format of a settings bundle
+pub const SETTINGS_BUNDLE_FORMAT: &str = "go-word-settings";
+
+/// Current settings bundle version
+pub const SETTINGS_BUNDLE_VERSION: u32 = 1;
+
+/// Portable export of a user's settings profile, for syncing between machines
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsBundle {
+    /// Always [`SETTINGS_BUNDLE_FORMAT`]
+    pub format: String,
+    /// Bundle version
+    pub version: u32,
+    /// When the bundle was exported (RFC 3339)
+    pub exported_at: String,
+    /// The user's settings, as changes from the defaults
+    pub settings: SettingsOverrides,
+    /// The user's macros
+    #[serde(default)]
+    pub macros: MacroLibrary,
+}
+
 /// Settings manager for loading, saving, and updating application settings
 pub struct SettingsManager {
     /// Path to the settings file
     settings_path: PathBuf,
-    /// Current settings (cached)
+    /// Path to the machine-wide settings file
+    machine_path: PathBuf,
+    /// Current settings (cached), resolved from every layer but documents
     current: AppSettings,
+    /// Machine layer
+    machine: SettingsOverrides,
+    /// User profile layer
+    user: SettingsOverrides,
+    /// Overrides of open documents, by document ID
+    documents: HashMap<String, SettingsOverrides>,
+    /// Change listeners, by subscription ID
+    listeners: Vec<(u64, SettingsListener)>,
+    /// Next subscription ID
+    next_listener_id: u64,
+    /// Path to the macro library file
+    macros_path: PathBuf,
+    /// Saved macros (cached)
+    macros: MacroLibrary,
 }
 
 impl SettingsManager {
     /// Create a new settings manager with the given app data directory
     pub fn new(app_data_dir: PathBuf) -> Self {
         let settings_path = app_data_dir.join("settings.json");
+        let machine_path = app_data_dir.join("machine-settings.json");
+        let macros_path = app_data_dir.join("macros.json");
         Self {
             settings_path,
+            machine_path,
             current: AppSettings::default(),
+            machine: SettingsOverrides::new(),
+            user: SettingsOverrides::new(),
+            documents: HashMap::new(),
+            listeners: Vec::new(),
+            next_listener_id: 1,
+            macros_path,
+            macros: MacroLibrary::default(),
         }
     }
 
+    /// Read machine settings from a different file
+    pub fn with_machine_settings_path(mut self, path: PathBuf) -> Self {
+        self.machine_path = path;
+        self
+    }
+
     /// Get the path to the settings file
     pub fn settings_path(&self) -> &PathBuf {
         &self.settings_path
     }
 
+    /// Get the path to the machine settings file
+    pub fn machine_settings_path(&self) -> &PathBuf {
+        &self.machine_path
+    }
+
     /// Load settings from disk, or return defaults if file doesn't exist
     pub async fn load(&mut self) -> Result<&AppSettings> {
-        if self.settings_path.exists() {
+        self.machine = match tokio::fs::read_to_string(&self.machine_path).await {
+            Ok(content) => parse_overrides(&content, &self.machine_path),
+            Err(_) => SettingsOverrides::new(),
+        };
+        self.user = if self.settings_path.exists() {
             let content = tokio::fs::read_to_string(&self.settings_path).await?;
-            match serde_json::from_str::<AppSettings>(&content) {
-                Ok(settings) => {
-                    self.current = settings;
-                }
-                Err(e) => {
-                    // Log the error but use defaults
-                    tracing::warn!(
-                        "Failed to parse settings file, using defaults: {}",
-                        e
-                    );
-                    self.current = AppSettings::default();
-                }
-            }
+            parse_overrides(&content, &self.settings_path)
         } else {
-            self.current = AppSettings::default();
-        }
+            SettingsOverrides::new()
+        };
+        self.resolve();
         Ok(&self.current)
     }
 
     /// Load settings synchronously (for use during app startup)
     pub fn load_sync(&mut self) -> Result<&AppSettings> {
-        if self.settings_path.exists() {
+        self.machine = match std::fs::read_to_string(&self.machine_path) {
+            Ok(content) => parse_overrides(&content, &self.machine_path),
+            Err(_) => SettingsOverrides::new(),
+        };
+        self.user = if self.settings_path.exists() {
             let content = std::fs::read_to_string(&self.settings_path)?;
-            match serde_json::from_str::<AppSettings>(&content) {
-                Ok(settings) => {
-                    self.current = settings;
-                }
-                Err(e) => {
-                    tracing::warn!(
-                        "Failed to parse settings file, using defaults: {}",
-                        e
-                    );
-                    self.current = AppSettings::default();
-                }
-            }
+            parse_overrides(&content, &self.settings_path)
         } else {
-            self.current = AppSettings::default();
-        }
+            SettingsOverrides::new()
+        };
+        self.resolve();
         Ok(&self.current)
     }
 
@@ -187,7 +463,7 @@ impl SettingsManager {
             tokio::fs::create_dir_all(parent).await?;
         }
 
-        let content = serde_json::to_string_pretty(&self.current)?;
+        let content = serde_json::to_string_pretty(&self.user)?;
         tokio::fs::write(&self.settings_path, content).await?;
         Ok(())
     }
@@ -198,7 +474,7 @@ impl SettingsManager {
             std::fs::create_dir_all(parent)?;
         }
 
-        let content = serde_json::to_string_pretty(&self.current)?;
+        let content = serde_json::to_string_pretty(&self.user)?;
         std::fs::write(&self.settings_path, content)?;
         Ok(())
     }
@@ -208,49 +484,292 @@ impl SettingsManager {
         &self.current
     }
 
+    /// Settings below the user profile: defaults with machine settings applied
+    fn base(&self) -> AppSettings {
+        self.machine.apply(&AppSettings::default())
+    }
+
+    /// Recompute the current settings from the layers
+    fn resolve(&mut self) {
+        self.current = self.user.apply(&self.base());
+    }
+
+    /// Set the user profile so the current settings become `settings`,
+    /// notifying listeners
+    fn set_user_settings(&mut self, settings: &AppSettings) {
+        let before = self.current.clone();
+        self.user = SettingsOverrides::diff(&self.base(), settings);
+        self.resolve();
+        self.notify(SettingsLayer::User, None, &before);
+    }
+
     /// Update settings and save to disk
     pub async fn update(&mut self, settings: AppSettings) -> Result<()> {
-        self.current = settings;
+        self.set_user_settings(&settings);
         self.save().await
     }
 
     /// Update settings synchronously
     pub fn update_sync(&mut self, settings: AppSettings) -> Result<()> {
-        self.current = settings;
+        self.set_user_settings(&settings);
         self.save_sync()
     }
 
     /// Reset settings to defaults and save
     pub async fn reset(&mut self) -> Result<&AppSettings> {
-        self.current = AppSettings::default();
+        self.set_user_settings(&self.base());
         self.save().await?;
         Ok(&self.current)
     }
 
     /// Reset settings to defaults synchronously
     pub fn reset_sync(&mut self) -> Result<&AppSettings> {
-        self.current = AppSettings::default();
+        self.set_user_settings(&self.base());
         self.save_sync()?;
         Ok(&self.current)
     }
 
     /// Update only general settings
     pub async fn update_general(&mut self, general: GeneralSettings) -> Result<()> {
-        self.current.general = general;
-        self.save().await
+        let mut settings = self.current.clone();
+        settings.general = general;
+        self.update(settings).await
     }
 
     /// Update only editing settings
     pub async fn update_editing(&mut self, editing: EditingSettings) -> Result<()> {
-        self.current.editing = editing;
-        self.save().await
+        let mut settings = self.current.clone();
+        settings.editing = editing;
+        self.update(settings).await
     }
 
     /// Update only privacy settings
     pub async fn update_privacy(&mut self, privacy: PrivacySettings) -> Result<()> {
-        self.current.privacy = privacy;
-        self.save().await
+        let mut settings = self.current.clone();
+        settings.privacy = privacy;
+        self.update(settings).await
     }
+
+    /// Get the machine settings layer
+    pub fn machine_overrides(&self) -> &SettingsOverrides {
+        &self.machine
+    }
+
+    /// Get the user profile layer
+    pub fn user_overrides(&self) -> &SettingsOverrides {
+        &self.user
+    }
+
+    /// Replace the machine settings and save them
+    pub fn set_machine_overrides_sync(&mut self, overrides: SettingsOverrides) -> Result<()> {
+        let before = self.current.clone();
+        self.machine = overrides;
+        self.resolve();
+        self.notify(SettingsLayer::Machine, None, &before);
+
+        if let Some(parent) = self.machine_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.machine_path, serde_json::to_string_pretty(&self.machine)?)?;
+        Ok(())
+    }
+
+    /// Settings for a document: the current settings with its overrides applied
+    pub fn settings_for_document(&self, document_id: &str) -> AppSettings {
+        match self.documents.get(document_id) {
+            Some(overrides) => overrides.apply(&self.current),
+            None => self.current.clone(),
+        }
+    }
+
+    /// Get a document's overrides
+    pub fn document_overrides(&self, document_id: &str) -> Option<&SettingsOverrides> {
+        self.documents.get(document_id)
+    }
+
+    /// Replace a document's overrides
+    pub fn set_document_overrides(&mut self, document_id: &str, overrides: SettingsOverrides) {
+        let before = self.settings_for_document(document_id);
+        if overrides.is_empty() {
+            self.documents.remove(document_id);
+        } else {
+            self.documents.insert(document_id.to_string(), overrides);
+        }
+        self.notify(SettingsLayer::Document, Some(document_id), &before);
+    }
+
+    /// Override one setting for a document
+    pub fn set_document_setting(&mut self, document_id: &str, path: &str, value: Value) {
+        let mut overrides = self.documents.get(document_id).cloned().unwrap_or_default();
+        overrides.set(path, value);
+        self.set_document_overrides(document_id, overrides);
+    }
+
+    /// Remove every override of a document, e.g. when it is closed
+    pub fn clear_document_overrides(&mut self, document_id: &str) {
+        self.set_document_overrides(document_id, SettingsOverrides::new());
+    }
+
+    /// The layer a setting's resolved value comes from
+    pub fn layer_of(&self, path: &str, document_id: Option<&str>) -> SettingsLayer {
+        let overrides_path = |overrides: &SettingsOverrides| overrides.get(path).is_some();
+        if document_id
+            .and_then(|id| self.documents.get(id))
+            .is_some_and(overrides_path)
+        {
+            SettingsLayer::Document
+        } else if overrides_path(&self.user) {
+            SettingsLayer::User
+        } else if overrides_path(&self.machine) {
+            SettingsLayer::Machine
+        } else {
+            SettingsLayer::Default
+        }
+    }
+
+    /// Subscribe to settings changes
+    ///
+    /// Returns an ID for [`SettingsManager::unsubscribe`].
+    pub fn subscribe(&mut self, listener: SettingsListener) -> u64 {
+        let id = self.next_listener_id;
+        self.next_listener_id += 1;
+        self.listeners.push((id, listener));
+        id
+    }
+
+    /// Stop notifying a listener
+    pub fn unsubscribe(&mut self, id: u64) -> bool {
+        let count = self.listeners.len();
+        self.listeners.retain(|(listener_id, _)| *listener_id != id);
+        self.listeners.len() != count
+    }
+
+    /// Tell listeners what changed since `before`
+    fn notify(&self, layer: SettingsLayer, document_id: Option<&str>, before: &AppSettings) {
+        let after = match document_id {
+            Some(id) => self.settings_for_document(id),
+            None => self.current.clone(),
+        };
+        let changed = changed_paths(before, &after);
+        if changed.is_empty() {
+            return;
+        }
+        let change = SettingsChange {
+            layer,
+            document_id: document_id.map(str::to_string),
+            changed,
+        };
+        for (_, listener) in &self.listeners {
+            listener(&change);
+        }
+    }
+
+    /// Export the user profile and macros as a bundle
+    pub fn export_bundle(&self) -> SettingsBundle {
+        SettingsBundle {
+            format: SETTINGS_BUNDLE_FORMAT.to_string(),
+            version: SETTINGS_BUNDLE_VERSION,
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            settings: SettingsOverrides::diff(&AppSettings::default(), &self.current),
+            macros: self.macros.clone(),
+        }
+    }
+
+    /// Write the settings bundle to a file
+    pub fn export_bundle_sync(&self, path: impl AsRef<Path>) -> Result<()> {
+        let content = serde_json::to_string_pretty(&self.export_bundle())?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Replace the user profile with a bundle's settings and add its macros
+    pub fn import_bundle_sync(&mut self, bundle: SettingsBundle) -> Result<&AppSettings> {
+        if bundle.format != SETTINGS_BUNDLE_FORMAT || bundle.version > SETTINGS_BUNDLE_VERSION {
+            return Err(StoreError::InvalidFormat(format!(
+                "Unsupported settings bundle: {} version {}",
+                bundle.format, bundle.version
+            )));
+        }
+
+        let settings = bundle.settings.apply(&AppSettings::default());
+        self.set_user_settings(&settings);
+        self.save_sync()?;
+
+        if !bundle.macros.macros.is_empty() {
+            for macro_def in bundle.macros.macros {
+                self.macros.insert(macro_def);
+            }
+            self.save_macros_sync()?;
+        }
+        Ok(&self.current)
+    }
+
+    /// Read a settings bundle from a file and import it
+    pub fn import_bundle_file_sync(&mut self, path: impl AsRef<Path>) -> Result<&AppSettings> {
+        let content = std::fs::read_to_string(path)?;
+        let bundle: SettingsBundle = serde_json::from_str(&content)?;
+        self.import_bundle_sync(bundle)
+    }
+
+    /// Load the macro library, or an empty one if the file doesn't exist
+    pub fn load_macros_sync(&mut self) -> Result<&MacroLibrary> {
+        self.macros = if self.macros_path.exists() {
+            let content = std::fs::read_to_string(&self.macros_path)?;
+            serde_json::from_str(&content).unwrap_or_else(|e| {
+                tracing::warn!("Failed to parse macro library, starting empty: {}", e);
+                MacroLibrary::default()
+            })
+        } else {
+            MacroLibrary::default()
+        };
+        Ok(&self.macros)
+    }
+
+    /// Get the saved macros
+    pub fn macros(&self) -> &MacroLibrary {
+        &self.macros
+    }
+
+    /// Save a macro (replacing one with the same name) and persist the library
+    pub fn save_macro_sync(&mut self, macro_def: Macro) -> Result<()> {
+        self.macros.insert(macro_def);
+        self.save_macros_sync()
+    }
+
+    /// Delete a macro and persist the library
+    pub fn remove_macro_sync(&mut self, name: &str) -> Result<Option<Macro>> {
+        let removed = self.macros.remove(name);
+        if removed.is_some() {
+            self.save_macros_sync()?;
+        }
+        Ok(removed)
+    }
+
+    fn save_macros_sync(&self) -> Result<()> {
+        if let Some(parent) = self.macros_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string_pretty(&self.macros)?;
+        std::fs::write(&self.macros_path, content)?;
+        Ok(())
+    }
+}
+
+/// Parse a settings layer file, using no overrides if it's unreadable
+///
+/// Older settings files hold complete settings, which read as overrides of
+/// every value.
+fn parse_overrides(content: &str, path: &Path) -> SettingsOverrides {
+    serde_json::from_str(content).unwrap_or_else(|e| {
+        // Log the error but use defaults
+        tracing::warn!(
+            "Failed to parse settings file {}, using defaults: {}",
+            path.display(),
+            e
+        );
+        SettingsOverrides::new()
+    })
 }
 
 #[cfg(test)]
@@ -329,6 +848,33 @@ mod tests {
         assert_eq!(settings.general.language, "en");
     }
 
+    #[test]
+    fn test_macro_library_persistence() {
+        use edit_engine::{Direction, MacroStep, MovementUnit};
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = SettingsManager::new(temp_dir.path().to_path_buf());
+        assert!(manager.load_macros_sync().unwrap().macros.is_empty());
+
+        let mut select_word = Macro::new("SelectWord").with_keybinding("Ctrl+Alt+W");
+        select_word.steps.push(MacroStep::Move {
+            direction: Direction::Forward,
+            unit: MovementUnit::Word,
+            extend: true,
+        });
+        manager.save_macro_sync(select_word.clone()).unwrap();
+        manager.save_macro_sync(Macro::new("Empty")).unwrap();
+
+        let mut manager2 = SettingsManager::new(temp_dir.path().to_path_buf());
+        let library = manager2.load_macros_sync().unwrap();
+        assert_eq!(library.macros.len(), 2);
+        assert_eq!(library.for_command("macro.SelectWord"), Some(&select_word));
+
+        assert!(manager2.remove_macro_sync("Empty").unwrap().is_some());
+        assert!(manager2.remove_macro_sync("Missing").unwrap().is_none());
+        assert_eq!(manager2.macros().macros.len(), 1);
+    }
+
     #[tokio::test]
     async fn test_settings_manager_async() {
         let temp_dir = TempDir::new().unwrap();
@@ -347,4 +893,51 @@ mod tests {
         let loaded = manager2.load().await.unwrap();
         assert_eq!(loaded.editing.autosave_interval_seconds, 120);
     }
+
+    #[test]
+    fn test_settings_layers_and_notifications() {
+        use std::sync::{Arc, Mutex};
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = SettingsManager::new(temp_dir.path().to_path_buf());
+        let mut machine = SettingsOverrides::new();
+        machine.set("editing.default_font_family", Value::from("Calibri"));
+        machine.set("general.theme", Value::from("dark"));
+        manager.set_machine_overrides_sync(machine).unwrap();
+
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let sink = changes.clone();
+        let id = manager.subscribe(Box::new(move |change| sink.lock().unwrap().push(change.clone())));
+
+        let mut settings = manager.get().clone();
+        settings.general.theme = Theme::Light;
+        manager.update_sync(settings).unwrap();
+        assert_eq!(manager.user_overrides().get("general.theme"), Some(&Value::from("light")));
+        assert!(manager.user_overrides().get("editing.default_font_family").is_none());
+
+        manager.set_document_setting("doc", "editing.default_font_size", Value::from(14.0));
+        manager.set_document_setting("doc", "editing.default_font_size", Value::from("huge"));
+        let doc_settings = manager.settings_for_document("doc");
+        assert_eq!(doc_settings.editing.default_font_family, "Calibri");
+        assert_eq!(doc_settings.editing.default_font_size, 12.0);
+        assert_eq!(manager.layer_of("general.theme", Some("doc")), SettingsLayer::User);
+        assert_eq!(manager.layer_of("editing.default_font_family", None), SettingsLayer::Machine);
+
+        {
+            let changes = changes.lock().unwrap();
+            assert_eq!(changes.len(), 3);
+            assert_eq!(changes[0].changed, vec!["general.theme".to_string()]);
+            assert_eq!(changes[1].document_id.as_deref(), Some("doc"));
+        }
+        assert!(manager.unsubscribe(id));
+
+        // The bundle carries the profile to another machine
+        let path = temp_dir.path().join("profile.json");
+        manager.export_bundle_sync(&path).unwrap();
+        let other_dir = TempDir::new().unwrap();
+        let mut other = SettingsManager::new(other_dir.path().to_path_buf());
+        let imported = other.import_bundle_file_sync(&path).unwrap();
+        assert_eq!(imported.general.theme, Theme::Light);
+        assert_eq!(imported.editing.default_font_family, "Calibri");
+    }
 }
@@ -0,0 +1,56 @@
+//! Digital Signatures Public API
+
+use super::error::{SignatureError, SignatureResult};
+use super::identity::SigningIdentity;
+use super::native::{sign_native_json, verify_native_json};
+use super::ooxml::{sign_docx_bytes, verify_docx_bytes};
+use super::report::SignatureReport;
+use std::path::Path;
+
+/// Signable file kinds, by extension
+enum SignedFormat {
+    Native,
+    Docx,
+}
+
+fn signed_format(path: &Path) -> SignatureResult<SignedFormat> {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        crate::FILE_EXTENSION => Ok(SignedFormat::Native),
+        "docx" => Ok(SignedFormat::Docx),
+        _ => Err(SignatureError::Unsupported(format!(
+            "can't sign .{} files",
+            extension
+        ))),
+    }
+}
+
+/// Sign a saved native or DOCX document in place
+pub fn sign_document(path: impl AsRef<Path>, identity: &SigningIdentity) -> SignatureResult<()> {
+    let path = path.as_ref();
+    match signed_format(path)? {
+        SignedFormat::Native => {
+            let json = std::fs::read_to_string(path)?;
+            std::fs::write(path, sign_native_json(&json, identity)?)?;
+        }
+        SignedFormat::Docx => {
+            let data = std::fs::read(path)?;
+            std::fs::write(path, sign_docx_bytes(&data, identity)?)?;
+        }
+    }
+    Ok(())
+}
+
+/// Verify the signatures of a native or DOCX document
+///
+/// Returns no reports for an unsigned document.
+pub fn verify_signatures(path: impl AsRef<Path>) -> SignatureResult<Vec<SignatureReport>> {
+    let path = path.as_ref();
+    match signed_format(path)? {
+        SignedFormat::Native => verify_native_json(&std::fs::read_to_string(path)?),
+        SignedFormat::Docx => verify_docx_bytes(&std::fs::read(path)?),
+    }
+}
@@ -0,0 +1,48 @@
+//! Digital signature errors
+
+use thiserror::Error;
+
+/// Error type for signing and verifying documents
+#[derive(Debug, Error)]
+pub enum SignatureError {
+    /// IO error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// ZIP archive error
+    #[error("ZIP error: {0}")]
+    Zip(#[from] zip::result::ZipError),
+
+    /// JSON error
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    /// XML parsing error
+    #[error("XML parsing error: {0}")]
+    Xml(String),
+
+    /// The certificate couldn't be read
+    #[error("Invalid certificate: {0}")]
+    Certificate(String),
+
+    /// The private key couldn't be read or doesn't match the certificate
+    #[error("Invalid private key: {0}")]
+    Key(String),
+
+    /// Signing failed
+    #[error("Signing failed: {0}")]
+    Signing(String),
+
+    /// The file can't be signed
+    #[error("Unsupported file: {0}")]
+    Unsupported(String),
+}
+
+impl From<quick_xml::Error> for SignatureError {
+    fn from(err: quick_xml::Error) -> Self {
+        SignatureError::Xml(err.to_string())
+    }
+}
+
+/// Result type for signing and verifying documents
+pub type SignatureResult<T> = std::result::Result<T, SignatureError>;
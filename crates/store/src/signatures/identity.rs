@@ -0,0 +1,171 @@
+//! Signing certificates and keys
+
+use super::error::{SignatureError, SignatureResult};
+use super::report::{SignatureReport, SignatureStatus};
+use rsa::pkcs1::DecodeRsaPrivateKey;
+use rsa::pkcs1v15::{Signature, SigningKey, VerifyingKey};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use rsa::signature::{SignatureEncoding, Signer, Verifier};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use x509_cert::der::{Decode, DecodePem, Encode};
+use x509_cert::Certificate;
+
+/// Details of a signer's certificate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateInfo {
+    /// Subject distinguished name
+    pub subject: String,
+    /// Issuer distinguished name
+    pub issuer: String,
+    /// Serial number (hex)
+    pub serial_number: String,
+    /// Start of the validity period (Unix timestamp in seconds)
+    pub not_before: u64,
+    /// End of the validity period (Unix timestamp in seconds)
+    pub not_after: u64,
+}
+
+impl CertificateInfo {
+    /// Check if the certificate is within its validity period now
+    pub fn is_current(&self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        (self.not_before..=self.not_after).contains(&now)
+    }
+
+    /// Check if the certificate is signed by its own subject
+    pub fn is_self_signed(&self) -> bool {
+        self.subject == self.issuer
+    }
+}
+
+/// A certificate with its private key, used to sign documents
+pub struct SigningIdentity {
+    certificate_der: Vec<u8>,
+    info: CertificateInfo,
+    key: RsaPrivateKey,
+}
+
+impl SigningIdentity {
+    /// Load a PEM certificate and its RSA private key (PKCS#8 or PKCS#1 PEM)
+    pub fn from_pem(certificate_pem: &str, private_key_pem: &str) -> SignatureResult<Self> {
+        let certificate = Certificate::from_pem(certificate_pem)
+            .map_err(|e| SignatureError::Certificate(e.to_string()))?;
+        let certificate_der = certificate
+            .to_der()
+            .map_err(|e| SignatureError::Certificate(e.to_string()))?;
+        let (info, public_key) = parse_certificate(&certificate_der)?;
+
+        let key = RsaPrivateKey::from_pkcs8_pem(private_key_pem)
+            .or_else(|_| RsaPrivateKey::from_pkcs1_pem(private_key_pem))
+            .map_err(|e| SignatureError::Key(e.to_string()))?;
+        if RsaPublicKey::from(&key) != public_key {
+            return Err(SignatureError::Key(
+                "the key doesn't belong to the certificate".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            certificate_der,
+            info,
+            key,
+        })
+    }
+
+    /// Load a PEM certificate and private key from files
+    pub fn from_pem_files(
+        certificate_path: impl AsRef<Path>,
+        private_key_path: impl AsRef<Path>,
+    ) -> SignatureResult<Self> {
+        let certificate = std::fs::read_to_string(certificate_path)?;
+        let key = std::fs::read_to_string(private_key_path)?;
+        Self::from_pem(&certificate, &key)
+    }
+
+    /// Details of the certificate
+    pub fn info(&self) -> &CertificateInfo {
+        &self.info
+    }
+
+    /// The certificate in DER encoding
+    pub fn certificate_der(&self) -> &[u8] {
+        &self.certificate_der
+    }
+
+    /// Sign a message with RSA PKCS#1 v1.5 and SHA-256
+    pub(crate) fn sign(&self, message: &[u8]) -> SignatureResult<Vec<u8>> {
+        let signing_key = SigningKey::<Sha256>::new(self.key.clone());
+        signing_key
+            .try_sign(message)
+            .map(|signature| signature.to_vec())
+            .map_err(|e| SignatureError::Signing(e.to_string()))
+    }
+}
+
+/// Read the details and RSA public key of a DER certificate
+pub(crate) fn parse_certificate(der: &[u8]) -> SignatureResult<(CertificateInfo, RsaPublicKey)> {
+    let certificate =
+        Certificate::from_der(der).map_err(|e| SignatureError::Certificate(e.to_string()))?;
+    let tbs = &certificate.tbs_certificate;
+    let info = CertificateInfo {
+        subject: tbs.subject.to_string(),
+        issuer: tbs.issuer.to_string(),
+        serial_number: tbs
+            .serial_number
+            .as_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+        not_before: tbs.validity.not_before.to_unix_duration().as_secs(),
+        not_after: tbs.validity.not_after.to_unix_duration().as_secs(),
+    };
+
+    let spki = tbs
+        .subject_public_key_info
+        .to_der()
+        .map_err(|e| SignatureError::Certificate(e.to_string()))?;
+    let public_key = RsaPublicKey::from_public_key_der(&spki)
+        .map_err(|e| SignatureError::Certificate(format!("not an RSA certificate: {}", e)))?;
+    Ok((info, public_key))
+}
+
+/// Check a signature over a message
+pub(crate) fn verify_signature(public_key: &RsaPublicKey, message: &[u8], signature: &[u8]) -> bool {
+    let Ok(signature) = Signature::try_from(signature) else {
+        return false;
+    };
+    VerifyingKey::<Sha256>::new(public_key.clone())
+        .verify(message, &signature)
+        .is_ok()
+}
+
+/// Report for a signature whose signed data checked out
+pub(crate) fn verified_report(info: CertificateInfo, signed_at: Option<String>) -> SignatureReport {
+    let (status, detail) = if info.is_current() {
+        (SignatureStatus::Valid, None)
+    } else {
+        (
+            SignatureStatus::CertificateExpired,
+            Some("the certificate is outside its validity period".to_string()),
+        )
+    };
+    SignatureReport {
+        status,
+        signer: Some(info),
+        signed_at,
+        detail,
+    }
+}
+
+/// SHA-256 digest of data
+pub(crate) fn sha256(data: &[u8]) -> Vec<u8> {
+    use sha2::Digest;
+    Sha256::digest(data).to_vec()
+}
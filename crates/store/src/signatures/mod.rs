@@ -0,0 +1,33 @@
+//! Digital Signatures Module
+//!
+//! This module signs documents with a user's certificate and verifies the
+//! signatures when a document is opened.
+//!
+//! # Architecture
+//!
+//! - `error`: Signature error types
+//! - `identity`: Signing certificates and private keys (RSA, PEM)
+//! - `report`: Verification results
+//! - `native`: Signatures embedded in the native format
+//! - `ooxml`: OOXML package signatures (`_xmlsignatures/` parts) for DOCX
+//! - `api`: Public API for signing and verifying files by path
+//!
+//! Signatures use RSA PKCS#1 v1.5 with SHA-256. Certificates are checked
+//! for their validity period; chains aren't checked against trusted roots.
+
+mod api;
+mod error;
+mod identity;
+mod native;
+mod ooxml;
+mod report;
+
+pub use api::*;
+pub use error::{SignatureError, SignatureResult};
+pub use identity::{CertificateInfo, SigningIdentity};
+pub use native::{sign_native_json, verify_native_json, NativeSignature};
+pub use ooxml::{sign_docx_bytes, verify_docx_bytes};
+pub use report::{SignatureReport, SignatureStatus};
+
+#[cfg(test)]
+mod tests;
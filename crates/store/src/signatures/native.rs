@@ -0,0 +1,98 @@
+//! Signatures in the native format
+//!
+//! Each signature covers the SHA-256 digest of the document part (as
+//! compact JSON) and the signing time. Signatures are kept next to the
+//! document in the file, so adding one doesn't change what earlier ones
+//! signed. Saving the document again writes it without signatures.
+
+use super::error::{SignatureError, SignatureResult};
+use super::identity::{parse_certificate, sha256, verified_report, verify_signature, SigningIdentity};
+use super::report::{SignatureReport, SignatureStatus};
+use crate::serializer::RawDocumentFile;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A signature stored in a native document file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NativeSignature {
+    /// Signer's certificate (base64 DER)
+    pub certificate: String,
+    /// When the document was signed (RFC 3339)
+    pub signed_at: String,
+    /// SHA-256 digest of the document part (base64)
+    pub digest: String,
+    /// RSA signature over the digest and signing time (base64)
+    pub signature: String,
+}
+
+/// Digest of the document part of a native file
+fn document_digest(document: &Value) -> SignatureResult<String> {
+    Ok(STANDARD.encode(sha256(&serde_json::to_vec(document)?)))
+}
+
+/// The bytes a signature is made over
+fn signed_message(digest: &str, signed_at: &str) -> Vec<u8> {
+    format!("{}\n{}", digest, signed_at).into_bytes()
+}
+
+fn parse_file(json: &str) -> SignatureResult<RawDocumentFile> {
+    serde_json::from_str(json)
+        .map_err(|e| SignatureError::Unsupported(format!("not a native document: {}", e)))
+}
+
+/// Add a signature to a serialized native document
+pub fn sign_native_json(json: &str, identity: &SigningIdentity) -> SignatureResult<String> {
+    let mut file = parse_file(json)?;
+    let digest = document_digest(&file.document)?;
+    let signed_at = chrono::Utc::now().to_rfc3339();
+    let signature = identity.sign(&signed_message(&digest, &signed_at))?;
+    file.signatures.push(NativeSignature {
+        certificate: STANDARD.encode(identity.certificate_der()),
+        signed_at,
+        digest,
+        signature: STANDARD.encode(signature),
+    });
+    Ok(serde_json::to_string_pretty(&file)?)
+}
+
+/// Verify the signatures of a serialized native document
+pub fn verify_native_json(json: &str) -> SignatureResult<Vec<SignatureReport>> {
+    let file = parse_file(json)?;
+    let digest = document_digest(&file.document)?;
+    Ok(file
+        .signatures
+        .iter()
+        .map(|signature| verify_one(signature, &digest))
+        .collect())
+}
+
+fn verify_one(signature: &NativeSignature, digest: &str) -> SignatureReport {
+    let certificate = match STANDARD.decode(&signature.certificate) {
+        Ok(der) => parse_certificate(&der),
+        Err(e) => Err(SignatureError::Certificate(e.to_string())),
+    };
+    let (info, public_key) = match certificate {
+        Ok(parsed) => parsed,
+        Err(e) => return SignatureReport::failed(SignatureStatus::Malformed, e.to_string()),
+    };
+    let failed = |status, detail: &str| SignatureReport {
+        status,
+        signer: Some(info.clone()),
+        signed_at: Some(signature.signed_at.clone()),
+        detail: Some(detail.to_string()),
+    };
+
+    let Ok(value) = STANDARD.decode(&signature.signature) else {
+        return failed(SignatureStatus::Malformed, "the signature isn't valid base64");
+    };
+    let message = signed_message(&signature.digest, &signature.signed_at);
+    if !verify_signature(&public_key, &message, &value) {
+        return failed(SignatureStatus::InvalidSignature, "the signature doesn't match");
+    }
+    if signature.digest != digest {
+        return failed(SignatureStatus::ContentChanged, "the document changed after it was signed");
+    }
+    verified_report(info, Some(signature.signed_at.clone()))
+}
@@ -0,0 +1,675 @@
+//! OOXML package signatures
+//!
+//! Signs a DOCX package the way Office does: a signature part under
+//! `_xmlsignatures/` holds an XML-DSig signature whose `Object` lists a
+//! SHA-256 digest of every signed package part, and whose `SignedInfo`
+//! covers the canonical form of that `Object`. The root relationships point
+//! at `_xmlsignatures/origin.sigs`, whose relationships point at each
+//! signature part.
+//!
+//! Relationship parts are digested as stored rather than through the OPC
+//! relationship transform. When verifying signatures made by other
+//! applications, references that use a transform are skipped.
+
+use super::error::{SignatureError, SignatureResult};
+use super::identity::{parse_certificate, sha256, verified_report, verify_signature, SigningIdentity};
+use super::report::{SignatureReport, SignatureStatus};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Cursor, Read, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const CONTENT_TYPES_PART: &str = "[Content_Types].xml";
+const ROOT_RELS_PART: &str = "_rels/.rels";
+const SIGNATURES_DIR: &str = "_xmlsignatures/";
+const ORIGIN_PART: &str = "_xmlsignatures/origin.sigs";
+const ORIGIN_RELS_PART: &str = "_xmlsignatures/_rels/origin.sigs.rels";
+
+const REL_ORIGIN: &str =
+    "http://schemas.openxmlformats.org/package/2006/relationships/digital-signature/origin";
+const REL_SIGNATURE: &str =
+    "http://schemas.openxmlformats.org/package/2006/relationships/digital-signature/signature";
+const CT_ORIGIN: &str = "application/vnd.openxmlformats-package.digital-signature-origin";
+const CT_SIGNATURE: &str =
+    "application/vnd.openxmlformats-package.digital-signature-xmlsignature+xml";
+const RELS_NS: &str = "http://schemas.openxmlformats.org/package/2006/relationships";
+
+const XMLDSIG_NS: &str = "http://www.w3.org/2000/09/xmldsig#";
+const MDSSI_NS: &str = "http://schemas.openxmlformats.org/package/2006/digital-signature";
+const C14N_ALGORITHM: &str = "http://www.w3.org/TR/2001/REC-xml-c14n-20010315";
+const RSA_SHA256_ALGORITHM: &str = "http://www.w3.org/2001/04/xmldsig-more#rsa-sha256";
+const SHA256_ALGORITHM: &str = "http://www.w3.org/2001/04/xmlenc#sha256";
+const OBJECT_TYPE: &str = "http://www.w3.org/2000/09/xmldsig#Object";
+const PACKAGE_OBJECT_ID: &str = "idPackageObject";
+
+/// Add a signature to a DOCX package
+pub fn sign_docx_bytes(data: &[u8], identity: &SigningIdentity) -> SignatureResult<Vec<u8>> {
+    let mut package = Package::read(data)?;
+    if package.get(CONTENT_TYPES_PART).is_none() {
+        return Err(SignatureError::Unsupported("not an OOXML package".to_string()));
+    }
+    let number = (1..)
+        .find(|n| package.get(&signature_part_name(*n)).is_none())
+        .unwrap_or(1);
+    let signature_part = signature_part_name(number);
+
+    // Written before digesting, so the root relationships stay as signed
+    add_signature_parts(&mut package, number)?;
+
+    let content_types = ContentTypes::parse(package.get(CONTENT_TYPES_PART).unwrap_or_default())?;
+    let mut manifest = String::new();
+    for (name, data) in &package.parts {
+        if !is_signed_part(name) {
+            continue;
+        }
+        manifest.push_str(&format!(
+            r#"<Reference URI="/{}?ContentType={}"><DigestMethod Algorithm="{}"></DigestMethod><DigestValue>{}</DigestValue></Reference>"#,
+            escape(name.as_str()),
+            escape(content_types.of(name)),
+            SHA256_ALGORITHM,
+            STANDARD.encode(sha256(data)),
+        ));
+    }
+
+    let signed_at = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let object = format!(
+        r##"<Object Id="{PACKAGE_OBJECT_ID}"><Manifest>{manifest}</Manifest><SignatureProperties><SignatureProperty Id="idSignatureTime" Target="#idPackageSignature"><mdssi:SignatureTime xmlns:mdssi="{MDSSI_NS}"><mdssi:Format>YYYY-MM-DDThh:mm:ssTZD</mdssi:Format><mdssi:Value>{signed_at}</mdssi:Value></mdssi:SignatureTime></SignatureProperty></SignatureProperties></Object>"##
+    );
+    let object_digest = canonical_digest(&object, ElementMatch::Id(PACKAGE_OBJECT_ID))?;
+    let signed_info = format!(
+        r##"<SignedInfo><CanonicalizationMethod Algorithm="{C14N_ALGORITHM}"></CanonicalizationMethod><SignatureMethod Algorithm="{RSA_SHA256_ALGORITHM}"></SignatureMethod><Reference Type="{OBJECT_TYPE}" URI="#{PACKAGE_OBJECT_ID}"><DigestMethod Algorithm="{SHA256_ALGORITHM}"></DigestMethod><DigestValue>{}</DigestValue></Reference></SignedInfo>"##,
+        STANDARD.encode(object_digest)
+    );
+    let canonical_signed_info = canonical_element(&in_signature(&signed_info), ElementMatch::Name("SignedInfo"))?
+        .ok_or_else(|| SignatureError::Xml("SignedInfo not found".to_string()))?;
+    let signature_value = identity.sign(canonical_signed_info.as_bytes())?;
+
+    let signature_xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Signature xmlns="{XMLDSIG_NS}" Id="idPackageSignature">{signed_info}<SignatureValue>{}</SignatureValue><KeyInfo><X509Data><X509Certificate>{}</X509Certificate></X509Data></KeyInfo>{object}</Signature>"#,
+        STANDARD.encode(signature_value),
+        STANDARD.encode(identity.certificate_der()),
+    );
+    package.set(&signature_part, signature_xml.into_bytes());
+    package.write()
+}
+
+/// Verify the signatures of a DOCX package
+///
+/// Returns no reports for an unsigned package.
+pub fn verify_docx_bytes(data: &[u8]) -> SignatureResult<Vec<SignatureReport>> {
+    let package = Package::read(data)?;
+    let Some(origin_rels) = package.get(ORIGIN_RELS_PART) else {
+        return Ok(Vec::new());
+    };
+
+    let mut reports = Vec::new();
+    for (rel_type, target) in relationships(origin_rels)? {
+        if rel_type != REL_SIGNATURE {
+            continue;
+        }
+        let part = match target.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => format!("{}{}", SIGNATURES_DIR, target),
+        };
+        reports.push(match package.get(&part) {
+            Some(xml) => verify_signature_part(&String::from_utf8_lossy(xml), &package),
+            None => SignatureReport::failed(
+                SignatureStatus::Malformed,
+                format!("signature part {} is missing", part),
+            ),
+        });
+    }
+    Ok(reports)
+}
+
+fn signature_part_name(number: u32) -> String {
+    format!("{}sig{}.xml", SIGNATURES_DIR, number)
+}
+
+fn is_signed_part(name: &str) -> bool {
+    name != CONTENT_TYPES_PART && !name.starts_with(SIGNATURES_DIR)
+}
+
+/// Add the relationships, content types and origin part for a new signature
+fn add_signature_parts(package: &mut Package, number: u32) -> SignatureResult<()> {
+    let root_rels = package
+        .get_string(ROOT_RELS_PART)
+        .unwrap_or_else(empty_relationships);
+    if !root_rels.contains(REL_ORIGIN) {
+        let relationship = format!(
+            r#"<Relationship Id="{}" Type="{}" Target="{}"/>"#,
+            unused_relationship_id(&root_rels),
+            REL_ORIGIN,
+            ORIGIN_PART
+        );
+        package.set(ROOT_RELS_PART, insert_before(&root_rels, "</Relationships>", &relationship)?.into_bytes());
+    }
+    if package.get(ORIGIN_PART).is_none() {
+        package.set(ORIGIN_PART, Vec::new());
+    }
+
+    let origin_rels = package
+        .get_string(ORIGIN_RELS_PART)
+        .unwrap_or_else(empty_relationships);
+    let relationship = format!(
+        r#"<Relationship Id="{}" Type="{}" Target="sig{}.xml"/>"#,
+        unused_relationship_id(&origin_rels),
+        REL_SIGNATURE,
+        number
+    );
+    package.set(ORIGIN_RELS_PART, insert_before(&origin_rels, "</Relationships>", &relationship)?.into_bytes());
+
+    let mut content_types = package.get_string(CONTENT_TYPES_PART).unwrap_or_default();
+    if !content_types.contains(CT_ORIGIN) {
+        let default = format!(r#"<Default Extension="sigs" ContentType="{}"/>"#, CT_ORIGIN);
+        content_types = insert_before(&content_types, "</Types>", &default)?;
+    }
+    let override_part = format!(
+        r#"<Override PartName="/{}" ContentType="{}"/>"#,
+        signature_part_name(number),
+        CT_SIGNATURE
+    );
+    content_types = insert_before(&content_types, "</Types>", &override_part)?;
+    package.set(CONTENT_TYPES_PART, content_types.into_bytes());
+    Ok(())
+}
+
+fn empty_relationships() -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="{}"></Relationships>"#,
+        RELS_NS
+    )
+}
+
+fn unused_relationship_id(rels: &str) -> String {
+    (1..)
+        .map(|n| format!("rId{}", n))
+        .find(|id| !rels.contains(&format!(r#"Id="{}""#, id)))
+        .unwrap_or_default()
+}
+
+fn insert_before(xml: &str, closing_tag: &str, element: &str) -> SignatureResult<String> {
+    let index = xml
+        .rfind(closing_tag)
+        .ok_or_else(|| SignatureError::Xml(format!("{} not found", closing_tag)))?;
+    Ok(format!("{}{}{}", &xml[..index], element, &xml[index..]))
+}
+
+/// Check one signature part against the package
+fn verify_signature_part(xml: &str, package: &Package) -> SignatureReport {
+    let parsed = match ParsedSignature::parse(xml) {
+        Ok(parsed) => parsed,
+        Err(e) => return SignatureReport::failed(SignatureStatus::Malformed, e.to_string()),
+    };
+    let certificate = STANDARD
+        .decode(strip_whitespace(&parsed.certificate))
+        .map_err(|e| SignatureError::Certificate(e.to_string()))
+        .and_then(|der| parse_certificate(&der));
+    let (info, public_key) = match certificate {
+        Ok(parsed) => parsed,
+        Err(e) => return SignatureReport::failed(SignatureStatus::Malformed, e.to_string()),
+    };
+    let failed = |status, detail: String| SignatureReport {
+        status,
+        signer: Some(info.clone()),
+        signed_at: parsed.signed_at.clone(),
+        detail: Some(detail),
+    };
+
+    if parsed.signature_method.as_deref() != Some(RSA_SHA256_ALGORITHM) {
+        return failed(
+            SignatureStatus::Malformed,
+            format!("unsupported signature algorithm {:?}", parsed.signature_method),
+        );
+    }
+    let signed_info = match canonical_element(xml, ElementMatch::Name("SignedInfo")) {
+        Ok(Some(signed_info)) => signed_info,
+        _ => return failed(SignatureStatus::Malformed, "SignedInfo not found".to_string()),
+    };
+    let signature_value = STANDARD.decode(strip_whitespace(&parsed.signature_value)).unwrap_or_default();
+    if !verify_signature(&public_key, signed_info.as_bytes(), &signature_value) {
+        return failed(SignatureStatus::InvalidSignature, "the signature doesn't match".to_string());
+    }
+
+    for reference in &parsed.signed_info_references {
+        let Some(id) = reference.uri.strip_prefix('#') else {
+            continue;
+        };
+        let digest = match canonical_element(xml, ElementMatch::Id(id)) {
+            Ok(Some(element)) => STANDARD.encode(sha256(element.as_bytes())),
+            _ => return failed(SignatureStatus::Malformed, format!("signed object {} not found", id)),
+        };
+        if digest != strip_whitespace(&reference.digest) {
+            return failed(SignatureStatus::ContentChanged, format!("signed object {} changed", id));
+        }
+    }
+
+    let mut unchecked = 0;
+    for reference in &parsed.manifest_references {
+        if reference.transformed {
+            unchecked += 1;
+            continue;
+        }
+        let part = reference.uri.trim_start_matches('/');
+        let part = part.split('?').next().unwrap_or(part);
+        let Some(data) = package.get(part) else {
+            return failed(SignatureStatus::ContentChanged, format!("part {} was removed", part));
+        };
+        if STANDARD.encode(sha256(data)) != strip_whitespace(&reference.digest) {
+            return failed(SignatureStatus::ContentChanged, format!("part {} changed", part));
+        }
+    }
+
+    let mut report = verified_report(info, parsed.signed_at.clone());
+    if report.detail.is_none() && unchecked > 0 {
+        report.detail = Some(format!("{} transformed parts weren't checked", unchecked));
+    }
+    report
+}
+
+fn strip_whitespace(text: &str) -> String {
+    text.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// A `Reference` of a signature
+#[derive(Debug, Default)]
+struct ParsedReference {
+    uri: String,
+    digest: String,
+    transformed: bool,
+}
+
+/// The parts of an XML-DSig signature needed to verify it
+#[derive(Debug, Default)]
+struct ParsedSignature {
+    signature_method: Option<String>,
+    signature_value: String,
+    certificate: String,
+    signed_at: Option<String>,
+    signed_info_references: Vec<ParsedReference>,
+    manifest_references: Vec<ParsedReference>,
+}
+
+impl ParsedSignature {
+    fn parse(xml: &str) -> SignatureResult<Self> {
+        let mut reader = Reader::from_str(xml);
+        let mut parsed = Self::default();
+        let mut stack: Vec<String> = Vec::new();
+        let mut reference: Option<(ParsedReference, bool)> = None;
+        let mut certificates = 0;
+
+        loop {
+            let event = reader.read_event()?;
+            match &event {
+                Event::Start(e) | Event::Empty(e) => {
+                    let name = local_name(e);
+                    match name.as_str() {
+                        "Reference" => {
+                            let in_signed_info = stack.iter().any(|n| n == "SignedInfo");
+                            let uri = attribute(e, "URI")?.unwrap_or_default();
+                            reference = Some((ParsedReference { uri, ..Default::default() }, in_signed_info));
+                        }
+                        "Transforms" => {
+                            if let Some((reference, _)) = &mut reference {
+                                reference.transformed = true;
+                            }
+                        }
+                        "SignatureMethod" => parsed.signature_method = attribute(e, "Algorithm")?,
+                        "X509Certificate" => certificates += 1,
+                        _ => {}
+                    }
+                    if matches!(event, Event::Start(_)) {
+                        stack.push(name);
+                    }
+                }
+                Event::Text(t) => {
+                    let text = t.unescape()?;
+                    let parent = stack.len().checked_sub(2).map(|i| stack[i].as_str());
+                    match stack.last().map(String::as_str) {
+                        Some("DigestValue") => {
+                            if let Some((reference, _)) = &mut reference {
+                                reference.digest.push_str(&text);
+                            }
+                        }
+                        Some("SignatureValue") => parsed.signature_value.push_str(&text),
+                        Some("X509Certificate") if certificates == 1 => parsed.certificate.push_str(&text),
+                        Some("Value") if parent == Some("SignatureTime") => {
+                            parsed.signed_at = Some(text.trim().to_string())
+                        }
+                        _ => {}
+                    }
+                }
+                Event::End(_) => {
+                    if stack.pop().as_deref() != Some("Reference") {
+                        continue;
+                    }
+                    if let Some((reference, in_signed_info)) = reference.take() {
+                        if in_signed_info {
+                            parsed.signed_info_references.push(reference);
+                        } else {
+                            parsed.manifest_references.push(reference);
+                        }
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Which element to canonicalize
+#[derive(Debug, Clone, Copy)]
+enum ElementMatch<'a> {
+    /// The first element with this local name
+    Name(&'a str),
+    /// The element with this `Id` attribute
+    Id(&'a str),
+}
+
+/// Wrap a fragment in the signature element, so it canonicalizes with the
+/// namespace it inherits in the signature part
+fn in_signature(fragment: &str) -> String {
+    format!(r#"<Signature xmlns="{}">{}</Signature>"#, XMLDSIG_NS, fragment)
+}
+
+fn canonical_digest(fragment: &str, target: ElementMatch) -> SignatureResult<Vec<u8>> {
+    let canonical = canonical_element(&in_signature(fragment), target)?
+        .ok_or_else(|| SignatureError::Xml("signed element not found".to_string()))?;
+    Ok(sha256(canonical.as_bytes()))
+}
+
+/// Canonical XML (C14N 1.0, without comments) of an element
+fn canonical_element(xml: &str, target: ElementMatch) -> SignatureResult<Option<String>> {
+    let mut reader = Reader::from_str(xml);
+    // Namespace declarations of the open elements before the target
+    let mut scopes: Vec<Vec<(String, String)>> = Vec::new();
+    // Namespaces rendered on each open element of the output
+    let mut rendered: Vec<BTreeMap<String, String>> = Vec::new();
+    let mut out = String::new();
+
+    loop {
+        let event = reader.read_event()?;
+        match &event {
+            Event::Start(e) | Event::Empty(e) => {
+                let empty = matches!(event, Event::Empty(_));
+                let (declarations, attributes) = split_attributes(e)?;
+                let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+
+                if rendered.is_empty() {
+                    let found = match target {
+                        ElementMatch::Name(wanted) => local_name(e) == wanted,
+                        ElementMatch::Id(wanted) => attributes.iter().any(|(k, v)| k == "Id" && v == wanted),
+                    };
+                    if !found {
+                        if !empty {
+                            scopes.push(declarations);
+                        }
+                        continue;
+                    }
+                    // The apex element carries every namespace in scope
+                    let mut in_scope: BTreeMap<String, String> = scopes.iter().flatten().cloned().collect();
+                    in_scope.extend(declarations);
+                    in_scope.retain(|prefix, uri| !(prefix.is_empty() && uri.is_empty()));
+                    write_start_tag(&mut out, &name, &in_scope, &in_scope, &attributes);
+                    if empty {
+                        out.push_str(&format!("</{}>", name));
+                        return Ok(Some(out));
+                    }
+                    rendered.push(in_scope);
+                } else {
+                    let parent = rendered.last().cloned().unwrap_or_default();
+                    let mut context = parent.clone();
+                    let mut new = BTreeMap::new();
+                    for (prefix, uri) in declarations {
+                        let redundant = match parent.get(&prefix) {
+                            Some(existing) => *existing == uri,
+                            None => prefix.is_empty() && uri.is_empty(),
+                        };
+                        if !redundant {
+                            context.insert(prefix.clone(), uri.clone());
+                            new.insert(prefix, uri);
+                        }
+                    }
+                    write_start_tag(&mut out, &name, &new, &context, &attributes);
+                    if empty {
+                        out.push_str(&format!("</{}>", name));
+                    } else {
+                        rendered.push(context);
+                    }
+                }
+            }
+            Event::End(e) => {
+                if rendered.is_empty() {
+                    scopes.pop();
+                    continue;
+                }
+                out.push_str(&format!("</{}>", String::from_utf8_lossy(e.name().as_ref())));
+                rendered.pop();
+                if rendered.is_empty() {
+                    return Ok(Some(out));
+                }
+            }
+            Event::Text(t) if !rendered.is_empty() => out.push_str(&escape_text(&t.unescape()?)),
+            Event::CData(c) if !rendered.is_empty() => {
+                out.push_str(&escape_text(&String::from_utf8_lossy(c.as_ref())))
+            }
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+/// Write a start tag with namespaces first, then attributes in C14N order
+fn write_start_tag(
+    out: &mut String,
+    name: &str,
+    namespaces: &BTreeMap<String, String>,
+    context: &BTreeMap<String, String>,
+    attributes: &[(String, String)],
+) {
+    out.push('<');
+    out.push_str(name);
+    for (prefix, uri) in namespaces {
+        if prefix.is_empty() {
+            out.push_str(&format!(r#" xmlns="{}""#, escape_attribute(uri)));
+        } else {
+            out.push_str(&format!(r#" xmlns:{}="{}""#, prefix, escape_attribute(uri)));
+        }
+    }
+    // Unqualified attributes first, then by namespace URI and local name
+    let mut sorted: Vec<(String, &str, &(String, String))> = attributes
+        .iter()
+        .map(|attribute| match attribute.0.split_once(':') {
+            Some((prefix, local)) => (context.get(prefix).cloned().unwrap_or_default(), local, attribute),
+            None => (String::new(), attribute.0.as_str(), attribute),
+        })
+        .collect();
+    sorted.sort_by(|a, b| (&a.0, a.1).cmp(&(&b.0, b.1)));
+    for (_, _, (key, value)) in sorted {
+        out.push_str(&format!(r#" {}="{}""#, key, escape_attribute(value)));
+    }
+    out.push('>');
+}
+
+/// Namespace declarations (prefix, URI) and other attributes of an element
+#[allow(clippy::type_complexity)]
+fn split_attributes(e: &BytesStart) -> SignatureResult<(Vec<(String, String)>, Vec<(String, String)>)> {
+    let mut declarations = Vec::new();
+    let mut attributes = Vec::new();
+    for attribute in e.attributes() {
+        let attribute = attribute.map_err(|e| SignatureError::Xml(e.to_string()))?;
+        let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+        let value = attribute.unescape_value()?.into_owned();
+        if key == "xmlns" {
+            declarations.push((String::new(), value));
+        } else if let Some(prefix) = key.strip_prefix("xmlns:") {
+            declarations.push((prefix.to_string(), value));
+        } else {
+            attributes.push((key, value));
+        }
+    }
+    Ok((declarations, attributes))
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\r', "&#xD;")
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('"', "&quot;")
+        .replace('\t', "&#x9;")
+        .replace('\n', "&#xA;")
+        .replace('\r', "&#xD;")
+}
+
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).into_owned()
+}
+
+fn attribute(e: &BytesStart, name: &str) -> SignatureResult<Option<String>> {
+    for attribute in e.attributes() {
+        let attribute = attribute.map_err(|e| SignatureError::Xml(e.to_string()))?;
+        if attribute.key.local_name().as_ref() == name.as_bytes() {
+            return Ok(Some(attribute.unescape_value()?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// (type, target) of each relationship in a relationships part
+fn relationships(xml: &[u8]) -> SignatureResult<Vec<(String, String)>> {
+    let mut reader = Reader::from_reader(xml);
+    let mut buf = Vec::new();
+    let mut relationships = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) | Event::Empty(e) if local_name(&e) == "Relationship" => {
+                if let (Some(rel_type), Some(target)) = (attribute(&e, "Type")?, attribute(&e, "Target")?) {
+                    relationships.push((rel_type, target));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(relationships)
+}
+
+/// Content types of a package's parts
+struct ContentTypes {
+    /// By lowercase extension
+    defaults: HashMap<String, String>,
+    /// By lowercase part name, without the leading slash
+    overrides: HashMap<String, String>,
+}
+
+impl ContentTypes {
+    fn parse(xml: &[u8]) -> SignatureResult<Self> {
+        let mut content_types = Self {
+            defaults: HashMap::new(),
+            overrides: HashMap::new(),
+        };
+        let mut reader = Reader::from_reader(xml);
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(e) | Event::Empty(e) => {
+                    let content_type = attribute(&e, "ContentType")?.unwrap_or_default();
+                    match local_name(&e).as_str() {
+                        "Default" => {
+                            if let Some(extension) = attribute(&e, "Extension")? {
+                                content_types.defaults.insert(extension.to_lowercase(), content_type);
+                            }
+                        }
+                        "Override" => {
+                            if let Some(part) = attribute(&e, "PartName")? {
+                                let part = part.trim_start_matches('/').to_lowercase();
+                                content_types.overrides.insert(part, content_type);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+        Ok(content_types)
+    }
+
+    fn of(&self, part: &str) -> &str {
+        let part = part.to_lowercase();
+        let extension = part.rsplit_once('.').map(|(_, ext)| ext).unwrap_or_default();
+        self.overrides
+            .get(&part)
+            .or_else(|| self.defaults.get(extension))
+            .map(String::as_str)
+            .unwrap_or("application/octet-stream")
+    }
+}
+
+/// Parts of a ZIP package, in archive order
+struct Package {
+    parts: Vec<(String, Vec<u8>)>,
+}
+
+impl Package {
+    fn read(data: &[u8]) -> SignatureResult<Self> {
+        let mut archive = ZipArchive::new(Cursor::new(data))?;
+        let mut parts = Vec::with_capacity(archive.len());
+        for index in 0..archive.len() {
+            let mut file = archive.by_index(index)?;
+            if file.is_dir() {
+                continue;
+            }
+            let mut content = Vec::new();
+            file.read_to_end(&mut content)?;
+            parts.push((file.name().to_string(), content));
+        }
+        Ok(Self { parts })
+    }
+
+    fn get(&self, name: &str) -> Option<&[u8]> {
+        self.parts
+            .iter()
+            .find(|(part, _)| part == name)
+            .map(|(_, data)| data.as_slice())
+    }
+
+    fn get_string(&self, name: &str) -> Option<String> {
+        self.get(name).map(|data| String::from_utf8_lossy(data).into_owned())
+    }
+
+    fn set(&mut self, name: &str, data: Vec<u8>) {
+        match self.parts.iter_mut().find(|(part, _)| part == name) {
+            Some(part) => part.1 = data,
+            None => self.parts.push((name.to_string(), data)),
+        }
+    }
+
+    fn write(&self) -> SignatureResult<Vec<u8>> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        for (name, data) in &self.parts {
+            zip.start_file(name.as_str(), options)?;
+            zip.write_all(data)?;
+        }
+        Ok(zip.finish()?.into_inner())
+    }
+}
@@ -0,0 +1,52 @@
+//! Signature verification results
+
+use super::identity::CertificateInfo;
+use serde::{Deserialize, Serialize};
+
+/// Outcome of verifying a signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SignatureStatus {
+    /// The signature is intact and its certificate is current
+    Valid,
+    /// The signature is intact but its certificate has expired or isn't
+    /// valid yet
+    CertificateExpired,
+    /// The document changed after it was signed
+    ContentChanged,
+    /// The signature doesn't match the signed data or certificate
+    InvalidSignature,
+    /// The signature couldn't be read or uses an unsupported algorithm
+    Malformed,
+}
+
+/// A signature found in a document and whether it holds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureReport {
+    /// Verification outcome
+    pub status: SignatureStatus,
+    /// Signer's certificate, if it could be read
+    pub signer: Option<CertificateInfo>,
+    /// When the document was signed, as recorded by the signer
+    pub signed_at: Option<String>,
+    /// Why the signature isn't valid
+    pub detail: Option<String>,
+}
+
+impl SignatureReport {
+    /// Report for a signature that couldn't be checked
+    pub(crate) fn failed(status: SignatureStatus, detail: impl Into<String>) -> Self {
+        Self {
+            status,
+            signer: None,
+            signed_at: None,
+            detail: Some(detail.into()),
+        }
+    }
+
+    /// Check if the signature is valid
+    pub fn is_valid(&self) -> bool {
+        self.status == SignatureStatus::Valid
+    }
+}
@@ -0,0 +1,228 @@
+//! Text-to-speech
+//!
+//! Speaks text through the platform speech synthesizer for Read Aloud:
+//! `say` on macOS, `espeak-ng` on Linux and System.Speech (via PowerShell)
+//! on Windows. Each utterance runs as a child process so it can be stopped
+//! at any time. These synthesizers don't report word boundaries, so callers
+//! estimate progress from the speaking rate.
+
+use serde::{Deserialize, Serialize};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use thiserror::Error;
+
+/// Default speaking rate in words per minute
+pub const DEFAULT_WORDS_PER_MINUTE: u32 = 180;
+
+/// Error type for speech
+#[derive(Debug, Error)]
+pub enum SpeechError {
+    /// The platform's speech synthesizer couldn't be started
+    #[error("Speech synthesizer unavailable: {0}")]
+    Unavailable(String),
+
+    /// The synthesizer failed
+    #[error("Speech synthesizer error: {0}")]
+    System(String),
+}
+
+/// Result type for speech
+pub type Result<T> = std::result::Result<T, SpeechError>;
+
+/// Voice and rate for speaking
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeechSettings {
+    /// Speaking rate in words per minute
+    pub words_per_minute: u32,
+    /// Voice name, or None for the system default
+    pub voice: Option<String>,
+}
+
+impl Default for SpeechSettings {
+    fn default() -> Self {
+        Self {
+            words_per_minute: DEFAULT_WORDS_PER_MINUTE,
+            voice: None,
+        }
+    }
+}
+
+impl SpeechSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the speaking rate
+    pub fn with_words_per_minute(mut self, words_per_minute: u32) -> Self {
+        self.words_per_minute = words_per_minute.clamp(80, 450);
+        self
+    }
+
+    /// Set the voice
+    pub fn with_voice(mut self, voice: impl Into<String>) -> Self {
+        self.voice = Some(voice.into());
+        self
+    }
+}
+
+/// A platform speech synthesizer
+pub trait SpeechBackend: Send + Sync {
+    /// Name of the synthesizer
+    fn name(&self) -> &'static str;
+
+    /// Start speaking text, replacing anything being spoken
+    fn speak(&self, text: &str, settings: &SpeechSettings) -> Result<()>;
+
+    /// Check if an utterance is still being spoken
+    fn is_speaking(&self) -> bool;
+
+    /// Stop speaking
+    fn stop(&self) -> Result<()>;
+}
+
+/// Speech backend that runs the platform's command-line synthesizer
+#[derive(Debug, Default)]
+pub struct CommandSpeechBackend {
+    child: Mutex<Option<Child>>,
+}
+
+impl CommandSpeechBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build the synthesizer command for an utterance
+    #[cfg(target_os = "macos")]
+    fn command(text: &str, settings: &SpeechSettings) -> Command {
+        let mut command = Command::new("say");
+        command.arg("-r").arg(settings.words_per_minute.to_string());
+        if let Some(voice) = &settings.voice {
+            command.arg("-v").arg(voice);
+        }
+        command.arg("--").arg(text);
+        command
+    }
+
+    /// Build the synthesizer command for an utterance
+    #[cfg(windows)]
+    fn command(text: &str, settings: &SpeechSettings) -> Command {
+        // System.Speech rates run from -10 to 10 with 0 at about 180 wpm
+        let rate = ((settings.words_per_minute as i32 - 180) / 20).clamp(-10, 10);
+        let mut script = format!(
+            "Add-Type -AssemblyName System.Speech; \
+             $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; $s.Rate = {};",
+            rate
+        );
+        if settings.voice.is_some() {
+            script.push_str(" $s.SelectVoice($env:GO_WORD_SPEECH_VOICE);");
+        }
+        script.push_str(" $s.Speak($env:GO_WORD_SPEECH_TEXT)");
+
+        let mut command = Command::new("powershell");
+        command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+        command.env("GO_WORD_SPEECH_TEXT", text);
+        if let Some(voice) = &settings.voice {
+            command.env("GO_WORD_SPEECH_VOICE", voice);
+        }
+        command
+    }
+
+    /// Build the synthesizer command for an utterance
+    #[cfg(not(any(target_os = "macos", windows)))]
+    fn command(text: &str, settings: &SpeechSettings) -> Command {
+        let mut command = Command::new("espeak-ng");
+        command.arg("-s").arg(settings.words_per_minute.to_string());
+        if let Some(voice) = &settings.voice {
+            command.arg("-v").arg(voice);
+        }
+        command.arg("--").arg(text);
+        command
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Option<Child>>> {
+        self.child
+            .lock()
+            .map_err(|e| SpeechError::System(format!("Failed to acquire speech lock: {}", e)))
+    }
+}
+
+impl SpeechBackend for CommandSpeechBackend {
+    fn name(&self) -> &'static str {
+        if cfg!(target_os = "macos") {
+            "say"
+        } else if cfg!(windows) {
+            "System.Speech"
+        } else {
+            "espeak-ng"
+        }
+    }
+
+    fn speak(&self, text: &str, settings: &SpeechSettings) -> Result<()> {
+        self.stop()?;
+        let child = Self::command(text, settings)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => SpeechError::Unavailable(format!("{} not found", self.name())),
+                _ => SpeechError::System(e.to_string()),
+            })?;
+        *self.lock()? = Some(child);
+        Ok(())
+    }
+
+    fn is_speaking(&self) -> bool {
+        let Ok(mut guard) = self.lock() else {
+            return false;
+        };
+        let running = guard
+            .as_mut()
+            .is_some_and(|child| matches!(child.try_wait(), Ok(None)));
+        if !running {
+            *guard = None;
+        }
+        running
+    }
+
+    fn stop(&self) -> Result<()> {
+        if let Some(mut child) = self.lock()?.take() {
+            // The process may have just exited on its own
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        Ok(())
+    }
+}
+
+impl Drop for CommandSpeechBackend {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+/// Get the speech backend for this platform
+pub fn default_backend() -> Box<dyn SpeechBackend> {
+    Box::new(CommandSpeechBackend::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speech_command_uses_rate_and_voice() {
+        let settings = SpeechSettings::new().with_words_per_minute(1000).with_voice("en");
+        assert_eq!(settings.words_per_minute, 450);
+
+        let command = CommandSpeechBackend::command("Hello -there", &settings);
+        let args: Vec<String> = command.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+        if cfg!(windows) {
+            assert!(args.iter().any(|a| a.contains("$s.Rate = 10")));
+        } else {
+            let rate_flag = if cfg!(target_os = "macos") { "-r" } else { "-s" };
+            assert_eq!(args, [rate_flag, "450", "-v", "en", "--", "Hello -there"]);
+        }
+    }
+}
@@ -0,0 +1,612 @@
+//! SVG rasterization
+//!
+//! SVG images are stored as vectors and drawn into a bitmap on demand at
+//! whatever resolution the caller needs. This covers the static subset of
+//! SVG that documents use for icons and diagrams: shapes, paths, groups,
+//! transforms, solid fills and strokes. Gradients, text, clipping and
+//! filters are not drawn.
+
+use crate::image_codec::RgbaImage;
+use crate::image_store::{ImageStoreError, Result};
+use crate::raster::{Path, Pixmap, Transform};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use render_model::Color;
+
+/// CSS pixels per inch (SVG user units without a viewBox)
+const CSS_DPI: f64 = 96.0;
+
+/// Intrinsic size of an SVG in CSS pixels, from width/height or the viewBox
+pub(crate) fn svg_size(data: &[u8]) -> Option<(f64, f64)> {
+    let text = std::str::from_utf8(data).ok()?;
+    let mut reader = Reader::from_str(text);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if local_name(&e) == "svg" => {
+                return Some(Viewport::from_root(&e).size);
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+    }
+}
+
+/// Draw an SVG into a `width` x `height` pixel bitmap
+pub(crate) fn rasterize_svg(data: &[u8], width: u32, height: u32) -> Result<RgbaImage> {
+    let text = std::str::from_utf8(data).map_err(|e| ImageStoreError::DecodeError(e.to_string()))?;
+    let mut pixmap = Pixmap::new(width, height).map_err(|e| ImageStoreError::DecodeError(e.to_string()))?;
+
+    let mut reader = Reader::from_str(text);
+    let mut stack: Vec<Style> = Vec::new();
+    // Depth of an element whose content isn't drawn (defs, clipPath, ...)
+    let mut skip_depth = 0usize;
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| ImageStoreError::DecodeError(e.to_string()))?;
+        let (element, is_empty) = match event {
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::End(_) => {
+                if skip_depth > 0 {
+                    skip_depth -= 1;
+                } else {
+                    stack.pop();
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        let name = local_name(&element);
+        if skip_depth > 0 || matches!(name.as_str(), "defs" | "clipPath" | "mask" | "symbol" | "title" | "desc" | "style") {
+            if !is_empty {
+                skip_depth += 1;
+            }
+            continue;
+        }
+
+        let style = match stack.last() {
+            Some(parent) => parent.inherit(&element),
+            None if name == "svg" => {
+                let viewport = Viewport::from_root(&element);
+                Style::root(viewport.transform(width, height)).inherit(&element)
+            }
+            // Not an SVG document
+            None => break,
+        };
+        draw_element(&mut pixmap, &name, &element, &style);
+        if !is_empty {
+            stack.push(style);
+        }
+    }
+
+    Ok(RgbaImage {
+        width,
+        height,
+        data: pixmap.data().to_vec(),
+    })
+}
+
+/// Size and coordinate system of the root element
+struct Viewport {
+    size: (f64, f64),
+    view_box: Option<(f64, f64, f64, f64)>,
+}
+
+impl Viewport {
+    fn from_root(e: &BytesStart) -> Self {
+        let view_box = attr(e, "viewBox").and_then(|v| {
+            let n = parse_numbers(&v);
+            (n.len() == 4 && n[2] > 0.0 && n[3] > 0.0).then(|| (n[0], n[1], n[2], n[3]))
+        });
+        let width = attr(e, "width").and_then(|v| parse_length(&v));
+        let height = attr(e, "height").and_then(|v| parse_length(&v));
+        let size = match (width, height, view_box) {
+            (Some(w), Some(h), _) => (w, h),
+            (Some(w), None, Some(vb)) => (w, w * vb.3 / vb.2),
+            (None, Some(h), Some(vb)) => (h * vb.2 / vb.3, h),
+            (None, None, Some(vb)) => (vb.2, vb.3),
+            _ => (300.0, 150.0),
+        };
+        Self { size, view_box }
+    }
+
+    /// Map user units to output pixels (uniformly scaled and centered, like
+    /// the default preserveAspectRatio)
+    fn transform(&self, width: u32, height: u32) -> Transform {
+        let (x, y, w, h) = self.view_box.unwrap_or((0.0, 0.0, self.size.0, self.size.1));
+        let scale = (width as f64 / w).min(height as f64 / h);
+        let dx = (width as f64 - w * scale) / 2.0;
+        let dy = (height as f64 - h * scale) / 2.0;
+        Transform::translate(-x, -y)
+            .then(&Transform::scale(scale, scale))
+            .then(&Transform::translate(dx, dy))
+    }
+}
+
+/// Paint applied to a shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Paint {
+    None,
+    Color(Color),
+}
+
+/// Presentation attributes, inherited down the element tree
+#[derive(Debug, Clone)]
+struct Style {
+    transform: Transform,
+    fill: Paint,
+    stroke: Paint,
+    stroke_width: f64,
+    opacity: f64,
+    fill_opacity: f64,
+    stroke_opacity: f64,
+    visible: bool,
+}
+
+impl Style {
+    fn root(transform: Transform) -> Self {
+        Self {
+            transform,
+            fill: Paint::Color(Color::BLACK),
+            stroke: Paint::None,
+            stroke_width: 1.0,
+            opacity: 1.0,
+            fill_opacity: 1.0,
+            stroke_opacity: 1.0,
+            visible: true,
+        }
+    }
+
+    /// The style of a child element
+    fn inherit(&self, e: &BytesStart) -> Self {
+        let mut style = self.clone();
+        if let Some(transform) = attr(e, "transform") {
+            style.transform = parse_transform(&transform).then(&self.transform);
+        }
+
+        // Presentation attributes, then the style attribute which wins
+        let mut declarations: Vec<(String, String)> = ["fill", "stroke", "stroke-width", "opacity", "fill-opacity", "stroke-opacity", "display", "visibility"]
+            .iter()
+            .filter_map(|name| attr(e, name).map(|value| (name.to_string(), value)))
+            .collect();
+        if let Some(css) = attr(e, "style") {
+            declarations.extend(css.split(';').filter_map(|declaration| {
+                let (name, value) = declaration.split_once(':')?;
+                Some((name.trim().to_string(), value.trim().to_string()))
+            }));
+        }
+
+        for (name, value) in declarations {
+            match name.as_str() {
+                "fill" => style.fill = parse_paint(&value).unwrap_or(style.fill),
+                "stroke" => style.stroke = parse_paint(&value).unwrap_or(style.stroke),
+                "stroke-width" => style.stroke_width = parse_length(&value).unwrap_or(style.stroke_width),
+                // Group opacity is approximated by multiplying into children
+                "opacity" => style.opacity *= parse_opacity(&value),
+                "fill-opacity" => style.fill_opacity = parse_opacity(&value),
+                "stroke-opacity" => style.stroke_opacity = parse_opacity(&value),
+                "display" => style.visible &= value != "none",
+                "visibility" => style.visible = value != "hidden" && value != "collapse",
+                _ => {}
+            }
+        }
+        style
+    }
+
+    fn color(paint: Paint, opacity: f64) -> Option<Color> {
+        match paint {
+            Paint::None => None,
+            Paint::Color(color) => Some(Color::rgba(
+                color.r,
+                color.g,
+                color.b,
+                (color.a as f64 * opacity.clamp(0.0, 1.0)).round() as u8,
+            )),
+        }
+    }
+}
+
+/// Fill and stroke one shape element
+fn draw_element(pixmap: &mut Pixmap, name: &str, e: &BytesStart, style: &Style) {
+    if !style.visible {
+        return;
+    }
+    let number = |key: &str| attr(e, key).and_then(|v| parse_length(&v)).unwrap_or(0.0);
+    let t = &style.transform;
+    let mut path = Path::new();
+    match name {
+        "rect" => {
+            let (x, y, w, h) = (number("x"), number("y"), number("width"), number("height"));
+            if w > 0.0 && h > 0.0 {
+                path.rect(x, y, w, h, t);
+            }
+        }
+        "circle" => {
+            let r = number("r");
+            if r > 0.0 {
+                path.ellipse(number("cx") - r, number("cy") - r, r * 2.0, r * 2.0, t);
+            }
+        }
+        "ellipse" => {
+            let (rx, ry) = (number("rx"), number("ry"));
+            if rx > 0.0 && ry > 0.0 {
+                path.ellipse(number("cx") - rx, number("cy") - ry, rx * 2.0, ry * 2.0, t);
+            }
+        }
+        "line" => {
+            path.move_to(t.apply(number("x1"), number("y1")));
+            path.line_to(t.apply(number("x2"), number("y2")));
+        }
+        "polyline" | "polygon" => {
+            let points = parse_numbers(&attr(e, "points").unwrap_or_default());
+            for (i, pair) in points.chunks_exact(2).enumerate() {
+                let point = t.apply(pair[0], pair[1]);
+                if i == 0 {
+                    path.move_to(point);
+                } else {
+                    path.line_to(point);
+                }
+            }
+            if name == "polygon" {
+                path.close();
+            }
+        }
+        "path" => build_path(&mut path, &attr(e, "d").unwrap_or_default(), t),
+        _ => return,
+    }
+    if path.is_empty() {
+        return;
+    }
+
+    // Lines have no interior
+    if !matches!(name, "line" | "polyline") {
+        if let Some(color) = Style::color(style.fill, style.opacity * style.fill_opacity) {
+            pixmap.fill_path(&path, color, true);
+        }
+    }
+    if let Some(color) = Style::color(style.stroke, style.opacity * style.stroke_opacity) {
+        let width = (style.stroke_width * t.scale_factor()) as f32;
+        if width > 0.0 {
+            pixmap.fill_path(&path.stroke(width, &[]), color, true);
+        }
+    }
+}
+
+/// Build path data ("d" attribute) into a pixel-space path
+fn build_path(path: &mut Path, data: &str, t: &Transform) {
+    let tokens = tokenize_path(data);
+    let mut i = 0;
+    let mut command = 'M';
+    let (mut x, mut y) = (0.0f64, 0.0f64);
+    let (mut start_x, mut start_y) = (0.0f64, 0.0f64);
+    // Reflected control point for smooth curve commands
+    let mut last_control: Option<(f64, f64)> = None;
+
+    while i < tokens.len() {
+        if let PathToken::Command(c) = tokens[i] {
+            command = c;
+            i += 1;
+            if matches!(c, 'Z' | 'z') {
+                path.close();
+                x = start_x;
+                y = start_y;
+                last_control = None;
+                continue;
+            }
+        }
+        let arity = match command.to_ascii_uppercase() {
+            'M' | 'L' | 'T' => 2,
+            'H' | 'V' => 1,
+            'S' | 'Q' => 4,
+            'C' => 6,
+            'A' => 7,
+            _ => return,
+        };
+        let args: Vec<f64> = tokens[i..]
+            .iter()
+            .take(arity)
+            .map_while(|token| match token {
+                PathToken::Number(n) => Some(*n),
+                PathToken::Command(_) => None,
+            })
+            .collect();
+        if args.len() < arity {
+            return;
+        }
+        i += arity;
+
+        let relative = command.is_ascii_lowercase();
+        let (ox, oy) = if relative { (x, y) } else { (0.0, 0.0) };
+        let point = |px: f64, py: f64| (ox + px, oy + py);
+        let mut control = None;
+        match command.to_ascii_uppercase() {
+            'M' => {
+                (x, y) = point(args[0], args[1]);
+                (start_x, start_y) = (x, y);
+                path.move_to(t.apply(x, y));
+                // Further coordinate pairs are implicit line-tos
+                command = if relative { 'l' } else { 'L' };
+            }
+            'L' => {
+                (x, y) = point(args[0], args[1]);
+                path.line_to(t.apply(x, y));
+            }
+            'H' => {
+                x = if relative { x + args[0] } else { args[0] };
+                path.line_to(t.apply(x, y));
+            }
+            'V' => {
+                y = if relative { y + args[0] } else { args[0] };
+                path.line_to(t.apply(x, y));
+            }
+            'C' => {
+                let c1 = point(args[0], args[1]);
+                let c2 = point(args[2], args[3]);
+                (x, y) = point(args[4], args[5]);
+                path.cubic_to(t.apply(c1.0, c1.1), t.apply(c2.0, c2.1), t.apply(x, y));
+                control = Some(c2);
+            }
+            'S' => {
+                let c1 = last_control.map_or((x, y), |(cx, cy)| (2.0 * x - cx, 2.0 * y - cy));
+                let c2 = point(args[0], args[1]);
+                (x, y) = point(args[2], args[3]);
+                path.cubic_to(t.apply(c1.0, c1.1), t.apply(c2.0, c2.1), t.apply(x, y));
+                control = Some(c2);
+            }
+            'Q' => {
+                let c = point(args[0], args[1]);
+                (x, y) = point(args[2], args[3]);
+                path.quad_to(t.apply(c.0, c.1), t.apply(x, y));
+                control = Some(c);
+            }
+            'T' => {
+                let c = last_control.map_or((x, y), |(cx, cy)| (2.0 * x - cx, 2.0 * y - cy));
+                (x, y) = point(args[0], args[1]);
+                path.quad_to(t.apply(c.0, c.1), t.apply(x, y));
+                control = Some(c);
+            }
+            _ => {
+                // Elliptical arc
+                let to = point(args[5], args[6]);
+                arc_to(path, (x, y), args, to, t);
+                (x, y) = to;
+            }
+        }
+        last_control = control;
+    }
+}
+
+/// Add an SVG elliptical arc (endpoint parameterization) as line segments
+fn arc_to(path: &mut Path, from: (f64, f64), args: Vec<f64>, to: (f64, f64), t: &Transform) {
+    let (mut rx, mut ry) = (args[0].abs(), args[1].abs());
+    let (large_arc, sweep) = (args[3] != 0.0, args[4] != 0.0);
+    if rx == 0.0 || ry == 0.0 || from == to {
+        path.line_to(t.apply(to.0, to.1));
+        return;
+    }
+    let (sin, cos) = args[2].to_radians().sin_cos();
+
+    // Center parameterization (SVG implementation notes, F.6.5)
+    let dx = (from.0 - to.0) / 2.0;
+    let dy = (from.1 - to.1) / 2.0;
+    let x1 = cos * dx + sin * dy;
+    let y1 = -sin * dx + cos * dy;
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut factor = (numerator / denominator).max(0.0).sqrt();
+    if large_arc == sweep {
+        factor = -factor;
+    }
+    let cx1 = factor * rx * y1 / ry;
+    let cy1 = -factor * ry * x1 / rx;
+    let cx = cos * cx1 - sin * cy1 + (from.0 + to.0) / 2.0;
+    let cy = sin * cx1 + cos * cy1 + (from.1 + to.1) / 2.0;
+
+    let angle = |ux: f64, uy: f64| uy.atan2(ux);
+    let start = angle((x1 - cx1) / rx, (y1 - cy1) / ry);
+    let mut sweep_angle = angle((-x1 - cx1) / rx, (-y1 - cy1) / ry) - start;
+    if sweep && sweep_angle < 0.0 {
+        sweep_angle += std::f64::consts::TAU;
+    } else if !sweep && sweep_angle > 0.0 {
+        sweep_angle -= std::f64::consts::TAU;
+    }
+
+    let radius_px = rx.max(ry) * t.scale_factor();
+    let steps = ((sweep_angle.abs() * radius_px.sqrt() * 2.0) as usize).clamp(4, 256);
+    for step in 1..=steps {
+        let theta = start + sweep_angle * step as f64 / steps as f64;
+        let (ex, ey) = (rx * theta.cos(), ry * theta.sin());
+        path.line_to(t.apply(cos * ex - sin * ey + cx, sin * ex + cos * ey + cy));
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum PathToken {
+    Command(char),
+    Number(f64),
+}
+
+/// Split path data into commands and numbers
+fn tokenize_path(data: &str) -> Vec<PathToken> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = data.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_ascii_alphabetic() && c != 'e' && c != 'E' {
+            tokens.push(PathToken::Command(c));
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' || c == '-' || c == '+' {
+            let start = i;
+            let mut seen_dot = c == '.';
+            i += 1;
+            while i < chars.len() {
+                let d = chars[i];
+                let exponent_sign = (d == '-' || d == '+') && matches!(chars[i - 1], 'e' | 'E');
+                if d.is_ascii_digit() || d == 'e' || d == 'E' || exponent_sign || (d == '.' && !seen_dot) {
+                    seen_dot |= d == '.';
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            if let Ok(n) = text.parse() {
+                tokens.push(PathToken::Number(n));
+            }
+        } else {
+            i += 1;
+        }
+    }
+    tokens
+}
+
+/// Parse a transform list into a single transform
+fn parse_transform(value: &str) -> Transform {
+    let mut result = Transform::IDENTITY;
+    for part in value.split(')') {
+        let Some((name, args)) = part.split_once('(') else {
+            continue;
+        };
+        let n = parse_numbers(args);
+        let arg = |i: usize, default: f64| n.get(i).copied().unwrap_or(default);
+        let transform = match name.trim().trim_start_matches(',').trim() {
+            "matrix" if n.len() == 6 => Transform { a: n[0], b: n[1], c: n[2], d: n[3], e: n[4], f: n[5] },
+            "translate" => Transform::translate(arg(0, 0.0), arg(1, 0.0)),
+            "scale" => Transform::scale(arg(0, 1.0), arg(1, arg(0, 1.0))),
+            "rotate" => Transform::rotate_around(arg(0, 0.0), arg(1, 0.0), arg(2, 0.0)),
+            "skewX" => Transform { c: arg(0, 0.0).to_radians().tan(), ..Transform::IDENTITY },
+            "skewY" => Transform { b: arg(0, 0.0).to_radians().tan(), ..Transform::IDENTITY },
+            _ => continue,
+        };
+        // Later transforms in the list apply first
+        result = transform.then(&result);
+    }
+    result
+}
+
+/// Parse a list of numbers separated by commas and/or whitespace
+fn parse_numbers(value: &str) -> Vec<f64> {
+    tokenize_path(value)
+        .into_iter()
+        .filter_map(|token| match token {
+            PathToken::Number(n) => Some(n),
+            PathToken::Command(_) => None,
+        })
+        .collect()
+}
+
+/// Parse a length in CSS pixels
+fn parse_length(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+' || c == 'e'))
+        .unwrap_or(value.len());
+    let number: f64 = value[..split].parse().ok()?;
+    let scale = match value[split..].trim() {
+        "" | "px" => 1.0,
+        "pt" => CSS_DPI / 72.0,
+        "pc" => CSS_DPI / 6.0,
+        "in" => CSS_DPI,
+        "cm" => CSS_DPI / 2.54,
+        "mm" => CSS_DPI / 25.4,
+        // Percentages need a reference size we don't track
+        _ => return None,
+    };
+    Some(number * scale)
+}
+
+fn parse_opacity(value: &str) -> f64 {
+    match value.trim().strip_suffix('%') {
+        Some(percent) => percent.parse::<f64>().map_or(1.0, |p| p / 100.0),
+        None => value.trim().parse().unwrap_or(1.0),
+    }
+    .clamp(0.0, 1.0)
+}
+
+/// Parse a fill or stroke value; None leaves the inherited paint
+fn parse_paint(value: &str) -> Option<Paint> {
+    let value = value.trim().to_ascii_lowercase();
+    if value == "none" || value == "transparent" {
+        return Some(Paint::None);
+    }
+    if value == "inherit" {
+        return None;
+    }
+    if let Some(hex) = value.strip_prefix('#') {
+        let expand = |c: char| c.to_digit(16).map(|d| (d * 17) as u8);
+        let color = match hex.len() {
+            3 => {
+                let mut digits = hex.chars().map(expand);
+                Color::rgb(digits.next()??, digits.next()??, digits.next()??)
+            }
+            6 => Color::rgb(
+                u8::from_str_radix(&hex[0..2], 16).ok()?,
+                u8::from_str_radix(&hex[2..4], 16).ok()?,
+                u8::from_str_radix(&hex[4..6], 16).ok()?,
+            ),
+            _ => return None,
+        };
+        return Some(Paint::Color(color));
+    }
+    if let Some(args) = value.strip_prefix("rgb(").and_then(|v| v.strip_suffix(')')) {
+        let channels: Vec<u8> = args
+            .split(',')
+            .map(|c| {
+                let c = c.trim();
+                match c.strip_suffix('%') {
+                    Some(p) => p.parse::<f64>().map_or(0, |p| (p * 2.55).round() as u8),
+                    None => c.parse::<f64>().map_or(0, |v| v.clamp(0.0, 255.0) as u8),
+                }
+            })
+            .collect();
+        return (channels.len() == 3).then(|| Paint::Color(Color::rgb(channels[0], channels[1], channels[2])));
+    }
+    // Gradients and patterns (url(...)) aren't supported; draw them gray so
+    // the shape is still visible
+    if value.starts_with("url(") {
+        return Some(Paint::Color(Color::rgb(128, 128, 128)));
+    }
+    let (r, g, b) = match value.as_str() {
+        "black" | "currentcolor" => (0, 0, 0),
+        "white" => (255, 255, 255),
+        "red" => (255, 0, 0),
+        "lime" => (0, 255, 0),
+        "green" => (0, 128, 0),
+        "blue" => (0, 0, 255),
+        "yellow" => (255, 255, 0),
+        "cyan" | "aqua" => (0, 255, 255),
+        "magenta" | "fuchsia" => (255, 0, 255),
+        "gray" | "grey" => (128, 128, 128),
+        "silver" => (192, 192, 192),
+        "maroon" => (128, 0, 0),
+        "olive" => (128, 128, 0),
+        "purple" => (128, 0, 128),
+        "teal" => (0, 128, 128),
+        "navy" => (0, 0, 128),
+        "orange" => (255, 165, 0),
+        _ => return None,
+    };
+    Some(Paint::Color(Color::rgb(r, g, b)))
+}
+
+/// Element name without a namespace prefix
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).into_owned()
+}
+
+/// Unescaped attribute value
+fn attr(e: &BytesStart, name: &str) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == name.as_bytes())
+        .and_then(|a| a.unescape_value().ok().map(|v| v.into_owned()))
+}
@@ -37,6 +37,25 @@ pub enum TemplateError {
     #[error("Invalid template ID: {0}")]
     InvalidId(String),
 
+    /// Placeholder values that are missing or invalid
+    #[error("Invalid placeholder values: {}", .0.join("; "))]
+    InvalidPlaceholders(Vec<String>),
+
+    /// Downloaded package doesn't match the gallery checksum
+    #[error("Checksum mismatch for template {template_id}: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        /// Template ID
+        template_id: String,
+        /// Checksum from the gallery index
+        expected: String,
+        /// Checksum of the downloaded data
+        actual: String,
+    },
+
+    /// Remote gallery error
+    #[error("Gallery error: {0}")]
+    Gallery(String),
+
     /// Locked region error
     #[error("Cannot edit locked region: {0}")]
     LockedRegion(String),
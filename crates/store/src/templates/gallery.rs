@@ -0,0 +1,261 @@
+//! Remote template gallery
+//!
+//! The gallery publishes a JSON index of templates grouped by category.
+//! Packages are downloaded on demand and checked against the SHA-256 in
+//! the index before they are installed. Thumbnails and the last index are
+//! cached locally so the gallery can still be listed offline.
+//!
+//! The store crate doesn't do networking itself; the host supplies a
+//! [`GalleryFetcher`] that performs the HTTPS requests.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::{TemplateError, TemplatePackage, TemplateResult, TemplateSummary, TEMPLATE_EXTENSION};
+
+/// File name of the cached index in the cache directory
+pub const GALLERY_INDEX_FILE: &str = "gallery_index.json";
+
+/// Directory for cached thumbnails in the cache directory
+pub const GALLERY_THUMBNAIL_DIR: &str = "thumbnails";
+
+/// Fetches gallery resources over HTTPS
+pub trait GalleryFetcher: Send + Sync {
+    /// Get the body of a URL
+    fn fetch(&self, url: &str) -> TemplateResult<Vec<u8>>;
+}
+
+/// A category in the gallery index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GalleryCategory {
+    /// Category ID referenced by entries
+    pub id: String,
+    /// Display name
+    pub name: String,
+}
+
+/// A template published in the gallery
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GalleryEntry {
+    /// Template ID
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// Short description
+    #[serde(default)]
+    pub description: String,
+    /// Category ID
+    pub category: String,
+    /// Author
+    #[serde(default)]
+    pub author: String,
+    /// Tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// URL of the .wdt package
+    pub package_url: String,
+    /// Hex SHA-256 of the package
+    pub sha256: String,
+    /// URL of the thumbnail image
+    #[serde(default)]
+    pub thumbnail_url: Option<String>,
+}
+
+impl GalleryEntry {
+    /// Summary for template listings
+    pub fn to_summary(&self) -> TemplateSummary {
+        TemplateSummary {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            description: self.description.clone(),
+            category: self.category.clone(),
+            author: self.author.clone(),
+            tags: self.tags.clone(),
+            has_thumbnail: self.thumbnail_url.is_some(),
+            preview_text: None,
+            remote: true,
+        }
+    }
+}
+
+/// The gallery index
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GalleryIndex {
+    /// Categories in display order
+    #[serde(default)]
+    pub categories: Vec<GalleryCategory>,
+    /// Published templates
+    #[serde(default)]
+    pub templates: Vec<GalleryEntry>,
+}
+
+impl GalleryIndex {
+    /// Find an entry by template ID
+    pub fn get(&self, template_id: &str) -> Option<&GalleryEntry> {
+        self.templates.iter().find(|t| t.id == template_id)
+    }
+
+    /// Entries in a category
+    pub fn in_category<'a>(&'a self, category: &'a str) -> impl Iterator<Item = &'a GalleryEntry> {
+        self.templates.iter().filter(move |t| t.category == category)
+    }
+}
+
+/// Client for a remote template gallery
+pub struct RemoteGallery {
+    index_url: String,
+    cache_dir: PathBuf,
+    fetcher: Arc<dyn GalleryFetcher>,
+    index: Option<GalleryIndex>,
+}
+
+impl fmt::Debug for RemoteGallery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteGallery")
+            .field("index_url", &self.index_url)
+            .field("cache_dir", &self.cache_dir)
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+impl RemoteGallery {
+    /// Create a client for the index at `index_url`, caching under `cache_dir`
+    pub fn new(
+        index_url: impl Into<String>,
+        cache_dir: impl Into<PathBuf>,
+        fetcher: Arc<dyn GalleryFetcher>,
+    ) -> Self {
+        Self {
+            index_url: index_url.into(),
+            cache_dir: cache_dir.into(),
+            fetcher,
+            index: None,
+        }
+    }
+
+    /// The index URL
+    pub fn index_url(&self) -> &str {
+        &self.index_url
+    }
+
+    /// The loaded index, if any
+    pub fn index(&self) -> Option<&GalleryIndex> {
+        self.index.as_ref()
+    }
+
+    /// Fetch the index, falling back to the cached copy if the fetch fails
+    pub fn refresh(&mut self) -> TemplateResult<&GalleryIndex> {
+        let cache_path = self.cache_dir.join(GALLERY_INDEX_FILE);
+        let index = match self.fetch_https(&self.index_url) {
+            Ok(bytes) => {
+                let index: GalleryIndex = serde_json::from_slice(&bytes)?;
+                fs::create_dir_all(&self.cache_dir)?;
+                fs::write(&cache_path, &bytes)?;
+                index
+            }
+            Err(e) => match fs::read(&cache_path) {
+                Ok(bytes) => serde_json::from_slice(&bytes)?,
+                Err(_) => return Err(e),
+            },
+        };
+        Ok(self.index.insert(index))
+    }
+
+    /// Summaries of all gallery templates, loading the index if needed
+    pub fn summaries(&mut self) -> TemplateResult<Vec<TemplateSummary>> {
+        if self.index.is_none() {
+            self.refresh()?;
+        }
+        Ok(self
+            .index
+            .iter()
+            .flat_map(|index| index.templates.iter().map(GalleryEntry::to_summary))
+            .collect())
+    }
+
+    /// Look up a template in the loaded index
+    pub fn entry(&self, template_id: &str) -> TemplateResult<&GalleryEntry> {
+        self.index
+            .as_ref()
+            .and_then(|index| index.get(template_id))
+            .ok_or_else(|| TemplateError::NotFound(template_id.to_string()))
+    }
+
+    /// Download a package into `templates_dir` after verifying its checksum
+    ///
+    /// Returns the path of the installed package.
+    pub fn download(&self, template_id: &str, templates_dir: &Path) -> TemplateResult<PathBuf> {
+        let entry = self.entry(template_id)?;
+        validate_template_id(&entry.id)?;
+
+        let bytes = self.fetch_https(&entry.package_url)?;
+        let actual = sha256_hex(&bytes);
+        if !actual.eq_ignore_ascii_case(&entry.sha256) {
+            return Err(TemplateError::ChecksumMismatch {
+                template_id: entry.id.clone(),
+                expected: entry.sha256.clone(),
+                actual,
+            });
+        }
+
+        let package = TemplatePackage::read_from_reader(Cursor::new(&bytes))?;
+        if package.metadata.id != entry.id {
+            return Err(TemplateError::InvalidFormat(format!(
+                "package ID {} does not match gallery entry {}",
+                package.metadata.id, entry.id
+            )));
+        }
+
+        fs::create_dir_all(templates_dir)?;
+        let path = templates_dir.join(format!("{}.{}", entry.id, TEMPLATE_EXTENSION));
+        fs::write(&path, &bytes)?;
+        Ok(path)
+    }
+
+    /// Get a template's thumbnail, downloading it into the cache if needed
+    pub fn thumbnail(&self, template_id: &str) -> TemplateResult<Option<Vec<u8>>> {
+        validate_template_id(template_id)?;
+        let path = self.cache_dir.join(GALLERY_THUMBNAIL_DIR).join(format!("{}.png", template_id));
+        if let Ok(bytes) = fs::read(&path) {
+            return Ok(Some(bytes));
+        }
+
+        let Some(url) = self.entry(template_id)?.thumbnail_url.as_deref() else {
+            return Ok(None);
+        };
+        let bytes = self.fetch_https(url)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, &bytes)?;
+        Ok(Some(bytes))
+    }
+
+    fn fetch_https(&self, url: &str) -> TemplateResult<Vec<u8>> {
+        if !url.starts_with("https://") {
+            return Err(TemplateError::Gallery(format!("refusing non-HTTPS URL: {}", url)));
+        }
+        self.fetcher.fetch(url)
+    }
+}
+
+/// Reject IDs that can't be used as file names
+fn validate_template_id(template_id: &str) -> TemplateResult<()> {
+    if template_id.is_empty()
+        || template_id.starts_with('.')
+        || template_id.contains(['/', '\\', ':'])
+    {
+        return Err(TemplateError::InvalidId(template_id.to_string()));
+    }
+    Ok(())
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -1,8 +1,8 @@
 //! Template manager for CRUD operations on templates
 
 use super::{
-    read_metadata, read_thumbnail, validate_package, LockedRegion, TemplateError, TemplateMetadata,
-    TemplatePackage, TemplateResult, TemplateSummary, TEMPLATE_EXTENSION,
+    read_metadata, read_thumbnail, validate_package, LockedRegion, RemoteGallery, TemplateError,
+    TemplateMetadata, TemplatePackage, TemplateResult, TemplateSummary, TEMPLATE_EXTENSION,
 };
 use doc_model::DocumentTree;
 use std::collections::HashMap;
@@ -16,6 +16,8 @@ pub struct TemplateManager {
     templates_dir: PathBuf,
     /// Cache of template metadata
     metadata_cache: HashMap<String, TemplateMetadata>,
+    /// Remote gallery merged into listings
+    gallery: Option<RemoteGallery>,
 }
 
 impl TemplateManager {
@@ -24,9 +26,31 @@ impl TemplateManager {
         Self {
             templates_dir: templates_dir.into(),
             metadata_cache: HashMap::new(),
+            gallery: None,
         }
     }
 
+    /// Merge a remote gallery into listings and download its templates on demand
+    pub fn with_gallery(mut self, gallery: RemoteGallery) -> Self {
+        self.gallery = Some(gallery);
+        self
+    }
+
+    /// Set or remove the remote gallery
+    pub fn set_gallery(&mut self, gallery: Option<RemoteGallery>) {
+        self.gallery = gallery;
+    }
+
+    /// Get the remote gallery
+    pub fn gallery(&self) -> Option<&RemoteGallery> {
+        self.gallery.as_ref()
+    }
+
+    /// Get the remote gallery mutably, e.g. to refresh its index
+    pub fn gallery_mut(&mut self) -> Option<&mut RemoteGallery> {
+        self.gallery.as_mut()
+    }
+
     /// Ensure the templates directory exists
     pub fn ensure_directory(&self) -> TemplateResult<()> {
         if !self.templates_dir.exists() {
@@ -47,19 +71,45 @@ impl TemplateManager {
     }
 
     /// List all available templates
+    ///
+    /// Gallery templates that aren't installed are included with `remote`
+    /// set. If the gallery can't be reached and has no cached index, only
+    /// local templates are listed.
     pub fn list_templates(&mut self) -> TemplateResult<Vec<TemplateSummary>> {
         self.ensure_directory()?;
         self.refresh_cache()?;
 
-        let summaries = self
+        let mut summaries: Vec<_> = self
             .metadata_cache
             .values()
             .map(TemplateSummary::from)
             .collect();
 
+        if let Some(remote) = self.gallery.as_mut().and_then(|g| g.summaries().ok()) {
+            summaries.extend(
+                remote
+                    .into_iter()
+                    .filter(|s| !self.metadata_cache.contains_key(&s.id)),
+            );
+        }
+
         Ok(summaries)
     }
 
+    /// Download a gallery template into the templates directory
+    pub fn download_template(&mut self, template_id: &str) -> TemplateResult<TemplateMetadata> {
+        let gallery = self
+            .gallery
+            .as_ref()
+            .ok_or_else(|| TemplateError::NotFound(template_id.to_string()))?;
+        let path = gallery.download(template_id, &self.templates_dir)?;
+
+        let metadata = read_metadata(&path)?;
+        self.metadata_cache
+            .insert(template_id.to_string(), metadata.clone());
+        Ok(metadata)
+    }
+
     /// Refresh the metadata cache by scanning the templates directory
     pub fn refresh_cache(&mut self) -> TemplateResult<()> {
         self.ensure_directory()?;
@@ -102,20 +152,31 @@ impl TemplateManager {
     }
 
     /// Load a complete template package
+    ///
+    /// Gallery templates that aren't installed yet are downloaded first.
     pub fn load_template(&self, template_id: &str) -> TemplateResult<TemplatePackage> {
-        let path = self.template_path(template_id);
+        let mut path = self.template_path(template_id);
         if !path.exists() {
-            return Err(TemplateError::NotFound(template_id.to_string()));
+            match &self.gallery {
+                Some(gallery) => path = gallery.download(template_id, &self.templates_dir)?,
+                None => return Err(TemplateError::NotFound(template_id.to_string())),
+            }
         }
 
         TemplatePackage::read_from_file(&path)
     }
 
     /// Get template thumbnail
+    ///
+    /// Thumbnails of gallery templates that aren't installed come from the
+    /// gallery's thumbnail cache.
     pub fn get_thumbnail(&self, template_id: &str) -> TemplateResult<Option<Vec<u8>>> {
         let path = self.template_path(template_id);
         if !path.exists() {
-            return Err(TemplateError::NotFound(template_id.to_string()));
+            return match &self.gallery {
+                Some(gallery) => gallery.thumbnail(template_id),
+                None => Err(TemplateError::NotFound(template_id.to_string())),
+            };
         }
 
         read_thumbnail(&path)
@@ -127,6 +188,15 @@ impl TemplateManager {
         Ok(package.create_document())
     }
 
+    /// Create a new document from a template, filling its placeholders
+    pub fn instantiate_template(
+        &self,
+        template_id: &str,
+        values: &HashMap<String, String>,
+    ) -> TemplateResult<DocumentTree> {
+        self.load_template(template_id)?.instantiate(values)
+    }
+
     /// Save a document as a template
     pub fn save_as_template(
         &mut self,
@@ -3,6 +3,8 @@
 use doc_model::Position;
 use serde::{Deserialize, Serialize};
 
+use super::TemplatePlaceholder;
+
 /// Template categories
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TemplateCategory {
@@ -145,6 +147,9 @@ pub struct TemplateMetadata {
     pub has_thumbnail: bool,
     /// Preview text (first few lines of content)
     pub preview_text: Option<String>,
+    /// Values the user fills in when creating a document
+    #[serde(default)]
+    pub placeholders: Vec<TemplatePlaceholder>,
 }
 
 impl TemplateMetadata {
@@ -163,6 +168,7 @@ impl TemplateMetadata {
             tags: Vec::new(),
             has_thumbnail: false,
             preview_text: None,
+            placeholders: Vec::new(),
         }
     }
 
@@ -214,6 +220,12 @@ impl TemplateMetadata {
         self
     }
 
+    /// Add a placeholder
+    pub fn with_placeholder(mut self, placeholder: TemplatePlaceholder) -> Self {
+        self.placeholders.push(placeholder);
+        self
+    }
+
     /// Update the modified timestamp
     pub fn touch(&mut self) {
         self.modified = Some(Self::now_iso8601());
@@ -267,6 +279,9 @@ pub struct TemplateSummary {
     pub has_thumbnail: bool,
     /// Preview text
     pub preview_text: Option<String>,
+    /// Whether the template is in the remote gallery and not installed
+    #[serde(default)]
+    pub remote: bool,
 }
 
 impl From<&TemplateMetadata> for TemplateSummary {
@@ -280,6 +295,7 @@ impl From<&TemplateMetadata> for TemplateSummary {
             tags: meta.tags.clone(),
             has_thumbnail: meta.has_thumbnail,
             preview_text: meta.preview_text.clone(),
+            remote: false,
         }
     }
 }
@@ -5,11 +5,15 @@
 //! - Template metadata and locked regions
 //! - Template CRUD operations
 //! - Style pack export/import
+//! - Typed placeholders filled in when a document is created
+//! - Remote template gallery with cached downloads
 
 mod metadata;
 mod package;
 mod manager;
 mod style_pack;
+mod placeholder;
+mod gallery;
 mod error;
 
 #[cfg(test)]
@@ -19,4 +23,6 @@ pub use metadata::*;
 pub use package::*;
 pub use manager::*;
 pub use style_pack::*;
+pub use placeholder::*;
+pub use gallery::*;
 pub use error::*;
@@ -4,10 +4,12 @@
 //! - template.json: Template metadata
 //! - document.wdj: Base document in the internal format
 //! - thumbnail.png: Preview image (optional)
+//! - content_controls.json: Content controls placeholders are bound to (optional)
 //! - resources/: Directory for embedded images, fonts, etc.
 
-use super::{TemplateError, TemplateMetadata, TemplateResult};
-use doc_model::DocumentTree;
+use super::{fill_placeholders, TemplateError, TemplateMetadata, TemplateResult};
+use doc_model::{ContentControlRegistry, DocumentTree};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{Read, Write};
 use std::path::Path;
@@ -19,6 +21,7 @@ use zip::ZipWriter;
 pub const METADATA_FILE: &str = "template.json";
 pub const DOCUMENT_FILE: &str = "document.wdj";
 pub const THUMBNAIL_FILE: &str = "thumbnail.png";
+pub const CONTENT_CONTROLS_FILE: &str = "content_controls.json";
 pub const RESOURCES_DIR: &str = "resources/";
 
 /// Template package extension
@@ -35,6 +38,8 @@ pub struct TemplatePackage {
     pub thumbnail: Option<Vec<u8>>,
     /// Embedded resources (filename -> data)
     pub resources: std::collections::HashMap<String, Vec<u8>>,
+    /// Content controls in the document
+    pub content_controls: ContentControlRegistry,
 }
 
 impl TemplatePackage {
@@ -45,9 +50,16 @@ impl TemplatePackage {
             document,
             thumbnail: None,
             resources: std::collections::HashMap::new(),
+            content_controls: ContentControlRegistry::new(),
         }
     }
 
+    /// Set the document's content controls
+    pub fn with_content_controls(mut self, controls: ContentControlRegistry) -> Self {
+        self.content_controls = controls;
+        self
+    }
+
     /// Add a thumbnail image
     pub fn with_thumbnail(mut self, thumbnail: Vec<u8>) -> Self {
         self.thumbnail = Some(thumbnail);
@@ -101,6 +113,16 @@ impl TemplatePackage {
             Err(_) => None,
         };
 
+        // Read content controls (optional)
+        let content_controls = match archive.by_name(CONTENT_CONTROLS_FILE) {
+            Ok(mut file) => {
+                let mut contents = String::new();
+                file.read_to_string(&mut contents)?;
+                serde_json::from_str(&contents)?
+            }
+            Err(_) => ContentControlRegistry::new(),
+        };
+
         // Read resources
         let mut resources = std::collections::HashMap::new();
         for i in 0..archive.len() {
@@ -119,6 +141,7 @@ impl TemplatePackage {
             document,
             thumbnail,
             resources,
+            content_controls,
         })
     }
 
@@ -151,6 +174,12 @@ impl TemplatePackage {
             zip.write_all(thumbnail)?;
         }
 
+        // Write content controls if present
+        if !self.content_controls.is_empty() {
+            zip.start_file(CONTENT_CONTROLS_FILE, options)?;
+            zip.write_all(serde_json::to_string(&self.content_controls)?.as_bytes())?;
+        }
+
         // Write resources
         for (name, data) in &self.resources {
             let resource_path = format!("{}{}", RESOURCES_DIR, name);
@@ -169,6 +198,15 @@ impl TemplatePackage {
         self.document.clone()
     }
 
+    /// Create a document with the placeholders filled from `values` (keyed by tag)
+    ///
+    /// Fails listing every missing or invalid value.
+    pub fn instantiate(&self, values: &HashMap<String, String>) -> TemplateResult<DocumentTree> {
+        let mut document = self.create_document();
+        fill_placeholders(&mut document, &self.content_controls, &self.metadata.placeholders, values)?;
+        Ok(document)
+    }
+
     /// Get the template ID
     pub fn id(&self) -> &str {
         &self.metadata.id
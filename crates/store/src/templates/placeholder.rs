@@ -0,0 +1,167 @@
+//! Typed placeholders the user fills in when creating a document from a template
+//!
+//! Each placeholder is bound to the content controls in the template with
+//! the same tag. Filling it replaces the text inside those controls.
+
+use chrono::NaiveDate;
+use doc_model::{ContentControlRegistry, DocumentTree, Node};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{TemplateError, TemplateResult};
+
+/// Format used to read date values
+pub const PLACEHOLDER_DATE_INPUT: &str = "%Y-%m-%d";
+
+/// The kind of value a placeholder takes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PlaceholderKind {
+    /// Free text
+    Text,
+    /// A date entered as YYYY-MM-DD and written with `format`
+    Date {
+        /// chrono format string for the document text
+        format: String,
+    },
+    /// One of a fixed set of options
+    Dropdown {
+        /// Allowed values
+        options: Vec<String>,
+    },
+}
+
+/// A value the user is prompted for when creating a document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplatePlaceholder {
+    /// Tag of the content controls this placeholder fills
+    pub tag: String,
+    /// Prompt shown to the user
+    pub label: String,
+    /// Value type
+    pub kind: PlaceholderKind,
+    /// Whether a value must be given
+    pub required: bool,
+    /// Value used when none is given
+    pub default_value: Option<String>,
+}
+
+impl TemplatePlaceholder {
+    /// Create an optional placeholder
+    pub fn new(tag: impl Into<String>, label: impl Into<String>, kind: PlaceholderKind) -> Self {
+        Self {
+            tag: tag.into(),
+            label: label.into(),
+            kind,
+            required: false,
+            default_value: None,
+        }
+    }
+
+    /// Create a text placeholder
+    pub fn text(tag: impl Into<String>, label: impl Into<String>) -> Self {
+        Self::new(tag, label, PlaceholderKind::Text)
+    }
+
+    /// Create a date placeholder written like "March 5, 2024"
+    pub fn date(tag: impl Into<String>, label: impl Into<String>) -> Self {
+        Self::new(
+            tag,
+            label,
+            PlaceholderKind::Date {
+                format: "%B %-d, %Y".to_string(),
+            },
+        )
+    }
+
+    /// Create a dropdown placeholder
+    pub fn dropdown(tag: impl Into<String>, label: impl Into<String>, options: Vec<String>) -> Self {
+        Self::new(tag, label, PlaceholderKind::Dropdown { options })
+    }
+
+    /// Require a value
+    pub fn with_required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Set the default value
+    pub fn with_default(mut self, value: impl Into<String>) -> Self {
+        self.default_value = Some(value.into());
+        self
+    }
+
+    /// Check a value and return the text to put in the document
+    ///
+    /// Returns None if the placeholder is optional and has no value.
+    pub fn resolve(&self, value: Option<&str>) -> Result<Option<String>, String> {
+        let value = value
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .or(self.default_value.as_deref());
+        let Some(value) = value else {
+            return if self.required {
+                Err(format!("{} is required", self.label))
+            } else {
+                Ok(None)
+            };
+        };
+
+        match &self.kind {
+            PlaceholderKind::Text => Ok(Some(value.to_string())),
+            PlaceholderKind::Date { format } => NaiveDate::parse_from_str(value, PLACEHOLDER_DATE_INPUT)
+                .map(|date| Some(date.format(format).to_string()))
+                .map_err(|_| format!("{} must be a date (YYYY-MM-DD)", self.label)),
+            PlaceholderKind::Dropdown { options } => {
+                if options.iter().any(|o| o == value) {
+                    Ok(Some(value.to_string()))
+                } else {
+                    Err(format!("{} must be one of: {}", self.label, options.join(", ")))
+                }
+            }
+        }
+    }
+}
+
+/// Fill placeholders in a document from user values keyed by tag
+///
+/// Every placeholder is checked first, and all problems are reported
+/// together. The first run in each bound content control gets the value
+/// and the control's other runs are emptied.
+pub fn fill_placeholders(
+    document: &mut DocumentTree,
+    controls: &ContentControlRegistry,
+    placeholders: &[TemplatePlaceholder],
+    values: &HashMap<String, String>,
+) -> TemplateResult<()> {
+    let mut resolved = Vec::new();
+    let mut errors = Vec::new();
+    for placeholder in placeholders {
+        match placeholder.resolve(values.get(&placeholder.tag).map(String::as_str)) {
+            Ok(Some(text)) => resolved.push((placeholder, text)),
+            Ok(None) => {}
+            Err(e) => errors.push(e),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(TemplateError::InvalidPlaceholders(errors));
+    }
+
+    for (placeholder, text) in resolved {
+        for control in controls.get_by_tag(&placeholder.tag) {
+            let mut runs = control.children().iter().filter(|&&id| document.get_run(id).is_some());
+            if let Some(&first) = runs.next() {
+                let rest: Vec<_> = runs.copied().collect();
+                if let Some(run) = document.get_run_mut(first) {
+                    run.text = text.clone();
+                }
+                for id in rest {
+                    if let Some(run) = document.get_run_mut(id) {
+                        run.text.clear();
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
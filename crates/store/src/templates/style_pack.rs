@@ -1,10 +1,15 @@
 //! Style pack for exporting and importing document styles
+//!
+//! Before importing, [`StylePack::diff`] shows how a pack differs from a
+//! document's styles, and [`StylePack::merge_into`] applies only the chosen
+//! styles so local customizations aren't overwritten.
 
 use doc_model::{
     CharacterProperties, ParagraphProperties, Style, StyleId, StyleRegistry, StyleType,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 
 use super::{TemplateError, TemplateResult};
 
@@ -69,6 +74,152 @@ impl From<&Style> for ExportedStyle {
     }
 }
 
+impl ExportedStyle {
+    /// Parse the style type
+    fn parse_style_type(&self) -> TemplateResult<StyleType> {
+        match self.style_type.as_str() {
+            "paragraph" => Ok(StyleType::Paragraph),
+            "character" => Ok(StyleType::Character),
+            "table" => Ok(StyleType::Table),
+            "numbering" => Ok(StyleType::Numbering),
+            other => Err(TemplateError::InvalidFormat(format!(
+                "Unknown style type: {}",
+                other
+            ))),
+        }
+    }
+
+    /// Create a new style from this one
+    ///
+    /// Returns None for table and numbering styles, which aren't imported yet.
+    fn to_style(&self) -> TemplateResult<Option<Style>> {
+        let mut style = match self.parse_style_type()? {
+            StyleType::Paragraph => Style::paragraph(self.id.as_str(), self.name.as_str()),
+            StyleType::Character => Style::character(self.id.as_str(), self.name.as_str()),
+            _ => return Ok(None),
+        };
+        self.apply_to(&mut style);
+        Ok(Some(style))
+    }
+
+    /// Copy this style's exported properties onto an existing style
+    fn apply_to(&self, style: &mut Style) {
+        style.name = self.name.clone();
+        style.priority = self.priority;
+        style.paragraph_props = self.paragraph_props.clone();
+        style.character_props = self.character_props.clone();
+        style.based_on = self.based_on.as_deref().map(StyleId::new);
+        style.next_style = self.next_style.as_deref().map(StyleId::new);
+    }
+
+    /// Properties that differ from `other`, as dotted paths
+    fn property_diffs(&self, other: &ExportedStyle) -> Vec<PropertyDiff> {
+        let mut current = HashMap::new();
+        let mut incoming = HashMap::new();
+        flatten(&serde_json::to_value(other).unwrap_or(Value::Null), "", &mut current);
+        flatten(&serde_json::to_value(self).unwrap_or(Value::Null), "", &mut incoming);
+
+        let keys: HashSet<&String> = current.keys().chain(incoming.keys()).collect();
+        let mut diffs: Vec<PropertyDiff> = keys
+            .into_iter()
+            .filter(|key| !matches!(key.as_str(), "id" | "built_in"))
+            .filter(|key| current.get(*key) != incoming.get(*key))
+            .map(|key| PropertyDiff {
+                property: key.clone(),
+                current: current.get(key).cloned().unwrap_or(Value::Null),
+                incoming: incoming.get(key).cloned().unwrap_or(Value::Null),
+            })
+            .collect();
+        diffs.sort_by(|a, b| a.property.cmp(&b.property));
+        diffs
+    }
+}
+
+/// Flatten nested objects into dotted paths, leaving out nulls
+fn flatten(value: &Value, prefix: &str, out: &mut HashMap<String, Value>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(value, &path, out);
+            }
+        }
+        Value::Null => {}
+        other => {
+            out.insert(prefix.to_string(), other.clone());
+        }
+    }
+}
+
+/// How a style in a pack compares to the document's style with the same ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StyleChange {
+    /// The document doesn't have the style
+    Added,
+    /// The document's style has different properties
+    Changed,
+    /// The document has a custom style the pack doesn't
+    Removed,
+}
+
+/// A property that differs between the document and a style pack
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertyDiff {
+    /// Dotted property path, e.g. "character_props.bold"
+    pub property: String,
+    /// Value in the document (null if unset)
+    pub current: Value,
+    /// Value in the style pack (null if unset)
+    pub incoming: Value,
+}
+
+/// Differences for one style
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleDiff {
+    /// Style identifier
+    pub style_id: String,
+    /// Display name
+    pub name: String,
+    /// Kind of change
+    pub change: StyleChange,
+    /// Whether the style is built in
+    pub built_in: bool,
+    /// Changed properties (empty for added and removed styles)
+    pub properties: Vec<PropertyDiff>,
+}
+
+/// Differences between a style pack and a document's styles
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StylePackDiff {
+    /// One entry per added, changed or removed style
+    pub styles: Vec<StyleDiff>,
+}
+
+impl StylePackDiff {
+    /// Styles with the given kind of change
+    pub fn with_change(&self, change: StyleChange) -> impl Iterator<Item = &StyleDiff> {
+        self.styles.iter().filter(move |s| s.change == change)
+    }
+
+    /// Get the diff for a style
+    pub fn get(&self, style_id: &str) -> Option<&StyleDiff> {
+        self.styles.iter().find(|s| s.style_id == style_id)
+    }
+
+    /// Check if the pack matches the document
+    pub fn is_empty(&self) -> bool {
+        self.styles.is_empty()
+    }
+}
+
 impl StylePack {
     /// Create a new empty style pack
     pub fn new(name: impl Into<String>) -> Self {
@@ -152,46 +303,104 @@ impl StylePack {
                 continue;
             }
 
-            let style_type = match exported.style_type.as_str() {
-                "paragraph" => StyleType::Paragraph,
-                "character" => StyleType::Character,
-                "table" => StyleType::Table,
-                "numbering" => StyleType::Numbering,
-                other => {
-                    return Err(TemplateError::InvalidFormat(format!(
-                        "Unknown style type: {}",
-                        other
-                    )))
-                }
-            };
+            // Skip table and numbering for now
+            if let Some(style) = exported.to_style()? {
+                registry.register(style);
+                count += 1;
+            }
+        }
 
-            let mut style = match style_type {
-                StyleType::Paragraph => {
-                    Style::paragraph(exported.id.as_str(), exported.name.as_str())
-                }
-                StyleType::Character => {
-                    Style::character(exported.id.as_str(), exported.name.as_str())
-                }
-                _ => continue, // Skip table and numbering for now
-            };
+        Ok(count)
+    }
 
-            style.priority = exported.priority;
-            style.paragraph_props = exported.paragraph_props.clone();
-            style.character_props = exported.character_props.clone();
+    /// Compare the pack against a registry's styles
+    ///
+    /// Custom styles in the registry that the pack lacks are listed as
+    /// removed; merging never deletes them.
+    pub fn diff(&self, registry: &StyleRegistry) -> StylePackDiff {
+        let mut styles = Vec::new();
 
-            if let Some(ref base) = exported.based_on {
-                style.based_on = Some(StyleId::new(base));
+        for exported in &self.styles {
+            match registry.get(&StyleId::new(&exported.id)) {
+                None => styles.push(StyleDiff {
+                    style_id: exported.id.clone(),
+                    name: exported.name.clone(),
+                    change: StyleChange::Added,
+                    built_in: exported.built_in,
+                    properties: Vec::new(),
+                }),
+                Some(current) => {
+                    let properties = exported.property_diffs(&ExportedStyle::from(current));
+                    if !properties.is_empty() {
+                        styles.push(StyleDiff {
+                            style_id: exported.id.clone(),
+                            name: current.name.clone(),
+                            change: StyleChange::Changed,
+                            built_in: current.built_in,
+                            properties,
+                        });
+                    }
+                }
             }
+        }
+
+        let in_pack: HashSet<&str> = self.styles.iter().map(|s| s.id.as_str()).collect();
+        let mut removed: Vec<StyleDiff> = registry
+            .all_styles()
+            .filter(|style| !style.built_in && !in_pack.contains(style.id.as_str()))
+            .map(|style| StyleDiff {
+                style_id: style.id.to_string(),
+                name: style.name.clone(),
+                change: StyleChange::Removed,
+                built_in: false,
+                properties: Vec::new(),
+            })
+            .collect();
+        removed.sort_by(|a, b| a.style_id.cmp(&b.style_id));
+        styles.extend(removed);
+
+        StylePackDiff { styles }
+    }
 
-            if let Some(ref next) = exported.next_style {
-                style.next_style = Some(StyleId::new(next));
+    /// Apply the styles chosen by `options` to a registry
+    ///
+    /// New styles are added. Existing styles are only updated with
+    /// `overwrite_existing`, and built-in ones also need
+    /// `include_builtin_overrides`. Returns the IDs of the styles applied.
+    pub fn merge_into(
+        &self,
+        registry: &mut StyleRegistry,
+        options: &StylePackApplyOptions,
+    ) -> TemplateResult<Vec<String>> {
+        let mut applied = Vec::new();
+
+        for exported in &self.styles {
+            if !options.filter_style_ids.is_empty() && !options.filter_style_ids.contains(&exported.id) {
+                continue;
             }
 
-            registry.register(style);
-            count += 1;
+            let id = StyleId::new(&exported.id);
+            match registry.get_mut(&id) {
+                Some(current) => {
+                    if !options.overwrite_existing || (current.built_in && !options.include_builtin_overrides) {
+                        continue;
+                    }
+                    exported.apply_to(current);
+                }
+                None => {
+                    if exported.built_in && !options.include_builtin_overrides {
+                        continue;
+                    }
+                    match exported.to_style()? {
+                        Some(style) => registry.register(style),
+                        None => continue,
+                    }
+                }
+            }
+            applied.push(exported.id.clone());
         }
 
-        Ok(count)
+        Ok(applied)
     }
 
     /// Serialize to JSON
@@ -347,4 +556,49 @@ mod tests {
         assert!(ids.contains(&"Style3"));
         assert!(!ids.contains(&"Style2"));
     }
+
+    #[test]
+    fn test_style_pack_diff_and_selective_merge() {
+        let mut corporate = StyleRegistry::new();
+        corporate.register(Style::paragraph("Quote", "Quote").with_character_props(CharacterProperties {
+            italic: Some(true),
+            font_size: Some(12.0),
+            ..Default::default()
+        }));
+        corporate.register(Style::paragraph("Callout", "Callout"));
+        let pack = StylePack::from_registry(&corporate, "Corporate");
+
+        let mut document = StyleRegistry::new();
+        document.register(Style::paragraph("Quote", "Quote").with_character_props(CharacterProperties {
+            italic: Some(true),
+            font_size: Some(14.0),
+            ..Default::default()
+        }));
+        document.register(Style::paragraph("MyNotes", "My Notes"));
+
+        let diff = pack.diff(&document);
+        assert_eq!(diff.get("Callout").unwrap().change, StyleChange::Added);
+        assert_eq!(diff.get("MyNotes").unwrap().change, StyleChange::Removed);
+        let quote = diff.get("Quote").unwrap();
+        assert_eq!(quote.change, StyleChange::Changed);
+        assert_eq!(quote.properties.len(), 1);
+        assert_eq!(quote.properties[0].property, "character_props.font_size");
+        assert_eq!(quote.properties[0].current, serde_json::json!(14.0));
+
+        // Existing styles are kept unless overwriting is allowed
+        let applied = pack.merge_into(&mut document, &StylePackApplyOptions::new()).unwrap();
+        assert_eq!(applied, ["Callout"]);
+        let font_size = |registry: &StyleRegistry| {
+            registry.get(&StyleId::new("Quote")).unwrap().character_props.font_size
+        };
+        assert_eq!(font_size(&document), Some(14.0));
+
+        let options = StylePackApplyOptions::new()
+            .with_overwrite()
+            .with_filter(vec!["Quote".to_string()]);
+        assert_eq!(pack.merge_into(&mut document, &options).unwrap(), ["Quote"]);
+        assert_eq!(font_size(&document), Some(12.0));
+        assert!(document.contains(&StyleId::new("MyNotes")));
+        assert!(pack.diff(&document).with_change(StyleChange::Changed).next().is_none());
+    }
 }
@@ -7,6 +7,7 @@
 mod integration_tests {
     use crate::templates::*;
     use doc_model::{DocumentTree, Node, NodeId, Position, StyleRegistry};
+    use std::collections::HashMap;
     use tempfile::tempdir;
 
     #[test]
@@ -324,4 +325,129 @@ mod integration_tests {
         let result = manager.delete_template("nonexistent");
         assert!(matches!(result, Err(TemplateError::NotFound(_))));
     }
+
+    #[test]
+    fn test_instantiate_template_with_placeholders() {
+        use doc_model::{ContentControl, ContentControlRegistry, Paragraph, Run};
+
+        let mut doc = DocumentTree::new();
+        let mut controls = ContentControlRegistry::new();
+        for (tag, text) in [("client", "[Client]"), ("date", "[Date]"), ("tier", "[Tier]")] {
+            let para_id = doc.insert_paragraph(Paragraph::new(), doc.root_id(), None).unwrap();
+            let run_id = doc.insert_run(Run::new(text), para_id, None).unwrap();
+            let mut control = ContentControl::plain_text().with_tag(tag);
+            control.add_child(run_id);
+            controls.insert(control);
+        }
+
+        let metadata = TemplateMetadata::new("proposal", "Proposal")
+            .with_placeholder(TemplatePlaceholder::text("client", "Client name").with_required())
+            .with_placeholder(TemplatePlaceholder::date("date", "Date"))
+            .with_placeholder(TemplatePlaceholder::dropdown(
+                "tier",
+                "Tier",
+                vec!["Gold".to_string(), "Silver".to_string()],
+            ));
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("proposal.wdt");
+        TemplatePackage::new(doc, metadata)
+            .with_content_controls(controls)
+            .write_to_file(&path)
+            .unwrap();
+        let package = TemplatePackage::read_from_file(&path).unwrap();
+        assert_eq!(package.metadata.placeholders.len(), 3);
+
+        let values = HashMap::from([("date".to_string(), "2024-13-01".to_string())]);
+        match package.instantiate(&values) {
+            Err(TemplateError::InvalidPlaceholders(errors)) => assert_eq!(errors.len(), 2),
+            other => panic!("expected validation errors, got {:?}", other.map(|_| ())),
+        }
+
+        let values = HashMap::from([
+            ("client".to_string(), "Acme".to_string()),
+            ("date".to_string(), "2024-03-05".to_string()),
+            ("tier".to_string(), "Gold".to_string()),
+        ]);
+        let filled = package.instantiate(&values).unwrap();
+        let texts: Vec<_> = filled
+            .paragraphs()
+            .flat_map(|p| p.children().iter().filter_map(|&id| filled.get_run(id)))
+            .map(|run| run.text.as_str())
+            .collect();
+        assert_eq!(texts, ["Acme", "March 5, 2024", "Gold"]);
+    }
+
+    /// Serves canned responses and counts requests
+    struct FakeFetcher {
+        responses: HashMap<String, Vec<u8>>,
+        requests: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl GalleryFetcher for FakeFetcher {
+        fn fetch(&self, url: &str) -> TemplateResult<Vec<u8>> {
+            self.requests.lock().unwrap().push(url.to_string());
+            self.responses
+                .get(url)
+                .cloned()
+                .ok_or_else(|| TemplateError::Gallery(format!("404: {}", url)))
+        }
+    }
+
+    #[test]
+    fn test_remote_gallery_list_download_and_cache() {
+        use sha2::{Digest, Sha256};
+        use std::sync::Arc;
+
+        let mut package = Vec::new();
+        TemplatePackage::new(DocumentTree::with_empty_paragraph(), TemplateMetadata::new("memo", "Memo"))
+            .write_to_writer(std::io::Cursor::new(&mut package))
+            .unwrap();
+        let checksum: String = Sha256::digest(&package).iter().map(|b| format!("{:02x}", b)).collect();
+        let index = format!(
+            r#"{{"categories":[{{"id":"business","name":"Business"}}],"templates":[
+                {{"id":"memo","name":"Memo","category":"business","package_url":"https://g.test/memo.wdt",
+                  "sha256":"{}","thumbnail_url":"https://g.test/memo.png"}},
+                {{"id":"bad","name":"Bad","category":"business","package_url":"https://g.test/memo.wdt",
+                  "sha256":"00"}}]}}"#,
+            checksum
+        );
+        let fetcher = Arc::new(FakeFetcher {
+            responses: HashMap::from([
+                ("https://g.test/index.json".to_string(), index.into_bytes()),
+                ("https://g.test/memo.wdt".to_string(), package),
+                ("https://g.test/memo.png".to_string(), vec![1, 2, 3]),
+            ]),
+            requests: Default::default(),
+        });
+
+        let dir = tempdir().unwrap();
+        let gallery = RemoteGallery::new("https://g.test/index.json", dir.path().join("cache"), fetcher.clone());
+        let mut manager = TemplateManager::new(dir.path().join("templates")).with_gallery(gallery);
+        manager
+            .save_as_template(&DocumentTree::with_empty_paragraph(), TemplateMetadata::new("local", "Local"), None)
+            .unwrap();
+
+        let list = manager.list_templates().unwrap();
+        assert_eq!(list.len(), 3);
+        assert!(list.iter().any(|t| t.id == "memo" && t.remote));
+        assert!(list.iter().any(|t| t.id == "local" && !t.remote));
+
+        // Thumbnails are fetched once and then served from the cache
+        assert_eq!(manager.get_thumbnail("memo").unwrap(), Some(vec![1, 2, 3]));
+        assert_eq!(manager.get_thumbnail("memo").unwrap(), Some(vec![1, 2, 3]));
+        let thumbnail_requests = fetcher.requests.lock().unwrap().iter().filter(|u| u.ends_with(".png")).count();
+        assert_eq!(thumbnail_requests, 1);
+
+        assert!(matches!(
+            manager.load_template("bad"),
+            Err(TemplateError::ChecksumMismatch { .. })
+        ));
+        assert!(!manager.template_exists("bad"));
+
+        // Loading downloads on demand, after which the template is local
+        assert_eq!(manager.load_template("memo").unwrap().metadata.name, "Memo");
+        assert!(manager.template_exists("memo"));
+        let list = manager.list_templates().unwrap();
+        assert_eq!(list.iter().filter(|t| t.remote).count(), 1);
+    }
 }
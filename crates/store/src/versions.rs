@@ -2,9 +2,14 @@
 //!
 //! This module provides functionality to track document versions,
 //! compare versions, and restore to previous versions.
+//!
+//! Snapshots are content-addressed: each one is stored under the checksum
+//! of its content, so saving an unchanged document again doesn't take any
+//! more space. Versions can be given a name to keep them as checkpoints,
+//! which are never removed by cleanup.
 
 use crate::{IntegrityChecker, Result, StoreError};
-use doc_model::DocumentTree;
+use doc_model::{DocumentTree, Node};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -47,6 +52,22 @@ impl VersionConfig {
     }
 }
 
+/// What caused a version to be created
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum VersionTrigger {
+    /// Created directly through the API
+    #[default]
+    Manual,
+    /// The document was saved
+    Save,
+    /// The user created a named checkpoint
+    Checkpoint,
+    /// An earlier version was restored
+    Restore,
+    /// The document was branched from a version of another document
+    Branch,
+}
+
 /// A single document version
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentVersion {
@@ -64,6 +85,12 @@ pub struct DocumentVersion {
     pub file_size: u64,
     /// Human-readable time description
     pub time_description: String,
+    /// Checkpoint name, for named versions
+    #[serde(default)]
+    pub name: Option<String>,
+    /// What caused the version to be created
+    #[serde(default)]
+    pub trigger: VersionTrigger,
 }
 
 impl DocumentVersion {
@@ -149,6 +176,9 @@ pub struct VersionDiff {
     pub changes: Vec<VersionChange>,
     /// Whether the documents are identical
     pub is_identical: bool,
+    /// Paragraph-level changes, in document order
+    #[serde(default)]
+    pub paragraph_changes: Vec<ParagraphChange>,
 }
 
 /// Types of changes between versions
@@ -175,6 +205,121 @@ pub enum VersionChange {
     StructureChanged { details: String },
 }
 
+/// A change to a single paragraph between two versions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ParagraphChange {
+    /// Paragraph added at `index` in the newer version
+    Added { index: usize, text: String },
+    /// Paragraph removed from `index` in the older version
+    Removed { index: usize, text: String },
+    /// Paragraph text changed
+    Modified {
+        old_index: usize,
+        new_index: usize,
+        old_text: String,
+        new_text: String,
+    },
+}
+
+/// Largest paragraph table compared with a full LCS; bigger edits are
+/// paired up by position instead
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Compare the paragraphs of two documents
+///
+/// Paragraphs are matched on their text. Removed and added paragraphs that
+/// sit in the same place are reported as modified.
+pub fn diff_paragraphs(old: &DocumentTree, new: &DocumentTree) -> Vec<ParagraphChange> {
+    let old_texts = paragraph_texts(old);
+    let new_texts = paragraph_texts(new);
+
+    // Common prefix and suffix don't need the LCS table
+    let prefix = old_texts
+        .iter()
+        .zip(&new_texts)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_texts[prefix..]
+        .iter()
+        .rev()
+        .zip(new_texts[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old_texts[prefix..old_texts.len() - suffix];
+    let new_mid = &new_texts[prefix..new_texts.len() - suffix];
+
+    // Matched (old, new) index pairs within the changed middle
+    let mut matches = Vec::new();
+    if old_mid.len().saturating_mul(new_mid.len()) <= MAX_DIFF_CELLS {
+        let (n, m) = (old_mid.len(), new_mid.len());
+        let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if old_mid[i] == new_mid[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if old_mid[i] == new_mid[j] {
+                matches.push((i, j));
+                i += 1;
+                j += 1;
+            } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+    }
+    matches.push((old_mid.len(), new_mid.len()));
+
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (next_i, next_j) in matches {
+        let paired = (next_i - i).min(next_j - j);
+        for k in 0..paired {
+            changes.push(ParagraphChange::Modified {
+                old_index: prefix + i + k,
+                new_index: prefix + j + k,
+                old_text: old_mid[i + k].clone(),
+                new_text: new_mid[j + k].clone(),
+            });
+        }
+        for (k, text) in old_mid[..next_i].iter().enumerate().skip(i + paired) {
+            changes.push(ParagraphChange::Removed {
+                index: prefix + k,
+                text: text.clone(),
+            });
+        }
+        for (k, text) in new_mid[..next_j].iter().enumerate().skip(j + paired) {
+            changes.push(ParagraphChange::Added {
+                index: prefix + k,
+                text: text.clone(),
+            });
+        }
+        (i, j) = (next_i + 1, next_j + 1);
+    }
+    changes
+}
+
+/// Plain text of each body paragraph
+fn paragraph_texts(tree: &DocumentTree) -> Vec<String> {
+    tree.paragraphs()
+        .map(|para| {
+            para.children()
+                .iter()
+                .filter_map(|id| tree.nodes.runs.get(id))
+                .map(|run| run.text.as_str())
+                .collect()
+        })
+        .collect()
+}
+
 /// Version manager for a document
 pub struct VersionManager {
     /// Configuration
@@ -214,10 +359,11 @@ impl VersionManager {
         self.config.versions_dir.join(&self.document_id)
     }
 
-    /// Get the path for a version file
-    fn version_path(&self, version_number: u64) -> PathBuf {
+    /// Get the path for a snapshot with the given checksum
+    fn object_path(&self, checksum: &str) -> PathBuf {
         self.version_dir()
-            .join(format!("v{}.wdj", version_number))
+            .join("objects")
+            .join(format!("{}.wdj", checksum))
     }
 
     /// Get the path for the history metadata file
@@ -231,19 +377,57 @@ impl VersionManager {
         tree: &DocumentTree,
         summary: Option<String>,
     ) -> Result<DocumentVersion> {
-        // Ensure version directory exists
-        tokio::fs::create_dir_all(self.version_dir()).await?;
+        self.create_snapshot(tree, VersionTrigger::Manual, None, summary)
+            .await
+    }
 
+    /// Create a named checkpoint
+    pub async fn create_checkpoint(
+        &mut self,
+        tree: &DocumentTree,
+        name: impl Into<String>,
+    ) -> Result<DocumentVersion> {
+        self.create_snapshot(tree, VersionTrigger::Checkpoint, Some(name.into()), None)
+            .await
+    }
+
+    /// Create a version when the document is saved
+    ///
+    /// Returns None without creating a version if the document hasn't
+    /// changed since the latest version.
+    pub async fn version_on_save(&mut self, tree: &DocumentTree) -> Result<Option<DocumentVersion>> {
+        let checksum = self.checker.compute_checksum(tree);
+        if self.history.latest().is_some_and(|v| v.checksum == checksum) {
+            return Ok(None);
+        }
+        self.create_snapshot(tree, VersionTrigger::Save, None, None)
+            .await
+            .map(Some)
+    }
+
+    /// Create a version, storing the snapshot under its checksum
+    pub async fn create_snapshot(
+        &mut self,
+        tree: &DocumentTree,
+        trigger: VersionTrigger,
+        name: Option<String>,
+        summary: Option<String>,
+    ) -> Result<DocumentVersion> {
         let version_number = self.history.next_version;
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
             .as_millis() as u64;
         let checksum = self.checker.compute_checksum(tree);
-        let path = self.version_path(version_number);
+        let path = self.object_path(&checksum);
 
-        // Save the document
-        crate::save_document(tree, &path).await?;
+        // Identical content is only stored once
+        if !path.exists() {
+            if let Some(dir) = path.parent() {
+                tokio::fs::create_dir_all(dir).await?;
+            }
+            crate::save_document(tree, &path).await?;
+        }
 
         // Get file size
         let metadata = tokio::fs::metadata(&path).await?;
@@ -257,6 +441,8 @@ impl VersionManager {
             path,
             file_size,
             time_description: DocumentVersion::format_time_description(timestamp),
+            name,
+            trigger,
         };
 
         // Add to history
@@ -316,8 +502,10 @@ impl VersionManager {
         let tree = self.load_version(version_number).await?;
 
         // Create a new version for the restore
-        self.create_version(
+        self.create_snapshot(
             &tree,
+            VersionTrigger::Restore,
+            None,
             Some(format!("Restored from version {}", version_number)),
         )
         .await?;
@@ -325,6 +513,33 @@ impl VersionManager {
         Ok(tree)
     }
 
+    /// Start a new document from a version
+    ///
+    /// Returns the document and a version manager for it, whose first
+    /// version records where it was branched from.
+    pub async fn branch_version(
+        &self,
+        version_number: u64,
+        new_document_id: impl Into<String>,
+    ) -> Result<(DocumentTree, VersionManager)> {
+        let tree = self.load_version(version_number).await?;
+
+        let mut branch = VersionManager::new(new_document_id, self.config.clone());
+        branch
+            .create_snapshot(
+                &tree,
+                VersionTrigger::Branch,
+                None,
+                Some(format!(
+                    "Branched from {} version {}",
+                    self.document_id, version_number
+                )),
+            )
+            .await?;
+
+        Ok((tree, branch))
+    }
+
     /// Compare two versions
     pub async fn compare_versions(
         &self,
@@ -343,6 +558,7 @@ impl VersionManager {
                 new_version,
                 changes: Vec::new(),
                 is_identical: true,
+                paragraph_changes: Vec::new(),
             });
         }
 
@@ -390,6 +606,21 @@ impl VersionManager {
             });
         }
 
+        let paragraph_changes = diff_paragraphs(&old_tree, &new_tree);
+        let modified = paragraph_changes
+            .iter()
+            .filter(|c| matches!(c, ParagraphChange::Modified { .. }))
+            .count();
+        if modified > 0 {
+            changes.push(VersionChange::ContentModified {
+                details: format!(
+                    "{} paragraph{} modified",
+                    modified,
+                    if modified == 1 { "" } else { "s" }
+                ),
+            });
+        }
+
         // If no specific changes detected but checksums differ, content was modified
         if changes.is_empty() {
             changes.push(VersionChange::ContentModified {
@@ -402,17 +633,24 @@ impl VersionManager {
             new_version,
             changes,
             is_identical: false,
+            paragraph_changes,
         })
     }
 
     /// Cleanup old versions beyond max_versions
+    ///
+    /// Named checkpoints are kept.
     async fn cleanup_old_versions(&mut self) -> Result<()> {
         while self.history.versions.len() > self.config.max_versions {
-            if let Some(oldest) = self.history.versions.pop() {
-                // Delete the version file
-                if oldest.path.exists() {
-                    tokio::fs::remove_file(&oldest.path).await?;
-                }
+            let Some(index) = self.history.versions.iter().rposition(|v| v.name.is_none()) else {
+                break;
+            };
+            let oldest = self.history.versions.remove(index);
+
+            // Delete the snapshot unless another version shares it
+            let shared = self.history.versions.iter().any(|v| v.path == oldest.path);
+            if !shared && oldest.path.exists() {
+                tokio::fs::remove_file(&oldest.path).await?;
             }
         }
         Ok(())
@@ -431,8 +669,16 @@ impl VersionManager {
     }
 
     /// Get total size of all versions
+    ///
+    /// Snapshots shared by several versions are counted once.
     pub fn total_size(&self) -> u64 {
-        self.history.versions.iter().map(|v| v.file_size).sum()
+        let mut seen = std::collections::HashSet::new();
+        self.history
+            .versions
+            .iter()
+            .filter(|v| seen.insert(&v.path))
+            .map(|v| v.file_size)
+            .sum()
     }
 }
 
@@ -706,4 +952,67 @@ mod tests {
         let total = manager.total_size();
         assert!(total > 0);
     }
+
+    #[tokio::test]
+    async fn test_version_manager_snapshots_and_checkpoints() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = VersionConfig::default()
+            .with_versions_dir(temp_dir.path().to_path_buf())
+            .with_max_versions(2);
+
+        let mut manager = VersionManager::new("test-doc", config);
+        let tree = DocumentTree::with_empty_paragraph();
+
+        let named = manager.create_checkpoint(&tree, "Draft").await.unwrap();
+        assert!(manager.version_on_save(&tree).await.unwrap().is_none());
+
+        let mut edited = tree.clone();
+        let para_id = edited.paragraphs().next().unwrap().id();
+        edited.insert_run(doc_model::Run::new("Hello"), para_id, None).unwrap();
+        let saved = manager.version_on_save(&edited).await.unwrap().unwrap();
+        assert_eq!(saved.trigger, VersionTrigger::Save);
+        manager.create_version(&tree, None).await.unwrap();
+        manager.create_version(&edited, None).await.unwrap();
+
+        // The checkpoint survives cleanup and shares its snapshot
+        let history = manager.history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.versions[1].name.as_deref(), Some("Draft"));
+        assert_eq!(history.versions[1].path, named.path);
+        assert!(named.path.exists());
+
+        let (branch_tree, branch) = manager.branch_version(1, "branch-doc").await.unwrap();
+        assert_eq!(branch_tree.root_id(), tree.root_id());
+        assert_eq!(branch.history().latest().unwrap().trigger, VersionTrigger::Branch);
+    }
+
+    #[test]
+    fn test_diff_paragraphs() {
+        use doc_model::{Paragraph, Run};
+
+        let build = |texts: &[&str]| {
+            let mut tree = DocumentTree::new();
+            for text in texts {
+                let para = tree.insert_paragraph(Paragraph::new(), tree.root_id(), None).unwrap();
+                tree.insert_run(Run::new(*text), para, None).unwrap();
+            }
+            tree
+        };
+        let old = build(&["Title", "First", "Second", "Third"]);
+        let new = build(&["Title", "First edited", "Second", "New", "Third"]);
+
+        assert_eq!(
+            diff_paragraphs(&old, &new),
+            vec![
+                ParagraphChange::Modified {
+                    old_index: 1,
+                    new_index: 1,
+                    old_text: "First".to_string(),
+                    new_text: "First edited".to_string(),
+                },
+                ParagraphChange::Added { index: 3, text: "New".to_string() },
+            ]
+        );
+        assert!(diff_paragraphs(&old, &old).is_empty());
+    }
 }
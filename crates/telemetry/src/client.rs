@@ -3,10 +3,12 @@
 use serde_json::Value;
 use std::collections::HashMap;
 
+use crate::crash::CrashReport;
 use crate::error::TelemetryResult;
 use crate::event::{CoreEvent, TelemetryEvent};
 use crate::metrics::{MetricsCollector, MetricsSummary, PerformanceMetrics};
 use crate::privacy::{PrivacyManager, PrivacySettings};
+use crate::schema::SchemaRegistry;
 use crate::session::TelemetrySession;
 use crate::transport::{TelemetryTransport, TransportConfig};
 
@@ -23,6 +25,8 @@ pub struct TelemetryConfig {
     pub max_metrics_samples: usize,
     /// Transport configuration
     pub transport: TransportConfig,
+    /// Event schemas and sample rates
+    pub schemas: SchemaRegistry,
 }
 
 impl Default for TelemetryConfig {
@@ -33,6 +37,7 @@ impl Default for TelemetryConfig {
             privacy: PrivacySettings::default(),
             max_metrics_samples: 1000,
             transport: TransportConfig::default(),
+            schemas: SchemaRegistry::core(),
         }
     }
 }
@@ -64,6 +69,18 @@ impl TelemetryConfig {
         self.max_metrics_samples = count;
         self
     }
+
+    /// Set the event schemas.
+    pub fn with_schemas(mut self, schemas: SchemaRegistry) -> Self {
+        self.schemas = schemas;
+        self
+    }
+
+    /// Send only this fraction of events with the given name.
+    pub fn with_sample_rate(mut self, event_name: &str, rate: f64) -> Self {
+        self.schemas.set_sample_rate(event_name, rate);
+        self
+    }
 }
 
 /// High-level telemetry client integrating all telemetry components.
@@ -73,8 +90,11 @@ pub struct TelemetryClient {
     transport: TelemetryTransport,
     privacy: PrivacyManager,
     metrics: MetricsCollector,
+    schemas: SchemaRegistry,
     events_tracked: u64,
     events_filtered: u64,
+    events_rejected: u64,
+    events_sampled_out: u64,
 }
 
 impl TelemetryClient {
@@ -90,8 +110,11 @@ impl TelemetryClient {
             transport,
             privacy,
             metrics,
+            schemas: config.schemas,
             events_tracked: 0,
             events_filtered: 0,
+            events_rejected: 0,
+            events_sampled_out: 0,
         }
     }
 
@@ -138,21 +161,42 @@ impl TelemetryClient {
         self.track_event(event);
     }
 
+    /// Queue a crash report for upload, if crash reporting is allowed.
+    pub fn report_crash(&mut self, report: &CrashReport) -> bool {
+        self.track_event(report.to_event())
+    }
+
     /// Internal method to process and queue an event.
-    fn track_event(&mut self, event: TelemetryEvent) {
+    fn track_event(&mut self, event: TelemetryEvent) -> bool {
         // Check privacy settings
         if !self.privacy.is_allowed(&event) {
             self.events_filtered += 1;
-            return;
+            return false;
         }
 
+        // Strip undeclared fields and reject malformed events
+        let event = match self.schemas.validate(event) {
+            Ok(event) => event,
+            Err(_) => {
+                self.events_rejected += 1;
+                return false;
+            }
+        };
+
+        let Some(event) = self.schemas.sample(event) else {
+            self.events_sampled_out += 1;
+            return false;
+        };
+
         // Scrub sensitive data
         let scrubbed = self.privacy.scrub_event(event);
 
         // Queue for sending
         if self.transport.queue(scrubbed).is_ok() {
             self.events_tracked += 1;
+            return true;
         }
+        false
     }
 
     /// Record performance metrics.
@@ -208,6 +252,16 @@ impl TelemetryClient {
         self.events_filtered
     }
 
+    /// Get total number of events rejected by their schema.
+    pub fn events_rejected(&self) -> u64 {
+        self.events_rejected
+    }
+
+    /// Get total number of events dropped by sampling.
+    pub fn events_sampled_out(&self) -> u64 {
+        self.events_sampled_out
+    }
+
     /// Get number of events waiting to be sent.
     pub fn events_queued(&self) -> usize {
         self.transport.queued_count()
@@ -459,6 +513,26 @@ mod tests {
         assert_eq!(client.events_queued(), 3);
     }
 
+    #[test]
+    fn test_client_schema_and_sampling() {
+        let config = TelemetryConfig::new("1.0.0")
+            .with_privacy(PrivacySettings::all_enabled())
+            .with_sample_rate("perf_metric", 0.0);
+        let mut client = TelemetryClient::new(config);
+
+        client.track(CoreEvent::Performance {
+            metric_name: "keystroke_latency".to_string(),
+            value_ms: 4.0,
+        });
+        assert_eq!(client.events_sampled_out(), 1);
+
+        let mut props = HashMap::new();
+        props.insert("cold_start".to_string(), serde_json::json!("yes"));
+        client.track_custom("app_start", props);
+        assert_eq!(client.events_rejected(), 1);
+        assert_eq!(client.events_queued(), 0);
+    }
+
     #[test]
     fn test_client_should_flush() {
         let config = TelemetryConfig::new("1.0.0")
@@ -9,13 +9,27 @@
 //! 2. **CrashReporter**: Manages crash lifecycle (capture, persist, send, cleanup)
 //! 3. **ErrorBoundary**: Catches and categorizes errors at application boundaries
 //! 4. **RecoveryManager**: Manages document recovery files from crashes
+//!
+//! ## Panics
+//!
+//! [`CrashReporter::install_panic_hook`] turns a panic into a report with the
+//! panicking thread's backtrace, the recent breadcrumb trail, document
+//! metrics, and system information, and writes it to the crash directory.
+//! Native crashes (minidumps) are outside what a panic hook can see. On the
+//! next launch [`CrashReporter::recovery_prompt`] finds the report so the
+//! app can offer to restore documents, and [`CrashReporter::upload_pending`]
+//! sends reports if the user allows crash reporting.
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex, TryLockError};
 use uuid::Uuid;
 
+use crate::client::TelemetryClient;
+use crate::event::TelemetryEvent;
+
 // =============================================================================
 // Crash Report Types
 // =============================================================================
@@ -47,8 +61,14 @@ pub struct CrashReport {
     pub system_info: SystemInfo,
     /// Additional context properties
     pub context: HashMap<String, String>,
+    /// Recent commands and events, oldest first
+    #[serde(default)]
+    pub breadcrumbs: Vec<Breadcrumb>,
     /// Whether the report has been sent
     pub sent: bool,
+    /// Whether the user has dismissed the recovery prompt for this crash
+    #[serde(default)]
+    pub acknowledged: bool,
 }
 
 impl CrashReport {
@@ -73,7 +93,9 @@ impl CrashReport {
             document_metrics: None,
             system_info: SystemInfo::collect(),
             context: HashMap::new(),
+            breadcrumbs: Vec::new(),
             sent: false,
+            acknowledged: false,
         }
     }
 
@@ -106,6 +128,22 @@ impl CrashReport {
         self.sent = true;
     }
 
+    /// Convert to a `crash` telemetry event for upload
+    ///
+    /// Only the crash type, message, and stack trace are sent; the privacy
+    /// manager scrubs paths from them before queueing.
+    pub fn to_event(&self) -> TelemetryEvent {
+        let mut event = TelemetryEvent::new("crash", &self.session_id, &self.app_version, &self.platform)
+            .with_property("crash_id", self.crash_id.as_str())
+            .with_property("crash_type", self.crash_type.as_str())
+            .with_property("error_message", self.message.as_str())
+            .with_measurement("breadcrumb_count", self.breadcrumbs.len() as f64);
+        if let Some(trace) = &self.stack_trace {
+            event = event.with_property("stack_trace", trace.as_str());
+        }
+        event
+    }
+
     /// Get a fingerprint for grouping similar crashes
     pub fn fingerprint(&self) -> String {
         // Group by crash type + first line of stack trace (or message)
@@ -185,6 +223,30 @@ impl CrashType {
     }
 }
 
+/// A recent command or event leading up to a crash
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Breadcrumb {
+    pub timestamp: DateTime<Utc>,
+    /// Kind of breadcrumb, such as "command" or "navigation"
+    pub category: String,
+    pub message: String,
+}
+
+impl Breadcrumb {
+    pub fn new(category: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { timestamp: Utc::now(), category: category.into(), message: message.into() }
+    }
+}
+
+/// A crash from an earlier run, shown on launch so the user can restore
+/// documents
+#[derive(Debug, Clone)]
+pub struct RecoveryPrompt {
+    pub report: CrashReport,
+    /// Files in the recovery directory
+    pub recovery_files: Vec<PathBuf>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentMetrics {
     pub page_count: u32,
@@ -266,12 +328,37 @@ impl ErrorBoundary {
     pub fn recent_errors(&self) -> &[BoundaryError] { &self.recent_errors }
 }
 
-pub struct CrashReporter { crash_dir: PathBuf, recovery_dir: PathBuf, app_version: String, platform: String, session_id: String, last_command: Option<String>, document_metrics: Option<DocumentMetrics>, pending_reports: Vec<CrashReport>, max_pending: usize }
+pub struct CrashReporter { crash_dir: PathBuf, recovery_dir: PathBuf, app_version: String, platform: String, session_id: String, last_command: Option<String>, document_metrics: Option<DocumentMetrics>, pending_reports: Vec<CrashReport>, max_pending: usize, breadcrumbs: VecDeque<Breadcrumb>, max_breadcrumbs: usize }
 impl CrashReporter {
-    pub fn new(crash_dir: impl Into<PathBuf>, recovery_dir: impl Into<PathBuf>, app_version: impl Into<String>, platform: impl Into<String>, session_id: impl Into<String>) -> Self { Self { crash_dir: crash_dir.into(), recovery_dir: recovery_dir.into(), app_version: app_version.into(), platform: platform.into(), session_id: session_id.into(), last_command: None, document_metrics: None, pending_reports: Vec::new(), max_pending: 100 } }
-    pub fn set_last_command(&mut self, command: impl Into<String>) { self.last_command = Some(command.into()); }
+    pub fn new(crash_dir: impl Into<PathBuf>, recovery_dir: impl Into<PathBuf>, app_version: impl Into<String>, platform: impl Into<String>, session_id: impl Into<String>) -> Self { Self { crash_dir: crash_dir.into(), recovery_dir: recovery_dir.into(), app_version: app_version.into(), platform: platform.into(), session_id: session_id.into(), last_command: None, document_metrics: None, pending_reports: Vec::new(), max_pending: 100, breadcrumbs: VecDeque::new(), max_breadcrumbs: 50 } }
+    /// Set how many breadcrumbs to keep.
+    pub fn with_max_breadcrumbs(mut self, max: usize) -> Self {
+        self.max_breadcrumbs = max;
+        self.breadcrumbs.truncate(max);
+        self
+    }
+    /// Record the last command, also adding it to the breadcrumb trail.
+    pub fn set_last_command(&mut self, command: impl Into<String>) {
+        let command = command.into();
+        self.add_breadcrumb("command", command.clone());
+        self.last_command = Some(command);
+    }
+    /// Add a breadcrumb, dropping the oldest once the trail is full.
+    pub fn add_breadcrumb(&mut self, category: impl Into<String>, message: impl Into<String>) {
+        if self.max_breadcrumbs == 0 {
+            return;
+        }
+        if self.breadcrumbs.len() >= self.max_breadcrumbs {
+            self.breadcrumbs.pop_front();
+        }
+        self.breadcrumbs.push_back(Breadcrumb::new(category, message));
+    }
+    /// The breadcrumb trail, oldest first.
+    pub fn breadcrumbs(&self) -> impl Iterator<Item = &Breadcrumb> {
+        self.breadcrumbs.iter()
+    }
     pub fn set_document_metrics(&mut self, metrics: DocumentMetrics) { self.document_metrics = Some(metrics); }
-    pub fn capture_crash(&mut self, crash_type: CrashType, message: impl Into<String>) -> CrashReport { let mut report = CrashReport::new(&self.app_version, &self.platform, &self.session_id, crash_type, message); if let Some(ref cmd) = self.last_command { report = report.with_last_command(cmd.clone()); } if let Some(ref metrics) = self.document_metrics { report = report.with_document_metrics(metrics.clone()); } self.pending_reports.push(report.clone()); if self.pending_reports.len() > self.max_pending { self.pending_reports.remove(0); } report }
+    pub fn capture_crash(&mut self, crash_type: CrashType, message: impl Into<String>) -> CrashReport { let mut report = CrashReport::new(&self.app_version, &self.platform, &self.session_id, crash_type, message); if let Some(ref cmd) = self.last_command { report = report.with_last_command(cmd.clone()); } if let Some(ref metrics) = self.document_metrics { report = report.with_document_metrics(metrics.clone()); } report.breadcrumbs = self.breadcrumbs.iter().cloned().collect(); self.pending_reports.push(report.clone()); if self.pending_reports.len() > self.max_pending { self.pending_reports.remove(0); } report }
     pub fn capture_crash_with_trace(&mut self, crash_type: CrashType, message: impl Into<String>, trace: impl Into<String>) -> CrashReport { let mut report = self.capture_crash(crash_type, message); report.stack_trace = Some(trace.into()); if let Some(last) = self.pending_reports.last_mut() { last.stack_trace = report.stack_trace.clone(); } report }
     pub fn persist_report(&self, report: &CrashReport) -> std::io::Result<PathBuf> {
         std::fs::create_dir_all(&self.crash_dir)?;
@@ -302,6 +389,127 @@ impl CrashReporter {
     pub fn crash_dir(&self) -> &PathBuf {
         &self.crash_dir
     }
+
+    /// Install a panic hook that writes a crash report before the previous
+    /// hook runs.
+    ///
+    /// The report carries the panicking thread's backtrace. If another
+    /// thread holds the reporter when the panic happens, no report is
+    /// written rather than risk a deadlock.
+    pub fn install_panic_hook(reporter: Arc<Mutex<Self>>) {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let mut reporter = match reporter.try_lock() {
+                Ok(reporter) => reporter,
+                Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+                Err(TryLockError::WouldBlock) => return previous(info),
+            };
+
+            let payload = info.payload();
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "Box<dyn Any>".to_string());
+            let trace = std::backtrace::Backtrace::force_capture().to_string();
+            let thread = std::thread::current().name().unwrap_or("<unnamed>").to_string();
+
+            let mut report = reporter.capture_crash_with_trace(CrashType::Panic, message, trace);
+            report.context.insert("thread".to_string(), thread);
+            if let Some(location) = info.location() {
+                report.context.insert("location".to_string(), location.to_string());
+            }
+            if let Some(last) = reporter.pending_reports.last_mut() {
+                last.context = report.context.clone();
+            }
+            let _ = reporter.persist_report(&report);
+            drop(reporter);
+            previous(info);
+        }));
+    }
+
+    /// Load the crash reports written by earlier runs, oldest first.
+    pub fn load_persisted_reports(&self) -> std::io::Result<Vec<CrashReport>> {
+        let entries = match std::fs::read_dir(&self.crash_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut reports = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            let is_report = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("crash-") && name.ends_with(".json"));
+            if !is_report {
+                continue;
+            }
+            if let Ok(report) = serde_json::from_slice::<CrashReport>(&std::fs::read(&path)?) {
+                reports.push(report);
+            }
+        }
+        reports.sort_by_key(|report| report.timestamp);
+        Ok(reports)
+    }
+
+    /// The latest crash the user hasn't dismissed, with the recovery files
+    /// to offer.
+    pub fn recovery_prompt(&self) -> std::io::Result<Option<RecoveryPrompt>> {
+        let Some(report) = self.load_persisted_reports()?.into_iter().rev().find(|r| !r.acknowledged) else {
+            return Ok(None);
+        };
+        let mut recovery_files = Vec::new();
+        if let Ok(entries) = std::fs::read_dir(&self.recovery_dir) {
+            for entry in entries {
+                let path = entry?.path();
+                if path.is_file() {
+                    recovery_files.push(path);
+                }
+            }
+        }
+        recovery_files.sort();
+        Ok(Some(RecoveryPrompt { report, recovery_files }))
+    }
+
+    /// Dismiss the recovery prompt for every crash so far.
+    pub fn dismiss_recovery(&self) -> std::io::Result<()> {
+        for mut report in self.load_persisted_reports()? {
+            if !report.acknowledged {
+                report.acknowledged = true;
+                self.persist_report(&report)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Queue unsent crash reports from this and earlier runs for upload.
+    ///
+    /// Reports go through the client's privacy settings, so nothing is
+    /// sent unless crash reporting is enabled. Returns how many were
+    /// queued.
+    pub fn upload_pending(&mut self, client: &mut TelemetryClient) -> std::io::Result<usize> {
+        let mut reports = self.load_persisted_reports()?;
+        for report in &self.pending_reports {
+            if !reports.iter().any(|r| r.crash_id == report.crash_id) {
+                reports.push(report.clone());
+            }
+        }
+
+        let mut queued = 0;
+        for mut report in reports.into_iter().filter(|r| !r.sent) {
+            if !client.report_crash(&report) {
+                continue;
+            }
+            report.mark_sent();
+            self.persist_report(&report)?;
+            if let Some(pending) = self.pending_reports.iter_mut().find(|r| r.crash_id == report.crash_id) {
+                pending.mark_sent();
+            }
+            queued += 1;
+        }
+        Ok(queued)
+    }
 }
 
 #[cfg(test)]
@@ -396,4 +604,54 @@ mod tests {
         assert_eq!(report.last_command, Some("save".to_string()));
         assert_eq!(reporter.pending_reports().len(), 1);
     }
+
+    #[test]
+    fn test_panic_hook_report_and_recovery() {
+        use crate::client::TelemetryConfig;
+        use crate::privacy::PrivacySettings;
+
+        let dir = tempfile::tempdir().unwrap();
+        let recovery_dir = dir.path().join("recovery");
+        std::fs::create_dir_all(&recovery_dir).unwrap();
+        std::fs::write(recovery_dir.join("report.autosave"), "{}").unwrap();
+
+        let mut reporter = CrashReporter::new(dir.path().join("crashes"), &recovery_dir, "1.0.0", "test", "session-123")
+            .with_max_breadcrumbs(2);
+        reporter.add_breadcrumb("navigation", "open document");
+        reporter.set_last_command("bold");
+        reporter.set_last_command("save");
+        reporter.set_document_metrics(DocumentMetrics::empty());
+        let reporter = Arc::new(Mutex::new(reporter));
+
+        CrashReporter::install_panic_hook(reporter.clone());
+        let result = std::thread::Builder::new()
+            .name("worker".to_string())
+            .spawn(|| panic!("boom"))
+            .unwrap()
+            .join();
+        drop(std::panic::take_hook());
+        assert!(result.is_err());
+
+        // On the next launch the crash is found on disk
+        let mut reporter = reporter.lock().unwrap();
+        let prompt = reporter.recovery_prompt().unwrap().unwrap();
+        let report = &prompt.report;
+        assert_eq!((report.crash_type, report.message.as_str()), (CrashType::Panic, "boom"));
+        assert_eq!(report.context.get("thread").map(String::as_str), Some("worker"));
+        assert!(report.stack_trace.is_some() && report.document_metrics.is_some());
+        let crumbs: Vec<_> = report.breadcrumbs.iter().map(|b| b.message.as_str()).collect();
+        assert_eq!(crumbs, ["bold", "save"]);
+        assert_eq!(prompt.recovery_files, [recovery_dir.join("report.autosave")]);
+
+        // Reports are only uploaded with consent, and only once
+        let mut client = TelemetryClient::new(TelemetryConfig::new("1.0.0"));
+        assert_eq!(reporter.upload_pending(&mut client).unwrap(), 0);
+        let config = TelemetryConfig::new("1.0.0").with_privacy(PrivacySettings::minimal());
+        let mut client = TelemetryClient::new(config);
+        assert_eq!(reporter.upload_pending(&mut client).unwrap(), 1);
+        assert_eq!(reporter.upload_pending(&mut client).unwrap(), 0);
+
+        reporter.dismiss_recovery().unwrap();
+        assert!(reporter.recovery_prompt().unwrap().is_none());
+    }
 }
@@ -32,6 +32,10 @@ pub enum TelemetryError {
     /// Transport is in offline mode
     #[error("Transport is offline")]
     Offline,
+
+    /// Failed to read or write the on-disk queue
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
 }
 
 /// Result type for telemetry operations.
@@ -6,7 +6,8 @@
 //! - Event tracking for user actions, feature usage, and errors
 //! - Performance metrics collection with statistical analysis
 //! - Privacy-first design with configurable data collection
-//! - Batched transport with offline support
+//! - Event schema validation and sampling
+//! - Batched transport with offline support and an on-disk retry queue
 //! - Crash reporting and recovery management
 //! - Performance profiling with hierarchical spans
 //! - Memory profiling and leak detection
@@ -54,6 +55,8 @@
 //! - [`privacy`] - Privacy settings and filtering
 //! - [`session`] - Session management
 //! - [`transport`] - Event batching and transport
+//! - [`queue`] - Persistent event queue
+//! - [`schema`] - Event schemas and sampling
 //! - [`client`] - High-level telemetry client
 //! - [`crash`] - Crash reporting and recovery
 //! - [`error`] - Error types
@@ -71,20 +74,29 @@ pub mod memory;
 mod metrics;
 mod privacy;
 pub mod profiler;
+mod queue;
 pub mod report;
+mod schema;
 mod session;
 mod transport;
 
 pub use client::{TelemetryClient, TelemetryConfig};
-pub use crash::{CrashReport, CrashReporter, CrashType, DocumentMetrics, ErrorBoundary, SystemInfo as CrashSystemInfo};
+pub use crash::{
+    Breadcrumb, CrashReport, CrashReporter, CrashType, DocumentMetrics, ErrorBoundary, RecoveryPrompt,
+    SystemInfo as CrashSystemInfo,
+};
 pub use error::{TelemetryError, TelemetryResult};
 pub use event::{CommandSource, CoreEvent, TelemetryEvent};
 pub use inspector::{CrdtState, DocumentInspector, InspectorNode, InspectorFilter};
-pub use memory::{AllocationInfo, LeakInfo, MemoryProfiler, MemorySnapshot, SnapshotComparison};
+pub use memory::{
+    AllocationInfo, ComponentUsage, LeakInfo, MemoryProfiler, MemorySnapshot, MemorySource, SnapshotComparison, Subsystem,
+};
 pub use metrics::{MetricsCollector, MetricsSummary, PerformanceMetrics};
 pub use privacy::{EventCategory, PrivacyManager, PrivacySettings};
 pub use profiler::{PerformanceProfiler, ProfileSpan, ProfileTrace, TimelineData};
+pub use queue::PersistentQueue;
 pub use report::{AppState, LogEntry, LogLevel, PerformanceSummary, ReportConfig, SupportReport, SupportReportGenerator, SystemInfo};
+pub use schema::{EventSchema, PropertyType, SchemaRegistry, SAMPLE_RATE_MEASUREMENT};
 pub use session::{get_platform, TelemetrySession};
 pub use transport::{TelemetryTransport, TransportConfig};
 
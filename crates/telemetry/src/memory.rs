@@ -22,6 +22,10 @@
 //!     }
 //! }
 //! ```
+//!
+//! Subsystems such as the document tree or undo stack can register a
+//! [`MemorySource`] that reports their usage whenever a snapshot is taken,
+//! and [`MemorySnapshot::breakdown`] attributes the total to each of them.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -93,6 +97,65 @@ impl AllocationInfo {
     }
 }
 
+// =============================================================================
+// Subsystems
+// =============================================================================
+
+/// A part of the application whose memory use is attributed separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    /// Document tree nodes and text
+    DocumentTree,
+    /// Cached paragraph and page layouts
+    LayoutCache,
+    /// Embedded image data
+    ImageStore,
+    /// Undo and redo history
+    UndoStack,
+    /// Collaboration CRDT state and operation log
+    CrdtState,
+}
+
+impl Subsystem {
+    /// Every subsystem.
+    pub const ALL: [Subsystem; 5] = [
+        Subsystem::DocumentTree,
+        Subsystem::LayoutCache,
+        Subsystem::ImageStore,
+        Subsystem::UndoStack,
+        Subsystem::CrdtState,
+    ];
+
+    /// Component name used in snapshots.
+    pub fn component(&self) -> &'static str {
+        match self {
+            Subsystem::DocumentTree => "doc_tree",
+            Subsystem::LayoutCache => "layout_cache",
+            Subsystem::ImageStore => "image_store",
+            Subsystem::UndoStack => "undo_stack",
+            Subsystem::CrdtState => "crdt_state",
+        }
+    }
+}
+
+/// Reports a subsystem's current memory use when a snapshot is taken.
+pub type MemorySource = Box<dyn Fn() -> AllocationInfo + Send + Sync>;
+
+/// Memory attributed to one component of a snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentUsage {
+    /// Component name
+    pub component: String,
+    /// Bytes in use
+    pub bytes: usize,
+    /// Number of allocations or items
+    pub count: usize,
+    /// Share of the snapshot's total, from 0 to 100
+    pub percent: f64,
+}
+
 // =============================================================================
 // Memory Snapshot
 // =============================================================================
@@ -148,6 +211,26 @@ impl MemorySnapshot {
         self.allocations.len()
     }
 
+    /// Usage of each component, largest first.
+    pub fn breakdown(&self) -> Vec<ComponentUsage> {
+        let mut usage: Vec<_> = self
+            .allocations
+            .iter()
+            .map(|(component, info)| ComponentUsage {
+                component: component.clone(),
+                bytes: info.bytes,
+                count: info.count,
+                percent: if self.total_bytes == 0 {
+                    0.0
+                } else {
+                    info.bytes as f64 / self.total_bytes as f64 * 100.0
+                },
+            })
+            .collect();
+        usage.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.component.cmp(&b.component)));
+        usage
+    }
+
     /// Add metadata to the snapshot.
     pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.metadata.insert(key.into(), value.into());
@@ -278,7 +361,6 @@ pub struct LeakInfo {
 // =============================================================================
 
 /// Memory profiler for tracking allocations and detecting leaks.
-#[derive(Debug)]
 pub struct MemoryProfiler {
     /// Collected memory snapshots
     snapshots: Vec<MemorySnapshot>,
@@ -292,6 +374,22 @@ pub struct MemoryProfiler {
     next_id: u64,
     /// Minimum growth count for leak detection
     leak_detection_threshold: usize,
+    /// Components polled for their usage on each snapshot
+    sources: Vec<(String, MemorySource)>,
+}
+
+impl std::fmt::Debug for MemoryProfiler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryProfiler")
+            .field("snapshots", &self.snapshots)
+            .field("max_snapshots", &self.max_snapshots)
+            .field("start_time", &self.start_time)
+            .field("current_allocations", &self.current_allocations)
+            .field("next_id", &self.next_id)
+            .field("leak_detection_threshold", &self.leak_detection_threshold)
+            .field("sources", &self.sources.iter().map(|(name, _)| name).collect::<Vec<_>>())
+            .finish()
+    }
 }
 
 impl Default for MemoryProfiler {
@@ -310,6 +408,7 @@ impl MemoryProfiler {
             current_allocations: HashMap::new(),
             next_id: 0,
             leak_detection_threshold: 3,
+            sources: Vec::new(),
         }
     }
 
@@ -344,8 +443,41 @@ impl MemoryProfiler {
         self.current_allocations.insert(component.into(), info);
     }
 
+    /// Register a source polled for a component's usage on each snapshot,
+    /// replacing any earlier source for the component.
+    pub fn register_source(
+        &mut self,
+        component: impl Into<String>,
+        source: impl Fn() -> AllocationInfo + Send + Sync + 'static,
+    ) {
+        let component = component.into();
+        self.sources.retain(|(name, _)| *name != component);
+        self.sources.push((component, Box::new(source)));
+    }
+
+    /// Register a source for a subsystem.
+    pub fn register_subsystem(
+        &mut self,
+        subsystem: Subsystem,
+        source: impl Fn() -> AllocationInfo + Send + Sync + 'static,
+    ) {
+        self.register_source(subsystem.component(), source);
+    }
+
+    /// Poll every registered source, keeping each component's peak.
+    fn poll_sources(&mut self) {
+        for (component, source) in &self.sources {
+            let sample = source();
+            let info = self.current_allocations.entry(component.clone()).or_default();
+            info.peak_bytes = info.peak_bytes.max(sample.bytes);
+            info.bytes = sample.bytes;
+            info.count = sample.count;
+        }
+    }
+
     /// Take a memory snapshot of the current state.
     pub fn take_snapshot(&mut self) -> &MemorySnapshot {
+        self.poll_sources();
         let id = self.next_id;
         self.next_id += 1;
 
@@ -644,6 +776,32 @@ mod duration_serde {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_subsystem_sources_and_breakdown() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let undo_bytes = Arc::new(AtomicUsize::new(1000));
+        let mut profiler = MemoryProfiler::new();
+        profiler.record_allocation("misc", 1000);
+        profiler.register_subsystem(Subsystem::DocumentTree, || AllocationInfo::new(3000, 30));
+        let bytes = undo_bytes.clone();
+        profiler.register_subsystem(Subsystem::UndoStack, move || {
+            AllocationInfo::new(bytes.load(Ordering::Relaxed), 5)
+        });
+
+        let breakdown = profiler.take_snapshot().breakdown();
+        let names: Vec<_> = breakdown.iter().map(|u| u.component.as_str()).collect();
+        assert_eq!(names, ["doc_tree", "misc", "undo_stack"]);
+        assert_eq!((breakdown[0].bytes, breakdown[0].count, breakdown[0].percent), (3000, 30, 60.0));
+
+        // Sources are polled again on each snapshot and keep their peak
+        undo_bytes.store(500, Ordering::Relaxed);
+        let snapshot = profiler.take_snapshot();
+        let undo = snapshot.get_allocation("undo_stack").unwrap();
+        assert_eq!((snapshot.total_bytes, undo.bytes, undo.peak_bytes), (4500, 500, 1000));
+    }
+
     #[test]
     fn test_allocation_info_default() {
         let info = AllocationInfo::default();
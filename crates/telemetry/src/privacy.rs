@@ -166,12 +166,14 @@ impl PrivacyManager {
             event.properties.remove(key);
         }
 
-        // Redact error messages that might contain paths
-        if let Some(msg) = event.properties.get_mut("error_message") {
-            if let Some(s) = msg.as_str() {
-                // Redact file paths
-                let redacted = redact_paths(s);
-                *msg = serde_json::Value::String(redacted);
+        // Redact error messages and stack traces that might contain paths
+        for key in ["error_message", "stack_trace"] {
+            if let Some(msg) = event.properties.get_mut(key) {
+                if let Some(s) = msg.as_str() {
+                    // Redact file paths
+                    let redacted = redact_paths(s);
+                    *msg = serde_json::Value::String(redacted);
+                }
             }
         }
 
@@ -22,9 +22,16 @@
 //! let trace = profiler.get_trace();
 //! println!("Total duration: {:?}", trace.total_duration);
 //! ```
+//!
+//! Traces can be exported to the Chrome trace-event format (for
+//! `chrome://tracing` or Perfetto) with [`ProfileTrace::to_chrome_trace`],
+//! and to folded stacks for flame graph tools with
+//! [`ProfileTrace::to_folded_stacks`].
 
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use serde_json::json;
+use std::collections::{BTreeMap, VecDeque};
+use std::path::Path;
 use std::time::{Duration, Instant};
 
 // =============================================================================
@@ -36,6 +43,9 @@ use std::time::{Duration, Instant};
 pub struct ProfileSpan {
     /// Name of the operation being profiled
     pub name: String,
+    /// When this span started, relative to the start of the session
+    #[serde(default, with = "duration_serde")]
+    pub start_offset: Duration,
     /// Duration of this span (calculated when span ends)
     #[serde(with = "duration_serde")]
     pub duration: Duration,
@@ -52,6 +62,7 @@ impl ProfileSpan {
     pub fn new(name: impl Into<String>, depth: usize) -> Self {
         Self {
             name: name.into(),
+            start_offset: Duration::ZERO,
             duration: Duration::ZERO,
             children: Vec::new(),
             depth,
@@ -98,9 +109,10 @@ impl ActiveSpan {
         }
     }
 
-    fn into_span(self) -> ProfileSpan {
+    fn into_span(self, origin: Instant) -> ProfileSpan {
         ProfileSpan {
             name: self.name,
+            start_offset: self.start_time.saturating_duration_since(origin),
             duration: self.start_time.elapsed(),
             children: self.children,
             depth: self.depth,
@@ -202,6 +214,80 @@ impl ProfileTrace {
             total_duration: self.total_duration,
         }
     }
+
+    /// Convert to the Chrome trace-event format.
+    ///
+    /// Each span becomes a complete (`"X"`) event with microsecond
+    /// timestamps and its tags as arguments.
+    pub fn to_chrome_trace(&self) -> serde_json::Value {
+        fn push_events(span: &ProfileSpan, events: &mut Vec<serde_json::Value>) {
+            let args: serde_json::Map<_, _> =
+                span.tags.iter().map(|(k, v)| (k.clone(), json!(v))).collect();
+            events.push(json!({
+                "name": span.name,
+                "cat": "profile",
+                "ph": "X",
+                "ts": span.start_offset.as_secs_f64() * 1e6,
+                "dur": span.duration.as_secs_f64() * 1e6,
+                "pid": 1,
+                "tid": 1,
+                "args": args,
+            }));
+            for child in &span.children {
+                push_events(child, events);
+            }
+        }
+
+        let mut events = vec![json!({
+            "name": "process_name",
+            "ph": "M",
+            "pid": 1,
+            "args": { "name": self.name },
+        })];
+        for span in &self.spans {
+            push_events(span, &mut events);
+        }
+        json!({
+            "traceEvents": events,
+            "displayTimeUnit": "ms",
+            "otherData": { "startedAt": self.started_at.to_rfc3339() },
+        })
+    }
+
+    /// Convert to folded stacks for flame graph tools.
+    ///
+    /// Each line is a `;`-separated stack followed by the self time of
+    /// its innermost span in microseconds. Identical stacks are merged.
+    pub fn to_folded_stacks(&self) -> String {
+        fn fold(span: &ProfileSpan, prefix: &str, stacks: &mut BTreeMap<String, u128>) {
+            let name = span.name.replace([';', '\n'], " ");
+            let stack = if prefix.is_empty() { name } else { format!("{};{}", prefix, name) };
+            *stacks.entry(stack.clone()).or_default() += span.self_time().as_micros();
+            for child in &span.children {
+                fold(child, &stack, stacks);
+            }
+        }
+
+        let mut stacks = BTreeMap::new();
+        for span in &self.spans {
+            fold(span, "", &mut stacks);
+        }
+        stacks
+            .into_iter()
+            .filter(|(_, micros)| *micros > 0)
+            .map(|(stack, micros)| format!("{} {}\n", stack, micros))
+            .collect()
+    }
+
+    /// Write the trace in the Chrome trace-event format.
+    pub fn write_chrome_trace(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_chrome_trace().to_string())
+    }
+
+    /// Write the trace as folded stacks.
+    pub fn write_folded_stacks(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_folded_stacks())
+    }
 }
 
 /// Timeline visualization data.
@@ -336,7 +422,8 @@ impl PerformanceProfiler {
     /// Returns the completed span, or `None` if no span was active.
     pub fn end_span(&mut self) -> Option<ProfileSpan> {
         let active = self.active_spans.pop()?;
-        let completed = active.into_span();
+        let origin = self.start_time.unwrap_or(active.start_time);
+        let completed = active.into_span(origin);
 
         // Add to parent or root
         if let Some(parent) = self.active_spans.last_mut() {
@@ -583,6 +670,28 @@ mod tests {
         assert_eq!(timeline.entries[1].name, "child");
     }
 
+    #[test]
+    fn test_profile_trace_exports() {
+        let mut root = ProfileSpan::new("layout", 0).with_tag("pages", "3");
+        root.start_offset = Duration::from_micros(100);
+        root.duration = Duration::from_micros(500);
+        let mut child = ProfileSpan::new("shape;text", 1);
+        child.start_offset = Duration::from_micros(150);
+        child.duration = Duration::from_micros(200);
+        root.children.push(child);
+        let trace = ProfileTrace::new("session", vec![root], Duration::from_millis(1));
+
+        let chrome = trace.to_chrome_trace();
+        let events = chrome["traceEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[1]["ph"], "X");
+        assert_eq!((events[1]["ts"].as_f64(), events[1]["dur"].as_f64()), (Some(100.0), Some(500.0)));
+        assert_eq!(events[1]["args"]["pages"], "3");
+        assert_eq!(events[2]["ts"].as_f64(), Some(150.0));
+
+        assert_eq!(trace.to_folded_stacks(), "layout 300\nlayout;shape text 200\n");
+    }
+
     #[test]
     fn test_profiler_new() {
         let profiler = PerformanceProfiler::new();
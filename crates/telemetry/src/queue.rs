@@ -0,0 +1,207 @@
+//! On-disk event queue so telemetry survives crashes and offline periods.
+//!
+//! Events are appended to a JSON-lines file as they are queued and removed
+//! only after a send succeeds. Delivery is at-least-once: a crash between
+//! sending and removing resends those events, and the endpoint can drop
+//! duplicates by `event_id`. When the file would grow past its size limit
+//! the oldest events are evicted.
+
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::error::{TelemetryError, TelemetryResult};
+use crate::event::TelemetryEvent;
+
+/// A persistent FIFO queue of telemetry events.
+#[derive(Debug)]
+pub struct PersistentQueue {
+    path: PathBuf,
+    max_bytes: u64,
+    /// Queued events with the size of their line in the file
+    events: VecDeque<(TelemetryEvent, u64)>,
+    size: u64,
+    evicted: u64,
+}
+
+impl PersistentQueue {
+    /// Open the queue at `path`, loading any events left from earlier runs.
+    ///
+    /// Lines that don't parse, such as one cut short by a crash, are
+    /// dropped.
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64) -> TelemetryResult<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut queue = Self {
+            path,
+            max_bytes,
+            events: VecDeque::new(),
+            size: 0,
+            evicted: 0,
+        };
+
+        let contents = match fs::read_to_string(&queue.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let mut dirty = false;
+        for line in contents.lines() {
+            match serde_json::from_str::<TelemetryEvent>(line) {
+                Ok(event) => {
+                    let len = line.len() as u64 + 1;
+                    queue.size += len;
+                    queue.events.push_back((event, len));
+                }
+                Err(_) => dirty = true,
+            }
+        }
+        if queue.evict(0) > 0 || dirty {
+            queue.rewrite()?;
+        }
+        Ok(queue)
+    }
+
+    /// Append an event, returning how many old events were evicted to
+    /// make room for it.
+    pub fn push(&mut self, event: &TelemetryEvent) -> TelemetryResult<usize> {
+        let mut line = serde_json::to_string(event)?;
+        line.push('\n');
+        let len = line.len() as u64;
+        if len > self.max_bytes {
+            return Err(TelemetryError::QueueFull);
+        }
+
+        let evicted = self.evict(len);
+        self.size += len;
+        self.events.push_back((event.clone(), len));
+        if evicted > 0 {
+            self.rewrite()?;
+        } else {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?
+                .write_all(line.as_bytes())?;
+        }
+        Ok(evicted)
+    }
+
+    /// The oldest events, up to `count`.
+    pub fn peek(&self, count: usize) -> Vec<TelemetryEvent> {
+        self.events.iter().take(count).map(|(event, _)| event.clone()).collect()
+    }
+
+    /// Remove the oldest `count` events once they've been delivered.
+    pub fn remove_front(&mut self, count: usize) -> TelemetryResult<()> {
+        let count = count.min(self.events.len());
+        for (_, len) in self.events.drain(..count) {
+            self.size -= len;
+        }
+        self.rewrite()
+    }
+
+    /// Remove every event.
+    pub fn clear(&mut self) -> TelemetryResult<()> {
+        self.remove_front(self.events.len())
+    }
+
+    /// Number of queued events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Check if the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Size of the queue file in bytes.
+    pub fn size_bytes(&self) -> u64 {
+        self.size
+    }
+
+    /// Number of events evicted since the queue was opened.
+    pub fn evicted_count(&self) -> u64 {
+        self.evicted
+    }
+
+    /// Path of the queue file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Drop the oldest events until `extra` more bytes fit.
+    fn evict(&mut self, extra: u64) -> usize {
+        let mut count = 0;
+        while self.size + extra > self.max_bytes {
+            let Some((_, len)) = self.events.pop_front() else {
+                break;
+            };
+            self.size -= len;
+            count += 1;
+        }
+        self.evicted += count as u64;
+        count
+    }
+
+    /// Replace the file with the current events.
+    ///
+    /// Writes to a temporary file first so a crash leaves either the old
+    /// or the new contents.
+    fn rewrite(&self) -> TelemetryResult<()> {
+        let mut contents = String::with_capacity(self.size as usize);
+        for (event, _) in &self.events {
+            contents.push_str(&serde_json::to_string(event)?);
+            contents.push('\n');
+        }
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, contents)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_event(name: &str) -> TelemetryEvent {
+        TelemetryEvent::new(name, "session", "1.0", "test")
+    }
+
+    #[test]
+    fn test_queue_survives_reopen_and_evicts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("telemetry").join("queue.jsonl");
+
+        let mut queue = PersistentQueue::open(&path, 1024 * 1024).unwrap();
+        for name in ["a", "b", "c"] {
+            assert_eq!(queue.push(&make_event(name)).unwrap(), 0);
+        }
+        queue.remove_front(1).unwrap();
+        drop(queue);
+
+        // A torn final line is dropped on open
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"event_id\":").unwrap();
+
+        let queue = PersistentQueue::open(&path, 1024 * 1024).unwrap();
+        let names: Vec<_> = queue.peek(10).into_iter().map(|e| e.event_name).collect();
+        assert_eq!(names, ["b", "c"]);
+        let size = queue.size_bytes();
+        assert_eq!(fs::metadata(&path).unwrap().len(), size);
+
+        // Room for two events: pushing a third evicts the oldest
+        let mut queue = PersistentQueue::open(&path, size + size / 4).unwrap();
+        assert_eq!(queue.push(&make_event("d")).unwrap(), 1);
+        let names: Vec<_> = queue.peek(10).into_iter().map(|e| e.event_name).collect();
+        assert_eq!(names, ["c", "d"]);
+        assert_eq!(queue.evicted_count(), 1);
+        assert_eq!(PersistentQueue::open(&path, size * 2).unwrap().len(), 2);
+    }
+}
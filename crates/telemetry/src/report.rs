@@ -20,12 +20,19 @@
 //! // Export to file
 //! generator.export_to_file(&anonymized, "/tmp/support_report.json").unwrap();
 //! ```
+//!
+//! [`SupportReportGenerator::export_to_zip`] bundles the report with its logs,
+//! import fidelity warnings and document structure into a single archive the
+//! user can attach to a bug report. The document structure is stripped of
+//! text before it is added.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
 use crate::crash::{CrashReport, CrashType, SystemInfo as CrashSystemInfo};
+use crate::inspector::InspectorNode;
 use crate::metrics::MetricsSummary;
 
 // =============================================================================
@@ -432,6 +439,15 @@ pub struct SupportReport {
     pub crash_reports: Vec<CrashReport>,
     /// Performance summary
     pub performance_summary: PerformanceSummary,
+    /// Application settings
+    #[serde(default)]
+    pub settings: HashMap<String, String>,
+    /// Fidelity warnings from the last import
+    #[serde(default)]
+    pub fidelity_warnings: Vec<String>,
+    /// Document structure with all text removed
+    #[serde(default)]
+    pub document_structure: Option<InspectorNode>,
     /// User description of the issue (if provided)
     pub user_description: Option<String>,
     /// Steps to reproduce (if provided)
@@ -454,6 +470,9 @@ impl SupportReport {
             recent_logs: Vec::new(),
             crash_reports: Vec::new(),
             performance_summary: PerformanceSummary::default(),
+            settings: HashMap::new(),
+            fidelity_warnings: Vec::new(),
+            document_structure: None,
             user_description: None,
             steps_to_reproduce: None,
             attachments: HashMap::new(),
@@ -491,6 +510,24 @@ impl SupportReport {
         self
     }
 
+    /// Set application settings.
+    pub fn with_settings(mut self, settings: HashMap<String, String>) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Set fidelity warnings from the last import.
+    pub fn with_fidelity_warnings(mut self, warnings: Vec<String>) -> Self {
+        self.fidelity_warnings = warnings;
+        self
+    }
+
+    /// Set the document structure, removing its text.
+    pub fn with_document_structure(mut self, root: &InspectorNode) -> Self {
+        self.document_structure = Some(sanitize_structure(root));
+        self
+    }
+
     /// Set user description.
     pub fn with_description(mut self, description: impl Into<String>) -> Self {
         self.user_description = Some(description.into());
@@ -640,6 +677,12 @@ pub struct SupportReportGenerator {
     app_state: Option<AppState>,
     /// Performance metrics
     performance: Option<PerformanceSummary>,
+    /// Application settings
+    settings: HashMap<String, String>,
+    /// Fidelity warnings from the last import
+    fidelity_warnings: Vec<String>,
+    /// Sanitized document structure
+    document_structure: Option<InspectorNode>,
 }
 
 impl Default for SupportReportGenerator {
@@ -657,6 +700,9 @@ impl SupportReportGenerator {
             crashes: Vec::new(),
             app_state: None,
             performance: None,
+            settings: HashMap::new(),
+            fidelity_warnings: Vec::new(),
+            document_structure: None,
         }
     }
 
@@ -695,6 +741,21 @@ impl SupportReportGenerator {
         self.performance = Some(PerformanceSummary::from_metrics(summary));
     }
 
+    /// Set application settings.
+    pub fn set_settings(&mut self, settings: HashMap<String, String>) {
+        self.settings = settings;
+    }
+
+    /// Set fidelity warnings from the last import.
+    pub fn set_fidelity_warnings(&mut self, warnings: Vec<String>) {
+        self.fidelity_warnings = warnings;
+    }
+
+    /// Set the document structure, removing its text.
+    pub fn set_document_structure(&mut self, root: &InspectorNode) {
+        self.document_structure = Some(sanitize_structure(root));
+    }
+
     /// Generate a support report.
     pub fn generate_report(&self) -> SupportReport {
         let mut report = SupportReport::new();
@@ -731,6 +792,10 @@ impl SupportReportGenerator {
             }
         }
 
+        report.settings = self.settings.clone();
+        report.fidelity_warnings = self.fidelity_warnings.clone();
+        report.document_structure = self.document_structure.clone();
+
         // Auto-anonymize if configured
         if self.config.auto_anonymize {
             report = self.anonymize(report);
@@ -768,6 +833,14 @@ impl SupportReportGenerator {
             }
         }
 
+        // Anonymize settings and warnings, which may mention file paths
+        for value in report.settings.values_mut() {
+            *value = anonymize_text(value);
+        }
+        for warning in &mut report.fidelity_warnings {
+            *warning = anonymize_text(warning);
+        }
+
         // Anonymize user description
         if let Some(ref mut desc) = report.user_description {
             *desc = anonymize_text(desc);
@@ -801,6 +874,53 @@ impl SupportReportGenerator {
         Ok(path)
     }
 
+    /// Export report to a zip archive.
+    ///
+    /// The archive holds `report.json`, the logs as `logs.txt`, and when
+    /// present `fidelity_warnings.txt` and `document_structure.json`.
+    pub fn export_to_zip(
+        &self,
+        report: &SupportReport,
+        path: impl AsRef<Path>,
+    ) -> std::io::Result<PathBuf> {
+        let path = path.as_ref().to_path_buf();
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path)?);
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+
+        let mut add = |name: &str, contents: String| -> std::io::Result<()> {
+            zip.start_file(name, options).map_err(std::io::Error::other)?;
+            zip.write_all(contents.as_bytes())
+        };
+
+        add("report.json", serde_json::to_string_pretty(report)?)?;
+
+        let logs: String = report
+            .recent_logs
+            .iter()
+            .map(|log| {
+                format!(
+                    "{} {:5} [{}] {}\n",
+                    log.timestamp.to_rfc3339(),
+                    format!("{:?}", log.level).to_uppercase(),
+                    log.source,
+                    log.message
+                )
+            })
+            .collect();
+        add("logs.txt", logs)?;
+
+        if !report.fidelity_warnings.is_empty() {
+            add("fidelity_warnings.txt", report.fidelity_warnings.join("\n") + "\n")?;
+        }
+        if let Some(ref structure) = report.document_structure {
+            add("document_structure.json", serde_json::to_string_pretty(structure)?)?;
+        }
+
+        zip.finish().map_err(std::io::Error::other)?;
+        Ok(path)
+    }
+
     /// Get configuration.
     pub fn config(&self) -> &ReportConfig {
         &self.config
@@ -812,6 +932,9 @@ impl SupportReportGenerator {
         self.crashes.clear();
         self.app_state = None;
         self.performance = None;
+        self.settings.clear();
+        self.fidelity_warnings.clear();
+        self.document_structure = None;
     }
 }
 
@@ -819,6 +942,38 @@ impl SupportReportGenerator {
 // Anonymization Helpers
 // =============================================================================
 
+/// Properties kept on structure nodes even though they aren't numbers.
+const STRUCTURE_PROPERTIES: &[&str] = &["format"];
+
+/// Copy a document structure tree without its text.
+///
+/// Node names are replaced by their type, and only numeric, boolean and
+/// format properties are kept, so titles, previews and style names are
+/// dropped.
+fn sanitize_structure(node: &InspectorNode) -> InspectorNode {
+    let properties = node
+        .properties
+        .iter()
+        .filter(|(key, value)| {
+            STRUCTURE_PROPERTIES.contains(&key.as_str())
+                || value.parse::<f64>().is_ok()
+                || value.parse::<bool>().is_ok()
+        })
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+
+    InspectorNode {
+        id: node.id.clone(),
+        node_type: node.node_type.clone(),
+        name: node.node_type.clone(),
+        properties,
+        children: node.children.iter().map(sanitize_structure).collect(),
+        crdt_state: node.crdt_state.clone(),
+        expanded: node.expanded,
+        selected: node.selected,
+    }
+}
+
 fn anonymize_id(id: &str) -> String {
     if id.len() <= 8 {
         "********".to_string()
@@ -1139,4 +1294,38 @@ mod tests {
         assert!(json.contains("report_id"));
         assert!(json.contains("system_info"));
     }
+
+    #[test]
+    fn test_generator_export_zip() {
+        use crate::inspector::{DocumentData, DocumentInspector};
+        use std::io::Read;
+
+        let mut generator = SupportReportGenerator::new(ReportConfig::default());
+        generator.add_log(LogEntry::new(LogLevel::Error, "Failed to open /home/alice/notes.docx", "store"));
+        generator.set_settings(HashMap::from([("autosave".to_string(), "true".to_string())]));
+        generator.set_fidelity_warnings(vec!["SmartArt is not supported".to_string()]);
+        let root = DocumentInspector::new().inspect_document(&DocumentData::sample());
+        generator.set_document_structure(&root);
+
+        let report = generator.anonymize(generator.generate_report());
+        let structure = report.document_structure.as_ref().unwrap();
+        assert_eq!(structure.name, "document");
+        assert_eq!(structure.get_property("page_count"), Some(&"5".to_string()));
+        assert!(structure.find_by_type("section").iter().all(|n| n.properties.is_empty()));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = generator.export_to_zip(&report, dir.path().join("report.zip")).unwrap();
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path).unwrap()).unwrap();
+        assert_eq!(archive.len(), 4);
+
+        let mut logs = String::new();
+        archive.by_name("logs.txt").unwrap().read_to_string(&mut logs).unwrap();
+        assert!(logs.contains("ERROR [store]"));
+        assert!(!logs.contains("alice"));
+
+        let mut json = String::new();
+        archive.by_name("document_structure.json").unwrap().read_to_string(&mut json).unwrap();
+        assert!(!json.contains("Introduction"));
+        assert!(!json.contains("Sample Document"));
+    }
 }
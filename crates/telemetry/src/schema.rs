@@ -0,0 +1,257 @@
+//! Event schemas and sampling.
+//!
+//! Every event passes through a [`SchemaRegistry`] before it is queued.
+//! Events with a registered schema have undeclared properties and
+//! measurements stripped and are rejected if a declared one has the wrong
+//! type, so only fields someone has reviewed leave the machine. High
+//! frequency events can be sampled so only a fraction of them are sent.
+
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+use crate::error::{TelemetryError, TelemetryResult};
+use crate::event::TelemetryEvent;
+
+/// Measurement added to sampled events so counts can be scaled back up.
+pub const SAMPLE_RATE_MEASUREMENT: &str = "sample_rate";
+
+/// The type of an event property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyType {
+    String,
+    Bool,
+    Number,
+    /// A string from a fixed set of values
+    Enum(Vec<String>),
+}
+
+impl PropertyType {
+    /// Create an enum type from its values.
+    pub fn one_of(values: &[&str]) -> Self {
+        PropertyType::Enum(values.iter().map(|v| v.to_string()).collect())
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        match (self, value) {
+            (PropertyType::String, Value::String(_)) => true,
+            (PropertyType::Bool, Value::Bool(_)) => true,
+            (PropertyType::Number, Value::Number(_)) => true,
+            (PropertyType::Enum(values), Value::String(s)) => values.contains(s),
+            _ => false,
+        }
+    }
+}
+
+/// The properties and measurements an event may carry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventSchema {
+    /// Event name
+    pub name: String,
+    /// Allowed properties and their types
+    pub properties: HashMap<String, PropertyType>,
+    /// Allowed measurements
+    pub measurements: HashSet<String>,
+}
+
+impl EventSchema {
+    /// Create a schema with no fields.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            properties: HashMap::new(),
+            measurements: HashSet::new(),
+        }
+    }
+
+    /// Allow a property of the given type.
+    pub fn with_property(mut self, key: &str, ty: PropertyType) -> Self {
+        self.properties.insert(key.to_string(), ty);
+        self
+    }
+
+    /// Allow a measurement.
+    pub fn with_measurement(mut self, key: &str) -> Self {
+        self.measurements.insert(key.to_string());
+        self
+    }
+}
+
+/// Schemas and sample rates for telemetry events.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaRegistry {
+    schemas: HashMap<String, EventSchema>,
+    sample_rates: HashMap<String, f64>,
+    reject_unknown: bool,
+}
+
+impl SchemaRegistry {
+    /// Create an empty registry that lets unknown events through.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with schemas for the core events.
+    pub fn core() -> Self {
+        let source = PropertyType::one_of(&["menu", "toolbar", "keyboard", "context_menu", "api"]);
+        let mut registry = Self::new();
+        for schema in [
+            EventSchema::new("app_start").with_property("cold_start", PropertyType::Bool),
+            EventSchema::new("app_exit").with_measurement("session_duration_ms"),
+            EventSchema::new("doc_open")
+                .with_property("format", PropertyType::String)
+                .with_measurement("size_kb")
+                .with_measurement("page_count"),
+            EventSchema::new("doc_save")
+                .with_property("format", PropertyType::String)
+                .with_measurement("size_kb")
+                .with_measurement("duration_ms"),
+            EventSchema::new("doc_export")
+                .with_property("format", PropertyType::String)
+                .with_property("success", PropertyType::Bool),
+            EventSchema::new("command_execute")
+                .with_property("command_id", PropertyType::String)
+                .with_property("source", source),
+            EventSchema::new("feature_use").with_property("feature_name", PropertyType::String),
+            EventSchema::new("error")
+                .with_property("error_type", PropertyType::String)
+                .with_property("error_message", PropertyType::String),
+            EventSchema::new("perf_metric")
+                .with_property("metric_name", PropertyType::String)
+                .with_measurement("value_ms"),
+            EventSchema::new("crash")
+                .with_property("crash_id", PropertyType::String)
+                .with_property("crash_type", PropertyType::String)
+                .with_property("error_message", PropertyType::String)
+                .with_property("stack_trace", PropertyType::String)
+                .with_measurement("breadcrumb_count"),
+        ] {
+            registry.register(schema);
+        }
+        registry
+    }
+
+    /// Reject events without a schema instead of passing them through.
+    pub fn with_reject_unknown(mut self, reject: bool) -> Self {
+        self.reject_unknown = reject;
+        self
+    }
+
+    /// Register or replace a schema.
+    pub fn register(&mut self, schema: EventSchema) {
+        self.schemas.insert(schema.name.clone(), schema);
+    }
+
+    /// Get the schema for an event name.
+    pub fn get(&self, name: &str) -> Option<&EventSchema> {
+        self.schemas.get(name)
+    }
+
+    /// Send only this fraction of events with the given name.
+    pub fn set_sample_rate(&mut self, name: &str, rate: f64) {
+        self.sample_rates.insert(name.to_string(), rate.clamp(0.0, 1.0));
+    }
+
+    /// The fraction of events with the given name that are sent.
+    pub fn sample_rate(&self, name: &str) -> f64 {
+        self.sample_rates.get(name).copied().unwrap_or(1.0)
+    }
+
+    /// Check an event against its schema, stripping undeclared fields.
+    pub fn validate(&self, mut event: TelemetryEvent) -> TelemetryResult<TelemetryEvent> {
+        let Some(schema) = self.schemas.get(&event.event_name) else {
+            if self.reject_unknown {
+                return Err(TelemetryError::InvalidEvent(format!("unknown event '{}'", event.event_name)));
+            }
+            return Ok(event);
+        };
+
+        event.properties.retain(|key, _| schema.properties.contains_key(key));
+        for (key, value) in &event.properties {
+            if !schema.properties[key].matches(value) {
+                return Err(TelemetryError::InvalidEvent(format!(
+                    "{}.{}: unexpected value {}",
+                    event.event_name, key, value
+                )));
+            }
+        }
+
+        event.measurements.retain(|key, _| schema.measurements.contains(key));
+        if let Some((key, _)) = event.measurements.iter().find(|(_, value)| !value.is_finite()) {
+            return Err(TelemetryError::InvalidEvent(format!(
+                "{}.{}: measurement is not finite",
+                event.event_name, key
+            )));
+        }
+        Ok(event)
+    }
+
+    /// Decide whether to send an event, tagging kept events with their
+    /// sample rate.
+    ///
+    /// The decision hashes the event ID, so it is the same each time an
+    /// event is checked.
+    pub fn sample(&self, mut event: TelemetryEvent) -> Option<TelemetryEvent> {
+        let rate = self.sample_rate(&event.event_name);
+        if rate >= 1.0 {
+            return Some(event);
+        }
+        if sample_point(&event.event_id) >= rate {
+            return None;
+        }
+        event.measurements.insert(SAMPLE_RATE_MEASUREMENT.to_string(), rate);
+        Some(event)
+    }
+}
+
+/// Map an event ID to a point in `[0, 1)` (FNV-1a).
+fn sample_point(event_id: &str) -> f64 {
+    let hash = event_id
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3));
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::CoreEvent;
+
+    #[test]
+    fn test_validate_core_events() {
+        let registry = SchemaRegistry::core();
+        let event = CoreEvent::CommandExecute {
+            command_id: "bold".to_string(),
+            source: crate::event::CommandSource::Keyboard,
+        }
+        .to_event("s", "1.0", "test")
+        .with_property("file_name", "secret.docx")
+        .with_measurement("extra", 1.0);
+
+        let event = registry.validate(event).unwrap();
+        assert_eq!(event.properties.len(), 2);
+        assert!(event.measurements.is_empty());
+
+        let bad = TelemetryEvent::new("command_execute", "s", "1.0", "test").with_property("source", "voice");
+        assert!(matches!(registry.validate(bad), Err(TelemetryError::InvalidEvent(_))));
+
+        // Unknown events pass unless the registry is strict
+        let custom = TelemetryEvent::new("ui_interaction", "s", "1.0", "test");
+        assert!(registry.validate(custom.clone()).is_ok());
+        assert!(registry.with_reject_unknown(true).validate(custom).is_err());
+    }
+
+    #[test]
+    fn test_sampling() {
+        let mut registry = SchemaRegistry::new();
+        registry.set_sample_rate("perf_metric", 0.1);
+
+        let kept: Vec<_> = (0..10_000)
+            .filter_map(|_| registry.sample(TelemetryEvent::new("perf_metric", "s", "1.0", "test")))
+            .collect();
+        assert!((800..1200).contains(&kept.len()), "kept {}", kept.len());
+        assert_eq!(kept[0].measurements.get(SAMPLE_RATE_MEASUREMENT), Some(&0.1));
+
+        let event = TelemetryEvent::new("doc_open", "s", "1.0", "test");
+        assert_eq!(registry.sample(event.clone()), Some(event));
+    }
+}
@@ -1,9 +1,16 @@
 //! Transport layer for sending telemetry data.
+//!
+//! With a queue path configured, queued events are also written to a
+//! [`PersistentQueue`] and reloaded on the next start, so nothing is lost
+//! to a crash or a long offline period. Failed sends back off
+//! exponentially before the next attempt.
 
-use std::time::Duration;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 use crate::error::{TelemetryError, TelemetryResult};
 use crate::event::TelemetryEvent;
+use crate::queue::PersistentQueue;
 
 /// Configuration for telemetry transport.
 #[derive(Debug, Clone)]
@@ -18,6 +25,14 @@ pub struct TransportConfig {
     pub max_queue_size: usize,
     /// Request timeout
     pub timeout: Duration,
+    /// File to persist queued events to, if any
+    pub queue_path: Option<PathBuf>,
+    /// Maximum size of the queue file before the oldest events are evicted
+    pub max_queue_bytes: u64,
+    /// Delay before retrying after the first failed send
+    pub retry_backoff: Duration,
+    /// Longest delay between retries
+    pub max_retry_backoff: Duration,
 }
 
 impl Default for TransportConfig {
@@ -28,6 +43,10 @@ impl Default for TransportConfig {
             flush_interval: Duration::from_secs(60),
             max_queue_size: 10000,
             timeout: Duration::from_secs(30),
+            queue_path: None,
+            max_queue_bytes: 10 * 1024 * 1024,
+            retry_backoff: Duration::from_secs(1),
+            max_retry_backoff: Duration::from_secs(300),
         }
     }
 }
@@ -64,6 +83,25 @@ impl TransportConfig {
         self.timeout = timeout;
         self
     }
+
+    /// Persist queued events to a file.
+    pub fn with_queue_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.queue_path = Some(path.into());
+        self
+    }
+
+    /// Set the maximum size of the queue file.
+    pub fn with_max_queue_bytes(mut self, bytes: u64) -> Self {
+        self.max_queue_bytes = bytes;
+        self
+    }
+
+    /// Set the initial and maximum retry backoff.
+    pub fn with_retry_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.retry_backoff = initial;
+        self.max_retry_backoff = max;
+        self
+    }
 }
 
 /// Transport layer for sending telemetry events to a remote endpoint.
@@ -71,8 +109,10 @@ impl TransportConfig {
 pub struct TelemetryTransport {
     config: TransportConfig,
     batch: Vec<TelemetryEvent>,
+    store: Option<PersistentQueue>,
     offline: bool,
     failed_send_count: u32,
+    retry_at: Option<Instant>,
 }
 
 impl TelemetryTransport {
@@ -82,27 +122,66 @@ impl TelemetryTransport {
     }
 
     /// Create a new transport with full configuration.
+    ///
+    /// Events left in the queue file by an earlier run are queued again.
+    /// If the file can't be opened the transport keeps events in memory.
     pub fn with_config(config: TransportConfig) -> Self {
+        let store = config
+            .queue_path
+            .as_ref()
+            .and_then(|path| PersistentQueue::open(path, config.max_queue_bytes).ok());
+        let batch = store.as_ref().map_or_else(Vec::new, |store| store.peek(store.len()));
         Self {
             config,
-            batch: Vec::new(),
+            batch,
+            store,
             offline: false,
             failed_send_count: 0,
+            retry_at: None,
         }
     }
 
     /// Queue an event for sending.
+    ///
+    /// If writing to the queue file fails, the transport stops persisting
+    /// and keeps events in memory.
     pub fn queue(&mut self, event: TelemetryEvent) -> TelemetryResult<()> {
         if self.batch.len() >= self.config.max_queue_size {
             return Err(TelemetryError::QueueFull);
         }
+        if let Some(store) = &mut self.store {
+            match store.push(&event) {
+                Ok(evicted) => {
+                    self.batch.drain(..evicted.min(self.batch.len()));
+                }
+                Err(TelemetryError::QueueFull) => return Err(TelemetryError::QueueFull),
+                Err(_) => self.store = None,
+            }
+        }
         self.batch.push(event);
         Ok(())
     }
 
     /// Check if the batch is ready to be flushed.
+    ///
+    /// Always false while backing off after a failed send.
     pub fn should_flush(&self) -> bool {
-        self.batch.len() >= self.config.batch_size
+        self.batch.len() >= self.config.batch_size && self.retry_delay().is_none()
+    }
+
+    /// Time left before the next retry, if backing off after a failed send.
+    pub fn retry_delay(&self) -> Option<Duration> {
+        let delay = self.retry_at?.saturating_duration_since(Instant::now());
+        (!delay.is_zero()).then_some(delay)
+    }
+
+    /// Backoff after the current run of failed sends.
+    fn backoff(&self) -> Duration {
+        let exponent = self.failed_send_count.saturating_sub(1).min(16);
+        self.config
+            .retry_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.config.max_retry_backoff)
     }
 
     /// Get the number of queued events.
@@ -117,43 +196,46 @@ impl TelemetryTransport {
 
     /// Flush all queued events.
     ///
+    /// Events are sent in batches of `batch_size`, each removed from the
+    /// queue only once it has been sent. A failure leaves the rest queued
+    /// and starts a backoff before the next automatic flush.
+    ///
     /// In a real implementation, this would send events to the endpoint.
-    /// For now, this simulates the send and clears the batch.
+    /// For now, this simulates the send.
     pub async fn flush(&mut self) -> TelemetryResult<()> {
         if self.offline {
             return Err(TelemetryError::Offline);
         }
 
-        if self.batch.is_empty() {
-            return Ok(());
-        }
-
-        // In a real implementation, we would serialize and send the events
-        // For now, we just simulate success
-        let result = self.send_batch().await;
-
-        match result {
-            Ok(()) => {
-                self.failed_send_count = 0;
-                self.batch.clear();
-                Ok(())
-            }
-            Err(e) => {
+        while !self.batch.is_empty() {
+            let count = self.batch.len().min(self.config.batch_size.max(1));
+            if let Err(e) = self.send_batch(&self.batch[..count]).await {
                 self.failed_send_count += 1;
-                Err(e)
+                self.retry_at = Some(Instant::now() + self.backoff());
+                return Err(e);
+            }
+
+            self.failed_send_count = 0;
+            self.retry_at = None;
+            self.batch.drain(..count);
+            if let Some(store) = &mut self.store {
+                if store.remove_front(count).is_err() {
+                    self.store = None;
+                }
             }
         }
+        Ok(())
     }
 
-    /// Internal method to send the batch.
-    async fn send_batch(&self) -> TelemetryResult<()> {
+    /// Internal method to send a batch.
+    async fn send_batch(&self, events: &[TelemetryEvent]) -> TelemetryResult<()> {
         if self.config.endpoint.is_empty() {
             // No endpoint configured - silently succeed (useful for testing/dev)
             return Ok(());
         }
 
         // Serialize the batch
-        let _payload = serde_json::to_string(&self.batch)?;
+        let _payload = serde_json::to_string(events)?;
 
         // In a real implementation, this would use an HTTP client to POST
         // to the endpoint. For now, we just simulate success.
@@ -186,6 +268,11 @@ impl TelemetryTransport {
         self.failed_send_count
     }
 
+    /// Check if queued events are being written to disk.
+    pub fn is_persistent(&self) -> bool {
+        self.store.is_some()
+    }
+
     /// Get a reference to the currently queued events.
     pub fn queued_events(&self) -> &[TelemetryEvent] {
         &self.batch
@@ -199,12 +286,22 @@ impl TelemetryTransport {
     /// Clear all queued events without sending.
     pub fn clear(&mut self) {
         self.batch.clear();
+        self.clear_store();
     }
 
     /// Take ownership of queued events (for persistence/retry).
     pub fn take_queued(&mut self) -> Vec<TelemetryEvent> {
+        self.clear_store();
         std::mem::take(&mut self.batch)
     }
+
+    fn clear_store(&mut self) {
+        if let Some(store) = &mut self.store {
+            if store.clear().is_err() {
+                self.store = None;
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -374,6 +471,51 @@ mod tests {
         assert_eq!(transport.failed_send_count(), 0);
     }
 
+    #[tokio::test]
+    async fn test_transport_persists_until_sent() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = TransportConfig::new("")
+            .with_batch_size(2)
+            .with_queue_path(dir.path().join("queue.jsonl"));
+
+        let mut transport = TelemetryTransport::with_config(config.clone());
+        assert!(transport.is_persistent());
+        for name in ["a", "b", "c"] {
+            transport.queue(make_event(name)).unwrap();
+        }
+        transport.set_offline(true);
+        assert!(transport.flush().await.is_err());
+        drop(transport);
+
+        // Events queued before a restart are sent once back online
+        let mut transport = TelemetryTransport::with_config(config.clone());
+        assert_eq!(transport.queued_count(), 3);
+        assert_eq!(transport.queued_events()[2].event_name, "c");
+        transport.flush().await.unwrap();
+        assert_eq!(TelemetryTransport::with_config(config).queued_count(), 0);
+    }
+
+    #[test]
+    fn test_transport_retry_backoff() {
+        let config = TransportConfig::new("")
+            .with_batch_size(1)
+            .with_retry_backoff(Duration::from_secs(1), Duration::from_secs(10));
+        let mut transport = TelemetryTransport::with_config(config);
+        transport.queue(make_event("test")).unwrap();
+
+        let backoffs: Vec<_> = (1..=5)
+            .map(|failures| {
+                transport.failed_send_count = failures;
+                transport.backoff().as_secs()
+            })
+            .collect();
+        assert_eq!(backoffs, [1, 2, 4, 8, 10]);
+
+        transport.retry_at = Some(Instant::now() + transport.backoff());
+        assert!(transport.retry_delay().is_some());
+        assert!(!transport.should_flush());
+    }
+
     #[test]
     fn test_transport_config_access() {
         let transport = TelemetryTransport::new("https://example.com");
@@ -0,0 +1,128 @@
+//! Cursor clusters
+//!
+//! The caret moves, and deletion removes, whole cursor clusters. A cursor
+//! cluster is an extended grapheme cluster, joined with the next one when
+//! it ends in a virama or coeng that stacks the following consonant: Indic
+//! conjuncts (क्ष), Khmer subscripts (ក្ស) and Myanmar stacked consonants
+//! (က္က) are shaped as one unit, so putting the caret inside one or
+//! deleting half of it breaks the conjunct. The grapheme rules already
+//! keep most of these together; the joining covers scripts they leave
+//! out, such as Kannada.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Zero width joiner, which may follow a virama to request a half form
+const ZWJ: char = '\u{200D}';
+
+/// Characters that stack the consonant after them
+fn is_stacker(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{094D}' // Devanagari virama
+            | '\u{09CD}' // Bengali virama
+            | '\u{0ACD}' // Gujarati virama
+            | '\u{0B4D}' // Oriya virama
+            | '\u{0C4D}' // Telugu virama
+            | '\u{0CCD}' // Kannada virama
+            | '\u{0D4D}' // Malayalam virama
+            | '\u{1039}' // Myanmar virama
+            | '\u{17D2}' // Khmer coeng
+            | '\u{1A60}' // Tai Tham sakot
+    )
+}
+
+/// Split text into cursor clusters, with the byte offset of each
+pub fn cluster_indices(text: &str) -> Vec<(usize, &str)> {
+    let mut clusters: Vec<(usize, &str)> = Vec::new();
+    for (start, grapheme) in text.grapheme_indices(true) {
+        if let Some((last_start, last)) = clusters.last_mut() {
+            let stacks = last.trim_end_matches(ZWJ).ends_with(is_stacker);
+            if stacks && grapheme.starts_with(char::is_alphabetic) {
+                *last = &text[*last_start..start + grapheme.len()];
+                continue;
+            }
+        }
+        clusters.push((start, grapheme));
+    }
+    clusters
+}
+
+/// Character offsets between cursor clusters, from 0 to the text's length
+pub fn cluster_boundaries(text: &str) -> Vec<usize> {
+    let mut boundaries = vec![0];
+    let mut offset = 0;
+    for (_, cluster) in cluster_indices(text) {
+        offset += cluster.chars().count();
+        boundaries.push(offset);
+    }
+    boundaries
+}
+
+/// The first cluster boundary after a character offset
+///
+/// At or past the end of the text, this is the text's length.
+pub fn next_cluster_boundary(text: &str, offset: usize) -> usize {
+    let boundaries = cluster_boundaries(text);
+    let end = boundaries.last().copied().unwrap_or(0);
+    boundaries.into_iter().find(|&boundary| boundary > offset).unwrap_or(end)
+}
+
+/// The last cluster boundary before a character offset, or 0
+pub fn prev_cluster_boundary(text: &str, offset: usize) -> usize {
+    cluster_boundaries(text)
+        .into_iter()
+        .rev()
+        .find(|&boundary| boundary < offset)
+        .unwrap_or(0)
+}
+
+/// Whether a character offset falls between cursor clusters
+pub fn is_cluster_boundary(text: &str, offset: usize) -> bool {
+    cluster_boundaries(text).contains(&offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (script, text, expected clusters)
+    const FIXTURES: &[(&str, &str, &[&str])] = &[
+        ("Latin", "cafe\u{301}", &["c", "a", "f", "e\u{301}"]),
+        ("Devanagari", "क्षत्रिय", &["क्ष", "त्रि", "य"]),
+        ("Devanagari half form", "क्\u{200D}ष", &["क्\u{200D}ष"]),
+        ("Bengali", "স্ত্রী", &["স্ত্রী"]),
+        ("Kannada", "ಕ್ಷಣ", &["ಕ್ಷ", "ಣ"]),
+        ("Tamil", "க்ஷ", &["க்", "ஷ"]),
+        ("Khmer", "ក្សត្រ", &["ក្ស", "ត្រ"]),
+        ("Myanmar", "မန္တလေး", &["မ", "န္တ", "လေ", "း"]),
+        ("Thai", "น้ำใจ", &["น้ำ", "ใ", "จ"]),
+        ("Emoji", "a\u{1F468}\u{200D}\u{1F469}", &["a", "\u{1F468}\u{200D}\u{1F469}"]),
+    ];
+
+    #[test]
+    fn test_cluster_fixtures() {
+        for (script, text, expected) in FIXTURES {
+            let clusters: Vec<&str> = cluster_indices(text).into_iter().map(|(_, c)| c).collect();
+            assert_eq!(&clusters, expected, "{}", script);
+        }
+
+        // "ក្សត្រ" is two clusters of three characters each
+        let text = "ក្សត្រ";
+        assert_eq!(cluster_boundaries(text), [0, 3, 6]);
+        assert_eq!(next_cluster_boundary(text, 1), 3);
+        assert_eq!(prev_cluster_boundary(text, 5), 3);
+        assert_eq!(next_cluster_boundary(text, 6), 6);
+        assert!(!is_cluster_boundary(text, 2));
+    }
+
+    #[test]
+    fn test_fallback_shaping_advances_once_per_grapheme() {
+        let shaper = crate::TextShaper::new();
+        for (script, text, _) in FIXTURES {
+            let shaped = shaper.shape(text, 12.0).unwrap();
+            let advancing = shaped.glyphs.iter().filter(|g| g.x_advance > 0).count();
+            assert_eq!(advancing, text.graphemes(true).count(), "{}", script);
+            assert_eq!(shaped.glyphs.len(), text.chars().count(), "{}", script);
+        }
+    }
+}
@@ -5,6 +5,7 @@
 
 use crate::discovery::{FontDiscovery, FontIndex, FontInfo};
 use crate::fallback::{FallbackChain, FontResolution, Script, SubstitutionReason, SubstitutionWarning};
+use crate::math_table::{MathTable, DEFAULT_MATH_FAMILIES};
 use crate::{FontId, FontMetrics, FontStyle, FontWeight, Result, TextError};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -461,6 +462,25 @@ impl FontManager {
         }
     }
 
+    /// Load the OpenType MATH table of the first available math font
+    ///
+    /// Tries `families` in order, followed by well-known math fonts. Returns
+    /// `None` when no installed font carries a MATH table, in which case math
+    /// layout falls back to synthetic metrics.
+    pub fn load_math_table(&self, families: &[&str]) -> Option<MathTable> {
+        families
+            .iter()
+            .chain(DEFAULT_MATH_FAMILIES)
+            .filter(|family| self.is_font_available(family))
+            .find_map(|family| {
+                let (loaded, _) = self
+                    .resolve_and_load(family, FontWeight::Normal, FontStyle::Normal)
+                    .ok()?;
+                MathTable::from_font_data(&loaded.data, loaded.info.font_index)
+                    .map(|table| table.with_font_family(&loaded.info.family))
+            })
+    }
+
     /// Get the fallback chain configuration
     pub fn fallback_chain(&self) -> &FallbackChain {
         &self.fallback_chain
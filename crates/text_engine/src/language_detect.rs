@@ -0,0 +1,217 @@
+//! Language detection
+//!
+//! Guesses the language of a piece of text so the spellchecker can pick a
+//! dictionary for text that has no proofing language set. Detection compares
+//! the character trigrams of the text against a ranked profile of the most
+//! common trigrams of each supported language, with word boundaries marked
+//! by spaces. It only covers the languages the spellchecker supports and
+//! reports English as US English.
+
+use crate::spellcheck::Language;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Minimum number of letters needed to attempt detection
+pub const MIN_DETECTION_LETTERS: usize = 20;
+
+/// Minimum confidence for a detection to be used
+pub const DEFAULT_MIN_CONFIDENCE: f32 = 0.2;
+
+/// Most common trigrams per language, most frequent first
+const PROFILES: &[(Language, &[&str])] = &[
+    (
+        Language::EnUs,
+        &[
+            " th", "the", "he ", "nd ", " an", "and", " of", "of ", "ing", "ng ", " to", "to ", "ed ",
+            " in", "in ", "er ", "ion", "tio", " a ", "is ", "es ", "at ", "re ", "on ", "hat", " be",
+            "ent", "her", "ter", "for", " wh", "al ", "tha", "ly ", " is", "ere", "st ", "as ", "it ",
+            "his", " it", "ith", "wit", " wi", "you", " yo", "ou ", "ll ", "ve ", " ha",
+        ],
+    ),
+    (
+        Language::FrFr,
+        &[
+            "es ", " de", "de ", "le ", " le", "ent", " la", "la ", "on ", "nt ", "les", " et", "et ",
+            "ion", "re ", " co", "tio", " qu", "que", "ue ", "des", " pa", "ne ", " un", "ait", "men",
+            " po", "our", "ur ", "eme", " en", "en ", "par", "est", "ons", "ans", " du", "du ", " à ",
+            "ées", "été", " ét", "ais", "eur", "ous", " vo", "ire", "une", "qui", "dan",
+        ],
+    ),
+    (
+        Language::EsEs,
+        &[
+            " de", "de ", "os ", " la", "la ", "as ", " el", "el ", "es ", " qu", "que", "ue ", " en",
+            "en ", "ión", "ent", "ado", "on ", "del", " co", "nte", " lo", "los", "ra ", " pa", "er ",
+            "ar ", "aci", "cio", " se", "do ", "ien", "por", " po", "ero", "con", "las", " es", "est",
+            "una", " un", "ida", "ón ", "mos", " y ", "ada", "sta", "ndo", " su", "ño ",
+        ],
+    ),
+    (
+        Language::DeDe,
+        &[
+            "en ", "er ", " de", "der", "ie ", "ich", "die", " di", "ein", "sch", "che", " un", "und",
+            "nd ", "ch ", " ei", "den", "in ", " da", "te ", "gen", "ung", "cht", " zu", "ine", "es ",
+            "ten", "das", "ber", "ist", " is", "auf", " ni", "nic", " mi", "mit", "it ", "ere", "nde",
+            "sie", " si", "ße ", "ür ", " fü", "für", "ach", "hen", "eit", " au", "ges",
+        ],
+    ),
+];
+
+/// A detected language with how sure the detector is
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetectedLanguage {
+    /// The most likely language
+    pub language: Language,
+    /// How far ahead of the runner-up it is, from 0 to 1
+    pub confidence: f32,
+}
+
+/// N-gram language detector
+#[derive(Debug, Clone)]
+pub struct LanguageDetector {
+    /// Trigram ranks per language
+    profiles: Vec<(Language, HashMap<&'static str, usize>)>,
+    /// Minimum confidence for `detect` to return a language
+    min_confidence: f32,
+}
+
+impl Default for LanguageDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LanguageDetector {
+    /// Create a detector for the supported languages
+    pub fn new() -> Self {
+        let profiles = PROFILES
+            .iter()
+            .map(|(language, trigrams)| {
+                let ranks = trigrams.iter().enumerate().map(|(rank, &t)| (t, rank)).collect();
+                (*language, ranks)
+            })
+            .collect();
+        Self {
+            profiles,
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+        }
+    }
+
+    /// Set the minimum confidence for a detection
+    pub fn with_min_confidence(mut self, min_confidence: f32) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Score every language for a text, best first
+    ///
+    /// Scores are the share of the text's trigrams found in each profile,
+    /// weighted towards the most common ones.
+    pub fn scores(&self, text: &str) -> Vec<(Language, f32)> {
+        let trigrams = trigram_counts(text);
+        let total: usize = trigrams.values().sum();
+        if total == 0 {
+            return Vec::new();
+        }
+
+        let mut scores: Vec<(Language, f32)> = self
+            .profiles
+            .iter()
+            .map(|(language, ranks)| {
+                let weighted: f32 = trigrams
+                    .iter()
+                    .filter_map(|(trigram, &count)| {
+                        let rank = *ranks.get(trigram.as_str())?;
+                        Some(count as f32 * (1.0 - rank as f32 / (2 * ranks.len()) as f32))
+                    })
+                    .sum();
+                (*language, weighted / total as f32)
+            })
+            .collect();
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scores
+    }
+
+    /// Detect the language of a text
+    ///
+    /// Returns None for short text or when no language is clearly ahead.
+    pub fn detect(&self, text: &str) -> Option<DetectedLanguage> {
+        if text.chars().filter(|c| c.is_alphabetic()).count() < MIN_DETECTION_LETTERS {
+            return None;
+        }
+        let scores = self.scores(text);
+        let (language, best) = *scores.first()?;
+        let runner_up = scores.get(1).map_or(0.0, |s| s.1);
+        if best <= 0.0 {
+            return None;
+        }
+        let confidence = (best - runner_up) / best;
+        (confidence >= self.min_confidence).then_some(DetectedLanguage { language, confidence })
+    }
+
+    /// Pick the spellcheck language for text
+    ///
+    /// An explicit proofing language wins. Otherwise the detected language
+    /// is used, keeping `default` when it's a variant of the same language
+    /// (so British English text isn't checked as US English).
+    pub fn resolve(&self, explicit: Option<&str>, text: &str, default: Language) -> Language {
+        if let Some(language) = explicit.and_then(Language::from_code) {
+            return language;
+        }
+        match self.detect(text) {
+            Some(detected) if primary_subtag(detected.language) != primary_subtag(default) => detected.language,
+            _ => default,
+        }
+    }
+}
+
+/// The language part of a language's code, e.g. "en" for en-GB
+fn primary_subtag(language: Language) -> &'static str {
+    language.code().split('-').next().unwrap_or_default()
+}
+
+/// Count the trigrams of lowercased words, padded with spaces
+fn trigram_counts(text: &str) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for word in text.split(|c: char| !c.is_alphabetic()).filter(|w| !w.is_empty()) {
+        let padded: Vec<char> = std::iter::once(' ')
+            .chain(word.chars().flat_map(char::to_lowercase))
+            .chain(std::iter::once(' '))
+            .collect();
+        for window in padded.windows(3) {
+            *counts.entry(window.iter().collect::<String>()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_languages() {
+        let detector = LanguageDetector::new();
+        let samples = [
+            (Language::EnUs, "The weather is nice today and we are going to the park with the children."),
+            (Language::FrFr, "Les enfants sont allés à la plage avec leurs parents pendant les vacances de été."),
+            (Language::EsEs, "Los niños fueron a la playa con sus padres durante las vacaciones de verano."),
+            (Language::DeDe, "Die Kinder sind mit ihren Eltern in den Ferien an den Strand gefahren und haben gespielt."),
+        ];
+        for (language, text) in samples {
+            assert_eq!(detector.detect(text).map(|d| d.language), Some(language), "{}", text);
+        }
+        assert!(detector.detect("Too short").is_none());
+    }
+
+    #[test]
+    fn test_resolve_prefers_explicit_and_default_variant() {
+        let detector = LanguageDetector::new();
+        let english = "The colour of the sky is grey and the weather is cold this morning.";
+        assert_eq!(detector.resolve(None, english, Language::EnGb), Language::EnGb);
+        assert_eq!(detector.resolve(Some("de-DE"), english, Language::EnGb), Language::DeDe);
+        let french = "Le chat est sur la table et les enfants jouent dans le jardin.";
+        assert_eq!(detector.resolve(None, french, Language::EnUs), Language::FrFr);
+    }
+}
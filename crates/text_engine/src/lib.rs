@@ -6,25 +6,37 @@
 //! # Modules
 //!
 //! - `shaper`: Text shaping using rustybuzz
+//! - `shape_cache`: Shaped run cache shared across layout passes
 //! - `font`: Font types and basic font management
 //! - `metrics`: Text metric calculations
+//! - `clusters`: Cursor clusters for caret movement and deletion
 //! - `discovery`: System font discovery and enumeration
 //! - `fallback`: Font fallback chains and substitution rules
 //! - `font_manager`: Central font management integrating all components
 //! - `spellcheck`: Spell checking and dictionary support
+//! - `language_detect`: N-gram language detection for proofing
+//! - `thesaurus`: Offline synonym and antonym lookup
+//! - `math_table`: OpenType MATH table constants and glyph assembly
 
 mod shaper;
+mod shape_cache;
 mod font;
 mod metrics;
+mod clusters;
 mod error;
 pub mod discovery;
 pub mod fallback;
 pub mod font_manager;
 pub mod spellcheck;
+pub mod language_detect;
+pub mod thesaurus;
+pub mod math_table;
 
 pub use shaper::*;
+pub use shape_cache::*;
 pub use font::*;
 pub use metrics::*;
+pub use clusters::*;
 pub use error::*;
 
 // Re-export commonly used types from submodules
@@ -32,3 +44,9 @@ pub use discovery::{FontDiscovery, FontIndex, FontInfo};
 pub use fallback::{FallbackChain, FontResolution, Script, SubstitutionReason, SubstitutionWarning};
 pub use font_manager::{FontManager, FontManagerConfig, FontSubstitutionRecord, FontSubstitutionSummary, LoadedFont, LoadedFontId};
 pub use spellcheck::{DictionarySpellChecker, IgnoreRules, Language, SpellChecker, SpellingError};
+pub use language_detect::{DetectedLanguage, LanguageDetector};
+pub use thesaurus::{Thesaurus, ThesaurusEntry};
+pub use math_table::{
+    AssembledPart, GlyphStretch, MathConstants, MathGlyphConstruction, MathGlyphPart,
+    MathGlyphVariant, MathTable, DEFAULT_MATH_FAMILIES,
+};
@@ -0,0 +1,512 @@
+//! OpenType MATH Table Module
+//!
+//! Extracts the data math layout needs from a font's OpenType MATH table:
+//! layout constants, italic corrections, and the size variants and glyph
+//! assemblies used to stretch delimiters, radicals, and arrows.
+//!
+//! Values are kept in font design units; callers scale them by
+//! `font_size / units_per_em`.
+
+use rustybuzz::ttf_parser::{self, math, GlyphId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Well-known fonts with a MATH table, in order of preference
+pub const DEFAULT_MATH_FAMILIES: &[&str] = &[
+    "Cambria Math",
+    "STIX Two Math",
+    "Latin Modern Math",
+    "TeX Gyre Termes Math",
+    "Libertinus Math",
+    "Asana Math",
+    "DejaVu Math TeX Gyre",
+];
+
+/// Characters that stretch vertically (delimiters, radicals, integrals)
+const VERTICAL_STRETCHY: &[char] = &[
+    '(', ')', '[', ']', '{', '}', '|', '\u{2016}', '\u{27E8}', '\u{27E9}', '\u{2308}',
+    '\u{2309}', '\u{230A}', '\u{230B}', '\u{221A}', '\u{222B}', '\u{222C}', '\u{222D}',
+    '\u{222E}', '\u{2211}', '\u{220F}', '\u{2210}', '\u{22C3}', '\u{22C2}',
+];
+
+/// Characters that stretch horizontally (arrows, over/under braces, bars)
+const HORIZONTAL_STRETCHY: &[char] = &[
+    '\u{2190}', '\u{2192}', '\u{2194}', '\u{21D0}', '\u{21D2}', '\u{21D4}', '\u{21CC}',
+    '\u{23DE}', '\u{23DF}', '\u{23B4}', '\u{23B5}', '\u{203E}', '_', '\u{0302}', '\u{0303}',
+];
+
+/// Character ranges whose italic corrections are extracted
+const ITALIC_RANGES: &[(char, char)] = &[
+    ('A', 'Z'),
+    ('a', 'z'),
+    ('\u{0391}', '\u{03C9}'),
+    ('\u{1D400}', '\u{1D6A3}'),
+    ('\u{1D6A8}', '\u{1D7C9}'),
+];
+
+// =============================================================================
+// Constants
+// =============================================================================
+
+/// Layout constants from the MATH table, in font design units
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MathConstants {
+    /// Scale of first-level scripts, in percent
+    pub script_percent_scale_down: i16,
+    /// Scale of second-level scripts, in percent
+    pub script_script_percent_scale_down: i16,
+    /// Height of the math axis above the baseline
+    pub axis_height: i16,
+    /// Standard subscript shift down
+    pub subscript_shift_down: i16,
+    /// Standard superscript shift up
+    pub superscript_shift_up: i16,
+    /// Minimum gap between a superscript's bottom and a subscript's top
+    pub sub_superscript_gap_min: i16,
+    /// Space added after a script
+    pub space_after_script: i16,
+    /// Thickness of the fraction bar
+    pub fraction_rule_thickness: i16,
+    /// Minimum gap between the numerator and the fraction bar
+    pub fraction_numerator_gap_min: i16,
+    /// Minimum gap between the denominator and the fraction bar
+    pub fraction_denominator_gap_min: i16,
+    /// Gap between the radical rule and the radicand
+    pub radical_vertical_gap: i16,
+    /// Thickness of the radical rule
+    pub radical_rule_thickness: i16,
+    /// Extra space above the radical rule
+    pub radical_extra_ascender: i16,
+    /// Minimum gap between an upper limit and the operator
+    pub upper_limit_gap_min: i16,
+    /// Minimum gap between a lower limit and the operator
+    pub lower_limit_gap_min: i16,
+    /// Minimum height of a delimited sub-formula before delimiters stretch
+    pub delimited_sub_formula_min_height: u16,
+    /// Minimum height of display-style n-ary operators
+    pub display_operator_min_height: u16,
+}
+
+impl MathConstants {
+    fn from_table(c: &math::Constants) -> Self {
+        Self {
+            script_percent_scale_down: c.script_percent_scale_down(),
+            script_script_percent_scale_down: c.script_script_percent_scale_down(),
+            axis_height: c.axis_height().value,
+            subscript_shift_down: c.subscript_shift_down().value,
+            superscript_shift_up: c.superscript_shift_up().value,
+            sub_superscript_gap_min: c.sub_superscript_gap_min().value,
+            space_after_script: c.space_after_script().value,
+            fraction_rule_thickness: c.fraction_rule_thickness().value,
+            fraction_numerator_gap_min: c.fraction_numerator_gap_min().value,
+            fraction_denominator_gap_min: c.fraction_denominator_gap_min().value,
+            radical_vertical_gap: c.radical_vertical_gap().value,
+            radical_rule_thickness: c.radical_rule_thickness().value,
+            radical_extra_ascender: c.radical_extra_ascender().value,
+            upper_limit_gap_min: c.upper_limit_gap_min().value,
+            lower_limit_gap_min: c.lower_limit_gap_min().value,
+            delimited_sub_formula_min_height: c.delimited_sub_formula_min_height(),
+            display_operator_min_height: c.display_operator_min_height(),
+        }
+    }
+}
+
+// =============================================================================
+// Glyph Constructions
+// =============================================================================
+
+/// A pre-drawn size variant of a glyph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MathGlyphVariant {
+    /// Glyph ID of the variant
+    pub glyph_id: u16,
+    /// Advance in the stretch direction
+    pub advance: u16,
+}
+
+/// A part of a glyph assembly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MathGlyphPart {
+    /// Glyph ID of the part
+    pub glyph_id: u16,
+    /// Length of the connector at the start of the part
+    pub start_connector: u16,
+    /// Length of the connector at the end of the part
+    pub end_connector: u16,
+    /// Full advance of the part
+    pub full_advance: u16,
+    /// Whether the part may be repeated to extend the assembly
+    pub extender: bool,
+}
+
+/// How a glyph grows: size variants first, then an assembly of parts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MathGlyphConstruction {
+    /// Variants in increasing size
+    pub variants: Vec<MathGlyphVariant>,
+    /// Parts for building arbitrarily large versions, bottom/left first
+    pub assembly: Vec<MathGlyphPart>,
+}
+
+/// A glyph stretched to a target size
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GlyphStretch {
+    /// A single pre-drawn variant
+    Variant {
+        /// Glyph ID of the variant
+        glyph_id: u16,
+        /// Advance in the stretch direction, in design units
+        advance: f32,
+    },
+    /// Parts placed along the stretch direction
+    Assembly {
+        /// Parts with their offsets from the start, in design units
+        parts: Vec<AssembledPart>,
+        /// Total advance of the assembly, in design units
+        advance: f32,
+    },
+}
+
+impl GlyphStretch {
+    /// Advance of the stretched glyph in the stretch direction, in design units
+    pub fn advance(&self) -> f32 {
+        match self {
+            GlyphStretch::Variant { advance, .. } | GlyphStretch::Assembly { advance, .. } => {
+                *advance
+            }
+        }
+    }
+}
+
+/// A part placed within an assembly
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AssembledPart {
+    /// Glyph ID of the part
+    pub glyph_id: u16,
+    /// Offset of the part from the start of the assembly, in design units
+    pub offset: f32,
+}
+
+impl MathGlyphConstruction {
+    /// Find the smallest variant or assembly that covers `target` design units
+    ///
+    /// Falls back to the largest available variant when nothing is big enough.
+    pub fn stretch(&self, target: f32, min_connector_overlap: u16) -> Option<GlyphStretch> {
+        if let Some(variant) = self
+            .variants
+            .iter()
+            .find(|v| v.advance as f32 >= target)
+        {
+            return Some(GlyphStretch::Variant {
+                glyph_id: variant.glyph_id,
+                advance: variant.advance as f32,
+            });
+        }
+
+        if let Some(assembly) = self.assemble(target, min_connector_overlap as f32) {
+            return Some(assembly);
+        }
+
+        self.variants.last().map(|v| GlyphStretch::Variant {
+            glyph_id: v.glyph_id,
+            advance: v.advance as f32,
+        })
+    }
+
+    /// Build an assembly of at least `target` design units
+    fn assemble(&self, target: f32, min_overlap: f32) -> Option<GlyphStretch> {
+        if self.assembly.is_empty() {
+            return None;
+        }
+        let has_extender = self.assembly.iter().any(|p| p.extender);
+
+        // Add extender repetitions until the assembly can reach the target
+        let max_repeats = if has_extender { 1000 } else { 1 };
+        for repeats in 1..=max_repeats {
+            let parts = self.expand(repeats);
+            let total: f32 = parts.iter().map(|p| p.full_advance as f32).sum();
+            let connections = parts.len().saturating_sub(1) as f32;
+            if total - connections * min_overlap < target && has_extender {
+                continue;
+            }
+
+            // Overlap as much as the connectors allow without undershooting
+            let max_overlap = parts
+                .windows(2)
+                .map(|w| w[0].end_connector.min(w[1].start_connector) as f32)
+                .fold(f32::INFINITY, f32::min);
+            let overlap = if connections > 0.0 {
+                ((total - target) / connections)
+                    .min(max_overlap)
+                    .max(min_overlap)
+            } else {
+                0.0
+            };
+
+            let mut offset = 0.0;
+            let mut placed = Vec::with_capacity(parts.len());
+            for part in &parts {
+                placed.push(AssembledPart {
+                    glyph_id: part.glyph_id,
+                    offset,
+                });
+                offset += part.full_advance as f32 - overlap;
+            }
+            let advance = offset + overlap;
+
+            return Some(GlyphStretch::Assembly {
+                parts: placed,
+                advance,
+            });
+        }
+        None
+    }
+
+    /// Parts with each extender repeated `repeats` times
+    fn expand(&self, repeats: usize) -> Vec<MathGlyphPart> {
+        let mut parts = Vec::new();
+        for part in &self.assembly {
+            let count = if part.extender { repeats } else { 1 };
+            parts.extend(std::iter::repeat_n(*part, count));
+        }
+        parts
+    }
+
+    fn from_table(construction: &math::GlyphConstruction) -> Self {
+        Self {
+            variants: construction
+                .variants
+                .into_iter()
+                .map(|v| MathGlyphVariant {
+                    glyph_id: v.variant_glyph.0,
+                    advance: v.advance_measurement,
+                })
+                .collect(),
+            assembly: construction
+                .assembly
+                .map(|a| {
+                    a.parts
+                        .into_iter()
+                        .map(|p| MathGlyphPart {
+                            glyph_id: p.glyph_id.0,
+                            start_connector: p.start_connector_length,
+                            end_connector: p.end_connector_length,
+                            full_advance: p.full_advance,
+                            extender: p.part_flags.extender(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+// =============================================================================
+// Math Table
+// =============================================================================
+
+/// Math layout data extracted from a font with an OpenType MATH table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MathTable {
+    /// Family of the font the table was read from
+    pub font_family: Option<String>,
+    /// Face index within the font file (for TTC files)
+    pub font_index: u32,
+    /// Design units per em
+    pub units_per_em: u16,
+    /// Layout constants, if the table provides them
+    pub constants: Option<MathConstants>,
+    /// Minimum overlap between assembly parts
+    pub min_connector_overlap: u16,
+    /// Italic corrections by character
+    italic_corrections: HashMap<char, i16>,
+    /// Vertical constructions by character
+    vertical: HashMap<char, MathGlyphConstruction>,
+    /// Horizontal constructions by character
+    horizontal: HashMap<char, MathGlyphConstruction>,
+}
+
+impl MathTable {
+    /// Create an empty table with the given units per em
+    pub fn new(units_per_em: u16) -> Self {
+        Self {
+            font_family: None,
+            font_index: 0,
+            units_per_em,
+            constants: None,
+            min_connector_overlap: 0,
+            italic_corrections: HashMap::new(),
+            vertical: HashMap::new(),
+            horizontal: HashMap::new(),
+        }
+    }
+
+    /// Read the MATH table from font data
+    ///
+    /// Returns `None` if the data is not a font or the font has no MATH table.
+    pub fn from_font_data(data: &[u8], index: u32) -> Option<Self> {
+        let face = ttf_parser::Face::parse(data, index).ok()?;
+        let table = face.tables().math?;
+
+        let mut result = Self::new(face.units_per_em());
+        result.font_index = index;
+        result.constants = table.constants.as_ref().map(MathConstants::from_table);
+
+        if let Some(italics) = table.glyph_info.and_then(|info| info.italic_corrections) {
+            for &(start, end) in ITALIC_RANGES {
+                for c in start..=end {
+                    let correction = face.glyph_index(c).and_then(|g| italics.get(g));
+                    if let Some(value) = correction {
+                        result.italic_corrections.insert(c, value.value);
+                    }
+                }
+            }
+        }
+
+        if let Some(variants) = table.variants {
+            result.min_connector_overlap = variants.min_connector_overlap;
+            let lookup = |c: char, constructions: &math::GlyphConstructions| {
+                let glyph: GlyphId = face.glyph_index(c)?;
+                constructions
+                    .get(glyph)
+                    .map(|construction| MathGlyphConstruction::from_table(&construction))
+            };
+            for &c in VERTICAL_STRETCHY {
+                if let Some(construction) = lookup(c, &variants.vertical_constructions) {
+                    result.vertical.insert(c, construction);
+                }
+            }
+            for &c in HORIZONTAL_STRETCHY {
+                if let Some(construction) = lookup(c, &variants.horizontal_constructions) {
+                    result.horizontal.insert(c, construction);
+                }
+            }
+        }
+
+        Some(result)
+    }
+
+    /// Set the family of the font the table was read from
+    pub fn with_font_family(mut self, family: impl Into<String>) -> Self {
+        self.font_family = Some(family.into());
+        self
+    }
+
+    /// Italic correction for a character, in design units
+    pub fn italic_correction(&self, c: char) -> Option<i16> {
+        self.italic_corrections.get(&c).copied()
+    }
+
+    /// Set the italic correction for a character
+    pub fn set_italic_correction(&mut self, c: char, value: i16) {
+        self.italic_corrections.insert(c, value);
+    }
+
+    /// Vertical construction for a character
+    pub fn vertical_construction(&self, c: char) -> Option<&MathGlyphConstruction> {
+        self.vertical.get(&c)
+    }
+
+    /// Set the vertical construction for a character
+    pub fn set_vertical_construction(&mut self, c: char, construction: MathGlyphConstruction) {
+        self.vertical.insert(c, construction);
+    }
+
+    /// Horizontal construction for a character
+    pub fn horizontal_construction(&self, c: char) -> Option<&MathGlyphConstruction> {
+        self.horizontal.get(&c)
+    }
+
+    /// Set the horizontal construction for a character
+    pub fn set_horizontal_construction(&mut self, c: char, construction: MathGlyphConstruction) {
+        self.horizontal.insert(c, construction);
+    }
+
+    /// Stretch a character vertically to cover `target` design units
+    pub fn stretch_vertical(&self, c: char, target: f32) -> Option<GlyphStretch> {
+        self.vertical
+            .get(&c)?
+            .stretch(target, self.min_connector_overlap)
+    }
+
+    /// Stretch a character horizontally to cover `target` design units
+    pub fn stretch_horizontal(&self, c: char, target: f32) -> Option<GlyphStretch> {
+        self.horizontal
+            .get(&c)?
+            .stretch(target, self.min_connector_overlap)
+    }
+
+    /// Scale factor from design units to points at the given font size
+    pub fn scale(&self, font_size: f32) -> f32 {
+        font_size / self.units_per_em.max(1) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paren_construction() -> MathGlyphConstruction {
+        let part = |glyph_id, extender| MathGlyphPart {
+            glyph_id,
+            start_connector: 100,
+            end_connector: 100,
+            full_advance: 500,
+            extender,
+        };
+        MathGlyphConstruction {
+            variants: vec![
+                MathGlyphVariant { glyph_id: 10, advance: 1000 },
+                MathGlyphVariant { glyph_id: 11, advance: 1500 },
+            ],
+            assembly: vec![part(20, false), part(21, true), part(22, false)],
+        }
+    }
+
+    #[test]
+    fn test_stretch_picks_smallest_variant() {
+        let stretch = paren_construction().stretch(1200.0, 50).unwrap();
+        assert_eq!(
+            stretch,
+            GlyphStretch::Variant { glyph_id: 11, advance: 1500.0 }
+        );
+    }
+
+    #[test]
+    fn test_stretch_assembles_with_extenders() {
+        let stretch = paren_construction().stretch(3000.0, 50).unwrap();
+        let GlyphStretch::Assembly { parts, advance } = stretch else {
+            panic!("Expected assembly");
+        };
+        assert!(advance >= 3000.0);
+        assert_eq!(parts.first().map(|p| p.glyph_id), Some(20));
+        assert_eq!(parts.last().map(|p| p.glyph_id), Some(22));
+        assert!(parts.iter().filter(|p| p.glyph_id == 21).count() >= 2);
+        assert!(parts.windows(2).all(|w| w[0].offset < w[1].offset));
+    }
+
+    #[test]
+    fn test_stretch_without_assembly_falls_back_to_largest_variant() {
+        let mut construction = paren_construction();
+        construction.assembly.clear();
+        let stretch = construction.stretch(5000.0, 50).unwrap();
+        assert_eq!(stretch.advance(), 1500.0);
+    }
+
+    #[test]
+    fn test_table_lookup_and_scale() {
+        let mut table = MathTable::new(1000);
+        table.set_vertical_construction('(', paren_construction());
+        table.set_italic_correction('f', 40);
+
+        assert!(table.stretch_vertical('(', 900.0).is_some());
+        assert!(table.stretch_vertical('[', 900.0).is_none());
+        assert_eq!(table.italic_correction('f'), Some(40));
+        assert!((table.scale(12.0) - 0.012).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_from_font_data_rejects_non_font() {
+        assert!(MathTable::from_font_data(b"not a font", 0).is_none());
+    }
+}
@@ -0,0 +1,209 @@
+//! Shaped run cache
+//!
+//! Documents repeat the same words in the same fonts over and over, and a
+//! layout pass shapes every run again. `ShapeCache` keeps shaped runs keyed
+//! on everything that affects shaping, so repeated text and later layout
+//! passes reuse earlier results. The cache is a cheap, thread-safe handle:
+//! clones share the same entries, so several shapers (e.g. a foreground and
+//! a background paginator) can share one cache.
+
+use crate::{FontId, Script, ShapedRun};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Default number of shaped runs kept
+pub const DEFAULT_SHAPE_CACHE_CAPACITY: usize = 50_000;
+
+/// Everything that affects how a piece of text is shaped
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShapeKey {
+    /// The text
+    pub text: String,
+    /// The font face used, or `None` for the estimated fallback metrics
+    pub font: Option<FontId>,
+    /// Font size in points, as raw bits
+    pub font_size_bits: u32,
+    /// OpenType features as (tag, value, start, end)
+    pub features: Vec<(u32, u32, u32, u32)>,
+    /// Script the text is written in
+    pub script: Script,
+}
+
+impl ShapeKey {
+    /// Create a key for shaping `text` with a font, size, and features
+    pub fn new(
+        text: &str,
+        font: Option<&FontId>,
+        font_size: f32,
+        features: &[rustybuzz::Feature],
+    ) -> Self {
+        Self {
+            text: text.to_string(),
+            font: font.cloned(),
+            font_size_bits: font_size.to_bits(),
+            features: features
+                .iter()
+                .map(|feature| (feature.tag.0, feature.value, feature.start, feature.end))
+                .collect(),
+            script: Script::detect(text),
+        }
+    }
+}
+
+/// Shape cache hit/miss counts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShapeCacheStats {
+    /// Lookups answered from the cache
+    pub hits: u64,
+    /// Lookups that had to shape
+    pub misses: u64,
+    /// Runs currently cached
+    pub entries: usize,
+}
+
+impl ShapeCacheStats {
+    /// Fraction of lookups answered from the cache
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct ShapeCacheState {
+    /// Shaped runs and the tick they were last used
+    entries: HashMap<ShapeKey, (Arc<ShapedRun>, u64)>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+/// Shared cache of shaped runs
+#[derive(Debug, Clone)]
+pub struct ShapeCache {
+    state: Arc<Mutex<ShapeCacheState>>,
+    capacity: usize,
+}
+
+impl ShapeCache {
+    /// Create a cache with the default capacity
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_SHAPE_CACHE_CAPACITY)
+    }
+
+    /// Create a cache holding at most `capacity` runs
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ShapeCacheState::default())),
+            capacity: capacity.max(1),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ShapeCacheState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Look up a shaped run
+    pub fn get(&self, key: &ShapeKey) -> Option<Arc<ShapedRun>> {
+        let mut state = self.lock();
+        state.tick += 1;
+        let tick = state.tick;
+        match state.entries.get_mut(key) {
+            Some((run, last_used)) => {
+                *last_used = tick;
+                let run = Arc::clone(run);
+                state.hits += 1;
+                Some(run)
+            }
+            None => {
+                state.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Store a shaped run, evicting the least recently used quarter of the
+    /// cache when it's full
+    pub fn insert(&self, key: ShapeKey, run: Arc<ShapedRun>) {
+        let mut state = self.lock();
+        if state.entries.len() >= self.capacity && !state.entries.contains_key(&key) {
+            let mut ticks: Vec<u64> = state.entries.values().map(|(_, last_used)| *last_used).collect();
+            let cutoff_index = (self.capacity / 4).min(ticks.len() - 1);
+            let (_, &mut cutoff, _) = ticks.select_nth_unstable(cutoff_index);
+            state.entries.retain(|_, (_, last_used)| *last_used > cutoff);
+        }
+        state.tick += 1;
+        let tick = state.tick;
+        state.entries.insert(key, (run, tick));
+    }
+
+    /// Get hit/miss counts and the number of cached runs
+    pub fn stats(&self) -> ShapeCacheStats {
+        let state = self.lock();
+        ShapeCacheStats {
+            hits: state.hits,
+            misses: state.misses,
+            entries: state.entries.len(),
+        }
+    }
+
+    /// Remove all cached runs, e.g. after fonts change
+    pub fn clear(&self) {
+        self.lock().entries.clear();
+    }
+}
+
+impl Default for ShapeCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TextShaper;
+
+    #[test]
+    fn test_shaper_reuses_cached_runs() {
+        let cache = ShapeCache::new();
+        let shaper = TextShaper::new().with_shape_cache(cache.clone());
+        let other = TextShaper::new().with_shape_cache(cache.clone());
+
+        let first = shaper.shape("hello world", 12.0).unwrap();
+        let second = other.shape("hello world", 12.0).unwrap();
+        assert_eq!(first.width, second.width);
+        assert_eq!(first.glyphs.len(), second.glyphs.len());
+
+        // Size is part of the key
+        shaper.shape("hello world", 14.0).unwrap();
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.entries, 2);
+    }
+
+    #[test]
+    fn test_eviction_keeps_recent_runs() {
+        let cache = ShapeCache::with_capacity(8);
+        let shaper = TextShaper::new().with_shape_cache(cache.clone());
+
+        for i in 0..8 {
+            shaper.shape(&format!("word{}", i), 12.0).unwrap();
+        }
+        // Touch the first word so it survives eviction
+        shaper.shape("word0", 12.0).unwrap();
+        shaper.shape("word8", 12.0).unwrap();
+
+        let stats = cache.stats();
+        assert!(stats.entries < 8);
+        assert!(cache.get(&ShapeKey::new("word0", None, 12.0, &[])).is_some());
+        assert!(cache.get(&ShapeKey::new("word8", None, 12.0, &[])).is_some());
+        assert!(cache.get(&ShapeKey::new("word1", None, 12.0, &[])).is_none());
+    }
+}
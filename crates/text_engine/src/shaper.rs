@@ -4,9 +4,10 @@
 //! which is a pure Rust implementation of HarfBuzz. Text shaping is the process
 //! of converting a sequence of Unicode codepoints into properly positioned glyphs.
 
-use crate::{FontId, FontMetrics, FontStyle, FontWeight, Result, TextError};
+use crate::{FontId, FontMetrics, FontStyle, FontWeight, Result, ShapeCache, ShapeKey, TextError};
 use std::collections::HashMap;
 use std::sync::Arc;
+use unicode_segmentation::UnicodeSegmentation;
 
 /// A shaped glyph with position information
 #[derive(Debug, Clone)]
@@ -77,6 +78,52 @@ impl ShapedRun {
     }
 }
 
+/// A piece of a glyph outline, in font units with y pointing up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutlineSegment {
+    /// Start a new contour
+    MoveTo(f32, f32),
+    /// Straight line to a point
+    LineTo(f32, f32),
+    /// Quadratic curve through a control point
+    QuadTo(f32, f32, f32, f32),
+    /// Cubic curve through two control points
+    CurveTo(f32, f32, f32, f32, f32, f32),
+    /// Close the current contour
+    Close,
+}
+
+/// Outline of a single glyph
+#[derive(Debug, Clone, Default)]
+pub struct GlyphOutline {
+    /// Units per em of the font the outline came from
+    pub units_per_em: u16,
+    /// Outline segments
+    pub segments: Vec<OutlineSegment>,
+}
+
+impl rustybuzz::ttf_parser::OutlineBuilder for GlyphOutline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.segments.push(OutlineSegment::MoveTo(x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.segments.push(OutlineSegment::LineTo(x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.segments.push(OutlineSegment::QuadTo(x1, y1, x, y));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.segments.push(OutlineSegment::CurveTo(x1, y1, x2, y2, x, y));
+    }
+
+    fn close(&mut self) {
+        self.segments.push(OutlineSegment::Close);
+    }
+}
+
 /// Cached font face for shaping
 struct CachedFace {
     /// The font data (kept alive for rustybuzz)
@@ -89,10 +136,13 @@ struct CachedFace {
 /// Text shaper using rustybuzz
 ///
 /// The TextShaper handles text shaping operations, converting Unicode text
-/// into positioned glyphs. It maintains a cache of font faces for efficiency.
+/// into positioned glyphs. It maintains a cache of font faces and a (possibly
+/// shared) cache of shaped runs for efficiency.
 pub struct TextShaper {
     /// Cache of loaded font faces
     face_cache: HashMap<FontId, CachedFace>,
+    /// Cache of shaped runs
+    shape_cache: ShapeCache,
     /// Default metrics when no font is available
     default_metrics: FontMetrics,
 }
@@ -102,11 +152,30 @@ impl TextShaper {
     pub fn new() -> Self {
         Self {
             face_cache: HashMap::new(),
+            shape_cache: ShapeCache::new(),
             default_metrics: FontMetrics::default(),
         }
     }
 
+    /// Use a shaped run cache shared with other shapers
+    pub fn with_shape_cache(mut self, cache: ShapeCache) -> Self {
+        self.shape_cache = cache;
+        self
+    }
+
+    /// Replace the shaped run cache
+    pub fn set_shape_cache(&mut self, cache: ShapeCache) {
+        self.shape_cache = cache;
+    }
+
+    /// Get the shaped run cache
+    pub fn shape_cache(&self) -> &ShapeCache {
+        &self.shape_cache
+    }
+
     /// Load a font from data
+    ///
+    /// Replacing an already loaded font clears the shaped run cache.
     pub fn load_font(&mut self, font_id: FontId, data: Vec<u8>) -> Result<()> {
         let data = Arc::new(data);
         // SAFETY: We keep the Arc alive in CachedFace, so the data lives as long as the face
@@ -117,7 +186,9 @@ impl TextShaper {
         let face = rustybuzz::Face::from_slice(static_data, 0)
             .ok_or_else(|| TextError::InvalidFontData("Failed to parse font".into()))?;
 
-        self.face_cache.insert(font_id, CachedFace { data, face });
+        if self.face_cache.insert(font_id, CachedFace { data, face }).is_some() {
+            self.shape_cache.clear();
+        }
         Ok(())
     }
 
@@ -126,6 +197,20 @@ impl TextShaper {
         self.face_cache.contains_key(font_id)
     }
 
+    /// Get the outline of a glyph in a loaded font
+    ///
+    /// Returns `None` if the font isn't loaded or the glyph has no outline
+    /// (e.g. a space).
+    pub fn glyph_outline(&self, font_id: &FontId, glyph_id: u16) -> Option<GlyphOutline> {
+        let face = &self.face_cache.get(font_id)?.face;
+        let mut outline = GlyphOutline {
+            units_per_em: face.units_per_em() as u16,
+            segments: Vec::new(),
+        };
+        face.outline_glyph(rustybuzz::ttf_parser::GlyphId(glyph_id), &mut outline)?;
+        Some(outline)
+    }
+
     /// Shape a text string with optional font
     pub fn shape(&self, text: &str, font_size: f32) -> Result<ShapedRun> {
         self.shape_with_font(text, font_size, None)
@@ -137,14 +222,36 @@ impl TextShaper {
         text: &str,
         font_size: f32,
         font_id: Option<&FontId>,
+    ) -> Result<ShapedRun> {
+        self.shape_with_font_features(text, font_size, font_id, &[])
+    }
+
+    /// Shape a text string with a specific font and OpenType features
+    fn shape_with_font_features(
+        &self,
+        text: &str,
+        font_size: f32,
+        font_id: Option<&FontId>,
+        features: &[rustybuzz::Feature],
     ) -> Result<ShapedRun> {
         // Try to get the font face
-        let cached = font_id.and_then(|id| self.face_cache.get(id));
+        let cached = font_id.and_then(|id| self.face_cache.get(id).map(|face| (id, face)));
 
-        match cached {
-            Some(cached_face) => self.shape_with_face(text, font_size, &cached_face.face),
-            None => self.shape_fallback(text, font_size),
+        // Key on the face actually used; fallback shaping ignores features
+        let key = match cached {
+            Some((id, _)) => ShapeKey::new(text, Some(id), font_size, features),
+            None => ShapeKey::new(text, None, font_size, &[]),
+        };
+        if let Some(run) = self.shape_cache.get(&key) {
+            return Ok(ShapedRun::clone(&run));
         }
+
+        let run = match cached {
+            Some((_, cached_face)) => self.shape_with_face(text, font_size, &cached_face.face, features)?,
+            None => self.shape_fallback(text, font_size)?,
+        };
+        self.shape_cache.insert(key, Arc::new(run.clone()));
+        Ok(run)
     }
 
     /// Shape text using a rustybuzz face
@@ -153,6 +260,7 @@ impl TextShaper {
         text: &str,
         font_size: f32,
         face: &rustybuzz::Face<'_>,
+        features: &[rustybuzz::Feature],
     ) -> Result<ShapedRun> {
         let units_per_em = face.units_per_em() as u16;
         let scale = font_size / units_per_em as f32;
@@ -162,7 +270,7 @@ impl TextShaper {
         buffer.push_str(text);
 
         // Shape the text
-        let output = rustybuzz::shape(face, &[], buffer);
+        let output = rustybuzz::shape(face, features, buffer);
 
         // Extract glyph information
         let glyph_infos = output.glyph_infos();
@@ -206,24 +314,25 @@ impl TextShaper {
         let units_per_em = self.default_metrics.units_per_em;
         let scale = font_size / units_per_em as f32;
 
-        // Estimate advance widths based on character properties
+        // Estimate advance widths based on character properties; marks and
+        // joiners after the first character of a grapheme don't advance
         let mut glyphs = Vec::new();
         let mut total_advance = 0i32;
-        let mut byte_offset = 0u32;
 
-        for ch in text.chars() {
-            let advance = self.estimate_char_width(ch, units_per_em);
-            glyphs.push(ShapedGlyph {
-                glyph_id: ch as u16, // Use codepoint as pseudo glyph ID
-                x_advance: advance,
-                y_advance: 0,
-                x_offset: 0,
-                y_offset: 0,
-                cluster: byte_offset,
-                character: Some(ch),
-            });
-            total_advance += advance;
-            byte_offset += ch.len_utf8() as u32;
+        for (grapheme_offset, grapheme) in text.grapheme_indices(true) {
+            for (index, (char_offset, ch)) in grapheme.char_indices().enumerate() {
+                let advance = if index == 0 { self.estimate_char_width(ch, units_per_em) } else { 0 };
+                glyphs.push(ShapedGlyph {
+                    glyph_id: ch as u16, // Use codepoint as pseudo glyph ID
+                    x_advance: advance,
+                    y_advance: 0,
+                    x_offset: 0,
+                    y_offset: 0,
+                    cluster: (grapheme_offset + char_offset) as u32,
+                    character: Some(ch),
+                });
+                total_advance += advance;
+            }
         }
 
         let ascender = self.default_metrics.ascender as f32 * scale;
@@ -291,6 +400,30 @@ impl TextShaper {
 
         self.shape_with_font(text, font_size, font_id.as_ref())
     }
+
+    /// Shape text for a run style with pair kerning switched on or off
+    pub fn shape_run_with_kerning(
+        &self,
+        text: &str,
+        font_family: Option<&str>,
+        font_size: f32,
+        bold: bool,
+        italic: bool,
+        kerning: bool,
+    ) -> Result<ShapedRun> {
+        let font_id = font_family.map(|family| {
+            FontId::new(family)
+                .with_weight(if bold { FontWeight::Bold } else { FontWeight::Normal })
+                .with_style(if italic { FontStyle::Italic } else { FontStyle::Normal })
+        });
+        let kern = rustybuzz::Feature::new(
+            rustybuzz::ttf_parser::Tag::from_bytes(b"kern"),
+            kerning as u32,
+            ..,
+        );
+
+        self.shape_with_font_features(text, font_size, font_id.as_ref(), &[kern])
+    }
 }
 
 impl Default for TextShaper {
@@ -0,0 +1,168 @@
+//! Thesaurus
+//!
+//! Offline synonym and antonym lookup for the word at the cursor. A small
+//! built-in word list covers common words in each supported language, and
+//! callers can add entries. Lookups ignore case and fall back to the base
+//! form of simple English inflections ("cars" finds "car").
+
+use crate::spellcheck::Language;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Built-in entries: (language, word, synonyms, antonyms)
+const BUILTIN_ENTRIES: &[(Language, &str, &[&str], &[&str])] = &[
+    (Language::EnUs, "big", &["large", "huge", "vast", "enormous"], &["small", "little", "tiny"]),
+    (Language::EnUs, "small", &["little", "tiny", "minor", "compact"], &["big", "large"]),
+    (Language::EnUs, "good", &["fine", "great", "excellent", "decent"], &["bad", "poor"]),
+    (Language::EnUs, "bad", &["poor", "awful", "inferior", "harmful"], &["good", "fine"]),
+    (Language::EnUs, "fast", &["quick", "rapid", "swift", "speedy"], &["slow"]),
+    (Language::EnUs, "slow", &["gradual", "sluggish", "unhurried"], &["fast", "quick"]),
+    (Language::EnUs, "happy", &["glad", "cheerful", "content", "pleased"], &["sad", "unhappy"]),
+    (Language::EnUs, "sad", &["unhappy", "sorrowful", "gloomy"], &["happy", "glad"]),
+    (Language::EnUs, "important", &["significant", "essential", "key", "major"], &["minor", "trivial"]),
+    (Language::EnUs, "easy", &["simple", "effortless", "straightforward"], &["hard", "difficult"]),
+    (Language::EnUs, "hard", &["difficult", "tough", "demanding"], &["easy", "simple"]),
+    (Language::EnUs, "new", &["recent", "fresh", "modern", "novel"], &["old"]),
+    (Language::EnUs, "old", &["aged", "former", "ancient", "dated"], &["new", "young"]),
+    (Language::EnUs, "begin", &["start", "commence", "initiate"], &["end", "finish"]),
+    (Language::EnUs, "end", &["finish", "conclusion", "close"], &["begin", "start"]),
+    (Language::EnUs, "help", &["assist", "aid", "support"], &["hinder"]),
+    (Language::EnUs, "show", &["display", "present", "reveal", "demonstrate"], &["hide", "conceal"]),
+    (Language::EnUs, "use", &["employ", "apply", "utilize"], &[]),
+    (Language::EnUs, "make", &["create", "build", "produce", "form"], &["destroy"]),
+    (Language::EnUs, "change", &["alter", "modify", "adjust", "revise"], &["keep"]),
+    (Language::EnUs, "increase", &["raise", "grow", "expand", "boost"], &["decrease", "reduce"]),
+    (Language::EnUs, "decrease", &["reduce", "lower", "decline", "shrink"], &["increase"]),
+    (Language::EnUs, "clear", &["plain", "obvious", "evident", "lucid"], &["unclear", "vague"]),
+    (Language::EnUs, "say", &["state", "mention", "remark", "declare"], &[]),
+    (Language::EnUs, "think", &["believe", "consider", "suppose"], &[]),
+    (Language::EnUs, "need", &["require", "want", "lack"], &[]),
+    (Language::EnUs, "problem", &["issue", "difficulty", "trouble"], &["solution"]),
+    (Language::EnUs, "idea", &["concept", "notion", "thought", "plan"], &[]),
+    (Language::EnUs, "result", &["outcome", "consequence", "effect"], &["cause"]),
+    (Language::EnUs, "many", &["numerous", "several", "countless"], &["few"]),
+    (Language::FrFr, "grand", &["gros", "vaste", "énorme"], &["petit"]),
+    (Language::FrFr, "petit", &["menu", "minuscule", "réduit"], &["grand"]),
+    (Language::FrFr, "rapide", &["vite", "prompt", "véloce"], &["lent"]),
+    (Language::FrFr, "content", &["heureux", "joyeux", "ravi"], &["triste"]),
+    (Language::EsEs, "grande", &["amplio", "enorme", "vasto"], &["pequeño"]),
+    (Language::EsEs, "pequeño", &["chico", "diminuto", "reducido"], &["grande"]),
+    (Language::EsEs, "rápido", &["veloz", "ligero", "pronto"], &["lento"]),
+    (Language::EsEs, "feliz", &["contento", "alegre", "dichoso"], &["triste"]),
+    (Language::DeDe, "groß", &["riesig", "weit", "gewaltig"], &["klein"]),
+    (Language::DeDe, "klein", &["winzig", "gering", "knapp"], &["groß"]),
+    (Language::DeDe, "schnell", &["rasch", "zügig", "flink"], &["langsam"]),
+    (Language::DeDe, "glücklich", &["froh", "heiter", "zufrieden"], &["traurig"]),
+];
+
+/// Synonyms and antonyms of a word
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThesaurusEntry {
+    /// The word as found in the thesaurus
+    pub word: String,
+    /// Words with the same meaning
+    pub synonyms: Vec<String>,
+    /// Words with the opposite meaning
+    pub antonyms: Vec<String>,
+}
+
+/// Offline thesaurus
+#[derive(Debug, Clone, Default)]
+pub struct Thesaurus {
+    /// Entries per language, keyed by lowercase word
+    entries: HashMap<Language, HashMap<String, ThesaurusEntry>>,
+}
+
+impl Thesaurus {
+    /// Create an empty thesaurus
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a thesaurus with the built-in word list
+    pub fn with_builtin() -> Self {
+        let mut thesaurus = Self::new();
+        for (language, word, synonyms, antonyms) in BUILTIN_ENTRIES {
+            thesaurus.add_entry(
+                *language,
+                ThesaurusEntry {
+                    word: word.to_string(),
+                    synonyms: synonyms.iter().map(|s| s.to_string()).collect(),
+                    antonyms: antonyms.iter().map(|s| s.to_string()).collect(),
+                },
+            );
+        }
+        thesaurus
+    }
+
+    /// Add or replace an entry
+    pub fn add_entry(&mut self, language: Language, entry: ThesaurusEntry) {
+        self.entries
+            .entry(language)
+            .or_default()
+            .insert(entry.word.to_lowercase(), entry);
+    }
+
+    /// Look up a word
+    ///
+    /// British English uses the US English entries when it has none of its own.
+    pub fn lookup(&self, word: &str, language: Language) -> Option<&ThesaurusEntry> {
+        let word = word.trim().to_lowercase();
+        if word.is_empty() {
+            return None;
+        }
+        let languages: &[Language] = match language {
+            Language::EnGb => &[Language::EnGb, Language::EnUs],
+            _ => std::slice::from_ref(&language),
+        };
+        languages.iter().filter_map(|l| self.entries.get(l)).find_map(|entries| {
+            entries.get(&word).or_else(|| {
+                let english = matches!(language, Language::EnUs | Language::EnGb);
+                english
+                    .then(|| base_forms(&word))
+                    .into_iter()
+                    .flatten()
+                    .find_map(|base| entries.get(&base))
+            })
+        })
+    }
+
+    /// Synonyms of a word, most common first
+    pub fn synonyms(&self, word: &str, language: Language) -> Vec<String> {
+        self.lookup(word, language).map(|e| e.synonyms.clone()).unwrap_or_default()
+    }
+
+    /// Antonyms of a word
+    pub fn antonyms(&self, word: &str, language: Language) -> Vec<String> {
+        self.lookup(word, language).map(|e| e.antonyms.clone()).unwrap_or_default()
+    }
+}
+
+/// Candidate base forms of an inflected English word
+fn base_forms(word: &str) -> Vec<String> {
+    let mut forms = Vec::new();
+    for (suffix, replacement) in [("ies", "y"), ("es", ""), ("s", ""), ("ed", ""), ("ed", "e"), ("ing", ""), ("ing", "e")] {
+        if let Some(stem) = word.strip_suffix(suffix) {
+            if stem.chars().count() >= 2 {
+                forms.push(format!("{}{}", stem, replacement));
+            }
+        }
+    }
+    forms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_synonyms_and_antonyms() {
+        let thesaurus = Thesaurus::with_builtin();
+        assert_eq!(thesaurus.synonyms("Fast", Language::EnGb)[0], "quick");
+        assert_eq!(thesaurus.antonyms("problems", Language::EnUs), vec!["solution".to_string()]);
+        assert_eq!(thesaurus.lookup("changed", Language::EnUs).map(|e| e.word.as_str()), Some("change"));
+        assert_eq!(thesaurus.antonyms("klein", Language::DeDe), vec!["groß".to_string()]);
+        assert!(thesaurus.lookup("fast", Language::FrFr).is_none());
+    }
+}
@@ -0,0 +1,226 @@
+//! Tauri IPC commands for building blocks (Quick Parts)
+
+use crate::state::{AppState, BuildingBlockState};
+use doc_model::{BuildingBlock, BuildingBlockGallery};
+use edit_engine::{AutoText, AutoTextSuggestion, Command, InsertBuildingBlock};
+use serde::{Deserialize, Serialize};
+use store::{BuildingBlockEntry, BuildingBlockSource};
+use tauri::{Emitter, State};
+
+/// Event carrying the AutoText suggestion for the word being typed
+const AUTOTEXT_SUGGESTION_EVENT: &str = "autotext-suggestion";
+
+/// A gallery listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildingBlockGalleryDto {
+    /// Gallery display name
+    pub name: String,
+    /// Categories used in the gallery
+    pub categories: Vec<String>,
+    /// Blocks in the gallery
+    pub entries: Vec<BuildingBlockEntry>,
+}
+
+/// List a building block gallery for a document
+///
+/// The document's own blocks (including those from its template) are
+/// listed before the user's.
+#[tauri::command]
+pub fn list_building_blocks(
+    doc_id: Option<String>,
+    gallery: BuildingBlockGallery,
+    state: State<'_, AppState>,
+    blocks: State<'_, BuildingBlockState>,
+) -> Result<BuildingBlockGalleryDto, String> {
+    let documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let document = doc_id.as_ref().and_then(|id| documents.get(id)).map(|doc_state| &doc_state.tree);
+    let library = blocks.library.lock().map_err(|e| e.to_string())?;
+
+    Ok(BuildingBlockGalleryDto {
+        name: gallery.display_name().to_string(),
+        categories: library.categories(gallery, document),
+        entries: library.gallery(gallery, document),
+    })
+}
+
+/// Save the selection as a building block
+///
+/// With `source` of `document` the block is stored in the document (so a
+/// template carries it to new documents); otherwise in the user's library.
+#[tauri::command]
+pub fn save_selection_as_building_block(
+    doc_id: String,
+    name: String,
+    gallery: BuildingBlockGallery,
+    category: Option<String>,
+    description: Option<String>,
+    source: Option<BuildingBlockSource>,
+    state: State<'_, AppState>,
+    blocks: State<'_, BuildingBlockState>,
+) -> Result<BuildingBlockEntry, String> {
+    if name.trim().is_empty() {
+        return Err("Building block name is empty".to_string());
+    }
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let content = store::clipboard::extract_fragment(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+    let mut block = BuildingBlock::new(name.trim(), gallery, content)
+        .with_description(description.unwrap_or_default());
+    if let Some(category) = category.filter(|c| !c.trim().is_empty()) {
+        block = block.with_category(category.trim());
+    }
+    let (id, source) = (block.id.clone(), source.unwrap_or(BuildingBlockSource::User));
+
+    let mut library = blocks.library.lock().map_err(|e| e.to_string())?;
+    match source {
+        BuildingBlockSource::Document => {
+            doc_state.tree.building_blocks.add(block);
+            doc_state.dirty = true;
+        }
+        BuildingBlockSource::User => {
+            library.add(block);
+            library.save_sync().map_err(|e| e.to_string())?;
+        }
+    }
+
+    library
+        .gallery(gallery, Some(&doc_state.tree))
+        .into_iter()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| format!("Building block not found: {}", id))
+}
+
+/// Delete a building block from the document or the user's library
+#[tauri::command]
+pub fn delete_building_block(
+    doc_id: Option<String>,
+    block_id: String,
+    source: BuildingBlockSource,
+    state: State<'_, AppState>,
+    blocks: State<'_, BuildingBlockState>,
+) -> Result<(), String> {
+    let removed = match source {
+        BuildingBlockSource::Document => {
+            let doc_id = doc_id.ok_or("A document ID is needed to delete a document building block")?;
+            let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+            let doc_state = documents
+                .get_mut(&doc_id)
+                .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+            let removed = doc_state.tree.building_blocks.remove(&block_id).is_some();
+            doc_state.dirty |= removed;
+            removed
+        }
+        BuildingBlockSource::User => {
+            let mut library = blocks.library.lock().map_err(|e| e.to_string())?;
+            let removed = library.remove(&block_id).is_some();
+            if removed {
+                library.save_sync().map_err(|e| e.to_string())?;
+            }
+            removed
+        }
+    };
+
+    if removed {
+        Ok(())
+    } else {
+        Err(format!("Building block not found: {}", block_id))
+    }
+}
+
+/// Insert a copy of a building block at the cursor
+#[tauri::command]
+pub fn insert_building_block(
+    doc_id: String,
+    block_id: String,
+    state: State<'_, AppState>,
+    blocks: State<'_, BuildingBlockState>,
+) -> Result<(), String> {
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let block = {
+        let library = blocks.library.lock().map_err(|e| e.to_string())?;
+        library
+            .get(&block_id, Some(&doc_state.tree))
+            .cloned()
+            .ok_or_else(|| format!("Building block not found: {}", block_id))?
+    };
+
+    let cmd = InsertBuildingBlock::new(doc_state.selection.focus, block);
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+
+    doc_state.tree = result.tree;
+    doc_state.selection = result.selection;
+    doc_state.dirty = true;
+
+    Ok(())
+}
+
+/// Check the word before the caret for an AutoText entry
+///
+/// Called after typing. The suggestion (or `None`) is also emitted as an
+/// `autotext-suggestion` event for the suggestion tooltip.
+#[tauri::command]
+pub fn suggest_autotext(
+    doc_id: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    blocks: State<'_, BuildingBlockState>,
+) -> Result<Option<AutoTextSuggestion>, String> {
+    let documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let library = blocks.library.lock().map_err(|e| e.to_string())?;
+    let autotext = AutoText::new(library.autotext_entries(Some(&doc_state.tree)));
+    let suggestion = autotext.suggest(&doc_state.tree, &doc_state.selection);
+
+    if let Err(e) = app.emit(AUTOTEXT_SUGGESTION_EVENT, &suggestion) {
+        tracing::warn!("Failed to emit AutoText suggestion: {}", e);
+    }
+    Ok(suggestion)
+}
+
+/// Replace the word before the caret with the AutoText entry suggested for it
+#[tauri::command]
+pub fn accept_autotext(
+    doc_id: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    blocks: State<'_, BuildingBlockState>,
+) -> Result<(), String> {
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let autotext = {
+        let library = blocks.library.lock().map_err(|e| e.to_string())?;
+        AutoText::new(library.autotext_entries(Some(&doc_state.tree)))
+    };
+    let suggestion = autotext
+        .suggest(&doc_state.tree, &doc_state.selection)
+        .ok_or("No AutoText suggestion to accept")?;
+    let cmd = autotext.accept(&suggestion).map_err(|e| e.to_string())?;
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+
+    doc_state.tree = result.tree;
+    doc_state.selection = result.selection;
+    doc_state.dirty = true;
+
+    if let Err(e) = app.emit(AUTOTEXT_SUGGESTION_EVENT, None::<AutoTextSuggestion>) {
+        tracing::warn!("Failed to emit AutoText suggestion: {}", e);
+    }
+    Ok(())
+}
@@ -3,10 +3,12 @@
 use crate::state::CollaborationState;
 use chrono::Duration;
 use collab::{
-    CollaborativeDocument, ConnectionStatus, DocId, OfflineManager,
+    CollaborativeDocument, ConflictChoice, ConnectionStatus, DocId, OfflineManager, PendingConflict,
     PermissionLevel, PermissionManager, PermissionTarget, PresenceManager, PresenceState,
     SyncEngine, UserId, VersionHistory, VersionId,
 };
+use doc_model::{Comment, CommentId, CommentReply};
+use revisions::{Revision, RevisionId, RevisionStatus};
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
@@ -304,6 +306,65 @@ pub fn assign_presence_color(
     Ok(manager.assign_color(&user_id))
 }
 
+/// Follow another user's viewport
+#[tauri::command]
+pub fn follow_user(
+    doc_id: String,
+    user_id: String,
+    leader_id: String,
+    state: State<'_, CollaborationState>,
+) -> Result<(), String> {
+    let mut presence = state.presence.lock().unwrap();
+    let manager = presence.get_mut(&doc_id).ok_or("Presence manager not found")?;
+
+    if !manager.follow(&user_id, &leader_id) {
+        return Err(format!("Cannot follow user: {}", leader_id));
+    }
+    Ok(())
+}
+
+/// Stop following another user
+#[tauri::command]
+pub fn unfollow_user(
+    doc_id: String,
+    user_id: String,
+    state: State<'_, CollaborationState>,
+) -> Result<(), String> {
+    let mut presence = state.presence.lock().unwrap();
+    let manager = presence.get_mut(&doc_id).ok_or("Presence manager not found")?;
+
+    manager.unfollow(&user_id);
+    Ok(())
+}
+
+/// Start or stop presenting, pinning everyone else to this user's viewport
+#[tauri::command]
+pub fn set_presenting(
+    doc_id: String,
+    user_id: String,
+    presenting: bool,
+    state: State<'_, CollaborationState>,
+) -> Result<(), String> {
+    let mut presence = state.presence.lock().unwrap();
+    let manager = presence.get_mut(&doc_id).ok_or("Presence manager not found")?;
+
+    manager.set_presenting(&user_id, presenting);
+    Ok(())
+}
+
+/// Get where a following user should look
+#[tauri::command]
+pub fn get_follow_target(
+    doc_id: String,
+    user_id: String,
+    state: State<'_, CollaborationState>,
+) -> Result<serde_json::Value, String> {
+    let presence = state.presence.lock().unwrap();
+    let manager = presence.get(&doc_id).ok_or("Presence manager not found")?;
+
+    serde_json::to_value(manager.follow_target(&user_id)).map_err(|e| e.to_string())
+}
+
 // ============================================================================
 // Version History Commands
 // ============================================================================
@@ -521,6 +582,214 @@ pub fn sync_complete(
     Ok(())
 }
 
+/// Find offline edits that conflict with remote operations received on
+/// reconnect, for the user to review
+#[tauri::command]
+pub fn review_offline_conflicts(
+    remote_ops_json: String,
+    state: State<'_, CollaborationState>,
+) -> Result<Vec<PendingConflict>, String> {
+    let remote_ops: Vec<collab::operation::CrdtOp> = serde_json::from_str(&remote_ops_json)
+        .map_err(|e| format!("Failed to parse operations: {}", e))?;
+
+    let mut offline = state.offline.lock().unwrap();
+    Ok(offline.review_sync_response(&remote_ops).to_vec())
+}
+
+/// Choose how to settle an offline conflict: "ours", "theirs", or "both"
+#[tauri::command]
+pub fn resolve_offline_conflict(
+    conflict_id: usize,
+    choice: String,
+    state: State<'_, CollaborationState>,
+) -> Result<(), String> {
+    let choice = match choice.as_str() {
+        "ours" => ConflictChoice::Ours,
+        "theirs" => ConflictChoice::Theirs,
+        "both" => ConflictChoice::Both,
+        _ => return Err(format!("Unknown choice: {}", choice)),
+    };
+
+    let mut offline = state.offline.lock().unwrap();
+    offline.choose_resolution(conflict_id, choice).map_err(|e| e.to_string())
+}
+
+/// Finish the conflict review, applying the operations that carry out the
+/// choices; returns them (JSON) to send to the server
+#[tauri::command]
+pub fn finalize_offline_conflicts(
+    doc_id: String,
+    state: State<'_, CollaborationState>,
+) -> Result<String, String> {
+    let mut docs = state.documents.lock().unwrap();
+    let doc = docs.get_mut(&doc_id).ok_or("Document not found")?;
+
+    let mut offline = state.offline.lock().unwrap();
+    let plan = offline.finalize_review().map_err(|e| e.to_string())?;
+
+    let mut ops = Vec::new();
+    ops.extend(plan.retract.iter().filter_map(|op| doc.retract(op)));
+    ops.extend(plan.reassert.iter().filter_map(|op| doc.reassert(op)));
+    for op in &ops {
+        offline.queue_operation(op.clone());
+    }
+
+    let mut versions = state.versions.lock().unwrap();
+    if let Some(vh) = versions.get_mut(&doc_id) {
+        for op in &ops {
+            vh.record_operation(op.clone(), doc.clock(), "local");
+        }
+    }
+
+    serde_json::to_string(&ops).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Comment and Revision Sync Commands
+// ============================================================================
+
+/// Record a local comment or revision change's operations in version history
+/// and return them (JSON) to send to the server
+fn share_annotation_ops(
+    doc_id: &str,
+    doc: &CollaborativeDocument,
+    ops: Vec<collab::operation::CrdtOp>,
+    state: &State<'_, CollaborationState>,
+) -> Result<String, String> {
+    let mut versions = state.versions.lock().unwrap();
+    if let Some(vh) = versions.get_mut(doc_id) {
+        for op in &ops {
+            vh.record_operation(op.clone(), doc.clock(), "local");
+        }
+    }
+    serde_json::to_string(&ops).map_err(|e| e.to_string())
+}
+
+fn parse_comment_id(comment_id: &str) -> Result<CommentId, String> {
+    uuid::Uuid::parse_str(comment_id)
+        .map(CommentId::from_uuid)
+        .map_err(|e| format!("Invalid comment ID: {}", e))
+}
+
+/// Share a new comment (JSON) with collaborators
+#[tauri::command]
+pub fn share_comment(
+    doc_id: String,
+    comment_json: String,
+    state: State<'_, CollaborationState>,
+) -> Result<String, String> {
+    let comment: Comment = serde_json::from_str(&comment_json)
+        .map_err(|e| format!("Failed to parse comment: {}", e))?;
+
+    let mut docs = state.documents.lock().unwrap();
+    let doc = docs.get_mut(&doc_id).ok_or("Document not found")?;
+    let ops = doc.add_comment(&comment);
+    share_annotation_ops(&doc_id, doc, ops, &state)
+}
+
+/// Share a reply (JSON) to a comment
+#[tauri::command]
+pub fn share_comment_reply(
+    doc_id: String,
+    comment_id: String,
+    reply_json: String,
+    state: State<'_, CollaborationState>,
+) -> Result<String, String> {
+    let comment_id = parse_comment_id(&comment_id)?;
+    let reply: CommentReply = serde_json::from_str(&reply_json)
+        .map_err(|e| format!("Failed to parse reply: {}", e))?;
+
+    let mut docs = state.documents.lock().unwrap();
+    let doc = docs.get_mut(&doc_id).ok_or("Document not found")?;
+    let ops = doc.add_reply(comment_id, &reply);
+    share_annotation_ops(&doc_id, doc, ops, &state)
+}
+
+/// Resolve a shared comment, or reopen it when `resolved_by` is omitted
+#[tauri::command]
+pub fn share_comment_resolution(
+    doc_id: String,
+    comment_id: String,
+    resolved_by: Option<String>,
+    state: State<'_, CollaborationState>,
+) -> Result<String, String> {
+    let comment_id = parse_comment_id(&comment_id)?;
+
+    let mut docs = state.documents.lock().unwrap();
+    let doc = docs.get_mut(&doc_id).ok_or("Document not found")?;
+    let op = doc.resolve_comment(comment_id, resolved_by.as_deref());
+    share_annotation_ops(&doc_id, doc, vec![op], &state)
+}
+
+/// Delete a shared comment
+#[tauri::command]
+pub fn share_comment_deletion(
+    doc_id: String,
+    comment_id: String,
+    state: State<'_, CollaborationState>,
+) -> Result<String, String> {
+    let comment_id = parse_comment_id(&comment_id)?;
+
+    let mut docs = state.documents.lock().unwrap();
+    let doc = docs.get_mut(&doc_id).ok_or("Document not found")?;
+    let op = doc.delete_comment(comment_id);
+    share_annotation_ops(&doc_id, doc, vec![op], &state)
+}
+
+/// Share a tracked change (JSON) with collaborators
+#[tauri::command]
+pub fn share_revision(
+    doc_id: String,
+    revision_json: String,
+    state: State<'_, CollaborationState>,
+) -> Result<String, String> {
+    let revision: Revision = serde_json::from_str(&revision_json)
+        .map_err(|e| format!("Failed to parse revision: {}", e))?;
+
+    let mut docs = state.documents.lock().unwrap();
+    let doc = docs.get_mut(&doc_id).ok_or("Document not found")?;
+    let ops = doc.track_revision(&revision);
+    share_annotation_ops(&doc_id, doc, ops, &state)
+}
+
+/// Share a tracked change's status: "pending", "accepted", or "rejected"
+#[tauri::command]
+pub fn share_revision_status(
+    doc_id: String,
+    revision_id: String,
+    status: String,
+    state: State<'_, CollaborationState>,
+) -> Result<String, String> {
+    let revision_id = uuid::Uuid::parse_str(&revision_id)
+        .map(RevisionId::from_uuid)
+        .map_err(|e| format!("Invalid revision ID: {}", e))?;
+    let status = match status.as_str() {
+        "pending" => RevisionStatus::Pending,
+        "accepted" => RevisionStatus::Accepted,
+        "rejected" => RevisionStatus::Rejected,
+        _ => return Err(format!("Unknown status: {}", status)),
+    };
+
+    let mut docs = state.documents.lock().unwrap();
+    let doc = docs.get_mut(&doc_id).ok_or("Document not found")?;
+    let op = doc.set_revision_status(revision_id, status);
+    share_annotation_ops(&doc_id, doc, vec![op], &state)
+}
+
+/// Get the comments and tracked changes as collaborators currently see them
+#[tauri::command]
+pub fn get_shared_annotations(
+    doc_id: String,
+    state: State<'_, CollaborationState>,
+) -> Result<serde_json::Value, String> {
+    let docs = state.documents.lock().unwrap();
+    let doc = docs.get(&doc_id).ok_or("Document not found")?;
+    Ok(serde_json::json!({
+        "comments": doc.comments(),
+        "revisions": doc.revisions(),
+    }))
+}
+
 // ============================================================================
 // Permission Commands
 // ============================================================================
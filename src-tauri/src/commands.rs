@@ -3,9 +3,9 @@
 use crate::state::{AppState, FontManagerState, SettingsState};
 use doc_model::{
     Alignment, CharacterProperties, LineSpacing, Node, ParagraphProperties, PropertySource, StyleId,
-    StyleRegistry, StyleType,
+    StyleRegistry, StyleType, TextDirection,
 };
-use edit_engine::Command;
+use edit_engine::{Command, HistoryEntry, UndoHistory};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Mutex;
@@ -35,10 +35,13 @@ pub struct SimpleDocument {
     bold: bool,
     italic: bool,
     underline: bool,
-    undo_stack: Vec<DocumentSnapshot>,
-    redo_stack: Vec<DocumentSnapshot>,
+    undo_stack: Vec<(HistoryEntry, DocumentSnapshot)>,
+    redo_stack: Vec<(HistoryEntry, DocumentSnapshot)>,
 }
 
+/// Maximum number of undo steps kept per document
+const MAX_UNDO_STEPS: usize = 100;
+
 impl SimpleDocument {
     fn new() -> Self {
         Self {
@@ -64,11 +67,35 @@ impl SimpleDocument {
         }
     }
 
-    fn push_undo(&mut self) {
-        self.undo_stack.push(self.snapshot());
+    fn push_undo(&mut self, name: &str) {
+        let entry = HistoryEntry {
+            name: name.to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+        };
+        self.undo_stack.push((entry, self.snapshot()));
+        if self.undo_stack.len() > MAX_UNDO_STEPS {
+            self.undo_stack.remove(0);
+        }
         self.redo_stack.clear();
     }
 
+    fn history(&self) -> UndoHistory {
+        let memory_bytes = self
+            .undo_stack
+            .iter()
+            .chain(&self.redo_stack)
+            .map(|(_, snap)| snap.paragraphs.iter().map(String::capacity).sum::<usize>())
+            .sum();
+        UndoHistory {
+            undo: self.undo_stack.iter().rev().map(|(entry, _)| entry.clone()).collect(),
+            redo: self.redo_stack.iter().rev().map(|(entry, _)| entry.clone()).collect(),
+            memory_bytes,
+        }
+    }
+
     fn restore_snapshot(&mut self, snap: DocumentSnapshot) {
         self.paragraphs = snap.paragraphs;
         self.cursor_para = snap.cursor_para;
@@ -137,7 +164,7 @@ pub fn apply_command(
                 .and_then(|v| v.as_str())
                 .unwrap_or("");
             if !text.is_empty() {
-                doc.push_undo();
+                doc.push_undo("Insert Text");
                 let para = &mut doc.paragraphs[doc.cursor_para];
                 // Insert at byte offset (safe for ASCII; for Unicode we use char indices)
                 let byte_offset = char_to_byte_offset(para, doc.cursor_offset);
@@ -150,17 +177,19 @@ pub fn apply_command(
                 .get("direction")
                 .and_then(|v| v.as_str())
                 .unwrap_or("backward");
+            // Whole clusters are deleted so conjuncts aren't left half-formed
             if direction == "backward" {
                 if doc.cursor_offset > 0 {
-                    doc.push_undo();
+                    doc.push_undo("Delete");
                     let para = &mut doc.paragraphs[doc.cursor_para];
-                    let byte_start = char_to_byte_offset(para, doc.cursor_offset - 1);
+                    let start = text_engine::prev_cluster_boundary(para, doc.cursor_offset);
+                    let byte_start = char_to_byte_offset(para, start);
                     let byte_end = char_to_byte_offset(para, doc.cursor_offset);
                     para.replace_range(byte_start..byte_end, "");
-                    doc.cursor_offset -= 1;
+                    doc.cursor_offset = start;
                 } else if doc.cursor_para > 0 {
                     // Merge with previous paragraph
-                    doc.push_undo();
+                    doc.push_undo("Delete");
                     let current_text = doc.paragraphs.remove(doc.cursor_para);
                     doc.cursor_para -= 1;
                     doc.cursor_offset = doc.paragraphs[doc.cursor_para].chars().count();
@@ -170,21 +199,22 @@ pub fn apply_command(
                 // forward delete
                 let para_len = doc.paragraphs[doc.cursor_para].chars().count();
                 if doc.cursor_offset < para_len {
-                    doc.push_undo();
+                    doc.push_undo("Delete");
                     let para = &mut doc.paragraphs[doc.cursor_para];
+                    let end = text_engine::next_cluster_boundary(para, doc.cursor_offset);
                     let byte_start = char_to_byte_offset(para, doc.cursor_offset);
-                    let byte_end = char_to_byte_offset(para, doc.cursor_offset + 1);
+                    let byte_end = char_to_byte_offset(para, end);
                     para.replace_range(byte_start..byte_end, "");
                 } else if doc.cursor_para < doc.paragraphs.len() - 1 {
                     // Merge next paragraph into current
-                    doc.push_undo();
+                    doc.push_undo("Delete");
                     let next_text = doc.paragraphs.remove(doc.cursor_para + 1);
                     doc.paragraphs[doc.cursor_para].push_str(&next_text);
                 }
             }
         }
         "SplitParagraph" => {
-            doc.push_undo();
+            doc.push_undo("Split Paragraph");
             let para = &doc.paragraphs[doc.cursor_para];
             let byte_offset = char_to_byte_offset(para, doc.cursor_offset);
             let rest = para[byte_offset..].to_string();
@@ -201,7 +231,8 @@ pub fn apply_command(
             match direction {
                 "left" => {
                     if doc.cursor_offset > 0 {
-                        doc.cursor_offset -= 1;
+                        doc.cursor_offset =
+                            text_engine::prev_cluster_boundary(&doc.paragraphs[doc.cursor_para], doc.cursor_offset);
                     } else if doc.cursor_para > 0 {
                         doc.cursor_para -= 1;
                         doc.cursor_offset = doc.paragraphs[doc.cursor_para].chars().count();
@@ -210,7 +241,8 @@ pub fn apply_command(
                 "right" => {
                     let para_len = doc.paragraphs[doc.cursor_para].chars().count();
                     if doc.cursor_offset < para_len {
-                        doc.cursor_offset += 1;
+                        doc.cursor_offset =
+                            text_engine::next_cluster_boundary(&doc.paragraphs[doc.cursor_para], doc.cursor_offset);
                     } else if doc.cursor_para < doc.paragraphs.len() - 1 {
                         doc.cursor_para += 1;
                         doc.cursor_offset = 0;
@@ -271,8 +303,14 @@ pub fn apply_command(
                 .unwrap_or(0) as usize;
             if paragraph < doc.paragraphs.len() {
                 doc.cursor_para = paragraph;
-                let max_offset = doc.paragraphs[paragraph].chars().count();
-                doc.cursor_offset = offset.min(max_offset);
+                let text = &doc.paragraphs[paragraph];
+                let offset = offset.min(text.chars().count());
+                // A click inside a cluster puts the caret before it
+                doc.cursor_offset = if text_engine::is_cluster_boundary(text, offset) {
+                    offset
+                } else {
+                    text_engine::prev_cluster_boundary(text, offset)
+                };
             }
         }
         "bold" => {
@@ -518,40 +556,68 @@ pub fn load_document(path: String, store: State<'_, DocumentStore>) -> Result<St
     Ok(doc_id)
 }
 
-/// Undo the last operation
+/// Undo the last operation, or the last `count` operations (undo-history dropdown)
 #[tauri::command]
-pub fn undo(doc_id: String, store: State<'_, DocumentStore>) -> Result<DocumentChange, String> {
+pub fn undo(
+    doc_id: String,
+    count: Option<usize>,
+    store: State<'_, DocumentStore>,
+) -> Result<DocumentChange, String> {
     let mut docs = store.documents.lock().map_err(|e| e.to_string())?;
     let doc = docs
         .get_mut(&doc_id)
         .ok_or_else(|| format!("Document not found: {}", doc_id))?;
 
-    if let Some(snap) = doc.undo_stack.pop() {
+    for _ in 0..count.unwrap_or(1) {
+        let Some((entry, snap)) = doc.undo_stack.pop() else {
+            break;
+        };
         let current = doc.snapshot();
-        doc.redo_stack.push(current);
+        doc.redo_stack.push((entry, current));
         doc.restore_snapshot(snap);
     }
 
     Ok(make_doc_change(doc))
 }
 
-/// Redo the last undone operation
+/// Redo the last undone operation, or the last `count` undone operations
 #[tauri::command]
-pub fn redo(doc_id: String, store: State<'_, DocumentStore>) -> Result<DocumentChange, String> {
+pub fn redo(
+    doc_id: String,
+    count: Option<usize>,
+    store: State<'_, DocumentStore>,
+) -> Result<DocumentChange, String> {
     let mut docs = store.documents.lock().map_err(|e| e.to_string())?;
     let doc = docs
         .get_mut(&doc_id)
         .ok_or_else(|| format!("Document not found: {}", doc_id))?;
 
-    if let Some(snap) = doc.redo_stack.pop() {
+    for _ in 0..count.unwrap_or(1) {
+        let Some((entry, snap)) = doc.redo_stack.pop() else {
+            break;
+        };
         let current = doc.snapshot();
-        doc.undo_stack.push(current);
+        doc.undo_stack.push((entry, current));
         doc.restore_snapshot(snap);
     }
 
     Ok(make_doc_change(doc))
 }
 
+/// Get the undo and redo history (most recent first) for the undo-history dropdown
+#[tauri::command]
+pub fn get_undo_history(
+    doc_id: String,
+    store: State<'_, DocumentStore>,
+) -> Result<UndoHistory, String> {
+    let docs = store.documents.lock().map_err(|e| e.to_string())?;
+    let doc = docs
+        .get(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    Ok(doc.history())
+}
+
 // IPC Types
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -624,6 +690,12 @@ pub struct EditingSettingsDto {
     pub default_font_size: f32,
     pub show_spelling_errors: bool,
     pub show_grammar_errors: bool,
+    #[serde(default = "default_auto_format_hyperlinks")]
+    pub auto_format_hyperlinks: bool,
+}
+
+fn default_auto_format_hyperlinks() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -651,6 +723,7 @@ impl From<&AppSettings> for SettingsDto {
                 default_font_size: settings.editing.default_font_size,
                 show_spelling_errors: settings.editing.show_spelling_errors,
                 show_grammar_errors: settings.editing.show_grammar_errors,
+                auto_format_hyperlinks: settings.editing.auto_format_hyperlinks,
             },
             privacy: PrivacySettingsDto {
                 telemetry_enabled: settings.privacy.telemetry_enabled,
@@ -679,6 +752,7 @@ impl From<SettingsDto> for AppSettings {
                 default_font_size: dto.editing.default_font_size,
                 show_spelling_errors: dto.editing.show_spelling_errors,
                 show_grammar_errors: dto.editing.show_grammar_errors,
+                auto_format_hyperlinks: dto.editing.auto_format_hyperlinks,
             },
             privacy: PrivacySettings {
                 telemetry_enabled: dto.privacy.telemetry_enabled,
@@ -714,6 +788,73 @@ pub fn reset_settings(state: State<'_, SettingsState>) -> Result<SettingsDto, St
     Ok(SettingsDto::from(settings))
 }
 
+/// Get the settings of a document, with its overrides applied
+#[tauri::command]
+pub fn get_document_settings(
+    doc_id: String,
+    state: State<'_, SettingsState>,
+) -> Result<SettingsDto, String> {
+    let manager = state.manager.lock().map_err(|e| e.to_string())?;
+    Ok(SettingsDto::from(&manager.settings_for_document(&doc_id)))
+}
+
+/// Override one setting (by dotted path, e.g. "editing.default_font_size")
+/// for a document
+#[tauri::command]
+pub fn set_document_setting(
+    doc_id: String,
+    path: String,
+    value: serde_json::Value,
+    state: State<'_, SettingsState>,
+) -> Result<SettingsDto, String> {
+    let mut manager = state.manager.lock().map_err(|e| e.to_string())?;
+    manager.set_document_setting(&doc_id, &path, value);
+    Ok(SettingsDto::from(&manager.settings_for_document(&doc_id)))
+}
+
+/// Remove every setting override of a document
+#[tauri::command]
+pub fn clear_document_settings(
+    doc_id: String,
+    state: State<'_, SettingsState>,
+) -> Result<SettingsDto, String> {
+    let mut manager = state.manager.lock().map_err(|e| e.to_string())?;
+    manager.clear_document_overrides(&doc_id);
+    Ok(SettingsDto::from(&manager.settings_for_document(&doc_id)))
+}
+
+/// Get the layer a setting's value comes from
+#[tauri::command]
+pub fn get_setting_layer(
+    path: String,
+    doc_id: Option<String>,
+    state: State<'_, SettingsState>,
+) -> Result<store::SettingsLayer, String> {
+    let manager = state.manager.lock().map_err(|e| e.to_string())?;
+    Ok(manager.layer_of(&path, doc_id.as_deref()))
+}
+
+/// Export the settings profile as a portable JSON bundle
+#[tauri::command]
+pub fn export_settings_bundle(
+    path: String,
+    state: State<'_, SettingsState>,
+) -> Result<(), String> {
+    let manager = state.manager.lock().map_err(|e| e.to_string())?;
+    manager.export_bundle_sync(&path).map_err(|e| e.to_string())
+}
+
+/// Import a settings profile bundle, replacing the user's settings
+#[tauri::command]
+pub fn import_settings_bundle(
+    path: String,
+    state: State<'_, SettingsState>,
+) -> Result<SettingsDto, String> {
+    let mut manager = state.manager.lock().map_err(|e| e.to_string())?;
+    let settings = manager.import_bundle_file_sync(&path).map_err(|e| e.to_string())?;
+    Ok(SettingsDto::from(settings))
+}
+
 // =============================================================================
 // Style System Commands
 // =============================================================================
@@ -745,6 +886,7 @@ pub struct ParagraphPropertiesDto {
     pub space_before: Option<f32>,
     pub space_after: Option<f32>,
     pub line_spacing: Option<LineSpacingDto>,
+    pub direction: Option<String>, // "ltr", "rtl", "auto"
 }
 
 /// Line spacing DTO
@@ -837,6 +979,14 @@ impl From<PropertySource> for PropertySourceDto {
     }
 }
 
+fn direction_to_string(direction: TextDirection) -> String {
+    match direction {
+        TextDirection::Ltr => "ltr".to_string(),
+        TextDirection::Rtl => "rtl".to_string(),
+        TextDirection::Auto => "auto".to_string(),
+    }
+}
+
 fn alignment_to_string(alignment: Alignment) -> String {
     match alignment {
         Alignment::Left => "left".to_string(),
@@ -888,6 +1038,7 @@ impl From<&ParagraphProperties> for ParagraphPropertiesDto {
             space_before: props.space_before,
             space_after: props.space_after,
             line_spacing: props.line_spacing.map(|ls| ls.into()),
+            direction: props.direction.map(direction_to_string),
         }
     }
 }
@@ -917,6 +1068,15 @@ fn parse_alignment(s: &str) -> Option<Alignment> {
     }
 }
 
+fn parse_direction(s: &str) -> Option<TextDirection> {
+    match s {
+        "ltr" => Some(TextDirection::Ltr),
+        "rtl" => Some(TextDirection::Rtl),
+        "auto" => Some(TextDirection::Auto),
+        _ => None,
+    }
+}
+
 fn parse_line_spacing(dto: &LineSpacingDto) -> LineSpacing {
     match dto {
         LineSpacingDto::Multiple { value } => LineSpacing::Multiple(*value),
@@ -935,6 +1095,7 @@ impl From<&ParagraphPropertiesDto> for ParagraphProperties {
             space_before: dto.space_before,
             space_after: dto.space_after,
             line_spacing: dto.line_spacing.as_ref().map(parse_line_spacing),
+            direction: dto.direction.as_ref().and_then(|s| parse_direction(s)),
             ..Default::default()
         }
     }
@@ -1367,6 +1528,42 @@ pub fn resolve_font(
     })
 }
 
+// =============================================================================
+// Equation Commands
+// =============================================================================
+
+/// Lay out and render a LaTeX equation with an installed math font
+///
+/// `font_family` is tried before the well-known math fonts. Without any
+/// math font the equation is laid out with synthetic metrics.
+#[tauri::command]
+pub fn render_equation(
+    latex: String,
+    font_size: f32,
+    font_family: Option<String>,
+    state: State<'_, FontManagerState>,
+) -> Result<math::RenderOutput, String> {
+    let node = math::parse_latex(&latex).map_err(|e| e.to_string())?;
+
+    let engine = {
+        let manager = state.manager.lock().map_err(|e| e.to_string())?;
+        let families: Vec<&str> = font_family.as_deref().into_iter().collect();
+        math::LayoutEngine::with_font_manager(&manager, &families, font_size)
+    };
+    let layout = engine.layout(&node).map_err(|e| e.to_string())?;
+
+    let mut config = math::RenderConfig {
+        font_size,
+        ..Default::default()
+    };
+    if let Some(family) = engine.math_table().and_then(|table| table.font_family.clone()) {
+        config.font_family = family;
+    }
+    math::Renderer::with_config(config)
+        .render(&layout)
+        .map_err(|e| e.to_string())
+}
+
 // =============================================================================
 // Bookmark Commands
 // =============================================================================
@@ -2164,6 +2361,51 @@ pub struct PdfAConformanceLevelDto {
     pub requires_font_embedding: bool,
 }
 
+// =============================================================================
+// Accessibility Commands
+// =============================================================================
+
+/// Check a document for accessibility issues
+#[tauri::command]
+pub fn check_accessibility(
+    doc_id: String,
+    state: State<'_, AppState>,
+) -> Result<edit_engine::AccessibilityReport, String> {
+    let documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    Ok(edit_engine::check_accessibility(&doc_state.tree))
+}
+
+/// Set the alt text of an image, shape or table, or mark it decorative
+#[tauri::command]
+pub fn set_alt_text(
+    doc_id: String,
+    node_id: String,
+    alt_text: Option<String>,
+    decorative: bool,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let node_id = doc_model::NodeId::from_string(&node_id)
+        .ok_or_else(|| format!("Invalid node ID: {}", node_id))?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let cmd = edit_engine::SetAltText { node_id, alt_text, decorative };
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+
+    doc_state.tree = result.tree;
+    doc_state.dirty = true;
+
+    Ok(())
+}
+
 // =============================================================================
 // DOCX Import/Export Commands
 // =============================================================================
@@ -2213,6 +2455,19 @@ pub struct DocumentDataDto {
     pub paragraph_count: usize,
     /// Approximate word count
     pub word_count: usize,
+    /// Digital signatures found in the file, verified on open
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signatures: Vec<store::signatures::SignatureReport>,
+}
+
+/// Verify the signatures of a file being opened
+///
+/// A file whose signatures can't be read opens as unsigned.
+fn signatures_on_open(path: &std::path::Path) -> Vec<store::signatures::SignatureReport> {
+    store::signatures::verify_signatures(path).unwrap_or_else(|e| {
+        tracing::warn!("Failed to verify signatures of {}: {}", path.display(), e);
+        Vec::new()
+    })
 }
 
 /// Open a DOCX file and return document data
@@ -2237,6 +2492,7 @@ pub fn open_docx(path: String) -> Result<DocumentDataDto, String> {
         title,
         paragraph_count,
         word_count,
+        signatures: signatures_on_open(path),
     })
 }
 
@@ -2397,6 +2653,7 @@ pub fn import_docx_with_options(
         title,
         paragraph_count,
         word_count,
+        signatures: signatures_on_open(path),
     })
 }
 
@@ -2421,10 +2678,56 @@ pub fn export_docx_with_options(
 
 /// Get import warnings for a document
 #[tauri::command]
-pub fn get_docx_import_warnings(_doc_id: String) -> Result<Vec<DocxFidelityWarningDto>, String> {
-    // TODO: Retrieve warnings from document state
-    // For now, return empty list
-    Ok(Vec::new())
+pub fn get_docx_import_warnings(
+    doc_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<DocxFidelityWarningDto>, String> {
+    let documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    Ok(docx::embedded_object_warnings(&doc_state.tree)
+        .into_iter()
+        .map(|w| DocxFidelityWarningDto {
+            code: w.code,
+            message: w.message,
+            severity: format!("{:?}", w.severity).to_lowercase(),
+            category: format!("{:?}", w.category),
+            suggestion: w.suggestion,
+            count: w.count,
+        })
+        .collect())
+}
+
+/// Get the cached preview picture of an embedded object as a data URL
+///
+/// Returns None if the object has no preview; it's then shown as a
+/// placeholder.
+#[tauri::command]
+pub fn get_embedded_object_preview(
+    doc_id: String,
+    object_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, String> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    let object_id = doc_model::NodeId::from_string(&object_id)
+        .ok_or_else(|| format!("Invalid object ID: {}", object_id))?;
+
+    let documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+    let object = doc_state
+        .tree
+        .get_embedded_object(object_id)
+        .ok_or_else(|| format!("Embedded object not found: {}", object_id))?;
+
+    Ok(object
+        .preview
+        .as_ref()
+        .map(|preview| format!("data:{};base64,{}", preview.content_type, STANDARD.encode(&preview.data))))
 }
 
 /// Validate DOCX fidelity for a document
@@ -2444,7 +2747,6 @@ pub fn validate_docx_fidelity(_doc_id: String) -> Result<FidelityReportDto, Stri
 // Print Commands
 // =============================================================================
 
-use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine as _};
 use tauri::AppHandle;
 
 /// Print capabilities information
@@ -2483,6 +2785,28 @@ pub struct PrintOptions {
     pub copies: u32,
     /// Whether to collate when printing multiple copies
     pub collate: bool,
+    /// Highest resolution for printers that take bitmaps, in dots per inch
+    /// (default 300)
+    #[serde(default)]
+    pub dpi: Option<u32>,
+    /// Double-sided printing
+    #[serde(default)]
+    pub duplex: store::print::DuplexMode,
+    /// Paper tray (None = printer default)
+    #[serde(default)]
+    pub paper_tray: Option<String>,
+    /// Color or grayscale output
+    #[serde(default)]
+    pub color_mode: store::print::ColorMode,
+    /// How pages are sized on the paper
+    #[serde(default)]
+    pub scaling: store::print::PageScaling,
+    /// Pages per sheet, booklet printing, and scaling to a paper size
+    #[serde(default)]
+    pub imposition: store::pdf::Imposition,
+    /// Swap left and right margins on even pages for double-sided printing
+    #[serde(default)]
+    pub mirror_margins: bool,
 }
 
 /// Page range specification for printing
@@ -2598,17 +2922,135 @@ pub async fn get_print_capabilities() -> Result<PrintCapabilities, String> {
     }
 }
 
+/// Lay out a document into render pages
+fn document_render_pages(
+    tree: &doc_model::DocumentTree,
+    config: layout_engine::PageConfig,
+) -> Result<Vec<render_model::PageRender>, String> {
+    // Sections carry the document's binding gutter and mirrored margins
+    let config = match tree.sections.first() {
+        Some(section) => config.with_section_binding(&section.page_setup),
+        None => config,
+    };
+    let layout = layout_engine::Paginator::new(config)
+        .layout(tree)
+        .map_err(|e| e.to_string())?;
+    let model = render_model::RenderConverter::new(render_model::RenderConfig::default())
+        .convert(&layout, tree)
+        .map_err(|e| e.to_string())?;
+    Ok(model.pages)
+}
+
+/// Lay out an open document into render pages
+fn render_pages_for(
+    doc_id: &str,
+    state: &AppState,
+    config: layout_engine::PageConfig,
+) -> Result<Vec<render_model::PageRender>, String> {
+    let tree = {
+        let documents = state.documents.lock().map_err(|e| e.to_string())?;
+        documents
+            .get(doc_id)
+            .ok_or_else(|| format!("Document not found: {}", doc_id))?
+            .tree
+            .clone()
+    };
+    document_render_pages(&tree, config)
+}
+
+/// Get the tree and pictures of an open document
+fn document_with_images(
+    doc_id: &str,
+    state: &AppState,
+) -> Result<(doc_model::DocumentTree, store::ImageStore), String> {
+    let documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get(doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+    Ok((doc_state.tree.clone(), doc_state.images.clone()))
+}
+
+/// Create a rasterizer with the fonts used on the pages loaded, drawing
+/// pictures from the document's image store
+///
+/// Fonts that can't be found are drawn as placeholder bars.
+fn page_rasterizer(
+    pages: &[render_model::PageRender],
+    options: store::raster::RasterOptions,
+    fonts: &FontManagerState,
+    images: store::ImageStore,
+) -> store::raster::PageRasterizer {
+    fn collect_fonts(items: &[render_model::RenderItem], fonts: &mut Vec<(String, bool, bool)>) {
+        for item in items {
+            let font = match item {
+                render_model::RenderItem::GlyphRun(run) => (run.font_family.clone(), run.bold, run.italic),
+                render_model::RenderItem::LineNumber(number) => (number.font_family.clone(), false, false),
+                render_model::RenderItem::Watermark(render_model::WatermarkRenderInfo {
+                    content: render_model::WatermarkRenderContent::Text { font_family, .. },
+                    ..
+                }) => (font_family.clone(), false, false),
+                render_model::RenderItem::TextBox(text_box) => {
+                    collect_fonts(&text_box.content_items, fonts);
+                    continue;
+                }
+                _ => continue,
+            };
+            if !fonts.contains(&font) {
+                fonts.push(font);
+            }
+        }
+    }
+
+    let mut used = Vec::new();
+    for page in pages {
+        collect_fonts(&page.items, &mut used);
+    }
+
+    let mut rasterizer = store::raster::PageRasterizer::new(options);
+    rasterizer.set_images(images);
+    let Ok(manager) = fonts.manager.lock() else {
+        return rasterizer;
+    };
+    for (family, bold, italic) in used {
+        let weight = if bold { FontWeight::Bold } else { FontWeight::Normal };
+        let style = if italic { FontStyle::Italic } else { FontStyle::Normal };
+        match manager.resolve_and_load(&family, weight, style) {
+            Ok((loaded, _)) => {
+                let font_id = text_engine::FontId::new(&family).with_weight(weight).with_style(style);
+                if let Err(e) = rasterizer.load_font(font_id, loaded.data.to_vec()) {
+                    tracing::warn!("Failed to load font {} for rendering: {:?}", family, e);
+                }
+            }
+            Err(e) => tracing::warn!("No font found for {}: {:?}", family, e),
+        }
+    }
+    rasterizer
+}
+
+/// Default highest resolution for printers that take bitmaps
+const DEFAULT_PRINT_DPI: u32 = 300;
+
+/// Interval between print job status checks
+const PRINT_STATUS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Event emitted with a `PrintJobStatus` while a print job is in progress
+const PRINT_JOB_STATUS_EVENT: &str = "print-job-status";
+
 /// Send a document to the printer
 ///
-/// This generates a PDF internally and opens the system print dialog,
-/// or sends directly to the specified printer.
+/// With a printer selected, the job goes to the platform print system with
+/// the requested options and its status is emitted as `print-job-status`
+/// events until it finishes. Otherwise a PDF is generated and opened so the
+/// system print dialog can be used, and no job is returned.
 #[tauri::command]
 pub async fn print_document(
     doc_id: String,
     options: PrintOptions,
     app: AppHandle,
-) -> Result<(), String> {
-    // Get app cache directory for temporary PDF
+    state: State<'_, AppState>,
+    fonts: State<'_, FontManagerState>,
+) -> Result<Option<store::print::PrintJob>, String> {
+    // Get app cache directory for temporary files
     let cache_dir = app
         .path()
         .app_cache_dir()
@@ -2618,17 +3060,8 @@ pub async fn print_document(
     std::fs::create_dir_all(&cache_dir)
         .map_err(|e| format!("Failed to create cache directory: {}", e))?;
 
-    // Generate a temporary PDF file
-    let temp_pdf_path = cache_dir.join(format!("print_{}.pdf", doc_id));
-
-    // TODO: Get actual render pages from the document state
-    // For now, create a placeholder page
-    let pages = vec![render_model::PageRender {
-        page_index: 0,
-        width: 612.0,
-        height: 792.0,
-        items: vec![],
-    }];
+    let config = layout_engine::PageConfig::default().with_mirror_margins(options.mirror_margins);
+    let pages = render_pages_for(&doc_id, &state, config)?;
 
     // Filter pages based on print options
     let pages_to_print: Vec<_> = match &options.page_range {
@@ -2654,100 +3087,117 @@ pub async fn print_document(
         return Err("No pages to print".to_string());
     }
 
-    // Export to PDF
-    let pdf_options = store::pdf::PdfExportOptions::new();
-    store::pdf::export_pdf(&pages_to_print, &temp_pdf_path, pdf_options)
+    if let Some(printer_name) = &options.printer {
+        let backend = store::print::default_backend().map_err(|e| e.to_string())?;
+        let dpi = options.dpi.unwrap_or(DEFAULT_PRINT_DPI);
+        let mut settings = store::print::PrintSettings::new()
+            .with_title(&doc_id)
+            .with_copies(options.copies)
+            .with_collate(options.collate)
+            .with_duplex(options.duplex)
+            .with_color_mode(options.color_mode)
+            .with_scaling(options.scaling)
+            .with_imposition(options.imposition)
+            .with_max_dpi(dpi);
+        if let Some(tray) = &options.paper_tray {
+            settings = settings.with_paper_tray(tray);
+        }
+        let (_, images) = document_with_images(&doc_id, &state)?;
+        let raster_options = store::raster::RasterOptions::print(dpi as f64);
+        let rasterizer = page_rasterizer(&pages_to_print, raster_options, &fonts, images);
+        let job = backend
+            .submit(printer_name, &pages_to_print, &settings, &rasterizer)
+            .map_err(|e| e.to_string())?;
+        watch_print_job(app, backend, job.clone());
+        return Ok(Some(job));
+    }
+
+    // Generate a temporary PDF file for the print dialog
+    let temp_pdf_path = cache_dir.join(format!("print_{}.pdf", doc_id));
+    let pdf_options = store::pdf::PdfExportOptions::new().with_imposition(options.imposition);
+    let (tree, images) = document_with_images(&doc_id, &state)?;
+    store::pdf::export_pdf_with_images(&pages_to_print, &tree, &images, &temp_pdf_path, pdf_options)
         .map_err(|e| format!("Failed to generate PDF for printing: {}", e))?;
 
-    // Open the PDF with the system's default handler or print dialog
+    // Open the PDF with the system's default handler, which offers printing
     #[cfg(target_os = "macos")]
     {
-        // On macOS, use 'lpr' for direct printing or 'open' to open in Preview
-        if let Some(printer_name) = &options.printer {
-            // Direct print to specified printer
-            let mut cmd = std::process::Command::new("lpr");
-            cmd.arg("-P").arg(printer_name);
-
-            if options.copies > 1 {
-                cmd.arg("-#").arg(options.copies.to_string());
-            }
-
-            cmd.arg(&temp_pdf_path);
-
-            let status = cmd
-                .status()
-                .map_err(|e| format!("Failed to execute lpr: {}", e))?;
-
-            if !status.success() {
-                return Err("Print command failed".to_string());
-            }
-        } else {
-            // Open in system viewer which allows user to use print dialog
-            std::process::Command::new("open")
-                .arg(&temp_pdf_path)
-                .spawn()
-                .map_err(|e| format!("Failed to open PDF: {}", e))?;
-        }
+        std::process::Command::new("open")
+            .arg(&temp_pdf_path)
+            .spawn()
+            .map_err(|e| format!("Failed to open PDF: {}", e))?;
     }
 
     #[cfg(target_os = "windows")]
     {
-        // On Windows, use the shell to open the print dialog
-        if let Some(printer_name) = &options.printer {
-            // Direct print using PowerShell
-            let script = format!(
-                "Start-Process -FilePath '{}' -Verb Print -ArgumentList '/d:{}'",
-                temp_pdf_path.display(),
-                printer_name
-            );
-            std::process::Command::new("powershell")
-                .args(["-Command", &script])
-                .spawn()
-                .map_err(|e| format!("Failed to print: {}", e))?;
-        } else {
-            // Open with default handler
-            std::process::Command::new("cmd")
-                .args(["/C", "start", "", temp_pdf_path.to_str().unwrap_or("")])
-                .spawn()
-                .map_err(|e| format!("Failed to open PDF: {}", e))?;
-        }
+        std::process::Command::new("cmd")
+            .args(["/C", "start", "", temp_pdf_path.to_str().unwrap_or("")])
+            .spawn()
+            .map_err(|e| format!("Failed to open PDF: {}", e))?;
     }
 
     #[cfg(target_os = "linux")]
     {
-        // On Linux, use lpr for printing or xdg-open for viewing
-        if let Some(printer_name) = &options.printer {
-            let mut cmd = std::process::Command::new("lpr");
-            cmd.arg("-P").arg(printer_name);
+        std::process::Command::new("xdg-open")
+            .arg(&temp_pdf_path)
+            .spawn()
+            .map_err(|e| format!("Failed to open PDF: {}", e))?;
+    }
 
-            if options.copies > 1 {
-                cmd.arg("-#").arg(options.copies.to_string());
-            }
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+    {
+        return Err("Printing not supported on this platform".to_string());
+    }
 
-            cmd.arg(&temp_pdf_path);
+    Ok(None)
+}
 
-            let status = cmd
-                .status()
-                .map_err(|e| format!("Failed to execute lpr: {}", e))?;
+/// Emit status events for a print job until it finishes
+fn watch_print_job(app: AppHandle, backend: Box<dyn store::print::PrintBackend>, job: store::print::PrintJob) {
+    use tauri::Emitter;
 
-            if !status.success() {
-                return Err("Print command failed".to_string());
+    std::thread::spawn(move || loop {
+        match backend.status(&job) {
+            Ok(status) => {
+                let finished = status.state.is_finished();
+                if let Err(e) = app.emit(PRINT_JOB_STATUS_EVENT, &status) {
+                    tracing::warn!("Failed to emit print job status: {}", e);
+                }
+                if finished {
+                    break;
+                }
+            }
+            Err(e) => {
+                // Jobs leave the queue once done on some print systems
+                tracing::debug!("Stopped watching print job {}: {}", job.id, e);
+                break;
             }
-        } else {
-            // Open with default handler
-            std::process::Command::new("xdg-open")
-                .arg(&temp_pdf_path)
-                .spawn()
-                .map_err(|e| format!("Failed to open PDF: {}", e))?;
         }
-    }
+        std::thread::sleep(PRINT_STATUS_INTERVAL);
+    });
+}
 
-    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
-    {
-        return Err("Printing not supported on this platform".to_string());
-    }
+/// Get the status of a print job
+#[tauri::command]
+pub async fn get_print_job_status(printer: String, job_id: u32) -> Result<store::print::PrintJobStatus, String> {
+    let backend = store::print::default_backend().map_err(|e| e.to_string())?;
+    backend
+        .status(&store::print::PrintJob { printer, id: job_id })
+        .map_err(|e| e.to_string())
+}
 
-    Ok(())
+/// Cancel a print job
+#[tauri::command]
+pub async fn cancel_print_job(printer: String, job_id: u32) -> Result<(), String> {
+    let backend = store::print::default_backend().map_err(|e| e.to_string())?;
+    backend
+        .cancel(&store::print::PrintJob { printer, id: job_id })
+        .map_err(|e| e.to_string())
+}
+
+/// Raster options for a print preview zoom level
+fn preview_options(scale: f64) -> store::raster::RasterOptions {
+    store::raster::RasterOptions::new().with_scale(scale)
 }
 
 /// Render a single page for print preview
@@ -2755,215 +3205,66 @@ pub async fn print_document(
 /// Returns a base64-encoded PNG image of the page at the specified scale.
 #[tauri::command]
 pub async fn render_preview_page(
-    _doc_id: String,
+    doc_id: String,
     page_number: usize,
     scale: f64,
+    state: State<'_, AppState>,
+    fonts: State<'_, FontManagerState>,
 ) -> Result<String, String> {
-    // TODO: Get actual render page from document state
-    // For now, create a placeholder page
-    let page = render_model::PageRender {
-        page_index: page_number as u32,
-        width: 612.0,  // Letter size width in points
-        height: 792.0, // Letter size height in points
-        items: vec![],
-    };
-
-    // Calculate dimensions based on scale
-    let width = (page.width * scale) as u32;
-    let height = (page.height * scale) as u32;
+    let pages = render_pages_for(&doc_id, &state, layout_engine::PageConfig::default())?;
+    let page = pages
+        .get(page_number)
+        .ok_or_else(|| format!("Page {} not found", page_number + 1))?;
 
-    // Create a placeholder image
-    // In a real implementation, this would render the actual page content
-    let png_data = create_placeholder_png(width, height, page_number)?;
+    let (_, images) = document_with_images(&doc_id, &state)?;
+    let rasterizer = page_rasterizer(std::slice::from_ref(page), preview_options(scale), &fonts, images);
+    let png_data = rasterizer
+        .render_png(page)
+        .map_err(|e| format!("Failed to render page: {}", e))?;
 
-    // Encode as base64
-    let base64_encoded = BASE64_STANDARD.encode(&png_data);
-
-    Ok(format!("data:image/png;base64,{}", base64_encoded))
+    Ok(store::raster::png_data_url(&png_data))
 }
 
 /// Render multiple thumbnail images for print preview
 ///
-/// Returns an array of base64-encoded PNG thumbnails.
+/// Returns an array of base64-encoded PNG thumbnails, stopping at the last
+/// page of the document.
 #[tauri::command]
 pub async fn render_preview_thumbnails(
     doc_id: String,
     start_page: usize,
     count: usize,
+    state: State<'_, AppState>,
+    fonts: State<'_, FontManagerState>,
 ) -> Result<Vec<String>, String> {
-    let mut thumbnails = Vec::with_capacity(count);
-
     // Thumbnail scale (smaller than full preview)
     let thumbnail_scale = 0.2;
 
-    for i in 0..count {
-        let page_number = start_page + i;
-
-        // TODO: Check if page exists in document
-        // For now, generate placeholder thumbnails
-
-        let thumbnail = render_preview_page(doc_id.clone(), page_number, thumbnail_scale).await?;
-        thumbnails.push(thumbnail);
-    }
+    let pages = render_pages_for(&doc_id, &state, layout_engine::PageConfig::default())?;
+    let pages: Vec<_> = pages.into_iter().skip(start_page).take(count).collect();
+    let (_, images) = document_with_images(&doc_id, &state)?;
+    let rasterizer = page_rasterizer(&pages, preview_options(thumbnail_scale), &fonts, images);
 
-    Ok(thumbnails)
-}
-
-/// Create a simple placeholder PNG image
-///
-/// This creates a minimal valid PNG with a white background and a border.
-/// In production, this would be replaced with actual page rendering.
-fn create_placeholder_png(width: u32, height: u32, _page_number: usize) -> Result<Vec<u8>, String> {
-    let mut png_data = Vec::new();
-
-    // PNG signature
-    png_data.extend_from_slice(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]);
-
-    // IHDR chunk
-    let mut ihdr_data = Vec::new();
-    ihdr_data.extend_from_slice(&width.to_be_bytes());
-    ihdr_data.extend_from_slice(&height.to_be_bytes());
-    ihdr_data.push(8);  // Bit depth
-    ihdr_data.push(2);  // Color type (RGB)
-    ihdr_data.push(0);  // Compression method
-    ihdr_data.push(0);  // Filter method
-    ihdr_data.push(0);  // Interlace method
-
-    write_png_chunk(&mut png_data, b"IHDR", &ihdr_data)?;
-
-    // IDAT chunk (image data)
-    // Create a simple white image with a border
-    let mut raw_data = Vec::new();
-    for y in 0..height {
-        raw_data.push(0); // Filter type: None
-        for x in 0..width {
-            // Create a light gray background with a darker border
-            let is_border = x < 2 || x >= width - 2 || y < 2 || y >= height - 2;
-            if is_border {
-                raw_data.extend_from_slice(&[200, 200, 200]); // Gray border
-            } else {
-                raw_data.extend_from_slice(&[255, 255, 255]); // White background
-            }
-        }
-    }
-
-    // Compress the image data using deflate
-    let compressed = compress_deflate(&raw_data)?;
-    write_png_chunk(&mut png_data, b"IDAT", &compressed)?;
-
-    // IEND chunk
-    write_png_chunk(&mut png_data, b"IEND", &[])?;
-
-    Ok(png_data)
-}
-
-/// Write a PNG chunk with CRC
-fn write_png_chunk(output: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) -> Result<(), String> {
-    // Length (4 bytes, big-endian)
-    output.extend_from_slice(&(data.len() as u32).to_be_bytes());
-
-    // Chunk type
-    output.extend_from_slice(chunk_type);
-
-    // Chunk data
-    output.extend_from_slice(data);
-
-    // CRC32 of chunk type + data
-    let crc = calculate_crc32(chunk_type, data);
-    output.extend_from_slice(&crc.to_be_bytes());
-
-    Ok(())
-}
-
-/// Calculate CRC32 for PNG chunk
-fn calculate_crc32(chunk_type: &[u8], data: &[u8]) -> u32 {
-    let mut crc: u32 = 0xFFFFFFFF;
-
-    // CRC table generation
-    let mut crc_table = [0u32; 256];
-    for n in 0..256 {
-        let mut c = n as u32;
-        for _ in 0..8 {
-            if c & 1 != 0 {
-                c = 0xEDB88320 ^ (c >> 1);
-            } else {
-                c >>= 1;
-            }
-        }
-        crc_table[n] = c;
-    }
-
-    // Update CRC with chunk type
-    for &byte in chunk_type {
-        crc = crc_table[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
-    }
-
-    // Update CRC with data
-    for &byte in data {
-        crc = crc_table[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
-    }
-
-    crc ^ 0xFFFFFFFF
-}
-
-/// Simple deflate compression for PNG
-fn compress_deflate(data: &[u8]) -> Result<Vec<u8>, String> {
-    let mut output = Vec::new();
-
-    // zlib header (CMF + FLG)
-    output.push(0x78); // CMF: compression method 8 (deflate), window size 32K
-    output.push(0x01); // FLG: no dict, compression level 0
-
-    // Split data into blocks of max 65535 bytes
-    let chunks: Vec<&[u8]> = data.chunks(65535).collect();
-    let num_chunks = chunks.len();
-
-    for (i, chunk) in chunks.iter().enumerate() {
-        let is_final = i == num_chunks - 1;
-
-        // Block header: BFINAL (1 bit) + BTYPE (2 bits) = 00 for stored
-        output.push(if is_final { 0x01 } else { 0x00 });
-
-        // LEN (2 bytes, little-endian)
-        let len = chunk.len() as u16;
-        output.push((len & 0xFF) as u8);
-        output.push((len >> 8) as u8);
-
-        // NLEN (one's complement of LEN)
-        let nlen = !len;
-        output.push((nlen & 0xFF) as u8);
-        output.push((nlen >> 8) as u8);
-
-        // Data
-        output.extend_from_slice(chunk);
-    }
-
-    // Adler-32 checksum
-    let adler = calculate_adler32(data);
-    output.extend_from_slice(&adler.to_be_bytes());
-
-    Ok(output)
-}
-
-/// Calculate Adler-32 checksum
-fn calculate_adler32(data: &[u8]) -> u32 {
-    let mut a: u32 = 1;
-    let mut b: u32 = 0;
-
-    for &byte in data {
-        a = (a + byte as u32) % 65521;
-        b = (b + a) % 65521;
-    }
-
-    (b << 16) | a
+    pages
+        .iter()
+        .map(|page| {
+            rasterizer
+                .render_png(page)
+                .map(|png| store::raster::png_data_url(&png))
+                .map_err(|e| format!("Failed to render thumbnail: {}", e))
+        })
+        .collect()
 }
 
 // =============================================================================
 // Performance Telemetry Commands
 // =============================================================================
 
-use crate::state::PerfMetricsState;
-use perf::{BudgetReport, BudgetViolation, PerfBudget, PerfSummary, TimingStats, ViolationSeverity};
+use crate::state::{CollaborationState, PerfMetricsState, ProfilerState};
+use collab::operation::CrdtOp;
+use collab::VectorClock;
+use perf::{BudgetReport, BudgetViolation, FrameRecord, PerfBudget, PerfSummary, TimingStats, ViolationSeverity};
+use telemetry::{AllocationInfo, ComponentUsage, MemorySnapshot, Subsystem};
 
 /// Performance summary DTO for frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -3265,6 +3566,321 @@ pub fn record_input_latency(
     Ok(())
 }
 
+/// Get the per-frame timeline for the performance HUD
+///
+/// Pass the number after the last frame already shown as `since` to get
+/// only new frames.
+#[tauri::command]
+pub fn get_frame_timeline(
+    since: Option<u64>,
+    state: State<'_, PerfMetricsState>,
+) -> Result<Vec<FrameRecord>, String> {
+    let metrics = state.metrics.lock().map_err(|e| e.to_string())?;
+    Ok(metrics.frames_since(since.unwrap_or(0)))
+}
+
+/// Summary of a finished profiling session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileSessionDto {
+    pub name: String,
+    pub span_count: usize,
+    pub total_ms: f64,
+}
+
+/// File format for saved profile traces
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProfileTraceFormat {
+    /// Chrome trace-event JSON
+    Chrome,
+    /// Folded stacks for flame graph tools
+    Folded,
+}
+
+/// Start a profiling session
+#[tauri::command]
+pub fn start_profiling(
+    name: String,
+    state: State<'_, ProfilerState>,
+) -> Result<(), String> {
+    let mut profiler = state.profiler.lock().map_err(|e| e.to_string())?;
+    if profiler.is_active() {
+        return Err("A profiling session is already running".to_string());
+    }
+    profiler.start_session(name);
+    Ok(())
+}
+
+/// Stop the profiling session
+#[tauri::command]
+pub fn stop_profiling(
+    state: State<'_, ProfilerState>,
+) -> Result<ProfileSessionDto, String> {
+    let mut profiler = state.profiler.lock().map_err(|e| e.to_string())?;
+    if !profiler.is_active() {
+        return Err("No profiling session is running".to_string());
+    }
+    let trace = profiler.finish_session();
+    Ok(ProfileSessionDto {
+        name: trace.name.clone(),
+        span_count: trace.span_count(),
+        total_ms: trace.total_duration.as_secs_f64() * 1000.0,
+    })
+}
+
+/// Save the last finished profiling session's trace to a file
+#[tauri::command]
+pub fn save_profile_trace(
+    path: String,
+    format: ProfileTraceFormat,
+    state: State<'_, ProfilerState>,
+) -> Result<(), String> {
+    let profiler = state.profiler.lock().map_err(|e| e.to_string())?;
+    let trace = profiler
+        .get_history()
+        .back()
+        .ok_or_else(|| "No profiling session has finished".to_string())?;
+    match format {
+        ProfileTraceFormat::Chrome => trace.write_chrome_trace(&path),
+        ProfileTraceFormat::Folded => trace.write_folded_stacks(&path),
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Memory use per subsystem for the diagnostics panel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryBreakdownDto {
+    pub total_bytes: usize,
+    /// Usage per subsystem, largest first
+    pub components: Vec<ComponentUsage>,
+}
+
+/// Estimated bytes and node count of a document tree's paragraphs and runs
+fn tree_memory(tree: &doc_model::DocumentTree) -> (usize, usize) {
+    let mut bytes = 0;
+    let mut count = 0;
+    for para in tree.paragraphs() {
+        bytes += std::mem::size_of::<doc_model::Paragraph>();
+        count += 1;
+        for run in para.children().iter().filter_map(|&id| tree.get_run(id)) {
+            bytes += std::mem::size_of::<doc_model::Run>() + run.text.capacity();
+            count += 1;
+        }
+    }
+    (bytes, count)
+}
+
+/// Estimate the memory held by each subsystem across open documents
+///
+/// Layouts aren't cached in app state, so the layout cache isn't listed.
+#[tauri::command]
+pub fn get_memory_breakdown(
+    store: State<'_, DocumentStore>,
+    app_state: State<'_, AppState>,
+    collab_state: State<'_, CollaborationState>,
+) -> Result<MemoryBreakdownDto, String> {
+    let mut usage: HashMap<Subsystem, AllocationInfo> = HashMap::new();
+    let mut add = |subsystem: Subsystem, bytes: usize, count: usize| {
+        let info = usage.entry(subsystem).or_default();
+        info.bytes += bytes;
+        info.count += count;
+    };
+
+    for doc in store.documents.lock().map_err(|e| e.to_string())?.values() {
+        let text: usize = doc.paragraphs.iter().map(String::capacity).sum();
+        add(Subsystem::DocumentTree, text, doc.paragraphs.len());
+        let history = doc.history();
+        add(Subsystem::UndoStack, history.memory_bytes, history.undo.len() + history.redo.len());
+    }
+    for doc in app_state.documents.lock().map_err(|e| e.to_string())?.values() {
+        let (bytes, count) = tree_memory(&doc.tree);
+        add(Subsystem::DocumentTree, bytes, count);
+        add(Subsystem::ImageStore, doc.images.total_size(), doc.images.len());
+    }
+    for doc in collab_state.documents.lock().map_err(|e| e.to_string())?.values() {
+        let ops = doc.ops_since(&VectorClock::new()).len();
+        add(Subsystem::CrdtState, ops * std::mem::size_of::<CrdtOp>(), ops);
+    }
+
+    let mut snapshot = MemorySnapshot::new(0, std::time::Duration::ZERO);
+    for (subsystem, info) in usage {
+        snapshot.add_allocation(subsystem.component(), info);
+    }
+    Ok(MemoryBreakdownDto {
+        total_bytes: snapshot.total_bytes,
+        components: snapshot.breakdown(),
+    })
+}
+
+// =============================================================================
+// Support Report Commands
+// =============================================================================
+
+use telemetry::report::{LogEntry, PerformanceSummary, ReportConfig, SupportReportGenerator};
+use telemetry::InspectorNode;
+
+/// Build a document structure tree for a support report
+///
+/// Only counts and lengths are recorded; the generator strips anything else.
+fn document_structure(tree: &doc_model::DocumentTree) -> InspectorNode {
+    let mut root = InspectorNode::new("document", "document");
+    let mut paragraphs = 0;
+    for para in tree.paragraphs() {
+        let mut node = InspectorNode::new("paragraph", "paragraph");
+        for run in para.children().iter().filter_map(|&id| tree.get_run(id)) {
+            node.add_child(
+                InspectorNode::new("run", "run").with_property("length", run.text.chars().count().to_string()),
+            );
+        }
+        node.set_property("run_count", node.children.len().to_string());
+        root.add_child(node);
+        paragraphs += 1;
+    }
+    root.set_property("paragraph_count", paragraphs.to_string());
+    root
+}
+
+/// Flatten a settings object into dotted keys
+fn flatten_settings(value: &serde_json::Value, prefix: &str, out: &mut HashMap<String, String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten_settings(value, &path, out);
+            }
+        }
+        other => {
+            out.insert(prefix.to_string(), other.to_string());
+        }
+    }
+}
+
+/// Write a support report zip to `path` for the user to attach to a bug report
+///
+/// The report holds system info, settings, a performance summary, the given
+/// log entries and, when `doc_id` is set, that document's import fidelity
+/// warnings and structure without its text. It is anonymized before writing.
+#[tauri::command]
+pub fn generate_support_report(
+    path: String,
+    doc_id: Option<String>,
+    description: Option<String>,
+    logs: Vec<LogEntry>,
+    app_state: State<'_, AppState>,
+    settings_state: State<'_, SettingsState>,
+    perf_state: State<'_, PerfMetricsState>,
+) -> Result<String, String> {
+    let mut generator = SupportReportGenerator::new(ReportConfig::default());
+    for entry in logs {
+        generator.add_log(entry);
+    }
+
+    let mut report_state = telemetry::AppState::new(env!("CARGO_PKG_VERSION"), "");
+    {
+        let manager = settings_state.manager.lock().map_err(|e| e.to_string())?;
+        let settings = serde_json::to_value(SettingsDto::from(manager.get())).map_err(|e| e.to_string())?;
+        let mut flat = HashMap::new();
+        flatten_settings(&settings, "", &mut flat);
+        generator.set_settings(flat);
+    }
+
+    {
+        let metrics = perf_state.metrics.lock().map_err(|e| e.to_string())?;
+        let summary = metrics.summary();
+        generator.set_performance(PerformanceSummary {
+            sample_count: summary.total_inputs + summary.total_layouts + summary.total_renders,
+            avg_input_latency_ms: summary.input_latency_stats.mean_ms,
+            avg_layout_time_ms: summary.layout_stats.mean_ms,
+            avg_render_time_ms: summary.render_stats.mean_ms,
+            p95_frame_time_ms: summary.render_stats.p95_ms,
+            budget_violations: metrics.check_budget().len(),
+            ..Default::default()
+        });
+    }
+
+    {
+        let documents = app_state.documents.lock().map_err(|e| e.to_string())?;
+        report_state.open_documents = documents.len() as u32;
+        if let Some(doc_id) = doc_id {
+            let doc_state = documents
+                .get(&doc_id)
+                .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+            report_state.has_unsaved_changes = doc_state.dirty;
+            generator.set_fidelity_warnings(
+                docx::embedded_object_warnings(&doc_state.tree)
+                    .into_iter()
+                    .map(|w| format!("[{:?}] {}: {}", w.severity, w.code, w.message))
+                    .collect(),
+            );
+            generator.set_document_structure(&document_structure(&doc_state.tree));
+        }
+    }
+    generator.set_app_state(report_state);
+
+    let mut report = generator.generate_report();
+    if let Some(description) = description {
+        report = report.with_description(description);
+    }
+    let report = generator.anonymize(report);
+    generator
+        .export_to_zip(&report, &path)
+        .map(|path| path.to_string_lossy().into_owned())
+        .map_err(|e| format!("Failed to write support report: {}", e))
+}
+
+// =============================================================================
+// Crash Recovery Commands
+// =============================================================================
+
+use crate::state::CrashReporterState;
+
+/// Crash recovery prompt DTO for frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryPromptDto {
+    /// Identifier of the crash
+    pub crash_id: String,
+    /// When the crash happened (Unix timestamp in ms)
+    pub timestamp: i64,
+    /// Kind of crash
+    pub crash_type: String,
+    /// Error message
+    pub message: String,
+    /// Recovery files the user can restore
+    pub recovery_files: Vec<String>,
+}
+
+/// Get the prompt for the latest crash the user hasn't dismissed
+#[tauri::command]
+pub fn get_recovery_prompt(
+    state: State<'_, CrashReporterState>,
+) -> Result<Option<RecoveryPromptDto>, String> {
+    let reporter = state.reporter.lock().map_err(|e| e.to_string())?;
+    let prompt = reporter.recovery_prompt().map_err(|e| e.to_string())?;
+    Ok(prompt.map(|prompt| RecoveryPromptDto {
+        crash_id: prompt.report.crash_id,
+        timestamp: prompt.report.timestamp.timestamp_millis(),
+        crash_type: prompt.report.crash_type.as_str().to_string(),
+        message: prompt.report.message,
+        recovery_files: prompt
+            .recovery_files
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect(),
+    }))
+}
+
+/// Dismiss the crash recovery prompt
+#[tauri::command]
+pub fn dismiss_recovery_prompt(
+    state: State<'_, CrashReporterState>,
+) -> Result<(), String> {
+    let reporter = state.reporter.lock().map_err(|e| e.to_string())?;
+    reporter.dismiss_recovery().map_err(|e| e.to_string())
+}
+
 // =============================================================================
 // Field Commands
 // =============================================================================
@@ -3341,6 +3957,11 @@ fn parse_number_format(format: &str) -> NumberFormat {
         "ordinal" => NumberFormat::Ordinal,
         "cardinaltext" => NumberFormat::CardinalText,
         "ordinaltext" => NumberFormat::OrdinalText,
+        "hebrew" => NumberFormat::Hebrew,
+        "arabicindic" => NumberFormat::ArabicIndic,
+        "chinesecounting" => NumberFormat::ChineseCounting,
+        "japanesecounting" => NumberFormat::JapaneseCounting,
+        "thai" => NumberFormat::Thai,
         _ => NumberFormat::Arabic,
     }
 }
@@ -3355,6 +3976,11 @@ fn number_format_to_string(format: &NumberFormat) -> String {
         NumberFormat::Ordinal => "ordinal".to_string(),
         NumberFormat::CardinalText => "cardinalText".to_string(),
         NumberFormat::OrdinalText => "ordinalText".to_string(),
+        NumberFormat::Hebrew => "hebrew".to_string(),
+        NumberFormat::ArabicIndic => "arabicIndic".to_string(),
+        NumberFormat::ChineseCounting => "chineseCounting".to_string(),
+        NumberFormat::JapaneseCounting => "japaneseCounting".to_string(),
+        NumberFormat::Thai => "thai".to_string(),
     }
 }
 
@@ -3791,7 +4417,10 @@ pub fn get_section_properties(
     Ok(edit_engine::SectionProperties::default_properties())
 }
 
-/// Set section page setup (margins, size, orientation)
+/// Set section page setup (margins, size, orientation, binding gutter)
+///
+/// `gutter_position` is "left" or "top"; with `mirror_margins` the left
+/// margin and gutter sit on the inside of facing pages.
 #[tauri::command]
 pub fn set_section_page_setup(
     doc_id: String,
@@ -3801,9 +4430,12 @@ pub fn set_section_page_setup(
     margin_bottom: Option<f32>,
     margin_left: Option<f32>,
     margin_right: Option<f32>,
+    gutter: Option<f32>,
+    gutter_position: Option<String>,
+    mirror_margins: Option<bool>,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    use doc_model::{Orientation, PageSizePreset};
+    use doc_model::{GutterPosition, Orientation, PageSizePreset};
 
     let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
     let doc_state = documents
@@ -3825,6 +4457,12 @@ pub fn set_section_page_setup(
         _ => None,
     });
 
+    let gutter_position = gutter_position.and_then(|s| match s.as_str() {
+        "Left" | "left" => Some(GutterPosition::Left),
+        "Top" | "top" => Some(GutterPosition::Top),
+        _ => None,
+    });
+
     let cmd = edit_engine::SetPageSetup {
         section_id: None,
         page_size_preset,
@@ -3837,9 +4475,115 @@ pub fn set_section_page_setup(
         margin_right,
         margin_header: None,
         margin_footer: None,
-        gutter: None,
-        gutter_position: None,
+        gutter,
+        gutter_position,
+        mirror_margins,
+    };
+
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+
+    doc_state.tree = result.tree;
+    doc_state.selection = result.selection;
+
+    Ok(())
+}
+
+/// Set or remove the current section's watermark
+///
+/// Pass `text` for a text watermark or `resource_id` (with the image's
+/// natural size) for a picture watermark; pass neither to remove it.
+#[tauri::command]
+pub fn set_section_watermark(
+    doc_id: String,
+    text: Option<String>,
+    diagonal: Option<bool>,
+    color: Option<String>,
+    resource_id: Option<String>,
+    image_width: Option<f32>,
+    image_height: Option<f32>,
+    washout: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    use doc_model::{PictureWatermark, ResourceId, TextWatermark, Watermark, WatermarkLayout};
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let watermark = match (text, resource_id) {
+        (Some(text), _) => {
+            let layout = if diagonal.unwrap_or(true) {
+                WatermarkLayout::Diagonal
+            } else {
+                WatermarkLayout::Horizontal
+            };
+            let mut watermark = TextWatermark::new(text).with_layout(layout);
+            if let Some(color) = color {
+                watermark = watermark.with_color(color);
+            }
+            Some(Watermark::Text(watermark))
+        }
+        (None, Some(resource_id)) => Some(Watermark::Picture(
+            PictureWatermark::new(
+                ResourceId::new(resource_id),
+                image_width.unwrap_or(0.0),
+                image_height.unwrap_or(0.0),
+            )
+            .with_washout(washout.unwrap_or(true)),
+        )),
+        (None, None) => None,
+    };
+
+    let cmd = edit_engine::SetWatermark {
+        section_id: None,
+        watermark,
+    };
+
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+
+    doc_state.tree = result.tree;
+    doc_state.selection = result.selection;
+
+    Ok(())
+}
+
+/// Set the current section's page number format and restart
+///
+/// `format` is "arabic", "lowerRoman", "upperRoman", "lowerLetter",
+/// "upperLetter", "hebrew", "arabicIndic", "chineseCounting",
+/// "japaneseCounting", or "thai"; without `start_at` numbering continues
+/// from the previous section.
+#[tauri::command]
+pub fn set_section_page_numbering(
+    doc_id: String,
+    format: String,
+    start_at: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    use doc_model::{PageNumberFormat, PageNumbering};
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let format = match format.as_str() {
+        "arabic" => PageNumberFormat::Arabic,
+        "lowerRoman" => PageNumberFormat::LowercaseRoman,
+        "upperRoman" => PageNumberFormat::UppercaseRoman,
+        "lowerLetter" => PageNumberFormat::LowercaseLetter,
+        "upperLetter" => PageNumberFormat::UppercaseLetter,
+        "hebrew" => PageNumberFormat::Hebrew,
+        "arabicIndic" => PageNumberFormat::ArabicIndic,
+        "chineseCounting" => PageNumberFormat::ChineseCounting,
+        "japaneseCounting" => PageNumberFormat::JapaneseCounting,
+        "thai" => PageNumberFormat::Thai,
+        _ => return Err(format!("Unknown page number format: {}", format)),
     };
+    let cmd = edit_engine::SetPageNumbering::new(PageNumbering { format, start_at });
 
     let result = cmd.apply(&doc_state.tree, &doc_state.selection)
         .map_err(|e| e.to_string())?;
@@ -4309,37 +5053,1175 @@ pub fn import_rtf_bytes(data: Vec<u8>) -> Result<RtfImportResultDto, String> {
     let result = store::import_rtf_bytes(&data)
         .map_err(|e| format!("Failed to import RTF: {}", e))?;
 
-    let tree = result.tree;
+    let tree = result.tree;
+
+    let paragraph_count = tree.paragraphs().count();
+    let text_content = tree.text_content();
+    let word_count = text_content.split_whitespace().count();
+    let title = tree.document.metadata.title.clone();
+
+    let warnings: Vec<ImportWarningDto> = result.warnings.iter().map(|w| {
+        ImportWarningDto {
+            kind: format!("{:?}", w.kind),
+            message: w.message.clone(),
+        }
+    }).collect();
+
+    Ok(RtfImportResultDto {
+        doc_id: tree.root_id().to_string(),
+        title,
+        paragraph_count,
+        word_count,
+        warnings,
+    })
+}
+
+/// Export a document to RTF bytes (for clipboard operations)
+#[tauri::command]
+pub fn export_rtf_bytes(_doc_id: String) -> Result<Vec<u8>, String> {
+    // TODO: Get actual document from document state
+    let tree = doc_model::DocumentTree::new();
+
+    store::export_rtf_bytes(&tree)
+        .map_err(|e| format!("Failed to export RTF: {}", e))
+}
+
+// =============================================================================
+// Clipboard Image Commands
+// =============================================================================
+
+/// Paste a clipboard bitmap (PNG, BMP or DIB bytes) as an inline picture
+///
+/// The image is stored as PNG and sized from its recorded resolution, no
+/// wider than `max_width` points (defaults to the text width of a Letter
+/// page with 1" margins).
+#[tauri::command]
+pub fn paste_image_from_clipboard(
+    doc_id: String,
+    data: Vec<u8>,
+    max_width: Option<f32>,
+    state: State<'_, AppState>,
+) -> Result<store::PastedImage, String> {
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let pasted = store::paste_image(&doc_state.images, &data, max_width.unwrap_or(468.0))
+        .map_err(|e| format!("Failed to paste image: {}", e))?;
+
+    let result = pasted
+        .insert_command()
+        .apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+
+    doc_state.tree = result.tree;
+    doc_state.selection = result.selection;
+    doc_state.dirty = true;
+
+    Ok(pasted)
+}
+
+/// Compress the document's pictures and report the space saved
+///
+/// Defaults to print quality (220 ppi) when no options are given.
+#[tauri::command]
+pub fn compress_pictures(
+    doc_id: String,
+    options: Option<store::ImageOptimizationOptions>,
+    state: State<'_, AppState>,
+) -> Result<store::ImageOptimizationReport, String> {
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let options = options.unwrap_or_else(store::ImageOptimizationOptions::print);
+    let report = doc_state.images.optimize_images(&doc_state.tree, &options);
+    if report.compressed_count() > 0 {
+        doc_state.dirty = true;
+    }
+
+    Ok(report)
+}
+
+// =============================================================================
+// Ink Commands
+// =============================================================================
+
+/// Anchor pen strokes captured by the frontend to a paragraph
+///
+/// Stroke coordinates are in points relative to the paragraph's top-left.
+/// Returns the new ink node's ID.
+#[tauri::command]
+pub fn insert_ink(
+    doc_id: String,
+    paragraph_id: String,
+    strokes: Vec<doc_model::InkStroke>,
+    alt_text: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let paragraph_id = doc_model::NodeId::from_string(&paragraph_id)
+        .ok_or_else(|| format!("Invalid paragraph ID: {}", paragraph_id))?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let existing: Vec<_> = doc_state.tree.inks().map(|ink| ink.id()).collect();
+    let cmd = edit_engine::InsertInk { paragraph_id, strokes, alt_text };
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+    let ink_id = result
+        .tree
+        .inks()
+        .map(|ink| ink.id())
+        .find(|id| !existing.contains(id))
+        .ok_or("Ink was not inserted")?;
+
+    doc_state.tree = result.tree;
+    doc_state.dirty = true;
+
+    Ok(ink_id.to_string())
+}
+
+/// Draw, erase, or move strokes of an ink node
+#[tauri::command]
+pub fn edit_ink(
+    doc_id: String,
+    ink_id: String,
+    edit: edit_engine::InkEdit,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let ink_id = doc_model::NodeId::from_string(&ink_id)
+        .ok_or_else(|| format!("Invalid ink ID: {}", ink_id))?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let cmd = edit_engine::EditInk::new(ink_id, edit);
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+
+    doc_state.tree = result.tree;
+    doc_state.dirty = true;
+
+    Ok(())
+}
+
+/// Delete an ink node
+#[tauri::command]
+pub fn delete_ink(
+    doc_id: String,
+    ink_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let ink_id = doc_model::NodeId::from_string(&ink_id)
+        .ok_or_else(|| format!("Invalid ink ID: {}", ink_id))?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let result = edit_engine::DeleteInk::new(ink_id)
+        .apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+
+    doc_state.tree = result.tree;
+    doc_state.dirty = true;
+
+    Ok(())
+}
+
+// =============================================================================
+// Drawing Canvas Commands
+// =============================================================================
+
+/// Group shapes onto a new drawing canvas anchored to a paragraph
+///
+/// With no frame, the canvas just covers the shapes. Returns the canvas ID.
+#[tauri::command]
+pub fn create_drawing_canvas(
+    doc_id: String,
+    paragraph_id: String,
+    shape_ids: Vec<String>,
+    frame: Option<doc_model::Rect>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let paragraph_id = doc_model::NodeId::from_string(&paragraph_id)
+        .ok_or_else(|| format!("Invalid paragraph ID: {}", paragraph_id))?;
+    let shape_ids = shape_ids
+        .iter()
+        .map(|id| doc_model::NodeId::from_string(id).ok_or_else(|| format!("Invalid shape ID: {}", id)))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let cmd = edit_engine::CreateCanvas {
+        frame,
+        ..edit_engine::CreateCanvas::around_shapes(paragraph_id, shape_ids)
+    };
+    let existing: Vec<_> = doc_state.tree.canvases().map(|canvas| canvas.id()).collect();
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+    let canvas_id = result
+        .tree
+        .canvases()
+        .map(|canvas| canvas.id())
+        .find(|id| !existing.contains(id))
+        .ok_or("Canvas was not created")?;
+
+    doc_state.tree = result.tree;
+    doc_state.dirty = true;
+
+    Ok(canvas_id.to_string())
+}
+
+/// Remove a drawing canvas, leaving its shapes floating in the paragraph
+#[tauri::command]
+pub fn ungroup_drawing_canvas(
+    doc_id: String,
+    canvas_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let canvas_id = doc_model::NodeId::from_string(&canvas_id)
+        .ok_or_else(|| format!("Invalid canvas ID: {}", canvas_id))?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let result = edit_engine::DissolveCanvas::new(canvas_id)
+        .apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+
+    doc_state.tree = result.tree;
+    doc_state.dirty = true;
+
+    Ok(())
+}
+
+/// Snap a dragged shape and get the smart guides to draw
+///
+/// The context holds the page (or canvas) bounds, margins, and the other
+/// shapes, in the same coordinates as `bounds`.
+#[tauri::command]
+pub fn snap_shape_drag(
+    context: layout_engine::SnapContext,
+    bounds: layout_engine::Rect,
+    settings: Option<layout_engine::SnapSettings>,
+) -> layout_engine::SnapResult {
+    layout_engine::AlignmentGuideEngine::new(settings.unwrap_or_default()).snap(&context, bounds)
+}
+
+// =============================================================================
+// Hyperlink Management Commands
+// =============================================================================
+
+/// Turn a URL or email address just typed into a hyperlink
+///
+/// Called when a space, tab, or punctuation follows a word and
+/// auto-formatting of hyperlinks is enabled. Returns the linked text, or
+/// None if the word isn't a link.
+#[tauri::command]
+pub fn auto_format_hyperlink(
+    doc_id: String,
+    paragraph_id: String,
+    offset: usize,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, String> {
+    let paragraph_id = doc_model::NodeId::from_string(&paragraph_id)
+        .ok_or_else(|| format!("Invalid paragraph ID: {}", paragraph_id))?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let cmd = edit_engine::AutoFormatHyperlink::new(paragraph_id, offset);
+    let Some(link) = cmd.detect(&doc_state.tree) else {
+        return Ok(None);
+    };
+    let result = cmd
+        .apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+
+    doc_state.tree = result.tree;
+    doc_state.dirty = true;
+
+    Ok(Some(link.text))
+}
+
+/// List every hyperlink in a document with whether it can be followed
+#[tauri::command]
+pub fn audit_hyperlinks(
+    doc_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<edit_engine::LinkAuditEntry>, String> {
+    let documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    Ok(edit_engine::audit_hyperlinks(&doc_state.tree))
+}
+
+/// Set the base that relative hyperlinks are resolved against
+#[tauri::command]
+pub fn set_hyperlink_base(
+    doc_id: String,
+    base: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    doc_state.tree.document.metadata.hyperlink_base = base
+        .map(|base| base.trim().to_string())
+        .filter(|base| !base.is_empty());
+    doc_state.dirty = true;
+
+    Ok(())
+}
+
+/// Move every link in one domain to another
+///
+/// Returns the number of links changed.
+#[tauri::command]
+pub fn replace_hyperlink_domain(
+    doc_id: String,
+    from: String,
+    to: String,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let cmd = edit_engine::ReplaceHyperlinkDomain::new(from, to);
+    let count = cmd.changes(&doc_state.tree).len();
+    if count == 0 {
+        return Ok(0);
+    }
+    let result = cmd
+        .apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+
+    doc_state.tree = result.tree;
+    doc_state.dirty = true;
+
+    Ok(count)
+}
+
+// =============================================================================
+// Document Statistics Commands
+// =============================================================================
+
+/// Get word count and readability statistics
+///
+/// With `selection_only`, counts only the current selection (the whole
+/// document if nothing is selected).
+#[tauri::command]
+pub fn get_document_statistics(
+    doc_id: String,
+    selection_only: bool,
+    state: State<'_, AppState>,
+) -> Result<layout_engine::DocumentStatistics, String> {
+    let (tree, selection) = {
+        let documents = state.documents.lock().map_err(|e| e.to_string())?;
+        let doc_state = documents
+            .get(&doc_id)
+            .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+        (doc_state.tree.clone(), doc_state.selection)
+    };
+
+    let config = match tree.sections.first() {
+        Some(section) => layout_engine::PageConfig::default().with_section_binding(&section.page_setup),
+        None => layout_engine::PageConfig::default(),
+    };
+    let layout = layout_engine::Paginator::new(config)
+        .layout(&tree)
+        .map_err(|e| e.to_string())?;
+
+    Ok(if selection_only {
+        layout_engine::DocumentStatistics::for_selection(&tree, &layout, &selection)
+    } else {
+        layout_engine::DocumentStatistics::compute(&tree, &layout)
+    })
+}
+
+// =============================================================================
+// Proofing Language Commands
+// =============================================================================
+
+/// Set the proofing language of the selection
+///
+/// `language` is a BCP 47 tag such as "fr-FR"; None clears it so the
+/// language is detected. `no_proof` marks the text as "do not check spelling".
+#[tauri::command]
+pub fn set_proofing_language(
+    doc_id: String,
+    language: Option<String>,
+    no_proof: bool,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let cmd = edit_engine::SetProofingLanguage { language, no_proof };
+    let result = cmd
+        .apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+
+    doc_state.tree = result.tree;
+    doc_state.dirty = true;
+
+    Ok(())
+}
+
+/// Detect the language of a piece of text
+#[tauri::command]
+pub fn detect_language(text: String) -> Option<text_engine::DetectedLanguage> {
+    text_engine::LanguageDetector::new().detect(&text)
+}
+
+/// Spellcheck a document, using each run's proofing language
+///
+/// Text marked "do not check spelling" is skipped. Text without a proofing
+/// language is checked in its detected language, or `default_language`.
+#[tauri::command]
+pub fn spellcheck_document(
+    doc_id: String,
+    default_language: String,
+    state: State<'_, AppState>,
+) -> Result<edit_engine::SpellcheckResults, String> {
+    let documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+    let tree = &doc_state.tree;
+
+    let default = text_engine::Language::from_code(&default_language).unwrap_or_default();
+    let detector = text_engine::LanguageDetector::new();
+    let checker = text_engine::DictionarySpellChecker::new();
+    let rules = text_engine::IgnoreRules::default();
+
+    let mut errors = Vec::new();
+    let mut words_checked = 0;
+    for para in tree.paragraphs() {
+        let Some(job) = edit_engine::SpellcheckJob::capture(tree, para.id(), 0) else {
+            continue;
+        };
+        let update = job.run(&checker, &detector, default, &rules);
+        words_checked += update.words_checked;
+        errors.extend(update.errors);
+    }
+
+    Ok(edit_engine::SpellcheckResults::from_errors(errors, words_checked))
+}
+
+// =============================================================================
+// Translation Commands
+// =============================================================================
+
+/// Translate a document (or its selection) into a new document
+///
+/// Uses the offline dictionary translator with the given glossary
+/// (source word to translation). Returns the new document's ID.
+#[tauri::command]
+pub fn translate_document(
+    doc_id: String,
+    from: Option<String>,
+    to: String,
+    selection_only: bool,
+    glossary: HashMap<String, String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let source_language = from.as_deref().unwrap_or("und");
+    let mut translator = edit_engine::DictionaryTranslator::new();
+    for (word, translation) in &glossary {
+        translator.add_entry(source_language, &to, word, translation);
+    }
+
+    let tree = if selection_only {
+        edit_engine::translate_selection(
+            &doc_state.tree,
+            &doc_state.selection,
+            &translator,
+            from.as_deref(),
+            &to,
+        )
+    } else {
+        edit_engine::translate_document(&doc_state.tree, &translator, from.as_deref(), &to)
+    }
+    .map_err(|e| e.to_string())?;
+
+    let new_id = Uuid::new_v4().to_string();
+    let mut translated = crate::state::DocumentState::new(new_id.clone());
+    translated.tree = tree;
+    translated.dirty = true;
+    documents.insert(new_id.clone(), translated);
+
+    Ok(new_id)
+}
+
+// =============================================================================
+// Read Aloud Commands
+// =============================================================================
+
+/// Event emitted with a `ReadAloudEvent` while reading aloud
+const READ_ALOUD_EVENT: &str = "read-aloud-progress";
+
+/// Interval between read aloud progress checks
+const READ_ALOUD_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Start reading the document aloud from the cursor
+///
+/// Stops any reading in progress. Progress is emitted as
+/// `read-aloud-progress` events: one per sentence, one per word (estimated
+/// from the speaking rate) and a final `finished` event. Returns the
+/// sentences that will be read.
+#[tauri::command]
+pub fn start_read_aloud(
+    doc_id: String,
+    settings: store::speech::SpeechSettings,
+    app: AppHandle,
+    state: State<'_, AppState>,
+    read_aloud: State<'_, crate::state::ReadAloudState>,
+) -> Result<Vec<edit_engine::SpeechSegment>, String> {
+    let segments = {
+        let documents = state.documents.lock().map_err(|e| e.to_string())?;
+        let doc_state = documents
+            .get(&doc_id)
+            .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+        edit_engine::read_aloud_segments(&doc_state.tree, &doc_state.selection.focus)
+            .map_err(|e| e.to_string())?
+    };
+
+    let stop = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let mut active = read_aloud.active.lock().map_err(|e| e.to_string())?;
+    if let Some(previous) = active.replace(stop.clone()) {
+        previous.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    read_aloud_thread(app, segments.clone(), settings, stop);
+    Ok(segments)
+}
+
+/// Stop reading aloud
+#[tauri::command]
+pub fn stop_read_aloud(read_aloud: State<'_, crate::state::ReadAloudState>) -> Result<(), String> {
+    let mut active = read_aloud.active.lock().map_err(|e| e.to_string())?;
+    if let Some(stop) = active.take() {
+        stop.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+/// Speak sentences in turn, emitting progress until done or stopped
+fn read_aloud_thread(
+    app: AppHandle,
+    segments: Vec<edit_engine::SpeechSegment>,
+    settings: store::speech::SpeechSettings,
+    stop: std::sync::Arc<std::sync::atomic::AtomicBool>,
+) {
+    use edit_engine::ReadAloudEvent;
+    use std::sync::atomic::Ordering;
+    use tauri::Emitter;
+
+    std::thread::spawn(move || {
+        let emit = |event: ReadAloudEvent| {
+            if let Err(e) = app.emit(READ_ALOUD_EVENT, &event) {
+                tracing::warn!("Failed to emit read aloud progress: {}", e);
+            }
+        };
+        let backend = store::speech::default_backend();
+        let mut completed = true;
+
+        'sentences: for segment in &segments {
+            if stop.load(Ordering::SeqCst) {
+                completed = false;
+                break;
+            }
+            emit(ReadAloudEvent::sentence(segment));
+            if let Err(e) = backend.speak(&segment.text, &settings) {
+                tracing::warn!("Read aloud failed: {}", e);
+                completed = false;
+                break;
+            }
+
+            let started = std::time::Instant::now();
+            let mut current_word = None;
+            while backend.is_speaking() {
+                if stop.load(Ordering::SeqCst) {
+                    let _ = backend.stop();
+                    completed = false;
+                    break 'sentences;
+                }
+                let elapsed = started.elapsed().as_secs_f64();
+                if let Some(word) = segment.estimated_word(elapsed, settings.words_per_minute) {
+                    if current_word != Some(word.start) {
+                        current_word = Some(word.start);
+                        emit(ReadAloudEvent::word(segment, word));
+                    }
+                }
+                std::thread::sleep(READ_ALOUD_POLL_INTERVAL);
+            }
+        }
+
+        emit(ReadAloudEvent::Finished { completed });
+    });
+}
+
+// =============================================================================
+// Thesaurus Commands
+// =============================================================================
+
+/// Look up synonyms and antonyms of a word
+///
+/// `language` is a language code such as "en-US"; unknown codes use US English.
+#[tauri::command]
+pub fn get_synonyms(word: String, language: String) -> Option<text_engine::ThesaurusEntry> {
+    let language = text_engine::Language::from_code(&language).unwrap_or_default();
+    text_engine::Thesaurus::with_builtin().lookup(&word, language).cloned()
+}
+
+/// Get the word at the cursor for thesaurus lookup
+#[tauri::command]
+pub fn get_word_at_cursor(
+    doc_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<edit_engine::WordAtCursor>, String> {
+    let documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+    edit_engine::WordAtCursor::find(&doc_state.tree, &doc_state.selection.focus).map_err(|e| e.to_string())
+}
+
+/// Replace the word at the cursor with a synonym
+#[tauri::command]
+pub fn replace_with_synonym(
+    doc_id: String,
+    synonym: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let cmd = edit_engine::ReplaceWithSynonym::new(synonym);
+    let result = cmd
+        .apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+
+    doc_state.tree = result.tree;
+    doc_state.selection = result.selection;
+    doc_state.dirty = true;
+
+    Ok(())
+}
+
+// =============================================================================
+// AutoSummarize Commands
+// =============================================================================
+
+/// Summarize a document
+#[tauri::command]
+pub fn summarize_document(
+    doc_id: String,
+    options: edit_engine::SummaryOptions,
+    state: State<'_, AppState>,
+) -> Result<edit_engine::Summary, String> {
+    let documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+    Ok(edit_engine::Summary::compute(&doc_state.tree, &options))
+}
+
+/// Insert a summary section at the top of the document
+#[tauri::command]
+pub fn insert_executive_summary(
+    doc_id: String,
+    options: edit_engine::SummaryOptions,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let cmd = edit_engine::InsertExecutiveSummary::new(options);
+    let result = cmd
+        .apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+
+    doc_state.tree = result.tree;
+    doc_state.dirty = true;
+
+    Ok(())
+}
+
+// =============================================================================
+// Local Version History Commands
+// =============================================================================
+
+/// Open the version manager for a document, with its saved history
+async fn local_version_manager(
+    app: &tauri::AppHandle,
+    doc_id: &str,
+) -> Result<store::VersionManager, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    let config = store::VersionConfig::default().with_versions_dir(app_data_dir.join("versions"));
+    let mut manager = store::VersionManager::new(doc_id, config);
+    manager.load_history().await.map_err(|e| e.to_string())?;
+    Ok(manager)
+}
+
+/// Snapshot a document, as a named checkpoint if a name is given
+///
+/// Without a name this is the on-save snapshot, which returns None when
+/// nothing changed since the latest version.
+#[tauri::command]
+pub async fn create_local_version(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    doc_id: String,
+    name: Option<String>,
+) -> Result<Option<store::DocumentVersion>, String> {
+    let tree = {
+        let documents = state.documents.lock().map_err(|e| e.to_string())?;
+        let doc_state = documents
+            .get(&doc_id)
+            .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+        doc_state.tree.clone()
+    };
+
+    let mut manager = local_version_manager(&app, &doc_id).await?;
+    match name {
+        Some(name) => manager.create_checkpoint(&tree, name).await.map(Some),
+        None => manager.version_on_save(&tree).await,
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// List the versions of a document, newest first
+#[tauri::command]
+pub async fn list_local_versions(
+    app: tauri::AppHandle,
+    doc_id: String,
+) -> Result<Vec<store::DocumentVersion>, String> {
+    let manager = local_version_manager(&app, &doc_id).await?;
+    Ok(manager.history().versions.clone())
+}
+
+/// Compare two versions of a document
+#[tauri::command]
+pub async fn diff_local_versions(
+    app: tauri::AppHandle,
+    doc_id: String,
+    old_version: u64,
+    new_version: u64,
+) -> Result<store::VersionDiff, String> {
+    let manager = local_version_manager(&app, &doc_id).await?;
+    manager
+        .compare_versions(old_version, new_version)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Replace a document's content with an earlier version
+#[tauri::command]
+pub async fn restore_local_version(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    doc_id: String,
+    version: u64,
+) -> Result<(), String> {
+    let mut manager = local_version_manager(&app, &doc_id).await?;
+    let tree = manager
+        .restore_version(version)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+    doc_state.tree = tree;
+    doc_state.selection = doc_model::Selection::default();
+    doc_state.dirty = true;
+
+    Ok(())
+}
+
+/// Open a version of a document as a new document
+///
+/// Returns the new document ID.
+#[tauri::command]
+pub async fn branch_local_version(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    doc_id: String,
+    version: u64,
+) -> Result<String, String> {
+    let manager = local_version_manager(&app, &doc_id).await?;
+    let new_id = Uuid::new_v4().to_string();
+    let (tree, _) = manager
+        .branch_version(version, new_id.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let mut branched = crate::state::DocumentState::new(new_id.clone());
+    branched.tree = tree;
+    branched.dirty = true;
+    documents.insert(new_id.clone(), branched);
+
+    Ok(new_id)
+}
+
+// =============================================================================
+// Open and Repair Commands
+// =============================================================================
+
+/// Result of opening a damaged document
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenAndRepairResult {
+    /// ID of the recovered document
+    pub doc_id: String,
+    /// What was damaged, lost and repaired
+    pub report: store::RepairReport,
+}
+
+/// Open a damaged document, recovering whatever is readable
+#[tauri::command]
+pub async fn open_and_repair(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<OpenAndRepairResult, String> {
+    let (tree, report) = store::open_and_repair(&path)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let doc_id = Uuid::new_v4().to_string();
+    let mut doc_state = crate::state::DocumentState::new(doc_id.clone());
+    doc_state.tree = tree;
+    doc_state.dirty = !report.is_clean();
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    documents.insert(doc_id.clone(), doc_state);
+
+    Ok(OpenAndRepairResult { doc_id, report })
+}
+
+// =============================================================================
+// Recent Documents Commands
+// =============================================================================
+
+use crate::state::RecentFilesState;
+use store::RecentDocument;
+
+/// Get the recent documents for the start screen
+///
+/// With `validate`, documents are first checked for existence, and missing
+/// ones are marked so the start screen can grey them out.
+#[tauri::command]
+pub fn get_recent_documents(
+    validate: bool,
+    recent: State<'_, RecentFilesState>,
+) -> Result<Vec<RecentDocument>, String> {
+    let mut manager = recent.manager.lock().map_err(|e| e.to_string())?;
+    if validate {
+        manager.validate();
+    }
+    Ok(manager.list().to_vec())
+}
+
+/// Record that a document was opened
+///
+/// If the document is open, a thumbnail of its first page is stored too.
+#[tauri::command]
+pub fn add_recent_document(
+    path: String,
+    doc_id: Option<String>,
+    state: State<'_, AppState>,
+    settings: State<'_, SettingsState>,
+    fonts: State<'_, FontManagerState>,
+    recent: State<'_, RecentFilesState>,
+) -> Result<RecentDocument, String> {
+    let limit = settings
+        .manager
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get()
+        .general
+        .recent_files_count as usize;
+
+    // Render outside the recent documents lock
+    let thumbnail = match doc_id {
+        Some(doc_id) => {
+            let pages = render_pages_for(&doc_id, &state, layout_engine::PageConfig::default())?;
+            let (_, images) = document_with_images(&doc_id, &state)?;
+            pages.first().and_then(|page| {
+                page_rasterizer(std::slice::from_ref(page), store::thumbnail_options(), &fonts, images)
+                    .render_png(page)
+                    .map_err(|e| tracing::warn!("Failed to render thumbnail: {}", e))
+                    .ok()
+            })
+        }
+        None => None,
+    };
+
+    let mut manager = recent.manager.lock().map_err(|e| e.to_string())?;
+    manager.set_limit(limit);
+    manager.add(&path);
+    if let Some(png) = thumbnail {
+        manager.set_thumbnail(&path, &png).map_err(|e| e.to_string())?;
+    }
+    manager.save_sync().map_err(|e| e.to_string())?;
+    manager
+        .get(&path)
+        .cloned()
+        .ok_or_else(|| format!("Recent document not found: {}", path))
+}
+
+/// Pin or unpin a recent document
+#[tauri::command]
+pub fn pin_recent_document(
+    path: String,
+    pinned: bool,
+    recent: State<'_, RecentFilesState>,
+) -> Result<bool, String> {
+    let mut manager = recent.manager.lock().map_err(|e| e.to_string())?;
+    let found = manager.set_pinned(&path, pinned);
+    manager.save_sync().map_err(|e| e.to_string())?;
+    Ok(found)
+}
+
+/// Remove a document from the recent documents
+#[tauri::command]
+pub fn remove_recent_document(
+    path: String,
+    recent: State<'_, RecentFilesState>,
+) -> Result<bool, String> {
+    let mut manager = recent.manager.lock().map_err(|e| e.to_string())?;
+    let found = manager.remove(&path);
+    manager.save_sync().map_err(|e| e.to_string())?;
+    Ok(found)
+}
+
+/// Remove every unpinned recent document
+#[tauri::command]
+pub fn clear_recent_documents(recent: State<'_, RecentFilesState>) -> Result<(), String> {
+    let mut manager = recent.manager.lock().map_err(|e| e.to_string())?;
+    manager.clear_unpinned();
+    manager.save_sync().map_err(|e| e.to_string())
+}
+
+/// Get the thumbnail of a recent document as a PNG data URL
+#[tauri::command]
+pub fn get_recent_thumbnail(
+    path: String,
+    recent: State<'_, RecentFilesState>,
+) -> Result<Option<String>, String> {
+    let manager = recent.manager.lock().map_err(|e| e.to_string())?;
+    Ok(manager.thumbnail(&path).map(|png| store::raster::png_data_url(&png)))
+}
+
+// =============================================================================
+// Document Locking Commands
+// =============================================================================
+
+use crate::state::FileLockState;
+use store::{DocumentLock, LockAttempt, LockOwner, LockStatus};
+
+/// Result of locking a document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockDocumentResult {
+    /// Whether this instance now holds the lock
+    pub acquired: bool,
+    /// Who holds the lock otherwise, if known
+    pub owner: Option<LockOwner>,
+}
+
+/// Lock an opened document
+///
+/// If another instance holds the lock, the frontend offers to open the
+/// document read-only, notify when it is released, or open a copy.
+#[tauri::command]
+pub fn lock_document(
+    doc_id: String,
+    path: String,
+    locks: State<'_, FileLockState>,
+) -> Result<LockDocumentResult, String> {
+    let mut locks = locks.locks.lock().map_err(|e| e.to_string())?;
+    if let Some(lock) = locks.get(&doc_id) {
+        if lock.document_path() == std::path::Path::new(&path) {
+            return Ok(LockDocumentResult { acquired: true, owner: Some(lock.owner().clone()) });
+        }
+    }
+    match DocumentLock::acquire(&path).map_err(|e| e.to_string())? {
+        LockAttempt::Acquired(lock) => {
+            let owner = lock.owner().clone();
+            locks.insert(doc_id, lock);
+            Ok(LockDocumentResult { acquired: true, owner: Some(owner) })
+        }
+        LockAttempt::Locked(owner) => Ok(LockDocumentResult { acquired: false, owner }),
+    }
+}
+
+/// Lock a document, breaking the lock another instance holds
+#[tauri::command]
+pub fn force_lock_document(
+    doc_id: String,
+    path: String,
+    locks: State<'_, FileLockState>,
+) -> Result<LockOwner, String> {
+    let mut locks = locks.locks.lock().map_err(|e| e.to_string())?;
+    locks.remove(&doc_id);
+    let lock = DocumentLock::force_acquire(&path).map_err(|e| e.to_string())?;
+    let owner = lock.owner().clone();
+    locks.insert(doc_id, lock);
+    Ok(owner)
+}
+
+/// Release the lock on a document, e.g. when it is closed
+#[tauri::command]
+pub fn unlock_document(doc_id: String, locks: State<'_, FileLockState>) -> Result<bool, String> {
+    let lock = locks.locks.lock().map_err(|e| e.to_string())?.remove(&doc_id);
+    match lock {
+        Some(lock) => lock.release().map(|_| true).map_err(|e| e.to_string()),
+        None => Ok(false),
+    }
+}
+
+/// Check whether a document is locked
+#[tauri::command]
+pub fn get_document_lock_status(path: String) -> LockStatus {
+    store::lock_status(&path)
+}
+
+/// Copy a locked document next to it so the copy can be edited
+///
+/// Returns the path of the copy.
+#[tauri::command]
+pub fn open_document_copy(path: String) -> Result<String, String> {
+    let source = std::path::Path::new(&path);
+    let dest_dir = source.parent().unwrap_or(std::path::Path::new("."));
+    store::copy_for_editing(source, dest_dir)
+        .map(|copy| copy.display().to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Emit "document-lock-released" with the path once another instance
+/// releases its lock on a document
+#[tauri::command]
+pub async fn watch_document_lock(app: AppHandle, path: String) -> Result<(), String> {
+    use tauri::Emitter;
+
+    tokio::spawn(async move {
+        while matches!(store::lock_status(&path), LockStatus::Locked { .. }) {
+            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        }
+        let _ = app.emit("document-lock-released", &path);
+    });
+    Ok(())
+}
+
+// =============================================================================
+// Digital Signature Commands
+// =============================================================================
+
+use store::signatures::{SignatureReport, SigningIdentity};
+
+/// Sign a saved native or DOCX document with a PEM certificate and key
+///
+/// Returns the signatures of the document after signing.
+#[tauri::command]
+pub fn sign_document(
+    path: String,
+    certificate_path: String,
+    private_key_path: String,
+) -> Result<Vec<SignatureReport>, String> {
+    let identity = SigningIdentity::from_pem_files(&certificate_path, &private_key_path)
+        .map_err(|e| e.to_string())?;
+    store::signatures::sign_document(&path, &identity).map_err(|e| e.to_string())?;
+    store::signatures::verify_signatures(&path).map_err(|e| e.to_string())
+}
+
+/// Verify the digital signatures of a native or DOCX document
+#[tauri::command]
+pub fn verify_signatures(path: String) -> Result<Vec<SignatureReport>, String> {
+    store::signatures::verify_signatures(&path).map_err(|e| e.to_string())
+}
 
-    let paragraph_count = tree.paragraphs().count();
-    let text_content = tree.text_content();
-    let word_count = text_content.split_whitespace().count();
-    let title = tree.document.metadata.title.clone();
+// =============================================================================
+// Document Inspector Commands
+// =============================================================================
 
-    let warnings: Vec<ImportWarningDto> = result.warnings.iter().map(|w| {
-        ImportWarningDto {
-            kind: format!("{:?}", w.kind),
-            message: w.message.clone(),
-        }
-    }).collect();
+use crate::state::RevisionStateWrapper;
+use store::{InspectionCategory, InspectionReport};
 
-    Ok(RtfImportResultDto {
-        doc_id: tree.root_id().to_string(),
-        title,
-        paragraph_count,
-        word_count,
-        warnings,
-    })
+/// Find comments, revision authors, hidden text, and other information in a document
+#[tauri::command]
+pub fn inspect_document(
+    doc_id: String,
+    state: State<'_, AppState>,
+    revisions: State<'_, RevisionStateWrapper>,
+) -> Result<InspectionReport, String> {
+    let documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+    let revision_state = revisions.state.lock().map_err(|e| e.to_string())?;
+    Ok(store::inspect_document(&doc_state.tree, Some(&revision_state)))
 }
 
-/// Export a document to RTF bytes (for clipboard operations)
+/// Remove the chosen categories of information from a document
 #[tauri::command]
-pub fn export_rtf_bytes(_doc_id: String) -> Result<Vec<u8>, String> {
-    // TODO: Get actual document from document state
-    let tree = doc_model::DocumentTree::new();
-
-    store::export_rtf_bytes(&tree)
-        .map_err(|e| format!("Failed to export RTF: {}", e))
+pub fn remove_document_info(
+    doc_id: String,
+    categories: Vec<InspectionCategory>,
+    state: State<'_, AppState>,
+    revisions: State<'_, RevisionStateWrapper>,
+) -> Result<InspectionReport, String> {
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+    let mut revision_state = revisions.state.lock().map_err(|e| e.to_string())?;
+    let report = store::remove_document_info(&mut doc_state.tree, Some(&mut revision_state), &categories);
+    if !report.removed.is_empty() {
+        doc_state.dirty = true;
+    }
+    Ok(report)
 }
 
 // =============================================================================
@@ -4602,6 +6484,7 @@ fn text_direction_to_string(direction: TableTextDirection) -> String {
         TableTextDirection::Rtl => "rtl".to_string(),
         TableTextDirection::TbLr => "tb-lr".to_string(),
         TableTextDirection::TbRl => "tb-rl".to_string(),
+        TableTextDirection::BtLr => "bt-lr".to_string(),
     }
 }
 
@@ -4729,22 +6612,56 @@ pub fn table_split_cell_vertical(
 #[tauri::command]
 pub fn table_set_header_row(
     _doc_id: String,
-    _table_id: String,
-    _row_index: usize,
-    _is_header: bool,
+    table_id: String,
+    row_index: usize,
+    is_header: bool,
+    state: State<'_, crate::state::AppState>,
 ) -> Result<DocumentChange, String> {
-    Ok(DocumentChange::default())
+    let id = doc_model::NodeId::from_string(&table_id)
+        .ok_or_else(|| format!("Invalid table ID: {}", table_id))?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&_doc_id)
+        .ok_or_else(|| format!("Document not found: {}", _doc_id))?;
+
+    let cmd = edit_engine::SetHeaderRow::new(id, row_index, is_header);
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+    doc_state.tree = result.tree;
+
+    Ok(DocumentChange {
+        changed_nodes: vec![table_id],
+        ..Default::default()
+    })
 }
 
 /// Set row break behavior
 #[tauri::command]
 pub fn table_set_row_can_split(
     _doc_id: String,
-    _table_id: String,
-    _row_index: usize,
-    _can_split: bool,
+    table_id: String,
+    row_index: usize,
+    can_split: bool,
+    state: State<'_, crate::state::AppState>,
 ) -> Result<DocumentChange, String> {
-    Ok(DocumentChange::default())
+    let id = doc_model::NodeId::from_string(&table_id)
+        .ok_or_else(|| format!("Invalid table ID: {}", table_id))?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&_doc_id)
+        .ok_or_else(|| format!("Document not found: {}", _doc_id))?;
+
+    let cmd = edit_engine::SetRowCanSplit::new(id, row_index, can_split);
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+    doc_state.tree = result.tree;
+
+    Ok(DocumentChange {
+        changed_nodes: vec![table_id],
+        ..Default::default()
+    })
 }
 
 /// Set cell vertical alignment
@@ -4769,7 +6686,7 @@ pub fn table_set_cell_text_direction(
     direction: String,
 ) -> Result<DocumentChange, String> {
     match direction.as_str() {
-        "ltr" | "rtl" | "tb-lr" | "tb-rl" => {}
+        "ltr" | "rtl" | "tb-lr" | "tb-rl" | "bt-lr" => {}
         _ => return Err(format!("Invalid text direction: {}", direction)),
     }
     Ok(DocumentChange::default())
@@ -4792,14 +6709,251 @@ pub fn table_set_cell_padding(
 #[tauri::command]
 pub fn table_set_auto_fit(
     _doc_id: String,
-    _table_id: String,
+    table_id: String,
     mode: String,
+    state: State<'_, crate::state::AppState>,
 ) -> Result<DocumentChange, String> {
-    match mode.as_str() {
-        "autoFitContent" | "autoFitWindow" | "fixedWidth" => {}
+    let mode = match mode.as_str() {
+        "autoFitContent" => TableAutoFitMode::AutoFitContent,
+        "autoFitWindow" => TableAutoFitMode::AutoFitWindow,
+        "fixedWidth" => TableAutoFitMode::FixedWidth,
         _ => return Err(format!("Invalid auto-fit mode: {}", mode)),
+    };
+    let id = doc_model::NodeId::from_string(&table_id)
+        .ok_or_else(|| format!("Invalid table ID: {}", table_id))?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&_doc_id)
+        .ok_or_else(|| format!("Document not found: {}", _doc_id))?;
+
+    // Recompute the column widths for the new mode against the section's text width
+    let mut config = layout_engine::TableLayoutConfig {
+        auto_fit_mode: Some(mode),
+        ..Default::default()
+    };
+    if let Some(section) = doc_state.tree.sections.iter().find(|section| section.children().contains(&id)) {
+        config.available_width = section.page_setup.content_width();
     }
-    Ok(DocumentChange::default())
+    let widths = layout_engine::TableLayoutEngine::new()
+        .compute_column_widths(&doc_state.tree, id, &config)
+        .map_err(|e| e.to_string())?;
+
+    let cmd = edit_engine::SetTableAutoFit::new(id, mode).with_column_widths(widths);
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+    doc_state.tree = result.tree;
+
+    Ok(DocumentChange {
+        changed_nodes: vec![table_id],
+        ..Default::default()
+    })
+}
+
+/// Table look options (which table style regions apply)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableLookDto {
+    pub first_row: bool,
+    pub last_row: bool,
+    pub first_column: bool,
+    pub last_column: bool,
+    pub banded_rows: bool,
+    pub banded_columns: bool,
+}
+
+impl From<TableLookDto> for doc_model::TableLook {
+    fn from(dto: TableLookDto) -> Self {
+        Self {
+            first_row: dto.first_row,
+            last_row: dto.last_row,
+            first_column: dto.first_column,
+            last_column: dto.last_column,
+            banded_rows: dto.banded_rows,
+            banded_columns: dto.banded_columns,
+        }
+    }
+}
+
+/// Get the table styles for the table style gallery
+#[tauri::command]
+pub fn get_table_styles(
+    _doc_id: String,
+    state: State<'_, crate::state::AppState>,
+) -> Result<Vec<StyleDto>, String> {
+    let documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get(&_doc_id)
+        .ok_or_else(|| format!("Document not found: {}", _doc_id))?;
+
+    Ok(doc_state
+        .tree
+        .style_registry()
+        .table_gallery_styles()
+        .iter()
+        .map(|s| StyleDto::from(*s))
+        .collect())
+}
+
+/// Apply a table style (None clears it) with the given look options
+#[tauri::command]
+pub fn table_set_style(
+    _doc_id: String,
+    table_id: String,
+    style_id: Option<String>,
+    look: Option<TableLookDto>,
+    state: State<'_, crate::state::AppState>,
+) -> Result<DocumentChange, String> {
+    let id = doc_model::NodeId::from_string(&table_id)
+        .ok_or_else(|| format!("Invalid table ID: {}", table_id))?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&_doc_id)
+        .ok_or_else(|| format!("Document not found: {}", _doc_id))?;
+
+    // Keep the table's current look unless a new one is given
+    let look = match look {
+        Some(dto) => dto.into(),
+        None => doc_state
+            .tree
+            .get_table(id)
+            .map(|t| t.properties.look)
+            .ok_or_else(|| format!("Table not found: {}", table_id))?,
+    };
+
+    let cmd = edit_engine::SetTableStyle::new(id, style_id.map(StyleId::new), look);
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+    doc_state.tree = result.tree;
+
+    Ok(DocumentChange {
+        changed_nodes: vec![table_id],
+        ..Default::default()
+    })
+}
+
+/// A sort level for table sorting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableSortKeyDto {
+    pub column: usize,
+    /// "text", "number" or "date"
+    pub key_type: String,
+    pub descending: bool,
+}
+
+fn parse_table_delimiter(delimiter: &str) -> Result<edit_engine::TableDelimiter, String> {
+    let mut chars = delimiter.chars();
+    match (delimiter, chars.next(), chars.next()) {
+        ("tab", _, _) => Ok(edit_engine::TableDelimiter::Tab),
+        ("comma", _, _) => Ok(edit_engine::TableDelimiter::Comma),
+        ("paragraph", _, _) => Ok(edit_engine::TableDelimiter::Paragraph),
+        (_, Some(c), None) => Ok(edit_engine::TableDelimiter::Other(c)),
+        _ => Err(format!("Invalid delimiter: {}", delimiter)),
+    }
+}
+
+/// Sort table rows by one or more columns
+#[tauri::command]
+pub fn table_sort(
+    _doc_id: String,
+    table_id: String,
+    keys: Vec<TableSortKeyDto>,
+    has_header: bool,
+    state: State<'_, crate::state::AppState>,
+) -> Result<DocumentChange, String> {
+    let id = doc_model::NodeId::from_string(&table_id)
+        .ok_or_else(|| format!("Invalid table ID: {}", table_id))?;
+    let keys = keys
+        .iter()
+        .map(|key| {
+            let key_type = match key.key_type.as_str() {
+                "text" => edit_engine::SortKeyType::Text,
+                "number" => edit_engine::SortKeyType::Number,
+                "date" => edit_engine::SortKeyType::Date,
+                other => return Err(format!("Invalid sort key type: {}", other)),
+            };
+            let sort_key = edit_engine::SortKey::new(key.column, key_type);
+            Ok(if key.descending { sort_key.descending() } else { sort_key })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&_doc_id)
+        .ok_or_else(|| format!("Document not found: {}", _doc_id))?;
+
+    let cmd = edit_engine::SortTable::new(id, keys).with_header(has_header);
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+    doc_state.tree = result.tree;
+
+    Ok(DocumentChange {
+        changed_nodes: vec![table_id],
+        ..Default::default()
+    })
+}
+
+/// Convert paragraphs to a table, splitting cells at a delimiter
+/// ("tab", "comma", "paragraph" or a single character)
+#[tauri::command]
+pub fn table_convert_text_to_table(
+    _doc_id: String,
+    paragraph_ids: Vec<String>,
+    delimiter: String,
+    state: State<'_, crate::state::AppState>,
+) -> Result<DocumentChange, String> {
+    let ids = paragraph_ids
+        .iter()
+        .map(|id| doc_model::NodeId::from_string(id).ok_or_else(|| format!("Invalid paragraph ID: {}", id)))
+        .collect::<Result<Vec<_>, String>>()?;
+    let delimiter = parse_table_delimiter(&delimiter)?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&_doc_id)
+        .ok_or_else(|| format!("Document not found: {}", _doc_id))?;
+
+    let cmd = edit_engine::ConvertTextToTable::new(ids, delimiter);
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+    doc_state.tree = result.tree;
+    doc_state.selection = result.selection;
+
+    Ok(DocumentChange {
+        changed_nodes: paragraph_ids,
+        ..Default::default()
+    })
+}
+
+/// Convert a table to paragraphs, separating cells with a delimiter
+#[tauri::command]
+pub fn table_convert_to_text(
+    _doc_id: String,
+    table_id: String,
+    delimiter: String,
+    state: State<'_, crate::state::AppState>,
+) -> Result<DocumentChange, String> {
+    let id = doc_model::NodeId::from_string(&table_id)
+        .ok_or_else(|| format!("Invalid table ID: {}", table_id))?;
+    let delimiter = parse_table_delimiter(&delimiter)?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&_doc_id)
+        .ok_or_else(|| format!("Document not found: {}", _doc_id))?;
+
+    let cmd = edit_engine::ConvertTableToText::new(id, delimiter);
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+    doc_state.tree = result.tree;
+    doc_state.selection = result.selection;
+
+    Ok(DocumentChange {
+        changed_nodes: vec![table_id],
+        ..Default::default()
+    })
 }
 
 /// Insert a nested table inside a cell
@@ -5047,6 +7201,11 @@ pub enum NumberFormatDto {
     Ordinal,
     CardinalText,
     OrdinalText,
+    Hebrew,
+    ArabicIndic,
+    ChineseCounting,
+    JapaneseCounting,
+    Thai,
 }
 
 impl From<NumberFormatDto> for NumberFormat {
@@ -5060,6 +7219,11 @@ impl From<NumberFormatDto> for NumberFormat {
             NumberFormatDto::Ordinal => NumberFormat::Ordinal,
             NumberFormatDto::CardinalText => NumberFormat::CardinalText,
             NumberFormatDto::OrdinalText => NumberFormat::OrdinalText,
+            NumberFormatDto::Hebrew => NumberFormat::Hebrew,
+            NumberFormatDto::ArabicIndic => NumberFormat::ArabicIndic,
+            NumberFormatDto::ChineseCounting => NumberFormat::ChineseCounting,
+            NumberFormatDto::JapaneseCounting => NumberFormat::JapaneseCounting,
+            NumberFormatDto::Thai => NumberFormat::Thai,
         }
     }
 }
@@ -5075,6 +7239,11 @@ impl From<NumberFormat> for NumberFormatDto {
             NumberFormat::Ordinal => NumberFormatDto::Ordinal,
             NumberFormat::CardinalText => NumberFormatDto::CardinalText,
             NumberFormat::OrdinalText => NumberFormatDto::OrdinalText,
+            NumberFormat::Hebrew => NumberFormatDto::Hebrew,
+            NumberFormat::ArabicIndic => NumberFormatDto::ArabicIndic,
+            NumberFormat::ChineseCounting => NumberFormatDto::ChineseCounting,
+            NumberFormat::JapaneseCounting => NumberFormatDto::JapaneseCounting,
+            NumberFormat::Thai => NumberFormatDto::Thai,
         }
     }
 }
@@ -5679,6 +7848,35 @@ pub fn set_paragraph_keep_rules(
     })
 }
 
+/// Set the base direction of the selected paragraphs
+///
+/// `direction` is "ltr", "rtl", or "auto" (from the first strong
+/// character). Alignment, indents and list markers mirror for RTL.
+#[tauri::command]
+pub fn set_paragraph_direction(
+    doc_id: String,
+    direction: String,
+    state: State<'_, crate::state::AppState>,
+) -> Result<(), String> {
+    let direction = parse_direction(&direction)
+        .ok_or_else(|| format!("Invalid text direction: {}", direction))?;
+
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let cmd = edit_engine::SetParagraphDirection::new(direction);
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+
+    doc_state.tree = result.tree;
+    doc_state.selection = result.selection;
+    doc_state.dirty = true;
+
+    Ok(())
+}
+
 /// Get paragraph keep rules for a specific paragraph
 ///
 /// Retrieves the current pagination/keep rules from the paragraph's
@@ -5763,12 +7961,16 @@ pub fn get_widow_orphan_control(
 ///
 /// Updates the line numbering settings for a section. Line numbering displays
 /// numbers in the margin for each line of text, useful for legal documents,
-/// code listings, or reference materials.
+/// code listings, or reference materials. Paragraphs listed in
+/// `suppressed_paragraphs` are left unnumbered and uncounted; those in
+/// `unsuppressed_paragraphs` are numbered again.
 #[tauri::command]
 pub fn set_line_numbering(
     _doc_id: String,
     section_id: String,
     settings: LineNumberingDto,
+    suppressed_paragraphs: Option<Vec<String>>,
+    unsuppressed_paragraphs: Option<Vec<String>>,
     state: State<'_, crate::state::AppState>,
 ) -> Result<DocumentChange, String> {
     let id = doc_model::NodeId::from_string(&section_id)
@@ -5782,15 +7984,40 @@ pub fn set_line_numbering(
         .get_mut(&_doc_id)
         .ok_or_else(|| format!("Document not found: {}", _doc_id))?;
 
-    // Get the section and update its line numbering settings
-    let section = doc_state.sections.get_mut(&id)
-        .ok_or_else(|| format!("Section not found: {}", section_id))?;
+    // Update the section in the tree, which layout reads, and in the
+    // section storage
+    let tree_section = doc_state.tree.sections.iter_mut().find(|section| section.id() == id);
+    let found_in_tree = tree_section.is_some();
+    if let Some(section) = tree_section {
+        section.page_setup.line_numbering = line_numbering.clone();
+    }
+    match doc_state.sections.get_mut(&id) {
+        Some(section) => section.page_setup.line_numbering = line_numbering,
+        None if found_in_tree => {}
+        None => return Err(format!("Section not found: {}", section_id)),
+    }
 
-    section.page_setup.line_numbering = line_numbering;
+    // Apply paragraph-level suppression
+    let mut changed_nodes = vec![section_id];
+    let suppression = suppressed_paragraphs
+        .unwrap_or_default()
+        .into_iter()
+        .map(|para_id| (para_id, true))
+        .chain(unsuppressed_paragraphs.unwrap_or_default().into_iter().map(|para_id| (para_id, false)));
+    for (para_id, suppress) in suppression {
+        let node_id = doc_model::NodeId::from_string(&para_id)
+            .ok_or_else(|| format!("Invalid paragraph ID: {}", para_id))?;
+        let para = doc_state
+            .tree
+            .get_paragraph_mut(node_id)
+            .ok_or_else(|| format!("Paragraph not found: {}", para_id))?;
+        para.direct_formatting.suppress_line_numbers = suppress.then_some(true);
+        changed_nodes.push(para_id);
+    }
     doc_state.dirty = true;
 
     Ok(DocumentChange {
-        changed_nodes: vec![section_id],
+        changed_nodes,
         dirty_pages: vec![], // Pages in this section need re-layout
         selection: None,
     })
@@ -6258,7 +8485,8 @@ pub struct NoteInfoDto {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FootnotePropertiesDto {
-    /// Numbering scheme: "arabic", "lowerRoman", "upperRoman", "lowerLetter", "upperLetter", "symbols"
+    /// Numbering scheme: "arabic", "lowerRoman", "upperRoman", "lowerLetter", "upperLetter", "symbols",
+    /// "hebrew", "arabicIndic", "chineseCounting", "japaneseCounting", "thai"
     pub numbering: String,
     /// Restart mode: "continuous", "perSection", "perPage"
     pub restart: String,
@@ -6286,6 +8514,11 @@ impl From<&FootnoteProperties> for FootnotePropertiesDto {
                 NumberingScheme::LowerLetter => "lowerLetter",
                 NumberingScheme::UpperLetter => "upperLetter",
                 NumberingScheme::Symbols => "symbols",
+                NumberingScheme::Hebrew => "hebrew",
+                NumberingScheme::ArabicIndic => "arabicIndic",
+                NumberingScheme::ChineseCounting => "chineseCounting",
+                NumberingScheme::JapaneseCounting => "japaneseCounting",
+                NumberingScheme::Thai => "thai",
             }
             .to_string(),
             restart: match props.restart {
@@ -6317,6 +8550,11 @@ impl From<FootnotePropertiesDto> for FootnoteProperties {
                 "lowerletter" => NumberingScheme::LowerLetter,
                 "upperletter" => NumberingScheme::UpperLetter,
                 "symbols" => NumberingScheme::Symbols,
+                "hebrew" => NumberingScheme::Hebrew,
+                "arabicindic" => NumberingScheme::ArabicIndic,
+                "chinesecounting" => NumberingScheme::ChineseCounting,
+                "japanesecounting" => NumberingScheme::JapaneseCounting,
+                "thai" => NumberingScheme::Thai,
                 _ => NumberingScheme::Arabic,
             },
             restart: match dto.restart.to_lowercase().as_str() {
@@ -6341,7 +8579,8 @@ impl From<FootnotePropertiesDto> for FootnoteProperties {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EndnotePropertiesDto {
-    /// Numbering scheme: "arabic", "lowerRoman", "upperRoman", "lowerLetter", "upperLetter", "symbols"
+    /// Numbering scheme: "arabic", "lowerRoman", "upperRoman", "lowerLetter", "upperLetter", "symbols",
+    /// "hebrew", "arabicIndic", "chineseCounting", "japaneseCounting", "thai"
     pub numbering: String,
     /// Restart mode: "continuous", "perSection"
     pub restart: String,
@@ -6349,6 +8588,15 @@ pub struct EndnotePropertiesDto {
     pub start_at: u32,
     /// Position: "endOfSection" or "endOfDocument"
     pub position: String,
+    /// Whether to draw a separator line above the endnotes
+    #[serde(default)]
+    pub show_separator: Option<bool>,
+    /// Separator length as a fraction of the column width
+    #[serde(default)]
+    pub separator_length: Option<f32>,
+    /// Separator weight in points
+    #[serde(default)]
+    pub separator_weight: Option<f32>,
 }
 
 impl From<&EndnoteProperties> for EndnotePropertiesDto {
@@ -6361,6 +8609,11 @@ impl From<&EndnoteProperties> for EndnotePropertiesDto {
                 NumberingScheme::LowerLetter => "lowerLetter",
                 NumberingScheme::UpperLetter => "upperLetter",
                 NumberingScheme::Symbols => "symbols",
+                NumberingScheme::Hebrew => "hebrew",
+                NumberingScheme::ArabicIndic => "arabicIndic",
+                NumberingScheme::ChineseCounting => "chineseCounting",
+                NumberingScheme::JapaneseCounting => "japaneseCounting",
+                NumberingScheme::Thai => "thai",
             }
             .to_string(),
             restart: match props.restart {
@@ -6375,12 +8628,16 @@ impl From<&EndnoteProperties> for EndnotePropertiesDto {
                 EndnotePosition::EndOfDocument => "endOfDocument",
             }
             .to_string(),
+            show_separator: Some(props.show_separator),
+            separator_length: Some(props.separator_length),
+            separator_weight: Some(props.separator_weight),
         }
     }
 }
 
 impl From<EndnotePropertiesDto> for EndnoteProperties {
     fn from(dto: EndnotePropertiesDto) -> Self {
+        let defaults = EndnoteProperties::default();
         Self {
             numbering: match dto.numbering.to_lowercase().as_str() {
                 "lowerroman" => NumberingScheme::LowerRoman,
@@ -6388,6 +8645,11 @@ impl From<EndnotePropertiesDto> for EndnoteProperties {
                 "lowerletter" => NumberingScheme::LowerLetter,
                 "upperletter" => NumberingScheme::UpperLetter,
                 "symbols" => NumberingScheme::Symbols,
+                "hebrew" => NumberingScheme::Hebrew,
+                "arabicindic" => NumberingScheme::ArabicIndic,
+                "chinesecounting" => NumberingScheme::ChineseCounting,
+                "japanesecounting" => NumberingScheme::JapaneseCounting,
+                "thai" => NumberingScheme::Thai,
                 _ => NumberingScheme::Arabic,
             },
             restart: match dto.restart.to_lowercase().as_str() {
@@ -6399,6 +8661,9 @@ impl From<EndnotePropertiesDto> for EndnoteProperties {
                 "endofsection" => EndnotePosition::EndOfSection,
                 _ => EndnotePosition::EndOfDocument,
             },
+            show_separator: dto.show_separator.unwrap_or(defaults.show_separator),
+            separator_length: dto.separator_length.unwrap_or(defaults.separator_length),
+            separator_weight: dto.separator_weight.unwrap_or(defaults.separator_weight),
         }
     }
 }
@@ -6531,11 +8796,16 @@ pub fn set_footnote_properties(
 pub fn get_endnote_properties(
     _doc_id: String,
     section_id: Option<String>,
+    state: State<'_, crate::state::AppState>,
 ) -> Result<EndnotePropertiesDto, String> {
-    let _section_id = section_id.as_ref().and_then(|s| doc_model::NodeId::from_string(s));
+    let section_id = section_id.as_ref().and_then(|s| doc_model::NodeId::from_string(s));
 
-    // TODO: Get from document state
-    Ok(EndnotePropertiesDto::from(&EndnoteProperties::default()))
+    let documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get(&_doc_id)
+        .ok_or_else(|| format!("Document not found: {}", _doc_id))?;
+
+    Ok(EndnotePropertiesDto::from(doc_state.tree.get_endnote_properties(section_id)))
 }
 
 /// Set endnote properties for a section (or document default)
@@ -6544,12 +8814,25 @@ pub fn set_endnote_properties(
     _doc_id: String,
     section_id: Option<String>,
     properties: EndnotePropertiesDto,
+    state: State<'_, crate::state::AppState>,
 ) -> Result<DocumentChange, String> {
-    let _section_id = section_id.as_ref().and_then(|s| doc_model::NodeId::from_string(s));
-    let _props = EndnoteProperties::from(properties);
+    let id = section_id.as_ref().and_then(|s| doc_model::NodeId::from_string(s));
+    let props = EndnoteProperties::from(properties);
 
-    // TODO: Implement with edit_engine SetEndnoteProperties command
-    Ok(DocumentChange::default())
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&_doc_id)
+        .ok_or_else(|| format!("Document not found: {}", _doc_id))?;
+
+    // Renumbers the endnotes; layout moves them to their new place
+    doc_state.tree.set_endnote_properties(id, props);
+    doc_state.dirty = true;
+
+    Ok(DocumentChange {
+        changed_nodes: section_id.into_iter().collect(),
+        dirty_pages: vec![],
+        selection: None,
+    })
 }
 
 /// Navigate to a note (from reference to note content)
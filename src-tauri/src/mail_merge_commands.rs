@@ -2,7 +2,8 @@
 
 use crate::state::MailMergeState;
 use mail_merge::{
-    ColumnDef, CsvConfig, CsvParser, DataSource, DataType, JsonConfig, JsonParser, Value,
+    ColumnDef, CsvConfig, CsvParser, DataSource, DataType, JsonConfig, JsonParser,
+    ProviderDescriptor, Value,
 };
 use serde::{Deserialize, Serialize};
 use tauri::State;
@@ -76,7 +77,7 @@ pub struct RecordDto {
 pub struct DataSourceSummaryDto {
     /// Data source ID
     pub id: String,
-    /// Source type (csv, json, inline)
+    /// Source type (csv, json, xlsx, inline, plugin)
     pub source_type: String,
     /// Number of columns
     pub column_count: usize,
@@ -93,6 +94,7 @@ impl From<&DataSource> for DataSourceSummaryDto {
             mail_merge::DataSourceType::Json { .. } => "json",
             mail_merge::DataSourceType::Xlsx { .. } => "xlsx",
             mail_merge::DataSourceType::Inline { .. } => "inline",
+            mail_merge::DataSourceType::Plugin { .. } => "plugin",
         };
         Self {
             id: ds.id.clone(),
@@ -104,6 +106,28 @@ impl From<&DataSource> for DataSourceSummaryDto {
     }
 }
 
+/// Data source provider DTO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataSourceProviderDto {
+    /// Provider ID
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// Plugin that contributed the provider
+    pub plugin_id: String,
+}
+
+impl From<&ProviderDescriptor> for DataSourceProviderDto {
+    fn from(descriptor: &ProviderDescriptor) -> Self {
+        Self {
+            id: descriptor.id.clone(),
+            name: descriptor.name.clone(),
+            plugin_id: descriptor.plugin_id.clone(),
+        }
+    }
+}
+
 /// Preview data DTO with columns and sample records
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -218,6 +242,53 @@ pub fn load_json_from_string(
     Ok(summary)
 }
 
+/// List the data source providers contributed by plugins
+#[tauri::command]
+pub fn list_data_source_providers(
+    state: State<'_, MailMergeState>,
+) -> Result<Vec<DataSourceProviderDto>, String> {
+    let providers = state.providers.lock().map_err(|e| e.to_string())?;
+    Ok(providers.providers().map(DataSourceProviderDto::from).collect())
+}
+
+/// List the columns a plugin data source provider offers
+#[tauri::command]
+pub fn get_provider_columns(
+    provider: String,
+    config: Option<serde_json::Value>,
+    state: State<'_, MailMergeState>,
+) -> Result<Vec<ColumnDefDto>, String> {
+    let providers = state.providers.lock().map_err(|e| e.to_string())?;
+    let columns = providers
+        .columns(&provider, &config.unwrap_or_default())
+        .map_err(|e| e.to_string())?;
+
+    Ok(columns.iter().map(ColumnDefDto::from).collect())
+}
+
+/// Load a data source from a plugin provider
+#[tauri::command]
+pub fn load_plugin_data_source(
+    id: String,
+    provider: String,
+    config: Option<serde_json::Value>,
+    state: State<'_, MailMergeState>,
+) -> Result<DataSourceSummaryDto, String> {
+    let data_source = {
+        let providers = state.providers.lock().map_err(|e| e.to_string())?;
+        providers
+            .load(&id, &provider, config.unwrap_or_default())
+            .map_err(|e| e.to_string())?
+    };
+
+    let summary = DataSourceSummaryDto::from(&data_source);
+
+    let mut sources = state.sources.lock().map_err(|e| e.to_string())?;
+    sources.insert(id, data_source);
+
+    Ok(summary)
+}
+
 /// Get columns for a data source
 #[tauri::command]
 pub fn get_data_source_columns(
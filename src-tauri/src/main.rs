@@ -4,6 +4,7 @@
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod building_block_commands;
 mod collab_commands;
 mod commands;
 mod mail_merge_commands;
@@ -13,7 +14,7 @@ mod template_commands;
 mod view_mode_commands;
 
 use commands::DocumentStore;
-use state::{CollaborationState, FontManagerState, MailMergeState, PerfMetricsState, RevisionStateWrapper, SettingsState, TemplateState, ViewModeState};
+use state::{BuildingBlockState, CollaborationState, CrashReporterState, FileLockState, FontManagerState, MailMergeState, PerfMetricsState, ProfilerState, ReadAloudState, RecentFilesState, RevisionStateWrapper, SettingsState, TemplateState, ViewModeState};
 use tauri::Manager;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -40,8 +41,27 @@ fn main() {
 
             // Initialize settings state
             let settings_state = SettingsState::new(app_data_dir.clone());
+            if let Ok(mut manager) = settings_state.manager.lock() {
+                // Let open settings panels update live
+                let handle = app.handle().clone();
+                manager.subscribe(Box::new(move |change| {
+                    use tauri::Emitter;
+                    let _ = handle.emit("settings-changed", change);
+                }));
+            }
+            let recent_limit = settings_state
+                .manager
+                .lock()
+                .map(|m| m.get().general.recent_files_count as usize)
+                .unwrap_or(store::DEFAULT_RECENT_LIMIT);
             app.manage(settings_state);
 
+            // Initialize recent documents state
+            app.manage(RecentFilesState::new(app_data_dir.clone(), recent_limit));
+
+            // Initialize the user's building block library
+            app.manage(BuildingBlockState::new(app_data_dir.clone()));
+
             // Initialize font manager state
             tracing::info!("Initializing font manager...");
             let font_manager_state = FontManagerState::new();
@@ -52,12 +72,18 @@ fn main() {
             tracing::info!("Initializing performance metrics...");
             let perf_metrics_state = PerfMetricsState::new();
             app.manage(perf_metrics_state);
+            app.manage(ProfilerState::default());
             tracing::info!("Performance metrics initialized");
 
+            // Write a crash report when the app panics
+            let crash_reporter_state = CrashReporterState::new(app_data_dir.clone());
+            telemetry::CrashReporter::install_panic_hook(crash_reporter_state.reporter.clone());
+            app.manage(crash_reporter_state);
+
             // Initialize template state
             tracing::info!("Initializing template manager...");
             let templates_dir = app_data_dir.join("templates");
-            let template_state = TemplateState::new(templates_dir);
+            let template_state = TemplateState::new(templates_dir, app_data_dir.join("template_gallery"));
             app.manage(template_state);
             tracing::info!("Template manager initialized");
 
@@ -85,6 +111,12 @@ fn main() {
             app.manage(mail_merge_state);
             tracing::info!("Mail merge state initialized");
 
+            // Initialize read aloud state
+            app.manage(ReadAloudState::default());
+
+            // Initialize document lock state
+            app.manage(FileLockState::default());
+
             // Initialize document store
             tracing::info!("Initializing document store...");
             let doc_store = DocumentStore::default();
@@ -101,6 +133,7 @@ fn main() {
             commands::load_document,
             commands::undo,
             commands::redo,
+            commands::get_undo_history,
             commands::get_settings,
             commands::update_settings,
             commands::reset_settings,
@@ -112,14 +145,18 @@ fn main() {
             commands::apply_character_style,
             commands::apply_direct_formatting,
             commands::clear_direct_formatting,
+            commands::set_paragraph_direction,
             commands::get_style_inspector,
             commands::create_style,
             commands::modify_style,
+            commands::get_table_styles,
+            commands::table_set_style,
             // Font substitution commands
             commands::get_font_substitutions,
             commands::clear_font_substitutions,
             commands::get_available_fonts,
             commands::is_font_available,
+            commands::render_equation,
             commands::resolve_font,
             // Bookmark commands
             commands::insert_bookmark,
@@ -147,15 +184,21 @@ fn main() {
             commands::export_pdf_a_bytes,
             commands::validate_pdf_a_compliance,
             commands::get_pdfa_conformance_levels,
+            commands::check_accessibility,
+            commands::set_alt_text,
             // DOCX import/export commands
             commands::open_docx,
             commands::save_as_docx,
+            commands::get_docx_import_warnings,
+            commands::get_embedded_object_preview,
             commands::get_supported_formats,
             commands::get_import_formats,
             commands::get_export_formats,
             // Print commands
             commands::get_print_capabilities,
             commands::print_document,
+            commands::get_print_job_status,
+            commands::cancel_print_job,
             commands::render_preview_page,
             commands::render_preview_thumbnails,
             // Performance telemetry commands
@@ -170,11 +213,28 @@ fn main() {
             commands::record_layout_timing,
             commands::record_render_timing,
             commands::record_input_latency,
+            commands::get_frame_timeline,
+            commands::start_profiling,
+            commands::stop_profiling,
+            commands::save_profile_trace,
+            commands::get_memory_breakdown,
+            commands::generate_support_report,
+            commands::get_recovery_prompt,
+            commands::dismiss_recovery_prompt,
             // Template commands
             template_commands::list_templates,
+            template_commands::refresh_template_gallery,
+            template_commands::download_gallery_template,
+            building_block_commands::list_building_blocks,
+            building_block_commands::save_selection_as_building_block,
+            building_block_commands::delete_building_block,
+            building_block_commands::insert_building_block,
+            building_block_commands::suggest_autotext,
+            building_block_commands::accept_autotext,
             template_commands::get_template_metadata,
             template_commands::get_template_thumbnail,
             template_commands::create_from_template,
+            template_commands::instantiate_template_with_values,
             template_commands::save_as_template,
             template_commands::delete_template,
             template_commands::search_templates,
@@ -185,6 +245,8 @@ fn main() {
             // Style pack commands
             template_commands::export_style_pack,
             template_commands::import_style_pack,
+            template_commands::diff_style_pack,
+            template_commands::merge_style_pack,
             // Locked region commands
             template_commands::get_locked_regions,
             template_commands::set_locked_regions,
@@ -214,6 +276,59 @@ fn main() {
             commands::export_rtf,
             commands::import_rtf_bytes,
             commands::export_rtf_bytes,
+            // Clipboard image commands
+            commands::paste_image_from_clipboard,
+            commands::compress_pictures,
+            commands::insert_ink,
+            commands::edit_ink,
+            commands::delete_ink,
+            commands::create_drawing_canvas,
+            commands::ungroup_drawing_canvas,
+            commands::snap_shape_drag,
+            commands::auto_format_hyperlink,
+            commands::audit_hyperlinks,
+            commands::set_hyperlink_base,
+            commands::replace_hyperlink_domain,
+            commands::get_document_statistics,
+            commands::set_proofing_language,
+            commands::detect_language,
+            commands::spellcheck_document,
+            commands::translate_document,
+            commands::start_read_aloud,
+            commands::stop_read_aloud,
+            commands::get_synonyms,
+            commands::get_word_at_cursor,
+            commands::replace_with_synonym,
+            commands::summarize_document,
+            commands::insert_executive_summary,
+            commands::create_local_version,
+            commands::list_local_versions,
+            commands::diff_local_versions,
+            commands::restore_local_version,
+            commands::branch_local_version,
+            commands::open_and_repair,
+            commands::get_document_settings,
+            commands::set_document_setting,
+            commands::clear_document_settings,
+            commands::get_setting_layer,
+            commands::export_settings_bundle,
+            commands::import_settings_bundle,
+            commands::get_recent_documents,
+            commands::add_recent_document,
+            commands::pin_recent_document,
+            commands::remove_recent_document,
+            commands::clear_recent_documents,
+            commands::get_recent_thumbnail,
+            commands::lock_document,
+            commands::force_lock_document,
+            commands::unlock_document,
+            commands::get_document_lock_status,
+            commands::open_document_copy,
+            commands::watch_document_lock,
+            commands::sign_document,
+            commands::verify_signatures,
+            commands::inspect_document,
+            commands::remove_document_info,
             // ODT import commands (read-only)
             commands::import_odt,
             commands::import_odt_bytes,
@@ -276,6 +391,7 @@ fn main() {
             view_mode_commands::promote_heading,
             view_mode_commands::demote_heading,
             view_mode_commands::move_section,
+            view_mode_commands::move_outline_heading,
             view_mode_commands::expand_outline_heading,
             view_mode_commands::collapse_outline_heading,
             view_mode_commands::set_outline_level_filter,
@@ -384,6 +500,10 @@ fn main() {
             collab_commands::get_remote_presence,
             collab_commands::get_active_users,
             collab_commands::assign_presence_color,
+            collab_commands::follow_user,
+            collab_commands::unfollow_user,
+            collab_commands::set_presenting,
+            collab_commands::get_follow_target,
             // Version history commands
             collab_commands::get_version_history,
             collab_commands::create_named_version,
@@ -398,6 +518,16 @@ fn main() {
             collab_commands::get_offline_queue,
             collab_commands::clear_offline_queue,
             collab_commands::sync_complete,
+            collab_commands::review_offline_conflicts,
+            collab_commands::resolve_offline_conflict,
+            collab_commands::finalize_offline_conflicts,
+            collab_commands::share_comment,
+            collab_commands::share_comment_reply,
+            collab_commands::share_comment_resolution,
+            collab_commands::share_comment_deletion,
+            collab_commands::share_revision,
+            collab_commands::share_revision_status,
+            collab_commands::get_shared_annotations,
             // Permission commands
             collab_commands::check_permission,
             collab_commands::grant_permission,
@@ -411,6 +541,9 @@ fn main() {
             mail_merge_commands::load_json_data_source,
             mail_merge_commands::load_csv_from_string,
             mail_merge_commands::load_json_from_string,
+            mail_merge_commands::list_data_source_providers,
+            mail_merge_commands::get_provider_columns,
+            mail_merge_commands::load_plugin_data_source,
             mail_merge_commands::get_data_source_columns,
             mail_merge_commands::get_data_source_preview,
             mail_merge_commands::get_data_source_record,
@@ -2,12 +2,14 @@
 
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
 use doc_model::{DocumentPaginationSettings, DocumentTree, NodeId, Section, Selection};
 use layout_engine::{ViewMode, ViewModeConfig, DraftViewOptions, OutlineViewOptions};
 use perf::PerfMetrics;
 use revisions::RevisionState;
-use store::{LockedRegionManager, SettingsManager, TemplateManager};
+use store::{BuildingBlockLibrary, DocumentLock, GalleryFetcher, ImageStore, LockedRegionManager, RecentFilesManager, RemoteGallery, SettingsManager, TemplateError, TemplateManager, TemplateResult};
+use telemetry::{CrashReporter, PerformanceProfiler, TelemetrySession};
 use text_engine::FontManager;
 
 /// Global application state
@@ -40,6 +42,8 @@ pub struct DocumentState {
     pub pagination_settings: DocumentPaginationSettings,
     /// Section storage (sections are not part of the main tree yet)
     pub sections: HashMap<NodeId, Section>,
+    /// Pictures referenced by the document
+    pub images: ImageStore,
 }
 
 impl DocumentState {
@@ -53,6 +57,7 @@ impl DocumentState {
             selection: Selection::default(),
             pagination_settings: DocumentPaginationSettings::default(),
             sections: HashMap::new(),
+            images: ImageStore::new(),
         }
     }
 
@@ -66,6 +71,7 @@ impl DocumentState {
             selection: Selection::default(),
             pagination_settings: DocumentPaginationSettings::default(),
             sections: HashMap::new(),
+            images: ImageStore::new(),
         }
     }
 }
@@ -89,6 +95,48 @@ impl SettingsState {
     }
 }
 
+/// Recent documents state wrapper for thread-safe access
+pub struct RecentFilesState {
+    pub manager: Mutex<RecentFilesManager>,
+}
+
+impl RecentFilesState {
+    /// Create a new recent documents state with the given app data directory
+    pub fn new(app_data_dir: PathBuf, limit: usize) -> Self {
+        let mut manager = RecentFilesManager::new(app_data_dir).with_limit(limit);
+        if let Err(e) = manager.load_sync() {
+            tracing::warn!("Failed to load recent documents: {}", e);
+        }
+        Self {
+            manager: Mutex::new(manager),
+        }
+    }
+}
+
+/// The user's building block library
+pub struct BuildingBlockState {
+    pub library: Mutex<BuildingBlockLibrary>,
+}
+
+impl BuildingBlockState {
+    /// Load the library from the given app data directory
+    pub fn new(app_data_dir: PathBuf) -> Self {
+        let mut library = BuildingBlockLibrary::new(app_data_dir);
+        if let Err(e) = library.load_sync() {
+            tracing::warn!("Failed to load building blocks: {}", e);
+        }
+        Self {
+            library: Mutex::new(library),
+        }
+    }
+}
+
+/// Locks held on open documents, by document ID
+#[derive(Default)]
+pub struct FileLockState {
+    pub locks: Mutex<HashMap<String, DocumentLock>>,
+}
+
 /// Font manager state wrapper for thread-safe access
 pub struct FontManagerState {
     pub manager: Mutex<FontManager>,
@@ -143,6 +191,34 @@ impl Default for PerfMetricsState {
     }
 }
 
+/// Profiler state wrapper for thread-safe access
+#[derive(Default)]
+pub struct ProfilerState {
+    pub profiler: Mutex<PerformanceProfiler>,
+}
+
+/// Crash reporter state, shared with the panic hook
+pub struct CrashReporterState {
+    pub reporter: Arc<Mutex<CrashReporter>>,
+}
+
+impl CrashReporterState {
+    /// Create a crash reporter writing to the app data directory
+    pub fn new(app_data_dir: PathBuf) -> Self {
+        let session = TelemetrySession::new(env!("CARGO_PKG_VERSION"));
+        let reporter = CrashReporter::new(
+            app_data_dir.join("crashes"),
+            app_data_dir.join("recovery"),
+            session.app_version,
+            session.platform,
+            session.session_id,
+        );
+        Self {
+            reporter: Arc::new(Mutex::new(reporter)),
+        }
+    }
+}
+
 /// Revision tracking state wrapper for thread-safe access
 pub struct RevisionStateWrapper {
     pub state: Mutex<RevisionState>,
@@ -170,6 +246,45 @@ impl Default for RevisionStateWrapper {
     }
 }
 
+/// Environment variable naming the template gallery's index URL
+pub const TEMPLATE_GALLERY_URL_VAR: &str = "GO_WORD_TEMPLATE_GALLERY_URL";
+
+/// Largest gallery download accepted, in bytes
+const MAX_GALLERY_DOWNLOAD: u64 = 64 * 1024 * 1024;
+
+/// Fetches template gallery resources over HTTPS
+struct HttpsGalleryFetcher {
+    agent: ureq::Agent,
+}
+
+impl HttpsGalleryFetcher {
+    fn new() -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .https_only(true)
+            .timeout(std::time::Duration::from_secs(30))
+            .build();
+        Self { agent }
+    }
+}
+
+impl GalleryFetcher for HttpsGalleryFetcher {
+    fn fetch(&self, url: &str) -> TemplateResult<Vec<u8>> {
+        use std::io::Read;
+
+        let response = self
+            .agent
+            .get(url)
+            .call()
+            .map_err(|e| TemplateError::Gallery(e.to_string()))?;
+        let mut bytes = Vec::new();
+        response
+            .into_reader()
+            .take(MAX_GALLERY_DOWNLOAD)
+            .read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
 /// Template manager state wrapper for thread-safe access
 pub struct TemplateState {
     /// Template manager for CRUD operations
@@ -180,8 +295,19 @@ pub struct TemplateState {
 
 impl TemplateState {
     /// Create a new template state with the given templates directory
-    pub fn new(templates_dir: PathBuf) -> Self {
-        let manager = TemplateManager::new(templates_dir);
+    ///
+    /// When `GO_WORD_TEMPLATE_GALLERY_URL` names a gallery index, its
+    /// templates are listed alongside local ones and cached in `gallery_cache_dir`.
+    pub fn new(templates_dir: PathBuf, gallery_cache_dir: PathBuf) -> Self {
+        let mut manager = TemplateManager::new(templates_dir);
+        if let Ok(index_url) = std::env::var(TEMPLATE_GALLERY_URL_VAR) {
+            tracing::info!("Using template gallery at {}", index_url);
+            manager = manager.with_gallery(RemoteGallery::new(
+                index_url,
+                gallery_cache_dir,
+                Arc::new(HttpsGalleryFetcher::new()),
+            ));
+        }
         // Ensure the templates directory exists
         if let Err(e) = manager.ensure_directory() {
             tracing::warn!("Failed to create templates directory: {}", e);
@@ -196,7 +322,7 @@ impl TemplateState {
 impl Default for TemplateState {
     fn default() -> Self {
         // Default to current directory - should be overridden in app setup
-        Self::new(PathBuf::from("templates"))
+        Self::new(PathBuf::from("templates"), PathBuf::from("templates/gallery_cache"))
     }
 }
 
@@ -263,6 +389,17 @@ impl ViewModeState {
         configs.get(doc_id).map(|c| c.outline_options.clone()).unwrap_or_default()
     }
 
+    /// Collapse or expand an outline heading for a document
+    pub fn set_heading_collapsed(&self, doc_id: &str, heading_id: NodeId, collapsed: bool) {
+        let mut configs = self.configs.lock().unwrap();
+        let config = configs.entry(doc_id.to_string()).or_default();
+        if collapsed {
+            config.outline_collapse.collapse(heading_id);
+        } else {
+            config.outline_collapse.expand(heading_id);
+        }
+    }
+
     /// Remove view mode config for a document (when document is closed)
     pub fn remove(&self, doc_id: &str) {
         let mut configs = self.configs.lock().unwrap();
@@ -328,12 +465,14 @@ impl Default for CollaborationState {
 // Mail Merge State
 // =============================================================================
 
-use mail_merge::DataSource;
+use mail_merge::{DataSource, ProviderRegistry};
 
 /// Mail merge state for managing data sources
 pub struct MailMergeState {
     /// Loaded data sources by ID
     pub sources: Mutex<HashMap<String, DataSource>>,
+    /// Data source providers contributed by plugins
+    pub providers: Mutex<ProviderRegistry>,
 }
 
 impl MailMergeState {
@@ -341,6 +480,7 @@ impl MailMergeState {
     pub fn new() -> Self {
         Self {
             sources: Mutex::new(HashMap::new()),
+            providers: Mutex::new(ProviderRegistry::new()),
         }
     }
 }
@@ -350,3 +490,10 @@ impl Default for MailMergeState {
         Self::new()
     }
 }
+
+/// Read Aloud state
+#[derive(Default)]
+pub struct ReadAloudState {
+    /// Stop flag of the session being read, if any
+    pub active: Mutex<Option<Arc<AtomicBool>>>,
+}
@@ -1,10 +1,12 @@
 //! Tauri IPC commands for template operations
 
-use crate::state::TemplateState;
+use crate::state::{AppState, TemplateState};
 use doc_model::{NodeId, Position};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use store::{
-    LockedRegion, StylePack, TemplateCategory, TemplateMetadata,
+    LockedRegion, StylePack, StylePackApplyOptions, StylePackDiff, TemplateCategory, TemplateMetadata,
+    TemplatePlaceholder,
 };
 use tauri::State;
 
@@ -32,6 +34,8 @@ pub struct TemplateSummaryDto {
     pub has_thumbnail: bool,
     /// Preview text
     pub preview_text: Option<String>,
+    /// Whether the template is in the remote gallery and not installed
+    pub remote: bool,
 }
 
 impl From<store::TemplateSummary> for TemplateSummaryDto {
@@ -45,6 +49,7 @@ impl From<store::TemplateSummary> for TemplateSummaryDto {
             tags: summary.tags,
             has_thumbnail: summary.has_thumbnail,
             preview_text: summary.preview_text,
+            remote: summary.remote,
         }
     }
 }
@@ -114,6 +119,9 @@ pub struct TemplateMetadataInput {
     pub author: Option<String>,
     /// Tags
     pub tags: Option<Vec<String>>,
+    /// Values to prompt for when creating a document
+    #[serde(default)]
+    pub placeholders: Vec<TemplatePlaceholder>,
 }
 
 impl From<TemplateMetadataInput> for TemplateMetadata {
@@ -133,6 +141,7 @@ impl From<TemplateMetadataInput> for TemplateMetadata {
         if let Some(tags) = input.tags {
             meta = meta.with_tags(tags);
         }
+        meta.placeholders = input.placeholders;
 
         meta
     }
@@ -204,6 +213,16 @@ impl From<PositionDto> for Position {
     }
 }
 
+/// A document created from a template and the values to prompt for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateFromTemplateDto {
+    /// Root node ID of the new document
+    pub root_id: String,
+    /// Placeholders to prompt for before calling `instantiate_template_with_values`
+    pub placeholders: Vec<TemplatePlaceholder>,
+}
+
 /// Style pack DTO
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -252,6 +271,34 @@ pub fn list_templates(
     Ok(templates.into_iter().map(TemplateSummaryDto::from).collect())
 }
 
+/// Refetch the remote gallery index and list templates again
+#[tauri::command]
+pub fn refresh_template_gallery(
+    state: State<'_, TemplateState>,
+) -> Result<Vec<TemplateSummaryDto>, String> {
+    let mut manager = state.manager.lock().map_err(|e| e.to_string())?;
+    manager
+        .gallery_mut()
+        .ok_or("No template gallery configured")?
+        .refresh()
+        .map_err(|e| e.to_string())?;
+    let templates = manager.list_templates().map_err(|e| e.to_string())?;
+    Ok(templates.into_iter().map(TemplateSummaryDto::from).collect())
+}
+
+/// Download a gallery template so it is available offline
+#[tauri::command]
+pub fn download_gallery_template(
+    template_id: String,
+    state: State<'_, TemplateState>,
+) -> Result<TemplateMetadataDto, String> {
+    let mut manager = state.manager.lock().map_err(|e| e.to_string())?;
+    let metadata = manager
+        .download_template(&template_id)
+        .map_err(|e| e.to_string())?;
+    Ok(TemplateMetadataDto::from(&metadata))
+}
+
 /// Get template metadata by ID
 #[tauri::command]
 pub fn get_template_metadata(
@@ -287,14 +334,42 @@ pub fn get_template_thumbnail(
 pub fn create_from_template(
     template_id: String,
     state: State<'_, TemplateState>,
+) -> Result<CreateFromTemplateDto, String> {
+    let manager = state.manager.lock().map_err(|e| e.to_string())?;
+    let package = manager
+        .load_template(&template_id)
+        .map_err(|e| e.to_string())?;
+    let document = package.create_document();
+
+    Ok(CreateFromTemplateDto {
+        root_id: document.root_id().to_string(),
+        placeholders: package.metadata.placeholders,
+    })
+}
+
+/// Create a document from a template with its placeholders filled in
+///
+/// `values` maps placeholder tags to the user's input. Fails listing every
+/// missing required or invalid value. Returns the new document's ID.
+#[tauri::command]
+pub fn instantiate_template_with_values(
+    template_id: String,
+    values: HashMap<String, String>,
+    state: State<'_, TemplateState>,
+    app_state: State<'_, AppState>,
 ) -> Result<String, String> {
     let manager = state.manager.lock().map_err(|e| e.to_string())?;
-    let document = manager
-        .create_from_template(&template_id)
+    let tree = manager
+        .instantiate_template(&template_id, &values)
         .map_err(|e| e.to_string())?;
 
-    // Return the document root ID
-    Ok(document.root_id().to_string())
+    let doc_id = uuid::Uuid::new_v4().to_string();
+    let mut doc_state = crate::state::DocumentState::new(doc_id.clone());
+    doc_state.tree = tree;
+    let mut documents = app_state.documents.lock().map_err(|e| e.to_string())?;
+    documents.insert(doc_id.clone(), doc_state);
+
+    Ok(doc_id)
 }
 
 /// Save a document as a template
@@ -440,6 +515,53 @@ pub fn import_style_pack(
     Ok(count)
 }
 
+/// Compare a style pack against a document's styles before importing it
+#[tauri::command]
+pub fn diff_style_pack(
+    doc_id: String,
+    style_pack_json: String,
+    app_state: State<'_, AppState>,
+) -> Result<StylePackDiff, String> {
+    let pack = StylePack::from_json(&style_pack_json).map_err(|e| e.to_string())?;
+    let documents = app_state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    Ok(pack.diff(doc_state.tree.style_registry()))
+}
+
+/// Apply only the chosen styles from a style pack to a document
+///
+/// Returns the IDs of the styles that were applied.
+#[tauri::command]
+pub fn merge_style_pack(
+    doc_id: String,
+    style_pack_json: String,
+    style_ids: Vec<String>,
+    overwrite_existing: bool,
+    include_builtin_overrides: bool,
+    app_state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let pack = StylePack::from_json(&style_pack_json).map_err(|e| e.to_string())?;
+    let mut options = StylePackApplyOptions::new().with_filter(style_ids);
+    options.overwrite_existing = overwrite_existing;
+    options.include_builtin_overrides = include_builtin_overrides;
+
+    let mut documents = app_state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(&doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+    let applied = pack
+        .merge_into(doc_state.tree.style_registry_mut(), &options)
+        .map_err(|e| e.to_string())?;
+    if !applied.is_empty() {
+        doc_state.dirty = true;
+    }
+
+    Ok(applied)
+}
+
 // =============================================================================
 // Locked Region Commands
 // =============================================================================
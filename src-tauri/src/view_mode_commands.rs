@@ -1,6 +1,8 @@
 //! Tauri IPC commands for view mode operations
 
-use crate::state::ViewModeState;
+use crate::state::{AppState, ViewModeState};
+use doc_model::NodeId;
+use edit_engine::{Command, MoveHeading};
 use layout_engine::{DraftViewOptions, OutlineViewOptions, ViewMode, ViewModeConfig};
 use serde::{Deserialize, Serialize};
 use tauri::State;
@@ -123,6 +125,8 @@ pub struct ViewModeConfigDto {
     pub mode: ViewModeDto,
     pub draft_options: DraftViewOptionsDto,
     pub outline_options: OutlineViewOptionsDto,
+    /// IDs of collapsed outline headings
+    pub collapsed_headings: Vec<String>,
 }
 
 impl From<ViewModeConfig> for ViewModeConfigDto {
@@ -131,6 +135,12 @@ impl From<ViewModeConfig> for ViewModeConfigDto {
             mode: config.mode.into(),
             draft_options: config.draft_options.into(),
             outline_options: config.outline_options.into(),
+            collapsed_headings: config
+                .outline_collapse
+                .collapsed
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
         }
     }
 }
@@ -285,39 +295,74 @@ pub fn demote_heading(doc_id: String, heading_id: String) -> Result<(), String>
 }
 
 /// Move a section (heading and its content) to a new position
+///
+/// The section goes before `before_id`, or to the end of the document if
+/// `before_id` is None.
 #[tauri::command]
 pub fn move_section(
     doc_id: String,
     heading_id: String,
     before_id: Option<String>,
+    state: State<'_, AppState>,
 ) -> Result<(), String> {
-    // TODO: Implement with edit_engine
-    // This would:
-    // 1. Find the heading paragraph and all content until next same-or-higher level heading
-    // 2. Cut the section
-    // 3. Insert before the specified location (or at end if before_id is None)
-    tracing::info!(
-        "Move section {} in doc {} before {:?}",
-        heading_id,
-        doc_id,
-        before_id
-    );
+    let heading_id = parse_heading_id(&heading_id)?;
+    let before_id = before_id.as_deref().map(parse_heading_id).transpose()?;
+    apply_outline_move(&doc_id, MoveHeading::before(heading_id, before_id), &state)
+}
+
+/// Move a heading and its subtree past `offset` sibling headings
+///
+/// Used when dragging in outline view; a negative offset moves up.
+#[tauri::command]
+pub fn move_outline_heading(
+    doc_id: String,
+    heading_id: String,
+    offset: i32,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let heading_id = parse_heading_id(&heading_id)?;
+    apply_outline_move(&doc_id, MoveHeading::by_offset(heading_id, offset), &state)
+}
+
+fn apply_outline_move(doc_id: &str, cmd: MoveHeading, state: &AppState) -> Result<(), String> {
+    let mut documents = state.documents.lock().map_err(|e| e.to_string())?;
+    let doc_state = documents
+        .get_mut(doc_id)
+        .ok_or_else(|| format!("Document not found: {}", doc_id))?;
+
+    let result = cmd.apply(&doc_state.tree, &doc_state.selection)
+        .map_err(|e| e.to_string())?;
+
+    doc_state.tree = result.tree;
+    doc_state.selection = result.selection;
+    doc_state.dirty = true;
+
     Ok(())
 }
 
+fn parse_heading_id(id: &str) -> Result<NodeId, String> {
+    NodeId::from_string(id).ok_or_else(|| format!("Invalid heading ID: {}", id))
+}
+
 /// Expand a heading in outline view
 #[tauri::command]
-pub fn expand_outline_heading(doc_id: String, heading_id: String) -> Result<(), String> {
-    // This is tracked in frontend state, but we log it for debugging
-    tracing::debug!("Expand outline heading {} in doc {}", heading_id, doc_id);
+pub fn expand_outline_heading(
+    doc_id: String,
+    heading_id: String,
+    state: State<'_, ViewModeState>,
+) -> Result<(), String> {
+    state.set_heading_collapsed(&doc_id, parse_heading_id(&heading_id)?, false);
     Ok(())
 }
 
 /// Collapse a heading in outline view
 #[tauri::command]
-pub fn collapse_outline_heading(doc_id: String, heading_id: String) -> Result<(), String> {
-    // This is tracked in frontend state, but we log it for debugging
-    tracing::debug!("Collapse outline heading {} in doc {}", heading_id, doc_id);
+pub fn collapse_outline_heading(
+    doc_id: String,
+    heading_id: String,
+    state: State<'_, ViewModeState>,
+) -> Result<(), String> {
+    state.set_heading_collapsed(&doc_id, parse_heading_id(&heading_id)?, true);
     Ok(())
 }
 